@@ -1,10 +1,10 @@
-use self::state::StateOverride;
+use self::{simulate::SimulatePayload, state::StateOverride};
 use crate::{
     eth::{
         subscription::{SubscriptionId, SubscriptionKind, SubscriptionParams},
         transaction::EthTransactionRequest,
     },
-    types::{EvmMineOptions, Forking, Index},
+    types::{BuildBlockRequest, EvmMineOptions, Forking, Index},
 };
 use ethers_core::{
     abi::ethereum_types::H64,
@@ -17,6 +17,7 @@ use ethers_core::{
 pub mod block;
 pub mod proof;
 pub mod receipt;
+pub mod simulate;
 pub mod state;
 pub mod subscription;
 pub mod transaction;
@@ -167,6 +168,14 @@ pub enum EthRequest {
         #[cfg_attr(feature = "serde", serde(default))] Option<StateOverride>,
     ),
 
+    /// Simulates calls in multiple blocks on top of the given block, with state and block
+    /// overrides
+    #[cfg_attr(feature = "serde", serde(rename = "eth_simulateV1"))]
+    EthSimulateV1(
+        SimulatePayload,
+        #[cfg_attr(feature = "serde", serde(default))] Option<BlockId>,
+    ),
+
     #[cfg_attr(feature = "serde", serde(rename = "eth_createAccessList"))]
     EthCreateAccessList(
         EthTransactionRequest,
@@ -303,6 +312,10 @@ pub enum EthRequest {
         )
     )]
     StopImpersonatingAccount(Address),
+    /// Executes a transaction as its `from` address without a signature and mines it right away,
+    /// the sender does not need to be impersonated and can be a contract
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_impersonateAndCall", with = "sequence"))]
+    ImpersonateAndCall(Box<EthTransactionRequest>),
     /// Returns true if automatic mining is enabled, and false.
     #[cfg_attr(
         feature = "serde",
@@ -322,6 +335,21 @@ pub enum EthRequest {
         Option<U256>,
     ),
 
+    /// Rolls back the given number of blocks and mines the given number of replacement blocks
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_reorg"))]
+    Reorg(
+        /// Number of blocks to roll back
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_number"))]
+        U256,
+        /// Number of blocks to mine on the new branch
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_number"))]
+        U256,
+        /// Whether the transactions of the removed blocks are added back to the pool, defaults to
+        /// `false`
+        #[cfg_attr(feature = "serde", serde(default))]
+        Option<bool>,
+    ),
+
     /// Enables or disables, based on the single boolean argument, the automatic mining of new
     /// blocks with each new transaction submitted to the network.
     #[cfg_attr(
@@ -352,6 +380,13 @@ pub enum EthRequest {
     )]
     DropTransaction(H256),
 
+    /// Removes all transactions from the pool
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "anvil_dropAllTransactions", with = "empty_params")
+    )]
+    DropAllTransactions(()),
+
     /// Reset the fork to a fresh forked state, and optionally update the fork config
     #[cfg_attr(feature = "serde", serde(rename = "anvil_reset", alias = "hardhat_reset"))]
     Reset(#[cfg_attr(feature = "serde", serde(default))] Option<Params<Option<Forking>>>),
@@ -462,6 +497,10 @@ pub enum EthRequest {
     )]
     DumpState(()),
 
+    /// Returns the current state and chain settings as a geth compatible `genesis.json`
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_dumpGenesis", with = "empty_params"))]
+    DumpGenesis(()),
+
     /// Adds state previously dumped with `DumpState` to the current chain
     #[cfg_attr(
         feature = "serde",
@@ -552,12 +591,35 @@ pub enum EthRequest {
     /// this is a separate call since `evm_mine` is not an anvil original.
     #[cfg_attr(
         feature = "serde",
-        serde(rename = "anvil_mine_detailed", alias = "evm_mine_detailed",)
+        serde(
+            rename = "anvil_mine_detailed",
+            alias = "anvil_mineDetailed",
+            alias = "evm_mine_detailed",
+        )
     )]
     EvmMineDetailed(
         #[cfg_attr(feature = "serde", serde(default))] Option<Params<Option<EvmMineOptions>>>,
     ),
 
+    /// Builds a block from an ordered list of transactions and returns it, the block is only added
+    /// to the chain if the request is committed
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_buildBlock", with = "sequence"))]
+    BuildBlock(BuildBlockRequest),
+
+    /// Returns the nodes of the cluster this node is part of, see `anvil --cluster`
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_clusterInfo", with = "empty_params"))]
+    ClusterInfo(()),
+
+    /// Splits the cluster into the given groups of node indices, transactions only propagate
+    /// between the nodes of the same group. Nodes that are not part of any group form another
+    /// group.
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_partitionCluster", with = "sequence"))]
+    PartitionCluster(Vec<Vec<usize>>),
+
+    /// Reconnects all nodes of the cluster, the transactions of all mempools are propagated again
+    #[cfg_attr(feature = "serde", serde(rename = "anvil_healCluster", with = "empty_params"))]
+    HealCluster(()),
+
     /// Execute a transaction regardless of signature status
     #[cfg_attr(
         feature = "serde",
@@ -710,6 +772,19 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_custom_reorg() {
+        let s = r#"{"method": "anvil_reorg", "params": [2, "0x3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Reorg(2u64.into(), 3u64.into(), None));
+
+        let s = r#"{"method": "anvil_reorg", "params": [1, 1, true]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::Reorg(1u64.into(), 1u64.into(), Some(true)));
+    }
+
     #[test]
     fn test_custom_mine() {
         let s = r#"{"method": "anvil_mine", "params": []}"#;
@@ -772,6 +847,13 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_custom_drop_all_txs() {
+        let s = r#"{"method": "anvil_dropAllTransactions", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_custom_reset() {
         let s = r#"{"method": "anvil_reset", "params": [ { "forking": {
@@ -965,6 +1047,13 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_dump_genesis() {
+        let s = r#"{"method": "anvil_dumpGenesis", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_dump_state() {
         let s = r#"{"method": "anvil_dumpState", "params": [] }"#;
@@ -1044,6 +1133,22 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_cluster() {
+        let s = r#"{"method": "anvil_clusterInfo", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "anvil_partitionCluster", "params": [[[0], [1, 2]]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::PartitionCluster(vec![vec![0], vec![1, 2]]));
+
+        let s = r#"{"method": "anvil_healCluster", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_remove_timestamp_interval() {
         let s = r#"{"method": "anvil_removeBlockTimestampInterval", "params": []}"#;
@@ -1088,6 +1193,42 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_build_block() {
+        use crate::types::BuildBlockTransaction;
+
+        let s = r#"{"method": "anvil_buildBlock", "params": [{
+            "transactions": [
+                "0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff",
+                "0xf86b808504a817c800825208942e5e1a1d2d1f9c2b6e6ae6f2a7f1d4f0a56bc1e3880de0b6b3a76400008025a0"
+            ],
+            "timestamp": "0x64",
+            "coinbase": "0x295a70b2de5e3953354a6a8344e616ed314d7251",
+            "commit": true
+        }]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::BuildBlock(BuildBlockRequest {
+                transactions,
+                timestamp,
+                coinbase,
+                commit,
+            }) => {
+                assert!(matches!(transactions[0], BuildBlockTransaction::Hash(_)));
+                assert!(matches!(transactions[1], BuildBlockTransaction::Raw(_)));
+                assert_eq!(timestamp, Some(100));
+                assert!(coinbase.is_some());
+                assert!(commit);
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_buildBlock", "params": [{"transactions": []}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_evm_mine_detailed() {
         let s = r#"{"method": "anvil_mine_detailed", "params": [100]}"#;
@@ -1274,6 +1415,32 @@ mod tests {
         let _req = serde_json::from_str::<EthRequest>(s).unwrap();
     }
 
+    #[test]
+    fn test_eth_simulate() {
+        let s = r#"{"method": "eth_simulateV1", "params": [{"blockStateCalls": [{"blockOverrides": {"number": "0x10", "time": "0x64", "baseFeePerGas": "0x0"}, "stateOverrides": {"0xc000000000000000000000000000000000000000": {"balance": "0x3e8"}}, "calls": [{"from": "0xc000000000000000000000000000000000000000", "to": "0xc100000000000000000000000000000000000000", "value": "0x3e8"}]}, {}], "traceTransfers": true}, "latest"]}"#;
+        let req = serde_json::from_str::<EthRequest>(s).unwrap();
+        match req {
+            EthRequest::EthSimulateV1(payload, block) => {
+                assert!(block.is_some());
+                assert!(payload.trace_transfers);
+                assert!(!payload.validation);
+                assert_eq!(payload.block_state_calls.len(), 2);
+
+                let block = &payload.block_state_calls[0];
+                let overrides = block.block_overrides.as_ref().unwrap();
+                assert_eq!(overrides.number, Some(16u64.into()));
+                assert_eq!(overrides.time, Some(100u64.into()));
+                assert_eq!(block.state_overrides.as_ref().unwrap().len(), 1);
+                assert_eq!(block.calls[0].value, Some(1000u64.into()));
+                assert_eq!(payload.block_state_calls[1], Default::default());
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "eth_simulateV1", "params": [{"blockStateCalls": []}]}"#;
+        let _req = serde_json::from_str::<EthRequest>(s).unwrap();
+    }
+
     #[test]
     fn test_serde_eth_balance() {
         let s = r#"{"method": "eth_getBalance", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", "latest"]}"#;
@@ -6,6 +6,7 @@ use ethers_core::{
         rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream},
     },
 };
+use foundry_evm::executor::delegation::SET_CODE_TX_TYPE;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "fastrlp", derive(open_fastrlp::RlpEncodable, open_fastrlp::RlpDecodable))]
@@ -85,6 +86,7 @@ impl Decodable for EIP658Receipt {
 // same underlying data structure
 pub type EIP2930Receipt = EIP658Receipt;
 pub type EIP1559Receipt = EIP658Receipt;
+pub type EIP7702Receipt = EIP658Receipt;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -95,6 +97,8 @@ pub enum TypedReceipt {
     EIP2930(EIP2930Receipt),
     /// EIP-1559 receipt
     EIP1559(EIP1559Receipt),
+    /// EIP-7702 receipt
+    EIP7702(EIP7702Receipt),
 }
 
 // == impl TypedReceipt ==
@@ -103,18 +107,20 @@ impl TypedReceipt {
     /// Returns the gas used by the transactions
     pub fn gas_used(&self) -> U256 {
         match self {
-            TypedReceipt::Legacy(r) | TypedReceipt::EIP2930(r) | TypedReceipt::EIP1559(r) => {
-                r.gas_used
-            }
+            TypedReceipt::Legacy(r) |
+            TypedReceipt::EIP2930(r) |
+            TypedReceipt::EIP1559(r) |
+            TypedReceipt::EIP7702(r) => r.gas_used,
         }
     }
 
     /// Returns the gas used by the transactions
     pub fn logs_bloom(&self) -> &Bloom {
         match self {
-            TypedReceipt::Legacy(r) | TypedReceipt::EIP2930(r) | TypedReceipt::EIP1559(r) => {
-                &r.logs_bloom
-            }
+            TypedReceipt::Legacy(r) |
+            TypedReceipt::EIP2930(r) |
+            TypedReceipt::EIP1559(r) |
+            TypedReceipt::EIP7702(r) => &r.logs_bloom,
         }
     }
 }
@@ -125,6 +131,7 @@ impl Encodable for TypedReceipt {
             TypedReceipt::Legacy(r) => r.rlp_append(s),
             TypedReceipt::EIP2930(r) => enveloped(1, r, s),
             TypedReceipt::EIP1559(r) => enveloped(2, r, s),
+            TypedReceipt::EIP7702(r) => enveloped(SET_CODE_TX_TYPE, r, s),
         }
     }
 }
@@ -149,6 +156,10 @@ impl Decodable for TypedReceipt {
             return rlp::decode(s).map(TypedReceipt::EIP1559)
         }
 
+        if first == SET_CODE_TX_TYPE {
+            return rlp::decode(s).map(TypedReceipt::EIP7702)
+        }
+
         Err(DecoderError::Custom("unknown receipt type"))
     }
 }
@@ -162,6 +173,7 @@ impl open_fastrlp::Encodable for TypedReceipt {
                 let payload_len = match receipt {
                     TypedReceipt::EIP2930(r) => r.length() + 1,
                     TypedReceipt::EIP1559(r) => r.length() + 1,
+                    TypedReceipt::EIP7702(r) => r.length() + 1,
                     _ => unreachable!("receipt already matched"),
                 };
 
@@ -179,6 +191,7 @@ impl open_fastrlp::Encodable for TypedReceipt {
                 let payload_len = match receipt {
                     TypedReceipt::EIP2930(r) => r.length() + 1,
                     TypedReceipt::EIP1559(r) => r.length() + 1,
+                    TypedReceipt::EIP7702(r) => r.length() + 1,
                     _ => unreachable!("receipt already matched"),
                 };
 
@@ -199,6 +212,14 @@ impl open_fastrlp::Encodable for TypedReceipt {
                         out.put_u8(0x02);
                         r.encode(out);
                     }
+                    TypedReceipt::EIP7702(r) => {
+                        let receipt_string_header =
+                            Header { list: false, payload_length: payload_len };
+
+                        receipt_string_header.encode(out);
+                        out.put_u8(SET_CODE_TX_TYPE);
+                        r.encode(out);
+                    }
                     _ => unreachable!("receipt already matched"),
                 }
             }
@@ -235,6 +256,10 @@ impl open_fastrlp::Decodable for TypedReceipt {
                     buf.advance(1);
                     <EIP1559Receipt as open_fastrlp::Decodable>::decode(buf)
                         .map(TypedReceipt::EIP1559)
+                } else if receipt_type == SET_CODE_TX_TYPE {
+                    buf.advance(1);
+                    <EIP7702Receipt as open_fastrlp::Decodable>::decode(buf)
+                        .map(TypedReceipt::EIP7702)
                 } else {
                     Err(open_fastrlp::DecodeError::Custom("invalid receipt type"))
                 }
@@ -255,6 +280,7 @@ impl From<TypedReceipt> for EIP658Receipt {
             TypedReceipt::Legacy(receipt) => receipt,
             TypedReceipt::EIP2930(receipt) => receipt,
             TypedReceipt::EIP1559(receipt) => receipt,
+            TypedReceipt::EIP7702(receipt) => receipt,
         }
     }
 }
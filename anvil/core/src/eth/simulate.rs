@@ -0,0 +1,100 @@
+//! Request and return types for `eth_simulateV1`
+
+use crate::eth::{state::StateOverride, transaction::EthTransactionRequest};
+use ethers_core::types::{Address, Block, Bytes, Log, TxHash, H256, U256, U64};
+
+/// The address that emits the logs of ether transfers if `traceTransfers` is enabled
+pub const TRANSFER_LOG_ADDRESS: Address = Address::repeat_byte(0xee);
+
+/// The parameters of `eth_simulateV1`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatePayload {
+    /// The blocks to simulate, each on top of the previous one
+    pub block_state_calls: Vec<SimulateBlock>,
+    /// Whether to add a log for every ether transfer
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trace_transfers: bool,
+    /// Whether to validate nonces, balances and fees like for real transactions
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub validation: bool,
+    /// Whether to return full transactions instead of hashes, which is ignored since simulated
+    /// calls are not included as transactions in the returned blocks
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub return_full_transactions: bool,
+}
+
+/// A block of calls to simulate
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulateBlock {
+    /// Overrides of the block environment
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub block_overrides: Option<BlockOverrides>,
+    /// Overrides of the state before the calls are executed
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub state_overrides: Option<StateOverride>,
+    /// The calls to execute in this block, in order
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub calls: Vec<EthTransactionRequest>,
+}
+
+/// Overrides of the block environment of a simulated block
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BlockOverrides {
+    pub number: Option<U256>,
+    pub time: Option<U64>,
+    pub gas_limit: Option<U256>,
+    pub fee_recipient: Option<Address>,
+    pub prev_randao: Option<H256>,
+    pub base_fee_per_gas: Option<U256>,
+}
+
+/// A simulated block
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatedBlock {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub inner: Block<TxHash>,
+    /// The results of the calls, in order
+    pub calls: Vec<SimulatedCall>,
+}
+
+/// The result of a simulated call
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SimulatedCall {
+    pub return_data: Bytes,
+    /// The logs emitted by the call, including ether transfers if `traceTransfers` is enabled
+    pub logs: Vec<Log>,
+    pub gas_used: U256,
+    /// `1` if the call succeeded, `0` otherwise
+    pub status: U64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub error: Option<SimulateCallError>,
+}
+
+/// The error of a failed simulated call
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SimulateCallError {
+    pub code: i64,
+    pub message: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub data: Option<Bytes>,
+}
+
+impl SimulateCallError {
+    /// The error code of a reverted call
+    pub const REVERTED: i64 = 3;
+    /// The error code of a call that halted, for example because it ran out of gas
+    pub const HALTED: i64 = -32015;
+}
+
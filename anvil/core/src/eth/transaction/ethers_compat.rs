@@ -2,8 +2,9 @@
 
 use super::EthTransactionRequest;
 use crate::eth::transaction::{
-    EIP1559TransactionRequest, EIP2930TransactionRequest, LegacyTransactionRequest,
-    MaybeImpersonatedTransaction, TypedTransaction, TypedTransactionRequest,
+    EIP1559TransactionRequest, EIP2930TransactionRequest, EIP7702TransactionRequest,
+    LegacyTransactionRequest, MaybeImpersonatedTransaction, TypedTransaction,
+    TypedTransactionRequest,
 };
 use ethers_core::types::{
     transaction::eip2718::TypedTransaction as EthersTypedTransactionRequest, Address,
@@ -12,6 +13,7 @@ use ethers_core::types::{
     Transaction as EthersTransaction, TransactionRequest as EthersLegacyTransactionRequest,
     TransactionRequest, H256, U256, U64,
 };
+use foundry_evm::executor::delegation::SET_CODE_TX_TYPE;
 
 impl From<TypedTransactionRequest> for EthersTypedTransactionRequest {
     fn from(tx: TypedTransactionRequest) -> Self {
@@ -87,6 +89,33 @@ impl From<TypedTransactionRequest> for EthersTypedTransactionRequest {
                     chain_id: Some(chain_id.into()),
                 })
             }
+            // ethers doesn't know set code transactions, the authorizations are dropped
+            TypedTransactionRequest::EIP7702(tx) => {
+                let EIP7702TransactionRequest {
+                    chain_id,
+                    nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    gas_limit,
+                    kind,
+                    value,
+                    input,
+                    access_list,
+                    ..
+                } = tx;
+                EthersTypedTransactionRequest::Eip1559(EthersEip1559TransactionRequest {
+                    from: None,
+                    to: kind.as_call().cloned().map(Into::into),
+                    gas: Some(gas_limit),
+                    value: Some(value),
+                    data: Some(input),
+                    nonce: Some(nonce),
+                    access_list: access_list.into(),
+                    max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+                    max_fee_per_gas: Some(max_fee_per_gas),
+                    chain_id: Some(chain_id.into()),
+                })
+            }
         }
     }
 }
@@ -163,6 +192,35 @@ fn to_ethers_transaction_with_hash_and_sender(
             transaction_type: Some(2u64.into()),
             other: Default::default(),
         },
+        TypedTransaction::EIP7702(t) => {
+            let mut tx = EthersTransaction {
+                hash,
+                nonce: t.nonce,
+                block_hash: None,
+                block_number: None,
+                transaction_index: None,
+                from,
+                to: None,
+                value: t.value,
+                gas_price: None,
+                max_fee_per_gas: Some(t.max_fee_per_gas),
+                max_priority_fee_per_gas: Some(t.max_priority_fee_per_gas),
+                gas: t.gas_limit,
+                input: t.input.clone(),
+                chain_id: Some(t.chain_id.into()),
+                v: U64::from(t.odd_y_parity as u8),
+                r: U256::from(t.r.as_bytes()),
+                s: U256::from(t.s.as_bytes()),
+                access_list: Some(t.access_list),
+                transaction_type: Some(SET_CODE_TX_TYPE.into()),
+                other: Default::default(),
+            };
+            tx.other.insert(
+                "authorizationList".to_string(),
+                serde_json::to_value(t.authorization_list).expect("Infallible"),
+            );
+            tx
+        }
     }
 }
 
@@ -200,6 +258,7 @@ impl From<TransactionRequest> for EthTransactionRequest {
             nonce,
             chain_id,
             access_list: None,
+            authorization_list: None,
             transaction_type: None,
         }
     }
@@ -14,7 +14,10 @@ use ethers_core::{
         rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream},
     },
 };
-use foundry_evm::trace::CallTraceArena;
+use foundry_evm::{
+    executor::delegation::{SignedAuthorization, SET_CODE_TX_TYPE},
+    trace::CallTraceArena,
+};
 use revm::{CreateScheme, Return, TransactTo, TxEnv};
 use std::ops::Deref;
 
@@ -32,11 +35,13 @@ pub const IMPERSONATED_SIGNATURE: Signature =
 /// 1. Legacy (pre-EIP2718) [`LegacyTransactionRequest`]
 /// 2. EIP2930 (state access lists) [`EIP2930TransactionRequest`]
 /// 3. EIP1559 [`EIP1559TransactionRequest`]
+/// 4. EIP7702 (set code) [`EIP7702TransactionRequest`]
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TypedTransactionRequest {
     Legacy(LegacyTransactionRequest),
     EIP2930(EIP2930TransactionRequest),
     EIP1559(EIP1559TransactionRequest),
+    EIP7702(EIP7702TransactionRequest),
 }
 
 /// Represents _all_ transaction requests received from RPC
@@ -72,6 +77,9 @@ pub struct EthTransactionRequest {
     /// warm storage access pre-payment
     #[cfg_attr(feature = "serde", serde(default))]
     pub access_list: Option<Vec<AccessListItem>>,
+    /// EIP-7702 delegations of the authorities to contract code
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub authorization_list: Option<Vec<SignedAuthorization>>,
     /// EIP-2718 type
     #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub transaction_type: Option<U256>,
@@ -93,9 +101,25 @@ impl EthTransactionRequest {
             nonce,
             mut access_list,
             chain_id,
+            authorization_list,
             ..
         } = self;
         let chain_id = chain_id.map(|id| id.as_u64());
+        if let Some(authorization_list) = authorization_list {
+            // set code transactions can't create contracts
+            return Some(TypedTransactionRequest::EIP7702(EIP7702TransactionRequest {
+                chain_id: chain_id.unwrap_or_default(),
+                nonce: nonce.unwrap_or(U256::zero()),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.unwrap_or(U256::zero()),
+                max_fee_per_gas: max_fee_per_gas.or(gas_price).unwrap_or_default(),
+                gas_limit: gas.unwrap_or_default(),
+                kind: TransactionKind::Call(to?),
+                value: value.unwrap_or(U256::zero()),
+                input: data.unwrap_or_default(),
+                access_list: access_list.unwrap_or_default(),
+                authorization_list,
+            }))
+        }
         match (gas_price, max_fee_per_gas, access_list.take()) {
             // legacy transaction
             (Some(_), None, None) => {
@@ -398,6 +422,66 @@ impl Encodable for EIP1559TransactionRequest {
     }
 }
 
+/// An EIP-7702 set code transaction request, the `kind` is always a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EIP7702TransactionRequest {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub kind: TransactionKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: Vec<AccessListItem>,
+    pub authorization_list: Vec<SignedAuthorization>,
+}
+
+// == impl EIP7702TransactionRequest ==
+
+impl EIP7702TransactionRequest {
+    pub fn hash(&self) -> H256 {
+        let encoded = rlp::encode(self);
+        let mut out = vec![0; 1 + encoded.len()];
+        out[0] = SET_CODE_TX_TYPE;
+        out[1..].copy_from_slice(&encoded);
+        H256::from_slice(keccak256(&out).as_slice())
+    }
+}
+
+impl From<EIP7702Transaction> for EIP7702TransactionRequest {
+    fn from(t: EIP7702Transaction) -> Self {
+        Self {
+            chain_id: t.chain_id,
+            nonce: t.nonce,
+            max_priority_fee_per_gas: t.max_priority_fee_per_gas,
+            max_fee_per_gas: t.max_fee_per_gas,
+            gas_limit: t.gas_limit,
+            kind: t.kind,
+            value: t.value,
+            input: t.input,
+            access_list: t.access_list.0,
+            authorization_list: t.authorization_list,
+        }
+    }
+}
+
+impl Encodable for EIP7702TransactionRequest {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(10);
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas_limit);
+        s.append(&self.kind);
+        s.append(&self.value);
+        s.append(&self.input.as_ref());
+        s.append_list(&self.access_list);
+        s.append_list(&self.authorization_list);
+    }
+}
+
 /// A wrapper for `TypedTransaction` that allows impersonating accounts.
 ///
 /// This is a helper that carries the `impersonated` sender so that the right hash
@@ -516,6 +600,8 @@ pub enum TypedTransaction {
     EIP2930(EIP2930Transaction),
     /// EIP-1559 transaction
     EIP1559(EIP1559Transaction),
+    /// EIP-7702 set code transaction
+    EIP7702(EIP7702Transaction),
 }
 
 // == impl TypedTransaction ==
@@ -526,6 +612,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.gas_price,
             TypedTransaction::EIP2930(tx) => tx.gas_price,
             TypedTransaction::EIP1559(tx) => tx.max_fee_per_gas,
+            TypedTransaction::EIP7702(tx) => tx.max_fee_per_gas,
         }
     }
 
@@ -534,6 +621,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.gas_limit,
             TypedTransaction::EIP2930(tx) => tx.gas_limit,
             TypedTransaction::EIP1559(tx) => tx.gas_limit,
+            TypedTransaction::EIP7702(tx) => tx.gas_limit,
         }
     }
 
@@ -542,6 +630,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.value,
             TypedTransaction::EIP2930(tx) => tx.value,
             TypedTransaction::EIP1559(tx) => tx.value,
+            TypedTransaction::EIP7702(tx) => tx.value,
         }
     }
 
@@ -550,6 +639,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => &tx.input,
             TypedTransaction::EIP2930(tx) => &tx.input,
             TypedTransaction::EIP1559(tx) => &tx.input,
+            TypedTransaction::EIP7702(tx) => &tx.input,
         }
     }
 
@@ -559,6 +649,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(_) => None,
             TypedTransaction::EIP2930(_) => Some(1),
             TypedTransaction::EIP1559(_) => Some(2),
+            TypedTransaction::EIP7702(_) => Some(SET_CODE_TX_TYPE),
         }
     }
 
@@ -606,6 +697,18 @@ impl TypedTransaction {
                 chain_id: Some(t.chain_id),
                 access_list: t.access_list.clone(),
             },
+            TypedTransaction::EIP7702(t) => TransactionEssentials {
+                kind: t.kind,
+                input: t.input.clone(),
+                nonce: t.nonce,
+                gas_limit: t.gas_limit,
+                gas_price: None,
+                max_fee_per_gas: Some(t.max_fee_per_gas),
+                max_priority_fee_per_gas: Some(t.max_priority_fee_per_gas),
+                value: t.value,
+                chain_id: Some(t.chain_id),
+                access_list: t.access_list.clone(),
+            },
         }
     }
 
@@ -614,6 +717,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.nonce(),
             TypedTransaction::EIP2930(t) => t.nonce(),
             TypedTransaction::EIP1559(t) => t.nonce(),
+            TypedTransaction::EIP7702(t) => t.nonce(),
         }
     }
 
@@ -622,6 +726,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.chain_id(),
             TypedTransaction::EIP2930(t) => Some(t.chain_id),
             TypedTransaction::EIP1559(t) => Some(t.chain_id),
+            TypedTransaction::EIP7702(t) => Some(t.chain_id),
         }
     }
 
@@ -642,6 +747,14 @@ impl TypedTransaction {
         matches!(self, TypedTransaction::EIP1559(_))
     }
 
+    /// Returns the EIP-7702 authorizations of the transaction, empty for other transactions
+    pub fn authorization_list(&self) -> &[SignedAuthorization] {
+        match self {
+            TypedTransaction::EIP7702(tx) => &tx.authorization_list,
+            _ => &[],
+        }
+    }
+
     /// Returns the hash of the transaction.
     ///
     /// Note: If this transaction has the Impersonated signature then this returns a modified unique
@@ -651,6 +764,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.hash(),
             TypedTransaction::EIP2930(t) => t.hash(),
             TypedTransaction::EIP1559(t) => t.hash(),
+            TypedTransaction::EIP7702(t) => t.hash(),
         }
     }
 
@@ -676,6 +790,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.recover(),
             TypedTransaction::EIP2930(tx) => tx.recover(),
             TypedTransaction::EIP1559(tx) => tx.recover(),
+            TypedTransaction::EIP7702(tx) => tx.recover(),
         }
     }
 
@@ -685,6 +800,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => &tx.kind,
             TypedTransaction::EIP2930(tx) => &tx.kind,
             TypedTransaction::EIP1559(tx) => &tx.kind,
+            TypedTransaction::EIP7702(tx) => &tx.kind,
         }
     }
 
@@ -709,6 +825,12 @@ impl TypedTransaction {
                 let s = U256::from_big_endian(&tx.s[..]);
                 Signature { r, s, v: v.into() }
             }
+            TypedTransaction::EIP7702(tx) => {
+                let v = tx.odd_y_parity as u8;
+                let r = U256::from_big_endian(&tx.r[..]);
+                let s = U256::from_big_endian(&tx.s[..]);
+                Signature { r, s, v: v.into() }
+            }
         }
     }
 }
@@ -719,6 +841,7 @@ impl Encodable for TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.rlp_append(s),
             TypedTransaction::EIP2930(tx) => enveloped(1, tx, s),
             TypedTransaction::EIP1559(tx) => enveloped(2, tx, s),
+            TypedTransaction::EIP7702(tx) => enveloped(SET_CODE_TX_TYPE, tx, s),
         }
     }
 }
@@ -737,6 +860,9 @@ impl Decodable for TypedTransaction {
         if first == 0x02 {
             return rlp::decode(s).map(TypedTransaction::EIP1559)
         }
+        if first == SET_CODE_TX_TYPE {
+            return rlp::decode(s).map(TypedTransaction::EIP7702)
+        }
         Err(DecoderError::Custom("invalid tx type"))
     }
 }
@@ -750,6 +876,7 @@ impl open_fastrlp::Encodable for TypedTransaction {
                 let payload_len = match tx {
                     TypedTransaction::EIP2930(tx) => tx.length() + 1,
                     TypedTransaction::EIP1559(tx) => tx.length() + 1,
+                    TypedTransaction::EIP7702(tx) => tx.length() + 1,
                     _ => unreachable!("legacy tx length already matched"),
                 };
 
@@ -770,6 +897,14 @@ impl open_fastrlp::Encodable for TypedTransaction {
                         out.put_u8(0x02);
                         tx.encode(out);
                     }
+                    TypedTransaction::EIP7702(tx) => {
+                        let tx_string_header =
+                            open_fastrlp::Header { list: false, payload_length: payload_len };
+
+                        tx_string_header.encode(out);
+                        out.put_u8(SET_CODE_TX_TYPE);
+                        tx.encode(out);
+                    }
                     _ => unreachable!("legacy tx encode already matched"),
                 }
             }
@@ -782,6 +917,7 @@ impl open_fastrlp::Encodable for TypedTransaction {
                 let payload_len = match tx {
                     TypedTransaction::EIP2930(tx) => tx.length() + 1,
                     TypedTransaction::EIP1559(tx) => tx.length() + 1,
+                    TypedTransaction::EIP7702(tx) => tx.length() + 1,
                     _ => unreachable!("legacy tx length already matched"),
                 };
                 // we include a string header for signed types txs, so include the length here
@@ -827,6 +963,10 @@ impl open_fastrlp::Decodable for TypedTransaction {
                     buf.advance(1);
                     <EIP1559Transaction as open_fastrlp::Decodable>::decode(buf)
                         .map(TypedTransaction::EIP1559)
+                } else if tx_type == SET_CODE_TX_TYPE {
+                    buf.advance(1);
+                    <EIP7702Transaction as open_fastrlp::Decodable>::decode(buf)
+                        .map(TypedTransaction::EIP7702)
                 } else {
                     Err(open_fastrlp::DecodeError::Custom("invalid tx type"))
                 }
@@ -1102,6 +1242,135 @@ impl Decodable for EIP1559Transaction {
     }
 }
 
+/// An EIP-7702 set code transaction
+///
+/// The authorizations are applied before the transaction is executed, set code transactions can't
+/// create contracts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EIP7702Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub kind: TransactionKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: AccessList,
+    pub authorization_list: Vec<SignedAuthorization>,
+    pub odd_y_parity: bool,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl EIP7702Transaction {
+    pub fn nonce(&self) -> &U256 {
+        &self.nonce
+    }
+
+    pub fn hash(&self) -> H256 {
+        let encoded = rlp::encode(self);
+        let mut out = vec![0; 1 + encoded.len()];
+        out[0] = SET_CODE_TX_TYPE;
+        out[1..].copy_from_slice(&encoded);
+        H256::from_slice(keccak256(&out).as_slice())
+    }
+
+    /// Recovers the Ethereum address which was used to sign the transaction.
+    pub fn recover(&self) -> Result<Address, SignatureError> {
+        let mut sig = [0u8; 65];
+        sig[0..32].copy_from_slice(&self.r[..]);
+        sig[32..64].copy_from_slice(&self.s[..]);
+        sig[64] = self.odd_y_parity as u8;
+        let signature = Signature::try_from(&sig[..])?;
+        signature.recover(EIP7702TransactionRequest::from(self.clone()).hash())
+    }
+}
+
+impl Encodable for EIP7702Transaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(13);
+        s.append(&self.chain_id);
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas_limit);
+        s.append(&self.kind);
+        s.append(&self.value);
+        s.append(&self.input.as_ref());
+        s.append(&self.access_list);
+        s.append_list(&self.authorization_list);
+        s.append(&self.odd_y_parity);
+        s.append(&U256::from_big_endian(&self.r[..]));
+        s.append(&U256::from_big_endian(&self.s[..]));
+    }
+}
+
+impl Decodable for EIP7702Transaction {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 13 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+
+        let kind = rlp.val_at(5)?;
+        if kind == TransactionKind::Create {
+            return Err(DecoderError::Custom("set code transactions can't create contracts"))
+        }
+
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            nonce: rlp.val_at(1)?,
+            max_priority_fee_per_gas: rlp.val_at(2)?,
+            max_fee_per_gas: rlp.val_at(3)?,
+            gas_limit: rlp.val_at(4)?,
+            kind,
+            value: rlp.val_at(6)?,
+            input: rlp.val_at::<Vec<u8>>(7)?.into(),
+            access_list: rlp.val_at(8)?,
+            authorization_list: rlp.list_at(9)?,
+            odd_y_parity: rlp.val_at(10)?,
+            r: {
+                let mut rarr = [0u8; 32];
+                rlp.val_at::<U256>(11)?.to_big_endian(&mut rarr);
+                H256::from(rarr)
+            },
+            s: {
+                let mut sarr = [0u8; 32];
+                rlp.val_at::<U256>(12)?.to_big_endian(&mut sarr);
+                H256::from(sarr)
+            },
+        })
+    }
+}
+
+// The authorizations only implement `rlp`, so the `open_fastrlp` codec delegates to it
+#[cfg(feature = "fastrlp")]
+impl open_fastrlp::Encodable for EIP7702Transaction {
+    fn length(&self) -> usize {
+        rlp::encode(self).len()
+    }
+    fn encode(&self, out: &mut dyn open_fastrlp::BufMut) {
+        out.put_slice(&rlp::encode(self))
+    }
+}
+
+#[cfg(feature = "fastrlp")]
+impl open_fastrlp::Decodable for EIP7702Transaction {
+    fn decode(buf: &mut &[u8]) -> Result<Self, open_fastrlp::DecodeError> {
+        let item = *buf;
+        let header = open_fastrlp::Header::decode(buf)?;
+        let len = item.len() - buf.len() + header.payload_length;
+        if len > item.len() {
+            return Err(open_fastrlp::DecodeError::InputTooShort)
+        }
+        let tx = rlp::decode(&item[..len])
+            .map_err(|_| open_fastrlp::DecodeError::Custom("invalid set code transaction"))?;
+        *buf = &item[len..];
+        Ok(tx)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TransactionEssentials {
     pub kind: TransactionKind,
@@ -1240,6 +1509,32 @@ impl PendingTransaction {
                     access_list: to_access_list(access_list.0.clone()),
                 }
             }
+            TypedTransaction::EIP7702(tx) => {
+                let EIP7702Transaction {
+                    chain_id,
+                    nonce,
+                    max_priority_fee_per_gas,
+                    max_fee_per_gas,
+                    gas_limit,
+                    kind,
+                    value,
+                    input,
+                    access_list,
+                    ..
+                } = tx;
+                TxEnv {
+                    caller,
+                    transact_to: transact_to(kind),
+                    data: input.0.clone(),
+                    chain_id: Some(*chain_id),
+                    nonce: Some(nonce.as_u64()),
+                    value: *value,
+                    gas_price: *max_fee_per_gas,
+                    gas_priority_fee: Some(*max_priority_fee_per_gas),
+                    gas_limit: gas_limit.as_u64(),
+                    access_list: to_access_list(access_list.0.clone()),
+                }
+            }
         }
     }
 }
@@ -1545,6 +1840,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_encode_set_code_tx() {
+        let authorization = SignedAuthorization {
+            chain_id: 1u64.into(),
+            address: Address::repeat_byte(1),
+            nonce: 2u64.into(),
+            y_parity: 1u64.into(),
+            r: 3u64.into(),
+            s: 4u64.into(),
+        };
+        let tx = TypedTransaction::EIP7702(EIP7702Transaction {
+            chain_id: 1u64,
+            nonce: U256::from(1),
+            max_priority_fee_per_gas: U256::from(1),
+            max_fee_per_gas: U256::from(2),
+            gas_limit: U256::from(100_000),
+            kind: TransactionKind::Call(Address::repeat_byte(2)),
+            value: U256::from(3),
+            input: Bytes::from(vec![1, 2]),
+            access_list: vec![].into(),
+            authorization_list: vec![authorization],
+            odd_y_parity: true,
+            r: H256::default(),
+            s: H256::default(),
+        });
+
+        let encoded = rlp::encode(&tx);
+        let decoded: TypedTransaction = rlp::decode(&encoded).unwrap();
+        assert_eq!(decoded, tx);
+        assert_eq!(decoded.r#type(), Some(SET_CODE_TX_TYPE));
+        assert_eq!(decoded.authorization_list().len(), 1);
+
+        #[cfg(feature = "fastrlp")]
+        {
+            use open_fastrlp::Encodable;
+
+            let mut encoded = bytes::BytesMut::new();
+            tx.encode(&mut encoded);
+            let decoded =
+                <TypedTransaction as open_fastrlp::Decodable>::decode(&mut &*encoded).unwrap();
+            assert_eq!(decoded, tx);
+        }
+    }
+
     // <https://github.com/gakonst/ethers-rs/issues/1732>
     #[test]
     fn test_recover_legacy_tx() {
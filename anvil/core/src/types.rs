@@ -1,4 +1,4 @@
-use ethers_core::types::{H256, U256, U64};
+use ethers_core::types::{Address, Bytes, H256, U256, U64};
 use revm::SpecId;
 
 #[cfg(feature = "serde")]
@@ -76,6 +76,41 @@ pub enum EvmMineOptions {
     Timestamp(Option<u64>),
 }
 
+/// A transaction of an `anvil_buildBlock` request
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum BuildBlockTransaction {
+    /// The hash of a transaction in the transaction pool
+    Hash(H256),
+    /// A raw signed transaction
+    Raw(Bytes),
+}
+
+/// Params of `anvil_buildBlock`
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BuildBlockRequest {
+    /// The transactions of the block, executed in this order
+    pub transactions: Vec<BuildBlockTransaction>,
+    /// The timestamp of the block, defaults to the timestamp of the next block
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            deserialize_with = "ethers_core::types::serde_helpers::deserialize_stringified_u64_opt"
+        )
+    )]
+    pub timestamp: Option<u64>,
+    /// The coinbase of the block, defaults to the configured coinbase
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub coinbase: Option<Address>,
+    /// Whether to add the block to the chain, by default the state is left unchanged
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub commit: bool,
+}
+
 impl Default for EvmMineOptions {
     fn default() -> Self {
         EvmMineOptions::Options { timestamp: None, blocks: None }
@@ -199,3 +234,26 @@ pub struct NodeForkConfig {
     pub fork_block_number: Option<u64>,
     pub fork_retry_backoff: Option<u128>,
 }
+
+/// The nodes of the cluster a node is part of, see `anvil --cluster`
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ClusterInfo {
+    /// The index of the node that answered the request
+    pub node: usize,
+    /// The time it takes a transaction to reach the other nodes, in milliseconds
+    pub propagation_delay: u64,
+    pub nodes: Vec<ClusterNodeInfo>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ClusterNodeInfo {
+    pub index: usize,
+    /// The http endpoint of the node
+    pub endpoint: Option<String>,
+    /// The partition of the node, transactions only propagate between nodes of the same partition
+    pub partition: usize,
+}
@@ -30,6 +30,11 @@ impl RpcResponse {
     pub fn invalid_request(id: Id) -> Self {
         Self::new(id, RpcError::invalid_request())
     }
+
+    /// Returns the result of the call
+    pub fn result(&self) -> &ResponseResult {
+        &self.result
+    }
 }
 
 /// Represents the result of a call either success or error
@@ -15,7 +15,11 @@ use axum::{
 };
 use hyper::server::conn::AddrIncoming;
 use serde::de::DeserializeOwned;
-use std::{fmt, net::SocketAddr};
+use std::{
+    fmt,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::{error, trace};
 
@@ -123,24 +127,71 @@ pub trait RpcHandler: Clone + Send + Sync + 'static {
         let params: serde_json::Value = params.into();
         let call = serde_json::json!({
             "method": &method,
-            "params": params
+            "params": &params
         });
 
-        match serde_json::from_value::<Self::Request>(call) {
+        let start = Instant::now();
+        let mut is_known_method = true;
+        let response = match serde_json::from_value::<Self::Request>(call) {
             Ok(req) => {
                 let result = self.on_request(req).await;
                 RpcResponse::new(id, result)
             }
             Err(err) => {
                 let err = err.to_string();
-                if err.contains("unknown variant") {
-                    error!(target: "rpc", ?method, "failed to deserialize method due to unknown variant");
-                    RpcResponse::new(id, RpcError::method_not_found())
+                is_known_method = !err.contains("unknown variant");
+                if err.contains("unknown variant") || err.contains("did not match any variant") {
+                    match self.on_unknown_method(&method, params.clone()).await {
+                        Some(result) => RpcResponse::new(id, result),
+                        None if err.contains("unknown variant") => {
+                            error!(
+                                target: "rpc",
+                                ?method,
+                                "failed to deserialize method due to unknown variant"
+                            );
+                            RpcResponse::new(id, RpcError::method_not_found())
+                        }
+                        None => {
+                            error!(target: "rpc", ?method, ?err, "failed to deserialize method");
+                            RpcResponse::new(id, RpcError::invalid_params(err))
+                        }
+                    }
                 } else {
                     error!(target: "rpc", ?method, ?err, "failed to deserialize method");
                     RpcResponse::new(id, RpcError::invalid_params(err))
                 }
             }
-        }
+        };
+        self.on_response(&method, is_known_method, &params, start.elapsed(), &response);
+        response
+    }
+
+    /// Invoked by [`Self::on_call`] if the method could not be deserialized into the `Request`
+    /// type, because it is unknown
+    ///
+    /// Returns `None` by default, which responds with a method not found error. This can be used
+    /// to forward unknown methods to another endpoint.
+    async fn on_unknown_method(
+        &self,
+        _method: &str,
+        _params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        None
+    }
+
+    /// Invoked by [`Self::on_call`] once a call was handled, with the time it took to handle it
+    ///
+    /// `is_known_method` is false if the method is not a method of the `Request` type, even if it
+    /// was handled by [`Self::on_unknown_method`].
+    ///
+    /// Does nothing by default, this can be used to record metrics or to log requests.
+    fn on_response(
+        &self,
+        _method: &str,
+        _is_known_method: bool,
+        _params: &serde_json::Value,
+        _elapsed: Duration,
+        _response: &RpcResponse,
+    ) {
     }
 }
@@ -2,7 +2,7 @@ use crate::{error::RequestError, handler::handle_request, RpcHandler};
 use anvil_rpc::{
     error::RpcError,
     request::Request,
-    response::{Response, ResponseResult},
+    response::{Response, ResponseResult, RpcResponse},
 };
 
 use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt};
@@ -16,6 +16,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{error, trace};
 
@@ -31,6 +32,30 @@ pub trait PubSubRpcHandler: Clone + Send + Sync + Unpin + 'static {
 
     /// Invoked when the request was received
     async fn on_request(&self, request: Self::Request, cx: PubSubContext<Self>) -> ResponseResult;
+
+    /// Invoked if the method of a call is unknown
+    ///
+    /// See [`RpcHandler::on_unknown_method`]
+    async fn on_unknown_method(
+        &self,
+        _method: &str,
+        _params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        None
+    }
+
+    /// Invoked once a call was handled, with the time it took to handle it
+    ///
+    /// See [`RpcHandler::on_response`]
+    fn on_response(
+        &self,
+        _method: &str,
+        _is_known_method: bool,
+        _params: &serde_json::Value,
+        _elapsed: Duration,
+        _response: &RpcResponse,
+    ) {
+    }
 }
 
 type Subscriptions<SubscriptionId, Subscription> = Arc<Mutex<Vec<(SubscriptionId, Subscription)>>>;
@@ -108,6 +133,25 @@ impl<Handler: PubSubRpcHandler> RpcHandler for ContextAwareHandler<Handler> {
     async fn on_request(&self, request: Self::Request) -> ResponseResult {
         self.handler.on_request(request, self.context.clone()).await
     }
+
+    async fn on_unknown_method(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        self.handler.on_unknown_method(method, params).await
+    }
+
+    fn on_response(
+        &self,
+        method: &str,
+        is_known_method: bool,
+        params: &serde_json::Value,
+        elapsed: Duration,
+        response: &RpcResponse,
+    ) {
+        self.handler.on_response(method, is_known_method, params, elapsed, response)
+    }
 }
 
 /// Represents a connection to a client via websocket
@@ -0,0 +1,265 @@
+//! Multiple logical nodes that share one chain but have independent mempools, see
+//! `anvil --cluster`
+//!
+//! All nodes of a cluster are backed by the same [Backend](crate::eth::backend::mem::Backend),
+//! so a block mined by any node is instantly part of the canonical chain of all nodes, there is
+//! no block propagation. Transactions however are only added to the mempool of the node they were
+//! sent to, and reach the mempools of the other nodes after the propagation delay, as long as the
+//! nodes are in the same partition.
+
+use crate::{
+    eth::{error::BlockchainError, EthApi},
+    server::error::NodeError,
+};
+use anvil_core::types::{ClusterInfo, ClusterNodeInfo};
+use ethers::providers::{Http, Provider};
+use futures::StreamExt;
+use parking_lot::RwLock;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{sync::broadcast, task::JoinHandle};
+use tracing::trace;
+
+/// The shared state of all nodes of a cluster
+#[derive(Debug)]
+pub struct Cluster {
+    /// The time it takes a transaction to reach the other nodes
+    propagation_delay: Duration,
+    /// The rpc addresses of the nodes, by index
+    addresses: RwLock<Vec<Option<SocketAddr>>>,
+    /// The partition of each node, by index
+    partitions: RwLock<Vec<usize>>,
+    /// Notifies the nodes that all partitions were healed
+    healed: broadcast::Sender<()>,
+}
+
+// === impl Cluster ===
+
+impl Cluster {
+    /// Creates a cluster of `size` connected nodes
+    pub fn new(size: usize, propagation_delay: Duration) -> Self {
+        let (healed, _) = broadcast::channel(1);
+        Self {
+            propagation_delay,
+            addresses: RwLock::new(vec![None; size]),
+            partitions: RwLock::new(vec![0; size]),
+            healed,
+        }
+    }
+
+    /// Returns the number of nodes of the cluster
+    pub fn size(&self) -> usize {
+        self.partitions.read().len()
+    }
+
+    /// Returns the time it takes a transaction to reach the other nodes
+    pub fn propagation_delay(&self) -> Duration {
+        self.propagation_delay
+    }
+
+    /// Sets the rpc address of the node
+    pub(crate) fn set_address(&self, index: usize, address: SocketAddr) {
+        self.addresses.write()[index] = Some(address);
+    }
+
+    /// Returns true if transactions of node `from` propagate to node `to`
+    pub fn can_reach(&self, from: usize, to: usize) -> bool {
+        let partitions = self.partitions.read();
+        partitions.get(from).is_some() && partitions.get(from) == partitions.get(to)
+    }
+
+    /// Splits the cluster into the given groups of node indices, the nodes that are not part of
+    /// any group form another group
+    pub fn partition(&self, groups: Vec<Vec<usize>>) -> Result<(), BlockchainError> {
+        let size = self.size();
+        let unassigned = groups.len();
+        let mut partitions = vec![unassigned; size];
+        for (partition, group) in groups.into_iter().enumerate() {
+            for index in group {
+                if index >= size {
+                    return Err(BlockchainError::ClusterError(format!(
+                        "node {index} is not part of the cluster of {size} nodes"
+                    )))
+                }
+                if partitions[index] != unassigned {
+                    return Err(BlockchainError::ClusterError(format!(
+                        "node {index} is part of multiple groups"
+                    )))
+                }
+                partitions[index] = partition;
+            }
+        }
+        trace!(target: "cluster", ?partitions, "partitioned cluster");
+        *self.partitions.write() = partitions;
+        Ok(())
+    }
+
+    /// Reconnects all nodes, the nodes propagate all transactions of their mempools again
+    pub fn heal(&self) {
+        let size = self.size();
+        *self.partitions.write() = vec![0; size];
+        trace!(target: "cluster", "healed cluster");
+        let _ = self.healed.send(());
+    }
+
+    /// Returns the state of the cluster, as seen by the given node
+    pub fn info(&self, node: usize) -> ClusterInfo {
+        let addresses = self.addresses.read();
+        let partitions = self.partitions.read();
+        ClusterInfo {
+            node,
+            propagation_delay: self.propagation_delay.as_millis() as u64,
+            nodes: partitions
+                .iter()
+                .enumerate()
+                .map(|(index, partition)| ClusterNodeInfo {
+                    index,
+                    endpoint: addresses[index].map(|addr| format!("http://{addr}")),
+                    partition: *partition,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The membership of a node in a cluster
+#[derive(Debug, Clone)]
+pub struct ClusterNode {
+    /// The index of the node in the cluster
+    pub index: usize,
+    pub cluster: Arc<Cluster>,
+}
+
+impl ClusterNode {
+    pub fn new(index: usize, cluster: Arc<Cluster>) -> Self {
+        Self { index, cluster }
+    }
+}
+
+/// A node of a cluster that is spawned in addition to the first node
+pub struct ClusterNodeHandle {
+    /// The api of the node
+    pub api: EthApi,
+    /// The address of the rpc server of the node
+    pub(crate) address: SocketAddr,
+    /// Join handle for the Node Service of the node
+    pub node_service: JoinHandle<Result<(), NodeError>>,
+    /// Join handle for the rpc server of the node
+    pub server: JoinHandle<Result<(), NodeError>>,
+}
+
+impl ClusterNodeHandle {
+    /// The address of the rpc server of the node
+    pub fn socket_address(&self) -> &SocketAddr {
+        &self.address
+    }
+
+    /// Returns the http endpoint of the node
+    pub fn http_endpoint(&self) -> String {
+        format!("http://{}", self.socket_address())
+    }
+
+    /// Returns the websocket endpoint of the node
+    pub fn ws_endpoint(&self) -> String {
+        format!("ws://{}", self.socket_address())
+    }
+
+    /// Returns a Provider for the http endpoint of the node
+    pub fn http_provider(&self) -> Provider<Http> {
+        Provider::<Http>::try_from(self.http_endpoint())
+            .unwrap()
+            .interval(Duration::from_millis(500))
+    }
+}
+
+/// Spawns the tasks that propagate the transactions of every node to the other nodes, and prune
+/// the transactions of blocks mined by any node from the mempools of all nodes
+///
+/// `apis` holds the api of every node, by index.
+pub(crate) fn spawn_sync(cluster: Arc<Cluster>, apis: Vec<EthApi>) {
+    let apis = Arc::new(apis);
+    for (index, api) in apis.iter().enumerate() {
+        tokio::task::spawn(propagate_transactions(Arc::clone(&cluster), Arc::clone(&apis), index));
+        tokio::task::spawn(prune_mined_transactions(api.clone()));
+    }
+}
+
+/// Sends every new ready transaction of the node, and all of them once the cluster was healed, to
+/// the other nodes
+async fn propagate_transactions(cluster: Arc<Cluster>, apis: Arc<Vec<EthApi>>, from: usize) {
+    let mut ready = apis[from].new_ready_transactions();
+    let mut healed = cluster.healed.subscribe();
+    loop {
+        let hashes = tokio::select! {
+            hash = ready.next() => match hash {
+                Some(hash) => vec![hash],
+                None => return,
+            },
+            _ = healed.recv() => apis[from].ready_transaction_hashes(),
+        };
+        let transactions = hashes
+            .into_iter()
+            .filter_map(|hash| apis[from].pool_transaction(hash))
+            .collect::<Vec<_>>();
+        if transactions.is_empty() {
+            continue
+        }
+
+        let cluster = Arc::clone(&cluster);
+        let apis = Arc::clone(&apis);
+        tokio::task::spawn(async move {
+            tokio::time::sleep(cluster.propagation_delay()).await;
+            for (to, api) in apis.iter().enumerate() {
+                if to == from || !cluster.can_reach(from, to) {
+                    continue
+                }
+                for tx in transactions.iter().cloned() {
+                    let hash = *tx.hash();
+                    if let Err(err) = api.import_propagated_transaction(tx).await {
+                        trace!(
+                            target: "cluster",
+                            ?hash,
+                            from,
+                            to,
+                            ?err,
+                            "dropped propagated transaction"
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Removes the transactions of every new block from the mempool of the node, blocks that were
+/// mined by other nodes are not pruned by the node service
+async fn prune_mined_transactions(api: EthApi) {
+    let mut blocks = api.new_block_notifications();
+    while let Some(block) = blocks.next().await {
+        api.prune_block_transactions(block.hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_partition_and_heal() {
+        let cluster = Cluster::new(4, Duration::ZERO);
+        assert!(cluster.can_reach(0, 3));
+
+        cluster.partition(vec![vec![0], vec![1, 2]]).unwrap();
+        assert!(!cluster.can_reach(0, 1));
+        assert!(cluster.can_reach(1, 2));
+        assert!(!cluster.can_reach(2, 3));
+        assert_eq!(cluster.info(0).nodes[3].partition, 2);
+
+        assert!(cluster.partition(vec![vec![0], vec![0]]).is_err());
+        assert!(cluster.partition(vec![vec![4]]).is_err());
+
+        cluster.heal();
+        assert!(cluster.can_reach(0, 1));
+        assert!(cluster.can_reach(2, 3));
+        assert!(!cluster.can_reach(0, 4));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     config::DEFAULT_MNEMONIC,
-    eth::{backend::db::SerializableState, pool::transactions::TransactionOrder, EthApi},
+    eth::{
+        backend::db::SerializableState, pool::transactions::TransactionOrder, proxy::ProxyConfig,
+        EthApi,
+    },
     genesis::Genesis,
     AccountGenerator, Hardfork, NodeConfig, CHAIN_ID,
 };
@@ -9,6 +12,7 @@ use clap::Parser;
 use core::fmt;
 use ethers::utils::WEI_IN_ETHER;
 use foundry_config::{Chain, Config};
+use foundry_evm::executor::inspector::GasOverrides;
 use futures::FutureExt;
 use std::{
     future::Future,
@@ -118,6 +122,60 @@ pub struct NodeArgs {
     )]
     pub host: Option<IpAddr>,
 
+    #[clap(
+        long,
+        help = "Spawn a cluster of the given number of nodes that share one chain, but have their own mempool.",
+        long_help = "Spawn a cluster of the given number of nodes that share one chain, but have their own mempool. The nodes listen on consecutive ports, starting at --port. Transactions reach the mempools of the other nodes after --cluster-delay, the cluster can be partitioned with `anvil_partitionCluster`.",
+        value_name = "NODES",
+        value_parser = clap::value_parser!(u64).range(2..),
+        help_heading = "Server options"
+    )]
+    pub cluster: Option<u64>,
+
+    #[clap(
+        long,
+        help = "The time it takes a transaction to reach the other nodes of the cluster, in milliseconds.",
+        value_name = "MILLISECONDS",
+        default_value = "0",
+        requires = "cluster",
+        help_heading = "Server options"
+    )]
+    pub cluster_delay: u64,
+
+    #[clap(
+        long,
+        help = "Port of an additional HTTP server that exposes node control operations (mine, snapshot, set balance, dump/load state, metrics) as REST endpoints.",
+        value_name = "NUM",
+        help_heading = "Server options"
+    )]
+    pub api_port: Option<u16>,
+
+    #[clap(
+        long,
+        help = "Bearer token that is required by the REST endpoints, see --api-port.",
+        value_name = "TOKEN",
+        env = "ANVIL_API_TOKEN",
+        requires = "api_port",
+        help_heading = "Server options"
+    )]
+    pub api_token: Option<String>,
+
+    #[clap(
+        long = "prometheus",
+        help = "Port of an additional HTTP server that exposes prometheus metrics (RPC calls, response times, mined transactions, fork requests) on /metrics.",
+        value_name = "NUM",
+        help_heading = "Server options"
+    )]
+    pub prometheus_port: Option<u16>,
+
+    #[clap(
+        long,
+        help = "Append every RPC call with its params, duration and result to the file as a line of JSON.",
+        value_name = "PATH",
+        help_heading = "Server options"
+    )]
+    pub log_requests: Option<PathBuf>,
+
     #[clap(
         long,
         help = "How transactions are sorted in the mempool",
@@ -129,6 +187,7 @@ pub struct NodeArgs {
     #[clap(
         long,
         help = "Initialize the genesis block with the given `genesis.json` file.",
+        long_help = "Initialize the genesis block with the given `genesis.json` file. Accepts geth and reth genesis files, the hardfork is the latest one the `config` activates at genesis, unless --hardfork is set. `anvil_dumpGenesis` exports the current chain as a `genesis.json`.",
         value_name = "PATH",
         value_parser = Genesis::parse
     )]
@@ -198,7 +257,11 @@ const IPC_HELP: &str = "Launch an ipc server at the given path or default path =
 const DEFAULT_DUMP_INTERVAL: Duration = Duration::from_secs(60);
 
 impl NodeArgs {
-    pub fn into_node_config(self) -> NodeConfig {
+    /// Returns the config of the node
+    ///
+    /// Returns an error if the project configuration file is invalid.
+    pub fn into_node_config(self) -> Result<NodeConfig, Box<dyn std::error::Error>> {
+        let gas_overrides = self.evm_opts.gas_overrides()?;
         let genesis_balance = WEI_IN_ETHER.saturating_mul(self.balance.into());
         let compute_units_per_second = if self.evm_opts.no_rate_limit {
             Some(u64::MAX)
@@ -206,7 +269,7 @@ impl NodeArgs {
             self.evm_opts.compute_units_per_second
         };
 
-        NodeConfig::default()
+        Ok(NodeConfig::default()
             .with_gas_limit(self.evm_opts.gas_limit)
             .disable_block_gas_limit(self.evm_opts.disable_block_gas_limit)
             .with_gas_price(self.evm_opts.gas_price)
@@ -239,10 +302,18 @@ impl NodeArgs {
             .with_genesis(self.init)
             .with_steps_tracing(self.evm_opts.steps_tracing)
             .with_ipc(self.ipc)
+            .with_api_port(self.api_port)
+            .with_cluster(self.cluster.map(|nodes| nodes as usize))
+            .with_cluster_propagation_delay(Duration::from_millis(self.cluster_delay))
+            .with_api_token(self.api_token)
+            .with_prometheus_port(self.prometheus_port)
+            .with_log_requests(self.log_requests)
             .with_code_size_limit(self.evm_opts.code_size_limit)
+            .with_gas_overrides(gas_overrides)
+            .with_proxy(self.evm_opts.proxy_config())
             .set_pruned_history(self.prune_history)
             .with_init_state(self.load_state.or_else(|| self.state.and_then(|s| s.state)))
-            .with_transaction_block_keeper(self.transaction_block_keeper)
+            .with_transaction_block_keeper(self.transaction_block_keeper))
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -265,13 +336,13 @@ impl NodeArgs {
 
     /// Starts the node
     ///
-    /// See also [crate::spawn()]
+    /// See also [crate::try_spawn()]
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         let dump_state = self.dump_state_path();
         let dump_interval =
             self.state_interval.map(Duration::from_secs).unwrap_or(DEFAULT_DUMP_INTERVAL);
 
-        let (api, mut handle) = crate::spawn(self.into_node_config()).await;
+        let (api, mut handle) = crate::try_spawn(self.into_node_config()?).await?;
 
         // sets the signal handler to gracefully shutdown.
         let mut fork = api.get_fork().cloned();
@@ -424,6 +495,56 @@ pub struct AnvilEvmArgs {
     #[clap(long, requires = "fork_url", help_heading = "Fork config")]
     pub no_storage_caching: bool,
 
+    /// Forward the RPC methods that anvil doesn't implement to the fork url.
+    ///
+    /// Methods that anvil implements, like state changing and cheat methods, are always served
+    /// locally. Lets apps use a forked node as their RPC endpoint, e.g. for `trace_*` methods.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork_url", help_heading = "Fork config")]
+    pub proxy: bool,
+
+    /// Only forward the given methods, a trailing `*` matches any suffix, e.g. `trace_*`.
+    ///
+    /// See --proxy.
+    #[clap(
+        long,
+        requires = "proxy",
+        value_name = "METHODS",
+        value_delimiter = ',',
+        help_heading = "Fork config"
+    )]
+    pub proxy_allow: Vec<String>,
+
+    /// Never forward the given methods, a trailing `*` matches any suffix.
+    ///
+    /// See --proxy.
+    #[clap(
+        long,
+        requires = "proxy",
+        value_name = "METHODS",
+        value_delimiter = ',',
+        help_heading = "Fork config"
+    )]
+    pub proxy_deny: Vec<String>,
+
+    /// Forward unknown methods that may sign or broadcast, like `eth_sendBundle` or
+    /// `personal_sign`, which are refused by default.
+    ///
+    /// These reach the real chain, e.g. a transaction signed for --spoof-chain-id is broadcast.
+    ///
+    /// See --proxy.
+    #[clap(long, requires = "proxy", help_heading = "Fork config")]
+    pub proxy_allow_writes: bool,
+
+    /// Report this chain id to clients instead of the chain id of the node.
+    ///
+    /// Transactions signed for this chain id are accepted as well.
+    ///
+    /// See --proxy.
+    #[clap(long, requires = "proxy", value_name = "CHAIN_ID", help_heading = "Fork config")]
+    pub spoof_chain_id: Option<u64>,
+
     /// The block gas limit.
     #[clap(long, value_name = "GAS_LIMIT", help_heading = "Environment config")]
     pub gas_limit: Option<u64>,
@@ -469,19 +590,45 @@ pub struct AnvilEvmArgs {
 }
 
 /// Resolves an alias passed as fork-url to the matching url defined in the rpc_endpoints section
-/// of the project configuration file.
+/// of the project configuration file, including the block the alias is pinned to.
 /// Does nothing if the fork-url is not a configured alias.
 impl AnvilEvmArgs {
     pub fn resolve_rpc_alias(&mut self) {
         if let Some(fork_url) = &self.fork_url {
             let config = Config::load();
             if let Some(Ok(url)) = config.get_rpc_url_with_alias(&fork_url.url) {
-                self.fork_url = Some(ForkUrl { url: url.to_string(), block: fork_url.block });
+                // use the block the alias is pinned to if no block was set explicitly
+                let block =
+                    fork_url.block.or_else(|| config.rpc_endpoints.pinned_block(&fork_url.url));
+                self.fork_url = Some(ForkUrl { url: url.to_string(), block });
             }
         }
     }
 }
 
+impl AnvilEvmArgs {
+    /// Returns the custom gas schedule configured in the `gas_overrides` of the project
+    /// configuration file.
+    ///
+    /// Returns an error if the configuration file is invalid or the gas schedule contains unknown
+    /// opcodes.
+    pub fn gas_overrides(&self) -> Result<GasOverrides, Box<dyn std::error::Error>> {
+        let config = Config::try_from(Config::figment())?;
+        GasOverrides::new(&config.gas_overrides)
+            .map_err(|err| format!("invalid `gas_overrides`: {err}").into())
+    }
+
+    /// Returns the [ProxyConfig] if unknown methods are forwarded to the fork url
+    pub fn proxy_config(&self) -> Option<ProxyConfig> {
+        self.proxy.then(|| ProxyConfig {
+            allow: self.proxy_allow.clone(),
+            deny: self.proxy_deny.clone(),
+            allow_writes: self.proxy_allow_writes,
+            chain_id: self.spoof_chain_id,
+        })
+    }
+}
+
 /// Helper type to periodically dump the state of the chain to disk
 struct PeriodicStateDumper {
     in_progress_dump: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync + 'static>>>,
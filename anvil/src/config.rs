@@ -9,10 +9,12 @@ use crate::{
         },
         fees::{INITIAL_BASE_FEE, INITIAL_GAS_PRICE},
         pool::transactions::TransactionOrder,
+        proxy::ProxyConfig,
     },
     genesis::Genesis,
     mem,
     mem::in_memory_db::MemDb,
+    metrics::NodeMetrics,
     FeeManager, Hardfork,
 };
 use anvil_server::ServerConfig;
@@ -30,7 +32,10 @@ use ethers::{
 use foundry_common::{ProviderBuilder, ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT};
 use foundry_config::Config;
 use foundry_evm::{
-    executor::fork::{BlockchainDb, BlockchainDbMeta, SharedBackend},
+    executor::{
+        fork::{BlockchainDb, BlockchainDbMeta, SharedBackend},
+        inspector::GasOverrides,
+    },
     revm,
     revm::{BlockEnv, CfgEnv, SpecId, TxEnv},
     utils::apply_chain_and_block_specific_env_changes,
@@ -142,6 +147,14 @@ pub struct NodeConfig {
     pub compute_units_per_second: u64,
     /// The ipc path
     pub ipc_path: Option<Option<String>>,
+    /// port to use for the REST control interface, if any
+    pub api_port: Option<u16>,
+    /// bearer token required by the REST control interface
+    pub api_token: Option<String>,
+    /// port to serve prometheus metrics on, if any
+    pub prometheus_port: Option<u16>,
+    /// file to log all RPC calls to as JSON, if any
+    pub log_requests: Option<PathBuf>,
     /// Enable transaction/call steps tracing for debug calls returning geth-style traces
     pub enable_steps_tracing: bool,
     /// Configure the code size limit
@@ -154,6 +167,17 @@ pub struct NodeConfig {
     pub init_state: Option<SerializableState>,
     /// max number of blocks with transactions in memory
     pub transaction_block_keeper: Option<usize>,
+    /// The custom gas schedule, if any
+    pub gas_overrides: Option<GasOverrides>,
+    /// Forwards unknown RPC methods to the fork url, if set
+    pub proxy: Option<ProxyConfig>,
+    /// The number of nodes of the cluster, if more than one node is spawned
+    ///
+    /// All nodes share one chain, but every node has its own mempool and rpc server. The nodes
+    /// listen on consecutive ports starting at `port`.
+    pub cluster: Option<usize>,
+    /// The time it takes a transaction to reach the other nodes of the cluster
+    pub cluster_propagation_delay: Duration,
 }
 
 impl NodeConfig {
@@ -374,10 +398,18 @@ impl Default for NodeConfig {
             // alchemy max cpus <https://github.com/alchemyplatform/alchemy-docs/blob/master/documentation/compute-units.md#rate-limits-cups>
             compute_units_per_second: ALCHEMY_FREE_TIER_CUPS,
             ipc_path: None,
+            api_port: None,
+            api_token: None,
+            prometheus_port: None,
+            log_requests: None,
             code_size_limit: None,
             prune_history: Default::default(),
             init_state: None,
             transaction_block_keeper: None,
+            gas_overrides: None,
+            proxy: None,
+            cluster: None,
+            cluster_propagation_delay: Duration::ZERO,
         }
     }
 }
@@ -395,9 +427,27 @@ impl NodeConfig {
         self.gas_price.unwrap_or_else(|| INITIAL_GAS_PRICE.into())
     }
 
-    /// Returns the base fee to use
+    /// Returns the hardfork to use
+    ///
+    /// If no hardfork is set, this is the latest hardfork the `genesis.json` activates at genesis
     pub fn get_hardfork(&self) -> Hardfork {
-        self.hardfork.unwrap_or_default()
+        self.hardfork
+            .or_else(|| self.genesis.as_ref().and_then(|genesis| genesis.hardfork()))
+            .unwrap_or_default()
+    }
+
+    /// Sets the custom gas schedule
+    #[must_use]
+    pub fn with_gas_overrides(mut self, gas_overrides: GasOverrides) -> Self {
+        self.gas_overrides = if gas_overrides.is_empty() { None } else { Some(gas_overrides) };
+        self
+    }
+
+    /// Forwards the RPC methods that anvil doesn't implement to the fork url
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
     }
 
     /// Sets a custom code size limit
@@ -594,6 +644,48 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the number of nodes of the cluster, see [NodeConfig::cluster]
+    #[must_use]
+    pub fn with_cluster(mut self, cluster: Option<usize>) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// Sets the time it takes a transaction to reach the other nodes of the cluster
+    #[must_use]
+    pub fn with_cluster_propagation_delay(mut self, delay: Duration) -> Self {
+        self.cluster_propagation_delay = delay;
+        self
+    }
+
+    /// Sets the port of the REST control interface, `None` disables it
+    #[must_use]
+    pub fn with_api_port(mut self, api_port: Option<u16>) -> Self {
+        self.api_port = api_port;
+        self
+    }
+
+    /// Sets the bearer token that is required by the REST control interface
+    #[must_use]
+    pub fn with_api_token(mut self, api_token: Option<String>) -> Self {
+        self.api_token = api_token;
+        self
+    }
+
+    /// Sets the port to serve prometheus metrics on, `None` disables it
+    #[must_use]
+    pub fn with_prometheus_port(mut self, prometheus_port: Option<u16>) -> Self {
+        self.prometheus_port = prometheus_port;
+        self
+    }
+
+    /// Sets the file all RPC calls are logged to
+    #[must_use]
+    pub fn with_log_requests(mut self, log_requests: Option<PathBuf>) -> Self {
+        self.log_requests = log_requests;
+        self
+    }
+
     /// Sets the file path to write the Anvil node's config info to.
     #[must_use]
     pub fn set_config_out(mut self, config_out: Option<String>) -> Self {
@@ -748,7 +840,7 @@ impl NodeConfig {
     /// [Backend](mem::Backend)
     ///
     /// *Note*: only memory based backend for now
-    pub(crate) async fn setup(&mut self) -> mem::Backend {
+    pub(crate) async fn setup(&mut self, metrics: &NodeMetrics) -> mem::Backend {
         // configure the revm environment
         let mut env = revm::Env {
             cfg: CfgEnv {
@@ -783,6 +875,7 @@ impl NodeConfig {
                     .timeout_retry(self.fork_request_retries)
                     .initial_backoff(self.fork_retry_backoff.as_millis() as u64)
                     .compute_units_per_second(self.compute_units_per_second)
+                    .request_counter(metrics.fork_requests())
                     .max_retry(10)
                     .initial_backoff(1000)
                     .build()
@@ -955,18 +1048,20 @@ impl NodeConfig {
         };
 
         // only memory based backend for now
-        let backend = mem::Backend::with_genesis(
+        let mut backend = mem::Backend::with_genesis(
             db,
             Arc::new(RwLock::new(env)),
             genesis,
             fees,
             fork,
             self.enable_steps_tracing,
+            self.gas_overrides.clone(),
             self.prune_history,
             self.transaction_block_keeper,
             self.block_time,
         )
         .await;
+        backend.set_spoofed_chain_id(self.proxy.as_ref().and_then(|proxy| proxy.chain_id));
 
         if let Some(ref state) = self.init_state {
             backend
@@ -1,4 +1,5 @@
 use crate::{
+    cluster::ClusterNode,
     eth::{
         backend,
         backend::{
@@ -18,11 +19,14 @@ use crate::{
             },
             Pool,
         },
+        proxy::{is_unknown_method, is_write_method, ProxyConfig},
         sign,
         sign::Signer,
     },
     filter::{EthFilter, Filters, LogsFilter},
+    genesis::Genesis,
     mem::transaction_build,
+    metrics::NodeMetrics,
     revm::TransactOut,
     ClientFork, LoggingManager, Miner, MiningMode, StorageInfo,
 };
@@ -30,6 +34,7 @@ use anvil_core::{
     eth::{
         block::BlockInfo,
         proof::AccountProof,
+        simulate::{SimulatePayload, SimulatedBlock},
         state::StateOverride,
         transaction::{
             EthTransactionRequest, LegacyTransaction, PendingTransaction, TypedTransaction,
@@ -37,7 +42,10 @@ use anvil_core::{
         },
         EthRequest,
     },
-    types::{EvmMineOptions, Forking, Index, NodeEnvironment, NodeForkConfig, NodeInfo, Work},
+    types::{
+        BuildBlockRequest, BuildBlockTransaction, ClusterInfo, EvmMineOptions, Forking, Index,
+        NodeEnvironment, NodeForkConfig, NodeInfo, Work,
+    },
 };
 use anvil_rpc::{error::RpcError, response::ResponseResult};
 use ethers::{
@@ -102,6 +110,12 @@ pub struct EthApi {
     transaction_order: Arc<RwLock<TransactionOrder>>,
     /// Whether we're listening for RPC calls
     net_listening: bool,
+    /// Metrics of the node
+    metrics: Arc<NodeMetrics>,
+    /// Forwards unknown methods to the fork url, if set
+    proxy: Option<Arc<ProxyConfig>>,
+    /// The cluster this node is part of, if any
+    cluster: Option<ClusterNode>,
 }
 
 // === impl Eth RPC API ===
@@ -119,6 +133,8 @@ impl EthApi {
         logger: LoggingManager,
         filters: Filters,
         transactions_order: TransactionOrder,
+        metrics: Arc<NodeMetrics>,
+        proxy: Option<ProxyConfig>,
     ) -> Self {
         Self {
             pool,
@@ -132,9 +148,64 @@ impl EthApi {
             filters,
             net_listening: true,
             transaction_order: Arc::new(RwLock::new(transactions_order)),
+            metrics,
+            proxy: proxy.map(Arc::new),
+            cluster: None,
         }
     }
 
+    /// Makes this node a node of the cluster
+    pub fn with_cluster(mut self, cluster: ClusterNode) -> Self {
+        self.cluster = Some(cluster);
+        self
+    }
+
+    /// Forwards a method that anvil doesn't implement to the fork url
+    ///
+    /// Returns `None` if the method is not forwarded, because anvil is not in proxy mode, the
+    /// method is filtered or anvil implements it.
+    pub async fn proxy_request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        let proxy = self.proxy.as_ref()?;
+        let fork = self.backend.get_fork()?;
+        if !is_unknown_method(method, &params) {
+            return None
+        }
+        if !proxy.allow_writes && is_write_method(method) {
+            return Some(
+                RpcError::invalid_params(format!(
+                    "{method} is not forwarded to the fork url, since it could reach the real \
+                     chain, see --proxy-allow-writes"
+                ))
+                .into(),
+            )
+        }
+        if !proxy.is_proxied(method) {
+            return None
+        }
+
+        node_info!("{} (proxied)", method);
+        let params = if params.is_null() { serde_json::json!([]) } else { params };
+        let res = fork.provider().request::<_, serde_json::Value>(method, params).await;
+        Some(match res {
+            Ok(value) => ResponseResult::Success(value),
+            Err(err) => {
+                RpcError::internal_error_with(format!("proxied request failed: {err}")).into()
+            }
+        })
+    }
+
+    /// The chain id that is reported to clients, see [ProxyConfig::chain_id]
+    fn reported_chain_id(&self) -> u64 {
+        self.proxy
+            .as_ref()
+            .and_then(|proxy| proxy.chain_id)
+            .unwrap_or_else(|| self.backend.chain_id().as_u64())
+    }
+
     /// Executes the [EthRequest] and returns an RPC [RpcResponse]
     pub async fn execute(&self, request: EthRequest) -> ResponseResult {
         trace!(target: "rpc::api", "executing eth request");
@@ -213,6 +284,9 @@ impl EthApi {
             EthRequest::EthCall(call, block, overrides) => {
                 self.call(call, block, overrides).await.to_rpc_result()
             }
+            EthRequest::EthSimulateV1(payload, block) => {
+                self.simulate_v1(payload, block).await.to_rpc_result()
+            }
             EthRequest::EthCreateAccessList(call, block) => {
                 self.create_access_list(call, block).await.to_rpc_result()
             }
@@ -263,10 +337,16 @@ impl EthApi {
             EthRequest::StopImpersonatingAccount(addr) => {
                 self.anvil_stop_impersonating_account(addr).await.to_rpc_result()
             }
+            EthRequest::ImpersonateAndCall(tx) => {
+                self.anvil_impersonate_and_call(*tx).await.to_rpc_result()
+            }
             EthRequest::GetAutoMine(()) => self.anvil_get_auto_mine().to_rpc_result(),
             EthRequest::Mine(blocks, interval) => {
                 self.anvil_mine(blocks, interval).await.to_rpc_result()
             }
+            EthRequest::Reorg(depth, new_blocks, reinclude) => {
+                self.anvil_reorg(depth, new_blocks, reinclude).await.to_rpc_result()
+            }
             EthRequest::SetAutomine(enabled) => {
                 self.anvil_set_auto_mine(enabled).await.to_rpc_result()
             }
@@ -276,6 +356,17 @@ impl EthApi {
             EthRequest::DropTransaction(tx) => {
                 self.anvil_drop_transaction(tx).await.to_rpc_result()
             }
+            EthRequest::BuildBlock(request) => {
+                self.anvil_build_block(request).await.to_rpc_result()
+            }
+            EthRequest::ClusterInfo(()) => self.anvil_cluster_info().await.to_rpc_result(),
+            EthRequest::PartitionCluster(groups) => {
+                self.anvil_partition_cluster(groups).await.to_rpc_result()
+            }
+            EthRequest::HealCluster(()) => self.anvil_heal_cluster().await.to_rpc_result(),
+            EthRequest::DropAllTransactions(()) => {
+                self.anvil_drop_all_transactions().await.to_rpc_result()
+            }
             EthRequest::Reset(fork) => {
                 self.anvil_reset(fork.and_then(|p| p.params)).await.to_rpc_result()
             }
@@ -300,6 +391,7 @@ impl EthApi {
                 self.anvil_set_next_block_base_fee_per_gas(gas).await.to_rpc_result()
             }
             EthRequest::DumpState(_) => self.anvil_dump_state().await.to_rpc_result(),
+            EthRequest::DumpGenesis(_) => self.anvil_dump_genesis().await.to_rpc_result(),
             EthRequest::LoadState(buf) => self.anvil_load_state(buf).await.to_rpc_result(),
             EthRequest::NodeInfo(_) => self.anvil_node_info().await.to_rpc_result(),
             EthRequest::EvmSnapshot(_) => self.evm_snapshot().await.to_rpc_result(),
@@ -439,7 +531,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_chainId`
     pub fn eth_chain_id(&self) -> Result<Option<U64>> {
         node_info!("eth_chainId");
-        Ok(Some(self.backend.chain_id().as_u64().into()))
+        Ok(Some(self.reported_chain_id().into()))
     }
 
     /// Returns the same as `chain_id`
@@ -447,7 +539,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_networkId`
     pub fn network_id(&self) -> Result<Option<String>> {
         node_info!("eth_networkId");
-        let chain_id = self.backend.chain_id().as_u64();
+        let chain_id = self.reported_chain_id();
         Ok(Some(format!("{chain_id}")))
     }
 
@@ -778,49 +870,13 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_sendRawTransaction`
     pub async fn send_raw_transaction(&self, tx: Bytes) -> Result<TxHash> {
         node_info!("eth_sendRawTransaction");
-        let data = tx.as_ref();
-        if data.is_empty() {
-            return Err(BlockchainError::EmptyRawTransactionData)
-        }
-        let transaction = if data[0] > 0x7f {
-            // legacy transaction
-            match rlp::decode::<LegacyTransaction>(data) {
-                Ok(transaction) => TypedTransaction::Legacy(transaction),
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
-            }
-        } else {
-            // the [TypedTransaction] requires a valid rlp input,
-            // but EIP-1559 prepends a version byte, so we need to encode the data first to get a
-            // valid rlp and then rlp decode impl of `TypedTransaction` will remove and check the
-            // version byte
-            let extend = rlp::encode(&data);
-            let tx = match rlp::decode::<TypedTransaction>(&extend[..]) {
-                Ok(transaction) => transaction,
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
-            };
-
-            self.ensure_typed_transaction_supported(&tx)?;
-
-            tx
-        };
-
-        let pending_transaction = PendingTransaction::new(transaction)?;
+        let pending_transaction = self.decode_raw_transaction(&tx)?;
 
         // pre-validate
         self.backend.validate_pool_transaction(&pending_transaction).await?;
 
-        let on_chain_nonce = self.backend.current_nonce(*pending_transaction.sender()).await?;
         let from = *pending_transaction.sender();
-        let nonce = *pending_transaction.transaction.nonce();
-        let requires = required_marker(nonce, on_chain_nonce, from);
-
-        let priority = self.transaction_priority(&pending_transaction.transaction);
-        let pool_transaction = PoolTransaction {
-            requires,
-            provides: vec![to_marker(nonce.as_u64(), *pending_transaction.sender())],
-            pending_transaction,
-            priority,
-        };
+        let pool_transaction = self.to_pool_transaction(pending_transaction).await?;
 
         let tx = self.pool.add_transaction(pool_transaction)?;
         trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
@@ -866,6 +922,31 @@ impl EthApi {
         ensure_return_ok(exit, &out)
     }
 
+    /// Simulates calls in multiple blocks on top of the given block, returning the simulated blocks
+    /// with the results of their calls.
+    ///
+    /// Handler for ETH RPC call: `eth_simulateV1`
+    pub async fn simulate_v1(
+        &self,
+        payload: SimulatePayload,
+        block_number: Option<BlockId>,
+    ) -> Result<Vec<SimulatedBlock>> {
+        node_info!("eth_simulateV1");
+        let block_request = self.block_request(block_number).await?;
+        // check if the number predates the fork, if in fork mode
+        if let BlockRequest::Number(number) = &block_request {
+            if let Some(fork) = self.get_fork() {
+                if fork.predates_fork(number.as_u64()) {
+                    return Err(BlockchainError::SimulationError(
+                        "not available on past forked blocks".to_string(),
+                    ))
+                }
+            }
+        }
+
+        self.backend.simulate(payload, Some(block_request)).await
+    }
+
     /// This method creates an EIP2930 type accessList based on a given Transaction. The accessList
     /// contains all storage slots and addresses read and written by the transaction, except for the
     /// sender account and the precompiles.
@@ -1351,6 +1432,45 @@ impl EthApi {
         Ok(())
     }
 
+    /// Executes the transaction as its `from` address without a signature and mines it right
+    /// away in its own block, regardless of the mining mode.
+    ///
+    /// Unlike `anvil_impersonateAccount` the sender is not added to the impersonated accounts. The
+    /// sender can be a contract, its nonce is used and incremented like for any other transaction
+    /// so contract creations are deployed to the address derived from the sender and its nonce.
+    /// The transaction skips the pool, so it uses the current nonce of the sender unless a nonce
+    /// is provided.
+    ///
+    /// Handler for ETH RPC call: `anvil_impersonateAndCall`
+    pub async fn anvil_impersonate_and_call(
+        &self,
+        mut request: EthTransactionRequest,
+    ) -> Result<TransactionReceipt> {
+        node_info!("anvil_impersonateAndCall");
+        let from = request.from.ok_or(BlockchainError::NoSignerAvailable)?;
+        let current_nonce = self.backend.current_nonce(from).await?;
+        let nonce = *request.nonce.get_or_insert(current_nonce);
+        if nonce > current_nonce {
+            return Err(InvalidTransactionError::NonceTooHigh.into())
+        }
+
+        let (pending_transaction, _) = self.build_impersonated_transaction(request).await?;
+        let pool_transaction = self.to_pool_transaction(pending_transaction).await?;
+        let hash = *pool_transaction.hash();
+
+        let outcome = self.backend.mine_block(vec![Arc::new(pool_transaction)]).await;
+        let included = !outcome.included.is_empty();
+        trace!(target: "node", blocknumber = ?outcome.block_number, ?hash, "mined impersonated tx");
+        self.pool.on_mined_block(outcome);
+        if !included {
+            return Err(BlockchainError::Internal(format!("transaction {hash:?} was not mined")))
+        }
+
+        self.transaction_receipt(hash)
+            .await?
+            .ok_or_else(|| BlockchainError::Internal(format!("no receipt for {hash:?}")))
+    }
+
     /// Returns true if auto mining is enabled, and false.
     ///
     /// Handler for ETH RPC call: `anvil_getAutomine`
@@ -1401,6 +1521,42 @@ impl EthApi {
         Ok(())
     }
 
+    /// Rolls back `depth` blocks and mines `new_blocks` blocks on the new branch.
+    ///
+    /// If `reinclude_transactions` is set, the transactions of the removed blocks are added back
+    /// to the pool before the new blocks are mined.
+    ///
+    /// Handler for ETH RPC call: `anvil_reorg`
+    pub async fn anvil_reorg(
+        &self,
+        depth: U256,
+        new_blocks: U256,
+        reinclude_transactions: Option<bool>,
+    ) -> Result<()> {
+        node_info!("anvil_reorg");
+        if new_blocks.is_zero() {
+            return Err(RpcError::invalid_params("At least one new block must be mined").into())
+        }
+        let removed = self.backend.rollback(depth.try_into().unwrap_or(u64::MAX)).await?;
+
+        if reinclude_transactions.unwrap_or_default() {
+            for tx in removed.into_iter().flat_map(|removed| removed.block.transactions) {
+                let pending_transaction = match tx.impersonated_sender {
+                    Some(sender) => PendingTransaction::with_impersonated(tx.transaction, sender),
+                    None => PendingTransaction::new(tx.transaction)?,
+                };
+                let pool_transaction = self.to_pool_transaction(pending_transaction).await?;
+                self.pool.add_transaction(pool_transaction)?;
+            }
+        }
+
+        for _ in 0..new_blocks.try_into().unwrap_or(u64::MAX) {
+            self.mine_one().await;
+        }
+
+        Ok(())
+    }
+
     /// Sets the mining behavior to interval with the given interval (seconds)
     ///
     /// Handler for ETH RPC call: `evm_setIntervalMining`
@@ -1428,6 +1584,63 @@ impl EthApi {
         Ok(self.pool.drop_transaction(tx_hash).map(|tx| *tx.hash()))
     }
 
+    /// Removes all transactions from the pool
+    ///
+    /// Handler for RPC call: `anvil_dropAllTransactions`
+    pub async fn anvil_drop_all_transactions(&self) -> Result<()> {
+        node_info!("anvil_dropAllTransactions");
+        self.pool.clear();
+        Ok(())
+    }
+
+    /// Builds a block from the given transactions, in the given order, and returns it
+    ///
+    /// Handler for RPC call: `anvil_buildBlock`
+    ///
+    /// The transactions are either hashes of transactions in the pool or raw signed transactions.
+    /// Unless the request is committed, the block is discarded and the chain remains unchanged.
+    pub async fn anvil_build_block(
+        &self,
+        request: BuildBlockRequest,
+    ) -> Result<Block<Transaction>> {
+        node_info!("anvil_buildBlock");
+        let BuildBlockRequest { transactions, timestamp, coinbase, commit } = request;
+
+        let mut pool_transactions = Vec::with_capacity(transactions.len());
+        for tx in transactions {
+            let pending_transaction = match tx {
+                BuildBlockTransaction::Hash(hash) => self
+                    .pool
+                    .get_transaction(hash)
+                    .ok_or(BlockchainError::TransactionNotFound(hash))?,
+                BuildBlockTransaction::Raw(tx) => self.decode_raw_transaction(&tx)?,
+            };
+            pool_transactions.push(Arc::new(self.to_pool_transaction(pending_transaction).await?));
+        }
+
+        if !commit {
+            let info = self.backend.build_block(pool_transactions, timestamp, coinbase).await;
+            return self.convert_block_info(info).ok_or(BlockchainError::BlockNotFound)
+        }
+
+        if let Some(timestamp) = timestamp {
+            self.evm_set_next_block_timestamp(timestamp)?;
+        }
+        let outcome = match coinbase {
+            Some(coinbase) => {
+                self.backend.mine_block_with_coinbase(pool_transactions, coinbase).await
+            }
+            None => self.backend.mine_block(pool_transactions).await,
+        };
+        let block_number = outcome.block_number;
+        self.pool.on_mined_block(outcome);
+
+        self.backend
+            .block_by_number_full(BlockNumber::Number(block_number))
+            .await?
+            .ok_or(BlockchainError::BlockNotFound)
+    }
+
     /// Reset the fork to a fresh forked state, and optionally update the fork config.
     ///
     /// If `forking` is `None` then this will disable forking entirely.
@@ -1540,6 +1753,15 @@ impl EthApi {
         self.backend.dump_state().await
     }
 
+    /// Returns the current state and chain settings as a `genesis.json`, which initializes geth,
+    /// reth or another anvil instance with `--init` to continue from the current state
+    ///
+    /// Handler for RPC call: `anvil_dumpGenesis`
+    pub async fn anvil_dump_genesis(&self) -> Result<Genesis> {
+        node_info!("anvil_dumpGenesis");
+        self.backend.genesis().await
+    }
+
     /// Returns the current state
     pub async fn serialized_state(&self) -> Result<SerializableState> {
         self.backend.serialized_state().await
@@ -1554,6 +1776,33 @@ impl EthApi {
         self.backend.load_state(buf).await
     }
 
+    /// Returns the nodes of the cluster this node is part of
+    ///
+    /// Handler for RPC call: `anvil_clusterInfo`
+    pub async fn anvil_cluster_info(&self) -> Result<ClusterInfo> {
+        node_info!("anvil_clusterInfo");
+        let node = self.cluster_node()?;
+        Ok(node.cluster.info(node.index))
+    }
+
+    /// Splits the cluster into the given groups of node indices, transactions only propagate
+    /// between the nodes of the same group
+    ///
+    /// Handler for RPC call: `anvil_partitionCluster`
+    pub async fn anvil_partition_cluster(&self, groups: Vec<Vec<usize>>) -> Result<()> {
+        node_info!("anvil_partitionCluster");
+        self.cluster_node()?.cluster.partition(groups)
+    }
+
+    /// Reconnects all nodes of the cluster
+    ///
+    /// Handler for RPC call: `anvil_healCluster`
+    pub async fn anvil_heal_cluster(&self) -> Result<()> {
+        node_info!("anvil_healCluster");
+        self.cluster_node()?.cluster.heal();
+        Ok(())
+    }
+
     /// Retrieves the Anvil node configuration params.
     ///
     /// Handler for RPC call: `anvil_nodeInfo`
@@ -1782,7 +2031,32 @@ impl EthApi {
         request: EthTransactionRequest,
     ) -> Result<TxHash> {
         node_info!("eth_sendUnsignedTransaction");
-        // either use the impersonated account of the request's `from` field
+        self.send_impersonated_transaction(request).await
+    }
+
+    /// Adds the transaction to the pool as an impersonated transaction of its `from` address
+    async fn send_impersonated_transaction(
+        &self,
+        request: EthTransactionRequest,
+    ) -> Result<TxHash> {
+        let (pending_transaction, on_chain_nonce) =
+            self.build_impersonated_transaction(request).await?;
+        let from = *pending_transaction.sender();
+        let nonce = *pending_transaction.transaction.nonce();
+
+        let requires = required_marker(nonce, on_chain_nonce, from);
+        let provides = vec![to_marker(nonce.as_u64(), from)];
+
+        self.add_pending_transaction(pending_transaction, requires, provides)
+    }
+
+    /// Builds and pre-validates the impersonated transaction of the request's `from` address
+    ///
+    /// Returns the transaction and the highest nonce of the sender.
+    async fn build_impersonated_transaction(
+        &self,
+        request: EthTransactionRequest,
+    ) -> Result<(PendingTransaction, U256)> {
         let from = request.from.ok_or(BlockchainError::NoSignerAvailable)?;
 
         let (nonce, on_chain_nonce) = self.request_nonce(&request, from).await?;
@@ -1799,10 +2073,7 @@ impl EthApi {
         // pre-validate
         self.backend.validate_pool_transaction(&pending_transaction).await?;
 
-        let requires = required_marker(nonce, on_chain_nonce, from);
-        let provides = vec![to_marker(nonce.as_u64(), from)];
-
-        self.add_pending_transaction(pending_transaction, requires, provides)
+        Ok((pending_transaction, on_chain_nonce))
     }
 
     /// Returns the number of transactions currently pending for inclusion in the next block(s), as
@@ -1897,6 +2168,56 @@ impl EthApi {
 // === impl EthApi utility functions ===
 
 impl EthApi {
+    /// Decodes a raw signed transaction
+    fn decode_raw_transaction(&self, tx: &Bytes) -> Result<PendingTransaction> {
+        let data = tx.as_ref();
+        if data.is_empty() {
+            return Err(BlockchainError::EmptyRawTransactionData)
+        }
+        let transaction = if data[0] > 0x7f {
+            // legacy transaction
+            match rlp::decode::<LegacyTransaction>(data) {
+                Ok(transaction) => TypedTransaction::Legacy(transaction),
+                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
+            }
+        } else {
+            // the [TypedTransaction] requires a valid rlp input,
+            // but EIP-1559 prepends a version byte, so we need to encode the data first to get a
+            // valid rlp and then rlp decode impl of `TypedTransaction` will remove and check the
+            // version byte
+            let extend = rlp::encode(&data);
+            let tx = match rlp::decode::<TypedTransaction>(&extend[..]) {
+                Ok(transaction) => transaction,
+                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
+            };
+
+            self.ensure_typed_transaction_supported(&tx)?;
+
+            tx
+        };
+
+        Ok(PendingTransaction::new(transaction)?)
+    }
+
+    /// Creates the [PoolTransaction] of the transaction, with the markers of its nonce
+    async fn to_pool_transaction(
+        &self,
+        pending_transaction: PendingTransaction,
+    ) -> Result<PoolTransaction> {
+        let on_chain_nonce = self.backend.current_nonce(*pending_transaction.sender()).await?;
+        let from = *pending_transaction.sender();
+        let nonce = *pending_transaction.transaction.nonce();
+        let requires = required_marker(nonce, on_chain_nonce, from);
+
+        let priority = self.transaction_priority(&pending_transaction.transaction);
+        Ok(PoolTransaction {
+            requires,
+            provides: vec![to_marker(nonce.as_u64(), *pending_transaction.sender())],
+            pending_transaction,
+            priority,
+        })
+    }
+
     /// Executes the `evm_mine` and returns the number of blocks mined
     async fn do_evm_mine(&self, opts: Option<EvmMineOptions>) -> Result<u64> {
         let mut blocks_to_mine = 1u64;
@@ -2131,6 +2452,11 @@ impl EthApi {
         self.signers.iter().find(|signer| signer.is_signer_for(address))
     }
 
+    /// Returns the metrics of the node
+    pub fn metrics(&self) -> &Arc<NodeMetrics> {
+        &self.metrics
+    }
+
     /// Returns a new block event stream that yields Notifications when a new block was added
     pub fn new_block_notifications(&self) -> NewBlockNotifications {
         self.backend.new_block_notifications()
@@ -2141,6 +2467,59 @@ impl EthApi {
         self.pool.add_ready_listener()
     }
 
+    /// Returns the cluster this node is part of
+    fn cluster_node(&self) -> Result<&ClusterNode> {
+        self.cluster.as_ref().ok_or_else(|| {
+            BlockchainError::ClusterError("node is not part of a cluster".to_string())
+        })
+    }
+
+    /// Returns the transaction with the given hash from the pool of this node
+    pub(crate) fn pool_transaction(&self, hash: TxHash) -> Option<PendingTransaction> {
+        self.pool.get_transaction(hash)
+    }
+
+    /// Returns the hashes of all ready transactions in the pool of this node
+    pub(crate) fn ready_transaction_hashes(&self) -> Vec<TxHash> {
+        self.pool.ready_transactions().map(|tx| *tx.hash()).collect()
+    }
+
+    /// Adds a transaction that was propagated by another node of the cluster to the pool
+    ///
+    /// Transactions that are already in the pool are ignored, transactions that are no longer
+    /// valid, e.g. because they were mined in the meantime, are rejected.
+    pub(crate) async fn import_propagated_transaction(
+        &self,
+        pending_transaction: PendingTransaction,
+    ) -> Result<()> {
+        if self.pool.contains(pending_transaction.hash()) {
+            return Ok(())
+        }
+        self.backend.validate_pool_transaction(&pending_transaction).await?;
+        let pool_transaction = self.to_pool_transaction(pending_transaction).await?;
+        self.pool.add_transaction(pool_transaction)?;
+        Ok(())
+    }
+
+    /// Prunes the transactions of the block from the pool of this node
+    ///
+    /// The pool of the node that mined a block is pruned by its node service, but the pools of
+    /// the other nodes of a cluster still hold the transactions, or transactions that depend on
+    /// them.
+    pub(crate) fn prune_block_transactions(&self, block_hash: H256) {
+        let block = match self.backend.get_block_by_hash(block_hash) {
+            Some(block) => block,
+            None => return,
+        };
+        let markers = self
+            .backend
+            .mined_transaction_senders(&block)
+            .into_iter()
+            .map(|(from, nonce)| to_marker(nonce.as_u64(), from))
+            .collect::<Vec<_>>();
+        self.pool.prune_markers(block.header.number.as_u64().into(), markers);
+    }
+
     /// Returns a new accessor for certain storage elements
     pub fn storage_info(&self) -> StorageInfo {
         StorageInfo::new(Arc::clone(&self.backend))
@@ -2170,8 +2549,13 @@ impl EthApi {
     /// Returns the full pending block with `Transaction` objects
     async fn pending_block_full(&self) -> Option<Block<Transaction>> {
         let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
-        let BlockInfo { block, transactions, receipts: _ } =
-            self.backend.pending_block(transactions).await;
+        let info = self.backend.pending_block(transactions).await;
+        self.convert_block_info(info)
+    }
+
+    /// Converts a block that is not part of the chain into a full block
+    fn convert_block_info(&self, info: BlockInfo) -> Option<Block<Transaction>> {
+        let BlockInfo { block, transactions, receipts: _ } = info;
 
         let ethers_block = self.backend.convert_block(block.clone());
 
@@ -2234,6 +2618,15 @@ impl EthApi {
                 }
                 TypedTransactionRequest::EIP1559(m)
             }
+            Some(TypedTransactionRequest::EIP7702(mut m)) => {
+                m.nonce = nonce;
+                m.chain_id = chain_id;
+                m.gas_limit = gas_limit;
+                if max_fee_per_gas.is_none() {
+                    m.max_fee_per_gas = self.gas_price().unwrap_or_default();
+                }
+                TypedTransactionRequest::EIP7702(m)
+            }
             _ => return Err(BlockchainError::FailedToDecodeTransaction),
         };
         Ok(request)
@@ -2309,7 +2702,9 @@ impl EthApi {
     fn ensure_typed_transaction_supported(&self, tx: &TypedTransaction) -> Result<()> {
         match &tx {
             TypedTransaction::EIP2930(_) => self.backend.ensure_eip2930_active(),
-            TypedTransaction::EIP1559(_) => self.backend.ensure_eip1559_active(),
+            TypedTransaction::EIP1559(_) | TypedTransaction::EIP7702(_) => {
+                self.backend.ensure_eip1559_active()
+            }
             TypedTransaction::Legacy(_) => Ok(()),
         }
     }
@@ -8,7 +8,9 @@ use crate::{
 };
 use anvil_core::eth::{
     block::{Block, BlockInfo, Header, PartialHeader},
-    receipt::{EIP1559Receipt, EIP2930Receipt, EIP658Receipt, Log, TypedReceipt},
+    receipt::{
+        EIP1559Receipt, EIP2930Receipt, EIP658Receipt, EIP7702Receipt, Log, TypedReceipt,
+    },
     transaction::{PendingTransaction, TransactionInfo, TypedTransaction},
     trie,
 };
@@ -19,7 +21,7 @@ use ethers::{
 };
 use forge::revm::ExecutionResult;
 use foundry_evm::{
-    executor::backend::DatabaseError,
+    executor::{backend::DatabaseError, inspector::GasOverrides},
     revm,
     revm::{BlockEnv, CfgEnv, Env, Return, SpecId, TransactOut},
     trace::{node::CallTraceNode, CallTraceArena},
@@ -68,6 +70,12 @@ impl ExecutedTransaction {
                 logs_bloom: bloom,
                 logs,
             }),
+            TypedTransaction::EIP7702(_) => TypedReceipt::EIP7702(EIP7702Receipt {
+                status_code,
+                gas_used: used_gas,
+                logs_bloom: bloom,
+                logs,
+            }),
         }
     }
 }
@@ -98,6 +106,8 @@ pub struct TransactionExecutor<'a, Db: ?Sized, Validator: TransactionValidator>
     /// Cumulative gas used by all executed transactions
     pub gas_used: U256,
     pub enable_steps_tracing: bool,
+    /// The custom gas schedule, if any
+    pub gas_overrides: Option<GasOverrides>,
 }
 
 impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'a, DB, Validator> {
@@ -250,8 +260,14 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
         evm.env = env;
         evm.database(&mut self.db);
 
-        // records all call and step traces
-        let mut inspector = Inspector::default().with_tracing();
+        // records all call and step traces, set code transactions delegate the authorities
+        // before the call is executed
+        let authorization_list =
+            transaction.pending_transaction.transaction.authorization_list().to_vec();
+        let mut inspector = Inspector::default()
+            .with_tracing()
+            .with_gas_overrides(self.gas_overrides.clone())
+            .with_authorizations(authorization_list);
         if self.enable_steps_tracing {
             inspector = inspector.with_steps_tracing();
         }
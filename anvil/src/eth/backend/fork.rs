@@ -134,7 +134,7 @@ impl ClientFork {
         self.config.read().chain_id
     }
 
-    fn provider(&self) -> Arc<RetryProvider> {
+    pub fn provider(&self) -> Arc<RetryProvider> {
         self.config.read().provider.clone()
     }
 
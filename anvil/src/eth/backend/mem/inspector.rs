@@ -1,7 +1,7 @@
 //! Anvil specific [`revm::Inspector`] implementation
 
 use crate::{
-    eth::macros::node_info,
+    eth::{backend::mem::simulate::SimulationTracer, macros::node_info},
     revm::{CreateInputs, Database, Interpreter},
 };
 use bytes::Bytes;
@@ -9,7 +9,10 @@ use ethers::types::{Address, Log, H256};
 use foundry_evm::{
     call_inspectors,
     decode::decode_console_logs,
-    executor::inspector::{LogCollector, Tracer},
+    executor::{
+        delegation::{apply_authorizations, resolve_delegation, SignedAuthorization},
+        inspector::{GasOverrides, LogCollector, Tracer},
+    },
     revm,
     revm::{CallInputs, EVMData, Gas, GasInspector, Return},
 };
@@ -22,6 +25,12 @@ pub struct Inspector {
     pub tracer: Option<Tracer>,
     /// collects all `console.sol` logs
     pub logs: LogCollector,
+    /// applies the custom gas schedule, if any
+    pub gas_overrides: Option<GasOverrides>,
+    /// collects the logs of `eth_simulateV1` calls
+    pub simulation: Option<SimulationTracer>,
+    /// the authorizations of a set code transaction, applied when the transaction is executed
+    pub authorizations: Option<Vec<SignedAuthorization>>,
 }
 
 // === impl Inspector ===
@@ -40,6 +49,26 @@ impl Inspector {
         self
     }
 
+    /// Applies the custom gas schedule to all executions
+    pub fn with_gas_overrides(mut self, gas_overrides: Option<GasOverrides>) -> Self {
+        self.gas_overrides = gas_overrides;
+        self
+    }
+
+    /// Collects the logs for `eth_simulateV1`, optionally including ether transfers
+    pub fn with_simulation(mut self, trace_transfers: bool) -> Self {
+        self.simulation = Some(SimulationTracer::new(trace_transfers));
+        self
+    }
+
+    /// Applies the authorizations of a set code transaction before its call is executed
+    pub fn with_authorizations(mut self, authorization_list: Vec<SignedAuthorization>) -> Self {
+        if !authorization_list.is_empty() {
+            self.authorizations = Some(authorization_list);
+        }
+        self
+    }
+
     /// Enables steps recording for `Tracer` and attaches `GasInspector` to it
     /// If `Tracer` wasn't configured before, configures it automatically
     pub fn with_steps_tracing(mut self) -> Self {
@@ -63,7 +92,11 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
     ) -> Return {
         call_inspectors!(
             inspector,
-            [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
+            [
+                &mut self.gas_overrides,
+                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
+                &mut self.tracer
+            ],
             { inspector.initialize_interp(interp, data, is_static) }
         );
         Return::Continue
@@ -75,6 +108,13 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         data: &mut EVMData<'_, DB>,
         is_static: bool,
     ) -> Return {
+        if let Some(gas_overrides) = &mut self.gas_overrides {
+            // disabled opcodes halt the execution
+            let status = gas_overrides.step(interp, data, is_static);
+            if status != Return::Continue {
+                return status
+            }
+        }
         call_inspectors!(
             inspector,
             [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
@@ -97,7 +137,8 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
             [
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.tracer,
-                Some(&mut self.logs)
+                Some(&mut self.logs),
+                &mut self.simulation
             ],
             {
                 inspector.log(evm_data, address, topics, data);
@@ -112,6 +153,13 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         is_static: bool,
         eval: Return,
     ) -> Return {
+        if let Some(gas_overrides) = &mut self.gas_overrides {
+            // running out of gas due to a more expensive opcode halts the execution
+            let status = gas_overrides.step_end(interp, data, is_static, eval);
+            if status != Return::Continue {
+                return status
+            }
+        }
         call_inspectors!(
             inspector,
             [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
@@ -128,18 +176,30 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
         call: &mut CallInputs,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        // the authorizations are applied once, when the call of the transaction is entered
+        if data.journaled_state.depth() == 0 {
+            if let Some(authorization_list) = self.authorizations.take() {
+                apply_authorizations(data, call, &authorization_list);
+            }
+        }
+
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.tracer,
-                Some(&mut self.logs)
+                Some(&mut self.logs),
+                &mut self.simulation
             ],
             {
                 inspector.call(data, call, is_static);
             }
         );
 
+        // calls to accounts with an EIP-7702 delegation run the code of the delegate
+        resolve_delegation(data, call);
+
         (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -154,7 +214,12 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
     ) -> (Return, Gas, Bytes) {
         call_inspectors!(
             inspector,
-            [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
+            [
+                &mut self.gas_overrides,
+                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
+                &mut self.tracer,
+                &mut self.simulation
+            ],
             {
                 inspector.call_end(data, inputs, remaining_gas, ret, out.clone(), is_static);
             }
@@ -169,7 +234,12 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
     ) -> (Return, Option<Address>, Gas, Bytes) {
         call_inspectors!(
             inspector,
-            [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
+            [
+                &mut self.gas_overrides,
+                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
+                &mut self.tracer,
+                &mut self.simulation
+            ],
             {
                 inspector.create(data, call);
             }
@@ -189,7 +259,12 @@ impl<DB: Database> revm::Inspector<DB> for Inspector {
     ) -> (Return, Option<Address>, Gas, Bytes) {
         call_inspectors!(
             inspector,
-            [&mut self.gas.as_deref().map(|gas| gas.borrow_mut()), &mut self.tracer],
+            [
+                &mut self.gas_overrides,
+                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
+                &mut self.tracer,
+                &mut self.simulation
+            ],
             {
                 inspector.create_end(data, inputs, status, address, gas, retdata.clone());
             }
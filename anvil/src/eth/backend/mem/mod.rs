@@ -9,7 +9,7 @@ use crate::{
             fork::ClientFork,
             genesis::GenesisConfig,
             mem::storage::MinedTransactionReceipt,
-            notifications::{NewBlockNotification, NewBlockNotifications},
+            notifications::{NewBlockNotification, NewBlockNotifications, RemovedBlock},
             time::{utc_from_secs, TimeManager},
             validate::TransactionValidator,
         },
@@ -19,6 +19,7 @@ use crate::{
         pool::transactions::PoolTransaction,
         util::get_precompiles_for,
     },
+    genesis::Genesis,
     mem::{
         inspector::Inspector,
         storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome},
@@ -27,9 +28,12 @@ use crate::{
 };
 use anvil_core::{
     eth::{
-        block::{Block, BlockInfo, Header},
+        block::{Block, BlockInfo, Header, PartialHeader},
         proof::{AccountProof, BasicAccount, StorageProof},
         receipt::{EIP658Receipt, TypedReceipt},
+        simulate::{
+            SimulateBlock, SimulateCallError, SimulatePayload, SimulatedBlock, SimulatedCall,
+        },
         state::StateOverride,
         transaction::{
             EthTransactionRequest, MaybeImpersonatedTransaction, PendingTransaction,
@@ -53,15 +57,19 @@ use ethers::{
 };
 use forge::{
     executor::inspector::AccessListTracer,
-    revm::{return_ok, BlockEnv, ExecutionResult, Return},
+    revm::{return_ok, return_revert, BlockEnv, ExecutionResult, Return},
 };
 use foundry_evm::{
     decode::decode_revert,
-    executor::backend::{DatabaseError, DatabaseResult},
+    executor::{
+        backend::{DatabaseError, DatabaseResult},
+        delegation::PER_AUTHORIZATION_GAS,
+        inspector::GasOverrides,
+    },
     revm,
     revm::{
-        db::CacheDB, Account, CreateScheme, Env, SpecId, TransactOut, TransactTo, TxEnv,
-        KECCAK_EMPTY,
+        db::CacheDB, Account, CreateScheme, DatabaseCommit, Env, SpecId, TransactOut, TransactTo,
+        TxEnv, KECCAK_EMPTY,
     },
     utils::u256_to_h256_be,
 };
@@ -70,7 +78,7 @@ use hash_db::HashDB;
 use parking_lot::{Mutex, RwLock};
 use std::{collections::HashMap, ops::Deref, sync::Arc, time::Duration};
 use storage::{Blockchain, MinedTransaction};
-use tokio::sync::RwLock as AsyncRwLock;
+use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
 use tracing::{trace, warn};
 use trie_db::{Recorder, Trie};
 
@@ -78,6 +86,7 @@ pub mod cache;
 pub mod fork_db;
 pub mod in_memory_db;
 pub mod inspector;
+pub mod simulate;
 pub mod state;
 pub mod storage;
 
@@ -142,13 +151,23 @@ pub struct Backend {
     genesis: GenesisConfig,
     /// listeners for new blocks that get notified when a new block was imported
     new_block_listeners: Arc<Mutex<Vec<UnboundedSender<NewBlockNotification>>>>,
+    /// blocks that were removed by a reorg, reported with the next new block notification
+    removed_blocks: Arc<Mutex<Vec<RemovedBlock>>>,
     /// keeps track of active snapshots at a specific block
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, H256)>>>,
+    /// held while a block is mined, so the blocks of multiple nodes of a cluster, or of
+    /// concurrent `evm_mine` calls, are mined one after another
+    mining: Arc<AsyncMutex<()>>,
     enable_steps_tracing: bool,
+    /// The custom gas schedule, if any
+    gas_overrides: Option<GasOverrides>,
     /// How to keep history state
     prune_state_history_config: PruneStateHistoryConfig,
     /// max number of blocks with transactions in memory
     transaction_block_keeper: Option<usize>,
+    /// The chain id that is reported to clients instead of the actual one, see
+    /// [ProxyConfig](crate::eth::proxy::ProxyConfig)
+    spoofed_chain_id: Option<U256>,
 }
 
 impl Backend {
@@ -161,6 +180,7 @@ impl Backend {
         fees: FeeManager,
         fork: Option<ClientFork>,
         enable_steps_tracing: bool,
+        gas_overrides: Option<GasOverrides>,
         prune_state_history_config: PruneStateHistoryConfig,
         transaction_block_keeper: Option<usize>,
         automine_block_time: Option<Duration>,
@@ -200,12 +220,16 @@ impl Backend {
             time: TimeManager::new(start_timestamp),
             cheats: Default::default(),
             new_block_listeners: Default::default(),
+            removed_blocks: Default::default(),
             fees,
             genesis,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
+            mining: Default::default(),
             enable_steps_tracing,
+            gas_overrides,
             prune_state_history_config,
             transaction_block_keeper,
+            spoofed_chain_id: None,
         };
 
         if let Some(interval_block_time) = automine_block_time {
@@ -217,6 +241,11 @@ impl Backend {
         backend
     }
 
+    /// Accepts transactions signed for the spoofed chain id in addition to the actual chain id
+    pub fn set_spoofed_chain_id(&mut self, chain_id: Option<u64>) {
+        self.spoofed_chain_id = chain_id.map(Into::into);
+    }
+
     /// Updates memory limits that should be more strict when auto-mine is enabled
     pub(crate) fn update_interval_mine_block_time(&self, block_time: Duration) {
         self.states.write().update_interval_mine_block_time(block_time)
@@ -599,6 +628,80 @@ impl Backend {
         Ok(self.db.write().await.revert(id))
     }
 
+    /// Rolls the chain back by `depth` blocks and returns the removed blocks, oldest first
+    ///
+    /// The state is restored from the state history of the new best block, so this fails if the
+    /// state history is disabled or no longer contains the block.
+    ///
+    /// The removed blocks are reported to all listeners with the next new block notification.
+    pub async fn rollback(&self, depth: u64) -> Result<Vec<RemovedBlock>, BlockchainError> {
+        let best_number = self.best_number().as_u64();
+        let lowest_number =
+            self.get_fork().map(|fork| fork.block_number()).unwrap_or_default();
+        if depth == 0 || depth > best_number.saturating_sub(lowest_number) {
+            return Err(RpcError::invalid_params(format!(
+                "Reorg depth must be between 1 and {}",
+                best_number.saturating_sub(lowest_number)
+            ))
+            .into())
+        }
+        let target_number = best_number - depth;
+        let target_hash = self
+            .blockchain
+            .storage
+            .read()
+            .hashes
+            .get(&target_number.into())
+            .copied()
+            .ok_or(BlockchainError::BlockNotFound)?;
+
+        let snapshot = self.states.write().snapshot(&target_hash).ok_or_else(|| {
+            BlockchainError::RpcError(RpcError::invalid_params(format!(
+                "State of block {target_number} is not available, reorgs require state history"
+            )))
+        })?;
+        {
+            let mut db = self.db.write().await;
+            db.clear();
+            db.init_from_snapshot(snapshot);
+        }
+
+        let removed = {
+            let mut storage = self.blockchain.storage.write();
+            let mut states = self.states.write();
+            let mut removed = Vec::with_capacity(depth as usize);
+            for n in (target_number + 1)..=best_number {
+                trace!(target: "backend", "removing block {} in reorg", n);
+                let n: U64 = n.into();
+                if let Some(hash) = storage.hashes.remove(&n) {
+                    states.remove(&hash);
+                    if let Some(block) = storage.blocks.remove(&hash) {
+                        let receipts = block
+                            .transactions
+                            .iter()
+                            .filter_map(|tx| storage.transactions.remove(&tx.hash()))
+                            .map(|tx| tx.receipt)
+                            .collect();
+                        removed.push(RemovedBlock { block, receipts });
+                    }
+                }
+            }
+            storage.best_number = target_number.into();
+            storage.best_hash = target_hash;
+            removed
+        };
+
+        // start the replacement branch at the timestamp of the first removed block, this ensures
+        // the new blocks differ from the removed ones
+        if let Some(first) = removed.first() {
+            self.time.reset(first.block.header.timestamp);
+        }
+        self.set_block_number(target_number.into());
+        self.removed_blocks.lock().extend(removed.iter().cloned());
+
+        Ok(removed)
+    }
+
     /// Get the current state.
     pub async fn serialized_state(&self) -> Result<SerializableState, BlockchainError> {
         let state = self.db.read().await.dump_state()?;
@@ -608,6 +711,13 @@ impl Backend {
         })
     }
 
+    /// Returns the current state and chain settings as a `genesis.json`
+    pub async fn genesis(&self) -> Result<Genesis, BlockchainError> {
+        let state = self.serialized_state().await?;
+        let block = self.get_block(self.best_hash()).ok_or(BlockchainError::BlockNotFound)?;
+        Ok(Genesis::from_state(state, &block.header, self.chain_id().as_u64(), self.spec_id()))
+    }
+
     /// Write all chain data to serialized bytes buffer
     pub async fn dump_state(&self) -> Result<Bytes, BlockchainError> {
         let state = self.serialized_state().await?;
@@ -648,7 +758,7 @@ impl Backend {
         let mut env = self.next_env();
         env.tx = tx.pending_transaction.to_revm_tx_env();
         let db = self.db.read().await;
-        let mut inspector = Inspector::default();
+        let mut inspector = Inspector::default().with_gas_overrides(self.gas_overrides.clone());
 
         let mut evm = revm::EVM::new();
         evm.env = env;
@@ -674,11 +784,44 @@ impl Backend {
         pool_transactions: Vec<Arc<PoolTransaction>>,
         f: F,
     ) -> T
+    where
+        F: FnOnce(Box<dyn MaybeHashDatabase + '_>, BlockInfo) -> T,
+    {
+        self.with_block_env(self.next_env(), pool_transactions, f).await
+    }
+
+    /// Builds a block with the given transactions, in the given order, on top of the current state
+    ///
+    /// The `timestamp` and `coinbase` replace the values of the next block. This will not mine the
+    /// block.
+    pub async fn build_block(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+        timestamp: Option<u64>,
+        coinbase: Option<Address>,
+    ) -> BlockInfo {
+        let mut env = self.next_env();
+        if let Some(timestamp) = timestamp {
+            env.block.timestamp = timestamp.into();
+        }
+        if let Some(coinbase) = coinbase {
+            env.block.coinbase = coinbase;
+        }
+        self.with_block_env(env, pool_transactions, |_, block| block).await
+    }
+
+    /// Executes the transactions in a block with the given `env` without writing to the underlying
+    /// database
+    async fn with_block_env<F, T>(
+        &self,
+        env: Env,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+        f: F,
+    ) -> T
     where
         F: FnOnce(Box<dyn MaybeHashDatabase + '_>, BlockInfo) -> T,
     {
         let db = self.db.read().await;
-        let env = self.next_env();
 
         let mut cache_db = CacheDB::new(&*db);
 
@@ -693,6 +836,7 @@ impl Backend {
             parent_hash: storage.best_hash,
             gas_used: U256::zero(),
             enable_steps_tracing: self.enable_steps_tracing,
+            gas_overrides: self.gas_overrides.clone(),
         };
 
         // create a new pending block
@@ -708,14 +852,27 @@ impl Backend {
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
     ) -> MinedBlockOutcome {
-        self.do_mine_block(pool_transactions).await
+        self.do_mine_block(pool_transactions, None).await
+    }
+
+    /// Mines a new block with the given `coinbase` and stores it, see [Self::mine_block]
+    ///
+    /// The `coinbase` only applies to this block.
+    pub async fn mine_block_with_coinbase(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+        coinbase: Address,
+    ) -> MinedBlockOutcome {
+        self.do_mine_block(pool_transactions, Some(coinbase)).await
     }
 
     async fn do_mine_block(
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
+        coinbase: Option<Address>,
     ) -> MinedBlockOutcome {
         trace!(target: "backend", "creating new block with {} transactions", pool_transactions.len());
+        let _mining = self.mining.lock().await;
 
         let (outcome, header, block_hash) = {
             let current_base_fee = self.base_fee();
@@ -725,6 +882,8 @@ impl Backend {
             env.block.number = env.block.number.saturating_add(U256::one());
             env.block.basefee = current_base_fee;
             env.block.timestamp = self.time.next_timestamp().into();
+            let configured_coinbase =
+                coinbase.map(|coinbase| std::mem::replace(&mut env.block.coinbase, coinbase));
 
             let best_hash = self.blockchain.storage.read().best_hash;
 
@@ -745,6 +904,7 @@ impl Backend {
                     parent_hash: best_hash,
                     gas_used: U256::zero(),
                     enable_steps_tracing: self.enable_steps_tracing,
+                    gas_overrides: self.gas_overrides.clone(),
                 };
                 let executed_tx = executor.execute();
 
@@ -849,6 +1009,11 @@ impl Backend {
             // we intentionally set the difficulty to `0` for newer blocks
             env.block.difficulty = U256::zero();
 
+            // the coinbase of this block does not replace the configured one
+            if let Some(coinbase) = configured_coinbase {
+                env.block.coinbase = coinbase;
+            }
+
             // update env with new values
             *self.env.write() = env;
 
@@ -903,6 +1068,173 @@ impl Backend {
         }).await?
     }
 
+    /// Simulates the calls of multiple blocks on top of the given block without writing to the DB
+    ///
+    /// Every block and every call is executed on top of the state of the previous one.
+    pub async fn simulate(
+        &self,
+        request: SimulatePayload,
+        block_request: Option<BlockRequest>,
+    ) -> Result<Vec<SimulatedBlock>, BlockchainError> {
+        self.with_database_at(block_request, |state, mut block_env| {
+            let SimulatePayload { block_state_calls, trace_transfers, validation, .. } = request;
+            let mut parent_hash = self
+                .get_block(block_env.number.as_u64())
+                .map(|block| block.header.hash())
+                .unwrap_or_default();
+            let mut cache_db = CacheDB::new(state);
+
+            let mut blocks = Vec::with_capacity(block_state_calls.len());
+            for SimulateBlock { block_overrides, state_overrides, calls } in block_state_calls {
+                let parent = block_env;
+                block_env = simulate::next_block_env(&parent, block_overrides);
+                if block_env.number <= parent.number {
+                    return Err(BlockchainError::SimulationError(format!(
+                        "block number {} is not greater than the number of its parent {}",
+                        block_env.number, parent.number
+                    )))
+                }
+                if block_env.timestamp <= parent.timestamp {
+                    return Err(BlockchainError::SimulationError(format!(
+                        "block timestamp {} is not greater than the timestamp of its parent {}",
+                        block_env.timestamp, parent.timestamp
+                    )))
+                }
+
+                if let Some(overrides) = state_overrides {
+                    state::apply_state_override_to(overrides, &mut cache_db)?;
+                }
+
+                let mut gas_used = 0u64;
+                let mut results = Vec::with_capacity(calls.len());
+                for request in calls {
+                    let fees = FeeDetails::new(
+                        request.gas_price,
+                        request.max_fee_per_gas,
+                        request.max_priority_fee_per_gas,
+                    )?;
+                    let (fees, request) = if validation {
+                        let nonce = match request.nonce {
+                            Some(nonce) => nonce,
+                            None => {
+                                let from = request.from.unwrap_or_default();
+                                cache_db.basic(from)?.unwrap_or_default().nonce.into()
+                            }
+                        };
+                        (fees, EthTransactionRequest { nonce: Some(nonce), ..request })
+                    } else {
+                        (fees.or_zero_fees(), request)
+                    };
+
+                    let mut env = self.build_call_env(request, fees, block_env.clone());
+                    if validation {
+                        // fees are validated against the base fee of the block
+                        env.block.basefee = block_env.basefee;
+                        env.cfg.disable_block_gas_limit = false;
+                    }
+
+                    let mut inspector = Inspector::default()
+                        .with_gas_overrides(self.gas_overrides.clone())
+                        .with_simulation(trace_transfers);
+                    let (exit, out, call_gas_used, changes) = {
+                        let mut evm = revm::EVM::new();
+                        evm.env = env;
+                        evm.database(&cache_db);
+                        let (ExecutionResult { exit_reason, out, gas_used, .. }, changes) =
+                            evm.inspect_ref(&mut inspector);
+                        (exit_reason, out, gas_used, changes)
+                    };
+                    inspector.print_logs();
+                    cache_db.commit(changes);
+                    gas_used += call_gas_used;
+
+                    let return_data = match out {
+                        TransactOut::None => Bytes::default(),
+                        TransactOut::Call(out) | TransactOut::Create(out, _) => {
+                            out.to_vec().into()
+                        }
+                    };
+                    let (logs, error) = match exit {
+                        return_ok!() => (
+                            inspector.simulation.map(|tracer| tracer.into_logs()).unwrap_or_default(),
+                            None,
+                        ),
+                        return_revert!() => (
+                            vec![],
+                            Some(SimulateCallError {
+                                code: SimulateCallError::REVERTED,
+                                message: "execution reverted".to_string(),
+                                data: Some(return_data.clone()),
+                            }),
+                        ),
+                        reason => (
+                            vec![],
+                            Some(SimulateCallError {
+                                code: SimulateCallError::HALTED,
+                                message: format!("{reason:?}"),
+                                data: None,
+                            }),
+                        ),
+                    };
+                    results.push(SimulatedCall {
+                        return_data,
+                        logs,
+                        gas_used: call_gas_used.into(),
+                        status: u64::from(error.is_none()).into(),
+                        error,
+                    });
+                }
+
+                if validation && U256::from(gas_used) > block_env.gas_limit {
+                    return Err(BlockchainError::SimulationError(format!(
+                        "block {} uses {gas_used} gas, exceeding its gas limit {}",
+                        block_env.number, block_env.gas_limit
+                    )))
+                }
+
+                let partial_header = PartialHeader {
+                    parent_hash,
+                    beneficiary: block_env.coinbase,
+                    logs_bloom: simulate::logs_bloom(
+                        results.iter().flat_map(|result| result.logs.iter()),
+                    ),
+                    difficulty: block_env.difficulty,
+                    number: block_env.number,
+                    gas_limit: block_env.gas_limit,
+                    gas_used: gas_used.into(),
+                    timestamp: block_env.timestamp.as_u64(),
+                    mix_hash: block_env.prevrandao.unwrap_or_default(),
+                    base_fee: Some(block_env.basefee),
+                    ..Default::default()
+                };
+                let block = self.convert_block(Block::new::<MaybeImpersonatedTransaction>(
+                    partial_header,
+                    vec![],
+                    vec![],
+                ));
+                let block_hash = block.hash.unwrap_or_default();
+                parent_hash = block_hash;
+
+                // assign the positions of the logs within the block
+                let mut log_index = 0u64;
+                for (transaction_index, result) in results.iter_mut().enumerate() {
+                    for log in &mut result.logs {
+                        log.block_hash = Some(block_hash);
+                        log.block_number = Some(block_env.number.as_u64().into());
+                        log.transaction_index = Some((transaction_index as u64).into());
+                        log.log_index = Some(log_index.into());
+                        log_index += 1;
+                    }
+                }
+
+                blocks.push(SimulatedBlock { inner: block, calls: results });
+            }
+
+            Ok(blocks)
+        })
+        .await?
+    }
+
     fn build_call_env(
         &self,
         request: EthTransactionRequest,
@@ -955,7 +1287,10 @@ impl Backend {
     where
         D: DatabaseRef<Error = DatabaseError>,
     {
-        let mut inspector = Inspector::default();
+        let authorization_list = request.authorization_list.clone().unwrap_or_default();
+        let mut inspector = Inspector::default()
+            .with_gas_overrides(self.gas_overrides.clone())
+            .with_authorizations(authorization_list);
         let mut evm = revm::EVM::new();
         evm.env = self.build_call_env(request, fee_details, block_env);
         evm.database(state);
@@ -973,7 +1308,11 @@ impl Backend {
         opts: GethDebugTracingOptions,
     ) -> Result<DefaultFrame, BlockchainError> {
         self.with_database_at(block_request, |state, block| {
-            let mut inspector = Inspector::default().with_steps_tracing();
+            let authorization_list = request.authorization_list.clone().unwrap_or_default();
+            let mut inspector = Inspector::default()
+                .with_steps_tracing()
+                .with_gas_overrides(self.gas_overrides.clone())
+                .with_authorizations(authorization_list);
             let block_number = block.number;
             let mut evm = revm::EVM::new();
             evm.env = self.build_call_env(request, fee_details, block);
@@ -1292,6 +1631,22 @@ impl Backend {
         self.blockchain.storage.read().blocks.get(&hash).cloned()
     }
 
+    /// Returns the sender and nonce of every transaction of the block
+    ///
+    /// The senders are taken from the stored transaction info, since the signatures of
+    /// impersonated transactions don't recover to their sender.
+    pub(crate) fn mined_transaction_senders(&self, block: &Block) -> Vec<(Address, U256)> {
+        let storage = self.blockchain.storage.read();
+        block
+            .transactions
+            .iter()
+            .filter_map(|tx| {
+                let info = &storage.transactions.get(&tx.hash())?.info;
+                Some((info.from, *tx.nonce()))
+            })
+            .collect()
+    }
+
     fn mined_block_by_number(&self, number: BlockNumber) -> Option<EthersBlock<TxHash>> {
         Some(self.convert_block(self.get_block(number)?))
     }
@@ -1728,6 +2083,12 @@ impl Backend {
                 .unwrap_or(self.base_fee())
                 .checked_add(t.max_priority_fee_per_gas)
                 .unwrap_or_else(U256::max_value),
+            TypedTransaction::EIP7702(t) => block
+                .header
+                .base_fee_per_gas
+                .unwrap_or(self.base_fee())
+                .checked_add(t.max_priority_fee_per_gas)
+                .unwrap_or_else(U256::max_value),
         };
 
         let inner = TransactionReceipt {
@@ -1952,7 +2313,9 @@ impl Backend {
         // sender half for the set
         self.new_block_listeners.lock().retain(|tx| !tx.is_closed());
 
-        let notification = NewBlockNotification { hash, header: Arc::new(header) };
+        let removed = std::mem::take(&mut *self.removed_blocks.lock());
+        let notification =
+            NewBlockNotification { hash, header: Arc::new(header), removed: Arc::new(removed) };
 
         self.new_block_listeners
             .lock()
@@ -2004,7 +2367,9 @@ impl TransactionValidator for Backend {
 
         if let Some(tx_chain_id) = tx.chain_id() {
             let chain_id = self.chain_id();
-            if chain_id != tx_chain_id.into() {
+            if chain_id != tx_chain_id.into() &&
+                self.spoofed_chain_id != Some(tx_chain_id.into())
+            {
                 if let Some(legacy) = tx.as_legacy() {
                     // <https://github.com/ethereum/EIPs/blob/master/EIPS/eip-155.md>
                     if env.cfg.spec_id >= SpecId::SPURIOUS_DRAGON &&
@@ -2020,7 +2385,9 @@ impl TransactionValidator for Backend {
             }
         }
 
-        if tx.gas_limit() < MIN_TRANSACTION_GAS {
+        // set code transactions are charged the intrinsic gas of their authorizations
+        let authorization_gas = PER_AUTHORIZATION_GAS * tx.authorization_list().len() as u64;
+        if tx.gas_limit() < MIN_TRANSACTION_GAS + authorization_gas {
             warn!(target: "backend", "[{:?}] gas too low", tx.hash());
             return Err(InvalidTransactionError::GasTooLow)
         }
@@ -2095,7 +2462,7 @@ pub fn transaction_build(
 ) -> Transaction {
     let mut transaction: Transaction = eth_transaction.clone().into();
 
-    if let TypedTransaction::EIP1559(_) = eth_transaction.as_ref() {
+    if let TypedTransaction::EIP1559(_) | TypedTransaction::EIP7702(_) = eth_transaction.as_ref() {
         if block.is_none() && info.is_none() {
             // transaction is not mined yet, gas price is considered just `max_fee_per_gas`
             transaction.gas_price = transaction.max_fee_per_gas;
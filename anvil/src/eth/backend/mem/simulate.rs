@@ -0,0 +1,217 @@
+//! Support for `eth_simulateV1`
+
+use crate::revm::{CallInputs, CreateInputs, Database, EVMData, Gas, Return};
+use anvil_core::eth::simulate::{BlockOverrides, TRANSFER_LOG_ADDRESS};
+use bytes::Bytes;
+use ethers::{
+    types::{Address, Bloom, BloomInput, Log, H256, U256},
+    utils::keccak256,
+};
+use foundry_evm::revm::{self, return_ok, BlockEnv};
+
+/// Collects the logs of a simulated call in the order they are emitted, optionally including a
+/// log for every ether transfer
+///
+/// Logs of reverted call frames are discarded.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationTracer {
+    /// Whether to add logs for ether transfers
+    trace_transfers: bool,
+    logs: Vec<Log>,
+    /// The number of logs before each ongoing call frame, innermost last
+    frames: Vec<usize>,
+}
+
+// === impl SimulationTracer ===
+
+impl SimulationTracer {
+    pub fn new(trace_transfers: bool) -> Self {
+        Self { trace_transfers, ..Default::default() }
+    }
+
+    /// Returns all collected logs
+    pub fn into_logs(self) -> Vec<Log> {
+        self.logs
+    }
+
+    /// Adds an ERC20 like `Transfer(address,address,uint256)` log, emitted by
+    /// [TRANSFER_LOG_ADDRESS]
+    fn push_transfer(&mut self, from: Address, to: Address, value: U256) {
+        let mut data = [0u8; 32];
+        value.to_big_endian(&mut data);
+        self.logs.push(Log {
+            address: TRANSFER_LOG_ADDRESS,
+            topics: vec![
+                H256::from(keccak256("Transfer(address,address,uint256)")),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: data.to_vec().into(),
+            ..Default::default()
+        });
+    }
+
+    /// Discards all logs of the call frame that ended if it was not successful
+    fn on_frame_end(&mut self, status: Return) -> Option<usize> {
+        let start = self.frames.pop()?;
+        if !matches!(status, return_ok!()) {
+            self.logs.truncate(start);
+            return None
+        }
+        Some(start)
+    }
+}
+
+impl<DB: Database> revm::Inspector<DB> for SimulationTracer {
+    fn log(&mut self, _: &mut EVMData<'_, DB>, address: &Address, topics: &[H256], data: &Bytes) {
+        self.logs.push(Log {
+            address: *address,
+            topics: topics.to_vec(),
+            data: data.to_vec().into(),
+            ..Default::default()
+        });
+    }
+
+    fn call(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.frames.push(self.logs.len());
+        if self.trace_transfers && !call.transfer.value.is_zero() {
+            self.push_transfer(call.transfer.source, call.transfer.target, call.transfer.value);
+        }
+
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        _: &CallInputs,
+        remaining_gas: Gas,
+        ret: Return,
+        out: Bytes,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.on_frame_end(ret);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.frames.push(self.logs.len());
+        if self.trace_transfers && !call.value.is_zero() {
+            // the address of the created contract is set once it is known
+            self.push_transfer(call.caller, Address::zero(), call.value);
+        }
+
+        (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        inputs: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        if let Some(start) = self.on_frame_end(status) {
+            if self.trace_transfers && !inputs.value.is_zero() {
+                self.logs[start].topics[2] = H256::from(address.unwrap_or_default());
+            }
+        }
+        (status, address, gas, retdata)
+    }
+}
+
+/// Returns the environment of the simulated block that follows the given one, with the overrides
+/// applied
+///
+/// Unless overridden, the number is incremented by one and the timestamp by 12 seconds.
+pub fn next_block_env(parent: &BlockEnv, overrides: Option<BlockOverrides>) -> BlockEnv {
+    let mut block = parent.clone();
+    block.number = parent.number.saturating_add(U256::one());
+    block.timestamp = parent.timestamp.saturating_add(12u64.into());
+
+    if let Some(overrides) = overrides {
+        let BlockOverrides {
+            number,
+            time,
+            gas_limit,
+            fee_recipient,
+            prev_randao,
+            base_fee_per_gas,
+        } = overrides;
+        if let Some(number) = number {
+            block.number = number;
+        }
+        if let Some(time) = time {
+            block.timestamp = time.as_u64().into();
+        }
+        if let Some(gas_limit) = gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(fee_recipient) = fee_recipient {
+            block.coinbase = fee_recipient;
+        }
+        if let Some(prev_randao) = prev_randao {
+            block.prevrandao = Some(prev_randao);
+        }
+        if let Some(base_fee) = base_fee_per_gas {
+            block.basefee = base_fee;
+        }
+    }
+    block
+}
+
+/// Returns the bloom filter of the logs
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::default();
+    for log in logs {
+        bloom.accrue(BloomInput::Raw(&log.address[..]));
+        for topic in &log.topics {
+            bloom.accrue(BloomInput::Raw(&topic[..]));
+        }
+    }
+    bloom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_apply_block_overrides() {
+        let parent = BlockEnv {
+            number: 10u64.into(),
+            timestamp: 100u64.into(),
+            gas_limit: 30_000_000u64.into(),
+            ..Default::default()
+        };
+
+        let block = next_block_env(&parent, None);
+        assert_eq!(block.number, 11u64.into());
+        assert_eq!(block.timestamp, 112u64.into());
+        assert_eq!(block.gas_limit, parent.gas_limit);
+
+        let block = next_block_env(
+            &parent,
+            Some(BlockOverrides {
+                number: Some(20u64.into()),
+                time: Some(200u64.into()),
+                base_fee_per_gas: Some(7u64.into()),
+                ..Default::default()
+            }),
+        );
+        assert_eq!(block.number, 20u64.into());
+        assert_eq!(block.timestamp, 200u64.into());
+        assert_eq!(block.basefee, 7u64.into());
+    }
+}
@@ -118,6 +118,18 @@ where
     D: DatabaseRef<Error = DatabaseError>,
 {
     let mut cache_db = CacheDB::new(state);
+    apply_state_override_to(overrides, &mut cache_db)?;
+    Ok(cache_db)
+}
+
+/// Applies the given state overrides to an existing CacheDB state
+pub fn apply_state_override_to<D>(
+    overrides: StateOverride,
+    cache_db: &mut CacheDB<D>,
+) -> Result<(), BlockchainError>
+where
+    D: DatabaseRef<Error = DatabaseError>,
+{
     for (account, account_overrides) in overrides.iter() {
         let mut account_info = cache_db.basic(*account)?.unwrap_or_default();
 
@@ -163,5 +175,5 @@ where
             }
         };
     }
-    Ok(cache_db)
+    Ok(())
 }
@@ -16,6 +16,7 @@ use ethers::{
     types::{ActionType, Bytes, GethDebugTracingOptions, TransactionReceipt, U256},
 };
 use forge::revm::{Env, Return};
+use foundry_evm::executor::backend::snapshot::StateSnapshot;
 use parking_lot::RwLock;
 use std::{
     collections::{HashMap, VecDeque},
@@ -164,6 +165,32 @@ impl InMemoryBlockStates {
         })
     }
 
+    /// Returns a copy of the state for the given `hash` as [StateSnapshot], if present
+    ///
+    /// The state itself is kept, so it can be restored again.
+    pub fn snapshot(&mut self, hash: &H256) -> Option<StateSnapshot> {
+        if let Some(state) = self.states.get_mut(hash) {
+            let snapshot = state.clear_into_snapshot();
+            state.init_from_snapshot(snapshot.clone());
+            return Some(snapshot)
+        }
+        if self.on_disk_states.contains_key(hash) {
+            return self.disk_cache.read(*hash)
+        }
+        None
+    }
+
+    /// Removes the state for the given `hash`
+    pub fn remove(&mut self, hash: &H256) {
+        if self.states.remove(hash).is_some() {
+            self.present.retain(|present| present != hash);
+        }
+        if self.on_disk_states.remove(hash).is_some() {
+            self.oldest_on_disk.retain(|on_disk| on_disk != hash);
+            self.disk_cache.remove(*hash);
+        }
+    }
+
     /// Sets the maximum number of stats we keep in memory
     pub fn set_cache_limit(&mut self, limit: usize) {
         self.in_memory_limit = limit;
@@ -1,6 +1,9 @@
 //! Notifications emitted from the backed
 
-use anvil_core::eth::block::Header;
+use anvil_core::eth::{
+    block::{Block, Header},
+    receipt::TypedReceipt,
+};
 use ethers::types::H256;
 use futures::channel::mpsc::UnboundedReceiver;
 use std::sync::Arc;
@@ -12,6 +15,15 @@ pub struct NewBlockNotification {
     pub hash: H256,
     /// block header
     pub header: Arc<Header>,
+    /// The blocks this block replaced in a reorg, oldest first
+    pub removed: Arc<Vec<RemovedBlock>>,
+}
+
+/// A block that was removed from the chain by a reorg
+#[derive(Clone, Debug)]
+pub struct RemovedBlock {
+    pub block: Block,
+    pub receipts: Vec<TypedReceipt>,
 }
 
 /// Type alias for a receiver that receives [NewBlockNotification]
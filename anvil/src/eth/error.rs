@@ -9,7 +9,7 @@ use ethers::{
     abi::AbiDecode,
     providers::ProviderError,
     signers::WalletError,
-    types::{Bytes, SignatureError, U256},
+    types::{Bytes, SignatureError, H256, U256},
 };
 use foundry_common::SELECTOR_LEN;
 use foundry_evm::{executor::backend::DatabaseError, revm::Return};
@@ -60,6 +60,8 @@ pub enum BlockchainError {
     BlockOutOfRange(u64, u64),
     #[error("Resource not found")]
     BlockNotFound,
+    #[error("Transaction {0:?} not found in the transaction pool")]
+    TransactionNotFound(H256),
     #[error("Required data unavailable")]
     DataUnavailable,
     #[error("Trie error: {0}")]
@@ -70,6 +72,10 @@ pub enum BlockchainError {
     StateOverrideError(String),
     #[error("Timestamp error: {0}")]
     TimestampError(String),
+    #[error("Simulation error: {0}")]
+    SimulationError(String),
+    #[error("Cluster error: {0}")]
+    ClusterError(String),
     #[error(transparent)]
     DatabaseError(#[from] DatabaseError),
     #[error("EIP-1559 style fee params (maxFeePerGas or maxPriorityFeePerGas) received but they are not supported by the current hardfork.\n\nYou can use them by running anvil with '--hardfork london' or later.")]
@@ -274,6 +280,9 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                     message: err.to_string().into(),
                     data: None,
                 },
+                err @ BlockchainError::TransactionNotFound(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 err @ BlockchainError::DataUnavailable => {
                     RpcError::internal_error_with(err.to_string())
                 }
@@ -287,6 +296,12 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::TimestampError(_) => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ BlockchainError::SimulationError(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
+                err @ BlockchainError::ClusterError(_) => {
+                    RpcError::invalid_params(err.to_string())
+                }
                 BlockchainError::DatabaseError(err) => {
                     RpcError::internal_error_with(err.to_string())
                 }
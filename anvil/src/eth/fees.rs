@@ -247,6 +247,10 @@ impl FeeHistoryService {
                             .max_priority_fee_per_gas
                             .min(t.max_fee_per_gas.saturating_sub(base_fee))
                             .as_u64(),
+                        Some(TypedTransaction::EIP7702(t)) => t
+                            .max_priority_fee_per_gas
+                            .min(t.max_fee_per_gas.saturating_sub(base_fee))
+                            .as_u64(),
                         None => 0,
                     };
 
@@ -9,5 +9,6 @@ pub mod fees;
 pub(crate) mod macros;
 pub mod miner;
 pub mod pool;
+pub mod proxy;
 pub mod sign;
 pub mod util;
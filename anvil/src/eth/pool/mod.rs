@@ -153,7 +153,13 @@ impl Pool {
         trace!(target: "txpool", "Dropping transaction: [{:?}]", tx);
         let removed = {
             let mut pool = self.inner.write();
-            pool.ready_transactions.remove_with_markers(vec![tx], None)
+            let removed = pool.ready_transactions.remove_with_markers(vec![tx], None);
+            if removed.is_empty() {
+                // the transaction may still be waiting for another transaction
+                pool.pending_transactions.remove(vec![tx])
+            } else {
+                removed
+            }
         };
         trace!(target: "txpool", "Dropped transactions: {:?}", removed);
 
@@ -164,6 +170,13 @@ impl Pool {
         dropped
     }
 
+    /// Removes all transactions from the pool
+    pub fn clear(&self) {
+        trace!(target: "txpool", "Dropping all transactions");
+        let mut pool = self.inner.write();
+        pool.clear();
+    }
+
     /// notifies all listeners about the transaction
     fn notify_listener(&self, hash: TxHash) {
         let mut listener = self.transaction_listener.lock();
@@ -221,6 +234,12 @@ impl PoolInner {
         )
     }
 
+    /// Removes all ready and pending transactions
+    fn clear(&mut self) {
+        self.ready_transactions.clear();
+        self.pending_transactions.clear();
+    }
+
     /// Returns true if this pool already contains the transaction
     fn contains(&self, tx_hash: &TxHash) -> bool {
         self.pending_transactions.contains(tx_hash) || self.ready_transactions.contains(tx_hash)
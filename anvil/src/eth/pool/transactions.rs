@@ -135,6 +135,13 @@ impl PendingTransactions {
         self.waiting_queue.is_empty()
     }
 
+    /// Removes all waiting transactions
+    pub fn clear(&mut self) {
+        self.required_markers.clear();
+        self.waiting_markers.clear();
+        self.waiting_queue.clear();
+    }
+
     /// Returns an iterator over all transactions in the waiting pool
     pub fn transactions(&self) -> impl Iterator<Item = Arc<PoolTransaction>> + '_ {
         self.waiting_queue.values().map(|tx| tx.transaction.clone())
@@ -378,6 +385,13 @@ impl ReadyTransactions {
         }
     }
 
+    /// Removes all ready transactions
+    pub fn clear(&mut self) {
+        self.provided_markers.clear();
+        self.ready_tx.write().clear();
+        self.independent_transactions.clear();
+    }
+
     /// Returns true if the transaction is part of the queue.
     pub fn contains(&self, hash: &TxHash) -> bool {
         self.ready_tx.read().contains_key(hash)
@@ -0,0 +1,131 @@
+//! Support for proxying RPC methods that anvil doesn't implement to the fork url
+
+use anvil_core::eth::EthRequest;
+
+/// Unknown methods that may sign, submit or broadcast something on the real chain, see
+/// [ProxyConfig::allow_writes]
+pub const WRITE_METHODS: &[&str] = &[
+    "eth_send*",
+    "eth_sign*",
+    "eth_submit*",
+    "eth_cancel*",
+    "personal_*",
+    "wallet_*",
+    "mev_*",
+    "flashbots_*",
+    "engine_*",
+    "admin_*",
+];
+
+/// Configures which unknown RPC methods are forwarded to the endpoint of the fork
+///
+/// Methods that anvil implements, like state changing and cheat methods, are always served
+/// locally. Unknown methods are forwarded if they match `allow`, or any method if `allow` is empty,
+/// unless they match `deny`. A trailing `*` matches any suffix, e.g. `trace_*`.
+///
+/// Unknown methods that match [WRITE_METHODS], like `eth_sendBundle`, are never forwarded unless
+/// `allow_writes` is set, since they would reach the real chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// The methods that are forwarded, all if empty
+    pub allow: Vec<String>,
+    /// The methods that are never forwarded
+    pub deny: Vec<String>,
+    /// Whether methods that match [WRITE_METHODS] may be forwarded
+    pub allow_writes: bool,
+    /// The chain id that is reported to clients instead of the chain id of the node
+    ///
+    /// Transactions signed for this chain id are accepted as well.
+    pub chain_id: Option<u64>,
+}
+
+// === impl ProxyConfig ===
+
+impl ProxyConfig {
+    /// Returns true if the method is forwarded if anvil doesn't implement it
+    pub fn is_proxied(&self, method: &str) -> bool {
+        let matches = |patterns: &[String]| patterns.iter().any(|p| method_matches(p, method));
+        (self.allow.is_empty() || matches(&self.allow)) &&
+            !matches(&self.deny) &&
+            (self.allow_writes || !is_write_method(method))
+    }
+}
+
+/// Returns true if the method matches [WRITE_METHODS]
+pub fn is_write_method(method: &str) -> bool {
+    WRITE_METHODS.iter().any(|pattern| method_matches(pattern, method))
+}
+
+/// Returns true if the method matches the pattern, which may end with a `*` wildcard
+fn method_matches(pattern: &str, method: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => pattern == method,
+    }
+}
+
+/// Returns true if anvil doesn't implement the method, regardless of the params
+pub fn is_unknown_method(method: &str, params: &serde_json::Value) -> bool {
+    // subscriptions are only known to pubsub connections
+    if matches!(method, "eth_subscribe" | "eth_unsubscribe") {
+        return false
+    }
+    let call = serde_json::json!({ "method": method, "params": params });
+    match serde_json::from_value::<EthRequest>(call) {
+        Ok(_) => false,
+        Err(err) => err.to_string().contains("unknown variant"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_filter_proxied_methods() {
+        let config = ProxyConfig::default();
+        assert!(config.is_proxied("trace_block"));
+
+        let config = ProxyConfig {
+            allow: vec!["trace_*".to_string(), "debug_traceTransaction".to_string()],
+            deny: vec!["trace_replayBlockTransactions".to_string()],
+            allow_writes: false,
+            chain_id: None,
+        };
+        assert!(config.is_proxied("trace_block"));
+        assert!(config.is_proxied("debug_traceTransaction"));
+        assert!(!config.is_proxied("debug_traceCall"));
+        assert!(!config.is_proxied("trace_replayBlockTransactions"));
+    }
+
+    #[test]
+    fn refuses_write_methods_by_default() {
+        let config = ProxyConfig::default();
+        for method in [
+            "eth_sendBundle",
+            "eth_sendPrivateTransaction",
+            "eth_sendRawTransactionConditional",
+            "eth_signTypedData_v4",
+            "personal_sign",
+        ] {
+            assert!(!config.is_proxied(method), "{method}");
+        }
+        assert!(config.is_proxied("eth_callBundle"));
+
+        // an explicit allow list doesn't opt in
+        let config = ProxyConfig { allow: vec!["eth_*".to_string()], ..Default::default() };
+        assert!(!config.is_proxied("eth_sendBundle"));
+
+        let config = ProxyConfig { allow_writes: true, ..Default::default() };
+        assert!(config.is_proxied("eth_sendBundle"));
+    }
+
+    #[test]
+    fn can_detect_unknown_methods() {
+        assert!(is_unknown_method("trace_block", &serde_json::json!(["latest"])));
+        assert!(!is_unknown_method("eth_blockNumber", &serde_json::json!([])));
+        // known methods with invalid params are not forwarded
+        assert!(!is_unknown_method("eth_getBalance", &serde_json::json!(["invalid"])));
+        assert!(!is_unknown_method("eth_subscribe", &serde_json::json!(["newHeads"])));
+    }
+}
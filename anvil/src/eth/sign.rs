@@ -1,7 +1,8 @@
 use crate::eth::error::BlockchainError;
 use anvil_core::eth::transaction::{
     EIP1559Transaction, EIP1559TransactionRequest, EIP2930Transaction, EIP2930TransactionRequest,
-    LegacyTransaction, LegacyTransactionRequest, TypedTransaction, TypedTransactionRequest,
+    EIP7702Transaction, EIP7702TransactionRequest, LegacyTransaction, LegacyTransactionRequest,
+    TypedTransaction, TypedTransactionRequest,
 };
 use ethers::{
     core::k256::ecdsa::SigningKey,
@@ -90,9 +91,14 @@ impl Signer for DevSigner {
         address: &Address,
     ) -> Result<TypedTransaction, BlockchainError> {
         let signer = self.accounts.get(address).ok_or(BlockchainError::NoSignerAvailable)?;
-        let ethers_tx: EthersTypedTransactionRequest = request.clone().into();
-
-        let signature = signer.sign_transaction_sync(&ethers_tx)?;
+        let signature = match &request {
+            // ethers doesn't know set code transactions, the request hash is the signing hash
+            TypedTransactionRequest::EIP7702(tx) => signer.sign_hash(tx.hash())?,
+            _ => {
+                let ethers_tx: EthersTypedTransactionRequest = request.clone().into();
+                signer.sign_transaction_sync(&ethers_tx)?
+            }
+        };
 
         build_typed_transaction(request, signature)
     }
@@ -196,6 +202,46 @@ pub fn build_typed_transaction(
                 },
             })
         }
+        TypedTransactionRequest::EIP7702(tx) => {
+            let EIP7702TransactionRequest {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+                kind,
+                value,
+                input,
+                access_list,
+                authorization_list,
+            } = tx;
+
+            let recid: u8 = signature.recovery_id()?.into();
+
+            TypedTransaction::EIP7702(EIP7702Transaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit,
+                kind,
+                value,
+                input,
+                access_list: access_list.into(),
+                authorization_list,
+                odd_y_parity: recid != 0,
+                r: {
+                    let mut rarr = [0u8; 32];
+                    signature.r.to_big_endian(&mut rarr);
+                    H256::from(rarr)
+                },
+                s: {
+                    let mut sarr = [0u8; 32];
+                    signature.s.to_big_endian(&mut sarr);
+                    H256::from(sarr)
+                },
+            })
+        }
     };
 
     Ok(tx)
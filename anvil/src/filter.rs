@@ -1,7 +1,7 @@
 //! Support for polling based filters
 use crate::{
     eth::{backend::notifications::NewBlockNotifications, error::ToRpcResponseResult},
-    pubsub::filter_logs,
+    pubsub::{filter_logs, removed_logs},
     StorageInfo,
 };
 use anvil_core::eth::subscription::SubscriptionId;
@@ -175,6 +175,7 @@ impl LogsFilter {
     pub fn poll(&mut self, cx: &mut Context<'_>) -> Vec<EthersLog> {
         let mut logs = self.historic.take().unwrap_or_default();
         while let Poll::Ready(Some(block)) = self.blocks.poll_next_unpin(cx) {
+            logs.extend(removed_logs(&block.removed, &self.filter));
             let b = self.storage.block(block.hash);
             let receipts = self.storage.receipts(block.hash);
             if let (Some(receipts), Some(block)) = (receipts, b) {
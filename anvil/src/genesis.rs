@@ -1,10 +1,11 @@
 //! Bindings for geth's `genesis.json` format
-use crate::revm::AccountInfo;
+use crate::{eth::backend::db::SerializableState, revm::AccountInfo, Hardfork};
+use anvil_core::eth::block::Header;
 use ethers::{
     signers::LocalWallet,
     types::{serde_helpers::*, Address, Bytes, H256, U256},
 };
-use forge::revm::{Bytecode, Env, KECCAK_EMPTY};
+use forge::revm::{Bytecode, Env, SpecId, KECCAK_EMPTY};
 use foundry_common::errors::FsPathError;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -97,14 +98,72 @@ impl Genesis {
         if let Some(coinbase) = self.coinbase {
             env.block.coinbase = coinbase;
         }
+        if let Some(mix_hash) = self.mix_hash {
+            env.block.prevrandao = Some(mix_hash);
+        }
         env.block.difficulty = self.difficulty.into();
         env.block.gas_limit = self.gas_limit.into();
     }
 
+    /// Returns the latest hardfork that is active at the genesis block, if there is a config
+    pub fn hardfork(&self) -> Option<Hardfork> {
+        let (number, timestamp) =
+            (self.number.unwrap_or_default(), self.timestamp.unwrap_or_default());
+        Some(self.config.as_ref()?.hardfork_at(number, timestamp))
+    }
+
     /// Returns all private keys from the genesis accounts, if they exist
     pub fn private_keys(&self) -> Vec<LocalWallet> {
         self.alloc.accounts.values().filter_map(|acc| acc.private_key.clone()).collect()
     }
+
+    /// Creates a genesis that starts a chain with the given state, on top of the block with the
+    /// given header
+    ///
+    /// All hardforks up to `spec_id` are active at genesis.
+    pub fn from_state(
+        state: SerializableState,
+        header: &Header,
+        chain_id: u64,
+        spec_id: SpecId,
+    ) -> Self {
+        let accounts = state
+            .accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let storage = account
+                    .storage
+                    .into_iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(slot, value)| (storage::to_word(slot), storage::to_word(value)))
+                    .collect();
+                let account = GenesisAccount {
+                    code: (!account.code.is_empty()).then_some(account.code),
+                    storage,
+                    balance: account.balance,
+                    nonce: (account.nonce > 0).then_some(account.nonce),
+                    private_key: None,
+                };
+                (address, account)
+            })
+            .collect();
+
+        Genesis {
+            config: Some(Config::at_genesis(chain_id, spec_id)),
+            nonce: Some(u64::from_be_bytes(header.nonce.0)),
+            timestamp: Some(header.timestamp),
+            extra_data: Some(header.extra_data.clone()),
+            gas_limit: header.gas_limit.as_u64(),
+            difficulty: header.difficulty.as_u64(),
+            mix_hash: Some(header.mix_hash),
+            coinbase: Some(header.beneficiary),
+            alloc: Alloc { accounts },
+            number: Some(header.number.as_u64()),
+            gas_used: None,
+            parent_hash: None,
+            base_fee_per_gas: header.base_fee_per_gas,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -117,7 +176,11 @@ pub struct Alloc {
 pub struct GenesisAccount {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub code: Option<Bytes>,
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "storage::deserialize"
+    )]
     pub storage: HashMap<H256, H256>,
     #[serde(deserialize_with = "deserialize_stringified_numeric")]
     pub balance: U256,
@@ -155,7 +218,7 @@ impl From<GenesisAccount> for AccountInfo {
 /// that any network, identified by its genesis block, can have its own
 /// set of configuration options.
 /// <(https://github.com/ethereum/go-ethereum/blob/0ce494b60cd00d70f1f9f2dd0b9bfbd76204168a/params/config.go#L342-L387>
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -198,8 +261,17 @@ pub struct Config {
     pub shanghai_block: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub cancun_block: Option<u64>,
+    /// Post merge hardforks are activated by timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shanghai_time: Option<u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub terminal_total_difficulty: Option<u64>,
+    pub cancun_time: Option<u64>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_stringified_numeric_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub terminal_total_difficulty: Option<U256>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub terminal_total_difficulty_passed: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -208,6 +280,80 @@ pub struct Config {
     pub clique: Option<CliqueConfig>,
 }
 
+impl Config {
+    /// Returns the config of a chain on which all hardforks up to `spec_id` are active since
+    /// genesis
+    pub fn at_genesis(chain_id: u64, spec_id: SpecId) -> Self {
+        let active = |fork: SpecId| ((fork as u8) <= (spec_id as u8)).then_some(0);
+        let merged = (SpecId::MERGE as u8) <= (spec_id as u8);
+        Config {
+            chain_id: Some(chain_id),
+            homestead_block: active(SpecId::HOMESTEAD),
+            eip150_block: active(SpecId::TANGERINE),
+            eip155_block: active(SpecId::SPURIOUS_DRAGON),
+            eip158_block: active(SpecId::SPURIOUS_DRAGON),
+            byzantium_block: active(SpecId::BYZANTIUM),
+            constantinople_block: active(SpecId::CONSTANTINOPLE),
+            petersburg_block: active(SpecId::PETERSBURG),
+            istanbul_block: active(SpecId::ISTANBUL),
+            muir_glacier_block: active(SpecId::MUIR_GLACIER),
+            berlin_block: active(SpecId::BERLIN),
+            london_block: active(SpecId::LONDON),
+            // the difficulty bomb delays are only relevant before the merge
+            arrow_glacier_block: active(SpecId::MERGE),
+            gray_glacier_block: active(SpecId::MERGE),
+            merge_netsplit_block: active(SpecId::MERGE),
+            terminal_total_difficulty: merged.then(U256::zero),
+            terminal_total_difficulty_passed: merged.then_some(true),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the latest hardfork that is active at the block with the given number and
+    /// timestamp
+    pub fn hardfork_at(&self, number: u64, timestamp: u64) -> Hardfork {
+        let block = |fork: Option<u64>| fork.map_or(false, |fork| fork <= number);
+        let time = |fork: Option<u64>| fork.map_or(false, |fork| fork <= timestamp);
+        let merged = self.terminal_total_difficulty_passed == Some(true) ||
+            self.terminal_total_difficulty.map_or(false, |ttd| ttd.is_zero()) ||
+            block(self.merge_netsplit_block);
+
+        if merged ||
+            time(self.shanghai_time) ||
+            time(self.cancun_time) ||
+            block(self.gray_glacier_block)
+        {
+            Hardfork::Latest
+        } else if block(self.arrow_glacier_block) {
+            Hardfork::ArrowGlacier
+        } else if block(self.london_block) {
+            Hardfork::London
+        } else if block(self.berlin_block) {
+            Hardfork::Berlin
+        } else if block(self.muir_glacier_block) {
+            Hardfork::Muirglacier
+        } else if block(self.istanbul_block) {
+            Hardfork::Istanbul
+        } else if block(self.petersburg_block) {
+            Hardfork::Petersburg
+        } else if block(self.constantinople_block) {
+            Hardfork::Constantinople
+        } else if block(self.byzantium_block) {
+            Hardfork::Byzantium
+        } else if block(self.eip155_block) || block(self.eip158_block) {
+            Hardfork::SpuriousDragon
+        } else if block(self.eip150_block) {
+            Hardfork::Tangerine
+        } else if block(self.dao_fork_block) {
+            Hardfork::Dao
+        } else if block(self.homestead_block) {
+            Hardfork::Homestead
+        } else {
+            Hardfork::Frontier
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EthashConfig {}
 
@@ -217,6 +363,34 @@ pub struct CliqueConfig {
     pub epoch: u64,
 }
 
+/// serde support for storage in genesis, geth allows slots and values without leading zeros
+mod storage {
+    use ethers::types::{H256, U256};
+    use serde::{de, Deserialize, Deserializer};
+    use std::collections::HashMap;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<H256, H256>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HashMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(slot, value)| Ok((parse_word(&slot)?, parse_word(&value)?)))
+            .collect()
+    }
+
+    pub fn to_word(value: U256) -> H256 {
+        let mut word = H256::zero();
+        value.to_big_endian(word.as_bytes_mut());
+        word
+    }
+
+    fn parse_word<E: de::Error>(word: &str) -> Result<H256, E> {
+        let word = word.strip_prefix("0x").unwrap_or(word);
+        U256::from_str_radix(word, 16).map(to_word).map_err(E::custom)
+    }
+}
+
 /// serde support for `secretKey` in genesis
 
 pub mod secret_key {
@@ -297,5 +471,39 @@ mod tests {
         assert_eq!(gen.alloc.accounts.len(), 1);
         let config = gen.config.unwrap();
         assert_eq!(config.chain_id, Some(19763));
+        assert_eq!(gen.hardfork(), Some(Hardfork::Byzantium));
+    }
+
+    #[test]
+    fn can_parse_geth_storage() {
+        let s = r#"{
+    "storage": {
+        "0x0": "0x01",
+        "0x0000000000000000000000000000000000000000000000000000000000000002": "0xff00"
+    },
+    "balance": "1000"
+}"#;
+        let acc: GenesisAccount = serde_json::from_str(s).unwrap();
+        assert_eq!(acc.balance, 1000u64.into());
+        assert_eq!(acc.storage[&H256::zero()], H256::from_low_u64_be(1));
+        assert_eq!(acc.storage[&H256::from_low_u64_be(2)], H256::from_low_u64_be(0xff00));
+    }
+
+    #[test]
+    fn can_get_hardfork_at() {
+        let config =
+            Config { london_block: Some(10), shanghai_time: Some(100), ..Default::default() };
+        assert_eq!(config.hardfork_at(0, 0), Hardfork::Frontier);
+        assert_eq!(config.hardfork_at(10, 0), Hardfork::London);
+        assert_eq!(config.hardfork_at(10, 100), Hardfork::Latest);
+
+        let config: Config =
+            serde_json::from_str(r#"{"terminalTotalDifficulty": 0, "londonBlock": 0}"#).unwrap();
+        assert_eq!(config.hardfork_at(0, 0), Hardfork::Latest);
+
+        for spec_id in [SpecId::BERLIN, SpecId::LONDON, SpecId::LATEST] {
+            let hardfork = Config::at_genesis(1, spec_id).hardfork_at(0, 0);
+            assert_eq!(SpecId::from(hardfork), spec_id);
+        }
     }
 }
@@ -1,4 +1,5 @@
 use crate::{
+    cluster::{Cluster, ClusterNode, ClusterNodeHandle},
     eth::{
         backend::{info::StorageInfo, mem},
         fees::{FeeHistoryService, FeeManager},
@@ -9,6 +10,7 @@ use crate::{
     },
     filter::Filters,
     logging::{LoggingManager, NodeLogLayer},
+    metrics::NodeMetrics,
     service::NodeService,
     shutdown::Signal,
     tasks::TaskManager,
@@ -50,6 +52,8 @@ use crate::server::{
 };
 pub use hardfork::Hardfork;
 
+/// multiple nodes that share one chain
+pub mod cluster;
 /// ethereum related implementations
 pub mod eth;
 /// support for polling filters
@@ -58,6 +62,8 @@ pub mod filter;
 pub mod genesis;
 /// commandline output
 pub mod logging;
+/// Prometheus metrics and request logging
+pub mod metrics;
 /// types for subscriptions
 pub mod pubsub;
 /// axum RPC server implementations
@@ -90,38 +96,28 @@ pub mod cmd;
 /// handle.await.unwrap();
 /// # }
 /// ```
-pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
+///
+/// # Panics
+///
+/// If the node can't be created, see [try_spawn()]
+pub async fn spawn(config: NodeConfig) -> (EthApi, NodeHandle) {
+    try_spawn(config).await.expect("failed to spawn node")
+}
+
+/// Creates the node and runs the server
+///
+/// Same as [spawn()], but returns an error if the node can't be created, for example if the
+/// request log can't be opened.
+pub async fn try_spawn(mut config: NodeConfig) -> Result<(EthApi, NodeHandle), NodeError> {
     let logger = if config.enable_tracing { init_tracing() } else { Default::default() };
     logger.set_enabled(!config.silent);
 
-    let backend = Arc::new(config.setup().await);
+    let metrics = Arc::new(NodeMetrics::new(config.log_requests.as_deref())?);
+    let backend = Arc::new(config.setup(&metrics).await);
 
     let fork = backend.get_fork().cloned();
 
-    let NodeConfig {
-        signer_accounts,
-        block_time,
-        port,
-        max_transactions,
-        server_config,
-        no_mining,
-        transaction_order,
-        genesis,
-        ..
-    } = config.clone();
-
-    let pool = Arc::new(Pool::default());
-
-    let mode = if let Some(block_time) = block_time {
-        MiningMode::interval(block_time)
-    } else if no_mining {
-        MiningMode::None
-    } else {
-        // get a listener for ready transactions
-        let listener = pool.add_ready_listener();
-        MiningMode::instant(max_transactions, listener)
-    };
-    let miner = Miner::new(mode);
+    let NodeConfig { signer_accounts, port, server_config, genesis, .. } = config.clone();
 
     let dev_signer: Box<dyn EthSigner> = Box::new(DevSigner::new(signer_accounts));
     let mut signers = vec![dev_signer];
@@ -133,64 +129,166 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
             signers.push(genesis_signers);
         }
     }
-
-    let fees = backend.fees().clone();
-    let fee_history_cache = Arc::new(Mutex::new(Default::default()));
-    let fee_history_service = FeeHistoryService::new(
-        backend.new_block_notifications(),
-        Arc::clone(&fee_history_cache),
-        fees,
-        StorageInfo::new(Arc::clone(&backend)),
-    );
-
-    let filters = Filters::default();
+    let signers = Arc::new(signers);
 
     // create the cloneable api wrapper
-    let api = EthApi::new(
-        Arc::clone(&pool),
-        Arc::clone(&backend),
-        Arc::new(signers),
-        fee_history_cache,
-        fee_history_service.fee_history_limit(),
-        miner.clone(),
-        logger,
-        filters.clone(),
-        transaction_order,
-    );
+    let (mut api, node_service) =
+        create_node(&config, &backend, &signers, logger.clone(), &metrics, 0);
+
+    let cluster = config
+        .cluster
+        .filter(|nodes| *nodes > 1)
+        .map(|nodes| Arc::new(Cluster::new(nodes, config.cluster_propagation_delay)));
+    if let Some(ref cluster) = cluster {
+        api = api.with_cluster(ClusterNode::new(0, Arc::clone(cluster)));
+    }
+
+    // track the mined blocks if the metrics are served
+    if config.prometheus_port.is_some() {
+        tokio::task::spawn(metrics::track_mined_blocks(Arc::clone(&metrics), Arc::clone(&backend)));
+    }
 
     // spawn the node service
-    let node_service =
-        tokio::task::spawn(NodeService::new(pool, backend, miner, fee_history_service, filters));
+    let node_service = tokio::task::spawn(node_service);
 
     let host = config.host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
     let mut addr = SocketAddr::new(host, port);
 
     // configure the rpc server and use its actual local address
-    let server = server::serve(addr, api.clone(), server_config);
+    let server = server::serve(addr, api.clone(), server_config.clone());
     addr = server.local_addr();
 
     // spawn the server on a new task
     let serve = tokio::task::spawn(server.map_err(NodeError::from));
 
+    // spawn the other nodes of the cluster, each with its own pool and server, on the next ports
+    let mut cluster_nodes = Vec::new();
+    if let Some(ref cluster) = cluster {
+        cluster.set_address(0, addr);
+        for index in 1..cluster.size() {
+            let (api, node_service) =
+                create_node(&config, &backend, &signers, logger.clone(), &metrics, index);
+            let api = api.with_cluster(ClusterNode::new(index, Arc::clone(cluster)));
+            let node_service = tokio::task::spawn(node_service);
+
+            let port = if port == 0 { 0 } else { port.saturating_add(index as u16) };
+            let server =
+                server::serve(SocketAddr::new(host, port), api.clone(), server_config.clone());
+            let address = server.local_addr();
+            cluster.set_address(index, address);
+            let server = tokio::task::spawn(server.map_err(NodeError::from));
+
+            cluster_nodes.push(ClusterNodeHandle { api, address, node_service, server });
+        }
+
+        let apis = std::iter::once(api.clone())
+            .chain(cluster_nodes.iter().map(|node| node.api.clone()))
+            .collect();
+        cluster::spawn_sync(Arc::clone(cluster), apis);
+    }
+
     let tokio_handle = Handle::current();
     let (signal, on_shutdown) = shutdown::signal();
     let task_manager = TaskManager::new(tokio_handle, on_shutdown);
 
     let ipc_task = config.get_ipc_path().map(|path| spawn_ipc(api.clone(), path));
 
+    // configure the REST control interface, if enabled
+    let mut api_address = None;
+    let api_server = config.api_port.map(|api_port| {
+        let rest = server::rest::serve(
+            SocketAddr::new(host, api_port),
+            api.clone(),
+            config.api_token.clone(),
+        );
+        api_address = Some(rest.local_addr());
+        tokio::task::spawn(rest.map_err(NodeError::from))
+    });
+
+    // configure the prometheus metrics server, if enabled
+    let mut prometheus_address = None;
+    let prometheus_server = config.prometheus_port.map(|prometheus_port| {
+        let server =
+            metrics::serve(SocketAddr::new(host, prometheus_port), Arc::clone(&metrics));
+        prometheus_address = Some(server.local_addr());
+        tokio::task::spawn(server.map_err(NodeError::from))
+    });
+
     let handle = NodeHandle {
         config,
         node_service,
         server: serve,
         ipc_task,
+        api_server,
         address: addr,
+        api_address,
+        prometheus_server,
+        prometheus_address,
+        cluster_nodes,
         _signal: Some(signal),
         task_manager,
     };
 
     handle.print(fork.as_ref());
 
-    (api, handle)
+    Ok((api, handle))
+}
+
+/// Creates the api and the service of a node with its own pool on top of the backend
+///
+/// Only the first node of a cluster mines blocks in interval mining mode, so the cluster produces
+/// one block per `block_time`. The transactions of the other nodes are mined once they reached the
+/// first node.
+fn create_node(
+    config: &NodeConfig,
+    backend: &Arc<mem::Backend>,
+    signers: &Arc<Vec<Box<dyn EthSigner>>>,
+    logger: LoggingManager,
+    metrics: &Arc<NodeMetrics>,
+    index: usize,
+) -> (EthApi, NodeService) {
+    let pool = Arc::new(Pool::default());
+
+    let mode = match config.block_time {
+        Some(block_time) if index == 0 => MiningMode::interval(block_time),
+        Some(_) => MiningMode::None,
+        None if config.no_mining => MiningMode::None,
+        None => {
+            // get a listener for ready transactions
+            let listener = pool.add_ready_listener();
+            MiningMode::instant(config.max_transactions, listener)
+        }
+    };
+    let miner = Miner::new(mode);
+
+    let fees = backend.fees().clone();
+    let fee_history_cache = Arc::new(Mutex::new(Default::default()));
+    let fee_history_service = FeeHistoryService::new(
+        backend.new_block_notifications(),
+        Arc::clone(&fee_history_cache),
+        fees,
+        StorageInfo::new(Arc::clone(backend)),
+    );
+
+    let filters = Filters::default();
+
+    let api = EthApi::new(
+        Arc::clone(&pool),
+        Arc::clone(backend),
+        Arc::clone(signers),
+        fee_history_cache,
+        fee_history_service.fee_history_limit(),
+        miner.clone(),
+        logger,
+        filters.clone(),
+        config.transaction_order,
+        Arc::clone(metrics),
+        config.proxy.clone(),
+    );
+    let node_service =
+        NodeService::new(pool, Arc::clone(backend), miner, fee_history_service, filters);
+
+    (api, node_service)
 }
 
 type IpcTask = JoinHandle<io::Result<()>>;
@@ -208,6 +306,16 @@ pub struct NodeHandle {
     pub server: JoinHandle<Result<(), NodeError>>,
     // The future that joins the ipc server, if any
     ipc_task: Option<IpcTask>,
+    /// Join handle for the REST control server, if any
+    pub api_server: Option<JoinHandle<Result<(), NodeError>>>,
+    /// The address of the running REST control server, if any
+    api_address: Option<SocketAddr>,
+    /// Join handle for the prometheus metrics server, if any
+    pub prometheus_server: Option<JoinHandle<Result<(), NodeError>>>,
+    /// The address of the running prometheus metrics server, if any
+    prometheus_address: Option<SocketAddr>,
+    /// The other nodes of the cluster, if the node is the first node of a cluster
+    cluster_nodes: Vec<ClusterNodeHandle>,
     /// A signal that fires the shutdown, fired on drop.
     _signal: Option<Signal>,
     /// A task manager that can be used to spawn additional tasks
@@ -224,7 +332,16 @@ impl NodeHandle {
     pub(crate) fn print(&self, fork: Option<&ClientFork>) {
         self.config.print(fork);
         if !self.config.silent {
-            println!("Listening on {}", self.socket_address())
+            println!("Listening on {}", self.socket_address());
+            if let Some(api_address) = self.api_address {
+                println!("REST API listening on {api_address}")
+            }
+            if let Some(prometheus_address) = self.prometheus_address {
+                println!("Prometheus metrics listening on {prometheus_address}")
+            }
+            for (index, node) in self.cluster_nodes.iter().enumerate() {
+                println!("Cluster node {} listening on {}", index + 1, node.socket_address())
+            }
         }
     }
 
@@ -241,6 +358,23 @@ impl NodeHandle {
         format!("http://{}", self.socket_address())
     }
 
+    /// Returns the endpoint of the REST control interface, if enabled
+    pub fn api_endpoint(&self) -> Option<String> {
+        self.api_address.map(|addr| format!("http://{addr}"))
+    }
+
+    /// Returns the endpoint of the prometheus metrics, if enabled
+    pub fn prometheus_endpoint(&self) -> Option<String> {
+        self.prometheus_address.map(|addr| format!("http://{addr}/metrics"))
+    }
+
+    /// Returns the other nodes of the cluster, the first node of the cluster is this node
+    ///
+    /// Empty if no cluster was spawned, see [NodeConfig::cluster]
+    pub fn cluster_nodes(&self) -> &[ClusterNodeHandle] {
+        &self.cluster_nodes
+    }
+
     /// Returns the websocket endpoint
     pub fn ws_endpoint(&self) -> String {
         format!("ws://{}", self.socket_address())
@@ -347,11 +481,39 @@ impl Future for NodeHandle {
             }
         }
 
+        // poll the REST control server task
+        if let Some(mut api_server) = pin.api_server.take() {
+            if let Poll::Ready(res) = api_server.poll_unpin(cx) {
+                return Poll::Ready(res)
+            } else {
+                pin.api_server = Some(api_server);
+            }
+        }
+
+        // poll the prometheus metrics server task
+        if let Some(mut prometheus_server) = pin.prometheus_server.take() {
+            if let Poll::Ready(res) = prometheus_server.poll_unpin(cx) {
+                return Poll::Ready(res)
+            } else {
+                pin.prometheus_server = Some(prometheus_server);
+            }
+        }
+
         // poll the node service task
         if let Poll::Ready(res) = pin.node_service.poll_unpin(cx) {
             return Poll::Ready(res)
         }
 
+        // poll the tasks of the other nodes of the cluster
+        for node in pin.cluster_nodes.iter_mut() {
+            if let Poll::Ready(res) = node.node_service.poll_unpin(cx) {
+                return Poll::Ready(res)
+            }
+            if let Poll::Ready(res) = node.server.poll_unpin(cx) {
+                return Poll::Ready(res)
+            }
+        }
+
         pin.server.poll_unpin(cx)
     }
 }
@@ -0,0 +1,294 @@
+//! Prometheus metrics and the JSON request log of the node
+//!
+//! If enabled via `--prometheus`, the metrics are served in the prometheus text format on
+//! `GET /metrics`. If enabled via `--log-requests`, every handled RPC call is appended to a file as
+//! a single line of JSON.
+
+use crate::eth::backend::mem::Backend;
+use anvil_rpc::response::{ResponseResult, RpcResponse};
+use anvil_server::AnvilServer;
+use axum::{
+    extract::Extension, http::header, response::IntoResponse, routing::get, Router, Server,
+};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write as _},
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    net::SocketAddr,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tower_http::trace::TraceLayer;
+
+/// The upper bounds in seconds of the buckets of the response time histogram
+pub const RESPONSE_TIME_BUCKETS: [f64; 12] =
+    [0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0];
+
+/// The method label of all calls of methods the node doesn't know
+pub const UNKNOWN_METHOD: &str = "unknown";
+
+/// Collects the metrics of a running node
+#[derive(Default)]
+pub struct NodeMetrics {
+    /// Metrics of all RPC methods that were called, by method name
+    methods: Mutex<BTreeMap<String, MethodMetrics>>,
+    mined_blocks: AtomicU64,
+    mined_transactions: AtomicU64,
+    /// Requests sent to the fork's upstream endpoint
+    fork_requests: Arc<AtomicU64>,
+    /// The file all RPC calls are logged to, if any
+    request_log: Option<Mutex<File>>,
+}
+
+// === impl NodeMetrics ===
+
+impl NodeMetrics {
+    /// Creates a new instance that also logs all RPC calls to the file at the given path, if any
+    ///
+    /// Calls are appended to the file if it already exists.
+    pub fn new(request_log: Option<&Path>) -> io::Result<Self> {
+        let request_log = request_log
+            .map(|path| OpenOptions::new().create(true).append(true).open(path))
+            .transpose()?
+            .map(Mutex::new);
+        Ok(Self { request_log, ..Default::default() })
+    }
+
+    /// Returns the counter for requests sent to the fork's upstream endpoint
+    pub fn fork_requests(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.fork_requests)
+    }
+
+    /// Records a handled RPC call
+    ///
+    /// Calls of unknown methods are counted under [UNKNOWN_METHOD], so clients can't create an
+    /// unbounded number of series.
+    pub fn record_call(
+        &self,
+        method: &str,
+        is_known_method: bool,
+        params: &serde_json::Value,
+        elapsed: Duration,
+        response: &RpcResponse,
+    ) {
+        let error = match response.result() {
+            ResponseResult::Success(_) => None,
+            ResponseResult::Error(err) => Some(err),
+        };
+        let label = if is_known_method { method } else { UNKNOWN_METHOD };
+        self.methods.lock().entry(label.to_string()).or_default().record(elapsed, error.is_some());
+
+        if let Some(ref log) = self.request_log {
+            let entry = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "method": method,
+                "params": params,
+                "durationMs": elapsed.as_secs_f64() * 1000.0,
+                "success": error.is_none(),
+                "error": error,
+            });
+            let mut log = log.lock();
+            if let Err(err) = writeln!(log, "{entry}") {
+                tracing::warn!(target: "node", ?err, "failed to log request");
+            }
+        }
+    }
+
+    /// Records a mined block with the given number of transactions
+    pub fn record_mined_block(&self, transactions: usize) {
+        self.mined_blocks.fetch_add(1, Ordering::Relaxed);
+        self.mined_transactions.fetch_add(transactions as u64, Ordering::Relaxed);
+    }
+
+    /// Renders all metrics in the prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.write_prometheus(&mut out).expect("writing to a string can't fail");
+        out
+    }
+
+    fn write_prometheus(&self, out: &mut String) -> fmt::Result {
+        let methods = self.methods.lock();
+        let methods: Vec<_> =
+            methods.iter().map(|(method, metrics)| (escape_label(method), metrics)).collect();
+
+        writeln!(out, "# HELP anvil_rpc_requests_total Number of handled RPC calls.")?;
+        writeln!(out, "# TYPE anvil_rpc_requests_total counter")?;
+        for (method, metrics) in methods.iter() {
+            writeln!(out, "anvil_rpc_requests_total{{method=\"{method}\"}} {}", metrics.calls)?;
+        }
+
+        writeln!(out, "# HELP anvil_rpc_errors_total Number of RPC calls that returned an error.")?;
+        writeln!(out, "# TYPE anvil_rpc_errors_total counter")?;
+        for (method, metrics) in methods.iter() {
+            writeln!(out, "anvil_rpc_errors_total{{method=\"{method}\"}} {}", metrics.errors)?;
+        }
+
+        writeln!(
+            out,
+            "# HELP anvil_rpc_request_duration_seconds Time it took to handle RPC calls."
+        )?;
+        writeln!(out, "# TYPE anvil_rpc_request_duration_seconds histogram")?;
+        for (method, metrics) in methods.iter() {
+            for (bucket, count) in RESPONSE_TIME_BUCKETS.iter().zip(metrics.buckets) {
+                writeln!(
+                    out,
+                    "anvil_rpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"{bucket}\"}} {count}"
+                )?;
+            }
+            writeln!(
+                out,
+                "anvil_rpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} {}",
+                metrics.calls
+            )?;
+            writeln!(
+                out,
+                "anvil_rpc_request_duration_seconds_sum{{method=\"{method}\"}} {}",
+                metrics.duration.as_secs_f64()
+            )?;
+            writeln!(
+                out,
+                "anvil_rpc_request_duration_seconds_count{{method=\"{method}\"}} {}",
+                metrics.calls
+            )?;
+        }
+
+        let counters = [
+            ("anvil_mined_blocks_total", "Number of mined blocks.", &self.mined_blocks),
+            (
+                "anvil_mined_transactions_total",
+                "Number of transactions in mined blocks.",
+                &self.mined_transactions,
+            ),
+            (
+                "anvil_fork_requests_total",
+                "Number of requests sent to the upstream endpoint of the fork.",
+                &*self.fork_requests,
+            ),
+        ];
+        for (name, help, counter) in counters {
+            writeln!(out, "# HELP {name} {help}")?;
+            writeln!(out, "# TYPE {name} counter")?;
+            writeln!(out, "{name} {}", counter.load(Ordering::Relaxed))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for NodeMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeMetrics")
+            .field("mined_blocks", &self.mined_blocks)
+            .field("mined_transactions", &self.mined_transactions)
+            .field("fork_requests", &self.fork_requests)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Metrics of a single RPC method
+#[derive(Debug, Clone, Default)]
+struct MethodMetrics {
+    calls: u64,
+    errors: u64,
+    /// Total time it took to handle all calls
+    duration: Duration,
+    /// Cumulative counts of the calls per bucket of [RESPONSE_TIME_BUCKETS]
+    buckets: [u64; RESPONSE_TIME_BUCKETS.len()],
+}
+
+impl MethodMetrics {
+    fn record(&mut self, elapsed: Duration, is_error: bool) {
+        self.calls += 1;
+        self.errors += is_error as u64;
+        self.duration += elapsed;
+        let secs = elapsed.as_secs_f64();
+        for (bucket, count) in RESPONSE_TIME_BUCKETS.iter().zip(self.buckets.iter_mut()) {
+            if secs <= *bucket {
+                *count += 1;
+            }
+        }
+    }
+}
+
+/// Escapes a label value of the prometheus text format
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Records all blocks that are mined by the backend
+pub async fn track_mined_blocks(metrics: Arc<NodeMetrics>, backend: Arc<Backend>) {
+    let mut blocks = backend.new_block_notifications();
+    while let Some(notification) = blocks.next().await {
+        if let Some(block) = backend.get_block_by_hash(notification.hash) {
+            metrics.record_mined_block(block.transactions.len());
+        }
+    }
+}
+
+/// Configures an [axum::Server] that serves the metrics on `GET /metrics`
+pub fn serve(addr: SocketAddr, metrics: Arc<NodeMetrics>) -> AnvilServer {
+    let svc = Router::new()
+        .route("/metrics", get(handle_metrics))
+        .layer(Extension(metrics))
+        .layer(TraceLayer::new_for_http())
+        .into_make_service();
+    Server::bind(&addr).serve(svc)
+}
+
+async fn handle_metrics(Extension(metrics): Extension<Arc<NodeMetrics>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], metrics.render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anvil_rpc::{error::RpcError, request::Id};
+
+    #[test]
+    fn can_render_metrics() {
+        let metrics = NodeMetrics::default();
+        let ok = RpcResponse::new(Id::Number(1), ResponseResult::Success(1u64.into()));
+        let err = RpcResponse::new(Id::Number(2), RpcError::method_not_found());
+        let params = serde_json::Value::Null;
+        metrics.record_call("eth_blockNumber", true, &params, Duration::from_millis(2), &ok);
+        metrics.record_call("eth_blockNumber", true, &params, Duration::from_secs(10), &err);
+        metrics.record_call("foo_bar", false, &params, Duration::from_millis(2), &err);
+        metrics.record_call("foo\"}\n", false, &params, Duration::from_millis(2), &err);
+        metrics.record_call("a\"b\\", true, &params, Duration::from_millis(2), &ok);
+        metrics.record_mined_block(3);
+
+        let out = metrics.render();
+        assert!(out.contains("anvil_rpc_requests_total{method=\"eth_blockNumber\"} 2\n"));
+        assert!(out.contains("anvil_rpc_errors_total{method=\"eth_blockNumber\"} 1\n"));
+        assert!(out.contains(
+            "anvil_rpc_request_duration_seconds_bucket{method=\"eth_blockNumber\",le=\"0.001\"} 0\n"
+        ));
+        assert!(out.contains(
+            "anvil_rpc_request_duration_seconds_bucket{method=\"eth_blockNumber\",le=\"0.0025\"} 1\n"
+        ));
+        assert!(out.contains(
+            "anvil_rpc_request_duration_seconds_bucket{method=\"eth_blockNumber\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(out.contains("anvil_rpc_requests_total{method=\"unknown\"} 2\n"));
+        assert!(!out.contains("foo"));
+        assert!(out.contains("anvil_rpc_requests_total{method=\"a\\\"b\\\\\"} 1\n"));
+        assert!(out.contains("anvil_mined_transactions_total 3\n"));
+        assert!(out.contains("anvil_fork_requests_total 0\n"));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    eth::{backend::notifications::NewBlockNotifications, error::to_rpc_result},
+    eth::{
+        backend::notifications::{NewBlockNotifications, RemovedBlock},
+        error::to_rpc_result,
+    },
     StorageInfo, U256,
 };
 use anvil_core::eth::{
@@ -44,11 +47,12 @@ impl LogsSubscription {
             }
 
             if let Some(block) = ready!(self.blocks.poll_next_unpin(cx)) {
+                self.queued.extend(removed_logs(&block.removed, &self.filter));
                 let b = self.storage.block(block.hash);
                 let receipts = self.storage.receipts(block.hash);
                 if let (Some(receipts), Some(block)) = (receipts, b) {
                     let logs = filter_logs(block, receipts, &self.filter);
-                    if logs.is_empty() {
+                    if logs.is_empty() && self.queued.is_empty() {
                         // this ensures we poll the receiver until it is pending, in which case the
                         // underlying `UnboundedReceiver` will register the new waker, see
                         // [`futures::channel::mpsc::UnboundedReceiver::poll_next()`]
@@ -214,3 +218,18 @@ pub fn filter_logs(
     }
     logs
 }
+
+/// Returns all the logs of the blocks removed by a reorg that match the given filter, marked as
+/// removed
+///
+/// The logs of the most recent block come first.
+pub fn removed_logs(removed: &[RemovedBlock], filter: &FilteredParams) -> Vec<EthersLog> {
+    removed
+        .iter()
+        .rev()
+        .flat_map(|removed| {
+            filter_logs(removed.block.clone(), removed.receipts.clone(), filter).into_iter().rev()
+        })
+        .map(|log| EthersLog { removed: Some(true), ..log })
+        .collect()
+}
@@ -8,9 +8,13 @@ use anvil_core::eth::{
     subscription::{SubscriptionId, SubscriptionKind},
     EthPubSub, EthRequest, EthRpcCall,
 };
-use anvil_rpc::{error::RpcError, response::ResponseResult};
+use anvil_rpc::{
+    error::RpcError,
+    response::{ResponseResult, RpcResponse},
+};
 use anvil_server::{PubSubContext, PubSubRpcHandler, RpcHandler};
 use ethers::types::FilteredParams;
+use std::time::Duration;
 use tracing::trace;
 
 /// A `RpcHandler` that expects `EthRequest` rpc calls via http
@@ -36,6 +40,25 @@ impl RpcHandler for HttpEthRpcHandler {
     async fn on_request(&self, request: Self::Request) -> ResponseResult {
         self.api.execute(request).await
     }
+
+    async fn on_unknown_method(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        self.api.proxy_request(method, params).await
+    }
+
+    fn on_response(
+        &self,
+        method: &str,
+        is_known_method: bool,
+        params: &serde_json::Value,
+        elapsed: Duration,
+        response: &RpcResponse,
+    ) {
+        self.api.metrics().record_call(method, is_known_method, params, elapsed, response)
+    }
 }
 
 /// A `RpcHandler` that expects `EthRequest` rpc calls and `EthPubSub` via pubsub connection
@@ -117,4 +140,23 @@ impl PubSubRpcHandler for PubSubEthRpcHandler {
             EthRpcCall::PubSub(pubsub) => self.on_pub_sub(pubsub, cx).await,
         }
     }
+
+    async fn on_unknown_method(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Option<ResponseResult> {
+        self.api.proxy_request(method, params).await
+    }
+
+    fn on_response(
+        &self,
+        method: &str,
+        is_known_method: bool,
+        params: &serde_json::Value,
+        elapsed: Duration,
+        response: &RpcResponse,
+    ) {
+        self.api.metrics().record_call(method, is_known_method, params, elapsed, response)
+    }
 }
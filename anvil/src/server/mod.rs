@@ -10,6 +10,8 @@ use tracing::trace;
 mod handler;
 
 pub mod error;
+/// REST interface for node control operations
+pub mod rest;
 
 /// Configures an [axum::Server] that handles [EthApi] related JSON-RPC calls via HTTP and WS
 pub fn serve(addr: SocketAddr, api: EthApi, config: ServerConfig) -> AnvilServer {
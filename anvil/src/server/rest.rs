@@ -0,0 +1,205 @@
+//! A small REST interface for node control operations
+//!
+//! This exposes a subset of the `anvil_*`/`evm_*` JSON-RPC methods as plain HTTP endpoints, so
+//! that test orchestrators can manage the node without crafting JSON-RPC bodies:
+//!
+//! | Method | Path                 | Body                                  |
+//! |--------|----------------------|---------------------------------------|
+//! | `POST` | `/mine`              | `{ "blocks": 1, "interval": 0 }`      |
+//! | `POST` | `/snapshot`          |                                       |
+//! | `POST` | `/revert/:id`        |                                       |
+//! | `POST` | `/balance/:address`  | `{ "balance": "0x..." }`              |
+//! | `GET`  | `/state`             |                                       |
+//! | `POST` | `/state`             | `{ "state": "0x..." }`                |
+//! | `GET`  | `/metrics`           |                                       |
+//!
+//! If a token is configured, every request must carry an `Authorization: Bearer <token>` header.
+
+use crate::{eth::error::ToRpcResponseResult, EthApi};
+use anvil_rpc::{error::RpcError, response::ResponseResult};
+use anvil_server::AnvilServer;
+use axum::{
+    extract::{Extension, Path},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router, Server,
+};
+use ethers::types::{Address, Bytes, U256};
+use serde::Deserialize;
+use std::{net::SocketAddr, sync::Arc};
+use tower_http::trace::TraceLayer;
+
+/// Shared state of all REST handlers
+#[derive(Clone)]
+struct RestState {
+    api: EthApi,
+    token: Option<Arc<String>>,
+}
+
+impl RestState {
+    /// Returns an error response if the request is not authorized
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), Response> {
+        let token = match self.token.as_ref() {
+            Some(token) => token,
+            None => return Ok(()),
+        };
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+            .unwrap_or_default();
+        if authorized {
+            Ok(())
+        } else {
+            Err((StatusCode::UNAUTHORIZED, Json(serde_json::json!({ "error": "unauthorized" })))
+                .into_response())
+        }
+    }
+}
+
+/// Compares the tokens in constant time, so the token can't be guessed from response times
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false
+    }
+    a.iter().zip(b).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Rejects unauthorized requests before any handler extracts their body
+async fn authorize<B>(req: Request<B>, next: Next<B>) -> Response {
+    let state = req.extensions().get::<RestState>().expect("state is configured");
+    if let Err(resp) = state.authorize(req.headers()) {
+        return resp
+    }
+    next.run(req).await
+}
+
+/// Configures an [axum::Server] that serves the REST control interface for the given [EthApi]
+pub fn serve(addr: SocketAddr, api: EthApi, token: Option<String>) -> AnvilServer {
+    let state = RestState { api, token: token.map(Arc::new) };
+    let svc = Router::new()
+        .route("/mine", post(mine))
+        .route("/snapshot", post(snapshot))
+        .route("/revert/:id", post(revert))
+        .route("/balance/:address", post(set_balance))
+        .route("/state", get(dump_state).post(load_state))
+        .route("/metrics", get(metrics))
+        .route_layer(middleware::from_fn(authorize))
+        .layer(Extension(state))
+        .layer(TraceLayer::new_for_http())
+        .into_make_service();
+    Server::bind(&addr).serve(svc)
+}
+
+/// Converts the result of an rpc call into an http response
+fn into_response(result: ResponseResult) -> Response {
+    match result {
+        ResponseResult::Success(value) => (StatusCode::OK, Json(value)).into_response(),
+        ResponseResult::Error(err) => (StatusCode::BAD_REQUEST, Json(err)).into_response(),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct MineRequest {
+    /// Number of blocks to mine, defaults to `1`
+    blocks: Option<u64>,
+    /// Interval in seconds between the timestamps of the mined blocks
+    interval: Option<u64>,
+}
+
+async fn mine(
+    Extension(state): Extension<RestState>,
+    body: Option<Json<MineRequest>>,
+) -> Response {
+    let MineRequest { blocks, interval } = body.map(|Json(body)| body).unwrap_or_default();
+    into_response(
+        state
+            .api
+            .anvil_mine(blocks.map(U256::from), interval.map(U256::from))
+            .await
+            .to_rpc_result(),
+    )
+}
+
+async fn snapshot(Extension(state): Extension<RestState>) -> Response {
+    into_response(state.api.evm_snapshot().await.to_rpc_result())
+}
+
+async fn revert(
+    Extension(state): Extension<RestState>,
+    Path(id): Path<String>,
+) -> Response {
+    let id = match id.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(&id).ok(),
+    };
+    let id = match id {
+        Some(id) => id,
+        None => return into_response(RpcError::invalid_params("invalid snapshot id").into()),
+    };
+    into_response(state.api.evm_revert(id).await.to_rpc_result())
+}
+
+#[derive(Debug, Deserialize)]
+struct BalanceRequest {
+    balance: U256,
+}
+
+async fn set_balance(
+    Extension(state): Extension<RestState>,
+    Path(address): Path<Address>,
+    Json(body): Json<BalanceRequest>,
+) -> Response {
+    into_response(state.api.anvil_set_balance(address, body.balance).await.to_rpc_result())
+}
+
+async fn dump_state(Extension(state): Extension<RestState>) -> Response {
+    into_response(state.api.anvil_dump_state().await.to_rpc_result())
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadStateRequest {
+    state: Bytes,
+}
+
+async fn load_state(
+    Extension(state): Extension<RestState>,
+    Json(body): Json<LoadStateRequest>,
+) -> Response {
+    into_response(state.api.anvil_load_state(body.state).await.to_rpc_result())
+}
+
+async fn metrics(Extension(state): Extension<RestState>) -> Response {
+    let node_info = match state.api.anvil_node_info().await {
+        Ok(info) => info,
+        Err(err) => return into_response(Err::<(), _>(err).to_rpc_result()),
+    };
+    let txpool = match state.api.txpool_status().await {
+        Ok(status) => status,
+        Err(err) => return into_response(Err::<(), _>(err).to_rpc_result()),
+    };
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "node": node_info,
+            "txpool": txpool,
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_compare_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+}
@@ -68,3 +68,62 @@ async fn test_can_use_default_genesis_timestamp() {
 
     assert_ne!(0u64, provider.get_block(0).await.unwrap().unwrap().timestamp.as_u64());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_record_metrics_and_log_requests() {
+    let tmp = tempfile::tempdir().unwrap();
+    let log = tmp.path().join("requests.jsonl");
+    let config =
+        NodeConfig::test().with_prometheus_port(Some(0)).with_log_requests(Some(log.clone()));
+    let (api, handle) = spawn(config).await;
+    assert!(handle.prometheus_endpoint().is_some());
+    let provider = handle.http_provider();
+
+    let num = provider.get_block_number().await.unwrap();
+    assert_eq!(num.as_u64(), 0);
+    api.evm_mine(None).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let metrics = api.metrics().render();
+    assert!(metrics.contains("anvil_rpc_requests_total{method=\"eth_blockNumber\"} 1\n"));
+    assert!(metrics.contains("anvil_mined_blocks_total 1\n"));
+
+    let entries = std::fs::read_to_string(&log).unwrap();
+    let entry: serde_json::Value = serde_json::from_str(entries.lines().next().unwrap()).unwrap();
+    assert_eq!(entry["method"], "eth_blockNumber");
+    assert_eq!(entry["success"], true);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_authorize_rest_requests() {
+    use hyper::{header, Body, Client, Method, Request, StatusCode};
+
+    let config =
+        NodeConfig::test().with_api_port(Some(0)).with_api_token(Some("secret".to_string()));
+    let (api, handle) = spawn(config).await;
+    let endpoint = handle.api_endpoint().unwrap();
+    let client = Client::new();
+    let address = Address::random();
+
+    let request = |token: Option<&str>, body: &'static str| {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{endpoint}/balance/{address:?}"))
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        request.body(Body::from(body)).unwrap()
+    };
+
+    // the token is checked before the body is parsed
+    let resp = client.request(request(None, "not json")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = client.request(request(Some("wrong"), r#"{"balance":"0x1"}"#)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(api.balance(address, None).await.unwrap(), 0u64.into());
+
+    let resp = client.request(request(Some("secret"), r#"{"balance":"0x1"}"#)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(api.balance(address, None).await.unwrap(), 1u64.into());
+}
@@ -2,8 +2,8 @@
 use crate::{abi::*, fork::fork_config};
 use anvil::{spawn, Hardfork, NodeConfig};
 use anvil_core::{
-    eth::EthRequest,
-    types::{NodeEnvironment, NodeForkConfig, NodeInfo},
+    eth::{transaction::EthTransactionRequest, EthRequest},
+    types::{BuildBlockRequest, BuildBlockTransaction, NodeEnvironment, NodeForkConfig, NodeInfo},
 };
 use ethers::{
     abi::{ethereum_types::BigEndianHash, AbiDecode},
@@ -12,7 +12,7 @@ use ethers::{
         transaction::eip2718::TypedTransaction, Address, BlockNumber, Eip1559TransactionRequest,
         TransactionRequest, H256, U256, U64,
     },
-    utils::hex,
+    utils::{get_contract_address, hex},
 };
 use forge::revm::SpecId;
 use std::{
@@ -141,6 +141,102 @@ async fn can_impersonate_contract() {
     assert_eq!("Hello World!", greeting);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_impersonate_and_call_from_contract() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+
+    let greeter_contract = Greeter::deploy(Arc::clone(&client), "Hello World!".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    let sender = greeter_contract.address();
+    api.anvil_set_balance(sender, U256::from(1e18 as u64)).await.unwrap();
+
+    // contracts start with nonce 1
+    let nonce = provider.get_transaction_count(sender, None).await.unwrap();
+    assert_eq!(nonce, 1u64.into());
+
+    let deploy_tx = Greeter::deploy(client, "Hello Sender!".to_string()).unwrap().deployer.tx;
+    let request = EthTransactionRequest {
+        from: Some(sender),
+        data: deploy_tx.data().cloned(),
+        ..Default::default()
+    };
+    let receipt = api.anvil_impersonate_and_call(request).await.unwrap();
+    assert_eq!(receipt.from, sender);
+    assert_eq!(receipt.contract_address, Some(get_contract_address(sender, nonce)));
+
+    let nonce = provider.get_transaction_count(sender, None).await.unwrap();
+    assert_eq!(nonce, 2u64.into());
+
+    // the sender was not added to the impersonated accounts
+    let tx = TransactionRequest::new().from(sender).to(Address::random()).value(1337u64);
+    provider.send_transaction(tx, None).await.unwrap_err();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn impersonate_and_call_mines_without_automine() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let sender = Address::random();
+    api.anvil_set_balance(sender, U256::from(1e18 as u64)).await.unwrap();
+    let block_number = provider.get_block_number().await.unwrap();
+
+    let to = Address::random();
+    let request = EthTransactionRequest {
+        from: Some(sender),
+        to: Some(to),
+        value: Some(1337u64.into()),
+        ..Default::default()
+    };
+    let receipt = api.anvil_impersonate_and_call(request.clone()).await.unwrap();
+    assert_eq!(receipt.block_number, Some(block_number + 1u64));
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+
+    // the transaction is executed with the current nonce
+    let request = EthTransactionRequest { nonce: Some(5u64.into()), ..request };
+    api.anvil_impersonate_and_call(request).await.unwrap_err();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_deploy_from_impersonated_contract() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+
+    let greeter_contract = Greeter::deploy(Arc::clone(&client), "Hello World!".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+    let impersonate = greeter_contract.address();
+    api.anvil_set_balance(impersonate, U256::from(1e18 as u64)).await.unwrap();
+    api.anvil_impersonate_account(impersonate).await.unwrap();
+
+    let mut deploy_tx = Greeter::deploy(client, "Hello Sender!".to_string()).unwrap().deployer.tx;
+    deploy_tx.set_from(impersonate);
+
+    for nonce in 1u64..3 {
+        let pending = provider.send_transaction(deploy_tx.clone(), None).await.unwrap();
+        let receipt = pending.await.unwrap().unwrap();
+        assert_eq!(receipt.from, impersonate);
+        assert_eq!(receipt.contract_address, Some(get_contract_address(impersonate, nonce)));
+
+        let tx = provider.get_transaction(receipt.transaction_hash).await.unwrap().unwrap();
+        assert_eq!(tx.from, impersonate);
+        assert_eq!(tx.to, None);
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_impersonate_gnosis_safe() {
     let (api, handle) = spawn(fork_config()).await;
@@ -442,3 +538,62 @@ async fn test_get_transaction_receipt() {
         new_receipt.unwrap().effective_gas_price.unwrap().as_u64()
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_build_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    let tx = TransactionRequest::new().to(accounts[1]).from(accounts[0]).value(1337u64);
+    let tx_hash = *provider.send_transaction(tx, None).await.unwrap();
+
+    let coinbase = Address::random();
+    let request = BuildBlockRequest {
+        transactions: vec![BuildBlockTransaction::Hash(tx_hash)],
+        coinbase: Some(coinbase),
+        ..Default::default()
+    };
+
+    // building the block leaves the chain unchanged
+    let block = api.anvil_build_block(request.clone()).await.unwrap();
+    assert_eq!(block.number, Some(1u64.into()));
+    assert_eq!(block.author, Some(coinbase));
+    assert_eq!(block.transactions[0].hash, tx_hash);
+    assert_eq!(provider.get_block_number().await.unwrap(), 0u64.into());
+    assert_eq!(provider.txpool_status().await.unwrap().pending, 1u64.into());
+
+    let block = api.anvil_build_block(BuildBlockRequest { commit: true, ..request }).await.unwrap();
+    assert_eq!(block.author, Some(coinbase));
+    assert_eq!(provider.get_block_number().await.unwrap(), 1u64.into());
+    assert_eq!(provider.txpool_status().await.unwrap().pending, 0u64.into());
+    let receipt = provider.get_transaction_receipt(tx_hash).await.unwrap().unwrap();
+    assert_eq!(receipt.block_number, Some(1u64.into()));
+
+    // the coinbase only applies to the built block
+    api.mine_one().await;
+    let latest = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_ne!(latest.author, Some(coinbase));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reorg_twice_to_same_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+
+    for _ in 0..2 {
+        let tx = TransactionRequest::new().to(to).from(from).value(1337u64);
+        provider.send_transaction(tx, None).await.unwrap().await.unwrap();
+    }
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 2674u64.into());
+
+    // both reorgs replace block 2 and restore the state of block 1
+    for _ in 0..2 {
+        api.anvil_reorg(1u64.into(), 1u64.into(), None).await.unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), 2u64.into());
+        assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+    }
+}
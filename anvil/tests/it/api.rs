@@ -4,7 +4,11 @@ use anvil::{
     eth::{api::CLIENT_VERSION, EthApi},
     spawn, NodeConfig, CHAIN_ID,
 };
-use anvil_core::eth::{state::AccountOverride, transaction::EthTransactionRequest};
+use anvil_core::eth::{
+    simulate::{BlockOverrides, SimulateBlock, SimulatePayload, TRANSFER_LOG_ADDRESS},
+    state::AccountOverride,
+    transaction::EthTransactionRequest,
+};
 use ethers::{
     abi::{Address, Tokenizable},
     prelude::{builders::ContractCall, decode_function_data, Middleware, SignerMiddleware},
@@ -333,3 +337,76 @@ async fn can_call_with_state_override() {
     // `value` *is* changed with state
     assert_eq!(value, "");
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_simulate_v1() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let sender = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+    let multicall =
+        MulticallContract::deploy(Arc::clone(&client), ()).unwrap().send().await.unwrap();
+
+    let recipient = Address::random();
+    let value: U256 = 1000u64.into();
+    let balance_call = multicall.get_eth_balance(recipient);
+
+    let payload = SimulatePayload {
+        block_state_calls: vec![
+            SimulateBlock {
+                block_overrides: Some(BlockOverrides {
+                    number: Some(100u64.into()),
+                    ..Default::default()
+                }),
+                calls: vec![EthTransactionRequest {
+                    from: Some(sender),
+                    to: Some(recipient),
+                    value: Some(value),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            SimulateBlock {
+                calls: vec![EthTransactionRequest {
+                    from: Some(sender),
+                    to: Some(multicall.address()),
+                    data: balance_call.tx.data().cloned(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        ],
+        trace_transfers: true,
+        ..Default::default()
+    };
+    let blocks = api.simulate_v1(payload, None).await.unwrap();
+    assert_eq!(blocks.len(), 2);
+
+    let first = &blocks[0];
+    assert_eq!(first.inner.number, Some(100u64.into()));
+    assert_eq!(first.calls[0].status, 1u64.into());
+    // the ether transfer is traced as a log
+    assert_eq!(first.calls[0].logs.len(), 1);
+    let transfer = &first.calls[0].logs[0];
+    assert_eq!(transfer.address, TRANSFER_LOG_ADDRESS);
+    assert_eq!(transfer.topics[1], H256::from(sender));
+    assert_eq!(transfer.topics[2], H256::from(recipient));
+    assert_eq!(transfer.block_hash, first.inner.hash);
+
+    // the second block is simulated on top of the first one
+    let second = &blocks[1];
+    assert_eq!(second.inner.number, Some(101u64.into()));
+    assert_eq!(second.inner.parent_hash, first.inner.hash.unwrap());
+    let balance: U256 = decode_function_data(
+        &balance_call.function,
+        second.calls[0].return_data.as_ref(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(balance, value);
+
+    // nothing was written to the chain
+    assert_eq!(client.get_balance(recipient, None).await.unwrap(), U256::zero());
+}
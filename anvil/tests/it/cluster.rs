@@ -0,0 +1,97 @@
+//! tests for clusters of nodes, see `anvil --cluster`
+
+use anvil::{spawn, NodeConfig};
+use ethers::{
+    prelude::Middleware,
+    types::{Address, TransactionRequest, U256},
+};
+use std::time::Duration;
+
+/// Waits for the propagation of transactions between the nodes
+async fn propagate() {
+    tokio::time::sleep(Duration::from_millis(200)).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_propagate_transactions_in_cluster() {
+    let config = NodeConfig::test().with_no_mining(true).with_cluster(Some(3));
+    let (api, handle) = spawn(config).await;
+    let nodes = handle.cluster_nodes();
+    assert_eq!(nodes.len(), 2);
+
+    let info = api.anvil_cluster_info().await.unwrap();
+    assert_eq!(info.node, 0);
+    assert_eq!(info.nodes.len(), 3);
+    assert_eq!(info.nodes[2].endpoint, Some(nodes[1].http_endpoint()));
+
+    let providers = [handle.http_provider(), nodes[0].http_provider(), nodes[1].http_provider()];
+    let accounts = providers[0].get_accounts().await.unwrap();
+
+    // a transaction sent to the first node reaches all nodes
+    let tx = TransactionRequest::new().to(accounts[2]).from(accounts[0]).value(1u64);
+    providers[0].send_transaction(tx, None).await.unwrap();
+    propagate().await;
+    for provider in providers.iter() {
+        assert_eq!(provider.txpool_status().await.unwrap().pending.as_u64(), 1);
+    }
+
+    // a transaction only reaches the nodes of the same partition
+    api.anvil_partition_cluster(vec![vec![0], vec![1, 2]]).await.unwrap();
+    let tx = TransactionRequest::new().to(accounts[2]).from(accounts[1]).value(1u64);
+    providers[1].send_transaction(tx, None).await.unwrap();
+    propagate().await;
+    assert_eq!(providers[0].txpool_status().await.unwrap().pending.as_u64(), 1);
+    assert_eq!(providers[2].txpool_status().await.unwrap().pending.as_u64(), 2);
+
+    // healing propagates the mempools again
+    api.anvil_heal_cluster().await.unwrap();
+    propagate().await;
+    assert_eq!(providers[0].txpool_status().await.unwrap().pending.as_u64(), 2);
+
+    // a block mined by any node is part of the chain of all nodes and pruned from all mempools
+    nodes[1].api.evm_mine(None).await.unwrap();
+    propagate().await;
+    for provider in providers.iter() {
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+        assert_eq!(provider.txpool_status().await.unwrap().pending.as_u64(), 0);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_prune_impersonated_transactions_in_cluster() {
+    let config = NodeConfig::test().with_no_mining(true).with_cluster(Some(2));
+    let (api, handle) = spawn(config).await;
+    let nodes = handle.cluster_nodes();
+    let providers = [handle.http_provider(), nodes[0].http_provider()];
+    let accounts = providers[0].get_accounts().await.unwrap();
+
+    let impersonate = Address::random();
+    api.anvil_set_balance(impersonate, U256::from(1e18 as u64)).await.unwrap();
+    api.anvil_impersonate_account(impersonate).await.unwrap();
+
+    // the second transaction depends on the first one
+    for _ in 0..2 {
+        let tx = TransactionRequest::new().to(accounts[0]).from(impersonate).value(1u64);
+        providers[0].send_transaction(tx, None).await.unwrap();
+    }
+    propagate().await;
+    for provider in providers.iter() {
+        assert_eq!(provider.txpool_status().await.unwrap().pending.as_u64(), 2);
+    }
+
+    // the transactions are pruned from the pool of the node that didn't mine them
+    nodes[0].api.evm_mine(None).await.unwrap();
+    propagate().await;
+    for provider in providers.iter() {
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+        assert_eq!(provider.txpool_status().await.unwrap().pending.as_u64(), 0);
+        assert_eq!(provider.get_transaction_count(impersonate, None).await.unwrap().as_u64(), 2);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_cluster_requests_without_cluster() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    assert!(api.anvil_cluster_info().await.is_err());
+    assert!(api.anvil_partition_cluster(vec![vec![0]]).await.is_err());
+}
@@ -1,7 +1,10 @@
 //! various fork related test
 
 use crate::{abi::*, utils};
-use anvil::{eth::EthApi, spawn, NodeConfig, NodeHandle};
+use anvil::{
+    eth::{proxy::ProxyConfig, EthApi},
+    spawn, NodeConfig, NodeHandle,
+};
 use anvil_core::{eth::transaction::EthTransactionRequest, types::Forking};
 use ethers::{
     core::rand,
@@ -860,3 +863,42 @@ async fn can_override_fork_chain_id() {
     let chain_id = provider.get_chainid().await.unwrap();
     assert_eq!(chain_id.as_u64(), chain_id_override);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_proxy_unknown_methods() {
+    let proxy = ProxyConfig { chain_id: Some(1), ..Default::default() };
+    let fork =
+        LocalFork::setup(NodeConfig::test(), NodeConfig::test().with_proxy(Some(proxy))).await;
+    let provider = fork.fork_handle.http_provider();
+
+    assert_eq!(provider.get_chainid().await.unwrap(), 1u64.into());
+    assert_eq!(provider.get_net_version().await.unwrap(), "1");
+
+    // the origin doesn't know the method either, but the error is returned by the origin
+    let err = provider
+        .request::<_, serde_json::Value>("trace_block", ["latest"])
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("proxied request failed"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_proxy_refuses_send_methods() {
+    let proxy = ProxyConfig { chain_id: Some(1), ..Default::default() };
+    let fork =
+        LocalFork::setup(NodeConfig::test(), NodeConfig::test().with_proxy(Some(proxy))).await;
+    let provider = fork.fork_handle.http_provider();
+
+    // refused by the fork itself, the origin never sees the bundle
+    let err = provider
+        .request::<_, serde_json::Value>(
+            "eth_sendBundle",
+            [serde_json::json!({ "txs": ["0x00"], "blockNumber": "0x1" })],
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("--proxy-allow-writes"), "{err}");
+    assert!(!err.contains("proxied request failed"), "{err}");
+}
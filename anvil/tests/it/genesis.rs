@@ -1,7 +1,11 @@
 //! genesis.json tests
 
-use anvil::{genesis::Genesis, spawn, NodeConfig};
-use ethers::{abi::Address, prelude::Middleware, types::U256};
+use anvil::{genesis::Genesis, spawn, Hardfork, NodeConfig};
+use ethers::{
+    abi::Address,
+    prelude::Middleware,
+    types::{Bytes, H256, U256},
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_apply_genesis() {
@@ -45,3 +49,29 @@ async fn can_apply_genesis() {
     let expected: U256 = "ffffffffffffffffffffffffff".parse().unwrap();
     assert_eq!(balance, expected);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dump_and_init_genesis() {
+    let (api, _handle) = spawn(NodeConfig::test().with_hardfork(Some(Hardfork::London))).await;
+
+    let addr: Address = "71562b71999873db5b286df957af199ec94617f7".parse().unwrap();
+    let code = Bytes::from(vec![0x60, 0x00]);
+    api.anvil_set_balance(addr, 1337u64.into()).await.unwrap();
+    api.anvil_set_code(addr, code.clone()).await.unwrap();
+    api.anvil_set_storage_at(addr, 1u64.into(), H256::from_low_u64_be(42)).await.unwrap();
+
+    let genesis = serde_json::to_string(&api.anvil_dump_genesis().await.unwrap()).unwrap();
+    let genesis: Genesis = serde_json::from_str(&genesis).unwrap();
+    assert_eq!(genesis.hardfork(), Some(Hardfork::London));
+
+    let (api, handle) = spawn(NodeConfig::test().with_genesis(Some(genesis))).await;
+    let provider = handle.http_provider();
+
+    assert_eq!(api.anvil_node_info().await.unwrap().hard_fork, Hardfork::London.into());
+    assert_eq!(provider.get_balance(addr, None).await.unwrap(), 1337u64.into());
+    assert_eq!(provider.get_code(addr, None).await.unwrap(), code);
+    assert_eq!(
+        provider.get_storage_at(addr, H256::from_low_u64_be(1), None).await.unwrap(),
+        H256::from_low_u64_be(42)
+    );
+}
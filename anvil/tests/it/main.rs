@@ -2,6 +2,7 @@ mod abi;
 mod anvil;
 mod anvil_api;
 mod api;
+mod cluster;
 mod fork;
 mod ganache;
 mod gas;
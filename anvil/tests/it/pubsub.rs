@@ -272,3 +272,51 @@ async fn test_sub_new_heads_fast() {
     let numbers = (1..=num).collect::<Vec<_>>();
     assert_eq!(block_numbers, numbers);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_logs_reorg() {
+    abigen!(EmitLogs, "test-data/emit_logs.json");
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.ws_provider().await;
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let contract = EmitLogs::deploy(Arc::clone(&client), "First Message".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+    let filter = Filter::new().address(ValueOrArray::Value(contract.address()));
+    let mut logs_sub = client.subscribe_logs(&filter).await.unwrap();
+
+    let receipt = contract
+        .set_value("Next Message".to_string())
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .unwrap();
+    let log = logs_sub.next().await.unwrap();
+    assert_eq!(log.removed, Some(false));
+
+    api.anvil_reorg(1u64.into(), 2u64.into(), Some(true)).await.unwrap();
+
+    // the log of the replaced block is reported as removed
+    let removed = logs_sub.next().await.unwrap();
+    assert_eq!(removed.removed, Some(true));
+    assert_eq!(removed.block_hash, receipt.block_hash);
+
+    // the transaction is included in the first block of the new branch
+    let log = logs_sub.next().await.unwrap();
+    assert_eq!(log.removed, Some(false));
+    assert_eq!(log.transaction_hash, Some(receipt.transaction_hash));
+    assert_eq!(log.block_number, receipt.block_number);
+    assert_ne!(log.block_hash, receipt.block_hash);
+
+    assert_eq!(client.get_block_number().await.unwrap().as_u64(), 3);
+    assert_eq!(contract.get_value().call().await.unwrap(), "Next Message");
+}
@@ -1,16 +1,18 @@
 use crate::abi::*;
 use anvil::{spawn, Hardfork, NodeConfig};
+use anvil_core::eth::transaction::EthTransactionRequest;
 use ethers::{
     abi::ethereum_types::BigEndianHash,
     prelude::{
-        signer::SignerMiddlewareError, BlockId, Middleware, Signer, SignerMiddleware,
-        TransactionRequest,
+        signer::SignerMiddlewareError, BlockId, Middleware, PendingTransaction, Signer,
+        SignerMiddleware, TransactionRequest,
     },
     types::{
         transaction::eip2930::{AccessList, AccessListItem},
         Address, BlockNumber, Transaction, TransactionReceipt, H256, U256,
     },
 };
+use foundry_evm::executor::delegation::{delegation_code, Authorization, PER_AUTHORIZATION_GAS};
 use futures::{future::join_all, FutureExt, StreamExt};
 use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::time::timeout;
@@ -1016,3 +1018,42 @@ async fn test_reject_eip1559_pre_london() {
     let greeting = greeter_contract.greet().call().await.unwrap();
     assert_eq!("Hello World!", greeting);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_set_code_tx() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let sender = accounts[0].address();
+    let authority = &accounts[1];
+    let delegate = Address::random();
+
+    let authorization = Authorization {
+        chain_id: api.chain_id().into(),
+        address: delegate,
+        nonce: 0u64.into(),
+    };
+    let signature = authority.sign_hash(authorization.signature_hash()).unwrap();
+    let request = EthTransactionRequest {
+        from: Some(sender),
+        to: Some(authority.address()),
+        authorization_list: Some(vec![authorization.into_signed(signature)]),
+        ..Default::default()
+    };
+    let hash = api.send_transaction(request).await.unwrap();
+    let receipt = PendingTransaction::new(hash, &provider).await.unwrap().unwrap();
+    assert_eq!(receipt.transaction_type, Some(4u64.into()));
+    assert_eq!(receipt.status, Some(1u64.into()));
+    // the authorization is charged on top of the base cost
+    assert_eq!(receipt.gas_used, Some((21_000 + PER_AUTHORIZATION_GAS).into()));
+
+    let code = provider.get_code(authority.address(), None).await.unwrap();
+    assert_eq!(code, delegation_code(delegate));
+    let nonce = provider.get_transaction_count(authority.address(), None).await.unwrap();
+    assert_eq!(nonce, 1u64.into());
+
+    // the authority can still send transactions
+    let tx = TransactionRequest::new().to(sender).value(1u64).from(authority.address());
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+}
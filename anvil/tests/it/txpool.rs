@@ -48,3 +48,34 @@ async fn geth_txpool() {
         assert!(content.contains_key(&nonce.to_string()));
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_drop_txpool_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let account = provider.get_accounts().await.unwrap()[0];
+    let tx = TransactionRequest::new().to(account).from(account).value(42u64);
+
+    for _ in 0..3 {
+        provider.send_transaction(tx.clone(), None).await.unwrap();
+    }
+    // a nonce gap keeps this transaction queued
+    let queued = provider.send_transaction(tx.clone().nonce(10u64), None).await.unwrap();
+
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 3);
+    assert_eq!(status.queued.as_u64(), 1);
+
+    assert_eq!(api.anvil_drop_transaction(*queued).await.unwrap(), Some(*queued));
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 3);
+    assert_eq!(status.queued.as_u64(), 0);
+
+    api.anvil_drop_all_transactions().await.unwrap();
+    let status = provider.txpool_status().await.unwrap();
+    assert_eq!(status.pending.as_u64(), 0);
+    assert_eq!(status.queued.as_u64(), 0);
+    assert!(provider.txpool_content().await.unwrap().pending.is_empty());
+}
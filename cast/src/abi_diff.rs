@@ -0,0 +1,560 @@
+//! Comparison of two ABIs, see `cast abi-diff`
+//!
+//! Changes are breaking if existing callers, deployers or indexers of the old interface can break:
+//! removed or changed functions, events and errors, a changed constructor, and functions that
+//! accept less than before, e.g. a payable function that becomes non-payable.
+
+use ethers_core::abi::{Abi, AbiError, Event, Function, Param, StateMutability};
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+/// The kind of a change between two ABIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AbiChangeKind {
+    FunctionRemoved,
+    FunctionAdded,
+    /// The only overload of a function has different parameters
+    FunctionSignatureChanged,
+    FunctionOutputsChanged,
+    FunctionMutabilityChanged,
+    EventRemoved,
+    EventAdded,
+    /// The parameters, the indexed parameters or the anonymity of an event changed
+    EventChanged,
+    ErrorRemoved,
+    ErrorAdded,
+    /// The only error of a name has different parameters
+    ErrorChanged,
+    /// The parameters of the constructor changed, a missing constructor has no parameters
+    ConstructorChanged,
+    FallbackRemoved,
+    FallbackAdded,
+    ReceiveRemoved,
+    ReceiveAdded,
+}
+
+impl fmt::Display for AbiChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            AbiChangeKind::FunctionRemoved => "function removed",
+            AbiChangeKind::FunctionAdded => "function added",
+            AbiChangeKind::FunctionSignatureChanged => "function signature changed",
+            AbiChangeKind::FunctionOutputsChanged => "function outputs changed",
+            AbiChangeKind::FunctionMutabilityChanged => "function mutability changed",
+            AbiChangeKind::EventRemoved => "event removed",
+            AbiChangeKind::EventAdded => "event added",
+            AbiChangeKind::EventChanged => "event changed",
+            AbiChangeKind::ErrorRemoved => "error removed",
+            AbiChangeKind::ErrorAdded => "error added",
+            AbiChangeKind::ErrorChanged => "error changed",
+            AbiChangeKind::ConstructorChanged => "constructor changed",
+            AbiChangeKind::FallbackRemoved => "fallback removed",
+            AbiChangeKind::FallbackAdded => "fallback added",
+            AbiChangeKind::ReceiveRemoved => "receive removed",
+            AbiChangeKind::ReceiveAdded => "receive added",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A single change between two ABIs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AbiChange {
+    pub kind: AbiChangeKind,
+    /// The item of the old ABI, if it had one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// The item of the new ABI, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+    /// Whether callers or indexers of the old ABI can break
+    pub breaking: bool,
+}
+
+impl AbiChange {
+    fn new(kind: AbiChangeKind, old: Option<String>, new: Option<String>, breaking: bool) -> Self {
+        Self { kind, old, new, breaking }
+    }
+}
+
+impl fmt::Display for AbiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match (self.old.as_ref(), self.new.as_ref()) {
+            (Some(old), Some(new)) if old != new => write!(f, ": {old} -> {new}"),
+            (Some(item), _) | (_, Some(item)) => write!(f, ": {item}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// All changes between two ABIs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AbiDiff {
+    pub changes: Vec<AbiChange>,
+}
+
+impl AbiDiff {
+    /// Compares the `old` ABI to the `new` ABI
+    pub fn new(old: &Abi, new: &Abi) -> Self {
+        let mut changes = Vec::new();
+        diff_functions(old, new, &mut changes);
+        diff_events(old, new, &mut changes);
+        diff_errors(old, new, &mut changes);
+
+        let (old_constructor, new_constructor) =
+            (constructor_signature(old), constructor_signature(new));
+        if old_constructor != new_constructor {
+            changes.push(AbiChange::new(
+                AbiChangeKind::ConstructorChanged,
+                Some(old_constructor),
+                Some(new_constructor),
+                true,
+            ));
+        }
+
+        if old.fallback != new.fallback {
+            let kind = if old.fallback {
+                AbiChangeKind::FallbackRemoved
+            } else {
+                AbiChangeKind::FallbackAdded
+            };
+            changes.push(AbiChange::new(kind, None, None, old.fallback));
+        }
+        if old.receive != new.receive {
+            let kind = if old.receive {
+                AbiChangeKind::ReceiveRemoved
+            } else {
+                AbiChangeKind::ReceiveAdded
+            };
+            changes.push(AbiChange::new(kind, None, None, old.receive));
+        }
+
+        Self { changes }
+    }
+
+    /// Returns true if the ABIs are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns true if any change is breaking
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|change| change.breaking)
+    }
+
+    /// Returns the breaking changes
+    pub fn breaking(&self) -> impl Iterator<Item = &AbiChange> {
+        self.changes.iter().filter(|change| change.breaking)
+    }
+
+    /// Returns the changes that are compatible with the old ABI
+    pub fn non_breaking(&self) -> impl Iterator<Item = &AbiChange> {
+        self.changes.iter().filter(|change| !change.breaking)
+    }
+}
+
+fn diff_functions(old: &Abi, new: &Abi, changes: &mut Vec<AbiChange>) {
+    let old_functions = functions_by_signature(old);
+    let new_functions = functions_by_signature(new);
+
+    let removed = old_functions
+        .iter()
+        .filter(|(signature, _)| !new_functions.contains_key(*signature))
+        .map(|(_, function)| *function);
+    let added = new_functions
+        .iter()
+        .filter(|(signature, _)| !old_functions.contains_key(*signature))
+        .map(|(_, function)| *function);
+    for (old_fn, new_fn) in pair_by_name(removed, added, |function| &function.name) {
+        match (old_fn, new_fn) {
+            (Some(old_fn), Some(new_fn)) => changes.push(AbiChange::new(
+                AbiChangeKind::FunctionSignatureChanged,
+                Some(function_signature(old_fn)),
+                Some(function_signature(new_fn)),
+                true,
+            )),
+            (Some(old_fn), None) => changes.push(AbiChange::new(
+                AbiChangeKind::FunctionRemoved,
+                Some(function_signature(old_fn)),
+                None,
+                true,
+            )),
+            (None, Some(new_fn)) => changes.push(AbiChange::new(
+                AbiChangeKind::FunctionAdded,
+                None,
+                Some(function_signature(new_fn)),
+                false,
+            )),
+            (None, None) => {}
+        }
+    }
+
+    for (signature, old_fn) in old_functions.iter() {
+        let new_fn = match new_functions.get(signature) {
+            Some(new_fn) => new_fn,
+            None => continue,
+        };
+        let old_outputs = param_types(&old_fn.outputs);
+        let new_outputs = param_types(&new_fn.outputs);
+        if old_outputs != new_outputs {
+            changes.push(AbiChange::new(
+                AbiChangeKind::FunctionOutputsChanged,
+                Some(format!("{signature} returns ({old_outputs})")),
+                Some(format!("{signature} returns ({new_outputs})")),
+                true,
+            ));
+        }
+        let (old_mutability, new_mutability) = (old_fn.state_mutability, new_fn.state_mutability);
+        if old_mutability != new_mutability {
+            changes.push(AbiChange::new(
+                AbiChangeKind::FunctionMutabilityChanged,
+                Some(format!("{signature} {}", mutability_name(old_mutability))),
+                Some(format!("{signature} {}", mutability_name(new_mutability))),
+                is_breaking_mutability_change(old_mutability, new_mutability),
+            ));
+        }
+    }
+}
+
+fn diff_events(old: &Abi, new: &Abi, changes: &mut Vec<AbiChange>) {
+    let old_events = events_by_signature(old);
+    let new_events = events_by_signature(new);
+
+    let removed = old_events
+        .iter()
+        .filter(|(signature, _)| !new_events.contains_key(*signature))
+        .map(|(_, event)| *event);
+    let added = new_events
+        .iter()
+        .filter(|(signature, _)| !old_events.contains_key(*signature))
+        .map(|(_, event)| *event);
+    for (old_event, new_event) in pair_by_name(removed, added, |event| &event.name) {
+        match (old_event, new_event) {
+            (Some(old_event), Some(new_event)) => changes.push(AbiChange::new(
+                AbiChangeKind::EventChanged,
+                Some(event_signature(old_event)),
+                Some(event_signature(new_event)),
+                true,
+            )),
+            (Some(old_event), None) => changes.push(AbiChange::new(
+                AbiChangeKind::EventRemoved,
+                Some(event_signature(old_event)),
+                None,
+                true,
+            )),
+            (None, Some(new_event)) => changes.push(AbiChange::new(
+                AbiChangeKind::EventAdded,
+                None,
+                Some(event_signature(new_event)),
+                false,
+            )),
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_errors(old: &Abi, new: &Abi, changes: &mut Vec<AbiChange>) {
+    let old_errors = errors_by_signature(old);
+    let new_errors = errors_by_signature(new);
+
+    let removed = old_errors
+        .iter()
+        .filter(|(signature, _)| !new_errors.contains_key(*signature))
+        .map(|(_, error)| *error);
+    let added = new_errors
+        .iter()
+        .filter(|(signature, _)| !old_errors.contains_key(*signature))
+        .map(|(_, error)| *error);
+    for (old_error, new_error) in pair_by_name(removed, added, |error| &error.name) {
+        match (old_error, new_error) {
+            (Some(old_error), Some(new_error)) => changes.push(AbiChange::new(
+                AbiChangeKind::ErrorChanged,
+                Some(error_signature(old_error)),
+                Some(error_signature(new_error)),
+                true,
+            )),
+            (Some(old_error), None) => changes.push(AbiChange::new(
+                AbiChangeKind::ErrorRemoved,
+                Some(error_signature(old_error)),
+                None,
+                true,
+            )),
+            (None, Some(new_error)) => changes.push(AbiChange::new(
+                AbiChangeKind::ErrorAdded,
+                None,
+                Some(error_signature(new_error)),
+                false,
+            )),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Returns the functions keyed by their signature, e.g. `transfer(address,uint256)`
+fn functions_by_signature(abi: &Abi) -> BTreeMap<String, &Function> {
+    abi.functions().map(|function| (function_signature(function), function)).collect()
+}
+
+/// Returns the events keyed by their full signature, including the indexed parameters and
+/// anonymity, since changing those changes the topics of the logs
+fn events_by_signature(abi: &Abi) -> BTreeMap<String, &Event> {
+    abi.events().map(|event| (event_signature(event), event)).collect()
+}
+
+/// Returns the errors keyed by their signature, e.g. `InsufficientBalance(uint256)`
+fn errors_by_signature(abi: &Abi) -> BTreeMap<String, &AbiError> {
+    abi.errors().map(|error| (error_signature(error), error)).collect()
+}
+
+/// Pairs the removed and added items of the same name if it is the only overload on both sides,
+/// since that is a changed signature rather than an unrelated removal and addition
+fn pair_by_name<'a, T>(
+    removed: impl Iterator<Item = &'a T>,
+    added: impl Iterator<Item = &'a T>,
+    name: impl Fn(&T) -> &String,
+) -> Vec<(Option<&'a T>, Option<&'a T>)> {
+    let mut by_name: BTreeMap<&String, (Vec<&'a T>, Vec<&'a T>)> = BTreeMap::new();
+    for item in removed {
+        by_name.entry(name(item)).or_default().0.push(item);
+    }
+    for item in added {
+        by_name.entry(name(item)).or_default().1.push(item);
+    }
+
+    let mut pairs = Vec::new();
+    for (_, (removed, added)) in by_name {
+        if removed.len() == 1 && added.len() == 1 {
+            pairs.push((Some(removed[0]), Some(added[0])));
+            continue
+        }
+        pairs.extend(removed.into_iter().map(|item| (Some(item), None)));
+        pairs.extend(added.into_iter().map(|item| (None, Some(item))));
+    }
+    pairs
+}
+
+/// Returns true if callers of a function with the `old` mutability can fail with the `new` one:
+/// sending value to a non-payable function and calling a state changing function in a static
+/// context revert
+fn is_breaking_mutability_change(old: StateMutability, new: StateMutability) -> bool {
+    let is_static =
+        |mutability| matches!(mutability, StateMutability::Pure | StateMutability::View);
+    (old == StateMutability::Payable && new != StateMutability::Payable) ||
+        (is_static(old) && !is_static(new))
+}
+
+fn mutability_name(mutability: StateMutability) -> &'static str {
+    match mutability {
+        StateMutability::Pure => "pure",
+        StateMutability::View => "view",
+        StateMutability::NonPayable => "nonpayable",
+        StateMutability::Payable => "payable",
+    }
+}
+
+/// Returns the signature of the function without its outputs, e.g. `transfer(address,uint256)`
+fn function_signature(function: &Function) -> String {
+    format!("{}({})", function.name, param_types(&function.inputs))
+}
+
+/// Returns the signature of the error, e.g. `InsufficientBalance(uint256)`
+fn error_signature(error: &AbiError) -> String {
+    format!("{}({})", error.name, param_types(&error.inputs))
+}
+
+/// Returns the signature of the constructor, e.g. `constructor(address)`, which is
+/// `constructor()` if the ABI has no constructor
+fn constructor_signature(abi: &Abi) -> String {
+    let inputs = abi.constructor().map(|constructor| param_types(&constructor.inputs));
+    format!("constructor({})", inputs.unwrap_or_default())
+}
+
+/// Returns the signature of the event with the indexed parameters, e.g.
+/// `Transfer(address indexed,address indexed,uint256)`
+fn event_signature(event: &Event) -> String {
+    let params = event
+        .inputs
+        .iter()
+        .map(|param| {
+            if param.indexed {
+                format!("{} indexed", param.kind)
+            } else {
+                param.kind.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let anonymous = if event.anonymous { " anonymous" } else { "" };
+    format!("{}({params}){anonymous}", event.name)
+}
+
+fn param_types(params: &[Param]) -> String {
+    params.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::parse_abi;
+
+    fn diff(old: &[&str], new: &[&str]) -> AbiDiff {
+        AbiDiff::new(&parse_abi(old).unwrap(), &parse_abi(new).unwrap())
+    }
+
+    #[test]
+    fn identical_abis_have_no_changes() {
+        let abi = ["function transfer(address,uint256) external returns (bool)"];
+        let diff = diff(&abi, &abi);
+        assert!(diff.is_empty());
+        assert!(!diff.is_breaking());
+    }
+
+    #[test]
+    fn can_detect_function_changes() {
+        let diff = diff(
+            &[
+                "function transfer(address,uint256) external returns (bool)",
+                "function burn(uint256) external",
+                "function balanceOf(address) external view returns (uint256)",
+            ],
+            &[
+                "function transfer(address,uint256,bytes) external returns (bool)",
+                "function mint(uint256) external",
+                "function balanceOf(address) external view returns (uint128)",
+            ],
+        );
+        let kinds = diff.changes.iter().map(|change| change.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                AbiChangeKind::FunctionRemoved,
+                AbiChangeKind::FunctionAdded,
+                AbiChangeKind::FunctionSignatureChanged,
+                AbiChangeKind::FunctionOutputsChanged,
+            ]
+        );
+        assert_eq!(
+            diff.changes[2].to_string(),
+            "function signature changed: transfer(address,uint256) -> transfer(address,uint256,bytes)"
+        );
+        assert_eq!(diff.breaking().count(), 3);
+        assert_eq!(diff.non_breaking().count(), 1);
+    }
+
+    #[test]
+    fn can_classify_mutability_changes() {
+        let diff = diff(
+            &[
+                "function deposit() external payable",
+                "function total() external view returns (uint256)",
+                "function poke() external",
+            ],
+            &[
+                "function deposit() external",
+                "function total() external returns (uint256)",
+                "function poke() external payable",
+            ],
+        );
+        let breaking = diff
+            .changes
+            .iter()
+            .map(|change| (change.kind, change.old.clone().unwrap(), change.breaking))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            breaking,
+            vec![
+                (AbiChangeKind::FunctionMutabilityChanged, "deposit() payable".to_string(), true),
+                (AbiChangeKind::FunctionMutabilityChanged, "poke() nonpayable".to_string(), false),
+                (AbiChangeKind::FunctionMutabilityChanged, "total() view".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_detect_event_changes() {
+        let diff = diff(
+            &[
+                "event Transfer(address indexed from, address indexed to, uint256 amount)",
+                "event Approval(address indexed owner, address indexed spender, uint256 amount)",
+            ],
+            &[
+                "event Transfer(address indexed from, address to, uint256 amount)",
+                "event Paused(address account)",
+            ],
+        );
+        assert_eq!(
+            diff.changes,
+            vec![
+                AbiChange::new(
+                    AbiChangeKind::EventRemoved,
+                    Some("Approval(address indexed,address indexed,uint256)".to_string()),
+                    None,
+                    true
+                ),
+                AbiChange::new(
+                    AbiChangeKind::EventAdded,
+                    None,
+                    Some("Paused(address)".to_string()),
+                    false
+                ),
+                AbiChange::new(
+                    AbiChangeKind::EventChanged,
+                    Some("Transfer(address indexed,address indexed,uint256)".to_string()),
+                    Some("Transfer(address indexed,address,uint256)".to_string()),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_detect_error_and_constructor_changes() {
+        let diff = diff(
+            &[
+                "constructor(address owner)",
+                "error Unauthorized(address caller)",
+                "error InsufficientBalance(uint256 balance)",
+            ],
+            &[
+                "constructor(address owner, uint256 fee)",
+                "error InsufficientBalance(uint256 balance, uint256 needed)",
+                "error Paused()",
+            ],
+        );
+        assert_eq!(
+            diff.changes,
+            vec![
+                AbiChange::new(
+                    AbiChangeKind::ErrorChanged,
+                    Some("InsufficientBalance(uint256)".to_string()),
+                    Some("InsufficientBalance(uint256,uint256)".to_string()),
+                    true
+                ),
+                AbiChange::new(
+                    AbiChangeKind::ErrorAdded,
+                    None,
+                    Some("Paused()".to_string()),
+                    false
+                ),
+                AbiChange::new(
+                    AbiChangeKind::ErrorRemoved,
+                    Some("Unauthorized(address)".to_string()),
+                    None,
+                    true
+                ),
+                AbiChange::new(
+                    AbiChangeKind::ConstructorChanged,
+                    Some("constructor(address)".to_string()),
+                    Some("constructor(address,uint256)".to_string()),
+                    true
+                ),
+            ]
+        );
+
+        // a missing constructor is the same as one without parameters
+        let diff = diff(&["constructor()", "function f()"], &["function f()"]);
+        assert!(diff.is_empty());
+    }
+}
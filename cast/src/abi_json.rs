@@ -0,0 +1,410 @@
+//! ABI encoding and decoding of function arguments as JSON
+//!
+//! Tuples are JSON objects keyed by the names of their components in the signature, or arrays of
+//! their components in order, e.g. the arguments of `f((address to,uint256 amount)[] transfers)`
+//! are `{"transfers": [{"to": "0x..", "amount": "1"}]}` or `[[["0x..", "1"]]]`.
+
+use ethers_core::{
+    abi::{self, HumanReadableParser, ParamType, Token},
+    types::I256,
+    utils::{hex, to_checksum},
+};
+use eyre::{Context, Result};
+use foundry_common::abi::parse_tokens;
+use serde_json::{Map, Value};
+
+/// A parameter of a signature, with the names of its components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonParam {
+    /// The name of the parameter, if any
+    pub name: Option<String>,
+    /// The type of the parameter
+    pub kind: JsonParamKind,
+}
+
+/// The type of a [JsonParam]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonParamKind {
+    /// A type without components, e.g. `uint256` or `bytes`
+    Elementary(ParamType),
+    /// A struct
+    Tuple(Vec<JsonParam>),
+    /// A dynamic array `T[]`
+    Array(Box<JsonParamKind>),
+    /// A fixed size array `T[N]`
+    FixedArray(Box<JsonParamKind>, usize),
+}
+
+impl JsonParamKind {
+    /// Returns the ABI type
+    pub fn param_type(&self) -> ParamType {
+        match self {
+            JsonParamKind::Elementary(ty) => ty.clone(),
+            JsonParamKind::Tuple(params) => {
+                ParamType::Tuple(params.iter().map(|param| param.kind.param_type()).collect())
+            }
+            JsonParamKind::Array(kind) => ParamType::Array(Box::new(kind.param_type())),
+            JsonParamKind::FixedArray(kind, len) => {
+                ParamType::FixedArray(Box::new(kind.param_type()), *len)
+            }
+        }
+    }
+}
+
+/// A function signature with the names of all parameters and components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonSignature {
+    /// The name of the function
+    pub name: String,
+    /// The input parameters
+    pub inputs: Vec<JsonParam>,
+    /// The output parameters
+    pub outputs: Vec<JsonParam>,
+}
+
+impl JsonSignature {
+    /// Parses a signature in the format `<name>(<in-params>)(<out-params>)`, where the outputs are
+    /// optional and may also be declared as `returns (<out-params>)`
+    pub fn parse(sig: &str) -> Result<Self> {
+        let sig = sig.trim();
+        let sig = sig.strip_prefix("function ").unwrap_or(sig);
+        let open = sig.find('(').ok_or_else(|| eyre::eyre!("Missing parameters in `{sig}`"))?;
+        let name = sig[..open].trim().to_string();
+        let (inputs, rest) = split_group(&sig[open..])?;
+
+        let outputs = match rest.find('(') {
+            Some(open) => {
+                let (outputs, rest) = split_group(&rest[open..])?;
+                if !rest.trim().is_empty() {
+                    eyre::bail!("Unexpected `{}` after the outputs of `{sig}`", rest.trim())
+                }
+                parse_params(outputs)?
+            }
+            None => vec![],
+        };
+
+        Ok(Self { name, inputs: parse_params(inputs)?, outputs })
+    }
+
+    /// Returns the canonical signature, e.g. `f((address,uint256)[])`
+    pub fn canonical(&self) -> String {
+        let inputs = self.inputs.iter().map(|param| param.kind.param_type().to_string());
+        format!("{}({})", self.name, inputs.collect::<Vec<_>>().join(","))
+    }
+}
+
+/// ABI encodes the arguments, given as JSON object or array, without the selector
+///
+/// The names of the fields of JSON objects are validated against the signature.
+pub fn encode(sig: &str, json: &str) -> Result<Vec<u8>> {
+    let sig = JsonSignature::parse(sig)?;
+    let value: Value = serde_json::from_str(json).wrap_err("Failed to parse the arguments")?;
+    let args = JsonParamKind::Tuple(sig.inputs);
+    match tokenize(&args, &value, "arguments")? {
+        Token::Tuple(tokens) => Ok(abi::encode(&tokens)),
+        _ => unreachable!("tuples are tokenized as tuples"),
+    }
+}
+
+/// Decodes the ABI encoded outputs of the function, or the inputs of its calldata if `input` is
+/// set, to JSON
+///
+/// Tuples with names for all components are decoded as objects, others as arrays.
+pub fn decode(sig: &str, data: &str, input: bool) -> Result<Value> {
+    let sig = JsonSignature::parse(sig)?;
+    let data = hex::decode(data.trim().trim_start_matches("0x"))?;
+    let (params, data) = if input {
+        if data.len() < 4 {
+            eyre::bail!("The calldata is shorter than a selector")
+        }
+        (sig.inputs, &data[4..])
+    } else {
+        (sig.outputs, &data[..])
+    };
+
+    let kind = JsonParamKind::Tuple(params);
+    let tokens = abi::decode(&[kind.param_type()], data)?;
+    Ok(to_json(&kind, &tokens[0]))
+}
+
+/// Returns the inner part of the group of balanced parentheses `s` starts with, and the rest
+fn split_group(s: &str) -> Result<(&str, &str)> {
+    let mut depth = 0;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&s[1..idx], &s[idx + 1..]))
+                }
+            }
+            _ => {}
+        }
+    }
+    eyre::bail!("Unbalanced parentheses in `{s}`")
+}
+
+/// Parses the comma separated parameters of a group
+fn parse_params(s: &str) -> Result<Vec<JsonParam>> {
+    if s.trim().is_empty() {
+        return Ok(vec![])
+    }
+    let mut params = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (idx, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                params.push(parse_param(&s[start..idx])?);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(parse_param(&s[start..])?);
+    Ok(params)
+}
+
+/// Parses a single parameter like `(address to,uint256 amount)[] transfers`
+fn parse_param(s: &str) -> Result<JsonParam> {
+    let s = s.trim();
+    let s = s.strip_prefix("tuple").filter(|rest| rest.starts_with('(')).unwrap_or(s);
+    let (mut kind, rest) = if s.starts_with('(') {
+        let (components, rest) = split_group(s)?;
+        (JsonParamKind::Tuple(parse_params(components)?), rest)
+    } else {
+        let end = s.find(|c: char| c.is_whitespace() || c == '[').unwrap_or(s.len());
+        let ty = HumanReadableParser::parse_type(&s[..end])
+            .map_err(|err| eyre::eyre!("Invalid type `{}`: {err}", &s[..end]))?;
+        (JsonParamKind::Elementary(ty), &s[end..])
+    };
+
+    let mut rest = rest.trim_start();
+    while let Some(suffix) = rest.strip_prefix('[') {
+        let close = suffix.find(']').ok_or_else(|| eyre::eyre!("Unclosed `[` in `{s}`"))?;
+        let len = suffix[..close].trim();
+        kind = if len.is_empty() {
+            JsonParamKind::Array(Box::new(kind))
+        } else {
+            let len = len.parse().wrap_err_with(|| format!("Invalid array length in `{s}`"))?;
+            JsonParamKind::FixedArray(Box::new(kind), len)
+        };
+        rest = suffix[close + 1..].trim_start();
+    }
+
+    // the name is preceded by the data location or `indexed`, if any
+    let name = rest
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "memory" | "calldata" | "storage" | "indexed"))
+        .last()
+        .map(str::to_string);
+    Ok(JsonParam { name, kind })
+}
+
+/// Converts the JSON value to a token of the given type, `path` is the location of the value in
+/// the arguments for error messages
+fn tokenize(kind: &JsonParamKind, value: &Value, path: &str) -> Result<Token> {
+    let expected = |what: &str| eyre::eyre!("Expected {what} for `{path}`, found `{value}`");
+    match kind {
+        JsonParamKind::Elementary(ty) => {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return Err(expected(&format!("a value of type `{ty}`"))),
+            };
+            let mut tokens = parse_tokens([(ty, value.as_str())], true)
+                .wrap_err_with(|| format!("Invalid value for `{path}`"))?;
+            Ok(tokens.remove(0))
+        }
+        JsonParamKind::Array(kind) => {
+            let values = value.as_array().ok_or_else(|| expected("an array"))?;
+            let tokens = values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| tokenize(kind, value, &format!("{path}[{idx}]")))
+                .collect::<Result<_>>()?;
+            Ok(Token::Array(tokens))
+        }
+        JsonParamKind::FixedArray(kind, len) => {
+            let values = value
+                .as_array()
+                .filter(|values| values.len() == *len)
+                .ok_or_else(|| expected(&format!("an array of length {len}")))?;
+            let tokens = values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| tokenize(kind, value, &format!("{path}[{idx}]")))
+                .collect::<Result<_>>()?;
+            Ok(Token::FixedArray(tokens))
+        }
+        JsonParamKind::Tuple(params) => {
+            let tokens = match value {
+                Value::Array(values) => {
+                    if values.len() != params.len() {
+                        return Err(expected(&format!("{} components", params.len())))
+                    }
+                    params
+                        .iter()
+                        .zip(values)
+                        .enumerate()
+                        .map(|(idx, (param, value))| {
+                            let name = param.name.clone().unwrap_or_else(|| idx.to_string());
+                            tokenize(&param.kind, value, &format!("{path}.{name}"))
+                        })
+                        .collect::<Result<_>>()?
+                }
+                Value::Object(fields) => tokenize_fields(params, fields, path)?,
+                _ => return Err(expected("an object or an array")),
+            };
+            Ok(Token::Tuple(tokens))
+        }
+    }
+}
+
+/// Converts the fields of a JSON object to the tokens of the components of a tuple, all
+/// components must be named and all fields must be components
+fn tokenize_fields(
+    params: &[JsonParam],
+    fields: &Map<String, Value>,
+    path: &str,
+) -> Result<Vec<Token>> {
+    let names = params.iter().filter_map(|param| param.name.as_deref()).collect::<Vec<_>>();
+    if names.len() != params.len() {
+        eyre::bail!("`{path}` has unnamed components in the signature, pass it as an array")
+    }
+    if let Some(unknown) = fields.keys().find(|key| !names.contains(&key.as_str())) {
+        eyre::bail!("Unknown field `{unknown}` in `{path}`, expected one of: {}", names.join(", "))
+    }
+
+    params
+        .iter()
+        .zip(names)
+        .map(|(param, name)| {
+            let value = fields
+                .get(name)
+                .ok_or_else(|| eyre::eyre!("Missing field `{name}` in `{path}`"))?;
+            tokenize(&param.kind, value, &format!("{path}.{name}"))
+        })
+        .collect()
+}
+
+/// Converts the token to JSON, numbers are decimal strings so they don't lose precision
+fn to_json(kind: &JsonParamKind, token: &Token) -> Value {
+    match (kind, token) {
+        (JsonParamKind::Tuple(params), Token::Tuple(tokens)) => {
+            if params.iter().all(|param| param.name.is_some()) {
+                let fields = params
+                    .iter()
+                    .zip(tokens)
+                    .map(|(param, token)| {
+                        (param.name.clone().unwrap(), to_json(&param.kind, token))
+                    })
+                    .collect();
+                Value::Object(fields)
+            } else {
+                Value::Array(
+                    params
+                        .iter()
+                        .zip(tokens)
+                        .map(|(param, token)| to_json(&param.kind, token))
+                        .collect(),
+                )
+            }
+        }
+        (JsonParamKind::Array(kind) | JsonParamKind::FixedArray(kind, _), token) => {
+            let tokens = token.clone().into_array().unwrap_or_default();
+            Value::Array(tokens.iter().map(|token| to_json(kind, token)).collect())
+        }
+        (_, token) => match token {
+            Token::Address(addr) => Value::String(to_checksum(addr, None)),
+            Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+                Value::String(format!("0x{}", hex::encode(bytes)))
+            }
+            Token::Uint(n) => Value::String(n.to_string()),
+            Token::Int(n) => Value::String(I256::from_raw(*n).to_string()),
+            Token::Bool(b) => Value::Bool(*b),
+            Token::String(s) => Value::String(s.clone()),
+            Token::Array(_) | Token::FixedArray(_) | Token::Tuple(_) => {
+                unreachable!("decoded with the type of the parameter")
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIG: &str = "submit((address to,uint256 amount,(bytes32 id,string memo) meta)[] orders, \
+                       bool strict)(uint256 filled, int8 delta)";
+
+    #[test]
+    fn can_parse_signature() {
+        let sig = JsonSignature::parse(SIG).unwrap();
+        assert_eq!(sig.name, "submit");
+        assert_eq!(sig.canonical(), "submit((address,uint256,(bytes32,string))[],bool)");
+        assert_eq!(sig.inputs[0].name.as_deref(), Some("orders"));
+        assert_eq!(sig.outputs.len(), 2);
+
+        let sig = JsonSignature::parse("function f(uint[2] calldata xs) returns (bool)").unwrap();
+        assert_eq!(sig.canonical(), "f(uint256[2])");
+        assert_eq!(sig.inputs[0].name.as_deref(), Some("xs"));
+        assert_eq!(sig.outputs[0].name, None);
+    }
+
+    #[test]
+    fn can_encode_and_decode_nested_structs() {
+        let json = r#"{
+            "orders": [{
+                "to": "0x000000000000000000000000000000000000dEaD",
+                "amount": 1000,
+                "meta": { "id": "0x01", "memo": "first" }
+            }],
+            "strict": true
+        }"#;
+        let encoded = encode(SIG, json).unwrap();
+
+        // positional arrays encode the same
+        let to = "0x000000000000000000000000000000000000dEaD";
+        let positional = format!(r#"[[["{to}", "1000", ["0x01", "first"]]], true]"#);
+        assert_eq!(encode(SIG, &positional).unwrap(), encoded);
+
+        let selector = ethers_core::utils::id(JsonSignature::parse(SIG).unwrap().canonical());
+        let calldata = format!("0x{}{}", hex::encode(selector), hex::encode(&encoded));
+        let decoded = decode(SIG, &calldata, true).unwrap();
+        assert_eq!(decoded["strict"], Value::Bool(true));
+        assert_eq!(decoded["orders"][0]["amount"], Value::String("1000".to_string()));
+        assert_eq!(decoded["orders"][0]["meta"]["memo"], Value::String("first".to_string()));
+        assert_eq!(
+            decoded["orders"][0]["to"],
+            Value::String("0x000000000000000000000000000000000000dEaD".to_string())
+        );
+
+        let outputs =
+            abi::encode(&[Token::Uint(5u64.into()), Token::Int(I256::minus_one().into_raw())]);
+        let decoded = decode(SIG, &hex::encode(outputs), false).unwrap();
+        assert_eq!(decoded, serde_json::json!({ "filled": "5", "delta": "-1" }));
+    }
+
+    #[test]
+    fn validates_field_names() {
+        let err = encode(SIG, r#"{ "orders": [], "strict": true, "extra": 1 }"#).unwrap_err();
+        assert!(err.to_string().contains("Unknown field `extra` in `arguments`"), "{err}");
+
+        let missing = r#"{ "orders": [{ "to": "0x000000000000000000000000000000000000dEaD" }] }"#;
+        let err = encode(SIG, missing).unwrap_err();
+        assert!(
+            err.to_string().contains("Missing field `amount` in `arguments.orders[0]`"),
+            "{err}"
+        );
+
+        let err = encode("f((uint256,bool) x)", r#"{ "x": { "a": 1 } }"#).unwrap_err();
+        assert!(err.to_string().contains("unnamed components"), "{err}");
+
+        let err = encode("f(uint8[2] xs)", r#"{ "xs": [1] }"#).unwrap_err();
+        assert!(err.to_string().contains("array of length 2"), "{err}");
+    }
+}
@@ -0,0 +1,269 @@
+//! EIP-4844 blob transaction support
+//!
+//! `ethers` has no notion of blob carrying transactions, so this module contains the minimal set
+//! of helpers to pack arbitrary data into blobs, compute the KZG commitments and proofs, and to
+//! encode and sign a type `0x03` transaction with its sidecar.
+
+use c_kzg::{Blob, KzgCommitment, KzgProof, KzgSettings};
+use ethers_core::{
+    types::{transaction::eip2930::AccessList, Address, Bytes, Signature, H256, U256, U64},
+    utils::{keccak256, rlp::RlpStream},
+};
+use eyre::{Result, WrapErr};
+use sha2::{Digest, Sha256};
+use std::{path::Path, sync::Arc};
+
+/// The EIP-2718 type of blob transactions
+pub const BLOB_TX_TYPE: u8 = 0x03;
+
+/// Number of bytes in a single blob
+pub const BYTES_PER_BLOB: usize = 131_072;
+
+/// Number of bytes in a field element of a blob
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+
+/// Number of usable data bytes per field element, the first byte is always zero so that the
+/// element stays below the BLS modulus
+pub const USABLE_BYTES_PER_FIELD_ELEMENT: usize = BYTES_PER_FIELD_ELEMENT - 1;
+
+/// Number of usable data bytes in a single blob
+pub const USABLE_BYTES_PER_BLOB: usize =
+    BYTES_PER_BLOB / BYTES_PER_FIELD_ELEMENT * USABLE_BYTES_PER_FIELD_ELEMENT;
+
+/// Maximum number of blobs a single transaction can carry
+pub const MAX_BLOBS_PER_TX: usize = 6;
+
+/// Version byte of KZG versioned hashes
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Environment variable that points to the trusted setup file, if not passed explicitly
+pub const TRUSTED_SETUP_ENV: &str = "CAST_KZG_TRUSTED_SETUP";
+
+/// Packs the given data into blobs.
+///
+/// Every field element holds 31 bytes of data, prefixed with a zero byte, the last blob is padded
+/// with zeros.
+pub fn encode_blobs(data: &[u8]) -> Result<Vec<Vec<u8>>> {
+    if data.is_empty() {
+        eyre::bail!("blob data is empty")
+    }
+    let blobs = data
+        .chunks(USABLE_BYTES_PER_BLOB)
+        .map(|chunk| {
+            let mut blob = vec![0u8; BYTES_PER_BLOB];
+            for (i, element) in chunk.chunks(USABLE_BYTES_PER_FIELD_ELEMENT).enumerate() {
+                let start = i * BYTES_PER_FIELD_ELEMENT + 1;
+                blob[start..start + element.len()].copy_from_slice(element);
+            }
+            blob
+        })
+        .collect::<Vec<_>>();
+    if blobs.len() > MAX_BLOBS_PER_TX {
+        eyre::bail!(
+            "data needs {} blobs, but a transaction can carry at most {MAX_BLOBS_PER_TX}",
+            blobs.len()
+        )
+    }
+    Ok(blobs)
+}
+
+/// Reverses [encode_blobs].
+///
+/// Since the padding of the last blob is indistinguishable from data, trailing zero bytes are
+/// stripped.
+pub fn decode_blobs<'a>(blobs: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut data = Vec::new();
+    for blob in blobs {
+        for element in blob.chunks(BYTES_PER_FIELD_ELEMENT) {
+            data.extend_from_slice(&element[1..]);
+        }
+    }
+    let len = data.iter().rposition(|b| *b != 0).map(|pos| pos + 1).unwrap_or_default();
+    data.truncate(len);
+    data
+}
+
+/// Returns the versioned hash of a KZG commitment: `0x01 ++ sha256(commitment)[1..]`
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    H256(hash)
+}
+
+/// Loads the KZG trusted setup from the given file or the file set in [TRUSTED_SETUP_ENV].
+///
+/// The file has the format of the `trusted_setup.txt` of the
+/// [consensus specs](https://github.com/ethereum/c-kzg-4844/blob/main/src/trusted_setup.txt).
+/// If neither is set, the mainnet trusted setup that is bundled with `c-kzg` is used.
+pub fn load_trusted_setup(path: Option<&Path>) -> Result<Arc<KzgSettings>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match std::env::var_os(TRUSTED_SETUP_ENV) {
+            Some(path) => path.into(),
+            None => return Ok(c_kzg::ethereum_kzg_settings_arc()),
+        },
+    };
+    KzgSettings::load_trusted_setup_file(&path)
+        .map(Arc::new)
+        .map_err(|err| eyre::eyre!("{err:?}"))
+        .wrap_err_with(|| format!("failed to load KZG trusted setup from {}", path.display()))
+}
+
+/// The blobs of a transaction with their commitments and proofs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobSidecar {
+    pub blobs: Vec<Bytes>,
+    pub commitments: Vec<Bytes>,
+    pub proofs: Vec<Bytes>,
+}
+
+impl BlobSidecar {
+    /// Computes the commitments and proofs of the given blobs
+    pub fn new(blobs: Vec<Vec<u8>>, settings: &KzgSettings) -> Result<Self> {
+        let mut sidecar = Self::default();
+        for blob in blobs {
+            let kzg_blob = Blob::from_bytes(&blob).map_err(|err| eyre::eyre!("{err:?}"))?;
+            let commitment = KzgCommitment::blob_to_kzg_commitment(&kzg_blob, settings)
+                .map_err(|err| eyre::eyre!("failed to compute KZG commitment: {err:?}"))?
+                .to_bytes();
+            let proof = KzgProof::compute_blob_kzg_proof(&kzg_blob, &commitment, settings)
+                .map_err(|err| eyre::eyre!("failed to compute KZG proof: {err:?}"))?
+                .to_bytes();
+            sidecar.blobs.push(blob.into());
+            sidecar.commitments.push(commitment.into_inner().to_vec().into());
+            sidecar.proofs.push(proof.into_inner().to_vec().into());
+        }
+        Ok(sidecar)
+    }
+
+    /// Returns the versioned hashes of all commitments
+    pub fn versioned_hashes(&self) -> Vec<H256> {
+        self.commitments.iter().map(|commitment| kzg_to_versioned_hash(commitment)).collect()
+    }
+}
+
+/// An EIP-4844 transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobTransaction {
+    pub chain_id: U64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: U256,
+    /// Blob transactions can't create contracts
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: AccessList,
+    pub max_fee_per_blob_gas: U256,
+    pub blob_versioned_hashes: Vec<H256>,
+}
+
+impl BlobTransaction {
+    const NUM_FIELDS: usize = 11;
+
+    fn rlp_fields(&self, rlp: &mut RlpStream) {
+        rlp.append(&self.chain_id);
+        rlp.append(&self.nonce);
+        rlp.append(&self.max_priority_fee_per_gas);
+        rlp.append(&self.max_fee_per_gas);
+        rlp.append(&self.gas);
+        rlp.append(&self.to);
+        rlp.append(&self.value);
+        rlp.append(&self.data);
+        rlp.append(&self.access_list);
+        rlp.append(&self.max_fee_per_blob_gas);
+        rlp.append_list(&self.blob_versioned_hashes);
+    }
+
+    /// Returns the hash that is signed by the sender
+    pub fn sighash(&self) -> H256 {
+        let mut rlp = RlpStream::new_list(Self::NUM_FIELDS);
+        self.rlp_fields(&mut rlp);
+        let mut encoded = vec![BLOB_TX_TYPE];
+        encoded.extend_from_slice(rlp.as_raw());
+        keccak256(encoded).into()
+    }
+
+    fn rlp_signed(&self, rlp: &mut RlpStream, signature: &Signature) {
+        rlp.begin_list(Self::NUM_FIELDS + 3);
+        self.rlp_fields(rlp);
+        // EIP-2718 transactions use the y parity instead of `v`
+        rlp.append(&normalize_v(signature.v));
+        rlp.append(&signature.r);
+        rlp.append(&signature.s);
+    }
+
+    /// Returns the hash of the signed transaction, which does not include the sidecar
+    pub fn hash(&self, signature: &Signature) -> H256 {
+        let mut rlp = RlpStream::new();
+        self.rlp_signed(&mut rlp, signature);
+        let mut encoded = vec![BLOB_TX_TYPE];
+        encoded.extend_from_slice(rlp.as_raw());
+        keccak256(encoded).into()
+    }
+
+    /// Returns the network encoding of the signed transaction with its sidecar, as expected by
+    /// `eth_sendRawTransaction`:
+    /// `0x03 || rlp([tx_payload_body, blobs, commitments, proofs])`
+    pub fn rlp_network(&self, signature: &Signature, sidecar: &BlobSidecar) -> Bytes {
+        let mut rlp = RlpStream::new_list(4);
+        self.rlp_signed(&mut rlp, signature);
+        rlp.append_list::<Bytes, _>(&sidecar.blobs);
+        rlp.append_list::<Bytes, _>(&sidecar.commitments);
+        rlp.append_list::<Bytes, _>(&sidecar.proofs);
+        let mut encoded = vec![BLOB_TX_TYPE];
+        encoded.extend_from_slice(rlp.as_raw());
+        encoded.into()
+    }
+}
+
+pub(crate) fn normalize_v(v: u64) -> u64 {
+    match v {
+        0 | 1 => v,
+        27 | 28 => v - 27,
+        v => (v - 1) % 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_roundtrip() {
+        let data =
+            (0..USABLE_BYTES_PER_BLOB + 100).map(|i| (i % 255 + 1) as u8).collect::<Vec<_>>();
+        let blobs = encode_blobs(&data).unwrap();
+        assert_eq!(blobs.len(), 2);
+        assert!(blobs.iter().all(|blob| blob.len() == BYTES_PER_BLOB));
+        assert!(blobs[0].chunks(BYTES_PER_FIELD_ELEMENT).all(|element| element[0] == 0));
+        assert_eq!(decode_blobs(blobs.iter().map(|blob| blob.as_slice())), data);
+    }
+
+    #[test]
+    fn rejects_too_much_data() {
+        assert!(encode_blobs(&vec![1u8; USABLE_BYTES_PER_BLOB * MAX_BLOBS_PER_TX + 1]).is_err());
+        assert!(encode_blobs(&[]).is_err());
+    }
+
+    #[test]
+    fn versioned_hash() {
+        let hash = kzg_to_versioned_hash(&[0u8; 48]);
+        assert_eq!(hash.as_bytes()[0], VERSIONED_HASH_VERSION_KZG);
+    }
+
+    #[test]
+    fn sidecar_with_bundled_trusted_setup() {
+        let settings = load_trusted_setup(None).unwrap();
+        let sidecar = BlobSidecar::new(encode_blobs(b"hello").unwrap(), &settings).unwrap();
+        assert_eq!(sidecar.blobs.len(), 1);
+        assert_eq!(sidecar.commitments[0].len(), 48);
+        assert_eq!(sidecar.proofs[0].len(), 48);
+
+        let commitment = c_kzg::Bytes48::from_bytes(&sidecar.commitments[0]).unwrap();
+        let proof = c_kzg::Bytes48::from_bytes(&sidecar.proofs[0]).unwrap();
+        let blob = Blob::from_bytes(&sidecar.blobs[0]).unwrap();
+        assert!(KzgProof::verify_blob_kzg_proof(&blob, &commitment, &proof, &settings).unwrap());
+    }
+}
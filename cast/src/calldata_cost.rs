@@ -0,0 +1,253 @@
+//! Calldata and L1 data cost calculation, see `cast calldata-cost`
+
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// Gas per zero byte of calldata
+const ZERO_BYTE_GAS: u64 = 4;
+/// Gas per non-zero byte of calldata before [EIP-2028](https://eips.ethereum.org/EIPS/eip-2028)
+const NON_ZERO_BYTE_GAS_FRONTIER: u64 = 68;
+/// Gas per non-zero byte of calldata since [EIP-2028](https://eips.ethereum.org/EIPS/eip-2028)
+const NON_ZERO_BYTE_GAS_ISTANBUL: u64 = 16;
+/// Gas per calldata token of the [EIP-7623](https://eips.ethereum.org/EIPS/eip-7623) floor
+const FLOOR_GAS_PER_TOKEN: u64 = 10;
+/// The size of the signature of a transaction, which OP stack chains add to the data size
+const OP_SIGNATURE_SIZE: u64 = 68;
+/// The constants of the Fjord linear regression of the compressed size, scaled by 1e6
+const FJORD_INTERCEPT: i64 = -42_585_600;
+const FJORD_FASTLZ_COEF: i64 = 836_500;
+const FJORD_MIN_TRANSACTION_SIZE: i64 = 100;
+
+/// The rules the gas cost of calldata can be calculated with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CalldataCostRule {
+    /// 4 gas per zero byte and 68 gas per non-zero byte, before EIP-2028
+    Frontier,
+    /// 4 gas per zero byte and 16 gas per non-zero byte, see EIP-2028
+    Istanbul,
+    /// The EIP-7623 floor of 10 gas per token, a zero byte is 1 token and a non-zero byte 4
+    /// tokens. This is the data cost of transactions that use little execution gas.
+    Prague,
+    /// The L1 gas of an OP stack transaction since the Fjord upgrade, which estimates the
+    /// compressed size of the transaction with FastLZ
+    OpFjord,
+    /// An estimate of the L1 gas of an Arbitrum transaction, 16 gas per byte of the compressed
+    /// data. Arbitrum compresses with brotli, which is approximated with FastLZ here.
+    Arbitrum,
+}
+
+impl CalldataCostRule {
+    /// All rules, in the order they are compared
+    pub const ALL: [CalldataCostRule; 5] = [
+        CalldataCostRule::Frontier,
+        CalldataCostRule::Istanbul,
+        CalldataCostRule::Prague,
+        CalldataCostRule::OpFjord,
+        CalldataCostRule::Arbitrum,
+    ];
+
+    /// Returns the name of the rule, as accepted by [FromStr]
+    pub fn name(&self) -> &'static str {
+        match self {
+            CalldataCostRule::Frontier => "frontier",
+            CalldataCostRule::Istanbul => "istanbul",
+            CalldataCostRule::Prague => "prague",
+            CalldataCostRule::OpFjord => "op-fjord",
+            CalldataCostRule::Arbitrum => "arbitrum",
+        }
+    }
+
+    /// Returns the gas the calldata costs under this rule
+    pub fn gas(&self, data: &[u8]) -> u64 {
+        let zeros = data.iter().filter(|b| **b == 0).count() as u64;
+        let non_zeros = data.len() as u64 - zeros;
+        match self {
+            CalldataCostRule::Frontier => {
+                zeros * ZERO_BYTE_GAS + non_zeros * NON_ZERO_BYTE_GAS_FRONTIER
+            }
+            CalldataCostRule::Istanbul => {
+                zeros * ZERO_BYTE_GAS + non_zeros * NON_ZERO_BYTE_GAS_ISTANBUL
+            }
+            CalldataCostRule::Prague => (zeros + non_zeros * 4) * FLOOR_GAS_PER_TOKEN,
+            CalldataCostRule::OpFjord => {
+                let fastlz_size = fastlz_compressed_len(data) as i64 + OP_SIGNATURE_SIZE as i64;
+                let estimated_size = (FJORD_INTERCEPT + FJORD_FASTLZ_COEF * fastlz_size)
+                    .max(FJORD_MIN_TRANSACTION_SIZE * 1_000_000) /
+                    1_000_000;
+                estimated_size as u64 * NON_ZERO_BYTE_GAS_ISTANBUL
+            }
+            CalldataCostRule::Arbitrum => {
+                fastlz_compressed_len(data) as u64 * NON_ZERO_BYTE_GAS_ISTANBUL
+            }
+        }
+    }
+}
+
+impl fmt::Display for CalldataCostRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for CalldataCostRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "frontier" | "pre-eip-2028" => Ok(CalldataCostRule::Frontier),
+            "istanbul" | "eip-2028" => Ok(CalldataCostRule::Istanbul),
+            "prague" | "eip-7623" => Ok(CalldataCostRule::Prague),
+            "op-fjord" | "op" | "optimism" => Ok(CalldataCostRule::OpFjord),
+            "arbitrum" | "arb" => Ok(CalldataCostRule::Arbitrum),
+            _ => Err(format!(
+                "unknown rule `{s}`, expected one of {}",
+                CalldataCostRule::ALL.map(|rule| rule.name()).join(", ")
+            )),
+        }
+    }
+}
+
+/// The cost of calldata under a rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalldataCost {
+    pub rule: CalldataCostRule,
+    pub gas: u64,
+}
+
+/// The size and byte counts of calldata and its cost under a set of rules
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalldataCostReport {
+    pub size: usize,
+    pub zero_bytes: usize,
+    pub non_zero_bytes: usize,
+    pub fastlz_size: usize,
+    pub costs: Vec<CalldataCost>,
+}
+
+impl CalldataCostReport {
+    pub fn new(data: &[u8], rules: &[CalldataCostRule]) -> Self {
+        let zero_bytes = data.iter().filter(|b| **b == 0).count();
+        Self {
+            size: data.len(),
+            zero_bytes,
+            non_zero_bytes: data.len() - zero_bytes,
+            fastlz_size: fastlz_compressed_len(data),
+            costs: rules
+                .iter()
+                .map(|rule| CalldataCost { rule: *rule, gas: rule.gas(data) })
+                .collect(),
+        }
+    }
+}
+
+/// Returns the length of the data compressed with FastLZ level 1
+///
+/// This is the compression OP stack chains estimate the L1 data size with since Fjord, a port of
+/// `FlzCompressLen` of op-geth.
+pub fn fastlz_compressed_len(data: &[u8]) -> usize {
+    let mut n = 0usize;
+    let mut table = vec![0usize; 8192];
+
+    let u24 = |i: usize| data[i] as u32 | (data[i + 1] as u32) << 8 | (data[i + 2] as u32) << 16;
+    let hash = |v: u32| ((2654435769u32.wrapping_mul(v) >> 19) & 0x1fff) as usize;
+    let literals = |n: &mut usize, r: usize| {
+        *n += 0x21 * (r / 0x20);
+        if r % 0x20 != 0 {
+            *n += r % 0x20 + 1;
+        }
+    };
+
+    let ip_limit = data.len().saturating_sub(13);
+    let mut anchor = 0;
+    let mut ip = 2;
+    while ip < ip_limit {
+        let mut reference;
+        loop {
+            let seq = u24(ip);
+            let h = hash(seq);
+            reference = table[h];
+            table[h] = ip;
+            let distance = ip - reference;
+            if ip >= ip_limit {
+                break
+            }
+            ip += 1;
+            if distance <= 0x1fff && seq == u24(reference) {
+                break
+            }
+        }
+        if ip >= ip_limit {
+            break
+        }
+        ip -= 1;
+        if ip > anchor {
+            literals(&mut n, ip - anchor);
+        }
+
+        // the length of the match, including the first mismatching byte
+        let (p, q) = (reference + 3, ip + 3);
+        let end = ip_limit + 9 - q;
+        let mut len = 0;
+        while len < end {
+            len += 1;
+            if data[p + len - 1] != data[q + len - 1] {
+                break
+            }
+        }
+
+        let l = len - 1;
+        n += 3 * (l / 262);
+        n += if l % 262 >= 6 { 3 } else { 2 };
+
+        ip += len;
+        table[hash(u24(ip))] = ip;
+        ip += 1;
+        table[hash(u24(ip))] = ip;
+        ip += 1;
+        anchor = ip;
+    }
+    literals(&mut n, data.len() - anchor);
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_calculate_calldata_gas() {
+        let data =
+            hex::decode("a9059cbb0000000000000000000000000000000000000000000000000000000000000001")
+                .unwrap();
+        // 4 non-zero bytes of the selector, 1 non-zero byte in the word and 31 zero bytes
+        assert_eq!(CalldataCostRule::Frontier.gas(&data), 31 * 4 + 5 * 68);
+        assert_eq!(CalldataCostRule::Istanbul.gas(&data), 31 * 4 + 5 * 16);
+        assert_eq!(CalldataCostRule::Prague.gas(&data), (31 + 5 * 4) * 10);
+        // small transactions are charged the minimum size
+        assert_eq!(CalldataCostRule::OpFjord.gas(&data), 100 * 16);
+    }
+
+    #[test]
+    fn can_estimate_fastlz_len() {
+        assert_eq!(fastlz_compressed_len(&[]), 0);
+        // literals only
+        assert_eq!(fastlz_compressed_len(&[1, 2, 3]), 4);
+        // long runs of the same byte compress well
+        let zeros = vec![0u8; 1024];
+        assert!(fastlz_compressed_len(&zeros) < 64);
+        let report = CalldataCostReport::new(&zeros, &CalldataCostRule::ALL);
+        assert_eq!(report.zero_bytes, 1024);
+        assert_eq!(report.costs.len(), CalldataCostRule::ALL.len());
+    }
+
+    #[test]
+    fn can_parse_rule() {
+        for rule in CalldataCostRule::ALL {
+            assert_eq!(rule.name().parse::<CalldataCostRule>().unwrap(), rule);
+        }
+        assert_eq!("eip-2028".parse::<CalldataCostRule>().unwrap(), CalldataCostRule::Istanbul);
+        assert!("foo".parse::<CalldataCostRule>().is_err());
+    }
+}
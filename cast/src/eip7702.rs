@@ -0,0 +1,125 @@
+//! EIP-7702 set code transaction support
+//!
+//! Like blob transactions, `ethers` can't build set code transactions, so they are encoded and
+//! signed here and published as raw transactions.
+
+use crate::blob::normalize_v;
+use ethers_core::{
+    types::{transaction::eip2930::AccessList, Address, Bytes, Signature, H256, U256, U64},
+    utils::{
+        keccak256,
+        rlp::{self, RlpStream},
+    },
+};
+use ethers_signers::{LocalWallet, Signer};
+use eyre::{Result, WrapErr};
+use foundry_evm::executor::delegation::{Authorization, SignedAuthorization, SET_CODE_TX_TYPE};
+
+/// Signs an authorization that delegates the account of the wallet to `delegate`
+pub fn sign_authorization(
+    wallet: &LocalWallet,
+    chain_id: u64,
+    delegate: Address,
+    nonce: u64,
+) -> Result<SignedAuthorization> {
+    let authorization =
+        Authorization { chain_id: chain_id.into(), address: delegate, nonce: nonce.into() };
+    let signature = wallet.sign_hash(authorization.signature_hash())?;
+    Ok(authorization.into_signed(signature))
+}
+
+/// Decodes an authorization that was signed elsewhere from its hex encoded rlp
+pub fn decode_authorization(hex_rlp: &str) -> Result<SignedAuthorization> {
+    let bytes = hex::decode(hex_rlp.strip_prefix("0x").unwrap_or(hex_rlp))
+        .wrap_err("signed authorization is not valid hex")?;
+    rlp::decode(&bytes).map_err(|err| eyre::eyre!("invalid signed authorization: {err}"))
+}
+
+/// An EIP-7702 transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetCodeTransaction {
+    pub chain_id: U64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: U256,
+    /// Set code transactions can't create contracts
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: AccessList,
+    pub authorization_list: Vec<SignedAuthorization>,
+}
+
+impl SetCodeTransaction {
+    const NUM_FIELDS: usize = 10;
+
+    fn rlp_fields(&self, rlp: &mut RlpStream) {
+        rlp.append(&self.chain_id);
+        rlp.append(&self.nonce);
+        rlp.append(&self.max_priority_fee_per_gas);
+        rlp.append(&self.max_fee_per_gas);
+        rlp.append(&self.gas);
+        rlp.append(&self.to);
+        rlp.append(&self.value);
+        rlp.append(&self.data);
+        rlp.append(&self.access_list);
+        rlp.append_list(&self.authorization_list);
+    }
+
+    /// Returns the hash that is signed by the sender
+    pub fn sighash(&self) -> H256 {
+        let mut rlp = RlpStream::new_list(Self::NUM_FIELDS);
+        self.rlp_fields(&mut rlp);
+        let mut encoded = vec![SET_CODE_TX_TYPE];
+        encoded.extend_from_slice(rlp.as_raw());
+        keccak256(encoded).into()
+    }
+
+    /// Returns the network encoding of the signed transaction, as expected by
+    /// `eth_sendRawTransaction`: `0x04 || rlp([...fields, y_parity, r, s])`
+    pub fn rlp_signed(&self, signature: &Signature) -> Bytes {
+        let mut rlp = RlpStream::new_list(Self::NUM_FIELDS + 3);
+        self.rlp_fields(&mut rlp);
+        rlp.append(&normalize_v(signature.v));
+        rlp.append(&signature.r);
+        rlp.append(&signature.s);
+        let mut encoded = vec![SET_CODE_TX_TYPE];
+        encoded.extend_from_slice(rlp.as_raw());
+        encoded.into()
+    }
+
+    /// Returns the hash of the signed transaction
+    pub fn hash(&self, signature: &Signature) -> H256 {
+        keccak256(self.rlp_signed(signature)).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_sign_authorizations() {
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let delegate = Address::repeat_byte(0x11);
+        let authorization = sign_authorization(&wallet, 1, delegate, 7).unwrap();
+        assert_eq!(authorization.recover_authority().unwrap(), wallet.address());
+
+        let encoded = format!("0x{}", hex::encode(rlp::encode(&authorization)));
+        assert_eq!(decode_authorization(&encoded).unwrap(), authorization);
+        assert!(decode_authorization("0x1234").is_err());
+
+        let tx = SetCodeTransaction {
+            chain_id: 1u64.into(),
+            to: wallet.address(),
+            authorization_list: vec![authorization],
+            ..Default::default()
+        };
+        let signature = wallet.sign_hash(tx.sighash()).unwrap();
+        let raw = tx.rlp_signed(&signature);
+        assert_eq!(raw[0], SET_CODE_TX_TYPE);
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), wallet.address());
+    }
+}
@@ -8,9 +8,9 @@ use chrono::NaiveDateTime;
 use ethers_core::{
     abi::{
         token::{LenientTokenizer, Tokenizer},
-        Function, HumanReadableParser, ParamType, RawAbi, Token,
+        Abi, Function, HumanReadableParser, ParamType, RawAbi, Token,
     },
-    types::{Chain, *},
+    types::{transaction::eip2718::TypedTransaction, Chain, *},
     utils::{
         self, format_bytes32_string, format_units, get_contract_address, keccak256,
         parse_bytes32_string, parse_units, rlp, Units,
@@ -19,8 +19,13 @@ use ethers_core::{
 use ethers_etherscan::{errors::EtherscanError, Client};
 use ethers_providers::{Middleware, PendingTransaction};
 use evm_disassembler::{disassemble_bytes, disassemble_str, format_operations};
-use eyre::{Context, Result};
-use foundry_common::{abi::encode_args, fmt::*, TransactionReceiptWithRevertReason};
+use eyre::{Context, ContextCompat, Result};
+use foundry_common::{
+    abi::{encode_args, find_source},
+    fmt::*,
+    TransactionReceiptWithRevertReason,
+};
+use foundry_config::with_key_rotation;
 pub use foundry_evm::*;
 pub use rusoto_core::{
     credential::ChainProvider as AwsChainProvider, region::Region as AwsRegion,
@@ -28,19 +33,30 @@ pub use rusoto_core::{
 };
 pub use rusoto_kms::KmsClient;
 use rustc_hex::{FromHexIter, ToHex};
+use state_override::StateOverride;
 use std::{path::PathBuf, str::FromStr};
 pub use tx::TxBuilder;
 use tx::{TxBuilderOutput, TxBuilderPeekOutput};
 
+pub mod abi_diff;
+pub mod abi_json;
 pub mod base;
+pub mod blob;
+pub mod calldata_cost;
+pub mod eip7702;
 pub mod errors;
+pub mod proof;
 mod rlp_converter;
+pub mod state_override;
 mod tx;
+pub mod user_op;
 
 // TODO: CastContract with common contract initializers? Same for CastProviders?
 
 pub struct Cast<M> {
     provider: M,
+    /// The chain id the extra fields of blocks, transactions and receipts are decoded for
+    chain_id: Option<u64>,
 }
 
 impl<M: Middleware> Cast<M>
@@ -62,7 +78,27 @@ where
     /// # }
     /// ```
     pub fn new(provider: M) -> Self {
-        Self { provider }
+        Self { provider, chain_id: None }
+    }
+
+    /// Sets the chain whose extra fields of blocks, transactions and receipts are decoded
+    ///
+    /// By default, the chain id of the provider is used.
+    pub fn with_chain_id(mut self, chain_id: Option<u64>) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Returns the L2 chain the provider is connected to if there are extra fields to decode
+    async fn l2_chain(&self, other: &OtherFields) -> Option<L2Chain> {
+        if other.is_empty() {
+            return None
+        }
+        let chain_id = match self.chain_id {
+            Some(chain_id) => chain_id,
+            None => self.provider.get_chainid().await.ok()?.as_u64(),
+        };
+        L2Chain::from_chain_id(chain_id)
     }
 
     /// Makes a read-only call to the specified address
@@ -97,42 +133,55 @@ where
     ) -> Result<String> {
         let (tx, func) = builder_output;
         let res = self.provider.call(&tx, block).await?;
+        self.format_call_output(&tx, block, res, func).await
+    }
 
-        let mut decoded = vec![];
-
-        if let Some(func) = func {
-            // decode args into tokens
-            decoded = match func.decode_output(res.as_ref()) {
-                Ok(decoded) => decoded,
-                Err(err) => {
-                    // ensure the address is a contract
-                    if res.is_empty() {
-                        // check that the recipient is a contract that can be called
-                        if let Some(NameOrAddress::Address(addr)) = tx.to() {
-                            let code = self.provider.get_code(*addr, block).await?;
-                            if code.is_empty() {
-                                eyre::bail!("Contract {:?} does not exist", addr)
-                            }
-                        }
-                    }
-                    return Err(err).wrap_err(
-                        "could not decode output. did you specify the wrong function return data type perhaps?"
-                    );
+    /// Makes a read-only call to the specified address with the given state overrides applied,
+    /// see [Self::call()]
+    ///
+    /// Fails if the node does not support state overrides, see
+    /// [state_override::is_state_override_unsupported()]
+    pub async fn call_with_state_override(
+        &self,
+        builder_output: TxBuilderOutput,
+        block: Option<BlockId>,
+        overrides: &StateOverride,
+    ) -> Result<String> {
+        let (tx, func) = builder_output;
+        let res: Bytes = self
+            .provider
+            .provider()
+            .request(
+                "eth_call",
+                (&tx, block.unwrap_or_else(|| BlockNumber::Latest.into()), overrides),
+            )
+            .await?;
+        self.format_call_output(&tx, block, res, func).await
+    }
+
+    /// Decodes the output `res` of the call `tx` to `func`
+    pub async fn format_call_output(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+        res: Bytes,
+        func: Option<Function>,
+    ) -> Result<String> {
+        let err = match SimpleCast::decode_call_output(&res, func.as_ref()) {
+            Ok(output) => return Ok(output),
+            Err(err) => err,
+        };
+        // ensure the address is a contract
+        if res.is_empty() {
+            // check that the recipient is a contract that can be called
+            if let Some(NameOrAddress::Address(addr)) = tx.to() {
+                let code = self.provider.get_code(*addr, block).await?;
+                if code.is_empty() {
+                    eyre::bail!("Contract {:?} does not exist", addr)
                 }
-            };
+            }
         }
-        // handle case when return type is not specified
-        Ok(if decoded.is_empty() {
-            format!("{res}\n")
-        } else {
-            // seth compatible user-friendly return type conversions
-            decoded
-                .iter()
-                .map(TokenDisplay)
-                .map(|token| token.to_string())
-                .collect::<Vec<_>>()
-                .join("\n")
-        })
+        Err(err)
     }
 
     /// Generates an access list for the specified transaction
@@ -301,6 +350,22 @@ where
         Ok::<_, eyre::Error>(res)
     }
 
+    /// Estimates the gas of the transaction with the given state overrides applied, see
+    /// [Self::estimate()]
+    pub async fn estimate_with_state_override(
+        &self,
+        builder_output: TxBuilderPeekOutput<'_>,
+        overrides: &StateOverride,
+    ) -> Result<U256> {
+        let (tx, _) = builder_output;
+        let res = self
+            .provider
+            .provider()
+            .request("eth_estimateGas", (tx, BlockNumber::Latest, overrides))
+            .await?;
+        Ok(res)
+    }
+
     /// # Example
     ///
     /// ```no_run
@@ -330,12 +395,12 @@ where
                 .await?
                 .ok_or_else(|| eyre::eyre!("block {:?} not found", block))?;
             if let Some(ref field) = field {
-                get_pretty_block_attr(&block, field)
+                get_pretty_block_attr(&block, field, self.l2_chain(&block.other).await)
                     .unwrap_or_else(|| format!("{field} is not a valid block field"))
             } else if to_json {
                 serde_json::to_value(&block).unwrap().to_string()
             } else {
-                block.pretty()
+                block.pretty_l2(self.l2_chain(&block.other).await)
             }
         } else {
             let block = self
@@ -348,13 +413,13 @@ where
                 if field == "transactions" {
                     "use --full to view transactions".to_string()
                 } else {
-                    get_pretty_block_attr(&block, field)
+                    get_pretty_block_attr(&block, field, self.l2_chain(&block.other).await)
                         .unwrap_or_else(|| format!("{field} is not a valid block field"))
                 }
             } else if to_json {
                 serde_json::to_value(&block).unwrap().to_string()
             } else {
-                block.pretty()
+                block.pretty_l2(self.l2_chain(&block.other).await)
             }
         };
 
@@ -645,13 +710,13 @@ where
             .ok_or_else(|| eyre::eyre!("tx not found: {:?}", tx_hash))?;
 
         Ok(if let Some(ref field) = field {
-            get_pretty_tx_attr(&tx, field)
+            get_pretty_tx_attr(&tx, field, self.l2_chain(&tx.other).await)
                 .ok_or_else(|| eyre::eyre!("invalid tx field: {}", field))?
         } else if to_json {
             // to_value first to sort json object keys
             serde_json::to_value(&tx)?.to_string()
         } else {
-            tx.pretty()
+            tx.pretty_l2(self.l2_chain(&tx.other).await)
         })
     }
 
@@ -705,16 +770,143 @@ where
         let _ = receipt.update_revert_reason(&self.provider).await;
 
         Ok(if let Some(ref field) = field {
-            get_pretty_tx_receipt_attr(&receipt, field)
+            get_pretty_tx_receipt_attr(&receipt, field, self.l2_chain(&receipt.receipt.other).await)
                 .ok_or_else(|| eyre::eyre!("invalid receipt field: {}", field))?
         } else if to_json {
             // to_value first to sort json object keys
             serde_json::to_value(&receipt)?.to_string()
         } else {
-            receipt.pretty()
+            receipt.pretty_l2(self.l2_chain(&receipt.receipt.other).await)
         })
     }
 
+    /// Returns the `eth_getProof` response of the account and its storage `slots` as JSON
+    ///
+    /// If `verify` is set, the proofs are verified against the `state_root`, which defaults to the
+    /// state root of the block.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::H256;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let addr = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+    /// let proof = cast.proof(addr, vec![H256::zero()], None, true, None).await?;
+    /// println!("{}", proof);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn proof<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        who: T,
+        slots: Vec<H256>,
+        block: Option<BlockId>,
+        verify: bool,
+        state_root: Option<H256>,
+    ) -> Result<String> {
+        let (block, state_root) = match (verify, state_root) {
+            (_, Some(state_root)) => (block, Some(state_root)),
+            (true, None) => {
+                // pin the block, so the proof is fetched for the state root of the same block
+                let header = self
+                    .provider
+                    .get_block(block.unwrap_or_else(|| BlockNumber::Latest.into()))
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("block not found"))?;
+                let number = header.number.ok_or_else(|| eyre::eyre!("block is pending"))?;
+                (Some(BlockId::Number(number.into())), Some(header.state_root))
+            }
+            (false, None) => (block, None),
+        };
+
+        let response = self.provider.get_proof(who, slots.clone(), block).await?;
+        if let Some(state_root) = state_root {
+            proof::verify_account_proof(state_root, &slots, &response)?;
+        }
+        Ok(serde_json::to_string(&response)?)
+    }
+
+    /// Returns the Merkle-Patricia proof of the inclusion of the receipt of the transaction in the
+    /// receipts trie of its block as JSON
+    ///
+    /// If no `proof` is given, it's built from all receipts of the block. Either way the proof is
+    /// verified against the receipts root of the block.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let tx_hash = "0xf8d1713ea15a81482958fb7ddf884baee8d3bcc478c5f2f604e008dc788ee4fc";
+    /// let proof = cast.receipt_proof(tx_hash.to_string(), vec![]).await?;
+    /// println!("{}", proof);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn receipt_proof(&self, tx_hash: String, proof: Vec<Bytes>) -> Result<String> {
+        let tx_hash = H256::from_str(&tx_hash).wrap_err("invalid tx hash")?;
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("tx not found: {:?}", tx_hash))?;
+        let block_hash =
+            receipt.block_hash.ok_or_else(|| eyre::eyre!("tx is pending: {:?}", tx_hash))?;
+        let block = self
+            .provider
+            .get_block(block_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("block not found: {:?}", block_hash))?;
+
+        let proof = if proof.is_empty() {
+            let receipts = futures::future::try_join_all(
+                block.transactions.iter().map(|tx| self.provider.get_transaction_receipt(*tx)),
+            )
+            .await?
+            .into_iter()
+            .zip(&block.transactions)
+            .map(|(tx_receipt, tx)| {
+                tx_receipt
+                    .map(|tx_receipt| proof::encode_receipt(&tx_receipt))
+                    .ok_or_else(|| eyre::eyre!("receipt not found: {:?}", tx))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+            let (root, proof) =
+                proof::ordered_trie_proof(&receipts, receipt.transaction_index.as_usize());
+            if root != block.receipts_root {
+                eyre::bail!(
+                    "The receipts of block {:?} don't match its receipts root {:?}, got {:?}",
+                    block_hash,
+                    block.receipts_root,
+                    root
+                )
+            }
+            proof
+        } else {
+            proof
+        };
+        proof::verify_receipt_proof(block.receipts_root, &receipt, &proof)?;
+
+        let value = serde_json::json!({
+            "blockHash": block_hash,
+            "receiptsRoot": block.receipts_root,
+            "transactionIndex": receipt.transaction_index,
+            "receipt": Bytes::from(proof::encode_receipt(&receipt)),
+            "proof": proof,
+        });
+        Ok(serde_json::to_string(&value)?)
+    }
+
     /// Perform a raw JSON-RPC request
     ///
     /// # Example
@@ -776,15 +968,52 @@ pub struct InterfaceSource {
 }
 
 // Local is a path to the directory containing the ABI files
-// In case of etherscan, ABI is fetched from the address on the chain
+// In case of etherscan, ABI is fetched from the address on the chain, the requests rotate through
+// the clients of every API key, see `Config::get_etherscan_clients()`
 pub enum AbiPath {
     Local { path: String, name: Option<String> },
-    Etherscan { address: Address, chain: Chain, api_key: String },
+    Etherscan { address: Address, chain: Chain, clients: Vec<Client> },
 }
 
 pub struct SimpleCast;
 
 impl SimpleCast {
+    /// Decodes the output `res` of a call to `func`, without checking the callee like
+    /// [Cast::format_call_output]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cast::SimpleCast;
+    /// # use ethers_core::{abi::{encode, Token}, types::Bytes};
+    /// # use foundry_common::abi::get_func;
+    /// let func = get_func("balance()(uint256)")?;
+    /// let res = Bytes::from(encode(&[Token::Uint(5u64.into())]));
+    /// assert_eq!(SimpleCast::decode_call_output(&res, Some(&func))?, "5");
+    /// assert_eq!(SimpleCast::decode_call_output(&Bytes::from(vec![1]), None)?, "0x01\n");
+    /// # Ok::<(), eyre::Report>(())
+    /// ```
+    pub fn decode_call_output(res: &Bytes, func: Option<&Function>) -> Result<String> {
+        let decoded = match func {
+            Some(func) => func.decode_output(res.as_ref()).wrap_err(
+                "could not decode output. did you specify the wrong function return data type perhaps?",
+            )?,
+            None => vec![],
+        };
+        // handle case when return type is not specified
+        Ok(if decoded.is_empty() {
+            format!("{res}\n")
+        } else {
+            // seth compatible user-friendly return type conversions
+            decoded
+                .iter()
+                .map(TokenDisplay)
+                .map(|token| token.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
     /// Returns the maximum value of the given integer type
     ///
     /// # Example
@@ -1347,6 +1576,45 @@ impl SimpleCast {
         Ok(format!("0x{encoded}"))
     }
 
+    /// Performs ABI encoding of the arguments given as JSON, see [abi_json]. Does not include the
+    /// function selector in the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///     assert_eq!(
+    ///         "0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000001",
+    ///         Cast::abi_encode_json("f((uint a, bool b) s)", r#"{"s": {"a": 1, "b": true}}"#)?
+    ///     );
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn abi_encode_json(sig: &str, json: &str) -> Result<String> {
+        Ok(format!("0x{}", hex::encode(abi_json::encode(sig, json)?)))
+    }
+
+    /// Decodes ABI-encoded output data, or input data if `input` is set, to pretty printed JSON,
+    /// see [abi_json]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///     let data = "0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000001";
+    ///     let decoded = Cast::abi_decode_json("f()((uint a, bool b) s)", data, false)?;
+    ///     assert_eq!(decoded, "{\n  \"s\": {\n    \"a\": \"1\",\n    \"b\": true\n  }\n}");
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn abi_decode_json(sig: &str, data: &str, input: bool) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&abi_json::decode(sig, data, input)?)?)
+    }
+
     /// Performs ABI encoding to produce the hexadecimal calldata with the given arguments.
     ///
     /// # Example
@@ -1395,22 +1663,13 @@ impl SimpleCast {
 
                 (vec![abi], vec![name.unwrap_or_else(|| "Interface".to_owned())])
             }
-            AbiPath::Etherscan { address, chain, api_key } => {
-                let client = Client::new(chain, api_key)?;
-
+            AbiPath::Etherscan { address, chain, clients } => {
                 // get the source
-                let source = match client.contract_source_code(address).await {
-                    Ok(source) => source,
-                    Err(EtherscanError::InvalidApiKey) => {
-                        eyre::bail!("Invalid Etherscan API key. Did you set it correctly? You may be using an API key for another Etherscan API chain (e.g. Etherscan API key for Polygonscan).")
-                    }
-                    Err(EtherscanError::ContractCodeNotVerified(address)) => {
-                        eyre::bail!("Contract source code at {:?} on {} not verified. Maybe you have selected the wrong chain?", address, chain)
-                    }
-                    Err(err) => {
-                        eyre::bail!(err)
-                    }
-                };
+                let source = with_key_rotation(&clients, |client| async move {
+                    client.contract_source_code(address).await
+                })
+                .await
+                .map_err(|err| etherscan_error(err.into(), chain))?;
 
                 let names = source
                     .items
@@ -1433,6 +1692,43 @@ impl SimpleCast {
             .collect::<Result<Vec<InterfaceSource>>>()
     }
 
+    /// Returns the ABI of a local file, which is either a JSON ABI or an artifact with an `abi`
+    /// key, or of a verified contract on Etherscan
+    ///
+    /// The ABI of the implementation is returned if the contract on Etherscan is a proxy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use cast::{AbiPath, SimpleCast as Cast};
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let path = AbiPath::Local {
+    ///     path: "utils/testdata/interfaceTestABI.json".to_owned(),
+    ///     name: None,
+    /// };
+    /// let abi = Cast::load_abi(path).await?;
+    /// println!("{} functions", abi.functions().count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load_abi(address_or_path: AbiPath) -> Result<Abi> {
+        match address_or_path {
+            AbiPath::Local { path, .. } => {
+                let file = std::fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("unable to read abi file {path}"))?;
+                let mut json: serde_json::Value = serde_json::from_str(&file)?;
+                let json = if !json["abi"].is_null() { json["abi"].take() } else { json };
+                serde_json::from_value(json).wrap_err("unable to parse json ABI from file")
+            }
+            AbiPath::Etherscan { address, chain, clients } => {
+                let source =
+                    find_source(clients, address).await.map_err(|err| etherscan_error(err, chain))?;
+                let metadata = source.items.first().wrap_err("Etherscan returned no data")?;
+                Ok(metadata.abi()?)
+            }
+        }
+    }
+
     /// Prints the slot number for the specified mapping type and input data
     /// Uses abi_encode to pad the data to 32 bytes.
     /// For value types v, slot number of v is keccak256(concat(h(v) , p)) where h is the padding
@@ -1585,29 +1881,33 @@ impl SimpleCast {
 
     /// Fetches source code of verified contracts from etherscan.
     ///
+    /// The requests rotate through the `clients` if one is rate limited, see
+    /// `Config::get_etherscan_clients()`.
+    ///
     /// # Example
     ///
     /// ```
     /// # use cast::SimpleCast as Cast;
     /// # use ethers_core::types::Chain;
+    /// # use foundry_config::Config;
     ///
     /// # async fn foo() -> eyre::Result<()> {
+    ///     let clients = Config::default().get_etherscan_clients(Chain::Mainnet)?;
     ///     assert_eq!(
     ///             "/*
     ///             - Bytecode Verification performed was compared on second iteration -
     ///             This file is part of the DAO.....",
-    ///         Cast::etherscan_source(Chain::Mainnet, "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(), "<etherscan_api_key>".to_string()).await.unwrap().as_str()
+    ///         Cast::etherscan_source(&clients, "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string()).await.unwrap().as_str()
     ///     );
     /// #    Ok(())
     /// # }
     /// ```
-    pub async fn etherscan_source(
-        chain: Chain,
-        contract_address: String,
-        etherscan_api_key: String,
-    ) -> Result<String> {
-        let client = Client::new(chain, etherscan_api_key)?;
-        let metadata = client.contract_source_code(contract_address.parse()?).await?;
+    pub async fn etherscan_source(clients: &[Client], contract_address: String) -> Result<String> {
+        let address: Address = contract_address.parse()?;
+        let metadata = with_key_rotation(clients, |client| async move {
+            client.contract_source_code(address).await
+        })
+        .await?;
         Ok(metadata.source_code())
     }
 
@@ -1619,21 +1919,25 @@ impl SimpleCast {
     /// ```
     /// # use cast::SimpleCast as Cast;
     /// # use ethers_core::types::Chain;
+    /// # use foundry_config::Config;
     /// # use std::path::PathBuf;
     ///
     /// # async fn expand() -> eyre::Result<()> {
-    ///      Cast::expand_etherscan_source_to_directory(Chain::Mainnet, "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(), "<etherscan_api_key>".to_string(), PathBuf::from("output_dir")).await?;
+    ///      let clients = Config::default().get_etherscan_clients(Chain::Mainnet)?;
+    ///      Cast::expand_etherscan_source_to_directory(&clients, "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(), PathBuf::from("output_dir")).await?;
     /// #    Ok(())
     /// # }
     /// ```
     pub async fn expand_etherscan_source_to_directory(
-        chain: Chain,
+        clients: &[Client],
         contract_address: String,
-        etherscan_api_key: String,
         output_directory: PathBuf,
     ) -> eyre::Result<()> {
-        let client = Client::new(chain, etherscan_api_key)?;
-        let meta = client.contract_source_code(contract_address.parse()?).await?;
+        let address: Address = contract_address.parse()?;
+        let meta = with_key_rotation(clients, |client| async move {
+            client.contract_source_code(address).await
+        })
+        .await?;
         let source_tree = meta.source_tree();
         source_tree.write_to(&output_directory)?;
         Ok(())
@@ -1658,6 +1962,19 @@ impl SimpleCast {
     }
 }
 
+/// Replaces the errors of requests for a contract's source on Etherscan with helpful messages
+fn etherscan_error(err: eyre::Report, chain: Chain) -> eyre::Report {
+    match err.downcast_ref::<EtherscanError>() {
+        Some(EtherscanError::InvalidApiKey) => {
+            eyre::eyre!("Invalid Etherscan API key. Did you set it correctly? You may be using an API key for another Etherscan API chain (e.g. Etherscan API key for Polygonscan).")
+        }
+        Some(EtherscanError::ContractCodeNotVerified(address)) => {
+            eyre::eyre!("Contract source code at {:?} on {} not verified. Maybe you have selected the wrong chain?", address, chain)
+        }
+        _ => err,
+    }
+}
+
 fn strip_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
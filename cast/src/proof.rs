@@ -0,0 +1,414 @@
+//! Verification of Merkle-Patricia proofs
+//!
+//! Supports the account and storage proofs returned by `eth_getProof` and proofs of the inclusion
+//! of a receipt in the receipts trie of a block.
+
+use ethers_core::{
+    types::{Bytes, EIP1186ProofResponse, TransactionReceipt, H256, U256},
+    utils::{
+        keccak256,
+        rlp::{self, Rlp, RlpStream},
+    },
+};
+use eyre::{Context, Result};
+
+/// The root of an empty trie, `keccak256(rlp(""))`
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The code hash of accounts without code, `keccak256("")`
+pub const EMPTY_CODE_HASH: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// A reference to a trie node, nodes shorter than 32 bytes are embedded in their parent
+enum NodeRef<'a> {
+    Hash(H256),
+    Inline(&'a [u8]),
+}
+
+/// Verifies the `proof` of the `key` against the `root` of a trie
+///
+/// Returns the value of the `key`, or `None` if the proof shows that the trie does not contain
+/// the key. Fails if the proof is invalid.
+pub fn verify_proof(root: H256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>> {
+    if proof.is_empty() && root == EMPTY_ROOT {
+        return Ok(None)
+    }
+
+    let nibbles = to_nibbles(key);
+    let mut nibbles = nibbles.as_slice();
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(root);
+    loop {
+        let node = match next {
+            NodeRef::Hash(hash) => {
+                let node = nodes.next().ok_or_else(|| {
+                    eyre::eyre!("The proof is incomplete, the node {hash:?} is missing")
+                })?;
+                if H256(keccak256(node)) != hash {
+                    eyre::bail!("The proof is invalid, expected a node with hash {hash:?}")
+                }
+                node.as_ref()
+            }
+            NodeRef::Inline(node) => node,
+        };
+
+        let node = Rlp::new(node);
+        let child = match node.item_count().wrap_err("Failed to decode a proof node")? {
+            // branch node
+            17 => match nibbles.split_first() {
+                Some((nibble, rest)) => {
+                    nibbles = rest;
+                    node.at(*nibble as usize)?
+                }
+                None => return value_of(&node.at(16)?),
+            },
+            // extension or leaf node
+            2 => {
+                let (path, is_leaf) = decode_path(node.at(0)?.data()?)?;
+                if is_leaf {
+                    return if nibbles == path.as_slice() {
+                        value_of(&node.at(1)?)
+                    } else {
+                        Ok(None)
+                    }
+                }
+                match nibbles.strip_prefix(path.as_slice()) {
+                    Some(rest) => nibbles = rest,
+                    None => return Ok(None),
+                }
+                node.at(1)?
+            }
+            count => eyre::bail!("The proof is invalid, found a node with {count} items"),
+        };
+
+        next = if child.is_empty() {
+            return Ok(None)
+        } else if child.is_list() {
+            NodeRef::Inline(child.as_raw())
+        } else {
+            let data = child.data()?;
+            if data.len() != 32 {
+                eyre::bail!("The proof is invalid, found a node reference of {} bytes", data.len())
+            }
+            NodeRef::Hash(H256::from_slice(data))
+        };
+    }
+}
+
+/// Verifies the account proof and all storage proofs of the `eth_getProof` response against the
+/// `state_root`
+///
+/// The storage proofs are verified for the `slots`, in the order they were requested.
+pub fn verify_account_proof(
+    state_root: H256,
+    slots: &[H256],
+    response: &EIP1186ProofResponse,
+) -> Result<()> {
+    let account =
+        verify_proof(state_root, keccak256(response.address).as_slice(), &response.account_proof)
+            .wrap_err_with(|| format!("Invalid account proof of {:?}", response.address))?;
+
+    let expected = encode_account(response);
+    let is_empty = response.nonce.is_zero() &&
+        response.balance.is_zero() &&
+        response.code_hash == EMPTY_CODE_HASH &&
+        response.storage_hash == EMPTY_ROOT;
+    match account {
+        Some(account) if account != expected => eyre::bail!(
+            "Invalid account proof of {:?}, the proven account does not match the response",
+            response.address
+        ),
+        None if !is_empty => eyre::bail!(
+            "Invalid account proof of {:?}, the account does not exist at the state root",
+            response.address
+        ),
+        _ => {}
+    }
+
+    if slots.len() != response.storage_proof.len() {
+        eyre::bail!(
+            "Expected {} storage proofs, the response contains {}",
+            slots.len(),
+            response.storage_proof.len()
+        )
+    }
+    for (slot, storage) in slots.iter().zip(&response.storage_proof) {
+        verify_storage_proof(response.storage_hash, *slot, storage.value, &storage.proof)
+            .wrap_err_with(|| format!("Invalid storage proof of slot {slot:?}"))?;
+    }
+    Ok(())
+}
+
+/// Verifies that the `slot` of the storage trie with the `storage_root` holds the `value`
+pub fn verify_storage_proof(
+    storage_root: H256,
+    slot: H256,
+    value: U256,
+    proof: &[Bytes],
+) -> Result<()> {
+    let proven = verify_proof(storage_root, &keccak256(slot), proof)?;
+    let proven = match proven {
+        Some(proven) => rlp::decode::<U256>(&proven)?,
+        None => U256::zero(),
+    };
+    if proven != value {
+        eyre::bail!("the proven value is {proven}, the response contains {value}")
+    }
+    Ok(())
+}
+
+/// Returns the RLP encoded account as it is stored in the state trie
+fn encode_account(account: &EIP1186ProofResponse) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&account.nonce.as_u64());
+    stream.append(&account.balance);
+    stream.append(&account.storage_hash);
+    stream.append(&account.code_hash);
+    stream.out().to_vec()
+}
+
+/// Returns the receipt as it is stored in the receipts trie of a block
+///
+/// Typed receipts are prefixed with their transaction type.
+pub fn encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    match receipt.status {
+        Some(status) => stream.append(&status.as_u64()),
+        // receipts before byzantium contain the intermediate state root instead of a status
+        None => stream.append(&receipt.root.unwrap_or_default()),
+    };
+    stream.append(&receipt.cumulative_gas_used);
+    stream.append(&receipt.logs_bloom.as_bytes().to_vec());
+    stream.begin_list(receipt.logs.len());
+    for log in &receipt.logs {
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list::<H256, H256>(&log.topics);
+        stream.append(&log.data.to_vec());
+    }
+
+    let mut encoded = Vec::new();
+    match receipt.transaction_type {
+        Some(ty) if !ty.is_zero() => encoded.push(ty.as_u64() as u8),
+        _ => {}
+    }
+    encoded.extend_from_slice(&stream.out());
+    encoded
+}
+
+/// Builds the trie of the `values` by their RLP encoded index, as the transactions and receipts
+/// tries of a block are built
+///
+/// Returns the root of the trie and the proof of the value at `index`.
+pub fn ordered_trie_proof(values: &[Vec<u8>], index: usize) -> (H256, Vec<Bytes>) {
+    let entries = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (rlp::encode(&(i as u64)).to_vec(), value.as_slice()))
+        .collect::<Vec<_>>();
+    trie_proof(&entries, &rlp::encode(&(index as u64)))
+}
+
+/// Builds the trie of the key value pairs, the keys must be unique
+///
+/// Returns the root of the trie and the proof of the `key`.
+pub fn trie_proof(entries: &[(Vec<u8>, &[u8])], key: &[u8]) -> (H256, Vec<Bytes>) {
+    let mut items =
+        entries.iter().map(|(key, value)| (to_nibbles(key), *value)).collect::<Vec<_>>();
+    items.sort();
+    if items.is_empty() {
+        return (EMPTY_ROOT, vec![])
+    }
+
+    let mut proof = vec![];
+    let root = encode_node(&items, 0, Some(&to_nibbles(key)), &mut proof);
+    (H256(keccak256(root)), proof)
+}
+
+/// Returns the RLP encoding of the node of the sorted `items` whose keys share the first `depth`
+/// nibbles
+///
+/// The nodes on the path of the `target` key that are referenced by their hash are added to the
+/// `proof`, starting with the root, which is always referenced by its hash.
+fn encode_node(
+    items: &[(Vec<u8>, &[u8])],
+    depth: usize,
+    target: Option<&[u8]>,
+    proof: &mut Vec<Bytes>,
+) -> Vec<u8> {
+    let position = proof.len();
+
+    let node = if items.len() == 1 {
+        let (key, value) = &items[0];
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&encode_path(&key[depth..], true));
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    } else {
+        let (first, last) = (&items[0].0, &items[items.len() - 1].0);
+        let shared = first[depth..].iter().zip(&last[depth..]).take_while(|(a, b)| a == b).count();
+        if shared > 0 {
+            let path = &first[depth..depth + shared];
+            let target = target.filter(|target| target.get(depth..depth + shared) == Some(path));
+            let child = encode_node(items, depth + shared, target, proof);
+            let mut stream = RlpStream::new_list(2);
+            stream.append(&encode_path(path, false));
+            append_child(&mut stream, &child);
+            stream.out().to_vec()
+        } else {
+            let mut stream = RlpStream::new_list(17);
+            // a key that ends at this node sorts first
+            let (value, mut rest) = match items.split_first() {
+                Some(((key, value), rest)) if key.len() == depth => (Some(value.to_vec()), rest),
+                _ => (None, items),
+            };
+            for nibble in 0..16u8 {
+                let count = rest.iter().take_while(|(key, _)| key[depth] == nibble).count();
+                let (children, remaining) = rest.split_at(count);
+                rest = remaining;
+                if children.is_empty() {
+                    stream.append_empty_data();
+                } else {
+                    let target = target.filter(|target| target.get(depth) == Some(&nibble));
+                    let child = encode_node(children, depth + 1, target, proof);
+                    append_child(&mut stream, &child);
+                }
+            }
+            match value {
+                Some(value) => stream.append(&value),
+                None => stream.append_empty_data(),
+            };
+            stream.out().to_vec()
+        }
+    };
+
+    if target.is_some() && (node.len() >= 32 || depth == 0) {
+        proof.insert(position, node.clone().into());
+    }
+    node
+}
+
+/// Appends the reference to the child node, which is embedded if it's shorter than 32 bytes
+fn append_child(stream: &mut RlpStream, child: &[u8]) {
+    if child.len() < 32 {
+        stream.append_raw(child, 1);
+    } else {
+        stream.append(&H256(keccak256(child)));
+    }
+}
+
+/// Returns the value of the branch or leaf node item, `None` if it's empty
+fn value_of(item: &Rlp) -> Result<Option<Vec<u8>>> {
+    let value = item.data()?;
+    Ok((!value.is_empty()).then(|| value.to_vec()))
+}
+
+/// Splits the bytes into nibbles, high nibble first
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]).collect()
+}
+
+/// Decodes the hex prefix encoded path of an extension or leaf node, returns the nibbles and
+/// whether it's a leaf
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let nibbles = to_nibbles(encoded);
+    let flag = *nibbles.first().ok_or_else(|| eyre::eyre!("The proof contains an empty path"))?;
+    if flag > 3 {
+        eyre::bail!("The proof contains a path with the invalid prefix {flag}")
+    }
+    // an even number of nibbles is padded with a zero nibble
+    let skip = if flag & 1 == 1 { 1 } else { 2 };
+    Ok((nibbles[skip..].to_vec(), flag & 2 == 2))
+}
+
+/// Hex prefix encodes the path of an extension or leaf node
+fn encode_path(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut prefixed = if nibbles.len() % 2 == 1 { vec![flag + 1] } else { vec![flag, 0] };
+    prefixed.extend_from_slice(nibbles);
+    prefixed.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+}
+
+/// Verifies that the `receipt` is included in the receipts trie with the `receipts_root`
+pub fn verify_receipt_proof(
+    receipts_root: H256,
+    receipt: &TransactionReceipt,
+    proof: &[Bytes],
+) -> Result<()> {
+    let index = receipt.transaction_index.as_usize();
+    let proven = verify_proof(receipts_root, &rlp::encode(&(index as u64)), proof)?
+        .ok_or_else(|| eyre::eyre!("The receipts trie has no receipt at index {index}"))?;
+    if proven != encode_receipt(receipt) {
+        eyre::bail!("The receipt at index {index} does not match the proven receipt")
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_and_verify_tries() {
+        // the `puppy` trie of the ethereum tests
+        let entries = [("do", "verb"), ("horse", "stallion"), ("doge", "coin"), ("dog", "puppy")]
+            .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes()));
+        let root: H256 =
+            "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84".parse().unwrap();
+
+        for (key, value) in &entries {
+            let (built, proof) = trie_proof(&entries, key);
+            assert_eq!(built, root);
+            assert_eq!(verify_proof(root, key, &proof).unwrap().as_deref(), Some(*value));
+        }
+
+        let (_, proof) = trie_proof(&entries, b"dot");
+        assert_eq!(verify_proof(root, b"dot", &proof).unwrap(), None);
+    }
+
+    #[test]
+    fn can_verify_ordered_trie_proofs() {
+        assert_eq!(ordered_trie_proof(&[], 0), (EMPTY_ROOT, vec![]));
+        assert_eq!(verify_proof(EMPTY_ROOT, &rlp::encode(&0u64), &[]).unwrap(), None);
+
+        for len in [1, 2, 16, 17, 130, 300] {
+            let values = (0..len).map(|i| format!("value {i}").into_bytes()).collect::<Vec<_>>();
+            for index in [0, len / 2, len - 1] {
+                let (root, proof) = ordered_trie_proof(&values, index);
+                let key = rlp::encode(&(index as u64));
+                assert_eq!(verify_proof(root, &key, &proof).unwrap(), Some(values[index].clone()));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_proofs() {
+        let values = (0..20).map(|i| vec![i as u8; 40]).collect::<Vec<_>>();
+        let (root, mut proof) = ordered_trie_proof(&values, 7);
+        let key = rlp::encode(&7u64);
+
+        assert!(verify_proof(root, &key, &proof[..proof.len() - 1]).is_err());
+        assert!(verify_proof(H256::zero(), &key, &proof).is_err());
+
+        let mut node = proof.last().unwrap().to_vec();
+        *node.last_mut().unwrap() ^= 1;
+        *proof.last_mut().unwrap() = node.into();
+        assert!(verify_proof(root, &key, &proof).is_err());
+    }
+
+    #[test]
+    fn can_encode_paths() {
+        for (nibbles, is_leaf) in [(vec![1, 2, 3], true), (vec![0, 1, 2, 3], false), (vec![], true)]
+        {
+            let encoded = encode_path(&nibbles, is_leaf);
+            assert_eq!(decode_path(&encoded).unwrap(), (nibbles, is_leaf));
+        }
+        assert_eq!(encode_path(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(encode_path(&[0, 15, 1, 12, 11, 8], true), vec![0x20, 0x0f, 0x1c, 0xb8]);
+    }
+}
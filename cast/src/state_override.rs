@@ -0,0 +1,253 @@
+//! State overrides of `eth_call` and `eth_estimateGas`
+
+use ethers_core::{
+    types::{Address, Bytes, H256, U256, U64},
+    utils::keccak256,
+};
+use ethers_providers::{ProviderError, RpcError};
+use eyre::{Context, Result};
+use foundry_evm::{
+    executor::Executor,
+    revm::{Bytecode, Database},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// The state override set of `eth_call` and `eth_estimateGas`, by account address
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
+
+/// The fields of an account that are replaced for the duration of a call
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replaces the entire storage of the account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Replaces only the given storage slots of the account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+impl AccountOverride {
+    /// Merges `other` into this override, the fields of `other` take precedence
+    pub fn merge(&mut self, other: AccountOverride) {
+        let AccountOverride { balance, nonce, code, state, state_diff } = other;
+        self.balance = balance.or(self.balance);
+        self.nonce = nonce.or(self.nonce);
+        self.code = code.or_else(|| self.code.take());
+        self.state = state.or_else(|| self.state.take());
+        if let Some(state_diff) = state_diff {
+            self.state_diff.get_or_insert_with(Default::default).extend(state_diff);
+        }
+    }
+}
+
+/// Parses an account override of the form
+/// `<ADDRESS>:balance=<WEI>,nonce=<NONCE>,code=<HEX|@FILE>,slot:<SLOT>=<VALUE>`
+///
+/// All fields are optional, `slot:<SLOT>=<VALUE>` can be repeated.
+pub fn parse_account_override(s: &str) -> Result<(Address, AccountOverride)> {
+    let (address, fields) = s
+        .split_once(':')
+        .ok_or_else(|| eyre::eyre!("expected `<ADDRESS>:<FIELD>=<VALUE>,...`, got `{s}`"))?;
+    let address = Address::from_str(address.trim())
+        .wrap_err_with(|| format!("invalid address `{address}`"))?;
+
+    let mut account = AccountOverride::default();
+    for field in fields.split(',').map(str::trim).filter(|field| !field.is_empty()) {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("expected `<FIELD>=<VALUE>`, got `{field}`"))?;
+        let value = value.trim();
+        match key.trim() {
+            "balance" => account.balance = Some(parse_u256(value)?),
+            "nonce" => {
+                let nonce = u64::try_from(parse_u256(value)?)
+                    .map_err(|_| eyre::eyre!("nonce `{value}` does not fit into 64 bits"))?;
+                account.nonce = Some(nonce.into());
+            }
+            "code" => {
+                let code = match value.strip_prefix('@') {
+                    Some(path) => std::fs::read_to_string(path)
+                        .wrap_err_with(|| format!("failed to read code from `{path}`"))?,
+                    None => value.to_string(),
+                };
+                let code = code.trim();
+                account.code = Some(
+                    hex::decode(code.strip_prefix("0x").unwrap_or(code))
+                        .wrap_err("expected hex encoded code")?
+                        .into(),
+                );
+            }
+            key => {
+                let slot = key.strip_prefix("slot:").ok_or_else(|| {
+                    eyre::eyre!(
+                        "unknown field `{key}`, expected one of `balance`, `nonce`, `code` or `slot:<SLOT>`"
+                    )
+                })?;
+                account
+                    .state_diff
+                    .get_or_insert_with(Default::default)
+                    .insert(parse_word(slot)?, parse_word(value)?);
+            }
+        }
+    }
+
+    Ok((address, account))
+}
+
+/// Reads a state override set in the JSON format of `eth_call` from the file
+pub fn read_state_override(path: &Path) -> Result<StateOverride> {
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read state overrides from {}", path.display()))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("invalid state overrides in {}", path.display()))
+}
+
+/// Returns whether the error was returned by a node that does not support state overrides
+///
+/// Only errors about the state override parameter itself qualify, so that invalid calls are not
+/// silently executed locally.
+pub fn is_state_override_unsupported(err: &eyre::Report) -> bool {
+    err.downcast_ref::<ProviderError>()
+        .and_then(|err| err.as_error_response())
+        .map(|err| is_state_override_unsupported_message(err.code, &err.message))
+        .unwrap_or_default()
+}
+
+fn is_state_override_unsupported_message(code: i64, message: &str) -> bool {
+    let message = message.to_lowercase();
+    // nodes that don't know the parameter reject the number of parameters, e.g. geth's
+    // "too many arguments, want at most 2"
+    let too_many_params = code == -32602 &&
+        message.contains("too many") &&
+        (message.contains("argument") || message.contains("param"));
+    let unsupported = message.contains("override") &&
+        (message.contains("not supported") || message.contains("unsupported"));
+    too_many_params || unsupported
+}
+
+/// Applies the overrides to the state of the executor
+///
+/// This emulates the state overrides of `eth_call` locally, for nodes that don't support them.
+pub fn apply_state_override(executor: &mut Executor, overrides: &StateOverride) -> Result<()> {
+    for (address, account) in overrides {
+        let address = *address;
+        if let Some(balance) = account.balance {
+            executor.set_balance(address, balance)?;
+        }
+        if let Some(nonce) = account.nonce {
+            executor.set_nonce(address, nonce.as_u64())?;
+        }
+        if let Some(ref code) = account.code {
+            let backend = executor.backend_mut();
+            let mut info = backend.basic(address)?.unwrap_or_default();
+            info.code_hash = keccak256(code).into();
+            info.code = Some(Bytecode::new_raw(code.0.clone()).to_checked());
+            backend.insert_account_info(address, info);
+        }
+        if let Some(ref state) = account.state {
+            let storage = state.iter().map(|(slot, value)| (word(slot), word(value))).collect();
+            executor.backend_mut().replace_account_storage(address, storage)?;
+        }
+        if let Some(ref state_diff) = account.state_diff {
+            for (slot, value) in state_diff {
+                executor.backend_mut().insert_account_storage(address, word(slot), word(value))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn word(value: &H256) -> U256 {
+    U256::from_big_endian(value.as_bytes())
+}
+
+/// Parses a decimal or `0x` prefixed hex number
+fn parse_u256(s: &str) -> Result<U256> {
+    Ok(if s.starts_with("0x") { U256::from_str(s)? } else { U256::from_dec_str(s)? })
+}
+
+/// Parses a storage slot or value, which may be shorter than 32 bytes
+fn parse_word(s: &str) -> Result<H256> {
+    let mut word = H256::zero();
+    parse_u256(s)?.to_big_endian(word.as_bytes_mut());
+    Ok(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_account_override() {
+        let (address, account) = parse_account_override(
+            "0x00000000000000000000000000000000000000aa:balance=100,nonce=0x2,code=0x6000,slot:0x1=42",
+        )
+        .unwrap();
+        assert_eq!(address, Address::from_low_u64_be(0xaa));
+        assert_eq!(account.balance, Some(100u64.into()));
+        assert_eq!(account.nonce, Some(2u64.into()));
+        assert_eq!(account.code, Some(vec![0x60, 0x00].into()));
+        assert_eq!(
+            account.state_diff,
+            Some(BTreeMap::from([(H256::from_low_u64_be(1), H256::from_low_u64_be(42))]))
+        );
+
+        assert!(parse_account_override("0x00000000000000000000000000000000000000aa").is_err());
+        assert!(parse_account_override("0x00000000000000000000000000000000000000aa:foo=1").is_err());
+        assert!(parse_account_override(
+            "0x00000000000000000000000000000000000000aa:nonce=0x10000000000000000"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn detects_unsupported_state_overrides() {
+        assert!(is_state_override_unsupported_message(
+            -32602,
+            "too many arguments, want at most 2"
+        ));
+        assert!(is_state_override_unsupported_message(-32000, "state override is not supported"));
+
+        // errors of the call itself are not retried locally
+        assert!(!is_state_override_unsupported_message(
+            -32602,
+            "invalid argument 0: json: cannot unmarshal hex string without 0x prefix"
+        ));
+        assert!(!is_state_override_unsupported_message(
+            -32601,
+            "the method eth_call is not supported"
+        ));
+        assert!(!is_state_override_unsupported_message(3, "execution reverted"));
+    }
+
+    #[test]
+    fn can_serialize_state_override() {
+        let (address, account) =
+            parse_account_override("0x00000000000000000000000000000000000000aa:balance=1,slot:1=2")
+                .unwrap();
+        let overrides = StateOverride::from([(address, account)]);
+        let json = serde_json::to_value(&overrides).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "0x00000000000000000000000000000000000000aa": {
+                    "balance": "0x1",
+                    "stateDiff": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }
+                }
+            })
+        );
+        let parsed: StateOverride = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, overrides);
+    }
+}
@@ -0,0 +1,541 @@
+//! ERC-4337 user operations
+//!
+//! Supports the EntryPoint versions 0.6 and 0.7, which differ in how user operations are packed,
+//! hashed and sent to bundlers.
+
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A version of the EntryPoint contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPointVersion {
+    V06,
+    V07,
+}
+
+impl EntryPointVersion {
+    /// Returns the canonical address of the EntryPoint
+    pub fn address(&self) -> Address {
+        match self {
+            EntryPointVersion::V06 => "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789",
+            EntryPointVersion::V07 => "0x0000000071727De22E5E9d8BAf0edAc6f37da032",
+        }
+        .parse()
+        .expect("valid address")
+    }
+
+    /// Returns the ABI type of a user operation as it is passed to `handleOps`
+    fn user_op_type(&self) -> ParamType {
+        let fields = match self {
+            EntryPointVersion::V06 => vec![
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Bytes,
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Bytes,
+            ],
+            EntryPointVersion::V07 => vec![
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Bytes,
+                ParamType::FixedBytes(32),
+                ParamType::Uint(256),
+                ParamType::FixedBytes(32),
+                ParamType::Bytes,
+                ParamType::Bytes,
+            ],
+        };
+        ParamType::Tuple(fields)
+    }
+
+    /// Returns the selector of `handleOps(UserOperation[],address)`
+    fn handle_ops_selector(&self) -> [u8; 4] {
+        let signature = format!("handleOps({}[],address)", self.user_op_type());
+        let hash = keccak256(signature);
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+}
+
+impl FromStr for EntryPointVersion {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim_start_matches('v') {
+            "0.6" | "0.6.0" => Ok(EntryPointVersion::V06),
+            "0.7" | "0.7.0" => Ok(EntryPointVersion::V07),
+            _ => eyre::bail!("unsupported EntryPoint version `{s}`, expected 0.6 or 0.7"),
+        }
+    }
+}
+
+impl fmt::Display for EntryPointVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryPointVersion::V06 => f.write_str("0.6"),
+            EntryPointVersion::V07 => f.write_str("0.7"),
+        }
+    }
+}
+
+/// A user operation
+///
+/// The fields are the unpacked fields of the EntryPoint v0.7, which are combined into
+/// `initCode` and `paymasterAndData` for the v0.6.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub factory: Option<Address>,
+    pub factory_data: Bytes,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster: Option<Address>,
+    pub paymaster_verification_gas_limit: U256,
+    pub paymaster_post_op_gas_limit: U256,
+    pub paymaster_data: Bytes,
+    pub signature: Bytes,
+}
+
+/// A user operation in the JSON format of the bundler RPC of either EntryPoint version
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    /// v0.6 only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    /// v0.6 only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_and_data: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    #[serde(default)]
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Returns the `initCode`, the factory address followed by the factory data
+    pub fn init_code(&self) -> Bytes {
+        match self.factory {
+            Some(factory) => [factory.as_bytes(), &self.factory_data[..]].concat().into(),
+            None => Bytes::default(),
+        }
+    }
+
+    /// Returns the `paymasterAndData` of the given EntryPoint version
+    ///
+    /// For the v0.7 the gas limits of the paymaster are packed between its address and data.
+    pub fn paymaster_and_data(&self, version: EntryPointVersion) -> Bytes {
+        let paymaster = match self.paymaster {
+            Some(paymaster) => paymaster,
+            None => return Bytes::default(),
+        };
+        let mut packed = paymaster.as_bytes().to_vec();
+        if version == EntryPointVersion::V07 {
+            packed.extend_from_slice(&uint128_bytes(self.paymaster_verification_gas_limit));
+            packed.extend_from_slice(&uint128_bytes(self.paymaster_post_op_gas_limit));
+        }
+        packed.extend_from_slice(&self.paymaster_data);
+        packed.into()
+    }
+
+    /// Returns the hash of the user operation for the EntryPoint at the given address and chain,
+    /// which is signed by the account
+    pub fn hash(&self, version: EntryPointVersion, entry_point: Address, chain_id: u64) -> H256 {
+        let hash_bytes = |bytes: &[u8]| Token::FixedBytes(keccak256(bytes).to_vec());
+        let mut fields = vec![
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            hash_bytes(&self.init_code()),
+            hash_bytes(&self.call_data),
+        ];
+        match version {
+            EntryPointVersion::V06 => fields.extend([
+                Token::Uint(self.call_gas_limit),
+                Token::Uint(self.verification_gas_limit),
+                Token::Uint(self.pre_verification_gas),
+                Token::Uint(self.max_fee_per_gas),
+                Token::Uint(self.max_priority_fee_per_gas),
+            ]),
+            EntryPointVersion::V07 => fields.extend([
+                Token::FixedBytes(pack_uint128s(self.verification_gas_limit, self.call_gas_limit)),
+                Token::Uint(self.pre_verification_gas),
+                Token::FixedBytes(pack_uint128s(
+                    self.max_priority_fee_per_gas,
+                    self.max_fee_per_gas,
+                )),
+            ]),
+        }
+        fields.push(hash_bytes(&self.paymaster_and_data(version)));
+
+        let packed_hash = keccak256(abi::encode(&fields));
+        keccak256(abi::encode(&[
+            Token::FixedBytes(packed_hash.to_vec()),
+            Token::Address(entry_point),
+            Token::Uint(chain_id.into()),
+        ]))
+        .into()
+    }
+
+    /// Returns the user operation in the JSON format of the bundler RPC of the given version
+    pub fn to_rpc(&self, version: EntryPointVersion) -> RpcUserOperation {
+        let mut rpc = RpcUserOperation {
+            sender: self.sender,
+            nonce: self.nonce,
+            call_data: self.call_data.clone(),
+            call_gas_limit: self.call_gas_limit,
+            verification_gas_limit: self.verification_gas_limit,
+            pre_verification_gas: self.pre_verification_gas,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            signature: self.signature.clone(),
+            ..Default::default()
+        };
+        match version {
+            EntryPointVersion::V06 => {
+                rpc.init_code = Some(self.init_code());
+                rpc.paymaster_and_data = Some(self.paymaster_and_data(version));
+            }
+            EntryPointVersion::V07 => {
+                if let Some(factory) = self.factory {
+                    rpc.factory = Some(factory);
+                    rpc.factory_data = Some(self.factory_data.clone());
+                }
+                if let Some(paymaster) = self.paymaster {
+                    rpc.paymaster = Some(paymaster);
+                    rpc.paymaster_verification_gas_limit =
+                        Some(self.paymaster_verification_gas_limit);
+                    rpc.paymaster_post_op_gas_limit = Some(self.paymaster_post_op_gas_limit);
+                    rpc.paymaster_data = Some(self.paymaster_data.clone());
+                }
+            }
+        }
+        rpc
+    }
+
+    /// Returns the ABI encoding of the user operation as it is passed to `handleOps`
+    fn to_token(&self, version: EntryPointVersion) -> Token {
+        let mut fields = vec![
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::Bytes(self.init_code().to_vec()),
+            Token::Bytes(self.call_data.to_vec()),
+        ];
+        match version {
+            EntryPointVersion::V06 => fields.extend([
+                Token::Uint(self.call_gas_limit),
+                Token::Uint(self.verification_gas_limit),
+                Token::Uint(self.pre_verification_gas),
+                Token::Uint(self.max_fee_per_gas),
+                Token::Uint(self.max_priority_fee_per_gas),
+            ]),
+            EntryPointVersion::V07 => fields.extend([
+                Token::FixedBytes(pack_uint128s(self.verification_gas_limit, self.call_gas_limit)),
+                Token::Uint(self.pre_verification_gas),
+                Token::FixedBytes(pack_uint128s(
+                    self.max_priority_fee_per_gas,
+                    self.max_fee_per_gas,
+                )),
+            ]),
+        }
+        fields.push(Token::Bytes(self.paymaster_and_data(version).to_vec()));
+        fields.push(Token::Bytes(self.signature.to_vec()));
+        Token::Tuple(fields)
+    }
+
+    /// Decodes a user operation that was passed to `handleOps`
+    fn from_token(token: Token, version: EntryPointVersion) -> Result<Self> {
+        let fields = match token {
+            Token::Tuple(fields) => fields,
+            _ => eyre::bail!("expected a user operation tuple"),
+        };
+        let mut fields = fields.into_iter();
+        let mut next = || fields.next().ok_or_else(|| eyre::eyre!("missing user operation field"));
+
+        let mut op = UserOperation {
+            sender: next()?.into_address().unwrap_or_default(),
+            nonce: next()?.into_uint().unwrap_or_default(),
+            ..Default::default()
+        };
+        let init_code = next()?.into_bytes().unwrap_or_default();
+        if init_code.len() >= 20 {
+            op.factory = Some(Address::from_slice(&init_code[..20]));
+            op.factory_data = init_code[20..].to_vec().into();
+        }
+        op.call_data = next()?.into_bytes().unwrap_or_default().into();
+        match version {
+            EntryPointVersion::V06 => {
+                op.call_gas_limit = next()?.into_uint().unwrap_or_default();
+                op.verification_gas_limit = next()?.into_uint().unwrap_or_default();
+                op.pre_verification_gas = next()?.into_uint().unwrap_or_default();
+                op.max_fee_per_gas = next()?.into_uint().unwrap_or_default();
+                op.max_priority_fee_per_gas = next()?.into_uint().unwrap_or_default();
+            }
+            EntryPointVersion::V07 => {
+                let account_gas_limits = next()?.into_fixed_bytes().unwrap_or_default();
+                (op.verification_gas_limit, op.call_gas_limit) =
+                    unpack_uint128s(&account_gas_limits);
+                op.pre_verification_gas = next()?.into_uint().unwrap_or_default();
+                let gas_fees = next()?.into_fixed_bytes().unwrap_or_default();
+                (op.max_priority_fee_per_gas, op.max_fee_per_gas) = unpack_uint128s(&gas_fees);
+            }
+        }
+        let paymaster_and_data = next()?.into_bytes().unwrap_or_default();
+        if paymaster_and_data.len() >= 20 {
+            op.paymaster = Some(Address::from_slice(&paymaster_and_data[..20]));
+            let mut data = &paymaster_and_data[20..];
+            if version == EntryPointVersion::V07 {
+                if data.len() < 32 {
+                    eyre::bail!("paymasterAndData is missing the paymaster gas limits");
+                }
+                (op.paymaster_verification_gas_limit, op.paymaster_post_op_gas_limit) =
+                    unpack_uint128s(&data[..32]);
+                data = &data[32..];
+            }
+            op.paymaster_data = data.to_vec().into();
+        }
+        op.signature = next()?.into_bytes().unwrap_or_default().into();
+        Ok(op)
+    }
+}
+
+impl TryFrom<RpcUserOperation> for UserOperation {
+    type Error = eyre::Report;
+
+    /// Converts a user operation in the format of either EntryPoint version
+    fn try_from(rpc: RpcUserOperation) -> Result<Self> {
+        let mut op = UserOperation {
+            sender: rpc.sender,
+            nonce: rpc.nonce,
+            factory: rpc.factory,
+            factory_data: rpc.factory_data.unwrap_or_default(),
+            call_data: rpc.call_data,
+            call_gas_limit: rpc.call_gas_limit,
+            verification_gas_limit: rpc.verification_gas_limit,
+            pre_verification_gas: rpc.pre_verification_gas,
+            max_fee_per_gas: rpc.max_fee_per_gas,
+            max_priority_fee_per_gas: rpc.max_priority_fee_per_gas,
+            paymaster: rpc.paymaster,
+            paymaster_verification_gas_limit: rpc
+                .paymaster_verification_gas_limit
+                .unwrap_or_default(),
+            paymaster_post_op_gas_limit: rpc.paymaster_post_op_gas_limit.unwrap_or_default(),
+            paymaster_data: rpc.paymaster_data.unwrap_or_default(),
+            signature: rpc.signature,
+        };
+
+        if let Some(init_code) = rpc.init_code.filter(|code| !code.is_empty()) {
+            if init_code.len() < 20 {
+                eyre::bail!("initCode must start with the factory address");
+            }
+            op.factory = Some(Address::from_slice(&init_code[..20]));
+            op.factory_data = init_code[20..].to_vec().into();
+        }
+        if let Some(packed) = rpc.paymaster_and_data.filter(|packed| !packed.is_empty()) {
+            if packed.len() < 20 {
+                eyre::bail!("paymasterAndData must start with the paymaster address");
+            }
+            op.paymaster = Some(Address::from_slice(&packed[..20]));
+            op.paymaster_data = packed[20..].to_vec().into();
+        }
+        Ok(op)
+    }
+}
+
+impl FromStr for UserOperation {
+    type Err = eyre::Report;
+
+    /// Parses a user operation from its JSON in the format of either EntryPoint version
+    fn from_str(s: &str) -> Result<Self> {
+        let rpc: RpcUserOperation =
+            serde_json::from_str(s).wrap_err("failed to parse user operation JSON")?;
+        rpc.try_into()
+    }
+}
+
+/// Returns the calldata of `handleOps(ops, beneficiary)`
+pub fn encode_handle_ops(
+    ops: &[UserOperation],
+    beneficiary: Address,
+    version: EntryPointVersion,
+) -> Bytes {
+    let ops = ops.iter().map(|op| op.to_token(version)).collect();
+    let args = abi::encode(&[Token::Array(ops), Token::Address(beneficiary)]);
+    [&version.handle_ops_selector()[..], &args[..]].concat().into()
+}
+
+/// Decodes the user operations and the beneficiary from the calldata of `handleOps`
+///
+/// The EntryPoint version is detected from the selector.
+pub fn decode_handle_ops(
+    calldata: &[u8],
+) -> Result<(EntryPointVersion, Vec<UserOperation>, Address)> {
+    if calldata.len() < 4 {
+        eyre::bail!("calldata is too short");
+    }
+    let version = [EntryPointVersion::V06, EntryPointVersion::V07]
+        .into_iter()
+        .find(|version| version.handle_ops_selector() == calldata[..4])
+        .ok_or_else(|| {
+            eyre::eyre!("calldata is not a `handleOps` call of EntryPoint v0.6 or v0.7")
+        })?;
+
+    let params = [ParamType::Array(Box::new(version.user_op_type())), ParamType::Address];
+    let mut tokens = abi::decode(&params, &calldata[4..])?.into_iter();
+    let ops = tokens
+        .next()
+        .and_then(Token::into_array)
+        .ok_or_else(|| eyre::eyre!("missing user operations"))?
+        .into_iter()
+        .map(|token| UserOperation::from_token(token, version))
+        .collect::<Result<Vec<_>>>()?;
+    let beneficiary = tokens.next().and_then(Token::into_address).unwrap_or_default();
+    Ok((version, ops, beneficiary))
+}
+
+/// Returns the lower 16 bytes of the big endian encoding of the value
+fn uint128_bytes(value: U256) -> [u8; 16] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    let mut low = [0u8; 16];
+    low.copy_from_slice(&bytes[16..]);
+    low
+}
+
+/// Packs two `uint128`s into a `bytes32`, `high` first
+fn pack_uint128s(high: U256, low: U256) -> Vec<u8> {
+    [uint128_bytes(high), uint128_bytes(low)].concat()
+}
+
+/// Unpacks the two `uint128`s of a `bytes32`, `high` first
+fn unpack_uint128s(packed: &[u8]) -> (U256, U256) {
+    (U256::from_big_endian(&packed[..16]), U256::from_big_endian(&packed[16..32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_op() -> UserOperation {
+        UserOperation {
+            sender: Address::repeat_byte(1),
+            nonce: 7u64.into(),
+            factory: Some(Address::repeat_byte(2)),
+            factory_data: vec![0xaa, 0xbb].into(),
+            call_data: vec![0xcc].into(),
+            call_gas_limit: 100_000u64.into(),
+            verification_gas_limit: 200_000u64.into(),
+            pre_verification_gas: 50_000u64.into(),
+            max_fee_per_gas: 30_000_000_000u64.into(),
+            max_priority_fee_per_gas: 1_000_000_000u64.into(),
+            paymaster: Some(Address::repeat_byte(3)),
+            paymaster_verification_gas_limit: 60_000u64.into(),
+            paymaster_post_op_gas_limit: 10_000u64.into(),
+            paymaster_data: vec![0xdd].into(),
+            signature: vec![0xee; 65].into(),
+        }
+    }
+
+    #[test]
+    fn can_parse_entry_point_versions() {
+        assert_eq!("0.6".parse::<EntryPointVersion>().unwrap(), EntryPointVersion::V06);
+        assert_eq!("v0.7.0".parse::<EntryPointVersion>().unwrap(), EntryPointVersion::V07);
+        assert!("0.8".parse::<EntryPointVersion>().is_err());
+    }
+
+    #[test]
+    fn can_pack_fields() {
+        let op = user_op();
+        assert_eq!(op.init_code().len(), 22);
+        assert_eq!(op.paymaster_and_data(EntryPointVersion::V06).len(), 21);
+        assert_eq!(op.paymaster_and_data(EntryPointVersion::V07).len(), 53);
+
+        let packed = pack_uint128s(op.verification_gas_limit, op.call_gas_limit);
+        assert_eq!(unpack_uint128s(&packed), (op.verification_gas_limit, op.call_gas_limit));
+    }
+
+    #[test]
+    fn can_convert_rpc_user_ops() {
+        let op = user_op();
+        for version in [EntryPointVersion::V06, EntryPointVersion::V07] {
+            let json = serde_json::to_string(&op.to_rpc(version)).unwrap();
+            let parsed: UserOperation = json.parse().unwrap();
+            match version {
+                // the paymaster gas limits are not part of the v0.6 format
+                EntryPointVersion::V06 => assert_eq!(
+                    parsed,
+                    UserOperation {
+                        paymaster_verification_gas_limit: U256::zero(),
+                        paymaster_post_op_gas_limit: U256::zero(),
+                        ..op.clone()
+                    }
+                ),
+                EntryPointVersion::V07 => assert_eq!(parsed, op),
+            }
+        }
+    }
+
+    #[test]
+    fn can_encode_and_decode_handle_ops() {
+        let op = user_op();
+        let beneficiary = Address::repeat_byte(9);
+
+        let calldata = encode_handle_ops(&[op.clone()], beneficiary, EntryPointVersion::V07);
+        let (version, ops, decoded_beneficiary) = decode_handle_ops(&calldata).unwrap();
+        assert_eq!(version, EntryPointVersion::V07);
+        assert_eq!(ops, vec![op.clone()]);
+        assert_eq!(decoded_beneficiary, beneficiary);
+
+        let calldata = encode_handle_ops(&[op], beneficiary, EntryPointVersion::V06);
+        let (version, ..) = decode_handle_ops(&calldata).unwrap();
+        assert_eq!(version, EntryPointVersion::V06);
+    }
+
+    #[test]
+    fn hash_depends_on_entry_point_and_chain() {
+        let op = user_op();
+        let v06 = EntryPointVersion::V06;
+        let v07 = EntryPointVersion::V07;
+        let hash = op.hash(v07, v07.address(), 1);
+
+        assert_ne!(hash, op.hash(v06, v06.address(), 1));
+        assert_ne!(hash, op.hash(v07, v07.address(), 10));
+        // the signature is not part of the hash
+        let unsigned = UserOperation { signature: Bytes::default(), ..op };
+        assert_eq!(hash, unsigned.hash(v07, v07.address(), 1));
+    }
+}
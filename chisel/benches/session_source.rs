@@ -72,6 +72,7 @@ fn get_empty_session_source() -> SessionSource {
             foundry_config: Config::default(),
             evm_opts: EvmOpts::default(),
             backend: None,
+            state: None,
             traces: false,
         },
     )
@@ -80,6 +80,7 @@ async fn main() -> eyre::Result<()> {
         foundry_config: config,
         evm_opts,
         backend: None,
+        state: None,
     })?;
 
     // Check for chisel subcommands
@@ -123,8 +123,8 @@ impl From<ChiselCommand> for CmdDescriptor {
             // Session
             ChiselCommand::Clear => (&["clear", "c"], "Clear current session source", CmdCategory::Session),
             ChiselCommand::Source => (&["source", "so"], "Display the source code of the current session", CmdCategory::Session),
-            ChiselCommand::Save => (&["save [id]", "s [id]"], "Save the current session to cache", CmdCategory::Session),
-            ChiselCommand::Load => (&["load <id>", "l <id>"], "Load a previous session ID from cache", CmdCategory::Session),
+            ChiselCommand::Save => (&["save [id]", "s [id]"], "Save the current session, including its EVM state, to cache", CmdCategory::Session),
+            ChiselCommand::Load => (&["load <id>", "l <id>"], "Load a previous session ID, including its EVM state, from cache", CmdCategory::Session),
             ChiselCommand::ListSessions => (&["list", "ls"], "List all cached sessions", CmdCategory::Session),
             ChiselCommand::ClearCache => (&["clearcache", "cc"], "Clear the chisel cache of all stored sessions", CmdCategory::Session),
             ChiselCommand::Export => (&["export", "ex"], "Export the current session source to a script file", CmdCategory::Session),
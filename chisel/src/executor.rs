@@ -239,9 +239,13 @@ impl SessionSource {
 
         // Create an in-memory backend
         let backend = self.config.backend.take().unwrap_or_else(|| {
-            let backend = Backend::spawn(
+            let mut backend = Backend::spawn(
                 self.config.evm_opts.get_fork(&self.config.foundry_config, env.clone()),
             );
+            // Restore the EVM state of a loaded session
+            if let Some(state) = self.config.state.take() {
+                backend.load_state(state);
+            }
             self.config.backend = Some(backend.clone());
             backend
         });
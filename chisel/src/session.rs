@@ -106,7 +106,8 @@ impl ChiselSession {
         Ok(())
     }
 
-    /// Writes the ChiselSession to a file by serializing it to a JSON string
+    /// Writes the ChiselSession, including the EVM state of its backend, to a file by serializing
+    /// it to a JSON string
     ///
     /// ### Returns
     ///
@@ -131,6 +132,14 @@ impl ChiselSession {
             }
         };
 
+        // Persist the EVM state of the session, a session that was loaded but not executed yet
+        // still holds its loaded state
+        if let Some(source) = self.session_source.as_mut() {
+            if let Some(backend) = source.config.backend.as_ref() {
+                source.config.state = Some(backend.dump_state());
+            }
+        }
+
         // Write the current ChiselSession to that file
         let serialized_contents = serde_json::to_string_pretty(self)?;
         std::fs::write(&cache_file_name, serialized_contents)?;
@@ -6,10 +6,11 @@
 
 use ethers_solc::{
     artifacts::{Source, Sources},
+    resolver::Graph,
     CompilerInput, CompilerOutput, Solc,
 };
 use eyre::Result;
-use forge::executor::{opts::EvmOpts, Backend};
+use forge::executor::{backend::snapshot::StateSnapshot, opts::EvmOpts, Backend};
 use forge_fmt::solang_ext::SafeUnwrap;
 use foundry_config::Config;
 use semver::Version;
@@ -20,6 +21,12 @@ use std::{collections::HashMap, fs, path::PathBuf};
 /// Solidity source for the `Vm` interface in [forge-std](https://github.com/foundry-rs/forge-std)
 static VM_SOURCE: &str = include_str!("../../testdata/cheats/Cheats.sol");
 
+/// Source unit name of [VM_SOURCE]
+///
+/// This must not collide with the project's remappings, e.g. `forge-std/`, otherwise the import
+/// would be remapped to the project's copy of forge-std.
+static VM_SOURCE_PATH: &str = "chisel/Vm.sol";
+
 /// Intermediate output for the compiled [SessionSource]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntermediateOutput {
@@ -71,6 +78,9 @@ pub struct SessionSourceConfig {
     #[serde(skip)]
     /// In-memory REVM db for the session's runner.
     pub backend: Option<Backend>,
+    /// EVM state of a loaded session that is inserted into the `backend` once it is created
+    #[serde(default)]
+    pub state: Option<StateSnapshot>,
     /// Optionally enable traces for the REPL contract execution
     pub traces: bool,
 }
@@ -240,15 +250,30 @@ impl SessionSource {
 
     /// Generates and ethers_solc::CompilerInput from the source
     ///
+    /// All imports of the session are resolved against the foundry project the session was
+    /// started in, using the project's remappings, so that project files like `src/Token.sol` can
+    /// be imported directly.
+    ///
     /// ### Returns
     ///
-    /// A [CompilerInput] object containing forge-std's `Vm` interface as well as the REPL contract
-    /// source.
+    /// A [CompilerInput] object containing forge-std's `Vm` interface, the REPL contract source and
+    /// all project sources it imports.
     pub fn compiler_input(&self) -> CompilerInput {
+        let paths = self.config.foundry_config.project_paths();
+
         let mut sources = Sources::new();
-        sources.insert(PathBuf::from("forge-std/Vm.sol"), Source::new(VM_SOURCE.to_owned()));
-        sources.insert(self.file_name.clone(), Source::new(self.to_repl_source()));
-        CompilerInput::with_sources(sources).pop().unwrap()
+        sources.insert(paths.root.join(&self.file_name), Source::new(self.to_repl_source()));
+        // If the imports can't be resolved, solc will report the missing sources
+        let mut sources = Graph::resolve_sources(&paths, sources.clone())
+            .map(|graph| graph.into_sources().0)
+            .unwrap_or(sources);
+        sources.insert(paths.root.join(VM_SOURCE_PATH), Source::new(VM_SOURCE.to_owned()));
+
+        CompilerInput::with_sources(sources)
+            .pop()
+            .unwrap()
+            .with_remappings(paths.remappings)
+            .with_base_path(&paths.root)
     }
 
     /// Compiles the source using [solang_parser]
@@ -390,7 +415,7 @@ contract {} is Script {{
 // SPDX-License-Identifier: UNLICENSED
 pragma solidity ^{major}.{minor}.{patch};
 
-import {{Cheats}} from "forge-std/Vm.sol";
+import {{Cheats}} from "chisel/Vm.sol";
 {}
 
 contract {} {{
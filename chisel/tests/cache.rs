@@ -1,8 +1,10 @@
 use std::path::Path;
 
 use chisel::{session::ChiselSession, session_source::SessionSourceConfig};
-use forge::executor::opts::EvmOpts;
+use ethers::types::{Address, U256};
+use forge::executor::{backend::snapshot::StateSnapshot, opts::EvmOpts};
 use foundry_config::Config;
+use revm::AccountInfo;
 use serial_test::serial;
 
 #[test]
@@ -42,6 +44,7 @@ fn test_write_session() {
         foundry_config: Config::default(),
         evm_opts: EvmOpts::default(),
         backend: None,
+        state: None,
         traces: false,
     })
     .unwrap_or_else(|_| panic!("Failed to create ChiselSession!"));
@@ -140,6 +143,33 @@ fn test_load_cache() {
     );
 }
 
+#[test]
+#[serial]
+fn test_load_cache_with_state() {
+    // Create and clear the cache directory
+    ChiselSession::create_cache_dir().unwrap();
+    ChiselSession::clear_cache().unwrap();
+
+    // Create a new session with some EVM state
+    let mut env = ChiselSession::new(SessionSourceConfig::default())
+        .unwrap_or_else(|_| panic!("Failed to create ChiselSession!"));
+    let account = Address::repeat_byte(1);
+    let mut state = StateSnapshot::default();
+    state.accounts.insert(account, AccountInfo { balance: U256::from(100), ..Default::default() });
+    state.storage.insert(account, [(U256::from(1), U256::from(2))].into_iter().collect());
+    env.session_source.as_mut().unwrap().config.state = Some(state);
+    env.id = Some(String::from("state"));
+    env.write().unwrap();
+
+    // Load the session
+    let new_env = ChiselSession::load("state").unwrap();
+
+    // Validate the state
+    let state = new_env.session_source.unwrap().config.state.unwrap();
+    assert_eq!(state.accounts[&account].balance, U256::from(100));
+    assert_eq!(state.storage[&account][&U256::from(1)], U256::from(2));
+}
+
 #[test]
 #[serial]
 fn test_write_same_session_multiple_times() {
@@ -1,6 +1,5 @@
 use cast::{Cast, SimpleCast, TxBuilder};
 use clap::{CommandFactory, Parser};
-use clap_complete::generate;
 use ethers::{
     abi::HumanReadableParser,
     core::types::{BlockId, BlockNumber::Latest, H256},
@@ -9,8 +8,8 @@ use ethers::{
     utils::keccak256,
 };
 use foundry_cli::{
-    cmd::Cmd,
-    handler,
+    cmd::{cast::blobs::tx_blobs, Cmd},
+    completions, handler,
     opts::cast::{Opts, Subcommands, ToBaseArgs},
     prompt, stdin, utils,
 };
@@ -167,15 +166,23 @@ async fn main() -> eyre::Result<()> {
         Subcommands::AbiEncode { sig, args } => {
             println!("{}", SimpleCast::abi_encode(&sig, &args)?);
         }
+        Subcommands::AbiEncodeJson { sig, json } => {
+            println!("{}", SimpleCast::abi_encode_json(&sig, &json)?);
+        }
+        Subcommands::AbiDecodeJson { sig, data, input } => {
+            println!("{}", SimpleCast::abi_decode_json(&sig, &data, input)?);
+        }
         Subcommands::CalldataDecode { sig, calldata } => {
             let tokens = SimpleCast::abi_decode(&sig, &calldata, true)?;
             let tokens = format_tokens(&tokens);
             tokens.for_each(|t| println!("{t}"));
         }
+        Subcommands::CalldataCost(cmd) => cmd.run()?,
         Subcommands::CalldataEncode { sig, args } => {
             println!("{}", SimpleCast::calldata_encode(sig, &args)?);
         }
         Subcommands::Interface(cmd) => cmd.run().await?,
+        Subcommands::AbiDiff(cmd) => cmd.run().await?,
         Subcommands::Bind(cmd) => cmd.run().await?,
         Subcommands::PrettyCalldata { calldata, offline } => {
             let calldata = stdin::unwrap_line(calldata)?;
@@ -226,12 +233,13 @@ async fn main() -> eyre::Result<()> {
                 Cast::new(provider).base_fee(block.unwrap_or(BlockId::Number(Latest))).await?
             );
         }
-        Subcommands::Block { block, full, field, to_json, rpc } => {
+        Subcommands::Block { block, full, field, to_json, chain, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
             println!(
                 "{}",
                 Cast::new(provider)
+                    .with_chain_id(chain.map(|chain| chain.id()))
                     .block(block.unwrap_or(BlockId::Number(Latest)), full, field, to_json)
                     .await?
             );
@@ -278,6 +286,8 @@ async fn main() -> eyre::Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).gas_price().await?);
         }
+        Subcommands::GasHistory(cmd) => cmd.run().await?,
+        Subcommands::WatchMempool(cmd) => cmd.run().await?,
         Subcommands::Index { key_type, key, slot_number } => {
             println!("{}", SimpleCast::index(&key_type, &key, &slot_number)?);
         }
@@ -296,11 +306,22 @@ async fn main() -> eyre::Result<()> {
             let provider = utils::get_provider(&config)?;
             println!("{}", Cast::new(provider).nonce(who, block).await?);
         }
-        Subcommands::Proof { address, slots, rpc, block } => {
+        Subcommands::Proof { address, slots, rpc, block, verify, state_root } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            let value = provider.get_proof(address, slots, block).await?;
-            println!("{}", serde_json::to_string(&value)?);
+            let verify = verify || state_root.is_some();
+            let proof =
+                Cast::new(provider).proof(address, slots, block, verify, state_root).await?;
+            println!("{proof}");
+            if verify {
+                eprintln!("The account and storage proofs are valid");
+            }
+        }
+        Subcommands::VerifyReceiptProof { tx_hash, proof, rpc } => {
+            let config = Config::from(&rpc);
+            let provider = utils::get_provider(&config)?;
+            println!("{}", Cast::new(provider).receipt_proof(tx_hash, proof).await?);
+            eprintln!("The receipt is included in the receipts root of its block");
         }
         Subcommands::Rpc(cmd) => cmd.run().await?,
         Subcommands::Storage(cmd) => cmd.run().await?,
@@ -323,22 +344,30 @@ async fn main() -> eyre::Result<()> {
                 println!("{}", serde_json::json!(receipt));
             }
         }
-        Subcommands::Receipt { tx_hash, field, to_json, cast_async, confirmations, rpc } => {
+        Subcommands::Logs(cmd) => cmd.run().await?,
+        Subcommands::Receipt { tx_hash, field, to_json, cast_async, confirmations, chain, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
             println!(
                 "{}",
                 Cast::new(provider)
+                    .with_chain_id(chain.map(|chain| chain.id()))
                     .receipt(tx_hash, field, confirmations, cast_async, to_json)
                     .await?
             );
         }
         Subcommands::Run(cmd) => cmd.run().await?,
         Subcommands::SendTx(cmd) => cmd.run().await?,
-        Subcommands::Tx { tx_hash, field, to_json, rpc } => {
+        Subcommands::Tx { tx_hash, field, to_json, blobs, beacon_url, chain, rpc } => {
             let config = Config::from(&rpc);
             let provider = utils::get_provider(&config)?;
-            println!("{}", Cast::new(&provider).transaction(tx_hash, field, to_json).await?)
+            if blobs {
+                let beacon_url = beacon_url.expect("required by --blobs");
+                println!("{}", tx_blobs(&provider, &tx_hash, &beacon_url, to_json).await?)
+            } else {
+                let cast = Cast::new(&provider).with_chain_id(chain.map(|chain| chain.id()));
+                println!("{}", cast.transaction(tx_hash, field, to_json).await?)
+            }
         }
 
         // 4Byte
@@ -459,26 +488,26 @@ async fn main() -> eyre::Result<()> {
         }
         Subcommands::EtherscanSource { address, directory, etherscan } => {
             let config = Config::from(&etherscan);
-            let chain = config.chain_id.unwrap_or_default();
-            let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
-            let chain = chain.named()?;
+            let clients = config.get_etherscan_clients(config.chain_id.unwrap_or_default())?;
             match directory {
                 Some(dir) => {
-                    SimpleCast::expand_etherscan_source_to_directory(chain, address, api_key, dir)
-                        .await?
+                    SimpleCast::expand_etherscan_source_to_directory(&clients, address, dir).await?
                 }
                 None => {
-                    println!("{}", SimpleCast::etherscan_source(chain, address, api_key).await?);
+                    println!("{}", SimpleCast::etherscan_source(&clients, address).await?);
                 }
             }
         }
         Subcommands::Create2(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Create2Deployer(cmd) => cmd.run().await?,
+        Subcommands::UserOp { command } => command.run().await?,
         Subcommands::Wallet { command } => command.run().await?,
         Subcommands::Completions { shell } => {
-            generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())
+            completions::generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())?
         }
+        Subcommands::Complete { kind } => completions::print_candidates(kind),
         Subcommands::GenerateFigSpec => clap_complete::generate(
             clap_complete_fig::Fig,
             &mut Opts::command(),
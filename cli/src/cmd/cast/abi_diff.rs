@@ -0,0 +1,78 @@
+//! cast abi-diff subcommand
+
+use crate::{opts::EtherscanOpts, utils};
+use cast::abi_diff::AbiDiff;
+use clap::Parser;
+use foundry_config::Config;
+use yansi::Paint;
+
+/// The exit code if the new ABI has breaking changes
+pub const BREAKING_CHANGES_EXIT_CODE: i32 = 2;
+
+/// The exit code if the new ABI only has non-breaking changes and `--strict` is set
+pub const CHANGES_EXIT_CODE: i32 = 3;
+
+/// CLI arguments for `cast abi-diff`.
+#[derive(Debug, Clone, Parser)]
+pub struct AbiDiffArgs {
+    #[clap(
+        help = "The old contract address, or the path to an ABI or artifact file.",
+        long_help = r#"The old contract address, or the path to an ABI or artifact file.
+
+If an address is specified, then the ABI is fetched from Etherscan, which requires `--chain`. The ABI of the implementation is used for proxies."#,
+        value_name = "OLD"
+    )]
+    old: String,
+
+    #[clap(
+        help = "The new contract address, or the path to an ABI or artifact file.",
+        value_name = "NEW"
+    )]
+    new: String,
+
+    #[clap(
+        long,
+        help = "Exit with an error on any change, not only on breaking changes.",
+        long_help = "Exit with an error on any change, not only on breaking changes. Breaking changes exit with code 2, other changes with code 3."
+    )]
+    strict: bool,
+
+    #[clap(long, short, help = "Print the changes as JSON.")]
+    json: bool,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+impl AbiDiffArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let AbiDiffArgs { old, new, strict, json, etherscan } = self;
+        let config = Config::from(&etherscan);
+        let old = utils::load_abi(&config, None, &old).await?;
+        let new = utils::load_abi(&config, None, &new).await?;
+        let diff = AbiDiff::new(&old, &new);
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else if diff.is_empty() {
+            println!("No changes");
+        } else {
+            if diff.is_breaking() {
+                println!("{}", Paint::red("Breaking changes:"));
+                diff.breaking().for_each(|change| println!("  {change}"));
+            }
+            if diff.non_breaking().next().is_some() {
+                println!("{}", Paint::yellow("Non-breaking changes:"));
+                diff.non_breaking().for_each(|change| println!("  {change}"));
+            }
+        }
+
+        if diff.is_breaking() {
+            std::process::exit(BREAKING_CHANGES_EXIT_CODE)
+        }
+        if strict && !diff.is_empty() {
+            std::process::exit(CHANGES_EXIT_CODE)
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
 use crate::opts::EtherscanOpts;
 use clap::{Parser, ValueHint};
-use ethers::prelude::{errors::EtherscanError, Abigen, Client, MultiAbigen};
+use ethers::prelude::{errors::EtherscanError, Abigen, Address, MultiAbigen};
 use eyre::Result;
-use foundry_config::Config;
+use foundry_config::{with_key_rotation, Config};
 use std::path::{Path, PathBuf};
 
 static DEFAULT_CRATE_NAME: &str = "foundry-contracts";
@@ -79,12 +79,14 @@ impl BindArgs {
         let config = Config::from(&self.etherscan);
 
         let chain = config.chain_id.unwrap_or_default();
-        let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
-        let chain = chain.named()?;
+        let clients = config.get_etherscan_clients(chain)?;
 
-        let client = Client::new(chain, api_key)?;
-        let address = self.path_or_address.parse()?;
-        let source = match client.contract_source_code(address).await {
+        let address: Address = self.path_or_address.parse()?;
+        let source = match with_key_rotation(&clients, |client| async move {
+            client.contract_source_code(address).await
+        })
+        .await
+        {
             Ok(source) => source,
             Err(EtherscanError::InvalidApiKey) => {
                 eyre::bail!("Invalid Etherscan API key. Did you set it correctly? You may be using an API key for another Etherscan API chain (e.g. Etherscan API key for Polygonscan).")
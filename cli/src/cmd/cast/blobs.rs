@@ -0,0 +1,99 @@
+//! Retrieval of the blobs of EIP-4844 transactions
+
+use cast::blob;
+use ethers::{
+    providers::Middleware,
+    types::{Bytes, H256},
+};
+use eyre::WrapErr;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// Duration of a beacon chain slot in seconds
+const SECONDS_PER_SLOT: u64 = 12;
+
+/// Fetches the blobs of the given transaction from the beacon node and decodes their data.
+///
+/// Blobs are only kept by beacon nodes for ~18 days, so this fails for older transactions.
+pub async fn tx_blobs<M: Middleware>(
+    provider: &M,
+    tx_hash: &str,
+    beacon_url: &str,
+    to_json: bool,
+) -> eyre::Result<String>
+where
+    M::Error: 'static,
+{
+    let tx_hash = H256::from_str(tx_hash).wrap_err("invalid tx hash")?;
+    let tx = provider
+        .get_transaction(tx_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("tx not found: {:?}", tx_hash))?;
+    let versioned_hashes: Vec<H256> = match tx.other.get("blobVersionedHashes") {
+        Some(hashes) => serde_json::from_value(hashes.clone())?,
+        None => eyre::bail!("tx {:?} does not carry any blobs", tx_hash),
+    };
+    let block_number =
+        tx.block_number.ok_or_else(|| eyre::eyre!("tx {:?} is still pending", tx_hash))?;
+    let block = provider
+        .get_block(block_number)
+        .await?
+        .ok_or_else(|| eyre::eyre!("block not found: {}", block_number))?;
+
+    let client = reqwest::Client::new();
+    let beacon_url = beacon_url.trim_end_matches('/');
+    let genesis: Value = client
+        .get(format!("{beacon_url}/eth/v1/beacon/genesis"))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let genesis_time = genesis["data"]["genesis_time"]
+        .as_str()
+        .and_then(|time| time.parse::<u64>().ok())
+        .ok_or_else(|| eyre::eyre!("invalid genesis response from beacon node"))?;
+    let slot = block.timestamp.as_u64().saturating_sub(genesis_time) / SECONDS_PER_SLOT;
+
+    let sidecars: Value = client
+        .get(format!("{beacon_url}/eth/v1/beacon/blob_sidecars/{slot}"))
+        .send()
+        .await?
+        .error_for_status()
+        .wrap_err_with(|| format!("failed to fetch blob sidecars of slot {slot}"))?
+        .json()
+        .await?;
+
+    let mut blobs: Vec<Option<Bytes>> = vec![None; versioned_hashes.len()];
+    for sidecar in sidecars["data"].as_array().into_iter().flatten() {
+        let commitment: Bytes = serde_json::from_value(sidecar["kzg_commitment"].clone())?;
+        let versioned_hash = blob::kzg_to_versioned_hash(&commitment);
+        if let Some(pos) = versioned_hashes.iter().position(|hash| *hash == versioned_hash) {
+            blobs[pos] = Some(serde_json::from_value(sidecar["blob"].clone())?);
+        }
+    }
+    let blobs = blobs
+        .into_iter()
+        .zip(&versioned_hashes)
+        .map(|(blob, hash)| {
+            blob.ok_or_else(|| eyre::eyre!("blob {:?} not found in slot {slot}", hash))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let data = Bytes::from(blob::decode_blobs(blobs.iter().map(|blob| blob.as_ref())));
+
+    Ok(if to_json {
+        json!({
+            "versionedHashes": versioned_hashes,
+            "blobs": blobs,
+            "data": data,
+        })
+        .to_string()
+    } else {
+        let mut out = String::new();
+        for (hash, blob) in versioned_hashes.iter().zip(&blobs) {
+            out.push_str(&format!("{hash:?}: {} bytes\n", blob.len()));
+        }
+        out.push_str(&format!("data: {data}"));
+        out
+    })
+}
@@ -3,12 +3,31 @@ use crate::{
     opts::{EthereumOpts, TransactionOpts},
     utils::{self, parse_ether_value},
 };
-use cast::{Cast, TxBuilder};
+use cast::{
+    state_override::{
+        apply_state_override, is_state_override_unsupported, parse_account_override,
+        read_state_override, AccountOverride, StateOverride,
+    },
+    Cast, SimpleCast, TxBuilder,
+};
 use clap::Parser;
-use ethers::types::{BlockId, NameOrAddress, U256};
+use ethers::{
+    abi::Function,
+    providers::Middleware,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, BlockNumber,
+        Eip1559TransactionRequest, NameOrAddress, TransactionRequest, U256,
+    },
+};
 use eyre::WrapErr;
-use foundry_config::Config;
-use std::str::FromStr;
+use forge::{
+    decode::decode_revert,
+    executor::{opts::EvmOpts, Backend, ExecutorBuilder, RawCallResult},
+    revm::TransactTo,
+};
+use foundry_common::abi::{encode_args, get_func};
+use foundry_config::{find_project_root_path, Config};
+use std::{path::PathBuf, str::FromStr};
 
 #[derive(Debug, Parser)]
 pub struct CallArgs {
@@ -49,10 +68,192 @@ pub struct CallArgs {
     )]
     block: Option<BlockId>,
 
+    #[clap(
+        long,
+        help = "Execute the call locally on the RPC cache, without sending any request.",
+        long_help = "Execute the call locally on the RPC cache of a previous fork of the block, without sending any request. Requires `--block` to be a block number, full function signatures and no ENS names.",
+        requires = "block"
+    )]
+    offline: bool,
+
+    #[clap(flatten)]
+    state_override: StateOverrideArgs,
+
     #[clap(subcommand)]
     command: Option<CallSubcommands>,
 }
 
+/// State overrides of `cast call` and `cast estimate`
+#[derive(Debug, Clone, Default, Parser)]
+#[clap(next_help_heading = "State overrides")]
+pub struct StateOverrideArgs {
+    #[clap(
+        long = "override",
+        help = "Override the state of an account.",
+        long_help = r#"Override the state of an account for the duration of the call.
+
+Format: <ADDRESS>:balance=<WEI>,nonce=<NONCE>,code=<HEX|@FILE>,slot:<SLOT>=<VALUE>
+
+All fields are optional and `slot:<SLOT>=<VALUE>` can be repeated. If the RPC does not support state overrides, the call is executed locally on a fork of the RPC."#,
+        value_name = "OVERRIDE",
+        value_parser = parse_account_override
+    )]
+    overrides: Vec<(Address, AccountOverride)>,
+
+    #[clap(
+        long,
+        help = "A JSON file with state overrides in the format of `eth_call`.",
+        value_name = "PATH"
+    )]
+    override_file: Option<PathBuf>,
+}
+
+impl StateOverrideArgs {
+    /// Returns all state overrides, the `--override` values take precedence over the file
+    pub fn state_override(&self) -> eyre::Result<StateOverride> {
+        let mut state_override = match self.override_file {
+            Some(ref path) => read_state_override(path)?,
+            None => StateOverride::default(),
+        };
+        for (address, account) in self.overrides.iter().cloned() {
+            state_override.entry(address).or_default().merge(account);
+        }
+        Ok(state_override)
+    }
+}
+
+/// Executes the transaction locally on a fork of the configured RPC with the state overrides
+/// applied
+///
+/// This is used if the RPC does not support state overrides. In `offline` mode the fork is served
+/// from the RPC cache only, which requires `block` to be a block number.
+pub async fn emulate_with_state_override(
+    config: &Config,
+    tx: &TypedTransaction,
+    block: Option<BlockId>,
+    state_override: &StateOverride,
+    offline: bool,
+) -> eyre::Result<RawCallResult> {
+    let mut evm_opts = Config::figment_with_root(find_project_root_path()?).extract::<EvmOpts>()?;
+    let fork_url = config.get_rpc_url_or_localhost_http()?.into_owned();
+    evm_opts.offline = offline;
+    if offline {
+        // locates the block in the RPC cache
+        evm_opts.env.chain_id = config.chain_id.map(|chain| chain.id()).or(evm_opts.env.chain_id);
+    }
+    evm_opts.fork_block_number = match block {
+        Some(BlockId::Number(BlockNumber::Number(number))) => Some(number.as_u64()),
+        _ if offline => eyre::bail!("Offline calls require `--block` to be a block number"),
+        None | Some(BlockId::Number(BlockNumber::Latest | BlockNumber::Pending)) => None,
+        // tags and hashes are forked at the number of their block
+        Some(block) => {
+            let provider = utils::get_provider(config)?;
+            let number = provider
+                .get_block(block)
+                .await?
+                .and_then(|block| block.number)
+                .ok_or_else(|| eyre::eyre!("block {block:?} not found"))?;
+            Some(number.as_u64())
+        }
+    };
+
+    let env = evm_opts.fork_evm_env(&fork_url).await?;
+    evm_opts.fork_url = Some(fork_url);
+    let db = Backend::spawn(evm_opts.get_fork(config, env.clone()));
+    let mut executor = ExecutorBuilder::default()
+        .with_config(env)
+        .with_spec(utils::evm_spec(&config.evm_version))
+        .build(db);
+    apply_state_override(&mut executor, state_override)?;
+
+    let mut env = executor.env().clone();
+    env.tx.caller = tx.from().copied().unwrap_or_default();
+    env.tx.transact_to = match tx.to() {
+        Some(NameOrAddress::Address(to)) => TransactTo::Call(*to),
+        _ => TransactTo::create(),
+    };
+    env.tx.data = tx.data().cloned().unwrap_or_default().0;
+    env.tx.value = tx.value().copied().unwrap_or_default();
+    if let Some(gas) = tx.gas() {
+        env.tx.gas_limit = gas.as_u64();
+    }
+    // calls don't pay for gas
+    env.tx.gas_price = U256::zero();
+    env.tx.gas_priority_fee = None;
+    env.block.basefee = U256::zero();
+
+    let result = executor.call_raw_with_env(env)?;
+    if result.reverted {
+        let reason = decode_revert(&result.result, None, Some(result.exit_reason))
+            .unwrap_or_else(|_| format!("0x{}", hex::encode(&result.result)));
+        eyre::bail!("Execution reverted: {reason}")
+    }
+    Ok(result)
+}
+
+/// Builds the transaction of an offline call, without resolving names or signatures through the
+/// RPC or etherscan
+fn offline_tx(
+    sender: Address,
+    to: Option<NameOrAddress>,
+    sig: Option<String>,
+    args: Vec<String>,
+    data: Option<String>,
+    tx: &TransactionOpts,
+    command: Option<CallSubcommands>,
+) -> eyre::Result<(TypedTransaction, Option<Function>)> {
+    let to = match to {
+        Some(NameOrAddress::Name(name)) => {
+            eyre::bail!("ENS name `{name}` can't be resolved in offline mode")
+        }
+        to => to,
+    };
+    let offline_func = |sig: &str| {
+        if !sig.contains('(') {
+            eyre::bail!("`{sig}` is not a full signature, which is required in offline mode")
+        }
+        get_func(sig)
+    };
+
+    let (input, value, func) = match command {
+        Some(CallSubcommands::Create { code, sig, args, value }) => {
+            let mut input = hex::decode(code.strip_prefix("0x").unwrap_or(&code))?;
+            if let Some(sig) = sig {
+                input.extend(encode_args(&offline_func(&sig)?, &args)?);
+            }
+            (input, value, None)
+        }
+        _ => match (sig, data) {
+            (Some(sig), _) if sig.starts_with("0x") => (hex::decode(&sig[2..])?, tx.value, None),
+            (Some(sig), _) => {
+                let func = offline_func(&sig)?;
+                (encode_args(&func, &args)?, tx.value, Some(func))
+            }
+            (None, Some(data)) => (
+                hex::decode(data).wrap_err("Expected hex encoded function data")?,
+                tx.value,
+                None,
+            ),
+            (None, None) => (vec![], tx.value, None),
+        },
+    };
+
+    let tx: TypedTransaction = if tx.legacy {
+        let mut req = TransactionRequest::new().from(sender).data(input);
+        req.to = to;
+        req.value = value;
+        req.gas = tx.gas_limit;
+        req.into()
+    } else {
+        let mut req = Eip1559TransactionRequest::new().from(sender).data(input);
+        req.to = to;
+        req.value = value;
+        req.gas = tx.gas_limit;
+        req.into()
+    };
+    Ok((tx, func))
+}
+
 #[derive(Debug, Parser)]
 pub enum CallSubcommands {
     #[clap(name = "--create", about = "Simulate a contract deployment.")]
@@ -77,9 +278,20 @@ Examples: 1ether, 10gwei, 0.01ether"#,
 }
 impl CallArgs {
     pub async fn run(self) -> eyre::Result<()> {
-        let CallArgs { to, sig, args, data, tx, eth, command, block } = self;
+        let CallArgs { to, sig, args, data, tx, eth, command, block, offline, state_override } =
+            self;
+        let state_override = state_override.state_override()?;
 
         let config = Config::from(&eth);
+        if offline {
+            let sender = eth.wallet.sender().await;
+            let (tx, func) = offline_tx(sender, to, sig, args, data, &tx, command)?;
+            let result = emulate_with_state_override(&config, &tx, block, &state_override, true)
+                .await?;
+            println!("{}", SimpleCast::decode_call_output(&result.result.into(), func.as_ref())?);
+            return Ok(())
+        }
+
         let provider = utils::get_provider(&config)?;
         let chain = utils::get_chain(config.chain_id, &provider).await?;
         let sender = eth.wallet.sender().await;
@@ -120,7 +332,27 @@ impl CallArgs {
         };
 
         let builder_output = builder.build();
-        println!("{}", Cast::new(provider).call(builder_output, block).await?);
+        let cast = Cast::new(&provider);
+        if state_override.is_empty() {
+            println!("{}", cast.call(builder_output, block).await?);
+            return Ok(())
+        }
+
+        let output = match cast
+            .call_with_state_override(builder_output.clone(), block, &state_override)
+            .await
+        {
+            Err(err) if is_state_override_unsupported(&err) => {
+                eprintln!("The RPC does not support state overrides, executing the call locally.");
+                let (tx, func) = builder_output;
+                let result =
+                    emulate_with_state_override(&config, &tx, block, &state_override, false)
+                        .await?;
+                cast.format_call_output(&tx, block, result.result.into(), func).await?
+            }
+            res => res?,
+        };
+        println!("{output}");
         Ok(())
     }
 }
@@ -141,6 +373,52 @@ mod tests {
         assert_eq!(args.data, Some(data));
     }
 
+    #[test]
+    fn can_merge_state_overrides() {
+        let args = CallArgs::parse_from([
+            "foundry-cli",
+            "--override",
+            "0x00000000000000000000000000000000000000aa:balance=1,slot:1=2",
+            "--override",
+            "0x00000000000000000000000000000000000000aa:nonce=3,slot:2=4",
+        ]);
+        let state_override = args.state_override.state_override().unwrap();
+        let account = &state_override[&Address::from_low_u64_be(0xaa)];
+        assert_eq!(account.balance, Some(1u64.into()));
+        assert_eq!(account.nonce, Some(3u64.into()));
+        assert_eq!(account.state_diff.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn offline_requires_block() {
+        assert!(CallArgs::try_parse_from(["foundry-cli", "--offline"]).is_err());
+        let args = CallArgs::parse_from(["foundry-cli", "--offline", "--block", "1"]);
+        assert!(args.offline);
+    }
+
+    #[test]
+    fn can_build_offline_tx() {
+        let offline_call = |args: &[&str]| {
+            let args = CallArgs::parse_from(
+                ["foundry-cli", "--offline", "--block", "1"].iter().chain(args),
+            );
+            let CallArgs { to, sig, args, data, tx, command, .. } = args;
+            offline_tx(Address::zero(), to, sig, args, data, &tx, command)
+        };
+
+        let to = format!("{:?}", Address::from_low_u64_be(1));
+        let (tx, func) =
+            offline_call(&[&to, "balanceOf(address)(uint256)", &to, "--value", "2"]).unwrap();
+        assert_eq!(tx.to(), Some(&Address::from_low_u64_be(1).into()));
+        assert_eq!(tx.value(), Some(&2u64.into()));
+        assert_eq!(tx.data().unwrap().len(), 36);
+        assert_eq!(func.unwrap().name, "balanceOf");
+
+        // names need the RPC or etherscan
+        assert!(offline_call(&["vitalik.eth"]).is_err());
+        assert!(offline_call(&[&to, "balanceOf", &to]).is_err());
+    }
+
     #[test]
     fn call_sig_and_data_exclusive() {
         let data = hex::encode("hello");
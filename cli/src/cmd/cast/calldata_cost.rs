@@ -0,0 +1,106 @@
+//! cast calldata-cost subcommand
+
+use crate::{cmd::Cmd, stdin};
+use cast::calldata_cost::{CalldataCostReport, CalldataCostRule};
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use eyre::WrapErr;
+use std::path::Path;
+
+/// CLI arguments for `cast calldata-cost`.
+#[derive(Debug, Clone, Parser)]
+pub struct CalldataCostArgs {
+    #[clap(
+        help = "The calldata, either hex encoded or the path to a file containing it.",
+        long_help = "The calldata, either hex encoded or the path to a file containing it. Files that don't contain hex are read as raw bytes.",
+        value_name = "HEX|FILE"
+    )]
+    data: Option<String>,
+
+    #[clap(
+        long,
+        help = "The rule to calculate the gas with.",
+        long_help = "The rule to calculate the gas with, one of frontier (pre EIP-2028), istanbul (EIP-2028), prague (EIP-7623 floor), op-fjord or arbitrum. The L2 rules are estimates based on the FastLZ compressed size.",
+        default_value = "istanbul",
+        value_name = "RULE",
+        conflicts_with = "compare"
+    )]
+    rule: CalldataCostRule,
+
+    #[clap(long, help = "Print a table of the gas under all rules.")]
+    compare: bool,
+
+    #[clap(long, short, help = "Print the result as JSON.")]
+    json: bool,
+}
+
+impl Cmd for CalldataCostArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let CalldataCostArgs { data, rule, compare, json } = self;
+        let data = read_calldata(stdin::unwrap_line(data)?)?;
+
+        let rules = if compare { CalldataCostRule::ALL.to_vec() } else { vec![rule] };
+        let report = CalldataCostReport::new(&data, &rules);
+
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else if compare {
+            println!(
+                "{} bytes ({} zero, {} non-zero), {} bytes FastLZ compressed",
+                report.size, report.zero_bytes, report.non_zero_bytes, report.fastlz_size
+            );
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header(vec!["Rule", "Gas", "Gas per byte"]);
+            for cost in &report.costs {
+                let per_byte =
+                    if data.is_empty() { 0. } else { cost.gas as f64 / data.len() as f64 };
+                table.add_row(vec![
+                    cost.rule.to_string(),
+                    cost.gas.to_string(),
+                    format!("{per_byte:.2}"),
+                ]);
+            }
+            println!("{table}");
+        } else {
+            println!("{}", report.costs[0].gas);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the calldata of the hex string, or of the file at the path
+fn read_calldata(data: String) -> eyre::Result<Vec<u8>> {
+    let path = Path::new(&data);
+    if !data.starts_with("0x") && path.is_file() {
+        let content = std::fs::read(path)
+            .wrap_err_with(|| format!("failed to read calldata from {}", path.display()))?;
+        let hex = String::from_utf8_lossy(&content);
+        let hex = hex.trim();
+        return Ok(hex::decode(hex.strip_prefix("0x").unwrap_or(hex)).unwrap_or(content))
+    }
+    let data = data.trim();
+    hex::decode(data.strip_prefix("0x").unwrap_or(data)).wrap_err("invalid hex calldata")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_calldata() {
+        assert_eq!(read_calldata("0x0102".to_string()).unwrap(), vec![1, 2]);
+        assert_eq!(read_calldata("0102".to_string()).unwrap(), vec![1, 2]);
+        assert!(read_calldata("0xzz".to_string()).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let hex_file = dir.path().join("calldata.hex");
+        std::fs::write(&hex_file, "0x0102\n").unwrap();
+        assert_eq!(read_calldata(hex_file.display().to_string()).unwrap(), vec![1, 2]);
+        let raw_file = dir.path().join("calldata.bin");
+        std::fs::write(&raw_file, [0xff, 0x00]).unwrap();
+        assert_eq!(read_calldata(raw_file.display().to_string()).unwrap(), vec![0xff, 0x00]);
+    }
+}
@@ -0,0 +1,99 @@
+//! cast create2-deployer subcommand
+
+use crate::{opts::EthereumOpts, utils};
+use clap::Parser;
+use ethers::{
+    prelude::MiddlewareBuilder,
+    providers::Middleware,
+    types::{Address, TransactionRequest, U256},
+};
+use eyre::WrapErr;
+use foundry_config::Config;
+
+/// The keyless signer of [DEPLOYMENT_TX]
+const DEPLOYMENT_SIGNER: &str = "0x3fab184622dc19b6109349b94811493bf2a45362";
+
+/// Gas limit of [DEPLOYMENT_TX]
+const DEPLOYMENT_GAS_LIMIT: u64 = 100_000;
+
+/// Gas price of [DEPLOYMENT_TX], 100 gwei
+const DEPLOYMENT_GAS_PRICE: u64 = 100_000_000_000;
+
+/// The pre-signed (non EIP-155) transaction that deploys the canonical CREATE2 deployer to the
+/// same address on every chain, see <https://github.com/Arachnid/deterministic-deployment-proxy>
+const DEPLOYMENT_TX: &str = "f8a58085174876e800830186a08080b853604580600e600039806000f350fe7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf31ba02222222222222222222222222222222222222222222222222222222222222222a02222222222222222222222222222222222222222222222222222222222222222";
+
+/// CLI arguments for `cast create2-deployer`.
+#[derive(Debug, Parser)]
+pub struct Create2DeployerArgs {
+    #[clap(
+        long,
+        help = "Only check whether the CREATE2 deployer is present, without deploying it."
+    )]
+    check: bool,
+
+    #[clap(
+        long,
+        help = "The number of confirmations until the receipts are fetched.",
+        default_value = "1",
+        value_name = "CONFIRMATIONS"
+    )]
+    confirmations: usize,
+
+    #[clap(flatten)]
+    eth: EthereumOpts,
+}
+
+impl Create2DeployerArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let Create2DeployerArgs { check, confirmations, eth } = self;
+        let config = Config::from(&eth);
+        let provider = utils::get_provider(&config)?;
+        let chain = utils::get_chain(config.chain_id, &provider).await?;
+
+        let deployer = config.create2_deployer_for(chain);
+        if !provider.get_code(deployer, None).await?.is_empty() {
+            println!("CREATE2 deployer {deployer:?} is present on {chain}");
+            return Ok(())
+        }
+        if check {
+            eyre::bail!("CREATE2 deployer {deployer:?} is not present on {chain}")
+        }
+        if deployer != Config::DEFAULT_CREATE2_DEPLOYER {
+            eyre::bail!(
+                "Only the canonical CREATE2 deployer {:?} can be deployed, but {deployer:?} is configured for {chain}",
+                Config::DEFAULT_CREATE2_DEPLOYER
+            )
+        }
+
+        // the keyless signer of the deployment transaction needs to pay for its gas
+        let signer: Address = DEPLOYMENT_SIGNER.parse()?;
+        let cost = U256::from(DEPLOYMENT_GAS_LIMIT) * U256::from(DEPLOYMENT_GAS_PRICE);
+        let balance = provider.get_balance(signer, None).await?;
+        if balance < cost {
+            let wallet = eth.wallet.signer(chain.id()).await.wrap_err(
+                "A wallet is required to fund the signer of the CREATE2 deployer deployment",
+            )?;
+            let funder = provider.clone().with_signer(wallet);
+            println!("Funding deployment signer {signer:?} with {} wei", cost - balance);
+            funder
+                .send_transaction(TransactionRequest::pay(signer, cost - balance), None)
+                .await?
+                .confirmations(confirmations)
+                .await?;
+        }
+
+        let pending = provider
+            .send_raw_transaction(hex::decode(DEPLOYMENT_TX)?.into())
+            .await
+            .wrap_err("Failed to send the deployment transaction, the chain may only accept replay protected (EIP-155) transactions")?;
+        let tx_hash = *pending;
+        pending.confirmations(confirmations).await?;
+
+        if provider.get_code(deployer, None).await?.is_empty() {
+            eyre::bail!("Deployment transaction {tx_hash:#x} did not deploy the CREATE2 deployer")
+        }
+        println!("Deployed CREATE2 deployer {deployer:?} in transaction {tx_hash:#x}");
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
 // cast estimate subcommands
+use super::call::{emulate_with_state_override, StateOverrideArgs};
 use crate::{
     opts::{EtherscanOpts, RpcOpts},
     utils::{self, parse_ether_value},
 };
-use cast::{Cast, TxBuilder};
+use cast::{state_override::is_state_override_unsupported, Cast, TxBuilder};
 use clap::Parser;
 use ethers::types::{NameOrAddress, U256};
 use eyre::Result;
@@ -47,6 +48,9 @@ pub struct EstimateArgs {
     #[clap(flatten)]
     etherscan: EtherscanOpts,
 
+    #[clap(flatten)]
+    state_override: StateOverrideArgs,
+
     #[clap(subcommand)]
     command: Option<EstimateSubcommands>,
 }
@@ -77,7 +81,9 @@ pub enum EstimateSubcommands {
 
 impl EstimateArgs {
     pub async fn run(self) -> Result<()> {
-        let EstimateArgs { from, to, sig, args, value, rpc, etherscan, command } = self;
+        let EstimateArgs { from, to, sig, args, value, rpc, etherscan, state_override, command } =
+            self;
+        let state_override = state_override.state_override()?;
 
         let figment = Figment::from(Config::figment()).merge(etherscan).merge(rpc);
         let config = Config::from_provider(figment);
@@ -109,7 +115,24 @@ impl EstimateArgs {
         };
 
         let builder_output = builder.peek();
-        let gas = Cast::new(&provider).estimate(builder_output).await?;
+        let cast = Cast::new(&provider);
+        let gas = if state_override.is_empty() {
+            cast.estimate(builder_output).await?
+        } else {
+            match cast.estimate_with_state_override(builder_output, &state_override).await {
+                Err(err) if is_state_override_unsupported(&err) => {
+                    eprintln!(
+                        "The RPC does not support state overrides, estimating the gas locally."
+                    );
+                    let (tx, _) = builder_output;
+                    emulate_with_state_override(&config, tx, None, &state_override, false)
+                        .await?
+                        .gas_used
+                        .into()
+                }
+                res => res?,
+            }
+        };
         println!("{gas}");
         Ok(())
     }
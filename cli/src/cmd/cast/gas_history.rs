@@ -0,0 +1,321 @@
+//! cast gas-history subcommand
+
+use crate::{opts::RpcOpts, utils};
+use clap::Parser;
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers::{prelude::*, utils::format_units};
+use eyre::{Result, WrapErr};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use serde::{Deserialize, Serialize};
+
+/// The max number of blocks most nodes return the fee history of at once
+const MAX_BLOCKS_PER_REQUEST: u64 = 1024;
+
+/// CLI arguments for `cast gas-history`.
+#[derive(Debug, Clone, Parser)]
+pub struct GasHistoryArgs {
+    /// The number of blocks to aggregate, ending at `--block`
+    #[clap(long, default_value = "20", value_name = "N")]
+    blocks: u64,
+
+    /// The last block to aggregate
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, short = 'B', default_value = "latest")]
+    block: BlockNumber,
+
+    /// The percentiles of the priority fees paid in each block, comma separated
+    #[clap(long, value_delimiter = ',', default_value = "10,50,90", value_name = "PERCENTILES")]
+    percentiles: Vec<f64>,
+
+    /// Print the blocks and the summary as JSON
+    #[clap(long, short)]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl_figment_convert_cast!(GasHistoryArgs);
+
+impl figment::Provider for GasHistoryArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("GasHistoryArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        Ok(figment::value::Map::from([(Config::selected_profile(), self.rpc.dict())]))
+    }
+}
+
+/// The response of `eth_feeHistory`, including the blob fields of EIP-4844
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistoryResponse {
+    oldest_block: U256,
+    /// Contains the base fee of the block after the newest block as well
+    base_fee_per_gas: Vec<U256>,
+    gas_used_ratio: Vec<f64>,
+    #[serde(default)]
+    reward: Vec<Vec<U256>>,
+    #[serde(default)]
+    base_fee_per_blob_gas: Vec<U256>,
+    #[serde(default)]
+    blob_gas_used_ratio: Vec<f64>,
+}
+
+/// The fees of a single block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockFees {
+    pub number: u64,
+    pub base_fee: U256,
+    pub gas_used_ratio: f64,
+    /// The priority fees at the requested percentiles
+    pub rewards: Vec<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_base_fee: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used_ratio: Option<f64>,
+}
+
+/// The aggregated fees of all blocks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasHistory {
+    pub percentiles: Vec<f64>,
+    pub blocks: Vec<BlockFees>,
+    /// The base fee of the block after the newest block
+    pub next_base_fee: U256,
+    /// The blob base fee of the block after the newest block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_blob_base_fee: Option<U256>,
+    pub min_base_fee: U256,
+    pub avg_base_fee: U256,
+    pub max_base_fee: U256,
+    /// The change of the base fee from the oldest to the next block, in percent
+    pub base_fee_trend: f64,
+    /// The median of the priority fees of all blocks at each percentile
+    pub median_rewards: Vec<U256>,
+}
+
+impl GasHistoryArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let provider = utils::get_provider(&config)?;
+
+        let newest = match self.block {
+            BlockNumber::Number(number) => number.as_u64(),
+            tag => provider
+                .get_block(tag)
+                .await?
+                .and_then(|block| block.number)
+                .ok_or_else(|| eyre::eyre!("block {tag:?} not found"))?
+                .as_u64(),
+        };
+        let blocks = self.blocks.clamp(1, newest + 1);
+
+        // request the oldest blocks first, so the responses can be appended
+        let mut responses = Vec::new();
+        let mut oldest = newest + 1 - blocks;
+        while oldest <= newest {
+            let count = MAX_BLOCKS_PER_REQUEST.min(newest + 1 - oldest);
+            let last = BlockNumber::Number((oldest + count - 1).into());
+            let response: FeeHistoryResponse = provider
+                .request("eth_feeHistory", (U256::from(count), last, &self.percentiles))
+                .await
+                .wrap_err("failed to fetch the fee history")?;
+            responses.push(response);
+            oldest += count;
+        }
+
+        let history = GasHistory::new(self.percentiles, responses)?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&history)?);
+        } else {
+            println!("{}", history.table());
+            println!("{}", history.summary());
+        }
+        Ok(())
+    }
+}
+
+impl GasHistory {
+    /// Aggregates consecutive `eth_feeHistory` responses, ordered from the oldest to the newest
+    fn new(percentiles: Vec<f64>, responses: Vec<FeeHistoryResponse>) -> Result<Self> {
+        let mut blocks = Vec::new();
+        let mut next_base_fee = U256::zero();
+        let mut next_blob_base_fee = None;
+        for response in responses {
+            let oldest = response.oldest_block.as_u64();
+            for (i, gas_used_ratio) in response.gas_used_ratio.iter().enumerate() {
+                blocks.push(BlockFees {
+                    number: oldest + i as u64,
+                    base_fee: response.base_fee_per_gas.get(i).copied().unwrap_or_default(),
+                    gas_used_ratio: *gas_used_ratio,
+                    rewards: response.reward.get(i).cloned().unwrap_or_default(),
+                    blob_base_fee: response.base_fee_per_blob_gas.get(i).copied(),
+                    blob_gas_used_ratio: response.blob_gas_used_ratio.get(i).copied(),
+                });
+            }
+            next_base_fee = response.base_fee_per_gas.last().copied().unwrap_or_default();
+            next_blob_base_fee = response.base_fee_per_blob_gas.last().copied();
+        }
+        if blocks.is_empty() {
+            eyre::bail!("the node returned no fee history")
+        }
+
+        let base_fees = blocks.iter().map(|block| block.base_fee);
+        let min_base_fee = base_fees.clone().min().unwrap_or_default();
+        let max_base_fee = base_fees.clone().max().unwrap_or_default();
+        let avg_base_fee = base_fees.fold(U256::zero(), |sum, fee| sum + fee) / blocks.len();
+        let first = blocks[0].base_fee;
+        let base_fee_trend = if first.is_zero() {
+            0.
+        } else {
+            (to_f64(next_base_fee) - to_f64(first)) / to_f64(first) * 100.
+        };
+        let median_rewards = (0..percentiles.len())
+            .map(|i| {
+                let mut rewards: Vec<U256> =
+                    blocks.iter().filter_map(|block| block.rewards.get(i).copied()).collect();
+                rewards.sort();
+                rewards.get(rewards.len() / 2).copied().unwrap_or_default()
+            })
+            .collect();
+
+        Ok(Self {
+            percentiles,
+            blocks,
+            next_base_fee,
+            next_blob_base_fee,
+            min_base_fee,
+            avg_base_fee,
+            max_base_fee,
+            base_fee_trend,
+            median_rewards,
+        })
+    }
+
+    /// Returns a table of the fees of every block, in gwei
+    fn table(&self) -> Table {
+        let has_blobs = self.blocks.iter().any(|block| block.blob_base_fee.is_some());
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        let mut header = vec!["Block".to_string(), "Base fee".to_string(), "Gas used".to_string()];
+        header.extend(self.percentiles.iter().map(|p| format!("p{p} priority fee")));
+        if has_blobs {
+            header.extend(["Blob base fee".to_string(), "Blob gas used".to_string()]);
+        }
+        table.set_header(header);
+        for block in &self.blocks {
+            let mut row = vec![
+                block.number.to_string(),
+                gwei(block.base_fee),
+                format!("{:.1}%", block.gas_used_ratio * 100.),
+            ];
+            row.extend(block.rewards.iter().map(|reward| gwei(*reward)));
+            if has_blobs {
+                row.push(block.blob_base_fee.map(gwei).unwrap_or_default());
+                row.push(
+                    block
+                        .blob_gas_used_ratio
+                        .map(|ratio| format!("{:.1}%", ratio * 100.))
+                        .unwrap_or_default(),
+                );
+            }
+            table.add_row(row);
+        }
+        table
+    }
+
+    /// Returns the summary of the base fee trend and suggested fee parameters
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "Base fee: min {} gwei, avg {} gwei, max {} gwei, next {} gwei ({:+.1}%)",
+            gwei(self.min_base_fee),
+            gwei(self.avg_base_fee),
+            gwei(self.max_base_fee),
+            gwei(self.next_base_fee),
+            self.base_fee_trend
+        );
+        for (percentile, reward) in self.percentiles.iter().zip(&self.median_rewards) {
+            summary.push_str(&format!(
+                "\np{percentile} priority fee: {} gwei, max fee: {} gwei",
+                gwei(*reward),
+                // leaves room for the base fee to rise for a few blocks
+                gwei(self.next_base_fee * 2 + *reward)
+            ));
+        }
+        if let Some(blob_base_fee) = self.next_blob_base_fee {
+            summary.push_str(&format!("\nNext blob base fee: {} gwei", gwei(blob_base_fee)));
+        }
+        summary
+    }
+}
+
+fn gwei(value: U256) -> String {
+    format_units(value, "gwei").unwrap_or_else(|_| value.to_string())
+}
+
+fn to_f64(value: U256) -> f64 {
+    value.to_string().parse().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(oldest: u64, base_fees: &[u64], blob: bool) -> FeeHistoryResponse {
+        let blocks = base_fees.len() - 1;
+        FeeHistoryResponse {
+            oldest_block: oldest.into(),
+            base_fee_per_gas: base_fees.iter().map(|fee| U256::from(*fee)).collect(),
+            gas_used_ratio: vec![0.5; blocks],
+            reward: (0..blocks).map(|i| vec![U256::from(i), U256::from(10 * i)]).collect(),
+            base_fee_per_blob_gas: if blob { vec![1u64.into(); blocks + 1] } else { vec![] },
+            blob_gas_used_ratio: if blob { vec![0.25; blocks] } else { vec![] },
+        }
+    }
+
+    #[test]
+    fn can_aggregate_fee_history() {
+        let history = GasHistory::new(
+            vec![10., 90.],
+            vec![response(10, &[100, 110, 120], false), response(12, &[120, 130, 150], true)],
+        )
+        .unwrap();
+
+        assert_eq!(history.blocks.len(), 4);
+        assert_eq!(history.blocks[3].number, 13);
+        assert_eq!(history.blocks[0].blob_base_fee, None);
+        assert_eq!(history.blocks[3].blob_gas_used_ratio, Some(0.25));
+        assert_eq!(history.next_base_fee, 150u64.into());
+        assert_eq!(history.next_blob_base_fee, Some(1u64.into()));
+        assert_eq!(history.min_base_fee, 100u64.into());
+        assert_eq!(history.max_base_fee, 130u64.into());
+        assert_eq!(history.avg_base_fee, 115u64.into());
+        assert_eq!(history.base_fee_trend, 50.);
+        assert_eq!(history.median_rewards, vec![U256::from(1), U256::from(10)]);
+    }
+
+    #[test]
+    fn can_parse_fee_history() {
+        let response: FeeHistoryResponse = serde_json::from_str(
+            r#"{
+                "oldestBlock": "0x1",
+                "baseFeePerGas": ["0x64", "0x6e"],
+                "gasUsedRatio": [0.5],
+                "reward": [["0x1", "0x2"]],
+                "baseFeePerBlobGas": ["0x1", "0x1"],
+                "blobGasUsedRatio": [0.0]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(response.reward, vec![vec![U256::from(1), U256::from(2)]]);
+        assert_eq!(response.base_fee_per_blob_gas.len(), 2);
+    }
+}
@@ -49,9 +49,9 @@ impl InterfaceArgs {
         let source = if Path::new(&path_or_address).exists() {
             AbiPath::Local { path: path_or_address, name }
         } else {
-            let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
+            let clients = config.get_etherscan_clients(chain)?;
             let chain = chain.named()?;
-            AbiPath::Etherscan { chain, api_key, address: path_or_address.parse()? }
+            AbiPath::Etherscan { chain, clients, address: path_or_address.parse()? }
         };
         let interfaces = SimpleCast::generate_interface(source).await?;
 
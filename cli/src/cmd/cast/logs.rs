@@ -0,0 +1,464 @@
+//! cast logs subcommand
+
+use crate::{
+    opts::{EtherscanOpts, RpcOpts},
+    utils,
+};
+use clap::{Parser, ValueEnum};
+use ethers::{
+    abi::{Abi, Event, RawLog, Token},
+    prelude::*,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::{
+    abi::{format_token, get_event, get_indexed_event},
+    RetryProvider,
+};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use std::collections::BTreeMap;
+use tracing::trace;
+
+/// CLI arguments for `cast logs`.
+#[derive(Debug, Clone, Parser)]
+pub struct LogsArgs {
+    /// The event signature or topic 0 to filter by
+    ///
+    /// Logs are decoded if an event signature is given, for example
+    /// "Transfer(address indexed from, address indexed to, uint256 value)".
+    #[clap(value_name = "SIG_OR_TOPIC")]
+    sig_or_topic: Option<String>,
+
+    /// The contract address to filter by
+    #[clap(long)]
+    address: Option<Address>,
+
+    /// The block to start searching from
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending. Defaults to the
+    /// `--to-block`.
+    #[clap(long)]
+    from_block: Option<BlockNumber>,
+
+    /// The block to stop searching at
+    ///
+    /// Can also be the tags earliest, finalized, safe, latest, or pending.
+    #[clap(long, default_value = "latest")]
+    to_block: BlockNumber,
+
+    /// An ABI file, artifact or contract address to decode the logs with
+    ///
+    /// If an address is specified, then the ABI is fetched from Etherscan.
+    #[clap(long, value_name = "PATH_OR_ADDRESS")]
+    abi: Option<String>,
+
+    /// The max number of blocks to request logs for at once
+    ///
+    /// Ranges the RPC rejects, for example because they contain too many logs, are split further.
+    #[clap(long, default_value = "10000", value_name = "BLOCKS")]
+    chunk_size: u64,
+
+    /// The output format
+    #[clap(long, value_enum, default_value = "text")]
+    format: LogsFormat,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+/// The output formats of `cast logs`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogsFormat {
+    Text,
+    /// One JSON object per line
+    Json,
+    Csv,
+}
+
+impl_figment_convert_cast!(LogsArgs);
+
+impl figment::Provider for LogsArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("LogsArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.rpc.dict();
+        dict.extend(self.etherscan.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl LogsArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let provider = utils::get_provider(&config)?;
+
+        let mut decoder = LogDecoder::default();
+        let mut filter = Filter::new();
+        if let Some(address) = self.address {
+            filter = filter.address(address);
+        }
+        if let Some(ref sig_or_topic) = self.sig_or_topic {
+            match sig_or_topic.parse::<H256>() {
+                Ok(topic) => filter = filter.topic0(topic),
+                Err(_) => {
+                    let event = get_event(sig_or_topic)?;
+                    filter = filter.topic0(event.signature());
+                    decoder.add_event(event);
+                }
+            }
+        }
+        if let Some(ref abi) = self.abi {
+            let chain = utils::get_chain(config.chain_id, &provider).await?;
+            decoder.add_abi(utils::load_abi(&config, Some(chain), abi).await?);
+        }
+
+        let to = resolve_block(&provider, self.to_block).await?;
+        let from = match self.from_block {
+            Some(block) => resolve_block(&provider, block).await?,
+            None => to,
+        };
+        if from > to {
+            eyre::bail!("--from-block {from} is after --to-block {to}");
+        }
+
+        let logs = get_logs_chunked(&provider, &filter, from, to, self.chunk_size).await?;
+        let records = logs.into_iter().map(|log| LogRecord::new(&decoder, log)).collect::<Vec<_>>();
+
+        match self.format {
+            LogsFormat::Text => {
+                for record in &records {
+                    println!("{}", record.to_text());
+                }
+            }
+            LogsFormat::Json => {
+                for record in &records {
+                    println!("{}", serde_json::to_string(&record.to_json())?);
+                }
+            }
+            LogsFormat::Csv => print!("{}", to_csv(&records)),
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number of the given block
+async fn resolve_block(provider: &RetryProvider, block: BlockNumber) -> Result<u64> {
+    match block {
+        BlockNumber::Number(number) => Ok(number.as_u64()),
+        BlockNumber::Earliest => Ok(0),
+        block => provider
+            .get_block(block)
+            .await?
+            .and_then(|block| block.number)
+            .map(|number| number.as_u64())
+            .ok_or_else(|| eyre::eyre!("block {block:?} not found")),
+    }
+}
+
+/// Returns the block ranges of at most `chunk_size` blocks between `from` and `to`, inclusive
+fn chunk_ranges(from: u64, to: u64, chunk_size: u64) -> Vec<(u64, u64)> {
+    let chunk_size = chunk_size.max(1);
+    let mut ranges = vec![];
+    let mut start = from;
+    while start <= to {
+        let end = start.saturating_add(chunk_size - 1).min(to);
+        ranges.push((start, end));
+        if end == u64::MAX {
+            break
+        }
+        start = end + 1;
+    }
+    ranges
+}
+
+/// Parts of the errors RPCs return for log requests with a too large block range or too many
+/// results, e.g. `query returned more than 10000 results`
+const RANGE_ERRORS: &[&str] = &[
+    "block range",
+    "more than",
+    "too many",
+    "too large",
+    "response size",
+    "limited to",
+    "range is too",
+];
+
+/// Returns true if the RPC rejected a log request because of its block range or the number of
+/// results, which can be fixed by requesting a smaller range
+fn is_range_error(err: &impl std::fmt::Display) -> bool {
+    let err = err.to_string().to_lowercase();
+    RANGE_ERRORS.iter().any(|pattern| err.contains(pattern))
+}
+
+/// Fetches all logs matching the filter between `from` and `to` in chunks of at most
+/// `chunk_size` blocks
+///
+/// If the RPC rejects a chunk because of its range or number of results, it is split in halves
+/// until single blocks are requested. Other errors are returned.
+async fn get_logs_chunked(
+    provider: &RetryProvider,
+    filter: &Filter,
+    from: u64,
+    to: u64,
+    chunk_size: u64,
+) -> Result<Vec<Log>> {
+    let mut logs = vec![];
+    // the ranges to request, next one last
+    let mut ranges = chunk_ranges(from, to, chunk_size);
+    ranges.reverse();
+
+    while let Some((start, end)) = ranges.pop() {
+        let chunk_filter = filter.clone().from_block(start).to_block(end);
+        match provider.get_logs(&chunk_filter).await {
+            Ok(chunk) => logs.extend(chunk),
+            Err(err) if start < end && is_range_error(&err) => {
+                trace!(target: "cast::logs", ?err, start, end, "splitting rejected block range");
+                let mid = start + (end - start) / 2;
+                ranges.push((mid + 1, end));
+                ranges.push((start, mid));
+            }
+            Err(err) => {
+                return Err(err)
+                    .wrap_err_with(|| format!("Failed to get the logs of blocks {start}-{end}"))
+            }
+        }
+    }
+
+    Ok(logs)
+}
+
+/// Decodes logs with the events of their topic 0
+#[derive(Debug, Default)]
+struct LogDecoder {
+    events: BTreeMap<H256, Vec<Event>>,
+}
+
+impl LogDecoder {
+    fn add_event(&mut self, event: Event) {
+        self.events.entry(event.signature()).or_default().push(event);
+    }
+
+    fn add_abi(&mut self, abi: Abi) {
+        abi.events().cloned().for_each(|event| self.add_event(event));
+    }
+
+    /// Returns the name of the event and the decoded parameters, if any of the events matches
+    fn decode(&self, log: &Log) -> Option<(String, Vec<(String, Token)>)> {
+        let events = self.events.get(log.topics.first()?)?;
+        let raw = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+        events.iter().find_map(|event| {
+            // signatures without `indexed` markers are matched to the number of topics
+            let event = if event.inputs.iter().any(|param| param.indexed) {
+                event.clone()
+            } else {
+                get_indexed_event(event.clone(), &raw)
+            };
+            let decoded = event.parse_log(raw.clone()).ok()?;
+            let params = decoded.params.into_iter().map(|param| (param.name, param.value));
+            Some((event.name, params.collect()))
+        })
+    }
+}
+
+/// A log and its decoded event, if known
+#[derive(Debug, Clone)]
+struct LogRecord {
+    log: Log,
+    event: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl LogRecord {
+    fn new(decoder: &LogDecoder, log: Log) -> Self {
+        match decoder.decode(&log) {
+            Some((event, params)) => {
+                let params =
+                    params.iter().map(|(name, value)| (name.clone(), format_token(value))).collect();
+                Self { log, event: Some(event), params }
+            }
+            None => Self { log, event: None, params: vec![] },
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let log = &self.log;
+        let mut text = format!(
+            "- address: {:?}\n  blockNumber: {}\n  transactionHash: {}\n  logIndex: {}",
+            log.address,
+            display(log.block_number),
+            display(log.transaction_hash.map(|hash| format!("{hash:?}"))),
+            display(log.log_index),
+        );
+        match self.event {
+            Some(ref event) => {
+                text.push_str(&format!("\n  event: {event}"));
+                for (name, value) in &self.params {
+                    text.push_str(&format!("\n    {name}: {value}"));
+                }
+            }
+            None => {
+                text.push_str("\n  topics:");
+                for topic in &log.topics {
+                    text.push_str(&format!("\n    {topic:?}"));
+                }
+                text.push_str(&format!("\n  data: {}", log.data));
+            }
+        }
+        text
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let log = &self.log;
+        let params = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::Value::from(value.clone())))
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "address": log.address,
+            "blockNumber": log.block_number,
+            "transactionHash": log.transaction_hash,
+            "logIndex": log.log_index,
+            "event": self.event,
+            "args": params,
+            "topics": log.topics,
+            "data": log.data,
+        })
+    }
+}
+
+/// Returns the value or an empty string
+fn display<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Returns the records as CSV with a column for every decoded parameter name
+fn to_csv(records: &[LogRecord]) -> String {
+    let mut params: Vec<&str> = vec![];
+    for (name, _) in records.iter().flat_map(|record| &record.params) {
+        if !params.contains(&name.as_str()) {
+            params.push(name);
+        }
+    }
+
+    let mut header = ["address", "blockNumber", "transactionHash", "logIndex", "event"]
+        .iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<_>>();
+    header.extend(params.iter().map(|name| csv_field(name)));
+    header.extend(["topics".to_string(), "data".to_string()]);
+
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for record in records {
+        let log = &record.log;
+        let mut row = vec![
+            format!("{:?}", log.address),
+            display(log.block_number),
+            display(log.transaction_hash.map(|hash| format!("{hash:?}"))),
+            display(log.log_index),
+            csv_field(record.event.as_deref().unwrap_or_default()),
+        ];
+        row.extend(params.iter().map(|name| {
+            record
+                .params
+                .iter()
+                .find(|(param, _)| param == name)
+                .map(|(_, value)| csv_field(value))
+                .unwrap_or_default()
+        }));
+        let topics = log.topics.iter().map(|topic| format!("{topic:?}")).collect::<Vec<_>>();
+        row.push(csv_field(&topics.join(" ")));
+        row.push(log.data.to_string());
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes the value if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_chunk_block_ranges() {
+        assert_eq!(chunk_ranges(0, 0, 10), vec![(0, 0)]);
+        assert_eq!(chunk_ranges(5, 24, 10), vec![(5, 14), (15, 24)]);
+        assert_eq!(chunk_ranges(5, 25, 10), vec![(5, 14), (15, 24), (25, 25)]);
+        assert_eq!(chunk_ranges(1, 3, 0), vec![(1, 1), (2, 2), (3, 3)]);
+        assert!(chunk_ranges(2, 1, 10).is_empty());
+    }
+
+    #[test]
+    fn can_detect_range_errors() {
+        assert!(is_range_error(&"query returned more than 10000 results"));
+        assert!(is_range_error(
+            &"Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        ));
+        assert!(is_range_error(&"exceed maximum block range: 5000"));
+        assert!(!is_range_error(&"execution aborted (timeout = 5s)"));
+        assert!(!is_range_error(&"invalid params"));
+    }
+
+    #[test]
+    fn can_decode_logs() {
+        let mut decoder = LogDecoder::default();
+        decoder.add_event(get_event("Transfer(address,address,uint256)").unwrap());
+
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let log = Log {
+            topics: vec![
+                H256::from(ethers::utils::keccak256("Transfer(address,address,uint256)")),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: ethers::abi::encode(&[Token::Uint(1000.into())]).into(),
+            ..Default::default()
+        };
+
+        let record = LogRecord::new(&decoder, log);
+        assert_eq!(record.event.as_deref(), Some("Transfer"));
+        assert_eq!(
+            record.params,
+            vec![
+                ("param0".to_string(), ethers::utils::to_checksum(&from, None)),
+                ("param1".to_string(), ethers::utils::to_checksum(&to, None)),
+                ("param2".to_string(), "1000".to_string()),
+            ]
+        );
+
+        let csv = to_csv(&[record]);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "address,blockNumber,transactionHash,logIndex,event,param0,param1,param2,topics,data"
+        );
+        assert!(lines.next().unwrap().contains(",Transfer,"));
+    }
+
+    #[test]
+    fn can_escape_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("[1, 2]"), "\"[1, 2]\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -5,14 +5,22 @@
 //! implement `figment::Provider` which allows the subcommand to override the config's defaults, see
 //! [`foundry_config::Config`].
 
+pub mod abi_diff;
 pub mod bind;
+pub mod blobs;
 pub mod call;
+pub mod calldata_cost;
 pub mod create2;
+pub mod create2_deployer;
 pub mod estimate;
 pub mod find_block;
+pub mod gas_history;
 pub mod interface;
+pub mod logs;
 pub mod rpc;
 pub mod run;
 pub mod send;
 pub mod storage;
+pub mod user_op;
 pub mod wallet;
+pub mod watch_mempool;
@@ -1,15 +1,22 @@
 use crate::{opts::RpcOpts, utils};
 use cast::Cast;
-use clap::Parser;
-use eyre::Result;
+use clap::{Parser, ValueHint};
+use eyre::{Result, WrapErr};
 use foundry_config::Config;
+use futures::{stream, StreamExt};
 use itertools::Itertools;
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead},
+    path::PathBuf,
+};
 
 /// CLI arguments for `cast rpc`.
 #[derive(Debug, Clone, Parser)]
 pub struct RpcArgs {
     /// RPC method name
-    method: String,
+    #[clap(required_unless_present = "batch")]
+    method: Option<String>,
 
     /// RPC parameters
     ///
@@ -29,13 +36,59 @@ pub struct RpcArgs {
     #[clap(short = 'w', long)]
     raw: bool,
 
+    /// Send the requests of a file, or of stdin if `-`, as JSON-RPC batches
+    ///
+    /// Every line is a request, either a JSON object with a `method` and `params` or a method
+    /// followed by its params like the arguments of this command:
+    ///
+    /// eth_getBlockByNumber 0x123 false
+    ///
+    /// The result of every request is printed on its own line, in the order of the requests.
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_hint = ValueHint::FilePath,
+        conflicts_with_all = &["method", "raw"]
+    )]
+    batch: Option<PathBuf>,
+
+    /// A JSON request that every line of the batch is filled into
+    ///
+    /// `{}` is replaced with the whole line and `{0}`, `{1}`, ... with its whitespace separated
+    /// fields. For example:
+    ///
+    /// printf '0x%x\n' $(seq 100 110) | cast rpc --batch - --template '{"method":
+    /// "eth_getBlockByNumber", "params": ["{0}", false]}'
+    #[clap(long, value_name = "JSON", requires = "batch")]
+    template: Option<String>,
+
+    /// The number of requests in every JSON-RPC batch
+    #[clap(long, default_value = "100", value_name = "SIZE")]
+    batch_size: usize,
+
+    /// The number of batches that are sent at the same time
+    #[clap(long, default_value = "4", value_name = "NUM")]
+    concurrency: usize,
+
+    /// Only print the parts of the result that the filter selects
+    ///
+    /// Supports object fields `.hash`, array elements `[0]` and all elements of an array `[]`,
+    /// e.g. `.transactions[].hash`. Strings are printed without quotes.
+    #[clap(long, value_name = "FILTER")]
+    jq: Option<String>,
+
     #[clap(flatten)]
     rpc: RpcOpts,
 }
 
 impl RpcArgs {
     pub async fn run(self) -> Result<()> {
-        let RpcArgs { raw, method, params, rpc } = self;
+        let filter = self.jq.as_deref().map(parse_filter).transpose()?;
+        if self.batch.is_some() {
+            return self.run_batch(filter).await
+        }
+        let RpcArgs { raw, method, params, rpc, .. } = self;
+        let method = method.expect("required without --batch");
 
         let config = Config::from(&rpc);
         let provider = utils::get_provider(&config)?;
@@ -53,11 +106,253 @@ impl RpcArgs {
         } else {
             serde_json::Value::Array(params.into_iter().map(value_or_string).collect())
         };
-        println!("{}", Cast::new(provider).rpc(&method, params).await?);
+        let result = Cast::new(provider).rpc(&method, params).await?;
+        match filter {
+            Some(filter) => print_filtered(&serde_json::from_str(&result)?, &filter),
+            None => println!("{result}"),
+        }
         Ok(())
     }
+
+    /// Sends the requests of `--batch` in JSON-RPC batches and prints their results in order
+    async fn run_batch(self, filter: Option<Vec<Selector>>) -> Result<()> {
+        let batch = self.batch.expect("is batch");
+        let lines: Vec<String> = if batch == PathBuf::from("-") {
+            io::stdin().lock().lines().collect::<Result<_, _>>()?
+        } else {
+            let file = std::fs::File::open(&batch)
+                .wrap_err_with(|| format!("failed to read {}", batch.display()))?;
+            io::BufReader::new(file).lines().collect::<Result<_, _>>()?
+        };
+        let requests = lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .enumerate()
+            .map(|(id, line)| parse_request(id, line, self.template.as_deref()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let config = Config::from(&self.rpc);
+        let url = config.get_rpc_url_or_localhost_http()?.into_owned();
+        if !url.starts_with("http") {
+            eyre::bail!("Batch requests are only supported for HTTP endpoints, got {url}")
+        }
+        let client = reqwest::Client::new();
+
+        let mut batches = stream::iter(requests.chunks(self.batch_size.max(1)))
+            .map(|batch| send_batch(&client, &url, batch))
+            .buffered(self.concurrency.max(1));
+
+        let mut failed = 0;
+        while let Some(responses) = batches.next().await {
+            for response in responses? {
+                match response.get("result") {
+                    Some(result) => match filter {
+                        Some(ref filter) => print_filtered(result, filter),
+                        None => println!("{result}"),
+                    },
+                    None => {
+                        failed += 1;
+                        println!("{}", json!({ "error": response.get("error") }));
+                    }
+                }
+            }
+        }
+        if failed > 0 {
+            eyre::bail!("{failed} of {} requests failed", requests.len())
+        }
+        Ok(())
+    }
+}
+
+/// Sends the requests as one JSON-RPC batch and returns the responses in the order of the
+/// requests
+async fn send_batch(
+    client: &reqwest::Client,
+    url: &str,
+    requests: &[Value],
+) -> Result<Vec<Value>> {
+    let response: Value = client
+        .post(url)
+        .json(requests)
+        .send()
+        .await?
+        .error_for_status()
+        .wrap_err("the batch request failed")?
+        .json()
+        .await?;
+
+    let mut responses = match response {
+        Value::Array(responses) => responses,
+        // some nodes reject the whole batch with a single error
+        error => eyre::bail!("the batch request failed: {error}"),
+    };
+
+    // responses of a batch may be returned in any order
+    let ids = requests.iter().map(|request| request["id"].as_u64());
+    Ok(ids
+        .map(|id| {
+            responses
+                .iter()
+                .position(|response| response.get("id").and_then(Value::as_u64) == id)
+                .map(|idx| responses.swap_remove(idx))
+                .unwrap_or_else(|| json!({ "id": id, "error": "missing response" }))
+        })
+        .collect())
+}
+
+/// Returns the JSON-RPC request of a line of the batch file
+fn parse_request(id: usize, line: &str, template: Option<&str>) -> Result<Value> {
+    let request = match template {
+        Some(template) => {
+            let mut request = template.to_string();
+            for (i, field) in line.split_whitespace().enumerate() {
+                request = request.replace(&format!("{{{i}}}"), field);
+            }
+            serde_json::from_str(&request.replace("{}", line))
+                .wrap_err_with(|| format!("the template is not valid JSON for line `{line}`"))?
+        }
+        None if line.starts_with('{') => serde_json::from_str(line)
+            .wrap_err_with(|| format!("the request `{line}` is not valid JSON"))?,
+        None => {
+            let mut fields = line.split_whitespace();
+            let method = fields.next().unwrap_or_default();
+            let params = fields.map(|field| value_or_string(field.to_string())).collect();
+            json!({ "method": method, "params": Value::Array(params) })
+        }
+    };
+
+    let method = match request.get("method") {
+        Some(Value::String(method)) => method.clone(),
+        _ => eyre::bail!("the request `{line}` has no method"),
+    };
+    let params = request.get("params").cloned().unwrap_or_else(|| json!([]));
+    Ok(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
 }
 
 fn value_or_string(value: String) -> serde_json::Value {
     serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value))
 }
+
+/// A step of a `--jq` filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    /// The field of an object
+    Field(String),
+    /// The element of an array
+    Index(usize),
+    /// All elements of an array
+    Iter,
+}
+
+/// Parses a filter like `.transactions[0].hash`
+fn parse_filter(filter: &str) -> Result<Vec<Selector>> {
+    let mut selectors = vec![];
+    let mut chars = filter.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut field = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    field.push(*c);
+                    chars.next();
+                }
+                if !field.is_empty() {
+                    selectors.push(Selector::Field(field));
+                }
+            }
+            '[' => {
+                let index = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+                if index.trim().is_empty() {
+                    selectors.push(Selector::Iter);
+                } else {
+                    let index = index
+                        .trim()
+                        .parse()
+                        .wrap_err_with(|| format!("invalid array index `{index}` in `{filter}`"))?;
+                    selectors.push(Selector::Index(index));
+                }
+            }
+            c => eyre::bail!("unexpected `{c}` in filter `{filter}`"),
+        }
+    }
+    Ok(selectors)
+}
+
+/// Returns all values the selectors select, missing fields and elements are `null`
+fn apply_filter(value: &Value, selectors: &[Selector]) -> Vec<Value> {
+    let (selector, rest) = match selectors.split_first() {
+        Some(split) => split,
+        None => return vec![value.clone()],
+    };
+    match selector {
+        Selector::Field(field) => apply_filter(value.get(field).unwrap_or(&Value::Null), rest),
+        Selector::Index(index) => apply_filter(value.get(index).unwrap_or(&Value::Null), rest),
+        Selector::Iter => match value {
+            Value::Array(values) => {
+                values.iter().flat_map(|value| apply_filter(value, rest)).collect()
+            }
+            Value::Object(values) => {
+                values.values().flat_map(|value| apply_filter(value, rest)).collect()
+            }
+            _ => vec![],
+        },
+    }
+}
+
+fn print_filtered(value: &Value, filter: &[Selector]) {
+    for value in apply_filter(value, filter) {
+        match value {
+            Value::String(s) => println!("{s}"),
+            value => println!("{value}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_batch_requests() {
+        assert_eq!(
+            parse_request(0, "eth_getBlockByNumber 0x1 false", None).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "eth_getBlockByNumber",
+                "params": ["0x1", false]
+            })
+        );
+        assert_eq!(
+            parse_request(1, r#"{"method": "eth_chainId"}"#, None).unwrap(),
+            json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": []})
+        );
+        let template = r#"{"method": "eth_getBalance", "params": ["{0}", "{1}"]}"#;
+        assert_eq!(
+            parse_request(2, "0xdead 0x10", Some(template)).unwrap(),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "eth_getBalance",
+                "params": ["0xdead", "0x10"]
+            })
+        );
+        assert!(parse_request(3, r#"{"params": []}"#, None).is_err());
+    }
+
+    #[test]
+    fn can_apply_filter() {
+        let block = json!({
+            "number": "0x1",
+            "transactions": [{"hash": "0xaa"}, {"hash": "0xbb"}],
+        });
+        let filter = parse_filter(".transactions[].hash").unwrap();
+        assert_eq!(apply_filter(&block, &filter), vec![json!("0xaa"), json!("0xbb")]);
+        let filter = parse_filter(".transactions[1]").unwrap();
+        assert_eq!(apply_filter(&block, &filter), vec![json!({"hash": "0xbb"})]);
+        assert_eq!(apply_filter(&block, &parse_filter(".").unwrap()), vec![block.clone()]);
+        assert_eq!(apply_filter(&block, &parse_filter(".missing").unwrap()), vec![Value::Null]);
+        assert!(parse_filter(".transactions[x]").is_err());
+    }
+}
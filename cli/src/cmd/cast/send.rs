@@ -1,15 +1,25 @@
 // cast send subcommands
 use crate::{
-    opts::{EthereumOpts, TransactionOpts},
-    utils,
+    opts::{EthereumOpts, TransactionOpts, WalletSigner},
+    utils::{self, parse_ether_value},
 };
-use cast::{Cast, TxBuilder};
-use clap::Parser;
+use cast::{
+    blob::{self, BlobSidecar, BlobTransaction},
+    eip7702::{self, SetCodeTransaction},
+    Cast, TxBuilder,
+};
+use clap::{Parser, ValueHint};
 use ethers::{
-    prelude::MiddlewareBuilder, providers::Middleware, signers::Signer, types::NameOrAddress,
+    prelude::MiddlewareBuilder,
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, U256},
 };
+use eyre::WrapErr;
+use forge::executor::delegation::PER_AUTHORIZATION_GAS;
+use foundry_common::fs;
 use foundry_config::{Chain, Config};
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 
 /// CLI arguments for `cast send`.
 #[derive(Debug, Parser)]
@@ -51,11 +61,67 @@ pub struct SendTxArgs {
         conflicts_with = "nonce"
     )]
     resend: bool,
+    #[clap(flatten)]
+    blob: BlobArgs,
+    #[clap(
+        long,
+        help = "Send an EIP-7702 transaction with the given authorization, can be repeated.",
+        long_help = r#"Send an EIP-7702 transaction with the given authorization, can be repeated.
+
+Either the address of the delegate, the authorization is then signed by the sender, or the hex
+encoded rlp of an authorization that was signed by another account."#,
+        conflicts_with = "blob",
+        value_name = "AUTH"
+    )]
+    auth: Vec<String>,
 
     #[clap(subcommand)]
     command: Option<SendTxSubcommands>,
 }
 
+/// Options for sending EIP-4844 blob transactions.
+#[derive(Debug, Clone, Parser)]
+#[clap(next_help_heading = "Blob options")]
+pub struct BlobArgs {
+    #[clap(
+        long,
+        help = "Send an EIP-4844 transaction that carries the content of --blob-file as blobs.",
+        requires = "blob_file"
+    )]
+    pub blob: bool,
+
+    #[clap(
+        long,
+        help = "The file with the data to pack into blobs.",
+        requires = "blob",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    pub blob_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Max fee per blob gas. Defaults to twice the current blob base fee.",
+        requires = "blob",
+        value_parser = parse_ether_value,
+        value_name = "PRICE"
+    )]
+    pub blob_gas_price: Option<U256>,
+
+    #[clap(
+        long,
+        help = "The KZG trusted setup file used to compute the blob commitments and proofs.",
+        long_help = r#"The KZG trusted setup file used to compute the blob commitments and proofs.
+
+Uses the file set in CAST_KZG_TRUSTED_SETUP if not provided, or the bundled mainnet trusted
+setup if neither is set."#,
+        requires = "blob",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    pub trusted_setup: Option<PathBuf>,
+}
+
 #[derive(Debug, Parser)]
 pub enum SendTxSubcommands {
     #[clap(name = "--create", about = "Use to deploy raw contract bytecode")]
@@ -81,6 +147,8 @@ impl SendTxArgs {
             confirmations,
             to_json,
             resend,
+            blob,
+            auth,
             command,
         } = self;
         let config = Config::from(&eth);
@@ -110,6 +178,57 @@ impl SendTxArgs {
                 tx.nonce = Some(provider.get_transaction_count(from, None).await?);
             }
 
+            if blob.blob {
+                let wallet = match signer {
+                    WalletSigner::Local(wallet) => wallet,
+                    _ => eyre::bail!("Blob transactions can only be signed by local wallets"),
+                };
+                if command.is_some() {
+                    eyre::bail!("Blob transactions can't create contracts")
+                }
+                let to = to.ok_or_else(|| eyre::eyre!("Blob transactions require a recipient"))?;
+                return cast_send_blob(
+                    provider,
+                    wallet,
+                    to,
+                    (sig, args),
+                    tx,
+                    chain,
+                    api_key,
+                    blob,
+                    cast_async,
+                    confirmations,
+                    to_json,
+                )
+                .await;
+            }
+
+            if !auth.is_empty() {
+                let wallet = match signer {
+                    WalletSigner::Local(wallet) => wallet,
+                    _ => eyre::bail!("Set code transactions can only be signed by local wallets"),
+                };
+                if command.is_some() {
+                    eyre::bail!("Set code transactions can't create contracts")
+                }
+                let to =
+                    to.ok_or_else(|| eyre::eyre!("Set code transactions require a recipient"))?;
+                return cast_send_auth(
+                    provider,
+                    wallet,
+                    to,
+                    (sig, args),
+                    tx,
+                    chain,
+                    api_key,
+                    auth,
+                    cast_async,
+                    confirmations,
+                    to_json,
+                )
+                .await
+            }
+
             let code = if let Some(SendTxSubcommands::Create {
                 code,
                 sig: constructor_sig,
@@ -139,6 +258,10 @@ impl SendTxArgs {
                 to_json,
             )
             .await
+        } else if blob.blob {
+            eyre::bail!("Blob transactions require a local wallet to sign them")
+        } else if !auth.is_empty() {
+            eyre::bail!("Set code transactions require a local wallet to sign them")
         } else if config.sender != Config::DEFAULT_SENDER {
             // Checking if signer isn't the default value
             // 00a329c0648769A73afAc7F9381E08FB43dBEA72.
@@ -240,3 +363,202 @@ where
 
     Ok(())
 }
+
+/// Builds, signs and publishes an EIP-4844 transaction with the blobs of [BlobArgs::blob_file].
+///
+/// The transaction is signed locally because the provider and signer middlewares are not aware of
+/// blob transactions.
+#[allow(clippy::too_many_arguments)]
+async fn cast_send_blob<M: Middleware>(
+    provider: M,
+    wallet: LocalWallet,
+    to: NameOrAddress,
+    args: (String, Vec<String>),
+    tx: TransactionOpts,
+    chain: Chain,
+    etherscan_api_key: Option<String>,
+    blob: BlobArgs,
+    cast_async: bool,
+    confs: usize,
+    to_json: bool,
+) -> eyre::Result<()>
+where
+    M::Error: 'static,
+{
+    let blob_file = blob.blob_file.expect("required by --blob");
+    let data = fs::read(&blob_file)?;
+    let settings = blob::load_trusted_setup(blob.trusted_setup.as_deref())?;
+    let sidecar = BlobSidecar::new(blob::encode_blobs(&data)?, &settings)?;
+
+    let (sig, params) = args;
+    let params = if !sig.is_empty() { Some((&sig[..], params)) } else { None };
+    let mut builder = TxBuilder::new(&provider, wallet.address(), Some(to), chain, false).await?;
+    builder
+        .etherscan_api_key(etherscan_api_key)
+        .gas(tx.gas_limit)
+        .gas_price(tx.gas_price)
+        .priority_gas_price(tx.priority_gas_price)
+        .value(tx.value)
+        .nonce(tx.nonce);
+    builder.args(params).await?;
+    let (mut typed_tx, _) = builder.build();
+    provider
+        .fill_transaction(&mut typed_tx, None)
+        .await
+        .wrap_err("Failed to fill the blob transaction")?;
+
+    let tx = match typed_tx {
+        TypedTransaction::Eip1559(tx) => tx,
+        _ => eyre::bail!("Blob transactions can't be sent on chains without EIP1559"),
+    };
+    let max_fee_per_blob_gas = match blob.blob_gas_price {
+        Some(price) => price,
+        None => {
+            let base_fee: U256 =
+                provider.provider().request("eth_blobBaseFee", ()).await.wrap_err(
+                    "Failed to fetch the blob base fee, consider setting --blob-gas-price",
+                )?;
+            base_fee * 2
+        }
+    };
+
+    let blob_tx = BlobTransaction {
+        chain_id: chain.id().into(),
+        nonce: tx.nonce.unwrap_or_default(),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.unwrap_or_default(),
+        max_fee_per_gas: tx.max_fee_per_gas.unwrap_or_default(),
+        gas: tx.gas.unwrap_or_default(),
+        to: tx
+            .to
+            .as_ref()
+            .and_then(|to| to.as_address().copied())
+            .ok_or_else(|| eyre::eyre!("Blob transactions require a recipient"))?,
+        value: tx.value.unwrap_or_default(),
+        data: tx.data.unwrap_or_default(),
+        access_list: tx.access_list,
+        max_fee_per_blob_gas,
+        blob_versioned_hashes: sidecar.versioned_hashes(),
+    };
+    let signature = wallet.sign_hash(blob_tx.sighash())?;
+    let raw_tx = blob_tx.rlp_network(&signature, &sidecar);
+
+    let cast = Cast::new(provider);
+    let pending_tx = cast.publish(format!("{raw_tx}")).await?;
+    let tx_hash = *pending_tx;
+
+    if cast_async {
+        println!("{tx_hash:#x}");
+    } else {
+        let receipt = cast.receipt(format!("{tx_hash:#x}"), None, confs, false, to_json).await?;
+        println!("{receipt}");
+    }
+
+    Ok(())
+}
+
+/// Builds, signs and publishes an EIP-7702 transaction with the authorizations of `--auth`.
+///
+/// Authorizations given as a delegate address are signed by the sender. Since the nonce of the
+/// sender is incremented before the authorizations are applied, they are signed for the nonces
+/// following the nonce of the transaction.
+#[allow(clippy::too_many_arguments)]
+async fn cast_send_auth<M: Middleware>(
+    provider: M,
+    wallet: LocalWallet,
+    to: NameOrAddress,
+    args: (String, Vec<String>),
+    tx: TransactionOpts,
+    chain: Chain,
+    etherscan_api_key: Option<String>,
+    auth: Vec<String>,
+    cast_async: bool,
+    confs: usize,
+    to_json: bool,
+) -> eyre::Result<()>
+where
+    M::Error: 'static,
+{
+    let (sig, params) = args;
+    let params = if !sig.is_empty() { Some((&sig[..], params)) } else { None };
+    let mut builder = TxBuilder::new(&provider, wallet.address(), Some(to), chain, false).await?;
+    builder
+        .etherscan_api_key(etherscan_api_key)
+        .gas(tx.gas_limit)
+        .gas_price(tx.gas_price)
+        .priority_gas_price(tx.priority_gas_price)
+        .value(tx.value)
+        .nonce(tx.nonce);
+    builder.args(params).await?;
+    let (mut typed_tx, _) = builder.build();
+
+    if !matches!(typed_tx, TypedTransaction::Eip1559(_)) {
+        eyre::bail!("Set code transactions can't be sent on chains without EIP1559")
+    }
+    let nonce = match typed_tx.nonce() {
+        Some(nonce) => *nonce,
+        None => provider.get_transaction_count(wallet.address(), None).await?,
+    };
+    typed_tx.set_nonce(nonce);
+    let nonce = nonce.as_u64();
+
+    let mut authorization_list = Vec::with_capacity(auth.len());
+    let mut next_nonce = nonce + 1;
+    for auth in auth {
+        let authorization = match auth.parse::<Address>() {
+            Ok(delegate) => {
+                let authorization =
+                    eip7702::sign_authorization(&wallet, chain.id(), delegate, next_nonce)?;
+                next_nonce += 1;
+                authorization
+            }
+            Err(_) => eip7702::decode_authorization(&auth)?,
+        };
+        authorization_list.push(authorization);
+    }
+
+    // the gas estimate of the node doesn't include the authorizations
+    if typed_tx.gas().is_none() {
+        let gas = provider.estimate_gas(&typed_tx, None).await?;
+        typed_tx.set_gas(gas + PER_AUTHORIZATION_GAS * authorization_list.len() as u64);
+    }
+    provider
+        .fill_transaction(&mut typed_tx, None)
+        .await
+        .wrap_err("Failed to fill the set code transaction")?;
+    let tx = match typed_tx {
+        TypedTransaction::Eip1559(tx) => tx,
+        _ => unreachable!("checked above"),
+    };
+
+    let set_code_tx = SetCodeTransaction {
+        chain_id: chain.id().into(),
+        nonce: tx.nonce.unwrap_or_default(),
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.unwrap_or_default(),
+        max_fee_per_gas: tx.max_fee_per_gas.unwrap_or_default(),
+        gas: tx.gas.unwrap_or_default(),
+        to: tx
+            .to
+            .as_ref()
+            .and_then(|to| to.as_address().copied())
+            .ok_or_else(|| eyre::eyre!("Set code transactions require a recipient"))?,
+        value: tx.value.unwrap_or_default(),
+        data: tx.data.unwrap_or_default(),
+        access_list: tx.access_list,
+        authorization_list,
+    };
+    let signature = wallet.sign_hash(set_code_tx.sighash())?;
+    let raw_tx = set_code_tx.rlp_signed(&signature);
+
+    let cast = Cast::new(provider);
+    let pending_tx = cast.publish(format!("{raw_tx}")).await?;
+    let tx_hash = *pending_tx;
+
+    if cast_async {
+        println!("{tx_hash:#x}");
+    } else {
+        let receipt = cast.receipt(format!("{tx_hash:#x}"), None, confs, false, to_json).await?;
+        println!("{receipt}");
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::{
-    cmd::forge::build,
+    cmd::{forge::build, LoadConfig},
     opts::{cast::parse_slot, EtherscanOpts, RpcOpts},
     utils,
 };
@@ -7,7 +7,7 @@ use cast::Cast;
 use clap::Parser;
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use ethers::{
-    abi::ethabi::ethereum_types::BigEndianHash, etherscan::Client, prelude::*,
+    abi::ethabi::ethereum_types::BigEndianHash, prelude::*,
     solc::artifacts::StorageLayout,
 };
 use eyre::Result;
@@ -99,11 +99,12 @@ impl StorageArgs {
         }
 
         // Check if we're in a forge project and if we can find the address' code
-        let mut project = build.project()?;
+        let build_config = build.try_load_config_emit_warnings()?;
+        let mut project = build_config.project()?;
         if project.paths.has_input_files() {
             // Find in artifacts and pretty print
             add_storage_layout_output(&mut project);
-            let out = compile(&project, false, false)?;
+            let out = compile(&project, &build_config, false, false)?;
             let match_code = |artifact: &ConfigurableContractArtifact| -> Option<bool> {
                 let bytes =
                     artifact.deployed_bytecode.as_ref()?.bytecode.as_ref()?.object.as_bytes()?;
@@ -121,9 +122,8 @@ impl StorageArgs {
         eprintln!("No matching artifacts found, fetching source code from Etherscan...");
 
         let chain = utils::get_chain(config.chain_id, &provider).await?;
-        let api_key = config.get_etherscan_api_key(Some(chain)).unwrap_or_default();
-        let client = Client::new(chain.named()?, api_key)?;
-        let source = find_source(client, address).await?;
+        let clients = config.get_etherscan_clients(chain)?;
+        let source = find_source(clients, address).await?;
         let metadata = source.items.first().unwrap();
         if metadata.is_vyper() {
             eyre::bail!("Contract at provided address is not a valid Solidity contract")
@@ -141,7 +141,7 @@ impl StorageArgs {
         project.auto_detect = auto_detect;
 
         // Compile
-        let mut out = suppress_compile(&project)?;
+        let mut out = suppress_compile(&project, &Config::default())?;
         let artifact = {
             let (_, mut artifact) = out
                 .artifacts()
@@ -154,7 +154,7 @@ impl StorageArgs {
                 let solc = Solc::find_or_install_svm_version(MIN_SOLC.to_string())?;
                 project.solc = solc;
                 project.auto_detect = false;
-                if let Ok(output) = suppress_compile(&project) {
+                if let Ok(output) = suppress_compile(&project, &Config::default()) {
                     out = output;
                     let (_, new_artifact) = out
                         .artifacts()
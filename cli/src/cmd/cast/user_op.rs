@@ -0,0 +1,293 @@
+//! cast 4337 subcommands
+
+use crate::{
+    opts::{RpcOpts, Wallet},
+    stdin,
+    utils::{self, parse_u256},
+};
+use cast::user_op::{decode_handle_ops, EntryPointVersion, UserOperation};
+use clap::Parser;
+use ethers::{
+    abi::{self, Token},
+    prelude::*,
+    types::transaction::eip2718::TypedTransaction,
+    utils::id,
+};
+use eyre::WrapErr;
+use foundry_common::{fs, get_http_provider};
+use foundry_config::Config;
+use std::{path::Path, str::FromStr};
+
+/// CLI arguments for `cast 4337`.
+#[derive(Debug, Parser)]
+pub enum UserOpSubcommands {
+    #[clap(name = "build", visible_alias = "b", about = "Build a user operation.")]
+    Build(Box<BuildArgs>),
+    #[clap(
+        name = "hash",
+        visible_alias = "h",
+        about = "Get the hash of a user operation that is signed by the account."
+    )]
+    Hash {
+        #[clap(
+            help = "The user operation as JSON, or the path to a JSON file. Read from stdin if omitted.",
+            value_name = "USEROP"
+        )]
+        user_op: Option<String>,
+        #[clap(
+            long,
+            help = "The chain ID, fetched from the RPC if omitted.",
+            value_name = "CHAIN_ID"
+        )]
+        chain_id: Option<u64>,
+        #[clap(flatten)]
+        entry_point: EntryPointOpts,
+        #[clap(flatten)]
+        rpc: RpcOpts,
+    },
+    #[clap(
+        name = "sign",
+        visible_alias = "s",
+        about = "Sign the hash of a user operation and print the signed user operation."
+    )]
+    Sign {
+        #[clap(
+            help = "The user operation as JSON, or the path to a JSON file. Read from stdin if omitted.",
+            value_name = "USEROP"
+        )]
+        user_op: Option<String>,
+        #[clap(
+            long,
+            help = "The chain ID, fetched from the RPC if omitted.",
+            value_name = "CHAIN_ID"
+        )]
+        chain_id: Option<u64>,
+        #[clap(flatten)]
+        entry_point: EntryPointOpts,
+        #[clap(flatten)]
+        rpc: RpcOpts,
+        #[clap(flatten)]
+        wallet: Wallet,
+    },
+    #[clap(name = "send", about = "Send a signed user operation to a bundler and print its hash.")]
+    Send {
+        #[clap(
+            help = "The user operation as JSON, or the path to a JSON file. Read from stdin if omitted.",
+            value_name = "USEROP"
+        )]
+        user_op: Option<String>,
+        #[clap(
+            long,
+            help = "The RPC URL of the bundler.",
+            env = "BUNDLER_RPC_URL",
+            value_name = "URL"
+        )]
+        bundler_url: String,
+        #[clap(flatten)]
+        entry_point: EntryPointOpts,
+    },
+    #[clap(
+        name = "decode",
+        visible_alias = "d",
+        about = "Decode the user operations of `handleOps` calldata.",
+        long_about = "Decode the user operations of `handleOps` calldata. The EntryPoint version is detected from the selector."
+    )]
+    Decode {
+        #[clap(help = "The calldata. Read from stdin if omitted.", value_name = "CALLDATA")]
+        calldata: Option<String>,
+    },
+}
+
+/// The EntryPoint a user operation is meant for
+#[derive(Debug, Clone, Parser)]
+pub struct EntryPointOpts {
+    #[clap(
+        long = "entry-point-version",
+        help = "The EntryPoint version: 0.6 or 0.7.",
+        default_value = "0.7",
+        value_parser = EntryPointVersion::from_str,
+        value_name = "VERSION"
+    )]
+    version: EntryPointVersion,
+
+    #[clap(
+        long,
+        help = "The EntryPoint address, defaults to the canonical address of the version.",
+        value_name = "ADDRESS"
+    )]
+    entry_point: Option<Address>,
+}
+
+impl EntryPointOpts {
+    fn address(&self) -> Address {
+        self.entry_point.unwrap_or_else(|| self.version.address())
+    }
+}
+
+/// CLI arguments for `cast 4337 build`.
+#[derive(Debug, Parser)]
+pub struct BuildArgs {
+    #[clap(long, help = "The account that executes the user operation.", value_name = "ADDRESS")]
+    sender: Address,
+    #[clap(
+        long,
+        help = "The nonce of the account, fetched from the EntryPoint if omitted.",
+        value_parser = parse_u256,
+        value_name = "NONCE"
+    )]
+    nonce: Option<U256>,
+    #[clap(long, help = "The factory that deploys the account.", value_name = "ADDRESS")]
+    factory: Option<Address>,
+    #[clap(long, help = "The calldata of the factory.", value_name = "DATA", requires = "factory")]
+    factory_data: Option<Bytes>,
+    #[clap(long, help = "The calldata of the account.", value_name = "DATA")]
+    call_data: Option<Bytes>,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "GAS")]
+    call_gas_limit: U256,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "GAS")]
+    verification_gas_limit: U256,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "GAS")]
+    pre_verification_gas: U256,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "PRICE")]
+    max_fee_per_gas: U256,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "PRICE")]
+    max_priority_fee_per_gas: U256,
+    #[clap(long, help = "The paymaster that pays for the user operation.", value_name = "ADDRESS")]
+    paymaster: Option<Address>,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "GAS")]
+    paymaster_verification_gas_limit: U256,
+    #[clap(long, default_value = "0", value_parser = parse_u256, value_name = "GAS")]
+    paymaster_post_op_gas_limit: U256,
+    #[clap(long, help = "The data of the paymaster.", value_name = "DATA", requires = "paymaster")]
+    paymaster_data: Option<Bytes>,
+    #[clap(flatten)]
+    entry_point: EntryPointOpts,
+    #[clap(flatten)]
+    rpc: RpcOpts,
+}
+
+impl BuildArgs {
+    async fn run(self) -> eyre::Result<()> {
+        let nonce = match self.nonce {
+            Some(nonce) => nonce,
+            None => {
+                let provider = utils::get_provider(&Config::from(&self.rpc))?;
+                get_nonce(&provider, self.entry_point.address(), self.sender).await?
+            }
+        };
+        let op = UserOperation {
+            sender: self.sender,
+            nonce,
+            factory: self.factory,
+            factory_data: self.factory_data.unwrap_or_default(),
+            call_data: self.call_data.unwrap_or_default(),
+            call_gas_limit: self.call_gas_limit,
+            verification_gas_limit: self.verification_gas_limit,
+            pre_verification_gas: self.pre_verification_gas,
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            paymaster: self.paymaster,
+            paymaster_verification_gas_limit: self.paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit: self.paymaster_post_op_gas_limit,
+            paymaster_data: self.paymaster_data.unwrap_or_default(),
+            signature: Bytes::default(),
+        };
+        println!("{}", serde_json::to_string_pretty(&op.to_rpc(self.entry_point.version))?);
+        Ok(())
+    }
+}
+
+impl UserOpSubcommands {
+    pub async fn run(self) -> eyre::Result<()> {
+        match self {
+            UserOpSubcommands::Build(cmd) => cmd.run().await?,
+            UserOpSubcommands::Hash { user_op, chain_id, entry_point, rpc } => {
+                let op = read_user_op(user_op)?;
+                let chain_id = get_chain_id(chain_id, &rpc).await?;
+                println!("{:?}", op.hash(entry_point.version, entry_point.address(), chain_id));
+            }
+            UserOpSubcommands::Sign { user_op, chain_id, entry_point, rpc, wallet } => {
+                let mut op = read_user_op(user_op)?;
+                let chain_id = get_chain_id(chain_id, &rpc).await?;
+                let hash = op.hash(entry_point.version, entry_point.address(), chain_id);
+
+                let signer = wallet.signer(chain_id).await?;
+                if signer.address() != op.sender {
+                    eprintln!(
+                        "Note: the signer {:?} is not the sender {:?} of the user operation",
+                        signer.address(),
+                        op.sender
+                    );
+                }
+                let signature = signer.sign_message(hash.as_bytes()).await?;
+                op.signature = signature.to_vec().into();
+                println!("{}", serde_json::to_string_pretty(&op.to_rpc(entry_point.version))?);
+            }
+            UserOpSubcommands::Send { user_op, bundler_url, entry_point } => {
+                let op = read_user_op(user_op)?;
+                let provider = get_http_provider(bundler_url);
+                let hash: H256 = provider
+                    .request(
+                        "eth_sendUserOperation",
+                        (op.to_rpc(entry_point.version), entry_point.address()),
+                    )
+                    .await
+                    .wrap_err("the bundler rejected the user operation")?;
+                println!("{hash:?}");
+            }
+            UserOpSubcommands::Decode { calldata } => {
+                let calldata: String = stdin::unwrap_line(calldata)?;
+                let calldata = hex::decode(calldata.trim().trim_start_matches("0x"))?;
+                let (version, ops, beneficiary) = decode_handle_ops(&calldata)?;
+                let ops = ops.iter().map(|op| op.to_rpc(version)).collect::<Vec<_>>();
+                let decoded = serde_json::json!({
+                    "entryPointVersion": version.to_string(),
+                    "beneficiary": beneficiary,
+                    "userOperations": ops,
+                });
+                println!("{}", serde_json::to_string_pretty(&decoded)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a user operation from its JSON, a JSON file or stdin
+fn read_user_op(user_op: Option<String>) -> eyre::Result<UserOperation> {
+    let user_op: String = stdin::unwrap(user_op, false)?;
+    let path = Path::new(user_op.trim());
+    if path.is_file() {
+        return fs::read_to_string(path)?.parse()
+    }
+    user_op.parse()
+}
+
+/// Returns the given chain ID or the chain ID of the RPC
+async fn get_chain_id(chain_id: Option<u64>, rpc: &RpcOpts) -> eyre::Result<u64> {
+    match chain_id {
+        Some(chain_id) => Ok(chain_id),
+        None => {
+            let provider = utils::get_provider(&Config::from(rpc))?;
+            Ok(provider.get_chainid().await?.as_u64())
+        }
+    }
+}
+
+/// Returns the nonce of the account with key 0 from `EntryPoint.getNonce`
+async fn get_nonce<M: Middleware>(
+    provider: &M,
+    entry_point: Address,
+    sender: Address,
+) -> eyre::Result<U256>
+where
+    M::Error: 'static,
+{
+    let mut data = id("getNonce(address,uint192)").to_vec();
+    data.extend(abi::encode(&[Token::Address(sender), Token::Uint(U256::zero())]));
+    let tx: TypedTransaction = TransactionRequest::new().to(entry_point).data(data).into();
+    let out = provider.call(&tx, None).await.wrap_err("failed to get the nonce of the account")?;
+    if out.len() != 32 {
+        eyre::bail!("the EntryPoint at {entry_point:?} returned an invalid nonce");
+    }
+    Ok(U256::from_big_endian(&out))
+}
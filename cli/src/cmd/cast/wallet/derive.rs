@@ -0,0 +1,134 @@
+//! Derivation of many keys of a mnemonic
+
+use crate::cmd::Cmd;
+use cast::SimpleCast;
+use clap::Parser;
+use ethers::{
+    signers::{coins_bip39::English, MnemonicBuilder, Signer},
+    types::Address,
+};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::path::Path;
+
+/// The derivation path prefix of the default ethereum keys
+pub const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
+
+/// CLI arguments for `cast wallet derive`.
+#[derive(Debug, Clone, Parser)]
+pub struct DeriveArgs {
+    #[clap(
+        help = "The mnemonic phrase, or the path to a file containing it.",
+        value_name = "MNEMONIC"
+    )]
+    pub mnemonic: String,
+    #[clap(
+        long,
+        short,
+        help = "Number of keys to derive.",
+        default_value = "1",
+        value_name = "NUM"
+    )]
+    pub count: u32,
+    #[clap(
+        long,
+        help = "Index of the first key to derive.",
+        default_value = "0",
+        value_name = "INDEX"
+    )]
+    pub index: u32,
+    #[clap(
+        long,
+        help = "Derivation path of the keys, the index of each key is appended to it.",
+        default_value = DEFAULT_DERIVATION_PATH_PREFIX,
+        value_name = "PATH"
+    )]
+    pub path: String,
+    #[clap(long, help = "BIP39 passphrase of the mnemonic.", value_name = "PASSPHRASE")]
+    pub passphrase: Option<String>,
+    #[clap(long = "json", short = 'j', help_heading = "Display options")]
+    pub to_json: bool,
+}
+
+/// A key derived from a mnemonic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedKey {
+    pub path: String,
+    pub address: Address,
+    pub private_key: String,
+}
+
+impl Cmd for DeriveArgs {
+    type Output = Vec<DerivedKey>;
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let keys = self.derive()?;
+        if self.to_json {
+            println!("{}", serde_json::to_string_pretty(&keys)?);
+        } else {
+            for (idx, key) in keys.iter().enumerate() {
+                if idx > 0 {
+                    println!();
+                }
+                println!("Path:        {}", key.path);
+                println!("Address:     {}", SimpleCast::to_checksum_address(&key.address));
+                println!("Private key: {}", key.private_key);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+impl DeriveArgs {
+    /// Derives the `count` keys starting at `index`
+    pub fn derive(&self) -> eyre::Result<Vec<DerivedKey>> {
+        let mnemonic = if Path::new(&self.mnemonic).is_file() {
+            std::fs::read_to_string(&self.mnemonic)?.replace('\n', "")
+        } else {
+            self.mnemonic.clone()
+        };
+        let end = self
+            .index
+            .checked_add(self.count)
+            .ok_or_else(|| eyre::eyre!("Index {} + count {} overflows", self.index, self.count))?;
+
+        (self.index..end)
+            .into_par_iter()
+            .map(|index| {
+                let path = format!("{}{index}", self.path);
+                let mut builder = MnemonicBuilder::<English>::default().phrase(mnemonic.as_str());
+                if let Some(ref passphrase) = self.passphrase {
+                    builder = builder.password(passphrase.as_str());
+                }
+                let wallet = builder.derivation_path(&path)?.build()?;
+                let private_key = format!("0x{}", hex::encode(wallet.signer().to_bytes()));
+                Ok(DerivedKey { path, address: wallet.address(), private_key })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    #[test]
+    fn can_derive_many_keys() {
+        let args =
+            DeriveArgs::parse_from(["foundry-cli", MNEMONIC, "--count", "3", "--index", "1"]);
+        let keys = args.derive().unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].path, "m/44'/60'/0'/0/1");
+        assert_eq!(
+            keys[0].address,
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            keys[2].private_key,
+            "0x7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6"
+        );
+    }
+}
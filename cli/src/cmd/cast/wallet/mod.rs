@@ -1,9 +1,13 @@
 //! cast wallet subcommand
 
+pub mod derive;
 pub mod vanity;
 
 use crate::{
-    cmd::{cast::wallet::vanity::VanityArgs, Cmd},
+    cmd::{
+        cast::wallet::{derive::DeriveArgs, vanity::VanityArgs},
+        Cmd,
+    },
     opts::Wallet,
 };
 use cast::SimpleCast;
@@ -43,8 +47,18 @@ pub enum WalletSubcommands {
         )]
         unsafe_password: Option<String>,
     },
-    #[clap(name = "vanity", visible_alias = "va", about = "Generate a vanity address.")]
+    #[clap(
+        name = "vanity",
+        visible_alias = "va",
+        about = "Generate a vanity address, or mine a CREATE2 salt for one."
+    )]
     Vanity(VanityArgs),
+    #[clap(
+        name = "derive",
+        visible_alias = "d",
+        about = "Derive the addresses and private keys of a mnemonic."
+    )]
+    Derive(DeriveArgs),
     #[clap(name = "address", visible_aliases = &["a", "addr"], about = "Convert a private key to an address.")]
     Address {
         #[clap(
@@ -107,6 +121,13 @@ impl WalletSubcommands {
                 }
             }
             WalletSubcommands::Vanity(cmd) => {
+                if cmd.deployer.is_some() {
+                    cmd.run_create2()?;
+                } else {
+                    cmd.run()?;
+                }
+            }
+            WalletSubcommands::Derive(cmd) => {
                 cmd.run()?;
             }
             WalletSubcommands::Address { wallet, private_key_override } => {
@@ -6,112 +6,95 @@ use clap::{builder::TypedValueParser, Parser};
 use ethers::{
     core::{k256::ecdsa::SigningKey, rand::thread_rng},
     prelude::{LocalWallet, Signer},
-    types::{H160, U256},
-    utils::{get_contract_address, secret_key_to_address},
+    types::{Address, H160, H256, U256},
+    utils::{get_contract_address, get_create2_address_from_hash, secret_key_to_address},
 };
 use rayon::iter::{self, ParallelIterator};
 use regex::Regex;
-use std::time::Instant;
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
 /// Type alias for the result of [generate_wallet].
 pub type GeneratedWallet = (SigningKey, H160);
 
+/// Type alias for the result of [generate_salt].
+pub type GeneratedSalt = (H256, H160);
+
 /// CLI arguments for `cast wallet vanity`.
 #[derive(Debug, Clone, Parser)]
 pub struct VanityArgs {
     #[clap(
         long,
         help = "Prefix for the vanity address.",
-        required_unless_present = "ends_with",
+        required_unless_present_any = &["ends_with", "matching"],
         value_parser = HexAddressValidator::default(),
         value_name = "HEX"
     )]
     pub starts_with: Option<String>,
     #[clap(long, help = "Suffix for the vanity address.", value_parser = HexAddressValidator::default(), value_name = "HEX")]
     pub ends_with: Option<String>,
+    #[clap(
+        long,
+        help = "Regex the lowercase hex of the vanity address has to match, without the 0x prefix.",
+        conflicts_with_all = &["starts_with", "ends_with"],
+        value_name = "REGEX"
+    )]
+    pub matching: Option<String>,
     #[clap(
         long,
         help = "Generate a vanity contract address created by the generated keypair with the specified nonce.",
         value_name = "NONCE"
     )]
     pub nonce: Option<u64>, /* 2^64-1 is max possible nonce per https://eips.ethereum.org/EIPS/eip-2681 */
+    #[clap(
+        long,
+        help = "Mine a CREATE2 salt instead of a keypair, for a contract deployed by this address.",
+        requires = "init_code_hash",
+        conflicts_with = "nonce",
+        value_name = "ADDRESS"
+    )]
+    pub deployer: Option<Address>,
+    #[clap(
+        long,
+        help = "Init code hash of the contract deployed via CREATE2, see --deployer.",
+        requires = "deployer",
+        value_name = "HASH"
+    )]
+    pub init_code_hash: Option<H256>,
+    #[clap(
+        long,
+        help = "Number of threads to search with, defaults to the number of CPUs.",
+        value_name = "THREADS"
+    )]
+    pub threads: Option<usize>,
 }
 
 impl Cmd for VanityArgs {
     type Output = LocalWallet;
 
     fn run(self) -> eyre::Result<Self::Output> {
-        let Self { starts_with, ends_with, nonce } = self;
-        let mut left_exact_hex = None;
-        let mut left_regex = None;
-        let mut right_exact_hex = None;
-        let mut right_regex = None;
-
-        if let Some(prefix) = starts_with {
-            if let Ok(decoded) = hex::decode(prefix.as_bytes()) {
-                left_exact_hex = Some(decoded)
-            } else {
-                left_regex = Some(Regex::new(&format!(r"^{prefix}"))?);
-            }
-        }
-
-        if let Some(suffix) = ends_with {
-            if let Ok(decoded) = hex::decode(suffix.as_bytes()) {
-                right_exact_hex = Some(decoded)
-            } else {
-                right_regex = Some(Regex::new(&format!(r"{suffix}$"))?);
-            }
-        }
-
-        macro_rules! find_vanity {
-            ($m:ident, $nonce: ident) => {
-                if let Some(nonce) = $nonce {
-                    find_vanity_address_with_nonce($m, nonce)
-                } else {
-                    find_vanity_address($m)
-                }
-            };
+        if self.deployer.is_some() {
+            eyre::bail!("A CREATE2 salt is mined with `VanityArgs::run_create2`")
         }
+        let matcher = self.matcher()?;
+        let nonce = self.nonce;
 
         println!("Starting to generate vanity address...");
         let timer = Instant::now();
 
-        let wallet = match (left_exact_hex, left_regex, right_exact_hex, right_regex) {
-            (Some(left), _, Some(right), _) => {
-                let matcher = HexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (Some(left), _, _, Some(right)) => {
-                let matcher = LeftExactRightRegexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (_, Some(left), _, Some(right)) => {
-                let matcher = RegexMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (_, Some(left), Some(right), _) => {
-                let matcher = LeftRegexRightExactMatcher { left, right };
-                find_vanity!(matcher, nonce)
-            }
-            (Some(left), None, None, None) => {
-                let matcher = LeftHexMatcher { left };
-                find_vanity!(matcher, nonce)
-            }
-            (None, None, Some(right), None) => {
-                let matcher = RightHexMatcher { right };
-                find_vanity!(matcher, nonce)
-            }
-            (None, Some(re), None, None) => {
-                let matcher = SingleRegexMatcher { re };
-                find_vanity!(matcher, nonce)
-            }
-            (None, None, None, Some(re)) => {
-                let matcher = SingleRegexMatcher { re };
-                find_vanity!(matcher, nonce)
-            }
-            _ => unreachable!(),
-        }
-        .expect("failed to generate vanity wallet");
+        let (key, _) = self
+            .thread_pool()?
+            .install(|| match nonce {
+                Some(nonce) => find_with_progress(
+                    wallet_generator(),
+                    create_nonce_matcher(matcher, nonce.into()),
+                ),
+                None => find_with_progress(wallet_generator(), create_matcher(matcher)),
+            })
+            .expect("failed to generate vanity wallet");
+        let wallet = LocalWallet::from(key);
 
         println!(
             "Successfully found vanity address in {} seconds.{}{}\nAddress: {}\nPrivate Key: 0x{}",
@@ -133,6 +116,126 @@ impl Cmd for VanityArgs {
     }
 }
 
+impl VanityArgs {
+    /// Mines a salt for which the CREATE2 address of the `--init-code-hash` deployed by the
+    /// `--deployer` matches, returning the salt and the address.
+    pub fn run_create2(self) -> eyre::Result<GeneratedSalt> {
+        let (deployer, init_code_hash) = match (self.deployer, self.init_code_hash) {
+            (Some(deployer), Some(init_code_hash)) => (deployer, init_code_hash),
+            _ => eyre::bail!("Mining a CREATE2 salt requires --deployer and --init-code-hash"),
+        };
+        let matcher = self.matcher()?;
+
+        println!("Starting to mine CREATE2 salt...");
+        let timer = Instant::now();
+
+        let (salt, address) = self
+            .thread_pool()?
+            .install(|| {
+                find_with_progress(salt_generator(deployer, init_code_hash), |(_, addr)| {
+                    matcher.is_match(addr)
+                })
+            })
+            .expect("failed to mine vanity salt");
+
+        println!(
+            "Successfully found vanity address in {} seconds.\nAddress: {}\nSalt: {:?}",
+            timer.elapsed().as_secs(),
+            SimpleCast::to_checksum_address(&address),
+            salt,
+        );
+
+        Ok((salt, address))
+    }
+
+    /// Returns the matcher for the configured patterns
+    fn matcher(&self) -> eyre::Result<Box<dyn VanityMatcher>> {
+        if let Some(ref pattern) = self.matching {
+            return Ok(Box::new(SingleRegexMatcher { re: Regex::new(pattern)? }))
+        }
+
+        let mut left_exact_hex = None;
+        let mut left_regex = None;
+        let mut right_exact_hex = None;
+        let mut right_regex = None;
+
+        if let Some(ref prefix) = self.starts_with {
+            if let Ok(decoded) = hex::decode(prefix.as_bytes()) {
+                left_exact_hex = Some(decoded)
+            } else {
+                left_regex = Some(Regex::new(&format!(r"^{prefix}"))?);
+            }
+        }
+
+        if let Some(ref suffix) = self.ends_with {
+            if let Ok(decoded) = hex::decode(suffix.as_bytes()) {
+                right_exact_hex = Some(decoded)
+            } else {
+                right_regex = Some(Regex::new(&format!(r"{suffix}$"))?);
+            }
+        }
+
+        let matcher: Box<dyn VanityMatcher> =
+            match (left_exact_hex, left_regex, right_exact_hex, right_regex) {
+                (Some(left), _, Some(right), _) => Box::new(HexMatcher { left, right }),
+                (Some(left), _, _, Some(right)) => {
+                    Box::new(LeftExactRightRegexMatcher { left, right })
+                }
+                (_, Some(left), _, Some(right)) => Box::new(RegexMatcher { left, right }),
+                (_, Some(left), Some(right), _) => {
+                    Box::new(LeftRegexRightExactMatcher { left, right })
+                }
+                (Some(left), None, None, None) => Box::new(LeftHexMatcher { left }),
+                (None, None, Some(right), None) => Box::new(RightHexMatcher { right }),
+                (None, Some(re), None, None) => Box::new(SingleRegexMatcher { re }),
+                (None, None, None, Some(re)) => Box::new(SingleRegexMatcher { re }),
+                _ => eyre::bail!("No vanity pattern provided"),
+            };
+        Ok(matcher)
+    }
+
+    /// Returns the thread pool to search with
+    fn thread_pool(&self) -> eyre::Result<rayon::ThreadPool> {
+        Ok(rayon::ThreadPoolBuilder::new().num_threads(self.threads.unwrap_or_default()).build()?)
+    }
+}
+
+/// Searches the `generator` for an item `is_match` matches while showing the number of searched
+/// items and the search rate.
+pub fn find_with_progress<T, I, F>(generator: I, is_match: F) -> Option<T>
+where
+    T: Send,
+    I: ParallelIterator<Item = T>,
+    F: Fn(&T) -> bool + Sync + Send,
+{
+    let attempts = AtomicU64::new(0);
+    let done = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let pb = indicatif::ProgressBar::new_spinner();
+            pb.set_style(
+                indicatif::ProgressStyle::with_template(
+                    "{spinner:.green} [{elapsed_precise}] {human_pos} addresses searched ({per_sec})",
+                )
+                .unwrap(),
+            );
+            while !done.load(Ordering::Relaxed) {
+                pb.set_position(attempts.load(Ordering::Relaxed));
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            pb.finish_and_clear();
+        });
+
+        let found = generator.find_any(|item| {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            is_match(item)
+        });
+        done.store(true, Ordering::Relaxed);
+        found
+    })
+}
+
 /// Generates random wallets until `matcher` matches the wallet address, returning the wallet.
 pub fn find_vanity_address<T: VanityMatcher>(matcher: T) -> Option<LocalWallet> {
     wallet_generator().find_any(create_matcher(matcher)).map(|(key, _)| key.into())
@@ -174,6 +277,24 @@ pub fn wallet_generator() -> iter::Map<iter::Repeat<()>, fn(()) -> GeneratedWall
     iter::repeat(()).map(|_| generate_wallet())
 }
 
+/// Returns an infinite parallel iterator which yields random salts with the CREATE2 address of
+/// `init_code_hash` deployed by `deployer`.
+#[inline]
+pub fn salt_generator(
+    deployer: Address,
+    init_code_hash: H256,
+) -> impl ParallelIterator<Item = GeneratedSalt> {
+    iter::repeat(()).map(move |_| generate_salt(deployer, init_code_hash))
+}
+
+/// Generates a random salt and derives the CREATE2 address of `init_code_hash` deployed by
+/// `deployer`.
+pub fn generate_salt(deployer: Address, init_code_hash: H256) -> GeneratedSalt {
+    let salt = H256::random_using(&mut thread_rng());
+    let address = get_create2_address_from_hash(deployer, salt.as_bytes(), init_code_hash);
+    (salt, address)
+}
+
 /// Generates a random K-256 signing key and derives its Ethereum address.
 pub fn generate_wallet() -> GeneratedWallet {
     let key = SigningKey::random(&mut thread_rng());
@@ -186,6 +307,13 @@ pub trait VanityMatcher: Send + Sync {
     fn is_match(&self, addr: &H160) -> bool;
 }
 
+impl<T: VanityMatcher + ?Sized> VanityMatcher for Box<T> {
+    #[inline]
+    fn is_match(&self, addr: &H160) -> bool {
+        (**self).is_match(addr)
+    }
+}
+
 /// Matches start and end hex.
 pub struct HexMatcher {
     pub left: Vec<u8>,
@@ -330,6 +458,33 @@ mod tests {
         assert!(addr.starts_with('9'));
     }
 
+    #[test]
+    fn find_vanity_matching_regex() {
+        let args: VanityArgs = VanityArgs::parse_from(["foundry-cli", "--matching", "^0.*[a-f]$"]);
+        let wallet = args.run().unwrap();
+        let addr = format!("{:x}", wallet.address());
+        assert!(addr.starts_with('0'));
+        assert!(!addr.ends_with(|c: char| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn find_vanity_create2_salt() {
+        let deployer = Address::repeat_byte(0x11);
+        let init_code_hash = H256::repeat_byte(0x22);
+        let args: VanityArgs = VanityArgs::parse_from([
+            "foundry-cli",
+            "--starts-with",
+            "00",
+            "--deployer",
+            &format!("{deployer:?}"),
+            "--init-code-hash",
+            &format!("{init_code_hash:?}"),
+        ]);
+        let (salt, addr) = args.run_create2().unwrap();
+        assert_eq!(addr, get_create2_address_from_hash(deployer, salt.as_bytes(), init_code_hash));
+        assert!(format!("{addr:x}").starts_with("00"));
+    }
+
     #[test]
     fn find_simple_vanity_end() {
         let args: VanityArgs = VanityArgs::parse_from(["foundry-cli", "--ends-with", "00"]);
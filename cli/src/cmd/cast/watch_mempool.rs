@@ -0,0 +1,386 @@
+//! cast watch-mempool subcommand
+
+use crate::{
+    opts::{EtherscanOpts, RpcOpts},
+    utils::{self, load_abi},
+};
+use clap::Parser;
+use ethers::{
+    abi::{Abi, Function},
+    prelude::*,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::abi::{format_token, get_func};
+use foundry_config::{
+    figment::{self, value::Dict, Metadata, Profile},
+    impl_figment_convert_cast, Config,
+};
+use futures::{stream::FuturesUnordered, StreamExt};
+use std::collections::{HashMap, VecDeque};
+use tracing::trace;
+
+/// The number of pending transactions that are fetched at once
+const CONCURRENT_REQUESTS: usize = 16;
+
+/// The number of contracts whose fetched ABIs are kept, the oldest ones are evicted first
+const MAX_FETCHED_ABIS: usize = 1024;
+
+/// CLI arguments for `cast watch-mempool`.
+#[derive(Debug, Clone, Parser)]
+pub struct WatchMempoolArgs {
+    /// Only print transactions sent to these addresses
+    #[clap(long, value_name = "ADDRESS")]
+    to: Vec<Address>,
+
+    /// Only print transactions calling these functions, by signature or selector
+    ///
+    /// Transactions are decoded with the given signatures, for example
+    /// "transfer(address to, uint256 amount)".
+    #[clap(long, value_name = "SIG_OR_SELECTOR")]
+    selector: Vec<String>,
+
+    /// ABI files, artifacts or contract addresses to decode the calldata with
+    ///
+    /// If an address is specified, then the ABI is fetched from Etherscan.
+    #[clap(long, value_name = "PATH_OR_ADDRESS")]
+    abi: Vec<String>,
+
+    /// Fetch the ABI of every called contract from Etherscan, once per address
+    #[clap(long)]
+    fetch_abis: bool,
+
+    /// Print one JSON object per transaction
+    #[clap(long, short)]
+    json: bool,
+
+    #[clap(flatten)]
+    rpc: RpcOpts,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+}
+
+impl_figment_convert_cast!(WatchMempoolArgs);
+
+impl figment::Provider for WatchMempoolArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("WatchMempoolArgs")
+    }
+
+    fn data(&self) -> Result<figment::value::Map<Profile, Dict>, figment::Error> {
+        let mut dict = self.rpc.dict();
+        dict.extend(self.etherscan.dict());
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
+impl WatchMempoolArgs {
+    pub async fn run(self) -> Result<()> {
+        let config = Config::from(&self);
+        let url = config
+            .get_rpc_url()
+            .transpose()?
+            .ok_or_else(|| eyre::eyre!("No RPC URL set, use --rpc-url with a WebSocket URL"))?;
+        if !url.starts_with("ws") {
+            eyre::bail!("Watching the mempool requires a WebSocket RPC URL, got {url}")
+        }
+        let provider =
+            Provider::<Ws>::connect(url.as_ref()).await.wrap_err("Failed to connect to the RPC")?;
+
+        let mut decoder = CalldataDecoder::default();
+        let mut selectors = vec![];
+        for sig_or_selector in &self.selector {
+            let selector = match parse_selector(sig_or_selector) {
+                Some(selector) => selector,
+                None => {
+                    let func = get_func(sig_or_selector)?;
+                    let selector = func.short_signature();
+                    decoder.add_function(func);
+                    selector
+                }
+            };
+            selectors.push(selector);
+        }
+        let chain = utils::get_chain(config.chain_id, &provider).await?;
+        for abi in &self.abi {
+            decoder.add_abi(load_abi(&config, Some(chain), abi).await?);
+        }
+
+        let fetch_abi = |to: Address| {
+            let config = &config;
+            async move {
+                let abi = load_abi(config, Some(chain), &format!("{to:?}")).await;
+                if let Err(ref err) = abi {
+                    trace!(target: "cast::watch_mempool", ?err, ?to, "no ABI found");
+                }
+                (to, abi.ok())
+            }
+        };
+        let mut fetches = FuturesUnordered::new();
+        // transactions that are printed once the ABI of the called contract is fetched
+        let mut waiting: HashMap<Address, Vec<Transaction>> = HashMap::new();
+
+        let mut txs = provider
+            .subscribe_pending_txs()
+            .await?
+            .map(|hash| provider.get_transaction(hash))
+            .buffer_unordered(CONCURRENT_REQUESTS);
+        loop {
+            tokio::select! {
+                tx = txs.next() => {
+                    let tx = match tx {
+                        Some(Ok(Some(tx))) => tx,
+                        // the transaction was already mined or dropped
+                        Some(Ok(None)) => continue,
+                        Some(Err(err)) => {
+                            trace!(
+                                target: "cast::watch_mempool", ?err, "failed to get transaction"
+                            );
+                            continue
+                        }
+                        None => break,
+                    };
+                    if !matches_filter(&tx, &self.to, &selectors) {
+                        continue
+                    }
+
+                    let needs_abi = |to: &Address| self.fetch_abis && !decoder.is_fetched(to);
+                    if let Some(to) = tx.to.filter(needs_abi) {
+                        let pending = waiting.entry(to).or_default();
+                        if pending.is_empty() {
+                            fetches.push(fetch_abi(to));
+                        }
+                        pending.push(tx);
+                        continue
+                    }
+                    self.print(&decoder, tx)?;
+                }
+                Some((to, abi)) = fetches.next(), if !fetches.is_empty() => {
+                    decoder.add_fetched_abi(to, abi);
+                    for tx in waiting.remove(&to).unwrap_or_default() {
+                        self.print(&decoder, tx)?;
+                    }
+                }
+            }
+        }
+
+        while let Some((to, abi)) = fetches.next().await {
+            decoder.add_fetched_abi(to, abi);
+            for tx in waiting.remove(&to).unwrap_or_default() {
+                self.print(&decoder, tx)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print(&self, decoder: &CalldataDecoder, tx: Transaction) -> Result<()> {
+        let record = PendingTxRecord::new(decoder, tx);
+        if self.json {
+            println!("{}", serde_json::to_string(&record.to_json())?);
+        } else {
+            println!("{}", record.to_text());
+        }
+        Ok(())
+    }
+}
+
+/// Parses a hex encoded 4 byte selector
+fn parse_selector(s: &str) -> Option<[u8; 4]> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    if s.len() != 8 {
+        return None
+    }
+    hex::decode(s).ok()?.try_into().ok()
+}
+
+/// Returns true if the transaction is sent to one of the addresses and calls one of the selectors
+///
+/// Empty filters match all transactions.
+fn matches_filter(tx: &Transaction, to: &[Address], selectors: &[[u8; 4]]) -> bool {
+    let to_matches = to.is_empty() || tx.to.map(|addr| to.contains(&addr)).unwrap_or_default();
+    let selector_matches = selectors.is_empty() ||
+        tx.input
+            .get(..4)
+            .map(|selector| selectors.iter().any(|s| s == selector))
+            .unwrap_or_default();
+    to_matches && selector_matches
+}
+
+/// Decodes calldata with the functions of its selector
+#[derive(Debug, Default)]
+struct CalldataDecoder {
+    functions: HashMap<[u8; 4], Vec<Function>>,
+    /// The ABIs fetched for called contracts, `None` if there is none
+    fetched: HashMap<Address, Option<Abi>>,
+    /// The contracts in `fetched`, in the order they were fetched in
+    fetched_order: VecDeque<Address>,
+}
+
+impl CalldataDecoder {
+    fn add_function(&mut self, func: Function) {
+        self.functions.entry(func.short_signature()).or_default().push(func);
+    }
+
+    fn add_abi(&mut self, abi: Abi) {
+        abi.functions().cloned().for_each(|func| self.add_function(func));
+    }
+
+    fn is_fetched(&self, address: &Address) -> bool {
+        self.fetched.contains_key(address)
+    }
+
+    /// Adds the ABI fetched for the contract, evicting the oldest fetched ABI once there are more
+    /// than [MAX_FETCHED_ABIS]
+    fn add_fetched_abi(&mut self, address: Address, abi: Option<Abi>) {
+        if self.fetched.insert(address, abi).is_none() {
+            self.fetched_order.push_back(address);
+        }
+        if self.fetched_order.len() > MAX_FETCHED_ABIS {
+            if let Some(oldest) = self.fetched_order.pop_front() {
+                self.fetched.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the signature of the called function and the decoded arguments, if any of the
+    /// functions of the called contract's ABI or of the given functions matches
+    fn decode(
+        &self,
+        to: Option<Address>,
+        input: &[u8],
+    ) -> Option<(String, Vec<(String, String)>)> {
+        let selector: [u8; 4] = input.get(..4)?.try_into().ok()?;
+        let fetched = to
+            .and_then(|to| self.fetched.get(&to)?.as_ref())
+            .into_iter()
+            .flat_map(|abi| abi.functions())
+            .filter(|func| func.short_signature() == selector);
+        let functions = self.functions.get(&selector).into_iter().flatten();
+        fetched.chain(functions).find_map(|func| {
+            let tokens = func.decode_input(&input[4..]).ok()?;
+            let params = func
+                .inputs
+                .iter()
+                .zip(&tokens)
+                .enumerate()
+                .map(|(i, (param, token))| {
+                    let name =
+                        if param.name.is_empty() { i.to_string() } else { param.name.clone() };
+                    (name, format_token(token))
+                })
+                .collect();
+            Some((func.signature(), params))
+        })
+    }
+}
+
+/// A pending transaction and its decoded call, if known
+#[derive(Debug, Clone)]
+struct PendingTxRecord {
+    tx: Transaction,
+    function: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl PendingTxRecord {
+    fn new(decoder: &CalldataDecoder, tx: Transaction) -> Self {
+        match decoder.decode(tx.to, &tx.input) {
+            Some((function, params)) => Self { tx, function: Some(function), params },
+            None => Self { tx, function: None, params: vec![] },
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let tx = &self.tx;
+        let mut text = format!(
+            "- hash: {:?}\n  from: {:?}\n  to: {}\n  value: {}\n  nonce: {}",
+            tx.hash,
+            tx.from,
+            tx.to.map(|to| format!("{to:?}")).unwrap_or_else(|| "contract creation".to_string()),
+            tx.value,
+            tx.nonce,
+        );
+        match self.function {
+            Some(ref function) => {
+                text.push_str(&format!("\n  function: {function}"));
+                for (name, value) in &self.params {
+                    text.push_str(&format!("\n    {name}: {value}"));
+                }
+            }
+            None => text.push_str(&format!("\n  input: {}", tx.input)),
+        }
+        text
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let tx = &self.tx;
+        let params = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::Value::from(value.clone())))
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::json!({
+            "hash": tx.hash,
+            "from": tx.from,
+            "to": tx.to,
+            "value": tx.value,
+            "nonce": tx.nonce,
+            "function": self.function,
+            "args": params,
+            "input": tx.input,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Token;
+
+    #[test]
+    fn can_filter_and_decode_pending_txs() {
+        let token = Address::random();
+        let func = get_func("transfer(address to, uint256 amount)").unwrap();
+        let input = func.encode_input(&[Token::Address(token), Token::Uint(100.into())]).unwrap();
+        let tx = Transaction { to: Some(token), input: input.into(), ..Default::default() };
+
+        let selector = parse_selector("0xa9059cbb").unwrap();
+        assert_eq!(selector, func.short_signature());
+        assert!(matches_filter(&tx, &[], &[]));
+        assert!(matches_filter(&tx, &[token], &[selector]));
+        assert!(!matches_filter(&tx, &[Address::random()], &[]));
+        assert!(!matches_filter(&tx, &[], &[[0; 4]]));
+
+        let mut decoder = CalldataDecoder::default();
+        assert!(PendingTxRecord::new(&decoder, tx.clone()).function.is_none());
+        decoder.add_function(func);
+        let record = PendingTxRecord::new(&decoder, tx);
+        assert_eq!(record.function.as_deref(), Some("transfer(address,uint256)"));
+        assert_eq!(record.params[1], ("amount".to_string(), "100".to_string()));
+    }
+
+    #[test]
+    fn can_decode_with_fetched_abis() {
+        let token = Address::random();
+        let func = get_func("transfer(address to, uint256 amount)").unwrap();
+        let input = func.encode_input(&[Token::Address(token), Token::Uint(100.into())]).unwrap();
+        let abi = Abi {
+            functions: [(func.name.clone(), vec![func])].into_iter().collect(),
+            ..Default::default()
+        };
+
+        let mut decoder = CalldataDecoder::default();
+        decoder.add_fetched_abi(token, Some(abi));
+        assert!(decoder.decode(Some(token), &input).is_some());
+        assert!(decoder.decode(Some(Address::random()), &input).is_none());
+
+        // the oldest ABIs are evicted
+        for _ in 0..MAX_FETCHED_ABIS {
+            decoder.add_fetched_abi(Address::random(), None);
+        }
+        assert!(!decoder.is_fetched(&token));
+        assert_eq!(decoder.fetched.len(), MAX_FETCHED_ABIS);
+        assert!(decoder.decode(Some(token), &input).is_none());
+    }
+}
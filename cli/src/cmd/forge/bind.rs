@@ -210,8 +210,8 @@ impl Cmd for BindArgs {
     fn run(self) -> eyre::Result<Self::Output> {
         if !self.skip_build {
             // run `forge build`
-            let project = self.build_args.project()?;
-            compile::compile(&project, false, false)?;
+            let config = self.build_args.try_load_config_emit_warnings()?;
+            compile::compile(&config.project()?, &config, false, false)?;
         }
 
         let artifacts = self.try_load_config_emit_warnings()?.out;
@@ -29,6 +29,14 @@ pub struct CoreBuildArgs {
     #[serde(skip)]
     pub force: bool,
 
+    #[clap(
+        help_heading = "Cache options",
+        help = "Keep compiler outputs in ~/.foundry/artifacts, keyed by the solc settings.",
+        long
+    )]
+    #[serde(skip)]
+    pub shared_artifacts: bool,
+
     #[clap(
         help_heading = "Linker options",
         help = "Set pre-linked libraries.",
@@ -74,7 +82,7 @@ pub struct CoreBuildArgs {
     #[clap(
         help_heading = "Compiler options",
         help = "Do not access the network.",
-        long_help = "Do not access the network. Missing solc versions will not be installed.",
+        long_help = "Do not access the network. Missing solc versions will not be installed and forks are only served from the RPC cache, which requires a pinned fork block number.",
         long
     )]
     #[serde(skip)]
@@ -135,6 +143,16 @@ pub struct CoreBuildArgs {
     )]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub build_info_path: Option<PathBuf>,
+
+    #[clap(
+        help_heading = "Compiler options",
+        long,
+        value_name = "PATH",
+        help = "Compile with the settings of a solc standard JSON input.",
+        long_help = "Compile with the settings of a solc standard JSON input, e.g. one written by `forge build --standard-json`. The sources of the input must match the files of the project. The settings are not persisted, pass the same input to `forge test` or `forge script` to reuse the artifacts instead of recompiling with the settings of the config. Combine with `--use` to compile with a custom solc binary."
+    )]
+    #[serde(skip)]
+    pub from_standard_json: Option<PathBuf>,
 }
 
 impl CoreBuildArgs {
@@ -148,6 +166,14 @@ impl CoreBuildArgs {
         Ok(config.project()?)
     }
 
+    /// Applies the settings of the `--from-standard-json` input to the project, if any
+    pub fn apply_standard_json(&self, project: &mut Project) -> eyre::Result<()> {
+        if let Some(ref input) = self.from_standard_json {
+            super::standard_json::apply_standard_json(project, input)?;
+        }
+        Ok(())
+    }
+
     /// Returns the remappings to add to the config
     #[deprecated(note = "Use ProjectPathsArgs::get_remappings() instead")]
     pub fn get_remappings(&self) -> Vec<Remapping> {
@@ -228,6 +254,10 @@ impl Provider for CoreBuildArgs {
             dict.insert("force".to_string(), self.force.into());
         }
 
+        if self.shared_artifacts {
+            dict.insert("shared_artifacts".to_string(), true.into());
+        }
+
         if self.build_info {
             dict.insert("build_info".to_string(), self.build_info.into());
         }
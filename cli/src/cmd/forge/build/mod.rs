@@ -11,6 +11,7 @@ use ethers::solc::{Project, ProjectCompileOutput};
 use foundry_common::{
     compile,
     compile::{ProjectCompiler, SkipBuildFilter},
+    fs, vyper,
 };
 use foundry_config::{
     figment::{
@@ -22,7 +23,9 @@ use foundry_config::{
     Config,
 };
 use serde::Serialize;
+use std::path::{Path, PathBuf};
 use watchexec::config::{InitConfig, RuntimeConfig};
+use yansi::Paint;
 
 mod core;
 pub use self::core::CoreBuildArgs;
@@ -30,6 +33,11 @@ pub use self::core::CoreBuildArgs;
 mod paths;
 pub use self::paths::ProjectPathsArgs;
 
+
+mod reproducible;
+
+pub(crate) mod standard_json;
+
 foundry_config::merge_impl_figment_convert!(BuildArgs, args);
 
 /// CLI arguments for `forge build`.
@@ -76,6 +84,42 @@ pub struct BuildArgs {
     #[serde(skip)]
     pub skip: Option<Vec<SkipBuildFilter>>,
 
+    #[clap(
+        long,
+        help = "Compile the project twice in different directories and fail if any bytecode differs.",
+        long_help = "Compile the project twice, each time in a copy of it in a different temporary directory, and fail if the bytecode of any contract differs. Use `deterministic_metadata = true` for reproducible builds."
+    )]
+    #[serde(skip)]
+    pub assert_reproducible: bool,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Write the contracts that exceed their `[size_limits]` budgets to a JSON file.",
+        long_help = "Write the contracts that exceed their `[size_limits]` budgets to a JSON file. The file is written on every build with configured budgets, an empty list means all budgets are met."
+    )]
+    #[serde(skip)]
+    pub size_report: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Write the functions to move into libraries of contracts that exceed the size limit to a JSON file.",
+        long_help = "Write the functions of every contract that exceeds the EIP-170 size limit to a JSON file, with the number of deployed bytes attributed to each function by the source maps, largest first. An empty list means no contract exceeds the limit."
+    )]
+    #[serde(skip)]
+    pub size_suggestions: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "DIR",
+        conflicts_with = "from_standard_json",
+        help = "Write the solc standard JSON input of every compiler job to a directory.",
+        long_help = "Write the solc standard JSON input of every compiler job to a directory, named after the solc version, e.g. `0.8.19.json`. The inputs are the exact inputs solc is invoked with, except for the absolute source paths."
+    )]
+    #[serde(skip)]
+    pub standard_json: Option<PathBuf>,
+
     #[clap(flatten)]
     #[serde(skip)]
     pub watch: WatchArgs,
@@ -96,14 +140,45 @@ impl Cmd for BuildArgs {
             project = config.project()?;
         }
 
+        self.args.apply_standard_json(&mut project)?;
+        if let Some(ref out) = self.standard_json {
+            for file in standard_json::emit_standard_json(&project, out)? {
+                println!("Wrote standard json input to {}", file.display());
+            }
+        }
+
         let filters = self.skip.unwrap_or_default();
 
-        if self.args.silent {
-            compile::suppress_compile_with_filter(&project, filters)
+        let output = if self.args.silent {
+            compile::suppress_compile_with_filter(&project, &config, filters)
         } else {
-            let compiler = ProjectCompiler::with_filter(self.names, self.sizes, filters);
+            let mut compiler =
+                ProjectCompiler::with_filter(self.names, self.sizes, filters).config(&config);
+            if let Some(path) = self.size_suggestions.as_ref() {
+                compiler = compiler.size_suggestions(path);
+            }
             compiler.compile(&project)
+        }?;
+
+        if config.size_limits.has_limits() {
+            check_size_limits(&output, &config, self.size_report.as_deref())?;
+        }
+
+        vyper::compile_vyper(&project, config.offline)?;
+
+        if config.deterministic_metadata {
+            reproducible::normalize_metadata_paths(
+                &project.paths.root,
+                &output,
+                !config.optimizer_overrides.is_empty(),
+            )?;
+        }
+
+        if self.assert_reproducible {
+            reproducible::assert_reproducible(&self.args, &config)?;
         }
+
+        Ok(output)
     }
 }
 
@@ -133,6 +208,31 @@ impl BuildArgs {
     }
 }
 
+/// The exit code of `forge build` if a contract exceeds its `[size_limits]` budget
+pub const SIZE_LIMIT_EXIT_CODE: i32 = 3;
+
+/// Checks the sizes of the compiled contracts against their `[size_limits]` budgets and exits with
+/// [SIZE_LIMIT_EXIT_CODE] if any budget is exceeded
+fn check_size_limits(
+    output: &ProjectCompileOutput,
+    config: &Config,
+    report: Option<&Path>,
+) -> eyre::Result<()> {
+    let violations = compile::size_limit_violations(output, &config.size_limits);
+    if let Some(report) = report {
+        fs::write_json_file(report, &violations)?;
+    }
+    if violations.is_empty() {
+        return Ok(())
+    }
+
+    eprintln!("{}", Paint::red("Contracts exceed their size limits:"));
+    for violation in &violations {
+        eprintln!("  {violation}");
+    }
+    std::process::exit(SIZE_LIMIT_EXIT_CODE)
+}
+
 // Make this args a `figment::Provider` so that it can be merged into the `Config`
 impl Provider for BuildArgs {
     fn metadata(&self) -> Metadata {
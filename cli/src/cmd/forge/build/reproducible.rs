@@ -0,0 +1,229 @@
+//! Support for reproducible builds, see `deterministic_metadata` and `--assert-reproducible`
+
+use super::CoreBuildArgs;
+use crate::cmd::LoadConfig;
+use ethers::solc::{artifacts::BytecodeObject, Artifact, ProjectCompileOutput};
+use eyre::WrapErr;
+use foundry_common::{compile, fs};
+use foundry_config::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::{collections::BTreeMap, path::Path};
+use walkdir::WalkDir;
+
+/// Matches the placeholders of unlinked libraries, which are derived from the absolute path of
+/// the library
+static RE_PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"__\$[0-9a-fA-F]{34}\$__").unwrap());
+
+/// Makes the source paths in the metadata of all written artifacts relative to the root
+///
+/// solc is invoked with absolute paths, so the metadata would otherwise differ between checkouts
+/// of the same project.
+///
+/// Only the freshly compiled artifacts are normalized, unless `include_cached` is set. This is
+/// required for `optimizer_overrides`, whose output is read from the cache after the artifacts
+/// were replaced.
+pub fn normalize_metadata_paths(
+    root: &Path,
+    output: &ProjectCompileOutput,
+    include_cached: bool,
+) -> eyre::Result<()> {
+    let cached = include_cached.then(|| output.cached_artifacts().artifact_files());
+    for artifact in output.compiled_artifacts().artifact_files().chain(cached.into_iter().flatten())
+    {
+        normalize_artifact_metadata_paths(root, &artifact.file)?;
+    }
+    Ok(())
+}
+
+/// Makes the source paths in the metadata of the artifact file relative to the root, see
+/// [normalize_metadata_paths]
+fn normalize_artifact_metadata_paths(root: &Path, file: &Path) -> eyre::Result<()> {
+    let prefix = format!("{}{}", root.display(), std::path::MAIN_SEPARATOR);
+    let mut json: Value = fs::read_json_file(file)?;
+    let mut changed = false;
+    for key in ["metadata", "rawMetadata"] {
+        if let Some(value) = json.get_mut(key) {
+            changed |= strip_path_prefix(value, &prefix);
+        }
+    }
+    if changed {
+        fs::write_json_file(file, &json)?;
+    }
+    Ok(())
+}
+
+/// Strips the prefix from all strings and object keys, returns whether anything changed
+fn strip_path_prefix(value: &mut Value, prefix: &str) -> bool {
+    match value {
+        Value::String(s) if s.contains(prefix) => {
+            // `rawMetadata` is the JSON encoded metadata
+            *s = s.replace(prefix, "");
+            true
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .fold(false, |changed, value| strip_path_prefix(value, prefix) | changed),
+        Value::Object(map) => {
+            let mut changed = false;
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    changed |= strip_path_prefix(&mut value, prefix);
+                    match key.strip_prefix(prefix) {
+                        Some(stripped) => {
+                            changed = true;
+                            (stripped.to_string(), value)
+                        }
+                        None => (key, value),
+                    }
+                })
+                .collect();
+            changed
+        }
+        _ => false,
+    }
+}
+
+/// Compiles the project twice, each time in a copy of it in a different temporary directory, and
+/// fails if the bytecode of any contract differs
+pub fn assert_reproducible(args: &CoreBuildArgs, config: &Config) -> eyre::Result<()> {
+    let first = compile_copy(args, config)?;
+    let second = compile_copy(args, config)?;
+
+    let mismatches = first
+        .iter()
+        .filter(|(id, bytecode)| second.get(*id) != Some(bytecode))
+        .map(|(id, _)| id.as_str())
+        .chain(second.keys().filter(|id| !first.contains_key(*id)).map(String::as_str))
+        .collect::<Vec<_>>();
+    if !mismatches.is_empty() {
+        let mut msg = format!(
+            "The build is not reproducible, the bytecode of {} contracts differs:\n",
+            mismatches.len()
+        );
+        for id in mismatches {
+            msg.push_str(&format!("  {id}\n"));
+        }
+        if !config.deterministic_metadata {
+            msg.push_str(
+                "Set `deterministic_metadata = true` to exclude the metadata hash and solc version \
+                 from the bytecode",
+            );
+        }
+        eyre::bail!(msg.trim_end().to_string())
+    }
+
+    println!(
+        "Compiled {} contracts twice in different directories, all bytecode is identical",
+        first.len()
+    );
+    Ok(())
+}
+
+/// Copies the project into a temporary directory, compiles it there and returns the normalized
+/// creation and runtime bytecode of all contracts by `<source path>:<name>`
+fn compile_copy(
+    args: &CoreBuildArgs,
+    config: &Config,
+) -> eyre::Result<BTreeMap<String, (Option<String>, Option<String>)>> {
+    let root = &config.__root.0;
+    let dir = tempfile::tempdir()?;
+    copy_project(config, dir.path())
+        .wrap_err_with(|| format!("Failed to copy the project to {}", dir.path().display()))?;
+
+    let mut args = args.clone();
+    args.project_paths.root = Some(dir.path().to_path_buf());
+    if let Some(config_path) = &args.project_paths.config_path {
+        if let Ok(relative) = config_path.strip_prefix(root) {
+            args.project_paths.config_path = Some(dir.path().join(relative));
+        }
+    }
+    let copy = args.try_load_config()?;
+    let project = copy.ephemeral_no_artifacts_project()?;
+    let output = compile::suppress_compile(&project, &copy)?;
+
+    Ok(output
+        .into_artifacts()
+        .map(|(id, artifact)| {
+            let source = id.source.strip_prefix(dir.path()).unwrap_or(&id.source);
+            let id = format!("{}:{}", source.display(), id.name);
+            let bytecode = artifact.get_bytecode_object().map(|code| normalize_bytecode(&code));
+            let deployed =
+                artifact.get_deployed_bytecode_object().map(|code| normalize_bytecode(&code));
+            (id, (bytecode, deployed))
+        })
+        .collect())
+}
+
+/// Returns the hex encoded bytecode, with the library placeholders zeroed
+fn normalize_bytecode(code: &BytecodeObject) -> String {
+    match code {
+        BytecodeObject::Bytecode(bytes) => hex::encode(bytes),
+        BytecodeObject::Unlinked(code) => RE_PLACEHOLDER
+            .replace_all(code.trim_start_matches("0x"), "__$0000000000000000000000000000000000$__")
+            .to_string(),
+    }
+}
+
+/// Copies all files of the project, except for its artifacts, cache and git directory
+fn copy_project(config: &Config, to: &Path) -> eyre::Result<()> {
+    let root = &config.__root.0;
+    let paths = config.project_paths();
+    let skip =
+        [paths.artifacts, paths.cache.parent().unwrap_or(root).to_path_buf(), root.join(".git")];
+
+    let entries = WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|entry| entry.path() == root || !skip.iter().any(|dir| entry.path() == dir));
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(root)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_strip_metadata_paths() {
+        let mut metadata = serde_json::json!({
+            "metadata": {
+                "settings": { "compilationTarget": { "/root/src/A.sol": "A" } },
+                "sources": { "/root/src/A.sol": { "content": "contract A {}" } }
+            },
+            "rawMetadata": "{\"sources\":{\"/root/src/A.sol\":{}}}",
+            "ast": { "absolutePath": "/root/src/A.sol" }
+        });
+        let prefix = "/root/";
+        assert!(strip_path_prefix(metadata.get_mut("metadata").unwrap(), prefix));
+        assert!(strip_path_prefix(metadata.get_mut("rawMetadata").unwrap(), prefix));
+        assert_eq!(
+            metadata,
+            serde_json::json!({
+                "metadata": {
+                    "settings": { "compilationTarget": { "src/A.sol": "A" } },
+                    "sources": { "src/A.sol": { "content": "contract A {}" } }
+                },
+                "rawMetadata": "{\"sources\":{\"src/A.sol\":{}}}",
+                "ast": { "absolutePath": "/root/src/A.sol" }
+            })
+        );
+    }
+
+    #[test]
+    fn can_normalize_library_placeholders() {
+        let a = BytecodeObject::Unlinked("0x6080__$8e0cb39e8357fc3e4c0be3d6b1a5b9b5f4$__00".into());
+        let b = BytecodeObject::Unlinked("6080__$aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa$__00".into());
+        assert_eq!(normalize_bytecode(&a), normalize_bytecode(&b));
+    }
+}
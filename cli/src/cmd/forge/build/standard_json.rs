@@ -0,0 +1,74 @@
+//! Support for solc standard JSON input, see `--standard-json` and `--from-standard-json`
+
+use ethers::solc::{CompilerInput, Graph, Project};
+use eyre::WrapErr;
+use foundry_common::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes the standard JSON input of every solc job of the project to `out`
+///
+/// The inputs are named after the solc version, e.g. `0.8.19.json`, inputs of Yul sources are
+/// named `0.8.19.yul.json`. Returns the written files.
+pub fn emit_standard_json(project: &Project, out: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let jobs = if project.auto_detect {
+        let graph = Graph::resolve(&project.paths)?;
+        let (versions, _) = graph.into_sources_by_version(project.offline)?;
+        versions.get(project)?.into_values().collect::<Vec<_>>()
+    } else {
+        vec![(project.solc.version()?, project.paths.read_input_files()?)]
+    };
+
+    fs::create_dir_all(out)?;
+    let mut files = Vec::new();
+    for (version, sources) in jobs {
+        for input in CompilerInput::with_sources(sources) {
+            let input = input
+                .settings(project.solc_config.settings.clone())
+                .normalize_evm_version(&version)
+                .with_remappings(project.paths.remappings.clone())
+                .with_base_path(&project.paths.root)
+                .sanitized(&version);
+            let name = if input.language == "Solidity" {
+                format!("{version}.json")
+            } else {
+                format!("{version}.{}.json", input.language.to_lowercase())
+            };
+            let file = out.join(name);
+            fs::write_json_file(&file, &input)?;
+            files.push(file);
+        }
+    }
+    Ok(files)
+}
+
+/// Configures the project to compile with the settings of the standard JSON input at `path`
+///
+/// All sources of the input must match the files of the project, so the artifacts and the cache
+/// of the project stay consistent with the sources. The remappings and output selection of the
+/// project are kept, since the artifacts need all outputs.
+pub fn apply_standard_json(project: &mut Project, path: &Path) -> eyre::Result<()> {
+    let input: CompilerInput = fs::read_json_file(path)
+        .wrap_err_with(|| format!("Failed to read standard json input {}", path.display()))?;
+
+    for (source, content) in input.sources.iter() {
+        let file = project.paths.root.join(source);
+        let current = std::fs::read_to_string(&file).wrap_err_with(|| {
+            format!(
+                "Source `{}` of the standard json input is not in the project",
+                source.display()
+            )
+        })?;
+        if current != content.content.as_str() {
+            eyre::bail!(
+                "Source `{}` of the standard json input differs from the project file",
+                source.display()
+            )
+        }
+    }
+
+    let mut settings = input.settings;
+    settings.remappings.clear();
+    settings.output_selection = project.solc_config.settings.output_selection.clone();
+    project.solc_config.settings = settings;
+    Ok(())
+}
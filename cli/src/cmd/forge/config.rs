@@ -1,9 +1,14 @@
 //! config command
 
-use crate::cmd::{forge::build::BuildArgs, utils::Cmd, LoadConfig};
+use crate::{
+    cmd::{forge::build::BuildArgs, utils::Cmd, LoadConfig},
+    utils,
+};
 use clap::Parser;
-use foundry_common::{evm::EvmArgs, term::cli_warn};
-use foundry_config::fix::fix_tomls;
+use ethers::providers::Middleware;
+use foundry_common::{evm::EvmArgs, term::cli_warn, try_get_http_provider};
+use foundry_config::{figment::Figment, fix::fix_tomls, provenance, Config};
+use std::collections::BTreeMap;
 
 foundry_config::impl_figment_convert!(ConfigArgs, opts, evm_opts);
 
@@ -19,6 +24,19 @@ pub struct ConfigArgs {
     #[clap(help = "Attempt to fix any configuration warnings.", long)]
     fix: bool,
 
+    #[clap(
+        help = "Print the resolved config values and whether they were set by a config file, an environment variable or a command line argument.",
+        long,
+        conflicts_with = "basic"
+    )]
+    resolve: bool,
+
+    #[clap(
+        help = "Update all pinned `rpc_endpoints` to the latest block of their endpoint.",
+        long
+    )]
+    bump_fork_blocks: bool,
+
     // support nested build arguments
     #[clap(flatten)]
     opts: BuildArgs,
@@ -40,6 +58,15 @@ impl Cmd for ConfigArgs {
 
         let config = self.try_load_config_unsanitized_emit_warnings()?;
 
+        if self.bump_fork_blocks {
+            return utils::block_on(bump_fork_blocks(&config))
+        }
+
+        if self.resolve {
+            let figment: Figment = (&self).into();
+            return print_resolved(&config, &figment, self.json)
+        }
+
         let s = if self.basic {
             let config = config.into_basic();
             if self.json {
@@ -57,3 +84,53 @@ impl Cmd for ConfigArgs {
         Ok(())
     }
 }
+
+/// Updates the blocks of all pinned `rpc_endpoints` to the latest block of their endpoint
+async fn bump_fork_blocks(config: &Config) -> eyre::Result<()> {
+    let mut blocks = BTreeMap::new();
+    for (alias, pinned) in config.rpc_endpoints.pinned_blocks() {
+        let url = config
+            .get_rpc_url_with_alias(alias)
+            .ok_or_else(|| eyre::eyre!("unknown rpc endpoint `{alias}`"))??;
+        let provider = try_get_http_provider(url.as_ref())?;
+        let latest = provider.get_block_number().await?.as_u64();
+        println!("{alias}: {pinned} -> {latest}");
+        blocks.insert(alias.clone(), latest);
+    }
+
+    if blocks.is_empty() {
+        cli_warn!("No pinned `rpc_endpoints` found");
+        return Ok(())
+    }
+    config.update_pinned_blocks(&blocks)
+}
+
+/// Prints all config values with the provider that set them
+fn print_resolved(config: &Config, figment: &Figment, json: bool) -> eyre::Result<()> {
+    let values = provenance::resolve(config, figment)?;
+    if json {
+        let values = values
+            .iter()
+            .map(|resolved| {
+                serde_json::json!({
+                    "key": resolved.key,
+                    "value": resolved.value,
+                    "source": resolved.source.to_string(),
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&values)?);
+        return Ok(())
+    }
+
+    let activated = config.activated_profiles()?;
+    if activated.is_empty() {
+        println!("# profile: {}", config.profile);
+    } else {
+        println!("# profile: {}, activated: {}", config.profile, activated.join(", "));
+    }
+    for resolved in values {
+        println!("{} = {} # {}", resolved.key, resolved.value, resolved.source);
+    }
+    Ok(())
+}
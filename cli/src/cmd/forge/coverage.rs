@@ -113,6 +113,7 @@ impl CoverageArgs {
         };
 
         let output = ProjectCompiler::default()
+            .config(config)
             .compile(&project)?
             .with_stripped_file_prefixes(project.root());
 
@@ -6,7 +6,7 @@ use crate::{
         LoadConfig,
     },
     opts::{EthereumOpts, EtherscanOpts, TransactionOpts},
-    utils,
+    utils::{self, parse_u256},
 };
 use cast::SimpleCast;
 use clap::{Parser, ValueHint};
@@ -14,7 +14,8 @@ use ethers::{
     abi::{Abi, Constructor, Token},
     prelude::{artifacts::BytecodeObject, ContractFactory, Middleware, MiddlewareBuilder},
     solc::{info::ContractInfo, utils::canonicalized},
-    types::{transaction::eip2718::TypedTransaction, Chain},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, Chain, H256, U256},
+    utils::get_create2_address,
 };
 use eyre::Context;
 use foundry_common::{abi::parse_tokens, compile, estimate_eip1559_fees};
@@ -51,6 +52,15 @@ pub struct CreateArgs {
     )]
     constructor_args_path: Option<PathBuf>,
 
+    #[clap(
+        long,
+        help = "Deploy the contract deterministically via the CREATE2 deployer with the given salt.",
+        long_help = "Deploy the contract deterministically via the CREATE2 deployer with the given salt.\n\nThe deployer is the `create2_deployer` configured for the chain, which defaults to the canonical deterministic deployment proxy.",
+        value_parser = parse_u256,
+        value_name = "SALT"
+    )]
+    salt: Option<U256>,
+
     #[clap(flatten)]
     opts: CoreBuildArgs,
 
@@ -88,12 +98,13 @@ impl CreateArgs {
     /// Executes the command to create a contract
     pub async fn run(mut self) -> eyre::Result<()> {
         // Find Project & Compile
-        let project = self.opts.project()?;
+        let config = self.opts.try_load_config_emit_warnings()?;
+        let project = config.project()?;
         let mut output = if self.json || self.opts.silent {
             // Suppress compile stdout messages when printing json output or when silent
-            compile::suppress_compile(&project)
+            compile::suppress_compile(&project, &config)
         } else {
-            compile::compile(&project, false, false)
+            compile::compile(&project, &config, false, false)
         }?;
 
         if let Some(ref mut path) = self.contract.path {
@@ -135,16 +146,17 @@ impl CreateArgs {
         };
 
         let chain_id = provider.get_chainid().await?.as_u64();
+        let create2_deployer = self.salt.map(|_| config.create2_deployer_for(chain_id));
         if self.unlocked {
             // Deploy with unlocked account
             let sender = self.eth.wallet.from.expect("required");
             let provider = provider.with_sender(sender);
-            self.deploy(abi, bin, params, provider, chain_id).await
+            self.deploy(abi, bin, params, provider, chain_id, create2_deployer).await
         } else {
             // Deploy with signer
             let signer = self.eth.wallet.signer(chain_id).await?;
             let provider = provider.with_signer(signer);
-            self.deploy(abi, bin, params, provider, chain_id).await
+            self.deploy(abi, bin, params, provider, chain_id, create2_deployer).await
         }
     }
 
@@ -186,6 +198,8 @@ impl CreateArgs {
     }
 
     /// Deploys the contract
+    ///
+    /// If a salt is set, the contract is deployed via the given CREATE2 deployer.
     async fn deploy<M: Middleware + 'static>(
         self,
         abi: Abi,
@@ -193,6 +207,7 @@ impl CreateArgs {
         args: Vec<Token>,
         provider: M,
         chain: u64,
+        create2_deployer: Option<Address>,
     ) -> eyre::Result<()> {
         let deployer_address =
             provider.default_sender().expect("no sender address set for provider");
@@ -220,6 +235,35 @@ impl CreateArgs {
             deployer.tx.set_value(value);
         }
 
+        // redirect the deployment to the CREATE2 deployer, which expects the salt followed by the
+        // init code as calldata
+        let create2_address = match (self.salt, create2_deployer) {
+            (Some(salt), Some(create2_deployer)) => {
+                let init_code = deployer.tx.data().cloned().unwrap_or_default();
+                let salt = H256::from_uint(&salt);
+                let address =
+                    get_create2_address(create2_deployer, salt.as_bytes(), init_code.clone());
+
+                if provider.get_code(create2_deployer, None).await?.is_empty() {
+                    eyre::bail!("CREATE2 deployer {create2_deployer:?} is not present on chain {chain}, it can be deployed with `cast create2-deployer`")
+                }
+                if !provider.get_code(address, None).await?.is_empty() {
+                    eyre::bail!(
+                        "A contract is already deployed at the predicted address {}, use a different salt",
+                        SimpleCast::to_checksum_address(&address)
+                    )
+                }
+                if !self.json {
+                    println!("Predicted address: {}", SimpleCast::to_checksum_address(&address));
+                }
+
+                deployer.tx.set_to(create2_deployer);
+                deployer.tx.set_data(Bytes::from([salt.as_bytes(), &init_code[..]].concat()));
+                Some(address)
+            }
+            _ => None,
+        };
+
         // fill tx first because if you target a lower gas than current base, eth_estimateGas
         // will fail and create will fail
         provider.fill_transaction(&mut deployer.tx, None).await?;
@@ -284,9 +328,28 @@ impl CreateArgs {
         }
 
         // Deploy the actual contract
-        let (deployed_contract, receipt) = deployer.send_with_receipt().await?;
+        let (address, receipt) = match create2_address {
+            Some(address) => {
+                let receipt = provider
+                    .send_transaction(deployer.tx, None)
+                    .await?
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("no receipt for the deployment transaction"))?;
+                if provider.get_code(address, None).await?.is_empty() {
+                    eyre::bail!(
+                        "Transaction {:?} did not deploy the contract to {}",
+                        receipt.transaction_hash,
+                        SimpleCast::to_checksum_address(&address)
+                    )
+                }
+                (address, receipt)
+            }
+            None => {
+                let (deployed_contract, receipt) = deployer.send_with_receipt().await?;
+                (deployed_contract.address(), receipt)
+            }
+        };
 
-        let address = deployed_contract.address();
         if self.json {
             let output = json!({
                 "deployer": SimpleCast::to_checksum_address(&deployer_address),
@@ -363,4 +426,22 @@ mod tests {
         assert_eq!(args.retry.retries, 10);
         assert_eq!(args.retry.delay, 30);
     }
+
+    #[test]
+    fn can_parse_create_with_salt() {
+        let args: CreateArgs = CreateArgs::parse_from([
+            "foundry-cli",
+            "src/Domains.sol:Domains",
+            "--salt",
+            "0x1234",
+            "--value",
+            "100",
+        ]);
+        assert_eq!(args.salt, Some(U256::from(0x1234)));
+        assert_eq!(args.tx.value, Some(U256::from(100)));
+
+        let args: CreateArgs =
+            CreateArgs::parse_from(["foundry-cli", "src/Domains.sol:Domains", "--salt", "42"]);
+        assert_eq!(args.salt, Some(U256::from(42)));
+    }
 }
@@ -0,0 +1,381 @@
+//! diff-bytecode command
+
+use crate::{cmd::forge::verify::bytecode::metadata_len, opts::RpcOpts};
+use clap::Parser;
+use ethers::{prelude::Middleware, solc::sourcemap, types::Address};
+use forge::revm::OpCode;
+use foundry_common::{fs, try_get_http_provider};
+use foundry_config::Config;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use yansi::Paint;
+
+const JUMPDEST: u8 = 0x5b;
+
+/// CLI arguments for `forge diff-bytecode`.
+#[derive(Debug, Clone, Parser)]
+pub struct DiffBytecodeArgs {
+    #[clap(
+        help = "The old bytecode: an artifact file, the address of a deployed contract or hex encoded bytecode.",
+        value_name = "OLD"
+    )]
+    pub old: BytecodeSource,
+
+    #[clap(
+        help = "The new bytecode: an artifact file, the address of a deployed contract or hex encoded bytecode.",
+        value_name = "NEW"
+    )]
+    pub new: BytecodeSource,
+
+    #[clap(long, help = "Compare the creation code of artifacts instead of their runtime code.")]
+    pub creation: bool,
+
+    #[clap(long, help = "Print the diff as JSON.")]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub rpc: RpcOpts,
+}
+
+impl DiffBytecodeArgs {
+    /// Disassembles both bytecodes and prints the blocks that differ
+    pub async fn run(self) -> eyre::Result<BytecodeDiff> {
+        let old = self.load(&self.old).await?;
+        let new = self.load(&self.new).await?;
+
+        let diff = BytecodeDiff::new(&old, &new);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            println!("{diff}");
+        }
+        Ok(diff)
+    }
+
+    async fn load(&self, source: &BytecodeSource) -> eyre::Result<Disassembly> {
+        match source {
+            BytecodeSource::Address(address) => {
+                if self.creation {
+                    eyre::bail!("Only the runtime code of deployed contracts can be compared")
+                }
+                let config = Config::load();
+                let rpc_url = self.rpc.url(Some(&config))?.ok_or_else(|| {
+                    eyre::eyre!("No RPC URL set, use --rpc-url or set `eth_rpc_url`")
+                })?;
+                let provider = try_get_http_provider(rpc_url.as_ref())?;
+                let code = provider.get_code(*address, None).await?;
+                if code.is_empty() {
+                    eyre::bail!("No bytecode deployed at {address:?}")
+                }
+                Ok(Disassembly::new(&code, |_| None))
+            }
+            BytecodeSource::Code(code) => Ok(Disassembly::new(code, |_| None)),
+            BytecodeSource::Artifact(path) => read_artifact(path, self.creation),
+        }
+    }
+}
+
+/// Where a bytecode to compare comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeSource {
+    /// The runtime code of a deployed contract
+    Address(Address),
+    /// Hex encoded bytecode
+    Code(Vec<u8>),
+    /// A contract artifact, with source maps if they were emitted
+    Artifact(PathBuf),
+}
+
+impl FromStr for BytecodeSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 42 {
+            if let Ok(address) = s.parse() {
+                return Ok(BytecodeSource::Address(address))
+            }
+        }
+        match s.strip_prefix("0x") {
+            Some(code) => hex::decode(code)
+                .map(BytecodeSource::Code)
+                .map_err(|err| format!("Invalid bytecode: {err}")),
+            None => Ok(BytecodeSource::Artifact(PathBuf::from(s))),
+        }
+    }
+}
+
+/// A disassembled instruction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Instruction {
+    pub pc: usize,
+    /// The opcode, followed by the pushed value for `PUSH` instructions
+    pub opcode: String,
+}
+
+/// A sequence of instructions that starts at a `JUMPDEST`, or at the start of the code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Block {
+    pub pc: usize,
+    /// The source location of the block, if a source map is available
+    pub source: Option<String>,
+    pub instructions: Vec<Instruction>,
+    /// The instructions with pushed jump targets masked, so that blocks that only moved compare
+    /// equal
+    #[serde(skip)]
+    key: Vec<String>,
+}
+
+/// Bytecode split into blocks, without its metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    pub size: usize,
+    pub blocks: Vec<Block>,
+    /// The CBOR encoded metadata at the end of the bytecode
+    pub metadata: Option<Vec<u8>>,
+}
+
+impl Disassembly {
+    /// Disassembles the bytecode, `locate` returns the source location of the n-th instruction
+    pub fn new(code: &[u8], locate: impl Fn(usize) -> Option<String>) -> Self {
+        let (code, metadata) = match metadata_len(code) {
+            Some(len) => (&code[..code.len() - len], Some(code[code.len() - len..].to_vec())),
+            None => (code, None),
+        };
+
+        let mut instructions = Vec::new();
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = code[pc];
+            // PUSH1..PUSH32
+            let push_len = if (0x60..=0x7f).contains(&op) { (op - 0x5f) as usize } else { 0 };
+            let data = &code[(pc + 1).min(code.len())..(pc + 1 + push_len).min(code.len())];
+            instructions.push((pc, op, data));
+            pc += 1 + push_len;
+        }
+        let jumpdests: HashSet<usize> =
+            instructions.iter().filter(|(_, op, _)| *op == JUMPDEST).map(|(pc, ..)| *pc).collect();
+
+        let mut blocks: Vec<Block> = Vec::new();
+        for (ic, (pc, op, data)) in instructions.into_iter().enumerate() {
+            if blocks.is_empty() || op == JUMPDEST {
+                blocks.push(Block { pc, source: None, instructions: Vec::new(), key: Vec::new() });
+            }
+            let block = blocks.last_mut().expect("block was pushed");
+            if block.source.is_none() {
+                block.source = locate(ic);
+            }
+
+            let name = OpCode::try_from_u8(op)
+                .map(|opcode| opcode.as_str().to_string())
+                .unwrap_or_else(|| format!("UNDEFINED(0x{op:02x})"));
+            let (opcode, key) = if data.is_empty() {
+                (name.clone(), name)
+            } else {
+                let opcode = format!("{name} 0x{}", hex::encode(data));
+                let target = data.iter().fold(0usize, |acc, byte| acc << 8 | *byte as usize);
+                if data.len() <= 4 && jumpdests.contains(&target) {
+                    (opcode, format!("{name} <jumpdest>"))
+                } else {
+                    (opcode.clone(), opcode)
+                }
+            };
+            block.instructions.push(Instruction { pc, opcode });
+            block.key.push(key);
+        }
+
+        Self { size: code.len(), blocks, metadata }
+    }
+}
+
+/// A run of blocks that were removed from the old bytecode and added to the new one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffHunk {
+    pub removed: Vec<Block>,
+    pub added: Vec<Block>,
+}
+
+/// The result of `forge diff-bytecode`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytecodeDiff {
+    pub old_size: usize,
+    pub new_size: usize,
+    pub unchanged_blocks: usize,
+    pub metadata_changed: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl BytecodeDiff {
+    /// Aligns the blocks of both bytecodes by their longest common subsequence
+    pub fn new(old: &Disassembly, new: &Disassembly) -> Self {
+        let (n, m) = (old.blocks.len(), new.blocks.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old.blocks[i].key == new.blocks[j].key {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let mut hunks = Vec::new();
+        let mut hunk = DiffHunk::default();
+        let mut unchanged_blocks = 0;
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old.blocks[i].key == new.blocks[j].key {
+                if !hunk.removed.is_empty() || !hunk.added.is_empty() {
+                    hunks.push(std::mem::take(&mut hunk));
+                }
+                unchanged_blocks += 1;
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                hunk.removed.push(old.blocks[i].clone());
+                i += 1;
+            } else {
+                hunk.added.push(new.blocks[j].clone());
+                j += 1;
+            }
+        }
+        if !hunk.removed.is_empty() || !hunk.added.is_empty() {
+            hunks.push(hunk);
+        }
+
+        Self {
+            old_size: old.size,
+            new_size: new.size,
+            unchanged_blocks,
+            metadata_changed: old.metadata != new.metadata,
+            hunks,
+        }
+    }
+}
+
+impl fmt::Display for BytecodeDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Old code: {} bytes", self.old_size)?;
+        writeln!(f, "New code: {} bytes", self.new_size)?;
+        writeln!(f, "Unchanged blocks: {}", self.unchanged_blocks)?;
+        if self.metadata_changed {
+            writeln!(f, "{}", Paint::yellow("Metadata changed"))?;
+        }
+        if self.hunks.is_empty() {
+            return write!(f, "{}", Paint::green("No instructions changed."))
+        }
+
+        let write_blocks = |f: &mut fmt::Formatter<'_>, blocks: &[Block], sign: &str| {
+            for block in blocks {
+                if let Some(ref source) = block.source {
+                    writeln!(f, "{}", Paint::cyan(format!("{sign} // {source}")))?;
+                }
+                for instruction in block.instructions.iter() {
+                    let line = format!("{sign} {:#06x} {}", instruction.pc, instruction.opcode);
+                    if sign == "-" {
+                        writeln!(f, "{}", Paint::red(line))?;
+                    } else {
+                        writeln!(f, "{}", Paint::green(line))?;
+                    }
+                }
+            }
+            Ok(())
+        };
+        for hunk in self.hunks.iter() {
+            let pc = |blocks: &[Block]| {
+                blocks.first().map(|block| format!("{:#06x}", block.pc)).unwrap_or_default()
+            };
+            writeln!(f, "\n@@ -{} +{} @@", pc(&hunk.removed), pc(&hunk.added))?;
+            write_blocks(f, &hunk.removed, "-")?;
+            write_blocks(f, &hunk.added, "+")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads and disassembles the bytecode of an artifact
+///
+/// Blocks are annotated with the source lines of the contract's own source file if the artifact
+/// has a source map and the file exists relative to the current directory.
+fn read_artifact(path: &Path, creation: bool) -> eyre::Result<Disassembly> {
+    let artifact: Value = fs::read_json_file(path)?;
+    let bytecode = &artifact[if creation { "bytecode" } else { "deployedBytecode" }];
+    let object = bytecode["object"]
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("No bytecode found in {}", path.display()))?;
+    let code = decode_object(object)?;
+
+    let source_map = match bytecode["sourceMap"].as_str() {
+        Some(map) if !map.is_empty() => Some(sourcemap::parse(map).map_err(|err| {
+            eyre::eyre!("Failed to parse the source map of {}: {err}", path.display())
+        })?),
+        _ => None,
+    };
+    let source_id = artifact["id"].as_u64();
+    let source_path = artifact["ast"]["absolutePath"].as_str();
+    let source = source_path.and_then(|path| std::fs::read_to_string(path).ok());
+
+    Ok(Disassembly::new(&code, |ic| {
+        let element = source_map.as_ref()?.get(ic)?;
+        let index = element.index?;
+        match (source_path, source.as_ref()) {
+            (Some(path), Some(source)) if Some(index as u64) == source_id => {
+                let line = source.get(..element.offset)?.matches('\n').count() + 1;
+                Some(format!("{path}:{line}"))
+            }
+            _ => Some(format!("source {index}, offset {}", element.offset)),
+        }
+    }))
+}
+
+/// Decodes the bytecode object of an artifact, with unlinked libraries replaced by zeros
+fn decode_object(object: &str) -> eyre::Result<Vec<u8>> {
+    let mut object = object.strip_prefix("0x").unwrap_or(object).to_string();
+    // library placeholders are 40 characters long, like `__$<34 hex characters>$__`
+    while let Some(start) = object.find("__") {
+        let end = (start + 40).min(object.len());
+        object.replace_range(start..end, &"0".repeat(end - start));
+    }
+    Ok(hex::decode(object)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_moved_blocks() {
+        // PUSH1 0x05, JUMP, STOP, STOP, JUMPDEST, PUSH1 0x01, STOP
+        let old = Disassembly::new(
+            &[0x60, 0x05, 0x56, 0x00, 0x00, 0x5b, 0x60, 0x01, 0x00],
+            |ic| (ic == 4).then(|| "src/A.sol:3".to_string()),
+        );
+        assert_eq!(old.blocks.len(), 2);
+        assert_eq!(old.blocks[1].source.as_deref(), Some("src/A.sol:3"));
+
+        // an extra STOP moves the second block, which still compares equal
+        let new = Disassembly::new(
+            &[0x60, 0x06, 0x56, 0x00, 0x00, 0x00, 0x5b, 0x60, 0x01, 0x00],
+            |_| None,
+        );
+        let diff = BytecodeDiff::new(&old, &new);
+        assert_eq!(diff.unchanged_blocks, 1);
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].removed[0].pc, 0);
+        assert_eq!(diff.hunks[0].added[0].pc, 0);
+
+        let new =
+            Disassembly::new(&[0x60, 0x05, 0x56, 0x00, 0x00, 0x5b, 0x60, 0x02, 0x00], |_| None);
+        let diff = BytecodeDiff::new(&old, &new);
+        assert_eq!(diff.unchanged_blocks, 1);
+        assert_eq!(diff.hunks[0].removed[0].pc, 5);
+        assert_eq!(diff.hunks[0].added[0].instructions[1].opcode, "PUSH1 0x02");
+    }
+}
@@ -0,0 +1,418 @@
+//! export-deployments command
+
+use crate::cmd::{forge::build::ProjectPathsArgs, Cmd, LoadConfig};
+use cast::SimpleCast;
+use clap::{Parser, ValueEnum, ValueHint};
+use ethers::types::{Address, Chain, TxHash, U64};
+use eyre::WrapErr;
+use forge::CallKind;
+use foundry_common::fs;
+use foundry_config::Config;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+foundry_config::impl_figment_convert!(ExportDeploymentsArgs, opts);
+
+/// The directory deployments are exported to by default, relative to the project root
+pub const DEFAULT_EXPORT_DIR: &str = "deployments";
+
+/// The formats deployments can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// A `<network>/<Contract>.json` file per deployment, as written by `hardhat-deploy`
+    HardhatDeploy,
+    /// A `wagmi.ts` file with the ABI and the addresses by chain id of every contract
+    Wagmi,
+    /// A `viem.ts` file with the ABI of every contract and the deployments by chain id
+    Viem,
+    /// An `addresses.json` file with the addresses of all contracts by chain id, to connect
+    /// typechain factories
+    Typechain,
+}
+
+/// CLI arguments for `forge export-deployments`.
+#[derive(Debug, Clone, Parser)]
+pub struct ExportDeploymentsArgs {
+    #[clap(
+        long,
+        help = "The formats to export the deployments in.",
+        value_enum,
+        default_value = "hardhat-deploy",
+        num_args(1..)
+    )]
+    pub export: Vec<ExportFormat>,
+
+    #[clap(
+        long,
+        help = "The directory to export the deployments to. Defaults to `deployments` in the project root.",
+        value_hint = ValueHint::DirPath,
+        value_name = "PATH"
+    )]
+    pub export_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub opts: ProjectPathsArgs,
+}
+
+impl Cmd for ExportDeploymentsArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let config = self.try_load_config_emit_warnings()?;
+        let out = self.export_dir.unwrap_or_else(|| config.__root.0.join(DEFAULT_EXPORT_DIR));
+        export_deployments(&config, &self.export, &out)
+    }
+}
+
+/// A contract created by a broadcasted script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub name: String,
+    pub chain: u64,
+    pub address: Address,
+    pub transaction_hash: Option<TxHash>,
+    pub block_number: Option<U64>,
+    /// The constructor arguments
+    pub args: Vec<String>,
+}
+
+/// The parts of a `broadcast/<script>/<chain>/<sig>-latest.json` file relevant for exports
+#[derive(Deserialize)]
+struct BroadcastRun {
+    transactions: Vec<BroadcastTransaction>,
+    #[serde(default)]
+    receipts: Vec<BroadcastReceipt>,
+    timestamp: u64,
+    chain: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastTransaction {
+    hash: Option<TxHash>,
+    #[serde(rename = "transactionType")]
+    opcode: CallKind,
+    contract_name: Option<String>,
+    contract_address: Option<Address>,
+    arguments: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BroadcastReceipt {
+    transaction_hash: TxHash,
+    block_number: Option<U64>,
+    status: Option<U64>,
+}
+
+/// Reads all broadcasted runs and exports the latest deployment of every contract on every chain
+pub fn export_deployments(
+    config: &Config,
+    formats: &[ExportFormat],
+    out: &Path,
+) -> eyre::Result<()> {
+    let deployments = load_deployments(&config.broadcast)?;
+    if deployments.is_empty() {
+        println!("No deployments found in {}", config.broadcast.display());
+        return Ok(())
+    }
+    let artifacts = config.project_paths().artifacts;
+    fs::create_dir_all(out)?;
+
+    for format in formats {
+        match format {
+            ExportFormat::HardhatDeploy => export_hardhat_deploy(&deployments, &artifacts, out)?,
+            ExportFormat::Wagmi => {
+                fs::write(out.join("wagmi.ts"), wagmi_module(&deployments, &artifacts)?)?
+            }
+            ExportFormat::Viem => {
+                fs::write(out.join("viem.ts"), viem_module(&deployments, &artifacts)?)?
+            }
+            ExportFormat::Typechain => {
+                fs::write_json_file(&out.join("addresses.json"), &address_map(&deployments))?
+            }
+        }
+    }
+    println!("Deployments exported to: {}", out.display());
+    Ok(())
+}
+
+/// Returns the latest successful deployment of every contract, by chain id and contract name
+pub fn load_deployments(
+    broadcast: &Path,
+) -> eyre::Result<BTreeMap<u64, BTreeMap<String, Deployment>>> {
+    let mut runs = Vec::new();
+    for entry in walkdir::WalkDir::new(broadcast).into_iter().filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        // dry runs were never broadcasted, and multi chain runs are also stored per chain
+        let is_latest_run = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.ends_with("-latest.json"));
+        if !is_latest_run || path.components().any(|c| c.as_os_str() == "dry-run") {
+            continue
+        }
+        let run: BroadcastRun = fs::read_json_file(path)
+            .wrap_err_with(|| format!("Failed to read broadcast file {}", path.display()))?;
+        runs.push(run);
+    }
+    // later runs override the deployments of earlier runs
+    runs.sort_by_key(|run| run.timestamp);
+
+    let mut deployments: BTreeMap<u64, BTreeMap<String, Deployment>> = BTreeMap::new();
+    for run in runs {
+        for tx in run.transactions {
+            if !matches!(tx.opcode, CallKind::Create | CallKind::Create2) {
+                continue
+            }
+            let (name, address, hash) = match (tx.contract_name, tx.contract_address, tx.hash) {
+                (Some(name), Some(address), Some(hash)) if !name.is_empty() => {
+                    (name, address, hash)
+                }
+                _ => continue,
+            };
+            let receipt = match run.receipts.iter().find(|r| r.transaction_hash == hash) {
+                Some(receipt) if receipt.status != Some(U64::zero()) => receipt,
+                // not mined or failed
+                _ => continue,
+            };
+            deployments.entry(run.chain).or_default().insert(
+                name.clone(),
+                Deployment {
+                    name,
+                    chain: run.chain,
+                    address,
+                    transaction_hash: Some(hash),
+                    block_number: receipt.block_number,
+                    args: tx.arguments.unwrap_or_default(),
+                },
+            );
+        }
+    }
+    Ok(deployments)
+}
+
+/// The parts of an artifact included in exports
+#[derive(Debug, Default)]
+struct ExportedArtifact {
+    abi: Value,
+    bytecode: Option<Value>,
+    deployed_bytecode: Option<Value>,
+}
+
+/// Finds the artifact of the contract in `<artifacts>/<file>/<name>.json`
+fn find_artifact(artifacts: &Path, name: &str) -> eyre::Result<ExportedArtifact> {
+    let file_name = format!("{name}.json");
+    let path = walkdir::WalkDir::new(artifacts)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .find(|entry| entry.file_name().to_str() == Some(file_name.as_str()))
+        .map(|entry| entry.into_path());
+    let path = match path {
+        Some(path) => path,
+        None => {
+            eprintln!("Warning: No artifact found for {name}, exporting it without ABI.");
+            return Ok(ExportedArtifact { abi: json!([]), ..Default::default() })
+        }
+    };
+    let artifact: Value = fs::read_json_file(&path)?;
+    Ok(ExportedArtifact {
+        abi: artifact.get("abi").cloned().unwrap_or_else(|| json!([])),
+        bytecode: artifact.pointer("/bytecode/object").cloned(),
+        deployed_bytecode: artifact.pointer("/deployedBytecode/object").cloned(),
+    })
+}
+
+/// Writes `<out>/<network>/.chainId` and a `<Contract>.json` file per deployment
+fn export_hardhat_deploy(
+    deployments: &BTreeMap<u64, BTreeMap<String, Deployment>>,
+    artifacts: &Path,
+    out: &Path,
+) -> eyre::Result<()> {
+    for (chain, contracts) in deployments {
+        let dir = out.join(network_name(*chain));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(".chainId"), chain.to_string())?;
+        for (name, deployment) in contracts {
+            let artifact = find_artifact(artifacts, name)?;
+            let mut file = json!({
+                "address": SimpleCast::to_checksum_address(&deployment.address),
+                "abi": artifact.abi,
+                "transactionHash": deployment.transaction_hash,
+                "args": deployment.args,
+            });
+            if let Some(block_number) = deployment.block_number {
+                file["receipt"] = json!({ "blockNumber": block_number.as_u64() });
+            }
+            if let Some(bytecode) = artifact.bytecode {
+                file["bytecode"] = bytecode;
+            }
+            if let Some(deployed_bytecode) = artifact.deployed_bytecode {
+                file["deployedBytecode"] = deployed_bytecode;
+            }
+            fs::write_json_file(&dir.join(format!("{name}.json")), &file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the addresses of all contracts by chain id
+fn address_map(
+    deployments: &BTreeMap<u64, BTreeMap<String, Deployment>>,
+) -> BTreeMap<u64, BTreeMap<String, String>> {
+    deployments
+        .iter()
+        .map(|(chain, contracts)| {
+            let addresses = contracts
+                .iter()
+                .map(|(name, d)| (name.clone(), SimpleCast::to_checksum_address(&d.address)))
+                .collect();
+            (*chain, addresses)
+        })
+        .collect()
+}
+
+/// Returns the deployments of every contract name, by chain id
+fn by_contract(
+    deployments: &BTreeMap<u64, BTreeMap<String, Deployment>>,
+) -> BTreeMap<&str, Vec<&Deployment>> {
+    let mut contracts: BTreeMap<&str, Vec<&Deployment>> = BTreeMap::new();
+    for deployment in deployments.values().flat_map(|contracts| contracts.values()) {
+        contracts.entry(deployment.name.as_str()).or_default().push(deployment);
+    }
+    contracts
+}
+
+/// Returns a `const` declaration of the contract's ABI
+fn abi_declaration(name: &str, artifacts: &Path) -> eyre::Result<String> {
+    let abi = serde_json::to_string_pretty(&find_artifact(artifacts, name)?.abi)?;
+    Ok(format!("export const {}Abi = {abi} as const\n", identifier(name)))
+}
+
+/// Returns a module in the format of the `wagmi` CLI, with `<contract>Abi`, `<contract>Address`
+/// and `<contract>Config` for every contract
+fn wagmi_module(
+    deployments: &BTreeMap<u64, BTreeMap<String, Deployment>>,
+    artifacts: &Path,
+) -> eyre::Result<String> {
+    let mut module = String::from("// Generated by `forge export-deployments`, do not edit\n");
+    for (name, deployments) in by_contract(deployments) {
+        let ident = identifier(name);
+        writeln!(module)?;
+        module.push_str(&abi_declaration(name, artifacts)?);
+        writeln!(module, "\nexport const {ident}Address = {{")?;
+        for deployment in deployments {
+            writeln!(
+                module,
+                "  {}: '{}',",
+                deployment.chain,
+                SimpleCast::to_checksum_address(&deployment.address)
+            )?;
+        }
+        writeln!(module, "}} as const")?;
+        writeln!(
+            module,
+            "\nexport const {ident}Config = {{ address: {ident}Address, abi: {ident}Abi }} as const"
+        )?;
+    }
+    Ok(module)
+}
+
+/// Returns a module with `<contract>Abi` for every contract and all deployments by chain id, to
+/// be passed to viem's `getContract`
+fn viem_module(
+    deployments: &BTreeMap<u64, BTreeMap<String, Deployment>>,
+    artifacts: &Path,
+) -> eyre::Result<String> {
+    let mut module = String::from("// Generated by `forge export-deployments`, do not edit\n");
+    for name in by_contract(deployments).keys() {
+        writeln!(module)?;
+        module.push_str(&abi_declaration(name, artifacts)?);
+    }
+    writeln!(module, "\nexport const deployments = {{")?;
+    for (chain, contracts) in deployments {
+        writeln!(module, "  {chain}: {{")?;
+        for (name, deployment) in contracts {
+            writeln!(
+                module,
+                "    {name}: {{ address: '{}', abi: {}Abi }},",
+                SimpleCast::to_checksum_address(&deployment.address),
+                identifier(name)
+            )?;
+        }
+        writeln!(module, "  }},")?;
+    }
+    writeln!(module, "}} as const")?;
+    Ok(module)
+}
+
+/// Returns the name of the network directory of `hardhat-deploy`
+fn network_name(chain: u64) -> String {
+    Chain::try_from(chain).map(|chain| chain.to_string()).unwrap_or_else(|_| chain.to_string())
+}
+
+/// Converts the contract name into a camelCase identifier, `ERC20Token` becomes `erc20Token`
+fn identifier(name: &str) -> String {
+    let upper = name.chars().take_while(|c| c.is_ascii_uppercase() || c.is_ascii_digit()).count();
+    // keep the first letter of the next word uppercase, unless the name is all uppercase
+    let lower = if upper > 1 && upper < name.len() { upper - 1 } else { upper.max(1) };
+    let (head, tail) = name.split_at(lower.min(name.len()));
+    format!("{}{tail}", head.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_convert_identifiers() {
+        assert_eq!(identifier("Counter"), "counter");
+        assert_eq!(identifier("ERC20Token"), "erc20Token");
+        assert_eq!(identifier("WETH"), "weth");
+        assert_eq!(identifier("myToken"), "myToken");
+    }
+
+    #[test]
+    fn can_load_deployments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("Deploy.s.sol").join("1");
+        std::fs::create_dir_all(dir.join("dry-run")).unwrap();
+        let hash = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let run = |timestamp: u64, address: &str| {
+            json!({
+                "transactions": [{
+                    "hash": hash,
+                    "transactionType": "CREATE",
+                    "contractName": "Counter",
+                    "contractAddress": address,
+                    "arguments": ["1"],
+                }],
+                "receipts": [{ "transactionHash": hash, "blockNumber": "0x10", "status": "0x1" }],
+                "timestamp": timestamp,
+                "chain": 1,
+            })
+        };
+        let old = "0x5fbdb2315678afecb367f032d93f642f64180aa3";
+        let new = "0xe7f1725e7734ce288f8367e1bb143e90bb3f0512";
+        fs::write_json_file(&dir.join("run-latest.json"), &run(2, new)).unwrap();
+        fs::write_json_file(&dir.join("other-latest.json"), &run(1, old)).unwrap();
+        fs::write_json_file(&dir.join("dry-run").join("run-latest.json"), &run(3, old)).unwrap();
+
+        let deployments = load_deployments(tmp.path()).unwrap();
+        let counter = &deployments[&1]["Counter"];
+        assert_eq!(counter.address, new.parse().unwrap());
+        assert_eq!(counter.block_number, Some(16u64.into()));
+        assert_eq!(counter.args, vec!["1".to_string()]);
+        assert_eq!(
+            address_map(&deployments)[&1]["Counter"],
+            SimpleCast::to_checksum_address(&counter.address)
+        );
+    }
+}
@@ -1,10 +1,36 @@
 use crate::cmd::{
-    forge::build::{CoreBuildArgs, ProjectPathsArgs},
+    forge::{
+        build::{CoreBuildArgs, ProjectPathsArgs},
+        lsp::resolve_import,
+        test::GlobMatcher,
+    },
     Cmd, LoadConfig,
 };
 use clap::{Parser, ValueHint};
+use ethers::solc::{
+    artifacts::{Source, Sources},
+    remappings::Remapping,
+    AggregatedCompilerOutput, CompilerInput, Graph, Solc,
+};
 use foundry_common::fs;
-use std::path::PathBuf;
+use foundry_config::{Config, SolcReq};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+use yansi::Paint;
+
+static RE_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^[ \t]*import\b[^;]*?["']([^"']+)["'][^;]*;[ \t]*\n?"#).unwrap()
+});
+
+static RE_LICENSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*//\s*SPDX-License-Identifier:\s*(.+?)\s*$").unwrap());
+
+static RE_PRAGMA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*pragma\s+([^;]+?)\s*;\s*$").unwrap());
 
 /// CLI arguments for `forge flatten`.
 #[derive(Debug, Clone, Parser)]
@@ -22,6 +48,27 @@ pub struct FlattenArgs {
     )]
     pub output: Option<PathBuf>,
 
+    #[clap(
+        long,
+        help = "Keep the imports of files matching the glob instead of inlining them.",
+        long_help = "Keep the imports of files matching the glob instead of inlining them, e.g. `lib/openzeppelin-contracts/**`. Relative imports of these files are rewritten relative to the project root.",
+        value_name = "GLOB"
+    )]
+    pub preserve_imports_of: Vec<GlobMatcher>,
+
+    #[clap(
+        long,
+        help = "Compile the flattened contract before writing it and fail if it doesn't compile."
+    )]
+    pub verify_compiles: bool,
+
+    #[clap(
+        long,
+        help = "Declare the license identifiers and pragmas of all flattened files once, at the top.",
+        long_help = "Declare the license identifiers and pragmas of all flattened files once, at the top. Different licenses are combined with `AND` and ABI coder v1 is dropped if another file requires v2."
+    )]
+    pub dedup_license_and_pragmas: bool,
+
     #[clap(flatten)]
     project_paths: ProjectPathsArgs,
 }
@@ -29,7 +76,14 @@ pub struct FlattenArgs {
 impl Cmd for FlattenArgs {
     type Output = ();
     fn run(self) -> eyre::Result<Self::Output> {
-        let FlattenArgs { target_path, output, project_paths } = self;
+        let FlattenArgs {
+            target_path,
+            output,
+            preserve_imports_of,
+            verify_compiles,
+            dedup_license_and_pragmas: dedup,
+            project_paths,
+        } = self;
 
         // flatten is a subset of `BuildArgs` so we can reuse that to get the config
         let build_args = CoreBuildArgs {
@@ -42,21 +96,42 @@ impl Cmd for FlattenArgs {
             use_solc: None,
             offline: false,
             force: false,
+            shared_artifacts: false,
             libraries: vec![],
             via_ir: false,
             revert_strings: None,
             silent: false,
             build_info: false,
             build_info_path: None,
+            from_standard_json: None,
         };
 
         let config = build_args.try_load_config_emit_warnings()?;
 
         let paths = config.project_paths();
         let target_path = dunce::canonicalize(target_path)?;
-        let flattened = paths
-            .flatten(&target_path)
-            .map_err(|err| eyre::Error::msg(format!("Failed to flatten the file: {err}")))?;
+        let flattened = if preserve_imports_of.is_empty() {
+            paths
+                .flatten(&target_path)
+                .map_err(|err| eyre::Error::msg(format!("Failed to flatten the file: {err}")))?
+        } else {
+            Flattener::new(&paths.root, &config.get_all_remappings(), &preserve_imports_of)
+                .flatten(&target_path)?
+        };
+
+        let flattened = if dedup {
+            let (flattened, resolutions) = dedup_license_and_pragmas(&flattened);
+            for resolution in resolutions {
+                eprintln!("{}", Paint::yellow(resolution));
+            }
+            flattened
+        } else {
+            flattened
+        };
+
+        if verify_compiles {
+            check_compiles(&config, &flattened)?;
+        }
 
         match output {
             Some(output) => {
@@ -70,3 +145,267 @@ impl Cmd for FlattenArgs {
         Ok(())
     }
 }
+
+/// Flattens a file, but keeps the imports of all files matching the `preserve` globs
+///
+/// The files imported by a preserved file are preserved as well, since inlining them would
+/// declare their contents twice.
+struct Flattener<'a> {
+    root: &'a Path,
+    remappings: &'a [Remapping],
+    preserve: &'a [GlobMatcher],
+    /// The preserved files and all files they import
+    preserved: HashSet<PathBuf>,
+    /// All files that were visited
+    visited: HashSet<PathBuf>,
+    /// The import statements of preserved files, in order
+    imports: Vec<String>,
+    /// The inlined files without their imports, dependencies first
+    sources: Vec<(PathBuf, String)>,
+}
+
+impl<'a> Flattener<'a> {
+    fn new(root: &'a Path, remappings: &'a [Remapping], preserve: &'a [GlobMatcher]) -> Self {
+        Self {
+            root,
+            remappings,
+            preserve,
+            preserved: Default::default(),
+            visited: Default::default(),
+            imports: vec![],
+            sources: vec![],
+        }
+    }
+
+    fn flatten(mut self, target: &Path) -> eyre::Result<String> {
+        self.collect_preserved(target, false, &mut HashSet::new())?;
+        self.visit(target)?;
+
+        let mut flattened = String::new();
+        for import in &self.imports {
+            flattened.push_str(import);
+            flattened.push('\n');
+        }
+        for (path, source) in &self.sources {
+            let path = path.strip_prefix(self.root).unwrap_or(path);
+            flattened.push_str(&format!("\n// {}\n", path.display()));
+            flattened.push_str(source.trim());
+            flattened.push('\n');
+        }
+        Ok(flattened)
+    }
+
+    fn is_preserved(&self, path: &Path) -> bool {
+        let path = path.strip_prefix(self.root).unwrap_or(path).to_string_lossy();
+        self.preserve.iter().any(|glob| glob.is_match(&path))
+    }
+
+    /// Resolves an import of the file
+    fn resolve(&self, path: &Path, import: &str) -> eyre::Result<PathBuf> {
+        let resolved = resolve_import(self.root, path, import, self.remappings);
+        dunce::canonicalize(&resolved).map_err(|err| {
+            eyre::eyre!("Failed to resolve import `{import}` of {}: {err}", path.display())
+        })
+    }
+
+    /// Collects the preserved files in the import graph of the file, and all files imported by
+    /// them, `in_preserved` is set if the file itself is imported by a preserved file
+    fn collect_preserved(
+        &mut self,
+        path: &Path,
+        in_preserved: bool,
+        seen: &mut HashSet<(PathBuf, bool)>,
+    ) -> eyre::Result<()> {
+        if !seen.insert((path.to_path_buf(), in_preserved)) {
+            return Ok(())
+        }
+        let source = fs::read_to_string(path)?;
+        for cap in RE_IMPORT.captures_iter(&source) {
+            let resolved = self.resolve(path, &cap[1])?;
+            let preserved = in_preserved || self.is_preserved(&resolved);
+            if preserved {
+                self.preserved.insert(resolved.clone());
+            }
+            self.collect_preserved(&resolved, preserved, seen)?;
+        }
+        Ok(())
+    }
+
+    /// Inlines the file after all files it imports
+    fn visit(&mut self, path: &Path) -> eyre::Result<()> {
+        if !self.visited.insert(path.to_path_buf()) {
+            return Ok(())
+        }
+        let source = fs::read_to_string(path)?;
+
+        let mut stripped = String::with_capacity(source.len());
+        let mut last = 0;
+        for cap in RE_IMPORT.captures_iter(&source) {
+            let (statement, import) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+            stripped.push_str(&source[last..statement.start()]);
+            last = statement.end();
+
+            let resolved = self.resolve(path, import.as_str())?;
+            if self.preserved.contains(&resolved) {
+                // relative imports are only valid next to the file that is flattened
+                let import_path = if import.as_str().starts_with('.') {
+                    resolved.strip_prefix(self.root).unwrap_or(&resolved).display().to_string()
+                } else {
+                    import.as_str().to_string()
+                };
+                let statement = format!(
+                    "{}{import_path}{}",
+                    &source[statement.start()..import.start()],
+                    &source[import.end()..statement.end()]
+                );
+                let statement = statement.trim().to_string();
+                if !self.imports.contains(&statement) {
+                    self.imports.push(statement);
+                }
+            } else {
+                self.visit(&resolved)?;
+            }
+        }
+        stripped.push_str(&source[last..]);
+
+        self.sources.push((path.to_path_buf(), stripped));
+        Ok(())
+    }
+}
+
+/// Moves the license identifiers and pragmas of all flattened files to the top, so every one is
+/// declared once
+///
+/// Multiple license identifiers are not allowed in a single file, different licenses are combined
+/// with `AND`. Returns the flattened source and a description of every conflict that was resolved.
+fn dedup_license_and_pragmas(flattened: &str) -> (String, Vec<String>) {
+    let mut licenses: Vec<String> = vec![];
+    let mut pragmas: Vec<String> = vec![];
+    let mut body = String::with_capacity(flattened.len());
+    for line in flattened.lines() {
+        if let Some(cap) = RE_LICENSE.captures(line) {
+            let license = cap[1].trim_end_matches("*/").trim().to_string();
+            if !licenses.contains(&license) {
+                licenses.push(license);
+            }
+        } else if let Some(cap) = RE_PRAGMA.captures(line) {
+            let pragma = cap[1].split_whitespace().collect::<Vec<_>>().join(" ");
+            if !pragmas.contains(&pragma) {
+                pragmas.push(pragma);
+            }
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    let mut resolutions = vec![];
+    let mut header = String::new();
+    if !licenses.is_empty() {
+        let license = licenses.join(" AND ");
+        if licenses.len() > 1 {
+            resolutions.push(format!(
+                "Flattened files have different licenses, they are combined as `{license}`"
+            ));
+        }
+        header.push_str(&format!("// SPDX-License-Identifier: {license}\n"));
+    }
+
+    let versions = pragmas.iter().filter(|pragma| pragma.starts_with("solidity")).count();
+    if versions > 1 {
+        resolutions.push(
+            "Flattened files have different version pragmas, the flattened file requires all of \
+             them"
+                .to_string(),
+        );
+    }
+    // ABI coder v1 and v2 can't be mixed in a single file, but v2 can call code compiled for v1
+    if pragmas.iter().any(|pragma| pragma == "abicoder v2" || pragma == "experimental ABIEncoderV2")
+    {
+        if let Some(idx) = pragmas.iter().position(|pragma| pragma == "abicoder v1") {
+            pragmas.remove(idx);
+            resolutions
+                .push("Flattened files use ABI coder v1 and v2, v2 is used for all".to_string());
+        }
+    }
+    for pragma in pragmas {
+        header.push_str(&format!("pragma {pragma};\n"));
+    }
+
+    (format!("{header}\n{}", body.trim_start()), resolutions)
+}
+
+/// Compiles the flattened source with the configured solc, or the version its pragmas require
+fn check_compiles(config: &Config, flattened: &str) -> eyre::Result<()> {
+    let paths = config.project_paths();
+    let source = Source::new(flattened);
+    let solc = match &config.solc {
+        Some(SolcReq::Local(solc)) => Solc::new(solc),
+        Some(SolcReq::Version(version)) => Solc::find_or_install_svm_version(version.to_string())?,
+        None => Solc::find_or_install_svm_version(Solc::detect_version(&source)?.to_string())?,
+    };
+
+    // the imports that were preserved are resolved from the project
+    let sources = Sources::from([(paths.root.join("Flattened.sol"), source)]);
+    let sources = Graph::resolve_sources(&paths, sources)?.into_sources().0;
+    let input = CompilerInput::with_sources(sources)
+        .pop()
+        .unwrap()
+        .with_remappings(paths.remappings)
+        .with_base_path(&paths.root);
+
+    let out = solc.compile_exact(&input)?;
+    if out.has_error() {
+        let mut output = AggregatedCompilerOutput::default();
+        output.extend(solc.version()?, out);
+        eyre::bail!(
+            "The flattened contract does not compile:\n{}",
+            output.diagnostics(&[], Default::default())
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_dedup_license_and_pragmas() {
+        let flattened = r#"
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+pragma abicoder v1;
+
+contract A {}
+
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.8.4;
+pragma abicoder v2;
+
+contract B is A {}
+
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+contract C {}
+"#;
+        let (source, resolutions) = dedup_license_and_pragmas(flattened);
+        assert_eq!(
+            source,
+            r#"// SPDX-License-Identifier: MIT AND GPL-3.0
+pragma solidity ^0.8.0;
+pragma solidity >=0.8.4;
+pragma abicoder v2;
+
+contract A {}
+
+
+contract B is A {}
+
+contract C {}
+"#
+        );
+        assert_eq!(resolutions.len(), 3);
+        assert!(resolutions[0].contains("`MIT AND GPL-3.0`"));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    cmd::forge::build::{CoreBuildArgs, ProjectPathsArgs},
+    cmd::{
+        forge::build::{CoreBuildArgs, ProjectPathsArgs},
+        LoadConfig,
+    },
     opts::forge::CompilerArgs,
     utils::FoundryPathExt,
 };
@@ -44,8 +47,9 @@ impl UploadSelectorsArgs {
             ..Default::default()
         };
 
-        let project = build_args.project()?;
-        let outcome = compile::suppress_compile(&project)?;
+        let config = build_args.try_load_config_emit_warnings()?;
+        let project = config.project()?;
+        let outcome = compile::suppress_compile(&project, &config)?;
         let artifacts = if all {
             outcome
                 .into_artifacts_with_files()
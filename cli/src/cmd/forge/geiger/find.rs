@@ -11,10 +11,19 @@ use std::{
 use yansi::Paint;
 
 /// Scan a single file for `unsafe` usage.
-pub fn find_cheatcodes_in_file(path: &Path) -> Result<SolFileMetrics, ScanFileError> {
+///
+/// `startBroadcast` is only considered unsafe in dependencies, since the project's own scripts are
+/// expected to broadcast.
+pub fn find_cheatcodes_in_file(
+    path: &Path,
+    is_dependency: bool,
+) -> Result<SolFileMetrics, ScanFileError> {
     let content = fs::read_to_string(path)?;
-    let cheatcodes = find_cheatcodes_in_string(&content)
+    let mut cheatcodes = find_cheatcodes_in_string(&content)
         .map_err(|diagnostic| ScanFileError::ParseSol(diagnostic, path.to_path_buf()))?;
+    if !is_dependency {
+        cheatcodes.start_broadcast.clear();
+    }
     Ok(SolFileMetrics { content, cheatcodes, file: path.to_path_buf() })
 }
 
@@ -81,13 +90,17 @@ impl<'a, 'b> fmt::Display for SolFileMetricsPrinter<'a, 'b> {
             print_unsafe_fn!(
                 "ffi" => ffi,
                 "readFile" => read_file,
+                "readFileBinary" => read_file_binary,
                 "readLine" => read_line,
                 "writeFile" => write_file,
+                "writeFileBinary" => write_file_binary,
+                "writeJson" => write_json,
                 "writeLine" => write_line,
                 "removeFile" => remove_file,
                 "closeFile" => close_file,
                 "setEnv" => set_env,
-                "deriveKey" => derive_key
+                "deriveKey" => derive_key,
+                "startBroadcast" => start_broadcast
             );
         } else {
             writeln!(f, "0    {}", file.display())?
@@ -102,38 +115,38 @@ impl<'a, 'b> fmt::Display for SolFileMetricsPrinter<'a, 'b> {
 pub struct CheatcodeCounter {
     pub ffi: Vec<Loc>,
     pub read_file: Vec<Loc>,
+    pub read_file_binary: Vec<Loc>,
     pub read_line: Vec<Loc>,
     pub write_file: Vec<Loc>,
+    pub write_file_binary: Vec<Loc>,
+    pub write_json: Vec<Loc>,
     pub write_line: Vec<Loc>,
     pub remove_file: Vec<Loc>,
     pub close_file: Vec<Loc>,
     pub set_env: Vec<Loc>,
     pub derive_key: Vec<Loc>,
+    pub start_broadcast: Vec<Loc>,
 }
 
 impl CheatcodeCounter {
     pub fn has_unsafe(&self) -> bool {
-        !self.ffi.is_empty() ||
-            !self.read_file.is_empty() ||
-            !self.read_line.is_empty() ||
-            !self.write_file.is_empty() ||
-            !self.write_line.is_empty() ||
-            !self.close_file.is_empty() ||
-            !self.set_env.is_empty() ||
-            !self.derive_key.is_empty() ||
-            !self.remove_file.is_empty()
+        self.count() > 0
     }
 
     pub fn count(&self) -> usize {
         self.ffi.len() +
             self.read_file.len() +
+            self.read_file_binary.len() +
             self.read_line.len() +
             self.write_file.len() +
+            self.write_file_binary.len() +
+            self.write_json.len() +
             self.write_line.len() +
             self.close_file.len() +
             self.set_env.len() +
             self.derive_key.len() +
-            self.remove_file.len()
+            self.remove_file.len() +
+            self.start_broadcast.len()
     }
 }
 
@@ -172,4 +185,23 @@ mod tests {
         assert_eq!(count.ffi.len(), 1);
         assert!(count.has_unsafe());
     }
+
+    #[test]
+    fn can_find_file_writes_and_broadcasts() {
+        let s = r#"
+        contract A is Script {
+            function run() public {
+                vm.writeJson("{}", "./out.json");
+                vm.writeFileBinary("./out.bin", hex"01");
+                vm.startBroadcast();
+            }
+        }
+        "#;
+
+        let count = find_cheatcodes_in_string(s).unwrap();
+        assert_eq!(count.write_json.len(), 1);
+        assert_eq!(count.write_file_binary.len(), 1);
+        assert_eq!(count.start_broadcast.len(), 1);
+        assert_eq!(count.count(), 3);
+    }
 }
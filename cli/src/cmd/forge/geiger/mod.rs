@@ -98,11 +98,26 @@ impl Cmd for GeigerArgs {
         }
 
         let root = config.__root.0;
+        let cwd = std::env::current_dir()?;
+        let libs = config.libs.iter().map(|lib| root.join(lib)).collect::<Vec<_>>();
+
+        let results = sources
+            .par_iter()
+            .map(|file| {
+                let abs_path = if file.is_absolute() { file.clone() } else { cwd.join(file) };
+                let is_dependency = libs.iter().any(|lib| abs_path.starts_with(lib));
+                find_cheatcodes_in_file(file, is_dependency)
+            })
+            .collect::<Vec<_>>();
 
-        sources.par_iter().map(|file| find_cheatcodes_in_file(file)).for_each(|res| {
+        let mut unsafe_files = 0;
+        for res in results {
             match res {
                 Ok(metrics) => {
                     let printer = SolFileMetricsPrinter { metrics: &metrics, root: &root };
+                    if printer.metrics.cheatcodes.has_unsafe() {
+                        unsafe_files += 1;
+                    }
                     if self.full || printer.metrics.cheatcodes.has_unsafe() {
                         eprint!("{printer}");
                     }
@@ -111,7 +126,15 @@ impl Cmd for GeigerArgs {
                     eprintln!("{err}");
                 }
             };
-        });
+        }
+
+        if self.check && unsafe_files > 0 {
+            eprintln!(
+                "{}",
+                Paint::red(format!("Found unsafe cheatcodes in {unsafe_files} file(s)"))
+            );
+            std::process::exit(1);
+        }
 
         Ok(())
     }
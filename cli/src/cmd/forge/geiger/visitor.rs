@@ -66,13 +66,17 @@ impl Visitor for CheatcodeVisitor {
                         match identifier.name.as_str() {
                             "ffi" => self.cheatcodes.ffi.push(*loc),
                             "readFile" => self.cheatcodes.read_file.push(*loc),
+                            "readFileBinary" => self.cheatcodes.read_file_binary.push(*loc),
                             "writeFile" => self.cheatcodes.write_file.push(*loc),
+                            "writeFileBinary" => self.cheatcodes.write_file_binary.push(*loc),
+                            "writeJson" => self.cheatcodes.write_json.push(*loc),
                             "readLine" => self.cheatcodes.read_line.push(*loc),
                             "writeLine" => self.cheatcodes.write_line.push(*loc),
                             "closeFile" => self.cheatcodes.close_file.push(*loc),
                             "removeFile" => self.cheatcodes.remove_file.push(*loc),
                             "setEnv" => self.cheatcodes.set_env.push(*loc),
                             "deriveKey" => self.cheatcodes.derive_key.push(*loc),
+                            "startBroadcast" => self.cheatcodes.start_broadcast.push(*loc),
                             _ => {}
                         }
                     }
@@ -1,10 +1,11 @@
 use crate::{
-    cmd::{forge::build::CoreBuildArgs, Cmd},
+    cmd::{forge::build::CoreBuildArgs, Cmd, LoadConfig},
     opts::forge::CompilerArgs,
 };
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use comfy_table::{presets::ASCII_MARKDOWN, Table};
 use ethers::{
+    abi::{Abi, AbiError, Event, RawAbi},
     prelude::{
         artifacts::output_selection::{
             BytecodeOutputSelection, ContractOutputSelection, DeployedBytecodeOutputSelection,
@@ -12,14 +13,13 @@ use ethers::{
         },
         info::ContractInfo,
     },
-    solc::{
-        artifacts::{LosslessAbi, StorageLayout},
-        utils::canonicalize,
-    },
+    solc::{artifacts::StorageLayout, utils::canonicalize},
 };
 use foundry_common::compile;
+use foundry_utils::abi::abi_to_solidity;
+use serde::{Deserialize, Serialize};
 use serde_json::{to_value, Value};
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeMap, fmt, str::FromStr};
 use tracing::trace;
 
 /// CLI arguments for `forge inspect`.
@@ -38,13 +38,24 @@ pub struct InspectArgs {
 possible_values = ["abi", "b/bytes/bytecode", "deployedBytecode/deployed_bytecode/deployed-bytecode/deployedbytecode/deployed", "assembly/asm", "asmOptimized/assemblyOptimized/assemblyoptimized/assembly_optimized/asmopt/assembly-optimized/asmo/asm-optimized/asmoptimized/asm_optimized",
 "methods/methodidentifiers/methodIdentifiers/method_identifiers/method-identifiers/mi", "gasEstimates/gas/gas_estimates/gas-estimates/gasestimates",
 "storageLayout/storage_layout/storage-layout/storagelayout/storage", "devdoc/dev-doc/devDoc",
-"ir", "ir-optimized/irOptimized/iroptimized/iro/iropt", "metadata/meta", "userdoc/userDoc/user-doc", "ewasm/e-wasm", "events/ev"]"#
+"ir", "ir-optimized/irOptimized/iroptimized/iro/iropt", "metadata/meta", "userdoc/userDoc/user-doc", "ewasm/e-wasm", "events/ev", "errors/er"]"#
     )]
     pub field: ContractArtifactFields,
 
-    #[clap(long, help = "Pretty print the selected field, if supported.")]
+    #[clap(
+        long,
+        help = "Pretty print the selected field, if supported. Same as `--format table`."
+    )]
     pub pretty: bool,
 
+    #[clap(
+        long,
+        value_enum,
+        help = "The output format of the selected field. By default the field is printed as it is stored in the artifact.",
+        value_name = "FORMAT"
+    )]
+    pub format: Option<InspectFormat>,
+
     /// All build arguments are supported
     #[clap(flatten)]
     build: CoreBuildArgs,
@@ -54,7 +65,7 @@ impl Cmd for InspectArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
-        let InspectArgs { mut contract, field, build, pretty } = self;
+        let InspectArgs { mut contract, field, build, pretty, format } = self;
 
         trace!(target : "forge", ?field, ?contract, "running forge inspect");
 
@@ -78,13 +89,14 @@ impl Cmd for InspectArgs {
         };
 
         // Build the project
-        let project = modified_build_args.project()?;
+        let config = modified_build_args.try_load_config_emit_warnings()?;
+        let project = config.project()?;
         let outcome = if let Some(ref mut contract_path) = contract.path {
             let target_path = canonicalize(&*contract_path)?;
             *contract_path = target_path.to_string_lossy().to_string();
-            compile::compile_files(&project, vec![target_path], true)
+            compile::compile_files(&project, &config, vec![target_path], true)
         } else {
-            compile::suppress_compile(&project)
+            compile::suppress_compile(&project, &config)
         }?;
 
         // Find the artifact
@@ -97,97 +109,102 @@ impl Cmd for InspectArgs {
             eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
         })?;
 
-        // Match on ContractArtifactFields and Pretty Print
-        match field {
-            ContractArtifactFields::Abi => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.abi)?)?);
-            }
-            ContractArtifactFields::Bytecode => {
-                let tval: Value = to_value(&artifact.bytecode)?;
-                println!(
-                    "{}",
-                    tval.get("object").unwrap_or(&tval).clone().as_str().ok_or_else(
-                        || eyre::eyre!("Failed to extract artifact bytecode as a string")
-                    )?
-                );
-            }
-            ContractArtifactFields::DeployedBytecode => {
-                let tval: Value = to_value(&artifact.deployed_bytecode)?;
-                println!(
-                    "{}",
-                    tval.get("object").unwrap_or(&tval).clone().as_str().ok_or_else(
-                        || eyre::eyre!("Failed to extract artifact deployed bytecode as a string")
-                    )?
-                );
+        let format = format.or_else(|| pretty.then_some(InspectFormat::Table));
+        if let Some(format) = format {
+            if !field.supports(format) {
+                eyre::bail!("The `{format}` format is not supported for `{field}`")
             }
+        }
+
+        // Match on ContractArtifactFields and print in the selected format
+        match field {
+            ContractArtifactFields::Abi => match format {
+                Some(InspectFormat::Solidity) => {
+                    let abi = artifact
+                        .abi
+                        .as_ref()
+                        .ok_or_else(|| eyre::eyre!("Failed to extract artifact abi"))?;
+                    let abi: RawAbi = serde_json::from_value(abi.abi_value.clone())?;
+                    println!("{}", abi_to_solidity(&abi, &contract.name)?);
+                }
+                Some(InspectFormat::Table) => {
+                    let items = artifact.abi.as_ref().map(|abi| abi_items(&abi.abi));
+                    print_table(
+                        vec!["Type", "Signature", "Selector"],
+                        items
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|item| vec![item.kind.to_string(), item.signature, item.selector]),
+                    );
+                }
+                _ => print_json(&artifact.abi)?,
+            },
+            ContractArtifactFields::Bytecode => match format {
+                Some(_) => print_json(&artifact.bytecode)?,
+                None => {
+                    let tval: Value = to_value(&artifact.bytecode)?;
+                    println!(
+                        "{}",
+                        tval.get("object").unwrap_or(&tval).clone().as_str().ok_or_else(
+                            || eyre::eyre!("Failed to extract artifact bytecode as a string")
+                        )?
+                    );
+                }
+            },
+            ContractArtifactFields::DeployedBytecode => match format {
+                Some(_) => print_json(&artifact.deployed_bytecode)?,
+                None => {
+                    let tval: Value = to_value(&artifact.deployed_bytecode)?;
+                    println!(
+                        "{}",
+                        tval.get("object").unwrap_or(&tval).clone().as_str().ok_or_else(
+                            || eyre::eyre!(
+                                "Failed to extract artifact deployed bytecode as a string"
+                            )
+                        )?
+                    );
+                }
+            },
             ContractArtifactFields::Assembly | ContractArtifactFields::AssemblyOptimized => {
-                println!(
-                    "{}",
-                    to_value(&artifact.assembly)?.as_str().ok_or_else(|| eyre::eyre!(
-                        "Failed to extract artifact assembly as a string"
-                    ))?
-                );
+                print_str(&artifact.assembly, format, "assembly")?;
             }
             ContractArtifactFields::MethodIdentifiers => {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&to_value(&artifact.method_identifiers)?)?
-                );
-            }
-            ContractArtifactFields::GasEstimates => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.gas_estimates)?)?);
+                let methods = artifact.method_identifiers.clone().unwrap_or_default();
+                print_selectors(methods, format, ["Method", "Identifier"])?;
             }
+            ContractArtifactFields::GasEstimates => print_json(&artifact.gas_estimates)?,
             ContractArtifactFields::StorageLayout => {
-                print_storage_layout(&artifact.storage_layout, pretty)?;
-            }
-            ContractArtifactFields::DevDoc => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.devdoc)?)?);
-            }
-            ContractArtifactFields::Ir => {
-                println!(
-                    "{}",
-                    to_value(&artifact.ir)?
-                        .as_str()
-                        .ok_or_else(|| eyre::eyre!("Failed to extract artifact ir as a string"))?
-                );
+                print_storage_layout(
+                    &artifact.storage_layout,
+                    format == Some(InspectFormat::Table),
+                )?;
             }
+            ContractArtifactFields::DevDoc => print_json(&artifact.devdoc)?,
+            ContractArtifactFields::Ir => print_str(&artifact.ir, format, "ir")?,
             ContractArtifactFields::IrOptimized => {
-                println!(
-                    "{}",
-                    to_value(&artifact.ir_optimized)?.as_str().ok_or_else(|| eyre::eyre!(
-                        "Failed to extract artifact optimized ir as a string"
-                    ))?
-                );
-            }
-            ContractArtifactFields::Metadata => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.metadata)?)?);
-            }
-            ContractArtifactFields::UserDoc => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.userdoc)?)?);
+                print_str(&artifact.ir_optimized, format, "optimized ir")?;
             }
+            ContractArtifactFields::Metadata => print_json(&artifact.metadata)?,
+            ContractArtifactFields::UserDoc => print_json(&artifact.userdoc)?,
             ContractArtifactFields::Ewasm => {
-                println!(
-                    "{}",
-                    to_value(&artifact.ewasm)?.as_str().ok_or_else(|| eyre::eyre!(
-                        "Failed to extract artifact ewasm as a string"
-                    ))?
-                );
+                let ewasm = to_value(&artifact.ewasm)?.as_str().map(str::to_string);
+                print_str(&ewasm, format, "ewasm")?;
             }
             ContractArtifactFields::Events => {
-                let mut out = serde_json::Map::new();
-                if let Some(LosslessAbi { abi, .. }) = artifact.abi.as_ref() {
-                    let events: Vec<_> = abi.events.iter().flat_map(|(_, events)| events).collect();
-                    // print the signature of all events including anonymous
-                    for ev in events.iter() {
-                        let types =
-                            ev.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>();
-                        out.insert(
-                            format!("{}({})", ev.name, types.join(",")),
-                            format!("{:?}", ev.signature()).into(),
-                        );
-                    }
-                }
-                println!("{}", serde_json::to_string_pretty(&out)?);
+                let events = artifact
+                    .abi
+                    .as_ref()
+                    .map(|abi| selectors(&abi.abi, AbiItemKind::Event))
+                    .unwrap_or_default();
+                print_selectors(events, format, ["Event", "Topic"])?;
+            }
+            ContractArtifactFields::Errors => {
+                let errors = artifact
+                    .abi
+                    .as_ref()
+                    .map(|abi| selectors(&abi.abi, AbiItemKind::Error))
+                    .unwrap_or_default();
+                print_selectors(errors, format, ["Error", "Selector"])?;
             }
         };
 
@@ -195,6 +212,144 @@ impl Cmd for InspectArgs {
     }
 }
 
+/// The output format of `forge inspect`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum InspectFormat {
+    /// The field as JSON
+    Json,
+    /// The ABI as a Solidity interface
+    Solidity,
+    /// The field as a table
+    Table,
+}
+
+impl fmt::Display for InspectFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InspectFormat::Json => f.write_str("json"),
+            InspectFormat::Solidity => f.write_str("solidity"),
+            InspectFormat::Table => f.write_str("table"),
+        }
+    }
+}
+
+/// The JSON output of the `methods`, `events` and `errors` fields, which maps the signature of
+/// every item to its selector, or topic for events
+pub type Selectors = BTreeMap<String, String>;
+
+/// The kind of an [AbiItem]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiItemKind {
+    Function,
+    Event,
+    Error,
+}
+
+impl fmt::Display for AbiItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbiItemKind::Function => f.write_str("function"),
+            AbiItemKind::Event => f.write_str("event"),
+            AbiItemKind::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A function, event or error of the ABI together with its selector
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbiItem {
+    #[serde(rename = "type")]
+    pub kind: AbiItemKind,
+    pub signature: String,
+    pub selector: String,
+}
+
+/// Returns all functions, events and errors of the ABI
+pub fn abi_items(abi: &Abi) -> Vec<AbiItem> {
+    let functions = abi.functions().map(|func| AbiItem {
+        kind: AbiItemKind::Function,
+        signature: func.signature(),
+        selector: format!("0x{}", hex::encode(func.short_signature())),
+    });
+    let events = abi.events().map(|event| AbiItem {
+        kind: AbiItemKind::Event,
+        signature: event_signature(event),
+        selector: format!("{:?}", event.signature()),
+    });
+    let errors = abi.errors().map(|error| AbiItem {
+        kind: AbiItemKind::Error,
+        signature: error_signature(error),
+        selector: format!("0x{}", hex::encode(&error.signature()[..4])),
+    });
+    functions.chain(events).chain(errors).collect()
+}
+
+/// Returns the [Selectors] of all items of the given kind
+pub fn selectors(abi: &Abi, kind: AbiItemKind) -> Selectors {
+    abi_items(abi)
+        .into_iter()
+        .filter(|item| item.kind == kind)
+        .map(|item| (item.signature, item.selector))
+        .collect()
+}
+
+fn event_signature(event: &Event) -> String {
+    let types = event.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>();
+    format!("{}({})", event.name, types.join(","))
+}
+
+fn error_signature(error: &AbiError) -> String {
+    let types = error.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>();
+    format!("{}({})", error.name, types.join(","))
+}
+
+fn print_json<T: Serialize>(value: &T) -> eyre::Result<()> {
+    println!("{}", serde_json::to_string_pretty(&to_value(value)?)?);
+    Ok(())
+}
+
+/// Prints a string field, which is quoted in the `json` format
+fn print_str(
+    value: &Option<String>,
+    format: Option<InspectFormat>,
+    name: &str,
+) -> eyre::Result<()> {
+    if format == Some(InspectFormat::Json) {
+        return print_json(value)
+    }
+    let value = value
+        .as_ref()
+        .ok_or_else(|| eyre::eyre!("Failed to extract artifact {name} as a string"))?;
+    println!("{value}");
+    Ok(())
+}
+
+fn print_selectors(
+    selectors: Selectors,
+    format: Option<InspectFormat>,
+    header: [&str; 2],
+) -> eyre::Result<()> {
+    if format == Some(InspectFormat::Table) {
+        print_table(
+            header.to_vec(),
+            selectors.into_iter().map(|(signature, selector)| vec![signature, selector]),
+        );
+        return Ok(())
+    }
+    print_json(&selectors)
+}
+
+fn print_table(header: Vec<&str>, rows: impl IntoIterator<Item = Vec<String>>) {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(header);
+    for row in rows {
+        table.add_row(row);
+    }
+    println!("{table}");
+}
+
 pub fn print_storage_layout(
     storage_layout: &Option<StorageLayout>,
     pretty: bool,
@@ -249,6 +404,7 @@ pub enum ContractArtifactFields {
     UserDoc,
     Ewasm,
     Events,
+    Errors,
 }
 
 // === impl ContractArtifactFields ===
@@ -258,6 +414,22 @@ impl ContractArtifactFields {
     pub fn is_default(&self) -> bool {
         matches!(self, ContractArtifactFields::Bytecode | ContractArtifactFields::DeployedBytecode)
     }
+
+    /// Returns true if this field can be printed in the given format
+    pub fn supports(&self, format: InspectFormat) -> bool {
+        match format {
+            InspectFormat::Json => true,
+            InspectFormat::Solidity => matches!(self, ContractArtifactFields::Abi),
+            InspectFormat::Table => matches!(
+                self,
+                ContractArtifactFields::Abi |
+                    ContractArtifactFields::MethodIdentifiers |
+                    ContractArtifactFields::StorageLayout |
+                    ContractArtifactFields::Events |
+                    ContractArtifactFields::Errors
+            ),
+        }
+    }
 }
 
 impl From<ContractArtifactFields> for ContractOutputSelection {
@@ -288,7 +460,9 @@ impl From<ContractArtifactFields> for ContractOutputSelection {
             ContractArtifactFields::Ewasm => {
                 ContractOutputSelection::Ewasm(EwasmOutputSelection::All)
             }
-            ContractArtifactFields::Events => ContractOutputSelection::Abi,
+            ContractArtifactFields::Events | ContractArtifactFields::Errors => {
+                ContractOutputSelection::Abi
+            }
         }
     }
 }
@@ -317,6 +491,7 @@ impl fmt::Display for ContractArtifactFields {
             ContractArtifactFields::UserDoc => f.write_str("userdoc"),
             ContractArtifactFields::Ewasm => f.write_str("ewasm"),
             ContractArtifactFields::Events => f.write_str("events"),
+            ContractArtifactFields::Errors => f.write_str("errors"),
         }
     }
 }
@@ -351,6 +526,7 @@ impl FromStr for ContractArtifactFields {
             "userdoc" | "userDoc" | "user-doc" => Ok(ContractArtifactFields::UserDoc),
             "ewasm" | "e-wasm" => Ok(ContractArtifactFields::Ewasm),
             "events" | "ev" => Ok(ContractArtifactFields::Events),
+            "errors" | "er" => Ok(ContractArtifactFields::Errors),
             _ => Err(format!("Unknown field: {s}")),
         }
     }
@@ -0,0 +1,185 @@
+//! Resolves identifiers to their declarations with the ASTs of the compiler output
+
+use ethers::solc::{AggregatedCompilerOutput, SourceFile};
+use semver::Version;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// An identifier in a source file that references a declaration
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reference {
+    /// byte offset of the identifier
+    start: usize,
+    /// byte length of the identifier
+    length: usize,
+    /// the file of the declaration
+    target: PathBuf,
+    /// byte offset of the name of the declaration
+    target_start: usize,
+}
+
+/// The declarations that are referenced by the identifiers of every compiled source file
+#[derive(Debug, Default)]
+pub struct Definitions {
+    references: HashMap<PathBuf, Vec<Reference>>,
+}
+
+impl Definitions {
+    /// Returns true if no source file was compiled yet
+    pub fn is_empty(&self) -> bool {
+        self.references.is_empty()
+    }
+
+    /// Replaces the references of all source files of the output
+    ///
+    /// Source files that were not compiled again keep the references of their last compilation.
+    pub fn update(&mut self, root: &Path, output: &AggregatedCompilerOutput) {
+        // node ids are only unique within a single solc run, which compiles the files of a version
+        let mut runs: BTreeMap<&Version, Vec<(&String, &SourceFile)>> = BTreeMap::new();
+        for (path, file, version) in output.sources.sources_with_version() {
+            runs.entry(version).or_default().push((path, file));
+        }
+
+        for files in runs.into_values() {
+            let paths: HashMap<usize, PathBuf> =
+                files.iter().map(|(path, file)| (file.id as usize, root.join(path))).collect();
+            let mut declarations = HashMap::new();
+            let mut references = Vec::new();
+            for (_, file) in &files {
+                if let Some(ast) = file.ast.as_ref().and_then(|ast| serde_json::to_value(ast).ok())
+                {
+                    collect(&ast, &mut declarations, &mut references);
+                }
+            }
+
+            let mut resolved: HashMap<PathBuf, Vec<Reference>> =
+                paths.values().map(|path| (path.clone(), Vec::new())).collect();
+            for (src, id) in references {
+                let (target_start, target_index) = match declarations.get(&id) {
+                    Some(declaration) => *declaration,
+                    None => continue,
+                };
+                let (path, target) = match (paths.get(&src.index), paths.get(&target_index)) {
+                    (Some(path), Some(target)) => (path, target.clone()),
+                    _ => continue,
+                };
+                let reference =
+                    Reference { start: src.start, length: src.length, target, target_start };
+                resolved.entry(path.clone()).or_default().push(reference);
+            }
+            self.references.extend(resolved);
+        }
+    }
+
+    /// Returns the file and byte offset of the declaration that is referenced at the byte
+    /// `offset` of the `file`
+    pub fn definition(&self, file: &Path, offset: usize) -> Option<(&Path, usize)> {
+        self.references
+            .get(file)?
+            .iter()
+            .filter(|reference| {
+                reference.start <= offset && offset <= reference.start + reference.length
+            })
+            .min_by_key(|reference| reference.length)
+            .map(|reference| (reference.target.as_path(), reference.target_start))
+    }
+}
+
+/// A `start:length:index` source location of the AST
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Src {
+    start: usize,
+    length: usize,
+    index: usize,
+}
+
+impl Src {
+    /// Parses the location, `None` if it has no source file (`-1`)
+    fn parse(src: &str) -> Option<Self> {
+        let mut parts = src.split(':').map(|part| part.parse::<usize>().ok());
+        Some(Self { start: parts.next()??, length: parts.next()??, index: parts.next()?? })
+    }
+}
+
+/// Collects the location of the name of all nodes by their id, and the location of all nodes that
+/// reference a declaration, along with its id
+fn collect(
+    value: &Value,
+    declarations: &mut HashMap<u64, (usize, usize)>,
+    references: &mut Vec<(Src, u64)>,
+) {
+    match value {
+        Value::Object(node) => {
+            let src = |key: &str| node.get(key).and_then(Value::as_str).and_then(Src::parse);
+            if let Some(location) = src("src") {
+                if let Some(id) = node.get("id").and_then(Value::as_u64) {
+                    let name = src("nameLocation").unwrap_or(location);
+                    declarations.insert(id, (name.start, name.index));
+                }
+                if let Some(id) = node.get("referencedDeclaration").and_then(Value::as_u64) {
+                    references.push((location, id));
+                }
+            }
+            for child in node.values() {
+                collect(child, declarations, references);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                collect(value, declarations, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn can_collect_references() {
+        // contract Vault { uint x; function f() public { x; } }
+        let ast = json!({
+            "id": 1,
+            "src": "0:53:0",
+            "nodes": [{
+                "id": 2,
+                "src": "0:53:0",
+                "nameLocation": "9:5:0",
+                "nodes": [
+                    { "id": 3, "src": "17:6:0", "nameLocation": "22:1:0" },
+                    {
+                        "id": 4,
+                        "src": "25:26:0",
+                        "body": {
+                            "id": 5,
+                            "src": "45:6:0",
+                            "statements": [{ "id": 6, "src": "47:1:0", "referencedDeclaration": 3 }]
+                        }
+                    }
+                ]
+            }]
+        });
+        let mut declarations = HashMap::new();
+        let mut references = Vec::new();
+        collect(&ast, &mut declarations, &mut references);
+
+        assert_eq!(declarations[&2], (9, 0));
+        assert_eq!(declarations[&3], (22, 0));
+        assert_eq!(references, vec![(Src { start: 47, length: 1, index: 0 }, 3)]);
+        assert_eq!(Src::parse("-1:-1:-1"), None);
+
+        let mut definitions = Definitions::default();
+        let target = PathBuf::from("/project/src/Vault.sol");
+        let reference =
+            Reference { start: 47, length: 1, target: target.clone(), target_start: 22 };
+        definitions.references.insert(target.clone(), vec![reference]);
+        assert_eq!(definitions.definition(&target, 47), Some((target.as_path(), 22)));
+        assert_eq!(definitions.definition(&target, 30), None);
+    }
+}
@@ -0,0 +1,472 @@
+//! lsp command
+
+use crate::cmd::{
+    forge::{build::ProjectPathsArgs, snapshot::read_snapshot},
+    Cmd, LoadConfig,
+};
+use clap::{Parser, ValueHint};
+use ethers::solc::{artifacts::Severity, report::{self, NoReporter, Report}};
+use foundry_common::fs;
+use foundry_config::Config;
+use foundry_runner::{Filter, TestRunner};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+        Notification as _, PublishDiagnostics, ShowMessage,
+    },
+    request::{CodeLensRequest, ExecuteCommand, GotoDefinition, InlayHintRequest, Request as _},
+    CodeLens, CodeLensOptions, CodeLensParams, Command, Diagnostic, DiagnosticSeverity,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams,
+    GotoDefinitionParams, GotoDefinitionResponse, InlayHint, InlayHintLabel, InlayHintParams,
+    Location, MessageType, NumberOrString, OneOf, Position, PublishDiagnosticsParams, Range,
+    SaveOptions, ServerCapabilities, ShowMessageParams, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions, Url,
+};
+use parking_lot::Mutex;
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+};
+use tracing::trace;
+
+mod definitions;
+use definitions::Definitions;
+
+mod source;
+pub use source::resolve_import;
+use source::{import_at, offset_at, position_at, test_functions};
+
+foundry_config::impl_figment_convert!(LspArgs, opts);
+
+/// The command of the code lenses that run a single test
+const RUN_TEST_COMMAND: &str = "forge.runTest";
+
+/// CLI arguments for `forge lsp`.
+#[derive(Debug, Clone, Parser)]
+pub struct LspArgs {
+    /// The gas snapshot file the gas hints of test functions are read from.
+    #[clap(
+        long,
+        default_value = ".gas-snapshot",
+        value_hint = ValueHint::FilePath,
+        value_name = "FILE"
+    )]
+    snap: PathBuf,
+
+    #[clap(flatten)]
+    opts: ProjectPathsArgs,
+}
+
+impl Cmd for LspArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let config = self.try_load_config_emit_warnings()?;
+        let snapshot = config.__root.0.join(&self.snap);
+
+        // stdout is used by the protocol, so nothing else may be printed to it
+        let (connection, io_threads) = Connection::stdio();
+        let capabilities = ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                        include_text: Some(false),
+                    })),
+                    ..Default::default()
+                },
+            )),
+            definition_provider: Some(OneOf::Left(true)),
+            code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: vec![RUN_TEST_COMMAND.to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        connection.initialize(serde_json::to_value(capabilities)?)?;
+        trace!(target: "forge::lsp", root = ?config.__root.0, "initialized language server");
+
+        let mut server = Server {
+            config,
+            snapshot,
+            documents: HashMap::new(),
+            diagnosed: HashSet::new(),
+            definitions: Definitions::default(),
+            compiling: Arc::new(Mutex::new(())),
+        };
+        server.serve(&connection)?;
+
+        drop(connection);
+        io_threads.join()?;
+        Ok(())
+    }
+}
+
+/// The state of the language server
+struct Server {
+    config: Config,
+    /// The gas snapshot file
+    snapshot: PathBuf,
+    /// The content of all open documents
+    documents: HashMap<Url, String>,
+    /// The documents that have diagnostics from the last compilation
+    diagnosed: HashSet<Url>,
+    /// The declarations referenced in the compiled sources
+    definitions: Definitions,
+    /// Held while compiling, since the project must not be compiled concurrently
+    compiling: Arc<Mutex<()>>,
+}
+
+impl Server {
+    /// Handles all messages until the client shuts the server down
+    fn serve(&mut self, connection: &Connection) -> eyre::Result<()> {
+        for msg in &connection.receiver {
+            match msg {
+                Message::Request(req) => {
+                    if connection.handle_shutdown(&req)? {
+                        return Ok(())
+                    }
+                    let response = self.handle_request(req, connection);
+                    connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => {
+                    if let Err(err) = self.handle_notification(notification, connection) {
+                        let message = show_message(MessageType::ERROR, err.to_string());
+                        connection.sender.send(message)?;
+                    }
+                }
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, req: Request, connection: &Connection) -> Response {
+        let id = req.id.clone();
+        match self.dispatch(req, connection) {
+            Ok(Some(result)) => Response::new_ok(id, result),
+            Ok(None) => Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                "unsupported request".to_string(),
+            ),
+            Err(err) => Response::new_err(id, ErrorCode::InternalError as i32, err.to_string()),
+        }
+    }
+
+    /// Returns the result of the request, or `None` if the request is not supported
+    fn dispatch(&mut self, req: Request, connection: &Connection) -> eyre::Result<Option<Value>> {
+        let result = match req.method.as_str() {
+            GotoDefinition::METHOD => {
+                serde_json::to_value(self.definition(serde_json::from_value(req.params)?)?)?
+            }
+            CodeLensRequest::METHOD => {
+                serde_json::to_value(self.code_lenses(serde_json::from_value(req.params)?))?
+            }
+            InlayHintRequest::METHOD => {
+                serde_json::to_value(self.inlay_hints(serde_json::from_value(req.params)?))?
+            }
+            ExecuteCommand::METHOD => {
+                self.execute_command(serde_json::from_value(req.params)?, connection)?;
+                Value::Null
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(result))
+    }
+
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+        connection: &Connection,
+    ) -> eyre::Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.insert(params.text_document.uri, params.text_document.text);
+                self.publish_diagnostics(connection)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(params.text_document.uri, change.text);
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                let _: DidSaveTextDocumentParams = serde_json::from_value(notification.params)?;
+                self.publish_diagnostics(connection)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the content of the document, the open one if any
+    fn source(&self, uri: &Url) -> eyre::Result<String> {
+        match self.documents.get(uri) {
+            Some(source) => Ok(source.clone()),
+            None => Ok(fs::read_to_string(to_path(uri)?)?),
+        }
+    }
+
+    /// Compiles the project and publishes the errors and warnings of solc
+    ///
+    /// solc compiles the saved files, so all locations refer to their content on disk.
+    fn publish_diagnostics(&mut self, connection: &Connection) -> eyre::Result<()> {
+        let _compiling = self.compiling.lock();
+        // the first compilation ignores the cache to get the AST of every source file
+        let project = if self.definitions.is_empty() {
+            self.config.ephemeral_no_artifacts_project()?
+        } else {
+            self.config.project()?
+        };
+        let _lock = self.config.restore_shared_artifacts(&project)?;
+        let output =
+            report::with_scoped(&Report::new(NoReporter::default()), || project.compile())?
+                .output();
+        self.definitions.update(&self.config.__root.0, &output);
+
+        let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let mut diagnostics: BTreeMap<PathBuf, Vec<Diagnostic>> = BTreeMap::new();
+        for error in &output.errors {
+            if error.error_code.map(|code| project.ignored_error_codes.contains(&code)) ==
+                Some(true)
+            {
+                continue
+            }
+            let location = match error.source_location {
+                Some(ref location) => location,
+                None => continue,
+            };
+            let path = self.config.__root.0.join(&location.file);
+            let source = match sources
+                .entry(path.clone())
+                .or_insert_with(|| fs::read_to_string(&path).ok())
+            {
+                Some(source) => source,
+                None => continue,
+            };
+            let range = Range::new(
+                position_at(source, location.start.max(0) as usize),
+                position_at(source, location.end.max(0) as usize),
+            );
+            diagnostics.entry(path).or_default().push(Diagnostic {
+                range,
+                severity: Some(match error.severity {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                    Severity::Info => DiagnosticSeverity::INFORMATION,
+                }),
+                code: error.error_code.map(|code| NumberOrString::String(code.to_string())),
+                source: Some("solc".to_string()),
+                message: error.message.clone(),
+                ..Default::default()
+            });
+        }
+
+        let mut diagnosed = HashSet::new();
+        for (path, diagnostics) in diagnostics {
+            let uri = to_url(&path)?;
+            publish(connection, uri.clone(), diagnostics)?;
+            diagnosed.insert(uri);
+        }
+        // clear the diagnostics of all files that have none anymore
+        for uri in self.diagnosed.difference(&diagnosed) {
+            publish(connection, uri.clone(), vec![])?;
+        }
+        self.diagnosed = diagnosed;
+        Ok(())
+    }
+
+    /// Resolves imports via the remappings and identifiers to their declaration with the AST of
+    /// the last compilation
+    fn definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> eyre::Result<Option<GotoDefinitionResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let path = to_path(&uri)?;
+        let source = self.source(&uri)?;
+        let root = &self.config.__root.0;
+
+        if let Some(import) = import_at(&source, position) {
+            let target = resolve_import(root, &path, import, &self.config.get_all_remappings());
+            if !target.is_file() {
+                return Ok(None)
+            }
+            return location(&target, Position::default()).map(Some)
+        }
+
+        // the AST locations refer to the saved content of the files
+        let offset = match offset_at(&fs::read_to_string(&path)?, position) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let (target, offset) = match self.definitions.definition(&path, offset) {
+            Some(definition) => definition,
+            None => return Ok(None),
+        };
+        let position = position_at(&fs::read_to_string(target)?, offset);
+        location(target, position).map(Some)
+    }
+
+    /// Returns a code lens to run every test function of the document
+    fn code_lenses(&self, params: CodeLensParams) -> Vec<CodeLens> {
+        let uri = params.text_document.uri;
+        let source = match self.source(&uri) {
+            Ok(source) => source,
+            Err(_) => return vec![],
+        };
+        test_functions(&source)
+            .into_iter()
+            .map(|func| CodeLens {
+                range: Range::new(
+                    Position::new(func.line, 0),
+                    Position::new(func.line, func.line_len),
+                ),
+                command: Some(Command {
+                    title: "▶ Run test".to_string(),
+                    command: RUN_TEST_COMMAND.to_string(),
+                    arguments: Some(vec![
+                        Value::String(uri.to_string()),
+                        Value::String(func.contract),
+                        Value::String(func.name),
+                    ]),
+                }),
+                data: None,
+            })
+            .collect()
+    }
+
+    /// Returns the gas of the gas snapshot at the end of every test function definition
+    fn inlay_hints(&self, params: InlayHintParams) -> Vec<InlayHint> {
+        let source = match self.source(&params.text_document.uri) {
+            Ok(source) => source,
+            Err(_) => return vec![],
+        };
+        let snapshot = match read_snapshot(&self.snapshot) {
+            Ok(snapshot) => snapshot,
+            Err(_) => return vec![],
+        };
+
+        test_functions(&source)
+            .into_iter()
+            .filter(|func| {
+                params.range.start.line <= func.line && func.line <= params.range.end.line
+            })
+            .filter_map(|func| {
+                let signature = format!("{}(", func.name);
+                let entry = snapshot.iter().find(|entry| {
+                    entry.contract_name == func.contract && entry.signature.starts_with(&signature)
+                })?;
+                Some(InlayHint {
+                    position: Position::new(func.line, func.line_len),
+                    label: InlayHintLabel::String(entry.gas_used.to_string()),
+                    kind: None,
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: Some(true),
+                    padding_right: None,
+                    data: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Runs a single test in the background and shows its result
+    fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+        connection: &Connection,
+    ) -> eyre::Result<()> {
+        if params.command != RUN_TEST_COMMAND {
+            eyre::bail!("unsupported command {}", params.command)
+        }
+        let (uri, contract, test) = match params.arguments.as_slice() {
+            [Value::String(uri), Value::String(contract), Value::String(test)] => {
+                (Url::parse(uri)?, contract.clone(), test.clone())
+            }
+            _ => eyre::bail!("{RUN_TEST_COMMAND} expects the document, the contract and the test"),
+        };
+
+        let root = self.config.__root.0.clone();
+        let path = to_path(&uri)?;
+        let path = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy().to_string();
+        let filter = Filter::default()
+            .match_path(Regex::new(&format!("{}$", regex::escape(&path)))?)
+            .match_contract(Regex::new(&format!("^{}$", regex::escape(&contract)))?)
+            .match_test(Regex::new(&format!(r"^{}(\(|$)", regex::escape(&test)))?);
+
+        let sender = connection.sender.clone();
+        let compiling = self.compiling.clone();
+        thread::spawn(move || {
+            let results = TestRunner::load(root).and_then(|runner| {
+                let runner = runner.with_filter(filter);
+                let output = {
+                    let _compiling = compiling.lock();
+                    runner.compile()?
+                };
+                runner.run_compiled(output)
+            });
+            let (typ, message) = match results {
+                Ok(results) => match results.values().flat_map(|suite| suite.tests()).next() {
+                    Some((name, result)) if result.success => {
+                        (MessageType::INFO, format!("[PASS] {name} {}", result.kind.report()))
+                    }
+                    Some((name, result)) => (
+                        MessageType::ERROR,
+                        format!(
+                            "[FAIL. Reason: {}] {name}",
+                            result.reason.as_deref().unwrap_or("Assertion failed.")
+                        ),
+                    ),
+                    None => (MessageType::WARNING, format!("No test matches {contract}::{test}")),
+                },
+                Err(err) => (MessageType::ERROR, format!("Failed to run {test}: {err}")),
+            };
+            let _ = sender.send(show_message(typ, message));
+        });
+        Ok(())
+    }
+}
+
+fn publish(connection: &Connection, uri: Url, diagnostics: Vec<Diagnostic>) -> eyre::Result<()> {
+    let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+    let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+    connection.sender.send(Message::Notification(notification))?;
+    Ok(())
+}
+
+fn show_message(typ: MessageType, message: String) -> Message {
+    let params = ShowMessageParams { typ, message };
+    Message::Notification(Notification::new(ShowMessage::METHOD.to_string(), params))
+}
+
+fn location(path: &Path, position: Position) -> eyre::Result<GotoDefinitionResponse> {
+    Ok(GotoDefinitionResponse::Scalar(Location::new(to_url(path)?, Range::new(position, position))))
+}
+
+fn to_url(path: &Path) -> eyre::Result<Url> {
+    Url::from_file_path(path).map_err(|_| eyre::eyre!("invalid path {}", path.display()))
+}
+
+fn to_path(uri: &Url) -> eyre::Result<PathBuf> {
+    uri.to_file_path().map_err(|_| eyre::eyre!("{uri} is not a file"))
+}
@@ -0,0 +1,182 @@
+//! Helpers to find things in Solidity sources without a full parse
+
+use ethers::solc::remappings::Remapping;
+use lsp_types::Position;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+static RE_CONTRACT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:abstract\s+)?contract\s+(\w+)").unwrap());
+
+static RE_TEST_FUNCTION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"function\s+((?:test|invariant)\w*)\s*\(").unwrap());
+
+static RE_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*import\b.*?["']([^"']+)["']"#).unwrap());
+
+/// A test function in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFunction {
+    /// The name of the contract the function is defined in
+    pub contract: String,
+    /// The name of the function
+    pub name: String,
+    /// The zero-based line of the definition
+    pub line: u32,
+    /// The length of the line in UTF-16 code units
+    pub line_len: u32,
+}
+
+/// Returns all test and invariant functions of the source
+pub fn test_functions(source: &str) -> Vec<TestFunction> {
+    let mut contract = None;
+    let mut functions = vec![];
+    for (line_idx, line) in source.lines().enumerate() {
+        if let Some(cap) = RE_CONTRACT.captures(line) {
+            contract = Some(cap[1].to_string());
+        }
+        if let (Some(contract), Some(cap)) = (contract.as_ref(), RE_TEST_FUNCTION.captures(line)) {
+            functions.push(TestFunction {
+                contract: contract.clone(),
+                name: cap[1].to_string(),
+                line: line_idx as u32,
+                line_len: line.encode_utf16().count() as u32,
+            });
+        }
+    }
+    functions
+}
+
+/// Returns the position of the byte `offset` in the source
+pub fn position_at(source: &str, offset: usize) -> Position {
+    let mut position = Position::new(0, 0);
+    for (idx, c) in source.char_indices() {
+        if idx >= offset {
+            break
+        }
+        if c == '\n' {
+            position.line += 1;
+            position.character = 0;
+        } else {
+            position.character += c.len_utf16() as u32;
+        }
+    }
+    position
+}
+
+/// Returns the byte offset of the position in the source, if the source has that line
+pub fn offset_at(source: &str, position: Position) -> Option<usize> {
+    let start = match position.line {
+        0 => 0,
+        line => source.match_indices('\n').nth(line as usize - 1)?.0 + 1,
+    };
+    let (_, idx) = line_at(source, position)?;
+    Some(start + idx)
+}
+
+/// Returns the line of the position and the byte index of the position in that line
+fn line_at(source: &str, position: Position) -> Option<(&str, usize)> {
+    let line = source.lines().nth(position.line as usize)?;
+    let mut units = 0;
+    for (idx, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return Some((line, idx))
+        }
+        units += c.len_utf16();
+    }
+    Some((line, line.len()))
+}
+
+/// Returns the path of the import statement at the position, if any
+pub fn import_at(source: &str, position: Position) -> Option<&str> {
+    let (line, idx) = line_at(source, position)?;
+    let path = RE_IMPORT.captures(line)?.get(1)?;
+    // the quotes around the path are part of it
+    (path.start().saturating_sub(1) <= idx && idx <= path.end()).then_some(path.as_str())
+}
+
+/// Resolves the path of an import in the file at `file` like solc does with the remappings
+pub fn resolve_import(root: &Path, file: &Path, import: &str, remappings: &[Remapping]) -> PathBuf {
+    if import.starts_with('.') {
+        return file.parent().unwrap_or(root).join(import)
+    }
+    let remapping = remappings
+        .iter()
+        .filter(|remapping| import.starts_with(&remapping.name))
+        .max_by_key(|remapping| remapping.name.len());
+    match remapping {
+        Some(remapping) => Path::new(&remapping.path).join(&import[remapping.name.len()..]),
+        None => root.join(import),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"import "forge-std/Test.sol";
+import {Vault} from "../src/Vault.sol";
+
+contract VaultTest is Test {
+    Vault vault;
+
+    function testDeposit() public {
+        vault.deposit();
+    }
+
+    function invariant_solvent() public {}
+}
+"#;
+
+    #[test]
+    fn can_find_test_functions() {
+        let functions = test_functions(SOURCE);
+        assert_eq!(functions.len(), 2);
+        assert_eq!(functions[0].contract, "VaultTest");
+        assert_eq!(functions[0].name, "testDeposit");
+        assert_eq!(functions[0].line, 6);
+        assert_eq!(functions[1].name, "invariant_solvent");
+    }
+
+    #[test]
+    fn can_find_positions() {
+        let offset = SOURCE.find("Vault vault").unwrap();
+        assert_eq!(position_at(SOURCE, offset), Position::new(4, 4));
+        assert_eq!(position_at("a\u{20ac}b", 4), Position::new(0, 2));
+
+        assert_eq!(import_at(SOURCE, Position::new(0, 12)), Some("forge-std/Test.sol"));
+        assert_eq!(import_at(SOURCE, Position::new(1, 25)), Some("../src/Vault.sol"));
+        assert_eq!(import_at(SOURCE, Position::new(1, 9)), None);
+        assert_eq!(offset_at(SOURCE, Position::new(4, 4)), Some(offset));
+        assert_eq!(offset_at("a\u{20ac}b", Position::new(0, 2)), Some(4));
+        assert_eq!(offset_at(SOURCE, Position::new(20, 0)), None);
+    }
+
+    #[test]
+    fn can_resolve_imports() {
+        let root = Path::new("/project");
+        let file = root.join("test/Vault.t.sol");
+        let remappings = vec![
+            "forge-std/=/project/lib/forge-std/src/".parse::<Remapping>().unwrap(),
+            "forge-std/mocks/=/project/lib/mocks/".parse::<Remapping>().unwrap(),
+        ];
+
+        assert_eq!(
+            resolve_import(root, &file, "forge-std/Test.sol", &remappings),
+            PathBuf::from("/project/lib/forge-std/src/Test.sol")
+        );
+        assert_eq!(
+            resolve_import(root, &file, "forge-std/mocks/Mock.sol", &remappings),
+            PathBuf::from("/project/lib/mocks/Mock.sol")
+        );
+        assert_eq!(
+            resolve_import(root, &file, "../src/Vault.sol", &remappings),
+            PathBuf::from("/project/test/../src/Vault.sol")
+        );
+        assert_eq!(
+            resolve_import(root, &file, "src/Vault.sol", &remappings),
+            PathBuf::from("/project/src/Vault.sol")
+        );
+    }
+}
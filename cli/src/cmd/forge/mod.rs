@@ -46,7 +46,9 @@ pub mod config;
 pub mod coverage;
 pub mod create;
 pub mod debug;
+pub mod diff_bytecode;
 pub mod doc;
+pub mod export;
 pub mod flatten;
 pub mod fmt;
 pub mod fourbyte;
@@ -54,10 +56,12 @@ pub mod geiger;
 pub mod init;
 pub mod inspect;
 pub mod install;
+pub mod lsp;
 pub mod remappings;
 pub mod remove;
 pub mod script;
 pub mod snapshot;
+pub mod storage_check;
 pub mod test;
 pub mod tree;
 pub mod update;
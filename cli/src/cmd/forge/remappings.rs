@@ -2,8 +2,9 @@
 
 use crate::cmd::{Cmd, LoadConfig};
 use clap::{Parser, ValueHint};
-use foundry_config::impl_figment_convert_basic;
-use std::path::PathBuf;
+use ethers::solc::remappings::Remapping;
+use foundry_config::{impl_figment_convert_basic, Config};
+use std::path::{Path, PathBuf};
 
 /// CLI arguments for `forge remappings`.
 #[derive(Debug, Clone, Parser)]
@@ -15,6 +16,20 @@ pub struct RemappingArgs {
         value_name = "PATH"
     )]
     root: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Print which remapping is used for each name and the remappings it shadows.",
+        conflicts_with = "lock"
+    )]
+    report: bool,
+
+    #[clap(
+        long,
+        help = "Write the remappings to a remappings.lock file.",
+        long_help = "Write the remappings to a remappings.lock file. Builds fail if the remappings don't match the lock file anymore, for example because a nested dependency changed."
+    )]
+    lock: bool,
 }
 impl_figment_convert_basic!(RemappingArgs);
 
@@ -23,7 +38,44 @@ impl Cmd for RemappingArgs {
 
     fn run(self) -> eyre::Result<Self::Output> {
         let config = self.try_load_config_emit_warnings()?;
-        config.remappings.iter().for_each(|x| println!("{x}"));
+        if self.report {
+            print_report(&config)?;
+        } else if self.lock {
+            let path = config.write_remappings_lock()?;
+            println!("Wrote {} remappings to {}", config.remappings.len(), path.display());
+        } else {
+            config.remappings.iter().for_each(|x| println!("{x}"));
+        }
         Ok(())
     }
 }
+
+/// Prints the path and source of every remapping and the paths of the remappings it shadows
+fn print_report(config: &Config) -> eyre::Result<()> {
+    let root = &config.__root.0;
+    let relative = |r: &Remapping| {
+        Path::new(&r.path)
+            .strip_prefix(root)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| r.path.clone())
+    };
+
+    let resolutions = config.remapping_resolutions()?;
+    for resolution in &resolutions {
+        println!(
+            "{} => {} ({})",
+            resolution.remapping.name,
+            relative(&resolution.remapping),
+            resolution.source
+        );
+        for (shadowed, source) in &resolution.shadowed {
+            println!("    shadows {} ({source})", relative(shadowed));
+        }
+    }
+
+    let conflicts = resolutions.iter().filter(|r| r.is_conflict()).count();
+    if conflicts > 0 {
+        println!("\n{conflicts} conflicting remapping(s), the first listed path is used");
+    }
+    Ok(())
+}
@@ -1,13 +1,22 @@
-use super::{multi::MultiChainSequence, providers::ProvidersManager, sequence::ScriptSequence, *};
+use super::{
+    multi::MultiChainSequence, plan::ScriptPlan, providers::ProvidersManager,
+    sequence::ScriptSequence, *,
+};
 use crate::{
     cmd::{
-        forge::script::{
-            receipts::clear_pendings, transaction::TransactionWithMetadata, verify::VerifyBundle,
+        forge::{
+            export::{export_deployments, DEFAULT_EXPORT_DIR},
+            script::{
+                cost::{ChainGasSummary, PriceOracle, PRICE_CACHE_FILE},
+                receipts::clear_pendings,
+                transaction::TransactionWithMetadata,
+                verify::VerifyBundle,
+            },
         },
         has_batch_support, has_different_gas_calc,
     },
     init_progress,
-    opts::WalletSigner,
+    opts::{MultiWalletSigners, WalletSigner},
     update_progress,
 };
 use ethers::{
@@ -59,7 +68,7 @@ impl ScriptArgs {
                     .wallets
                     .find_all(provider.clone(), required_addresses, script_wallets)
                     .await?;
-                let chain = local_wallets.values().last().wrap_err("Error accessing local wallet when trying to send onchain transaction, did you set a private key, mnemonic or keystore?")?.chain_id();
+                let chain = local_wallets.signers.values().last().wrap_err("Error accessing local wallet when trying to send onchain transaction, did you set a private key, mnemonic or keystore?")?.chain_id();
                 (SendTransactionsKind::Raw(local_wallets), chain)
             };
 
@@ -212,6 +221,7 @@ impl ScriptArgs {
         Ok(())
     }
 
+    #[tracing::instrument(name = "broadcast", skip_all)]
     async fn send_transaction(
         &self,
         provider: Arc<RetryProvider>,
@@ -253,7 +263,15 @@ impl ScriptArgs {
 
                 Ok(pending.tx_hash())
             }
-            SendTransactionKind::Raw(signer) => self.broadcast(provider, signer, tx).await,
+            SendTransactionKind::Raw(signer, hardware) => {
+                if let Some(hardware) = hardware {
+                    shell::println(format!(
+                        "Confirm the transaction from {from:?} with nonce {} on the {hardware}",
+                        tx.nonce().expect("no nonce")
+                    ))?;
+                }
+                self.broadcast(provider, signer, tx).await
+            }
         }
     }
 
@@ -284,6 +302,12 @@ impl ScriptArgs {
                     )
                     .await?;
 
+                self.handle_plan(&deployments)?;
+
+                // the config is moved into the deployment
+                let export_config = (self.broadcast && !self.export.is_empty())
+                    .then(|| script_config.config.clone());
+
                 if script_config.has_multiple_rpcs() {
                     trace!(target: "script", "broadcasting multi chain deployment");
 
@@ -316,6 +340,11 @@ impl ScriptArgs {
                     .await?;
                 }
 
+                if let Some(config) = export_config {
+                    let out = config.__root.0.join(DEFAULT_EXPORT_DIR);
+                    export_deployments(&config, &self.export, &out)?;
+                }
+
                 if !self.broadcast {
                     shell::println("\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration(s) to the previous command. See forge script --help for more.")?;
                 }
@@ -326,6 +355,30 @@ impl ScriptArgs {
         Ok(())
     }
 
+    /// Writes the plan of the transactions to `--plan-out` and shows its diff to `--plan-diff`
+    fn handle_plan(&self, deployments: &[ScriptSequence]) -> Result<()> {
+        if self.plan_out.is_none() && self.plan_diff.is_none() {
+            return Ok(())
+        }
+        let plan = ScriptPlan::new(deployments);
+
+        if let Some(ref old) = self.plan_diff {
+            let old = ScriptPlan::load(old)?;
+            match plan.diff(&old) {
+                Some(diff) => {
+                    shell::println("\n## Changes to the planned transactions\n")?;
+                    shell::println(diff.trim_end())?;
+                }
+                None => shell::println("\nNo changes to the planned transactions.")?,
+            }
+        }
+        if let Some(ref out) = self.plan_out {
+            plan.save(out)?;
+            shell::println(format!("\nPlan written to: {}", out.display()))?;
+        }
+        Ok(())
+    }
+
     /// Broadcasts a single chain script.
     async fn single_deployment(
         &self,
@@ -432,7 +485,9 @@ impl ScriptArgs {
         let last_rpc = &transactions.back().expect("exists; qed").rpc;
         let is_multi_deployment = transactions.iter().any(|tx| &tx.rpc != last_rpc);
 
-        let mut total_gas_per_rpc: HashMap<RpcUrl, U256> = HashMap::new();
+        // The transactions sent to every rpc, by their position in the script
+        let mut txs_per_rpc: HashMap<RpcUrl, Vec<(usize, TransactionWithMetadata)>> =
+            HashMap::new();
 
         // Batches sequence of transactions from different rpcs.
         let mut new_sequence = VecDeque::new();
@@ -445,9 +500,9 @@ impl ScriptArgs {
 
         // Peeking is used to check if the next rpc url is different. If so, it creates a
         // [`ScriptSequence`] from all the collected transactions up to this point.
-        let mut txes_iter = transactions.into_iter().peekable();
+        let mut txes_iter = transactions.into_iter().enumerate().peekable();
 
-        while let Some(mut tx) = txes_iter.next() {
+        while let Some((index, mut tx)) = txes_iter.next() {
             let tx_rpc = match tx.rpc.clone() {
                 Some(rpc) => rpc,
                 None => {
@@ -490,14 +545,13 @@ impl ScriptArgs {
                     }
                 }
 
-                let total_gas = total_gas_per_rpc.entry(tx_rpc.clone()).or_insert(U256::zero());
-                *total_gas += *typed_tx.gas().expect("gas is set");
+                txs_per_rpc.entry(tx_rpc.clone()).or_default().push((index, tx.clone()));
             }
 
             new_sequence.push_back(tx);
             // We only create a [`ScriptSequence`] object when we collect all the rpc related
             // transactions.
-            if let Some(next_tx) = txes_iter.peek() {
+            if let Some((_, next_tx)) = txes_iter.peek() {
                 if next_tx.rpc == Some(tx_rpc) {
                     continue
                 }
@@ -523,8 +577,16 @@ impl ScriptArgs {
         config.chain_id = original_config_chain;
 
         if !self.skip_simulation {
+            let oracle = self.usd.then(|| {
+                PriceOracle::new(
+                    self.price_oracle.as_str(),
+                    Config::foundry_cache_dir().map(|dir| dir.join(PRICE_CACHE_FILE)),
+                )
+            });
+
             // Present gas information on a per RPC basis.
-            for (rpc, total_gas) in total_gas_per_rpc {
+            let mut summaries = vec![];
+            for (rpc, txs) in txs_per_rpc {
                 let provider_info = manager.get(&rpc).expect("provider is set.");
 
                 // We don't store it in the transactions, since we want the most updated value.
@@ -535,24 +597,30 @@ impl ScriptArgs {
                     provider_info.gas_price()?
                 };
 
-                shell::println("\n==========================")?;
-                shell::println(format!("\nChain {}", provider_info.chain))?;
+                let mut summary = ChainGasSummary::new(
+                    provider_info.chain,
+                    per_gas,
+                    txs.iter().map(|(index, tx)| (*index, tx)),
+                );
+                if let Some(oracle) = oracle.as_ref() {
+                    match oracle.usd_price(provider_info.chain).await {
+                        Ok(price) => summary.set_usd_price(price),
+                        Err(err) => shell::eprintln(format!(
+                            "{}",
+                            Paint::yellow(format!("Could not estimate costs in USD: {err}"))
+                        ))?,
+                    }
+                }
+                summaries.push(summary);
+            }
+            summaries.sort_by_key(|summary| summary.chain);
 
-                shell::println(format!(
-                    "\nEstimated gas price: {} gwei",
-                    format_units(per_gas, 9)
-                        .unwrap_or_else(|_| "[Could not calculate]".to_string())
-                        .trim_end_matches('0')
-                        .trim_end_matches('.')
-                ))?;
-                shell::println(format!("\nEstimated total gas used for script: {total_gas}"))?;
-                shell::println(format!(
-                    "\nEstimated amount required: {} ETH",
-                    format_units(total_gas.saturating_mul(per_gas), 18)
-                        .unwrap_or_else(|_| "[Could not calculate]".to_string())
-                        .trim_end_matches('0')
-                ))?;
-                shell::println("\n==========================")?;
+            if self.json {
+                shell::println(serde_json::to_string(&summaries)?)?;
+            } else {
+                for summary in summaries {
+                    shell::println(format!("\n{summary}"))?;
+                }
             }
         }
         Ok(deployments)
@@ -616,7 +684,8 @@ impl ScriptArgs {
 #[derive(Clone)]
 enum SendTransactionKind<'a> {
     Unlocked(Address),
-    Raw(&'a WalletSigner),
+    /// Sign with the signer, which has to be confirmed on the named hardware wallet if set
+    Raw(&'a WalletSigner, Option<&'a str>),
 }
 
 /// Represents how to send _all_ transactions
@@ -624,7 +693,7 @@ enum SendTransactionsKind {
     /// Send via `eth_sendTransaction` and rely on the  `from` address being unlocked.
     Unlocked(HashSet<Address>),
     /// Send a signed transaction via `eth_sendRawTransaction`
-    Raw(HashMap<Address, WalletSigner>),
+    Raw(MultiWalletSigners),
 }
 
 impl SendTransactionsKind {
@@ -640,8 +709,9 @@ impl SendTransactionsKind {
                 Ok(SendTransactionKind::Unlocked(*addr))
             }
             SendTransactionsKind::Raw(wallets) => {
-                if let Some(wallet) = wallets.get(addr) {
-                    Ok(SendTransactionKind::Raw(wallet))
+                if let Some(wallet) = wallets.signers.get(addr) {
+                    let hardware = wallets.hardware.get(addr).map(String::as_str);
+                    Ok(SendTransactionKind::Raw(wallet, hardware))
                 } else {
                     bail!("No matching signer for {:?} found", addr)
                 }
@@ -653,7 +723,7 @@ impl SendTransactionsKind {
     fn signers_count(&self) -> usize {
         match self {
             SendTransactionsKind::Unlocked(addr) => addr.len(),
-            SendTransactionsKind::Raw(signers) => signers.len(),
+            SendTransactionsKind::Raw(wallets) => wallets.signers.len(),
         }
     }
 }
@@ -38,13 +38,12 @@ impl ScriptArgs {
                 // Sources are only required for the debugger, but it *might* mean that there's
                 // something wrong with the build and/or artifacts.
                 if let Some(source) = artifact.source_file() {
-                    sources.insert(
-                        source.id,
-                        source
-                            .ast
-                            .ok_or(eyre::eyre!("Source from artifact has no AST."))?
-                            .absolute_path,
-                    );
+                    // Yul sources don't have an AST
+                    let path = match source.ast {
+                        Some(ast) => ast.absolute_path,
+                        None => id.source.to_string_lossy().to_string(),
+                    };
+                    sources.insert(source.id, path);
                 } else {
                     warn!("source not found for artifact={:?}", id);
                 }
@@ -187,7 +186,8 @@ impl ScriptArgs {
         &mut self,
         script_config: &ScriptConfig,
     ) -> eyre::Result<(Project, ProjectCompileOutput)> {
-        let project = script_config.config.project()?;
+        let mut project = script_config.config.project()?;
+        self.opts.args.apply_standard_json(&mut project)?;
 
         let filters = self.opts.skip.clone().unwrap_or_default();
         // We received a valid file path.
@@ -197,6 +197,7 @@ impl ScriptArgs {
             let output = compile::compile_target_with_filter(
                 &target_contract,
                 &project,
+                &script_config.config,
                 self.opts.args.silent,
                 self.verify,
                 filters,
@@ -218,6 +219,7 @@ impl ScriptArgs {
             let output = compile::compile_target_with_filter(
                 &path,
                 &project,
+                &script_config.config,
                 self.opts.args.silent,
                 self.verify,
                 filters,
@@ -228,9 +230,9 @@ impl ScriptArgs {
 
         // We received `contract_name`, and need to find its file path.
         let output = if self.opts.args.silent {
-            compile::suppress_compile(&project)
+            compile::suppress_compile(&project, &script_config.config)
         } else {
-            compile::compile(&project, false, false)
+            compile::compile(&project, &script_config.config, false, false)
         }?;
         let cache =
             SolFilesCache::read_joined(&project.paths).wrap_err("Could not open compiler cache")?;
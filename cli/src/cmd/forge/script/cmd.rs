@@ -1,7 +1,10 @@
 use super::{sequence::ScriptSequence, *};
-use crate::cmd::{
-    forge::script::{multi::MultiChainSequence, verify::VerifyBundle},
-    LoadConfig,
+use crate::{
+    cmd::{
+        forge::script::{multi::MultiChainSequence, verify::VerifyBundle},
+        LoadConfig,
+    },
+    utils,
 };
 use ethers::{
     prelude::{Middleware, Signer},
@@ -19,12 +22,23 @@ impl ScriptArgs {
     pub async fn run_script(mut self) -> eyre::Result<()> {
         trace!(target: "script", "executing script command");
 
-        let (config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        let (mut config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+        if let Some(create2_deployer) = self.create2_deployer {
+            config.create2_deployer = create2_deployer;
+            config.create2_deployers.clear();
+        }
+        for env_file in &self.env_file {
+            utils::load_env_file(&mut config, env_file)?;
+        }
+        let gas_overrides = evm_opts.gas_overrides()?;
+        let inspector_plugins = evm_opts.inspector_plugins()?;
         let mut script_config = ScriptConfig {
             // dapptools compatibility
             sender_nonce: U256::one(),
             config,
             evm_opts,
+            gas_overrides,
+            inspector_plugins,
             ..Default::default()
         };
 
@@ -63,6 +77,12 @@ impl ScriptArgs {
         // Execute once with default sender.
         let sender = script_config.evm_opts.sender;
 
+        // Check the on chain preconditions of the script before anything is broadcast
+        if !self.resume && !self.skip_preflight && script_config.evm_opts.fork_url.is_some() {
+            self.preflight(&mut script_config, contract.clone(), sender, &predeploy_libraries)
+                .await?;
+        }
+
         // We need to execute the script even if just resuming, in case we need to collect private
         // keys from the execution.
         let mut result =
@@ -110,7 +130,7 @@ impl ScriptArgs {
         }
 
         verify.known_contracts = flatten_contracts(&highlevel_known_contracts, false);
-        self.check_contract_sizes(&result, &highlevel_known_contracts)?;
+        self.check_contract_sizes(&script_config.config, &result, &highlevel_known_contracts)?;
 
         self.handle_broadcastable_transactions(
             result,
@@ -0,0 +1,255 @@
+//! Gas usage summary and cost estimation of the simulated transactions
+
+use super::limits::describe;
+use crate::cmd::forge::script::transaction::TransactionWithMetadata;
+use ethers::{types::U256, utils::format_units};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The default CoinGecko compatible API the USD prices of native tokens are fetched from
+pub const DEFAULT_PRICE_ORACLE: &str = "https://api.coingecko.com/api/v3";
+
+/// The file in the foundry cache dir fetched prices are cached in
+pub const PRICE_CACHE_FILE: &str = "token-prices.json";
+
+/// How long a fetched price is reused
+const PRICE_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The gas and cost of a single simulated transaction
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionCost {
+    /// The position of the transaction in the script
+    pub index: usize,
+    pub description: String,
+    pub gas: U256,
+    /// The cost in wei at the estimated gas price
+    pub cost: U256,
+    pub cost_usd: Option<f64>,
+}
+
+/// The estimated gas usage and cost of all transactions sent to a chain
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainGasSummary {
+    pub chain: u64,
+    /// The symbol of the native token of the chain
+    pub native_token: String,
+    pub gas_price: U256,
+    pub transactions: Vec<TransactionCost>,
+    pub total_gas: U256,
+    /// The total cost in wei at the estimated gas price
+    pub total_cost: U256,
+    pub total_cost_usd: Option<f64>,
+    /// The USD price of the native token the costs were converted with
+    pub usd_price: Option<f64>,
+}
+
+impl ChainGasSummary {
+    /// Creates the summary of the `(index, transaction)`s sent to `chain`
+    pub fn new<'a>(
+        chain: u64,
+        gas_price: U256,
+        txs: impl IntoIterator<Item = (usize, &'a TransactionWithMetadata)>,
+    ) -> Self {
+        let transactions = txs
+            .into_iter()
+            .map(|(index, tx)| {
+                let gas = tx.typed_tx().gas().copied().unwrap_or_default();
+                TransactionCost {
+                    index,
+                    description: describe(tx),
+                    gas,
+                    cost: gas.saturating_mul(gas_price),
+                    cost_usd: None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let total_gas = transactions.iter().fold(U256::zero(), |acc, tx| acc + tx.gas);
+
+        let symbol = native_token(chain).map(|(symbol, _)| symbol).unwrap_or("ETH");
+        Self {
+            chain,
+            native_token: symbol.to_string(),
+            gas_price,
+            transactions,
+            total_gas,
+            total_cost: total_gas.saturating_mul(gas_price),
+            total_cost_usd: None,
+            usd_price: None,
+        }
+    }
+
+    /// Converts all costs to USD with the given price of the native token
+    pub fn set_usd_price(&mut self, price: f64) {
+        for tx in self.transactions.iter_mut() {
+            tx.cost_usd = Some(to_native(tx.cost) * price);
+        }
+        self.total_cost_usd = Some(to_native(self.total_cost) * price);
+        self.usd_price = Some(price);
+    }
+}
+
+impl fmt::Display for ChainGasSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cost = |wei: U256, usd: Option<f64>| {
+            let native = format_units(wei, 18)
+                .map(|units| units.trim_end_matches('0').trim_end_matches('.').to_string())
+                .unwrap_or_else(|_| "[Could not calculate]".to_string());
+            match usd {
+                Some(usd) => format!("{native} {} (${usd:.2})", self.native_token),
+                None => format!("{native} {}", self.native_token),
+            }
+        };
+
+        writeln!(f, "==========================")?;
+        writeln!(f, "\nChain {}", self.chain)?;
+        writeln!(
+            f,
+            "\nEstimated gas price: {} gwei",
+            format_units(self.gas_price, 9)
+                .unwrap_or_else(|_| "[Could not calculate]".to_string())
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+        )?;
+        if let Some(price) = self.usd_price {
+            writeln!(f, "\n{} price: ${price:.2}", self.native_token)?;
+        }
+        writeln!(f)?;
+        for tx in self.transactions.iter() {
+            let tx_cost = cost(tx.cost, tx.cost_usd);
+            writeln!(f, "#{} {}: {} gas, {tx_cost}", tx.index, tx.description, tx.gas)?;
+        }
+        writeln!(f, "\nEstimated total gas used for script: {}", self.total_gas)?;
+        writeln!(
+            f,
+            "\nEstimated amount required: {}",
+            cost(self.total_cost, self.total_cost_usd)
+        )?;
+        write!(f, "\n==========================")
+    }
+}
+
+/// Returns the symbol and the CoinGecko id of the native token of the chain
+pub fn native_token(chain: u64) -> Option<(&'static str, &'static str)> {
+    let token = match chain {
+        // mainnet, goerli, sepolia, optimism, arbitrum and base are all paid in ETH
+        1 | 5 | 11155111 | 10 | 420 | 42161 | 421613 | 8453 | 84531 => ("ETH", "ethereum"),
+        137 | 80001 => ("MATIC", "matic-network"),
+        56 | 97 => ("BNB", "binancecoin"),
+        43114 | 43113 => ("AVAX", "avalanche-2"),
+        250 | 4002 => ("FTM", "fantom"),
+        100 => ("xDAI", "xdai"),
+        _ => return None,
+    };
+    Some(token)
+}
+
+/// Fetches the USD prices of native tokens from a CoinGecko compatible API
+///
+/// Prices are cached on disk for a few minutes, so consecutive runs don't hit the rate limits of
+/// the public API.
+#[derive(Debug, Clone)]
+pub struct PriceOracle {
+    url: String,
+    cache: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CachedPrice {
+    usd: f64,
+    timestamp: u64,
+}
+
+impl PriceOracle {
+    pub fn new(url: impl Into<String>, cache: Option<PathBuf>) -> Self {
+        Self { url: url.into(), cache }
+    }
+
+    /// Returns the USD price of the native token of the chain
+    pub async fn usd_price(&self, chain: u64) -> eyre::Result<f64> {
+        let (symbol, id) = native_token(chain)
+            .ok_or_else(|| eyre::eyre!("The native token of chain {chain} is not known"))?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut prices: BTreeMap<String, CachedPrice> = self
+            .cache
+            .as_ref()
+            .and_then(|path| foundry_common::fs::read_json_file(path).ok())
+            .unwrap_or_default();
+        if let Some(cached) = prices.get(id) {
+            if now.saturating_sub(cached.timestamp) < PRICE_CACHE_TTL.as_secs() {
+                return Ok(cached.usd)
+            }
+        }
+
+        let url =
+            format!("{}/simple/price?ids={id}&vs_currencies=usd", self.url.trim_end_matches('/'));
+        let response: Value = reqwest::get(&url)
+            .await?
+            .error_for_status()
+            .wrap_err_with(|| format!("Failed to fetch the price of {symbol}"))?
+            .json()
+            .await?;
+        let usd = response[id]["usd"]
+            .as_f64()
+            .ok_or_else(|| eyre::eyre!("The price oracle returned no USD price for {symbol}"))?;
+
+        if let Some(path) = self.cache.as_ref() {
+            prices.insert(id.to_string(), CachedPrice { usd, timestamp: now });
+            if let Some(parent) = path.parent() {
+                let _ = foundry_common::fs::create_dir_all(parent);
+            }
+            if let Err(err) = foundry_common::fs::write_json_file(path, &prices) {
+                tracing::trace!(?err, "failed to cache token prices");
+            }
+        }
+        Ok(usd)
+    }
+}
+
+/// Converts an amount of wei to the native token
+fn to_native(wei: U256) -> f64 {
+    format_units(wei, 18).ok().and_then(|units| units.parse().ok()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+    use forge::CallKind;
+
+    #[test]
+    fn can_summarize_gas_costs() {
+        let mut deploy = TransactionWithMetadata::from_typed_transaction(TypedTransaction::Legacy(
+            TransactionRequest::new().gas(1_000_000u64),
+        ));
+        deploy.opcode = CallKind::Create;
+        deploy.contract_name = Some("Token".to_string());
+        let call = TransactionWithMetadata::from_typed_transaction(TypedTransaction::Legacy(
+            TransactionRequest::new().gas(50_000u64),
+        ));
+
+        // 20 gwei
+        let mut summary =
+            ChainGasSummary::new(137, 20_000_000_000u64.into(), [(0, &deploy), (1, &call)]);
+        assert_eq!(summary.native_token, "MATIC");
+        assert_eq!(summary.total_gas, 1_050_000u64.into());
+        assert_eq!(summary.total_cost, U256::from(21_000_000_000_000_000u64));
+
+        summary.set_usd_price(2.0);
+        assert_eq!(summary.transactions[0].cost_usd, Some(0.04));
+        assert_eq!(summary.total_cost_usd, Some(0.042));
+
+        let out = summary.to_string();
+        assert!(out.contains("#0 deploying `Token`: 1000000 gas, 0.02 MATIC ($0.04)"));
+        assert!(out.contains("Estimated amount required: 0.021 MATIC ($0.04)"));
+    }
+}
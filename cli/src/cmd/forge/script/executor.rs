@@ -4,6 +4,7 @@ use crate::{
         ensure_clean_constructor,
         forge::script::{
             artifacts::ArtifactInfo,
+            limits::ChainLimits,
             runner::SimulationStage,
             transaction::{AdditionalContract, TransactionWithMetadata},
         },
@@ -18,7 +19,7 @@ use ethers::{
 };
 use forge::{
     executor::{inspector::CheatsConfig, Backend, ExecutorBuilder},
-    trace::{CallTraceDecoder, Traces},
+    trace::{CallTraceDecoder, RawOrDecodedReturnData, Traces},
     CallKind,
 };
 use foundry_common::{shell, RpcUrl};
@@ -30,6 +31,9 @@ use tracing::trace;
 /// Helper alias type for the processed result of a runner onchain simulation.
 type RunnerResult = (Option<TransactionWithMetadata>, Traces);
 
+/// The function of a script that checks the preconditions of the broadcast
+pub const PREFLIGHT_FUNCTION: &str = "preflight";
+
 impl ScriptArgs {
     /// Locally deploys and executes the contract method that will collect all broadcastable
     /// transactions.
@@ -89,6 +93,78 @@ impl ScriptArgs {
         Ok(result)
     }
 
+    /// Runs the `preflight()` function of the script against a fresh fork of the target chain.
+    ///
+    /// Fails if `preflight()` reverts or any precondition of an `assertOnChain*` cheatcode doesn't
+    /// hold, so nothing is broadcast if the chain isn't in the expected state.
+    pub async fn preflight(
+        &self,
+        script_config: &mut ScriptConfig,
+        contract: CompactContractBytecode,
+        sender: Address,
+        predeploy_libraries: &[ethers::types::Bytes],
+    ) -> eyre::Result<()> {
+        let CompactContractBytecode { abi, bytecode, .. } = contract;
+
+        let abi = abi.expect("no ABI for contract");
+        let preflight = match abi
+            .functions()
+            .find(|func| func.name == PREFLIGHT_FUNCTION && func.inputs.is_empty())
+        {
+            Some(func) => func.clone(),
+            None => return Ok(()),
+        };
+        let bytecode = bytecode.expect("no bytecode for contract").object.into_bytes().unwrap();
+
+        trace!(target: "script", "executing preflight checks");
+
+        let mut runner =
+            self.prepare_runner(script_config, sender, SimulationStage::Preflight).await;
+        if let Some(cheatcodes) = runner.executor.inspector_config_mut().cheatcodes.as_mut() {
+            cheatcodes.preflight = true;
+        }
+        let (address, setup) = runner.setup(
+            predeploy_libraries,
+            bytecode,
+            needs_setup(&abi),
+            script_config.sender_nonce,
+            true,
+            false,
+        )?;
+        if !setup.success {
+            eyre::bail!("Preflight checks failed: setUp() reverted.")
+        }
+
+        let result = runner.executor.call_raw(
+            sender,
+            address,
+            preflight.short_signature().to_vec().into(),
+            U256::zero(),
+        )?;
+
+        let mut failures = result
+            .cheatcodes
+            .map(|cheats| cheats.onchain_assertion_failures)
+            .unwrap_or_default()
+            .iter()
+            .map(|failure| failure.to_string())
+            .collect::<Vec<_>>();
+        if result.reverted {
+            let reason = decode::decode_revert(&result.result, Some(&abi), Some(result.exit_reason))
+                .unwrap_or_else(|_| format!("{:?}", result.exit_reason));
+            failures.push(format!("{PREFLIGHT_FUNCTION}() reverted: {reason}"));
+        }
+
+        if failures.is_empty() {
+            shell::println("\n## Preflight checks passed.")?;
+            return Ok(())
+        }
+        eyre::bail!(
+            "Preflight checks failed, no transactions were broadcast:\n{}",
+            failures.iter().map(|failure| format!("  - {failure}")).collect::<Vec<_>>().join("\n")
+        )
+    }
+
     /// Simulates onchain state by executing a list of transactions locally and persisting their
     /// state. Returns the transactions and any CREATE2 contract address created.
     pub async fn onchain_simulation(
@@ -133,6 +209,8 @@ impl ScriptArgs {
             })
             .collect();
 
+        let limits = self.fetch_chain_limits(script_config).await?;
+
         let mut final_txs = VecDeque::new();
 
         // Executes all transactions from the different forks concurrently.
@@ -192,6 +270,9 @@ impl ScriptArgs {
                         println!("Gas limit was set in script to {:}", tx.gas.unwrap());
                     }
 
+                    let create2_deployer = script_config
+                        .config
+                        .create2_deployer_for(runner.executor.env().cfg.chain_id.as_u64());
                     let tx = TransactionWithMetadata::new(
                         tx.into(),
                         transaction.rpc,
@@ -200,6 +281,7 @@ impl ScriptArgs {
                         decoder,
                         created_contracts,
                         is_fixed_gas_limit,
+                        create2_deployer,
                     )?;
 
                     Ok((Some(tx), result.traces))
@@ -210,12 +292,19 @@ impl ScriptArgs {
             .collect::<Vec<_>>();
 
         let mut abort = false;
+        let mut limit_diagnostics = vec![];
         for res in join_all(futs).await {
             // type hint
             let res: eyre::Result<RunnerResult> = res;
 
             let (tx, mut traces) = res?;
 
+            if let Some(ref tx) = tx {
+                let rpc = tx.rpc.as_ref().expect("to have been filled already.");
+                let deployed = deployed_code_sizes(&traces, decoder);
+                limit_diagnostics.extend(limits[rpc].check(final_txs.len(), tx, &deployed));
+            }
+
             // Transaction will be `None`, if execution didn't pass.
             if tx.is_none() || script_config.evm_opts.verbosity > 3 {
                 // Identify all contracts created during the call.
@@ -242,9 +331,32 @@ impl ScriptArgs {
             eyre::bail!("Simulated execution failed.")
         }
 
+        if !limit_diagnostics.is_empty() {
+            for diagnostic in &limit_diagnostics {
+                shell::println(format!("{}", Paint::red(diagnostic)))?;
+            }
+            eyre::bail!(
+                "{} transaction(s) would not fit into a block of the target chain.",
+                limit_diagnostics.len()
+            )
+        }
+
         Ok(final_txs)
     }
 
+    /// Fetches the limits of every chain the transactions are sent to, see [ChainLimits].
+    async fn fetch_chain_limits(
+        &self,
+        script_config: &ScriptConfig,
+    ) -> eyre::Result<HashMap<RpcUrl, ChainLimits>> {
+        let code_size_limit = script_config.config.code_size_limit;
+        let futs = script_config.total_rpcs.iter().map(|rpc| async move {
+            ChainLimits::fetch(rpc, code_size_limit).await.map(|limits| (rpc.clone(), limits))
+        });
+
+        join_all(futs).await.into_iter().collect()
+    }
+
     /// Build the multiple runners from different forks.
     async fn build_runners(
         &self,
@@ -307,15 +419,38 @@ impl ScriptArgs {
             .with_config(env)
             .with_spec(utils::evm_spec(&script_config.config.evm_version))
             .with_gas_limit(script_config.evm_opts.gas_limit())
+            .with_gas_overrides(script_config.gas_overrides.clone())
+            .with_inspector_plugins(script_config.inspector_plugins.iter().cloned())
             // We need it enabled to decode contract names: local or external.
             .set_tracing(true);
 
-        if let SimulationStage::Local = stage {
+        // The preflight checks need cheatcodes for the `assertOnChain*` preconditions
+        if matches!(stage, SimulationStage::Local | SimulationStage::Preflight) {
             builder = builder
-                .set_debugger(self.debug)
+                .set_debugger(self.debug && matches!(stage, SimulationStage::Local))
                 .with_cheatcodes(CheatsConfig::new(&script_config.config, &script_config.evm_opts));
         }
 
         ScriptRunner::new(builder.build(db), script_config.evm_opts.initial_balance, sender)
     }
 }
+
+/// Returns the name and runtime code size of every contract created in the traces
+fn deployed_code_sizes(traces: &Traces, decoder: &CallTraceDecoder) -> Vec<(String, usize)> {
+    traces
+        .iter()
+        .flat_map(|(_, traces)| traces.arena.iter())
+        .filter(|node| matches!(node.kind(), CallKind::Create | CallKind::Create2))
+        .filter_map(|node| match node.trace.output {
+            RawOrDecodedReturnData::Raw(ref code) => {
+                let name = decoder
+                    .contracts
+                    .get(&node.trace.address)
+                    .map(|id| get_contract_name(id).to_string())
+                    .unwrap_or_else(|| format!("{:?}", node.trace.address));
+                Some((name, code.len()))
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,114 @@
+//! Checks that simulated transactions fit into the blocks of the chains they are sent to
+
+use super::TransactionWithMetadata;
+use ethers::{
+    prelude::Middleware,
+    types::{BlockNumber, U256},
+};
+use eyre::WrapErr;
+use forge::CallKind;
+use foundry_common::{get_http_provider, CONTRACT_MAX_SIZE};
+
+/// The limits a transaction has to respect to be included in a block of a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainLimits {
+    /// The gas limit of the latest block
+    pub block_gas_limit: U256,
+    /// The max size of the runtime code of a deployed contract
+    pub max_code_size: usize,
+}
+
+impl ChainLimits {
+    /// Fetches the current block gas limit of the chain behind `rpc`
+    ///
+    /// The max code size can't be requested from a node, so this is `code_size_limit` if set,
+    /// or the EIP-170 limit otherwise.
+    pub async fn fetch(rpc: &str, code_size_limit: Option<usize>) -> eyre::Result<Self> {
+        let block = get_http_provider(rpc)
+            .get_block(BlockNumber::Latest)
+            .await
+            .wrap_err_with(|| format!("Failed to get the latest block of {rpc}"))?
+            .ok_or_else(|| eyre::eyre!("The latest block of {rpc} was not found"))?;
+
+        Ok(Self {
+            block_gas_limit: block.gas_limit,
+            max_code_size: code_size_limit.unwrap_or(CONTRACT_MAX_SIZE),
+        })
+    }
+
+    /// Returns a diagnostic for every limit the transaction at `index` exceeds
+    ///
+    /// `deployed` holds the name and runtime code size of every contract the transaction
+    /// creates.
+    pub fn check(
+        &self,
+        index: usize,
+        tx: &TransactionWithMetadata,
+        deployed: &[(String, usize)],
+    ) -> Vec<String> {
+        let mut diagnostics = vec![];
+        let target = describe(tx);
+
+        if let Some(gas) = tx.typed_tx().gas() {
+            if *gas > self.block_gas_limit {
+                diagnostics.push(format!(
+                    "Transaction #{index} ({target}) needs {gas} gas, which is above the block gas limit of {}. Consider splitting it into multiple transactions.",
+                    self.block_gas_limit
+                ));
+            }
+        }
+
+        for (name, size) in deployed {
+            if *size > self.max_code_size {
+                diagnostics.push(format!(
+                    "Transaction #{index} ({target}) deploys `{name}` with {size} bytes of runtime code, which is above the max code size of {} bytes. Consider splitting the contract, for example by moving code into libraries.",
+                    self.max_code_size
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Returns a short description of what the transaction does
+pub(super) fn describe(tx: &TransactionWithMetadata) -> String {
+    let name = tx.contract_name.as_deref().filter(|name| !name.is_empty());
+    match (tx.opcode, name, tx.function.as_deref()) {
+        (CallKind::Create | CallKind::Create2, Some(name), _) => {
+            format!("deploying `{name}`")
+        }
+        (_, Some(name), Some(function)) if !function.is_empty() => {
+            format!("calling `{name}::{function}`")
+        }
+        (_, _, _) => match tx.contract_address {
+            Some(address) => format!("to {address:?}"),
+            None => "unknown target".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{transaction::eip2718::TypedTransaction, TransactionRequest};
+
+    #[test]
+    fn can_check_chain_limits() {
+        let limits =
+            ChainLimits { block_gas_limit: 30_000_000u64.into(), max_code_size: CONTRACT_MAX_SIZE };
+        let mut tx = TransactionWithMetadata::from_typed_transaction(TypedTransaction::Legacy(
+            TransactionRequest::new().gas(1_000_000u64),
+        ));
+        tx.opcode = CallKind::Create;
+        tx.contract_name = Some("Token".to_string());
+
+        assert!(limits.check(0, &tx, &[("Token".to_string(), 1_000)]).is_empty());
+
+        tx.transaction.set_gas(31_000_000u64);
+        let diagnostics = limits.check(3, &tx, &[("Token".to_string(), CONTRACT_MAX_SIZE + 1)]);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].starts_with("Transaction #3 (deploying `Token`) needs 31000000 gas"));
+        assert!(diagnostics[1].contains("deploys `Token` with 24577 bytes"));
+    }
+}
@@ -1,11 +1,12 @@
 //! script command
-use crate::{cmd::forge::build::BuildArgs, opts::MultiWallet, utils::parse_ether_value};
+use crate::{
+    cmd::forge::{build::BuildArgs, export::ExportFormat},
+    opts::MultiWallet,
+    utils::parse_ether_value,
+};
 use cast::{
     decode,
-    executor::inspector::{
-        cheatcodes::{util::BroadcastableTransactions, BroadcastableTransaction},
-        DEFAULT_CREATE2_DEPLOYER,
-    },
+    executor::inspector::cheatcodes::{util::BroadcastableTransactions, BroadcastableTransaction},
 };
 use clap::{Parser, ValueHint};
 use dialoguer::Confirm;
@@ -26,7 +27,11 @@ use eyre::{ContextCompat, WrapErr};
 use forge::{
     debug::DebugArena,
     decode::decode_console_logs,
-    executor::{opts::EvmOpts, Backend},
+    executor::{
+        inspector::{GasOverrides, InspectorPluginFactory},
+        opts::EvmOpts,
+        Backend,
+    },
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
         CallTraceDecoder, CallTraceDecoderBuilder, RawOrDecodedCall, RawOrDecodedReturnData,
@@ -43,6 +48,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap, HashSet, VecDeque},
     path::PathBuf,
+    sync::Arc,
 };
 use tracing::log::trace;
 use yansi::Paint;
@@ -63,8 +69,11 @@ use ui::{TUIExitReason, Tui, Ui};
 
 mod artifacts;
 mod cmd;
+mod cost;
 mod executor;
+mod limits;
 mod multi;
+mod plan;
 mod providers;
 mod receipts;
 mod sequence;
@@ -117,6 +126,13 @@ pub struct ScriptArgs {
     #[clap(long, help = "Skips on-chain simulation")]
     pub skip_simulation: bool,
 
+    #[clap(
+        long,
+        help = "Skips the preflight() checks of the script.",
+        long_help = "Skips the preflight() checks of the script. By default, the preflight() function of the script is run against a fresh fork of the target chain before anything is simulated or broadcast, and any failed assertOnChain* precondition aborts the script."
+    )]
+    pub skip_preflight: bool,
+
     #[clap(
         long,
         short,
@@ -192,6 +208,50 @@ pub struct ScriptArgs {
     )]
     pub with_gas_price: Option<U256>,
 
+    /// Estimates the costs of the simulated transactions in USD as well.
+    ///
+    /// The price of the native token of every chain is fetched from `--price-oracle` and cached
+    /// for a few minutes.
+    #[clap(long)]
+    pub usd: bool,
+
+    /// The CoinGecko compatible API the USD prices of native tokens are fetched from.
+    #[clap(
+        long,
+        env = "FORGE_PRICE_ORACLE",
+        value_name = "URL",
+        default_value = cost::DEFAULT_PRICE_ORACLE
+    )]
+    pub price_oracle: String,
+
+    /// The CREATE2 factory to deploy contracts created with a salt through.
+    ///
+    /// Overrides `create2_deployer` and `create2_deployers` of the project's configuration file.
+    #[clap(long, value_name = "ADDRESS")]
+    pub create2_deployer: Option<Address>,
+
+    /// Writes the planned transactions to the given file.
+    ///
+    /// The plan contains the ordered transactions with their decoded calldata, value and the
+    /// addresses of created contracts, but no nonces or gas.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub plan_out: Option<PathBuf>,
+
+    /// Shows the changes of the planned transactions compared to a plan written with
+    /// `--plan-out`.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    pub plan_diff: Option<PathBuf>,
+
+    /// Exports the deployments of all broadcasted runs to `deployments` in the given formats
+    /// after broadcasting, see `forge export-deployments`.
+    #[clap(long, value_enum, value_name = "FORMAT")]
+    pub export: Vec<ExportFormat>,
+
+    /// Makes the variables of the dotenv file visible to the `env*` cheatcodes, in addition to
+    /// the `env` of the profile, without exporting them to the shell.
+    #[clap(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub env_file: Vec<PathBuf>,
+
     #[clap(flatten)]
     pub retry: RetryArgs,
 }
@@ -289,7 +349,11 @@ impl ScriptArgs {
 
                 if should_include {
                     decoder.decode(trace).await;
-                    shell::println(format!("{trace}"))?;
+                    if verbosity >= 5 {
+                        shell::println(format!("{trace:#}"))?;
+                    } else {
+                        shell::println(format!("{trace}"))?;
+                    }
                 }
             }
             shell::println(String::new())?;
@@ -507,6 +571,7 @@ impl ScriptArgs {
     /// the user.
     fn check_contract_sizes(
         &self,
+        config: &Config,
         result: &ScriptResult,
         known_contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
     ) -> eyre::Result<()> {
@@ -567,7 +632,7 @@ impl ScriptArgs {
 
             // Find if it's a CREATE or CREATE2. Otherwise, skip transaction.
             if let Some(NameOrAddress::Address(to)) = to {
-                if *to == DEFAULT_CREATE2_DEPLOYER {
+                if config.is_create2_deployer(to) {
                     // Size of the salt prefix.
                     offset = 32;
                 }
@@ -661,6 +726,10 @@ pub struct ScriptConfig {
     pub total_rpcs: HashSet<RpcUrl>,
     /// If true, one of the transactions did not have a rpc
     pub missing_rpc: bool,
+    /// The custom gas schedule
+    pub gas_overrides: GasOverrides,
+    /// The inspectors provided by downstream crates
+    pub inspector_plugins: Vec<Arc<dyn InspectorPluginFactory>>,
 }
 
 impl ScriptConfig {
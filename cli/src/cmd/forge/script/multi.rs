@@ -98,7 +98,8 @@ impl MultiChainSequence {
         ethers::solc::utils::read_json_file(path).wrap_err("Multi-chain deployment not found.")
     }
 
-    /// Saves the transactions as file if it's a standalone deployment.
+    /// Saves the multi chain deployment, as well as the sequence of every chain in its own
+    /// broadcast folder.
     pub fn save(&mut self) -> eyre::Result<()> {
         self.timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
         let path = self.path.to_string_lossy();
@@ -115,6 +116,11 @@ impl MultiChainSequence {
 
         println!("\nTransactions saved to: {path}\n");
 
+        // ../<chain_id>/run-latest.json
+        for sequence in self.deployments.iter_mut() {
+            sequence.write()?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,197 @@
+//! Serializable plans of the transactions a script would broadcast
+
+use super::{sequence::ScriptSequence, TransactionWithMetadata};
+use cast::CallKind;
+use ethers::{
+    types::{Address, Bytes, NameOrAddress, H256, U256},
+    utils::keccak256,
+};
+use foundry_common::fs;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::{fmt, io::BufWriter, path::Path};
+use yansi::Paint;
+
+/// The ordered transactions a script would broadcast
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptPlan {
+    pub transactions: Vec<PlannedTransaction>,
+}
+
+/// A transaction of a [ScriptPlan], without the fields that change between runs like nonces and
+/// gas
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedTransaction {
+    pub chain: u64,
+    pub transaction_type: CallKind,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub contract_name: Option<String>,
+    /// The called contract, or the address of the created contract
+    pub contract_address: Option<Address>,
+    pub function: Option<String>,
+    pub arguments: Vec<String>,
+    pub value: U256,
+    /// The raw calldata, only set if the call could not be decoded
+    pub calldata: Option<Bytes>,
+    /// The hash of the init code of created contracts
+    pub init_code_hash: Option<H256>,
+}
+
+impl PlannedTransaction {
+    pub fn new(chain: u64, tx: &TransactionWithMetadata) -> Self {
+        let is_create = matches!(tx.opcode, CallKind::Create | CallKind::Create2);
+        let data = tx.transaction.data().cloned().unwrap_or_default();
+        let init_code_hash = is_create.then(|| keccak256(&data).into());
+        Self {
+            chain,
+            transaction_type: tx.opcode,
+            from: tx.transaction.from().copied(),
+            to: match tx.transaction.to() {
+                Some(NameOrAddress::Address(to)) => Some(*to),
+                _ => None,
+            },
+            contract_name: tx.contract_name.clone().filter(|name| !name.is_empty()),
+            contract_address: tx.contract_address,
+            function: tx.function.clone().filter(|function| !function.is_empty()),
+            arguments: tx.arguments.clone().unwrap_or_default(),
+            value: tx.transaction.value().copied().unwrap_or_default(),
+            calldata: (!is_create && tx.function.is_none() && !data.is_empty()).then_some(data),
+            init_code_hash,
+        }
+    }
+}
+
+impl fmt::Display for PlannedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self.transaction_type).to_uppercase())?;
+        if let Some(ref name) = self.contract_name {
+            write!(f, " {name}")?;
+        }
+        if let Some(ref function) = self.function {
+            write!(f, "::{function}")?;
+        }
+        writeln!(f, " (chain {})", self.chain)?;
+
+        let addr = |addr: Option<Address>| addr.map(|addr| format!("{addr:?}"));
+        let fields = [
+            ("from", addr(self.from)),
+            ("to", addr(self.to)),
+            ("contract", addr(self.contract_address)),
+            ("value", Some(self.value.to_string())),
+            ("calldata", self.calldata.as_ref().map(|data| data.to_string())),
+            ("init code hash", self.init_code_hash.map(|hash| format!("{hash:?}"))),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                writeln!(f, "    {name}: {value}")?;
+            }
+        }
+        for (idx, arg) in self.arguments.iter().enumerate() {
+            writeln!(f, "    arg {idx}: {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ScriptPlan {
+    /// Creates the plan of all transactions of the sequences, in order
+    pub fn new(sequences: &[ScriptSequence]) -> Self {
+        let transactions = sequences
+            .iter()
+            .flat_map(|sequence| {
+                sequence.transactions.iter().map(|tx| PlannedTransaction::new(sequence.chain, tx))
+            })
+            .collect();
+        Self { transactions }
+    }
+
+    /// Loads a plan written by [ScriptPlan::save]
+    pub fn load(path: &Path) -> eyre::Result<Self> {
+        Ok(fs::read_json_file(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> eyre::Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(fs::create_file(path)?), self)?;
+        Ok(())
+    }
+
+    /// Returns a human-readable diff from the `old` plan to this plan, `None` if they're equal
+    pub fn diff(&self, old: &ScriptPlan) -> Option<String> {
+        if self == old {
+            return None
+        }
+        let (old, new) = (old.to_string(), self.to_string());
+        let diff = TextDiff::from_lines(&old, &new);
+
+        let mut out = String::new();
+        for (idx, group) in diff.grouped_ops(3).iter().enumerate() {
+            if idx > 0 {
+                out.push_str(&format!("{:-^1$}\n", "-", 80));
+            }
+            for op in group {
+                for change in diff.iter_changes(op) {
+                    let line = match change.tag() {
+                        ChangeTag::Delete => Paint::red(format!("-{change}")).to_string(),
+                        ChangeTag::Insert => Paint::green(format!("+{change}")).to_string(),
+                        ChangeTag::Equal => format!(" {change}"),
+                    };
+                    out.push_str(&line);
+                }
+            }
+        }
+        Some(out)
+    }
+}
+
+impl fmt::Display for ScriptPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, tx) in self.transactions.iter().enumerate() {
+            write!(f, "#{idx} {tx}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(function: &str, arguments: &[&str]) -> PlannedTransaction {
+        PlannedTransaction {
+            chain: 1,
+            transaction_type: CallKind::Call,
+            from: Some(Address::repeat_byte(1)),
+            to: Some(Address::repeat_byte(2)),
+            contract_name: Some("Governor".to_string()),
+            contract_address: Some(Address::repeat_byte(2)),
+            function: Some(function.to_string()),
+            arguments: arguments.iter().map(|arg| arg.to_string()).collect(),
+            value: U256::zero(),
+            calldata: None,
+            init_code_hash: None,
+        }
+    }
+
+    #[test]
+    fn can_diff_plans() {
+        yansi::Paint::disable();
+
+        let old = ScriptPlan {
+            transactions: vec![call("setFee(uint256)", &["100"]), call("pause()", &[])],
+        };
+        assert_eq!(old.diff(&old), None);
+
+        let plan = ScriptPlan {
+            transactions: vec![call("setFee(uint256)", &["200"]), call("pause()", &[])],
+        };
+        let diff = plan.diff(&old).unwrap();
+        assert!(diff.contains("-    arg 0: 100\n"));
+        assert!(diff.contains("+    arg 0: 200\n"));
+        assert!(diff.contains(" #1 CALL Governor::pause() (chain 1)\n"));
+
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(serde_json::from_str::<ScriptPlan>(&json).unwrap(), plan);
+    }
+}
@@ -129,6 +129,7 @@ pub async fn clear_pendings(
 
 /// Checks the status of a txhash by first polling for a receipt, then for
 /// mempool inclusion. Returns the tx hash, and a status
+#[tracing::instrument(name = "wait-for-receipt", skip(provider))]
 async fn check_tx_status(
     provider: &RetryProvider,
     hash: TxHash,
@@ -12,6 +12,8 @@ use tracing::log::trace;
 /// Represents which simulation stage is the script execution at.
 pub enum SimulationStage {
     Local,
+    /// The `preflight()` checks against a fresh fork of the target chain
+    Preflight,
     OnChain,
 }
 
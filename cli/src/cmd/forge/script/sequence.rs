@@ -5,7 +5,10 @@ use crate::cmd::forge::{
         transaction::{wrapper, AdditionalContract, TransactionWithMetadata},
         verify::VerifyBundle,
     },
-    verify::provider::VerificationProviderType,
+    verify::{
+        provider::VerificationProviderType,
+        queue::{QueuedVerification, VerificationQueue},
+    },
 };
 use ethers::{
     abi::Address,
@@ -40,7 +43,8 @@ pub struct ScriptSequence {
     pub returns: HashMap<String, NestedValue>,
     pub timestamp: u64,
     pub chain: u64,
-    /// If `True`, the sequence belongs to a `MultiChainSequence` and won't save to disk as usual.
+    /// If `True`, the sequence belongs to a `MultiChainSequence` and will only be saved to disk
+    /// as part of it.
     pub multi: bool,
     pub commit: Option<String>,
 }
@@ -57,13 +61,7 @@ impl ScriptSequence {
     ) -> eyre::Result<Self> {
         let chain = config.chain_id.unwrap_or_default().id();
 
-        let path = ScriptSequence::get_path(
-            &config.broadcast,
-            sig,
-            target,
-            chain,
-            broadcasted && !is_multi,
-        )?;
+        let path = ScriptSequence::get_path(&config.broadcast, sig, target, chain, broadcasted)?;
         let commit = get_commit_hash(&config.__root.0);
 
         Ok(ScriptSequence {
@@ -98,7 +96,19 @@ impl ScriptSequence {
 
     /// Saves the transactions as file if it's a standalone deployment.
     pub fn save(&mut self) -> eyre::Result<()> {
-        if !self.multi && !self.transactions.is_empty() {
+        if !self.multi {
+            self.write()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the transactions and receipts to the chain specific broadcast folder.
+    ///
+    /// Sequences of a `MultiChainSequence` are written through this as well, so every chain of a
+    /// multi chain deployment gets its own receipts file.
+    pub fn write(&mut self) -> eyre::Result<()> {
+        if !self.transactions.is_empty() {
             self.timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
             let path = self.path.to_string_lossy();
             //../run-latest.json
@@ -188,8 +198,10 @@ impl ScriptSequence {
         {
             trace!(target: "script", "prepare future verifications");
 
-            let mut future_verifications = Vec::with_capacity(self.receipts.len());
+            let mut queue = VerificationQueue::load(config);
+            let mut queued = Vec::with_capacity(self.receipts.len());
             let mut unverifiable_contracts = vec![];
+            let chain = self.chain.into();
 
             // Make sure the receipts have the right order first.
             self.sort_receipts();
@@ -208,7 +220,11 @@ impl ScriptSequence {
                     (receipt.contract_address, tx.typed_tx().data())
                 {
                     match verify.get_verify_args(address, offset, &data.0, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
+                        Some(args) => queued.push(queue.push(QueuedVerification::new(
+                            &args,
+                            chain,
+                            &config.__root.0,
+                        ))),
                         None => unverifiable_contracts.push(address),
                     };
                 }
@@ -216,21 +232,26 @@ impl ScriptSequence {
                 // Verify potential contracts created during the transaction execution
                 for AdditionalContract { address, init_code, .. } in &tx.additional_contracts {
                     match verify.get_verify_args(*address, 0, init_code, &self.libraries) {
-                        Some(verify) => future_verifications.push(verify.run()),
+                        Some(args) => queued.push(queue.push(QueuedVerification::new(
+                            &args,
+                            chain,
+                            &config.__root.0,
+                        ))),
                         None => unverifiable_contracts.push(*address),
                     };
                 }
             }
 
-            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", future_verifications.len(), unverifiable_contracts.len());
+            trace!(target: "script", "collected {} verification jobs and {} unverifiable contracts", queued.len(), unverifiable_contracts.len());
 
+            let retry = verify.retry;
             self.check_unverified(unverifiable_contracts, verify);
 
-            let num_verifications = future_verifications.len();
+            // persist the queue before the first submission, so it can be resumed
+            queue.save()?;
+            let num_verifications = queued.len();
             println!("##\nStart verification for ({num_verifications}) contracts",);
-            for verification in future_verifications {
-                verification.await?;
-            }
+            queue.process(config, queued, retry).await?;
 
             println!("All ({num_verifications}) contracts were verified!");
         }
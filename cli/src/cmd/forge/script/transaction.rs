@@ -1,5 +1,5 @@
 use crate::cmd::forge::script::{artifacts::ArtifactInfo, ScriptResult};
-use cast::{executor::inspector::DEFAULT_CREATE2_DEPLOYER, trace::CallTraceDecoder, CallKind};
+use cast::{trace::CallTraceDecoder, CallKind};
 use ethers::{
     abi,
     abi::Address,
@@ -68,12 +68,18 @@ impl TransactionWithMetadata {
         decoder: &CallTraceDecoder,
         additional_contracts: Vec<AdditionalContract>,
         is_fixed_gas_limit: bool,
+        create2_deployer: Address,
     ) -> eyre::Result<Self> {
         let mut metadata = Self { transaction, rpc, is_fixed_gas_limit, ..Default::default() };
 
         // Specify if any contract was directly created with this transaction
         if let Some(NameOrAddress::Address(to)) = metadata.transaction.to().cloned() {
-            if to == DEFAULT_CREATE2_DEPLOYER {
+            if to == create2_deployer {
+                if result.returned.len() != 20 {
+                    eyre::bail!(
+                        "CREATE2 deployer {create2_deployer:?} did not return a contract address, it is likely not deployed on this chain. Deploy it with `cast create2-deployer` or configure a different one with `--create2-deployer`"
+                    )
+                }
                 metadata.set_create(
                     true,
                     Address::from_slice(&result.returned),
@@ -273,7 +273,7 @@ impl FromStr for SnapshotEntry {
 }
 
 /// Reads a list of snapshot entries from a snapshot file
-fn read_snapshot(path: impl AsRef<Path>) -> eyre::Result<Vec<SnapshotEntry>> {
+pub(crate) fn read_snapshot(path: impl AsRef<Path>) -> eyre::Result<Vec<SnapshotEntry>> {
     let path = path.as_ref();
     let mut entries = Vec::new();
     for line in io::BufReader::new(
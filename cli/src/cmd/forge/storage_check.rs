@@ -0,0 +1,553 @@
+//! storage-check command
+
+use crate::cmd::Cmd;
+use clap::{Parser, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers::{
+    solc::artifacts::{Storage, StorageLayout, StorageType},
+    types::U256,
+};
+use eyre::WrapErr;
+use foundry_common::fs;
+use serde_json::Value;
+use std::{fmt, path::PathBuf};
+use yansi::Paint;
+
+/// Prefix of the variables that are treated as storage gaps, e.g. `uint256[50] private __gap;`
+pub const STORAGE_GAP_PREFIX: &str = "__gap";
+
+/// CLI arguments for `forge storage-check`.
+#[derive(Debug, Clone, Parser)]
+pub struct StorageCheckArgs {
+    #[clap(
+        help = "The artifact (or storage layout JSON) of the currently deployed contract version.",
+        value_hint = ValueHint::FilePath,
+        value_name = "OLD_ARTIFACT"
+    )]
+    pub old: PathBuf,
+
+    #[clap(
+        help = "The artifact (or storage layout JSON) of the new contract version.",
+        value_hint = ValueHint::FilePath,
+        value_name = "NEW_ARTIFACT"
+    )]
+    pub new: PathBuf,
+
+    #[clap(long, help = "Print the report as JSON.")]
+    pub json: bool,
+}
+
+impl Cmd for StorageCheckArgs {
+    type Output = Vec<StorageIssue>;
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let old = read_storage_layout(&self.old)?;
+        let new = read_storage_layout(&self.new)?;
+
+        let issues = diff_storage_layouts(&old, &new);
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&issues)?);
+        } else if issues.is_empty() {
+            println!("{}", Paint::green("Storage layouts are compatible."));
+        } else {
+            let mut table = Table::new();
+            table.load_preset(ASCII_MARKDOWN);
+            table.set_header(vec!["Severity", "Variable", "Slot", "Offset", "Reason"]);
+            for issue in &issues {
+                table.add_row(vec![
+                    issue.severity.to_string(),
+                    issue.label.clone(),
+                    issue.slot.to_string(),
+                    issue.offset.to_string(),
+                    issue.reason.clone(),
+                ]);
+            }
+            println!("{table}");
+        }
+
+        let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+        if errors > 0 {
+            eyre::bail!("Found {errors} incompatible storage layout change(s)")
+        }
+
+        Ok(issues)
+    }
+}
+
+/// Reads the storage layout from either a full contract artifact (`storageLayout` field) or a
+/// plain storage layout, as printed by `forge inspect <CONTRACT> storage`.
+pub fn read_storage_layout(path: &PathBuf) -> eyre::Result<StorageLayout> {
+    let value: Value = fs::read_json_file(path)?;
+    let layout = match value.get("storageLayout") {
+        Some(layout) => layout.clone(),
+        None if value.get("storage").is_some() => value,
+        None => eyre::bail!(
+            "No storage layout found in {}. Add `storageLayout` to `extra_output` in your foundry.toml and rebuild.",
+            path.display()
+        ),
+    };
+    serde_json::from_value(layout)
+        .wrap_err_with(|| format!("Failed to parse storage layout of {}", path.display()))
+}
+
+/// How severe a storage layout change is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The change is compatible, but worth double checking, e.g. a renamed variable.
+    Warning,
+    /// The change corrupts the existing storage of a proxy.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// A single finding when comparing two storage layouts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct StorageIssue {
+    pub severity: Severity,
+    /// Label of the variable in the old layout.
+    pub label: String,
+    pub slot: U256,
+    pub offset: i64,
+    pub reason: String,
+}
+
+/// A flattened storage variable with its resolved type.
+#[derive(Debug, Clone)]
+struct Variable {
+    label: String,
+    /// The contract that declares the variable, e.g. `src/Counter.sol:Counter`
+    contract: String,
+    slot: U256,
+    offset: i64,
+    type_id: String,
+    type_label: String,
+    bytes: U256,
+}
+
+impl Variable {
+    fn is_gap(&self) -> bool {
+        self.label.starts_with(STORAGE_GAP_PREFIX)
+    }
+
+    /// Returns the number of slots used by this variable.
+    fn slots(&self) -> U256 {
+        (self.bytes + 31) / 32
+    }
+
+    /// Returns the first slot that is no longer used by this variable.
+    fn end_slot(&self) -> U256 {
+        self.slot + self.slots()
+    }
+}
+
+/// Resolves the types of `storage`, the variables of a contract or the members of a struct
+/// stored at `base_slot`.
+fn variables(
+    layout: &StorageLayout,
+    storage: &[Storage],
+    base_slot: U256,
+    prefix: &str,
+) -> Vec<Variable> {
+    storage
+        .iter()
+        .map(|storage| {
+            let ty = layout.types.get(&storage.storage_type);
+            Variable {
+                label: format!("{prefix}{}", storage.label),
+                contract: storage.contract.clone(),
+                slot: base_slot + U256::from_dec_str(&storage.slot).unwrap_or_default(),
+                offset: storage.offset,
+                type_id: storage.storage_type.clone(),
+                type_label: ty.map(|ty| ty.label.clone()).unwrap_or_else(|| "?".to_string()),
+                bytes: ty
+                    .and_then(|ty| U256::from_dec_str(&ty.number_of_bytes).ok())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the members of a struct type
+fn members(ty: &StorageType) -> Option<Vec<Storage>> {
+    serde_json::from_value(ty.other.get("members")?.clone()).ok()
+}
+
+/// Returns the value type of a mapping or the element type of an array
+fn inner_type(ty: &StorageType) -> Option<String> {
+    ty.value.clone().or_else(|| ty.other.get("base")?.as_str().map(str::to_string))
+}
+
+/// Compares the storage layout of the deployed version (`old`) with the one of the upgrade
+/// (`new`).
+///
+/// Every variable of the old layout must stay at the same slot and offset with the same type,
+/// the members of structs, including the structs stored in mappings and arrays, are compared the
+/// same way. Storage gaps (`__gap` variables) are allowed to shrink by the slots of the variables
+/// added in front of them, so that they still end at the same slot.
+pub fn diff_storage_layouts(old: &StorageLayout, new: &StorageLayout) -> Vec<StorageIssue> {
+    let mut issues = Vec::new();
+    diff_variables(
+        old,
+        &variables(old, &old.storage, U256::zero(), ""),
+        new,
+        &variables(new, &new.storage, U256::zero(), ""),
+        &mut issues,
+    );
+    issues
+}
+
+fn issue(issues: &mut Vec<StorageIssue>, var: &Variable, severity: Severity, reason: String) {
+    issues.push(StorageIssue {
+        severity,
+        label: var.label.clone(),
+        slot: var.slot,
+        offset: var.offset,
+        reason,
+    })
+}
+
+fn diff_variables(
+    old_layout: &StorageLayout,
+    old: &[Variable],
+    new_layout: &StorageLayout,
+    new: &[Variable],
+    issues: &mut Vec<StorageIssue>,
+) {
+    for old_var in old {
+        if old_var.is_gap() {
+            // every contract of the inheritance tree may declare its own gap
+            match new.iter().find(|new_var| {
+                new_var.label == old_var.label && new_var.contract == old_var.contract
+            }) {
+                Some(new_var) => {
+                    // the slots taken by the variables added in front of the gap
+                    let added = new_var.slot.saturating_sub(old_var.slot);
+                    if new_var.slot < old_var.slot || new_var.slots() + added != old_var.slots() {
+                        issue(
+                            issues,
+                            old_var,
+                            Severity::Error,
+                            format!(
+                                "storage gap has {} slots instead of {}, it ends at slot {} \
+                                 instead of {}",
+                                new_var.slots(),
+                                old_var.slots().saturating_sub(added),
+                                new_var.end_slot(),
+                                old_var.end_slot()
+                            ),
+                        )
+                    }
+                }
+                None => {
+                    // a removed gap is fine as long as nothing was placed beyond its end
+                    if let Some(overflow) = new.iter().find(|new_var| {
+                        new_var.slot < old_var.end_slot() && new_var.end_slot() > old_var.end_slot()
+                    }) {
+                        issue(
+                            issues,
+                            old_var,
+                            Severity::Error,
+                            format!(
+                                "storage gap was removed and `{}` overflows its end",
+                                overflow.label
+                            ),
+                        )
+                    }
+                }
+            }
+            continue
+        }
+
+        match new
+            .iter()
+            .find(|new_var| new_var.slot == old_var.slot && new_var.offset == old_var.offset)
+        {
+            None => issue(
+                issues,
+                old_var,
+                Severity::Error,
+                "variable was removed or moved to a different slot".to_string(),
+            ),
+            Some(new_var) if new_var.is_gap() => issue(
+                issues,
+                old_var,
+                Severity::Error,
+                format!("variable was replaced by storage gap `{}`", new_var.label),
+            ),
+            Some(new_var) => {
+                if new_var.type_label != old_var.type_label {
+                    issue(
+                        issues,
+                        old_var,
+                        Severity::Error,
+                        format!(
+                            "type changed from `{}` to `{}`",
+                            old_var.type_label, new_var.type_label
+                        ),
+                    )
+                } else if new_var.bytes != old_var.bytes {
+                    issue(
+                        issues,
+                        old_var,
+                        Severity::Error,
+                        format!("size changed from {} to {} bytes", old_var.bytes, new_var.bytes),
+                    )
+                } else {
+                    diff_nested(
+                        old_layout,
+                        &old_var.type_id,
+                        new_layout,
+                        &new_var.type_id,
+                        old_var,
+                        issues,
+                    );
+                }
+                if new_var.label != old_var.label {
+                    let reason = format!("renamed to `{}`", new_var.label);
+                    issue(issues, old_var, Severity::Warning, reason)
+                }
+            }
+        }
+    }
+}
+
+/// Compares the members of the struct types of `var`, the label of a struct can stay the same
+/// while its members are reordered or retyped
+///
+/// The members of the structs stored in mappings and dynamic arrays are reported relative to the
+/// slot of `var`.
+fn diff_nested(
+    old_layout: &StorageLayout,
+    old_type: &str,
+    new_layout: &StorageLayout,
+    new_type: &str,
+    var: &Variable,
+    issues: &mut Vec<StorageIssue>,
+) {
+    let (old_type, new_type) =
+        match (old_layout.types.get(old_type), new_layout.types.get(new_type)) {
+            (Some(old_type), Some(new_type)) => (old_type, new_type),
+            _ => return,
+        };
+
+    if let (Some(old_members), Some(new_members)) = (members(old_type), members(new_type)) {
+        let prefix = format!("{}.", var.label);
+        diff_variables(
+            old_layout,
+            &variables(old_layout, &old_members, var.slot, &prefix),
+            new_layout,
+            &variables(new_layout, &new_members, var.slot, &prefix),
+            issues,
+        );
+    }
+
+    if let (Some(old_inner), Some(new_inner)) = (inner_type(old_type), inner_type(new_type)) {
+        let label = if old_type.value.is_some() {
+            format!("{}[key]", var.label)
+        } else {
+            format!("{}[i]", var.label)
+        };
+        let element = Variable { label, ..var.clone() };
+        diff_nested(old_layout, &old_inner, new_layout, &new_inner, &element, issues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER: &str = "src/Counter.sol:Counter";
+
+    fn storage(vars: &[(&str, &str, u64, i64, &str)]) -> Value {
+        vars.iter()
+            .enumerate()
+            .map(|(i, (contract, label, slot, offset, ty))| {
+                serde_json::json!({
+                    "astId": i,
+                    "contract": contract,
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot.to_string(),
+                    "type": ty,
+                })
+            })
+            .collect()
+    }
+
+    fn layout(vars: &[(&str, u64, i64, &str)]) -> StorageLayout {
+        let vars = vars
+            .iter()
+            .map(|(label, slot, offset, ty)| (COUNTER, *label, *slot, *offset, *ty))
+            .collect::<Vec<_>>();
+        layout_of(&vars, Value::Null)
+    }
+
+    /// A layout of variables declared in different contracts, with additional `types`
+    fn layout_of(vars: &[(&str, &str, u64, i64, &str)], types: Value) -> StorageLayout {
+        let mut layout = serde_json::json!({
+            "storage": storage(vars),
+            "types": {
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_array(t_uint256)50_storage": {
+                    "encoding": "inplace", "label": "uint256[50]", "numberOfBytes": "1600"
+                },
+                "t_array(t_uint256)49_storage": {
+                    "encoding": "inplace", "label": "uint256[49]", "numberOfBytes": "1568"
+                },
+            }
+        });
+        if let Value::Object(types) = types {
+            layout["types"].as_object_mut().unwrap().extend(types);
+        }
+        serde_json::from_value(layout).unwrap()
+    }
+
+    /// A layout with a `struct S` and a mapping of it, with the given members
+    fn struct_layout(members: &[(&str, u64, i64, &str)]) -> StorageLayout {
+        let members = members
+            .iter()
+            .map(|(label, slot, offset, ty)| (COUNTER, *label, *slot, *offset, *ty))
+            .collect::<Vec<_>>();
+        layout_of(
+            &[
+                (COUNTER, "s", 0, 0, "t_struct(S)1_storage"),
+                (COUNTER, "ss", 2, 0, "t_mapping(t_uint256,t_struct(S)1_storage)"),
+            ],
+            serde_json::json!({
+                "t_struct(S)1_storage": {
+                    "encoding": "inplace",
+                    "label": "struct Counter.S",
+                    "members": storage(&members),
+                    "numberOfBytes": "64"
+                },
+                "t_mapping(t_uint256,t_struct(S)1_storage)": {
+                    "encoding": "mapping",
+                    "key": "t_uint256",
+                    "label": "mapping(uint256 => struct Counter.S)",
+                    "numberOfBytes": "32",
+                    "value": "t_struct(S)1_storage"
+                },
+            }),
+        )
+    }
+
+    #[test]
+    fn appending_is_compatible() {
+        let old = layout(&[("owner", 0, 0, "t_address"), ("count", 1, 0, "t_uint256")]);
+        let new = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint256"),
+            ("total", 2, 0, "t_uint256"),
+        ]);
+        assert!(diff_storage_layouts(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn detects_shifted_and_retyped_variables() {
+        let old = layout(&[("owner", 0, 0, "t_address"), ("count", 1, 0, "t_uint256")]);
+        let new = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("paused", 0, 20, "t_bool"),
+            ("total", 1, 0, "t_address"),
+        ]);
+        let issues = diff_storage_layouts(&old, &new);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].reason, "type changed from `uint256` to `address`");
+        assert_eq!(issues[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn gap_can_shrink() {
+        let old = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("__gap", 1, 0, "t_array(t_uint256)50_storage"),
+        ]);
+        let new = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint256"),
+            ("__gap", 2, 0, "t_array(t_uint256)49_storage"),
+        ]);
+        assert!(diff_storage_layouts(&old, &new).is_empty());
+
+        let new = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint256"),
+            ("__gap", 2, 0, "t_array(t_uint256)50_storage"),
+        ]);
+        let issues = diff_storage_layouts(&old, &new);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].label, "__gap");
+    }
+
+    #[test]
+    fn gaps_are_matched_per_contract() {
+        let old = layout_of(
+            &[
+                ("src/Base.sol:Base", "__gap", 0, 0, "t_array(t_uint256)50_storage"),
+                ("src/Child.sol:Child", "__gap", 50, 0, "t_array(t_uint256)50_storage"),
+            ],
+            Value::Null,
+        );
+        let new = layout_of(
+            &[
+                ("src/Base.sol:Base", "__gap", 0, 0, "t_array(t_uint256)50_storage"),
+                ("src/Child.sol:Child", "count", 50, 0, "t_uint256"),
+                ("src/Child.sol:Child", "__gap", 51, 0, "t_array(t_uint256)49_storage"),
+            ],
+            Value::Null,
+        );
+        assert!(diff_storage_layouts(&old, &new).is_empty());
+
+        // the gap of the child was not shrunk
+        let new = layout_of(
+            &[
+                ("src/Base.sol:Base", "__gap", 0, 0, "t_array(t_uint256)50_storage"),
+                ("src/Child.sol:Child", "count", 50, 0, "t_uint256"),
+                ("src/Child.sol:Child", "__gap", 51, 0, "t_array(t_uint256)50_storage"),
+            ],
+            Value::Null,
+        );
+        let issues = diff_storage_layouts(&old, &new);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].slot, 50.into());
+        assert_eq!(
+            issues[0].reason,
+            "storage gap has 50 slots instead of 49, it ends at slot 101 instead of 100"
+        );
+    }
+
+    #[test]
+    fn compares_struct_members() {
+        let old = struct_layout(&[("a", 0, 0, "t_uint256"), ("b", 1, 0, "t_address")]);
+        let new = struct_layout(&[
+            ("a", 0, 0, "t_uint256"),
+            ("b", 1, 0, "t_address"),
+            ("c", 1, 20, "t_bool"),
+        ]);
+        assert!(diff_storage_layouts(&old, &new).is_empty());
+
+        // the members were swapped, the struct keeps its label and size
+        let new = struct_layout(&[("b", 0, 0, "t_address"), ("a", 1, 0, "t_uint256")]);
+        let issues = diff_storage_layouts(&old, &new);
+        let labels = issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .map(|issue| issue.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["s.a", "s.b", "ss[key].a", "ss[key].b"]);
+        assert_eq!(issues[0].reason, "type changed from `uint256` to `address`");
+    }
+}
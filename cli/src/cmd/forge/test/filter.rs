@@ -1,9 +1,15 @@
+use super::touches::TouchingFilter;
 use crate::utils::FoundryPathExt;
 use clap::Parser;
 use ethers::solc::{FileFilter, ProjectPathsConfig};
 use forge::TestFilter;
 use foundry_config::Config;
-use std::{fmt, path::Path, str::FromStr};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// The filter to use during testing.
 ///
@@ -71,6 +77,14 @@ pub struct FilterArgs {
         value_name = "GLOB"
     )]
     pub path_pattern_inverse: Option<GlobMatcher>,
+
+    /// Only run tests whose execution touched the given contract.
+    ///
+    /// The contract can be given as source file, name or `<path>:<name>` identifier. The touched
+    /// contracts of every test are recorded whenever tests run with traces, e.g. with `-vvv`. Tests
+    /// without an up-to-date record always run.
+    #[clap(long, value_name = "PATH", conflicts_with = "pattern")]
+    pub touching: Option<String>,
 }
 
 impl FilterArgs {
@@ -96,7 +110,12 @@ impl FilterArgs {
         if filter.path_pattern_inverse.is_none() {
             filter.path_pattern_inverse = config.path_pattern_inverse.clone().map(Into::into);
         }
-        ProjectPathsAwareFilter { args_filter: filter, paths: config.project_paths() }
+        ProjectPathsAwareFilter {
+            args_filter: filter,
+            paths: config.project_paths(),
+            touching: None,
+            test_files: None,
+        }
     }
 }
 
@@ -110,6 +129,7 @@ impl fmt::Debug for FilterArgs {
             .field("no-match-contract", &self.contract_pattern_inverse.as_ref().map(|r| r.as_str()))
             .field("match-path", &self.path_pattern.as_ref().map(|g| g.as_str()))
             .field("no-match-path", &self.path_pattern_inverse.as_ref().map(|g| g.as_str()))
+            .field("touching", &self.touching)
             .finish_non_exhaustive()
     }
 }
@@ -198,6 +218,9 @@ impl fmt::Display for FilterArgs {
         if let Some(ref p) = self.path_pattern_inverse {
             patterns.push(format!("\tno-match-path: `{}`", p.as_str()));
         }
+        if let Some(ref p) = self.touching {
+            patterns.push(format!("\ttouching: `{p}`"));
+        }
         write!(f, "{}", patterns.join("\n"))
     }
 }
@@ -207,6 +230,9 @@ impl fmt::Display for FilterArgs {
 pub struct ProjectPathsAwareFilter {
     args_filter: FilterArgs,
     paths: ProjectPathsConfig,
+    touching: Option<TouchingFilter>,
+    /// The only test files to run, relative to the root
+    test_files: Option<BTreeSet<PathBuf>>,
 }
 
 // === impl ProjectPathsAwareFilter ===
@@ -221,6 +247,23 @@ impl ProjectPathsAwareFilter {
     pub fn args_mut(&mut self) -> &mut FilterArgs {
         &mut self.args_filter
     }
+
+    /// Only includes the tests that touched the contract of the [TouchingFilter]
+    pub fn set_touching(&mut self, touching: TouchingFilter) {
+        self.touching = Some(touching);
+    }
+
+    /// Only includes the tests of the given files, see `forge test --watch`
+    pub fn set_test_files(&mut self, files: BTreeSet<PathBuf>) {
+        self.test_files = Some(files);
+    }
+
+    /// Returns whether the file is one of the files of [Self::set_test_files()]
+    fn is_test_file(&self, file: &Path) -> bool {
+        self.test_files.as_ref().map_or(true, |files| {
+            files.contains(file.strip_prefix(&self.paths.root).unwrap_or(file))
+        })
+    }
 }
 
 impl FileFilter for ProjectPathsAwareFilter {
@@ -229,7 +272,7 @@ impl FileFilter for ProjectPathsAwareFilter {
     /// If no file regex is set this returns true if the file ends with `.t.sol`, see
     /// [FoundryPathExr::is_sol_test()]
     fn is_match(&self, file: &Path) -> bool {
-        self.args_filter.is_match(file)
+        self.args_filter.is_match(file) && self.is_test_file(file)
     }
 }
 
@@ -245,7 +288,15 @@ impl TestFilter for ProjectPathsAwareFilter {
     fn matches_path(&self, path: impl AsRef<str>) -> bool {
         let path = path.as_ref();
         // we don't want to test files that belong to a library
-        self.args_filter.matches_path(path) && !self.paths.has_library_ancestor(Path::new(path))
+        self.args_filter.matches_path(path) &&
+            !self.paths.has_library_ancestor(Path::new(path)) &&
+            self.is_test_file(Path::new(path))
+    }
+
+    fn matches_contract_test(&self, contract_id: &str, test_signature: &str) -> bool {
+        self.touching
+            .as_ref()
+            .map_or(true, |touching| touching.matches(contract_id, test_signature))
     }
 }
 
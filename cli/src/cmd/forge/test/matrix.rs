@@ -0,0 +1,86 @@
+//! Collects the results of the test suite on multiple EVM versions, see `forge test --evm-versions`
+
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers::solc::EvmVersion;
+use forge::result::SuiteResult;
+use std::collections::BTreeMap;
+
+/// Whether every test passed on each of the EVM versions it was executed on
+#[derive(Debug, Clone, Default)]
+pub struct TestMatrix {
+    /// The EVM versions in the order they were executed
+    versions: Vec<EvmVersion>,
+    /// `test contract identifier -> test signature -> success on each version`
+    ///
+    /// A result is `None` if the test did not exist when compiling for the version.
+    tests: BTreeMap<String, BTreeMap<String, Vec<Option<bool>>>>,
+}
+
+impl TestMatrix {
+    /// Adds the results of a test run on the given EVM version
+    pub fn insert(&mut self, version: EvmVersion, results: &BTreeMap<String, SuiteResult>) {
+        let index = self.versions.len();
+        self.versions.push(version);
+        for (contract_id, suite) in results {
+            for (name, result) in suite.tests() {
+                self.record(index, contract_id, name, result.success);
+            }
+        }
+    }
+
+    fn record(&mut self, index: usize, contract_id: &str, name: &str, success: bool) {
+        let outcomes = self
+            .tests
+            .entry(contract_id.to_string())
+            .or_default()
+            .entry(name.to_string())
+            .or_default();
+        outcomes.resize(self.versions.len(), None);
+        outcomes[index] = Some(success);
+    }
+
+    /// Returns a table with a row for every test and a column for every EVM version
+    pub fn table(&self) -> Table {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        let mut header = vec!["Test".to_string()];
+        header.extend(self.versions.iter().map(|version| version.to_string()));
+        table.set_header(header);
+
+        for (contract_id, tests) in &self.tests {
+            for (name, outcomes) in tests {
+                let mut row = vec![format!("{contract_id}:{name}")];
+                row.extend((0..self.versions.len()).map(|index| {
+                    match outcomes.get(index).copied().flatten() {
+                        Some(true) => "PASS",
+                        Some(false) => "FAIL",
+                        None => "-",
+                    }
+                    .to_string()
+                }));
+                table.add_row(row);
+            }
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_build_matrix_table() {
+        let mut matrix = TestMatrix::default();
+        matrix.versions = vec![EvmVersion::London, EvmVersion::Berlin];
+        matrix.record(0, "test/A.t.sol:ATest", "testPush0()", true);
+        matrix.record(1, "test/A.t.sol:ATest", "testPush0()", false);
+        matrix.record(1, "test/A.t.sol:ATest", "testLegacy()", true);
+
+        let table = matrix.table().to_string();
+        assert!(table.contains("| Test"));
+        assert!(table.contains("london"));
+        assert!(table.contains("test/A.t.sol:ATest:testLegacy() | -      | PASS"));
+        assert!(table.contains("test/A.t.sol:ATest:testPush0()  | PASS   | FAIL"));
+    }
+}
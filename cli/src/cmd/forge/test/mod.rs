@@ -4,36 +4,65 @@ use crate::{
         forge::{build::CoreBuildArgs, debug::DebugArgs, install, watch::WatchArgs},
         Cmd, LoadConfig,
     },
-    suggestions, utils,
+    suggestions,
+    utils::{self, FoundryPathExt},
 };
 use cast::fuzz::CounterExample;
-use clap::Parser;
-use ethers::{solc::utils::RuntimeOrHandle, types::U256};
+use clap::{Parser, ValueEnum, ValueHint};
+use comfy_table::{presets::ASCII_MARKDOWN, Table};
+use ethers::{
+    solc::{
+        utils::RuntimeOrHandle, Artifact, ArtifactId, EvmVersion, Graph, Project,
+        ProjectCompileOutput, ProjectPathsConfig,
+    },
+    types::U256,
+};
 use forge::{
     decode::decode_console_logs,
-    executor::inspector::CheatsConfig,
+    executor::{inspector::CheatsConfig, opts::EvmOpts},
+    fuzz::invariant::{
+        checkpoint::InvariantCheckpoints, unsuccessful_handlers, InvariantHandlerStats,
+    },
     gas_report::GasReport,
     result::{SuiteResult, TestKind, TestResult},
+    revm::Env,
+    source_locator::SourceLocator,
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier, SignaturesIdentifier},
+        internal::InternalFunction,
         CallTraceDecoderBuilder, TraceKind,
     },
     MultiContractRunner, MultiContractRunnerBuilder, TestOptions,
 };
 use foundry_common::{
     compile::{self, ProjectCompiler},
+    contracts::ContractsByArtifact,
     evm::EvmArgs,
-    get_contract_name, get_file_name,
+    failover::failover_stats,
+    get_contract_name, get_file_name, vyper,
 };
 use foundry_config::{figment, Config};
 use regex::Regex;
-use std::{collections::BTreeMap, path::PathBuf, sync::mpsc::channel, thread, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Arc},
+    thread,
+    time::Duration,
+};
 use tracing::trace;
+use ui::{ExplorerTrace, TraceExplorer, Ui};
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 mod filter;
-use crate::cmd::forge::test::filter::ProjectPathsAwareFilter;
-pub use filter::FilterArgs;
+mod matrix;
+mod touches;
+use crate::cmd::forge::test::{
+    filter::ProjectPathsAwareFilter,
+    matrix::TestMatrix,
+    touches::{ProjectHashes, TestTouches, TEST_TOUCHES_FILE},
+};
+pub use filter::{FilterArgs, GlobMatcher};
 use foundry_common::shell;
 use foundry_config::figment::{
     value::{Dict, Map},
@@ -43,6 +72,9 @@ use foundry_config::figment::{
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::merge_impl_figment_convert!(TestArgs, opts, evm_opts);
 
+/// The directory in the cache directory the invariant checkpoints are persisted in
+const INVARIANT_CHECKPOINTS_DIR: &str = "invariant-checkpoints";
+
 /// CLI arguments for `forge test`.
 #[derive(Debug, Clone, Parser)]
 #[clap(next_help_heading = "Test options")]
@@ -76,6 +108,17 @@ pub struct TestArgs {
     #[clap(long, env = "FORGE_ALLOW_FAILURE")]
     allow_failure: bool,
 
+    /// Stop running tests after the first failure, same as `--max-failures 1`.
+    #[clap(long, conflicts_with = "max_failures")]
+    fail_fast: bool,
+
+    /// Stop running tests after the given number of failures.
+    ///
+    /// No new tests are started once the threshold is hit and running fuzz campaigns are
+    /// cancelled. The results of the tests that completed are still reported.
+    #[clap(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    max_failures: Option<u64>,
+
     /// Output test results in JSON format.
     #[clap(long, short, help_heading = "Display options")]
     json: bool,
@@ -97,12 +140,107 @@ pub struct TestArgs {
     #[clap(long, short, help_heading = "Display options")]
     list: bool,
 
+    /// How to report the test results.
+    ///
+    /// `tree` groups all results by source file and test contract, with the wall-clock time of
+    /// every test and of the `setUp` of every contract.
+    #[clap(
+        long,
+        value_enum,
+        default_value = "list",
+        value_name = "REPORT",
+        help_heading = "Display options"
+    )]
+    report: TestReportKind,
+
+    /// Highlight tests that take longer than the given number of milliseconds.
+    #[clap(long, value_name = "MS", help_heading = "Display options")]
+    slow: Option<u64>,
+
+    /// Show the gas spent and the refunds of every call in traces.
+    ///
+    /// This is enabled by default at verbosity level 5.
+    #[clap(long, help_heading = "Display options")]
+    gas_details: bool,
+
+    /// Show the internal function calls of contracts in traces.
+    ///
+    /// Internal calls are reconstructed from the jumps of the calls with the function debug data
+    /// of solc, their arguments and return values are shown as raw stack words. The project is
+    /// compiled without the cache, since the debug data is not part of the artifacts. Not
+    /// supported with `via_ir`, which inlines most internal functions.
+    #[clap(long, help_heading = "Display options")]
+    decode_internal: bool,
+
+    /// Explore the traces of failed tests in an interactive TUI after the run.
+    ///
+    /// Call frames can be expanded and collapsed, searched by address, selector or function name,
+    /// shown with raw or decoded calldata and next to the source of their contract.
+    #[clap(
+        long,
+        help_heading = "Display options",
+        conflicts_with_all = &["json", "debug", "list"]
+    )]
+    interactive_traces: bool,
+
     #[clap(
         long,
         help = "Set seed used to generate randomness during your fuzz runs",
         value_parser =  utils::parse_u256
     )]
     pub fuzz_seed: Option<U256>,
+
+    /// Abort every test execution that takes longer than the given number of seconds.
+    ///
+    /// Can be overridden for single tests with a `forge-config: default.test_timeout = <SECONDS>`
+    /// comment above the test function.
+    #[clap(long, value_name = "SECONDS")]
+    pub test_timeout: Option<u64>,
+
+    /// Fail every test that mutates the test backend beyond its own call.
+    ///
+    /// The state of the backend is captured before each test and compared with the state after
+    /// it, mutations that escaped the revert of the test (e.g. via `vm.selectFork`,
+    /// `vm.makePersistent` or forks created by the test) are reported as a diff. Helps to debug
+    /// order-dependent test failures.
+    #[clap(long)]
+    pub strict_isolation: bool,
+
+    /// Resume invariant campaigns from their checkpoints instead of starting from scratch.
+    ///
+    /// The progress of invariant campaigns is persisted in the cache directory, a resumed
+    /// campaign only executes the runs that are left.
+    #[clap(long)]
+    pub resume_invariants: bool,
+
+    /// Merge the invariant checkpoints in the directory into the local ones and resume from them.
+    ///
+    /// This allows to distribute a campaign across machines by merging the
+    /// `cache/invariant-checkpoints` directories of all of them.
+    #[clap(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub merge_invariants: Vec<PathBuf>,
+
+    /// Write the snapshot files of `expectSnapshot` with the logs of the tests instead of
+    /// comparing against them, which also creates missing snapshot files.
+    #[clap(long, env = "FORGE_UPDATE_SNAPSHOTS")]
+    pub update_snapshots: bool,
+
+    /// Makes the variables of the dotenv file visible to the `env*` cheatcodes, in addition to
+    /// the `env` of the profile, without exporting them to the shell.
+    #[clap(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub env_file: Vec<PathBuf>,
+
+    /// Compile and run the tests once for every given EVM version, e.g. `london,berlin`.
+    ///
+    /// A table of the tests that pass on each version is printed at the end. With `--json`, the
+    /// results of every version are printed on their own line instead.
+    #[clap(
+        long,
+        value_name = "VERSIONS",
+        value_delimiter = ',',
+        conflicts_with_all = &["debug", "list"]
+    )]
+    pub evm_versions: Vec<EvmVersion>,
 }
 
 impl TestArgs {
@@ -111,21 +249,39 @@ impl TestArgs {
         &self.opts
     }
 
-    /// Executes all the tests in the project
-    ///
-    /// This will trigger the build process first. On success all test contracts that match the
-    /// configured filter will be executed
-    ///
-    /// Returns the test results for all matching tests.
-    pub fn execute_tests(self) -> eyre::Result<TestOutcome> {
-        // Merge all configs
-        let (mut config, mut evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
+    /// Returns the number of failed tests after which the run is stopped
+    fn max_failures(&self) -> Option<usize> {
+        if self.fail_fast {
+            Some(1)
+        } else {
+            self.max_failures.map(|max| max as usize)
+        }
+    }
 
-        let test_options = TestOptions { fuzz: config.fuzz, invariant: config.invariant };
+    /// Returns where the progress of invariant campaigns is persisted, after merging the
+    /// checkpoints of `--merge-invariants` into it
+    fn invariant_checkpoints(&self, config: &Config) -> eyre::Result<Option<InvariantCheckpoints>> {
+        let resume = self.resume_invariants || !self.merge_invariants.is_empty();
+        if !config.cache {
+            if resume {
+                eyre::bail!("Resuming invariant campaigns requires the cache to be enabled");
+            }
+            return Ok(None)
+        }
 
-        let mut filter = self.filter(&config);
+        let checkpoints =
+            InvariantCheckpoints { dir: config.cache_path.join(INVARIANT_CHECKPOINTS_DIR), resume };
+        for dir in &self.merge_invariants {
+            let merged = checkpoints.merge_from(dir)?;
+            trace!(target: "forge::test", ?dir, merged, "merged invariant checkpoints");
+        }
+        Ok(Some(checkpoints))
+    }
 
-        trace!(target: "forge::test", ?filter, "using filter");
+    /// Loads the config, installs missing dependencies and resolves the environment of the tests
+    fn setup(&self) -> eyre::Result<TestSetup> {
+        // Merge all configs
+        let (mut config, evm_opts) = self.load_config_and_evm_opts_emit_warnings()?;
 
         // Set up the project
         let mut project = config.project()?;
@@ -139,22 +295,105 @@ impl TestArgs {
             project = config.project()?;
         }
 
-        let compiler = ProjectCompiler::default();
-        let output = if config.sparse_mode {
-            compiler.compile_sparse(&project, filter.clone())
+        for env_file in &self.env_file {
+            utils::load_env_file(&mut config, env_file)?;
+        }
+
+        if self.decode_internal {
+            if config.via_ir {
+                eyre::bail!("Internal calls can't be decoded when compiling via IR");
+            }
+            project = config.ephemeral_no_artifacts_project()?;
+            project
+                .solc_config
+                .settings
+                .push_output_selection("evm.deployedBytecode.functionDebugData");
+        }
+        self.build_args().apply_standard_json(&mut project)?;
+
+        let env = evm_opts.evm_env_blocking()?;
+
+        Ok(TestSetup { config, evm_opts, project, env })
+    }
+
+    /// Executes all the tests in the project
+    ///
+    /// This will trigger the build process first. On success all test contracts that match the
+    /// configured filter will be executed
+    ///
+    /// Returns the test results for all matching tests.
+    pub fn execute_tests(self) -> eyre::Result<TestOutcome> {
+        self.execute_tests_in(&mut TestSession::default(), None)
+    }
+
+    /// Executes the tests like [Self::execute_tests()], but reuses the setup of previous runs in
+    /// the session
+    ///
+    /// If `test_files` is set only the tests of these files, relative to the root, are compiled
+    /// and executed.
+    pub fn execute_tests_in(
+        self,
+        session: &mut TestSession,
+        test_files: Option<BTreeSet<PathBuf>>,
+    ) -> eyre::Result<TestOutcome> {
+        if !self.evm_versions.is_empty() {
+            return self.execute_matrix()
+        }
+
+        if session.setup.is_none() {
+            session.setup = Some(self.setup()?);
+        }
+        let TestSetup { config, evm_opts, project, env } = session.setup.as_ref().unwrap();
+        let (config, mut evm_opts) = (config.clone(), evm_opts.clone());
+
+        let test_options = TestOptions {
+            fuzz: config.fuzz,
+            invariant: config.invariant,
+            test_timeout: config.test_timeout,
+            auto_advance_time: config.auto_advance_time.map(|time| time.secs()),
+            strict_isolation: config.strict_isolation,
+        };
+
+        let mut filter = self.filter(&config);
+        let sparse = config.sparse_mode || test_files.is_some();
+        if let Some(test_files) = test_files {
+            filter.set_test_files(test_files);
+        }
+
+        trace!(target: "forge::test", ?filter, "using filter");
+
+        let invariant_checkpoints = self.invariant_checkpoints(&config)?;
+
+        let compiler = ProjectCompiler::default().config(&config);
+        let output = if sparse {
+            compiler.compile_sparse(project, filter.clone())
         } else if self.opts.silent {
-            compile::suppress_compile(&project)
+            compile::suppress_compile(project, &config)
         } else {
-            compiler.compile(&project)
+            compiler.compile(project)
         }?;
+        let internal_functions = if self.decode_internal {
+            internal_functions(&output, &project.paths.root)
+        } else {
+            HashMap::new()
+        };
+        let vyper_contracts = vyper::compile_vyper(project, config.offline)?
+            .into_iter()
+            .map(|(id, artifact)| (id, artifact.into_contract_bytecode()));
 
         // Determine print verbosity and executor verbosity
         let verbosity = evm_opts.verbosity;
         if self.gas_report && evm_opts.verbosity < 3 {
             evm_opts.verbosity = 3;
         }
+        if self.interactive_traces && evm_opts.verbosity < 3 {
+            evm_opts.verbosity = 3;
+        }
 
-        let env = evm_opts.evm_env_blocking()?;
+        evm_opts.decode_internal = self.decode_internal;
+
+        let mut cheats_config = CheatsConfig::new(&config, &evm_opts);
+        cheats_config.update_snapshots = self.update_snapshots;
 
         // Prepare the test builder
         let evm_spec = utils::evm_spec(&config.evm_version);
@@ -164,9 +403,26 @@ impl TestArgs {
             .evm_spec(evm_spec)
             .sender(evm_opts.sender)
             .with_fork(evm_opts.get_fork(&config, env.clone()))
-            .with_cheats_config(CheatsConfig::new(&config, &evm_opts))
+            .with_cheats_config(cheats_config)
             .with_test_options(test_options)
-            .build(project.paths.root, output, env, evm_opts)?;
+            .with_invariant_checkpoints(invariant_checkpoints)
+            .with_libraries(config.parsed_libraries()?)
+            .with_extra_contracts(vyper_contracts)
+            .with_max_failures(self.max_failures())
+            .with_source_locator(SourceLocator::new(&output, project.paths.root.clone()))
+            .with_signature_identifier(SignaturesIdentifier::new(
+                Config::foundry_cache_dir(),
+                config.offline,
+            )?)
+            .build(project.paths.root.clone(), output, env.clone(), evm_opts)?;
+
+        // the records are matched against the compiled contracts
+        if let Some(ref touching) = filter.args().touching {
+            let touches = TestTouches::read(&config.cache_path.join(TEST_TOUCHES_FILE));
+            let graph = Graph::resolve(&project.paths)?;
+            let hashes = ProjectHashes::new(&graph, &runner.known_contracts);
+            filter.set_touching(touches.touching(&config.__root.0, &hashes, touching));
+        }
 
         if self.debug.is_some() {
             filter.args_mut().test_pattern = self.debug;
@@ -229,10 +485,58 @@ impl TestArgs {
                 self.allow_failure,
                 test_options,
                 self.gas_report,
+                TestReporter {
+                    kind: self.report,
+                    slow: self.slow.map(Duration::from_millis),
+                    gas_details: self.gas_details || verbosity >= 5,
+                    interactive_traces: self.interactive_traces,
+                },
+                Arc::new(internal_functions),
             )
         }
     }
 
+    /// Executes the tests once for every EVM version of `--evm-versions`
+    ///
+    /// Returns the results of all runs, the test contracts are suffixed with the EVM version.
+    fn execute_matrix(self) -> eyre::Result<TestOutcome> {
+        if let Some(version) =
+            self.evm_versions.iter().find(|version| utils::try_evm_spec(version).is_none())
+        {
+            eyre::bail!("EVM version `{version}` is not supported by the test runner");
+        }
+
+        let mut matrix = TestMatrix::default();
+        let mut results = BTreeMap::new();
+        for version in self.evm_versions.iter().copied() {
+            let mut args = self.clone();
+            args.evm_versions.clear();
+            args.opts.compiler.evm_version = Some(version);
+            if !self.json {
+                let title = format!("Running tests on EVM version {version}");
+                println!("\n{}", Paint::new(title).bold());
+            }
+
+            let outcome = args.execute_tests()?;
+            matrix.insert(version, &outcome.results);
+            results.extend(
+                outcome.results.into_iter().map(|(id, suite)| (format!("{id} [{version}]"), suite)),
+            );
+        }
+
+        if !self.json {
+            println!("\nResults by EVM version:");
+            println!("{}", matrix.table());
+        }
+        Ok(TestOutcome::new(results, self.allow_failure))
+    }
+
+    /// Installs the shell the results are printed with
+    pub fn install_shell(&self) -> eyre::Result<()> {
+        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        Ok(())
+    }
+
     /// Returns the flattened [`FilterArgs`] arguments merged with [`Config`]
     pub fn filter(&self, config: &Config) -> ProjectPathsAwareFilter {
         self.filter.merge_with_config(config)
@@ -253,6 +557,135 @@ impl TestArgs {
     }
 }
 
+/// Returns the internal functions of all compiled contracts by `"<artifact>:<contract>"`, from the
+/// function debug data of their deployed bytecode
+fn internal_functions(
+    output: &ProjectCompileOutput,
+    root: &Path,
+) -> HashMap<String, Vec<InternalFunction>> {
+    output
+        .output()
+        .contracts
+        .contracts_with_files()
+        .filter_map(|(file, name, contract)| {
+            let bytecode = contract.evm.as_ref()?.deployed_bytecode.as_ref()?.bytecode.as_ref()?;
+            let functions = bytecode
+                .function_debug_data
+                .iter()
+                .filter_map(|(func, data)| InternalFunction::from_debug_data(func, data))
+                .collect();
+            let file = Path::new(file);
+            let file = file.strip_prefix(root).unwrap_or(file);
+            Some((format!("{}:{name}", file.display()), functions))
+        })
+        .collect()
+}
+
+/// The config, project and environment the tests are executed with
+struct TestSetup {
+    config: Config,
+    evm_opts: EvmOpts,
+    project: Project,
+    env: Env,
+}
+
+/// The state that is kept between the test runs of `forge test --watch`
+///
+/// The config, the project and the environment of the tests, which requires a request if forking,
+/// are only set up by the first run.
+#[derive(Default)]
+pub struct TestSession {
+    setup: Option<TestSetup>,
+}
+
+impl TestSession {
+    /// Sets up the tests again in the next run, e.g. after the config changed
+    pub fn reset(&mut self) {
+        self.setup = None;
+    }
+
+    /// Returns the test files, relative to the root, that are affected by the changed files
+    ///
+    /// A test file is affected if it or any file it imports changed, or if one of its tests
+    /// touched a contract of a changed file the last time it was executed with traces, see
+    /// [TestTouches]. Returns `None` if the tests were not set up yet.
+    pub fn affected_test_files(
+        &self,
+        changed: &[PathBuf],
+    ) -> eyre::Result<Option<BTreeSet<PathBuf>>> {
+        let (config, project) = match &self.setup {
+            Some(TestSetup { config, project, .. }) => (config, project),
+            None => return Ok(None),
+        };
+        let root = &config.__root.0;
+        let changed = ProjectRelativePaths::new(root).strip_all(changed);
+
+        let mut files = test_files_importing(&project.paths, &changed)?;
+        if config.cache {
+            let touches = TestTouches::read(&config.cache_path.join(TEST_TOUCHES_FILE));
+            let changed = changed.into_iter().collect::<Vec<_>>();
+            files.extend(touches.test_files_touching(root, &changed));
+        }
+        Ok(Some(files))
+    }
+}
+
+/// Makes paths relative to the root of the project
+///
+/// The paths reported by the watcher and the paths of the import graph are not necessarily both
+/// canonical, e.g. if the root is a symlink, so they are compared relative to the root, stripping
+/// both the root and its canonical path.
+struct ProjectRelativePaths {
+    root: PathBuf,
+    canonical_root: Option<PathBuf>,
+}
+
+impl ProjectRelativePaths {
+    fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf(), canonical_root: dunce::canonicalize(root).ok() }
+    }
+
+    /// Returns the path relative to the root, relative paths are relative to the root already
+    fn strip(&self, path: &Path) -> PathBuf {
+        // joining normalizes `./` prefixes, an absolute path replaces the root
+        let path = self.root.join(path);
+        std::iter::once(&self.root)
+            .chain(&self.canonical_root)
+            .find_map(|root| path.strip_prefix(root).ok())
+            .map(Path::to_path_buf)
+            .unwrap_or(path)
+    }
+
+    fn strip_all(&self, paths: &[PathBuf]) -> HashSet<PathBuf> {
+        paths.iter().map(|path| self.strip(path)).collect()
+    }
+}
+
+/// Returns the test files, relative to the root, that changed or import a changed file
+///
+/// `changed` must be relative to the root, see [ProjectRelativePaths].
+fn test_files_importing(
+    paths: &ProjectPathsConfig,
+    changed: &HashSet<PathBuf>,
+) -> eyre::Result<BTreeSet<PathBuf>> {
+    let relative = ProjectRelativePaths::new(&paths.root);
+    let is_changed = |path: &Path| changed.contains(&relative.strip(path));
+
+    let graph = Graph::resolve(paths)?;
+    let mut files = BTreeSet::new();
+    for (path, index) in graph.files() {
+        if !path.is_sol_test() || paths.has_library_ancestor(path) {
+            continue
+        }
+        if is_changed(path) ||
+            graph.all_imported_nodes(*index).any(|import| is_changed(graph.node(import).path()))
+        {
+            files.insert(relative.strip(path));
+        }
+    }
+    Ok(files)
+}
+
 impl Provider for TestArgs {
     fn metadata(&self) -> Metadata {
         Metadata::named("Core Build Args Provider")
@@ -271,6 +704,14 @@ impl Provider for TestArgs {
             dict.insert("etherscan_api_key".to_string(), etherscan_api_key.to_string().into());
         }
 
+        if let Some(test_timeout) = self.test_timeout {
+            dict.insert("test_timeout".to_string(), test_timeout.into());
+        }
+
+        if self.strict_isolation {
+            dict.insert("strict_isolation".to_string(), true.into());
+        }
+
         Ok(Map::from([(Config::selected_profile(), dict)]))
     }
 }
@@ -280,7 +721,7 @@ impl Cmd for TestArgs {
 
     fn run(self) -> eyre::Result<Self::Output> {
         trace!(target: "forge::test", "executing test command");
-        shell::set_shell(shell::Shell::from_args(self.opts.silent, self.json))?;
+        self.install_shell()?;
         self.execute_tests()
     }
 }
@@ -360,6 +801,7 @@ impl TestOutcome {
 
         if !shell::verbosity().is_normal() {
             // skip printing and exit early
+            crate::telemetry::flush();
             std::process::exit(1);
         }
 
@@ -374,7 +816,7 @@ impl TestOutcome {
             let term = if failures > 1 { "tests" } else { "test" };
             println!("Encountered {failures} failing {term} in {suite_name}");
             for (name, result) in suite.failures() {
-                short_test_result(name, result);
+                short_test_result(name, result, None);
             }
             println!();
         }
@@ -385,6 +827,7 @@ impl TestOutcome {
             Paint::red(failures.to_string()),
             Paint::green(successes.to_string())
         );
+        crate::telemetry::flush();
         std::process::exit(1);
     }
 
@@ -407,8 +850,97 @@ impl TestOutcome {
     }
 }
 
-fn short_test_result(name: &str, result: &TestResult) {
-    let status = if result.success {
+/// How test results are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TestReportKind {
+    /// Print the results of every test contract as soon as it finishes
+    List,
+    /// Print all results grouped by source file and test contract once all tests finished
+    Tree,
+}
+
+/// Display settings of the test results
+#[derive(Debug, Clone, Copy)]
+struct TestReporter {
+    kind: TestReportKind,
+    /// Tests that take longer are highlighted
+    slow: Option<Duration>,
+    /// Whether traces include the gas refund accounting of every call
+    gas_details: bool,
+    /// Whether the traces of failed tests are explored in the [TraceExplorer] after the run
+    interactive_traces: bool,
+}
+
+impl TestReporter {
+    fn is_slow(&self, result: &TestResult) -> bool {
+        self.slow.map(|slow| result.duration > slow).unwrap_or_default()
+    }
+
+    /// Prints all results grouped by source file and test contract
+    fn print_tree(&self, results: &BTreeMap<String, SuiteResult>) {
+        let mut files: BTreeMap<&str, Vec<(&str, &SuiteResult)>> = BTreeMap::new();
+        for (id, suite) in results {
+            files.entry(get_file_name(id)).or_default().push((get_contract_name(id), suite));
+        }
+
+        println!();
+        for (file, contracts) in files {
+            let tests = contracts.iter().map(|(_, suite)| suite.len()).sum::<usize>();
+            let duration = contracts.iter().map(|(_, suite)| suite.duration).sum::<Duration>();
+            println!("{} ({tests} tests, {duration:.2?})", Paint::new(file).bold());
+
+            let last_contract = contracts.len().saturating_sub(1);
+            for (i, (contract, suite)) in contracts.into_iter().enumerate() {
+                let (branch, indent) =
+                    if i == last_contract { ("└─", "   ") } else { ("├─", "│  ") };
+                println!("{branch} {contract} (setUp: {:.2?})", suite.setup_duration);
+
+                let last_test = suite.len().saturating_sub(1);
+                for (j, (name, result)) in suite.tests().enumerate() {
+                    let branch = if j == last_test { "└─" } else { "├─" };
+                    let duration = format!("{:.2?}", result.duration);
+                    let duration = if self.is_slow(result) {
+                        Paint::yellow(duration)
+                    } else {
+                        Paint::new(duration)
+                    };
+                    println!(
+                        "{indent}{branch} {} {name} {} {duration}",
+                        test_status(result),
+                        result.kind.report()
+                    );
+                }
+            }
+            println!();
+        }
+    }
+
+    /// Prints all slow tests, the slowest first
+    fn print_slow_tests(&self, results: &BTreeMap<String, SuiteResult>) {
+        let slow = match self.slow {
+            Some(slow) => slow,
+            None => return,
+        };
+        let mut tests = results
+            .iter()
+            .flat_map(|(id, suite)| suite.tests().map(move |(name, result)| (id, name, result)))
+            .filter(|(_, _, result)| self.is_slow(result))
+            .collect::<Vec<_>>();
+        if tests.is_empty() {
+            return
+        }
+        tests.sort_by(|(_, _, a), (_, _, b)| b.duration.cmp(&a.duration));
+
+        println!("\nSlow tests (> {slow:.2?}):");
+        for (id, name, result) in tests {
+            println!("  {} {id}:{name}", Paint::yellow(format!("{:.2?}", result.duration)));
+        }
+    }
+}
+
+/// Returns the `[PASS]` or `[FAIL. ...]` status of the test
+fn test_status(result: &TestResult) -> Paint<String> {
+    if result.success {
         Paint::green("[PASS]".to_string())
     } else {
         let reason = result
@@ -434,9 +966,46 @@ fn short_test_result(name: &str, result: &TestResult) {
             .unwrap_or_else(|| "]".to_string());
 
         Paint::red(format!("[FAIL. {reason}{counterexample}"))
-    };
+    }
+}
+
+/// Prints the status of the test, tests that take longer than `slow` are highlighted
+fn short_test_result(name: &str, result: &TestResult, slow: Option<Duration>) {
+    let status = test_status(result);
+    match slow {
+        Some(slow) if result.duration > slow => println!(
+            "{status} {name} {} {}",
+            result.kind.report(),
+            Paint::yellow(format!("[SLOW {:.2?}]", result.duration))
+        ),
+        _ => println!("{status} {name} {}", result.kind.report()),
+    }
+}
+
+/// Prints how often the invariant campaign called every handler function and warns about the
+/// handlers that never executed successfully
+fn print_handler_stats(contract_name: &str, handlers: &InvariantHandlerStats) {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN);
+    table.set_header(["Handler", "Calls", "Reverts", "Avg gas"]);
+    for (handler, stats) in handlers {
+        table.add_row([
+            handler.clone(),
+            stats.calls.to_string(),
+            stats.reverts.to_string(),
+            stats.avg_gas().to_string(),
+        ]);
+    }
+    println!("\nInvariant handlers of {contract_name}:\n{table}");
 
-    println!("{status} {name} {}", result.kind.report());
+    let unsuccessful = unsuccessful_handlers(handlers).collect::<Vec<_>>();
+    if !unsuccessful.is_empty() {
+        println!(
+            "{} Handlers that never executed successfully: {}",
+            Paint::yellow("Warning:").bold(),
+            unsuccessful.join(", ")
+        );
+    }
 }
 
 /// Lists all matching tests
@@ -472,8 +1041,11 @@ fn test(
     allow_failure: bool,
     test_options: TestOptions,
     gas_reporting: bool,
+    reporter: TestReporter,
+    internal_functions: Arc<HashMap<String, Vec<InternalFunction>>>,
 ) -> eyre::Result<TestOutcome> {
     trace!(target: "forge::test", "running all tests");
+    let root = config.__root.0.clone();
     if runner.count_filtered_tests(&filter) == 0 {
         let filter_str = filter.to_string();
         if filter_str.is_empty() {
@@ -495,7 +1067,9 @@ fn test(
     }
 
     if json {
+        let mut local_identifier = LocalTraceIdentifier::new(&runner.known_contracts);
         let results = runner.test(&filter, None, test_options)?;
+        record_touches(&config, &runner.known_contracts, &results, &mut local_identifier)?;
         println!("{}", serde_json::to_string(&results)?);
         Ok(TestOutcome::new(results, allow_failure))
     } else {
@@ -507,33 +1081,64 @@ fn test(
 
         // Set up test reporter channel
         let (tx, rx) = channel::<(String, SuiteResult)>();
+        let fail_fast = runner.fail_fast.clone();
+
+        // the traces of failed tests and the sources of the contracts, for the trace explorer
+        let mut explorer_traces = Vec::new();
+        let sources: HashMap<_, _> = if reporter.interactive_traces {
+            let source = |id: &ArtifactId| (id.identifier(), root.join(&id.source));
+            runner.known_contracts.keys().map(source).collect()
+        } else {
+            HashMap::new()
+        };
+
+        // the touched contracts are recorded after the runner finished
+        let known_contracts = runner.known_contracts.clone();
+
+        // the runner identifies custom errors with the same cache
+        let sig_identifier = match runner.signature_identifier.clone() {
+            Some(identifier) => identifier,
+            None => SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?,
+        };
 
         // Run tests
         let handle = thread::spawn(move || runner.test(&filter, Some(tx), test_options).unwrap());
 
         let mut results: BTreeMap<String, SuiteResult> = BTreeMap::new();
         let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore);
-        let sig_identifier =
-            SignaturesIdentifier::new(Config::foundry_cache_dir(), config.offline)?;
 
+        let is_list = reporter.kind == TestReportKind::List;
         for (contract_name, suite_result) in rx {
             let mut tests = suite_result.test_results.clone();
-            println!();
+            if is_list {
+                println!();
+            }
             for warning in suite_result.warnings.iter() {
                 eprintln!("{} {warning}", Paint::yellow("Warning:").bold());
             }
-            if !tests.is_empty() {
+            if is_list && !tests.is_empty() {
                 let term = if tests.len() > 1 { "tests" } else { "test" };
                 println!("Running {} {term} for {contract_name}", tests.len());
             }
             for (name, result) in &mut tests {
-                short_test_result(name, result);
+                // the tree report only prints the test if it has logs or traces to show
+                let mut announced = is_list;
+                let mut announce = || {
+                    if !announced {
+                        println!("\n{}", Paint::new(format!("{contract_name}:{name}")).bold());
+                        announced = true;
+                    }
+                };
+                if is_list {
+                    short_test_result(name, result, reporter.slow);
+                }
 
                 // We only display logs at level 2 and above
                 if verbosity >= 2 {
                     // We only decode logs from Hardhat and DS-style console events
                     let console_logs = decode_console_logs(&result.logs);
                     if !console_logs.is_empty() {
+                        announce();
                         println!("Logs:");
                         for log in console_logs {
                             println!("  {log}");
@@ -548,6 +1153,7 @@ fn test(
                         .with_labels(result.labeled_addresses.clone())
                         .with_events(local_identifier.events())
                         .with_verbosity(verbosity)
+                        .with_internal_functions(internal_functions.clone())
                         .build();
 
                     // Signatures are of no value for gas reports
@@ -562,6 +1168,11 @@ fn test(
                         decoder.identify(trace, &mut local_identifier);
                         decoder.identify(trace, &mut etherscan_identifier);
 
+                        let explore = reporter.interactive_traces &&
+                            !result.success &&
+                            matches!(kind, TraceKind::Setup | TraceKind::Execution);
+                        let raw = explore.then(|| trace.clone());
+
                         let should_include = match kind {
                             // At verbosity level 3, we only display traces for failed tests
                             // At verbosity level 4, we also display the setup trace for failed
@@ -577,17 +1188,34 @@ fn test(
                         };
 
                         // We decode the trace if we either need to build a gas report or we need
-                        // to print it
-                        if should_include || gas_reporting {
+                        // to print or explore it
+                        if should_include || gas_reporting || explore {
                             rt.block_on(decoder.decode(trace));
                         }
 
+                        if let Some(raw) = raw {
+                            let title = match kind {
+                                TraceKind::Setup => format!("{contract_name}:{name} (setUp)"),
+                                _ => format!("{contract_name}:{name}"),
+                            };
+                            explorer_traces.push(ExplorerTrace {
+                                title,
+                                decoded: trace.clone(),
+                                raw,
+                            });
+                        }
+
                         if should_include {
-                            decoded_traces.push(trace.to_string());
+                            if reporter.gas_details {
+                                decoded_traces.push(format!("{trace:#}"));
+                            } else {
+                                decoded_traces.push(trace.to_string());
+                            }
                         }
                     }
 
                     if !decoded_traces.is_empty() {
+                        announce();
                         println!("Traces:");
                         decoded_traces.into_iter().for_each(|trace| println!("{trace}"));
                     }
@@ -597,14 +1225,49 @@ fn test(
                     }
                 }
             }
-            let block_outcome = TestOutcome::new(
-                [(contract_name.clone(), suite_result.clone())].into(),
-                allow_failure,
-            );
-            println!("{}", block_outcome.summary());
+            // all invariants of a test contract are checked in the same campaign
+            if let Some(handlers) = tests.values().find_map(|result| match &result.kind {
+                TestKind::Invariant(_, _, handlers) if !handlers.is_empty() => Some(handlers),
+                _ => None,
+            }) {
+                print_handler_stats(&contract_name, handlers);
+            }
+            if is_list {
+                let block_outcome = TestOutcome::new(
+                    [(contract_name.clone(), suite_result.clone())].into(),
+                    allow_failure,
+                );
+                println!("{}", block_outcome.summary());
+            }
             results.insert(contract_name, suite_result);
         }
 
+        if !is_list {
+            reporter.print_tree(&results);
+        }
+        reporter.print_slow_tests(&results);
+
+        if fail_fast.is_stopped() {
+            println!(
+                "\n{}",
+                Paint::yellow(format!(
+                    "Stopped after {} failed tests, the remaining tests were not run",
+                    fail_fast.failures()
+                ))
+            );
+        }
+
+        // the endpoints of fork urls with fallbacks
+        let endpoints = failover_stats();
+        if !endpoints.is_empty() {
+            println!("\nFork endpoints:");
+            for endpoint in endpoints {
+                println!("  {endpoint}");
+            }
+        }
+
+        record_touches(&config, &known_contracts, &results, &mut local_identifier)?;
+
         if gas_reporting {
             println!("{}", gas_report.finalize());
         }
@@ -612,7 +1275,98 @@ fn test(
         // reattach the thread
         let _ = handle.join();
 
+        if !explorer_traces.is_empty() {
+            // the explorer takes over the terminal, it can't run if the output is redirected
+            if atty::is(atty::Stream::Stdout) {
+                TraceExplorer::new(explorer_traces, sources)?.start()?;
+            } else {
+                eprintln!("Skipping the trace explorer, stdout is not a terminal");
+            }
+        }
+
         trace!(target: "forge::test", "received {} results", results.len());
         Ok(TestOutcome::new(results, allow_failure))
     }
 }
+
+/// Records the contracts touched by all tests that were executed with tracing, see [TestTouches]
+///
+/// Nothing is recorded if the cache is disabled.
+fn record_touches(
+    config: &Config,
+    known_contracts: &ContractsByArtifact,
+    results: &BTreeMap<String, SuiteResult>,
+    identifier: &mut LocalTraceIdentifier,
+) -> eyre::Result<()> {
+    if !config.cache ||
+        results.values().all(|suite| suite.tests().all(|(_, t)| t.traces.is_empty()))
+    {
+        return Ok(())
+    }
+
+    let graph = Graph::resolve(&config.project_paths())?;
+    let hashes = ProjectHashes::new(&graph, known_contracts);
+    let path = config.cache_path.join(TEST_TOUCHES_FILE);
+    let mut touches = TestTouches::read(&path);
+    for (contract_id, suite) in results {
+        touches.record(&config.__root.0, &hashes, contract_id, suite, identifier);
+    }
+    touches.write(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn can_find_test_files_importing_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            ("src/Token.sol", "contract Token {}"),
+            ("src/Vault.sol", "import \"./Token.sol\";\ncontract Vault {}"),
+            ("test/Vault.t.sol", "import \"../src/Vault.sol\";\ncontract VaultTest {}"),
+            ("test/Other.t.sol", "contract OtherTest {}"),
+        ] {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), content).unwrap();
+        }
+        let paths = ProjectPathsConfig::builder().build_with_root(root);
+        let affected = |changed: &[&str]| {
+            let changed = changed.iter().map(PathBuf::from).collect();
+            test_files_importing(&paths, &changed).unwrap()
+        };
+
+        let vault_test = PathBuf::from("test/Vault.t.sol");
+        let other_test = PathBuf::from("test/Other.t.sol");
+
+        // imports are followed transitively
+        assert_eq!(affected(&["src/Token.sol"]), BTreeSet::from([vault_test.clone()]));
+        assert_eq!(affected(&["src/Vault.sol"]), BTreeSet::from([vault_test.clone()]));
+        assert_eq!(
+            affected(&["test/Other.t.sol", "src/Token.sol"]),
+            BTreeSet::from([other_test, vault_test])
+        );
+        assert!(affected(&["src/Missing.sol"]).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn can_make_paths_relative_to_symlinked_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        fs::create_dir_all(&root).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&root, &link).unwrap();
+
+        let relative = ProjectRelativePaths::new(&link);
+        let expected = PathBuf::from("src/Vault.sol");
+        assert_eq!(relative.strip(&link.join("src/Vault.sol")), expected);
+        let canonical = dunce::canonicalize(&root).unwrap().join("src/Vault.sol");
+        assert_eq!(relative.strip(&canonical), expected);
+        assert_eq!(relative.strip(Path::new("./src/Vault.sol")), expected);
+        let outside = Path::new("/elsewhere/Vault.sol");
+        assert_eq!(relative.strip(outside), outside);
+    }
+}
@@ -0,0 +1,316 @@
+//! Records which contracts the execution of a test touched, see `forge test --touching`
+
+use ethers::{solc::Graph, types::Address, utils::keccak256};
+use forge::{
+    result::SuiteResult,
+    trace::{identifier::TraceIdentifier, TraceKind},
+};
+use foundry_common::{contracts::ContractsByArtifact, fs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+use tracing::trace;
+
+/// The file in the cache directory the touched contracts are recorded in
+pub const TEST_TOUCHES_FILE: &str = "test-touches.json";
+
+/// The contracts touched by every recorded test
+///
+/// A record is only valid as long as the source files, including their imports, and the bytecode
+/// of the test contract and of all touched contracts are unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TestTouches {
+    /// The hashes of all source files at the time they were recorded, see [ProjectHashes]
+    sources: BTreeMap<String, String>,
+    /// The hashes of the deployed bytecode of all contracts at the time they were recorded
+    #[serde(default)]
+    bytecodes: BTreeMap<String, String>,
+    /// `test contract identifier -> test signature -> touched contract identifiers`
+    tests: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl TestTouches {
+    /// Reads the recorded touches from the given file, or returns empty records if there are none
+    pub fn read(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default()
+        }
+        fs::read_json_file(path).unwrap_or_else(|err| {
+            trace!(target: "forge::test", ?err, "failed to read test touches");
+            Self::default()
+        })
+    }
+
+    /// Writes the records to the given file
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(path, self)?;
+        Ok(())
+    }
+
+    /// Records the contracts touched by the tests of the suite
+    ///
+    /// Only tests that were executed with tracing enabled are recorded. The addresses of the
+    /// traces are resolved via the `identifier`.
+    pub fn record(
+        &mut self,
+        root: &Path,
+        hashes: &ProjectHashes,
+        contract_id: &str,
+        suite: &SuiteResult,
+        identifier: &mut impl TraceIdentifier,
+    ) {
+        for (signature, result) in suite.tests() {
+            if result.traces.is_empty() {
+                continue
+            }
+
+            // contracts are usually deployed in `setUp`, so all traces are needed to identify them
+            let addresses = result.traces.iter().flat_map(|(_, trace)| trace.addresses()).collect();
+            let contracts: HashMap<Address, String> = identifier
+                .identify_addresses(addresses)
+                .into_iter()
+                .filter_map(|identity| {
+                    let artifact_id = identity.artifact_id?;
+                    Some((identity.address, artifact_id.identifier()))
+                })
+                .collect();
+
+            let touched = result
+                .traces
+                .iter()
+                .filter(|(kind, _)| *kind == TraceKind::Execution)
+                .flat_map(|(_, trace)| trace.arena.iter().map(|node| node.trace.address))
+                .filter_map(|address| contracts.get(&address).cloned())
+                .filter(|id| id != contract_id)
+                .collect::<BTreeSet<_>>();
+
+            for id in touched.iter().map(String::as_str).chain(std::iter::once(contract_id)) {
+                let path = source_path(id);
+                if let Some(hash) = hashes.source(root, path) {
+                    self.sources.insert(path.to_string(), hash.clone());
+                }
+                if let Some(hash) = hashes.bytecodes.get(id) {
+                    self.bytecodes.insert(id.to_string(), hash.clone());
+                }
+            }
+            self.tests
+                .entry(contract_id.to_string())
+                .or_default()
+                .insert(signature.clone(), touched);
+        }
+    }
+
+    /// Returns a filter for the tests that touched the given contract
+    ///
+    /// `target` is either a source file, a contract name or a contract identifier. Records that
+    /// are outdated because a recorded source file or bytecode changed are ignored.
+    pub fn touching(&self, root: &Path, hashes: &ProjectHashes, target: &str) -> TouchingFilter {
+        let is_valid = |id: &str| {
+            let source = hashes.source(root, source_path(id));
+            let bytecode = hashes.bytecodes.get(id);
+            source.is_some() &&
+                bytecode.is_some() &&
+                self.sources.get(source_path(id)) == source &&
+                self.bytecodes.get(id) == bytecode
+        };
+
+        let mut tests = BTreeMap::new();
+        for (contract_id, recorded) in &self.tests {
+            if !is_valid(contract_id) {
+                continue
+            }
+            for (signature, touched) in recorded {
+                if touched.iter().all(|id| is_valid(id)) {
+                    let is_match = touched.iter().any(|id| is_target(root, id, target));
+                    tests.insert((contract_id.clone(), signature.clone()), is_match);
+                }
+            }
+        }
+
+        TouchingFilter { tests }
+    }
+
+    /// Returns the source files of the test contracts, relative to the root, with a recorded test
+    /// that touched a contract of one of the given files
+    pub fn test_files_touching(&self, root: &Path, files: &[PathBuf]) -> BTreeSet<PathBuf> {
+        self.tests
+            .iter()
+            .filter(|(_, recorded)| {
+                recorded
+                    .values()
+                    .flatten()
+                    .any(|id| files.iter().any(|file| is_target(root, id, &file.to_string_lossy())))
+            })
+            .map(|(contract_id, _)| {
+                let path = Path::new(source_path(contract_id));
+                path.strip_prefix(root).unwrap_or(path).to_path_buf()
+            })
+            .collect()
+    }
+}
+
+/// The current hashes of the sources and contracts of the project, a record is outdated once one
+/// of its hashes changed
+#[derive(Debug, Clone, Default)]
+pub struct ProjectHashes {
+    /// The hash of every source file and all files it imports, by path
+    sources: HashMap<PathBuf, String>,
+    /// The hash of the deployed bytecode of every contract, by identifier
+    bytecodes: HashMap<String, String>,
+}
+
+impl ProjectHashes {
+    pub fn new(graph: &Graph, contracts: &ContractsByArtifact) -> Self {
+        let sources = graph
+            .files()
+            .iter()
+            .map(|(path, index)| {
+                // a change of an imported file can change the contracts of the file
+                let mut nodes = graph
+                    .all_imported_nodes(*index)
+                    .map(|import| graph.node(import))
+                    .collect::<Vec<_>>();
+                nodes.sort_by(|a, b| a.path().cmp(b.path()));
+                let mut content = graph.node(*index).content().as_bytes().to_vec();
+                for node in nodes {
+                    content.extend_from_slice(node.path().to_string_lossy().as_bytes());
+                    content.extend_from_slice(node.content().as_bytes());
+                }
+                (path.clone(), hex::encode(keccak256(content)))
+            })
+            .collect();
+        let bytecodes = contracts
+            .iter()
+            .map(|(id, (_, code))| (id.identifier(), hex::encode(keccak256(code))))
+            .collect();
+        Self { sources, bytecodes }
+    }
+
+    /// Returns the hash of the source file, `path` is either absolute or relative to the root
+    fn source(&self, root: &Path, path: &str) -> Option<&String> {
+        self.sources.get(&root.join(path))
+    }
+}
+
+/// Matches tests by the contracts they touched
+#[derive(Debug, Clone)]
+pub struct TouchingFilter {
+    /// Whether the recorded tests touched the target, by test contract identifier and signature
+    tests: BTreeMap<(String, String), bool>,
+}
+
+impl TouchingFilter {
+    /// Returns whether the test touched the target
+    ///
+    /// Tests without a valid record are always included, since they may touch the target.
+    pub fn matches(&self, contract_id: &str, signature: &str) -> bool {
+        self.tests.get(&(contract_id.to_string(), signature.to_string())).copied().unwrap_or(true)
+    }
+}
+
+/// Returns the path of the contract identifier `<path>:<name>`
+fn source_path(id: &str) -> &str {
+    id.rsplit_once(':').map(|(path, _)| path).unwrap_or(id)
+}
+
+/// Returns whether the contract identifier `<path>:<name>` matches the target, which can be the
+/// identifier itself, the path or the name of the contract
+fn is_target(root: &Path, id: &str, target: &str) -> bool {
+    if id == target {
+        return true
+    }
+    let (path, name) = id.rsplit_once(':').unwrap_or((id, ""));
+    if name == target {
+        return true
+    }
+    let target = Path::new(target.trim_start_matches("./"));
+    let target = target.strip_prefix(root).unwrap_or(target);
+    let path = Path::new(path);
+    path.strip_prefix(root).unwrap_or(path) == target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_match_touched_contract() {
+        let root = Path::new("/project");
+        assert!(is_target(root, "src/Vault.sol:Vault", "src/Vault.sol"));
+        assert!(is_target(root, "src/Vault.sol:Vault", "./src/Vault.sol"));
+        assert!(is_target(root, "src/Vault.sol:Vault", "/project/src/Vault.sol"));
+        assert!(is_target(root, "/project/src/Vault.sol:Vault", "src/Vault.sol"));
+        assert!(is_target(root, "src/Vault.sol:Vault", "Vault"));
+        assert!(is_target(root, "src/Vault.sol:Vault", "src/Vault.sol:Vault"));
+        assert!(!is_target(root, "src/Vault.sol:Vault", "src/Token.sol"));
+        assert!(!is_target(root, "src/Vault.sol:Vault", "Token"));
+    }
+
+    #[test]
+    fn can_find_test_files_touching() {
+        let mut touches = TestTouches::default();
+        touches.tests.insert(
+            "test/Vault.t.sol:VaultTest".to_string(),
+            BTreeMap::from([(
+                "testDeposit()".to_string(),
+                BTreeSet::from(["src/Vault.sol:Vault".to_string()]),
+            )]),
+        );
+        let root = Path::new("/project");
+        assert_eq!(
+            touches.test_files_touching(root, &[PathBuf::from("/project/src/Vault.sol")]),
+            BTreeSet::from([PathBuf::from("test/Vault.t.sol")])
+        );
+        assert!(touches.test_files_touching(root, &[PathBuf::from("src/Token.sol")]).is_empty());
+    }
+
+    #[test]
+    fn includes_unrecorded_tests() {
+        let filter = TestTouches::default().touching(
+            Path::new("/project"),
+            &ProjectHashes::default(),
+            "src/Vault.sol",
+        );
+        assert!(filter.matches("test/Vault.t.sol:VaultTest", "testDeposit()"));
+    }
+
+    #[test]
+    fn ignores_outdated_records() {
+        let root = Path::new("/project");
+        let test = "test/Vault.t.sol:VaultTest";
+        let vault = "src/Vault.sol:Vault";
+        let token = "src/Token.sol:Token";
+        let mut hashes = ProjectHashes::default();
+        for id in [test, vault, token] {
+            hashes.sources.insert(root.join(source_path(id)), format!("source {id}"));
+            hashes.bytecodes.insert(id.to_string(), format!("bytecode {id}"));
+        }
+
+        let mut touches = TestTouches::default();
+        touches.tests.insert(
+            test.to_string(),
+            BTreeMap::from([("testDeposit()".to_string(), BTreeSet::from([vault.to_string()]))]),
+        );
+        for id in [test, vault] {
+            touches.sources.insert(source_path(id).to_string(), format!("source {id}"));
+            touches.bytecodes.insert(id.to_string(), format!("bytecode {id}"));
+        }
+
+        assert!(!touches.touching(root, &hashes, "Token").matches(test, "testDeposit()"));
+        assert!(touches.touching(root, &hashes, "Vault").matches(test, "testDeposit()"));
+
+        // the bytecode changed, e.g. because of the compiler settings
+        hashes.bytecodes.insert(vault.to_string(), "changed".to_string());
+        assert!(touches.touching(root, &hashes, "Token").matches(test, "testDeposit()"));
+
+        // an import of the test changed
+        hashes.bytecodes.insert(vault.to_string(), format!("bytecode {vault}"));
+        hashes.sources.insert(root.join(source_path(test)), "changed".to_string());
+        assert!(touches.touching(root, &hashes, "Token").matches(test, "testDeposit()"));
+    }
+}
@@ -3,6 +3,11 @@
 use crate::cmd::Cmd;
 use clap::Parser;
 use ethers::solc::Graph;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
 
 foundry_config::impl_figment_convert!(TreeArgs, opts);
 use crate::cmd::{forge::build::ProjectPathsArgs, LoadConfig};
@@ -20,6 +25,14 @@ pub struct TreeArgs {
         value_name = "CHARSET"
     )]
     charset: Charset,
+    #[clap(
+        help = "Show all sources that (transitively) import the given source instead",
+        long,
+        value_name = "FILE"
+    )]
+    invert: Option<PathBuf>,
+    #[clap(help = "Print the imports of every source and all import cycles as JSON", long)]
+    json: bool,
     #[clap(flatten)]
     opts: ProjectPathsArgs,
 }
@@ -30,9 +43,258 @@ impl Cmd for TreeArgs {
     fn run(self) -> eyre::Result<Self::Output> {
         let config = self.try_load_config_emit_warnings()?;
         let graph = Graph::resolve(&config.project_paths())?;
+        let imports = ImportGraph::new(&graph, &config.__root.0);
+
+        if let Some(ref target) = self.invert {
+            let target = imports.resolve(&config.__root.0, target)?;
+            if self.json {
+                println!("{}", serde_json::to_string_pretty(&imports.importers_of(&target))?);
+            } else {
+                print!("{}", imports.format_inverted(&target, &self.charset, self.no_dedupe));
+            }
+            return Ok(())
+        }
+
+        let cycles = imports.cycles();
+        if self.json {
+            let output = TreeOutput { imports: &imports.imports, cycles };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(())
+        }
+
         let opts = TreeOptions { charset: self.charset, no_dedupe: self.no_dedupe };
         graph.print_with_options(opts);
 
+        if !cycles.is_empty() {
+            println!("\nImport cycles:");
+            for cycle in cycles {
+                let cycle = cycle.iter().map(|path| path.display().to_string()).collect::<Vec<_>>();
+                println!("  {}", cycle.join(" -> "));
+            }
+        }
+
         Ok(())
     }
 }
+
+/// The JSON output of `forge tree --json`
+#[derive(Serialize)]
+struct TreeOutput<'a> {
+    imports: &'a BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    cycles: Vec<Vec<PathBuf>>,
+}
+
+/// The direct imports of all sources, relative to the project root
+#[derive(Debug, Default)]
+struct ImportGraph {
+    imports: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl ImportGraph {
+    fn new(graph: &Graph, root: &Path) -> Self {
+        let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        let imports = graph
+            .files()
+            .iter()
+            .map(|(path, index)| {
+                let imports = graph
+                    .imported_nodes(*index)
+                    .iter()
+                    .map(|import| relative(graph.node(*import).path()))
+                    .collect();
+                (relative(path), imports)
+            })
+            .collect();
+        Self { imports }
+    }
+
+    /// Returns the path of `file` as it's stored in the graph
+    fn resolve(&self, root: &Path, file: &Path) -> eyre::Result<PathBuf> {
+        let file = file.strip_prefix(root).unwrap_or(file).to_path_buf();
+        if self.imports.contains_key(&file) {
+            Ok(file)
+        } else {
+            eyre::bail!("{} is not a source of the project", file.display())
+        }
+    }
+
+    /// Returns all sources that directly import each source
+    fn inverted(&self) -> BTreeMap<&Path, BTreeSet<&Path>> {
+        let mut importers: BTreeMap<&Path, BTreeSet<&Path>> = BTreeMap::new();
+        for (path, imports) in &self.imports {
+            for import in imports {
+                importers.entry(import.as_path()).or_default().insert(path.as_path());
+            }
+        }
+        importers
+    }
+
+    /// Returns all sources that transitively import `target`
+    fn importers_of(&self, target: &Path) -> BTreeSet<PathBuf> {
+        let inverted = self.inverted();
+        let mut importers = BTreeSet::new();
+        let mut stack = vec![target];
+        while let Some(path) = stack.pop() {
+            for &importer in inverted.get(path).into_iter().flatten() {
+                if importer != target && importers.insert(importer.to_path_buf()) {
+                    stack.push(importer);
+                }
+            }
+        }
+        importers
+    }
+
+    /// Returns the tree of all sources that transitively import `target`
+    ///
+    /// Unless `no_dedupe` is set, the importers of a source are only listed the first time it
+    /// appears and later occurrences are marked with `(*)`, like in `forge tree`.
+    fn format_inverted(&self, target: &Path, charset: &Charset, no_dedupe: bool) -> String {
+        let inverted = self.inverted();
+        let mut out = format!("{}\n", target.display());
+        let mut visited = BTreeSet::from([target]);
+        let mut ancestors = vec![target];
+        format_importers(
+            &inverted,
+            target,
+            charset,
+            no_dedupe,
+            "",
+            &mut visited,
+            &mut ancestors,
+            &mut out,
+        );
+        out
+    }
+
+    /// Returns every import cycle once, each starting and ending with the same source
+    fn cycles(&self) -> Vec<Vec<PathBuf>> {
+        let mut cycles = vec![];
+        let mut done = BTreeSet::new();
+        for path in self.imports.keys() {
+            let mut stack = vec![];
+            self.find_cycles(path, &mut stack, &mut done, &mut cycles);
+        }
+        cycles
+    }
+
+    fn find_cycles<'a>(
+        &'a self,
+        path: &'a Path,
+        stack: &mut Vec<&'a Path>,
+        done: &mut BTreeSet<&'a Path>,
+        cycles: &mut Vec<Vec<PathBuf>>,
+    ) {
+        if let Some(start) = stack.iter().position(|ancestor| *ancestor == path) {
+            let mut cycle = stack[start..].iter().map(|p| p.to_path_buf()).collect::<Vec<_>>();
+            cycle.push(path.to_path_buf());
+            cycles.push(cycle);
+            return
+        }
+        if done.contains(path) {
+            return
+        }
+
+        stack.push(path);
+        for import in self.imports.get(path).into_iter().flatten() {
+            self.find_cycles(import, stack, done, cycles);
+        }
+        stack.pop();
+        done.insert(path);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_importers<'a>(
+    inverted: &BTreeMap<&'a Path, BTreeSet<&'a Path>>,
+    path: &'a Path,
+    charset: &Charset,
+    no_dedupe: bool,
+    prefix: &str,
+    visited: &mut BTreeSet<&'a Path>,
+    ancestors: &mut Vec<&'a Path>,
+    out: &mut String,
+) {
+    let (branch, last_branch, pipe) = match charset {
+        Charset::Utf8 => ("├── ", "└── ", "│   "),
+        Charset::Ascii => ("|-- ", "`-- ", "|   "),
+    };
+    let importers = match inverted.get(path) {
+        Some(importers) => importers,
+        None => return,
+    };
+
+    for (i, &importer) in importers.iter().enumerate() {
+        let is_last = i == importers.len() - 1;
+        out.push_str(prefix);
+        out.push_str(if is_last { last_branch } else { branch });
+        out.push_str(&importer.display().to_string());
+
+        // cycles are never expanded, shared importers only once unless `no_dedupe`
+        if ancestors.contains(&importer) || (!no_dedupe && !visited.insert(importer)) {
+            out.push_str(" (*)\n");
+            continue
+        }
+        out.push('\n');
+
+        let prefix = format!("{prefix}{}", if is_last { "    " } else { pipe });
+        ancestors.push(importer);
+        format_importers(inverted, importer, charset, no_dedupe, &prefix, visited, ancestors, out);
+        ancestors.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &[&str])]) -> ImportGraph {
+        let imports = edges
+            .iter()
+            .map(|(path, imports)| {
+                (PathBuf::from(path), imports.iter().map(PathBuf::from).collect())
+            })
+            .collect();
+        ImportGraph { imports }
+    }
+
+    #[test]
+    fn can_find_importers() {
+        let graph = graph(&[
+            ("src/A.sol", &["src/Lib.sol"]),
+            ("src/B.sol", &["src/A.sol"]),
+            ("src/C.sol", &[]),
+            ("src/Lib.sol", &[]),
+        ]);
+
+        assert_eq!(
+            graph.importers_of(Path::new("src/Lib.sol")),
+            BTreeSet::from([PathBuf::from("src/A.sol"), PathBuf::from("src/B.sol")])
+        );
+        assert_eq!(
+            graph.format_inverted(Path::new("src/Lib.sol"), &Charset::Ascii, false),
+            "src/Lib.sol\n`-- src/A.sol\n    `-- src/B.sol\n"
+        );
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn can_detect_cycles() {
+        let graph = graph(&[
+            ("src/A.sol", &["src/B.sol"]),
+            ("src/B.sol", &["src/A.sol"]),
+            ("src/C.sol", &["src/C.sol"]),
+        ]);
+
+        assert_eq!(
+            graph.cycles(),
+            vec![
+                vec![PathBuf::from("src/A.sol"), PathBuf::from("src/B.sol"), "src/A.sol".into()],
+                vec![PathBuf::from("src/C.sol"), "src/C.sol".into()],
+            ]
+        );
+        assert_eq!(
+            graph.format_inverted(Path::new("src/A.sol"), &Charset::Ascii, false),
+            "src/A.sol\n`-- src/B.sol\n    `-- src/A.sol (*)\n"
+        );
+    }
+}
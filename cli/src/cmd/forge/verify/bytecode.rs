@@ -0,0 +1,323 @@
+//! verify-bytecode command
+
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, remove_contract, LoadConfig},
+    opts::RpcOpts,
+};
+use clap::Parser;
+use ethers::{
+    prelude::{
+        artifacts::{BytecodeObject, CompactBytecode, CompactDeployedBytecode},
+        Middleware,
+    },
+    solc::{info::ContractInfo, utils::canonicalized},
+    types::{Address, Bytes, TxHash},
+};
+use foundry_common::{compile, try_get_http_provider};
+use foundry_config::{impl_figment_convert, SolcReq};
+use semver::Version;
+use serde::Serialize;
+use std::fmt;
+use yansi::Paint;
+
+/// CLI arguments for `forge verify-bytecode`.
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyBytecodeArgs {
+    #[clap(help = "The address of the deployed contract.", value_name = "ADDRESS")]
+    pub address: Address,
+
+    #[clap(
+        help = "The contract identifier in the form `<path>:<contractname>`.",
+        value_name = "CONTRACT"
+    )]
+    pub contract: ContractInfo,
+
+    #[clap(
+        long,
+        help = "The transaction that created the contract.",
+        long_help = "The transaction that created the contract. If set, the creation code of the transaction is compared as well and the constructor arguments are extracted from it.",
+        value_name = "TX_HASH"
+    )]
+    pub creation_tx: Option<TxHash>,
+
+    #[clap(long, help = "Print the result as JSON.")]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub rpc: RpcOpts,
+
+    #[clap(flatten)]
+    pub build: CoreBuildArgs,
+}
+
+impl_figment_convert!(VerifyBytecodeArgs, build, rpc);
+
+impl VerifyBytecodeArgs {
+    /// Compiles the contract locally and compares it with the deployed bytecode
+    ///
+    /// Unless a solc version is set with `--use`, the version encoded in the metadata of the
+    /// deployed bytecode is used.
+    pub async fn run(mut self) -> eyre::Result<BytecodeVerification> {
+        let mut config = self.try_load_config_emit_warnings()?;
+        let rpc_url = self.rpc.url(Some(&config))?.ok_or_else(|| {
+            eyre::eyre!("No RPC URL set, use --rpc-url or set `eth_rpc_url` in foundry.toml")
+        })?;
+        let provider = try_get_http_provider(rpc_url.as_ref())?;
+
+        let onchain = provider.get_code(self.address, None).await?;
+        if onchain.is_empty() {
+            eyre::bail!("No bytecode deployed at {:?}", self.address)
+        }
+        let creation_input = match self.creation_tx {
+            Some(hash) => {
+                let tx = provider
+                    .get_transaction(hash)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Creation transaction {hash:?} not found"))?;
+                if tx.to.is_some() {
+                    eyre::bail!(
+                        "Transaction {hash:?} is not a contract creation, contracts deployed by factories can only be verified by their runtime code"
+                    )
+                }
+                Some(tx.input)
+            }
+            None => None,
+        };
+
+        let compiler_version = match self.build.use_solc {
+            Some(ref version) => Some(version.clone()),
+            None => metadata_solc_version(&onchain).map(|version| {
+                config.solc = Some(SolcReq::Version(version.clone()));
+                version.to_string()
+            }),
+        };
+
+        let project = config.project()?;
+        let mut output = if self.json || self.build.silent {
+            compile::suppress_compile(&project, &config)
+        } else {
+            compile::compile(&project, &config, false, false)
+        }?;
+        if let Some(ref mut path) = self.contract.path {
+            // paths are absolute in the project's output
+            *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
+        }
+        let (_, bytecode, deployed_bytecode) = remove_contract(&mut output, &self.contract)?;
+
+        let (runtime_code, mut masked) = match deployed_bytecode.bytecode {
+            Some(ref bytecode) => decode_masked(bytecode)?,
+            None => eyre::bail!("Contract {} has no deployed bytecode", self.contract),
+        };
+        masked.extend(immutable_ranges(&deployed_bytecode));
+        let runtime = compare_bytecode(&runtime_code, &onchain, &masked);
+
+        let (creation, constructor_args) = match creation_input {
+            Some(input) => {
+                let (creation_code, masked) = decode_masked(&bytecode)?;
+                if input.len() < creation_code.len() {
+                    (Some(BytecodeMatch::Mismatch), None)
+                } else {
+                    let (code, args) = input.split_at(creation_code.len());
+                    let creation = compare_bytecode(&creation_code, code, &masked);
+                    (Some(creation), Some(Bytes::from(args.to_vec())))
+                }
+            }
+            None => (None, None),
+        };
+
+        let verification =
+            BytecodeVerification { runtime, creation, constructor_args, compiler_version };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&verification)?);
+        } else {
+            println!("{verification}");
+        }
+
+        if verification.runtime == BytecodeMatch::Mismatch ||
+            verification.creation == Some(BytecodeMatch::Mismatch)
+        {
+            eyre::bail!("Bytecode of {:?} does not match {}", self.address, self.contract)
+        }
+        Ok(verification)
+    }
+}
+
+/// How well local bytecode matches deployed bytecode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BytecodeMatch {
+    /// The bytecode matches, including the metadata hash
+    Exact,
+    /// The bytecode matches, but the metadata hash differs, e.g. because of different comments
+    /// or source paths
+    Partial,
+    /// The bytecode does not match
+    Mismatch,
+}
+
+impl fmt::Display for BytecodeMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytecodeMatch::Exact => write!(f, "{}", Paint::green("exact match")),
+            BytecodeMatch::Partial => {
+                write!(f, "{}", Paint::yellow("partial match (metadata differs)"))
+            }
+            BytecodeMatch::Mismatch => write!(f, "{}", Paint::red("mismatch")),
+        }
+    }
+}
+
+/// The result of `forge verify-bytecode`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BytecodeVerification {
+    pub runtime: BytecodeMatch,
+    /// The match of the creation code, if the creation transaction is known
+    pub creation: Option<BytecodeMatch>,
+    /// The constructor arguments of the creation transaction
+    pub constructor_args: Option<Bytes>,
+    /// The solc version the contract was compiled with
+    pub compiler_version: Option<String>,
+}
+
+impl fmt::Display for BytecodeVerification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Runtime code: {}", self.runtime)?;
+        if let Some(creation) = self.creation {
+            writeln!(f, "Creation code: {creation}")?;
+        }
+        if let Some(ref args) = self.constructor_args {
+            writeln!(f, "Constructor arguments: {args}")?;
+        }
+        if let Some(ref version) = self.compiler_version {
+            write!(f, "Compiler version: {version}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares local bytecode with deployed bytecode, ignoring the `masked` ranges of the local
+/// bytecode, like immutables and library addresses
+pub fn compare_bytecode(local: &[u8], onchain: &[u8], masked: &[(usize, usize)]) -> BytecodeMatch {
+    let equal = |len: usize| {
+        let mut local = local[..len].to_vec();
+        let mut onchain = onchain[..len].to_vec();
+        mask(&mut local, masked);
+        mask(&mut onchain, masked);
+        local == onchain
+    };
+
+    if local.len() == onchain.len() && equal(local.len()) {
+        return BytecodeMatch::Exact
+    }
+    match (metadata_len(local), metadata_len(onchain)) {
+        (Some(local_meta), Some(onchain_meta))
+            if local.len() - local_meta == onchain.len() - onchain_meta &&
+                equal(local.len() - local_meta) =>
+        {
+            BytecodeMatch::Partial
+        }
+        _ => BytecodeMatch::Mismatch,
+    }
+}
+
+/// Returns the length of the CBOR encoded metadata at the end of the bytecode, including the two
+/// bytes that encode its length
+pub fn metadata_len(code: &[u8]) -> Option<usize> {
+    if code.len() < 2 {
+        return None
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize + 2;
+    // the metadata is a CBOR map
+    if len > code.len() || !(0xa0..=0xbf).contains(&code[code.len() - len]) {
+        return None
+    }
+    Some(len)
+}
+
+/// Returns the solc version encoded in the metadata of the bytecode
+pub fn metadata_solc_version(code: &[u8]) -> Option<Version> {
+    let metadata = &code[code.len() - metadata_len(code)?..];
+    // the "solc" key, followed by the version as 3 bytes
+    let key = [0x64, b's', b'o', b'l', b'c', 0x43];
+    let pos = metadata.windows(key.len()).position(|window| window == key)? + key.len();
+    let version = metadata.get(pos..pos + 3)?;
+    Some(Version::new(version[0] as u64, version[1] as u64, version[2] as u64))
+}
+
+/// Decodes the bytecode and returns it with the ranges of unlinked library addresses
+fn decode_masked(bytecode: &CompactBytecode) -> eyre::Result<(Vec<u8>, Vec<(usize, usize)>)> {
+    let ranges = bytecode
+        .link_references
+        .values()
+        .flat_map(|libs| libs.values().flatten())
+        .map(|offset| (offset.start as usize, offset.length as usize))
+        .collect::<Vec<_>>();
+    let code = match bytecode.object {
+        BytecodeObject::Bytecode(ref code) => code.to_vec(),
+        BytecodeObject::Unlinked(ref code) => {
+            let mut code = code.strip_prefix("0x").unwrap_or(code).to_string();
+            for (start, len) in &ranges {
+                code.replace_range(start * 2..(start + len) * 2, &"0".repeat(len * 2));
+            }
+            hex::decode(code)?
+        }
+    };
+    Ok((code, ranges))
+}
+
+/// Returns the ranges of all immutables in the deployed bytecode
+fn immutable_ranges(
+    bytecode: &CompactDeployedBytecode,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    bytecode
+        .immutable_references
+        .values()
+        .flatten()
+        .map(|offset| (offset.start as usize, offset.length as usize))
+}
+
+/// Zeroes all ranges of the code
+fn mask(code: &mut [u8], ranges: &[(usize, usize)]) {
+    for (start, len) in ranges {
+        let end = (start + len).min(code.len());
+        if *start < end {
+            code[*start..end].iter_mut().for_each(|b| *b = 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `PUSH1 0x80 PUSH1 0x40 MSTORE` followed by the metadata of solc 0.8.17
+    fn code(metadata_hash: u8) -> Vec<u8> {
+        let mut code = vec![0x60, 0x80, 0x60, 0x40, 0x52];
+        let mut metadata = vec![0xa2, 0x64, b'i', b'p', b'f', b's', 0x42, metadata_hash, 0x00];
+        metadata.extend([0x64, b's', b'o', b'l', b'c', 0x43, 0x00, 0x08, 0x11]);
+        code.extend(&metadata);
+        code.extend((metadata.len() as u16).to_be_bytes());
+        code
+    }
+
+    #[test]
+    fn can_decode_metadata() {
+        assert_eq!(metadata_len(&code(1)), Some(20));
+        assert_eq!(metadata_solc_version(&code(1)), Some(Version::new(0, 8, 17)));
+        assert_eq!(metadata_len(&[0x60, 0x80, 0x60, 0x40, 0x52]), None);
+    }
+
+    #[test]
+    fn can_compare_bytecode() {
+        assert_eq!(compare_bytecode(&code(1), &code(1), &[]), BytecodeMatch::Exact);
+        assert_eq!(compare_bytecode(&code(1), &code(2), &[]), BytecodeMatch::Partial);
+
+        let mut other = code(1);
+        other[1] = 0x60;
+        assert_eq!(compare_bytecode(&code(1), &other, &[]), BytecodeMatch::Mismatch);
+        // the byte of an immutable differs
+        assert_eq!(compare_bytecode(&code(1), &other, &[(1, 1)]), BytecodeMatch::Exact);
+        assert_eq!(compare_bytecode(&code(1), &code(1)[..5], &[]), BytecodeMatch::Mismatch);
+    }
+}
@@ -3,8 +3,8 @@ mod standard_json;
 
 use super::{VerifyArgs, VerifyCheckArgs};
 use crate::cmd::{
-    forge::verify::provider::VerificationProvider, get_cached_entry_by_name,
-    read_constructor_args_file, retry::RETRY_CHECK_ON_VERIFY, LoadConfig,
+    forge::verify::provider::{VerificationProvider, VerificationRejected, VerificationSubmitted},
+    get_cached_entry_by_name, read_constructor_args_file, retry::RETRY_CHECK_ON_VERIFY, LoadConfig,
 };
 use cast::SimpleCast;
 use ethers::{
@@ -18,7 +18,7 @@ use ethers::{
 };
 use eyre::{eyre, Context};
 use foundry_common::abi::encode_args;
-use foundry_config::{Chain, Config, SolcReq};
+use foundry_config::{with_key_rotation, Chain, Config, SolcReq};
 use foundry_utils::Retry;
 use futures::FutureExt;
 use once_cell::sync::Lazy;
@@ -70,15 +70,17 @@ impl VerificationProvider for EtherscanVerificationProvider {
         let resp = retry.run_async(|| {
             async {
                 println!("\nSubmitting verification for [{}] {:?}.", verify_args.contract_name, SimpleCast::to_checksum_address(&verify_args.address));
-                let resp = etherscan
-                    .submit_contract_verification(&verify_args)
-                    .await
-                    .wrap_err_with(|| {
-                        // valid json
-                        let args = serde_json::to_string(&verify_args).unwrap();
-                        error!(target : "forge::verify",  ?args, "Failed to submit verification");
-                        format!("Failed to submit contract verification, payload:\n{args}")
-                    })?;
+                let resp = with_key_rotation(&etherscan, |client| {
+                    let verify_args = &verify_args;
+                    async move { client.submit_contract_verification(verify_args).await }
+                })
+                .await
+                .wrap_err_with(|| {
+                    // valid json
+                    let args = serde_json::to_string(&verify_args).unwrap();
+                    error!(target : "forge::verify",  ?args, "Failed to submit verification");
+                    format!("Failed to submit contract verification, payload:\n{args}")
+                })?;
 
                 trace!(target : "forge::verify",  ?resp, "Received verification response");
 
@@ -92,12 +94,10 @@ impl VerificationProvider for EtherscanVerificationProvider {
                         return Err(eyre!("Etherscan could not detect the deployment."))
                     }
 
-                    warn!("Failed verify submission: {:?}", resp);
-                    eprintln!(
-                        "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
-                    );
-                    std::process::exit(1);
+                    if resp.result.to_lowercase().contains("rate limit") {
+                        warn!("{}", resp.result);
+                        return Err(eyre!("Etherscan rate limit reached: {}", resp.result))
+                    }
                 }
 
                 Ok(Some(resp))
@@ -105,24 +105,36 @@ impl VerificationProvider for EtherscanVerificationProvider {
                 .boxed()
         }).await?;
 
+        if let Some(resp) = resp.as_ref().filter(|resp| resp.status == "0") {
+            warn!("Failed verify submission: {:?}", resp);
+            eprintln!(
+                "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
+                resp.message, resp.result
+            );
+            return Err(VerificationRejected(resp.result.clone()).into())
+        }
+
         if let Some(resp) = resp {
             println!(
                 "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`\n\tURL:
         {}",
                 resp.message,
                 resp.result,
-                etherscan.address_url(args.address)
+                etherscan[0].address_url(args.address)
             );
 
             if args.watch {
                 let check_args = VerifyCheckArgs {
-                    id: resp.result,
+                    id: resp.result.clone(),
                     etherscan: args.etherscan,
                     retry: RETRY_CHECK_ON_VERIFY,
                     verifier: args.verifier,
                 };
                 // return check_args.run().await
-                return self.check(check_args).await
+                return self
+                    .check(check_args)
+                    .await
+                    .map_err(|err| VerificationSubmitted { guid: resp.result, err }.into())
             }
         } else {
             println!("Contract source code already verified");
@@ -134,7 +146,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
     /// Executes the command to check verification status on Etherscan
     async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()> {
         let config = args.try_load_config_emit_warnings()?;
-        let etherscan = self.client(
+        let etherscan = self.clients(
             args.etherscan.chain.unwrap_or_default(),
             args.verifier.verifier_url.as_deref(),
             args.etherscan.key.as_deref(),
@@ -144,10 +156,12 @@ impl VerificationProvider for EtherscanVerificationProvider {
         retry
             .run_async(|| {
                 async {
-                    let resp = etherscan
-                        .check_contract_verification_status(args.id.clone())
-                        .await
-                        .wrap_err("Failed to request verification status")?;
+                    let resp = with_key_rotation(&etherscan, |client| {
+                        let id = args.id.clone();
+                        async move { client.check_contract_verification_status(id).await }
+                    })
+                    .await
+                    .wrap_err("Failed to request verification status")?;
 
                     trace!(target : "forge::verify",  ?resp, "Received verification response");
 
@@ -218,9 +232,9 @@ impl EtherscanVerificationProvider {
     async fn prepare_request(
         &mut self,
         args: &VerifyArgs,
-    ) -> eyre::Result<(Client, VerifyContract)> {
+    ) -> eyre::Result<(Vec<Client>, VerifyContract)> {
         let config = args.try_load_config_emit_warnings()?;
-        let etherscan = self.client(
+        let etherscan = self.clients(
             args.etherscan.chain.unwrap_or_default(),
             args.verifier.verifier_url.as_deref(),
             args.etherscan.key.as_deref(),
@@ -231,7 +245,7 @@ impl EtherscanVerificationProvider {
         Ok((etherscan, verify_args))
     }
 
-    /// Create an etherscan client
+    /// Create an etherscan client for the first API key
     pub(crate) fn client(
         &self,
         chain: Chain,
@@ -239,6 +253,20 @@ impl EtherscanVerificationProvider {
         etherscan_key: Option<&str>,
         config: &Config,
     ) -> eyre::Result<Client> {
+        Ok(self.clients(chain, verifier_url, etherscan_key, config)?.remove(0))
+    }
+
+    /// Create an etherscan client for every API key
+    ///
+    /// The key can be a comma separated list of keys, otherwise all keys of the etherscan config
+    /// are used. Requests rotate through the clients via [with_key_rotation].
+    pub(crate) fn clients(
+        &self,
+        chain: Chain,
+        verifier_url: Option<&str>,
+        etherscan_key: Option<&str>,
+        config: &Config,
+    ) -> eyre::Result<Vec<Client>> {
         let etherscan_config = config.get_etherscan_config_with_chain(Some(chain))?;
 
         let api_url =
@@ -248,21 +276,27 @@ impl EtherscanVerificationProvider {
             .and_then(|c| c.browser_url.as_deref())
             .or_else(|| chain.etherscan_urls().map(|urls| urls.1));
 
-        let etherscan_key =
-            etherscan_key.or_else(|| etherscan_config.as_ref().map(|c| c.key.as_str()));
+        let mut keys = match etherscan_key {
+            Some(key) => key.split(',').map(str::trim).filter(|key| !key.is_empty()).collect(),
+            None => etherscan_config.as_ref().map(|c| c.keys().collect()).unwrap_or_default(),
+        };
+        if keys.is_empty() {
+            keys.push("");
+        }
 
-        let mut builder = Client::builder();
+        keys.into_iter()
+            .map(|key| {
+                let mut builder = Client::builder();
 
-        builder = if let Some(api_url) = api_url {
-            builder.with_api_url(api_url)?.with_url(base_url.unwrap_or(api_url))?
-        } else {
-            builder.chain(chain.to_owned().try_into()?)?
-        };
+                builder = if let Some(api_url) = api_url {
+                    builder.with_api_url(api_url)?.with_url(base_url.unwrap_or(api_url))?
+                } else {
+                    builder.chain(chain.to_owned().try_into()?)?
+                };
 
-        builder
-            .with_api_key(etherscan_key.unwrap_or_default())
-            .build()
-            .wrap_err("Failed to create etherscan client")
+                builder.with_api_key(key).build().wrap_err("Failed to create etherscan client")
+            })
+            .collect()
     }
 
     /// Creates the `VerifyContract` etherscan request in order to verify the contract
@@ -505,6 +539,58 @@ mod tests {
         assert!(format!("{client:?}").contains("dummykey"));
     }
 
+    #[test]
+    fn can_create_client_per_etherscan_key() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+
+        let config = r#"
+                [profile.default]
+
+                [etherscan]
+                mumbai = { key = "dummykey", keys = ["otherkey"], chain = 80001, url = "https://api-testnet.polygonscan.com/" }
+            "#;
+
+        let toml_file = root.join(Config::FILE_NAME);
+        fs::write(toml_file, config).unwrap();
+
+        let args: VerifyArgs = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0xd8509bee9c9bf012282ad33aba0d87241baf5064",
+            "src/Counter.sol:Counter",
+            "--chain",
+            "mumbai",
+            "--root",
+            root.as_os_str().to_str().unwrap(),
+        ]);
+
+        let config = args.load_config();
+
+        let etherscan = EtherscanVerificationProvider::default();
+        let clients = etherscan
+            .clients(
+                args.etherscan.chain.unwrap_or_default(),
+                args.verifier.verifier_url.as_deref(),
+                args.etherscan.key.as_deref(),
+                &config,
+            )
+            .unwrap();
+        assert_eq!(clients.len(), 2);
+        assert!(format!("{:?}", clients[0]).contains("dummykey"));
+        assert!(format!("{:?}", clients[1]).contains("otherkey"));
+
+        let clients = etherscan
+            .clients(
+                args.etherscan.chain.unwrap_or_default(),
+                args.verifier.verifier_url.as_deref(),
+                Some("key1, key2"),
+                &config,
+            )
+            .unwrap();
+        assert_eq!(clients.len(), 2);
+        assert!(format!("{:?}", clients[1]).contains("key2"));
+    }
+
     #[tokio::test]
     async fn fails_on_disabled_cache_and_missing_info() {
         let temp = tempdir().unwrap();
@@ -12,11 +12,14 @@ use clap::{Parser, ValueHint};
 use ethers::{abi::Address, solc::info::ContractInfo};
 use foundry_config::{figment, impl_figment_convert, impl_figment_convert_cast, Config};
 use provider::VerificationProviderType;
+use queue::{QueuedVerification, VerificationQueue};
 use reqwest::Url;
 use std::path::PathBuf;
 
+pub mod bytecode;
 mod etherscan;
 pub mod provider;
+pub mod queue;
 mod sourcify;
 
 /// Verification provider arguments
@@ -167,6 +170,8 @@ impl figment::Provider for VerifyArgs {
 
 impl VerifyArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
+    ///
+    /// The verification is queued, so it can be resumed with `forge verify-resume` if it fails.
     pub async fn run(mut self) -> eyre::Result<()> {
         let config = self.load_config_emit_warnings();
         let chain = config.chain_id.unwrap_or_default();
@@ -180,6 +185,22 @@ impl VerifyArgs {
             return Ok(())
         }
 
+        let mut queue = VerificationQueue::load(&config);
+        let index = queue.push(QueuedVerification::new(&self, chain, &config.__root.0));
+        queue.save()?;
+        queue.process(&config, vec![index], self.retry).await
+    }
+
+    /// Submits the verification request to the verification provider
+    pub async fn submit(mut self) -> eyre::Result<()> {
+        let config = self.load_config_emit_warnings();
+        let chain = self.etherscan.chain.or(config.chain_id).unwrap_or_default();
+        self.etherscan.chain = Some(chain);
+        if self.etherscan.key.is_none() {
+            self.etherscan.key =
+                config.get_etherscan_config_with_chain(Some(chain))?.map(|c| c.key);
+        }
+
         let verifier_url = self.verifier.verifier_url.clone();
         println!("Start verifying contract `{:?}` deployed on {chain}", self.address);
         self.verifier.verifier.client(&self.etherscan.key)?.verify(self).await.map_err(|err| {
@@ -239,6 +260,61 @@ impl VerifyCheckArgs {
     }
 }
 
+/// CLI arguments for `forge verify-resume`.
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyResumeArgs {
+    #[clap(flatten)]
+    retry: RetryArgs,
+
+    #[clap(flatten)]
+    etherscan: EtherscanOpts,
+
+    #[clap(
+        help = "The project's root path.",
+        long_help = "The project's root path. By default, this is the root directory of the current Git repository, or the current working directory.",
+        long,
+        value_hint = ValueHint::DirPath,
+        value_name = "PATH"
+    )]
+    root: Option<PathBuf>,
+}
+
+impl_figment_convert!(VerifyResumeArgs);
+
+impl VerifyResumeArgs {
+    /// Submits all queued verifications that didn't succeed yet
+    pub async fn run(self) -> eyre::Result<()> {
+        let config = self.try_load_config_emit_warnings()?;
+        let mut queue = VerificationQueue::load(&config);
+        queue.reset_failed();
+        let unverified = queue.unverified();
+        if unverified.is_empty() {
+            println!("No pending verifications");
+            return Ok(())
+        }
+        println!("Resuming ({}) verifications", unverified.len());
+        queue.process(&config, unverified, self.retry).await?;
+        println!("All queued contracts were verified!");
+        Ok(())
+    }
+}
+
+impl figment::Provider for VerifyResumeArgs {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Verify Resume Provider")
+    }
+
+    fn data(
+        &self,
+    ) -> Result<figment::value::Map<figment::Profile, figment::value::Dict>, figment::Error> {
+        let mut dict = self.etherscan.dict();
+        if let Some(root) = self.root.as_ref() {
+            dict.insert("root".to_string(), figment::value::Value::serialize(root)?);
+        }
+        Ok(figment::value::Map::from([(Config::selected_profile(), dict)]))
+    }
+}
+
 impl figment::Provider for VerifyCheckArgs {
     fn metadata(&self) -> figment::Metadata {
         figment::Metadata::named("Verify Check Provider")
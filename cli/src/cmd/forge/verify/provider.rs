@@ -3,7 +3,8 @@ use crate::cmd::forge::verify::{
     VerifyCheckArgs,
 };
 use async_trait::async_trait;
-use std::{fmt, str::FromStr};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr, time::Duration};
 
 /// An abstraction for various verification providers such as etherscan, sourcify, blockscout
 #[async_trait]
@@ -24,6 +25,20 @@ pub trait VerificationProvider {
     async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()>;
 }
 
+/// The verification provider rejected the submitted contract, submitting it again won't succeed
+#[derive(Debug, thiserror::Error)]
+#[error("verification was rejected: {0}")]
+pub struct VerificationRejected(pub String);
+
+/// The verification provider accepted the submitted contract, but checking the status of the
+/// verification failed, so submitting it again would only create a duplicate request
+#[derive(Debug, thiserror::Error)]
+#[error("verification `{guid}` was submitted, but checking its status failed: {err:#}")]
+pub struct VerificationSubmitted {
+    pub guid: String,
+    pub err: eyre::Report,
+}
+
 impl FromStr for VerificationProviderType {
     type Err = String;
 
@@ -54,7 +69,8 @@ impl fmt::Display for VerificationProviderType {
     }
 }
 
-#[derive(clap::ValueEnum, Debug, Clone, PartialEq, Eq)]
+#[derive(clap::ValueEnum, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum VerificationProviderType {
     Etherscan,
     Sourcify,
@@ -79,4 +95,17 @@ impl VerificationProviderType {
             }
         }
     }
+
+    /// Returns the minimum time between two requests, according to the rate limit of the free
+    /// tier of the provider
+    pub fn request_interval(&self) -> Duration {
+        match self {
+            // 5 requests per second
+            VerificationProviderType::Etherscan => Duration::from_millis(200),
+            // 10 requests per second
+            VerificationProviderType::Blockscout => Duration::from_millis(100),
+            // not documented, verifying a contract compiles it on the server
+            VerificationProviderType::Sourcify => Duration::from_secs(1),
+        }
+    }
 }
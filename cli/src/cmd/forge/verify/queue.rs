@@ -0,0 +1,465 @@
+//! A queue of pending contract verifications that is persisted to the cache dir, so verifications
+//! that failed (e.g. because of rate limits) can be resumed with `forge verify-resume`
+
+use super::{
+    provider::{VerificationRejected, VerificationSubmitted},
+    VerifierArgs, VerifyArgs,
+};
+use crate::{
+    cmd::{forge::verify::provider::VerificationProviderType, retry::RetryArgs},
+    opts::EtherscanOpts,
+};
+use ethers::{solc::info::ContractInfo, types::Address};
+use foundry_common::fs;
+use foundry_config::{Chain, Config};
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{trace, warn};
+
+/// The file of the queue in the cache dir
+pub const VERIFICATION_QUEUE_FILE: &str = "verification-queue.json";
+
+/// The maximum time to wait before retrying a failed submission
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// The state of a queued verification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationStatus {
+    Pending,
+    Verified,
+    /// The provider accepted the verification with the given id, but its status is unknown
+    Submitted(String),
+    /// All attempts failed, with the error of the last attempt
+    Failed(String),
+}
+
+impl VerificationStatus {
+    /// Returns true if the verification still needs to be submitted
+    pub fn is_unsubmitted(&self) -> bool {
+        matches!(self, VerificationStatus::Pending | VerificationStatus::Failed(_))
+    }
+}
+
+/// Everything needed to submit a verification again, except for the API key which is resolved
+/// from the config when the verification is submitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedVerification {
+    pub address: Address,
+    pub chain: Chain,
+    pub contract_path: Option<String>,
+    pub contract_name: String,
+    pub constructor_args: Option<String>,
+    pub constructor_args_path: Option<PathBuf>,
+    pub compiler_version: Option<String>,
+    pub num_of_optimizations: Option<usize>,
+    pub libraries: Vec<String>,
+    pub root: Option<PathBuf>,
+    pub flatten: bool,
+    pub force: bool,
+    pub watch: bool,
+    pub verifier: VerificationProviderType,
+    pub verifier_url: Option<String>,
+    pub status: VerificationStatus,
+    /// The number of submissions of the verification
+    pub attempts: u32,
+}
+
+impl QueuedVerification {
+    /// Creates a pending verification of the contract of the `args`
+    pub fn new(args: &VerifyArgs, chain: Chain, root: &Path) -> Self {
+        Self {
+            address: args.address,
+            chain,
+            contract_path: args.contract.path.clone(),
+            contract_name: args.contract.name.clone(),
+            constructor_args: args.constructor_args.clone(),
+            constructor_args_path: args.constructor_args_path.clone(),
+            compiler_version: args.compiler_version.clone(),
+            num_of_optimizations: args.num_of_optimizations,
+            libraries: args.libraries.clone(),
+            root: Some(args.root.clone().unwrap_or_else(|| root.to_path_buf())),
+            flatten: args.flatten,
+            force: args.force,
+            watch: args.watch,
+            verifier: args.verifier.verifier.clone(),
+            verifier_url: args.verifier.verifier_url.clone(),
+            status: VerificationStatus::Pending,
+            attempts: 0,
+        }
+    }
+
+    /// Returns the args to submit the verification once, retries are handled by the queue
+    fn verify_args(&self, key: Option<String>) -> VerifyArgs {
+        VerifyArgs {
+            address: self.address,
+            contract: ContractInfo {
+                path: self.contract_path.clone(),
+                name: self.contract_name.clone(),
+            },
+            constructor_args: self.constructor_args.clone(),
+            constructor_args_path: self.constructor_args_path.clone(),
+            compiler_version: self.compiler_version.clone(),
+            num_of_optimizations: self.num_of_optimizations,
+            etherscan: EtherscanOpts { key, chain: Some(self.chain) },
+            flatten: self.flatten,
+            force: self.force,
+            watch: self.watch,
+            retry: RetryArgs { retries: 0, delay: 0 },
+            libraries: self.libraries.clone(),
+            root: self.root.clone(),
+            verifier: VerifierArgs {
+                verifier: self.verifier.clone(),
+                verifier_url: self.verifier_url.clone(),
+            },
+            show_standard_json_input: false,
+        }
+    }
+
+    fn is_same_contract(&self, other: &Self) -> bool {
+        self.chain.id() == other.chain.id() && self.address == other.address
+    }
+}
+
+/// The pending and failed verifications of a project
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VerificationQueue {
+    #[serde(skip)]
+    path: PathBuf,
+    verifications: Vec<QueuedVerification>,
+}
+
+impl VerificationQueue {
+    /// Reads the queue of the project, or returns an empty queue if there is none
+    pub fn load(config: &Config) -> Self {
+        let path = config.cache_path.join(VERIFICATION_QUEUE_FILE);
+        let mut queue = if path.exists() {
+            fs::read_json_file::<Self>(&path).unwrap_or_else(|err| {
+                warn!(?err, "failed to read the verification queue");
+                Self::default()
+            })
+        } else {
+            Self::default()
+        };
+        queue.path = path;
+        queue
+    }
+
+    /// Writes the verifications that were not submitted successfully yet to the cache dir, the
+    /// file is removed once all verifications were submitted
+    pub fn save(&self) -> eyre::Result<()> {
+        let verifications = self
+            .verifications
+            .iter()
+            .filter(|verification| verification.status.is_unsubmitted())
+            .cloned()
+            .collect::<Vec<_>>();
+        if verifications.is_empty() {
+            if self.path.exists() {
+                std::fs::remove_file(&self.path)?;
+            }
+            return Ok(())
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write_json_file(&self.path, &Self { path: PathBuf::new(), verifications })?;
+        Ok(())
+    }
+
+    /// Adds a verification to the queue, replacing any queued verification of the same contract,
+    /// and returns its index
+    pub fn push(&mut self, verification: QueuedVerification) -> usize {
+        match self.verifications.iter().position(|queued| queued.is_same_contract(&verification)) {
+            Some(index) => {
+                self.verifications[index] = verification;
+                index
+            }
+            None => {
+                self.verifications.push(verification);
+                self.verifications.len() - 1
+            }
+        }
+    }
+
+    /// Returns the indices of all verifications that were not submitted successfully yet
+    pub fn unverified(&self) -> Vec<usize> {
+        (0..self.verifications.len())
+            .filter(|index| self.verifications[*index].status.is_unsubmitted())
+            .collect()
+    }
+
+    /// Marks failed verifications as pending again, so they get all attempts of a new run
+    pub fn reset_failed(&mut self) {
+        for verification in self.verifications.iter_mut() {
+            if matches!(verification.status, VerificationStatus::Failed(_)) {
+                verification.status = VerificationStatus::Pending;
+                verification.attempts = 0;
+            }
+        }
+    }
+
+    /// Submits the verifications one after another
+    ///
+    /// Submissions are spaced according to the rate limit of the verification provider. Failed
+    /// submissions are retried up to `retry.retries` times, with an exponential backoff starting
+    /// at `retry.delay` seconds and some jitter, so a rate limited provider isn't hit again at
+    /// once. Rejected verifications and verifications the provider accepted are never submitted
+    /// again. The queue is saved after every submission.
+    pub async fn process(
+        &mut self,
+        config: &Config,
+        indices: Vec<usize>,
+        retry: RetryArgs,
+    ) -> eyre::Result<()> {
+        self.process_with(config, indices, retry, VerifyArgs::submit).await
+    }
+
+    async fn process_with<F, Fut>(
+        &mut self,
+        config: &Config,
+        indices: Vec<usize>,
+        retry: RetryArgs,
+        mut submit: F,
+    ) -> eyre::Result<()>
+    where
+        F: FnMut(VerifyArgs) -> Fut,
+        Fut: Future<Output = eyre::Result<()>>,
+    {
+        let total = indices.len();
+        let mut failed = 0;
+        let mut unconfirmed = vec![];
+        for (n, index) in indices.into_iter().enumerate() {
+            if n > 0 {
+                tokio::time::sleep(self.verifications[index].verifier.request_interval()).await;
+            }
+            loop {
+                let verification = &mut self.verifications[index];
+                verification.attempts += 1;
+                let args = verification
+                    .verify_args(config.get_etherscan_api_key(Some(verification.chain)));
+                trace!(
+                    target: "forge::verify",
+                    address=?verification.address,
+                    attempt=verification.attempts,
+                    "submitting queued verification"
+                );
+
+                let err = match submit(args).await {
+                    Ok(()) => {
+                        verification.status = VerificationStatus::Verified;
+                        break
+                    }
+                    Err(err) => err,
+                };
+                if let Some(submitted) = err.downcast_ref::<VerificationSubmitted>() {
+                    eprintln!("Failed to verify `{}`: {err:#}", verification.contract_name);
+                    verification.status = VerificationStatus::Submitted(submitted.guid.clone());
+                    unconfirmed.push(submitted.guid.clone());
+                    break
+                }
+                let rejected = err.downcast_ref::<VerificationRejected>().is_some();
+                if rejected || verification.attempts > retry.retries {
+                    verification.status = VerificationStatus::Failed(format!("{err:#}"));
+                    failed += 1;
+                    eprintln!(
+                        "Failed to verify `{}` at {:?} after {} attempt(s): {err:#}",
+                        verification.contract_name, verification.address, verification.attempts
+                    );
+                    break
+                }
+
+                let wait = backoff(retry.delay, verification.attempts);
+                eprintln!(
+                    "Failed to verify `{}` at {:?}: {err:#}\nRetrying in {}s...",
+                    verification.contract_name,
+                    verification.address,
+                    wait.as_secs()
+                );
+                self.save()?;
+                tokio::time::sleep(wait).await;
+            }
+            self.save()?;
+        }
+
+        if failed > 0 {
+            eyre::bail!(
+                "{failed} of {total} verification(s) failed, run `forge verify-resume` to retry them"
+            )
+        }
+        if !unconfirmed.is_empty() {
+            eyre::bail!(
+                "{} of {total} verification(s) were submitted but not confirmed, run \
+                 `forge verify-check <GUID>` to check them: {}",
+                unconfirmed.len(),
+                unconfirmed.join(", ")
+            )
+        }
+        Ok(())
+    }
+}
+
+/// Returns the time to wait before the next attempt, doubling `delay` seconds with every attempt
+/// and adding up to 50% jitter
+fn backoff(delay: u32, attempt: u32) -> Duration {
+    let base = Duration::from_secs(delay.max(1).into())
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_BACKOFF);
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+    let jitter = base.mul_f64(f64::from(nanos % 1000) / 2000.0);
+    base + jitter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verification(address: u64) -> QueuedVerification {
+        QueuedVerification {
+            address: Address::from_low_u64_be(address),
+            chain: Chain::Id(1),
+            contract_path: None,
+            contract_name: "Counter".to_string(),
+            constructor_args: None,
+            constructor_args_path: None,
+            compiler_version: None,
+            num_of_optimizations: None,
+            libraries: vec![],
+            root: None,
+            flatten: false,
+            force: false,
+            watch: true,
+            verifier: VerificationProviderType::Etherscan,
+            verifier_url: None,
+            status: VerificationStatus::Pending,
+            attempts: 0,
+        }
+    }
+
+    fn config(dir: &Path) -> Config {
+        Config { cache_path: dir.to_path_buf(), ..Default::default() }
+    }
+
+    const RETRY: RetryArgs = RetryArgs { retries: 3, delay: 0 };
+
+    #[test]
+    fn saves_and_resumes_unsubmitted_verifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+
+        let mut queue = VerificationQueue::load(&config);
+        for address in 1..=4 {
+            queue.push(verification(address));
+        }
+        // pushing the same contract again replaces it
+        assert_eq!(queue.push(verification(1)), 0);
+        queue.verifications[1].status = VerificationStatus::Verified;
+        queue.verifications[2].status = VerificationStatus::Submitted("guid".to_string());
+        queue.verifications[3].status = VerificationStatus::Failed("rate limit".to_string());
+        queue.verifications[3].attempts = 4;
+        queue.save().unwrap();
+
+        let mut queue = VerificationQueue::load(&config);
+        let addresses =
+            queue.verifications.iter().map(|verification| verification.address).collect::<Vec<_>>();
+        assert_eq!(addresses, vec![Address::from_low_u64_be(1), Address::from_low_u64_be(4)]);
+        assert_eq!(queue.unverified(), vec![0, 1]);
+
+        queue.reset_failed();
+        assert_eq!(queue.verifications[1].status, VerificationStatus::Pending);
+        assert_eq!(queue.verifications[1].attempts, 0);
+
+        // the file is removed once everything was submitted
+        for verification in queue.verifications.iter_mut() {
+            verification.status = VerificationStatus::Verified;
+        }
+        queue.save().unwrap();
+        assert!(!config.cache_path.join(VERIFICATION_QUEUE_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_rejected_verifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let mut queue = VerificationQueue::load(&config);
+        queue.push(verification(1));
+
+        let mut submissions = 0;
+        let res = queue
+            .process_with(&config, vec![0], RETRY, |_| {
+                submissions += 1;
+                async { Err(eyre::Report::new(VerificationRejected("invalid source".to_string()))) }
+            })
+            .await;
+        assert!(res.is_err());
+        assert_eq!(submissions, 1);
+        assert!(matches!(queue.verifications[0].status, VerificationStatus::Failed(_)));
+        assert_eq!(queue.unverified(), vec![0]);
+    }
+
+    #[tokio::test]
+    async fn does_not_resubmit_accepted_verifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let mut queue = VerificationQueue::load(&config);
+        queue.push(verification(1));
+
+        let mut submissions = 0;
+        let res = queue
+            .process_with(&config, vec![0], RETRY, |_| {
+                submissions += 1;
+                async {
+                    Err(eyre::Report::new(VerificationSubmitted {
+                        guid: "guid".to_string(),
+                        err: eyre::eyre!("Verification is still pending..."),
+                    }))
+                }
+            })
+            .await;
+        assert!(res.unwrap_err().to_string().contains("guid"));
+        assert_eq!(submissions, 1);
+        assert_eq!(queue.verifications[0].status, VerificationStatus::Submitted("guid".into()));
+        assert!(queue.unverified().is_empty());
+        assert!(!config.cache_path.join(VERIFICATION_QUEUE_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn retries_failed_submissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path());
+        let mut queue = VerificationQueue::load(&config);
+        queue.push(verification(1));
+
+        let mut submissions = 0;
+        queue
+            .process_with(&config, vec![0], RETRY, |_| {
+                submissions += 1;
+                let attempt = submissions;
+                async move {
+                    if attempt == 1 {
+                        eyre::bail!("Etherscan rate limit reached")
+                    }
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(submissions, 2);
+        assert_eq!(queue.verifications[0].status, VerificationStatus::Verified);
+    }
+
+    #[test]
+    fn backoff_grows_exponentially() {
+        assert!(backoff(5, 1) >= Duration::from_secs(5));
+        assert!(backoff(5, 1) < Duration::from_millis(7500));
+        assert!(backoff(5, 3) >= Duration::from_secs(20));
+        assert!(backoff(5, 3) < Duration::from_secs(30));
+        assert!(backoff(5, 20) <= MAX_BACKOFF.mul_f64(1.5));
+        // no delay still backs off
+        assert!(backoff(0, 1) >= Duration::from_secs(1));
+    }
+}
@@ -1,12 +1,25 @@
 //! Watch mode support
 
 use crate::{
-    cmd::forge::{build::BuildArgs, snapshot::SnapshotArgs, test::TestArgs},
+    cmd::forge::{
+        build::BuildArgs,
+        snapshot::SnapshotArgs,
+        test::{TestArgs, TestSession},
+    },
     utils::{self, FoundryPathExt},
 };
 use clap::{ArgAction, Parser};
 use foundry_config::Config;
-use std::{collections::HashSet, convert::Infallible, path::PathBuf, sync::Arc};
+use std::{
+    convert::Infallible,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+};
 use tracing::trace;
 use watchexec::{
     action::{Action, Outcome, PreSpawn},
@@ -18,6 +31,7 @@ use watchexec::{
     signal::source::MainSignal,
     Watchexec,
 };
+use yansi::Paint;
 
 #[derive(Debug, Clone, Parser, Default)]
 #[clap(next_help_heading = "Watch options")]
@@ -40,7 +54,9 @@ pub struct WatchArgs {
 
     /// Explicitly re-run all tests when a change is made.
     ///
-    /// By default, only the tests of the last modified test file are executed.
+    /// By default, only the tests affected by the changed files are executed: the tests of test
+    /// files that import a changed file and the tests that touched a contract of a changed file
+    /// the last time they were executed with traces.
     #[clap(long)]
     pub run_all: bool,
 
@@ -122,136 +138,118 @@ pub async fn watch_snapshot(args: SnapshotArgs) -> eyre::Result<()> {
     Ok(())
 }
 
-/// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
-/// test`
+/// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns the
+/// tests affected by the changes
+///
+/// Unlike `forge build --watch`, the tests are executed in this process, so the config, the
+/// project and the environment of the tests are only set up once, see [TestSession]. Changes made
+/// while the tests are running are tested once they finished.
 pub async fn watch_test(args: TestArgs) -> eyre::Result<()> {
     let (init, mut runtime) = args.watchexec_config()?;
-    let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
-    trace!("watch test cmd={:?}", cmd);
-    runtime.command(watch_command(cmd.clone()));
-    let wx = Watchexec::new(init, runtime.clone())?;
+    args.install_shell()?;
 
     let config: Config = args.build_args().into();
-
     let filter = args.filter(&config);
 
-    // marker to check whether to override the command
-    let no_reconfigure = filter.args().pattern.is_some() ||
+    // marker to check whether only the tests affected by the changes are executed
+    let run_all = filter.args().pattern.is_some() ||
         filter.args().test_pattern.is_some() ||
         filter.args().path_pattern.is_some() ||
         filter.args().contract_pattern.is_some() ||
         args.watch.run_all;
 
-    let state = WatchTestState {
-        project_root: config.__root.0,
-        no_reconfigure,
-        last_test_files: Default::default(),
-    };
-    on_action(args.watch.clone(), runtime, Arc::clone(&wx), cmd, state, on_test);
+    let (tx, rx) = channel();
+    let worker = thread::spawn(move || run_tests_on_change(args, run_all, rx));
 
-    // start executing the command immediately
-    wx.send_event(Event::default(), Priority::default()).await?;
-    wx.main().await??;
+    let tx = Mutex::new(tx);
+    let worker_panicked = Arc::new(AtomicBool::new(false));
+    let panicked = Arc::clone(&worker_panicked);
+    runtime.on_action(move |action: Action| {
+        let fut = async { Ok::<(), Infallible>(()) };
+        let signals: Vec<MainSignal> = action.events.iter().flat_map(|e| e.signals()).collect();
+        if signals.contains(&MainSignal::Terminate) || signals.contains(&MainSignal::Interrupt) {
+            action.outcome(Outcome::both(Outcome::Stop, Outcome::Exit));
+            return fut
+        }
 
-    Ok(())
-}
+        let changed: Vec<PathBuf> = action
+            .events
+            .iter()
+            .flat_map(|e| e.paths())
+            .map(|(path, _)| path.to_path_buf())
+            .collect();
+        // the event that starts the watcher has no paths
+        let changed = if changed.is_empty() { None } else { Some(changed) };
+        if tx.lock().unwrap().send(changed).is_err() {
+            // the receiver is only dropped if the worker panicked
+            panicked.store(true, Ordering::SeqCst);
+            action.outcome(Outcome::both(Outcome::Stop, Outcome::Exit));
+            return fut
+        }
 
-#[derive(Debug, Clone)]
-struct WatchTestState {
-    /// the root directory of the project
-    project_root: PathBuf,
-    /// marks whether we can reconfigure the watcher command with the `--match-path` arg
-    no_reconfigure: bool,
-    /// Tracks the last changed test files, if any so that if a non-test file was modified we run
-    /// this file instead *Note:* this is a vec, so we can also watch out for changes
-    /// introduced by `forge fmt`
-    last_test_files: HashSet<String>,
-}
+        action.outcome(Outcome::DoNothing);
+        fut
+    });
 
-/// The `on_action` hook for `forge test --watch`
-fn on_test(action: OnActionState<WatchTestState>) {
-    let OnActionState { args, runtime, action, wx, cmd, other } = action;
-    let WatchTestState { project_root, no_reconfigure, last_test_files } = other;
+    let wx = Watchexec::new(init, runtime)?;
 
-    if no_reconfigure {
-        // nothing to reconfigure
-        return
-    }
+    // start executing the tests immediately
+    wx.send_event(Event::default(), Priority::default()).await?;
+    wx.main().await??;
 
-    let mut cmd = cmd.clone();
-
-    let mut changed_sol_test_files: HashSet<_> = action
-        .events
-        .iter()
-        .flat_map(|e| e.paths())
-        .filter(|(path, _)| path.is_sol_test())
-        .filter_map(|(path, _)| path.to_str())
-        .map(str::to_string)
-        .collect();
-
-    // replace `--match-path` | `-mp` argument
-    if let Some(pos) = cmd.iter().position(|arg| arg == "--match-path" || arg == "-mp") {
-        // --match-path requires 1 argument
-        cmd.drain(pos..=(pos + 1));
+    if worker_panicked.load(Ordering::SeqCst) {
+        if let Err(panic) = worker.join() {
+            std::panic::resume_unwind(panic)
+        }
     }
 
-    if changed_sol_test_files.len() > 1 ||
-        (changed_sol_test_files.is_empty() && last_test_files.is_empty())
-    {
-        // this could happen if multiple files were changed at once, for example `forge fmt` was
-        // run, or if no test files were changed and no previous test files were modified in which
-        // case we simply run all
-        let mut config = runtime.clone();
-        config.command(watch_command(cmd.clone()));
-        // re-register the action
-        on_action(
-            args.clone(),
-            config,
-            wx,
-            cmd,
-            WatchTestState {
-                project_root,
-                no_reconfigure,
-                last_test_files: changed_sol_test_files,
-            },
-            on_test,
-        );
-        return
-    }
+    Ok(())
+}
 
-    if changed_sol_test_files.is_empty() {
-        // reuse the old test files if a non-test file was changed
-        changed_sol_test_files = last_test_files;
-    }
+/// Executes the tests affected by the changed files it receives, all tests are executed for
+/// `None`
+fn run_tests_on_change(args: TestArgs, run_all: bool, rx: Receiver<Option<Vec<PathBuf>>>) {
+    let mut session = TestSession::default();
+    while let Ok(mut changed) = rx.recv() {
+        // merge the changes that were made while the tests were running
+        while let Ok(more) = rx.try_recv() {
+            changed = changed.zip(more).map(|(mut changed, more)| {
+                changed.extend(more);
+                changed
+            });
+        }
 
-    // append `--match-path` glob
-    let mut file = changed_sol_test_files.clone().into_iter().next().expect("test file present");
+        let test_files = match changed {
+            _ if run_all => None,
+            Some(changed) if changed.iter().all(|path| path.is_sol()) => {
+                match session.affected_test_files(&changed) {
+                    Ok(files) => files,
+                    Err(err) => {
+                        trace!(?err, "failed to resolve the affected test files");
+                        None
+                    }
+                }
+            }
+            Some(_) => {
+                // e.g. the config changed
+                session.reset();
+                None
+            }
+            None => None,
+        };
+        if let Some(files) = &test_files {
+            if files.is_empty() {
+                println!("No tests are affected by the changes");
+                continue
+            }
+            let files = files.iter().map(|file| file.display().to_string()).collect::<Vec<_>>();
+            println!("Running the tests of {}", files.join(", "));
+        }
 
-    // remove the project root dir from the detected file
-    if let Some(root) = project_root.as_os_str().to_str() {
-        if let Some(f) = file.strip_prefix(root) {
-            file = f.trim_start_matches('/').to_string();
+        if let Err(err) = args.clone().execute_tests_in(&mut session, test_files) {
+            eprintln!("{}", Paint::red(format!("{err:?}")));
         }
     }
-
-    let mut new_cmd = cmd.clone();
-    new_cmd.push("--match-path".to_string());
-    new_cmd.push(file);
-    trace!("reconfigure test command {:?}", new_cmd);
-
-    // reconfigure the executor with a new runtime
-    let mut config = runtime.clone();
-    config.command(watch_command(new_cmd));
-
-    // re-register the action
-    on_action(
-        args.clone(),
-        config,
-        wx,
-        cmd,
-        WatchTestState { project_root, no_reconfigure, last_test_files: changed_sol_test_files },
-        on_test,
-    );
 }
 
 /// Converts a list of arguments to a `watchexec::Command`
@@ -15,7 +15,6 @@ use forge::executor::opts::EvmOpts;
 use foundry_common::{cli_warn, fs, TestFunctionExt};
 use foundry_config::{error::ExtractConfigError, figment::Figment, Chain as ConfigChain, Config};
 use std::{fmt::Write, path::PathBuf};
-use tracing::trace;
 use yansi::Paint;
 
 /// Common trait for all cli commands
@@ -241,12 +240,7 @@ where
 
         let mut evm_opts = figment.extract::<EvmOpts>().map_err(ExtractConfigError::new)?;
         let config = Config::try_from(figment)?.sanitized();
-
-        // update the fork url if it was an alias
-        if let Some(fork_url) = config.get_rpc_url() {
-            trace!(target: "forge::config", ?fork_url, "Update EvmOpts fork url");
-            evm_opts.fork_url = Some(fork_url?.into_owned());
-        }
+        evm_opts.resolve_fork(&config)?;
 
         Ok((config, evm_opts))
     }
@@ -0,0 +1,242 @@
+//! Shell completions with dynamic values
+//!
+//! The scripts generated by [clap_complete] only know the static structure of the CLI. For options
+//! whose values depend on the project or the machine, like RPC aliases from `foundry.toml`, the
+//! generated scripts are patched to call the hidden `__complete <kind>` subcommand, which prints
+//! one candidate per line.
+
+use clap::{Command, ValueEnum};
+use clap_complete::Shell;
+use ethers::types::Chain as NamedChain;
+use foundry_config::Config;
+use regex::Regex;
+use std::{io, path::Path};
+use strum::VariantNames;
+
+/// The kinds of values that are completed dynamically
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionKind {
+    /// The aliases of `rpc_endpoints`
+    RpcAliases,
+    /// The keystores in `~/.foundry/keystores`
+    Accounts,
+    /// The names of all known chains
+    Chains,
+    /// The names of all contracts in the project's artifacts
+    Contracts,
+}
+
+impl CompletionKind {
+    fn name(&self) -> &'static str {
+        match self {
+            CompletionKind::RpcAliases => "rpc-aliases",
+            CompletionKind::Accounts => "accounts",
+            CompletionKind::Chains => "chains",
+            CompletionKind::Contracts => "contracts",
+        }
+    }
+
+    /// Returns all candidates for this kind, sorted
+    pub fn candidates(&self) -> Vec<String> {
+        let mut candidates = match self {
+            CompletionKind::RpcAliases => load_config()
+                .map(|config| config.rpc_endpoints.resolved().keys().cloned().collect())
+                .unwrap_or_default(),
+            CompletionKind::Accounts => Config::foundry_dir()
+                .and_then(|dir| std::fs::read_dir(dir.join("keystores")).ok())
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok())
+                        .filter(|entry| entry.path().is_file())
+                        .map(|entry| entry.path().display().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            CompletionKind::Chains => NamedChain::VARIANTS.iter().map(|s| s.to_string()).collect(),
+            CompletionKind::Contracts => load_config()
+                .map(|config| contract_names(&config.project_paths().artifacts))
+                .unwrap_or_default(),
+        };
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// The long options whose values are completed dynamically
+const DYNAMIC_OPTIONS: &[(&str, CompletionKind)] = &[
+    ("rpc-url", CompletionKind::RpcAliases),
+    ("fork-url", CompletionKind::RpcAliases),
+    ("keystore", CompletionKind::Accounts),
+    ("chain", CompletionKind::Chains),
+    ("match-contract", CompletionKind::Contracts),
+    ("no-match-contract", CompletionKind::Contracts),
+    ("target-contract", CompletionKind::Contracts),
+];
+
+/// Prints all candidates of the given kind, one per line
+pub fn print_candidates(kind: CompletionKind) {
+    for candidate in kind.candidates() {
+        println!("{candidate}");
+    }
+}
+
+/// Generates the completions script for the shell, with dynamic completions for bash, zsh and fish
+pub fn generate(
+    shell: Shell,
+    cmd: &mut Command,
+    bin: &str,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    clap_complete::generate(shell, cmd, bin, &mut buf);
+    let script = String::from_utf8_lossy(&buf);
+    let script = match shell {
+        Shell::Bash => patch_bash(&script, bin),
+        Shell::Zsh => patch_zsh(&script, bin),
+        Shell::Fish => patch_fish(&script, bin),
+        _ => script.into_owned(),
+    };
+    out.write_all(script.as_bytes())
+}
+
+/// Replaces the file completion of the dynamic options in the `case "${prev}"` blocks
+fn patch_bash(script: &str, bin: &str) -> String {
+    let mut patched = String::with_capacity(script.len());
+    let mut pending = None;
+    for line in script.lines() {
+        let trimmed = line.trim();
+        if let Some(kind) = pending {
+            if trimmed.starts_with("COMPREPLY=(") {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                patched.push_str(&format!(
+                    "{indent}COMPREPLY=($(compgen -W \"$({bin} __complete {} 2>/dev/null)\" -- \"${{cur}}\"))\n",
+                    kind.name()
+                ));
+                pending = None;
+                continue
+            }
+        }
+        if let Some(opt) = trimmed.strip_prefix("--").and_then(|opt| opt.strip_suffix(')')) {
+            pending = dynamic_kind(opt);
+        }
+        patched.push_str(line);
+        patched.push('\n');
+    }
+    patched
+}
+
+/// Replaces the action of the dynamic options in the `_arguments` specs
+fn patch_zsh(script: &str, bin: &str) -> String {
+    let mut script = script.to_string();
+    for (opt, kind) in DYNAMIC_OPTIONS {
+        let re = Regex::new(&format!(r"(?m)^(\s*'(?:\([^)]*\))?--{opt}=\[.*\]:[^:']*:)[^']*'"))
+            .expect("valid regex");
+        let action = format!("{{compadd -- $({bin} __complete {} 2>/dev/null)}}'", kind.name());
+        script = re
+            .replace_all(&script, |caps: &regex::Captures| format!("{}{action}", &caps[1]))
+            .into_owned();
+    }
+    script
+}
+
+/// Adds the candidates to the `complete` commands of the dynamic options
+fn patch_fish(script: &str, bin: &str) -> String {
+    let mut patched = String::with_capacity(script.len());
+    for line in script.lines() {
+        patched.push_str(line);
+        if line.starts_with("complete -c") {
+            let kind = line
+                .split_whitespace()
+                .skip_while(|word| *word != "-l")
+                .nth(1)
+                .and_then(dynamic_kind);
+            if let Some(kind) = kind {
+                patched.push_str(&format!(" -f -a \"({bin} __complete {})\"", kind.name()));
+            }
+        }
+        patched.push('\n');
+    }
+    patched
+}
+
+fn dynamic_kind(opt: &str) -> Option<CompletionKind> {
+    DYNAMIC_OPTIONS.iter().find(|(name, _)| *name == opt).map(|(_, kind)| *kind)
+}
+
+/// Loads the config of the current project, without emitting warnings or panicking
+fn load_config() -> Option<Config> {
+    Config::try_from(Config::figment()).ok().map(Config::sanitized)
+}
+
+/// Returns the names of all contracts in the artifacts directory, from `<file>.sol/<name>.json`
+fn contract_names(artifacts: &Path) -> Vec<String> {
+    walkdir::WalkDir::new(artifacts)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.path().extension().map_or(false, |ext| ext == "json") &&
+                entry
+                    .path()
+                    .parent()
+                    .and_then(|dir| dir.extension())
+                    .map_or(false, |ext| ext == "sol" || ext == "yul" || ext == "vy")
+        })
+        .filter_map(|entry| {
+            // multiple compiler versions are stored as `<name>.<version>.json`
+            let name = entry.path().file_stem()?.to_str()?;
+            Some(name.split('.').next().unwrap_or(name).to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_patch_bash() {
+        let script = r#"
+            case "${prev}" in
+                --rpc-url)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --from)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+"#;
+        let patched = patch_bash(script, "cast");
+        assert!(patched.contains(
+            r#"                    COMPREPLY=($(compgen -W "$(cast __complete rpc-aliases 2>/dev/null)" -- "${cur}"))"#
+        ));
+        assert_eq!(patched.matches(r#"compgen -f "${cur}""#).count(), 1);
+    }
+
+    #[test]
+    fn can_patch_zsh() {
+        let script =
+            "'--rpc-url=[The RPC endpoint]:URL: ' \\\n'--from=[The sender]:ADDRESS: ' \\\n";
+        let patched = patch_zsh(script, "cast");
+        assert!(patched.contains(
+            "'--rpc-url=[The RPC endpoint]:URL:{compadd -- $(cast __complete rpc-aliases 2>/dev/null)}' \\"
+        ));
+        assert!(patched.contains("'--from=[The sender]:ADDRESS: ' \\"));
+    }
+
+    #[test]
+    fn can_patch_fish() {
+        let script = "complete -c forge -n \"__fish_seen_subcommand_from test\" -l match-contract -d 'Only run tests in contracts matching the regex' -r\n";
+        let patched = patch_fish(script, "forge");
+        assert!(patched.trim_end().ends_with("-r -f -a \"(forge __complete contracts)\""));
+    }
+
+    #[test]
+    fn can_complete_chains() {
+        let chains = CompletionKind::Chains.candidates();
+        assert!(chains.contains(&"mainnet".to_string()));
+    }
+}
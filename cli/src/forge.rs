@@ -1,22 +1,27 @@
 use clap::{CommandFactory, Parser};
-use clap_complete::generate;
 use foundry_cli::{
     cmd::{
         forge::{cache::CacheSubcommands, watch},
         Cmd,
     },
-    handler,
+    completions, handler,
     opts::forge::{Opts, Subcommands},
-    utils,
+    telemetry, utils,
 };
 
 fn main() -> eyre::Result<()> {
     utils::load_dotenv();
     handler::install()?;
-    utils::subscriber();
+    let opts = Opts::parse();
+    utils::subscriber_with_telemetry(opts.telemetry.clone(), "forge")?;
     utils::enable_paint();
 
-    let opts = Opts::parse();
+    let res = run(opts);
+    telemetry::flush();
+    res
+}
+
+fn run(opts: Opts) -> eyre::Result<()> {
     match opts.sub {
         Subcommands::Test(cmd) => {
             if cmd.is_watch() {
@@ -56,6 +61,12 @@ fn main() -> eyre::Result<()> {
         Subcommands::VerifyCheck(args) => {
             utils::block_on(args.run())?;
         }
+        Subcommands::VerifyResume(args) => {
+            utils::block_on(args.run())?;
+        }
+        Subcommands::VerifyBytecode(args) => {
+            utils::block_on(args.run())?;
+        }
         Subcommands::Cache(cmd) => match cmd.sub {
             CacheSubcommands::Clean(cmd) => {
                 cmd.run()?;
@@ -81,8 +92,9 @@ fn main() -> eyre::Result<()> {
             cmd.run()?;
         }
         Subcommands::Completions { shell } => {
-            generate(shell, &mut Opts::command(), "forge", &mut std::io::stdout())
+            completions::generate(shell, &mut Opts::command(), "forge", &mut std::io::stdout())?
         }
+        Subcommands::Complete { kind } => completions::print_candidates(kind),
         Subcommands::GenerateFigSpec => clap_complete::generate(
             clap_complete_fig::Fig,
             &mut Opts::command(),
@@ -112,6 +124,12 @@ fn main() -> eyre::Result<()> {
         Subcommands::Inspect(cmd) => {
             cmd.run()?;
         }
+        Subcommands::StorageCheck(cmd) => {
+            cmd.run()?;
+        }
+        Subcommands::DiffBytecode(args) => {
+            utils::block_on(args.run())?;
+        }
         Subcommands::UploadSelectors(args) => {
             utils::block_on(args.run())?;
         }
@@ -121,9 +139,15 @@ fn main() -> eyre::Result<()> {
         Subcommands::Geiger(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Lsp(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Doc(cmd) => {
             cmd.run()?;
         }
+        Subcommands::ExportDeployments(cmd) => {
+            cmd.run()?;
+        }
     }
 
     Ok(())
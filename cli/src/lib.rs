@@ -1,6 +1,8 @@
 pub mod cmd;
+pub mod completions;
 pub mod handler;
 pub mod opts;
 pub mod stdin;
 pub mod suggestions;
+pub mod telemetry;
 pub mod utils;
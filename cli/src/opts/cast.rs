@@ -1,17 +1,20 @@
-use super::{EtherscanOpts, RpcOpts};
+use super::{ChainValueParser, EtherscanOpts, RpcOpts};
 use crate::{
     cmd::cast::{
-        bind::BindArgs, call::CallArgs, create2::Create2Args, estimate::EstimateArgs,
-        find_block::FindBlockArgs, interface::InterfaceArgs, rpc::RpcArgs, run::RunArgs,
-        send::SendTxArgs, storage::StorageArgs, wallet::WalletSubcommands,
+        abi_diff::AbiDiffArgs, bind::BindArgs, call::CallArgs, calldata_cost::CalldataCostArgs,
+        create2::Create2Args, create2_deployer::Create2DeployerArgs, estimate::EstimateArgs,
+        find_block::FindBlockArgs, gas_history::GasHistoryArgs, interface::InterfaceArgs,
+        logs::LogsArgs, rpc::RpcArgs, run::RunArgs, send::SendTxArgs, storage::StorageArgs,
+        user_op::UserOpSubcommands, wallet::WalletSubcommands, watch_mempool::WatchMempoolArgs,
     },
     utils::parse_u256,
 };
 use clap::{Parser, Subcommand, ValueHint};
 use ethers::{
     abi::ethabi::ethereum_types::BigEndianHash,
-    types::{serde_helpers::Numeric, Address, BlockId, NameOrAddress, H256, U256},
+    types::{serde_helpers::Numeric, Address, BlockId, Bytes, NameOrAddress, H256, U256},
 };
+use foundry_config::Chain;
 use std::{path::PathBuf, str::FromStr};
 
 #[derive(Debug, Parser)]
@@ -304,6 +307,14 @@ Examples:
         #[clap(long = "json", short = 'j', help_heading = "Display options")]
         to_json: bool,
 
+        #[clap(
+            long,
+            help = "The chain whose extra fields are decoded, e.g. the L1 fee of Optimism. Defaults to the chain of the RPC.",
+            value_parser = ChainValueParser::default(),
+            value_name = "CHAIN"
+        )]
+        chain: Option<Chain>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -392,9 +403,46 @@ Examples:
         #[clap(long = "json", short = 'j', help_heading = "Display options")]
         to_json: bool,
 
+        #[clap(
+            long,
+            help = "Retrieve and decode the blobs of an EIP-4844 transaction from a beacon node.",
+            conflicts_with = "field",
+            requires = "beacon_url"
+        )]
+        blobs: bool,
+
+        #[clap(
+            long,
+            env = "ETH_BEACON_URL",
+            help = "The URL of the beacon node API to fetch blobs from.",
+            value_name = "URL"
+        )]
+        beacon_url: Option<String>,
+
+        #[clap(
+            long,
+            help = "The chain whose extra fields are decoded, e.g. the L1 fee of Optimism. Defaults to the chain of the RPC.",
+            value_parser = ChainValueParser::default(),
+            value_name = "CHAIN"
+        )]
+        chain: Option<Chain>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
+    #[clap(name = "logs")]
+    #[clap(visible_alias = "lo")]
+    #[clap(
+        about = "Get logs by signature or topic, decoded with an event signature or ABI.",
+        long_about = r#"Get logs by signature or topic, decoded with an event signature or ABI.
+
+Block ranges are requested in chunks to respect the limits of the RPC. The logs can be exported as JSON lines or CSV.
+
+Examples:
+- cast logs --from-block 15000000 --address 0x... "Transfer(address indexed from, address indexed to, uint256 value)"
+- cast logs --from-block 15000000 --abi out/Token.sol/Token.json --format csv"#
+    )]
+    Logs(LogsArgs),
     #[clap(name = "receipt")]
     #[clap(visible_alias = "re")]
     #[clap(about = "Get the transaction receipt for a transaction.")]
@@ -425,6 +473,14 @@ Examples:
         #[clap(long = "json", short = 'j', help_heading = "Display options")]
         to_json: bool,
 
+        #[clap(
+            long,
+            help = "The chain whose extra fields are decoded, e.g. the L1 fee of Optimism. Defaults to the chain of the RPC.",
+            value_parser = ChainValueParser::default(),
+            value_name = "CHAIN"
+        )]
+        chain: Option<Chain>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -468,6 +524,15 @@ Examples:
         #[clap(help = "The ABI-encoded calldata.", value_name = "CALLDATA")]
         calldata: String,
     },
+    #[clap(
+        name = "calldata-cost",
+        visible_alias = "cdc",
+        about = "Calculate the gas cost of calldata.",
+        long_about = r#"Calculate the gas cost of calldata.
+
+Supports the L1 rules before and after EIP-2028 and the EIP-7623 floor, and estimates the L1 data gas of OP stack and Arbitrum transactions. Pass --compare to show the gas under all rules."#
+    )]
+    CalldataCost(CalldataCostArgs),
     #[clap(name = "--abi-decode")]
     #[clap(visible_alias = "ad")]
     #[clap(
@@ -500,6 +565,50 @@ Defaults to decoding output data. To decode input data pass --input or use cast
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
     },
+    #[clap(name = "abi-encode-json")]
+    #[clap(visible_alias = "aej")]
+    #[clap(
+        about = "ABI encode the function arguments given as JSON, excluding the selector.",
+        long_about = r#"ABI encode the function arguments given as JSON, excluding the selector.
+
+Structs are JSON objects keyed by the names of their fields in the signature, or arrays of their fields in order. The field names of objects are validated against the signature.
+
+Example: cast abi-encode-json "f((address to,uint256 amount)[] transfers)" '{"transfers": [{"to": "0x...", "amount": 1}]}'"#
+    )]
+    AbiEncodeJson {
+        #[clap(
+            help = "The function signature, with the names of all struct fields.",
+            value_name = "SIG"
+        )]
+        sig: String,
+
+        #[clap(
+            help = "The arguments as JSON object keyed by the parameter names, or as JSON array.",
+            value_name = "JSON"
+        )]
+        json: String,
+    },
+    #[clap(name = "abi-decode-json")]
+    #[clap(visible_alias = "adj")]
+    #[clap(
+        about = "Decode ABI-encoded input or output data to JSON",
+        long_about = r#"Decode ABI-encoded input or output data to JSON.
+
+Structs are decoded as JSON objects keyed by the names of their fields in the signature, numbers as decimal strings. Defaults to decoding output data. To decode input data pass --input."#
+    )]
+    AbiDecodeJson {
+        #[clap(
+            help = "The function signature in the format `<name>(<in-params>)(<out-params>)`, with the names of all struct fields.",
+            value_name = "SIG"
+        )]
+        sig: String,
+
+        #[clap(help = "The ABI-encoded data.", value_name = "DATA")]
+        data: String,
+
+        #[clap(long, short, help = "Decode input data.")]
+        input: bool,
+    },
     #[clap(name = "index")]
     #[clap(visible_alias = "in")]
     #[clap(about = "Compute the storage slot for an entry in a mapping.")]
@@ -695,6 +804,32 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         #[clap(flatten)]
         rpc: RpcOpts,
     },
+    #[clap(name = "gas-history")]
+    #[clap(visible_alias = "gh")]
+    #[clap(
+        about = "Aggregate the fee history of recent blocks.",
+        long_about = r#"Aggregate the fee history of recent blocks.
+
+Prints the base fee, the priority fees at the given percentiles and the blob base fee of every block, followed by the base fee trend and suggested fee parameters.
+
+Examples:
+- cast gas-history --blocks 100 --percentiles 10,50,90
+- cast gas-history --block 19000000 --json"#
+    )]
+    GasHistory(GasHistoryArgs),
+    #[clap(name = "watch-mempool")]
+    #[clap(visible_alias = "wm")]
+    #[clap(
+        about = "Watch pending transactions and decode their calldata.",
+        long_about = r#"Watch pending transactions and decode their calldata.
+
+Subscribes to pending transactions over a WebSocket RPC, filters them by recipient and function selector, and decodes the calldata with the given function signatures or ABIs. The transactions can be printed as JSON lines for piping.
+
+Examples:
+- cast watch-mempool --rpc-url wss://... --to 0x... --selector "transfer(address to, uint256 amount)"
+- cast watch-mempool --rpc-url wss://... --abi out/Router.sol/Router.json --json"#
+    )]
+    WatchMempool(WatchMempoolArgs),
     #[clap(name = "sig-event")]
     #[clap(visible_alias = "se")]
     #[clap(about = "Generate event signatures from event string.")]
@@ -748,7 +883,10 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
     #[clap(
         name = "proof",
         visible_alias = "pr",
-        about = "Generate a storage proof for a given storage slot."
+        about = "Generate a storage proof for a given storage slot.",
+        after_help = "Examples:
+- cast proof 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2 0 --verify
+- cast proof 0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2 0 -B 17000000 --state-root 0x..."
     )]
     Proof {
         #[clap(help = "The contract address.", value_parser = NameOrAddress::from_str, value_name = "ADDRESS")]
@@ -766,6 +904,42 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         )]
         block: Option<BlockId>,
 
+        #[clap(
+            long,
+            help = "Verify the account and storage proofs against the state root of the block."
+        )]
+        verify: bool,
+
+        #[clap(
+            long,
+            help = "Verify the proofs against the given state root instead of the one of the block.",
+            value_name = "ROOT"
+        )]
+        state_root: Option<H256>,
+
+        #[clap(flatten)]
+        rpc: RpcOpts,
+    },
+    #[clap(
+        name = "verify-receipt-proof",
+        visible_alias = "vrp",
+        about = "Prove that the receipt of a transaction is included in the receipts root of its block.",
+        long_about = "Prove that the receipt of a transaction is included in the receipts root of its block.
+
+Without --proof, the proof is built from all receipts of the block. The proof is verified against the receipts root of the block and printed as JSON."
+    )]
+    VerifyReceiptProof {
+        #[clap(value_name = "TX_HASH")]
+        tx_hash: String,
+
+        #[clap(
+            long,
+            help = "The RLP encoded trie nodes of the proof to verify, separated by commas.",
+            value_delimiter = ',',
+            value_name = "NODES"
+        )]
+        proof: Vec<Bytes>,
+
         #[clap(flatten)]
         rpc: RpcOpts,
     },
@@ -801,6 +975,15 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         #[clap(flatten)]
         etherscan: EtherscanOpts,
     },
+    #[clap(
+        name = "4337",
+        visible_alias = "userop",
+        about = "ERC-4337 user operation utilities: build, hash, sign, send and decode."
+    )]
+    UserOp {
+        #[clap(subcommand)]
+        command: UserOpSubcommands,
+    },
     #[clap(name = "wallet", visible_alias = "w", about = "Wallet management utilities.")]
     Wallet {
         #[clap(subcommand)]
@@ -813,6 +996,17 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         long_about = "Generate a Solidity interface from a given ABI. Currently does not support ABI encoder v2."
     )]
     Interface(InterfaceArgs),
+    #[clap(
+        name = "abi-diff",
+        visible_alias = "abd",
+        about = "Compare two ABIs and report breaking changes.",
+        long_about = r#"Compare two ABIs and report breaking changes.
+
+The ABIs are read from ABI or artifact files, or fetched from Etherscan for verified contracts. Removed functions and events, changed signatures, outputs and event topics, and stricter mutability are breaking changes.
+
+Exits with code 2 if there are breaking changes, and with code 3 if there are other changes and --strict is set."#
+    )]
+    AbiDiff(AbiDiffArgs),
     #[clap(
         name = "bind",
         visible_alias = "bi",
@@ -835,6 +1029,11 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         about = "Generate a deterministic contract address using CREATE2"
     )]
     Create2(Create2Args),
+    #[clap(
+        name = "create2-deployer",
+        about = "Check for or deploy the canonical CREATE2 deployer on a chain lacking it."
+    )]
+    Create2Deployer(Create2DeployerArgs),
     #[clap(
         name = "find-block",
         visible_alias = "f",
@@ -846,6 +1045,11 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         #[clap(value_enum)]
         shell: clap_complete::Shell,
     },
+    #[clap(name = "__complete", hide = true)]
+    Complete {
+        #[clap(value_enum)]
+        kind: crate::completions::CompletionKind,
+    },
     #[clap(visible_alias = "fig", about = "Generate Fig autocompletion spec.")]
     GenerateFigSpec,
     #[clap(
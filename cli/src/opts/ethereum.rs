@@ -60,7 +60,7 @@ impl RpcOpts {
 
 #[derive(Clone, Debug, Default, Parser, Serialize)]
 pub struct EtherscanOpts {
-    /// The Etherscan (or equivalent) API key
+    /// The Etherscan (or equivalent) API key, multiple keys can be separated by commas
     #[clap(short = 'e', long = "etherscan-api-key", alias = "api-key", env = "ETHERSCAN_API_KEY")]
     #[serde(rename = "etherscan_api_key", skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
@@ -1,23 +1,31 @@
-use crate::cmd::forge::{
-    bind::BindArgs,
-    build::BuildArgs,
-    cache::CacheArgs,
-    config, coverage,
-    create::CreateArgs,
-    debug::DebugArgs,
-    doc::DocArgs,
-    flatten,
-    fmt::FmtArgs,
-    fourbyte::UploadSelectorsArgs,
-    geiger,
-    init::InitArgs,
-    inspect,
-    install::InstallArgs,
-    remappings::RemappingArgs,
-    remove::RemoveArgs,
-    script::ScriptArgs,
-    snapshot, test, tree, update,
-    verify::{VerifyArgs, VerifyCheckArgs},
+use crate::{
+    cmd::forge::{
+        bind::BindArgs,
+        build::BuildArgs,
+        cache::CacheArgs,
+        config, coverage,
+        create::CreateArgs,
+        debug::DebugArgs,
+        diff_bytecode::DiffBytecodeArgs,
+        doc::DocArgs,
+        export::ExportDeploymentsArgs,
+        flatten,
+        fmt::FmtArgs,
+        fourbyte::UploadSelectorsArgs,
+        geiger,
+        init::InitArgs,
+        inspect,
+        install::InstallArgs,
+        lsp::LspArgs,
+        remappings::RemappingArgs,
+        remove::RemoveArgs,
+        script::ScriptArgs,
+        snapshot,
+        storage_check::StorageCheckArgs,
+        test, tree, update,
+        verify::{bytecode::VerifyBytecodeArgs, VerifyArgs, VerifyCheckArgs, VerifyResumeArgs},
+    },
+    telemetry::TelemetryTarget,
 };
 use clap::{Parser, Subcommand, ValueHint};
 use ethers::solc::{artifacts::output_selection::ContractOutputSelection, EvmVersion};
@@ -30,6 +38,13 @@ use serde::Serialize;
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
+
+    /// Export the tracing spans of the command to an OTLP collector or a JSON file.
+    ///
+    /// An `http(s)://` URL exports the spans to the `/v1/traces` OTLP/HTTP endpoint of the
+    /// collector. Anything else is a file the spans are written to, one JSON object per line.
+    #[clap(long, global = true, value_name = "ENDPOINT|FILE", env = "FORGE_TELEMETRY")]
+    pub telemetry: Option<TelemetryTarget>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -89,6 +104,15 @@ pub enum Subcommands {
     #[clap(visible_alias = "vc", about = "Check verification status on Etherscan.")]
     VerifyCheck(VerifyCheckArgs),
 
+    #[clap(
+        visible_alias = "vr",
+        about = "Resume the queued verifications that failed or were interrupted."
+    )]
+    VerifyResume(VerifyResumeArgs),
+
+    #[clap(about = "Verify that deployed bytecode matches a contract of the project.")]
+    VerifyBytecode(VerifyBytecodeArgs),
+
     #[clap(visible_alias = "c", about = "Deploy a smart contract.")]
     Create(CreateArgs),
 
@@ -100,6 +124,11 @@ pub enum Subcommands {
         #[clap(value_enum)]
         shell: clap_complete::Shell,
     },
+    #[clap(name = "__complete", hide = true)]
+    Complete {
+        #[clap(value_enum)]
+        kind: crate::completions::CompletionKind,
+    },
     #[clap(visible_alias = "fig", about = "Generate Fig autocompletion spec.")]
     GenerateFigSpec,
     #[clap(visible_alias = "cl", about = "Remove the build artifacts and cache directories.")]
@@ -134,6 +163,18 @@ pub enum Subcommands {
     #[clap(visible_alias = "in", about = "Get specialized information about a smart contract.")]
     Inspect(inspect::InspectArgs),
 
+    #[clap(
+        visible_alias = "sc",
+        about = "Check that the storage layout of a new contract version is compatible with the old one."
+    )]
+    StorageCheck(StorageCheckArgs),
+
+    #[clap(
+        visible_alias = "db",
+        about = "Disassemble and diff two bytecodes, aligned by jumpdest blocks."
+    )]
+    DiffBytecode(DiffBytecodeArgs),
+
     #[clap(
         visible_alias = "up",
         about = "Uploads abi of given contract to https://sig.eth.samczsun.com function selector database."
@@ -153,6 +194,16 @@ pub enum Subcommands {
 
     #[clap(about = "Generate documentation for the project.")]
     Doc(DocArgs),
+
+    #[clap(
+        about = "Export the contracts deployed by broadcasted scripts for hardhat-deploy, wagmi, viem or typechain."
+    )]
+    ExportDeployments(ExportDeploymentsArgs),
+
+    #[clap(
+        about = "Start a language server with solc diagnostics, gas hints and test code lenses."
+    )]
+    Lsp(LspArgs),
 }
 
 // A set of solc compiler settings that can be set via command line arguments, which are intended
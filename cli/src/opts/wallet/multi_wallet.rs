@@ -30,35 +30,55 @@ macro_rules! get_wallets {
 
 /// A macro that initializes multiple wallets
 ///
-/// Should be used with a [`MultiWallet`] instance
+/// Should be used with a [`MultiWallet`] instance, every wallet is pushed together with a label
+/// naming the `$device` and derivation path, see [`hw_label`]
 macro_rules! create_hw_wallets {
-    ($self:ident, $chain_id:ident ,$get_wallet:ident, $wallets:ident) => {
+    ($self:ident, $chain_id:ident ,$get_wallet:ident, $device:expr, $wallets:ident) => {
         let mut $wallets = vec![];
 
         if let Some(hd_paths) = &$self.hd_paths {
             for path in hd_paths {
                 if let Some(hw) = $self.$get_wallet($chain_id, Some(path), None).await? {
-                    $wallets.push(hw);
+                    $wallets.push((hw, hw_label($device, Some(path), None)));
                 }
             }
         }
 
         if let Some(mnemonic_indexes) = &$self.mnemonic_indexes {
             for index in mnemonic_indexes {
-                if let Some(hw) = $self.$get_wallet($chain_id, None, Some(*index as usize)).await? {
-                    $wallets.push(hw);
+                let index = *index as usize;
+                if let Some(hw) = $self.$get_wallet($chain_id, None, Some(index)).await? {
+                    $wallets.push((hw, hw_label($device, None, Some(index))));
                 }
             }
         }
 
         if $wallets.is_empty() {
             if let Some(hw) = $self.$get_wallet($chain_id, None, Some(0)).await? {
-                $wallets.push(hw);
+                $wallets.push((hw, hw_label($device, None, Some(0))));
             }
         }
     };
 }
 
+/// Returns a label that tells the user which device and derivation path a hardware wallet uses
+fn hw_label(device: &str, hd_path: Option<&str>, mnemonic_index: Option<usize>) -> String {
+    match hd_path {
+        Some(hd_path) => format!("{device} at path {hd_path}"),
+        None => format!("{device} at index {}", mnemonic_index.unwrap_or(0)),
+    }
+}
+
+/// The signers of all senders of a broadcast
+#[derive(Debug, Default)]
+pub struct MultiWalletSigners {
+    pub signers: HashMap<Address, WalletSigner>,
+    /// The device and derivation path of every hardware wallet signer, by address
+    ///
+    /// Transactions of these signers have to be confirmed on the device.
+    pub hardware: HashMap<Address, String>,
+}
+
 #[derive(Parser, Debug, Clone, Serialize, Default)]
 #[cfg_attr(not(doc), allow(missing_docs))]
 #[cfg_attr(
@@ -179,7 +199,7 @@ pub struct MultiWallet {
         short,
         long = "ledger",
         help_heading = "Wallet options - hardware wallet",
-        help = "Use a Ledger hardware wallet."
+        help = "Use a Ledger hardware wallet. Sign with several of its accounts by passing multiple --hd-paths."
     )]
     pub ledger: bool,
 
@@ -219,23 +239,36 @@ impl WalletTrait for MultiWallet {
 impl MultiWallet {
     /// Given a list of addresses, it finds all the associated wallets if they exist. Throws an
     /// error, if it can't find all.
+    ///
+    /// Several hardware wallets, e.g. multiple `--hd-paths` of a Ledger, are told apart by their
+    /// address.
     pub async fn find_all(
         &self,
         provider: Arc<RetryProvider>,
         mut addresses: HashSet<Address>,
         script_wallets: &[LocalWallet],
-    ) -> Result<HashMap<Address, WalletSigner>> {
+    ) -> Result<MultiWalletSigners> {
         println!("\n###\nFinding wallets for all the necessary addresses...");
         let chain = provider.get_chainid().await?.as_u64();
 
         let mut local_wallets = HashMap::new();
         let mut unused_wallets = vec![];
 
+        let mut hw_labels = HashMap::new();
+        let mut hw_wallets: Vec<WalletSigner> = vec![];
+        for (hw, label) in self.trezors(chain).await?.into_iter().flatten() {
+            hw_labels.insert(hw.address(), label);
+            hw_wallets.push(hw.into());
+        }
+        for (hw, label) in self.ledgers(chain).await?.into_iter().flatten() {
+            hw_labels.insert(hw.address(), label);
+            hw_wallets.push(hw.into());
+        }
+
         get_wallets!(
             wallets,
             [
-                self.trezors(chain).await?,
-                self.ledgers(chain).await?,
+                (!hw_wallets.is_empty()).then_some(hw_wallets),
                 self.private_keys()?,
                 self.interactives()?,
                 self.mnemonics()?,
@@ -252,7 +285,11 @@ impl MultiWallet {
                     local_wallets.insert(address, signer);
 
                     if addresses.is_empty() {
-                        return Ok(local_wallets)
+                        hw_labels.retain(|address, _| local_wallets.contains_key(address));
+                        return Ok(MultiWalletSigners {
+                            signers: local_wallets,
+                            hardware: hw_labels,
+                        })
                     }
                 } else {
                     // Just to show on error.
@@ -355,26 +392,43 @@ impl MultiWallet {
         Ok(None)
     }
 
-    pub async fn ledgers(&self, chain_id: u64) -> Result<Option<Vec<Ledger>>> {
+    /// Returns a Ledger signer with its label for every derivation path or index
+    pub async fn ledgers(&self, chain_id: u64) -> Result<Option<Vec<(Ledger, String)>>> {
         if self.ledger {
+            self.ensure_one_ledger_signer()?;
             let mut args = self.clone();
 
-            if let Some(paths) = &args.hd_paths {
-                if paths.len() > 1 {
-                    eyre::bail!("Ledger only supports one signer.");
-                }
+            if args.hd_paths.is_some() {
                 args.mnemonic_indexes = None;
             }
 
-            create_hw_wallets!(args, chain_id, get_from_ledger, wallets);
+            create_hw_wallets!(args, chain_id, get_from_ledger, "Ledger", wallets);
             return Ok(Some(wallets))
         }
         Ok(None)
     }
 
-    pub async fn trezors(&self, chain_id: u64) -> Result<Option<Vec<Trezor>>> {
+    /// Ensures that the Ledger is opened for one signer only, unless its several accounts are
+    /// selected by multiple `--hd-paths`
+    ///
+    /// Multiple `--mnemonic-indexes` are rejected, and so are multiple `--hd-paths` if they may
+    /// belong to `--mnemonics` as well.
+    fn ensure_one_ledger_signer(&self) -> Result<()> {
+        let hd_paths = self.hd_paths.as_ref().map_or(0, Vec::len);
+        let indexes = self.mnemonic_indexes.as_ref().map_or(0, Vec::len);
+        let shared_hd_paths = hd_paths > 1 && self.mnemonics.is_some();
+        if indexes > 1 || shared_hd_paths {
+            eyre::bail!(
+                "Ledger only supports one signer, pass multiple --hd-paths to use several accounts of the device."
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns a Trezor signer with its label for every derivation path or index
+    pub async fn trezors(&self, chain_id: u64) -> Result<Option<Vec<(Trezor, String)>>> {
         if self.trezor {
-            create_hw_wallets!(self, chain_id, get_from_trezor, wallets);
+            create_hw_wallets!(self, chain_id, get_from_trezor, "Trezor", wallets);
             return Ok(Some(wallets))
         }
         Ok(None)
@@ -437,6 +491,43 @@ mod tests {
     use super::*;
     use std::path::PathBuf;
 
+    #[test]
+    fn parse_multiple_hd_paths() {
+        let args: MultiWallet = MultiWallet::parse_from([
+            "foundry-cli",
+            "--ledger",
+            "--hd-paths",
+            "m/44'/60'/0'/0/0",
+            "--hd-paths",
+            "m/44'/60'/1'/0/0",
+        ]);
+        assert_eq!(args.hd_paths.as_ref().map(Vec::len), Some(2));
+        assert_eq!(
+            hw_label("Ledger", Some("m/44'/60'/1'/0/0"), None),
+            "Ledger at path m/44'/60'/1'/0/0"
+        );
+        assert_eq!(hw_label("Trezor", None, Some(2)), "Trezor at index 2");
+    }
+
+    #[test]
+    fn ledger_supports_one_signer_or_several_hd_paths() {
+        let parse = |args: &[&str]| {
+            MultiWallet::parse_from(["foundry-cli", "--ledger"].iter().chain(args))
+                .ensure_one_ledger_signer()
+        };
+
+        let hd_paths = ["--hd-paths", "m/44'/60'/0'/0/0", "--hd-paths", "m/44'/60'/1'/0/0"];
+
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&["--mnemonic-indexes", "1"]).is_ok());
+        assert!(parse(&hd_paths).is_ok());
+
+        assert!(parse(&["--mnemonic-indexes", "0", "--mnemonic-indexes", "1"]).is_err());
+        // the paths may be meant for the mnemonic
+        let mnemonic = ["--mnemonics", "mnemonic.txt"];
+        assert!(parse(&[&mnemonic[..], &hd_paths].concat()).is_err());
+    }
+
     #[test]
     fn parse_keystore_args() {
         let args: MultiWallet =
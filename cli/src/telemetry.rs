@@ -0,0 +1,420 @@
+//! Exports tracing spans for profiling, see `forge --telemetry`
+//!
+//! Spans are either written to a file, one JSON object per line, or sent to an OTLP/HTTP
+//! collector in the JSON encoding of the OpenTelemetry protocol. All spans of a command belong to
+//! the same trace.
+
+use eyre::WrapErr;
+use once_cell::sync::OnceCell;
+use serde_json::{json, Value};
+use std::{
+    fmt,
+    fs::File,
+    io::{BufWriter, Write},
+    mem,
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The number of finished spans that are sent to an OTLP collector at once
+const OTLP_BATCH_SIZE: usize = 512;
+
+/// The longest time finished spans wait before they are sent to an OTLP collector
+const OTLP_EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The global exporter, so spans can be flushed before the process exits
+static EXPORTER: OnceCell<Arc<Exporter>> = OnceCell::new();
+
+/// Where spans are exported to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetryTarget {
+    /// The base URL of an OTLP/HTTP collector
+    Otlp(String),
+    /// A file the spans are written to as JSON lines
+    File(PathBuf),
+}
+
+impl FromStr for TelemetryTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("telemetry target must not be empty".to_string())
+        }
+        if s.starts_with("http://") || s.starts_with("https://") {
+            Ok(TelemetryTarget::Otlp(s.trim_end_matches('/').to_string()))
+        } else {
+            Ok(TelemetryTarget::File(s.into()))
+        }
+    }
+}
+
+impl fmt::Display for TelemetryTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryTarget::Otlp(endpoint) => f.write_str(endpoint),
+            TelemetryTarget::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Exports all finished spans that were not exported yet
+///
+/// Spans that are still open are lost, so this should be called right before the process exits.
+pub fn flush() {
+    if let Some(exporter) = EXPORTER.get() {
+        if let Err(err) = exporter.flush() {
+            eprintln!("Failed to export telemetry to {}: {err:?}", exporter.target);
+        }
+    }
+}
+
+/// A [Layer] that exports all spans to a [TelemetryTarget]
+pub struct TelemetryLayer {
+    exporter: Arc<Exporter>,
+    next_span_id: AtomicU64,
+}
+
+impl TelemetryLayer {
+    /// Creates a new layer and registers its exporter for [flush()]
+    ///
+    /// `service` is reported as the `service.name` of all spans.
+    pub fn new(target: TelemetryTarget, service: &str) -> eyre::Result<Self> {
+        let trace_id = format!("{:016x}{:016x}", now(), std::process::id());
+        let (file, worker) = match target {
+            TelemetryTarget::File(ref path) => {
+                let file = File::create(path).wrap_err_with(|| {
+                    format!("Failed to create telemetry file {}", path.display())
+                })?;
+                (Some(Mutex::new(BufWriter::new(file))), None)
+            }
+            TelemetryTarget::Otlp(ref endpoint) => {
+                (None, Some(Mutex::new(OtlpWorker::spawn(endpoint, service, &trace_id)?)))
+            }
+        };
+        let exporter = Arc::new(Exporter { target, trace_id, file, worker });
+        let _ = EXPORTER.set(exporter.clone());
+        Ok(Self { exporter, next_span_id: AtomicU64::new(1) })
+    }
+}
+
+impl<S> Layer<S> for TelemetryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let parent = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<OpenSpan>().map(|open| open.id));
+        let mut attributes = Vec::new();
+        attrs.record(&mut AttributeVisitor(&mut attributes));
+
+        span.extensions_mut().insert(OpenSpan {
+            id: self.next_span_id.fetch_add(1, Ordering::Relaxed),
+            parent,
+            start: now(),
+            attributes,
+        });
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+                values.record(&mut AttributeVisitor(&mut open.attributes));
+            }
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let open = match span.extensions_mut().remove::<OpenSpan>() {
+            Some(open) => open,
+            None => return,
+        };
+        self.exporter.export(FinishedSpan {
+            id: open.id,
+            parent: open.parent,
+            name: span.name(),
+            target: span.metadata().target(),
+            start: open.start,
+            end: now(),
+            attributes: open.attributes,
+        });
+    }
+}
+
+/// The data of a span that is still open, stored in the extensions of the span
+struct OpenSpan {
+    id: u64,
+    parent: Option<u64>,
+    start: u64,
+    attributes: Vec<(&'static str, String)>,
+}
+
+/// A closed span, timestamps are in nanoseconds since the unix epoch
+#[derive(Debug)]
+struct FinishedSpan {
+    id: u64,
+    parent: Option<u64>,
+    name: &'static str,
+    target: &'static str,
+    start: u64,
+    end: u64,
+    attributes: Vec<(&'static str, String)>,
+}
+
+impl FinishedSpan {
+    /// Returns the span as JSON line of a telemetry file
+    fn to_json(&self, trace_id: &str) -> Value {
+        json!({
+            "traceId": trace_id,
+            "spanId": format!("{:016x}", self.id),
+            "parentSpanId": self.parent.map(|parent| format!("{parent:016x}")),
+            "name": self.name,
+            "target": self.target,
+            "start": self.start,
+            "end": self.end,
+            "durationMs": (self.end.saturating_sub(self.start)) as f64 / 1e6,
+            "attributes": self
+                .attributes
+                .iter()
+                .map(|(key, value)| (key.to_string(), Value::from(value.as_str())))
+                .collect::<serde_json::Map<_, _>>(),
+        })
+    }
+
+    /// Returns the span in the JSON encoding of the OpenTelemetry protocol
+    fn to_otlp(&self, trace_id: &str) -> Value {
+        let mut attributes = vec![json!({
+            "key": "code.namespace",
+            "value": { "stringValue": self.target },
+        })];
+        attributes.extend(self.attributes.iter().map(|(key, value)| {
+            json!({
+                "key": key,
+                "value": { "stringValue": value },
+            })
+        }));
+
+        let mut span = json!({
+            "traceId": trace_id,
+            "spanId": format!("{:016x}", self.id),
+            "name": self.name,
+            // SPAN_KIND_INTERNAL
+            "kind": 1,
+            "startTimeUnixNano": self.start.to_string(),
+            "endTimeUnixNano": self.end.to_string(),
+            "attributes": attributes,
+        });
+        if let Some(parent) = self.parent {
+            span["parentSpanId"] = format!("{parent:016x}").into();
+        }
+        span
+    }
+}
+
+/// Records the fields of a span as string attributes
+struct AttributeVisitor<'a>(&'a mut Vec<(&'static str, String)>);
+
+impl Visit for AttributeVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name(), format!("{value:?}")));
+    }
+}
+
+struct Exporter {
+    target: TelemetryTarget,
+    trace_id: String,
+    /// The writer of [TelemetryTarget::File]
+    file: Option<Mutex<BufWriter<File>>>,
+    /// The channel to the [OtlpWorker] of [TelemetryTarget::Otlp]
+    worker: Option<Mutex<mpsc::Sender<Message>>>,
+}
+
+impl Exporter {
+    fn export(&self, span: FinishedSpan) {
+        if let Some(ref file) = self.file {
+            let mut file = file.lock().unwrap();
+            let _ = writeln!(file, "{}", span.to_json(&self.trace_id));
+        } else if let Some(ref worker) = self.worker {
+            // the worker runs until the process exits
+            let _ = worker.lock().unwrap().send(Message::Span(span));
+        }
+    }
+
+    fn flush(&self) -> eyre::Result<()> {
+        if let Some(ref file) = self.file {
+            file.lock().unwrap().flush()?;
+        } else if let Some(ref worker) = self.worker {
+            let (done, result) = mpsc::channel();
+            worker
+                .lock()
+                .unwrap()
+                .send(Message::Flush(done))
+                .map_err(|_| eyre::eyre!("telemetry worker stopped"))?;
+            return result.recv().map_err(|_| eyre::eyre!("telemetry worker stopped"))?
+        }
+        Ok(())
+    }
+}
+
+/// A message to the [OtlpWorker]
+enum Message {
+    Span(FinishedSpan),
+    /// Sends all pending spans and reports the result
+    Flush(mpsc::Sender<eyre::Result<()>>),
+}
+
+/// Sends spans to an OTLP collector in batches, on its own thread so exporting never blocks the
+/// traced code
+struct OtlpWorker {
+    url: String,
+    service: String,
+    trace_id: String,
+    client: reqwest::Client,
+    runtime: tokio::runtime::Runtime,
+    /// The spans that were not sent yet
+    spans: Vec<FinishedSpan>,
+}
+
+impl OtlpWorker {
+    /// Starts a worker that sends the spans to the collector at `endpoint`
+    fn spawn(endpoint: &str, service: &str, trace_id: &str) -> eyre::Result<mpsc::Sender<Message>> {
+        let worker = OtlpWorker {
+            url: format!("{endpoint}/v1/traces"),
+            service: service.to_string(),
+            trace_id: trace_id.to_string(),
+            client: reqwest::Client::new(),
+            runtime: tokio::runtime::Runtime::new()?,
+            spans: Vec::new(),
+        };
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("telemetry".to_string())
+            .spawn(move || worker.run(receiver))
+            .wrap_err("Failed to start the telemetry worker")?;
+        Ok(sender)
+    }
+
+    /// Sends a batch once [OTLP_BATCH_SIZE] spans are pending or [OTLP_EXPORT_INTERVAL] passed
+    /// since the last one
+    fn run(mut self, messages: mpsc::Receiver<Message>) {
+        let mut last_export = Instant::now();
+        loop {
+            let timeout = OTLP_EXPORT_INTERVAL.saturating_sub(last_export.elapsed());
+            match messages.recv_timeout(timeout) {
+                Ok(Message::Span(span)) => {
+                    self.spans.push(span);
+                    if self.spans.len() < OTLP_BATCH_SIZE {
+                        continue
+                    }
+                }
+                Ok(Message::Flush(done)) => {
+                    let _ = done.send(self.send());
+                    last_export = Instant::now();
+                    continue
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+            if let Err(err) = self.send() {
+                eprintln!("Failed to export telemetry to {}: {err:?}", self.url);
+            }
+            last_export = Instant::now();
+        }
+    }
+
+    /// Sends all pending spans to the collector
+    fn send(&mut self) -> eyre::Result<()> {
+        if self.spans.is_empty() {
+            return Ok(())
+        }
+        let spans = mem::take(&mut self.spans);
+        let body = json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [{
+                        "key": "service.name",
+                        "value": { "stringValue": self.service },
+                    }],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "foundry" },
+                    "spans": spans.iter().map(|span| span.to_otlp(&self.trace_id)).collect::<Vec<_>>(),
+                }],
+            }],
+        });
+
+        let request = self.client.post(&self.url).json(&body);
+        self.runtime.block_on(async move {
+            request.send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+/// Returns the current time in nanoseconds since the unix epoch
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_telemetry_target() {
+        assert_eq!(
+            "http://localhost:4318/".parse::<TelemetryTarget>().unwrap(),
+            TelemetryTarget::Otlp("http://localhost:4318".to_string())
+        );
+        assert_eq!(
+            "telemetry.json".parse::<TelemetryTarget>().unwrap(),
+            TelemetryTarget::File("telemetry.json".into())
+        );
+        assert!("".parse::<TelemetryTarget>().is_err());
+    }
+
+    #[test]
+    fn can_encode_otlp_span() {
+        let span = FinishedSpan {
+            id: 2,
+            parent: Some(1),
+            name: "test",
+            target: "forge::runner",
+            start: 10,
+            end: 20,
+            attributes: vec![("name", "testA()".to_string())],
+        };
+        let otlp = span.to_otlp("abc");
+        assert_eq!(otlp["spanId"], "0000000000000002");
+        assert_eq!(otlp["parentSpanId"], "0000000000000001");
+        assert_eq!(otlp["startTimeUnixNano"], "10");
+        assert_eq!(otlp["attributes"][1]["value"]["stringValue"], "testA()");
+
+        let json = span.to_json("abc");
+        assert_eq!(json["attributes"]["name"], "testA()");
+    }
+}
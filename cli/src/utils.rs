@@ -1,14 +1,17 @@
+use cast::{AbiPath, SimpleCast};
 use console::Emoji;
+use crate::telemetry::{TelemetryLayer, TelemetryTarget};
 use ethers::{
-    abi::token::{LenientTokenizer, Tokenizer},
+    abi::{
+        token::{LenientTokenizer, Tokenizer},
+        Abi,
+    },
     prelude::TransactionReceipt,
     providers::Middleware,
-    solc::EvmVersion,
     types::U256,
     utils::format_units,
 };
-use eyre::Result;
-use forge::executor::SpecId;
+use eyre::{Result, WrapErr};
 use foundry_config::{Chain, Config};
 use std::{
     future::Future,
@@ -19,13 +22,15 @@ use std::{
     time::Duration,
 };
 use tracing_error::ErrorLayer;
-use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter::LevelFilter, prelude::*, EnvFilter};
 use yansi::Paint;
 
 // reexport all `foundry_config::utils`
 #[doc(hidden)]
 pub use foundry_config::utils::*;
 
+pub use forge::utils::{evm_spec, try_evm_spec};
+
 /// The version message for the current program, like
 /// `forge 0.1.0 (f01b232bc 2022-01-22T23:28:39.493201+00:00)`
 pub(crate) const VERSION_MESSAGE: &str = concat!(
@@ -85,13 +90,22 @@ pub fn subscriber() {
         .init()
 }
 
-pub fn evm_spec(evm: &EvmVersion) -> SpecId {
-    match evm {
-        EvmVersion::Istanbul => SpecId::ISTANBUL,
-        EvmVersion::Berlin => SpecId::BERLIN,
-        EvmVersion::London => SpecId::LONDON,
-        _ => panic!("Unsupported EVM version"),
-    }
+/// Same as [subscriber()], but also exports all spans to the telemetry target, if any
+pub fn subscriber_with_telemetry(telemetry: Option<TelemetryTarget>, service: &str) -> Result<()> {
+    let telemetry = match telemetry {
+        Some(telemetry) => telemetry,
+        None => {
+            subscriber();
+            return Ok(())
+        }
+    };
+    let layer = TelemetryLayer::new(telemetry, service)?;
+    tracing_subscriber::Registry::default()
+        .with(ErrorLayer::default().with_filter(EnvFilter::from_default_env()))
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
+        .with(layer.with_filter(LevelFilter::INFO))
+        .init();
+    Ok(())
 }
 
 /// parse a hex str or decimal str as U256
@@ -120,6 +134,27 @@ where
     }
 }
 
+/// Reads the ABI of a file, which can be a plain ABI or an artifact, or fetches the ABI of a
+/// verified contract from Etherscan if `path_or_address` is an address
+///
+/// The contract is looked up on the `chain`, or on the chain of the config. Fails if neither is
+/// known, rather than assuming mainnet.
+pub async fn load_abi(config: &Config, chain: Option<Chain>, path_or_address: &str) -> Result<Abi> {
+    let source = if Path::new(path_or_address).exists() {
+        AbiPath::Local { path: path_or_address.to_string(), name: None }
+    } else {
+        let address = path_or_address
+            .parse()
+            .wrap_err_with(|| format!("{path_or_address} is neither a file nor an address"))?;
+        let chain = chain.or(config.chain_id).ok_or_else(|| {
+            eyre::eyre!("Pass `--chain` to fetch the ABI of {path_or_address} from Etherscan")
+        })?;
+        let clients = config.get_etherscan_clients(chain)?;
+        AbiPath::Etherscan { address, chain: chain.named()?, clients }
+    };
+    SimpleCast::load_abi(source).await
+}
+
 /// Parses an ether value from a string.
 ///
 /// The amount can be tagged with a unit, e.g. "1ether".
@@ -199,6 +234,19 @@ pub fn load_dotenv() {
     };
 }
 
+/// Adds the variables of the dotenv file to the `env` of the config, which makes them visible to
+/// the cheatcodes without exporting them to the shell
+pub fn load_env_file(config: &mut Config, path: &Path) -> Result<()> {
+    let vars = dotenvy::from_path_iter(path)
+        .wrap_err_with(|| format!("Failed to read env file {}", path.display()))?;
+    for var in vars {
+        let (key, value) =
+            var.wrap_err_with(|| format!("Failed to parse env file {}", path.display()))?;
+        config.env.insert(key, value);
+    }
+    Ok(())
+}
+
 /// Disables terminal colours if either:
 /// - Running windows and the terminal does not support colour codes.
 /// - Colour has been disabled by some environment variable.
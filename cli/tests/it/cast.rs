@@ -336,3 +336,117 @@ casttest!(cast_receipt_revert_reason, |_: TestProject, mut cmd: TestCommand| {
     assert!(output.contains("revertReason"));
     assert!(output.contains("Transaction too old"));
 });
+
+// tests that structs can be encoded from JSON and decoded back to JSON
+casttest!(abi_encode_decode_json, |_: TestProject, mut cmd: TestCommand| {
+    let sig = "f((address to,uint256 amount)[] transfers)";
+    let to = "0x000000000000000000000000000000000000dEaD";
+    let json = format!(r#"{{"transfers": [{{"to": "{to}", "amount": 7}}]}}"#);
+    cmd.args(["abi-encode-json", sig, json.as_str()]);
+    let encoded = cmd.stdout_lossy().trim().to_string();
+
+    let calldata = format!("0xdeadbeef{}", encoded.trim_start_matches("0x"));
+    cmd.cast_fuse().args(["abi-decode-json", sig, calldata.as_str(), "--input"]);
+    let decoded: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(decoded["transfers"][0]["amount"], "7");
+    assert_eq!(decoded["transfers"][0]["to"], to);
+
+    cmd.cast_fuse().args(["abi-encode-json", sig, r#"{"transfers": [{"to": "0x01"}]}"#]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Missing field `amount`"), "{err}");
+});
+
+// tests that account, storage and receipt proofs are verified
+casttest!(verifies_proofs, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    // WETH
+    cmd.args([
+        "proof",
+        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        "0",
+        "1",
+        "--verify",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let proof: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(proof["storageProof"].as_array().unwrap().len(), 2);
+
+    // the proofs can't be valid for an unrelated state root
+    cmd.cast_fuse().args([
+        "proof",
+        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        "--state-root",
+        "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Invalid account proof"), "{err}");
+
+    // <https://etherscan.io/tx/0x0e07d8b53ed3d91314c80e53cf25bcde02084939395845cbb625b029d568135c>
+    cmd.cast_fuse().args([
+        "verify-receipt-proof",
+        "0x0e07d8b53ed3d91314c80e53cf25bcde02084939395845cbb625b029d568135c",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let proof: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let nodes = proof["proof"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node.as_str().unwrap())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    // the generated proof can be verified on its own
+    cmd.cast_fuse().args([
+        "verify-receipt-proof",
+        "0x0e07d8b53ed3d91314c80e53cf25bcde02084939395845cbb625b029d568135c",
+        "--proof",
+        nodes.as_str(),
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    cmd.assert_non_empty_stdout();
+});
+
+// tests that breaking ABI changes are reported with a failing exit code
+casttest!(abi_diff_reports_breaking_changes, |prj: TestProject, mut cmd: TestCommand| {
+    let old = prj.root().join("Old.json");
+    let new = prj.root().join("New.json");
+    std::fs::write(
+        &old,
+        r#"[
+    {"type":"function","name":"deposit","inputs":[],"outputs":[],"stateMutability":"payable"},
+    {"type":"function","name":"burn","inputs":[{"name":"amount","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"}
+]"#,
+    )
+    .unwrap();
+    std::fs::write(
+        &new,
+        r#"{"abi": [
+    {"type":"function","name":"deposit","inputs":[],"outputs":[],"stateMutability":"payable"},
+    {"type":"function","name":"mint","inputs":[{"name":"amount","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"}
+]}"#,
+    )
+    .unwrap();
+
+    cmd.args(["abi-diff", old.to_str().unwrap(), old.to_str().unwrap()]);
+    assert!(cmd.stdout_lossy().contains("No changes"));
+
+    cmd.cast_fuse().args(["abi-diff", old.to_str().unwrap(), new.to_str().unwrap()]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("function removed: burn(uint256)"), "{stdout}");
+    assert!(stdout.contains("function added: mint(uint256)"), "{stdout}");
+
+    // addresses are only looked up on the given chain
+    let address = "0x0000000000000000000000000000000000000001";
+    cmd.cast_fuse().args(["abi-diff", old.to_str().unwrap(), address]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Pass `--chain` to fetch the ABI"), "{err}");
+});
@@ -15,9 +15,13 @@ use foundry_cli_test_utils::{
     forgetest, forgetest_init,
     util::{pretty_err, read_string, OutputExt, TestCommand, TestProject},
 };
-use foundry_config::{parse_with_profile, BasicConfig, Chain, Config, SolidityErrorCode};
+use foundry_config::{
+    fs_permissions::PathPermission, parse_with_profile, BasicConfig, Chain, Config, FsPermissions,
+    PathWarningRule, SizeLimit, SizeLimitsConfig, SolidityErrorCode, WarningLevel, WarningsConfig,
+};
 use semver::Version;
 use std::{
+    collections::BTreeMap,
     env, fs,
     path::PathBuf,
     process::{Command, Stdio},
@@ -748,6 +752,117 @@ contract A {
     assert!(!out.trim().contains("Compiler run successful (with warnings)"));
 });
 
+// test that the `[warnings]` config only denies warnings that aren't ignored for their path
+forgetest!(can_deny_warnings_by_path, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "A",
+            r#"
+pragma solidity 0.8.10;
+contract A {
+    function testExample() public {}
+}
+   "#,
+        )
+        .unwrap();
+
+    let config = |level| Config {
+        ignored_error_codes: vec![],
+        deny_warnings: true,
+        warnings: WarningsConfig {
+            paths: vec![PathWarningRule { path: "src/**".to_string(), level, codes: vec![] }],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    prj.write_config(config(WarningLevel::Ignore));
+    cmd.args(["build", "--force"]);
+    let out = cmd.stdout();
+    assert!(out.trim().contains("Compiler run successful"));
+    assert!(!out.trim().contains("Compiler run successful (with warnings)"));
+
+    prj.write_config(config(WarningLevel::Allow));
+    let out = cmd.stdout();
+    assert!(out.trim().contains("Compiler run successful (with warnings)"));
+
+    prj.write_config(config(WarningLevel::Deny));
+    cmd.assert_err();
+
+    // every compilation checks the denied warnings, not only a verbose `forge build`
+    cmd.forge_fuse().args(["build", "--force", "--silent"]);
+    cmd.assert_err();
+    cmd.forge_fuse().args(["test"]);
+    cmd.assert_err();
+});
+
+// test that `forge build` fails if a contract exceeds its `[size_limits]` budget
+forgetest!(can_enforce_size_limits, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "A",
+            r#"
+pragma solidity 0.8.10;
+contract A {
+    function a() public pure returns (uint256) { return 1; }
+}
+   "#,
+        )
+        .unwrap();
+
+    let config = |runtime| Config {
+        size_limits: SizeLimitsConfig {
+            contracts: BTreeMap::from([(
+                "A".to_string(),
+                SizeLimit { runtime: Some(runtime), initcode: None },
+            )]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let report = prj.root().join("size-report.json");
+
+    prj.write_config(config(1));
+    cmd.args(["build", "--force", "--size-report"]).arg(&report);
+    cmd.assert_err();
+    let violations: serde_json::Value = serde_json::from_str(&read_string(&report)).unwrap();
+    assert_eq!(violations[0]["contract"], "A");
+    assert_eq!(violations[0]["kind"], "runtime");
+    assert_eq!(violations[0]["limit"], 1);
+
+    prj.write_config(config(24576));
+    cmd.assert_non_empty_stdout();
+    assert_eq!(read_string(&report).trim(), "[]");
+});
+
+// test that `forge build` writes the functions of contracts that exceed the size limit to a report
+forgetest!(can_write_size_suggestions, |prj: TestProject, mut cmd: TestCommand| {
+    let source = |literal: &str| {
+        format!(
+            r#"
+pragma solidity 0.8.10;
+contract A {{
+    function big() public pure returns (string memory) {{ return "{literal}"; }}
+    function small() public pure returns (uint256) {{ return 1; }}
+}}
+   "#
+        )
+    };
+    let report = prj.root().join("size-suggestions.json");
+
+    prj.inner().add_source("A", source(&"a".repeat(25_000))).unwrap();
+    cmd.args(["build", "--force", "--size-suggestions"]).arg(&report);
+    cmd.assert_non_empty_stdout();
+    let suggestions: serde_json::Value = serde_json::from_str(&read_string(&report)).unwrap();
+    assert!(suggestions[0]["contract"].as_str().unwrap().ends_with("A.sol:A"));
+    assert!(suggestions[0]["size"].as_u64().unwrap() > 24576);
+    assert_eq!(suggestions[0]["functions"][0]["name"], "A.big");
+
+    prj.inner().add_source("A", source("a")).unwrap();
+    cmd.assert_non_empty_stdout();
+    assert_eq!(read_string(&report).trim(), "[]");
+});
+
 // test against a local checkout, useful to debug with local ethers-rs patch
 forgetest!(
     #[ignore]
@@ -1530,6 +1645,40 @@ forgetest_init!(can_inspect_ir_optimized, |_prj: TestProject, mut cmd: TestComma
     cmd.assert_success();
 });
 
+// checks `forge inspect --format` prints the selected format
+forgetest!(can_inspect_with_format, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Foo",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Foo {
+    error Unauthorized(address caller);
+    function run() external {
+        revert Unauthorized(msg.sender);
+    }
+}
+    "#,
+        )
+        .unwrap();
+
+    cmd.args(["inspect", "Foo", "errors"]);
+    let errors: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(errors["Unauthorized(address)"], "0x8e4a23d6");
+
+    cmd.forge_fuse().args(["inspect", "Foo", "abi", "--format", "table"]);
+    let table = cmd.stdout_lossy();
+    assert!(table.contains("| function | run()"));
+    assert!(table.contains("| error    | Unauthorized(address)"));
+
+    cmd.forge_fuse().args(["inspect", "Foo", "abi", "--format", "solidity"]);
+    assert!(cmd.stdout_lossy().contains("interface Foo {"));
+
+    cmd.forge_fuse().args(["inspect", "Foo", "bytecode", "--format", "table"]);
+    cmd.assert_err();
+});
+
 // checks forge bind works correctly on the default project
 forgetest_init!(can_bind, |_prj: TestProject, mut cmd: TestCommand| {
     cmd.arg("bind");
@@ -1610,3 +1759,324 @@ forgetest_init!(can_build_names_repeatedly, |_prj: TestProject, mut cmd: TestCom
     let unchanged = cmd.stdout();
     assert!(unchanged.contains(list), "{}", list);
 });
+
+// checks that a deterministic build is reproducible and its metadata has no absolute paths
+forgetest_init!(can_assert_reproducible_build, |prj: TestProject, mut cmd: TestCommand| {
+    let config = Config { deterministic_metadata: true, ..Default::default() };
+    prj.write_config(config);
+
+    cmd.args(["build", "--assert-reproducible"]);
+    let out = cmd.stdout();
+    assert!(out.contains("all bytecode is identical"), "{}", out);
+
+    let artifact_path = prj.paths().artifacts.join(TEMPLATE_CONTRACT_ARTIFACT_JSON);
+    let artifact: serde_json::Value =
+        ethers::solc::utils::read_json_file(artifact_path).unwrap();
+    let root = prj.root().display().to_string();
+    assert!(!artifact["rawMetadata"].as_str().unwrap().contains(&root));
+    assert!(!artifact["metadata"].to_string().contains(&root));
+});
+
+// checks that flatten keeps preserved imports and declares the license and pragmas once
+forgetest!(can_flatten_with_preserved_imports, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Lib",
+            r#"
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.10;
+library Lib {}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "Base",
+            r#"
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity 0.8.10;
+import {Lib} from "./Lib.sol";
+contract Base {}
+"#,
+        )
+        .unwrap();
+    let target = prj
+        .inner()
+        .add_source(
+            "Target",
+            r#"
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.10;
+import "./Base.sol";
+contract Target is Base {}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("flatten").arg(target).args(["--preserve-imports-of", "src/Lib.sol"]);
+    cmd.arg("--dedup-license-and-pragmas");
+    let out = cmd.stdout_lossy();
+    assert!(out.starts_with(
+        r#"// SPDX-License-Identifier: GPL-3.0 AND MIT
+pragma solidity 0.8.10;
+
+import {Lib} from "src/Lib.sol";
+"#
+    ));
+    assert!(out.contains("contract Base {}"));
+    assert!(out.contains("contract Target is Base {}"));
+    assert!(!out.contains("library Lib"));
+});
+
+// checks that the files imported by a preserved file are not inlined, even if they are imported
+// by an inlined file as well
+forgetest!(can_flatten_without_inlining_preserved_deps, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Dep",
+            r#"
+pragma solidity 0.8.10;
+library Dep {}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "Lib",
+            r#"
+pragma solidity 0.8.10;
+import "./Dep.sol";
+library Lib {}
+"#,
+        )
+        .unwrap();
+    let target = prj
+        .inner()
+        .add_source(
+            "Target",
+            r#"
+// SPDX-License-Identifier: MIT
+pragma solidity 0.8.10;
+import "./Dep.sol";
+import "./Lib.sol";
+contract Target {}
+"#,
+        )
+        .unwrap();
+
+    cmd.arg("flatten").arg(target).args(["--preserve-imports-of", "src/Lib.sol"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains(r#"import "src/Dep.sol";"#));
+    assert!(out.contains(r#"import "src/Lib.sol";"#));
+    assert!(!out.contains("library Dep"));
+    // the license and pragmas are kept as they are
+    assert!(out.contains("// SPDX-License-Identifier: MIT\npragma solidity 0.8.10;"));
+});
+
+// checks that `forge geiger --check` fails for unsafe cheatcodes in dependencies
+forgetest!(can_check_unsafe_cheatcodes_in_deps, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_lib(
+            "dep/Shell.sol",
+            r#"
+pragma solidity ^0.8.10;
+contract Shell {
+    function run() public {
+        vm.ffi(new string[](0));
+        vm.startBroadcast();
+    }
+}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "Deploy",
+            r#"
+pragma solidity ^0.8.10;
+import "../lib/dep/Shell.sol";
+contract Deploy {
+    function run() public {
+        vm.startBroadcast();
+    }
+}
+"#,
+        )
+        .unwrap();
+
+    cmd.args(["geiger", "--check"]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Shell.sol"));
+    assert!(stderr.contains("ffi"));
+    assert!(stderr.contains("startBroadcast"));
+    assert!(!stderr.contains("Deploy.sol"));
+    assert!(stderr.contains("Found unsafe cheatcodes in 1 file(s)"));
+});
+
+// checks that contracts of `optimizer_overrides` are compiled with their own optimizer runs
+forgetest_init!(can_build_with_optimizer_overrides, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Router",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+contract Router {
+    function route(uint256 a) public pure returns (uint256) {
+        return a * 2;
+    }
+}
+"#,
+        )
+        .unwrap();
+    let config = Config {
+        optimizer: true,
+        optimizer_runs: 200,
+        optimizer_overrides: BTreeMap::from([("src/Router.sol:Router".to_string(), 1_000_000)]),
+        ..Default::default()
+    };
+    prj.write_config(config);
+
+    cmd.arg("build");
+    cmd.assert_non_empty_stdout();
+
+    let out = prj.root().join("out");
+    let runs = |artifact: &str| {
+        let artifact: serde_json::Value =
+            ethers::solc::utils::read_json_file(out.join(artifact)).unwrap();
+        artifact["metadata"]["settings"]["optimizer"]["runs"].as_u64().unwrap()
+    };
+    assert_eq!(runs("Router.sol/Router.json"), 1_000_000);
+    assert_eq!(runs("Counter.sol/Counter.json"), 200);
+
+    let overrides: serde_json::Value =
+        ethers::solc::utils::read_json_file(out.join("optimizer-overrides.json")).unwrap();
+    assert_eq!(
+        overrides,
+        serde_json::json!([{
+            "contract": "src/Router.sol:Router",
+            "runs": 1000000,
+            "artifact": "Router.sol/Router.json"
+        }])
+    );
+});
+
+// checks that `forge test` deploys the artifacts of `optimizer_overrides`
+forgetest_init!(can_test_with_optimizer_overrides, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_test(
+            "Overridden.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+import "forge-std/Test.sol";
+
+interface Code {
+    function getDeployedCode(string calldata) external returns (bytes memory);
+}
+
+contract OverriddenTest is Test {
+    function testDeployedOverriddenArtifact() public {
+        bytes memory code = Code(address(vm)).getDeployedCode("Overridden.t.sol:OverriddenTest");
+        assertEq(address(this).code, code);
+    }
+}
+"#,
+        )
+        .unwrap();
+    let config = Config {
+        optimizer: true,
+        optimizer_runs: 200,
+        optimizer_overrides: BTreeMap::from([(
+            "test/Overridden.t.sol:OverriddenTest".to_string(),
+            1_000_000,
+        )]),
+        fs_permissions: FsPermissions::new(vec![PathPermission::read("./out")]),
+        ..Default::default()
+    };
+    prj.write_config(config);
+
+    cmd.args(["test", "--match-contract", "OverriddenTest"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testDeployedOverriddenArtifact()"), "{stdout}");
+});
+
+// checks that the `env` of the profile and `--env-file` are visible to the cheatcodes
+forgetest_init!(can_use_scoped_env_vars, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_test(
+            "Env.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+import "forge-std/Test.sol";
+contract EnvTest is Test {
+    function testScopedEnv() public {
+        assertEq(vm.envUint("PROFILE_VAR"), 42);
+        assertEq(vm.envString("FILE_VAR"), "hello");
+    }
+}
+"#,
+        )
+        .unwrap();
+    let config = Config {
+        env: BTreeMap::from([("PROFILE_VAR".to_string(), "42".to_string())]),
+        ..Default::default()
+    };
+    prj.write_config(config);
+    fs::write(prj.root().join("test.env"), "FILE_VAR=hello\n").unwrap();
+
+    cmd.args(["test", "--match-test", "testScopedEnv", "--env-file", "test.env"]);
+    assert!(cmd.stdout_lossy().contains("[PASS]"));
+});
+
+// checks that the standard json inputs are written and that the project can be compiled with one
+forgetest_init!(can_build_with_standard_json, |prj: TestProject, mut cmd: TestCommand| {
+    let dir = prj.root().join("standard-json");
+    cmd.args(["build", "--standard-json"]).arg(&dir);
+    assert!(cmd.stdout_lossy().contains("Wrote standard json input to"));
+
+    let mut input = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| ethers::solc::utils::read_json_file(entry.unwrap().path()).unwrap())
+        .find(|input: &serde_json::Value| !input["sources"]["src/Counter.sol"].is_null())
+        .expect("no standard json input of src/Counter.sol");
+    assert_eq!(input["language"], "Solidity");
+
+    input["settings"]["optimizer"] = serde_json::json!({ "enabled": true, "runs": 1234 });
+    let modified = prj.root().join("optimized.json");
+    std::fs::write(&modified, serde_json::to_string(&input).unwrap()).unwrap();
+
+    cmd.forge_fuse().args(["build", "--from-standard-json"]).arg(&modified);
+    cmd.assert_non_empty_stdout();
+    let artifact: serde_json::Value =
+        ethers::solc::utils::read_json_file(prj.root().join("out/Counter.sol/Counter.json"))
+            .unwrap();
+    assert_eq!(artifact["metadata"]["settings"]["optimizer"]["runs"], 1234);
+
+    // tests reuse the artifacts when they are given the same input
+    cmd.forge_fuse().args(["test", "--from-standard-json"]).arg(&modified);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("No files changed, compilation skipped"), "{out}");
+    let artifact: serde_json::Value =
+        ethers::solc::utils::read_json_file(prj.root().join("out/Counter.sol/Counter.json"))
+            .unwrap();
+    assert_eq!(artifact["metadata"]["settings"]["optimizer"]["runs"], 1234);
+
+    // the sources of the input must match the project
+    prj.inner()
+        .add_source(
+            "Counter",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+contract Counter {}
+"#,
+        )
+        .unwrap();
+    cmd.forge_fuse().args(["build", "--from-standard-json"]).arg(&modified);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("differs from the project file"), "{err}");
+});
@@ -13,10 +13,10 @@ use foundry_cli_test_utils::{
 };
 use foundry_config::{
     cache::{CachedChains, CachedEndpoints, StorageCachingConfig},
-    Config, FuzzConfig, InvariantConfig, OptimizerDetails, SolcReq,
+    AutoAdvanceTime, Config, FuzzConfig, GasOverride, InvariantConfig, OptimizerDetails, SolcReq,
 };
 use path_slash::PathBufExt;
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
 
 // tests all config values that are in use
 forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
@@ -31,6 +31,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         libs: vec!["lib-test".into()],
         cache: true,
         cache_path: "test-cache".into(),
+        shared_artifacts: true,
         broadcast: "broadcast".into(),
         force: true,
         evm_version: EvmVersion::Byzantium,
@@ -42,6 +43,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         offline: true,
         optimizer: false,
         optimizer_runs: 1000,
+        optimizer_overrides: BTreeMap::from([("src/Router.sol:Router".to_string(), 1_000_000)]),
         optimizer_details: Some(OptimizerDetails {
             yul: Some(false),
             yul_details: Some(YulDetails { stack_allocation: Some(true), ..Default::default() }),
@@ -52,6 +54,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         extra_output_files: Default::default(),
         names: true,
         sizes: true,
+        size_limits: Default::default(),
         test_pattern: None,
         test_pattern_inverse: None,
         contract_pattern: None,
@@ -66,8 +69,18 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         },
         invariant: InvariantConfig { runs: 256, ..Default::default() },
         ffi: true,
+        memory_access: true,
+        isolate: true,
+        test_timeout: Some(60),
+        auto_advance_time: Some(AutoAdvanceTime::from_secs(12)),
+        strict_isolation: false,
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
+        create2_deployer: Config::DEFAULT_CREATE2_DEPLOYER,
+        create2_deployers: BTreeMap::from([(
+            "optimism".to_string(),
+            "0x914d7fec6aac8cd542e72bca78b30650d45643d7".parse().unwrap(),
+        )]),
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
         block_number: 10,
         fork_block_number: Some(200),
@@ -82,9 +95,12 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         block_prevrandao: H256::random(),
         block_gas_limit: Some(100u64.into()),
         memory_limit: 2u64.pow(25),
+        gas_overrides: BTreeMap::from([("SSTORE".to_string(), GasOverride::Gas(5000))]),
+        inspector_plugins: vec![],
         eth_rpc_url: Some("localhost".to_string()),
         etherscan_api_key: None,
         etherscan: Default::default(),
+        etherscan_api_version: Default::default(),
         verbosity: 4,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
@@ -92,25 +108,31 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         ],
         ignored_error_codes: vec![],
         deny_warnings: false,
+        warnings: Default::default(),
         via_ir: true,
         rpc_storage_caching: StorageCachingConfig {
             chains: CachedChains::None,
             endpoints: CachedEndpoints::Remote,
         },
         no_storage_caching: true,
+        fork_prefetch_accounts: true,
+        rpc_cache_url: Some("https://cache.example.com/rpc".to_string()),
         no_rpc_rate_limit: true,
         bytecode_hash: Default::default(),
         cbor_metadata: true,
+        deterministic_metadata: false,
         revert_strings: Some(RevertStrings::Strip),
         sparse_mode: true,
         allow_paths: vec![],
         include_paths: vec![],
+        artifact_paths: vec![],
         rpc_endpoints: Default::default(),
         build_info: false,
         build_info_path: None,
         fmt: Default::default(),
         doc: Default::default(),
         fs_permissions: Default::default(),
+        env: BTreeMap::from([("OWNER".to_string(), "0xBEEF".to_string())]),
         __non_exhaustive: (),
         __warnings: vec![],
     };
@@ -517,6 +539,20 @@ forgetest!(can_update_libs_section, |prj: TestProject, mut cmd: TestCommand| {
     assert_eq!(config.libs, expected);
 });
 
+// test that `forge config --resolve` prints the source of each value
+forgetest!(can_resolve_config_sources, |prj: TestProject, mut cmd: TestCommand| {
+    let config = Config { optimizer_runs: 1337, ..Default::default() };
+    prj.write_config(config);
+
+    cmd.set_env("FOUNDRY_GAS_PRICE", 42);
+    cmd.args(["config", "--resolve", "--via-ir"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("# profile: default"));
+    assert!(stdout.contains("optimizer_runs = 1337 # file "));
+    assert!(stdout.contains("gas_price = 42 # env"));
+    assert!(stdout.contains("via_ir = true # cli"));
+});
+
 // test to check that loading the config emits warnings on the root foundry.toml and
 // is silent for any libs
 forgetest!(config_emit_warnings, |prj: TestProject, mut cmd: TestCommand| {
@@ -5,6 +5,7 @@ use foundry_cli_test_utils::{
     util::{TestCommand, TestProject},
     ScriptOutcome, ScriptTester,
 };
+use serde_json::Value;
 
 forgetest_async!(
     can_deploy_multi_chain_script_without_lib,
@@ -28,6 +29,38 @@ forgetest_async!(
     }
 );
 
+forgetest_async!(
+    can_write_receipts_per_chain,
+    |prj: TestProject, cmd: TestCommand| async move {
+        let (_, handle1) = spawn(NodeConfig::test().with_chain_id(Some(1337u64))).await;
+        let (_, handle2) = spawn(NodeConfig::test().with_chain_id(Some(1338u64))).await;
+        let mut tester = ScriptTester::new_broadcast_without_endpoint(cmd, prj.root());
+
+        tester
+            .load_private_keys(vec![0, 1])
+            .await
+            .add_sig("MultiChainBroadcastNoLink", "deploy(string memory,string memory)")
+            .args(vec![handle1.http_endpoint(), handle2.http_endpoint()])
+            .broadcast(ScriptOutcome::OkBroadcast);
+
+        // every chain gets its own sequence with the receipts of its transactions
+        for (chain, transactions) in [(1337u64, 2), (1338u64, 5)] {
+            let path = prj
+                .root()
+                .join(format!("broadcast/Broadcast.t.sol/{chain}/deploy-latest.json"));
+            let sequence: Value =
+                serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+            assert_eq!(sequence["chain"], chain);
+            assert_eq!(sequence["transactions"].as_array().unwrap().len(), transactions);
+
+            let receipts = sequence["receipts"].as_array().unwrap();
+            assert_eq!(receipts.len(), transactions);
+            assert!(receipts.iter().all(|receipt| receipt["status"] == "0x1"));
+        }
+    }
+);
+
 forgetest_async!(
     can_not_deploy_multi_chain_script_with_lib,
     |prj: TestProject, cmd: TestCommand| async move {
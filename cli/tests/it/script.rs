@@ -1051,3 +1051,133 @@ contract ContractC {
         assert!(cmd.stdout_lossy().contains("Script ran successfully."));
     }
 );
+
+// Tests that the planned transactions can be written and diffed against a previous plan
+forgetest_async!(can_diff_script_plans, |prj: TestProject, mut cmd: TestCommand| async move {
+    foundry_cli_test_utils::util::initialize(prj.root());
+    let script = prj
+        .inner()
+        .add_source(
+            "Plan",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "forge-std/Script.sol";
+
+contract Fees {
+    uint256 public fee;
+    function setFee(uint256 _fee) public {
+        fee = _fee;
+    }
+}
+contract PlanScript is Script {
+    function run(uint256 fee) external {
+        vm.startBroadcast();
+        Fees fees = new Fees();
+        fees.setFee(fee);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    let plan = prj.root().join("plan.json");
+    cmd.set_current_dir(prj.root());
+
+    let script_args = |fee: &str| {
+        vec![
+            "script".to_string(),
+            format!("{}:PlanScript", script.display()),
+            "--sig".to_string(),
+            "run(uint256)".to_string(),
+            fee.to_string(),
+            "--fork-url".to_string(),
+            handle.http_endpoint(),
+            "--sender".to_string(),
+            format!("{dev:?}"),
+        ]
+    };
+
+    cmd.args(script_args("100")).arg("--plan-out").arg(&plan);
+    assert!(cmd.stdout_lossy().contains("Plan written to:"));
+    let written: Value = serde_json::from_str(&std::fs::read_to_string(&plan).unwrap()).unwrap();
+    let txs = written["transactions"].as_array().unwrap();
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0]["transactionType"], "CREATE");
+    assert_eq!(txs[1]["function"], "setFee(uint256)");
+    assert_eq!(txs[1]["arguments"][0], "100");
+
+    cmd.forge_fuse().args(script_args("100")).arg("--plan-diff").arg(&plan);
+    assert!(cmd.stdout_lossy().contains("No changes to the planned transactions."));
+
+    cmd.forge_fuse().args(script_args("200")).arg("--plan-diff").arg(&plan);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Changes to the planned transactions"));
+    assert!(output.contains("-    arg 0: 100"));
+    assert!(output.contains("+    arg 0: 200"));
+});
+
+// Tests that failed preconditions of `preflight()` abort the script before broadcasting
+forgetest_async!(aborts_on_failed_preflight, |prj: TestProject, mut cmd: TestCommand| async move {
+    foundry_cli_test_utils::util::initialize(prj.root());
+    let script = prj
+        .inner()
+        .add_source(
+            "Preflight",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "forge-std/Script.sol";
+
+interface Preconditions {
+    function assertOnChain(bool, string calldata) external;
+    function assertOnChainCode(address, string calldata) external;
+}
+contract Counter {
+    uint256 public count;
+}
+contract PreflightScript is Script {
+    function preflight() external {
+        Preconditions(address(vm)).assertOnChainCode(address(0xdead), "token is deployed");
+        Preconditions(address(vm)).assertOnChain(block.chainid == 1, "connected to mainnet");
+        Preconditions(address(vm)).assertOnChain(block.chainid == 31337, "connected to anvil");
+    }
+    function run() external {
+        vm.startBroadcast();
+        new Counter();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let dev = handle.dev_accounts().next().unwrap();
+    cmd.set_current_dir(prj.root());
+
+    let script_args = vec![
+        "script".to_string(),
+        format!("{}:PreflightScript", script.display()),
+        "--fork-url".to_string(),
+        handle.http_endpoint(),
+        "--sender".to_string(),
+        format!("{dev:?}"),
+        "--broadcast".to_string(),
+        "--unlocked".to_string(),
+    ];
+
+    cmd.args(&script_args);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Preflight checks failed, no transactions were broadcast"), "{err}");
+    assert!(
+        err.contains("token is deployed: 0x000000000000000000000000000000000000dead has no code"),
+        "{err}"
+    );
+    assert!(err.contains("connected to mainnet: condition is false"), "{err}");
+    assert!(!err.contains("connected to anvil"), "{err}");
+
+    cmd.forge_fuse().args(&script_args).arg("--skip-preflight");
+    assert!(cmd.stdout_lossy().contains("ONCHAIN EXECUTION COMPLETE & SUCCESSFUL"));
+});
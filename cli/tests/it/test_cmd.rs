@@ -135,6 +135,229 @@ contract ATest is DSTest {
     cmd.stdout().contains("[PASS]")
 });
 
+// tests that `--report tree` groups the results by file and contract
+forgetest!(can_report_test_tree, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ATest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ATest is DSTest {
+    function testA() external {
+        assertTrue(true);
+    }
+}
+contract BTest is DSTest {
+    function testB() external {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--report", "tree", "--slow", "0"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("ATest.t.sol (2 tests"));
+    assert!(stdout.contains("├─ ATest (setUp:"));
+    assert!(stdout.contains("└─ BTest (setUp:"));
+    assert!(stdout.contains("testA()"));
+    assert!(stdout.contains("Slow tests"));
+    assert!(!stdout.contains("Running 1 test for"));
+});
+
+// tests that `--touching` only runs the tests that touched the contract
+forgetest!(can_run_tests_touching_contract, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "Vault.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Vault {
+    uint256 public balance;
+    function deposit(uint256 amount) external {
+        balance += amount;
+    }
+}
+contract Token {
+    uint256 public supply;
+    function mint(uint256 amount) external {
+        supply += amount;
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    prj.inner()
+        .add_source(
+            "ATest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+import "./Vault.sol";
+contract ATest is DSTest {
+    Vault vault;
+    Token token;
+    function setUp() public {
+        vault = new Vault();
+        token = new Token();
+    }
+    function testDeposit() external {
+        vault.deposit(1);
+    }
+    function testMint() external {
+        token.mint(1);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    // nothing is recorded yet, so all tests run, the touched contracts are recorded from traces
+    cmd.args(["test", "--touching", "Vault", "-vvv"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("testDeposit()"));
+    assert!(stdout.contains("testMint()"));
+
+    cmd.forge_fuse().args(["test", "--touching", "Vault"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("testDeposit()"));
+    assert!(!stdout.contains("testMint()"));
+
+    // a change of an imported file outdates the records
+    prj.inner()
+        .add_source(
+            "Vault.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+contract Vault {
+    uint256 public balance;
+    function deposit(uint256 amount) external {
+        balance += amount;
+    }
+}
+contract Token {
+    uint256 public supply;
+    function mint(uint256 amount) external {
+        supply += amount * 2;
+    }
+}
+   "#,
+        )
+        .unwrap();
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("testDeposit()"));
+    assert!(stdout.contains("testMint()"));
+});
+
+// tests that invariant campaigns are checkpointed and can be resumed
+forgetest!(can_resume_invariant_campaign, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "CounterTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract Counter {
+    uint256 public count;
+    function increment() external {
+        count++;
+    }
+}
+contract CounterTest is DSTest {
+    Counter counter;
+    function setUp() public {
+        counter = new Counter();
+    }
+    function invariant_countIsSmall() public {
+        assertTrue(counter.count() < type(uint128).max);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.set_env("FOUNDRY_INVARIANT_RUNS", 10);
+    cmd.args(["test"]);
+    cmd.assert_success();
+
+    let checkpoint =
+        prj.root().join("cache/invariant-checkpoints/src-CounterTest.t.sol-CounterTest.json");
+    let content: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&checkpoint).unwrap()).unwrap();
+    assert_eq!(content["runs"], 10);
+
+    // all runs are done, so nothing is left to resume
+    cmd.arg("--resume-invariants");
+    cmd.assert_success();
+    let content: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&checkpoint).unwrap()).unwrap();
+    assert_eq!(content["runs"], 10);
+});
+
+// tests that the calls of every invariant handler are reported
+forgetest!(can_report_invariant_handler_stats, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "HandlerTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract Handler {
+    uint256 public count;
+    function increment() external {
+        count++;
+    }
+    function alwaysReverts() external {
+        revert("nope");
+    }
+}
+contract HandlerTest is DSTest {
+    Handler handler;
+    function setUp() public {
+        handler = new Handler();
+    }
+    function invariant_countIsSmall() public {
+        assertTrue(handler.count() < type(uint128).max);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.set_env("FOUNDRY_INVARIANT_RUNS", 10);
+    cmd.args(["test"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("Invariant handlers of"));
+    assert!(stdout.contains("| Handler.increment()"));
+    assert!(stdout.contains("Handlers that never executed successfully: Handler.alwaysReverts()"));
+
+    cmd.arg("--json");
+    let results: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    let (_, suite) = results.as_object().unwrap().iter().next().unwrap();
+    let handlers = &suite["test_results"]["invariant_countIsSmall()"]["kind"]["Invariant"][2];
+    assert_eq!(
+        handlers["Handler.alwaysReverts()"]["calls"],
+        handlers["Handler.alwaysReverts()"]["reverts"]
+    );
+});
+
 // tests that `bytecode_hash` will be sanitized
 forgetest!(can_test_pre_bytecode_hash, |prj: TestProject, mut cmd: TestCommand| {
     prj.insert_ds_test();
@@ -366,3 +589,218 @@ contract ContractTest is Test {
             .join("tests/fixtures/can_use_libs_in_multi_fork.stdout"),
     );
 });
+
+// tests that Vyper contracts are compiled and can be deployed with `deployCode`
+//
+// uses a fake compiler in `PATH` that records its input and outputs a contract whose `number()`
+// returns 42, installing vyper from the GitHub releases is rate limited
+forgetest!(
+    #[cfg(unix)]
+    can_test_with_vyper_contracts,
+    |prj: TestProject, mut cmd: TestCommand| {
+        use std::os::unix::fs::PermissionsExt;
+
+        prj.insert_ds_test();
+
+        let bin = prj.root().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let output = serde_json::json!({
+            "contracts": {
+                "src/Counter.vy": {
+                    "Counter": {
+                        "abi": [{
+                            "type": "function",
+                            "name": "number",
+                            "inputs": [],
+                            "outputs": [{ "name": "", "type": "uint256" }],
+                            "stateMutability": "view"
+                        }],
+                        "evm": {
+                            "bytecode": {
+                                "object": "0x600a600c600039600a6000f3602a60005260206000f3"
+                            },
+                            "deployedBytecode": { "object": "0x602a60005260206000f3" },
+                            "methodIdentifiers": { "number()": "0x8381f58a" }
+                        }
+                    }
+                }
+            }
+        });
+        std::fs::write(bin.join("output.json"), output.to_string()).unwrap();
+        let vyper = bin.join("vyper");
+        std::fs::write(
+            &vyper,
+            r#"#!/bin/sh
+dir=$(dirname "$0")
+if [ "$1" = "--version" ]; then
+    echo "0.3.99+commit.00000000"
+    exit 0
+fi
+cat > "$dir/input.json"
+cat "$dir/output.json"
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&vyper, std::fs::Permissions::from_mode(0o755)).unwrap();
+        cmd.set_env("PATH", format!("{}:{}", bin.display(), std::env::var("PATH").unwrap()));
+
+        std::fs::create_dir_all(prj.paths().sources.join("interfaces")).unwrap();
+        std::fs::write(prj.paths().sources.join("interfaces/ICounter.json"), "[]").unwrap();
+        std::fs::write(
+            prj.paths().sources.join("Counter.vy"),
+            r#"
+# @version 0.3.99
+
+from .interfaces import ICounter
+
+@external
+@view
+def number() -> uint256:
+    return 42
+"#,
+        )
+        .unwrap();
+        prj.inner()
+            .add_source(
+                "CounterTest.t.sol",
+                r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+
+interface Vm {
+    function deployCode(string calldata) external returns (address);
+}
+
+interface ICounter {
+    function number() external view returns (uint256);
+}
+
+contract CounterTest is DSTest {
+    Vm constant vm = Vm(HEVM_ADDRESS);
+
+    function testNumber() public {
+        ICounter counter = ICounter(vm.deployCode("Counter.vy"));
+        assertEq(counter.number(), 42);
+    }
+}
+   "#,
+            )
+            .unwrap();
+
+        cmd.args(["test", "-vvvv"]);
+        let stdout = cmd.stdout_lossy();
+        assert!(stdout.contains("Compiling 1 files with vyper 0.3.99"));
+        assert!(stdout.contains("[PASS] testNumber()"));
+        // the calls to the Vyper contract are decoded
+        assert!(stdout.contains("Counter::number()"));
+        assert!(prj.paths().artifacts.join("Counter.vy/Counter.json").exists());
+
+        // the imported interface is part of the input
+        let input: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(bin.join("input.json")).unwrap())
+                .unwrap();
+        let interface = &input["interfaces"]["src/interfaces/ICounter.json"];
+        assert_eq!(interface["abi"], serde_json::json!([]));
+
+        // unchanged sources are not compiled again
+        let stdout = cmd.stdout_lossy();
+        assert!(!stdout.contains("with vyper"));
+        assert!(stdout.contains("[PASS] testNumber()"));
+
+        // sources are compiled again if a file they import changed
+        std::fs::write(prj.paths().sources.join("interfaces/ICounter.json"), r#"{"abi": []}"#)
+            .unwrap();
+        let stdout = cmd.stdout_lossy();
+        assert!(stdout.contains("Compiling 1 files with vyper 0.3.99"));
+    }
+);
+
+// checks that the chain time advances with every call of a test if `auto_advance_time` is set
+forgetest!(can_auto_advance_time, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    let config = Config {
+        auto_advance_time: Some("12s".parse().unwrap()),
+        ..Default::default()
+    };
+    prj.write_config(config);
+
+    prj.inner()
+        .add_source(
+            "ClockTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract Clock {
+    function read() external view returns (uint256, uint256) {
+        return (block.timestamp, block.number);
+    }
+}
+contract ClockTest is DSTest {
+    Clock clock;
+    function setUp() public {
+        clock = new Clock();
+    }
+    function testAdvancesWithConfig() public {
+        (uint256 t0, uint256 n0) = clock.read();
+        (uint256 t1, uint256 n1) = clock.read();
+        assertEq(t1 - t0, 12);
+        assertEq(n1 - n0, 1);
+    }
+    /// forge-config: default.auto_advance_time = "1m"
+    function testAdvancesWithInlineConfig() public {
+        (uint256 t0, uint256 n0) = clock.read();
+        (uint256 t1, uint256 n1) = clock.read();
+        assertEq(t1 - t0, 60);
+        assertEq(n1 - n0, 1);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testAdvancesWithConfig()"), "{stdout}");
+    assert!(stdout.contains("[PASS] testAdvancesWithInlineConfig()"), "{stdout}");
+});
+
+// tests that internal calls are reconstructed from the function debug data of solc
+forgetest!(can_decode_internal_calls, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    // the optimizer inlines small internal functions
+    let config = Config { optimizer: false, ..Default::default() };
+    prj.write_config(config);
+
+    prj.inner()
+        .add_source(
+            "AdderTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract Adder {
+    function addTwice(uint256 a) external pure returns (uint256) {
+        return _add(_add(a, 1), 1);
+    }
+    function _add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+}
+contract AdderTest is DSTest {
+    function testAddTwice() public {
+        assertEq(new Adder().addTwice(1), 3);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--decode-internal", "-vvvv"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testAddTwice()"), "{stdout}");
+    assert!(stdout.contains("[internal] _add(1, 1)"), "{stdout}");
+    assert!(stdout.contains("[internal] _add(2, 1)"), "{stdout}");
+    assert!(stdout.contains("← (3)"), "{stdout}");
+});
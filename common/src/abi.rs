@@ -10,6 +10,7 @@ use ethers_core::{
 };
 use ethers_etherscan::{contract::ContractMetadata, errors::EtherscanError, Client};
 use eyre::{ContextCompat, Result, WrapErr};
+use foundry_config::with_key_rotation;
 use std::{future::Future, pin::Pin, str::FromStr};
 
 /// Given a function and a vector of string arguments, it proceeds to convert the args to ethabi
@@ -266,7 +267,7 @@ pub async fn get_func_etherscan(
     etherscan_api_key: &str,
 ) -> Result<Function> {
     let client = Client::new(chain, etherscan_api_key)?;
-    let source = find_source(client, contract).await?;
+    let source = find_source(vec![client], contract).await?;
     let metadata = source.items.first().wrap_err("etherscan returned empty metadata")?;
 
     let mut abi = metadata.abi()?;
@@ -283,22 +284,27 @@ pub async fn get_func_etherscan(
 }
 
 /// If the code at `address` is a proxy, recurse until we find the implementation.
+///
+/// The requests rotate through the `clients` if one is rate limited, see [with_key_rotation].
 pub fn find_source(
-    client: Client,
+    clients: Vec<Client>,
     address: Address,
 ) -> Pin<Box<dyn Future<Output = Result<ContractMetadata>>>> {
     Box::pin(async move {
         tracing::trace!("find etherscan source for: {:?}", address);
-        let source = client.contract_source_code(address).await?;
+        let source = with_key_rotation(&clients, |client| async move {
+            client.contract_source_code(address).await
+        })
+        .await?;
         let metadata = source.items.first().wrap_err("Etherscan returned no data")?;
         if metadata.proxy == 0 {
             Ok(source)
         } else {
             let implementation = metadata.implementation.unwrap();
-            println!(
+            eprintln!(
                 "Contract at {address} is a proxy, trying to fetch source at {implementation:?}..."
             );
-            match find_source(client, implementation).await {
+            match find_source(clients, implementation).await {
                 impl_source @ Ok(_) => impl_source,
                 Err(e) => {
                     let err = EtherscanError::ContractCodeNotVerified(address).to_string();
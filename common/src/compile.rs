@@ -1,20 +1,28 @@
 //! Support for compiling [ethers::solc::Project]
-use crate::{term, TestFunctionExt};
+use crate::{optimizer, term, TestFunctionExt};
 use comfy_table::{presets::ASCII_MARKDOWN, *};
 use ethers_etherscan::contract::Metadata;
 use ethers_solc::{
-    artifacts::{BytecodeObject, ContractBytecodeSome},
+    artifacts::{
+        ast::{Ast, NodeType},
+        BytecodeObject, ContractBytecodeSome, Error as CompilerError, Severity,
+    },
     remappings::Remapping,
     report::NoReporter,
-    Artifact, ArtifactId, FileFilter, Graph, Project, ProjectCompileOutput, ProjectPathsConfig,
-    Solc, SolcConfig,
+    sourcemap::SourceMap,
+    Artifact, ArtifactId, ConfigurableContractArtifact, FileFilter, Graph, Project,
+    ProjectCompileOutput, ProjectPathsConfig, Solc, SolcConfig,
 };
 use eyre::Result;
+use foundry_config::{Config, SizeLimitsConfig, WarningLevel};
+use semver::Version;
+use serde::Serialize;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, HashMap},
     convert::Infallible,
     fmt::Display,
     path::{Path, PathBuf},
+    rc::Rc,
     result,
     str::FromStr,
 };
@@ -31,6 +39,10 @@ pub struct ProjectCompiler {
     print_sizes: bool,
     /// files to exclude
     filters: Vec<SkipBuildFilter>,
+    /// the config the project was created from, see [`compile_project`]
+    config: Option<Config>,
+    /// where to write the functions to move into libraries of oversized contracts, as JSON
+    size_suggestions: Option<PathBuf>,
 }
 
 impl ProjectCompiler {
@@ -45,7 +57,22 @@ impl ProjectCompiler {
         print_sizes: bool,
         filters: Vec<SkipBuildFilter>,
     ) -> Self {
-        Self { print_names, print_sizes, filters }
+        Self { print_names, print_sizes, filters, config: None, size_suggestions: None }
+    }
+
+    /// Sets the config the project was created from, so its shared artifacts are restored before
+    /// compiling, its warnings are handled according to its `[warnings]` config and the contracts
+    /// of its `optimizer_overrides` are compiled with their own optimizer runs
+    pub fn config(mut self, config: &Config) -> Self {
+        self.config = Some(config.clone());
+        self
+    }
+
+    /// Writes the [SizeSuggestion]s of all contracts that exceed the size limit to the given JSON
+    /// file, an empty list means no contract exceeds it
+    pub fn size_suggestions(mut self, path: impl Into<PathBuf>) -> Self {
+        self.size_suggestions = Some(path.into());
+        self
     }
 
     /// Compiles the project with [`Project::compile()`]
@@ -55,7 +82,7 @@ impl ProjectCompiler {
             let output = if filters.is_empty() {
                 prj.compile()
             } else {
-                prj.compile_sparse(SkipBuildFilters(filters))
+                prj.compile_sparse(SkipBuildFilters(filters.clone()))
             }?;
             Ok(output)
         })
@@ -71,11 +98,18 @@ impl ProjectCompiler {
         project: &Project,
         filter: F,
     ) -> Result<ProjectCompileOutput> {
-        self.compile_with(project, |prj| Ok(prj.compile_sparse(filter)?))
+        let filter = Rc::new(filter);
+        self.compile_with(project, |prj| {
+            let filter = Rc::clone(&filter);
+            Ok(prj.compile_sparse(move |file: &Path| filter.is_match(file))?)
+        })
     }
 
     /// Compiles the project with the given closure
     ///
+    /// The closure is invoked again if the config has `optimizer_overrides`, see
+    /// [`compile_project`].
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -85,9 +119,9 @@ impl ProjectCompiler {
     ///     .compile_with(&config.project().unwrap(), |prj| Ok(prj.compile()?)).unwrap();
     /// ```
     #[tracing::instrument(target = "forge::compile", skip_all)]
-    pub fn compile_with<F>(self, project: &Project, f: F) -> Result<ProjectCompileOutput>
+    pub fn compile_with<F>(self, project: &Project, mut f: F) -> Result<ProjectCompileOutput>
     where
-        F: FnOnce(&Project) -> Result<ProjectCompileOutput>,
+        F: FnMut(&Project) -> Result<ProjectCompileOutput>,
     {
         if !project.paths.has_input_files() {
             println!("Nothing to compile");
@@ -98,29 +132,44 @@ impl ProjectCompiler {
         let now = std::time::Instant::now();
         tracing::trace!("start compiling project");
 
-        let output = term::with_spinner_reporter(|| f(project))?;
+        let output = compile_project(
+            project,
+            self.config.as_ref(),
+            |prj| term::with_spinner_reporter(|| f(prj)),
+            |output| self.print_status(project, output),
+        )?;
 
         let elapsed = now.elapsed();
         tracing::trace!(?elapsed, "finished compiling");
 
-        if output.has_compiler_errors() {
-            tracing::warn!("compiled with errors");
-            eyre::bail!(output.to_string())
-        } else if output.is_unchanged() {
+        self.handle_output(&output)?;
+
+        Ok(output)
+    }
+
+    /// Prints whether the compilation was skipped or successful, along with its warnings
+    fn print_status(&self, project: &Project, output: &ProjectCompileOutput) {
+        if output.is_unchanged() {
             println!("No files changed, compilation skipped");
-            self.handle_output(&output);
+        } else if let Some(config) = self.config.as_ref().filter(|c| c.warnings.has_rules()) {
+            // denied warnings already failed the compilation
+            let warnings = CompilerWarnings::new(output, project, config);
+            if warnings.allowed.is_empty() {
+                println!("Compiler run successful");
+            } else {
+                println!("Compiler run successful (with warnings)");
+            }
+            for warning in warnings.allowed.iter() {
+                println!("{warning}");
+            }
         } else {
             // print the compiler output / warnings
             println!("{output}");
-
-            self.handle_output(&output);
         }
-
-        Ok(output)
     }
 
-    /// If configured, this will print sizes or names
-    fn handle_output(&self, output: &ProjectCompileOutput) {
+    /// If configured, this will print sizes or names and write the size suggestions
+    fn handle_output(&self, output: &ProjectCompileOutput) -> Result<()> {
         // print any sizes or names
         if self.print_names {
             let mut artifacts: BTreeMap<_, Vec<_>> = BTreeMap::new();
@@ -137,43 +186,269 @@ impl ProjectCompiler {
                 }
             }
         }
-        if self.print_sizes {
-            // add extra newline if names were already printed
-            if self.print_names {
-                println!();
-            }
+        if self.print_sizes || self.size_suggestions.is_some() {
             let mut size_report = SizeReport { contracts: BTreeMap::new() };
             let artifacts: BTreeMap<_, _> = output.artifacts().collect();
             for (name, artifact) in artifacts {
                 let size = deployed_contract_size(artifact).unwrap_or_default();
-
-                let dev_functions = artifact
-                    .abi
-                    .as_ref()
-                    .map(|abi| abi.abi.functions())
-                    .into_iter()
-                    .flatten()
-                    .filter(|func| {
-                        func.name.is_test() || func.name.eq("IS_TEST") || func.name.eq("IS_SCRIPT")
-                    });
-
-                let is_dev_contract = dev_functions.count() > 0;
+                let is_dev_contract = is_dev_contract(artifact);
                 size_report.contracts.insert(name, ContractInfo { size, is_dev_contract });
             }
 
-            println!("{size_report}");
+            let suggestions = if size_report.exceeds_size_limit() {
+                size_suggestions(output, &size_report)
+            } else {
+                Vec::new()
+            };
+            if let Some(path) = self.size_suggestions.as_ref() {
+                crate::fs::write_json_file(path, &suggestions)?;
+            }
+
+            if self.print_sizes {
+                // add extra newline if names were already printed
+                if self.print_names {
+                    println!();
+                }
+                println!("{size_report}");
+
+                // exit with error if any contract exceeds the size limit, excluding test
+                // contracts.
+                if size_report.exceeds_size_limit() {
+                    print_size_suggestions(&suggestions);
+                    std::process::exit(1);
+                }
+            }
+        }
 
-            // exit with error if any contract exceeds the size limit, excluding test contracts.
-            if size_report.exceeds_size_limit() {
-                std::process::exit(1);
+        Ok(())
+    }
+}
+
+/// The compiler errors, and the warnings that aren't ignored by the `[warnings]` config
+#[derive(Debug, Default)]
+pub struct CompilerWarnings {
+    pub errors: Vec<CompilerError>,
+    pub allowed: Vec<CompilerError>,
+    pub denied: Vec<CompilerError>,
+}
+
+impl CompilerWarnings {
+    /// Sorts the warnings of the output by their level for the source file they occur in
+    pub fn new(output: &ProjectCompileOutput, project: &Project, config: &Config) -> Self {
+        let mut warnings = Self::default();
+        // avoids cloning the output if there's nothing to sort
+        if !output.has_compiler_errors() && !output.has_compiler_warnings() {
+            return warnings
+        }
+
+        let matcher = config.warnings.matcher(config.deny_warnings);
+        for error in output.clone().output().errors {
+            if error.error_code.map(|code| project.ignored_error_codes.contains(&code)) ==
+                Some(true)
+            {
+                continue
+            }
+            if matches!(error.severity, Severity::Error) {
+                warnings.errors.push(error);
+                continue
+            }
+            let path = error.source_location.as_ref().map(|location| {
+                let path = Path::new(&location.file);
+                path.strip_prefix(project.root()).unwrap_or(path)
+            });
+            match matcher.level(error.error_code, path) {
+                WarningLevel::Deny => warnings.denied.push(error),
+                WarningLevel::Allow => warnings.allowed.push(error),
+                WarningLevel::Ignore => {}
             }
         }
+        warnings
+    }
+
+    /// Fails with the denied warnings, if any
+    pub fn ensure_not_denied(&self) -> Result<()> {
+        if self.denied.is_empty() {
+            return Ok(())
+        }
+        let denied = self.denied.iter().map(|warning| warning.to_string()).collect::<Vec<_>>();
+        eyre::bail!("Compiler run failed, denied warnings:\n{}", denied.join("\n"))
     }
 }
 
 // https://eips.ethereum.org/EIPS/eip-170
 const CONTRACT_SIZE_LIMIT: usize = 24576;
 
+/// The number of functions that are suggested for extraction per oversized contract
+const MAX_SIZE_SUGGESTIONS: usize = 10;
+
+/// The functions of a contract that exceeds the size limit, as candidates to move into an
+/// external library
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeSuggestion {
+    /// `<path>:<name>` of the contract
+    pub contract: String,
+    /// size of the deployed bytecode in bytes
+    pub size: usize,
+    /// all functions of the contract, sorted by their contribution to its size, largest first
+    pub functions: Vec<FunctionSize>,
+}
+
+/// Prints the functions that contribute the most to the deployed size of every oversized contract
+fn print_size_suggestions(suggestions: &[SizeSuggestion]) {
+    for suggestion in suggestions {
+        let mut table = Table::new();
+        table.load_preset(ASCII_MARKDOWN);
+        table.set_header(vec![
+            Cell::new("Function").add_attribute(Attribute::Bold).fg(Color::Blue),
+            Cell::new("Size (kB)").add_attribute(Attribute::Bold).fg(Color::Blue),
+        ]);
+        for func in suggestion.functions.iter().take(MAX_SIZE_SUGGESTIONS) {
+            table.add_row(vec![Cell::new(&func.name), Cell::new(func.size as f64 / 1000.0)]);
+        }
+        println!(
+            "`{}` exceeds the size limit. Consider moving some of its largest functions into an external library:\n{table}\n",
+            suggestion.contract
+        );
+    }
+}
+
+/// Attributes the deployed bytecode of every contract that exceeds the size limit to its
+/// functions, excluding test contracts and contracts without a deployed source map
+pub fn size_suggestions(
+    output: &ProjectCompileOutput,
+    size_report: &SizeReport,
+) -> Vec<SizeSuggestion> {
+    let artifacts: Vec<_> = output.artifact_ids().collect();
+
+    // source ranges of all functions, by compiler version and source file id
+    let mut functions: HashMap<Version, FunctionRanges> = HashMap::new();
+    for (id, artifact) in &artifacts {
+        if let Some(ast) = artifact.ast.as_ref() {
+            collect_function_ranges(ast, functions.entry(id.version.clone()).or_default());
+        }
+    }
+
+    let mut suggestions = Vec::new();
+    for (id, artifact) in &artifacts {
+        let size = match size_report.contracts.get(&id.name) {
+            Some(c) if !c.is_dev_contract && c.size > CONTRACT_SIZE_LIMIT => c.size,
+            _ => continue,
+        };
+        let functions = match functions
+            .get(&id.version)
+            .and_then(|functions| function_sizes(artifact, functions))
+        {
+            Some(sizes) => sizes,
+            None => continue,
+        };
+        suggestions.push(SizeSuggestion { contract: id.identifier(), size, functions });
+    }
+    suggestions
+}
+
+/// Source ranges `(start, length, name)` of function and modifier definitions, by source file id
+pub type FunctionRanges = HashMap<usize, Vec<(usize, usize, String)>>;
+
+/// Collects the source ranges of all function and modifier definitions in the given AST
+pub fn collect_function_ranges(ast: &Ast, ranges: &mut FunctionRanges) {
+    for node in &ast.nodes {
+        let (contract, children) = match node.node_type {
+            NodeType::ContractDefinition => {
+                (node.attribute::<String>("name"), node.nodes.iter().collect::<Vec<_>>())
+            }
+            // free functions
+            NodeType::FunctionDefinition => (None, vec![node]),
+            _ => continue,
+        };
+        for child in children {
+            if !matches!(
+                child.node_type,
+                NodeType::FunctionDefinition | NodeType::ModifierDefinition
+            ) {
+                continue
+            }
+            let (index, length) = match (child.src.index, child.src.length) {
+                (Some(index), Some(length)) => (index, length),
+                _ => continue,
+            };
+            let mut name = child.attribute::<String>("name").unwrap_or_default();
+            if name.is_empty() {
+                // constructor, fallback and receive functions are unnamed
+                name = child.attribute::<String>("kind").unwrap_or_default();
+            }
+            if let Some(contract) = contract.as_ref() {
+                name = format!("{contract}.{name}");
+            }
+            let entry = ranges.entry(index).or_default();
+            if !entry.iter().any(|(start, _, _)| *start == child.src.start) {
+                entry.push((child.src.start, length, name));
+            }
+        }
+    }
+}
+
+/// Size of the deployed bytecode that is attributed to a single function
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionSize {
+    /// `Contract.function` or the name of a free function
+    pub name: String,
+    /// number of bytes in the deployed bytecode
+    pub size: usize,
+}
+
+/// Attributes every instruction of the deployed bytecode to the innermost function that contains
+/// its source range, using the deployed source map.
+///
+/// Returns the functions sorted by size in descending order, or `None` if the artifact has no
+/// deployed bytecode or source map.
+pub fn function_sizes(
+    artifact: &ConfigurableContractArtifact,
+    functions: &FunctionRanges,
+) -> Option<Vec<FunctionSize>> {
+    let bytecode = artifact.get_deployed_bytecode_bytes()?;
+    let source_map = artifact.get_source_map_deployed()?.ok()?;
+    Some(attribute_bytecode(&bytecode, &source_map, functions))
+}
+
+fn attribute_bytecode(
+    bytecode: &[u8],
+    source_map: &SourceMap,
+    functions: &FunctionRanges,
+) -> Vec<FunctionSize> {
+    let mut sizes: HashMap<&str, usize> = HashMap::new();
+
+    let mut pc = 0;
+    let mut ic = 0;
+    while pc < bytecode.len() {
+        let op = bytecode[pc];
+        // PUSH1..PUSH32
+        let size = if (0x60..=0x7f).contains(&op) { 1 + (op - 0x5f) as usize } else { 1 };
+
+        let func = source_map.get(ic).and_then(|element| {
+            let index = element.index? as usize;
+            functions
+                .get(&index)?
+                .iter()
+                .filter(|(start, length, _)| {
+                    *start <= element.offset && element.offset + element.length <= start + length
+                })
+                .min_by_key(|(_, length, _)| *length)
+                .map(|(_, _, name)| name.as_str())
+        });
+        *sizes.entry(func.unwrap_or("<dispatcher>")).or_default() += size;
+
+        pc += size;
+        ic += 1;
+    }
+
+    let mut sizes = sizes
+        .into_iter()
+        .map(|(name, size)| FunctionSize { name: name.to_string(), size })
+        .collect::<Vec<_>>();
+    sizes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    sizes
+}
+
 /// Contracts with info about their size
 pub struct SizeReport {
     /// `<contract name>:info>`
@@ -229,10 +504,29 @@ impl Display for SizeReport {
     }
 }
 
+/// Returns true if the contract is a test or script contract
+fn is_dev_contract(artifact: &ConfigurableContractArtifact) -> bool {
+    artifact
+        .abi
+        .as_ref()
+        .map(|abi| abi.abi.functions())
+        .into_iter()
+        .flatten()
+        .any(|func| func.name.is_test() || func.name.eq("IS_TEST") || func.name.eq("IS_SCRIPT"))
+}
+
 /// Returns the size of the deployed contract
 pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
-    let bytecode = artifact.get_deployed_bytecode_object()?;
-    let size = match bytecode.as_ref() {
+    Some(bytecode_size(artifact.get_deployed_bytecode_object()?.as_ref()))
+}
+
+/// Returns the size of the creation code of the contract, without constructor arguments
+pub fn initcode_size<T: Artifact>(artifact: &T) -> Option<usize> {
+    Some(bytecode_size(artifact.get_bytecode_object()?.as_ref()))
+}
+
+fn bytecode_size(bytecode: &BytecodeObject) -> usize {
+    match bytecode {
         BytecodeObject::Bytecode(bytes) => bytes.len(),
         BytecodeObject::Unlinked(unlinked) => {
             // we don't need to account for placeholders here, because library placeholders take up
@@ -244,8 +538,7 @@ pub fn deployed_contract_size<T: Artifact>(artifact: &T) -> Option<usize> {
             // hex -> bytes
             size / 2
         }
-    };
-    Some(size)
+    }
 }
 
 /// How big the contract is and whether it is a dev contract where size limits can be neglected
@@ -257,14 +550,125 @@ pub struct ContractInfo {
     pub is_dev_contract: bool,
 }
 
+/// A contract that exceeds one of its budgets of the `[size_limits]` config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeLimitViolation {
+    pub contract: String,
+    /// The exceeded budget, either `runtime` or `initcode`
+    pub kind: &'static str,
+    /// size of the bytecode in bytes
+    pub size: usize,
+    pub limit: usize,
+}
+
+impl Display for SizeLimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` {} size of {} bytes exceeds its budget of {} bytes by {} bytes",
+            self.contract,
+            self.kind,
+            self.size,
+            self.limit,
+            self.size - self.limit
+        )
+    }
+}
+
+/// Returns all budgets of the `[size_limits]` config that are exceeded by the compiled contracts
+///
+/// Test and script contracts are not checked.
+pub fn size_limit_violations(
+    output: &ProjectCompileOutput,
+    limits: &SizeLimitsConfig,
+) -> Vec<SizeLimitViolation> {
+    let mut violations = Vec::new();
+    let artifacts: BTreeMap<_, _> = output.artifacts().collect();
+    for (name, artifact) in artifacts {
+        if is_dev_contract(artifact) {
+            continue
+        }
+        let limit = limits.limit(&name);
+        let budgets = [
+            ("runtime", limit.runtime, deployed_contract_size(artifact)),
+            ("initcode", limit.initcode, initcode_size(artifact)),
+        ];
+        for (kind, limit, size) in budgets {
+            if let (Some(limit), Some(size)) = (limit, size) {
+                if size > limit {
+                    let contract = name.clone();
+                    violations.push(SizeLimitViolation { contract, kind, size, limit });
+                }
+            }
+        }
+    }
+    violations
+}
+
+/// Compiles the project with the given closure and throws if there's any compiler error.
+///
+/// All compilations go through here: if the project was created from a config, its shared
+/// artifacts are restored first and stay locked until the compilation finished, see
+/// [`Config::restore_shared_artifacts`].
+///
+/// `report` is invoked with the output of the compilation once it succeeded.
+///
+/// If the config has `optimizer_overrides`, their contracts are compiled afterwards and replace
+/// the written artifacts, see [`optimizer::compile_optimizer_overrides`]. The closure is then
+/// invoked again, which reads all artifacts from the cache, so the returned output contains the
+/// replaced artifacts.
+fn compile_project<F, R>(
+    project: &Project,
+    config: Option<&Config>,
+    mut f: F,
+    report: R,
+) -> Result<ProjectCompileOutput>
+where
+    F: FnMut(&Project) -> Result<ProjectCompileOutput>,
+    R: FnOnce(&ProjectCompileOutput),
+{
+    let _lock = match config {
+        Some(config) => config.restore_shared_artifacts(project)?,
+        None => None,
+    };
+
+    let output = f(project)?;
+
+    // with a `[warnings]` config, warnings are denied by their code and path instead of the
+    // severity filter of the project
+    if let Some(config) = config.filter(|config| config.warnings.has_rules()) {
+        let warnings = CompilerWarnings::new(&output, project, config);
+        if !warnings.errors.is_empty() {
+            tracing::warn!("compiled with errors");
+            let errors = warnings.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            eyre::bail!("Compiler run failed\n{}", errors.join("\n"))
+        }
+        warnings.ensure_not_denied()?;
+    } else if output.has_compiler_errors() {
+        tracing::warn!("compiled with errors");
+        eyre::bail!(output.to_string())
+    }
+    report(&output);
+
+    if let Some(config) = config.filter(|config| !config.optimizer_overrides.is_empty()) {
+        if !project.no_artifacts {
+            optimizer::compile_optimizer_overrides(config, project)?;
+            return f(project)
+        }
+    }
+
+    Ok(output)
+}
+
 /// Compiles the provided [`Project`], throws if there's any compiler error and logs whether
 /// compilation was successful or if there was a cache hit.
 pub fn compile(
     project: &Project,
+    config: &Config,
     print_names: bool,
     print_sizes: bool,
 ) -> Result<ProjectCompileOutput> {
-    ProjectCompiler::new(print_names, print_sizes).compile(project)
+    ProjectCompiler::new(print_names, print_sizes).config(config).compile(project)
 }
 
 /// Compiles the provided [`Project`], throws if there's any compiler error and logs whether
@@ -273,39 +677,42 @@ pub fn compile(
 /// Takes a list of [`SkipBuildFilter`] for files to exclude from the build.
 pub fn compile_with_filter(
     project: &Project,
+    config: &Config,
     print_names: bool,
     print_sizes: bool,
     skip: Vec<SkipBuildFilter>,
 ) -> Result<ProjectCompileOutput> {
-    ProjectCompiler::with_filter(print_names, print_sizes, skip).compile(project)
+    ProjectCompiler::with_filter(print_names, print_sizes, skip).config(config).compile(project)
 }
 
 /// Compiles the provided [`Project`], throws if there's any compiler error and logs whether
 /// compilation was successful or if there was a cache hit.
 /// Doesn't print anything to stdout, thus is "suppressed".
-pub fn suppress_compile(project: &Project) -> Result<ProjectCompileOutput> {
-    let output = ethers_solc::report::with_scoped(
-        &ethers_solc::report::Report::new(NoReporter::default()),
-        || project.compile(),
-    )?;
-
-    if output.has_compiler_errors() {
-        eyre::bail!(output.to_string())
-    }
-
-    Ok(output)
+pub fn suppress_compile(project: &Project, config: &Config) -> Result<ProjectCompileOutput> {
+    compile_project(
+        project,
+        Some(config),
+        |prj| {
+            Ok(ethers_solc::report::with_scoped(
+                &ethers_solc::report::Report::new(NoReporter::default()),
+                || prj.compile(),
+            )?)
+        },
+        |_| {},
+    )
 }
 
 /// Depending on whether the `skip` is empty this will [`suppress_compile_sparse`] or
 /// [`suppress_compile`]
 pub fn suppress_compile_with_filter(
     project: &Project,
+    config: &Config,
     skip: Vec<SkipBuildFilter>,
 ) -> Result<ProjectCompileOutput> {
     if skip.is_empty() {
-        suppress_compile(project)
+        suppress_compile(project, config)
     } else {
-        suppress_compile_sparse(project, SkipBuildFilters(skip))
+        suppress_compile_sparse(project, config, SkipBuildFilters(skip))
     }
 }
 
@@ -316,18 +723,24 @@ pub fn suppress_compile_with_filter(
 /// See [`Project::compile_sparse`]
 pub fn suppress_compile_sparse<F: FileFilter + 'static>(
     project: &Project,
+    config: &Config,
     filter: F,
 ) -> Result<ProjectCompileOutput> {
-    let output = ethers_solc::report::with_scoped(
-        &ethers_solc::report::Report::new(NoReporter::default()),
-        || project.compile_sparse(filter),
-    )?;
-
-    if output.has_compiler_errors() {
-        eyre::bail!(output.to_string())
-    }
-
-    Ok(output)
+    let filter = Rc::new(filter);
+    compile_project(
+        project,
+        Some(config),
+        |prj| {
+            Ok(ethers_solc::report::with_scoped(
+                &ethers_solc::report::Report::new(NoReporter::default()),
+                || {
+                    let filter = Rc::clone(&filter);
+                    prj.compile_sparse(move |file: &Path| filter.is_match(file))
+                },
+            )?)
+        },
+        |_| {},
+    )
 }
 
 /// Compile a set of files not necessarily included in the `project`'s source dir
@@ -335,26 +748,30 @@ pub fn suppress_compile_sparse<F: FileFilter + 'static>(
 /// If `silent` no solc related output will be emitted to stdout
 pub fn compile_files(
     project: &Project,
+    config: &Config,
     files: Vec<PathBuf>,
     silent: bool,
 ) -> Result<ProjectCompileOutput> {
-    let output = if silent {
-        ethers_solc::report::with_scoped(
-            &ethers_solc::report::Report::new(NoReporter::default()),
-            || project.compile_files(files),
-        )
-    } else {
-        term::with_spinner_reporter(|| project.compile_files(files))
-    }?;
-
-    if output.has_compiler_errors() {
-        eyre::bail!(output.to_string())
-    }
-    if !silent {
-        println!("{output}");
-    }
-
-    Ok(output)
+    compile_project(
+        project,
+        Some(config),
+        |prj| {
+            let output = if silent {
+                ethers_solc::report::with_scoped(
+                    &ethers_solc::report::Report::new(NoReporter::default()),
+                    || prj.compile_files(files.clone()),
+                )
+            } else {
+                term::with_spinner_reporter(|| prj.compile_files(files.clone()))
+            }?;
+            Ok(output)
+        },
+        |output| {
+            if !silent {
+                println!("{output}");
+            }
+        },
+    )
 }
 
 /// Compiles target file path.
@@ -367,16 +784,18 @@ pub fn compile_files(
 pub fn compile_target(
     target_path: &Path,
     project: &Project,
+    config: &Config,
     silent: bool,
     verify: bool,
 ) -> Result<ProjectCompileOutput> {
-    compile_target_with_filter(target_path, project, silent, verify, Vec::new())
+    compile_target_with_filter(target_path, project, config, silent, verify, Vec::new())
 }
 
 /// Compiles target file path.
 pub fn compile_target_with_filter(
     target_path: &Path,
     project: &Project,
+    config: &Config,
     silent: bool,
     verify: bool,
     skip: Vec<SkipBuildFilter>,
@@ -388,13 +807,13 @@ pub fn compile_target_with_filter(
         if verify {
             eyre::bail!("You can only verify deployments from inside a project! Make sure it exists with `forge tree`.");
         }
-        return compile_files(project, vec![target_path.to_path_buf()], silent)
+        return compile_files(project, config, vec![target_path.to_path_buf()], silent)
     }
 
     if silent {
-        suppress_compile_with_filter(project, skip)
+        suppress_compile_with_filter(project, config, skip)
     } else {
-        compile_with_filter(project, false, false, skip)
+        compile_with_filter(project, config, false, false, skip)
     }
 }
 
@@ -552,4 +971,23 @@ mod tests {
         assert!(!SkipBuildFilter::Scripts.is_match(file));
         assert!(!SkipBuildFilter::Custom("A.s".to_string()).is_match(file));
     }
+
+    #[test]
+    fn test_attribute_bytecode() {
+        let mut functions = FunctionRanges::new();
+        functions.insert(0, vec![(0, 50, "A.outer".to_string()), (10, 20, "A.inner".to_string())]);
+        // PUSH1 0x01, PUSH1 0x02, ADD, STOP
+        let bytecode = [0x60, 0x01, 0x60, 0x02, 0x01, 0x00];
+        let source_map = ethers_solc::sourcemap::parse("0:10:0:-;12:3:0;40:2:0;100:1:0").unwrap();
+
+        let sizes = attribute_bytecode(&bytecode, &source_map, &functions);
+        assert_eq!(
+            sizes,
+            vec![
+                FunctionSize { name: "A.outer".to_string(), size: 3 },
+                FunctionSize { name: "A.inner".to_string(), size: 2 },
+                FunctionSize { name: "<dispatcher>".to_string(), size: 1 },
+            ]
+        );
+    }
 }
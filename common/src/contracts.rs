@@ -11,7 +11,7 @@ use regex::Regex;
 use std::{
     collections::BTreeMap,
     ops::{Deref, DerefMut},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 type ArtifactWithContractRef<'a> = (&'a ArtifactId, &'a (Abi, Vec<u8>));
@@ -173,15 +173,39 @@ pub fn get_file_name(id: &str) -> &str {
 }
 
 /// Returns the path to the json artifact depending on the input
+///
+/// The input is either the path to an artifact, `<File>.sol`/`<File>.yul` if the contract (or Yul
+/// object) has the same name as the file, or `<File>:<Contract>`. If the contract was compiled
+/// with multiple solc versions, `<File>:<Contract>:<Version>` selects the artifact of a version.
 pub fn get_artifact_path(paths: &ProjectPathsConfig, path: &str) -> PathBuf {
     if path.ends_with(".json") {
         PathBuf::from(path)
     } else {
-        let parts: Vec<&str> = path.split(':').collect();
-        let file = parts[0];
-        let contract_name =
-            if parts.len() == 1 { parts[0].replace(".sol", "") } else { parts[1].to_string() };
-        paths.artifacts.join(format!("{file}/{contract_name}.json"))
+        get_artifact_path_in(&paths.artifacts, path)
+    }
+}
+
+/// Returns the path to the json artifact of `<File>.sol`/`<File>.yul`/`<File>.vy` or
+/// `<File>:<Contract>[:<Version>]` in the given artifacts directory, see [get_artifact_path]
+pub fn get_artifact_path_in(artifacts: &Path, path: &str) -> PathBuf {
+    let parts: Vec<&str> = path.split(':').collect();
+    let file = parts[0];
+    let contract_name = if parts.len() == 1 {
+        Path::new(file)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default()
+            .trim_end_matches(".sol")
+            .trim_end_matches(".yul")
+            .trim_end_matches(".vy")
+            .to_string()
+    } else {
+        parts[1].to_string()
+    };
+    match parts.get(2) {
+        // artifacts of contracts compiled with multiple versions are suffixed with the version
+        Some(version) => artifacts.join(format!("{file}/{contract_name}.{version}.json")),
+        None => artifacts.join(format!("{file}/{contract_name}.json")),
     }
 }
 
@@ -224,6 +248,30 @@ mod tests {
     use super::*;
     use ethers_core::{abi, abi::ParamType};
 
+    #[test]
+    fn test_get_artifact_path() {
+        let paths = ProjectPathsConfig::builder().artifacts("/root/out").build_with_root("/root");
+        let out = std::path::Path::new("/root/out");
+        assert_eq!(get_artifact_path(&paths, "Counter.sol"), out.join("Counter.sol/Counter.json"));
+        assert_eq!(get_artifact_path(&paths, "Counter.yul"), out.join("Counter.yul/Counter.json"));
+        assert_eq!(
+            get_artifact_path(&paths, "Counter.yul:Runtime"),
+            out.join("Counter.yul/Runtime.json")
+        );
+        assert_eq!(
+            get_artifact_path(&paths, "out/Counter.json"),
+            PathBuf::from("out/Counter.json")
+        );
+        assert_eq!(
+            get_artifact_path_in(Path::new("/root/other-out"), "src/Counter.sol:Counter"),
+            PathBuf::from("/root/other-out/src/Counter.sol/Counter.json")
+        );
+        assert_eq!(
+            get_artifact_path(&paths, "Counter.sol:Counter:0.8.17"),
+            out.join("Counter.sol/Counter.0.8.17.json")
+        );
+    }
+
     // <https://github.com/foundry-rs/foundry/issues/3053>
     #[test]
     fn test_find_constructor_args() {
@@ -39,6 +39,9 @@ use serde::Serialize;
 pub struct EvmArgs {
     /// Fetch state over a remote endpoint instead of starting from an empty state.
     ///
+    /// Multiple comma separated urls are tried in order: requests fail over to the next endpoint
+    /// if one is rate limited, fails or times out.
+    ///
     /// If you want to fetch state from a specific block number, see --fork-block-number.
     #[clap(long, short, visible_alias = "rpc-url", value_name = "URL")]
     #[serde(rename = "eth_rpc_url", skip_serializing_if = "Option::is_none")]
@@ -84,6 +87,15 @@ pub struct EvmArgs {
     #[serde(skip)]
     pub ffi: bool,
 
+    /// Executes every call a test makes as a separate transaction.
+    ///
+    /// Each call starts with cold accounts and storage slots, is charged the intrinsic gas of a
+    /// transaction and its `tx.origin` is the caller. This makes gas reports match the cost of
+    /// the calls on chain.
+    #[clap(long)]
+    #[serde(skip)]
+    pub isolate: bool,
+
     /// Verbosity of the EVM.
     ///
     /// Pass multiple times to increase the verbosity (e.g. -v, -vv, -vvv).
@@ -155,6 +167,10 @@ impl Provider for EvmArgs {
             dict.insert("ffi".to_string(), self.ffi.into());
         }
 
+        if self.isolate {
+            dict.insert("isolate".to_string(), self.isolate.into());
+        }
+
         if self.no_storage_caching {
             dict.insert("no_storage_caching".to_string(), self.no_storage_caching.into());
         }
@@ -0,0 +1,466 @@
+//! A JSON-RPC transport that fails over between multiple HTTP endpoints
+//!
+//! A provider url can list multiple endpoints separated by commas, like
+//! `https://primary.io,https://fallback.io`, in the order of their priority. Requests go to the
+//! endpoint with the highest priority that is available and move on to the next one if it is rate
+//! limited (429), fails (5xx), times out or can't be reached.
+//!
+//! Every endpoint has a circuit breaker: after [BREAKER_THRESHOLD] consecutive failures the
+//! endpoint is skipped for [BREAKER_COOLDOWN], so a dead endpoint doesn't slow down every request.
+
+use async_trait::async_trait;
+use ethers_providers::{
+    HttpClientError, HttpRateLimitRetryPolicy, JsonRpcClient, JsonRpcError, RetryPolicy,
+};
+use once_cell::sync::Lazy;
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{trace, warn};
+
+/// Consecutive failures after which the circuit breaker of an endpoint opens
+pub const BREAKER_THRESHOLD: u32 = 3;
+
+/// How long an endpoint is skipped once its circuit breaker opened
+pub const BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// The stats of all endpoints that were used with fallbacks, by url
+static STATS: Lazy<Mutex<BTreeMap<String, Arc<EndpointStats>>>> = Lazy::new(Default::default);
+
+/// Splits a provider url into the urls of its endpoints, in the order of their priority
+pub fn split_urls(urls: &str) -> impl Iterator<Item = &str> {
+    urls.split(',').map(str::trim)
+}
+
+/// Returns the stats of all endpoints that were used with fallbacks during this run
+pub fn failover_stats() -> Vec<FailoverStats> {
+    STATS.lock().unwrap().values().map(|stats| stats.snapshot()).collect()
+}
+
+/// The stats of a single endpoint, see [failover_stats()]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailoverStats {
+    /// The scheme and host of the endpoint, the full url may contain an API key
+    pub host: String,
+    /// All requests sent to the endpoint
+    pub requests: u64,
+    /// Requests that failed with an error the endpoint was failed over for
+    pub failures: u64,
+    /// Requests the endpoint received because an endpoint with a higher priority was unavailable
+    pub failovers: u64,
+    /// How often the circuit breaker of the endpoint opened
+    pub trips: u64,
+}
+
+impl fmt::Display for FailoverStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} requests, {} failures, {} failovers, {} circuit breaker trips",
+            self.host, self.requests, self.failures, self.failovers, self.trips
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    host: String,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    failovers: AtomicU64,
+    trips: AtomicU64,
+}
+
+impl EndpointStats {
+    fn new(url: &Url) -> Self {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}://{host}:{port}", url.scheme()),
+            (Some(host), None) => format!("{}://{host}", url.scheme()),
+            _ => url.scheme().to_string(),
+        };
+        Self { host, ..Default::default() }
+    }
+
+    fn snapshot(&self) -> FailoverStats {
+        FailoverStats {
+            host: self.host.clone(),
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            failovers: self.failovers.load(Ordering::Relaxed),
+            trips: self.trips.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Tracks the consecutive failures of an endpoint
+#[derive(Debug)]
+struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self { threshold, cooldown, failures: 0, open_until: None }
+    }
+
+    /// Returns true if the endpoint should be skipped
+    ///
+    /// Once the cooldown passed the endpoint is tried again, but a single failure opens the breaker
+    /// again.
+    fn is_open(&self, now: Instant) -> bool {
+        self.open_until.map_or(false, |until| now < until)
+    }
+
+    /// Returns true if the breaker opened
+    fn record_failure(&mut self, now: Instant) -> bool {
+        self.failures += 1;
+        if self.failures >= self.threshold && !self.is_open(now) {
+            self.open_until = Some(now + self.cooldown);
+            return true
+        }
+        false
+    }
+
+    fn record_success(&mut self) {
+        self.failures = 0;
+        self.open_until = None;
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Url,
+    breaker: Mutex<CircuitBreaker>,
+    stats: Arc<EndpointStats>,
+}
+
+impl Endpoint {
+    fn is_available(&self, now: Instant) -> bool {
+        !self.breaker.lock().unwrap().is_open(now)
+    }
+
+    fn record_failure(&self) {
+        self.stats.failures.fetch_add(1, Ordering::Relaxed);
+        if self.breaker.lock().unwrap().record_failure(Instant::now()) {
+            self.stats.trips.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                target: "provider::failover",
+                host = %self.stats.host,
+                "endpoint failed repeatedly, skipping it for {BREAKER_COOLDOWN:?}"
+            );
+        }
+    }
+
+    fn record_success(&self) {
+        self.breaker.lock().unwrap().record_success();
+    }
+}
+
+/// An HTTP transport that sends every request to the first available of multiple endpoints
+///
+/// See the [module docs](self) for how endpoints are chosen.
+#[derive(Debug, Clone)]
+pub struct FailoverHttp {
+    client: Client,
+    /// All endpoints, in the order of their priority
+    endpoints: Arc<Vec<Endpoint>>,
+    id: Arc<AtomicU64>,
+    /// Counts all requests sent by this transport, if set
+    request_counter: Option<Arc<AtomicU64>>,
+}
+
+impl FailoverHttp {
+    /// Creates a new transport for the endpoints, in the order of their priority
+    ///
+    /// The stats of the endpoints are included in [failover_stats()] if there is more than one.
+    ///
+    /// # Panics
+    ///
+    /// If `urls` is empty
+    pub fn new(urls: Vec<Url>, client: Client) -> Self {
+        assert!(!urls.is_empty(), "no endpoints");
+        let has_fallbacks = urls.len() > 1;
+        let endpoints = urls
+            .into_iter()
+            .map(|url| {
+                let stats = if has_fallbacks {
+                    STATS
+                        .lock()
+                        .unwrap()
+                        .entry(url.to_string())
+                        .or_insert_with(|| Arc::new(EndpointStats::new(&url)))
+                        .clone()
+                } else {
+                    Arc::new(EndpointStats::new(&url))
+                };
+                let breaker = Mutex::new(CircuitBreaker::new(BREAKER_THRESHOLD, BREAKER_COOLDOWN));
+                Endpoint { url, breaker, stats }
+            })
+            .collect();
+        Self {
+            client,
+            endpoints: Arc::new(endpoints),
+            id: Default::default(),
+            request_counter: None,
+        }
+    }
+
+    /// Increments the given counter for every request sent by this transport, regardless of the
+    /// endpoint it is sent to
+    pub fn with_request_counter(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.request_counter = Some(counter);
+        self
+    }
+
+    /// Returns the urls of all endpoints, in the order of their priority
+    pub fn urls(&self) -> impl Iterator<Item = &Url> {
+        self.endpoints.iter().map(|endpoint| &endpoint.url)
+    }
+
+    async fn send(
+        &self,
+        endpoint: &Endpoint,
+        body: Vec<u8>,
+    ) -> Result<serde_json::Value, HttpClientError> {
+        let res = self
+            .client
+            .post(endpoint.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        let status_err = res.error_for_status_ref().err();
+        let body = res.bytes().await?;
+
+        // nodes may return the error of a call with a 5xx status
+        match serde_json::from_slice(&body) {
+            Ok(Response::Success { result }) => Ok(result),
+            Ok(Response::Error { error }) => Err(error.into()),
+            Err(err) => Err(match status_err {
+                Some(status_err) => status_err.into(),
+                None => {
+                    let text = String::from_utf8_lossy(&body).to_string();
+                    HttpClientError::SerdeJson { err, text }
+                }
+            }),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for FailoverHttp {
+    type Error = HttpClientError;
+
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, Self::Error> {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        if let Some(ref counter) = self.request_counter {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        let request = Request { id, jsonrpc: "2.0", method, params };
+        let body = serde_json::to_vec(&request)
+            .map_err(|err| HttpClientError::SerdeJson { err, text: String::new() })?;
+
+        // if all breakers are open all endpoints are tried regardless
+        let now = Instant::now();
+        let mut endpoints = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, endpoint)| endpoint.is_available(now))
+            .peekable();
+        let endpoints: Vec<_> = if endpoints.peek().is_some() {
+            endpoints.collect()
+        } else {
+            self.endpoints.iter().enumerate().collect()
+        };
+
+        let mut last_err = None;
+        for (idx, endpoint) in endpoints {
+            endpoint.stats.requests.fetch_add(1, Ordering::Relaxed);
+            if idx > 0 {
+                endpoint.stats.failovers.fetch_add(1, Ordering::Relaxed);
+            }
+
+            let result = match self.send(endpoint, body.clone()).await {
+                Ok(result) => result,
+                Err(err) if should_failover(&err) => {
+                    trace!(
+                        target: "provider::failover",
+                        host = %endpoint.stats.host,
+                        ?err,
+                        "request failed, trying next endpoint"
+                    );
+                    endpoint.record_failure();
+                    last_err = Some(err);
+                    continue
+                }
+                Err(err) => {
+                    // the endpoint answered, e.g. with the revert of a call
+                    endpoint.record_success();
+                    return Err(err)
+                }
+            };
+            endpoint.record_success();
+            return serde_json::from_value(result.clone())
+                .map_err(|err| HttpClientError::SerdeJson { err, text: result.to_string() })
+        }
+
+        Err(last_err.expect("at least one endpoint is tried"))
+    }
+}
+
+/// Returns true if the request should be sent to the next endpoint
+fn should_failover(err: &HttpClientError) -> bool {
+    match err {
+        HttpClientError::ReqwestError(err) => {
+            err.is_timeout() ||
+                err.is_connect() ||
+                err.status().map_or(false, |status| {
+                    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                })
+        }
+        err => HttpRateLimitRetryPolicy::default().should_retry(err),
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    id: u64,
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Error { error: JsonRpcError },
+    Success { result: serde_json::Value },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    /// Serves every request with the given status and body, returns its url
+    fn serve(status: &'static str, body: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap()).parse().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // read until the end of the body, announced by `content-length`
+                let mut request = vec![];
+                let mut buf = [0u8; 1024];
+                loop {
+                    let n = stream.read(&mut buf).unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let request = String::from_utf8_lossy(&request).to_lowercase();
+                    if let Some(end) = request.find("\r\n\r\n") {
+                        let len = request
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .map_or(0, |len| len.trim().parse().unwrap());
+                        if request.len() >= end + 4 + len {
+                            break
+                        }
+                    }
+                    if n == 0 {
+                        break
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn can_open_circuit_breaker() {
+        let now = Instant::now();
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(30));
+        assert!(!breaker.record_failure(now));
+        assert!(!breaker.record_failure(now));
+        assert!(!breaker.is_open(now));
+        assert!(breaker.record_failure(now));
+        assert!(breaker.is_open(now + Duration::from_secs(29)));
+
+        // half open after the cooldown, a single failure opens it again
+        let later = now + Duration::from_secs(30);
+        assert!(!breaker.is_open(later));
+        assert!(breaker.record_failure(later));
+        assert!(breaker.is_open(later));
+
+        breaker.record_success();
+        assert!(!breaker.is_open(later));
+        assert!(!breaker.record_failure(later));
+    }
+
+    #[test]
+    fn can_split_urls() {
+        let urls = split_urls("https://a.io/v2/key, http://localhost:8545").collect::<Vec<_>>();
+        assert_eq!(urls, vec!["https://a.io/v2/key", "http://localhost:8545"]);
+        assert_eq!(split_urls("https://a.io").collect::<Vec<_>>(), vec!["https://a.io"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_fail_over() {
+        let rate_limited = serve("429 Too Many Requests", "rate limited");
+        let unavailable = serve("503 Service Unavailable", "");
+        let reverted = serve(
+            "500 Internal Server Error",
+            r#"{"jsonrpc":"2.0","id":0,"error":{"code":3,"message":"execution reverted"}}"#,
+        );
+        let healthy = serve("200 OK", r#"{"jsonrpc":"2.0","id":0,"result":"0x1"}"#);
+
+        let transport = FailoverHttp::new(
+            vec![rate_limited.clone(), unavailable.clone(), healthy.clone()],
+            Client::new(),
+        );
+        for _ in 0..BREAKER_THRESHOLD + 1 {
+            let chain_id: String = transport.request("eth_chainId", ()).await.unwrap();
+            assert_eq!(chain_id, "0x1");
+        }
+
+        let stats = failover_stats();
+        let stats = |url: &Url| {
+            let host = EndpointStats::new(url).host;
+            stats.iter().find(|stats| stats.host == host).unwrap().clone()
+        };
+        // the failing endpoints are skipped once their breaker opened
+        for url in [&rate_limited, &unavailable] {
+            let stats = stats(url);
+            let threshold = BREAKER_THRESHOLD as u64;
+            assert_eq!((stats.requests, stats.failures, stats.trips), (threshold, threshold, 1));
+        }
+        assert_eq!(stats(&healthy).failovers, BREAKER_THRESHOLD as u64 + 1);
+
+        // errors of the node itself are returned without failing over
+        let transport = FailoverHttp::new(vec![reverted, healthy], Client::new());
+        let err = transport.request::<_, String>("eth_call", ()).await.unwrap_err();
+        assert!(matches!(err, HttpClientError::JsonRpcError(err) if err.code == 3));
+    }
+}
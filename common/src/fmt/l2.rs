@@ -0,0 +1,217 @@
+//! Typed models of the extra fields L2 chains add to blocks, transactions and receipts
+//!
+//! ethers keeps all fields it doesn't know in `other`, these are decoded for the chain the value
+//! was fetched from so they can be formatted like the standard fields.
+
+use super::UIfmt;
+use ethers_core::types::{OtherFields, H256, U256, U64};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+/// L2 chains with extra fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L2Chain {
+    /// OP stack chains: Optimism, Base, Zora
+    Optimism,
+    /// Arbitrum One and Nova
+    Arbitrum,
+    /// zkSync Era
+    ZkSync,
+}
+
+impl L2Chain {
+    /// Returns the L2 the chain id belongs to, if any
+    pub fn from_chain_id(chain_id: u64) -> Option<Self> {
+        match chain_id {
+            10 | 420 | 11155420 | 8453 | 84531 | 84532 | 7777777 => Some(L2Chain::Optimism),
+            42161 | 42170 | 421613 | 421614 => Some(L2Chain::Arbitrum),
+            324 | 280 | 300 => Some(L2Chain::ZkSync),
+            _ => None,
+        }
+    }
+}
+
+/// Extra fields of OP stack deposit transactions and receipts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(missing_docs)]
+pub struct OptimismFields {
+    #[serde(deserialize_with = "lenient")]
+    pub source_hash: Option<H256>,
+    #[serde(deserialize_with = "lenient")]
+    pub mint: Option<U256>,
+    #[serde(deserialize_with = "lenient")]
+    pub is_system_tx: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub deposit_nonce: Option<U64>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_fee: Option<U256>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_gas_used: Option<U256>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_gas_price: Option<U256>,
+    /// A decimal like `0.684`
+    #[serde(deserialize_with = "lenient")]
+    pub l1_fee_scalar: Option<String>,
+}
+
+/// Extra fields of Arbitrum blocks and receipts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(missing_docs)]
+pub struct ArbitrumFields {
+    #[serde(deserialize_with = "lenient")]
+    pub gas_used_for_l1: Option<U256>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_block_number: Option<U64>,
+    #[serde(deserialize_with = "lenient")]
+    pub send_count: Option<U64>,
+    #[serde(deserialize_with = "lenient")]
+    pub send_root: Option<H256>,
+}
+
+/// Extra fields of zkSync blocks, transactions and receipts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[allow(missing_docs)]
+pub struct ZkSyncFields {
+    #[serde(deserialize_with = "lenient")]
+    pub l1_batch_number: Option<U64>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_batch_tx_index: Option<U64>,
+    #[serde(deserialize_with = "lenient")]
+    pub l1_batch_timestamp: Option<U64>,
+}
+
+/// The decoded extra fields of an L2 chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum L2Fields {
+    Optimism(OptimismFields),
+    Arbitrum(ArbitrumFields),
+    ZkSync(ZkSyncFields),
+}
+
+impl L2Fields {
+    /// Decodes the extra fields of the chain
+    ///
+    /// A known field with an unexpected format is skipped, it's then only formatted as a plain
+    /// JSON value. Returns `None` if the fields are not an object.
+    pub fn decode(chain: L2Chain, other: &OtherFields) -> Option<Self> {
+        Some(match chain {
+            L2Chain::Optimism => L2Fields::Optimism(decode(other)?),
+            L2Chain::Arbitrum => L2Fields::Arbitrum(decode(other)?),
+            L2Chain::ZkSync => L2Fields::ZkSync(decode(other)?),
+        })
+    }
+
+    /// Returns the names and `UIfmt::pretty()` formatted values of all fields that are set
+    pub fn pretty_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = PrettyFields::default();
+        match self {
+            L2Fields::Optimism(op) => {
+                fields.push("sourceHash", &op.source_hash);
+                fields.push("mint", &op.mint);
+                fields.push("isSystemTx", &op.is_system_tx);
+                fields.push("depositNonce", &op.deposit_nonce);
+                fields.push("l1Fee", &op.l1_fee);
+                fields.push("l1GasUsed", &op.l1_gas_used);
+                fields.push("l1GasPrice", &op.l1_gas_price);
+                fields.push("l1FeeScalar", &op.l1_fee_scalar);
+            }
+            L2Fields::Arbitrum(arb) => {
+                fields.push("gasUsedForL1", &arb.gas_used_for_l1);
+                fields.push("l1BlockNumber", &arb.l1_block_number);
+                fields.push("sendCount", &arb.send_count);
+                fields.push("sendRoot", &arb.send_root);
+            }
+            L2Fields::ZkSync(zk) => {
+                fields.push("l1BatchNumber", &zk.l1_batch_number);
+                fields.push("l1BatchTxIndex", &zk.l1_batch_tx_index);
+                fields.push("l1BatchTimestamp", &zk.l1_batch_timestamp);
+            }
+        }
+        fields.0
+    }
+
+    /// Returns the `UIfmt::pretty()` formatted field
+    pub fn get_pretty_attr(&self, attr: &str) -> Option<String> {
+        self.pretty_fields().into_iter().find(|(name, _)| *name == attr).map(|(_, value)| value)
+    }
+}
+
+/// Types with extra fields that can be decoded for the L2 they were fetched from
+pub trait UIfmtL2 {
+    /// Like `UIfmt::pretty()`, but with the extra fields of the chain decoded
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String;
+}
+
+#[derive(Default)]
+struct PrettyFields(Vec<(&'static str, String)>);
+
+impl PrettyFields {
+    fn push<T: UIfmt>(&mut self, name: &'static str, value: &Option<T>) {
+        if let Some(value) = value {
+            self.0.push((name, value.pretty()));
+        }
+    }
+}
+
+/// Deserializes a field, or `None` if it has an unexpected format, so a single bad field doesn't
+/// fail the decoding of all fields
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+fn decode<T: DeserializeOwned>(other: &OtherFields) -> Option<T> {
+    let fields = other.iter().map(|(key, value)| (key.clone(), value.clone())).collect();
+    serde_json::from_value(serde_json::Value::Object(fields)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_decode_l2_fields() {
+        let other: OtherFields = serde_json::from_value(serde_json::json!({
+            "sourceHash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "mint": "0x10",
+            "isSystemTx": false,
+            "l1FeeScalar": "0.684",
+            "queueOrigin": "sequencer"
+        }))
+        .unwrap();
+
+        let fields = L2Fields::decode(L2Chain::Optimism, &other).unwrap();
+        assert_eq!(fields.get_pretty_attr("mint"), Some("16".to_string()));
+        assert_eq!(fields.get_pretty_attr("isSystemTx"), Some("false".to_string()));
+        assert_eq!(fields.get_pretty_attr("l1FeeScalar"), Some("0.684".to_string()));
+        assert_eq!(fields.get_pretty_attr("l1Fee"), None);
+        assert_eq!(fields.pretty_fields().len(), 4);
+
+        // unknown fields of other chains are ignored
+        assert_eq!(
+            L2Fields::decode(L2Chain::ZkSync, &other),
+            Some(L2Fields::ZkSync(Default::default()))
+        );
+
+        // a bad field doesn't fail the other fields
+        let invalid: OtherFields = serde_json::from_value(serde_json::json!({
+            "gasUsedForL1": "not a number",
+            "sendCount": "0x2"
+        }))
+        .unwrap();
+        let fields = L2Fields::decode(L2Chain::Arbitrum, &invalid).unwrap();
+        assert_eq!(fields.get_pretty_attr("gasUsedForL1"), None);
+        assert_eq!(fields.get_pretty_attr("sendCount"), Some("2".to_string()));
+
+        assert_eq!(L2Chain::from_chain_id(8453), Some(L2Chain::Optimism));
+        assert_eq!(L2Chain::from_chain_id(42161), Some(L2Chain::Arbitrum));
+        assert_eq!(L2Chain::from_chain_id(1), None);
+    }
+}
@@ -3,5 +3,8 @@
 mod ui;
 pub use ui::*;
 
+mod l2;
+pub use l2::*;
+
 mod token;
 pub use token::*;
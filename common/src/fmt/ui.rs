@@ -6,11 +6,15 @@ use ethers_core::{
 };
 use serde::Deserialize;
 
+use super::{L2Chain, L2Fields, UIfmtL2};
 use crate::TransactionReceiptWithRevertReason;
 
 /// length of the name column for pretty formatting `{:>20}{value}`
 const NAME_COLUMN_LEN: usize = 20usize;
 
+/// length of the name column of receipts
+const RECEIPT_NAME_COLUMN_LEN: usize = 23usize;
+
 ///
 /// Uifmt is a helper trait to format the usual ethers types
 /// It offers a `pretty()` function that returns a human readable String of the value
@@ -114,6 +118,12 @@ impl<T: UIfmt> UIfmt for Vec<T> {
 
 impl UIfmt for TransactionReceipt {
     fn pretty(&self) -> String {
+        self.pretty_l2(None)
+    }
+}
+
+impl UIfmtL2 for TransactionReceipt {
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String {
         format!(
             "
 blockHash               {}
@@ -128,7 +138,7 @@ root                    {}
 status                  {}
 transactionHash         {}
 transactionIndex        {}
-type                    {}",
+type                    {}{}",
             self.block_hash.pretty(),
             self.block_number.pretty(),
             self.contract_address.pretty(),
@@ -141,22 +151,29 @@ type                    {}",
             self.status.pretty(),
             self.transaction_hash.pretty(),
             self.transaction_index.pretty(),
-            self.transaction_type.pretty()
+            self.transaction_type.pretty(),
+            pretty_other_fields(&self.other, chain, RECEIPT_NAME_COLUMN_LEN).trim_end()
         )
     }
 }
 
 impl UIfmt for TransactionReceiptWithRevertReason {
     fn pretty(&self) -> String {
+        self.pretty_l2(None)
+    }
+}
+
+impl UIfmtL2 for TransactionReceiptWithRevertReason {
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String {
         if let Some(ref revert_reason) = self.revert_reason {
             format!(
                 "{}
 revertReason            {}",
-                self.receipt.pretty(),
+                self.receipt.pretty_l2(chain),
                 revert_reason
             )
         } else {
-            self.receipt.pretty()
+            self.receipt.pretty_l2(chain)
         }
     }
 }
@@ -189,29 +206,41 @@ transactionIndex: {}",
 
 impl UIfmt for Block<Transaction> {
     fn pretty(&self) -> String {
+        self.pretty_l2(None)
+    }
+}
+
+impl UIfmtL2 for Block<Transaction> {
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String {
         format!(
             "
 {}
 transactions         {}",
-            pretty_block_basics(self),
-            self.transactions.pretty()
+            pretty_block_basics(self, chain),
+            self.transactions.iter().map(|tx| tx.pretty_l2(chain)).collect::<Vec<_>>().pretty()
         )
     }
 }
 
 impl UIfmt for Block<TxHash> {
     fn pretty(&self) -> String {
+        self.pretty_l2(None)
+    }
+}
+
+impl UIfmtL2 for Block<TxHash> {
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String {
         format!(
             "
 {}
 transactions:        {}",
-            pretty_block_basics(self),
+            pretty_block_basics(self, chain),
             self.transactions.pretty()
         )
     }
 }
 
-fn pretty_block_basics<T>(block: &Block<T>) -> String {
+fn pretty_block_basics<T>(block: &Block<T>, chain: Option<L2Chain>) -> String {
     format!(
         "
 baseFeePerGas        {}
@@ -252,26 +281,45 @@ totalDifficulty      {}{}",
         block.state_root.pretty(),
         block.timestamp.pretty(),
         block.total_difficulty.pretty(),
-        block.other.pretty()
+        pretty_other_fields(&block.other, chain, NAME_COLUMN_LEN)
     )
 }
 
 impl UIfmt for OtherFields {
     fn pretty(&self) -> String {
-        let mut s = String::with_capacity(self.len() * 30);
-        if !self.is_empty() {
-            s.push('\n');
-        }
-        for (key, value) in self.iter() {
-            let val = EthValue::from(value.clone()).pretty();
-            let offset = NAME_COLUMN_LEN.saturating_sub(key.len());
-            s.push_str(key);
-            s.extend(std::iter::repeat(' ').take(offset + 1));
-            s.push_str(&val);
-            s.push('\n');
+        pretty_other_fields(self, None, NAME_COLUMN_LEN)
+    }
+}
+
+/// Formats the extra fields, the known fields of the L2 chain first
+fn pretty_other_fields(other: &OtherFields, chain: Option<L2Chain>, column_len: usize) -> String {
+    let mut fields: Vec<(String, String)> = chain
+        .and_then(|chain| L2Fields::decode(chain, other))
+        .map(|l2| l2.pretty_fields().into_iter().map(|(key, val)| (key.to_string(), val)).collect())
+        .unwrap_or_default();
+    for (key, value) in other.iter() {
+        if !fields.iter().any(|(name, _)| name == key) {
+            fields.push((key.clone(), EthValue::from(value.clone()).pretty()));
         }
-        s
     }
+
+    let mut s = String::with_capacity(fields.len() * 30);
+    if !fields.is_empty() {
+        s.push('\n');
+    }
+    for (key, val) in fields {
+        let offset = column_len.saturating_sub(key.len());
+        s.push_str(&key);
+        s.extend(std::iter::repeat(' ').take(offset + 1));
+        s.push_str(&val);
+        s.push('\n');
+    }
+    s
+}
+
+/// Returns the `UiFmt::pretty()` formatted extra field of the L2 chain
+fn get_pretty_l2_attr(other: &OtherFields, chain: Option<L2Chain>, attr: &str) -> Option<String> {
+    L2Fields::decode(chain?, other)?.get_pretty_attr(attr)
 }
 
 /// Various numerical ethereum types used for pretty printing
@@ -306,6 +354,12 @@ impl UIfmt for EthValue {
 
 impl UIfmt for Transaction {
     fn pretty(&self) -> String {
+        self.pretty_l2(None)
+    }
+}
+
+impl UIfmtL2 for Transaction {
+    fn pretty_l2(&self, chain: Option<L2Chain>) -> String {
         format!(
             "
 blockHash            {}
@@ -336,7 +390,7 @@ value                {}{}",
             self.transaction_index.pretty(),
             self.v.pretty(),
             self.value.pretty(),
-            self.other.pretty()
+            pretty_other_fields(&self.other, chain, NAME_COLUMN_LEN)
         )
     }
 }
@@ -353,7 +407,13 @@ pub fn to_bytes(uint: U256) -> Bytes {
 }
 
 /// Returns the `UiFmt::pretty()` formatted attribute of the transactions
-pub fn get_pretty_tx_attr(transaction: &Transaction, attr: &str) -> Option<String> {
+///
+/// The extra fields of the L2 `chain` are decoded.
+pub fn get_pretty_tx_attr(
+    transaction: &Transaction,
+    attr: &str,
+    chain: Option<L2Chain>,
+) -> Option<String> {
     match attr {
         "blockHash" | "block_hash" => Some(transaction.block_hash.pretty()),
         "blockNumber" | "block_number" => Some(transaction.block_number.pretty()),
@@ -370,6 +430,9 @@ pub fn get_pretty_tx_attr(transaction: &Transaction, attr: &str) -> Option<Strin
         "v" => Some(transaction.v.pretty()),
         "value" => Some(transaction.value.pretty()),
         other => {
+            if let Some(value) = get_pretty_l2_attr(&transaction.other, chain, other) {
+                return Some(value)
+            }
             if let Some(value) = transaction.other.get(other) {
                 return Some(value.to_string().trim_matches('"').to_string())
             }
@@ -379,7 +442,13 @@ pub fn get_pretty_tx_attr(transaction: &Transaction, attr: &str) -> Option<Strin
 }
 
 /// Returns the `UiFmt::pretty()` formatted attribute of the given block
-pub fn get_pretty_block_attr<TX>(block: &Block<TX>, attr: &str) -> Option<String> {
+///
+/// The extra fields of the L2 `chain` are decoded.
+pub fn get_pretty_block_attr<TX>(
+    block: &Block<TX>,
+    attr: &str,
+    chain: Option<L2Chain>,
+) -> Option<String> {
     match attr {
         "baseFeePerGas" | "base_fee_per_gas" => Some(block.base_fee_per_gas.pretty()),
         "difficulty" => Some(block.difficulty.pretty()),
@@ -401,6 +470,9 @@ pub fn get_pretty_block_attr<TX>(block: &Block<TX>, attr: &str) -> Option<String
         "timestamp" => Some(block.timestamp.pretty()),
         "totalDifficulty" | "total_difficult" => Some(block.total_difficulty.pretty()),
         other => {
+            if let Some(value) = get_pretty_l2_attr(&block.other, chain, other) {
+                return Some(value)
+            }
             if let Some(value) = block.other.get(other) {
                 let val = EthValue::from(value.clone());
                 return Some(val.pretty())
@@ -411,9 +483,12 @@ pub fn get_pretty_block_attr<TX>(block: &Block<TX>, attr: &str) -> Option<String
 }
 
 /// Returns the ``UiFmt::pretty()` formatted attribute of the transaction receipt
+///
+/// The extra fields of the L2 `chain` are decoded.
 pub fn get_pretty_tx_receipt_attr(
     receipt: &TransactionReceiptWithRevertReason,
     attr: &str,
+    chain: Option<L2Chain>,
 ) -> Option<String> {
     match attr {
         "blockHash" | "block_hash" => Some(receipt.receipt.block_hash.pretty()),
@@ -436,7 +511,9 @@ pub fn get_pretty_tx_receipt_attr(
         }
         "type" | "transaction_type" => Some(receipt.receipt.transaction_type.pretty()),
         "revertReason" | "revert_reason" => Some(receipt.revert_reason.pretty()),
-        _ => None,
+        other => get_pretty_l2_attr(&receipt.receipt.other, chain, other).or_else(|| {
+            receipt.receipt.other.get(other).map(|value| EthValue::from(value.clone()).pretty())
+        }),
     }
 }
 
@@ -540,6 +617,54 @@ value                0".to_string();
         assert_eq!(generated.as_str(), output.as_str());
     }
 
+    #[test]
+    fn can_pretty_print_optimism_receipt() {
+        let receipt = serde_json::json!({
+            "blockHash": "0x02b853cf50bc1c335b70790f93d5a390a35a166bea9c895e685cc866e4961cae",
+            "blockNumber": "0x1b4",
+            "contractAddress": null,
+            "cumulativeGasUsed": "0x5208",
+            "effectiveGasPrice": "0x1",
+            "from": "0x3b179dcfc5faa677044c27dce958e4bc0ad696a6",
+            "gasUsed": "0x5208",
+            "logs": [],
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "status": "0x1",
+            "to": "0x4a16a42407aa491564643e1dfc1fd50af29794ef",
+            "transactionHash": "0x2642e960d3150244e298d52b5b0f024782253e6d0b2c9a01dd4858f7b4665a3f",
+            "transactionIndex": "0x0",
+            "type": "0x2",
+            "l1Fee": "0x3e8",
+            "l1FeeScalar": "0.684",
+            "l1GasPrice": "0xa",
+            "l1GasUsed": "0x64"
+        });
+        let receipt: TransactionReceiptWithRevertReason =
+            serde_json::from_value::<TransactionReceipt>(receipt).unwrap().into();
+
+        let chain = L2Chain::from_chain_id(10);
+        assert_eq!(get_pretty_tx_receipt_attr(&receipt, "l1Fee", chain), Some("1000".to_string()));
+        assert_eq!(
+            get_pretty_tx_receipt_attr(&receipt, "l1FeeScalar", chain),
+            Some("0.684".to_string())
+        );
+        // without the chain, the fields are still found
+        assert_eq!(
+            get_pretty_tx_receipt_attr(&receipt, "l1GasUsed", None),
+            Some("100".to_string())
+        );
+
+        let pretty = receipt.pretty_l2(chain);
+        assert!(pretty.ends_with(
+            "
+type                    2
+l1Fee                   1000
+l1GasUsed               100
+l1GasPrice              10
+l1FeeScalar             0.684"
+        ));
+    }
+
     #[test]
     fn uifmt_option_u64() {
         let empty: Option<U64> = None;
@@ -587,44 +712,56 @@ value                0".to_string();
     fn test_pretty_tx_attr() {
         let block = r#"{"number":"0x3","hash":"0xda53da08ef6a3cbde84c33e51c04f68c3853b6a3731f10baa2324968eee63972","parentHash":"0x689c70c080ca22bc0e681694fa803c1aba16a69c8b6368fed5311d279eb9de90","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0000000000000000","sha3Uncles":"0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","transactionsRoot":"0x7270c1c4440180f2bd5215809ee3d545df042b67329499e1ab97eb759d31610d","stateRoot":"0x29f32984517a7d25607da485b23cefabfd443751422ca7e603395e1de9bc8a4b","receiptsRoot":"0x056b23fbba480696b65fe5a59b8f2148a1299103c4f57df839233af2cf4ca2d2","miner":"0x0000000000000000000000000000000000000000","difficulty":"0x0","totalDifficulty":"0x0","extraData":"0x","size":"0x3e8","gasLimit":"0x6691b7","gasUsed":"0x5208","timestamp":"0x5ecedbb9","transactions":[{"hash":"0xc3c5f700243de37ae986082fd2af88d2a7c2752a0c0f7b9d6ac47c729d45e067","nonce":"0x2","blockHash":"0xda53da08ef6a3cbde84c33e51c04f68c3853b6a3731f10baa2324968eee63972","blockNumber":"0x3","transactionIndex":"0x0","from":"0xfdcedc3bfca10ecb0890337fbdd1977aba84807a","to":"0xdca8ce283150ab773bcbeb8d38289bdb5661de1e","value":"0x0","gas":"0x15f90","gasPrice":"0x4a817c800","input":"0x","v":"0x25","r":"0x19f2694eb9113656dbea0b925e2e7ceb43df83e601c4116aee9c0dd99130be88","s":"0x73e5764b324a4f7679d890a198ba658ba1c8cd36983ff9797e10b1b89dbb448e"}],"uncles":[]}"#;
         let block: Block<Transaction> = serde_json::from_str(block).unwrap();
-        assert_eq!(None, get_pretty_tx_attr(&block.transactions[0], ""));
+        assert_eq!(None, get_pretty_tx_attr(&block.transactions[0], "", None));
         assert_eq!(
             Some("3".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "blockNumber")
+            get_pretty_tx_attr(&block.transactions[0], "blockNumber", None)
         );
         assert_eq!(
             Some("0xFdCeDC3bFca10eCb0890337fbdD1977aba84807a".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "from")
+            get_pretty_tx_attr(&block.transactions[0], "from", None)
+        );
+        assert_eq!(
+            Some("90000".to_string()),
+            get_pretty_tx_attr(&block.transactions[0], "gas", None)
         );
-        assert_eq!(Some("90000".to_string()), get_pretty_tx_attr(&block.transactions[0], "gas"));
         assert_eq!(
             Some("20000000000".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "gasPrice")
+            get_pretty_tx_attr(&block.transactions[0], "gasPrice", None)
         );
         assert_eq!(
             Some("0xc3c5f700243de37ae986082fd2af88d2a7c2752a0c0f7b9d6ac47c729d45e067".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "hash")
+            get_pretty_tx_attr(&block.transactions[0], "hash", None)
+        );
+        assert_eq!(
+            Some("0x".to_string()),
+            get_pretty_tx_attr(&block.transactions[0], "input", None)
+        );
+        assert_eq!(
+            Some("2".to_string()),
+            get_pretty_tx_attr(&block.transactions[0], "nonce", None)
         );
-        assert_eq!(Some("0x".to_string()), get_pretty_tx_attr(&block.transactions[0], "input"));
-        assert_eq!(Some("2".to_string()), get_pretty_tx_attr(&block.transactions[0], "nonce"));
         assert_eq!(
             Some("0x19f2694eb9113656dbea0b925e2e7ceb43df83e601c4116aee9c0dd99130be88".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "r")
+            get_pretty_tx_attr(&block.transactions[0], "r", None)
         );
         assert_eq!(
             Some("0x73e5764b324a4f7679d890a198ba658ba1c8cd36983ff9797e10b1b89dbb448e".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "s")
+            get_pretty_tx_attr(&block.transactions[0], "s", None)
         );
         assert_eq!(
             Some("0xdca8ce283150AB773BCbeB8d38289bdB5661dE1e".into()),
-            get_pretty_tx_attr(&block.transactions[0], "to")
+            get_pretty_tx_attr(&block.transactions[0], "to", None)
         );
         assert_eq!(
             Some("0".to_string()),
-            get_pretty_tx_attr(&block.transactions[0], "transactionIndex")
+            get_pretty_tx_attr(&block.transactions[0], "transactionIndex", None)
+        );
+        assert_eq!(Some("37".to_string()), get_pretty_tx_attr(&block.transactions[0], "v", None));
+        assert_eq!(
+            Some("0".to_string()),
+            get_pretty_tx_attr(&block.transactions[0], "value", None)
         );
-        assert_eq!(Some("37".to_string()), get_pretty_tx_attr(&block.transactions[0], "v"));
-        assert_eq!(Some("0".to_string()), get_pretty_tx_attr(&block.transactions[0], "value"));
     }
     #[test]
     fn test_pretty_block_attr() {
@@ -661,48 +798,57 @@ value                0".to_string();
 
         let block: Block<()> = serde_json::from_value(json).unwrap();
 
-        assert_eq!(None, get_pretty_block_attr(&block, ""));
-        assert_eq!(Some("7".to_string()), get_pretty_block_attr(&block, "baseFeePerGas"));
-        assert_eq!(Some("163591".to_string()), get_pretty_block_attr(&block, "difficulty"));
+        assert_eq!(None, get_pretty_block_attr(&block, "", None));
+        assert_eq!(Some("7".to_string()), get_pretty_block_attr(&block, "baseFeePerGas", None));
+        assert_eq!(Some("163591".to_string()), get_pretty_block_attr(&block, "difficulty", None));
         assert_eq!(
             Some("0x0000000000000000000000000000000000000000000000000000000000000000".to_string()),
-            get_pretty_block_attr(&block, "extraData")
+            get_pretty_block_attr(&block, "extraData", None)
         );
-        assert_eq!(Some("653145".to_string()), get_pretty_block_attr(&block, "gasLimit"));
-        assert_eq!(Some("653145".to_string()), get_pretty_block_attr(&block, "gasUsed"));
+        assert_eq!(Some("653145".to_string()), get_pretty_block_attr(&block, "gasLimit", None));
+        assert_eq!(Some("653145".to_string()), get_pretty_block_attr(&block, "gasUsed", None));
         assert_eq!(
             Some("0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331".to_string()),
-            get_pretty_block_attr(&block, "hash")
+            get_pretty_block_attr(&block, "hash", None)
         );
-        assert_eq!(Some("0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331".to_string()),  get_pretty_block_attr(&block, "logsBloom"));
+        assert_eq!(Some("0x0e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d15273310e670ec64341771606e55d6b4ca35a1a6b75ee3d5145a99d05921026d1527331".to_string()),  get_pretty_block_attr(&block, "logsBloom", None));
         assert_eq!(
             Some("0x0000000000000000000000000000000000000001".to_string()),
-            get_pretty_block_attr(&block, "miner")
+            get_pretty_block_attr(&block, "miner", None)
         );
         assert_eq!(
             Some("0x1010101010101010101010101010101010101010101010101010101010101010".to_string()),
-            get_pretty_block_attr(&block, "mixHash")
+            get_pretty_block_attr(&block, "mixHash", None)
+        );
+        assert_eq!(
+            Some("0x0000000000000000".to_string()),
+            get_pretty_block_attr(&block, "nonce", None)
         );
-        assert_eq!(Some("0x0000000000000000".to_string()), get_pretty_block_attr(&block, "nonce"));
-        assert_eq!(Some("436".to_string()), get_pretty_block_attr(&block, "number"));
+        assert_eq!(Some("436".to_string()), get_pretty_block_attr(&block, "number", None));
         assert_eq!(
             Some("0x9646252be9520f6e71339a8df9c55e4d7619deeb018d2a3f2d21fc165dde5eb5".to_string()),
-            get_pretty_block_attr(&block, "parentHash")
+            get_pretty_block_attr(&block, "parentHash", None)
         );
         assert_eq!(
             Some("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421".to_string()),
-            get_pretty_block_attr(&block, "receiptsRoot")
+            get_pretty_block_attr(&block, "receiptsRoot", None)
         );
         assert_eq!(
             Some("0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347".to_string()),
-            get_pretty_block_attr(&block, "sha3Uncles")
+            get_pretty_block_attr(&block, "sha3Uncles", None)
         );
-        assert_eq!(Some("163591".to_string()), get_pretty_block_attr(&block, "size"));
+        assert_eq!(Some("163591".to_string()), get_pretty_block_attr(&block, "size", None));
         assert_eq!(
             Some("0xd5855eb08b3387c0af375e9cdb6acfc05eb8f519e419b874b6ff2ffda7ed1dff".to_string()),
-            get_pretty_block_attr(&block, "stateRoot")
+            get_pretty_block_attr(&block, "stateRoot", None)
+        );
+        assert_eq!(
+            Some("1424182926".to_string()),
+            get_pretty_block_attr(&block, "timestamp", None)
+        );
+        assert_eq!(
+            Some("163591".to_string()),
+            get_pretty_block_attr(&block, "totalDifficulty", None)
         );
-        assert_eq!(Some("1424182926".to_string()), get_pretty_block_attr(&block, "timestamp"));
-        assert_eq!(Some("163591".to_string()), get_pretty_block_attr(&block, "totalDifficulty"));
     }
 }
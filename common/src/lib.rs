@@ -9,8 +9,10 @@ pub mod constants;
 pub mod contracts;
 pub mod errors;
 pub mod evm;
+pub mod failover;
 pub mod fmt;
 pub mod fs;
+pub mod optimizer;
 pub mod provider;
 pub mod selectors;
 pub mod shell;
@@ -22,3 +24,4 @@ pub use contracts::*;
 pub use traits::*;
 pub mod transactions;
 pub use transactions::*;
+pub mod vyper;
@@ -0,0 +1,137 @@
+//! Support for optimizer runs of individual contracts, see `optimizer_overrides`
+
+use crate::{compile, fs};
+use ethers_solc::Project;
+use eyre::WrapErr;
+use foundry_config::Config;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// The file in the artifacts directory that lists all artifacts compiled with overridden optimizer
+/// runs
+pub const OPTIMIZER_OVERRIDES_FILE: &str = "optimizer-overrides.json";
+
+/// An artifact that was compiled with the optimizer runs of `optimizer_overrides`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverriddenArtifact {
+    /// The contract as `<path>:<name>`, with the path relative to the root
+    pub contract: String,
+    /// The optimizer runs the contract was compiled with
+    pub runs: usize,
+    /// The artifact file, relative to the artifacts directory
+    pub artifact: PathBuf,
+}
+
+/// A target of `optimizer_overrides`
+struct OverrideTarget<'a> {
+    key: &'a str,
+    source: PathBuf,
+    /// The contract, all contracts of the source if not set
+    name: Option<&'a str>,
+}
+
+impl<'a> OverrideTarget<'a> {
+    fn parse(root: &Path, key: &'a str) -> eyre::Result<Self> {
+        let (path, name) = match key.rsplit_once(':') {
+            Some((path, name)) => (path, Some(name)),
+            None => (key, None),
+        };
+        let source = root.join(path);
+        if !source.is_file() {
+            eyre::bail!("Optimizer override `{key}` does not match a source file")
+        }
+        Ok(Self { key, source, name })
+    }
+
+    fn matches(&self, source: &Path, name: &str) -> bool {
+        self.source == source && self.name.map_or(true, |n| n == name)
+    }
+}
+
+/// Compiles all contracts of `optimizer_overrides` with their optimizer runs and replaces their
+/// artifacts in the project's artifacts directory
+///
+/// Every distinct number of runs is compiled in a separate solc job, with its own artifacts and
+/// cache directory inside the project's cache directory. The replaced artifacts are recorded in
+/// [OPTIMIZER_OVERRIDES_FILE].
+///
+/// This is invoked by every compilation of a project that was created from a config, see
+/// [compile::ProjectCompiler::config]
+pub fn compile_optimizer_overrides(
+    config: &Config,
+    project: &Project,
+) -> eyre::Result<Vec<OverriddenArtifact>> {
+    if config.optimizer_overrides.is_empty() {
+        return Ok(Vec::new())
+    }
+    let root = &config.__root.0;
+
+    let mut by_runs: BTreeMap<usize, Vec<OverrideTarget>> = BTreeMap::new();
+    for (key, runs) in &config.optimizer_overrides {
+        by_runs.entry(*runs).or_default().push(OverrideTarget::parse(root, key)?);
+    }
+
+    let mut overridden = Vec::new();
+    for (runs, targets) in by_runs {
+        let dir = config.cache_path.join("optimizer-overrides").join(runs.to_string());
+        let mut job = config.clone();
+        job.optimizer = true;
+        job.optimizer_runs = runs;
+        job.out = dir.join("out");
+        job.cache_path = dir.join("cache");
+        job.optimizer_overrides = Default::default();
+        let job_project = job.project()?;
+
+        let files = targets
+            .iter()
+            .map(|target| target.source.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let output = compile::compile_files(&job_project, &job, files, true)
+            .wrap_err_with(|| format!("Failed to compile contracts with {runs} optimizer runs"))?;
+
+        let mut matched = vec![false; targets.len()];
+        for (id, _) in output.into_artifacts() {
+            let mut is_target = false;
+            for (target, matched) in targets.iter().zip(matched.iter_mut()) {
+                if target.matches(&id.source, &id.name) {
+                    *matched = true;
+                    is_target = true;
+                }
+            }
+            if !is_target {
+                continue
+            }
+
+            let artifact = id.path.strip_prefix(&job_project.paths.artifacts)?.to_path_buf();
+            let target = project.paths.artifacts.join(&artifact);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&id.path, &target).wrap_err_with(|| {
+                format!("Failed to copy {} to {}", id.path.display(), target.display())
+            })?;
+
+            let source = id.source.strip_prefix(root).unwrap_or(&id.source);
+            overridden.push(OverriddenArtifact {
+                contract: format!("{}:{}", source.display(), id.name),
+                runs,
+                artifact,
+            });
+        }
+
+        if let Some(idx) = matched.iter().position(|matched| !matched) {
+            eyre::bail!("Optimizer override `{}` does not match a contract", targets[idx].key)
+        }
+    }
+
+    overridden.sort_by(|a, b| a.contract.cmp(&b.contract));
+    fs::write_json_file(&project.paths.artifacts.join(OPTIMIZER_OVERRIDES_FILE), &overridden)?;
+
+    Ok(overridden)
+}
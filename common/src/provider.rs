@@ -1,20 +1,29 @@
 //! Commonly used helpers to construct `Provider`s
 
-use crate::{ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT};
+use crate::{
+    failover::{split_urls, FailoverHttp},
+    ALCHEMY_FREE_TIER_CUPS, REQUEST_TIMEOUT,
+};
 use ethers_core::types::{Chain, U256};
 use ethers_middleware::gas_oracle::{GasCategory, GasOracle, Polygon};
 use ethers_providers::{
-    is_local_endpoint, Http, HttpRateLimitRetryPolicy, Middleware, Provider, RetryClient,
+    is_local_endpoint, HttpRateLimitRetryPolicy, Middleware, Provider, RetryClient,
     RetryClientBuilder, DEFAULT_LOCAL_POLL_INTERVAL,
 };
 use eyre::WrapErr;
 use reqwest::{IntoUrl, Url};
-use std::{borrow::Cow, time::Duration};
+use std::{
+    borrow::Cow,
+    sync::{atomic::AtomicU64, Arc},
+    time::Duration,
+};
 
 /// Helper type alias for a retry provider
-pub type RetryProvider = Provider<RetryClient<Http>>;
+pub type RetryProvider = Provider<RetryClient<FailoverHttp>>;
 
 /// Helper type alias for a rpc url
+///
+/// This can be a comma separated list of urls, see [FailoverHttp].
 pub type RpcUrl = String;
 
 /// Same as `try_get_http_provider`
@@ -45,7 +54,8 @@ pub fn try_get_http_provider(builder: impl Into<ProviderBuilder>) -> eyre::Resul
 #[derive(Debug)]
 pub struct ProviderBuilder {
     // Note: this is a result, so we can easily chain builder calls
-    url: eyre::Result<Url>,
+    /// The urls of all endpoints, in the order of their priority
+    urls: eyre::Result<Vec<Url>>,
     chain: Chain,
     max_retry: u32,
     timeout_retry: u32,
@@ -53,22 +63,20 @@ pub struct ProviderBuilder {
     timeout: Duration,
     /// available CUPS
     compute_units_per_second: u64,
+    /// counts all requests sent to the endpoints
+    request_counter: Option<Arc<AtomicU64>>,
 }
 
 // === impl ProviderBuilder ===
 
 impl ProviderBuilder {
     /// Creates a new builder instance
+    ///
+    /// The url can be a comma separated list of urls the provider fails over between, see
+    /// [FailoverHttp].
     pub fn new(url: impl IntoUrl) -> Self {
-        let url_str = url.as_str();
-        if url_str.starts_with("localhost:") {
-            // invalid url: non-prefixed URL scheme is not allowed, so we prepend the default http
-            // prefix
-            return Self::new(format!("http://{url_str}"))
-        }
-        let err = format!("Invalid provider url: {url_str}");
         Self {
-            url: url.into_url().wrap_err(err),
+            urls: split_urls(url.as_str()).map(parse_url).collect(),
             chain: Chain::Mainnet,
             max_retry: 100,
             timeout_retry: 5,
@@ -76,6 +84,7 @@ impl ProviderBuilder {
             timeout: REQUEST_TIMEOUT,
             // alchemy max cpus <https://github.com/alchemyplatform/alchemy-docs/blob/master/documentation/compute-units.md#rate-limits-cups>
             compute_units_per_second: ALCHEMY_FREE_TIER_CUPS,
+            request_counter: None,
         }
     }
 
@@ -124,6 +133,13 @@ impl ProviderBuilder {
         self
     }
 
+    /// Sets a counter that is incremented for every request sent to the endpoints, including
+    /// retries
+    pub fn request_counter(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.request_counter = Some(counter);
+        self
+    }
+
     /// Sets aggressive `max_retry` and `initial_backoff` values
     ///
     /// This is only recommend for local dev nodes
@@ -146,20 +162,24 @@ impl ProviderBuilder {
     /// Constructs the `RetryProvider` taking all configs into account
     pub fn build(self) -> eyre::Result<RetryProvider> {
         let ProviderBuilder {
-            url,
+            urls,
             chain,
             max_retry,
             timeout_retry,
             initial_backoff,
             timeout,
             compute_units_per_second,
+            request_counter,
         } = self;
-        let url = url?;
+        let urls = urls?;
 
         let client = reqwest::Client::builder().timeout(timeout).build()?;
-        let is_local = is_local_endpoint(url.as_str());
+        let is_local = urls.iter().all(|url| is_local_endpoint(url.as_str()));
 
-        let provider = Http::new_with_client(url, client);
+        let mut provider = FailoverHttp::new(urls, client);
+        if let Some(counter) = request_counter {
+            provider = provider.with_request_counter(counter);
+        }
 
         #[allow(clippy::box_default)]
         let mut provider = Provider::new(
@@ -180,6 +200,15 @@ impl ProviderBuilder {
     }
 }
 
+fn parse_url(url: &str) -> eyre::Result<Url> {
+    if url.starts_with("localhost:") {
+        // invalid url: non-prefixed URL scheme is not allowed, so we prepend the default http
+        // prefix
+        return parse_url(&format!("http://{url}"))
+    }
+    url.into_url().wrap_err_with(|| format!("Invalid provider url: {url}"))
+}
+
 impl<'a> From<&'a str> for ProviderBuilder {
     fn from(url: &'a str) -> Self {
         Self::new(url)
@@ -243,9 +272,24 @@ mod tests {
     #[test]
     fn can_auto_correct_missing_prefix() {
         let builder = ProviderBuilder::new("localhost:8545");
-        assert!(builder.url.is_ok());
+        assert!(builder.urls.is_ok());
+
+        let urls = builder.urls.unwrap();
+        assert_eq!(urls, vec![Url::parse("http://localhost:8545").unwrap()]);
+    }
+
+    #[test]
+    fn can_parse_fallback_urls() {
+        let urls = ProviderBuilder::new("https://primary.io/v2/key,localhost:8545").urls.unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                Url::parse("https://primary.io/v2/key").unwrap(),
+                Url::parse("http://localhost:8545").unwrap()
+            ]
+        );
 
-        let url = builder.url.unwrap();
-        assert_eq!(url, Url::parse("http://localhost:8545").unwrap());
+        let err = ProviderBuilder::new("https://primary.io,").urls.unwrap_err();
+        assert_eq!(err.to_string(), "Invalid provider url: ");
     }
 }
@@ -10,6 +10,11 @@ pub trait TestFilter: Send + Sync {
     fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool;
     /// Returns a contract with the given path should be included
     fn matches_path(&self, path: impl AsRef<str>) -> bool;
+    /// Returns whether the test of the contract with the given identifier (`<path>:<name>`) should
+    /// be included
+    fn matches_contract_test(&self, _contract_id: &str, _test_signature: &str) -> bool {
+        true
+    }
 }
 
 /// Extension trait for `Function`
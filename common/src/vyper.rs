@@ -0,0 +1,690 @@
+//! Support for compiling the Vyper contracts of a [Project]
+//!
+//! Vyper sources (`.vy`) are not handled by [Project::compile()], so they're compiled separately
+//! with the compiler version their `# @version` pragma requires. Compilers are installed to
+//! `~/.foundry/vyper/<version>`, similar to how `svm` manages solc versions. The artifacts are
+//! written in the same format and to the same location as the artifacts of Solidity contracts, so
+//! they can be used with `deployCode` and are known to the trace decoder.
+use crate::{fs, shell};
+use ethers_core::utils::{hex, keccak256};
+use ethers_solc::{ArtifactId, ConfigurableContractArtifact, Project, ProjectPathsConfig};
+use eyre::{Result, WrapErr};
+use foundry_config::Config;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// The file extension of Vyper sources
+pub const VYPER_EXTENSION: &str = "vy";
+
+/// The name of the file the Vyper compilation cache is stored in, next to the solc cache file
+pub const VYPER_CACHE_FILE_NAME: &str = "vyper-files-cache.json";
+
+const VYPER_RELEASES_URL: &str =
+    "https://api.github.com/repos/vyperlang/vyper/releases?per_page=100";
+
+/// The outputs that are requested for every compiled source
+const OUTPUT_SELECTION: [&str; 4] =
+    ["abi", "evm.bytecode", "evm.deployedBytecode", "evm.methodIdentifiers"];
+
+static VERSION_PRAGMA: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^#\s*(?:@version|pragma\s+version)\s+(.+?)\s*$").unwrap());
+
+static IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*(?:from\s+([\w.]+)\s+import\s+(\w+)|import\s+([\w.]+)\s+as\s+\w+)")
+        .unwrap()
+});
+
+/// Returns all Vyper sources in the source, test and script directories of the project
+pub fn vyper_sources(paths: &ProjectPathsConfig) -> BTreeSet<PathBuf> {
+    [&paths.sources, &paths.tests, &paths.scripts]
+        .into_iter()
+        .filter(|dir| dir.exists())
+        .flat_map(|dir| fs::files_with_ext(dir, VYPER_EXTENSION))
+        .collect()
+}
+
+/// Returns the compiler versions the `# @version` pragma of the source allows, if it has one
+///
+/// A plain version like `0.3.7` only matches that exact version.
+pub fn version_req(source: &str) -> Result<Option<VersionReq>> {
+    let pragma = match VERSION_PRAGMA.captures(source) {
+        Some(caps) => caps[1].to_string(),
+        None => return Ok(None),
+    };
+    let req = match Version::parse(&pragma) {
+        Ok(version) => VersionReq::parse(&format!("={version}"))?,
+        Err(_) => VersionReq::parse(&pragma)
+            .wrap_err_with(|| format!("Invalid Vyper version pragma `{pragma}`"))?,
+    };
+    Ok(Some(req))
+}
+
+/// Returns the source and all files it imports directly or transitively, relative to `root`
+///
+/// Imports are Vyper sources or JSON interfaces. Imports that aren't files of the project, like
+/// the builtin `vyper.interfaces`, are skipped.
+pub fn import_closure(root: &Path, source: &Path) -> Result<BTreeSet<PathBuf>> {
+    let mut closure = BTreeSet::new();
+    let mut queue = vec![source.to_path_buf()];
+    while let Some(file) = queue.pop() {
+        if !closure.insert(file.clone()) ||
+            file.extension().and_then(|ext| ext.to_str()) != Some(VYPER_EXTENSION)
+        {
+            continue
+        }
+        let content = fs::read_to_string(root.join(&file))?;
+        queue.extend(imports(root, &file, &content));
+    }
+    Ok(closure)
+}
+
+/// Returns the files that are imported by the source at `file`, relative to `root`
+///
+/// Relative imports (`from . import Foo`) are resolved from the directory of the file, all others
+/// from the directory of the file or the project root.
+fn imports(root: &Path, file: &Path, content: &str) -> Vec<PathBuf> {
+    let dir = file.parent().unwrap_or_else(|| Path::new(""));
+    IMPORT
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let module = match (caps.get(1), caps.get(2), caps.get(3)) {
+                (Some(from), Some(name), _) => format!("{}.{}", from.as_str(), name.as_str()),
+                (_, _, Some(module)) => module.as_str().to_string(),
+                _ => return None,
+            };
+            let relative = module.trim_start_matches('.');
+            let dots = module.len() - relative.len();
+            let bases = if dots > 0 {
+                let mut base = dir.to_path_buf();
+                for _ in 1..dots {
+                    base.pop();
+                }
+                vec![base]
+            } else {
+                vec![dir.to_path_buf(), PathBuf::new()]
+            };
+            let path = relative.split('.').filter(|part| !part.is_empty()).collect::<PathBuf>();
+            bases.into_iter().find_map(|base| {
+                ["vy", "json"]
+                    .into_iter()
+                    .map(|ext| base.join(&path).with_extension(ext))
+                    .find(|candidate| root.join(candidate).is_file())
+            })
+        })
+        .collect()
+}
+
+/// Returns the hash the cache entry of a source is keyed on: the content of all files of its
+/// import closure and the compiler settings
+fn cache_hash(root: &Path, closure: &BTreeSet<PathBuf>) -> Result<String> {
+    let mut data = serde_json::to_vec(&OUTPUT_SELECTION)?;
+    for file in closure {
+        data.extend(file.to_string_lossy().as_bytes());
+        data.push(0);
+        data.extend(std::fs::read(root.join(file))?);
+        data.push(0);
+    }
+    Ok(hex::encode(keccak256(data)))
+}
+
+/// A Vyper compiler binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vyper {
+    /// The path of the binary
+    pub path: PathBuf,
+    /// The version of the compiler
+    pub version: Version,
+}
+
+impl Vyper {
+    /// Creates a new instance for the binary, determining its version
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let output = Command::new(&path)
+            .arg("--version")
+            .output()
+            .wrap_err_with(|| format!("Failed to execute {}", path.display()))?;
+        // the version has the format `0.3.7+commit.6020b8bb`
+        let version = String::from_utf8_lossy(&output.stdout);
+        let version = version.trim().split('+').next().unwrap_or_default();
+        let version = Version::parse(version)
+            .wrap_err_with(|| format!("Unknown version of Vyper {}", path.display()))?;
+        Ok(Self { path, version })
+    }
+
+    /// Returns the directory the Vyper compilers are installed to, `~/.foundry/vyper`
+    pub fn compilers_dir() -> Option<PathBuf> {
+        Config::foundry_dir().map(|dir| dir.join("vyper"))
+    }
+
+    /// Returns all installed compilers
+    pub fn installed() -> Vec<Self> {
+        let dir = match Self::compilers_dir() {
+            Some(dir) => dir,
+            None => return vec![],
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+        entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let version = Version::parse(&entry.file_name().to_string_lossy()).ok()?;
+                let path = entry.path().join(binary_name());
+                path.exists().then_some(Self { path, version })
+            })
+            .collect()
+    }
+
+    /// Returns the newest compiler matching the requirement
+    ///
+    /// Installed compilers are preferred over the `vyper` in `PATH`. If neither matches and
+    /// `offline` is not set, the newest matching release is installed.
+    pub fn find_or_install(req: Option<&VersionReq>, offline: bool) -> Result<Self> {
+        let matches = |version: &Version| req.map_or(true, |req| req.matches(version));
+
+        if let Some(vyper) = Self::installed()
+            .into_iter()
+            .filter(|vyper| matches(&vyper.version))
+            .max_by(|a, b| a.version.cmp(&b.version))
+        {
+            return Ok(vyper)
+        }
+        if let Ok(vyper) = Self::new("vyper") {
+            if matches(&vyper.version) {
+                return Ok(vyper)
+            }
+        }
+        let req = req.map(|req| req.to_string()).unwrap_or_else(|| "*".to_string());
+        if offline {
+            eyre::bail!("No installed Vyper compiler matches `{req}` and offline mode is enabled")
+        }
+        Self::install(&req)
+    }
+
+    /// Installs the newest released compiler matching the requirement
+    pub fn install(req: &str) -> Result<Self> {
+        let version_req = VersionReq::parse(req)?;
+        let dir = Self::compilers_dir().wrap_err("Failed to find the home directory")?;
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| -> Result<Self> {
+                    let client = reqwest::blocking::Client::builder()
+                        .user_agent("foundry")
+                        .timeout(Duration::from_secs(600))
+                        .build()?;
+                    let releases: Vec<Release> =
+                        client.get(VYPER_RELEASES_URL).send()?.error_for_status()?.json()?;
+
+                    let (version, url) = releases
+                        .into_iter()
+                        .filter_map(|release| {
+                            let version =
+                                Version::parse(release.tag_name.trim_start_matches('v')).ok()?;
+                            if !version_req.matches(&version) {
+                                return None
+                            }
+                            let asset = release
+                                .assets
+                                .into_iter()
+                                .find(|asset| asset.name.ends_with(platform_suffix()))?;
+                            Some((version, asset.browser_download_url))
+                        })
+                        .max_by(|(a, _), (b, _)| a.cmp(b))
+                        .ok_or_else(|| eyre::eyre!("No Vyper release matches `{req}`"))?;
+
+                    shell::println(format!("installing vyper version \"{version}\""))?;
+                    let binary = client.get(url).send()?.error_for_status()?.bytes()?;
+
+                    let dir = dir.join(version.to_string());
+                    fs::create_dir_all(&dir)?;
+                    let path = dir.join(binary_name());
+                    fs::write(&path, binary)?;
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+                    }
+                    Ok(Self { path, version })
+                })
+                .join()
+                .map_err(|_| eyre::eyre!("Installing Vyper panicked"))?
+        })
+    }
+
+    /// Compiles the sources, given relative to `root`, and returns the contracts by source
+    ///
+    /// All files the sources import are part of the input, but only the sources are compiled.
+    /// Every Vyper source contains exactly one contract, named after the file.
+    pub fn compile(
+        &self,
+        root: &Path,
+        sources: &[PathBuf],
+    ) -> Result<BTreeMap<PathBuf, ConfigurableContractArtifact>> {
+        let mut files = BTreeSet::new();
+        for source in sources {
+            files.extend(import_closure(root, source)?);
+        }
+
+        let mut input_sources = serde_json::Map::new();
+        let mut interfaces = serde_json::Map::new();
+        for file in files {
+            let content = fs::read_to_string(root.join(&file))?;
+            let key = file.display().to_string();
+            if file.extension().and_then(|ext| ext.to_str()) == Some(VYPER_EXTENSION) {
+                input_sources.insert(key, json!({ "content": content }));
+            } else {
+                // JSON interfaces are either a plain ABI or an object with an `abi` field
+                let interface: Value = serde_json::from_str(&content)
+                    .wrap_err_with(|| format!("Invalid JSON interface {key}"))?;
+                let interface =
+                    if interface.is_array() { json!({ "abi": interface }) } else { interface };
+                interfaces.insert(key, interface);
+            }
+        }
+        let output_selection = sources
+            .iter()
+            .map(|source| (source.display().to_string(), json!(OUTPUT_SELECTION)))
+            .collect::<serde_json::Map<_, _>>();
+        let input = json!({
+            "language": "Vyper",
+            "sources": input_sources,
+            "interfaces": interfaces,
+            "settings": { "outputSelection": output_selection }
+        });
+
+        let mut child = Command::new(&self.path)
+            .arg("--standard-json")
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .wrap_err_with(|| format!("Failed to execute {}", self.path.display()))?;
+        child.stdin.take().expect("stdin is piped").write_all(input.to_string().as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            eyre::bail!("Vyper failed: {}", String::from_utf8_lossy(&output.stderr))
+        }
+
+        let output: VyperOutput = serde_json::from_slice(&output.stdout)
+            .wrap_err("Failed to parse the output of Vyper")?;
+        let errors = output
+            .errors
+            .iter()
+            .filter(|err| err.severity.as_deref() != Some("warning"))
+            .map(|err| err.formatted_message.as_deref().unwrap_or(&err.message))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            eyre::bail!("Vyper compilation failed:\n{}", errors.join("\n"))
+        }
+
+        let mut artifacts = BTreeMap::new();
+        for (source, contracts) in output.contracts {
+            for contract in contracts.into_values() {
+                artifacts.insert(PathBuf::from(&source), artifact_from_vyper(contract)?);
+            }
+        }
+        Ok(artifacts)
+    }
+}
+
+/// Compiles all Vyper sources of the project that changed since their last compilation and
+/// writes their artifacts
+///
+/// A source changed if it or any file it imports changed. Returns the artifacts of all Vyper
+/// contracts of the project, their sources relative to the project root.
+pub fn compile_vyper(
+    project: &Project,
+    offline: bool,
+) -> Result<Vec<(ArtifactId, ConfigurableContractArtifact)>> {
+    compile_vyper_with(project, |req| Vyper::find_or_install(req, offline))
+}
+
+fn compile_vyper_with(
+    project: &Project,
+    mut find_compiler: impl FnMut(Option<&VersionReq>) -> Result<Vyper>,
+) -> Result<Vec<(ArtifactId, ConfigurableContractArtifact)>> {
+    let root = &project.paths.root;
+    let sources = vyper_sources(&project.paths);
+    if sources.is_empty() {
+        return Ok(vec![])
+    }
+
+    let cache_path = project
+        .paths
+        .cache
+        .parent()
+        .map(|dir| dir.join(VYPER_CACHE_FILE_NAME))
+        .unwrap_or_else(|| PathBuf::from(VYPER_CACHE_FILE_NAME));
+    let mut cache: VyperCache = fs::read_json_file(&cache_path).unwrap_or_default();
+
+    let mut artifacts = Vec::new();
+    // the sources to compile and their cache hash, by compiler
+    let mut dirty: BTreeMap<Version, (Vyper, Vec<(PathBuf, String)>)> = BTreeMap::new();
+    let mut compilers: BTreeMap<String, Vyper> = BTreeMap::new();
+    for path in &sources {
+        let source = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+        let artifact_path = artifact_path(&project.paths.artifacts, &source);
+        let hash = cache_hash(root, &import_closure(root, &source)?)?;
+
+        if let Some(entry) = cache.files.get(&source) {
+            if entry.content_hash == hash && artifact_path.exists() {
+                if let Ok(artifact) = fs::read_json_file(&artifact_path) {
+                    artifacts.push((
+                        artifact_id(&source, artifact_path, entry.version.clone()),
+                        artifact,
+                    ));
+                    continue
+                }
+            }
+        }
+
+        let req = version_req(&fs::read_to_string(path)?)?;
+        let key = req.as_ref().map(|req| req.to_string()).unwrap_or_default();
+        let vyper = match compilers.get(&key) {
+            Some(vyper) => vyper.clone(),
+            None => {
+                let vyper = find_compiler(req.as_ref())?;
+                compilers.insert(key, vyper.clone());
+                vyper
+            }
+        };
+        dirty
+            .entry(vyper.version.clone())
+            .or_insert_with(|| (vyper, vec![]))
+            .1
+            .push((source, hash));
+    }
+
+    for (version, (vyper, sources)) in dirty {
+        shell::println(format!("Compiling {} files with vyper {version}", sources.len()))?;
+        let hashes: BTreeMap<_, _> = sources.into_iter().collect();
+        let sources = hashes.keys().cloned().collect::<Vec<_>>();
+        for (source, artifact) in vyper.compile(root, &sources)? {
+            let artifact_path = artifact_path(&project.paths.artifacts, &source);
+            if let Some(dir) = artifact_path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write_json_file(&artifact_path, &artifact)?;
+            if let Some(hash) = hashes.get(&source) {
+                cache.files.insert(
+                    source.clone(),
+                    VyperCacheEntry { content_hash: hash.clone(), version: version.clone() },
+                );
+            }
+            artifacts.push((artifact_id(&source, artifact_path, version.clone()), artifact));
+        }
+    }
+
+    cache.files.retain(|source, _| sources.contains(&root.join(source)));
+    if let Some(dir) = cache_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write_json_file(&cache_path, &cache)?;
+
+    Ok(artifacts)
+}
+
+/// The compilation cache of the Vyper sources
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VyperCache {
+    /// The cache entries by source, relative to the project root
+    pub files: BTreeMap<PathBuf, VyperCacheEntry>,
+}
+
+/// The cache entry of a Vyper source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VyperCacheEntry {
+    /// The hash of the content of the source, the files it imports and the compiler settings
+    pub content_hash: String,
+    /// The compiler version the source was compiled with
+    pub version: Version,
+}
+
+/// Returns the name of the contract of the Vyper source, the file name without the extension
+pub fn contract_name(source: &Path) -> String {
+    source.file_stem().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Returns the path of the artifact of the Vyper source, `<artifacts>/<File>.vy/<File>.json`
+fn artifact_path(artifacts: &Path, source: &Path) -> PathBuf {
+    let file_name = source.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    artifacts.join(file_name.as_ref()).join(format!("{}.json", contract_name(source)))
+}
+
+fn artifact_id(source: &Path, path: PathBuf, version: Version) -> ArtifactId {
+    ArtifactId { path, name: contract_name(source), source: source.to_path_buf(), version }
+}
+
+/// Converts a contract of the standard JSON output of Vyper into the artifact format of solc
+fn artifact_from_vyper(contract: Value) -> Result<ConfigurableContractArtifact> {
+    let evm = &contract["evm"];
+    let bytecode = |kind: &str| json!({ "object": evm[kind]["object"], "linkReferences": {} });
+    // unlike solc, vyper prefixes the selectors with `0x`
+    let method_identifiers = evm["methodIdentifiers"]
+        .as_object()
+        .map(|ids| {
+            ids.iter()
+                .map(|(sig, id)| {
+                    let id = id.as_str().unwrap_or_default().trim_start_matches("0x");
+                    (sig.clone(), Value::from(id))
+                })
+                .collect::<serde_json::Map<_, _>>()
+        })
+        .unwrap_or_default();
+    let artifact = json!({
+        "abi": contract["abi"],
+        "bytecode": bytecode("bytecode"),
+        "deployedBytecode": bytecode("deployedBytecode"),
+        "methodIdentifiers": method_identifiers,
+    });
+    Ok(serde_json::from_value(artifact)?)
+}
+
+fn binary_name() -> &'static str {
+    if cfg!(windows) {
+        "vyper.exe"
+    } else {
+        "vyper"
+    }
+}
+
+/// The suffix of the release asset of the current platform
+fn platform_suffix() -> &'static str {
+    if cfg!(target_os = "macos") {
+        ".darwin"
+    } else if cfg!(windows) {
+        ".windows.exe"
+    } else {
+        ".linux"
+    }
+}
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Deserialize)]
+struct VyperOutput {
+    #[serde(default)]
+    errors: Vec<VyperError>,
+    #[serde(default)]
+    contracts: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VyperError {
+    severity: Option<String>,
+    #[serde(default)]
+    message: String,
+    formatted_message: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_solc::Artifact;
+
+    #[test]
+    fn can_parse_version_pragma() {
+        let req = version_req("# @version ^0.3.7\n\nx: uint256\n").unwrap().unwrap();
+        assert!(req.matches(&"0.3.9".parse().unwrap()));
+        assert!(!req.matches(&"0.4.0".parse().unwrap()));
+
+        let req = version_req("# pragma version 0.3.10\n").unwrap().unwrap();
+        assert!(req.matches(&"0.3.10".parse().unwrap()));
+        assert!(!req.matches(&"0.3.11".parse().unwrap()));
+
+        assert_eq!(version_req("x: public(uint256)\n").unwrap(), None);
+        assert!(version_req("# @version latest\n").is_err());
+    }
+
+    #[test]
+    fn can_convert_vyper_output() {
+        let contract = json!({
+            "abi": [{"type": "function", "name": "number", "inputs": [], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"}],
+            "evm": {
+                "bytecode": {"object": "0x6003361161000c57", "opcodes": "PUSH1 0x3"},
+                "deployedBytecode": {"object": "0x6003361161000c", "opcodes": "PUSH1 0x3"},
+                "methodIdentifiers": {"number()": "0x8381f58a"}
+            }
+        });
+        let artifact = artifact_from_vyper(contract).unwrap();
+        assert_eq!(artifact.get_abi().unwrap().functions().count(), 1);
+        assert_eq!(
+            artifact.get_bytecode_bytes().unwrap().as_ref(),
+            &[0x60, 3, 0x36, 0x11, 0x61, 0, 0x0c, 0x57]
+        );
+        assert_eq!(artifact.get_deployed_bytecode_bytes().unwrap().len(), 7);
+        assert_eq!(artifact.method_identifiers.unwrap()["number()"], "8381f58a");
+    }
+
+    #[test]
+    fn can_find_vyper_sources() {
+        let root = tempfile::tempdir().unwrap();
+        let paths = ProjectPathsConfig::builder().build_with_root(root.path());
+        fs::create_dir_all(&paths.sources).unwrap();
+        fs::create_dir_all(&paths.tests).unwrap();
+        fs::write(paths.sources.join("Counter.vy"), "").unwrap();
+        fs::write(paths.sources.join("Counter.sol"), "").unwrap();
+        fs::write(paths.tests.join("Vault.vy"), "").unwrap();
+
+        let sources = vyper_sources(&paths);
+        assert_eq!(
+            sources.into_iter().collect::<Vec<_>>(),
+            [paths.sources.join("Counter.vy"), paths.tests.join("Vault.vy")]
+        );
+        assert_eq!(
+            artifact_path(&paths.artifacts, Path::new("src/Counter.vy")),
+            paths.artifacts.join("Counter.vy/Counter.json")
+        );
+    }
+
+    #[test]
+    fn can_resolve_import_closure() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        fs::create_dir_all(root.join("src/interfaces")).unwrap();
+        fs::write(
+            root.join("src/Vault.vy"),
+            "from .interfaces import IToken\nimport src.Math as Math\nfrom vyper.interfaces import ERC20\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/interfaces/IToken.json"), "[]").unwrap();
+        fs::write(root.join("src/Math.vy"), "from . import Vault\n").unwrap();
+
+        let closure = import_closure(root, Path::new("src/Vault.vy")).unwrap();
+        assert_eq!(
+            closure.into_iter().collect::<Vec<_>>(),
+            ["src/Math.vy", "src/Vault.vy", "src/interfaces/IToken.json"].map(PathBuf::from)
+        );
+        assert_eq!(
+            import_closure(root, Path::new("src/Math.vy")).unwrap(),
+            import_closure(root, Path::new("src/Vault.vy")).unwrap()
+        );
+    }
+
+    // compiles with a fake compiler that records its input and outputs a fixed contract
+    #[cfg(unix)]
+    #[test]
+    fn recompiles_dependents_of_changed_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let paths = ProjectPathsConfig::builder().build_with_root(root.path());
+        let project = Project::builder().paths(paths.clone()).build().unwrap();
+        fs::create_dir_all(paths.sources.join("interfaces")).unwrap();
+        fs::write(paths.sources.join("Counter.vy"), "from .interfaces import ICounter\n").unwrap();
+        fs::write(paths.sources.join("interfaces/ICounter.json"), "[]").unwrap();
+
+        let bin = root.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let output = json!({
+            "contracts": {
+                "src/Counter.vy": {
+                    "Counter": {
+                        "abi": [],
+                        "evm": {
+                            "bytecode": { "object": "0x00" },
+                            "deployedBytecode": { "object": "0x00" },
+                            "methodIdentifiers": {}
+                        }
+                    }
+                }
+            }
+        });
+        fs::write(bin.join("output.json"), output.to_string()).unwrap();
+        let path = bin.join("vyper");
+        fs::write(
+            &path,
+            "#!/bin/sh\ndir=$(dirname \"$0\")\ncat > \"$dir/input.json\"\necho >> \"$dir/calls\"\ncat \"$dir/output.json\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let vyper = Vyper { path, version: "0.3.7".parse().unwrap() };
+
+        let compile = || {
+            let artifacts = compile_vyper_with(&project, |_| Ok(vyper.clone())).unwrap();
+            assert_eq!(artifacts.len(), 1);
+            assert_eq!(artifacts[0].0.name, "Counter");
+            std::fs::read_to_string(bin.join("calls")).unwrap().lines().count()
+        };
+
+        assert_eq!(compile(), 1);
+        let input: Value = fs::read_json_file(&bin.join("input.json")).unwrap();
+        assert!(input["sources"]["src/Counter.vy"]["content"].is_string());
+        assert_eq!(input["interfaces"]["src/interfaces/ICounter.json"], json!({ "abi": [] }));
+        assert!(input["settings"]["outputSelection"]["src/Counter.vy"].is_array());
+
+        // unchanged sources are not compiled again
+        assert_eq!(compile(), 1);
+
+        // the source is compiled again if a file it imports changed
+        fs::write(paths.sources.join("interfaces/ICounter.json"), r#"{"abi": []}"#).unwrap();
+        assert_eq!(compile(), 2);
+        assert_eq!(compile(), 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! Support for advancing the chain time of tests, see `auto_advance_time`
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The time the block timestamp advances by with every top-level call of a test, mimicking the
+/// pacing of a real chain
+///
+/// ```toml
+/// auto_advance_time = "12s"
+/// ```
+///
+/// Accepts a number of seconds, optionally followed by a unit of `s`, `m`, `h` or `d`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AutoAdvanceTime {
+    secs: u64,
+}
+
+// === impl AutoAdvanceTime ===
+
+impl AutoAdvanceTime {
+    /// Creates a new instance that advances by the given number of seconds
+    pub fn from_secs(secs: u64) -> Self {
+        Self { secs }
+    }
+
+    /// Returns the number of seconds to advance by
+    pub fn secs(&self) -> u64 {
+        self.secs
+    }
+}
+
+impl FromStr for AutoAdvanceTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (num, multiplier) = match s.char_indices().last() {
+            Some((idx, 's')) => (&s[..idx], 1),
+            Some((idx, 'm')) => (&s[..idx], 60),
+            Some((idx, 'h')) => (&s[..idx], 60 * 60),
+            Some((idx, 'd')) => (&s[..idx], 24 * 60 * 60),
+            _ => (s, 1),
+        };
+        let num = num
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| format!("invalid auto advance time `{s}`, expected e.g. `12s`"))?;
+        num.checked_mul(multiplier)
+            .map(Self::from_secs)
+            .ok_or_else(|| format!("auto advance time `{s}` is too large"))
+    }
+}
+
+impl fmt::Display for AutoAdvanceTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}s", self.secs)
+    }
+}
+
+impl Serialize for AutoAdvanceTime {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AutoAdvanceTime {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Secs(u64),
+            Str(String),
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Secs(secs) => Ok(Self::from_secs(secs)),
+            Value::Str(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_auto_advance_time() {
+        assert_eq!("12s".parse::<AutoAdvanceTime>().unwrap().secs(), 12);
+        assert_eq!("12".parse::<AutoAdvanceTime>().unwrap().secs(), 12);
+        assert_eq!("2m".parse::<AutoAdvanceTime>().unwrap().secs(), 120);
+        assert_eq!("1d".parse::<AutoAdvanceTime>().unwrap().secs(), 86400);
+        assert!("12x".parse::<AutoAdvanceTime>().is_err());
+        assert!("s".parse::<AutoAdvanceTime>().is_err());
+
+        #[derive(Deserialize)]
+        struct Wrapper {
+            time: AutoAdvanceTime,
+        }
+        let wrapper: Wrapper = toml::from_str(r#"time = "12s""#).unwrap();
+        assert_eq!(wrapper.time, AutoAdvanceTime::from_secs(12));
+        let wrapper: Wrapper = toml::from_str("time = 5").unwrap();
+        assert_eq!(wrapper.time, AutoAdvanceTime::from_secs(5));
+    }
+}
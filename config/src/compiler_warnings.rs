@@ -0,0 +1,166 @@
+//! Support for handling compiler warnings by error code and source path, see `[warnings]`
+
+use crate::SolidityErrorCode;
+use globset::{Glob, GlobMatcher};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How a compiler warning is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningLevel {
+    /// The warning fails the build
+    Deny,
+    /// The warning is printed, but never fails the build, even with `deny_warnings`
+    Allow,
+    /// The warning is neither printed nor fails the build
+    Ignore,
+}
+
+/// Configures how compiler warnings are handled, per error code and per source path
+///
+/// ```toml
+/// [warnings]
+/// deny = ["unused-var"]
+/// ignore = ["license"]
+///
+/// [[warnings.paths]]
+/// path = "lib/**"
+/// level = "ignore"
+/// ```
+///
+/// Codes in `ignore` are silenced everywhere. Otherwise path rules take precedence over the code
+/// lists, of several matching path rules the last one wins. Warnings that match no rule are denied
+/// if `deny_warnings` is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarningsConfig {
+    /// Warnings that always fail the build
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<SolidityErrorCode>,
+    /// Warnings that never fail the build
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<SolidityErrorCode>,
+    /// Warnings that are silenced, like `ignored_error_codes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<SolidityErrorCode>,
+    /// Levels of warnings in source files matching a glob
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathWarningRule>,
+}
+
+/// The level of the warnings in all source files that match `path`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathWarningRule {
+    /// Glob of source files, relative to the project root, e.g. `lib/**`
+    pub path: String,
+    pub level: WarningLevel,
+    /// Restricts the rule to these error codes, if not empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codes: Vec<SolidityErrorCode>,
+}
+
+// === impl WarningsConfig ===
+
+impl WarningsConfig {
+    /// Returns true if warnings are denied or allowed by code or path
+    ///
+    /// Ignored codes alone are handled like `ignored_error_codes` by the compiler.
+    pub fn has_rules(&self) -> bool {
+        !self.deny.is_empty() || !self.allow.is_empty() || !self.paths.is_empty()
+    }
+
+    /// Returns a matcher that resolves the level of warnings, with the globs of the path rules
+    /// compiled once
+    pub fn matcher(&self, deny_warnings: bool) -> WarningsMatcher<'_> {
+        let paths = self
+            .paths
+            .iter()
+            .map(|rule| (rule, Glob::new(&rule.path).ok().map(|glob| glob.compile_matcher())))
+            .collect();
+        WarningsMatcher { config: self, paths, deny_warnings }
+    }
+}
+
+/// Resolves the level of compiler warnings according to a [WarningsConfig]
+#[derive(Debug)]
+pub struct WarningsMatcher<'a> {
+    config: &'a WarningsConfig,
+    /// The path rules with their glob, `None` if the path is not a valid glob
+    paths: Vec<(&'a PathWarningRule, Option<GlobMatcher>)>,
+    deny_warnings: bool,
+}
+
+// === impl WarningsMatcher ===
+
+impl<'a> WarningsMatcher<'a> {
+    /// Returns the level of a warning with the given error code in the source file at `path`,
+    /// relative to the project root
+    pub fn level(&self, code: Option<u64>, path: Option<&Path>) -> WarningLevel {
+        let contains = |codes: &[SolidityErrorCode]| {
+            code.map(|code| codes.iter().any(|c| u64::from(*c) == code)).unwrap_or_default()
+        };
+
+        if contains(&self.config.ignore) {
+            return WarningLevel::Ignore
+        }
+        if let Some(path) = path {
+            let rule = self.paths.iter().rev().find(|(rule, glob)| {
+                (rule.codes.is_empty() || contains(&rule.codes)) &&
+                    glob.as_ref()
+                        .map(|glob| glob.is_match(path))
+                        .unwrap_or_else(|| path.starts_with(&rule.path))
+            });
+            if let Some((rule, _)) = rule {
+                return rule.level
+            }
+        }
+
+        if contains(&self.config.deny) {
+            WarningLevel::Deny
+        } else if contains(&self.config.allow) || !self.deny_warnings {
+            WarningLevel::Allow
+        } else {
+            WarningLevel::Deny
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_warning_levels() {
+        let warnings: WarningsConfig = toml::from_str(
+            r#"
+            deny = ["unused-var"]
+            allow = [2018]
+            ignore = ["license"]
+
+            [[paths]]
+            path = "lib/**"
+            level = "ignore"
+
+            [[paths]]
+            path = "lib/mine/**"
+            level = "deny"
+            codes = ["shadowing"]
+        "#,
+        )
+        .unwrap();
+
+        let (allowed, denied) = (warnings.matcher(false), warnings.matcher(true));
+
+        let src = Some(Path::new("src/A.sol"));
+        assert_eq!(allowed.level(Some(2072), src), WarningLevel::Deny);
+        assert_eq!(denied.level(Some(2018), src), WarningLevel::Allow);
+        assert_eq!(denied.level(Some(1878), src), WarningLevel::Ignore);
+        assert_eq!(allowed.level(Some(5740), src), WarningLevel::Allow);
+        assert_eq!(denied.level(Some(5740), src), WarningLevel::Deny);
+        assert_eq!(denied.level(None, src), WarningLevel::Deny);
+
+        let lib = Some(Path::new("lib/mine/B.sol"));
+        assert_eq!(denied.level(Some(2072), lib), WarningLevel::Ignore);
+        assert_eq!(allowed.level(Some(2519), lib), WarningLevel::Deny);
+    }
+}
@@ -1,7 +1,7 @@
 //! Support for multiple RPC-endpoints
 
 use crate::resolve::{interpolate, UnresolvedEnvVarError, RE_PLACEHOLDER};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::BTreeMap,
     fmt,
@@ -9,10 +9,27 @@ use std::{
 };
 
 /// Container type for API endpoints, like various RPC endpoints
-#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
-#[serde(transparent)]
+///
+/// An endpoint is either configured as a plain url or as a table that pins it to a block:
+///
+/// ```toml
+/// [rpc_endpoints]
+/// optimism = "https://example.com/"
+/// mainnet = { url = "${RPC_MAINNET}", block = 19000000 }
+/// ```
+///
+/// The url can also be a list of urls to fail over between, tried by ascending `priority` and in
+/// order if the priority is the same. Lists are stored as comma separated urls:
+///
+/// ```toml
+/// [rpc_endpoints]
+/// mainnet = ["${RPC_MAINNET}", { url = "https://fallback.example.com/", priority = 1 }]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct RpcEndpoints {
     endpoints: BTreeMap<String, RpcEndpoint>,
+    /// The blocks the endpoints are pinned to, by alias
+    blocks: BTreeMap<String, u64>,
 }
 
 // === impl RpcEndpoints ===
@@ -20,7 +37,31 @@ pub struct RpcEndpoints {
 impl RpcEndpoints {
     /// Creates a new list of endpoints
     pub fn new(endpoints: impl IntoIterator<Item = (impl Into<String>, RpcEndpoint)>) -> Self {
-        Self { endpoints: endpoints.into_iter().map(|(name, url)| (name.into(), url)).collect() }
+        Self {
+            endpoints: endpoints.into_iter().map(|(name, url)| (name.into(), url)).collect(),
+            blocks: Default::default(),
+        }
+    }
+
+    /// Pins the endpoint with the given alias to the block
+    pub fn with_block(mut self, alias: impl Into<String>, block: u64) -> Self {
+        self.pin_block(alias, block);
+        self
+    }
+
+    /// Pins the endpoint with the given alias to the block
+    pub fn pin_block(&mut self, alias: impl Into<String>, block: u64) {
+        self.blocks.insert(alias.into(), block);
+    }
+
+    /// Returns the block the endpoint with the given alias is pinned to, if any
+    pub fn pinned_block(&self, alias: &str) -> Option<u64> {
+        self.blocks.get(alias).copied()
+    }
+
+    /// Returns all (alias -> block) pairs of pinned endpoints
+    pub fn pinned_blocks(&self) -> &BTreeMap<String, u64> {
+        &self.blocks
     }
 
     /// Returns `true` if this type doesn't contain any endpoints
@@ -32,7 +73,59 @@ impl RpcEndpoints {
     pub fn resolved(self) -> ResolvedRpcEndpoints {
         ResolvedRpcEndpoints {
             endpoints: self.endpoints.into_iter().map(|(name, e)| (name, e.resolve())).collect(),
+            blocks: self.blocks,
+        }
+    }
+}
+
+/// How a single endpoint is stored in the config, see [RpcEndpoints]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RpcEndpointEntry {
+    Url(RpcEndpoint),
+    Table {
+        url: RpcEndpoint,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        block: Option<u64>,
+    },
+}
+
+impl Serialize for RpcEndpoints {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.endpoints.len()))?;
+        for (alias, endpoint) in &self.endpoints {
+            let url = endpoint.clone();
+            let entry = match self.pinned_block(alias) {
+                Some(block) => RpcEndpointEntry::Table { url, block: Some(block) },
+                None => RpcEndpointEntry::Url(url),
+            };
+            map.serialize_entry(alias, &entry)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for RpcEndpoints {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let entries = BTreeMap::<String, RpcEndpointEntry>::deserialize(deserializer)?;
+        let mut endpoints = RpcEndpoints::default();
+        for (alias, entry) in entries {
+            let (url, block) = match entry {
+                RpcEndpointEntry::Url(url) => (url, None),
+                RpcEndpointEntry::Table { url, block } => (url, block),
+            };
+            if let Some(block) = block {
+                endpoints.blocks.insert(alias.clone(), block);
+            }
+            endpoints.endpoints.insert(alias, url);
         }
+        Ok(endpoints)
     }
 }
 
@@ -46,6 +139,8 @@ impl Deref for RpcEndpoints {
 
 /// Represents a single endpoint
 ///
+/// The url can be a comma separated list of urls, in the order of their priority.
+///
 /// This type preserves the value as it's stored in the config. If the value is a reference to an
 /// env var, then the `Endpoint::Env` var will hold the reference (`${MAIN_NET}`) and _not_ the
 /// value of the env var itself.
@@ -123,7 +218,21 @@ impl<'de> Deserialize<'de> for RpcEndpoint {
     where
         D: Deserializer<'de>,
     {
-        let val = String::deserialize(deserializer)?;
+        let val = match RpcEndpointUrls::deserialize(deserializer)? {
+            RpcEndpointUrls::Url(url) => url,
+            RpcEndpointUrls::Urls(urls) => {
+                let mut urls = urls
+                    .into_iter()
+                    .map(|url| match url {
+                        RpcEndpointUrl::Url(url) => (0, url),
+                        RpcEndpointUrl::Table { url, priority } => (priority, url),
+                    })
+                    .collect::<Vec<_>>();
+                // stable, so urls with the same priority keep their order
+                urls.sort_by_key(|(priority, _)| *priority);
+                urls.into_iter().map(|(_, url)| url).collect::<Vec<_>>().join(",")
+            }
+        };
         let endpoint = if RE_PLACEHOLDER.is_match(&val) {
             RpcEndpoint::Env(val)
         } else {
@@ -134,12 +243,34 @@ impl<'de> Deserialize<'de> for RpcEndpoint {
     }
 }
 
+/// The urls of an endpoint as they are stored in the config, either a single, possibly comma
+/// separated, url or a list of urls with optional priorities
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RpcEndpointUrls {
+    Url(String),
+    Urls(Vec<RpcEndpointUrl>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RpcEndpointUrl {
+    Url(String),
+    Table {
+        url: String,
+        #[serde(default)]
+        priority: u32,
+    },
+}
+
 /// Container type for _resolved_ endpoints, see [RpcEndpoints::resolve_all()]
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ResolvedRpcEndpoints {
     /// contains all named endpoints and their URL or an error if we failed to resolve the env var
     /// alias
     endpoints: BTreeMap<String, Result<String, UnresolvedEnvVarError>>,
+    /// The blocks the endpoints are pinned to, by alias
+    blocks: BTreeMap<String, u64>,
 }
 
 // === impl ResolvedEndpoints ===
@@ -149,6 +280,11 @@ impl ResolvedRpcEndpoints {
     pub fn has_unresolved(&self) -> bool {
         self.endpoints.values().any(|val| val.is_err())
     }
+
+    /// Returns the block the endpoint with the given alias is pinned to, if any
+    pub fn pinned_block(&self, alias: &str) -> Option<u64> {
+        self.blocks.get(alias).copied()
+    }
 }
 
 impl Deref for ResolvedRpcEndpoints {
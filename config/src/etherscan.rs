@@ -3,10 +3,12 @@ use crate::{
     resolve::{interpolate, UnresolvedEnvVarError, RE_PLACEHOLDER},
     Chain, Config,
 };
+use ethers_etherscan::errors::EtherscanError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::BTreeMap,
     fmt,
+    future::Future,
     ops::{Deref, DerefMut},
     time::Duration,
 };
@@ -15,6 +17,53 @@ use tracing::warn;
 /// The user agent to use when querying the etherscan API.
 pub const ETHERSCAN_USER_AGENT: &str = concat!("foundry/", env!("CARGO_PKG_VERSION"));
 
+/// The unified multichain etherscan API, the chain is selected via the `chainid` query parameter
+pub const ETHERSCAN_V2_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// The version of the etherscan API to use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EtherscanApiVersion {
+    /// The etherscan API of every chain's explorer, e.g. `api.polygonscan.com`
+    #[default]
+    V1,
+    /// The unified multichain API of `api.etherscan.io`, which supports all chains with a single
+    /// key
+    V2,
+}
+
+/// Runs `request` with every client in turn until the request is not rate limited
+///
+/// The clients are usually created via [ResolvedEtherscanConfig::into_clients()], one for each
+/// configured API key.
+pub async fn with_key_rotation<T, F, Fut>(
+    clients: &[ethers_etherscan::Client],
+    mut request: F,
+) -> Result<T, EtherscanError>
+where
+    F: FnMut(ethers_etherscan::Client) -> Fut,
+    Fut: Future<Output = Result<T, EtherscanError>>,
+{
+    let mut clients = clients.iter().enumerate().peekable();
+    loop {
+        let (idx, client) = match clients.next() {
+            Some(client) => client,
+            None => return Err(EtherscanError::RateLimitExceeded),
+        };
+        match request(client.clone()).await {
+            Err(EtherscanError::RateLimitExceeded) if clients.peek().is_some() => {
+                warn!("etherscan API key #{} is rate limited, rotating to the next key", idx + 1);
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Splits comma separated API keys
+fn split_keys(keys: &str) -> impl Iterator<Item = String> + '_ {
+    keys.split(',').map(str::trim).filter(|key| !key.is_empty()).map(str::to_string)
+}
+
 /// Errors that can occur when creating an `EtherscanConfig`
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum EtherscanConfigError {
@@ -148,6 +197,9 @@ pub struct EtherscanConfig {
     pub url: Option<String>,
     /// The etherscan API KEY that's required to make requests
     pub key: EtherscanApiKey,
+    /// Additional API keys that are used in turn if a key is rate limited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<EtherscanApiKey>,
 }
 
 // === impl EtherscanConfig ===
@@ -163,7 +215,7 @@ impl EtherscanConfig {
         self,
         alias: Option<&str>,
     ) -> Result<ResolvedEtherscanConfig, EtherscanConfigError> {
-        let EtherscanConfig { chain, url, key } = self;
+        let EtherscanConfig { chain, url, key, keys } = self;
         let (chain, alias) = match (chain, alias) {
             // fill one with the other
             (Some(chain), None) => (Some(chain), Some(chain.to_string())),
@@ -173,26 +225,39 @@ impl EtherscanConfig {
             (None, None) => (None, None),
         };
         let key = key.resolve()?;
+        let mut fallback_keys = Vec::with_capacity(keys.len());
+        for key in keys {
+            fallback_keys.push(key.resolve()?);
+        }
 
-        match (chain, url) {
+        let config = match (chain, url) {
             (Some(chain), Some(api_url)) => Ok(ResolvedEtherscanConfig {
                 api_url,
                 browser_url: chain.etherscan_urls().map(|(_, url)| url.to_string()),
-                key,
+                key: String::new(),
+                fallback_keys: Vec::new(),
                 chain: Some(chain),
-            }),
+            }
+            .with_keys(&key)),
             (Some(chain), None) => ResolvedEtherscanConfig::create(key, chain).ok_or_else(|| {
                 let msg = alias.map(|a| format!(" `{a}`")).unwrap_or_default();
                 EtherscanConfigError::UnknownChain(msg, chain)
             }),
-            (None, Some(api_url)) => {
-                Ok(ResolvedEtherscanConfig { api_url, browser_url: None, key, chain: None })
+            (None, Some(api_url)) => Ok(ResolvedEtherscanConfig {
+                api_url,
+                browser_url: None,
+                key: String::new(),
+                fallback_keys: Vec::new(),
+                chain: None,
             }
+            .with_keys(&key)),
             (None, None) => {
                 let msg = alias.map(|a| format!(" for Etherscan config `{a}`")).unwrap_or_default();
                 Err(EtherscanConfigError::MissingUrlOrChain(msg))
             }
-        }
+        }?;
+
+        Ok(config.with_fallback_keys(fallback_keys))
     }
 }
 
@@ -207,6 +272,9 @@ pub struct ResolvedEtherscanConfig {
     pub browser_url: Option<String>,
     /// Resolved api key
     pub key: String,
+    /// Additional api keys that are used in turn if `key` is rate limited
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_keys: Vec<String>,
     /// The chain if set
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chain: Option<Chain>,
@@ -216,15 +284,79 @@ pub struct ResolvedEtherscanConfig {
 
 impl ResolvedEtherscanConfig {
     /// Creates a new instance using the api key and chain
+    ///
+    /// The `api_key` may contain multiple comma separated keys, see [Self::with_keys()]
     pub fn create(api_key: impl Into<String>, chain: impl Into<Chain>) -> Option<Self> {
         let chain = chain.into();
         let (api_url, browser_url) = chain.etherscan_urls()?;
-        Some(Self {
+        let config = Self {
             api_url: api_url.to_string(),
             browser_url: Some(browser_url.to_string()),
-            key: api_key.into(),
+            key: String::new(),
+            fallback_keys: Vec::new(),
             chain: Some(chain),
-        })
+        };
+        Some(config.with_keys(&api_key.into()))
+    }
+
+    /// Creates a new instance for the unified v2 API using the api key and chain
+    ///
+    /// Unlike [Self::create()] this also works for chains without a known etherscan API URL.
+    pub fn create_v2(api_key: impl Into<String>, chain: impl Into<Chain>) -> Self {
+        let chain = chain.into();
+        Self {
+            api_url: format!("{ETHERSCAN_V2_API_URL}?chainid={}", chain.id()),
+            browser_url: chain.etherscan_urls().map(|(_, url)| url.to_string()),
+            key: String::new(),
+            fallback_keys: Vec::new(),
+            chain: Some(chain),
+        }
+        .with_keys(&api_key.into())
+    }
+
+    /// Uses the comma separated `keys` first, followed by all keys that were already set
+    pub fn with_keys(self, keys: &str) -> Self {
+        let Self { api_url, browser_url, key, fallback_keys, chain } = self;
+        let mut all_keys = split_keys(keys).collect::<Vec<_>>();
+        all_keys.extend(std::iter::once(key).chain(fallback_keys));
+
+        let mut keys = Vec::with_capacity(all_keys.len());
+        for key in all_keys {
+            if !key.is_empty() && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        let key = if keys.is_empty() { String::new() } else { keys.remove(0) };
+
+        Self { api_url, browser_url, key, fallback_keys: keys, chain }
+    }
+
+    /// Adds the given keys as fallback keys, that are used if the current keys are rate limited
+    pub fn with_fallback_keys(mut self, keys: impl IntoIterator<Item = String>) -> Self {
+        for key in keys.into_iter().flat_map(|key| split_keys(&key).collect::<Vec<_>>()) {
+            if key != self.key && !self.fallback_keys.contains(&key) {
+                self.fallback_keys.push(key);
+            }
+        }
+        self
+    }
+
+    /// Returns all api keys, in the order they should be used
+    pub fn keys(&self) -> impl Iterator<Item = &str> + '_ {
+        std::iter::once(self.key.as_str()).chain(self.fallback_keys.iter().map(String::as_str))
+    }
+
+    /// Switches to the unified v2 API, if `version` is [EtherscanApiVersion::V2] and the config
+    /// uses the default API URL of its chain
+    pub fn with_api_version(mut self, version: EtherscanApiVersion) -> Self {
+        if version == EtherscanApiVersion::V2 {
+            if let Some(chain) = self.chain {
+                if chain.etherscan_urls().map_or(false, |(api_url, _)| api_url == self.api_url) {
+                    self.api_url = format!("{ETHERSCAN_V2_API_URL}?chainid={}", chain.id());
+                }
+            }
+        }
+        self
     }
 
     /// Sets the chain value and consumes the type
@@ -251,7 +383,7 @@ impl ResolvedEtherscanConfig {
     pub fn into_client(
         self,
     ) -> Result<ethers_etherscan::Client, ethers_etherscan::errors::EtherscanError> {
-        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain } = self;
+        let ResolvedEtherscanConfig { api_url, browser_url, key: api_key, chain, .. } = self;
         let (mainnet_api, mainnet_url) =
             ethers_core::types::Chain::Mainnet.etherscan_urls().expect("exist; qed");
 
@@ -285,6 +417,20 @@ impl ResolvedEtherscanConfig {
             .with_cache(cache, Duration::from_secs(24 * 60 * 60))
             .build()
     }
+
+    /// Returns an `ethers_etherscan::Client` for every api key, see [Self::into_client()] and
+    /// [with_key_rotation()]
+    pub fn into_clients(
+        self,
+    ) -> Result<Vec<ethers_etherscan::Client>, ethers_etherscan::errors::EtherscanError> {
+        let keys = self.keys().map(str::to_string).collect::<Vec<_>>();
+        keys.into_iter()
+            .map(|key| {
+                ResolvedEtherscanConfig { key, fallback_keys: Vec::new(), ..self.clone() }
+                    .into_client()
+            })
+            .collect()
+    }
 }
 
 /// Represents a single etherscan API key
@@ -383,6 +529,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: None,
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                keys: vec![],
             },
         );
 
@@ -400,6 +547,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Key("ABCDEFG".to_string()),
+                keys: vec![],
             },
         );
 
@@ -418,6 +566,7 @@ mod tests {
                 chain: Some(Mainnet.into()),
                 url: Some("https://api.etherscan.io/api".to_string()),
                 key: EtherscanApiKey::Env(format!("${{{env}}}")),
+                keys: vec![],
             },
         );
 
@@ -434,4 +583,36 @@ mod tests {
 
         std::env::remove_var(env);
     }
+
+    #[test]
+    fn can_resolve_multiple_keys() {
+        let config = EtherscanConfig {
+            chain: Some(Mainnet.into()),
+            url: None,
+            key: EtherscanApiKey::Key("A,B".to_string()),
+            keys: vec![
+                EtherscanApiKey::Key("C".to_string()),
+                EtherscanApiKey::Key("A".to_string()),
+            ],
+        };
+
+        let config = config.resolve(None).unwrap();
+        assert_eq!(config.keys().collect::<Vec<_>>(), vec!["A", "B", "C"]);
+        assert_eq!(config.clone().into_clients().unwrap().len(), 3);
+
+        let config = config.with_keys("D");
+        assert_eq!(config.keys().collect::<Vec<_>>(), vec!["D", "A", "B", "C"]);
+    }
+
+    #[test]
+    fn can_use_v2_api() {
+        let config = ResolvedEtherscanConfig::create("ABCDEFG", Mainnet)
+            .unwrap()
+            .with_api_version(EtherscanApiVersion::V2);
+        assert_eq!(config.api_url, "https://api.etherscan.io/v2/api?chainid=1");
+        let _ = config.into_client().unwrap();
+
+        let config = ResolvedEtherscanConfig::create_v2("ABCDEFG", 8453u64);
+        assert_eq!(config.api_url, "https://api.etherscan.io/v2/api?chainid=8453");
+    }
 }
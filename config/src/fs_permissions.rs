@@ -1,9 +1,10 @@
 //! Support for controlling fs access
 
+use globset::{GlobBuilder, GlobMatcher};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     str::FromStr,
 };
 
@@ -38,16 +39,25 @@ impl FsPermissions {
     }
 
     /// Returns the permission for the matching path
+    ///
+    /// Permissions with [FsAccessPermission::None] are deny rules: if any of them matches the path,
+    /// access is denied regardless of other matching permissions. Otherwise the first matching
+    /// permission is returned.
     pub fn find_permission(&self, path: &Path) -> Option<FsAccessPermission> {
-        self.permissions.iter().find(|perm| path.starts_with(&perm.path)).map(|perm| perm.access)
+        let mut matching = self.permissions.iter().filter(|perm| perm.matches(path));
+        let first = matching.next()?;
+        if first.access == FsAccessPermission::None ||
+            matching.any(|perm| perm.access == FsAccessPermission::None)
+        {
+            return Some(FsAccessPermission::None)
+        }
+        Some(first.access)
     }
 
     /// Updates all `allowed_paths` and joins ([`Path::join`]) the `root` with all entries
     pub fn join_all(&mut self, root: impl AsRef<Path>) {
         let root = root.as_ref();
-        self.permissions.iter_mut().for_each(|perm| {
-            perm.path = root.join(&perm.path);
-        })
+        self.permissions.iter_mut().for_each(|perm| perm.join(root))
     }
 
     /// Same as [`Self::join_all`] but consumes the type
@@ -74,20 +84,61 @@ impl FsPermissions {
 }
 
 /// Represents an access permission to a single path
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(from = "RawPathPermission")]
 pub struct PathPermission {
     /// Permission level to access the `path`
     pub access: FsAccessPermission,
     /// The targeted path guarded by the permission
     pub path: PathBuf,
+    /// Whether the configured path is a glob pattern, decided before it is joined with the root
+    #[serde(skip)]
+    is_glob: bool,
+    /// The compiled glob pattern, `None` if the path is not a glob or the pattern is invalid
+    #[serde(skip)]
+    matcher: Option<GlobMatcher>,
+}
+
+/// A [PathPermission] as it is configured
+#[derive(Deserialize)]
+struct RawPathPermission {
+    access: FsAccessPermission,
+    path: PathBuf,
+}
+
+impl From<RawPathPermission> for PathPermission {
+    fn from(perm: RawPathPermission) -> Self {
+        Self::new(perm.path, perm.access)
+    }
+}
+
+impl PartialEq for PathPermission {
+    fn eq(&self, other: &Self) -> bool {
+        self.access == other.access && self.path == other.path
+    }
 }
 
+impl Eq for PathPermission {}
+
 // === impl PathPermission ===
 
 impl PathPermission {
     /// Returns a new permission for the path and the given access
+    ///
+    /// The path is a glob pattern if it contains any of `*`, `?`, `[` or `{`.
     pub fn new(path: impl Into<PathBuf>, access: FsAccessPermission) -> Self {
-        Self { path: path.into(), access }
+        let path = path.into();
+        let is_glob = path.to_string_lossy().contains(['*', '?', '[', '{']);
+        let matcher = if is_glob { compile_glob(None, &path) } else { None };
+        Self { access, path, is_glob, matcher }
+    }
+
+    /// Joins the `root` with the path, the `root` is matched literally if the path is a glob
+    fn join(&mut self, root: &Path) {
+        if self.is_glob {
+            self.matcher = compile_glob(Some(root), &self.path);
+        }
+        self.path = root.join(&self.path);
     }
 
     /// Returns a new read-only permission for the path
@@ -105,6 +156,11 @@ impl PathPermission {
         Self::new(path, FsAccessPermission::Write)
     }
 
+    /// Returns a new permission for the path that only allows reading metadata
+    pub fn read_metadata(path: impl Into<PathBuf>) -> Self {
+        Self::new(path, FsAccessPermission::ReadMetadata)
+    }
+
     /// Returns a non permission for the path
     pub fn none(path: impl Into<PathBuf>) -> Self {
         Self::new(path, FsAccessPermission::None)
@@ -114,6 +170,52 @@ impl PathPermission {
     pub fn is_granted(&self, kind: FsAccessKind) -> bool {
         self.access.is_granted(kind)
     }
+
+    /// Returns true if the configured path of this permission is a glob pattern, like
+    /// `./data/**/*.json`
+    pub fn is_glob(&self) -> bool {
+        self.is_glob
+    }
+
+    /// Returns true if the permission applies to the given path
+    ///
+    /// A plain path applies to itself and everything inside it. A glob pattern applies to all
+    /// paths that match it and everything inside them, `*` does not match across directories.
+    pub fn matches(&self, path: &Path) -> bool {
+        if !self.is_glob {
+            return path.starts_with(&self.path)
+        }
+        match self.matcher {
+            Some(ref matcher) => path.ancestors().any(|ancestor| matcher.is_match(ancestor)),
+            None => false,
+        }
+    }
+}
+
+/// Compiles the glob pattern of a permission that is joined with the `root`, if any
+///
+/// The `root` is escaped, so that a root like `/home/proj[1]` is matched literally.
+fn compile_glob(root: Option<&Path>, pattern: &Path) -> Option<GlobMatcher> {
+    // strips `.` components, like the ones of `./data/*.json`
+    let strip = |path: &Path| {
+        path.components().filter(|c| *c != Component::CurDir).collect::<PathBuf>()
+    };
+    let pattern = strip(pattern);
+    let glob = match root.map(strip) {
+        // an absolute pattern is only inside the root if it was joined with the root already
+        Some(root) if pattern.is_relative() || pattern.starts_with(&root) => {
+            let relative = pattern.strip_prefix(&root).unwrap_or(&pattern);
+            PathBuf::from(globset::escape(&root.to_string_lossy())).join(relative)
+        }
+        _ => pattern,
+    };
+    match GlobBuilder::new(&glob.to_string_lossy()).literal_separator(true).build() {
+        Ok(glob) => Some(glob.compile_matcher()),
+        Err(err) => {
+            tracing::warn!(?err, ?glob, "invalid fs permission pattern");
+            None
+        }
+    }
 }
 
 /// Represents the operation on the fs
@@ -123,6 +225,8 @@ pub enum FsAccessKind {
     Read,
     /// write to fs (`vm.writeFile`)
     Write,
+    /// read the metadata of a path (`vm.fsMetadata`)
+    Metadata,
 }
 
 impl fmt::Display for FsAccessKind {
@@ -130,6 +234,7 @@ impl fmt::Display for FsAccessKind {
         match self {
             FsAccessKind::Read => f.write_str("read"),
             FsAccessKind::Write => f.write_str("write"),
+            FsAccessKind::Metadata => f.write_str("metadata"),
         }
     }
 }
@@ -146,6 +251,8 @@ pub enum FsAccessPermission {
     Read,
     /// Only writing is allowed
     Write,
+    /// Only reading the metadata is allowed, like whether a file exists
+    ReadMetadata,
 }
 
 // === impl FsAccessPermission ===
@@ -156,8 +263,9 @@ impl FsAccessPermission {
         match (self, kind) {
             (FsAccessPermission::ReadWrite, _) => true,
             (FsAccessPermission::None, _) => false,
-            (FsAccessPermission::Read, FsAccessKind::Read) => true,
+            (FsAccessPermission::Read, FsAccessKind::Read | FsAccessKind::Metadata) => true,
             (FsAccessPermission::Write, FsAccessKind::Write) => true,
+            (FsAccessPermission::ReadMetadata, FsAccessKind::Metadata) => true,
             _ => false,
         }
     }
@@ -172,6 +280,7 @@ impl FromStr for FsAccessPermission {
             "false" | "none" => Ok(FsAccessPermission::None),
             "read" => Ok(FsAccessPermission::Read),
             "write" => Ok(FsAccessPermission::Write),
+            "read-metadata" | "readmetadata" => Ok(FsAccessPermission::ReadMetadata),
             _ => Err(format!("Unknown variant {s}")),
         }
     }
@@ -184,6 +293,7 @@ impl fmt::Display for FsAccessPermission {
             FsAccessPermission::None => f.write_str("none"),
             FsAccessPermission::Read => f.write_str("read"),
             FsAccessPermission::Write => f.write_str("write"),
+            FsAccessPermission::ReadMetadata => f.write_str("read-metadata"),
         }
     }
 }
@@ -198,6 +308,7 @@ impl Serialize for FsAccessPermission {
             FsAccessPermission::None => serializer.serialize_bool(false),
             FsAccessPermission::Read => serializer.serialize_str("read"),
             FsAccessPermission::Write => serializer.serialize_str("write"),
+            FsAccessPermission::ReadMetadata => serializer.serialize_str("read-metadata"),
         }
     }
 }
@@ -237,5 +348,78 @@ mod tests {
         assert_eq!(FsAccessPermission::None, "none".parse().unwrap());
         assert_eq!(FsAccessPermission::Read, "read".parse().unwrap());
         assert_eq!(FsAccessPermission::Write, "write".parse().unwrap());
+        assert_eq!(FsAccessPermission::ReadMetadata, "read-metadata".parse().unwrap());
+    }
+
+    #[test]
+    fn can_match_globs() {
+        let permissions = FsPermissions::new([
+            PathPermission::read("/root/data/*.json"),
+            PathPermission::read_write("/root/./out/**/cache"),
+        ]);
+        assert!(permissions.is_path_allowed(Path::new("/root/data/a.json"), FsAccessKind::Read));
+        assert!(permissions.is_path_allowed(Path::new("/root/data/a.json"), FsAccessKind::Metadata));
+        assert!(!permissions.is_path_allowed(Path::new("/root/data/a.json"), FsAccessKind::Write));
+        assert!(!permissions.is_path_allowed(Path::new("/root/data/a.txt"), FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed(Path::new("/root/data/b/a.json"), FsAccessKind::Read));
+        assert!(
+            permissions.is_path_allowed(Path::new("/root/out/a/b/cache/x"), FsAccessKind::Write)
+        );
+        assert!(permissions.is_path_allowed(Path::new("/root/out/cache"), FsAccessKind::Write));
+    }
+
+    #[test]
+    fn can_match_globs_in_root_with_glob_characters() {
+        let permissions = FsPermissions::new([
+            PathPermission::read("./data/*.json"),
+            PathPermission::read_write("out"),
+        ])
+        .joined("/home/proj[1]");
+        assert!(!permissions.permissions[1].is_glob());
+        assert!(
+            permissions.is_path_allowed(Path::new("/home/proj[1]/data/a.json"), FsAccessKind::Read)
+        );
+        assert!(
+            !permissions.is_path_allowed(Path::new("/home/proj1/data/a.json"), FsAccessKind::Read)
+        );
+        assert!(permissions.is_path_allowed(Path::new("/home/proj[1]/out/a"), FsAccessKind::Write));
+
+        // joining again keeps the root literal
+        let permissions = permissions.joined("/home/proj[1]");
+        assert!(
+            permissions.is_path_allowed(Path::new("/home/proj[1]/data/a.json"), FsAccessKind::Read)
+        );
+    }
+
+    #[test]
+    fn can_deserialize_globs() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            fs_permissions: FsPermissions,
+        }
+        let wrapper: Wrapper =
+            toml::from_str(r#"fs_permissions = [{ access = "read", path = "./data/*.json" }]"#)
+                .unwrap();
+        let permissions = wrapper.fs_permissions.joined("/root");
+        assert!(permissions.permissions[0].is_glob());
+        assert!(permissions.is_path_allowed(Path::new("/root/data/a.json"), FsAccessKind::Read));
+    }
+
+    #[test]
+    fn deny_takes_precedence() {
+        let permissions = FsPermissions::new([
+            PathPermission::read_write("/root"),
+            PathPermission::read_metadata("/root/secrets/public"),
+            PathPermission::none("/root/secrets"),
+            PathPermission::none("/root/**/*.key"),
+        ]);
+        assert!(permissions.is_path_allowed(Path::new("/root/a.txt"), FsAccessKind::Write));
+        assert!(!permissions.is_path_allowed(Path::new("/root/secrets/a"), FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed(Path::new("/root/a/b.key"), FsAccessKind::Read));
+        assert!(!permissions.is_path_allowed(Path::new("/root/b.key"), FsAccessKind::Metadata));
+
+        let permissions = FsPermissions::new([PathPermission::read_metadata("/root")]);
+        assert!(permissions.is_path_allowed(Path::new("/root/a"), FsAccessKind::Metadata));
+        assert!(!permissions.is_path_allowed(Path::new("/root/a"), FsAccessKind::Read));
     }
 }
@@ -22,10 +22,21 @@ pub struct FuzzConfig {
     /// The weight of the dictionary
     #[serde(deserialize_with = "crate::deserialize_stringified_percent")]
     pub dictionary_weight: u32,
+    /// The relative weights of the sources dictionary values are drawn from, if unset values are
+    /// drawn uniformly from the whole dictionary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_weights: Option<DictionaryWeights>,
     /// The flag indicating whether to include values from storage
     pub include_storage: bool,
     /// The flag indicating whether to include push bytes values
     pub include_push_bytes: bool,
+    /// The flag indicating whether to include account balances, e.g. balances of accounts read
+    /// from a fork during `setUp`
+    pub include_balances: bool,
+    /// The weight of fuzz runs that draw parameters from the test contract's fixtures, e.g.
+    /// `fixtureAmount()` for a parameter named `amount`
+    #[serde(deserialize_with = "crate::deserialize_stringified_percent")]
+    pub fixture_weight: u32,
 }
 
 impl Default for FuzzConfig {
@@ -35,8 +46,44 @@ impl Default for FuzzConfig {
             max_test_rejects: 65536,
             seed: None,
             dictionary_weight: 40,
+            dictionary_weights: None,
             include_storage: true,
             include_push_bytes: true,
+            include_balances: true,
+            fixture_weight: 20,
+        }
+    }
+}
+
+/// The relative weights of the sources of the fuzz dictionary
+///
+/// A source that is omitted has a weight of 1, a source with a weight of 0 is never drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DictionaryWeights {
+    /// The addresses of the accounts that were touched
+    pub addresses: u32,
+    /// The PUSH bytes of deployed bytecode
+    pub push_bytes: u32,
+    /// The storage slots and values
+    pub storage: u32,
+    /// The account balances
+    pub balances: u32,
+    /// The topics and data of emitted logs
+    pub logs: u32,
+    /// The stack values collected during invariant runs
+    pub stack: u32,
+}
+
+impl Default for DictionaryWeights {
+    fn default() -> Self {
+        DictionaryWeights {
+            addresses: 1,
+            push_bytes: 1,
+            storage: 1,
+            balances: 1,
+            logs: 1,
+            stack: 1,
         }
     }
 }
@@ -0,0 +1,95 @@
+//! Support for custom gas schedules, see `[gas_overrides]`
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+
+/// The cost of an opcode or an intrinsic transaction cost in `gas_overrides`
+///
+/// ```toml
+/// [profile.default.gas_overrides]
+/// SSTORE = 5000
+/// SELFDESTRUCT = "disabled"
+/// TX_BASE = 25000
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasOverride {
+    /// The gas the opcode or intrinsic cost is charged instead
+    Gas(u64),
+    /// Executing the opcode halts the execution like an invalid opcode
+    Disabled,
+}
+
+impl fmt::Display for GasOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GasOverride::Gas(gas) => gas.fmt(f),
+            GasOverride::Disabled => f.write_str("disabled"),
+        }
+    }
+}
+
+impl FromStr for GasOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("disabled") {
+            return Ok(GasOverride::Disabled)
+        }
+        s.parse()
+            .map(GasOverride::Gas)
+            .map_err(|_| format!("invalid gas override `{s}`, expected a number or `disabled`"))
+    }
+}
+
+impl Serialize for GasOverride {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            GasOverride::Gas(gas) => serializer.serialize_u64(*gas),
+            GasOverride::Disabled => serializer.serialize_str("disabled"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GasOverride {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Val {
+            Number(u64),
+            String(String),
+        }
+
+        match Val::deserialize(deserializer)? {
+            Val::Number(gas) => Ok(GasOverride::Gas(gas)),
+            Val::String(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn can_parse_gas_overrides() {
+        let overrides: BTreeMap<String, GasOverride> = toml::from_str(
+            r#"
+            SSTORE = 5000
+            SELFDESTRUCT = "disabled"
+            TX_BASE = "25000"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(overrides["SSTORE"], GasOverride::Gas(5000));
+        assert_eq!(overrides["SELFDESTRUCT"], GasOverride::Disabled);
+        assert_eq!(overrides["TX_BASE"], GasOverride::Gas(25000));
+        assert!("cheap".parse::<GasOverride>().is_err());
+    }
+}
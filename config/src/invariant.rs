@@ -1,5 +1,6 @@
 //! Configuration for invariant testing
 
+use crate::DictionaryWeights;
 use serde::{Deserialize, Serialize};
 
 /// Contains for invariant testing
@@ -17,10 +18,17 @@ pub struct InvariantConfig {
     /// The weight of the dictionary
     #[serde(deserialize_with = "crate::deserialize_stringified_percent")]
     pub dictionary_weight: u32,
+    /// The relative weights of the sources dictionary values are drawn from, if unset values are
+    /// drawn uniformly from the whole dictionary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_weights: Option<DictionaryWeights>,
     /// The flag indicating whether to include values from storage
     pub include_storage: bool,
     /// The flag indicating whether to include push bytes values
     pub include_push_bytes: bool,
+    /// The flag indicating whether to include account balances, e.g. balances of accounts read
+    /// from a fork during `setUp`
+    pub include_balances: bool,
 }
 
 impl Default for InvariantConfig {
@@ -31,8 +39,10 @@ impl Default for InvariantConfig {
             fail_on_revert: false,
             call_override: false,
             dictionary_weight: 80,
+            dictionary_weights: None,
             include_storage: true,
             include_push_bytes: true,
+            include_balances: true,
         }
     }
 }
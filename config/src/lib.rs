@@ -28,7 +28,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -61,12 +61,27 @@ pub use fmt::FormatterConfig;
 pub mod fs_permissions;
 pub use crate::fs_permissions::FsPermissions;
 
+pub mod gas_overrides;
+pub use gas_overrides::GasOverride;
+
 pub mod error;
 pub use error::SolidityErrorCode;
 
+pub mod compiler_warnings;
+pub use compiler_warnings::{PathWarningRule, WarningLevel, WarningsConfig, WarningsMatcher};
+
+pub mod size_limits;
+pub use size_limits::{SizeLimit, SizeLimitsConfig};
+
+pub mod auto_advance;
+pub use auto_advance::AutoAdvanceTime;
+
 pub mod doc;
 pub use doc::DocConfig;
 
+pub mod provenance;
+pub use provenance::{ResolvedValue, ValueSource};
+
 mod warning;
 pub use warning::*;
 
@@ -82,14 +97,20 @@ use crate::{
     error::ExtractConfigError,
     etherscan::{EtherscanConfigError, EtherscanConfigs, ResolvedEtherscanConfig},
 };
-use providers::*;
+pub use etherscan::{with_key_rotation, EtherscanApiVersion};
+use providers::{inheritance::ProfileInheritanceProvider, *};
 
 mod fuzz;
-pub use fuzz::FuzzConfig;
+pub use fuzz::{DictionaryWeights, FuzzConfig};
 
 mod invariant;
 use crate::fs_permissions::PathPermission;
 pub use invariant::InvariantConfig;
+
+mod shared_artifacts;
+use shared_artifacts::SharedArtifacts;
+pub use shared_artifacts::SharedArtifactsLock;
+pub use providers::remappings::{RemappingResolution, RemappingSource};
 use providers::remappings::RemappingsProvider;
 
 /// Foundry configuration
@@ -153,12 +174,22 @@ pub struct Config {
     pub cache: bool,
     /// where the cache is stored if enabled
     pub cache_path: PathBuf,
+    /// whether compiler outputs are also kept in `~/.foundry/artifacts`, keyed by the solc
+    /// settings they were compiled with
+    ///
+    /// Switching between profiles or branches then only recompiles the files that changed since
+    /// the last build with the same settings. Entries are never shared between projects.
+    pub shared_artifacts: bool,
     /// where the broadcast logs are stored
     pub broadcast: PathBuf,
     /// additional solc allow paths for `--allow-paths`
     pub allow_paths: Vec<PathBuf>,
     /// additional solc include paths for `--include-path`
     pub include_paths: Vec<PathBuf>,
+    /// additional directories with artifacts that `getCode`, `getDeployedCode` and `deployCode`
+    /// look up artifacts in, for example the output of a project compiled with different settings
+    #[serde(default)]
+    pub artifact_paths: Vec<PathBuf>,
     /// whether to force a `project.clean()`
     pub force: bool,
     /// evm version to use
@@ -184,11 +215,21 @@ pub struct Config {
     ///    - if `auto_detect_solc = true` and `offline = true`, the required solc version(s) will
     ///      be auto detected but if the solc version is not installed, it will _not_ try to
     ///      install it
+    ///
+    /// Forks are only served from the RPC cache in offline mode, values that are not cached fail
+    /// with an error instead of being fetched.
     pub offline: bool,
     /// Whether to activate optimizer
     pub optimizer: bool,
     /// Sets the optimizer runs
     pub optimizer_runs: usize,
+    /// Optimizer runs of individual contracts, by `<path>:<contract>`, or by `<path>` for all
+    /// contracts of a source file
+    ///
+    /// These contracts are compiled in separate solc jobs by `forge build` and their artifacts
+    /// replace the ones compiled with `optimizer_runs`.
+    #[serde(default)]
+    pub optimizer_overrides: BTreeMap<String, usize>,
     /// Switch optimizer components on or off in detail.
     /// The "enabled" switch above provides two defaults which can be
     /// tweaked here. If "details" is given, "enabled" can be omitted.
@@ -204,10 +245,15 @@ pub struct Config {
     /// Multiple etherscan api configs and their aliases
     #[serde(default, skip_serializing_if = "EtherscanConfigs::is_empty")]
     pub etherscan: EtherscanConfigs,
+    /// The version of the etherscan API, `v2` uses the unified multichain API for all chains
+    pub etherscan_api_version: EtherscanApiVersion,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
     /// When true, compiler warnings are treated as errors
     pub deny_warnings: bool,
+    /// How compiler warnings are handled per error code and source path, see [WarningsConfig]
+    #[serde(default)]
+    pub warnings: WarningsConfig,
     /// Only run test functions matching the specified regex pattern.
     #[serde(rename = "match_test")]
     pub test_pattern: Option<RegexWrapper>,
@@ -232,10 +278,25 @@ pub struct Config {
     pub invariant: InvariantConfig,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
+    /// Whether to allow the cheatcodes that read the memory and return data of the calling frame
+    pub memory_access: bool,
+    /// Whether every call a test makes is executed as a separate transaction
+    pub isolate: bool,
+    /// Abort every test execution that takes longer than the given number of seconds
+    pub test_timeout: Option<u64>,
+    /// Advance the block timestamp by the given time, and the block number by one, with every
+    /// top-level call of a test
+    pub auto_advance_time: Option<AutoAdvanceTime>,
+    /// Whether to fail tests whose mutations of the test backend escape the revert of the test
+    pub strict_isolation: bool,
     /// The address which will be executing all tests
     pub sender: Address,
     /// The tx.origin value during EVM execution
     pub tx_origin: Address,
+    /// The CREATE2 factory that deploys contracts created with a salt in scripts
+    pub create2_deployer: Address,
+    /// Per chain overrides of `create2_deployer`, keyed by chain name or id
+    pub create2_deployers: BTreeMap<String, Address>,
     /// the initial balance of each deployed test contract
     pub initial_balance: U256,
     /// the block.number value during EVM execution
@@ -267,6 +328,16 @@ pub struct Config {
     pub block_gas_limit: Option<GasLimit>,
     /// The memory limit of the EVM (32 MB by default)
     pub memory_limit: u64,
+    /// Custom gas costs of opcodes, by opcode name, and of the intrinsic costs of transactions
+    /// (`TX_BASE`, `TX_CREATE`, `TX_DATA_ZERO` and `TX_DATA_NON_ZERO`)
+    ///
+    /// This is useful to simulate chains with a modified gas schedule.
+    #[serde(default)]
+    pub gas_overrides: BTreeMap<String, GasOverride>,
+    /// The names of the inspector plugins to add to the EVM, which need to be registered by the
+    /// binary that runs forge
+    #[serde(default)]
+    pub inspector_plugins: Vec<String>,
     /// Additional output selection for all contracts
     /// such as "ir", "devdoc", "storageLayout", etc.
     /// See [Solc Compiler Api](https://docs.soliditylang.org/en/latest/using-the-compiler.html#compiler-api)
@@ -297,6 +368,9 @@ pub struct Config {
     pub names: bool,
     /// Print the sizes of the compiled contracts
     pub sizes: bool,
+    /// Byte budgets of the compiled contracts, see [SizeLimitsConfig]
+    #[serde(default)]
+    pub size_limits: SizeLimitsConfig,
     /// If set to true, changes compilation pipeline to go through the Yul intermediate
     /// representation.
     pub via_ir: bool,
@@ -305,6 +379,13 @@ pub struct Config {
     /// Disables storage caching entirely. This overrides any settings made in
     /// `rpc_storage_caching`
     pub no_storage_caching: bool,
+    /// Whether forks fetch the info of an account along with the first storage slot requested
+    /// for it
+    pub fork_prefetch_accounts: bool,
+    /// URL of a cache for RPC storage that is shared between machines, for example by all CI jobs
+    /// of a team. A `http(s)://` endpoint that supports `GET` and `PUT`, forks read missing caches
+    /// from it and upload the caches they created to it.
+    pub rpc_cache_url: Option<String>,
     /// Disables rate limiting entirely. This overrides any settings made in
     /// `compute_units_per_second`
     pub no_rpc_rate_limit: bool,
@@ -321,6 +402,13 @@ pub struct Config {
     /// If this is `false` and the `bytecode_hash` option above is not `None` solc will issue a
     /// warning.
     pub cbor_metadata: bool,
+    /// Whether to compile for reproducible builds.
+    ///
+    /// Implies `bytecode_hash = "none"` and `cbor_metadata = false`, so neither the metadata hash
+    /// nor the solc version are appended to the bytecode. The metadata embeds the source contents
+    /// instead of their urls and the source paths in the metadata of the artifacts are relative
+    /// to the project root.
+    pub deterministic_metadata: bool,
     /// How to treat revert (and require) reason strings.
     #[serde(with = "serde_helpers::display_from_str_opt")]
     pub revert_strings: Option<RevertStrings>,
@@ -345,6 +433,10 @@ pub struct Config {
     ///
     /// This includes what operations can be executed (read, write)
     pub fs_permissions: FsPermissions,
+    /// Environment variables visible to the `env*` cheatcodes and `ffi` commands, without being
+    /// exported to the shell. Variables set in the environment take precedence.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -389,12 +481,23 @@ impl Config {
     pub const PROFILE_SECTION: &'static str = "profile";
 
     /// Standalone sections in the config which get integrated into the selected profile
-    pub const STANDALONE_SECTIONS: &'static [&'static str] =
-        &["rpc_endpoints", "etherscan", "fmt", "doc", "fuzz", "invariant"];
+    pub const STANDALONE_SECTIONS: &'static [&'static str] = &[
+        "rpc_endpoints",
+        "etherscan",
+        "fmt",
+        "doc",
+        "fuzz",
+        "invariant",
+        "warnings",
+        "size_limits",
+    ];
 
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
 
+    /// The name of the file that pins the remappings of the project, see `forge remappings --lock`
+    pub const REMAPPINGS_LOCK_FILE: &'static str = "remappings.lock";
+
     /// The name of the directory foundry reserves for itself under the user's home directory: `~`
     pub const FOUNDRY_DIR_NAME: &'static str = ".foundry";
 
@@ -406,6 +509,14 @@ impl Config {
         0x07, 0x30, 0x9D, 0x1F, 0x38,
     ]);
 
+    /// Address of the canonical deterministic deployment proxy, which is used as CREATE2 factory
+    ///
+    /// `0x4e59b44847b379578588920ca78fbf26c0b4956c`
+    pub const DEFAULT_CREATE2_DEPLOYER: H160 = H160([
+        0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88, 0x92, 0x0c, 0xa7, 0x8f, 0xbf,
+        0x26, 0xc0, 0xb4, 0x95, 0x6c,
+    ]);
+
     /// Returns the current `Config`
     ///
     /// See `Config::figment`
@@ -525,6 +636,9 @@ impl Config {
 
         self.include_paths = self.include_paths.into_iter().map(|allow| p(&root, &allow)).collect();
 
+        self.artifact_paths =
+            self.artifact_paths.into_iter().map(|artifacts| p(&root, &artifacts)).collect();
+
         self.fs_permissions.join_all(&root);
 
         if let Some(ref mut model_checker) = self.model_checker {
@@ -597,6 +711,7 @@ impl Config {
     /// let project = config.project();
     /// ```
     pub fn project(&self) -> Result<Project, SolcError> {
+        self.verify_remappings_lock()?;
         self.create_project(true, false)
     }
 
@@ -615,7 +730,13 @@ impl Config {
             .allowed_paths(&self.allow_paths)
             .include_paths(&self.include_paths)
             .solc_config(SolcConfig::builder().settings(self.solc_settings()?).build())
-            .ignore_error_codes(self.ignored_error_codes.iter().copied().map(Into::into))
+            .ignore_error_codes(
+                self.ignored_error_codes
+                    .iter()
+                    .chain(&self.warnings.ignore)
+                    .copied()
+                    .map(Into::into),
+            )
             .set_compiler_severity_filter(if self.deny_warnings {
                 Severity::Warning
             } else {
@@ -639,6 +760,31 @@ impl Config {
         Ok(project)
     }
 
+    /// Swaps the artifacts and cache of the project for the ones compiled with its current
+    /// settings, if `shared_artifacts` is enabled, see [Config::shared_artifacts]
+    ///
+    /// This must be called right before the project is compiled. The returned lock must be held
+    /// until the compilation finished, so concurrent runs of the same project don't swap the
+    /// artifacts while they are compiled.
+    pub fn restore_shared_artifacts(
+        &self,
+        project: &Project,
+    ) -> Result<Option<SharedArtifactsLock>, SolcError> {
+        if !project.cached || !self.cache || !self.shared_artifacts {
+            return Ok(None)
+        }
+        let dir = match Self::foundry_artifacts_dir() {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+        let lock = SharedArtifacts::lock(project)?;
+        let solc = format!("{:?}", self.solc);
+        if let Err(err) = SharedArtifacts::new(dir).restore(project, &solc) {
+            warn!(?err, "failed to restore shared artifacts");
+        }
+        Ok(Some(lock))
+    }
+
     /// Ensures that the configured version is installed if explicitly set
     ///
     /// If `solc` is [`SolcReq::Version`] then this will download and install the solc version if
@@ -696,6 +842,23 @@ impl Config {
             self.rpc_storage_caching.enable_for_endpoint(endpoint)
     }
 
+    /// Returns the CREATE2 factory to use on the given chain.
+    ///
+    /// This is the matching entry of `create2_deployers`, if any, or `create2_deployer`.
+    pub fn create2_deployer_for(&self, chain: impl Into<Chain>) -> Address {
+        let chain = chain.into();
+        self.create2_deployers
+            .iter()
+            .find(|(key, _)| key.parse::<Chain>().map(|c| c.id() == chain.id()).unwrap_or_default())
+            .map(|(_, deployer)| *deployer)
+            .unwrap_or(self.create2_deployer)
+    }
+
+    /// Returns true if the address is configured as CREATE2 factory for any chain
+    pub fn is_create2_deployer(&self, address: &Address) -> bool {
+        self.create2_deployer == *address || self.create2_deployers.values().any(|d| d == address)
+    }
+
     /// Returns the `ProjectPathsConfig`  sub set of the config.
     ///
     /// **NOTE**: this uses the paths as they are and does __not__ modify them, see
@@ -725,6 +888,68 @@ impl Config {
         builder.build_with_root(&self.__root.0)
     }
 
+    /// Returns how the remappings of the project were resolved, including the remappings that are
+    /// shadowed by another remapping of the same name
+    pub fn remapping_resolutions(&self) -> Result<Vec<RemappingResolution>, figment::Error> {
+        let figment = self.figment_without_remappings();
+        self.remappings_provider(&figment).resolutions()
+    }
+
+    /// Returns the lines of the `remappings.lock` file for the current remappings
+    fn remappings_lock_lines(&self) -> Vec<String> {
+        let mut lines = self
+            .remappings
+            .iter()
+            .map(|r| RelativeRemapping::new(r.clone().into(), &self.__root.0).to_string())
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// Writes the current remappings to the `remappings.lock` file in the root and returns its path
+    pub fn write_remappings_lock(&self) -> std::io::Result<PathBuf> {
+        let path = self.__root.0.join(Config::REMAPPINGS_LOCK_FILE);
+        let mut content = self.remappings_lock_lines().join("\n");
+        content.push('\n');
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Ensures the current remappings match the `remappings.lock` file, if it exists
+    pub fn verify_remappings_lock(&self) -> Result<(), SolcError> {
+        let path = self.__root.0.join(Config::REMAPPINGS_LOCK_FILE);
+        if !path.is_file() {
+            return Ok(())
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|err| SolcError::msg(format!("failed to read {}: {err}", path.display())))?;
+        let locked = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect::<BTreeSet<_>>();
+        let current = self.remappings_lock_lines();
+        let current = current.iter().map(String::as_str).collect::<BTreeSet<_>>();
+        if locked == current {
+            return Ok(())
+        }
+
+        let mut diff = String::new();
+        for line in locked.difference(&current) {
+            diff.push_str(&format!("\n  - {line}"));
+        }
+        for line in current.difference(&locked) {
+            diff.push_str(&format!("\n  + {line}"));
+        }
+        Err(SolcError::msg(format!(
+            "remappings don't match {}:{diff}\n\
+             Run `forge remappings --report` to inspect the remappings and \
+             `forge remappings --lock` to update the lock file.",
+            path.display()
+        )))
+    }
+
     /// Returns all configured [`Remappings`]
     ///
     /// **Note:** this will add an additional `<src>/=<src path>` remapping here, see
@@ -796,6 +1021,17 @@ impl Config {
         Some(endpoints.remove(maybe_alias)?.map(Cow::Owned))
     }
 
+    /// Returns the block to fork from
+    ///
+    /// Returns:
+    ///    - the `fork_block_number` if set
+    ///    - the block the endpoint is pinned to in `rpc_endpoints` if `eth_rpc_url` is an alias
+    ///    - None otherwise
+    pub fn get_fork_block_number(&self) -> Option<u64> {
+        self.fork_block_number
+            .or_else(|| self.rpc_endpoints.pinned_block(self.eth_rpc_url.as_deref()?))
+    }
+
     /// Returns the configured rpc, or the fallback url
     ///
     /// # Example
@@ -860,14 +1096,29 @@ impl Config {
         if self.etherscan.contains_key(maybe_alias) {
             // etherscan points to an alias in the `etherscan` table, so we try to resolve that
             let mut resolved = self.etherscan.clone().resolved();
-            return resolved.remove(maybe_alias)
+            return resolved
+                .remove(maybe_alias)
+                .map(|config| config.map(|c| c.with_api_version(self.etherscan_api_version)))
         }
 
         // we treat the `etherscan_api_key` as actual API key
         // if no chain provided, we assume mainnet
         let chain = self.chain_id.unwrap_or(Chain::Named(Mainnet));
         let api_key = self.etherscan_api_key.as_ref()?;
-        ResolvedEtherscanConfig::create(api_key, chain).map(Ok)
+        self.create_etherscan_config(api_key, chain).map(Ok)
+    }
+
+    /// Creates the `ResolvedEtherscanConfig` for the `api_key` and `chain`, using the configured
+    /// etherscan API version
+    fn create_etherscan_config(
+        &self,
+        api_key: &str,
+        chain: Chain,
+    ) -> Option<ResolvedEtherscanConfig> {
+        match self.etherscan_api_version {
+            EtherscanApiVersion::V1 => ResolvedEtherscanConfig::create(api_key, chain),
+            EtherscanApiVersion::V2 => Some(ResolvedEtherscanConfig::create_v2(api_key, chain)),
+        }
     }
 
     /// Same as [`Self::get_etherscan_config()`] but optionally updates the config with the given
@@ -883,7 +1134,13 @@ impl Config {
         let chain = chain.map(Into::into);
         if let Some(maybe_alias) = self.etherscan_api_key.as_ref().or(self.eth_rpc_url.as_ref()) {
             if self.etherscan.contains_key(maybe_alias) {
-                return self.etherscan.clone().resolved().remove(maybe_alias).transpose()
+                return self
+                    .etherscan
+                    .clone()
+                    .resolved()
+                    .remove(maybe_alias)
+                    .transpose()
+                    .map(|config| config.map(|c| c.with_api_version(self.etherscan_api_version)))
             }
         }
 
@@ -891,12 +1148,13 @@ impl Config {
         if let Some(res) =
             chain.and_then(|chain| self.etherscan.clone().resolved().find_chain(chain))
         {
+            let res = res.map(|c| c.with_api_version(self.etherscan_api_version));
             match (res, self.etherscan_api_key.as_ref()) {
-                (Ok(mut config), Some(key)) => {
-                    // we update the key, because if an etherscan_api_key is set, it should take
-                    // precedence over the entry, since this is usually set via env var or CLI args.
-                    config.key = key.clone();
-                    return Ok(Some(config))
+                (Ok(config), Some(key)) => {
+                    // the etherscan_api_key should take precedence over the entry, since this is
+                    // usually set via env var or CLI args, the keys of the entry are used if it is
+                    // rate limited
+                    return Ok(Some(config.with_keys(key)))
                 }
                 (Ok(config), None) => return Ok(Some(config)),
                 (Err(err), None) => return Err(err),
@@ -909,12 +1167,30 @@ impl Config {
         // etherscan fallback via API key
         if let Some(key) = self.etherscan_api_key.as_ref() {
             let chain = chain.or(self.chain_id).unwrap_or_default();
-            return Ok(ResolvedEtherscanConfig::create(key, chain))
+            return Ok(self.create_etherscan_config(key, chain))
         }
 
         Ok(None)
     }
 
+    /// Returns an etherscan client for every API key of the `chain`, requests should be sent via
+    /// [with_key_rotation()]
+    ///
+    /// Returns a single client without API key if no key is configured.
+    pub fn get_etherscan_clients(
+        &self,
+        chain: impl Into<Chain>,
+    ) -> eyre::Result<Vec<ethers_etherscan::Client>> {
+        let chain = chain.into();
+        let config = match self.get_etherscan_config_with_chain(Some(chain))? {
+            Some(config) => config,
+            None => self
+                .create_etherscan_config("", chain)
+                .wrap_err_with(|| format!("Chain {chain} is not supported by Etherscan"))?,
+        };
+        Ok(config.into_clients()?)
+    }
+
     /// Helper function to just get the API key
     pub fn get_etherscan_api_key(&self, chain: Option<impl Into<Chain>>) -> Option<String> {
         self.get_etherscan_config_with_chain(chain).ok().flatten().map(|c| c.key)
@@ -978,6 +1254,16 @@ impl Config {
         Libraries::parse(&self.libraries)
     }
 
+    /// Returns the `SettingsMetadata` based on the configured settings
+    pub fn settings_metadata(&self) -> SettingsMetadata {
+        if self.deterministic_metadata {
+            let mut metadata = SettingsMetadata::new(BytecodeHash::None, false);
+            metadata.use_literal_content = Some(true);
+            return metadata
+        }
+        SettingsMetadata::new(self.bytecode_hash, self.cbor_metadata)
+    }
+
     /// Returns the configured `solc` `Settings` that includes:
     ///   - all libraries
     ///   - the optimizer (including details, if configured)
@@ -1000,7 +1286,7 @@ impl Config {
             optimizer,
             evm_version: Some(self.evm_version),
             libraries,
-            metadata: Some(SettingsMetadata::new(self.bytecode_hash, self.cbor_metadata)),
+            metadata: Some(self.settings_metadata()),
             debug: self.revert_strings.map(|revert_strings| DebuggingSettings {
                 revert_strings: Some(revert_strings),
                 debug_info: Vec::new(),
@@ -1179,6 +1465,26 @@ impl Config {
         })
     }
 
+    /// Sets the `block` of the given pinned `rpc_endpoints` inside a `foundry.toml` file but only
+    /// if it exists
+    ///
+    /// # Errors
+    ///
+    /// An error if the `foundry.toml` could not be parsed.
+    pub fn update_pinned_blocks(&self, blocks: &BTreeMap<String, u64>) -> eyre::Result<()> {
+        self.update(|doc| {
+            let mut updated = false;
+            for (alias, block) in blocks {
+                let endpoint = &mut doc["rpc_endpoints"][alias.as_str()];
+                if endpoint.is_table_like() {
+                    endpoint["block"] = toml_edit::value(*block as i64);
+                    updated = true;
+                }
+            }
+            updated
+        })
+    }
+
     /// Serialize the config type as a String of TOML.
     ///
     /// This serializes to a table with the name of the profile
@@ -1230,6 +1536,14 @@ impl Config {
         Profile::from_env_or("FOUNDRY_PROFILE", Config::DEFAULT_PROFILE)
     }
 
+    /// Returns the profiles of the local `foundry.toml` that are activated by their `activate`
+    /// conditions, in addition to the selected profile
+    pub fn activated_profiles(&self) -> Result<Vec<String>, figment::Error> {
+        let toml =
+            TomlFileProvider::new(Some("FOUNDRY_CONFIG"), self.__root.0.join(Config::FILE_NAME));
+        ProfileInheritanceProvider::new(toml, self.profile.clone()).activated_profiles()
+    }
+
     /// Returns the path to foundry's global toml file that's stored at `~/.foundry/foundry.toml`
     pub fn foundry_dir_toml() -> Option<PathBuf> {
         Self::foundry_dir().map(|p| p.join(Config::FILE_NAME))
@@ -1245,6 +1559,11 @@ impl Config {
         Self::foundry_dir().map(|p| p.join("cache"))
     }
 
+    /// Returns the path to foundry's shared artifacts dir `~/.foundry/artifacts`
+    pub fn foundry_artifacts_dir() -> Option<PathBuf> {
+        Self::foundry_dir().map(|p| p.join("artifacts"))
+    }
+
     /// Returns the path to foundry rpc cache dir `~/.foundry/cache/rpc`
     pub fn foundry_rpc_cache_dir() -> Option<PathBuf> {
         Some(Self::foundry_cache_dir()?.join("rpc"))
@@ -1466,6 +1785,9 @@ impl Config {
     ) -> Figment {
         figment = figment.select(profile.clone());
 
+        // resolve `inherits` and merge the activated profiles into the selected profile
+        let toml_provider = ProfileInheritanceProvider::new(toml_provider, profile.clone());
+
         // add warnings
         figment = {
             let warnings = WarningsProvider::for_figment(&toml_provider, &figment);
@@ -1507,7 +1829,22 @@ impl Config {
 impl From<Config> for Figment {
     fn from(c: Config) -> Figment {
         let profile = Config::selected_profile();
-        let mut figment = Figment::default().merge(DappHardhatDirProvider(&c.__root.0));
+        let figment = c.figment_without_remappings();
+
+        // we try to merge remappings after we've merged all other providers, this prevents
+        // redundant fs lookups to determine the default remappings that are eventually updated by
+        // other providers, like the toml file
+        let merge = figment.merge(c.remappings_provider(&figment));
+
+        Figment::from(c).merge(merge).select(profile)
+    }
+}
+
+impl Config {
+    /// Returns the figment of all providers of the config, except for the [RemappingsProvider]
+    fn figment_without_remappings(&self) -> Figment {
+        let profile = Config::selected_profile();
+        let mut figment = Figment::default().merge(DappHardhatDirProvider(&self.__root.0));
 
         // merge global foundry.toml file
         if let Some(global_toml) = Config::foundry_dir_toml().filter(|p| p.exists()) {
@@ -1520,7 +1857,7 @@ impl From<Config> for Figment {
         // merge local foundry.toml file
         figment = Config::merge_toml_provider(
             figment,
-            TomlFileProvider::new(Some("FOUNDRY_CONFIG"), c.__root.0.join(Config::FILE_NAME))
+            TomlFileProvider::new(Some("FOUNDRY_CONFIG"), self.__root.0.join(Config::FILE_NAME))
                 .cached(),
             profile.clone(),
         );
@@ -1554,25 +1891,23 @@ impl From<Config> for Figment {
                     })
                     .global(),
             )
-            .select(profile.clone());
+            .select(profile);
+        figment
+    }
 
-        // we try to merge remappings after we've merged all other providers, this prevents
-        // redundant fs lookups to determine the default remappings that are eventually updated by
-        // other providers, like the toml file
-        let remappings = RemappingsProvider {
+    /// Returns the [RemappingsProvider] for the figment of all other providers
+    fn remappings_provider<'a>(&'a self, figment: &Figment) -> RemappingsProvider<'a> {
+        RemappingsProvider {
             auto_detect_remappings: figment
                 .extract_inner::<bool>("auto_detect_remappings")
                 .unwrap_or(true),
             lib_paths: figment
                 .extract_inner::<Vec<PathBuf>>("libs")
                 .map(Cow::Owned)
-                .unwrap_or_else(|_| Cow::Borrowed(&c.libs)),
-            root: &c.__root.0,
+                .unwrap_or_else(|_| Cow::Borrowed(&self.libs)),
+            root: &self.__root.0,
             remappings: figment.extract_inner::<Vec<Remapping>>("remappings"),
-        };
-        let merge = figment.merge(remappings);
-
-        Figment::from(c).merge(merge).select(profile)
+        }
     }
 }
 
@@ -1708,6 +2043,7 @@ impl Default for Config {
         Self {
             profile: Self::DEFAULT_PROFILE,
             fs_permissions: FsPermissions::new([PathPermission::read("out")]),
+            env: Default::default(),
             __root: Default::default(),
             src: "src".into(),
             test: "test".into(),
@@ -1716,9 +2052,11 @@ impl Default for Config {
             libs: vec!["lib".into()],
             cache: true,
             cache_path: "cache".into(),
+            shared_artifacts: false,
             broadcast: "broadcast".into(),
             allow_paths: vec![],
             include_paths: vec![],
+            artifact_paths: vec![],
             force: false,
             evm_version: Default::default(),
             gas_reports: vec!["*".to_string()],
@@ -1728,12 +2066,14 @@ impl Default for Config {
             offline: false,
             optimizer: true,
             optimizer_runs: 200,
+            optimizer_overrides: Default::default(),
             optimizer_details: None,
             model_checker: None,
             extra_output: Default::default(),
             extra_output_files: Default::default(),
             names: false,
             sizes: false,
+            size_limits: Default::default(),
             test_pattern: None,
             test_pattern_inverse: None,
             contract_pattern: None,
@@ -1743,8 +2083,15 @@ impl Default for Config {
             fuzz: Default::default(),
             invariant: Default::default(),
             ffi: false,
+            memory_access: false,
+            isolate: false,
+            test_timeout: None,
+            auto_advance_time: None,
+            strict_isolation: false,
             sender: Config::DEFAULT_SENDER,
             tx_origin: Config::DEFAULT_SENDER,
+            create2_deployer: Config::DEFAULT_CREATE2_DEPLOYER,
+            create2_deployers: Default::default(),
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
             block_number: 1,
             fork_block_number: None,
@@ -1759,6 +2106,8 @@ impl Default for Config {
             block_prevrandao: Default::default(),
             block_gas_limit: None,
             memory_limit: 2u64.pow(25),
+            gas_overrides: Default::default(),
+            inspector_plugins: vec![],
             eth_rpc_url: None,
             etherscan_api_key: None,
             verbosity: 0,
@@ -1770,14 +2119,19 @@ impl Default for Config {
                 SolidityErrorCode::ContractExceeds24576Bytes,
             ],
             deny_warnings: false,
+            warnings: Default::default(),
             via_ir: false,
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
             etherscan: Default::default(),
+            etherscan_api_version: Default::default(),
             no_storage_caching: false,
+            fork_prefetch_accounts: false,
+            rpc_cache_url: None,
             no_rpc_rate_limit: false,
             bytecode_hash: BytecodeHash::Ipfs,
             cbor_metadata: true,
+            deterministic_metadata: false,
             revert_strings: None,
             sparse_mode: false,
             build_info: false,
@@ -2487,6 +2841,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn default_create2_deployer() {
+        assert_eq!(
+            Config::DEFAULT_CREATE2_DEPLOYER,
+            "0x4e59b44847b379578588920ca78fbf26c0b4956c".parse().unwrap()
+        );
+    }
+
     #[test]
     fn test_caching() {
         let mut config = Config::default();
@@ -2723,6 +3085,73 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_remapping_resolutions() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                auto_detect_remappings = false
+                remappings = ["other/=lib/toml-other/", "toml/=lib/toml/"]
+            "#,
+            )?;
+            jail.create_file(
+                "remappings.txt",
+                r#"
+                other/=lib/other/
+            "#,
+            )?;
+            jail.set_env("DAPP_REMAPPINGS", "other/=lib/other/");
+
+            let resolutions = Config::load().remapping_resolutions()?;
+            assert_eq!(resolutions.len(), 2);
+
+            let other = &resolutions[0];
+            assert_eq!(other.remapping, Remapping::from_str("other/=lib/other/").unwrap());
+            assert_eq!(other.source, RemappingSource::Env);
+            // the same path of remappings.txt is not a conflict
+            assert_eq!(
+                other.shadowed,
+                vec![(
+                    Remapping::from_str("other/=lib/toml-other/").unwrap(),
+                    RemappingSource::Config
+                )]
+            );
+            assert!(other.is_conflict());
+
+            assert_eq!(resolutions[1].source, RemappingSource::Config);
+            assert!(!resolutions[1].is_conflict());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_remappings_lock() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                auto_detect_remappings = false
+            "#,
+            )?;
+            jail.create_file("remappings.txt", "ds-test/=lib/ds-test/src/")?;
+
+            let config = Config::load();
+            config.verify_remappings_lock().unwrap();
+            let path = config.write_remappings_lock().unwrap();
+            assert_eq!(fs::read_to_string(path).unwrap(), "ds-test/=lib/ds-test/src/\n");
+            config.verify_remappings_lock().unwrap();
+
+            jail.create_file("remappings.txt", "ds-test/=lib/forge-std/lib/ds-test/src/")?;
+            let err = Config::load().verify_remappings_lock().unwrap_err().to_string();
+            assert!(err.contains("- ds-test/=lib/ds-test/src/"));
+            assert!(err.contains("+ ds-test/=lib/forge-std/lib/ds-test/src/"));
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_remappings_override() {
         figment::Jail::expect_with(|jail| {
@@ -2827,6 +3256,112 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_profile_inheritance() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                optimizer_runs = 100
+
+                [profile.strict]
+                deny_warnings = true
+                optimizer_runs = 1000
+
+                [profile.strict.fuzz]
+                runs = 10
+
+                [profile.ci]
+                inherits = "strict"
+                optimizer_runs = 5
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert!(!config.deny_warnings);
+            assert_eq!(config.optimizer_runs, 100);
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let config = Config::load();
+            assert!(config.deny_warnings);
+            assert_eq!(config.optimizer_runs, 5);
+            assert_eq!(config.fuzz.runs, 10);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_profile_inheritance_cycle() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.a]
+                inherits = "b"
+
+                [profile.b]
+                inherits = "a"
+            "#,
+            )?;
+
+            let err = Config::figment().extract::<Config>().unwrap_err();
+            assert!(err.to_string().contains("profiles inherit from each other: a -> b -> a"));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_conditional_profiles() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                optimizer_runs = 100
+
+                [profile.ci]
+                activate = { env = "FOUNDRY_TEST_CI" }
+                optimizer_runs = 5
+                sizes = true
+
+                [profile.mainnet]
+                activate = { chain = "mainnet" }
+                via_ir = true
+
+                [profile.local]
+                optimizer_runs = 7
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(config.optimizer_runs, 100);
+            assert!(!config.sizes);
+            assert!(config.activated_profiles().unwrap().is_empty());
+
+            jail.set_env("FOUNDRY_TEST_CI", "true");
+            let config = Config::load();
+            assert_eq!(config.optimizer_runs, 5);
+            assert!(config.sizes);
+            assert!(!config.via_ir);
+            assert_eq!(config.activated_profiles().unwrap(), vec!["ci".to_string()]);
+
+            // the selected profile takes precedence over activated profiles
+            jail.set_env("FOUNDRY_PROFILE", "local");
+            let config = Config::load();
+            assert_eq!(config.optimizer_runs, 7);
+            assert!(config.sizes);
+
+            jail.set_env("FOUNDRY_CHAIN_ID", "1");
+            let config = Config::load();
+            assert!(config.via_ir);
+
+            Ok(())
+        });
+    }
+
     #[test]
     #[should_panic]
     fn test_toml_file_parse_failure() {
@@ -2945,6 +3480,7 @@ mod tests {
                             chain: Some(Mainnet.into()),
                             browser_url: Some(mainnet_urls.1.to_string()),
                             key: "FX42Z3BBJJEWXWGYV2X1CIPRSCN".to_string(),
+                            fallback_keys: vec![],
                         }
                     ),
                     (
@@ -2954,6 +3490,7 @@ mod tests {
                             chain: Some(Moonbeam.into()),
                             browser_url: Some(mb_urls.1.to_string()),
                             key: "123456789".to_string(),
+                            fallback_keys: vec![],
                         }
                     ),
                 ])
@@ -2993,6 +3530,31 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_create2_deployers() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                [profile.default.create2_deployers]
+                optimism = "0x914d7fec6aac8cd542e72bca78b30650d45643d7"
+                1337 = "0x0000000000000000000000000000000000000001"
+            "#,
+            )?;
+
+            let config = Config::load();
+            let optimism: Address = "0x914d7fec6aac8cd542e72bca78b30650d45643d7".parse().unwrap();
+            assert_eq!(config.create2_deployer_for(ethers_core::types::Chain::Optimism), optimism);
+            assert_eq!(config.create2_deployer_for(10u64), optimism);
+            assert_eq!(config.create2_deployer_for(1337u64), Address::from_low_u64_be(1));
+            assert_eq!(config.create2_deployer_for(1u64), Config::DEFAULT_CREATE2_DEPLOYER);
+            assert!(config.is_create2_deployer(&optimism));
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_resolve_rpc_url_if_etherscan_set() {
         figment::Jail::expect_with(|jail| {
@@ -3041,6 +3603,114 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_pinned_rpc_endpoints() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                eth_rpc_url = "mainnet"
+                [rpc_endpoints]
+                optimism = "https://example.com/"
+                mainnet = { url = "https://eth-mainnet.alchemyapi.io/v2/123455", block = 19000000 }
+            "#,
+            )?;
+
+            let mut config = Config::load();
+            assert_eq!(
+                config.rpc_endpoints,
+                RpcEndpoints::new([
+                    ("optimism", RpcEndpoint::Url("https://example.com/".to_string())),
+                    (
+                        "mainnet",
+                        RpcEndpoint::Url("https://eth-mainnet.alchemyapi.io/v2/123455".to_string())
+                    ),
+                ])
+                .with_block("mainnet", 19000000)
+            );
+            assert_eq!(
+                config.get_rpc_url().unwrap().unwrap(),
+                "https://eth-mainnet.alchemyapi.io/v2/123455"
+            );
+            assert_eq!(config.get_fork_block_number(), Some(19000000));
+            assert_eq!(config.rpc_endpoints.clone().resolved().pinned_block("optimism"), None);
+
+            config.fork_block_number = Some(1);
+            assert_eq!(config.get_fork_block_number(), Some(1));
+
+            config.update_pinned_blocks(&BTreeMap::from([
+                ("mainnet".to_string(), 19000001),
+                ("optimism".to_string(), 1),
+            ]))
+            .unwrap();
+            let config = Config::load();
+            assert_eq!(config.rpc_endpoints.pinned_block("mainnet"), Some(19000001));
+            assert_eq!(config.rpc_endpoints.pinned_block("optimism"), None);
+
+            let roundtrip: RpcEndpoints =
+                toml::from_str(&toml::to_string(&config.rpc_endpoints).unwrap()).unwrap();
+            assert_eq!(roundtrip, config.rpc_endpoints);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_rpc_endpoint_fallbacks() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                eth_rpc_url = "mainnet"
+                [rpc_endpoints]
+                optimism = "https://a.optimism.io/,https://b.optimism.io/"
+                mainnet = [
+                    { url = "https://c.mainnet.io/", priority = 2 },
+                    "https://a.mainnet.io/",
+                    { url = "https://b.mainnet.io/" },
+                ]
+                goerli = { url = ["https://a.goerli.io/", "${GOERLI_RPC}"], block = 8000000 }
+            "#,
+            )?;
+            jail.set_env("GOERLI_RPC", "https://b.goerli.io/");
+
+            let config = Config::load();
+            assert_eq!(
+                config.rpc_endpoints,
+                RpcEndpoints::new([
+                    (
+                        "optimism",
+                        RpcEndpoint::Url("https://a.optimism.io/,https://b.optimism.io/".to_string())
+                    ),
+                    (
+                        "mainnet",
+                        RpcEndpoint::Url(
+                            "https://a.mainnet.io/,https://b.mainnet.io/,https://c.mainnet.io/"
+                                .to_string()
+                        )
+                    ),
+                    (
+                        "goerli",
+                        RpcEndpoint::Env("https://a.goerli.io/,${GOERLI_RPC}".to_string())
+                    ),
+                ])
+                .with_block("goerli", 8000000)
+            );
+            assert_eq!(
+                config.get_rpc_url_with_alias("goerli").unwrap().unwrap(),
+                "https://a.goerli.io/,https://b.goerli.io/"
+            );
+
+            let roundtrip: RpcEndpoints =
+                toml::from_str(&toml::to_string(&config.rpc_endpoints).unwrap()).unwrap();
+            assert_eq!(roundtrip, config.rpc_endpoints);
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_resolve_endpoints() {
         figment::Jail::expect_with(|jail| {
@@ -3178,6 +3848,33 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_create_etherscan_client_per_key() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+
+                [etherscan]
+                mumbai = { key = "key1", keys = ["key2"], chain = 80001 }
+            "#,
+            )?;
+
+            let config = Config::load();
+            let clients =
+                config.get_etherscan_clients(ethers_core::types::Chain::PolygonMumbai).unwrap();
+            assert_eq!(clients.len(), 2);
+            assert!(format!("{:?}", clients[1]).contains("key2"));
+
+            // a chain without keys is queried without a key
+            let clients = config.get_etherscan_clients(ethers_core::types::Chain::Goerli).unwrap();
+            assert_eq!(clients.len(), 1);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_extract_etherscan_config_by_chain_and_alias() {
         figment::Jail::expect_with(|jail| {
@@ -3844,6 +4541,37 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_profile_env() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default.env]
+                OWNER = "0xBEEF"
+                AMOUNT = "100"
+
+                [profile.ci.env]
+                AMOUNT = "1"
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(
+                loaded.env,
+                BTreeMap::from([
+                    ("AMOUNT".to_string(), "100".to_string()),
+                    ("OWNER".to_string(), "0xBEEF".to_string())
+                ])
+            );
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let loaded = Config::load();
+            assert_eq!(loaded.env.get("AMOUNT"), Some(&"1".to_string()));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_fs_permissions() {
         figment::Jail::expect_with(|jail| {
@@ -3871,6 +4599,19 @@ mod tests {
             let loaded = Config::load();
             assert_eq!(loaded.fs_permissions, FsPermissions::new(vec![PathPermission::none("./")]));
 
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                fs_permissions = [{ access = "read-metadata", path = "./data/**/*.json"}]
+            "#,
+            )?;
+            let loaded = Config::load();
+            assert_eq!(
+                loaded.fs_permissions,
+                FsPermissions::new(vec![PathPermission::read_metadata("./data/**/*.json")])
+            );
+
             Ok(())
         });
     }
@@ -3916,6 +4657,30 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_deterministic_metadata() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                bytecode_hash = "ipfs"
+                cbor_metadata = true
+                deterministic_metadata = true
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert!(config.deterministic_metadata);
+            let metadata = config.solc_settings().unwrap().metadata.unwrap();
+            assert_eq!(metadata.bytecode_hash, Some(BytecodeHash::None));
+            assert_eq!(metadata.cbor_metadata, Some(false));
+            assert_eq!(metadata.use_literal_content, Some(true));
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_model_checker_settings_basic() {
         figment::Jail::expect_with(|jail| {
@@ -4066,6 +4831,46 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_fuzz_dictionary_sources() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [fuzz]
+                include_balances = false
+
+                [invariant]
+                include_push_bytes = false
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert!(!config.fuzz.include_balances);
+            assert!(config.fuzz.include_push_bytes);
+            assert!(!config.invariant.include_balances);
+            assert!(!config.invariant.include_push_bytes);
+            assert_eq!(config.fuzz.dictionary_weights, None);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [fuzz.dictionary_weights]
+                balances = 5
+                push_bytes = 0
+            "#,
+            )?;
+
+            let config = Config::load();
+            assert_eq!(
+                config.fuzz.dictionary_weights,
+                Some(DictionaryWeights { balances: 5, push_bytes: 0, ..Default::default() })
+            );
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_standalone_sections_env() {
         figment::Jail::expect_with(|jail| {
@@ -0,0 +1,95 @@
+//! Where the resolved values of a config come from
+
+use crate::Config;
+use figment::{Figment, Metadata, Source};
+use std::{fmt, path::PathBuf};
+
+/// The kind of provider that set a config value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSource {
+    /// The built-in default of the key
+    Default,
+    /// Detected from the project, like the remappings of the installed libraries
+    Detected,
+    /// A `foundry.toml` file
+    File(PathBuf),
+    /// An environment variable
+    Env,
+    /// A command line argument, with the name of the provider of the command
+    Cli(String),
+}
+
+impl ValueSource {
+    /// Classifies the provider of the `metadata`
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        if let Some(Source::File(path)) = metadata.source.as_ref() {
+            return ValueSource::File(path.clone())
+        }
+        match metadata.name.as_ref() {
+            "Foundry Config" => ValueSource::Default,
+            "Remapping Provider" | "Dapp Hardhat dir compat" => ValueSource::Detected,
+            "Dapp env compat" => ValueSource::Env,
+            name if name.contains("environment variable") => ValueSource::Env,
+            name => ValueSource::Cli(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Default => f.write_str("default"),
+            ValueSource::Detected => f.write_str("detected"),
+            ValueSource::File(path) => write!(f, "file {}", path.display()),
+            ValueSource::Env => f.write_str("env"),
+            ValueSource::Cli(name) => write!(f, "cli ({name})"),
+        }
+    }
+}
+
+/// A resolved config value and the provider that set it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedValue {
+    /// The dotted key of the value, e.g. `fuzz.runs`
+    pub key: String,
+    pub value: toml::Value,
+    pub source: ValueSource,
+}
+
+/// Returns all values of the `config` with the provider of the `figment` the config was
+/// extracted from that set them
+///
+/// Values of nested tables are returned individually, values that are not set by any provider of
+/// the `figment` are attributed to the provider of their table.
+pub fn resolve(config: &Config, figment: &Figment) -> Result<Vec<ResolvedValue>, toml::ser::Error> {
+    let mut resolved = Vec::new();
+    if let toml::Value::Table(table) = toml::Value::try_from(config)? {
+        resolve_table(figment, None, table, &ValueSource::Default, &mut resolved);
+    }
+    Ok(resolved)
+}
+
+fn resolve_table(
+    figment: &Figment,
+    prefix: Option<&str>,
+    table: toml::value::Table,
+    parent: &ValueSource,
+    resolved: &mut Vec<ResolvedValue>,
+) {
+    for (key, value) in table {
+        let key = match prefix {
+            Some(prefix) => format!("{prefix}.{key}"),
+            None => key,
+        };
+        let source = figment
+            .find_metadata(&key)
+            .map(ValueSource::from_metadata)
+            .unwrap_or_else(|| parent.clone());
+        match value {
+            toml::Value::Table(table) if !table.is_empty() => {
+                resolve_table(figment, Some(&key), table, &source, resolved)
+            }
+            value => resolved.push(ResolvedValue { key, value, source }),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Profile inheritance and conditionally activated profiles
+
+use crate::{Chain, Config};
+use figment::{
+    value::{Dict, Map, Value},
+    Error, Metadata, Profile, Provider,
+};
+use serde::Deserialize;
+
+/// The key of the profile a profile inherits from
+const INHERITS_KEY: &str = "inherits";
+/// The key of the conditions that activate a profile
+const ACTIVATE_KEY: &str = "activate";
+
+/// The conditions under which a profile is activated without being selected with
+/// `FOUNDRY_PROFILE`
+///
+/// ```toml
+/// [profile.ci]
+/// activate = { env = "CI" }
+///
+/// [profile.mainnet]
+/// activate = { chain = "mainnet" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileActivation {
+    /// The environment variable that must be set to a non empty value
+    pub env: Option<String>,
+    /// The value the environment variable must be set to
+    pub value: Option<String>,
+    /// The chain the project must be configured for
+    pub chain: Option<Chain>,
+}
+
+impl ProfileActivation {
+    /// Returns true if all conditions are met, a profile without conditions is never activated
+    pub fn is_active(&self, chain: Option<Chain>) -> bool {
+        if self.env.is_none() && self.chain.is_none() {
+            return false
+        }
+        if let Some(var) = self.env.as_ref() {
+            match std::env::var(var) {
+                Ok(val) if !val.is_empty() => {
+                    if self.value.as_ref().map(|expected| *expected != val).unwrap_or_default() {
+                        return false
+                    }
+                }
+                _ => return false,
+            }
+        }
+        if let Some(expected) = self.chain {
+            if chain.map(|chain| chain.id()) != Some(expected.id()) {
+                return false
+            }
+        }
+        true
+    }
+}
+
+/// Resolves the `inherits` and `activate` keys of the `[profile.<name>]` tables
+///
+/// A profile that `inherits` another profile starts from all values of the other profile,
+/// including the ones it inherits itself. The values of all profiles whose `activate` conditions
+/// are met are layered between the default profile and the selected profile.
+pub struct ProfileInheritanceProvider<P> {
+    provider: P,
+    profile: Profile,
+}
+
+impl<P> ProfileInheritanceProvider<P> {
+    pub fn new(provider: P, profile: impl Into<Profile>) -> Self {
+        Self { provider, profile: profile.into() }
+    }
+}
+
+impl<P: Provider> ProfileInheritanceProvider<P> {
+    /// Returns the names of the profiles that are activated by their conditions, in alphabetical
+    /// order
+    pub fn activated_profiles(&self) -> Result<Vec<String>, Error> {
+        let data = self.provider.data()?;
+        match data.get(&Profile::new(Config::PROFILE_SECTION)) {
+            Some(profiles) => {
+                let resolved = resolve_profiles(profiles)?;
+                activated_profiles(profiles, &resolved, &self.profile)
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl<P: Provider> Provider for ProfileInheritanceProvider<P> {
+    fn metadata(&self) -> Metadata {
+        self.provider.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let mut data = self.provider.data()?;
+        let section = Profile::new(Config::PROFILE_SECTION);
+        let profiles = match data.remove(&section) {
+            Some(profiles) => profiles,
+            None => return Ok(data),
+        };
+        let mut resolved = resolve_profiles(&profiles)?;
+        let activated = activated_profiles(&profiles, &resolved, &self.profile)?;
+
+        if !activated.is_empty() {
+            let key = resolved
+                .keys()
+                .find(|name| Profile::new(name) == self.profile)
+                .cloned()
+                .unwrap_or_else(|| self.profile.to_string());
+            let selected = resolved.remove(&key).and_then(Value::into_dict).unwrap_or_default();
+            let is_default = self.profile == Config::DEFAULT_PROFILE;
+            let mut layered = if is_default { selected.clone() } else { Dict::new() };
+            for name in activated.iter() {
+                if let Some(Value::Dict(_, dict)) = resolved.get(name) {
+                    merge_dicts(&mut layered, dict.clone());
+                }
+            }
+            if !is_default {
+                merge_dicts(&mut layered, selected);
+            }
+            resolved.insert(key, Value::from(layered));
+        }
+
+        data.insert(section, resolved);
+        Ok(data)
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.provider.profile()
+    }
+}
+
+/// Resolves the inherited values of all profiles and removes the `inherits` and `activate` keys
+fn resolve_profiles(profiles: &Dict) -> Result<Dict, Error> {
+    let mut resolved = Dict::new();
+    for name in profiles.keys() {
+        let dict = resolve_profile(profiles, name, &mut vec![name.clone()])?;
+        resolved.insert(name.clone(), Value::from(dict));
+    }
+    Ok(resolved)
+}
+
+/// Resolves a single profile, `chain` are the profiles that inherit from it
+fn resolve_profile(profiles: &Dict, name: &str, chain: &mut Vec<String>) -> Result<Dict, Error> {
+    let mut dict = match profiles.get(name) {
+        Some(Value::Dict(_, dict)) => dict.clone(),
+        Some(_) => return Err(format!("profile `{name}` is not a table").into()),
+        None => {
+            let child = &chain[chain.len() - 2];
+            return Err(format!("profile `{child}` inherits from unknown profile `{name}`").into())
+        }
+    };
+    dict.remove(ACTIVATE_KEY);
+    let parent = match dict.remove(INHERITS_KEY) {
+        Some(parent) => parent
+            .into_string()
+            .ok_or_else(|| format!("`inherits` of profile `{name}` must be a profile name"))?,
+        None => return Ok(dict),
+    };
+    if chain.contains(&parent) {
+        chain.push(parent);
+        return Err(format!("profiles inherit from each other: {}", chain.join(" -> ")).into())
+    }
+    chain.push(parent.clone());
+    let mut resolved = resolve_profile(profiles, &parent, chain)?;
+    merge_dicts(&mut resolved, dict);
+    Ok(resolved)
+}
+
+/// Returns the profiles other than the `selected` one whose `activate` conditions are met
+fn activated_profiles(
+    profiles: &Dict,
+    resolved: &Dict,
+    selected: &Profile,
+) -> Result<Vec<String>, Error> {
+    let chain = detect_chain(resolved, selected);
+    let mut activated = Vec::new();
+    for (name, profile) in profiles {
+        let activation = match profile.as_dict().and_then(|dict| dict.get(ACTIVATE_KEY)) {
+            Some(activation) => activation,
+            None => continue,
+        };
+        let activation = activation.deserialize::<ProfileActivation>().map_err(|err| {
+            Error::from(format!("invalid `activate` conditions of profile `{name}`: {err}"))
+        })?;
+        if Profile::new(name) != *selected && activation.is_active(chain) {
+            activated.push(name.clone());
+        }
+    }
+    Ok(activated)
+}
+
+/// Returns the chain the project is configured for: the `FOUNDRY_CHAIN_ID` env var or the
+/// `chain_id` of the selected or default profile
+fn detect_chain(resolved: &Dict, selected: &Profile) -> Option<Chain> {
+    if let Ok(chain) = std::env::var("FOUNDRY_CHAIN_ID") {
+        return chain.parse().ok()
+    }
+    [selected, &Config::DEFAULT_PROFILE].into_iter().find_map(|profile| {
+        resolved
+            .iter()
+            .find(|(name, _)| Profile::new(name) == *profile)
+            .and_then(|(_, dict)| dict.as_dict()?.get("chain_id")?.deserialize().ok())
+    })
+}
+
+/// Merges the `overrides` into `base`, nested tables are merged recursively
+fn merge_dicts(base: &mut Dict, overrides: Dict) {
+    for (key, value) in overrides {
+        match value {
+            Value::Dict(_, dict) if matches!(base.get(&key), Some(Value::Dict(..))) => {
+                if let Some(Value::Dict(_, inner)) = base.get_mut(&key) {
+                    merge_dicts(inner, dict);
+                }
+            }
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
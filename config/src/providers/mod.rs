@@ -4,6 +4,7 @@ use figment::{
     Error, Figment, Metadata, Profile, Provider,
 };
 
+pub mod inheritance;
 pub mod remappings;
 
 /// Generate warnings for unknown sections and deprecated keys
@@ -7,7 +7,7 @@ use figment::{
 use std::{
     borrow::Cow,
     collections::{hash_map::Entry, HashMap},
-    fs,
+    fmt, fs,
     path::{Path, PathBuf},
 };
 use tracing::trace;
@@ -32,8 +32,52 @@ pub struct RemappingsProvider<'a> {
     pub remappings: Result<Vec<Remapping>, Error>,
 }
 
+/// Where a remapping was declared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemappingSource {
+    /// The `DAPP_REMAPPINGS` or `FOUNDRY_REMAPPINGS` env var
+    Env,
+    /// The `remappings.txt` file of the project
+    RemappingsTxt,
+    /// The `foundry.toml` of the project
+    Config,
+    /// The `foundry.toml` of a library
+    LibConfig,
+    /// Auto detected in a library
+    AutoDetected,
+}
+
+impl fmt::Display for RemappingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RemappingSource::Env => "env",
+            RemappingSource::RemappingsTxt => "remappings.txt",
+            RemappingSource::Config => "foundry.toml",
+            RemappingSource::LibConfig => "lib foundry.toml",
+            RemappingSource::AutoDetected => "auto-detected",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The remapping that is used for a name, and all remappings of the same name that it shadows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemappingResolution {
+    pub remapping: Remapping,
+    pub source: RemappingSource,
+    /// Remappings of the same name but to a different path, in order of precedence
+    pub shadowed: Vec<(Remapping, RemappingSource)>,
+}
+
+impl RemappingResolution {
+    /// Returns true if the name is remapped to more than one path
+    pub fn is_conflict(&self) -> bool {
+        !self.shadowed.is_empty()
+    }
+}
+
 impl<'a> RemappingsProvider<'a> {
-    /// Find and parse remappings for the projects
+    /// Find and parse remappings for the projects and returns how each name was resolved
     ///
     /// **Order**
     ///
@@ -43,31 +87,37 @@ impl<'a> RemappingsProvider<'a> {
     /// - `remappings.txt`
     /// - Environment variables
     /// - CLI parameters
-    fn get_remappings(&self, remappings: Vec<Remapping>) -> Result<Vec<Remapping>, Error> {
-        trace!("get all remappings from {:?}", self.root);
-        /// prioritizes remappings that are closer: shorter `path`
-        ///   - ("a", "1/2") over ("a", "1/2/3")
-        fn insert_closest(mappings: &mut HashMap<String, PathBuf>, key: String, path: PathBuf) {
-            match mappings.entry(key) {
-                Entry::Occupied(mut e) => {
-                    if e.get().components().count() > path.components().count() {
-                        e.insert(path);
-                    }
-                }
-                Entry::Vacant(e) => {
-                    e.insert(path);
+    pub fn resolutions(&self) -> Result<Vec<RemappingResolution>, Error> {
+        match &self.remappings {
+            Ok(remappings) => self.resolve(remappings.clone()),
+            Err(err) => {
+                if let figment::error::Kind::MissingField(_) = err.kind {
+                    self.resolve(vec![])
+                } else {
+                    Err(err.clone())
                 }
             }
         }
+    }
 
-        let mut new_remappings = Vec::new();
+    /// Resolves all remappings by name, the first remapping of a name wins
+    ///
+    /// Auto detected remappings that are closer (shorter `path`) take precedence, so
+    /// ("a", "lib/a/src") wins over ("a", "lib/b/lib/a/src") of a nested dependency.
+    fn resolve(&self, remappings: Vec<Remapping>) -> Result<Vec<RemappingResolution>, Error> {
+        trace!("get all remappings from {:?}", self.root);
+        let mut candidates = Vec::new();
 
         // check env var
         if let Some(env_remappings) = remappings_from_env_var("DAPP_REMAPPINGS")
             .or_else(|| remappings_from_env_var("FOUNDRY_REMAPPINGS"))
         {
-            new_remappings
-                .extend(env_remappings.map_err::<Error, _>(|err| err.to_string().into())?);
+            candidates.extend(
+                env_remappings
+                    .map_err::<Error, _>(|err| err.to_string().into())?
+                    .into_iter()
+                    .map(|r| (r, RemappingSource::Env)),
+            );
         }
 
         // check remappings.txt file
@@ -76,19 +126,23 @@ impl<'a> RemappingsProvider<'a> {
             let content = fs::read_to_string(remappings_file).map_err(|err| err.to_string())?;
             let remappings_from_file: Result<Vec<_>, _> =
                 remappings_from_newline(&content).collect();
-            new_remappings
-                .extend(remappings_from_file.map_err::<Error, _>(|err| err.to_string().into())?);
+            candidates.extend(
+                remappings_from_file
+                    .map_err::<Error, _>(|err| err.to_string().into())?
+                    .into_iter()
+                    .map(|r| (r, RemappingSource::RemappingsTxt)),
+            );
         }
 
-        new_remappings.extend(remappings);
+        candidates.extend(remappings.into_iter().map(|r| (r, RemappingSource::Config)));
 
         // scan all library dirs and autodetect remappings
         if self.auto_detect_remappings {
-            let mut lib_remappings = HashMap::new();
             // find all remappings of from libs that use a foundry.toml
-            for r in self.lib_foundry_toml_remappings() {
-                insert_closest(&mut lib_remappings, r.name, r.path.into());
-            }
+            let mut lib_remappings = self
+                .lib_foundry_toml_remappings()
+                .map(|r| (r, RemappingSource::LibConfig))
+                .collect::<Vec<_>>();
             // use auto detection for all libs
             for r in self
                 .lib_paths
@@ -103,21 +157,44 @@ impl<'a> RemappingsProvider<'a> {
                 if ["lib/", "src/", "contracts/"].contains(&r.name.as_str()) {
                     continue
                 }
-                insert_closest(&mut lib_remappings, r.name, r.path.into());
+                lib_remappings.push((r, RemappingSource::AutoDetected));
             }
+            // prioritizes remappings that are closer, the sort is stable so the first detected
+            // remapping wins if they're equally close
+            lib_remappings.sort_by_key(|(r, _)| Path::new(&r.path).components().count());
+            candidates.extend(lib_remappings);
+        }
 
-            new_remappings.extend(
-                lib_remappings
-                    .into_iter()
-                    .map(|(name, path)| Remapping { name, path: path.to_string_lossy().into() }),
-            );
+        let mut resolutions: Vec<RemappingResolution> = Vec::new();
+        let mut by_name = HashMap::new();
+        for (remapping, source) in candidates {
+            match by_name.entry(remapping.name.clone()) {
+                Entry::Occupied(e) => {
+                    let resolution = &mut resolutions[*e.get()];
+                    let path = self.normalized(&remapping.path);
+                    let known = std::iter::once(&resolution.remapping)
+                        .chain(resolution.shadowed.iter().map(|(r, _)| r))
+                        .any(|r| self.normalized(&r.path) == path);
+                    if !known {
+                        resolution.shadowed.push((remapping, source));
+                    }
+                }
+                Entry::Vacant(e) => {
+                    e.insert(resolutions.len());
+                    resolutions.push(RemappingResolution { remapping, source, shadowed: vec![] });
+                }
+            }
         }
+        resolutions.sort_by(|a, b| a.remapping.name.cmp(&b.remapping.name));
 
-        // remove duplicates at this point
-        new_remappings.sort_by(|a, b| a.name.cmp(&b.name));
-        new_remappings.dedup_by(|a, b| a.name.eq(&b.name));
+        Ok(resolutions)
+    }
 
-        Ok(new_remappings)
+    /// Returns the path of a remapping relative to the root, so remappings to the same dir compare
+    /// equal
+    fn normalized(&self, path: &str) -> PathBuf {
+        let path = Path::new(path.trim_end_matches('/'));
+        path.strip_prefix(self.root).unwrap_or(path).to_path_buf()
     }
 
     /// Returns all remappings declared in foundry.toml files of libraries
@@ -169,21 +246,11 @@ impl<'a> Provider for RemappingsProvider<'a> {
     }
 
     fn data(&self) -> Result<Map<Profile, Dict>, Error> {
-        let remappings = match &self.remappings {
-            Ok(remappings) => self.get_remappings(remappings.clone()),
-            Err(err) => {
-                if let figment::error::Kind::MissingField(_) = err.kind {
-                    self.get_remappings(vec![])
-                } else {
-                    return Err(err.clone())
-                }
-            }
-        }?;
-
         // turn the absolute remapping into a relative one by stripping the `root`
-        let remappings = remappings
+        let remappings = self
+            .resolutions()?
             .into_iter()
-            .map(|r| RelativeRemapping::new(r, self.root).to_string())
+            .map(|r| RelativeRemapping::new(r.remapping, self.root).to_string())
             .collect::<Vec<_>>();
 
         Ok(Map::from([(
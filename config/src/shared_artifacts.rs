@@ -0,0 +1,233 @@
+//! Support for sharing compiler outputs across profiles, branches and worktrees, see
+//! `shared_artifacts`
+
+use ethers_core::utils::{hex, keccak256};
+use ethers_solc::{error::SolcIoError, Project};
+use fs2::FileExt;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tracing::trace;
+use walkdir::WalkDir;
+
+/// The file in the cache directory of a project that holds the key of the settings the current
+/// artifacts were compiled with
+const SETTINGS_KEY_FILE: &str = "shared-artifacts-key";
+
+/// The dir of a store entry that holds the artifacts
+const ARTIFACTS_DIR: &str = "out";
+
+/// The file of a store entry that holds the solc cache
+const CACHE_FILE: &str = "solidity-files-cache.json";
+
+/// The file in the cache directory of a project that is locked while the artifacts are swapped
+/// and compiled
+const LOCK_FILE: &str = "shared-artifacts.lock";
+
+/// An exclusive lock on the artifacts and cache of a project, released when dropped
+///
+/// See [Config::restore_shared_artifacts](crate::Config::restore_shared_artifacts).
+#[derive(Debug)]
+pub struct SharedArtifactsLock {
+    _file: fs::File,
+}
+
+/// A store of compiler outputs with one entry for every combination of solc version and settings
+///
+/// Every entry holds the artifacts and the solc cache file of the last project that was compiled
+/// with these settings. The solc cache tracks the content hash of every source file, so after an
+/// entry is restored only the files that changed since are recompiled.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedArtifacts {
+    /// The root dir of the store, `~/.foundry/artifacts` by default
+    dir: PathBuf,
+}
+
+impl SharedArtifacts {
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Takes an exclusive lock on the artifacts and cache of the project, blocks until concurrent
+    /// runs of the same project released it
+    pub(crate) fn lock(project: &Project) -> Result<SharedArtifactsLock, SolcIoError> {
+        let cache_dir = project.paths.cache.parent().unwrap_or(&project.paths.root);
+        create_dir_all(cache_dir)?;
+        let path = cache_dir.join(LOCK_FILE);
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&path)
+            .map_err(|err| SolcIoError::new(err, &path))?;
+        file.lock_exclusive().map_err(|err| SolcIoError::new(err, &path))?;
+        Ok(SharedArtifactsLock { _file: file })
+    }
+
+    /// Swaps the artifacts and cache of the project for the entry of its current settings
+    ///
+    /// The outputs of the settings the project was compiled with before are moved into the store
+    /// first. Nothing happens if the settings did not change since the last compilation. The
+    /// caller must hold the [SharedArtifacts::lock] of the project.
+    pub(crate) fn restore(&self, project: &Project, solc: &str) -> Result<(), SolcIoError> {
+        let key = settings_key(project, solc);
+        let key_file = match project.paths.cache.parent() {
+            Some(cache_dir) => cache_dir.join(SETTINGS_KEY_FILE),
+            None => return Ok(()),
+        };
+        let previous = fs::read_to_string(&key_file).ok();
+        if previous.as_deref().map(str::trim) == Some(key.as_str()) {
+            return Ok(())
+        }
+
+        if let Some(previous) = previous {
+            trace!(target: "config", previous = previous.trim(), "moving artifacts into the store");
+            self.stash(project, &self.dir.join(previous.trim()))?;
+        }
+
+        let entry = self.dir.join(&key);
+        if entry.join(CACHE_FILE).exists() {
+            trace!(target: "config", key, "restoring artifacts from the store");
+            remove_dir_all(&project.paths.artifacts)?;
+            copy_dir_all(&entry.join(ARTIFACTS_DIR), &project.paths.artifacts)?;
+            copy(&entry.join(CACHE_FILE), &project.paths.cache)?;
+        }
+
+        create_dir_all(key_file.parent().expect("has parent"))?;
+        fs::write(&key_file, key).map_err(|err| SolcIoError::new(err, key_file))
+    }
+
+    /// Moves the artifacts and cache of the project into the given entry of the store
+    fn stash(&self, project: &Project, entry: &Path) -> Result<(), SolcIoError> {
+        if !project.paths.cache.exists() {
+            return Ok(())
+        }
+        remove_dir_all(entry)?;
+        create_dir_all(entry)?;
+        copy(&project.paths.cache, &entry.join(CACHE_FILE))?;
+
+        let artifacts = entry.join(ARTIFACTS_DIR);
+        if project.paths.artifacts.exists() &&
+            fs::rename(&project.paths.artifacts, &artifacts).is_err()
+        {
+            // the store is on a different file system
+            copy_dir_all(&project.paths.artifacts, &artifacts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the key of the project and the solc version and settings it is compiled with
+///
+/// Besides the settings, the key covers the canonical root of the project and the paths of its
+/// sources, so unrelated projects with the same settings never share an entry.
+fn settings_key(project: &Project, solc: &str) -> String {
+    let root = &project.paths.root;
+    let canonical_root = fs::canonicalize(root).unwrap_or_else(|_| root.clone());
+    let mut sources = project
+        .paths
+        .input_files()
+        .into_iter()
+        .map(|file| file.strip_prefix(root).unwrap_or(&file).display().to_string())
+        .collect::<Vec<_>>();
+    sources.sort();
+    let settings = format!("{:?}", project.solc_config.settings);
+    let key = format!(
+        "{solc}\n{}\n{settings}\n{}",
+        canonical_root.display(),
+        hex::encode(keccak256(sources.join("\n")))
+    );
+    hex::encode(&keccak256(key)[..16])
+}
+
+fn copy_dir_all(from: &Path, to: &Path) -> Result<(), SolcIoError> {
+    for entry in WalkDir::new(from).into_iter().filter_map(Result::ok) {
+        let target = to.join(entry.path().strip_prefix(from).expect("is child"));
+        if entry.file_type().is_dir() {
+            create_dir_all(&target)?;
+        } else {
+            copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy(from: &Path, to: &Path) -> Result<(), SolcIoError> {
+    if let Some(parent) = to.parent() {
+        create_dir_all(parent)?;
+    }
+    fs::copy(from, to).map(drop).map_err(|err| SolcIoError::new(err, from))
+}
+
+fn create_dir_all(dir: &Path) -> Result<(), SolcIoError> {
+    fs::create_dir_all(dir).map_err(|err| SolcIoError::new(err, dir))
+}
+
+fn remove_dir_all(dir: &Path) -> Result<(), SolcIoError> {
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(SolcIoError::new(err, dir)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_solc::ProjectPathsConfig;
+
+    fn project(root: &Path) -> Project {
+        let paths = ProjectPathsConfig::builder().build_with_root(root);
+        Project::builder().paths(paths).build().unwrap()
+    }
+
+    #[test]
+    fn can_swap_artifacts_by_settings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SharedArtifacts::new(tmp.path().join("store"));
+        let mut project = project(&tmp.path().join("project"));
+        let artifact = project.paths.artifacts.join("A.sol/A.json");
+
+        let settings = project.solc_config.settings.clone();
+        store.restore(&project, "0.8.17").unwrap();
+        fs::create_dir_all(artifact.parent().unwrap()).unwrap();
+        fs::write(&artifact, "artifact").unwrap();
+        fs::write(&project.paths.cache, "{}").unwrap();
+
+        // switching the settings moves the outputs into the store
+        project.solc_config.settings.optimizer.enable();
+        store.restore(&project, "0.8.17").unwrap();
+        assert!(!artifact.exists());
+
+        // and switching back restores them
+        project.solc_config.settings = settings;
+        store.restore(&project, "0.8.17").unwrap();
+        assert_eq!(fs::read_to_string(&artifact).unwrap(), "artifact");
+    }
+
+    #[test]
+    fn keys_include_project_identity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let a = project(&tmp.path().join("a"));
+        let b = project(&tmp.path().join("b"));
+        fs::create_dir_all(&a.paths.sources).unwrap();
+        fs::create_dir_all(&b.paths.sources).unwrap();
+        assert_ne!(settings_key(&a, "0.8.17"), settings_key(&b, "0.8.17"));
+
+        // adding a source changes the key of the project
+        let key = settings_key(&a, "0.8.17");
+        fs::write(a.paths.sources.join("A.sol"), "contract A {}").unwrap();
+        assert_ne!(settings_key(&a, "0.8.17"), key);
+    }
+
+    #[test]
+    fn lock_is_exclusive() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = project(&tmp.path().join("project"));
+        let lock = SharedArtifacts::lock(&project).unwrap();
+
+        let file = fs::File::open(tmp.path().join("project/cache").join(LOCK_FILE)).unwrap();
+        assert!(file.try_lock_exclusive().is_err());
+        drop(lock);
+        assert!(file.try_lock_exclusive().is_ok());
+    }
+}
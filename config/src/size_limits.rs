@@ -0,0 +1,85 @@
+//! Support for per contract byte budgets, see `[size_limits]`
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The maximum sizes of the bytecode of a contract, in bytes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeLimit {
+    /// Budget of the deployed bytecode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<usize>,
+    /// Budget of the creation bytecode, without constructor arguments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initcode: Option<usize>,
+}
+
+/// Configures the byte budgets of the contracts that are enforced by `forge build`
+///
+/// ```toml
+/// [size_limits]
+/// runtime = 24000
+/// initcode = 48000
+///
+/// [size_limits.contracts]
+/// Vault = { runtime = 20000 }
+/// ```
+///
+/// The budgets of a contract override the default budgets, test and script contracts have no
+/// budget.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeLimitsConfig {
+    /// Default budget of the deployed bytecode of all contracts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<usize>,
+    /// Default budget of the creation bytecode of all contracts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initcode: Option<usize>,
+    /// Budgets by contract name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contracts: BTreeMap<String, SizeLimit>,
+}
+
+// === impl SizeLimitsConfig ===
+
+impl SizeLimitsConfig {
+    /// Returns true if any budget is configured
+    pub fn has_limits(&self) -> bool {
+        self.runtime.is_some() || self.initcode.is_some() || !self.contracts.is_empty()
+    }
+
+    /// Returns the budgets of the contract with the given name
+    pub fn limit(&self, contract: &str) -> SizeLimit {
+        let limit = self.contracts.get(contract).copied().unwrap_or_default();
+        SizeLimit {
+            runtime: limit.runtime.or(self.runtime),
+            initcode: limit.initcode.or(self.initcode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_contract_limits() {
+        let limits: SizeLimitsConfig = toml::from_str(
+            r#"
+            runtime = 24000
+
+            [contracts]
+            Vault = { runtime = 20000, initcode = 30000 }
+            Token = { initcode = 40000 }
+        "#,
+        )
+        .unwrap();
+        assert!(limits.has_limits());
+        assert!(!SizeLimitsConfig::default().has_limits());
+
+        let limit = |runtime, initcode| SizeLimit { runtime, initcode };
+        assert_eq!(limits.limit("Vault"), limit(Some(20000), Some(30000)));
+        assert_eq!(limits.limit("Token"), limit(Some(24000), Some(40000)));
+        assert_eq!(limits.limit("Other"), limit(Some(24000), None));
+    }
+}
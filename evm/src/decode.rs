@@ -4,7 +4,7 @@ use crate::{
     error::ERROR_PREFIX,
 };
 use ethers::{
-    abi::{decode, AbiDecode, Contract as Abi, ParamType, RawLog, Token},
+    abi::{decode, AbiDecode, Contract as Abi, Function, ParamType, RawLog, Token},
     contract::EthLogDecode,
     prelude::U256,
     types::Log,
@@ -162,21 +162,9 @@ pub fn decode_revert(
         }
         _ => {
             // try to decode a custom error if provided an abi
-            if let Some(abi) = maybe_abi {
-                for abi_error in abi.errors() {
-                    if abi_error.signature()[..SELECTOR_LEN] == err[..SELECTOR_LEN] {
-                        // if we don't decode, don't return an error, try to decode as a
-                        // string later
-                        if let Ok(decoded) = abi_error.decode(&err[SELECTOR_LEN..]) {
-                            let inputs = decoded
-                                .iter()
-                                .map(foundry_common::abi::format_token)
-                                .collect::<Vec<_>>()
-                                .join(", ");
-                            return Ok(format!("{}({inputs})", abi_error.name))
-                        }
-                    }
-                }
+            // if we don't decode, don't return an error, try to decode as a string later
+            if let Some(decoded) = decode_abi_error(err, maybe_abi) {
+                return Ok(decoded)
             }
 
             // optimistically try to decode as string, unknown selector or `CheatcodeError`
@@ -190,6 +178,10 @@ pub fn decode_revert(
                         None
                     }
                 })
+                .or_else(|| {
+                    // try decoding revert data wrapped in an unknown error
+                    decode_nested_revert(err, maybe_abi)
+                })
                 .or_else(|| {
                     // try decoding as unknown err
                     String::decode(&err[SELECTOR_LEN..])
@@ -214,6 +206,101 @@ pub fn decode_revert(
     }
 }
 
+/// Tries to decode the error with the custom errors of the given abi
+fn decode_abi_error(err: &[u8], maybe_abi: Option<&Abi>) -> Option<String> {
+    maybe_abi?
+        .errors()
+        .filter(|abi_error| abi_error.signature()[..SELECTOR_LEN] == err[..SELECTOR_LEN])
+        .find_map(|abi_error| {
+            let decoded = abi_error.decode(&err[SELECTOR_LEN..]).ok()?;
+            let inputs = decoded.iter().map(format_token).collect::<Vec<_>>().join(", ");
+            Some(format!("{}({inputs})", abi_error.name))
+        })
+}
+
+/// Decodes the error with the given signature, e.g. a custom error identified by its selector
+pub fn decode_error(err: &[u8], error: &Function) -> Option<String> {
+    if err.len() < SELECTOR_LEN || error.short_signature()[..] != err[..SELECTOR_LEN] {
+        return None
+    }
+    let decoded = error.decode_input(&err[SELECTOR_LEN..]).ok()?;
+    let inputs = decoded.iter().map(format_token).collect::<Vec<_>>().join(", ");
+    Some(format!("{}({inputs})", error.name))
+}
+
+/// Returns true if the error has a selector that is neither builtin nor a custom error of the
+/// given abi, e.g. an error of a contract on a fork
+pub fn is_unknown_custom_error(err: &[u8], maybe_abi: Option<&Abi>) -> bool {
+    if err.len() < SELECTOR_LEN || err.starts_with(ERROR_PREFIX.as_slice()) {
+        return false
+    }
+    let builtin = matches!(
+        err[..SELECTOR_LEN],
+        [78, 72, 123, 113] | [8, 195, 121, 160] | [242, 141, 206, 179] | [195, 30, 176, 224]
+    );
+    !builtin && decode_abi_error(err, maybe_abi).is_none()
+}
+
+/// How many levels of wrapping errors [decode_nested_revert] unwraps at most
+const MAX_NESTED_REVERT_DEPTH: usize = 4;
+
+/// Tries to decode revert data that is wrapped in an unknown error, e.g. the revert data of a
+/// failed call that is passed along as `CallFailed(bytes)`
+///
+/// Every `bytes` argument of the error is checked for revert data that decodes to a builtin or
+/// custom error. Returns the reason of the innermost revert data that decodes.
+pub fn decode_nested_revert(err: &[u8], maybe_abi: Option<&Abi>) -> Option<String> {
+    decode_nested_revert_at_depth(err, maybe_abi, 0)
+}
+
+fn decode_nested_revert_at_depth(
+    err: &[u8],
+    maybe_abi: Option<&Abi>,
+    depth: usize,
+) -> Option<String> {
+    if depth >= MAX_NESTED_REVERT_DEPTH || err.len() <= SELECTOR_LEN {
+        return None
+    }
+    let args = &err[SELECTOR_LEN..];
+
+    // every word of the arguments could be the offset of a `bytes` argument
+    (0..args.len() / 32).find_map(|word| {
+        let offset = read_offset(args, word * 32)?;
+        if offset % 32 != 0 {
+            return None
+        }
+        let len = read_offset(args, offset)?;
+        let start = offset.checked_add(32)?;
+        let inner = args.get(start..start.checked_add(len)?)?;
+        decode_wrapped_revert(inner, maybe_abi, depth + 1)
+    })
+}
+
+/// Decodes revert data that was found within another error
+fn decode_wrapped_revert(err: &[u8], maybe_abi: Option<&Abi>, depth: usize) -> Option<String> {
+    if err.len() < SELECTOR_LEN {
+        return None
+    }
+    match err[..SELECTOR_LEN] {
+        // keccak(Panic(uint256)), keccak(Error(string))
+        [78, 72, 123, 113] | [8, 195, 121, 160] => decode_revert(err, maybe_abi, None).ok(),
+        _ => decode_nested_revert_at_depth(err, maybe_abi, depth)
+            .or_else(|| decode_abi_error(err, maybe_abi)),
+    }
+}
+
+/// Reads the abi-encoded word at `at` as an offset or a length
+fn read_offset(data: &[u8], at: usize) -> Option<usize> {
+    let word = U256::from(data.get(at..at.checked_add(32)?)?);
+    (word <= U256::from(u32::MAX)).then(|| word.as_usize())
+}
+
+/// Returns true if the reason doesn't tell anything but that the call reverted, as returned by
+/// [decode_revert] for empty or undecodable revert data
+pub fn is_generic_revert_reason(reason: &str) -> bool {
+    matches!(reason, "EvmError: Revert" | "Revert")
+}
+
 /// Tries to optimistically decode a custom solc error, with at most 4 arguments
 pub fn decode_custom_error(err: &[u8]) -> Option<Token> {
     decode_custom_error_args(err, 4)
@@ -280,6 +367,7 @@ mod tests {
         abi::{AbiEncode, Address},
         contract::EthError,
     };
+    use foundry_common::abi::get_func;
 
     #[test]
     fn test_decode_custom_error_address() {
@@ -309,4 +397,47 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_decode_identified_error() {
+        #[derive(Debug, Clone, EthError)]
+        struct ERC20InsufficientBalance(Address, U256, U256);
+        let err = ERC20InsufficientBalance(Address::zero(), 1u64.into(), 2u64.into()).encode();
+        assert!(is_unknown_custom_error(&err, None));
+
+        let error = get_func("ERC20InsufficientBalance(address,uint256,uint256)").unwrap();
+        assert_eq!(
+            decode_error(&err, &error).unwrap(),
+            "ERC20InsufficientBalance(0x0000000000000000000000000000000000000000, 1, 2)"
+        );
+
+        let other = get_func("InsufficientBalance(address,uint256,uint256)").unwrap();
+        assert!(decode_error(&err, &other).is_none());
+
+        let reason = [&[8, 195, 121, 160][..], &"slippage".to_string().encode()].concat();
+        assert!(!is_unknown_custom_error(&reason, None));
+    }
+
+    #[test]
+    fn test_decode_nested_revert() {
+        #[derive(Debug, Clone, EthError)]
+        struct CallFailed(Address, ethers::types::Bytes);
+        #[derive(Debug, Clone, EthError)]
+        struct Wrapper(ethers::types::Bytes);
+
+        let reason = [&[8, 195, 121, 160][..], &"slippage".to_string().encode()].concat();
+        let err = CallFailed(Address::random(), reason.into()).encode();
+        assert_eq!(decode_revert(&err, None, None).unwrap(), "slippage");
+
+        // unwraps multiple levels
+        let err = Wrapper(err.into()).encode();
+        assert_eq!(decode_nested_revert(&err, None).unwrap(), "slippage");
+
+        let panic = [&[78, 72, 123, 113][..], &U256::from(17u64).encode()].concat();
+        let err = Wrapper(panic.into()).encode();
+        assert_eq!(decode_revert(&err, None, None).unwrap(), "Arithmetic over/underflow");
+
+        let err = Wrapper(vec![1, 2, 3, 4, 5].into()).encode();
+        assert!(decode_nested_revert(&err, None).is_none());
+    }
 }
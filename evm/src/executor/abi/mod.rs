@@ -17,14 +17,23 @@ ethers::contract::abigen!(
     r#"[
             struct Log {bytes32[] topics; bytes data;}
             struct Rpc {string name; string url;}
+            struct StorageAccess {address account; bytes32 slot; bool isWrite; bytes32 previousValue; bytes32 newValue; bool reverted;}
+            struct AccountAccess {uint8 kind; address account; address accessor; bool initialized; uint256 oldBalance; uint256 newBalance; uint256 value; bytes data; bool reverted; StorageAccess[] storageAccesses; uint64 depth;}
+            struct StorageDiff {bytes32 slot; bytes32 previousValue; bytes32 newValue;}
+            struct AccountStateDiff {address account; bool created; uint256 previousBalance; uint256 newBalance; uint64 previousNonce; uint64 newNonce; StorageDiff[] storageDiffs;}
             struct FsMetadata {bool isDir; bool isSymlink; uint256 length; bool readOnly; uint256 modified; uint256 accessed; uint256 created;}
+            struct SignedDelegation {uint8 v; bytes32 r; bytes32 s; uint64 nonce; address implementation;}
             roll(uint256)
             warp(uint256)
+            skip(uint256)
+            rewind(uint256)
             difficulty(uint256)
             fee(uint256)
             coinbase(address)
             store(address,bytes32,bytes32)
             load(address,bytes32)(bytes32)
+            storeTransient(address,bytes32,bytes32)
+            loadTransient(address,bytes32)(bytes32)
             ffi(string[])(bytes)
             setEnv(string,string)
             envBool(string)(bool)
@@ -60,6 +69,9 @@ ethers::contract::abigen!(
             deriveKey(string,uint32)(uint256)
             deriveKey(string,string,uint32)(uint256)
             rememberKey(uint256)(address)
+            signDelegation(address,uint256)(SignedDelegation)
+            attachDelegation(SignedDelegation)
+            signAndAttachDelegation(address,uint256)(SignedDelegation)
             prank(address)
             startPrank(address)
             prank(address,address)
@@ -67,13 +79,27 @@ ethers::contract::abigen!(
             stopPrank()
             deal(address,uint256)
             etch(address,bytes)
+            copyStorage(address,address)
+            cloneAccount(address,address)
             expectRevert()
             expectRevert(bytes)
             expectRevert(bytes4)
+            expectPartialRevert(bytes4)
+            expectRevertContains(string)
             record()
             accesses(address)(bytes32[],bytes32[])
             recordLogs()
             getRecordedLogs()(Log[])
+            startStateDiffRecording()
+            stopAndReturnStateDiff()(AccountAccess[])
+            getStateDiff()(AccountStateDiff[])
+            assertStorageUnchanged(address)
+            assertOnChain(bool,string)
+            assertOnChainEq(uint256,uint256,string)
+            assertOnChainEq(address,address,string)
+            assertOnChainEq(bytes32,bytes32,string)
+            assertOnChainCode(address,string)
+            assertOnChainStorage(address,bytes32,bytes32,string)
             expectEmit()
             expectEmit(address)
             expectEmit(bool,bool,bool,bool)
@@ -85,10 +111,21 @@ ethers::contract::abigen!(
             expectCall(address,uint256,bytes)
             expectCall(address,uint256,uint64,bytes)
             expectCallMinGas(address,uint256,uint64,bytes)
+            expectCallSequence(address[],bytes[])
+            expectSnapshot(string)
             expectSafeMemory(uint64,uint64)
             expectSafeMemoryCall(uint64,uint64)
+            readMemory(uint256,uint256)(bytes)
+            readReturndata()(bytes)
             getCode(string)
+            deployCode(string)(address)
+            deployCode(string,bytes)(address)
+            deployCode(string,bytes,uint256)(address)
+            deployCodeWithHash(string,bytes,uint256)(address,bytes32)
+            deployYul(string)(address)
+            deployYul(string,bytes)(address)
             getDeployedCode(string)
+            getDeployedCode(string,string[],bytes32[])(bytes)
             label(address,string)
             assume(bool)
             setNonce(address,uint64)
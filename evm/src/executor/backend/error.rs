@@ -38,10 +38,12 @@ pub enum DatabaseError {
     GetTransaction(H256, Arc<eyre::Error>),
     #[error("Transaction {0:?} not found")]
     TransactionNotFound(H256),
+    #[error("{0} is not in the RPC cache and can't be fetched in offline mode")]
+    MissingFromCache(String),
     #[error(
-        "CREATE2 Deployer not present on this chain. [0x4e59b44847b379578588920ca78fbf26c0b4956c]"
+        "CREATE2 Deployer not present on this chain. [{0:?}]\nDeploy the canonical factory with `cast create2-deployer` or configure a different one with `--create2-deployer`"
     )]
-    MissingCreate2Deployer,
+    MissingCreate2Deployer(Address),
     #[error(transparent)]
     JoinError(#[from] tokio::task::JoinError),
 }
@@ -200,6 +200,10 @@ impl<'a> DatabaseExt for FuzzBackendWrapper<'a> {
     fn has_cheatcode_access(&self, account: Address) -> bool {
         self.backend.has_cheatcode_access(account)
     }
+
+    fn storage_slots(&self, account: Address) -> Option<Vec<U256>> {
+        self.backend.storage_slots(account)
+    }
 }
 
 impl<'a> DatabaseRef for FuzzBackendWrapper<'a> {
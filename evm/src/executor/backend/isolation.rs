@@ -0,0 +1,273 @@
+use crate::executor::backend::{Backend, LocalForkId};
+use ethers::types::{Address, H256, U256};
+use foundry_common::fmt::UIfmt;
+use revm::db::{CacheDB, DatabaseRef};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// The state of a [Backend] that a test must not mutate beyond its own call.
+///
+/// Everything a test does in its call is discarded afterwards, but cheatcodes like
+/// `vm.selectFork` or `vm.makePersistent` mutate the `Backend` itself. Comparing a snapshot taken
+/// before the test with one taken after it reveals such mutations.
+///
+/// Only state that was written is captured: the database also caches everything that was read,
+/// e.g. from a fork, which is not a mutation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IsolationSnapshot {
+    accounts: WrittenAccounts,
+    storage: WrittenStorage,
+    persistent_accounts: BTreeSet<Address>,
+    cheatcode_access_accounts: BTreeSet<Address>,
+    active_fork: Option<LocalForkId>,
+    forks: usize,
+}
+
+// === impl IsolationSnapshot ===
+
+impl IsolationSnapshot {
+    /// Captures the current state of the backend
+    pub fn capture(backend: &Backend) -> Self {
+        let (accounts, storage) = match backend.active_fork_db() {
+            Some(db) => written_state(db),
+            None => written_state(&backend.mem_db),
+        };
+
+        Self {
+            accounts,
+            storage,
+            persistent_accounts: backend.inner.persistent_accounts.iter().copied().collect(),
+            cheatcode_access_accounts: backend
+                .inner
+                .cheatcode_access_accounts
+                .iter()
+                .copied()
+                .collect(),
+            active_fork: backend.active_fork_ids.map(|(id, _)| id),
+            forks: backend.inner.issued_local_fork_ids.len(),
+        }
+    }
+
+    /// Returns all mutations that happened between `self` and the `after` snapshot
+    pub fn diff(&self, after: &Self) -> Vec<IsolationViolation> {
+        let mut violations = Vec::new();
+
+        let addresses: BTreeSet<_> = self.accounts.keys().chain(after.accounts.keys()).collect();
+        for addr in addresses {
+            let (before, now) = match values(self.accounts.get(addr), after.accounts.get(addr)) {
+                Some(values) => values,
+                None => continue,
+            };
+            if before.0 != now.0 {
+                violations.push(IsolationViolation::Balance {
+                    account: *addr,
+                    before: before.0,
+                    after: now.0,
+                });
+            }
+            if before.1 != now.1 {
+                violations.push(IsolationViolation::Nonce {
+                    account: *addr,
+                    before: before.1,
+                    after: now.1,
+                });
+            }
+            if before.2 != now.2 {
+                violations.push(IsolationViolation::Code { account: *addr });
+            }
+        }
+
+        let empty = BTreeMap::new();
+        let addresses: BTreeSet<_> = self.storage.keys().chain(after.storage.keys()).collect();
+        for addr in addresses {
+            let before = self.storage.get(addr).unwrap_or(&empty);
+            let now = after.storage.get(addr).unwrap_or(&empty);
+            let slots: BTreeSet<_> = before.keys().chain(now.keys()).collect();
+            for slot in slots {
+                let (old, new) = match values(before.get(slot), now.get(slot)) {
+                    Some(values) => values,
+                    None => continue,
+                };
+                if old != new {
+                    violations.push(IsolationViolation::Storage {
+                        account: *addr,
+                        slot: *slot,
+                        before: old,
+                        after: new,
+                    });
+                }
+            }
+        }
+
+        for account in after.persistent_accounts.difference(&self.persistent_accounts) {
+            violations.push(IsolationViolation::Persistent { account: *account, added: true });
+        }
+        for account in self.persistent_accounts.difference(&after.persistent_accounts) {
+            violations.push(IsolationViolation::Persistent { account: *account, added: false });
+        }
+        for account in after.cheatcode_access_accounts.difference(&self.cheatcode_access_accounts) {
+            violations.push(IsolationViolation::CheatcodeAccess { account: *account, added: true });
+        }
+        for account in self.cheatcode_access_accounts.difference(&after.cheatcode_access_accounts) {
+            violations
+                .push(IsolationViolation::CheatcodeAccess { account: *account, added: false });
+        }
+
+        if self.forks != after.forks {
+            violations.push(IsolationViolation::Forks { before: self.forks, after: after.forks });
+        }
+        if self.active_fork != after.active_fork {
+            violations.push(IsolationViolation::ActiveFork {
+                before: self.active_fork,
+                after: after.active_fork,
+            });
+        }
+
+        violations
+    }
+}
+
+/// A mutation of the [Backend] that escaped the revert of a test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsolationViolation {
+    Balance { account: Address, before: U256, after: U256 },
+    Nonce { account: Address, before: u64, after: u64 },
+    Code { account: Address },
+    Storage { account: Address, slot: U256, before: U256, after: U256 },
+    Persistent { account: Address, added: bool },
+    CheatcodeAccess { account: Address, added: bool },
+    Forks { before: usize, after: usize },
+    ActiveFork { before: Option<LocalForkId>, after: Option<LocalForkId> },
+}
+
+impl fmt::Display for IsolationViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fork = |id: &Option<LocalForkId>| {
+            id.map(|id| id.to_string()).unwrap_or_else(|| "none".to_string())
+        };
+        match self {
+            IsolationViolation::Balance { account, before, after } => {
+                write!(f, "balance of {}: {before} -> {after}", account.pretty())
+            }
+            IsolationViolation::Nonce { account, before, after } => {
+                write!(f, "nonce of {}: {before} -> {after}", account.pretty())
+            }
+            IsolationViolation::Code { account } => {
+                write!(f, "code of {} changed", account.pretty())
+            }
+            IsolationViolation::Storage { account, slot, before, after } => {
+                write!(
+                    f,
+                    "storage of {} at slot {}: {} -> {}",
+                    account.pretty(),
+                    slot.pretty(),
+                    before.pretty(),
+                    after.pretty()
+                )
+            }
+            IsolationViolation::Persistent { account, added } => {
+                let action = if *added { "marked" } else { "unmarked" };
+                write!(f, "{} was {action} as persistent", account.pretty())
+            }
+            IsolationViolation::CheatcodeAccess { account, added } => {
+                let action = if *added { "granted" } else { "revoked" };
+                write!(f, "cheatcode access of {} was {action}", account.pretty())
+            }
+            IsolationViolation::Forks { before, after } => {
+                write!(f, "number of forks: {before} -> {after}")
+            }
+            IsolationViolation::ActiveFork { before, after } => {
+                write!(f, "active fork: {} -> {}", fork(before), fork(after))
+            }
+        }
+    }
+}
+
+/// The balance, nonce and code hash of an account
+type AccountFields = (U256, u64, H256);
+
+type WrittenAccounts = BTreeMap<Address, Written<AccountFields>>;
+type WrittenStorage = BTreeMap<Address, BTreeMap<U256, Written<U256>>>;
+
+/// A value of the database that differs from the value in the database it caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Written<T> {
+    original: T,
+    current: T,
+}
+
+/// Returns the values before and after of state that was written in either snapshot
+///
+/// State that isn't written in a snapshot has its original value there.
+fn values<T: Copy>(before: Option<&Written<T>>, after: Option<&Written<T>>) -> Option<(T, T)> {
+    let old = before.map(|w| w.current).or_else(|| after.map(|w| w.original))?;
+    let new = after.map(|w| w.current).or_else(|| before.map(|w| w.original))?;
+    Some((old, new))
+}
+
+/// Returns the accounts and storage slots of the database that differ from the database it caches
+///
+/// Everything that was only read is cached with the value of the underlying database, which is
+/// served from memory, since reading the value in the first place cached it there as well.
+fn written_state<ExtDB: DatabaseRef>(db: &CacheDB<ExtDB>) -> (WrittenAccounts, WrittenStorage) {
+    let mut accounts = WrittenAccounts::new();
+    let mut storage = WrittenStorage::new();
+    for (addr, account) in db.accounts.iter() {
+        let original = db.db.basic(*addr).ok().flatten().unwrap_or_default();
+        let original = (original.balance, original.nonce, original.code_hash);
+        let current = (account.info.balance, account.info.nonce, account.info.code_hash);
+        if original != current {
+            accounts.insert(*addr, Written { original, current });
+        }
+
+        let slots: BTreeMap<_, _> = account
+            .storage
+            .iter()
+            .filter_map(|(slot, current)| {
+                let original = db.db.storage(*addr, *slot).unwrap_or_default();
+                (original != *current).then_some((*slot, Written { original, current: *current }))
+            })
+            .collect();
+        if !slots.is_empty() {
+            storage.insert(*addr, slots);
+        }
+    }
+    (accounts, storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::backend::DatabaseExt;
+    use revm::AccountInfo;
+
+    #[test]
+    fn detects_escaped_mutations() {
+        let mut backend = Backend::spawn(None);
+        let account = Address::random();
+        let before = IsolationSnapshot::capture(&backend);
+        assert!(before.diff(&IsolationSnapshot::capture(&backend)).is_empty());
+
+        backend.insert_account_info(account, AccountInfo::default());
+        backend.insert_account_storage(account, 0u64.into(), U256::zero()).unwrap();
+        assert!(before.diff(&IsolationSnapshot::capture(&backend)).is_empty());
+
+        backend.insert_account_storage(account, 1u64.into(), 2u64.into()).unwrap();
+        backend.add_persistent_account(account);
+        let violations = before.diff(&IsolationSnapshot::capture(&backend));
+        assert_eq!(
+            violations,
+            vec![
+                IsolationViolation::Storage {
+                    account,
+                    slot: 1u64.into(),
+                    before: U256::zero(),
+                    after: 2u64.into()
+                },
+                IsolationViolation::Persistent { account, added: true },
+            ]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::executor::{
+    backend::{diagnostic::RevertDiagnostic, error::DatabaseError, DatabaseExt, LocalForkId},
+    fork::{CreateFork, ForkId},
+    inspector::cheatcodes::{util::with_journaled_account, Cheatcodes},
+};
+use ethers::types::{Address, H256, U256};
+use hashbrown::HashMap as Map;
+use revm::{Account, AccountInfo, Bytecode, Database, Env, JournaledState};
+
+/// A database that reads the accounts and storage slots the ongoing transaction already loaded
+/// from its journaled state, so a nested execution sees all changes made so far
+///
+/// All other operations are forwarded to the underlying database.
+pub struct JournaledDb<'a, DB> {
+    journaled_state: &'a JournaledState,
+    db: &'a mut DB,
+}
+
+impl<'a, DB> JournaledDb<'a, DB> {
+    pub fn new(journaled_state: &'a JournaledState, db: &'a mut DB) -> Self {
+        Self { journaled_state, db }
+    }
+}
+
+impl<'a, DB: DatabaseExt> Database for JournaledDb<'a, DB> {
+    type Error = DatabaseError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.journaled_state.state.get(&address) {
+            return Ok(Some(account.info.clone()))
+        }
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(slot) =
+            self.journaled_state.state.get(&address).and_then(|acc| acc.storage.get(&index))
+        {
+            return Ok(slot.present_value())
+        }
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<H256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl<'a, DB: DatabaseExt> DatabaseExt for JournaledDb<'a, DB> {
+    fn snapshot(&mut self, journaled_state: &JournaledState, env: &Env) -> U256 {
+        self.db.snapshot(journaled_state, env)
+    }
+
+    fn revert(
+        &mut self,
+        id: U256,
+        journaled_state: &JournaledState,
+        current: &mut Env,
+    ) -> Option<JournaledState> {
+        self.db.revert(id, journaled_state, current)
+    }
+
+    fn create_fork(&mut self, fork: CreateFork) -> eyre::Result<LocalForkId> {
+        self.db.create_fork(fork)
+    }
+
+    fn create_fork_at_transaction(
+        &mut self,
+        fork: CreateFork,
+        transaction: H256,
+    ) -> eyre::Result<LocalForkId> {
+        self.db.create_fork_at_transaction(fork, transaction)
+    }
+
+    fn select_fork(
+        &mut self,
+        id: LocalForkId,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+    ) -> eyre::Result<()> {
+        self.db.select_fork(id, env, journaled_state)
+    }
+
+    fn roll_fork(
+        &mut self,
+        id: Option<LocalForkId>,
+        block_number: U256,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+    ) -> eyre::Result<()> {
+        self.db.roll_fork(id, block_number, env, journaled_state)
+    }
+
+    fn roll_fork_to_transaction(
+        &mut self,
+        id: Option<LocalForkId>,
+        transaction: H256,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+    ) -> eyre::Result<()> {
+        self.db.roll_fork_to_transaction(id, transaction, env, journaled_state)
+    }
+
+    fn transact(
+        &mut self,
+        id: Option<LocalForkId>,
+        transaction: H256,
+        env: &mut Env,
+        journaled_state: &mut JournaledState,
+        cheatcodes_inspector: Option<&mut Cheatcodes>,
+    ) -> eyre::Result<()> {
+        self.db.transact(id, transaction, env, journaled_state, cheatcodes_inspector)
+    }
+
+    fn active_fork_id(&self) -> Option<LocalForkId> {
+        self.db.active_fork_id()
+    }
+
+    fn active_fork_url(&self) -> Option<String> {
+        self.db.active_fork_url()
+    }
+
+    fn ensure_fork(&self, id: Option<LocalForkId>) -> eyre::Result<LocalForkId> {
+        self.db.ensure_fork(id)
+    }
+
+    fn ensure_fork_id(&self, id: LocalForkId) -> eyre::Result<&ForkId> {
+        self.db.ensure_fork_id(id)
+    }
+
+    fn diagnose_revert(
+        &self,
+        callee: Address,
+        journaled_state: &JournaledState,
+    ) -> Option<RevertDiagnostic> {
+        self.db.diagnose_revert(callee, journaled_state)
+    }
+
+    fn is_persistent(&self, acc: &Address) -> bool {
+        self.db.is_persistent(acc)
+    }
+
+    fn remove_persistent_account(&mut self, account: &Address) -> bool {
+        self.db.remove_persistent_account(account)
+    }
+
+    fn add_persistent_account(&mut self, account: Address) -> bool {
+        self.db.add_persistent_account(account)
+    }
+
+    fn allow_cheatcode_access(&mut self, account: Address) -> bool {
+        self.db.allow_cheatcode_access(account)
+    }
+
+    fn revoke_cheatcode_access(&mut self, account: Address) -> bool {
+        self.db.revoke_cheatcode_access(account)
+    }
+
+    fn has_cheatcode_access(&self, account: Address) -> bool {
+        self.db.has_cheatcode_access(account)
+    }
+
+    fn storage_slots(&self, account: Address) -> Option<Vec<U256>> {
+        journaled_storage_slots(self.journaled_state, &*self.db, account)
+    }
+}
+
+/// Returns all storage slots of the account, including the slots the ongoing transaction loaded
+/// into its journaled state, or `None` if they can't be enumerated, see
+/// [`DatabaseExt::storage_slots`]
+pub fn journaled_storage_slots<DB: DatabaseExt>(
+    journaled_state: &JournaledState,
+    db: &DB,
+    account: Address,
+) -> Option<Vec<U256>> {
+    let journaled = journaled_state.state.get(&account);
+    // all slots of an account whose storage was cleared by the transaction are journaled
+    let mut slots = match journaled {
+        Some(acc) if acc.storage_cleared => vec![],
+        _ => db.storage_slots(account)?,
+    };
+    if let Some(acc) = journaled {
+        for slot in acc.storage.keys() {
+            if !slots.contains(slot) {
+                slots.push(*slot);
+            }
+        }
+    }
+    Some(slots)
+}
+
+/// Applies the changes of a nested execution to the journaled state of the ongoing transaction
+///
+/// The changes are journaled, so they are rolled back if the ongoing call reverts.
+pub fn apply_to_journal<DB: DatabaseExt>(
+    journaled_state: &mut JournaledState,
+    db: &mut DB,
+    changes: Map<Address, Account>,
+) -> Result<(), DatabaseError> {
+    for (address, account) in changes {
+        let code = with_journaled_account(journaled_state, db, address, |journaled| {
+            journaled.info.balance = account.info.balance;
+            journaled.info.nonce = account.info.nonce;
+            if journaled.info.code_hash != account.info.code_hash {
+                account.info.code.clone()
+            } else {
+                None
+            }
+        })?;
+        if let Some(code) = code {
+            journaled_state.set_code(address, code);
+        }
+
+        for (slot, value) in account.storage {
+            if value.original_value() != value.present_value() {
+                journaled_state.sstore(address, slot, value.present_value(), db)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::{
     abi::CHEATCODE_ADDRESS,
     executor::{
-        backend::snapshot::BackendSnapshot,
+        backend::snapshot::{BackendSnapshot, StateSnapshot},
         fork::{CreateFork, ForkId, MultiFork, SharedBackend},
         inspector::{cheatcodes::Cheatcodes, DEFAULT_CREATE2_DEPLOYER},
         snapshot::Snapshots,
@@ -16,7 +16,7 @@ use ethers::{
 use hashbrown::HashMap as Map;
 pub use in_memory_db::MemDb;
 use revm::{
-    db::{CacheDB, DatabaseRef},
+    db::{AccountState, CacheDB, DatabaseRef},
     precompiles::Precompiles,
     Account, AccountInfo, Bytecode, CreateScheme, Database, DatabaseCommit, Env, ExecutionResult,
     Inspector, JournaledState, Log, SpecId, TransactTo, EVM, KECCAK_EMPTY,
@@ -27,9 +27,13 @@ use tracing::{trace, warn};
 mod fuzz;
 pub mod snapshot;
 pub use fuzz::FuzzBackendWrapper;
+mod journaled;
+pub use journaled::{apply_to_journal, journaled_storage_slots, JournaledDb};
 mod diagnostic;
 
 pub use diagnostic::RevertDiagnostic;
+mod isolation;
+pub use isolation::{IsolationSnapshot, IsolationViolation};
 
 pub mod error;
 use crate::executor::{
@@ -272,6 +276,14 @@ pub trait DatabaseExt: Database<Error = DatabaseError> {
     /// Returns `true` if the given account is allowed to execute cheatcodes
     fn has_cheatcode_access(&self, account: Address) -> bool;
 
+    /// Returns all storage slots of the account in the active database, or `None` if they can't
+    /// be enumerated
+    ///
+    /// In forking mode the storage of a remote account is fetched on demand and can't be
+    /// enumerated, so this is `None` unless the account doesn't exist remotely or its storage was
+    /// cleared, e.g. because it was created locally.
+    fn storage_slots(&self, account: Address) -> Option<Vec<U256>>;
+
     /// Ensures that `account` is allowed to execute cheatcodes
     ///
     /// Returns an error if [`Self::has_cheatcode_access`] returns `false`
@@ -454,6 +466,56 @@ impl Backend {
         }
     }
 
+    /// Sets the value of a storage slot of the account in the currently active database
+    pub fn insert_account_storage(
+        &mut self,
+        address: H160,
+        slot: U256,
+        value: U256,
+    ) -> DatabaseResult<()> {
+        if let Some(db) = self.active_fork_db_mut() {
+            db.insert_account_storage(address, slot, value)?;
+        } else {
+            self.mem_db.insert_account_storage(address, slot, value)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the entire storage of the account in the currently active database
+    pub fn replace_account_storage(
+        &mut self,
+        address: H160,
+        storage: Map<U256, U256>,
+    ) -> DatabaseResult<()> {
+        if let Some(db) = self.active_fork_db_mut() {
+            db.replace_account_storage(address, storage)?;
+        } else {
+            self.mem_db.replace_account_storage(address, storage)?;
+        }
+        Ok(())
+    }
+
+    /// Returns all accounts, their storage and the block hashes of the currently active database.
+    ///
+    /// In forking mode this only contains the state that was fetched from the fork or modified
+    pub fn dump_state(&self) -> StateSnapshot {
+        if let Some(db) = self.active_fork_db() {
+            dump_db_state(db)
+        } else {
+            dump_db_state(&self.mem_db)
+        }
+    }
+
+    /// Inserts all accounts, their storage and the block hashes of the given state into the
+    /// currently active database, see also [`Self::dump_state()`]
+    pub fn load_state(&mut self, state: StateSnapshot) {
+        if let Some(db) = self.active_fork_db_mut() {
+            load_db_state(db, state)
+        } else {
+            load_db_state(&mut self.mem_db, state)
+        }
+    }
+
     /// Returns all snapshots created in this backend
     pub fn snapshots(&self) -> &Snapshots<BackendSnapshot<BackendDatabaseSnapshot>> {
         &self.inner.snapshots
@@ -1203,6 +1265,25 @@ impl DatabaseExt for Backend {
     fn has_cheatcode_access(&self, account: Address) -> bool {
         self.inner.cheatcode_access_accounts.contains(&account)
     }
+
+    fn storage_slots(&self, account: Address) -> Option<Vec<U256>> {
+        if let Some(db) = self.active_fork_db() {
+            let acc = db.accounts.get(&account)?;
+            return match acc.account_state {
+                AccountState::NotExisting | AccountState::StorageCleared => {
+                    Some(acc.storage.keys().copied().collect())
+                }
+                AccountState::Touched | AccountState::None => None,
+            }
+        }
+        Some(
+            self.mem_db
+                .accounts
+                .get(&account)
+                .map(|acc| acc.storage.keys().copied().collect())
+                .unwrap_or_default(),
+        )
+    }
 }
 
 impl DatabaseRef for Backend {
@@ -1677,6 +1758,37 @@ fn merge_db_account_data<ExtDB: DatabaseRef>(
     fork_db.accounts.insert(addr, acc);
 }
 
+/// Returns the accounts, storage and block hashes of the given database
+fn dump_db_state<ExtDB: DatabaseRef>(db: &CacheDB<ExtDB>) -> StateSnapshot {
+    let mut state = StateSnapshot::default();
+    for (addr, acc) in db.accounts.iter() {
+        let mut info = acc.info.clone();
+        if info.code.is_none() {
+            info.code = db.contracts.get(&info.code_hash).cloned();
+        }
+        state.accounts.insert(*addr, info);
+        if !acc.storage.is_empty() {
+            state.storage.insert(*addr, acc.storage.iter().map(|(k, v)| (*k, *v)).collect());
+        }
+    }
+    state.block_hashes.extend(db.block_hashes.iter().map(|(k, v)| (*k, *v)));
+    state
+}
+
+/// Inserts the accounts, storage and block hashes of the given state into the database
+fn load_db_state<ExtDB: DatabaseRef>(db: &mut CacheDB<ExtDB>, state: StateSnapshot) {
+    let StateSnapshot { accounts, storage, block_hashes } = state;
+    for (addr, info) in accounts {
+        db.insert_account_info(addr, info);
+    }
+    for (addr, slots) in storage {
+        if let Some(acc) = db.accounts.get_mut(&addr) {
+            acc.storage.extend(slots);
+        }
+    }
+    db.block_hashes.extend(block_hashes);
+}
+
 /// Returns true of the address is a contract
 fn is_contract_in_state(journaled_state: &JournaledState, acc: Address) -> bool {
     journaled_state
@@ -4,7 +4,7 @@ use revm::{AccountInfo, Env, JournaledState};
 use serde::{Deserialize, Serialize};
 
 /// A minimal abstraction of a state at a certain point in time
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub struct StateSnapshot {
     pub accounts: Map<Address, AccountInfo>,
     pub storage: Map<Address, Map<U256, U256>>,
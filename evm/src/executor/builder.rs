@@ -1,5 +1,5 @@
 use super::{
-    inspector::{Cheatcodes, Fuzzer, InspectorStackConfig},
+    inspector::{Cheatcodes, Fuzzer, GasOverrides, InspectorPluginFactory, InspectorStackConfig},
     Executor,
 };
 use crate::{
@@ -8,6 +8,7 @@ use crate::{
 };
 use ethers::types::U256;
 use revm::{Env, SpecId};
+use std::{sync::Arc, time::Duration};
 
 /// The builder that allows to configure an evm [`Executor`] which a stack of optional
 /// [`revm::Inspector`]s, such as [`Cheatcodes`]
@@ -40,6 +41,14 @@ impl ExecutorBuilder {
         self
     }
 
+    /// Enables or disables recording the jumps of traced calls, to reconstruct internal function
+    /// calls in traces
+    #[must_use]
+    pub fn set_decode_internal(mut self, enable: bool) -> Self {
+        self.inspector_config.decode_internal = enable;
+        self
+    }
+
     /// Enables or disables the debugger
     #[must_use]
     pub fn set_debugger(mut self, enable: bool) -> Self {
@@ -104,6 +113,46 @@ impl ExecutorBuilder {
         self
     }
 
+    /// Sets the time budget of a test, see [Executor::start_timeout()]
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.inspector_config.timeout = timeout;
+        self
+    }
+
+    /// Applies a custom gas schedule, if it overrides anything
+    #[must_use]
+    pub fn with_gas_overrides(mut self, gas_overrides: GasOverrides) -> Self {
+        self.inspector_config.gas_overrides =
+            if gas_overrides.is_empty() { None } else { Some(gas_overrides) };
+        self
+    }
+
+    /// Executes every call the test contract makes as a separate transaction
+    #[must_use]
+    pub fn set_isolate(mut self, enable: bool) -> Self {
+        self.inspector_config.isolate = enable;
+        self
+    }
+
+    /// Adds an inspector provided by a downstream crate, which is called after all built-in
+    /// inspectors
+    #[must_use]
+    pub fn with_inspector_plugin(mut self, plugin: Arc<dyn InspectorPluginFactory>) -> Self {
+        self.inspector_config.plugins.push(plugin);
+        self
+    }
+
+    /// Adds all of the given inspector plugins, see [Self::with_inspector_plugin]
+    #[must_use]
+    pub fn with_inspector_plugins(
+        mut self,
+        plugins: impl IntoIterator<Item = Arc<dyn InspectorPluginFactory>>,
+    ) -> Self {
+        self.inspector_config.plugins.extend(plugins);
+        self
+    }
+
     /// Builds the executor as configured.
     pub fn build(self, db: Backend) -> Executor {
         let gas_limit = self.gas_limit.unwrap_or(self.env.block.gas_limit);
@@ -0,0 +1,292 @@
+//! [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) delegations of externally owned accounts
+//!
+//! `revm` does not know set code transactions, so delegations are emulated: an authorization sets
+//! the code of its authority to the delegation designator `0xef0100 ++ address`, and calls to an
+//! account with a designator execute the code of the delegate in the context of the account, see
+//! [resolve_delegation].
+
+use crate::executor::inspector::cheatcodes::util::with_journaled_account;
+use ethers::{
+    types::{Address, Bytes, Signature, SignatureError, H256, U256, U64},
+    utils::{
+        keccak256, rlp,
+        rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream},
+    },
+};
+use revm::{Bytecode, CallInputs, Database, EVMData};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// The EIP-2718 type of set code transactions
+pub const SET_CODE_TX_TYPE: u8 = 0x04;
+
+/// The prefix of the message that is signed by the authority of an authorization
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// The code of a delegating account is this prefix followed by the address of the delegate
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// The intrinsic gas that a set code transaction is charged per authorization
+pub const PER_AUTHORIZATION_GAS: u64 = 25_000;
+
+/// Returns the code of an account that delegates to `delegate`
+pub fn delegation_code(delegate: Address) -> Bytes {
+    DELEGATION_PREFIX.iter().chain(delegate.as_bytes()).copied().collect::<Vec<_>>().into()
+}
+
+/// Returns the delegate if the code is a delegation designator
+pub fn delegated_address(code: &[u8]) -> Option<Address> {
+    (code.len() == DELEGATION_PREFIX.len() + 20 && code.starts_with(&DELEGATION_PREFIX))
+        .then(|| Address::from_slice(&code[DELEGATION_PREFIX.len()..]))
+}
+
+/// Executes the code of the delegate if the callee of the call delegates to it
+///
+/// The address, balance and storage of the call stay the ones of the delegating account. Like in
+/// EIP-7702 only a single delegation is followed.
+pub fn resolve_delegation<DB: Database>(data: &mut EVMData<'_, DB>, call: &mut CallInputs) {
+    let delegate = match data.journaled_state.load_code(call.contract, data.db) {
+        Ok((account, _)) => account
+            .info
+            .code
+            .as_ref()
+            .and_then(|code| delegated_address(&code.bytes()[..code.len()])),
+        // the evm reports the error once it loads the account itself
+        Err(_) => None,
+    };
+    if let Some(delegate) = delegate {
+        call.contract = delegate;
+    }
+}
+
+/// Applies the authorizations of a set code transaction when its top level call is entered and
+/// charges their intrinsic gas from the gas limit of the call
+///
+/// The authorities are changed in the journaled state before the checkpoint of the call is
+/// created, so the delegations are committed with the transaction even if the call reverts.
+/// Invalid authorizations are skipped, they don't invalidate the transaction.
+pub fn apply_authorizations<DB: Database>(
+    data: &mut EVMData<'_, DB>,
+    call: &mut CallInputs,
+    authorization_list: &[SignedAuthorization],
+) {
+    let intrinsic_gas = PER_AUTHORIZATION_GAS * authorization_list.len() as u64;
+    call.gas_limit = call.gas_limit.saturating_sub(intrinsic_gas);
+
+    let chain_id = data.env.cfg.chain_id.as_u64();
+    for authorization in authorization_list {
+        let authority = match authorization.recover_authority() {
+            Ok(authority) => authority,
+            Err(err) => {
+                trace!(?err, "skipping authorization");
+                continue
+            }
+        };
+        // the nonce of the sender was already incremented by the evm
+        let (nonce, code) = match data.journaled_state.load_code(authority, data.db) {
+            Ok((account, _)) => (
+                account.info.nonce,
+                account.info.code.as_ref().map(|code| code.bytes()[..code.len()].to_vec()),
+            ),
+            // the evm reports the error once it loads the account itself
+            Err(_) => continue,
+        };
+        if let Err(err) =
+            authorization.validate(chain_id, authority, nonce, code.as_deref().unwrap_or_default())
+        {
+            trace!(%err, "skipping authorization");
+            continue
+        }
+
+        if with_journaled_account(&mut data.journaled_state, data.db, authority, |account| {
+            account.info.nonce += 1;
+        })
+        .is_err()
+        {
+            continue
+        }
+        let code = match authorization.delegation_code() {
+            code if code.is_empty() => Bytecode::new(),
+            code => Bytecode::new_raw(code.0).to_checked(),
+        };
+        data.journaled_state.set_code(authority, code);
+    }
+}
+
+/// Reasons an authorization is not applied
+#[derive(Debug, thiserror::Error)]
+pub enum AuthorizationError {
+    #[error("authorization is for chain {0}")]
+    ChainId(U256),
+    #[error("invalid authorization signature: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("authority {0:?} has code and can't delegate")]
+    NotEoa(Address),
+    #[error("authorization nonce {got} does not match the nonce {expected} of {authority:?}")]
+    Nonce { authority: Address, expected: u64, got: U64 },
+}
+
+/// An unsigned authorization to delegate the authority to `address`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    /// The chain the authorization is valid on, or `0` for all chains
+    pub chain_id: U256,
+    /// The delegate, the zero address clears the delegation
+    pub address: Address,
+    /// The nonce of the authority
+    pub nonce: U64,
+}
+
+impl Authorization {
+    /// Returns the hash that is signed by the authority:
+    /// `keccak256(0x05 || rlp([chain_id, address, nonce]))`
+    pub fn signature_hash(&self) -> H256 {
+        let mut encoded = vec![AUTHORIZATION_MAGIC];
+        encoded.extend_from_slice(&rlp::encode(self));
+        keccak256(encoded).into()
+    }
+
+    /// Attaches the signature of the authority
+    pub fn into_signed(self, signature: Signature) -> SignedAuthorization {
+        let y_parity = match signature.v {
+            0 | 1 => signature.v,
+            27 | 28 => signature.v - 27,
+            v => (v - 1) % 2,
+        };
+        SignedAuthorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+            y_parity: y_parity.into(),
+            r: signature.r,
+            s: signature.s,
+        }
+    }
+}
+
+impl Encodable for Authorization {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(3);
+        s.append(&self.chain_id);
+        s.append(&self.address);
+        s.append(&self.nonce);
+    }
+}
+
+/// An entry of the authorization list of a set code transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedAuthorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U64,
+    pub y_parity: U64,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    /// Returns the authorization without its signature
+    pub fn authorization(&self) -> Authorization {
+        Authorization { chain_id: self.chain_id, address: self.address, nonce: self.nonce }
+    }
+
+    pub fn signature(&self) -> Signature {
+        Signature { r: self.r, s: self.s, v: self.y_parity.as_u64() + 27 }
+    }
+
+    /// Recovers the account that signed the authorization
+    pub fn recover_authority(&self) -> Result<Address, SignatureError> {
+        self.signature().recover(self.authorization().signature_hash())
+    }
+
+    /// Checks the authorization against the chain and the current nonce and code of its authority
+    pub fn validate(
+        &self,
+        chain_id: u64,
+        authority: Address,
+        nonce: u64,
+        code: &[u8],
+    ) -> Result<(), AuthorizationError> {
+        if !self.chain_id.is_zero() && self.chain_id != chain_id.into() {
+            return Err(AuthorizationError::ChainId(self.chain_id))
+        }
+        if !code.is_empty() && delegated_address(code).is_none() {
+            return Err(AuthorizationError::NotEoa(authority))
+        }
+        if self.nonce != nonce.into() {
+            return Err(AuthorizationError::Nonce { authority, expected: nonce, got: self.nonce })
+        }
+        Ok(())
+    }
+
+    /// Returns the code of the authority once the authorization is applied
+    pub fn delegation_code(&self) -> Bytes {
+        if self.address.is_zero() {
+            Bytes::default()
+        } else {
+            delegation_code(self.address)
+        }
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(6);
+        s.append(&self.chain_id);
+        s.append(&self.address);
+        s.append(&self.nonce);
+        s.append(&self.y_parity);
+        s.append(&self.r);
+        s.append(&self.s);
+    }
+}
+
+impl Decodable for SignedAuthorization {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 6 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+        Ok(Self {
+            chain_id: rlp.val_at(0)?,
+            address: rlp.val_at(1)?,
+            nonce: rlp.val_at(2)?,
+            y_parity: rlp.val_at(3)?,
+            r: rlp.val_at(4)?,
+            s: rlp.val_at(5)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+
+    #[test]
+    fn can_sign_and_recover_authorization() {
+        let wallet: LocalWallet =
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80".parse().unwrap();
+        let delegate = Address::random();
+        let authorization =
+            Authorization { chain_id: 31337.into(), address: delegate, nonce: 1.into() };
+        let signature = wallet.sign_hash(authorization.signature_hash()).unwrap();
+        let signed = authorization.into_signed(signature);
+        assert!(signed.y_parity.as_u64() <= 1);
+        assert_eq!(signed.recover_authority().unwrap(), wallet.address());
+
+        let decoded: SignedAuthorization = rlp::decode(&rlp::encode(&signed)).unwrap();
+        assert_eq!(decoded, signed);
+
+        let authority = wallet.address();
+        assert!(signed.validate(31337, authority, 1, &[]).is_ok());
+        assert!(signed.validate(31337, authority, 1, &delegation_code(Address::random())).is_ok());
+        assert!(signed.validate(1, authority, 1, &[]).is_err());
+        assert!(signed.validate(31337, authority, 0, &[]).is_err());
+        assert!(signed.validate(31337, authority, 1, &[0x60, 0x00]).is_err());
+
+        assert_eq!(delegated_address(&signed.delegation_code()), Some(delegate));
+        assert_eq!(delegated_address(&[0xef, 0x01, 0x00]), None);
+    }
+}
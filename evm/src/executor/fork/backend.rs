@@ -15,16 +15,17 @@ use futures::{
     task::{Context, Poll},
     Future, FutureExt,
 };
+use parking_lot::Mutex;
 use revm::{db::DatabaseRef, AccountInfo, Bytecode, KECCAK_EMPTY};
 use std::{
-    collections::{hash_map::Entry, HashMap, VecDeque},
+    collections::{hash_map::Entry, BTreeSet, HashMap, VecDeque},
     pin::Pin,
     sync::{
         mpsc::{channel as oneshot_channel, Sender as OneshotSender},
         Arc,
     },
 };
-use tracing::{error, trace, warn};
+use tracing::{error, info_span, trace, warn, Instrument};
 
 // Various future/request type aliases
 
@@ -32,15 +33,10 @@ type AccountFuture<Err> =
     Pin<Box<dyn Future<Output = (Result<(U256, U256, Bytes), Err>, Address)> + Send>>;
 type StorageFuture<Err> = Pin<Box<dyn Future<Output = (Result<U256, Err>, Address, U256)> + Send>>;
 type BlockHashFuture<Err> = Pin<Box<dyn Future<Output = (Result<H256, Err>, u64)> + Send>>;
-type FullBlockFuture<Err> = Pin<
-    Box<
-        dyn Future<Output = (FullBlockSender, Result<Option<Block<Transaction>>, Err>, BlockId)>
-            + Send,
-    >,
->;
-type TransactionFuture<Err> = Pin<
-    Box<dyn Future<Output = (TransactionSender, Result<Option<Transaction>, Err>, H256)> + Send>,
->;
+type FullBlockFuture<Err> =
+    Pin<Box<dyn Future<Output = (Result<Option<Block<Transaction>>, Err>, BlockId)> + Send>>;
+type TransactionFuture<Err> =
+    Pin<Box<dyn Future<Output = (Result<Option<Transaction>, Err>, H256)> + Send>>;
 
 type AccountInfoSender = OneshotSender<DatabaseResult<AccountInfo>>;
 type StorageSender = OneshotSender<DatabaseResult<U256>>;
@@ -48,6 +44,9 @@ type BlockHashSender = OneshotSender<DatabaseResult<H256>>;
 type FullBlockSender = OneshotSender<DatabaseResult<Block<Transaction>>>;
 type TransactionSender = OneshotSender<DatabaseResult<Transaction>>;
 
+/// The keys that were requested from an offline backend but are not in the cache
+type MissingKeys = Arc<Mutex<BTreeSet<String>>>;
+
 /// Request variants that are executed by the provider
 enum ProviderRequest<Err> {
     Account(AccountFuture<Err>),
@@ -91,6 +90,10 @@ pub struct BackendHandler<M: Middleware> {
     storage_requests: HashMap<(Address, U256), Vec<StorageSender>>,
     /// Listeners that wait for a `get_block` response
     block_requests: HashMap<u64, Vec<BlockHashSender>>,
+    /// Listeners that wait for a `get_block_with_txs` response
+    full_block_requests: HashMap<BlockId, Vec<FullBlockSender>>,
+    /// Listeners that wait for a `get_transaction` response
+    transaction_requests: HashMap<H256, Vec<TransactionSender>>,
     /// Incoming commands.
     incoming: Receiver<BackendRequest>,
     /// unprocessed queued requests
@@ -98,6 +101,13 @@ pub struct BackendHandler<M: Middleware> {
     /// The block to fetch data from.
     // This is an `Option` so that we can have less code churn in the functions below
     block_id: Option<BlockId>,
+    /// Whether values that are not in the cache are rejected instead of fetched
+    offline: bool,
+    /// All values that were rejected in offline mode
+    missing: MissingKeys,
+    /// Whether the info of an account is fetched along with the first storage slot requested for
+    /// it
+    prefetch_accounts: bool,
 }
 
 impl<M> BackendHandler<M>
@@ -109,6 +119,7 @@ where
         db: BlockchainDb,
         rx: Receiver<BackendRequest>,
         block_id: Option<BlockId>,
+        missing: MissingKeys,
     ) -> Self {
         Self {
             provider,
@@ -117,10 +128,49 @@ where
             account_requests: Default::default(),
             storage_requests: Default::default(),
             block_requests: Default::default(),
+            full_block_requests: Default::default(),
+            transaction_requests: Default::default(),
             queued_requests: Default::default(),
             incoming: rx,
             block_id,
+            offline: false,
+            missing,
+            prefetch_accounts: false,
+        }
+    }
+
+    /// Sets whether values that are not in the cache should be rejected with
+    /// [DatabaseError::MissingFromCache] instead of being fetched from the provider.
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Sets whether the info of an account should be fetched as soon as a storage slot of the
+    /// account is requested, since it is most likely needed as well.
+    #[must_use]
+    pub fn with_prefetch_accounts(mut self, prefetch_accounts: bool) -> Self {
+        self.prefetch_accounts = prefetch_accounts;
+        self
+    }
+
+    /// Rejects the request for `key` if in offline mode.
+    ///
+    /// Returns the sender back if the value should be fetched.
+    fn reject_offline<T>(
+        &self,
+        key: impl FnOnce() -> String,
+        sender: OneshotSender<DatabaseResult<T>>,
+    ) -> Option<OneshotSender<DatabaseResult<T>>> {
+        if !self.offline {
+            return Some(sender)
         }
+        let key = key();
+        trace!(target: "backendhandler", %key, "rejecting request in offline mode");
+        self.missing.lock().insert(key.clone());
+        let _ = sender.send(Err(DatabaseError::MissingFromCache(key)));
+        None
     }
 
     /// handle the request in queue in the future.
@@ -136,7 +186,9 @@ where
                 let acc = self.db.accounts().read().get(&addr).cloned();
                 if let Some(basic) = acc {
                     let _ = sender.send(Ok(basic));
-                } else {
+                } else if let Some(sender) =
+                    self.reject_offline(|| format!("account {addr:?}"), sender)
+                {
                     self.request_account(addr, sender);
                 }
             }
@@ -144,15 +196,23 @@ where
                 let hash = self.db.block_hashes().read().get(&U256::from(number)).cloned();
                 if let Some(hash) = hash {
                     let _ = sender.send(Ok(hash));
-                } else {
+                } else if let Some(sender) =
+                    self.reject_offline(|| format!("block hash {number}"), sender)
+                {
                     self.request_hash(number, sender);
                 }
             }
             BackendRequest::FullBlock(number, sender) => {
-                self.request_full_block(number, sender);
+                // full blocks and transactions are never cached
+                if let Some(sender) = self.reject_offline(|| format!("block {number:?}"), sender) {
+                    self.request_full_block(number, sender);
+                }
             }
             BackendRequest::Transaction(tx, sender) => {
-                self.request_transaction(tx, sender);
+                if let Some(sender) = self.reject_offline(|| format!("transaction {tx:?}"), sender)
+                {
+                    self.request_transaction(tx, sender);
+                }
             }
             BackendRequest::Storage(addr, idx, sender) => {
                 // account is already stored in the cache
@@ -160,9 +220,14 @@ where
                     self.db.storage().read().get(&addr).and_then(|acc| acc.get(&idx).copied());
                 if let Some(value) = value {
                     let _ = sender.send(Ok(value));
-                } else {
+                } else if let Some(sender) =
+                    self.reject_offline(|| format!("storage {addr:?} slot {idx}"), sender)
+                {
                     // account present but not storage -> fetch storage
                     self.request_account_storage(addr, idx, sender);
+                    if self.prefetch_accounts {
+                        self.prefetch_account(addr);
+                    }
                 }
             }
             BackendRequest::SetPinnedBlock(block_id) => {
@@ -182,14 +247,22 @@ where
                 entry.insert(vec![listener]);
                 let provider = self.provider.clone();
                 let block_id = self.block_id;
-                let fut = Box::pin(async move {
+                let span = info_span!(
+                    target: "backendhandler",
+                    "fork-fetch",
+                    kind = "storage",
+                    ?address,
+                    %idx
+                );
+                let fut = async move {
                     // serialize & deserialize back to U256
                     let idx_req = H256::from_uint(&idx);
                     let storage = provider.get_storage_at(address, idx_req, block_id).await;
                     let storage = storage.map(|storage| storage.into_uint());
                     (storage, address, idx)
-                });
-                self.pending_requests.push(ProviderRequest::Storage(fut));
+                }
+                .instrument(span);
+                self.pending_requests.push(ProviderRequest::Storage(Box::pin(fut)));
             }
         }
     }
@@ -199,14 +272,16 @@ where
         trace!(target: "backendhandler", "preparing account request, address={:?}", address);
         let provider = self.provider.clone();
         let block_id = self.block_id;
-        let fut = Box::pin(async move {
+        let span = info_span!(target: "backendhandler", "fork-fetch", kind = "account", ?address);
+        let fut = async move {
             let balance = provider.get_balance(address, block_id);
             let nonce = provider.get_transaction_count(address, block_id);
             let code = provider.get_code(address, block_id);
             let resp = tokio::try_join!(balance, nonce, code);
             (resp, address)
-        });
-        ProviderRequest::Account(fut)
+        }
+        .instrument(span);
+        ProviderRequest::Account(Box::pin(fut))
     }
 
     /// process a request for an account
@@ -222,26 +297,52 @@ where
         }
     }
 
-    /// process a request for an entire block
-    fn request_full_block(&mut self, number: BlockId, sender: FullBlockSender) {
-        let provider = self.provider.clone();
-        let fut = Box::pin(async move {
-            let block = provider.get_block_with_txs(number).await;
-            (sender, block, number)
-        });
+    /// fetches the account info without a listener, unless it is cached or already requested
+    fn prefetch_account(&mut self, address: Address) {
+        if self.db.accounts().read().contains_key(&address) {
+            return
+        }
+        if let Entry::Vacant(entry) = self.account_requests.entry(address) {
+            trace!(target: "backendhandler", "prefetching account, address={:?}", address);
+            entry.insert(vec![]);
+            self.pending_requests.push(self.get_account_req(address));
+        }
+    }
 
-        self.pending_requests.push(ProviderRequest::FullBlock(fut));
+    /// process a request for an entire block
+    fn request_full_block(&mut self, number: BlockId, listener: FullBlockSender) {
+        match self.full_block_requests.entry(number) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().push(listener);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(vec![listener]);
+                let provider = self.provider.clone();
+                let fut = Box::pin(async move {
+                    let block = provider.get_block_with_txs(number).await;
+                    (block, number)
+                });
+                self.pending_requests.push(ProviderRequest::FullBlock(fut));
+            }
+        }
     }
 
     /// process a request for a transactions
-    fn request_transaction(&mut self, tx: H256, sender: TransactionSender) {
-        let provider = self.provider.clone();
-        let fut = Box::pin(async move {
-            let block = provider.get_transaction(tx).await;
-            (sender, block, tx)
-        });
-
-        self.pending_requests.push(ProviderRequest::Transaction(fut));
+    fn request_transaction(&mut self, tx: H256, listener: TransactionSender) {
+        match self.transaction_requests.entry(tx) {
+            Entry::Occupied(mut entry) => {
+                entry.get_mut().push(listener);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(vec![listener]);
+                let provider = self.provider.clone();
+                let fut = Box::pin(async move {
+                    let transaction = provider.get_transaction(tx).await;
+                    (transaction, tx)
+                });
+                self.pending_requests.push(ProviderRequest::Transaction(fut));
+            }
+        }
     }
 
     /// process a request for a block hash
@@ -254,7 +355,9 @@ where
                 trace!(target: "backendhandler", "preparing block hash request, number={}", number);
                 entry.insert(vec![listener]);
                 let provider = self.provider.clone();
-                let fut = Box::pin(async move {
+                let span =
+                    info_span!(target: "backendhandler", "fork-fetch", kind = "block-hash", number);
+                let fut = async move {
                     let block = provider.get_block(number).await;
 
                     let block_hash = match block {
@@ -273,13 +376,24 @@ where
                         }
                     };
                     (block_hash, number)
-                });
-                self.pending_requests.push(ProviderRequest::BlockHash(fut));
+                }
+                .instrument(span);
+                self.pending_requests.push(ProviderRequest::BlockHash(Box::pin(fut)));
             }
         }
     }
 }
 
+impl<M: Middleware> BackendHandler<M> {
+    /// Logs all keys that could not be served in offline mode
+    fn report_missing(&self) {
+        let missing = self.missing.lock();
+        if !missing.is_empty() {
+            warn!(target: "backendhandler", missing = ?*missing, "values are missing from the RPC cache of the offline fork");
+        }
+    }
+}
+
 impl<M> Future for BackendHandler<M>
 where
     M: Middleware + Clone + Unpin + 'static,
@@ -302,6 +416,7 @@ where
                     }
                     Poll::Ready(None) => {
                         trace!(target: "backendhandler", "last sender dropped, ready to drop (&flush cache)");
+                        pin.report_missing();
                         return Poll::Ready(())
                     }
                     Poll::Pending => break,
@@ -422,30 +537,44 @@ where
                         }
                     }
                     ProviderRequest::FullBlock(fut) => {
-                        if let Poll::Ready((sender, resp, number)) = fut.poll_unpin(cx) {
-                            let msg = match resp {
-                                Ok(Some(block)) => Ok(block),
-                                Ok(None) => Err(DatabaseError::BlockNotFound(number)),
-                                Err(err) => {
-                                    let err = Arc::new(eyre::Error::new(err));
-                                    Err(DatabaseError::GetFullBlock(number, err))
-                                }
-                            };
-                            let _ = sender.send(msg);
+                        if let Poll::Ready((resp, number)) = fut.poll_unpin(cx) {
+                            let resp = resp.map_err(|err| Arc::new(eyre::Error::new(err)));
+                            // notify all listeners
+                            if let Some(listeners) = pin.full_block_requests.remove(&number) {
+                                listeners.into_iter().for_each(|l| {
+                                    let msg = match &resp {
+                                        Ok(Some(block)) => Ok(block.clone()),
+                                        Ok(None) => Err(DatabaseError::BlockNotFound(number)),
+                                        Err(err) => Err(DatabaseError::GetFullBlock(
+                                            number,
+                                            Arc::clone(err),
+                                        )),
+                                    };
+                                    let _ = l.send(msg);
+                                })
+                            }
                             continue
                         }
                     }
                     ProviderRequest::Transaction(fut) => {
-                        if let Poll::Ready((sender, tx, tx_hash)) = fut.poll_unpin(cx) {
-                            let msg = match tx {
-                                Ok(Some(tx)) => Ok(tx),
-                                Ok(None) => Err(DatabaseError::TransactionNotFound(tx_hash)),
-                                Err(err) => {
-                                    let err = Arc::new(eyre::Error::new(err));
-                                    Err(DatabaseError::GetTransaction(tx_hash, err))
-                                }
-                            };
-                            let _ = sender.send(msg);
+                        if let Poll::Ready((resp, tx_hash)) = fut.poll_unpin(cx) {
+                            let resp = resp.map_err(|err| Arc::new(eyre::Error::new(err)));
+                            // notify all listeners
+                            if let Some(listeners) = pin.transaction_requests.remove(&tx_hash) {
+                                listeners.into_iter().for_each(|l| {
+                                    let msg = match &resp {
+                                        Ok(Some(tx)) => Ok(tx.clone()),
+                                        Ok(None) => {
+                                            Err(DatabaseError::TransactionNotFound(tx_hash))
+                                        }
+                                        Err(err) => Err(DatabaseError::GetTransaction(
+                                            tx_hash,
+                                            Arc::clone(err),
+                                        )),
+                                    };
+                                    let _ = l.send(msg);
+                                })
+                            }
                             continue
                         }
                     }
@@ -500,6 +629,11 @@ pub struct SharedBackend {
     /// There is only one instance of the type, so as soon as the last `SharedBackend` is deleted,
     /// `FlushJsonBlockCacheDB` is also deleted and the cache is flushed.
     cache: Arc<FlushJsonBlockCacheDB>,
+    /// The keys the `BackendHandler` could not serve in offline mode
+    missing: MissingKeys,
+    /// The data the `BackendHandler` already fetched, which is read directly so cached values
+    /// don't wait for the `BackendHandler`
+    db: BlockchainDb,
 }
 
 impl SharedBackend {
@@ -562,8 +696,16 @@ impl SharedBackend {
     {
         let (backend, backend_rx) = channel(1);
         let cache = Arc::new(FlushJsonBlockCacheDB(Arc::clone(db.cache())));
-        let handler = BackendHandler::new(provider, db, backend_rx, pin_block);
-        (Self { backend, cache }, handler)
+        let missing = MissingKeys::default();
+        let handler =
+            BackendHandler::new(provider, db.clone(), backend_rx, pin_block, missing.clone());
+        (Self { backend, cache, missing, db }, handler)
+    }
+
+    /// Returns all keys that were requested but are not in the cache, if the `BackendHandler` is
+    /// in offline mode
+    pub fn missing_cache_keys(&self) -> Vec<String> {
+        self.missing.lock().iter().cloned().collect()
     }
 
     /// Updates the pinned block to fetch data from
@@ -593,6 +735,9 @@ impl SharedBackend {
     }
 
     fn do_get_basic(&self, address: Address) -> DatabaseResult<Option<AccountInfo>> {
+        if let Some(basic) = self.db.accounts().read().get(&address).cloned() {
+            return Ok(Some(basic))
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Basic(address, sender);
@@ -602,6 +747,11 @@ impl SharedBackend {
     }
 
     fn do_get_storage(&self, address: Address, index: U256) -> DatabaseResult<U256> {
+        let value =
+            self.db.storage().read().get(&address).and_then(|acc| acc.get(&index).copied());
+        if let Some(value) = value {
+            return Ok(value)
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::Storage(address, index, sender);
@@ -611,6 +761,9 @@ impl SharedBackend {
     }
 
     fn do_get_block_hash(&self, number: u64) -> DatabaseResult<H256> {
+        if let Some(hash) = self.db.block_hashes().read().get(&U256::from(number)).copied() {
+            return Ok(hash)
+        }
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
             let req = BackendRequest::BlockHash(number, sender);
@@ -678,6 +831,56 @@ mod tests {
     use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
     const ENDPOINT: &str = "https://mainnet.infura.io/v3/c60b0bb42f8a4c6481ecd229eddaca27";
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_backend_rejects_uncached_values() {
+        let provider = get_http_provider("http://localhost:1");
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        };
+        let db = BlockchainDb::new(meta, None);
+        let cached: Address = "63091244180ae240c87d1f528f5f269134cb07b3".parse().unwrap();
+        db.accounts().write().insert(cached, AccountInfo::default());
+
+        let (backend, handler) = SharedBackend::new(Arc::new(provider), db, None);
+        tokio::spawn(handler.with_offline(true));
+
+        assert!(backend.basic(cached).unwrap().is_some());
+        let missing = Address::random();
+        assert!(matches!(backend.basic(missing), Err(DatabaseError::MissingFromCache(_))));
+        assert!(matches!(
+            backend.storage(cached, U256::from(1u64)),
+            Err(DatabaseError::MissingFromCache(_))
+        ));
+        assert_eq!(
+            backend.missing_cache_keys(),
+            vec![format!("account {missing:?}"), format!("storage {cached:?} slot 1")]
+        );
+    }
+
+    #[test]
+    fn shared_backend_reads_cached_values_directly() {
+        let provider = get_http_provider("http://localhost:1");
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        };
+        let db = BlockchainDb::new(meta, None);
+        // the handler is never polled, so cached values must be served without it
+        let (backend, _handler) = SharedBackend::new(Arc::new(provider), db.clone(), None);
+
+        let address = Address::random();
+        db.accounts().write().insert(address, AccountInfo::default());
+        db.storage().write().entry(address).or_default().insert(1u64.into(), 2u64.into());
+        db.block_hashes().write().insert(3u64.into(), H256::repeat_byte(4));
+
+        assert!(backend.basic(address).unwrap().is_some());
+        assert_eq!(backend.storage(address, 1u64.into()).unwrap(), 2u64.into());
+        assert_eq!(backend.block_hash(3u64.into()).unwrap(), H256::repeat_byte(4));
+    }
+
     #[test]
     fn shared_backend() {
         let provider = get_http_provider(ENDPOINT);
@@ -9,7 +9,10 @@ mod init;
 pub use init::environment;
 
 mod cache;
-pub use cache::{BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB, MemDb};
+pub use cache::{
+    BlockCacheBackend, BlockchainDb, BlockchainDbMeta, DiskBlockCache, JsonBlockCacheDB, MemDb,
+    RemoteBlockCache, SharedBlockCache,
+};
 
 pub mod database;
 
@@ -3,15 +3,19 @@
 //! The design is similar to the single `SharedBackend`, `BackendHandler` but supports multiple
 //! concurrently active pairs at once.
 
-use crate::executor::fork::{
-    BackendHandler, BlockchainDb, BlockchainDbMeta, CreateFork, SharedBackend,
+use crate::executor::{
+    fork::{
+        BackendHandler, BlockCacheBackend, BlockchainDb, BlockchainDbMeta, CreateFork,
+        DiskBlockCache, RemoteBlockCache, SharedBackend, SharedBlockCache,
+    },
+    opts::EvmOpts,
 };
 use ethers::{
     abi::{AbiDecode, AbiEncode, AbiError},
-    providers::{Http, Provider, RetryClient},
+    providers::Middleware,
     types::{BlockId, BlockNumber},
 };
-use foundry_common::ProviderBuilder;
+use foundry_common::{ProviderBuilder, RetryProvider};
 use foundry_config::Config;
 use futures::{
     channel::mpsc::{channel, Receiver, Sender},
@@ -170,7 +174,7 @@ impl MultiFork {
     }
 }
 
-type Handler = BackendHandler<Arc<Provider<RetryClient<Http>>>>;
+type Handler = BackendHandler<Arc<RetryProvider>>;
 
 type CreateFuture = Pin<Box<dyn Future<Output = eyre::Result<(CreatedFork, Handler)>> + Send>>;
 type CreateSender = OneshotSender<eyre::Result<(ForkId, SharedBackend, Env)>>;
@@ -470,6 +474,30 @@ fn create_fork_id(url: &str, num: Option<u64>) -> ForkId {
     ForkId(format!("{url}@{num}"))
 }
 
+/// Returns the cache of the `block`, which is shared between machines if a `rpc_cache_url` is
+/// configured and the fork is not offline
+async fn block_cache(
+    provider: &RetryProvider,
+    evm_opts: &EvmOpts,
+    chain_id: u64,
+    block: u64,
+) -> eyre::Result<Option<Box<dyn BlockCacheBackend>>> {
+    let local = Config::foundry_block_cache_dir(chain_id, block).map(DiskBlockCache::new);
+    let cache: Option<Box<dyn BlockCacheBackend>> = match &evm_opts.rpc_cache_url {
+        Some(url) if !evm_opts.offline => {
+            let block_hash = provider
+                .get_block(block)
+                .await?
+                .and_then(|block| block.hash)
+                .ok_or_else(|| eyre::eyre!("block {block} not found"))?;
+            let remote = RemoteBlockCache::new(url, chain_id, block, block_hash)?;
+            Some(Box::new(SharedBlockCache::new(local, remote)))
+        }
+        _ => local.map(|local| Box::new(local) as Box<_>),
+    };
+    Ok(cache)
+}
+
 /// Creates a new fork
 ///
 /// This will establish a new `Provider` to the endpoint and return the Fork Backend
@@ -492,16 +520,19 @@ async fn create_fork(
     let meta = BlockchainDbMeta::new(fork.env.clone(), fork.url.clone());
     let number = meta.block_env.number.as_u64();
 
-    // determine the cache path if caching is enabled
-    let cache_path = if fork.enable_caching {
-        Config::foundry_block_cache_dir(meta.cfg_env.chain_id.as_u64(), number)
+    // determine the cache if caching is enabled, offline forks can only be served from the cache
+    let offline = fork.evm_opts.offline;
+    let cache = if fork.enable_caching || offline {
+        block_cache(&provider, &fork.evm_opts, meta.cfg_env.chain_id.as_u64(), number).await?
     } else {
         None
     };
 
-    let db = BlockchainDb::new(meta, cache_path);
+    let db = BlockchainDb::with_cache_backend(meta, cache, offline);
     let (backend, handler) =
         SharedBackend::new(provider, db, Some(BlockId::Number(BlockNumber::Number(number.into()))));
+    let handler =
+        handler.with_offline(offline).with_prefetch_accounts(fork.evm_opts.fork_prefetch_accounts);
     let fork = CreatedFork::new(fork, backend);
     Ok((fork, handler))
 }
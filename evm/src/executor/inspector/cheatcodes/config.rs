@@ -2,13 +2,19 @@ use crate::executor::opts::EvmOpts;
 use bytes::Bytes;
 
 use crate::error;
-use ethers::solc::{utils::canonicalize, ProjectPathsConfig};
+use ethers::{
+    solc::{utils::canonicalize, ProjectPathsConfig},
+    types::Address,
+};
 use foundry_common::fs::normalize_path;
 use foundry_config::{
-    cache::StorageCachingConfig, fs_permissions::FsAccessKind, Config, FsPermissions,
+    cache::StorageCachingConfig, fs_permissions::FsAccessKind, Chain, Config, FsPermissions,
     ResolvedRpcEndpoints,
 };
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 use tracing::trace;
 
 /// Additional, configurable context the `Cheatcodes` inspector has access to
@@ -17,6 +23,8 @@ use tracing::trace;
 #[derive(Debug, Clone)]
 pub struct CheatsConfig {
     pub ffi: bool,
+    /// Whether `readMemory` and `readReturndata` are allowed
+    pub memory_access: bool,
     /// RPC storage caching settings determines what chains and endpoints to cache
     pub rpc_storage_caching: StorageCachingConfig,
     /// All known endpoints and their aliases
@@ -29,8 +37,20 @@ pub struct CheatsConfig {
     pub root: PathBuf,
     /// Paths (directories) where file reading/writing is allowed
     pub allowed_paths: Vec<PathBuf>,
+    /// Additional directories artifacts are looked up in
+    pub artifact_paths: Vec<PathBuf>,
     /// How the evm was configured by the user
     pub evm_opts: EvmOpts,
+    /// The CREATE2 factory used for CREATE2 deployments while broadcasting
+    pub create2_deployer: Address,
+    /// Per chain id overrides of `create2_deployer`
+    pub create2_deployers: BTreeMap<u64, Address>,
+    /// The directory of the snapshot files used by `expectSnapshot`
+    pub snapshots: PathBuf,
+    /// Environment variables of the profile, see [Config::env]
+    pub env: BTreeMap<String, String>,
+    /// Whether `expectSnapshot` overwrites snapshot files instead of comparing against them
+    pub update_snapshots: bool,
 }
 
 // === impl CheatsConfig ===
@@ -47,22 +67,42 @@ impl CheatsConfig {
 
         Self {
             ffi: evm_opts.ffi,
+            memory_access: config.memory_access,
             rpc_storage_caching: config.rpc_storage_caching.clone(),
             rpc_endpoints,
             paths: config.project_paths(),
             fs_permissions: config.fs_permissions.clone().joined(&config.__root),
             root: config.__root.0.clone(),
             allowed_paths,
+            artifact_paths: config.artifact_paths.clone(),
             evm_opts: evm_opts.clone(),
+            create2_deployer: config.create2_deployer,
+            create2_deployers: config
+                .create2_deployers
+                .iter()
+                .filter_map(|(chain, deployer)| {
+                    Some((chain.parse::<Chain>().ok()?.id(), *deployer))
+                })
+                .collect(),
+            snapshots: config.__root.0.join("snapshots"),
+            env: config.env.clone(),
+            update_snapshots: false,
         }
     }
 
+    /// Returns the CREATE2 factory to use on the chain with the given id
+    pub fn create2_deployer(&self, chain_id: u64) -> Address {
+        self.create2_deployers.get(&chain_id).copied().unwrap_or(self.create2_deployer)
+    }
+
     /// Attempts to canonicalize (see [std::fs::canonicalize]) the path.
     ///
-    /// Canonicalization fails for non-existing paths, in which case we just normalize the path.
+    /// Canonicalization fails for non-existing paths, in which case we resolve the symlinks of
+    /// the path's existing ancestors, so files can't be created outside of allowed paths through
+    /// linked directories or dangling links.
     pub fn normalized_path(&self, path: impl AsRef<Path>) -> PathBuf {
         let path = self.root.join(path);
-        canonicalize(&path).unwrap_or_else(|_| normalize_path(&path))
+        canonicalize(&path).unwrap_or_else(|_| resolve_symlinks(&normalize_path(&path), 0))
     }
 
     /// Returns true if the given path is allowed, if any path `allowed_paths` is an ancestor of the
@@ -153,17 +193,46 @@ impl CheatsConfig {
     }
 }
 
+/// Max number of links followed when resolving a path, same as linux' `MAXSYMLINKS`
+const MAX_SYMLINKS: usize = 40;
+
+/// Resolves the symlinks of a normalized path that may not exist
+///
+/// The deepest existing ancestor is canonicalized, and if the path itself is a dangling link, its
+/// target is resolved instead.
+fn resolve_symlinks(path: &Path, depth: usize) -> PathBuf {
+    if depth < MAX_SYMLINKS {
+        if let Ok(target) = std::fs::read_link(path) {
+            let parent = path.parent().unwrap_or(path);
+            return resolve_symlinks(&normalize_path(&parent.join(target)), depth + 1)
+        }
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            canonicalize(parent).unwrap_or_else(|_| resolve_symlinks(parent, depth)).join(name)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
 impl Default for CheatsConfig {
     fn default() -> Self {
         Self {
             ffi: false,
+            memory_access: false,
             rpc_storage_caching: Default::default(),
             rpc_endpoints: Default::default(),
             paths: ProjectPathsConfig::builder().build_with_root("./"),
             fs_permissions: Default::default(),
             root: Default::default(),
             allowed_paths: vec![],
+            artifact_paths: vec![],
             evm_opts: Default::default(),
+            create2_deployer: Config::DEFAULT_CREATE2_DEPLOYER,
+            create2_deployers: Default::default(),
+            snapshots: PathBuf::from("snapshots"),
+            env: Default::default(),
+            update_snapshots: false,
         }
     }
 }
@@ -193,6 +262,26 @@ mod tests {
         assert!(config.ensure_path_allowed("../../root/t.txt", FsAccessKind::Write).is_err());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_cannot_escape_allowed_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let root_path = canonicalize(root.path()).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root_path.join("linked")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("t.txt"), root_path.join("dangling"))
+            .unwrap();
+
+        let config = config(
+            root_path.to_str().unwrap(),
+            FsPermissions::new(vec![PathPermission::read_write("./")]),
+        );
+        assert!(config.ensure_path_allowed("./new/t.txt", FsAccessKind::Write).is_ok());
+        assert!(config.ensure_path_allowed("./linked/t.txt", FsAccessKind::Write).is_err());
+        assert!(config.ensure_path_allowed("./linked/a/t.txt", FsAccessKind::Write).is_err());
+        assert!(config.ensure_path_allowed("./dangling", FsAccessKind::Write).is_err());
+    }
+
     #[test]
     fn test_is_foundry_toml() {
         let root = "/my/project/root/";
@@ -0,0 +1,122 @@
+use super::util::{parse_private_key, with_journaled_account};
+use crate::{
+    abi::{HEVMCalls, SignedDelegation},
+    error::SolError,
+    executor::{
+        backend::DatabaseExt,
+        delegation::{Authorization, SignedAuthorization},
+    },
+};
+use bytes::Bytes;
+use ethers::{
+    abi::{self, AbiEncode, Token},
+    signers::{LocalWallet, Signer},
+    types::{Address, U256},
+};
+use revm::{Bytecode, EVMData};
+use tracing::trace;
+
+/// Signs an authorization for the current nonce of the account of the key
+fn sign_delegation<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    implementation: Address,
+    private_key: U256,
+) -> Result<SignedAuthorization, Bytes> {
+    let wallet = LocalWallet::from(parse_private_key(private_key)?);
+    let (account, _) = data
+        .journaled_state
+        .load_code(wallet.address(), data.db)
+        .map_err(|err| err.encode_string())?;
+    let authorization = Authorization {
+        chain_id: data.env.cfg.chain_id,
+        address: implementation,
+        nonce: account.info.nonce.into(),
+    };
+    let signature = wallet
+        .sign_hash(authorization.signature_hash())
+        .map_err(|err| err.to_string().encode())?;
+    Ok(authorization.into_signed(signature))
+}
+
+/// Applies the authorization like a set code transaction would: sets the code of the authority to
+/// the delegation designator and increments its nonce
+fn attach_delegation<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    authorization: &SignedAuthorization,
+) -> Result<(), Bytes> {
+    let authority = authorization.recover_authority().map_err(|err| err.to_string().encode())?;
+    let (account, _) = data
+        .journaled_state
+        .load_code(authority, data.db)
+        .map_err(|err| err.encode_string())?;
+    let code = account.info.code.as_ref().map(|code| code.bytes()[..code.len()].to_vec());
+    authorization
+        .validate(
+            data.env.cfg.chain_id.as_u64(),
+            authority,
+            account.info.nonce,
+            code.as_deref().unwrap_or_default(),
+        )
+        .map_err(|err| err.to_string().encode())?;
+
+    trace!(?authority, delegate=?authorization.address, "attach delegation cheatcode");
+    with_journaled_account(&mut data.journaled_state, data.db, authority, |account| {
+        account.info.nonce += 1;
+    })
+    .map_err(|err| err.encode_string())?;
+    let code = match authorization.delegation_code() {
+        code if code.is_empty() => Bytecode::new(),
+        code => Bytecode::new_raw(code.0).to_checked(),
+    };
+    data.journaled_state.set_code(authority, code);
+    Ok(())
+}
+
+fn encode_delegation(authorization: &SignedAuthorization) -> Bytes {
+    abi::encode(&[Token::Tuple(vec![
+        Token::Uint(authorization.y_parity.as_u64().into()),
+        Token::FixedBytes(authorization.r.encode()),
+        Token::FixedBytes(authorization.s.encode()),
+        Token::Uint(authorization.nonce.as_u64().into()),
+        Token::Address(authorization.address),
+    ])])
+    .into()
+}
+
+/// The authorization of the delegation, which is always for the current chain
+fn decode_delegation<DB: DatabaseExt>(
+    data: &EVMData<'_, DB>,
+    delegation: &SignedDelegation,
+) -> SignedAuthorization {
+    SignedAuthorization {
+        chain_id: data.env.cfg.chain_id,
+        address: delegation.implementation,
+        nonce: delegation.nonce.into(),
+        y_parity: delegation.v.into(),
+        r: U256::from_big_endian(&delegation.r),
+        s: U256::from_big_endian(&delegation.s),
+    }
+}
+
+/// Handles the EIP-7702 delegation cheatcodes
+pub fn apply<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::SignDelegation(inner) => {
+            sign_delegation(data, inner.0, inner.1).map(|auth| encode_delegation(&auth))
+        }
+        HEVMCalls::AttachDelegation(inner) => {
+            let authorization = decode_delegation(data, &inner.0);
+            attach_delegation(data, &authorization).map(|_| Bytes::new())
+        }
+        HEVMCalls::SignAndAttachDelegation(inner) => {
+            sign_delegation(data, inner.0, inner.1).and_then(|authorization| {
+                attach_delegation(data, &authorization)?;
+                Ok(encode_delegation(&authorization))
+            })
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,198 @@
+use super::{ext::read_creation_code, Cheatcodes};
+use crate::{
+    abi::HEVMCalls,
+    decode,
+    error::{self, SolError},
+    executor::{
+        backend::{apply_to_journal, DatabaseExt, JournaledDb},
+        inspector::{isolation::Nested, InspectorStack, IntrinsicGas},
+    },
+};
+use bytes::Bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, U256},
+};
+use revm::{
+    return_ok, CallInputs, CreateScheme, EVMData, Gas, Return, TransactOut, TransactTo, TxEnv,
+};
+
+/// A deployment requested by a `deployCode` or `deployYul` cheatcode
+///
+/// The cheatcode only reads the creation code, the inspector stack executes the deployment once
+/// the cheatcode call was handled, see [transact_deployment].
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    /// The contract that called the cheatcode
+    caller: Address,
+    /// The artifact the creation code was read from
+    path: String,
+    /// The creation code with the constructor arguments appended
+    init_code: Bytes,
+    value: U256,
+    /// Whether the hash of the runtime code is returned along with the address
+    with_code_hash: bool,
+}
+
+/// Reads the creation code of the matching artifact and requests its deployment with the given
+/// constructor arguments and value from `caller`
+fn request_deployment(
+    state: &mut Cheatcodes,
+    caller: Address,
+    path: &str,
+    args: &[u8],
+    value: U256,
+    with_code_hash: bool,
+) -> Result<Bytes, Bytes> {
+    let mut init_code = read_creation_code(state, path)?.to_vec();
+    init_code.extend_from_slice(args);
+    state.pending_deployment = Some(Deployment {
+        caller,
+        path: path.to_string(),
+        init_code: init_code.into(),
+        value,
+        with_code_hash,
+    });
+    Ok(Bytes::new())
+}
+
+/// Executes the deployment requested by the cheatcode call `call`
+///
+/// The deployment is executed as a separate transaction on top of the state of the ongoing one.
+/// All inspectors of the stack see it as a creation made by the cheatcode call, so the constructor
+/// shows up in traces and its logs are collected. The cheatcode call is charged what a `CREATE`
+/// of the caller would cost, i.e. the gas used without the base and calldata cost of a
+/// transaction. Returns the address of the new contract, and the hash of its runtime code if
+/// requested.
+pub fn transact_deployment<DB: DatabaseExt>(
+    stack: &mut InspectorStack,
+    data: &mut EVMData<'_, DB>,
+    call: &CallInputs,
+    deployment: Deployment,
+) -> (Return, Gas, Bytes) {
+    let Deployment { caller, path, init_code, value, with_code_hash } = deployment;
+    let mut env = data.env.clone();
+    // the caller is a contract and the deployment is free
+    env.cfg.disable_eip3607 = true;
+    env.block.basefee = U256::zero();
+    env.tx = TxEnv {
+        caller,
+        transact_to: TransactTo::Create(CreateScheme::Create),
+        value,
+        data: init_code,
+        gas_limit: 0,
+        gas_price: U256::zero(),
+        ..Default::default()
+    };
+    let stipend = IntrinsicGas::default().stipend(&env.tx.data, env.cfg.spec_id);
+    env.tx.gas_limit = call.gas_limit + stipend;
+
+    // cheatcodes called by the constructor must not overwrite the call site of this one
+    let call_site =
+        stack.cheatcodes.as_mut().and_then(|cheatcodes| cheatcodes.cheatcode_call_site.take());
+
+    // the creation is a child of the cheatcode call
+    let depth = data.journaled_state.depth + 1;
+    let (result, changes) = {
+        let mut db = JournaledDb::new(&data.journaled_state, &mut *data.db);
+        let mut nested = Nested::new(&mut *stack, depth, 0);
+        revm::evm_inner::<JournaledDb<DB>, true>(&mut env, &mut db, &mut nested).transact()
+    };
+
+    let mut gas = Gas::new(call.gas_limit);
+    gas.record_cost(result.gas_used.saturating_sub(stipend).min(call.gas_limit));
+
+    let address = match (result.exit_reason, result.out) {
+        (return_ok!(), TransactOut::Create(_, Some(address))) => address,
+        (status, out) => {
+            let out = match out {
+                TransactOut::Create(out, _) => out,
+                _ => Bytes::new(),
+            };
+            let reason = decode::decode_revert(&out, None, Some(status))
+                .unwrap_or_else(|_| format!("{status:?}"));
+            let err = error::encode_error(format!("Failed to deploy `{path}`: {reason}"));
+            if let Some(cheatcodes) = stack.cheatcodes.as_mut() {
+                cheatcodes.record_failure(data, call_site.as_ref(), &err);
+            }
+            return (Return::Revert, gas, err)
+        }
+    };
+    let code_hash = changes.get(&address).map(|acc| acc.info.code_hash).unwrap_or_default();
+
+    // apply the changes of the deployment to the ongoing transaction
+    if let Err(err) = apply_to_journal(&mut data.journaled_state, data.db, changes) {
+        return (Return::Revert, gas, err.encode_string())
+    }
+    for log in result.logs {
+        data.journaled_state.log(log);
+    }
+
+    // a contract deployed by a contract with cheatcode access is allowed to use cheatcodes too
+    if data.db.has_cheatcode_access(caller) {
+        data.db.allow_cheatcode_access(address);
+    }
+
+    let retdata = if with_code_hash { (address, code_hash).encode() } else { address.encode() };
+    (Return::Return, gas, retdata.into())
+}
+
+/// Returns the artifact path of a Yul object, where `<Name>` is short for `<Name>.yul:<Name>`
+fn yul_artifact_path(name: &str) -> String {
+    if name.contains(':') || name.ends_with(".yul") || name.ends_with(".json") {
+        name.to_string()
+    } else {
+        format!("{name}.yul:{name}")
+    }
+}
+
+pub fn apply(
+    state: &mut Cheatcodes,
+    caller: Address,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::DeployCode0(inner) => {
+            request_deployment(state, caller, &inner.0, &[], U256::zero(), false)
+        }
+        HEVMCalls::DeployCode1(inner) => {
+            request_deployment(state, caller, &inner.0, &inner.1, U256::zero(), false)
+        }
+        HEVMCalls::DeployCode2(inner) => {
+            request_deployment(state, caller, &inner.0, &inner.1, inner.2, false)
+        }
+        HEVMCalls::DeployCodeWithHash(inner) => {
+            request_deployment(state, caller, &inner.0, &inner.1, inner.2, true)
+        }
+        HEVMCalls::DeployYul0(inner) => request_deployment(
+            state,
+            caller,
+            &yul_artifact_path(&inner.0),
+            &[],
+            U256::zero(),
+            false,
+        ),
+        HEVMCalls::DeployYul1(inner) => request_deployment(
+            state,
+            caller,
+            &yul_artifact_path(&inner.0),
+            &inner.1,
+            U256::zero(),
+            false,
+        ),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_resolve_yul_artifact_paths() {
+        assert_eq!(yul_artifact_path("Counter"), "Counter.yul:Counter");
+        assert_eq!(yul_artifact_path("Counter.yul"), "Counter.yul");
+        assert_eq!(yul_artifact_path("Counter.yul:Runtime"), "Counter.yul:Runtime");
+        assert_eq!(yul_artifact_path("out/Counter.json"), "out/Counter.json");
+    }
+}
@@ -5,8 +5,12 @@ use crate::{
     abi::HEVMCalls,
     error::SolError,
     executor::{
-        backend::DatabaseExt,
-        inspector::cheatcodes::{util::with_journaled_account, DealRecord},
+        backend::{journaled_storage_slots, DatabaseExt},
+        inspector::cheatcodes::{
+            state_diff::{encode_state_diff, StateDiffRecorder},
+            util::with_journaled_account,
+            DealRecord,
+        },
     },
 };
 use bytes::Bytes;
@@ -18,7 +22,7 @@ use ethers::{
         Secp256k1,
     },
     signers::{LocalWallet, Signer},
-    types::{Address, U256},
+    types::{Address, H256, U256},
 };
 use foundry_config::Config;
 use revm::{Bytecode, Database, EVMData};
@@ -191,6 +195,103 @@ fn get_recorded_logs(state: &mut Cheatcodes) -> Bytes {
     }
 }
 
+/// Reverts if any storage slot of the account changed since `startStateDiffRecording`
+fn assert_storage_unchanged<DB: DatabaseExt>(
+    state: &Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    account: Address,
+) -> Result<Bytes, Bytes> {
+    let recorder = match &state.state_diff {
+        Some(recorder) => recorder,
+        None => return Err("No state diff recording in progress".to_string().encode().into()),
+    };
+    let changed = recorder
+        .state_diff(data)
+        .into_iter()
+        .filter(|diff| diff.account == account)
+        .flat_map(|diff| diff.storage_diffs)
+        .next();
+    if let Some(diff) = changed {
+        return Err(format!(
+            "Storage of {account:?} changed at slot {:?}: {:?} != {:?}",
+            H256::from_uint(&diff.slot),
+            H256::from_uint(&diff.previous_value),
+            H256::from_uint(&diff.new_value)
+        )
+        .encode()
+        .into())
+    }
+    Ok(Bytes::new())
+}
+
+/// Returns all storage slots of the account
+///
+/// Fails if they can't be enumerated, e.g. the storage of a remote account in forking mode, since
+/// copying only the slots fetched so far would silently produce an incomplete copy.
+fn storage_slots<DB: DatabaseExt>(
+    data: &EVMData<'_, DB>,
+    account: Address,
+) -> Result<Vec<U256>, Bytes> {
+    journaled_storage_slots(&data.journaled_state, &*data.db, account).ok_or_else(|| {
+        format!(
+            "The storage of {account:?} can't be enumerated, only the storage of local accounts \
+             can be copied in forking mode"
+        )
+        .encode()
+        .into()
+    })
+}
+
+/// Copies all storage slots of `from` to `to`
+fn copy_storage<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    from: Address,
+    to: Address,
+) -> Result<Vec<U256>, Bytes> {
+    let slots = storage_slots(data, from)?;
+    data.journaled_state.load_account(from, data.db).map_err(|err| err.encode_string())?;
+    data.journaled_state.load_account(to, data.db).map_err(|err| err.encode_string())?;
+    // ensure the account is touched
+    data.journaled_state.touch(&to);
+
+    for slot in slots.iter() {
+        let (value, _) =
+            data.journaled_state.sload(from, *slot, data.db).map_err(|err| err.encode_string())?;
+        data.journaled_state
+            .sstore(to, *slot, value, data.db)
+            .map_err(|err| err.encode_string())?;
+    }
+    Ok(slots)
+}
+
+/// Turns `to` into a copy of `from`: its storage, code, balance and nonce
+fn clone_account<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    from: Address,
+    to: Address,
+) -> Result<(), Bytes> {
+    let copied = copy_storage(data, from, to)?;
+    // slots of the target the source doesn't have would otherwise survive the clone
+    for slot in storage_slots(data, to)? {
+        if !copied.contains(&slot) {
+            data.journaled_state
+                .sstore(to, slot, U256::zero(), data.db)
+                .map_err(|err| err.encode_string())?;
+        }
+    }
+
+    let (account, _) =
+        data.journaled_state.load_code(from, data.db).map_err(|err| err.encode_string())?;
+    let info = account.info.clone();
+    with_journaled_account(&mut data.journaled_state, data.db, to, |account| {
+        account.info.balance = info.balance;
+        account.info.nonce = info.nonce;
+    })
+    .map_err(|err| err.encode_string())?;
+    data.journaled_state.set_code(to, info.code.unwrap_or_else(Bytecode::new));
+    Ok(())
+}
+
 pub fn apply<DB: DatabaseExt>(
     state: &mut Cheatcodes,
     data: &mut EVMData<'_, DB>,
@@ -202,6 +303,20 @@ pub fn apply<DB: DatabaseExt>(
             data.env.block.timestamp = inner.0;
             Bytes::new()
         }
+        HEVMCalls::Skip(inner) => {
+            data.env.block.timestamp =
+                data.env.block.timestamp.checked_add(inner.0).ok_or_else(|| {
+                    "Cannot skip beyond the maximum timestamp".to_string().encode()
+                })?;
+            Bytes::new()
+        }
+        HEVMCalls::Rewind(inner) => {
+            data.env.block.timestamp =
+                data.env.block.timestamp.checked_sub(inner.0).ok_or_else(|| {
+                    "Cannot rewind before timestamp 0".to_string().encode()
+                })?;
+            Bytes::new()
+        }
         HEVMCalls::Difficulty(inner) => {
             data.env.block.difficulty = inner.0;
             Bytes::new()
@@ -241,6 +356,13 @@ pub fn apply<DB: DatabaseExt>(
                 .map_err(|err| err.encode_string())?;
             val.encode().into()
         }
+        HEVMCalls::StoreTransient(inner) => {
+            state.transient_storage.store(inner.0, inner.1.into(), inner.2.into());
+            Bytes::new()
+        }
+        HEVMCalls::LoadTransient(inner) => {
+            state.transient_storage.load(inner.0, inner.1.into()).encode().into()
+        }
         HEVMCalls::Etch(inner) => {
             let code = inner.1.clone();
             trace!(address=?inner.0, code=?hex::encode(&code.0), "etch cheatcode");
@@ -251,6 +373,16 @@ pub fn apply<DB: DatabaseExt>(
             data.journaled_state.set_code(inner.0, Bytecode::new_raw(code.0).to_checked());
             Bytes::new()
         }
+        HEVMCalls::CopyStorage(inner) => {
+            trace!(from=?inner.0, to=?inner.1, "copyStorage cheatcode");
+            copy_storage(data, inner.0, inner.1)?;
+            Bytes::new()
+        }
+        HEVMCalls::CloneAccount(inner) => {
+            trace!(from=?inner.0, to=?inner.1, "cloneAccount cheatcode");
+            clone_account(data, inner.0, inner.1)?;
+            Bytes::new()
+        }
         HEVMCalls::Deal(inner) => {
             let who = inner.0;
             let value = inner.1;
@@ -319,6 +451,20 @@ pub fn apply<DB: DatabaseExt>(
             Bytes::new()
         }
         HEVMCalls::GetRecordedLogs(_) => get_recorded_logs(state),
+        HEVMCalls::StartStateDiffRecording(_) => {
+            // the balances of the sender and the test contract can change without an access
+            let sender = data.env.tx.caller;
+            state.state_diff = Some(StateDiffRecorder::new(data, &[sender, caller]));
+            Bytes::new()
+        }
+        HEVMCalls::StopAndReturnStateDiff(_) => {
+            state.state_diff.take().unwrap_or_default().encode()
+        }
+        HEVMCalls::GetStateDiff(_) => {
+            let diffs = state.state_diff.as_ref().map(|recorder| recorder.state_diff(data));
+            encode_state_diff(diffs.unwrap_or_default())
+        }
+        HEVMCalls::AssertStorageUnchanged(inner) => assert_storage_unchanged(state, data, inner.0)?,
         HEVMCalls::SetNonce(inner) => {
             with_journaled_account(&mut data.journaled_state, data.db, inner.0, |account| -> Result<Bytes, Bytes>{
                 // nonce must increment only
@@ -1,4 +1,4 @@
-use super::Cheatcodes;
+use super::{CheatcodeCallSite, Cheatcodes};
 use crate::{
     abi::HEVMCalls,
     error::{SolError, ERROR_PREFIX, REVERT_PREFIX},
@@ -9,7 +9,7 @@ use ethers::{
     abi::{AbiDecode, AbiEncode, RawLog},
     types::{Address, H160, U256},
 };
-use revm::{return_ok, Bytecode, EVMData, Return};
+use revm::{return_ok, Bytecode, CallInputs, EVMData, Return};
 use std::cmp::Ordering;
 use tracing::{instrument, trace};
 
@@ -29,13 +29,36 @@ static DUMMY_CREATE_ADDRESS: Address =
 pub struct ExpectedRevert {
     /// The expected data returned by the revert, None being any
     pub reason: Option<Bytes>,
+    /// How the revert data is matched against the `reason`
+    pub kind: RevertMatch,
     /// The depth at which the revert is expected
     pub depth: u64,
+    /// Where `expectRevert` was called
+    pub call_site: Option<CheatcodeCallSite>,
+}
+
+/// How the data of a revert is compared to the expected reason
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevertMatch {
+    /// The revert data must be equal to the reason
+    Exact,
+    /// The revert data must start with the reason, which is the selector of a custom error, its
+    /// parameters are ignored
+    Selector,
+    /// The (decoded) revert data must contain the reason
+    Substring,
+}
+
+impl Default for RevertMatch {
+    fn default() -> Self {
+        RevertMatch::Exact
+    }
 }
 
 fn expect_revert(
     state: &mut Cheatcodes,
     reason: Option<Bytes>,
+    kind: RevertMatch,
     depth: u64,
 ) -> Result<Bytes, Bytes> {
     if state.expected_revert.is_some() {
@@ -44,7 +67,8 @@ fn expect_revert(
             .encode()
             .into())
     } else {
-        state.expected_revert = Some(ExpectedRevert { reason, depth });
+        let call_site = state.cheatcode_call_site.clone();
+        state.expected_revert = Some(ExpectedRevert { reason, kind, depth, call_site });
         Ok(Bytes::new())
     }
 }
@@ -53,6 +77,7 @@ fn expect_revert(
 pub fn handle_expect_revert(
     is_create: bool,
     expected_revert: Option<&Bytes>,
+    kind: RevertMatch,
     status: Return,
     retdata: Bytes,
 ) -> Result<(Option<Address>, Bytes), Bytes> {
@@ -82,6 +107,21 @@ pub fn handle_expect_revert(
         return Err("Call reverted as expected, but without data".to_string().encode().into())
     }
 
+    // custom errors are matched by the selector of the raw revert data
+    if kind == RevertMatch::Selector {
+        return if retdata.starts_with(expected_revert) {
+            success_return!()
+        } else {
+            Err(format!(
+                "Error selector != expected error selector: 0x{} != 0x{}",
+                hex::encode(retdata.get(..expected_revert.len()).unwrap_or(&retdata)),
+                hex::encode(expected_revert),
+            )
+            .encode()
+            .into())
+        }
+    }
+
     let maybe_prefixed_error_string = match retdata {
         _ if retdata.len() >= REVERT_PREFIX.len() &&
             retdata[..REVERT_PREFIX.len()] == REVERT_PREFIX =>
@@ -131,8 +171,23 @@ pub fn handle_expect_revert(
         )
     };
 
-    if actual_revert == expected_revert {
+    let matches = match kind {
+        RevertMatch::Substring if expected_revert.is_empty() => true,
+        RevertMatch::Substring => {
+            actual_revert.windows(expected_revert.len()).any(|window| window == expected_revert)
+        }
+        _ => actual_revert == expected_revert,
+    };
+    if matches {
         success_return!()
+    } else if kind == RevertMatch::Substring {
+        Err(format!(
+            "Error does not contain expected error: '{}' does not contain '{}'",
+            stringify(&actual_revert),
+            stringify(expected_revert),
+        )
+        .encode()
+        .into())
     } else {
         Err(err_message)
     }
@@ -212,6 +267,85 @@ pub struct ExpectedCallData {
     pub min_gas: Option<u64>,
 }
 
+/// An ordered sequence of calls that is expected during the next call, see
+/// `expectCallSequence`
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedCallSequence {
+    /// The depth the sequence was expected at, the next call made at this depth is checked
+    pub depth: u64,
+    /// The expected targets and calldata, which matches calldata starting with it
+    pub expected: Vec<(Address, Bytes)>,
+    /// All calls made during the next call so far, including the next call itself
+    pub actual: Vec<(Address, Bytes)>,
+}
+
+impl ExpectedCallSequence {
+    /// Returns true if the next call started
+    pub fn is_started(&self) -> bool {
+        !self.actual.is_empty()
+    }
+
+    /// Records the call if it is the next call or made during it
+    pub fn on_call(&mut self, depth: u64, call: &CallInputs) {
+        if depth > self.depth || (depth == self.depth && !self.is_started()) {
+            self.actual.push((call.contract, call.input.clone()));
+        }
+    }
+
+    /// Checks that the expected calls were made in order, other calls may be made in between
+    ///
+    /// Fails with a diff of the actual calls: `-` marks an expected call that is missing and `+` a
+    /// call that was not expected.
+    pub fn check(&self) -> Result<(), Bytes> {
+        let matches = |(expected, actual): (&(Address, Bytes), &(Address, Bytes))| {
+            expected.0 == actual.0 && actual.1.starts_with(&expected.1)
+        };
+
+        // longest common subsequence, `lcs[i][j]` is the length for `expected[i..]`, `actual[j..]`
+        let (expected, actual) = (&self.expected, &self.actual);
+        let mut lcs = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+        for i in (0..expected.len()).rev() {
+            for j in (0..actual.len()).rev() {
+                lcs[i][j] = if matches((&expected[i], &actual[j])) {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        if lcs[0][0] == expected.len() {
+            return Ok(())
+        }
+
+        let fmt_call =
+            |(target, data): &(Address, Bytes)| format!("{target:?} 0x{}", hex::encode(data));
+        let mut diff = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < expected.len() || j < actual.len() {
+            if i < expected.len() && j < actual.len() && matches((&expected[i], &actual[j])) {
+                diff.push(format!("  {}", fmt_call(&actual[j])));
+                i += 1;
+                j += 1;
+            } else if j < actual.len() && (i == expected.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                diff.push(format!("+ {}", fmt_call(&actual[j])));
+                j += 1;
+            } else {
+                diff.push(format!("- {}", fmt_call(&expected[i])));
+                i += 1;
+            }
+        }
+
+        Err(format!(
+            "Expected call sequence was not made, {} of {} calls matched:\n{}",
+            lcs[0][0],
+            expected.len(),
+            diff.join("\n")
+        )
+        .encode()
+        .into())
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct MockCallDataContext {
     /// The partial calldata to match for mock
@@ -243,13 +377,33 @@ pub fn apply<DB: DatabaseExt>(
     call: &HEVMCalls,
 ) -> Option<Result<Bytes, Bytes>> {
     Some(match call {
-        HEVMCalls::ExpectRevert0(_) => expect_revert(state, None, data.journaled_state.depth()),
-        HEVMCalls::ExpectRevert1(inner) => {
-            expect_revert(state, Some(inner.0.to_vec().into()), data.journaled_state.depth())
-        }
-        HEVMCalls::ExpectRevert2(inner) => {
-            expect_revert(state, Some(inner.0.to_vec().into()), data.journaled_state.depth())
+        HEVMCalls::ExpectRevert0(_) => {
+            expect_revert(state, None, RevertMatch::Exact, data.journaled_state.depth())
         }
+        HEVMCalls::ExpectRevert1(inner) => expect_revert(
+            state,
+            Some(inner.0.to_vec().into()),
+            RevertMatch::Exact,
+            data.journaled_state.depth(),
+        ),
+        HEVMCalls::ExpectRevert2(inner) => expect_revert(
+            state,
+            Some(inner.0.to_vec().into()),
+            RevertMatch::Exact,
+            data.journaled_state.depth(),
+        ),
+        HEVMCalls::ExpectPartialRevert(inner) => expect_revert(
+            state,
+            Some(inner.0.to_vec().into()),
+            RevertMatch::Selector,
+            data.journaled_state.depth(),
+        ),
+        HEVMCalls::ExpectRevertContains(inner) => expect_revert(
+            state,
+            Some(inner.0.clone().into_bytes().into()),
+            RevertMatch::Substring,
+            data.journaled_state.depth(),
+        ),
         HEVMCalls::ExpectEmit0(_) => {
             state.expected_emits.push(ExpectedEmit {
                 depth: data.journaled_state.depth() - 1,
@@ -332,6 +486,35 @@ pub fn apply<DB: DatabaseExt>(
             });
             Ok(Bytes::new())
         }
+        HEVMCalls::ExpectCallSequence(inner) => {
+            if inner.0.len() != inner.1.len() {
+                return Some(Err(format!(
+                    "Expected as many targets as calldata, got {} and {}",
+                    inner.0.len(),
+                    inner.1.len()
+                )
+                .encode()
+                .into()))
+            }
+            if state.expected_call_sequence.is_some() {
+                return Some(Err("You must call another function prior to expecting a second \
+                                 call sequence."
+                    .to_string()
+                    .encode()
+                    .into()))
+            }
+            state.expected_call_sequence = Some(ExpectedCallSequence {
+                depth: data.journaled_state.depth(),
+                expected: inner
+                    .0
+                    .iter()
+                    .zip(&inner.1)
+                    .map(|(target, data)| (*target, data.to_vec().into()))
+                    .collect(),
+                actual: vec![],
+            });
+            Ok(Bytes::new())
+        }
         HEVMCalls::MockCall0(inner) => {
             // TODO: Does this increase gas usage?
             if let Err(err) = data.journaled_state.load_account(inner.0, data.db) {
@@ -12,7 +12,7 @@ use ethers::{
     prelude::artifacts::CompactContractBytecode,
     types::*,
 };
-use foundry_common::{fmt::*, fs, get_artifact_path};
+use foundry_common::{fmt::*, fs, get_artifact_path, get_artifact_path_in};
 use foundry_config::fs_permissions::FsAccessKind;
 use hex::FromHex;
 use jsonpath_lib;
@@ -22,7 +22,7 @@ use std::{
     collections::BTreeMap,
     env,
     io::{BufRead, BufReader, Write},
-    path::Path,
+    path::{Path, PathBuf},
     process::Command,
     str::FromStr,
     time::UNIX_EPOCH,
@@ -42,6 +42,9 @@ fn ffi(state: &Cheatcodes, args: &[String]) -> Result<Bytes, Bytes> {
 
     trace!(?args, "invoking ffi");
 
+    // variables of the profile, unless they are set in the environment
+    cmd.envs(state.config.env.iter().filter(|(key, _)| env::var_os(key).is_none()));
+
     let output = cmd
         .current_dir(&state.config.root)
         .output()
@@ -120,12 +123,18 @@ struct HuffArtifact {
 
 /// Returns the _deployed_ bytecode (`bytecode`) of the matching artifact
 fn get_code(state: &Cheatcodes, path: &str) -> Result<Bytes, Bytes> {
-    let bytecode = read_bytecode(state, path)?;
-    if let Some(bin) = bytecode.into_bytecode() {
-        Ok(abi::encode(&[Token::Bytes(bin.to_vec())]).into())
-    } else {
-        Err(error::encode_error("No bytecode for contract. Is it abstract or unlinked?"))
-    }
+    let bin = read_creation_code(state, path)?;
+    Ok(abi::encode(&[Token::Bytes(bin.to_vec())]).into())
+}
+
+/// Reads the creation code (`bytecode`) of the matching artifact
+pub(super) fn read_creation_code(
+    state: &Cheatcodes,
+    path: &str,
+) -> Result<ethers::types::Bytes, Bytes> {
+    read_bytecode(state, path)?
+        .into_bytecode()
+        .ok_or_else(|| error::encode_error("No bytecode for contract. Is it abstract or unlinked?"))
 }
 
 /// Returns the _deployed_ bytecode (`bytecode`) of the matching artifact
@@ -138,16 +147,173 @@ fn get_deployed_code(state: &Cheatcodes, path: &str) -> Result<Bytes, Bytes> {
     }
 }
 
-/// Reads the bytecode object(s) from the matching artifact
-fn read_bytecode(state: &Cheatcodes, path: &str) -> Result<ArtifactBytecode, Bytes> {
-    let path = get_artifact_path(&state.config.paths, path);
+/// Returns the path of the matching artifact
+///
+/// Unless `path` is the path of an artifact, the artifact is looked up in the project's artifacts
+/// directory first and then in the configured `artifact_paths`. The file of a fully qualified
+/// name like `src/Counter.sol:Counter` matches artifacts of the full path and of the file name.
+fn find_artifact_path(state: &Cheatcodes, path: &str) -> PathBuf {
+    if path.ends_with(".json") {
+        return get_artifact_path(&state.config.paths, path)
+    }
+
+    let mut names = vec![path.to_string()];
+    let (file, contract) = match path.split_once(':') {
+        Some((file, contract)) => (file, Some(contract)),
+        None => (path, None),
+    };
+    if let Some(file_name) = Path::new(file).file_name().map(|name| name.to_string_lossy()) {
+        if file_name != file {
+            names.push(match contract {
+                Some(contract) => format!("{file_name}:{contract}"),
+                None => file_name.to_string(),
+            });
+        }
+    }
+
+    std::iter::once(&state.config.paths.artifacts)
+        .chain(&state.config.artifact_paths)
+        .flat_map(|dir| names.iter().map(move |name| get_artifact_path_in(dir, name)))
+        .find(|candidate| state.config.normalized_path(candidate).exists())
+        .unwrap_or_else(|| get_artifact_path(&state.config.paths, path))
+}
+
+/// Reads the matching artifact
+fn read_artifact(state: &Cheatcodes, path: &str) -> Result<String, Bytes> {
+    read_artifact_at(state, find_artifact_path(state, path))
+}
+
+/// Reads the artifact at the given path
+fn read_artifact_at(state: &Cheatcodes, path: PathBuf) -> Result<String, Bytes> {
     let path =
         state.config.ensure_path_allowed(path, FsAccessKind::Read).map_err(error::encode_error)?;
 
-    let data = fs::read_to_string(path).map_err(error::encode_error)?;
+    fs::read_to_string(path).map_err(error::encode_error)
+}
+
+/// Reads the bytecode object(s) from the matching artifact
+fn read_bytecode(state: &Cheatcodes, path: &str) -> Result<ArtifactBytecode, Bytes> {
+    let data = read_artifact(state, path)?;
     serde_json::from_str::<ArtifactBytecode>(&data).map_err(error::encode_error)
 }
 
+/// Returns the _deployed_ bytecode of the matching artifact with the values of its immutables
+/// patched in
+///
+/// Immutables are identified by their name or by the id of their declaration in the AST of the
+/// artifact. Names are looked up in the contract of the artifact and its base contracts declared
+/// in the same file.
+fn get_deployed_code_with_immutables(
+    state: &Cheatcodes,
+    path: &str,
+    immutables: &[String],
+    values: &[[u8; 32]],
+) -> Result<Bytes, Bytes> {
+    if immutables.len() != values.len() {
+        return Err(error::encode_error("Immutables and values must have the same length"))
+    }
+    let artifact_path = find_artifact_path(state, path);
+    // contract names can't contain dots, the artifacts of multiple versions are suffixed with
+    // `.<version>`
+    let contract = artifact_path
+        .file_stem()
+        .and_then(|stem| stem.to_str()?.split('.').next())
+        .unwrap_or_default()
+        .to_string();
+    let data = read_artifact_at(state, artifact_path)?;
+    let artifact = serde_json::from_str::<Value>(&data).map_err(error::encode_error)?;
+    let code = serde_json::from_value::<ArtifactBytecode>(artifact.clone())
+        .map_err(error::encode_error)?
+        .into_deployed_bytecode()
+        .ok_or_else(|| {
+            error::encode_error("No bytecode for contract. Is it abstract or unlinked?")
+        })?;
+    let mut code = code.to_vec();
+
+    for (immutable, value) in immutables.iter().zip(values) {
+        for (start, length) in immutable_references(&artifact, &contract, immutable)? {
+            let slot = code.get_mut(start..start + length).ok_or_else(|| {
+                error::encode_error(format!("Immutable `{immutable}` is out of bounds of the code"))
+            })?;
+            slot.copy_from_slice(&value[32 - length..]);
+        }
+    }
+    Ok(abi::encode(&[Token::Bytes(code)]).into())
+}
+
+/// Returns the offsets and lengths of the immutable in the deployed bytecode of the artifact of
+/// `contract`
+fn immutable_references(
+    artifact: &Value,
+    contract: &str,
+    immutable: &str,
+) -> Result<Vec<(usize, usize)>, Bytes> {
+    let id = match immutable.parse::<u64>() {
+        Ok(id) => id,
+        Err(_) => artifact
+            .get("ast")
+            .and_then(|ast| find_immutable_id(ast, contract, immutable))
+            .ok_or_else(|| {
+                error::encode_error(format!(
+                    "Immutable `{immutable}` of `{contract}` not found in the AST"
+                ))
+            })?,
+    };
+    artifact
+        .pointer(&format!("/deployedBytecode/immutableReferences/{id}"))
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            error::encode_error(format!("Immutable `{immutable}` is not used in the deployed code"))
+        })?
+        .iter()
+        .map(|reference| {
+            let start = reference.get("start").and_then(Value::as_u64);
+            let length = reference.get("length").and_then(Value::as_u64);
+            match (start, length) {
+                (Some(start), Some(length)) if length <= 32 => {
+                    Ok((start as usize, length as usize))
+                }
+                _ => Err(error::encode_error(format!("Invalid reference to `{immutable}`"))),
+            }
+        })
+        .collect()
+}
+
+/// Returns the id of the declaration of the immutable with the given name in `contract` or the
+/// first of its linearized base contracts that declares it
+///
+/// Only the contracts declared in the source unit of the AST are searched.
+fn find_immutable_id(ast: &Value, contract: &str, name: &str) -> Option<u64> {
+    let contracts = ast
+        .get("nodes")?
+        .as_array()?
+        .iter()
+        .filter(|node| node.get("nodeType").and_then(Value::as_str) == Some("ContractDefinition"))
+        .collect::<Vec<_>>();
+    let target =
+        contracts.iter().find(|node| node.get("name").and_then(Value::as_str) == Some(contract))?;
+
+    // the contract itself is the first of its linearized base contracts
+    let bases = target.get("linearizedBaseContracts")?.as_array()?;
+    bases.iter().filter_map(Value::as_u64).find_map(|base| {
+        let base =
+            contracts.iter().find(|node| node.get("id").and_then(Value::as_u64) == Some(base))?;
+        base.get("nodes")?
+            .as_array()?
+            .iter()
+            .find(|node| is_immutable_declaration(node, name))?
+            .get("id")?
+            .as_u64()
+    })
+}
+
+/// Returns true if the node is the declaration of the immutable with the given name
+fn is_immutable_declaration(node: &Value, name: &str) -> bool {
+    node.get("nodeType").and_then(Value::as_str) == Some("VariableDeclaration") &&
+        node.get("mutability").and_then(Value::as_str) == Some("immutable") &&
+        node.get("name").and_then(Value::as_str) == Some(name)
+}
+
 fn set_env(key: &str, val: &str) -> Result<Bytes, Bytes> {
     // `std::env::set_var` may panic in the following situations
     // ref: https://doc.rust-lang.org/std/env/fn.set_var.html
@@ -165,7 +331,18 @@ fn set_env(key: &str, val: &str) -> Result<Bytes, Bytes> {
     }
 }
 
+/// Returns the variable from the environment, or from the `env` of the profile
+fn env_var(state: &Cheatcodes, key: &str) -> Result<String, env::VarError> {
+    match env::var(key) {
+        Err(env::VarError::NotPresent) => {
+            state.config.env.get(key).cloned().ok_or(env::VarError::NotPresent)
+        }
+        res => res,
+    }
+}
+
 fn get_env(
+    state: &Cheatcodes,
     key: &str,
     r#type: ParamType,
     delim: Option<&str>,
@@ -173,9 +350,9 @@ fn get_env(
 ) -> Result<Bytes, Bytes> {
     let msg = format!("Failed to get environment variable `{key}` as type `{}`", &r#type);
     let val = if let Some(value) = default {
-        env::var(key).unwrap_or(value)
+        env_var(state, key).unwrap_or(value)
     } else {
-        env::var(key).map_err::<Bytes, _>(|e| error::encode_error(format!("{msg}: {e}")))?
+        env_var(state, key).map_err::<Bytes, _>(|e| error::encode_error(format!("{msg}: {e}")))?
     };
     let val = if let Some(d) = delim {
         val.split(d).map(|v| v.trim().to_string()).collect()
@@ -320,8 +497,10 @@ fn remove_file(state: &mut Cheatcodes, path: impl AsRef<Path>) -> Result<Bytes,
 ///
 /// This will return an error if no file/directory is found, or if the target path isn't allowed
 fn fs_metadata(state: &mut Cheatcodes, path: impl AsRef<Path>) -> Result<Bytes, Bytes> {
-    let path =
-        state.config.ensure_path_allowed(&path, FsAccessKind::Read).map_err(error::encode_error)?;
+    let path = state
+        .config
+        .ensure_path_allowed(&path, FsAccessKind::Metadata)
+        .map_err(error::encode_error)?;
 
     let metadata = path.metadata().map_err(error::encode_error)?;
 
@@ -554,81 +733,106 @@ pub fn apply(
             }
         }
         HEVMCalls::GetCode(inner) => get_code(state, &inner.0),
-        HEVMCalls::GetDeployedCode(inner) => get_deployed_code(state, &inner.0),
+        HEVMCalls::GetDeployedCode0(inner) => get_deployed_code(state, &inner.0),
+        HEVMCalls::GetDeployedCode1(inner) => {
+            get_deployed_code_with_immutables(state, &inner.0, &inner.1, &inner.2)
+        }
         HEVMCalls::SetEnv(inner) => set_env(&inner.0, &inner.1),
-        HEVMCalls::EnvBool0(inner) => get_env(&inner.0, ParamType::Bool, None, None),
-        HEVMCalls::EnvUint0(inner) => get_env(&inner.0, ParamType::Uint(256), None, None),
-        HEVMCalls::EnvInt0(inner) => get_env(&inner.0, ParamType::Int(256), None, None),
-        HEVMCalls::EnvAddress0(inner) => get_env(&inner.0, ParamType::Address, None, None),
-        HEVMCalls::EnvBytes320(inner) => get_env(&inner.0, ParamType::FixedBytes(32), None, None),
-        HEVMCalls::EnvString0(inner) => get_env(&inner.0, ParamType::String, None, None),
-        HEVMCalls::EnvBytes0(inner) => get_env(&inner.0, ParamType::Bytes, None, None),
-        HEVMCalls::EnvBool1(inner) => get_env(&inner.0, ParamType::Bool, Some(&inner.1), None),
-        HEVMCalls::EnvUint1(inner) => get_env(&inner.0, ParamType::Uint(256), Some(&inner.1), None),
-        HEVMCalls::EnvInt1(inner) => get_env(&inner.0, ParamType::Int(256), Some(&inner.1), None),
+        HEVMCalls::EnvBool0(inner) => get_env(state, &inner.0, ParamType::Bool, None, None),
+        HEVMCalls::EnvUint0(inner) => get_env(state, &inner.0, ParamType::Uint(256), None, None),
+        HEVMCalls::EnvInt0(inner) => get_env(state, &inner.0, ParamType::Int(256), None, None),
+        HEVMCalls::EnvAddress0(inner) => get_env(state, &inner.0, ParamType::Address, None, None),
+        HEVMCalls::EnvBytes320(inner) => {
+            get_env(state, &inner.0, ParamType::FixedBytes(32), None, None)
+        }
+        HEVMCalls::EnvString0(inner) => get_env(state, &inner.0, ParamType::String, None, None),
+        HEVMCalls::EnvBytes0(inner) => get_env(state, &inner.0, ParamType::Bytes, None, None),
+        HEVMCalls::EnvBool1(inner) => {
+            get_env(state, &inner.0, ParamType::Bool, Some(&inner.1), None)
+        }
+        HEVMCalls::EnvUint1(inner) => {
+            get_env(state, &inner.0, ParamType::Uint(256), Some(&inner.1), None)
+        }
+        HEVMCalls::EnvInt1(inner) => {
+            get_env(state, &inner.0, ParamType::Int(256), Some(&inner.1), None)
+        }
         HEVMCalls::EnvAddress1(inner) => {
-            get_env(&inner.0, ParamType::Address, Some(&inner.1), None)
+            get_env(state, &inner.0, ParamType::Address, Some(&inner.1), None)
         }
         HEVMCalls::EnvBytes321(inner) => {
-            get_env(&inner.0, ParamType::FixedBytes(32), Some(&inner.1), None)
+            get_env(state, &inner.0, ParamType::FixedBytes(32), Some(&inner.1), None)
+        }
+        HEVMCalls::EnvString1(inner) => {
+            get_env(state, &inner.0, ParamType::String, Some(&inner.1), None)
+        }
+        HEVMCalls::EnvBytes1(inner) => {
+            get_env(state, &inner.0, ParamType::Bytes, Some(&inner.1), None)
         }
-        HEVMCalls::EnvString1(inner) => get_env(&inner.0, ParamType::String, Some(&inner.1), None),
-        HEVMCalls::EnvBytes1(inner) => get_env(&inner.0, ParamType::Bytes, Some(&inner.1), None),
         HEVMCalls::EnvOr0(inner) => {
-            get_env(&inner.0, ParamType::Bool, None, Some(inner.1.to_string()))
+            get_env(state, &inner.0, ParamType::Bool, None, Some(inner.1.to_string()))
         }
         HEVMCalls::EnvOr1(inner) => {
-            get_env(&inner.0, ParamType::Uint(256), None, Some(inner.1.to_string()))
+            get_env(state, &inner.0, ParamType::Uint(256), None, Some(inner.1.to_string()))
         }
         HEVMCalls::EnvOr2(inner) => {
-            get_env(&inner.0, ParamType::Int(256), None, Some(inner.1.to_string()))
+            get_env(state, &inner.0, ParamType::Int(256), None, Some(inner.1.to_string()))
         }
         HEVMCalls::EnvOr3(inner) => {
-            get_env(&inner.0, ParamType::Address, None, Some(hex::encode(inner.1)))
+            get_env(state, &inner.0, ParamType::Address, None, Some(hex::encode(inner.1)))
         }
         HEVMCalls::EnvOr4(inner) => {
-            get_env(&inner.0, ParamType::FixedBytes(32), None, Some(hex::encode(inner.1)))
+            get_env(state, &inner.0, ParamType::FixedBytes(32), None, Some(hex::encode(inner.1)))
         }
         HEVMCalls::EnvOr5(inner) => {
-            get_env(&inner.0, ParamType::String, None, Some(inner.1.to_string()))
+            get_env(state, &inner.0, ParamType::String, None, Some(inner.1.to_string()))
         }
         HEVMCalls::EnvOr6(inner) => {
-            get_env(&inner.0, ParamType::Bytes, None, Some(hex::encode(&inner.1)))
+            get_env(state, &inner.0, ParamType::Bytes, None, Some(hex::encode(&inner.1)))
         }
         HEVMCalls::EnvOr7(inner) => get_env(
+            state,
             &inner.0,
             ParamType::Bool,
             Some(&inner.1),
             Some(inner.2.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(&inner.1)),
         ),
         HEVMCalls::EnvOr8(inner) => get_env(
+            state,
             &inner.0,
             ParamType::Uint(256),
             Some(&inner.1),
             Some(inner.2.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(&inner.1)),
         ),
         HEVMCalls::EnvOr9(inner) => get_env(
+            state,
             &inner.0,
             ParamType::Int(256),
             Some(&inner.1),
             Some(inner.2.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(&inner.1)),
         ),
         HEVMCalls::EnvOr10(inner) => get_env(
+            state,
             &inner.0,
             ParamType::Address,
             Some(&inner.1),
             Some(inner.2.iter().map(hex::encode).collect::<Vec<_>>().join(&inner.1)),
         ),
         HEVMCalls::EnvOr11(inner) => get_env(
+            state,
             &inner.0,
             ParamType::FixedBytes(32),
             Some(&inner.1),
             Some(inner.2.iter().map(hex::encode).collect::<Vec<_>>().join(&inner.1)),
         ),
-        HEVMCalls::EnvOr12(inner) => {
-            get_env(&inner.0, ParamType::String, Some(&inner.1), Some(inner.2.join(&inner.1)))
-        }
+        HEVMCalls::EnvOr12(inner) => get_env(
+            state,
+            &inner.0,
+            ParamType::String,
+            Some(&inner.1),
+            Some(inner.2.join(&inner.1)),
+        ),
         HEVMCalls::EnvOr13(inner) => get_env(
+            state,
             &inner.0,
             ParamType::Bytes,
             Some(&inner.1),
@@ -784,4 +988,18 @@ mod tests {
         let artifact: ArtifactBytecode = serde_json::from_str(s).unwrap();
         assert!(artifact.into_deployed_bytecode().is_some());
     }
+
+    #[test]
+    fn can_find_immutables_of_linearized_bases() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../testdata/fixtures/GetCode/ImmutableOwner.json");
+        let artifact: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        let ast = &artifact["ast"];
+
+        // declared by the base contract, not by the other contract in the file
+        assert_eq!(find_immutable_id(ast, "ImmutableOwner", "owner"), Some(5));
+        assert_eq!(find_immutable_id(ast, "OtherOwner", "owner"), Some(8));
+        assert_eq!(find_immutable_id(ast, "ImmutableOwner", "admin"), None);
+        assert_eq!(find_immutable_id(ast, "Missing", "owner"), None);
+    }
 }
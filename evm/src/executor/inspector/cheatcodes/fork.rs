@@ -185,6 +185,7 @@ fn create_select_fork<DB: DatabaseExt>(
     // No need to correct since the sender's nonce does not get incremented when selecting a fork.
     state.corrected_nonce = true;
 
+    let block = block.or_else(|| state.config.rpc_endpoints.pinned_block(&url_or_alias));
     let fork = create_fork_request(state, url_or_alias, block, data)?;
     data.db
         .create_select_fork(fork, data.env, &mut data.journaled_state)
@@ -198,6 +199,7 @@ fn create_fork<DB: DatabaseExt>(
     url_or_alias: String,
     block: Option<u64>,
 ) -> Result<U256, Bytes> {
+    let block = block.or_else(|| state.config.rpc_endpoints.pinned_block(&url_or_alias));
     let fork = create_fork_request(state, url_or_alias, block, data)?;
     data.db.create_fork(fork).map_err(error::encode_error)
 }
@@ -0,0 +1,253 @@
+use super::{Cheatcodes, CheatsConfig};
+use crate::{abi::HEVMCalls, executor::backend::DatabaseExt};
+use bytes::Bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, H256},
+};
+use foundry_config::fs_permissions::FsAccessKind;
+use revm::EVMData;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::{
+    fmt::Write,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// A log as it is stored in a snapshot file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotLog {
+    pub emitter: Address,
+    pub topics: Vec<H256>,
+    pub data: ethers::types::Bytes,
+}
+
+/// The logs of a test that are compared against a snapshot file, see `expectSnapshot`
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedSnapshot {
+    /// The name of the snapshot file, relative to the snapshots directory and without extension
+    pub name: String,
+    /// All logs emitted since `expectSnapshot` was called, except those of reverted call frames
+    pub logs: Vec<SnapshotLog>,
+    /// The number of logs at the start of every entered call frame, with the depth of the frame
+    checkpoints: Vec<(u64, usize)>,
+}
+
+impl ExpectedSnapshot {
+    /// Creates an expected snapshot without logs
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..Default::default() }
+    }
+
+    /// Records the number of logs before a call frame at the given depth is entered
+    pub fn checkpoint(&mut self, depth: u64) {
+        self.checkpoints.push((depth, self.logs.len()));
+    }
+
+    /// Called when the call frame at the given depth ended, drops the logs of the frame if it was
+    /// not successful, since they are not part of the transaction
+    pub fn on_frame_end(&mut self, depth: u64, success: bool) {
+        if !matches!(self.checkpoints.last(), Some((d, _)) if *d == depth) {
+            return
+        }
+        let (_, len) = self.checkpoints.pop().expect("checkpoint exists; qed");
+        if !success {
+            self.logs.truncate(len);
+        }
+    }
+
+    /// Compares the recorded logs against the snapshot file
+    ///
+    /// The snapshot file is only written if `update_snapshots` is set in the config, a missing
+    /// snapshot is an error otherwise. Returns a diff of the snapshot and the recorded logs if they
+    /// don't match.
+    pub fn check(&self, config: &CheatsConfig, commit: bool) -> Result<(), String> {
+        let path = snapshot_file(config, &self.name)?;
+        let mut actual = serde_json::to_string_pretty(&self.logs).map_err(|err| err.to_string())?;
+        actual.push('\n');
+
+        if !config.update_snapshots {
+            if !path.exists() {
+                return Err(format!(
+                    "Snapshot `{}` does not exist, run with `--update-snapshots` to create it",
+                    self.name
+                ))
+            }
+            let expected = fs::read_to_string(&path)
+                .map_err(|err| format!("failed to read snapshot {}: {err}", path.display()))?;
+            if expected != actual {
+                return Err(format!(
+                    "Logs != snapshot `{}`, run with `--update-snapshots` to update it:\n{}",
+                    self.name,
+                    diff(&expected, &actual)
+                ))
+            }
+            return Ok(())
+        }
+
+        if commit {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, actual)
+                .map_err(|err| format!("failed to write snapshot {}: {err}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of the snapshot file with the given name
+///
+/// Names must be relative paths that stay inside the snapshots directory.
+fn snapshot_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name);
+    if name.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("invalid snapshot name `{name}`"))
+    }
+    Ok(dir.join(format!("{name}.json")))
+}
+
+/// Returns the path of the snapshot file with the given name if the `fs_permissions` allow
+/// reading it, or writing it if snapshots are updated
+fn snapshot_file(config: &CheatsConfig, name: &str) -> Result<PathBuf, String> {
+    let path = snapshot_path(&config.snapshots, name)?;
+    let kind = if config.update_snapshots { FsAccessKind::Write } else { FsAccessKind::Read };
+    config.ensure_path_allowed(path, kind)
+}
+
+/// Returns a line diff of the expected and the actual snapshot
+fn diff(expected: &str, actual: &str) -> String {
+    let mut out = String::new();
+    for change in TextDiff::from_lines(expected, actual).iter_all_changes() {
+        let sign = match change.tag() {
+            ChangeTag::Delete => "-",
+            ChangeTag::Insert => "+",
+            ChangeTag::Equal => " ",
+        };
+        let _ = write!(out, "{sign}{change}");
+    }
+    out
+}
+
+pub fn apply<DB: DatabaseExt>(
+    state: &mut Cheatcodes,
+    _: &mut EVMData<'_, DB>,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::ExpectSnapshot(inner) => {
+            if let Err(err) = snapshot_file(&state.config, &inner.0) {
+                return Some(Err(err.encode().into()))
+            }
+            if state.expected_snapshot.is_some() {
+                Err("You must finish the test before expecting a second snapshot."
+                    .to_string()
+                    .encode()
+                    .into())
+            } else {
+                state.expected_snapshot = Some(ExpectedSnapshot::new(inner.0.clone()));
+                Ok(Bytes::new())
+            }
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
+
+    fn config(root: &Path, fs_permissions: FsPermissions) -> CheatsConfig {
+        CheatsConfig::new(
+            &Config { __root: root.to_path_buf().into(), fs_permissions, ..Default::default() },
+            &Default::default(),
+        )
+    }
+
+    fn log(data: u8) -> SnapshotLog {
+        SnapshotLog {
+            emitter: Address::repeat_byte(1),
+            topics: vec![H256::repeat_byte(2)],
+            data: vec![data].into(),
+        }
+    }
+
+    #[test]
+    fn can_validate_snapshot_names() {
+        let dir = Path::new("/project/snapshots");
+        assert_eq!(
+            snapshot_path(dir, "token/transfer").unwrap(),
+            PathBuf::from("/project/snapshots/token/transfer.json")
+        );
+        assert!(snapshot_path(dir, "").is_err());
+        assert!(snapshot_path(dir, "../transfer").is_err());
+        assert!(snapshot_path(dir, "/etc/transfer").is_err());
+    }
+
+    #[test]
+    fn can_check_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let permissions = FsPermissions::new(vec![PathPermission::read_write("./")]);
+        let mut config = config(dir.path(), permissions);
+        let file = dir.path().join("snapshots/logs.json");
+        let mut snapshot = ExpectedSnapshot::new("logs");
+        snapshot.logs.push(log(3));
+
+        // a missing snapshot is only written when updating
+        let err = snapshot.check(&config, true).unwrap_err();
+        assert!(err.contains("does not exist"), "{err}");
+        assert!(!file.exists());
+        config.update_snapshots = true;
+        snapshot.check(&config, true).unwrap();
+        assert!(file.exists());
+        config.update_snapshots = false;
+        snapshot.check(&config, true).unwrap();
+
+        snapshot.logs[0].data = vec![4].into();
+        let err = snapshot.check(&config, true).unwrap_err();
+        assert!(err.contains("-    \"data\": \"0x03\""));
+        assert!(err.contains("+    \"data\": \"0x04\""));
+
+        config.update_snapshots = true;
+        snapshot.check(&config, true).unwrap();
+        config.update_snapshots = false;
+        snapshot.check(&config, true).unwrap();
+    }
+
+    #[test]
+    fn requires_fs_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let permissions = FsPermissions::new(vec![PathPermission::read("./snapshots")]);
+        let mut config = config(dir.path(), permissions);
+        assert!(snapshot_file(&config, "logs").is_ok());
+
+        config.update_snapshots = true;
+        let err = snapshot_file(&config, "logs").unwrap_err();
+        assert!(err.contains("is not allowed to be accessed"), "{err}");
+        let err = ExpectedSnapshot::new("logs").check(&config, true).unwrap_err();
+        assert!(err.contains("is not allowed to be accessed"), "{err}");
+    }
+
+    #[test]
+    fn drops_logs_of_reverted_frames() {
+        let mut snapshot = ExpectedSnapshot::new("logs");
+        snapshot.logs.push(log(1));
+
+        snapshot.checkpoint(1);
+        snapshot.logs.push(log(2));
+        snapshot.checkpoint(2);
+        snapshot.logs.push(log(3));
+        snapshot.on_frame_end(2, false);
+        snapshot.on_frame_end(1, true);
+
+        snapshot.checkpoint(1);
+        snapshot.logs.push(log(4));
+        snapshot.on_frame_end(1, false);
+
+        // the frame that called `expectSnapshot` has no checkpoint
+        snapshot.on_frame_end(0, true);
+        assert_eq!(snapshot.logs, vec![log(1), log(2)]);
+    }
+}
@@ -0,0 +1,118 @@
+use super::{provenance::is_cheatcode_call, Cheatcodes};
+use crate::{
+    abi::{
+        hevm::{ReadMemoryCall, ReadReturndataCall},
+        HEVMCalls,
+    },
+    error,
+};
+use bytes::Bytes;
+use ethers::{
+    abi::{self, Token},
+    contract::EthCall,
+    types::{Selector, U256},
+};
+use revm::{opcode, Interpreter};
+
+/// The memory and return data buffer of the frame that called `readMemory` or `readReturndata`,
+/// captured right before the call
+#[derive(Clone, Debug, Default)]
+pub struct CallerFrame {
+    pub memory: Bytes,
+    pub returndata: Bytes,
+}
+
+impl CallerFrame {
+    /// Captures the frame if the current opcode is a call of `readMemory` or `readReturndata`, so
+    /// the memory is not copied for any other cheatcode
+    pub fn capture(interpreter: &Interpreter) -> Option<Self> {
+        if !is_cheatcode_call(interpreter) {
+            return None
+        }
+        let selector = call_selector(interpreter)?;
+        if selector != ReadMemoryCall::selector() && selector != ReadReturndataCall::selector() {
+            return None
+        }
+        Some(Self {
+            memory: Bytes::copy_from_slice(interpreter.memory.data()),
+            returndata: interpreter.return_data_buffer.clone(),
+        })
+    }
+
+    /// Returns the `length` bytes of memory starting at `offset`
+    fn read_memory(&self, offset: U256, length: U256) -> Result<Bytes, Bytes> {
+        let size = self.memory.len();
+        match offset.checked_add(length) {
+            Some(end) if end <= U256::from(size) => {
+                Ok(self.memory.slice(offset.as_usize()..end.as_usize()))
+            }
+            _ => Err(error::encode_error(format!(
+                "Memory range of {length} bytes at offset {offset} exceeds the memory size of {size} bytes"
+            ))),
+        }
+    }
+}
+
+/// Returns the selector of the calldata of the call at the current opcode
+fn call_selector(interpreter: &Interpreter) -> Option<Selector> {
+    // the calldata is preceded by the value of `CALL` and `CALLCODE`
+    let op = interpreter.contract.bytecode.bytecode()[interpreter.program_counter()];
+    let args = if matches!(op, opcode::CALL | opcode::CALLCODE) { 3 } else { 2 };
+    let offset = interpreter.stack().peek(args).ok()?;
+    let size = interpreter.stack().peek(args + 1).ok()?;
+    let memory = interpreter.memory.data();
+    if size < U256::from(4) || offset >= U256::from(memory.len()) {
+        return None
+    }
+    let offset = offset.as_usize();
+    memory.get(offset..offset + 4)?.try_into().ok()
+}
+
+pub fn apply(state: &mut Cheatcodes, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
+    let frame = match call {
+        HEVMCalls::ReadMemory(_) | HEVMCalls::ReadReturndata(_) => {
+            if !state.config.memory_access {
+                return Some(Err(error::encode_error(
+                    "Memory access disabled: set `memory_access = true` in your config if you want to allow tests to read the memory of the calling frame.",
+                )))
+            }
+            match state.caller_frame.take() {
+                Some(frame) => frame,
+                None => {
+                    return Some(Err(error::encode_error(
+                        "The memory of the calling frame was not captured",
+                    )))
+                }
+            }
+        }
+        _ => return None,
+    };
+
+    let res = match call {
+        HEVMCalls::ReadMemory(inner) => frame.read_memory(inner.0, inner.1).map(encode_bytes),
+        HEVMCalls::ReadReturndata(_) => Ok(encode_bytes(frame.returndata)),
+        _ => unreachable!(),
+    };
+    Some(res)
+}
+
+fn encode_bytes(data: Bytes) -> Bytes {
+    abi::encode(&[Token::Bytes(data.to_vec())]).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_memory_range() {
+        let frame = CallerFrame {
+            memory: Bytes::from_static(&[1, 2, 3, 4, 5, 6]),
+            returndata: Bytes::new(),
+        };
+        assert_eq!(frame.read_memory(2.into(), 3.into()).unwrap(), Bytes::from_static(&[3, 4, 5]));
+        assert_eq!(frame.read_memory(6.into(), 0.into()).unwrap(), Bytes::new());
+        assert!(frame.read_memory(4.into(), 3.into()).is_err());
+        assert!(frame.read_memory(U256::MAX, 2.into()).is_err());
+    }
+}
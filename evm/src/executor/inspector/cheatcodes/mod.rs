@@ -22,8 +22,8 @@ use ethers::{
 };
 use itertools::Itertools;
 use revm::{
-    opcode, BlockEnv, CallInputs, CreateInputs, EVMData, Gas, Inspector, Interpreter, Return,
-    TransactTo,
+    opcode, return_ok, BlockEnv, CallInputs, CreateInputs, EVMData, Gas, Inspector, Interpreter,
+    Return, TransactTo,
 };
 use serde_json::Value;
 use std::{
@@ -36,12 +36,20 @@ use std::{
 };
 use tracing::trace;
 
+/// EIP-7702 delegations (`signDelegation`, `attachDelegation`)
+mod delegation;
+/// Deployment of artifacts (`deployCode`)
+mod deploy;
+pub(crate) use deploy::transact_deployment;
+use deploy::Deployment;
 /// Cheatcodes related to the execution environment.
 mod env;
 pub use env::{Log, Prank, RecordAccess};
 /// Assertion helpers (such as `expectEmit`)
 mod expect;
-pub use expect::{ExpectedCallData, ExpectedEmit, ExpectedRevert, MockCallDataContext};
+pub use expect::{
+    ExpectedCallData, ExpectedCallSequence, ExpectedEmit, ExpectedRevert, MockCallDataContext,
+};
 
 /// Cheatcodes that interact with the external environment (FFI etc.)
 mod ext;
@@ -49,8 +57,29 @@ mod ext;
 mod fork;
 /// Cheatcodes that configure the fuzzer
 mod fuzz;
+/// Comparison of logs against snapshot files (`expectSnapshot`)
+mod log_snapshot;
+pub use log_snapshot::{ExpectedSnapshot, SnapshotLog};
+/// Memory and return data inspection cheatcodes (`readMemory` etc.)
+mod memory;
+pub use memory::CallerFrame;
+/// On-chain preconditions of scripts (`assertOnChain*`)
+mod preflight;
+pub use preflight::OnChainAssertionFailure;
+/// The source of failing cheatcodes
+mod provenance;
+pub use provenance::{CheatcodeCallSite, CheatcodeFailure};
 /// Snapshot related cheatcodes
 mod snapshot;
+/// Recording of account and storage accesses (`startStateDiffRecording`) and state diffs
+mod state_diff;
+pub use state_diff::{
+    AccountAccess, AccountAccessKind, AccountStateDiff, StateDiffRecorder, StorageAccess,
+    StorageDiff,
+};
+/// Emulation of transient storage (`TLOAD` and `TSTORE`)
+mod transient;
+pub use transient::TransientStorage;
 /// Utility cheatcodes (`sign` etc.)
 pub mod util;
 pub use util::{BroadcastableTransaction, DEFAULT_CREATE2_DEPLOYER};
@@ -71,8 +100,8 @@ mod error;
 /// `readFile` which can manipulate files of the filesystem. Therefore, several restrictions are
 /// implemented for these cheatcodes:
 ///
-///    - `ffi`, and file cheatcodes are _always_ opt-in (via foundry config) and never enabled by
-///      default: all respective cheatcode handlers implement the appropriate checks
+///    - `ffi`, file and memory access cheatcodes are _always_ opt-in (via foundry config) and never
+///      enabled by default: all respective cheatcode handlers implement the appropriate checks
 ///    - File cheatcodes require explicit permissions which paths are allowed for which operation,
 ///      see `Config.fs_permission`
 ///    - Only permitted accounts are allowed to execute cheatcodes in forking mode, this ensures no
@@ -114,15 +143,24 @@ pub struct Cheatcodes {
     /// Recorded logs
     pub recorded_logs: Option<RecordedLogs>,
 
+    /// Recorded account and storage accesses
+    pub state_diff: Option<StateDiffRecorder>,
+
     /// Mocked calls
     pub mocked_calls: BTreeMap<Address, BTreeMap<MockCallDataContext, Bytes>>,
 
     /// Expected calls
     pub expected_calls: BTreeMap<Address, Vec<ExpectedCallData>>,
 
+    /// The ordered calls expected during the next call
+    pub expected_call_sequence: Option<ExpectedCallSequence>,
+
     /// Expected emits
     pub expected_emits: Vec<ExpectedEmit>,
 
+    /// The snapshot the logs of the test are compared against
+    pub expected_snapshot: Option<ExpectedSnapshot>,
+
     /// Map of context depths to memory offset ranges that may be written to within the call depth.
     pub allowed_mem_writes: BTreeMap<u64, Vec<Range<u64>>>,
 
@@ -163,6 +201,33 @@ pub struct Cheatcodes {
     /// CREATE / CREATE2 frames. This is needed to make gas meter pausing work correctly when
     /// paused and creating new contracts.
     pub gas_metering_create: Option<Option<revm::Gas>>,
+
+    /// Transient storage of all accounts, the EVM does not support `TLOAD` and `TSTORE` yet
+    pub transient_storage: TransientStorage,
+
+    /// The memory and return data of the frame that made the current cheatcode call, only
+    /// captured if `memory_access` is enabled
+    pub caller_frame: Option<CallerFrame>,
+
+    /// The instruction that made the current cheatcode call
+    pub cheatcode_call_site: Option<CheatcodeCallSite>,
+
+    /// The last cheatcode that failed, including mismatched `expectRevert`s
+    pub cheatcode_failure: Option<CheatcodeFailure>,
+
+    /// Whether the `preflight()` function of a script is executed, `assertOnChain*` cheatcodes
+    /// revert everywhere else
+    pub preflight: bool,
+
+    /// The preconditions of `assertOnChain*` cheatcodes that didn't hold
+    pub onchain_assertion_failures: Vec<OnChainAssertionFailure>,
+
+    /// The number of seconds the block timestamp advances by, along with the block number
+    /// advancing by one, before every top-level call of a test
+    pub auto_advance_time: Option<u64>,
+
+    /// The deployment requested by the current cheatcode call, which the inspector stack executes
+    pub(crate) pending_deployment: Option<Deployment>,
 }
 
 impl Cheatcodes {
@@ -197,10 +262,15 @@ impl Cheatcodes {
             .transpose()
             .or_else(|| util::apply(self, data, &decoded))
             .or_else(|| expect::apply(self, data, &decoded))
+            .or_else(|| log_snapshot::apply(self, data, &decoded))
             .or_else(|| fuzz::apply(data, &decoded))
             .or_else(|| ext::apply(self, self.config.ffi, &decoded))
+            .or_else(|| deploy::apply(self, caller, &decoded))
             .or_else(|| snapshot::apply(self, data, &decoded))
             .or_else(|| fork::apply(self, data, &decoded))
+            .or_else(|| memory::apply(self, &decoded))
+            .or_else(|| delegation::apply(data, &decoded))
+            .or_else(|| preflight::apply(self, data, &decoded))
             .ok_or_else(|| "Cheatcode was unhandled. This is a bug.".to_string().encode())?
     }
 
@@ -230,6 +300,17 @@ impl Cheatcodes {
         data.db.allow_cheatcode_access(created_address);
     }
 
+    /// Records the failure of the cheatcode that was called at `call_site`
+    fn record_failure<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call_site: Option<&CheatcodeCallSite>,
+        retdata: &Bytes,
+    ) {
+        self.cheatcode_failure =
+            call_site.map(|call_site| CheatcodeFailure::new(data, call_site, retdata.clone()));
+    }
+
     /// Called when there was a revert.
     ///
     /// Cleanup any previously applied cheatcodes that altered the state in such a way that revm's
@@ -373,6 +454,22 @@ where
             }
         }
 
+        if let Some(call_site) = CheatcodeCallSite::capture(interpreter) {
+            self.cheatcode_call_site = Some(call_site);
+        }
+
+        // Capture the memory of the frame if it is about to call a cheatcode
+        if self.config.memory_access {
+            if let Some(frame) = CallerFrame::capture(interpreter) {
+                self.caller_frame = Some(frame);
+            }
+        }
+
+        // Record account and storage accesses if `startStateDiffRecording` has been called
+        if let Some(state_diff) = &mut self.state_diff {
+            state_diff.step(interpreter, data);
+        }
+
         // If the allowed memory writes cheatcode is active at this context depth, check to see
         // if the current opcode can either mutate directly or expand memory. If the opcode at
         // the current program counter is a match, check if the modified memory lies within the
@@ -499,6 +596,20 @@ where
         Return::Continue
     }
 
+    fn step_end(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _: bool,
+        _: Return,
+    ) -> Return {
+        if let Some(state_diff) = &mut self.state_diff {
+            state_diff.step_end(interpreter, data);
+        }
+
+        Return::Continue
+    }
+
     fn log(&mut self, _: &mut EVMData<'_, DB>, address: &Address, topics: &[H256], data: &Bytes) {
         // Match logs if `expectEmit` has been called
         if !self.expected_emits.is_empty() {
@@ -516,6 +627,15 @@ where
                 inner: RawLog { topics: topics.to_vec(), data: data.to_vec() },
             });
         }
+
+        // Stores this log if `expectSnapshot` has been called
+        if let Some(snapshot) = &mut self.expected_snapshot {
+            snapshot.logs.push(SnapshotLog {
+                emitter: *address,
+                topics: topics.to_vec(),
+                data: data.to_vec().into(),
+            });
+        }
     }
 
     fn call(
@@ -526,10 +646,26 @@ where
     ) -> (Return, Gas, Bytes) {
         if call.contract == CHEATCODE_ADDRESS {
             match self.apply_cheatcode(data, call.context.caller, call) {
+                // the inspector stack executes the deployment, see `transact_deployment`
+                Ok(retdata) if self.pending_deployment.is_some() => {
+                    (Return::Continue, Gas::new(call.gas_limit), retdata)
+                }
                 Ok(retdata) => (Return::Return, Gas::new(call.gas_limit), retdata),
-                Err(err) => (Return::Revert, Gas::new(call.gas_limit), err),
+                Err(err) => {
+                    let call_site = self.cheatcode_call_site.take();
+                    self.record_failure(data, call_site.as_ref(), &err);
+                    (Return::Revert, Gas::new(call.gas_limit), err)
+                }
             }
         } else if call.contract != HARDHAT_CONSOLE_ADDRESS {
+            // Advance the chain time before every call the test contract makes
+            if let Some(secs) = self.auto_advance_time {
+                if data.journaled_state.depth() == 1 {
+                    data.env.block.timestamp = data.env.block.timestamp.saturating_add(secs.into());
+                    data.env.block.number = data.env.block.number.saturating_add(U256::one());
+                }
+            }
+
             // Handle expected calls
             if let Some(expecteds) = self.expected_calls.get_mut(&call.contract) {
                 if let Some(found_match) = expecteds.iter().position(|expected| {
@@ -543,6 +679,11 @@ where
                 }
             }
 
+            // Record the calls an expected call sequence is compared against
+            if let Some(sequence) = &mut self.expected_call_sequence {
+                sequence.on_call(data.journaled_state.depth(), call);
+            }
+
             // Handle mocked calls
             if let Some(mocks) = self.mocked_calls.get(&call.contract) {
                 let ctx = MockCallDataContext {
@@ -642,6 +783,14 @@ where
                 }
             }
 
+            self.transient_storage.checkpoint(data.journaled_state.depth());
+            if let Some(snapshot) = &mut self.expected_snapshot {
+                snapshot.checkpoint(data.journaled_state.depth());
+            }
+            if let Some(state_diff) = &mut self.state_diff {
+                state_diff.on_call(data, call);
+            }
+
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
         } else {
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
@@ -653,14 +802,23 @@ where
         data: &mut EVMData<'_, DB>,
         call: &CallInputs,
         remaining_gas: Gas,
-        status: Return,
-        retdata: Bytes,
+        mut status: Return,
+        mut retdata: Bytes,
         _: bool,
     ) -> (Return, Gas, Bytes) {
         if call.contract == CHEATCODE_ADDRESS || call.contract == HARDHAT_CONSOLE_ADDRESS {
             return (status, remaining_gas, retdata)
         }
 
+        self.transient_storage
+            .on_frame_end(data.journaled_state.depth(), matches!(status, return_ok!()));
+        if let Some(snapshot) = &mut self.expected_snapshot {
+            snapshot.on_frame_end(data.journaled_state.depth(), matches!(status, return_ok!()));
+        }
+        if let Some(state_diff) = &mut self.state_diff {
+            state_diff.on_frame_end(data, matches!(status, return_ok!()));
+        }
+
         // Clean up pranks
         if let Some(prank) = &self.prank {
             if data.journaled_state.depth() == prank.depth {
@@ -682,6 +840,18 @@ where
             }
         }
 
+        // Handle the expected call sequence once the next call ended, a mismatch is a revert that
+        // can be expected itself
+        if let Some(sequence) = &self.expected_call_sequence {
+            if sequence.is_started() && data.journaled_state.depth() <= sequence.depth {
+                let sequence = self.expected_call_sequence.take().unwrap();
+                if let Err(err) = sequence.check() {
+                    status = Return::Revert;
+                    retdata = err;
+                }
+            }
+        }
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if data.journaled_state.depth() <= expected_revert.depth {
@@ -689,11 +859,13 @@ where
                 return match handle_expect_revert(
                     false,
                     expected_revert.reason.as_ref(),
+                    expected_revert.kind,
                     status,
                     retdata,
                 ) {
                     Err(retdata) => {
                         trace!(expected=?expected_revert, actual=%hex::encode(&retdata), ?status, "Expected revert mismatch");
+                        self.record_failure(data, expected_revert.call_site.as_ref(), &retdata);
                         (Return::Revert, remaining_gas, retdata)
                     }
                     Ok((_, retdata)) => (Return::Return, remaining_gas, retdata),
@@ -744,6 +916,17 @@ where
                 )
             }
 
+            if self.expected_call_sequence.take().is_some() {
+                return (
+                    Return::Revert,
+                    remaining_gas,
+                    "Expected a call sequence, but no call was made afterward"
+                        .to_string()
+                        .encode()
+                        .into(),
+                )
+            }
+
             // Check if we have any leftover expected emits
             if !self.expected_emits.is_empty() {
                 return (
@@ -755,6 +938,13 @@ where
                         .into(),
                 )
             }
+
+            // Compare the logs of the test against the expected snapshot
+            if let Some(snapshot) = self.expected_snapshot.take() {
+                if let Err(err) = snapshot.check(&self.config, self.fs_commit) {
+                    return (Return::Revert, remaining_gas, err.encode().into())
+                }
+            }
         }
 
         // if there's a revert and a previous call was diagnosed as fork related revert then we can
@@ -818,8 +1008,11 @@ where
 
                 data.env.tx.caller = broadcast.new_origin;
 
+                let create2_deployer =
+                    self.config.create2_deployer(data.env.cfg.chain_id.as_u64());
                 let (bytecode, to, nonce) = match process_create(
                     broadcast.new_origin,
+                    create2_deployer,
                     call.init_code.clone(),
                     data,
                     call,
@@ -847,6 +1040,14 @@ where
             }
         }
 
+        self.transient_storage.checkpoint(data.journaled_state.depth());
+        if let Some(snapshot) = &mut self.expected_snapshot {
+            snapshot.checkpoint(data.journaled_state.depth());
+        }
+        if let Some(state_diff) = &mut self.state_diff {
+            state_diff.on_create(data, call);
+        }
+
         (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -854,11 +1055,20 @@ where
         &mut self,
         data: &mut EVMData<'_, DB>,
         _: &CreateInputs,
-        status: Return,
+        mut status: Return,
         address: Option<Address>,
         remaining_gas: Gas,
-        retdata: Bytes,
+        mut retdata: Bytes,
     ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.transient_storage
+            .on_frame_end(data.journaled_state.depth(), matches!(status, return_ok!()));
+        if let Some(snapshot) = &mut self.expected_snapshot {
+            snapshot.on_frame_end(data.journaled_state.depth(), matches!(status, return_ok!()));
+        }
+        if let Some(state_diff) = &mut self.state_diff {
+            state_diff.on_frame_end(data, matches!(status, return_ok!()));
+        }
+
         // Clean up pranks
         if let Some(prank) = &self.prank {
             if data.journaled_state.depth() == prank.depth {
@@ -880,6 +1090,18 @@ where
             }
         }
 
+        // Handle the expected call sequence once the next call ended, a mismatch is a revert that
+        // can be expected itself
+        if let Some(sequence) = &self.expected_call_sequence {
+            if sequence.is_started() && data.journaled_state.depth() <= sequence.depth {
+                let sequence = self.expected_call_sequence.take().unwrap();
+                if let Err(err) = sequence.check() {
+                    status = Return::Revert;
+                    retdata = err;
+                }
+            }
+        }
+
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
             if data.journaled_state.depth() <= expected_revert.depth {
@@ -887,10 +1109,14 @@ where
                 return match handle_expect_revert(
                     true,
                     expected_revert.reason.as_ref(),
+                    expected_revert.kind,
                     status,
                     retdata,
                 ) {
-                    Err(retdata) => (Return::Revert, None, remaining_gas, retdata),
+                    Err(retdata) => {
+                        self.record_failure(data, expected_revert.call_site.as_ref(), &retdata);
+                        (Return::Revert, None, remaining_gas, retdata)
+                    }
                     Ok((address, retdata)) => (Return::Return, address, remaining_gas, retdata),
                 }
             }
@@ -929,18 +1155,20 @@ pub struct DealRecord {
 mod revert_helper {
     use super::*;
 
-    /// Helper that expands memory, stores a revert string pertaining to a disallowed memory write,
-    /// and sets the return range to the revert string's location in memory.
+    /// Helper that expands memory, stores a revert string pertaining to a disallowed memory write
+    /// by the current opcode, and sets the return range to the revert string's location in memory.
     pub fn disallowed_mem_write(
         dest_offset: u64,
         size: u64,
         interpreter: &mut Interpreter,
         ranges: &[Range<u64>],
     ) {
+        let op = interpreter.contract.bytecode.bytecode()[interpreter.program_counter()];
         let revert_string: Bytes = format!(
-            "Memory write at offset 0x{:02X} of size 0x{:02X} not allowed. Safe range: {}",
+            "Memory write at offset 0x{:02X} of size 0x{:02X} not allowed by {}. Safe range: {}",
             dest_offset,
             size,
+            opcode::OPCODE_JUMPMAP[op as usize].unwrap_or("UNKNOWN"),
             ranges.iter().map(|r| format!("(0x{:02X}, 0x{:02X}]", r.start, r.end)).join(" ∪ ")
         )
         .encode()
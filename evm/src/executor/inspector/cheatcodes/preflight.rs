@@ -0,0 +1,122 @@
+use super::Cheatcodes;
+use crate::{abi::HEVMCalls, error::SolError, executor::backend::DatabaseExt};
+use bytes::Bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, H256, U256},
+};
+use revm::EVMData;
+use std::fmt;
+use tracing::trace;
+
+/// The revert reason of `assertOnChain*` cheatcodes outside of `preflight()`
+const NOT_IN_PREFLIGHT: &str =
+    "`assertOnChain*` can only be used in the `preflight()` function of a script";
+
+/// A precondition of an `assertOnChain*` cheatcode that doesn't hold
+///
+/// Unlike other assertions these don't revert, so all preconditions of a script's `preflight()`
+/// are checked before `forge script` aborts the broadcast.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnChainAssertionFailure {
+    /// The reason passed to the cheatcode
+    pub reason: String,
+    /// What was found on chain instead
+    pub found: String,
+}
+
+impl fmt::Display for OnChainAssertionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.reason, self.found)
+    }
+}
+
+/// Returns the failure if `actual` doesn't equal `expected`
+fn assert_eq<T: PartialEq + fmt::Debug>(
+    actual: T,
+    expected: T,
+    reason: &str,
+) -> Option<OnChainAssertionFailure> {
+    (actual != expected).then(|| OnChainAssertionFailure {
+        reason: reason.to_string(),
+        found: format!("expected {expected:?}, found {actual:?}"),
+    })
+}
+
+fn assert_code<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    account: Address,
+    reason: &str,
+) -> Result<Option<OnChainAssertionFailure>, Bytes> {
+    let (account_info, _) =
+        data.journaled_state.load_code(account, data.db).map_err(|err| err.encode_string())?;
+    let has_code =
+        account_info.info.code.as_ref().map(|code| !code.is_empty()).unwrap_or_default();
+    Ok((!has_code).then(|| OnChainAssertionFailure {
+        reason: reason.to_string(),
+        found: format!("{account:?} has no code"),
+    }))
+}
+
+fn assert_storage<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    account: Address,
+    slot: [u8; 32],
+    expected: [u8; 32],
+    reason: &str,
+) -> Result<Option<OnChainAssertionFailure>, Bytes> {
+    data.journaled_state.load_account(account, data.db).map_err(|err| err.encode_string())?;
+    let (value, _) = data
+        .journaled_state
+        .sload(account, U256::from(slot), data.db)
+        .map_err(|err| err.encode_string())?;
+    let mut actual = [0u8; 32];
+    value.to_big_endian(&mut actual);
+    Ok(assert_eq(H256(actual), H256(expected), reason))
+}
+
+pub fn apply<DB: DatabaseExt>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    let is_onchain_assertion = matches!(
+        call,
+        HEVMCalls::AssertOnChain(_) |
+            HEVMCalls::AssertOnChainEq0(_) |
+            HEVMCalls::AssertOnChainEq1(_) |
+            HEVMCalls::AssertOnChainEq2(_) |
+            HEVMCalls::AssertOnChainCode(_) |
+            HEVMCalls::AssertOnChainStorage(_)
+    );
+    if is_onchain_assertion && !state.preflight {
+        return Some(Err(NOT_IN_PREFLIGHT.to_string().encode().into()))
+    }
+
+    let failure = match call {
+        HEVMCalls::AssertOnChain(inner) => (!inner.0).then(|| OnChainAssertionFailure {
+            reason: inner.1.clone(),
+            found: "condition is false".to_string(),
+        }),
+        HEVMCalls::AssertOnChainEq0(inner) => assert_eq(inner.0, inner.1, &inner.2),
+        HEVMCalls::AssertOnChainEq1(inner) => assert_eq(inner.0, inner.1, &inner.2),
+        HEVMCalls::AssertOnChainEq2(inner) => assert_eq(H256(inner.0), H256(inner.1), &inner.2),
+        HEVMCalls::AssertOnChainCode(inner) => match assert_code(data, inner.0, &inner.1) {
+            Ok(failure) => failure,
+            Err(err) => return Some(Err(err)),
+        },
+        HEVMCalls::AssertOnChainStorage(inner) => {
+            match assert_storage(data, inner.0, inner.1, inner.2, &inner.3) {
+                Ok(failure) => failure,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        _ => return None,
+    };
+
+    if let Some(failure) = failure {
+        trace!(%failure, "on chain assertion failed");
+        state.onchain_assertion_failures.push(failure);
+    }
+    Some(Ok(Bytes::new()))
+}
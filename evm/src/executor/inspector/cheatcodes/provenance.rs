@@ -0,0 +1,68 @@
+use crate::executor::CHEATCODE_ADDRESS;
+use bytes::Bytes;
+use ethers::types::{Address, H256};
+use revm::{opcode, Database, EVMData, Interpreter};
+
+/// The instruction that called the cheatcode address
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheatcodeCallSite {
+    /// The account whose code made the call
+    pub address: Address,
+    /// The program counter of the call instruction
+    pub pc: usize,
+}
+
+impl CheatcodeCallSite {
+    /// Returns the call site if the current opcode is a call to the cheatcode address
+    pub fn capture(interpreter: &Interpreter) -> Option<Self> {
+        is_cheatcode_call(interpreter).then(|| Self {
+            address: interpreter.contract().address,
+            pc: interpreter.program_counter(),
+        })
+    }
+}
+
+/// A failed cheatcode and the code that called it, which can be resolved to a source location
+/// with the source map of the calling contract
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CheatcodeFailure {
+    /// The runtime code of the calling account, empty if the cheatcode was called by a
+    /// constructor
+    pub code: Bytes,
+    /// The program counter of the call instruction in `code`
+    pub pc: usize,
+    /// The revert data of the failure
+    pub retdata: Bytes,
+}
+
+impl CheatcodeFailure {
+    /// Records the failure with the current code of the account of the call site
+    pub fn new<DB: Database>(
+        data: &mut EVMData<'_, DB>,
+        call_site: &CheatcodeCallSite,
+        retdata: Bytes,
+    ) -> Self {
+        let code = match data.journaled_state.load_code(call_site.address, data.db) {
+            Ok((account, _)) => account
+                .info
+                .code
+                .as_ref()
+                .map(|code| Bytes::copy_from_slice(&code.bytes()[..code.len()]))
+                .unwrap_or_default(),
+            Err(_) => Bytes::new(),
+        };
+        Self { code, pc: call_site.pc, retdata }
+    }
+}
+
+/// Returns true if the current opcode is a call to the cheatcode address
+pub fn is_cheatcode_call(interpreter: &Interpreter) -> bool {
+    let op = interpreter.contract.bytecode.bytecode()[interpreter.program_counter()];
+    if !matches!(op, opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL) {
+        return false
+    }
+    match interpreter.stack().peek(1) {
+        Ok(target) => Address::from(H256::from_uint(&target)) == CHEATCODE_ADDRESS,
+        Err(_) => false,
+    }
+}
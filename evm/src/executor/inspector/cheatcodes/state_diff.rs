@@ -0,0 +1,513 @@
+use crate::executor::{backend::DatabaseExt, inspector::utils::get_create_address};
+use bytes::Bytes;
+use ethers::{
+    abi::{self, Token},
+    types::{Address, H256, U256},
+};
+use revm::{
+    opcode, AccountInfo, CallInputs, CallScheme, CreateInputs, Database, EVMData, Interpreter,
+};
+use std::collections::HashMap;
+
+/// The kind of an [AccountAccess], the `AccountAccessKind` enum of `Cheats.sol`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountAccessKind {
+    Call,
+    DelegateCall,
+    CallCode,
+    StaticCall,
+    Create,
+    SelfDestruct,
+    /// The execution of a call frame resumed after a nested call returned
+    Resume,
+    Balance,
+    Extcodesize,
+    Extcodehash,
+    Extcodecopy,
+}
+
+impl From<CallScheme> for AccountAccessKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => AccountAccessKind::Call,
+            CallScheme::CallCode => AccountAccessKind::CallCode,
+            CallScheme::DelegateCall => AccountAccessKind::DelegateCall,
+            CallScheme::StaticCall => AccountAccessKind::StaticCall,
+        }
+    }
+}
+
+/// A read or write of a storage slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageAccess {
+    /// The account whose storage was accessed
+    pub account: Address,
+    pub slot: U256,
+    pub is_write: bool,
+    /// The value before the access, `None` while the slot was not loaded yet
+    pub previous_value: Option<U256>,
+    /// The value after the access
+    pub new_value: U256,
+    /// Whether the access was reverted
+    pub reverted: bool,
+}
+
+impl StorageAccess {
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![
+            Token::Address(self.account),
+            word(self.slot),
+            Token::Bool(self.is_write),
+            word(self.previous_value.unwrap_or_default()),
+            word(self.new_value),
+            Token::Bool(self.reverted),
+        ])
+    }
+}
+
+/// An access of an account, either by entering a call frame in its context or by querying it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountAccess {
+    pub kind: AccountAccessKind,
+    /// The accessed account
+    pub account: Address,
+    /// The account that accessed it
+    pub accessor: Address,
+    /// Whether the account existed before the access
+    pub initialized: bool,
+    pub old_balance: U256,
+    pub new_balance: U256,
+    /// The transferred value
+    pub value: U256,
+    /// The calldata or the init code
+    pub data: Bytes,
+    /// Whether the access was reverted
+    pub reverted: bool,
+    /// All storage accesses made while the access was the innermost one
+    pub storage_accesses: Vec<StorageAccess>,
+    /// The call depth of the access
+    pub depth: u64,
+}
+
+impl AccountAccess {
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![
+            Token::Uint((self.kind as u8).into()),
+            Token::Address(self.account),
+            Token::Address(self.accessor),
+            Token::Bool(self.initialized),
+            Token::Uint(self.old_balance),
+            Token::Uint(self.new_balance),
+            Token::Uint(self.value),
+            Token::Bytes(self.data.to_vec()),
+            Token::Bool(self.reverted),
+            Token::Array(
+                self.storage_accesses.into_iter().map(StorageAccess::into_token).collect(),
+            ),
+            Token::Uint(self.depth.into()),
+        ])
+    }
+}
+
+/// The net change of a storage slot since recording started
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageDiff {
+    pub slot: U256,
+    pub previous_value: U256,
+    pub new_value: U256,
+}
+
+impl StorageDiff {
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![word(self.slot), word(self.previous_value), word(self.new_value)])
+    }
+}
+
+/// The net change of an account since recording started, see `getStateDiff`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountStateDiff {
+    pub account: Address,
+    /// Whether the account was created since recording started
+    pub created: bool,
+    pub previous_balance: U256,
+    pub new_balance: U256,
+    pub previous_nonce: u64,
+    pub new_nonce: u64,
+    /// All slots whose value differs from the value before recording started
+    pub storage_diffs: Vec<StorageDiff>,
+}
+
+impl AccountStateDiff {
+    /// Returns true if nothing changed
+    pub fn is_empty(&self) -> bool {
+        !self.created &&
+            self.previous_balance == self.new_balance &&
+            self.previous_nonce == self.new_nonce &&
+            self.storage_diffs.is_empty()
+    }
+
+    fn into_token(self) -> Token {
+        Token::Tuple(vec![
+            Token::Address(self.account),
+            Token::Bool(self.created),
+            Token::Uint(self.previous_balance),
+            Token::Uint(self.new_balance),
+            Token::Uint(self.previous_nonce.into()),
+            Token::Uint(self.new_nonce.into()),
+            Token::Array(self.storage_diffs.into_iter().map(StorageDiff::into_token).collect()),
+        ])
+    }
+}
+
+/// ABI encodes the diffs as `AccountStateDiff[]`
+pub fn encode_state_diff(diffs: Vec<AccountStateDiff>) -> Bytes {
+    let diffs = diffs.into_iter().map(AccountStateDiff::into_token).collect();
+    abi::encode(&[Token::Array(diffs)]).into()
+}
+
+/// Records all account and storage accesses, see `startStateDiffRecording`
+#[derive(Clone, Debug, Default)]
+pub struct StateDiffRecorder {
+    accesses: Vec<AccountAccess>,
+    /// The depth and the index of the access of every entered call frame, innermost last
+    frames: Vec<(u64, usize)>,
+    /// The index of the access new storage accesses are added to, if it is still the innermost
+    current: Option<usize>,
+    /// The storage access of the currently executed `SLOAD` or `SSTORE`
+    pending: Option<StorageAccess>,
+    /// The balance and nonce of every touched account when it was first touched, in order
+    touched: Vec<(Address, U256, u64)>,
+    /// The index of every touched account in `touched`
+    touched_indices: HashMap<Address, usize>,
+}
+
+impl StateDiffRecorder {
+    /// Starts recording, with the given accounts touched, e.g. the sender of the transaction and
+    /// the test contract whose balances can change without them being accessed
+    pub fn new<DB: DatabaseExt>(data: &mut EVMData<'_, DB>, accounts: &[Address]) -> Self {
+        let mut recorder = Self::default();
+        for account in accounts {
+            recorder.touch(data, *account);
+        }
+        recorder
+    }
+
+    /// Records the balance and nonce of the account, if it was not touched before
+    fn touch<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>, address: Address) {
+        if self.touched_indices.contains_key(&address) {
+            return
+        }
+        let info = account_info(data, address).unwrap_or_default();
+        self.touched_indices.insert(address, self.touched.len());
+        self.touched.push((address, info.balance, info.nonce));
+    }
+
+    /// ABI encodes the recorded accesses as `AccountAccess[]`
+    pub fn encode(self) -> Bytes {
+        let accesses = self.accesses.into_iter().map(AccountAccess::into_token).collect();
+        abi::encode(&[Token::Array(accesses)]).into()
+    }
+
+    /// Returns the net changes of all accounts touched since recording started, by comparing the
+    /// values recorded when they were first touched with the current state. Unchanged accounts
+    /// are omitted.
+    pub fn state_diff<DB: DatabaseExt>(&self, data: &mut EVMData<'_, DB>) -> Vec<AccountStateDiff> {
+        let mut diffs: Vec<AccountStateDiff> = self
+            .touched
+            .iter()
+            .map(|(account, balance, nonce)| AccountStateDiff {
+                account: *account,
+                created: false,
+                previous_balance: *balance,
+                new_balance: U256::zero(),
+                previous_nonce: *nonce,
+                new_nonce: 0,
+                storage_diffs: vec![],
+            })
+            .collect();
+
+        for access in &self.accesses {
+            let idx = match self.touched_indices.get(&access.account) {
+                Some(idx) => *idx,
+                None => continue,
+            };
+            if access.kind == AccountAccessKind::Create && !access.reverted {
+                diffs[idx].created = true;
+            }
+
+            for storage in &access.storage_accesses {
+                let idx = match self.touched_indices.get(&storage.account) {
+                    Some(idx) => *idx,
+                    None => continue,
+                };
+                let storage_diffs = &mut diffs[idx].storage_diffs;
+                if storage_diffs.iter().all(|diff| diff.slot != storage.slot) {
+                    storage_diffs.push(StorageDiff {
+                        slot: storage.slot,
+                        previous_value: storage.previous_value.unwrap_or_default(),
+                        new_value: U256::zero(),
+                    });
+                }
+            }
+        }
+
+        for diff in &mut diffs {
+            let info = account_info(data, diff.account).unwrap_or_default();
+            diff.new_balance = info.balance;
+            diff.new_nonce = info.nonce;
+            let account = diff.account;
+            for storage in &mut diff.storage_diffs {
+                storage.new_value =
+                    storage_value(data, account, storage.slot).unwrap_or(storage.previous_value);
+            }
+            diff.storage_diffs.retain(|storage| storage.previous_value != storage.new_value);
+        }
+        diffs.retain(|diff| !diff.is_empty());
+        diffs
+    }
+
+    /// Records the call frame that is about to be entered
+    pub fn on_call<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>, call: &CallInputs) {
+        self.touch(data, call.context.caller);
+        self.touch(data, call.context.address);
+        self.touch(data, call.contract);
+        let (initialized, balance) = account_balance(data, call.contract);
+        self.enter(
+            data.journaled_state.depth(),
+            AccountAccess {
+                kind: call.context.scheme.into(),
+                account: call.contract,
+                accessor: call.context.caller,
+                initialized,
+                old_balance: balance,
+                new_balance: balance,
+                value: call.transfer.value,
+                data: call.input.clone(),
+                reverted: false,
+                storage_accesses: vec![],
+                depth: data.journaled_state.depth(),
+            },
+        );
+    }
+
+    /// Records the create frame that is about to be entered
+    pub fn on_create<DB: DatabaseExt>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CreateInputs,
+    ) {
+        self.touch(data, call.caller);
+        let nonce = data
+            .journaled_state
+            .state
+            .get(&call.caller)
+            .map(|acc| acc.info.nonce)
+            .unwrap_or_default();
+        let address = get_create_address(call, nonce);
+        self.touch(data, address);
+        let (_, balance) = account_balance(data, address);
+        self.enter(
+            data.journaled_state.depth(),
+            AccountAccess {
+                kind: AccountAccessKind::Create,
+                account: address,
+                accessor: call.caller,
+                initialized: false,
+                old_balance: balance,
+                new_balance: balance,
+                value: call.value,
+                data: call.init_code.clone(),
+                reverted: false,
+                storage_accesses: vec![],
+                depth: data.journaled_state.depth(),
+            },
+        );
+    }
+
+    fn enter(&mut self, depth: u64, access: AccountAccess) {
+        self.current = Some(self.accesses.len());
+        self.frames.push((depth, self.accesses.len()));
+        self.accesses.push(access);
+    }
+
+    /// Called when the call frame at the current depth ended, marks all accesses made in the
+    /// frame as reverted if it was not successful
+    pub fn on_frame_end<DB: DatabaseExt>(&mut self, data: &mut EVMData<'_, DB>, success: bool) {
+        if !matches!(self.frames.last(), Some((depth, _)) if *depth == data.journaled_state.depth())
+        {
+            return
+        }
+        let (_, idx) = self.frames.pop().expect("frame exists; qed");
+        self.current = None;
+
+        let account = self.accesses[idx].account;
+        self.accesses[idx].new_balance = account_balance(data, account).1;
+        if !success {
+            for access in &mut self.accesses[idx..] {
+                access.reverted = true;
+                access.storage_accesses.iter_mut().for_each(|access| access.reverted = true);
+            }
+        }
+    }
+
+    /// Records account queries and storage accesses of the opcode at the current program counter
+    pub fn step<DB: DatabaseExt>(
+        &mut self,
+        interpreter: &Interpreter,
+        data: &mut EVMData<'_, DB>,
+    ) {
+        let address = interpreter.contract().address;
+        let kind = match interpreter.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                let slot = match interpreter.stack().peek(0) {
+                    Ok(slot) => slot,
+                    Err(_) => return,
+                };
+                let previous_value = data
+                    .journaled_state
+                    .state
+                    .get(&address)
+                    .and_then(|acc| acc.storage.get(&slot))
+                    .map(|slot| slot.present_value());
+                self.pending = Some(StorageAccess {
+                    account: address,
+                    slot,
+                    is_write: interpreter.current_opcode() == opcode::SSTORE,
+                    previous_value,
+                    new_value: U256::zero(),
+                    reverted: false,
+                });
+                return
+            }
+            opcode::BALANCE => AccountAccessKind::Balance,
+            opcode::EXTCODESIZE => AccountAccessKind::Extcodesize,
+            opcode::EXTCODEHASH => AccountAccessKind::Extcodehash,
+            opcode::EXTCODECOPY => AccountAccessKind::Extcodecopy,
+            opcode::SELFDESTRUCT => AccountAccessKind::SelfDestruct,
+            _ => return,
+        };
+
+        let is_selfdestruct = kind == AccountAccessKind::SelfDestruct;
+        let account = if is_selfdestruct {
+            address
+        } else {
+            match interpreter.stack().peek(0) {
+                Ok(word) => Address::from(H256::from_uint(&word)),
+                Err(_) => return,
+            }
+        };
+        self.touch(data, account);
+        if is_selfdestruct {
+            // the beneficiary receives the balance
+            if let Ok(word) = interpreter.stack().peek(0) {
+                self.touch(data, Address::from(H256::from_uint(&word)));
+            }
+        }
+        let (initialized, balance) = account_balance(data, account);
+        self.accesses.push(AccountAccess {
+            kind,
+            account,
+            accessor: address,
+            initialized,
+            old_balance: balance,
+            new_balance: if is_selfdestruct { U256::zero() } else { balance },
+            value: if is_selfdestruct { balance } else { U256::zero() },
+            data: Bytes::new(),
+            reverted: false,
+            storage_accesses: vec![],
+            depth: data.journaled_state.depth(),
+        });
+    }
+
+    /// Completes the storage access of the executed `SLOAD` or `SSTORE`, if any
+    pub fn step_end<DB: DatabaseExt>(
+        &mut self,
+        interpreter: &Interpreter,
+        data: &mut EVMData<'_, DB>,
+    ) {
+        let mut access = match self.pending.take() {
+            Some(access) => access,
+            None => return,
+        };
+        self.touch(data, access.account);
+        // the slot is not loaded if the opcode failed
+        let slot = match data
+            .journaled_state
+            .state
+            .get(&access.account)
+            .and_then(|acc| acc.storage.get(&access.slot))
+        {
+            Some(slot) => slot,
+            None => return,
+        };
+        access.new_value = slot.present_value();
+        if access.previous_value.is_none() {
+            // loaded by this access, so the slot was not modified before
+            access.previous_value = Some(slot.original_value());
+        }
+        if !access.is_write {
+            access.previous_value = Some(access.new_value);
+        }
+
+        // accesses after a nested call returned are attributed to a new access of the account
+        let idx = match self.current {
+            Some(idx) => idx,
+            None => {
+                let (initialized, balance) = account_balance(data, access.account);
+                self.current = Some(self.accesses.len());
+                self.accesses.push(AccountAccess {
+                    kind: AccountAccessKind::Resume,
+                    account: access.account,
+                    accessor: interpreter.contract().caller,
+                    initialized,
+                    old_balance: balance,
+                    new_balance: balance,
+                    value: U256::zero(),
+                    data: Bytes::new(),
+                    reverted: false,
+                    storage_accesses: vec![],
+                    depth: data.journaled_state.depth(),
+                });
+                self.accesses.len() - 1
+            }
+        };
+        self.accesses[idx].storage_accesses.push(access);
+    }
+}
+
+/// Encodes the value as `bytes32`
+fn word(value: U256) -> Token {
+    Token::FixedBytes(H256::from_uint(&value).0.to_vec())
+}
+
+/// Returns the current value of the slot, without loading it into the journaled state
+fn storage_value<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    address: Address,
+    slot: U256,
+) -> Option<U256> {
+    match data.journaled_state.state.get(&address).and_then(|acc| acc.storage.get(&slot)) {
+        Some(value) => Some(value.present_value()),
+        None => data.db.storage(address, slot).ok(),
+    }
+}
+
+/// Returns the current info of the account, without loading it into the journaled state, since
+/// that would make it warm
+fn account_info<DB: DatabaseExt>(
+    data: &mut EVMData<'_, DB>,
+    address: Address,
+) -> Option<AccountInfo> {
+    match data.journaled_state.state.get(&address) {
+        Some(acc) => Some(acc.info.clone()),
+        None => data.db.basic(address).ok().flatten(),
+    }
+}
+
+/// Returns whether the account exists and its balance
+fn account_balance<DB: DatabaseExt>(data: &mut EVMData<'_, DB>, address: Address) -> (bool, U256) {
+    match account_info(data, address) {
+        Some(info) => (!info.is_empty(), info.balance),
+        None => (false, U256::zero()),
+    }
+}
@@ -0,0 +1,134 @@
+use ethers::types::{Address, U256};
+use revm::{Interpreter, Return};
+use std::collections::BTreeMap;
+
+/// The `TLOAD` opcode of [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153)
+pub const TLOAD: u8 = 0x5c;
+
+/// The `TSTORE` opcode of [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153)
+pub const TSTORE: u8 = 0x5d;
+
+/// Gas cost of `TLOAD` and `TSTORE`, the same as a warm storage read
+const TRANSIENT_STORAGE_GAS: u64 = 100;
+
+/// Transient storage of all accounts, see [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153)
+///
+/// The EVM does not know the `TLOAD` and `TSTORE` opcodes, so they are executed by the
+/// [InspectorStack](crate::executor::inspector::InspectorStack) instead, see
+/// [TransientStorage::execute]. Changes are rolled back if the call frame that made them reverts
+/// and the storage is discarded at the end of the transaction.
+#[derive(Clone, Debug, Default)]
+pub struct TransientStorage {
+    storage: BTreeMap<Address, BTreeMap<U256, U256>>,
+    /// The previous values of all changed slots, in the order of the changes
+    journal: Vec<(Address, U256, U256)>,
+    /// The length of the journal at the start of every entered call frame, with the depth of the
+    /// frame
+    checkpoints: Vec<(u64, usize)>,
+}
+
+impl TransientStorage {
+    /// Returns the value of the transient storage slot of the account
+    pub fn load(&self, address: Address, slot: U256) -> U256 {
+        self.storage.get(&address).and_then(|slots| slots.get(&slot)).copied().unwrap_or_default()
+    }
+
+    /// Sets the value of the transient storage slot of the account
+    pub fn store(&mut self, address: Address, slot: U256, value: U256) {
+        let previous = self.set(address, slot, value);
+        // changes outside of a call frame can't be rolled back
+        if !self.checkpoints.is_empty() && previous != value {
+            self.journal.push((address, slot, previous));
+        }
+    }
+
+    /// Clears the storage, like at the end of a transaction
+    pub fn clear(&mut self) {
+        self.storage.clear();
+        self.journal.clear();
+        self.checkpoints.clear();
+    }
+
+    /// Sets the value of the slot and returns the previous value
+    fn set(&mut self, address: Address, slot: U256, value: U256) -> U256 {
+        if value.is_zero() {
+            let slots = match self.storage.get_mut(&address) {
+                Some(slots) => slots,
+                None => return U256::zero(),
+            };
+            let previous = slots.remove(&slot).unwrap_or_default();
+            if slots.is_empty() {
+                self.storage.remove(&address);
+            }
+            previous
+        } else {
+            self.storage.entry(address).or_default().insert(slot, value).unwrap_or_default()
+        }
+    }
+
+    /// Records the current storage before a call frame at the given depth is entered
+    pub fn checkpoint(&mut self, depth: u64) {
+        self.checkpoints.push((depth, self.journal.len()));
+    }
+
+    /// Called when the call frame at the given depth ended, rolls back all changes made in the
+    /// frame if it was not successful
+    pub fn on_frame_end(&mut self, depth: u64, success: bool) {
+        if !matches!(self.checkpoints.last(), Some((d, _)) if *d == depth) {
+            return
+        }
+        let (_, len) = self.checkpoints.pop().expect("checkpoint exists; qed");
+        if !success {
+            while self.journal.len() > len {
+                let (address, slot, previous) = self.journal.pop().expect("journal entry; qed");
+                self.set(address, slot, previous);
+            }
+        }
+
+        // the transaction ended
+        if depth == 0 {
+            self.clear();
+        }
+    }
+
+    /// Returns true if the opcode at the current program counter is `TLOAD` or `TSTORE`
+    pub fn is_transient_opcode(interpreter: &Interpreter) -> bool {
+        let op = interpreter.contract.bytecode.bytecode()[interpreter.program_counter()];
+        op == TLOAD || op == TSTORE
+    }
+
+    /// Executes the `TLOAD` or `TSTORE` opcode at the current program counter
+    ///
+    /// The executed opcode is skipped by advancing the instruction pointer, so the interpreter
+    /// continues with the next opcode.
+    pub fn execute(&mut self, interpreter: &mut Interpreter, is_static: bool) -> Return {
+        let op = interpreter.contract.bytecode.bytecode()[interpreter.program_counter()];
+        if op == TSTORE && is_static {
+            return Return::StateChangeDuringStaticCall
+        }
+        if !interpreter.gas.record_cost(TRANSIENT_STORAGE_GAS) {
+            return Return::OutOfGas
+        }
+
+        let address = interpreter.contract().address;
+        let slot = match interpreter.stack.pop() {
+            Ok(slot) => slot,
+            Err(err) => return err,
+        };
+        if op == TLOAD {
+            if let Err(err) = interpreter.stack.push(self.load(address, slot)) {
+                return err
+            }
+        } else {
+            let value = match interpreter.stack.pop() {
+                Ok(value) => value,
+                Err(err) => return err,
+            };
+            self.store(address, slot, value);
+        }
+
+        // SAFETY: the bytecode is padded with `STOP`s, so there always is a next opcode
+        interpreter.instruction_pointer = unsafe { interpreter.instruction_pointer.add(1) };
+        Return::Continue
+    }
+}
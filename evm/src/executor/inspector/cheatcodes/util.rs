@@ -20,6 +20,7 @@ use ethers::{
     utils,
 };
 use foundry_common::{fmt::*, RpcUrl};
+use foundry_config::Config;
 use hex::FromHex;
 use revm::{Account, CreateInputs, Database, EVMData, JournaledState, TransactTo};
 use std::{collections::VecDeque, str::FromStr};
@@ -28,9 +29,7 @@ use tracing::trace;
 const DEFAULT_DERIVATION_PATH_PREFIX: &str = "m/44'/60'/0'/0/";
 
 /// Address of the default CREATE2 deployer 0x4e59b44847b379578588920ca78fbf26c0b4956c
-pub const DEFAULT_CREATE2_DEPLOYER: H160 = H160([
-    78, 89, 180, 72, 71, 179, 121, 87, 133, 136, 146, 12, 167, 143, 191, 38, 192, 180, 149, 108,
-]);
+pub const DEFAULT_CREATE2_DEPLOYER: H160 = Config::DEFAULT_CREATE2_DEPLOYER;
 
 /// Helps collecting transactions from different forks.
 #[derive(Debug, Clone, Default)]
@@ -184,6 +183,7 @@ pub fn apply<DB: Database>(
 
 pub fn process_create<DB>(
     broadcast_sender: Address,
+    create2_deployer: Address,
     bytecode: Bytes,
     data: &mut EVMData<'_, DB>,
     call: &mut CreateInputs,
@@ -199,26 +199,26 @@ where
         }
         revm::CreateScheme::Create2 { salt } => {
             // Sanity checks for our CREATE2 deployer
-            data.journaled_state.load_account(DEFAULT_CREATE2_DEPLOYER, data.db)?;
+            data.journaled_state.load_account(create2_deployer, data.db)?;
 
-            let info = &data.journaled_state.account(DEFAULT_CREATE2_DEPLOYER).info;
+            let info = &data.journaled_state.account(create2_deployer).info;
             match &info.code {
                 Some(code) => {
                     if code.is_empty() {
-                        trace!(create2=?DEFAULT_CREATE2_DEPLOYER, "Empty Create 2 deployer code");
-                        return Err(DatabaseError::MissingCreate2Deployer)
+                        trace!(create2=?create2_deployer, "Empty Create 2 deployer code");
+                        return Err(DatabaseError::MissingCreate2Deployer(create2_deployer))
                     }
                 }
                 None => {
                     // forked db
-                    trace!(create2=?DEFAULT_CREATE2_DEPLOYER, "Missing Create 2 deployer code");
+                    trace!(create2=?create2_deployer, "Missing Create 2 deployer code");
                     if data.db.code_by_hash(info.code_hash)?.is_empty() {
-                        return Err(DatabaseError::MissingCreate2Deployer)
+                        return Err(DatabaseError::MissingCreate2Deployer(create2_deployer))
                     }
                 }
             }
 
-            call.caller = DEFAULT_CREATE2_DEPLOYER;
+            call.caller = create2_deployer;
 
             // We have to increment the nonce of the user address, since this create2 will be done
             // by the create2_deployer
@@ -233,7 +233,7 @@ where
             calldata.put_slice(&salt_bytes);
             calldata.put(bytecode);
 
-            Ok((calldata.freeze(), Some(NameOrAddress::Address(DEFAULT_CREATE2_DEPLOYER)), nonce))
+            Ok((calldata.freeze(), Some(NameOrAddress::Address(create2_deployer)), nonce))
         }
     }
 }
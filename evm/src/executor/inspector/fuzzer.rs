@@ -1,5 +1,8 @@
 use crate::{
-    fuzz::{invariant::RandomCallGenerator, strategies::EvmFuzzState},
+    fuzz::{
+        invariant::RandomCallGenerator,
+        strategies::{DictionarySource, EvmFuzzState},
+    },
     utils,
 };
 use bytes::Bytes;
@@ -79,7 +82,7 @@ impl Fuzzer {
         let mut state = self.fuzz_state.write();
 
         for slot in interpreter.stack().data() {
-            state.insert(utils::u256_to_h256_be(*slot).into());
+            state.insert_from(DictionarySource::Stack, utils::u256_to_h256_be(*slot).into());
         }
 
         // TODO: disabled for now since it's flooding the dictionary
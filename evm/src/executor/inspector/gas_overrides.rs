@@ -0,0 +1,326 @@
+use bytes::Bytes;
+use ethers::types::Address;
+use eyre::bail;
+use foundry_config::GasOverride;
+use revm::{
+    opcode, CallInputs, CreateInputs, Database, EVMData, Env, Gas, GasInspector, Inspector,
+    Interpreter, Return, SpecId, TransactTo, TxEnv,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// The intrinsic costs of a transaction that can be overridden
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntrinsicGas {
+    /// `TX_BASE`, the base cost of every transaction
+    pub base: Option<u64>,
+    /// `TX_CREATE`, the additional cost of a contract creation
+    pub create: Option<u64>,
+    /// `TX_DATA_ZERO`, the cost of a zero byte of calldata
+    pub data_zero: Option<u64>,
+    /// `TX_DATA_NON_ZERO`, the cost of a non-zero byte of calldata
+    pub data_non_zero: Option<u64>,
+}
+
+impl IntrinsicGas {
+    /// Returns true if no intrinsic cost is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the base cost and the cost of the calldata, using the overridden costs where set
+    pub fn stipend(&self, calldata: &[u8], spec: SpecId) -> u64 {
+        let zero_cost = self.data_zero.unwrap_or(4);
+        let non_zero_cost = self
+            .data_non_zero
+            .unwrap_or(if SpecId::enabled(spec, SpecId::ISTANBUL) { 16 } else { 68 });
+        calldata.iter().fold(self.base.unwrap_or(21000), |sum, byte| {
+            sum + if *byte == 0 { zero_cost } else { non_zero_cost }
+        })
+    }
+
+    /// Returns the intrinsic gas of the transaction, using the overridden costs where set
+    pub fn cost(&self, tx: &TxEnv, spec: SpecId) -> u64 {
+        let create_cost = match tx.transact_to {
+            TransactTo::Create(_) if SpecId::enabled(spec, SpecId::HOMESTEAD) => {
+                self.create.unwrap_or(32000)
+            }
+            _ => 0,
+        };
+        self.stipend(&tx.data, spec) + create_cost
+    }
+
+    /// Returns the difference between the overridden and the default intrinsic gas of the
+    /// transaction
+    pub fn cost_delta(&self, tx: &TxEnv, spec: SpecId) -> i64 {
+        self.cost(tx, spec) as i64 - Self::default().cost(tx, spec) as i64
+    }
+}
+
+/// An inspector that applies a custom gas schedule to opcodes and the intrinsic gas of
+/// transactions.
+///
+/// revm charges the default intrinsic gas before any inspector is invoked, so the difference to
+/// the custom intrinsic gas is taken from, or added to, the gas limit of the top level call. The
+/// gas used by the transaction, and with it its refund and fee, then include the custom intrinsic
+/// gas.
+///
+/// The gas an opcode charged is measured around its execution and then corrected to the
+/// configured cost. For opcodes that enter a new call frame, the gas forwarded to that frame is
+/// part of the measured cost, so overrides are best suited for opcodes that don't.
+///
+/// Executing a disabled opcode halts the current call frame like an invalid opcode.
+#[derive(Debug, Clone, Default)]
+pub struct GasOverrides {
+    /// Custom costs by opcode
+    opcodes: HashMap<u8, GasOverride>,
+    /// Custom intrinsic costs of transactions
+    pub intrinsic: IntrinsicGas,
+    gas_inspector: GasInspector,
+    /// The opcode and the gas remaining before each ongoing step, innermost last
+    step_stack: Vec<(u8, u64)>,
+    /// The correction applied at the end of the last step, which the gas inspector only picks up
+    /// at the end of the next one
+    last_correction: i64,
+}
+
+impl GasOverrides {
+    /// Creates the inspector from the `gas_overrides` of the config
+    ///
+    /// Returns an error if a key is neither an opcode nor an intrinsic cost
+    pub fn new(overrides: &BTreeMap<String, GasOverride>) -> eyre::Result<Self> {
+        let mut gas_overrides = Self::default();
+        for (name, gas_override) in overrides {
+            let name = name.to_uppercase();
+            let intrinsic = match name.as_str() {
+                "TX_BASE" => Some(&mut gas_overrides.intrinsic.base),
+                "TX_CREATE" => Some(&mut gas_overrides.intrinsic.create),
+                "TX_DATA_ZERO" => Some(&mut gas_overrides.intrinsic.data_zero),
+                "TX_DATA_NON_ZERO" => Some(&mut gas_overrides.intrinsic.data_non_zero),
+                _ => None,
+            };
+            if let Some(intrinsic) = intrinsic {
+                match gas_override {
+                    GasOverride::Gas(gas) => *intrinsic = Some(*gas),
+                    GasOverride::Disabled => {
+                        bail!("the intrinsic cost `{name}` can't be disabled")
+                    }
+                }
+                continue
+            }
+
+            match opcode::OPCODE_JUMPMAP.iter().position(|op| *op == Some(name.as_str())) {
+                Some(op) => {
+                    gas_overrides.opcodes.insert(op as u8, *gas_override);
+                }
+                None => bail!("unknown opcode `{name}` in gas overrides"),
+            }
+        }
+        Ok(gas_overrides)
+    }
+
+    /// Returns true if neither opcodes nor intrinsic costs are overridden
+    pub fn is_empty(&self) -> bool {
+        self.opcodes.is_empty() && self.intrinsic.is_empty()
+    }
+
+    /// Applies the custom intrinsic costs to the gas limit of the top level call of a transaction
+    /// executed in `env`
+    ///
+    /// If the gas limit is lower than the additional intrinsic gas, the call runs out of gas.
+    fn adjust_gas_limit(&self, env: &Env, gas_limit: &mut u64) {
+        let delta = self.intrinsic.cost_delta(&env.tx, env.cfg.spec_id);
+        if delta >= 0 {
+            *gas_limit = gas_limit.saturating_sub(delta as u64);
+        } else {
+            *gas_limit += delta.unsigned_abs();
+        }
+    }
+
+    /// Returns the configured cost of the given opcode, if any
+    pub fn opcode(&self, op: u8) -> Option<GasOverride> {
+        self.opcodes.get(&op).copied()
+    }
+}
+
+impl<DB: Database> Inspector<DB> for GasOverrides {
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        self.gas_inspector.initialize_interp(interp, data, is_static);
+        self.last_correction = 0;
+        Return::Continue
+    }
+
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        let op = interp.current_opcode();
+        if let Some(GasOverride::Disabled) = self.opcode(op) {
+            return Return::InvalidOpcode
+        }
+
+        let gas_remaining = self.gas_inspector.gas_remaining() as i64 - self.last_correction;
+        self.step_stack.push((op, gas_remaining.max(0) as u64));
+        self.last_correction = 0;
+
+        self.gas_inspector.step(interp, data, is_static);
+        Return::Continue
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+        eval: Return,
+    ) -> Return {
+        self.gas_inspector.step_end(interp, data, is_static, eval);
+
+        let (op, gas_before) = match self.step_stack.pop() {
+            Some(step) => step,
+            None => return Return::Continue,
+        };
+        let gas = match self.opcode(op) {
+            Some(GasOverride::Gas(gas)) => gas,
+            _ => return Return::Continue,
+        };
+
+        let charged = gas_before.saturating_sub(self.gas_inspector.gas_remaining());
+        if gas > charged {
+            if !interp.gas.record_cost(gas - charged) {
+                return Return::OutOfGas
+            }
+            self.last_correction = (gas - charged) as i64;
+        } else {
+            interp.gas.erase_cost(charged - gas);
+            self.last_correction = -((charged - gas) as i64);
+        }
+
+        Return::Continue
+    }
+
+    fn call(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CallInputs,
+        _is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        if data.journaled_state.depth() == 0 {
+            self.adjust_gas_limit(data.env, &mut inputs.gas_limit);
+        }
+        (Return::Continue, Gas::new(inputs.gas_limit), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &CallInputs,
+        remaining_gas: Gas,
+        ret: Return,
+        out: Bytes,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.gas_inspector.call_end(data, inputs, remaining_gas, ret, out.clone(), is_static);
+        (ret, remaining_gas, out)
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        if data.journaled_state.depth() == 0 {
+            self.adjust_gas_limit(data.env, &mut inputs.gas_limit);
+        }
+        (Return::Continue, None, Gas::new(inputs.gas_limit), Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        inputs: &CreateInputs,
+        ret: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        out: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.gas_inspector.create_end(data, inputs, ret, address, remaining_gas, out.clone());
+        (ret, address, remaining_gas, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+    use revm::{db::InMemoryDB, AccountInfo};
+
+    #[test]
+    fn can_parse_overrides() {
+        let overrides = GasOverrides::new(&BTreeMap::from([
+            ("SSTORE".to_string(), GasOverride::Gas(5000)),
+            ("selfdestruct".to_string(), GasOverride::Disabled),
+            ("TX_BASE".to_string(), GasOverride::Gas(25000)),
+        ]))
+        .unwrap();
+        assert_eq!(overrides.opcode(opcode::SSTORE), Some(GasOverride::Gas(5000)));
+        assert_eq!(overrides.opcode(opcode::SELFDESTRUCT), Some(GasOverride::Disabled));
+        assert_eq!(overrides.opcode(opcode::SLOAD), None);
+        assert_eq!(overrides.intrinsic.base, Some(25000));
+
+        assert!(GasOverrides::new(&BTreeMap::from([(
+            "NOT_AN_OPCODE".to_string(),
+            GasOverride::Gas(1)
+        )]))
+        .is_err());
+        assert!(GasOverrides::new(&BTreeMap::from([(
+            "TX_CREATE".to_string(),
+            GasOverride::Disabled
+        )]))
+        .is_err());
+    }
+
+    #[test]
+    fn can_calculate_intrinsic_gas() {
+        let tx = TxEnv { data: vec![0, 1, 0, 2].into(), ..Default::default() };
+        assert_eq!(IntrinsicGas::default().cost(&tx, SpecId::LONDON), 21000 + 2 * 4 + 2 * 16);
+
+        let intrinsic =
+            IntrinsicGas { base: Some(1000), data_non_zero: Some(1), ..Default::default() };
+        assert_eq!(intrinsic.cost(&tx, SpecId::LONDON), 1000 + 2 * 4 + 2);
+        assert_eq!(intrinsic.cost_delta(&tx, SpecId::LONDON), -20000 - 2 * 15);
+    }
+
+    #[test]
+    fn charges_custom_intrinsic_gas() {
+        let caller = Address::from_low_u64_be(1);
+        let balance = U256::from(1_000_000_000u64);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(caller, AccountInfo { balance, ..Default::default() });
+
+        let mut evm = revm::new();
+        evm.database(db);
+        evm.env.cfg.spec_id = SpecId::LONDON;
+        evm.env.tx = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(Address::from_low_u64_be(2)),
+            gas_limit: 100_000,
+            gas_price: 10.into(),
+            ..Default::default()
+        };
+        let overrides =
+            GasOverrides::new(&BTreeMap::from([("TX_BASE".to_string(), GasOverride::Gas(30000))]))
+                .unwrap();
+        let (result, state) = evm.inspect(overrides);
+
+        // the fee is charged for the custom intrinsic gas
+        assert_eq!(result.gas_used, 30000);
+        assert_eq!(state[&caller].info.balance, balance - 30000 * 10);
+    }
+}
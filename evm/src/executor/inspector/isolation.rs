@@ -0,0 +1,250 @@
+//! Executes the calls a test makes as separate transactions, see `--isolate`
+
+use super::{IntrinsicGas, InspectorStack};
+use crate::executor::{
+    backend::{apply_to_journal, DatabaseExt, JournaledDb},
+    CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS,
+};
+use bytes::Bytes;
+use ethers::types::{Address, H256, U256};
+use revm::{
+    return_ok, CallInputs, CallScheme, CreateInputs, EVMData, Gas, Inspector, Interpreter, Return,
+    TransactOut, TransactTo, TxEnv,
+};
+use tracing::warn;
+
+/// Returns true if the call is executed as its own transaction
+///
+/// Only calls made by the test contract itself, i.e. at depth 1, are isolated. Calls to the
+/// cheatcode and console addresses are never isolated.
+pub fn is_isolated<DB: DatabaseExt>(
+    stack: &InspectorStack,
+    data: &EVMData<'_, DB>,
+    call: &CallInputs,
+) -> bool {
+    stack.isolate &&
+        !stack.in_isolation &&
+        data.journaled_state.depth() == 1 &&
+        call.context.scheme == CallScheme::Call &&
+        call.contract != CHEATCODE_ADDRESS &&
+        call.contract != HARDHAT_CONSOLE_ADDRESS
+}
+
+/// Executes the call as a separate transaction on top of the state of the ongoing transaction
+///
+/// The transaction starts with cold accounts and storage slots and empty transient storage and is
+/// charged its intrinsic gas, its origin is the caller of the call, or the pranked caller if a
+/// prank applies to it. All inspectors of the stack see the calls of the
+/// transaction at the depth they have in the test, and its changes are applied to the ongoing
+/// transaction if it succeeds.
+pub fn transact_isolated<DB: DatabaseExt>(
+    stack: &mut InspectorStack,
+    data: &mut EVMData<'_, DB>,
+    call: &CallInputs,
+) -> (Return, Gas, Bytes) {
+    let caller = pranked_caller(stack, data, call);
+    let mut env = data.env.clone();
+    // the caller is usually a contract and the transaction is free
+    env.cfg.disable_eip3607 = true;
+    env.block.basefee = U256::zero();
+    env.tx = TxEnv {
+        caller,
+        transact_to: TransactTo::Call(call.contract),
+        value: call.transfer.value,
+        data: call.input.clone(),
+        gas_limit: 0,
+        gas_price: U256::zero(),
+        ..Default::default()
+    };
+    let intrinsic = IntrinsicGas::default().cost(&env.tx, env.cfg.spec_id);
+    env.tx.gas_limit = call.gas_limit + intrinsic;
+
+    // a real transaction would be sent by an EOA, the nonce of the test contract must not change
+    // so the addresses of the contracts it creates don't depend on the isolation
+    let nonce = data.journaled_state.state.get(&caller).map(|account| account.info.nonce);
+
+    // the transient storage of the ongoing transaction is restored once the isolated one ended
+    let transient_storage = stack.cheatcodes.as_mut().map(|cheatcodes| {
+        std::mem::take(&mut cheatcodes.transient_storage)
+    });
+
+    let depth = data.journaled_state.depth;
+    stack.in_isolation = true;
+    let (result, mut changes) = {
+        let mut db = JournaledDb::new(&data.journaled_state, &mut *data.db);
+        let mut nested = Nested::new(&mut *stack, depth, intrinsic);
+        revm::evm_inner::<JournaledDb<DB>, true>(&mut env, &mut db, &mut nested).transact()
+    };
+    stack.in_isolation = false;
+    if let (Some(cheatcodes), Some(transient_storage)) =
+        (stack.cheatcodes.as_mut(), transient_storage)
+    {
+        cheatcodes.transient_storage = transient_storage;
+    }
+
+    let mut gas = Gas::new(call.gas_limit);
+    gas.record_cost(result.gas_used.min(call.gas_limit));
+    let out = match result.out {
+        TransactOut::Call(out) => out,
+        _ => Bytes::new(),
+    };
+
+    if matches!(result.exit_reason, return_ok!()) {
+        if let (Some(nonce), Some(account)) = (nonce, changes.get_mut(&caller)) {
+            account.info.nonce = nonce;
+        }
+        if let Err(err) = apply_to_journal(&mut data.journaled_state, data.db, changes) {
+            warn!(target: "evm::isolation", ?err, "failed to apply isolated transaction");
+            return (Return::Revert, gas, Bytes::new())
+        }
+    }
+
+    (result.exit_reason, gas, out)
+}
+
+/// Returns the sender of the isolated transaction, which is the caller set by an active prank
+fn pranked_caller<DB: DatabaseExt>(
+    stack: &InspectorStack,
+    data: &EVMData<'_, DB>,
+    call: &CallInputs,
+) -> Address {
+    let prank = match stack.cheatcodes.as_ref().and_then(|cheatcodes| cheatcodes.prank.as_ref()) {
+        Some(prank) => prank,
+        None => return call.context.caller,
+    };
+    if data.journaled_state.depth() == prank.depth && call.context.caller == prank.prank_caller {
+        prank.new_caller
+    } else {
+        call.context.caller
+    }
+}
+
+/// Forwards the callbacks of an isolated transaction, or of a deployment requested by a cheatcode,
+/// to the inspector stack of the ongoing one
+///
+/// The call depth is offset by `depth` while an inspector is called, and the intrinsic gas is
+/// added to the gas of the top level call, so traces and gas reports show what the transaction
+/// would cost on chain.
+pub(super) struct Nested<'a> {
+    stack: &'a mut InspectorStack,
+    depth: usize,
+    intrinsic: u64,
+}
+
+impl<'a> Nested<'a> {
+    pub(super) fn new(stack: &'a mut InspectorStack, depth: usize, intrinsic: u64) -> Self {
+        Self { stack, depth, intrinsic }
+    }
+
+    fn with_depth<DB: DatabaseExt, R>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        f: impl FnOnce(&mut InspectorStack, &mut EVMData<'_, DB>) -> R,
+    ) -> R {
+        data.journaled_state.depth += self.depth;
+        let res = f(self.stack, data);
+        data.journaled_state.depth -= self.depth;
+        res
+    }
+
+    /// Adds the intrinsic gas to the gas of the top level call of the transaction
+    fn with_intrinsic<DB: DatabaseExt>(&self, data: &EVMData<'_, DB>, gas: Gas) -> Gas {
+        if data.journaled_state.depth() != 0 {
+            return gas
+        }
+        let mut with_intrinsic = Gas::new(gas.limit() + self.intrinsic);
+        with_intrinsic.record_cost(gas.spend() + self.intrinsic);
+        with_intrinsic.record_refund(gas.refunded());
+        with_intrinsic
+    }
+}
+
+impl<'a, DB: DatabaseExt> Inspector<DB> for Nested<'a> {
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        self.with_depth(data, |stack, data| stack.initialize_interp(interp, data, is_static))
+    }
+
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        self.with_depth(data, |stack, data| stack.step(interp, data, is_static))
+    }
+
+    fn log(
+        &mut self,
+        evm_data: &mut EVMData<'_, DB>,
+        address: &Address,
+        topics: &[H256],
+        data: &Bytes,
+    ) {
+        self.with_depth(evm_data, |stack, evm_data| stack.log(evm_data, address, topics, data))
+    }
+
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+        status: Return,
+    ) -> Return {
+        self.with_depth(data, |stack, data| stack.step_end(interp, data, is_static, status))
+    }
+
+    fn call(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.with_depth(data, |stack, data| stack.call(data, call, is_static))
+    }
+
+    fn call_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+        remaining_gas: Gas,
+        status: Return,
+        retdata: Bytes,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        let remaining_gas = self.with_intrinsic(data, remaining_gas);
+        self.with_depth(data, |stack, data| {
+            stack.call_end(data, call, remaining_gas, status, retdata, is_static)
+        })
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.with_depth(data, |stack, data| stack.create(data, call))
+    }
+
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.with_depth(data, |stack, data| {
+            stack.create_end(data, call, status, address, remaining_gas, retdata)
+        })
+    }
+
+    fn selfdestruct(&mut self) {
+        Inspector::<DB>::selfdestruct(self.stack);
+    }
+}
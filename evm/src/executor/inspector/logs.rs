@@ -8,6 +8,7 @@ use ethers::{
 };
 use foundry_macros::ConsoleFmt;
 use revm::{db::Database, CallInputs, EVMData, Gas, Inspector, Return};
+use tracing::trace;
 
 /// An inspector that collects logs during execution.
 ///
@@ -18,23 +19,16 @@ pub struct LogCollector {
 }
 
 impl LogCollector {
-    fn hardhat_log(&mut self, input: Vec<u8>) -> (Return, Bytes) {
+    fn hardhat_log(&mut self, input: Vec<u8>) {
         // Patch the Hardhat-style selectors
-        let input = patch_hardhat_console_selector(input.to_vec());
-        let decoded = match HardhatConsoleCalls::decode(input) {
-            Ok(inner) => inner,
-            Err(err) => {
-                return (
-                    Return::Revert,
-                    ethers::abi::encode(&[Token::String(err.to_string())]).into(),
-                )
-            }
-        };
-
-        // Convert it to a DS-style `emit log(string)` event
-        self.logs.push(convert_hh_log_to_event(decoded));
-
-        (Return::Continue, Bytes::new())
+        let input = patch_hardhat_console_selector(input);
+        match HardhatConsoleCalls::decode(input) {
+            // Convert it to a DS-style `emit log(string)` event
+            Ok(decoded) => self.logs.push(convert_hh_log_to_event(decoded)),
+            // Calls to the console address never revert, so an unknown log must not break the
+            // execution
+            Err(err) => trace!(?err, "failed to decode hardhat console log"),
+        }
     }
 }
 
@@ -58,11 +52,9 @@ where
         _: bool,
     ) -> (Return, Gas, Bytes) {
         if call.contract == HARDHAT_CONSOLE_ADDRESS {
-            let (status, reason) = self.hardhat_log(call.input.to_vec());
-            (status, Gas::new(call.gas_limit), reason)
-        } else {
-            (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+            self.hardhat_log(call.input.to_vec());
         }
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 }
 
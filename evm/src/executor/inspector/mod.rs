@@ -4,7 +4,7 @@ mod utils;
 mod logs;
 
 pub use logs::LogCollector;
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
 
 mod access_list;
 pub use access_list::AccessListTracer;
@@ -22,7 +22,10 @@ mod stack;
 pub use stack::{InspectorData, InspectorStack};
 
 pub mod cheatcodes;
-pub use cheatcodes::{Cheatcodes, CheatsConfig, DEFAULT_CREATE2_DEPLOYER};
+pub use cheatcodes::{
+    CheatcodeFailure, Cheatcodes, CheatsConfig, OnChainAssertionFailure, TransientStorage,
+    DEFAULT_CREATE2_DEPLOYER,
+};
 
 mod chisel_state;
 pub use chisel_state::ChiselState;
@@ -37,6 +40,22 @@ pub use fuzzer::Fuzzer;
 mod printer;
 pub use printer::TracePrinter;
 
+mod reverts;
+pub use reverts::RevertCollector;
+
+mod timeout;
+pub use timeout::{
+    is_timeout_reason, Timeout, TimeoutGuard, TimeoutLocation, TIMEOUT_REASON_PREFIX,
+};
+
+mod gas_overrides;
+pub use gas_overrides::{GasOverrides, IntrinsicGas};
+
+mod isolation;
+
+pub mod plugin;
+pub use plugin::{InspectorPlugin, InspectorPluginFactory, PluginContext};
+
 #[derive(Default, Clone, Debug)]
 pub struct InspectorStackConfig {
     /// The cheatcode inspector and its state, if cheatcodes are enabled.
@@ -54,6 +73,8 @@ pub struct InspectorStackConfig {
     pub gas_price: U256,
     /// Whether tracing is enabled
     pub tracing: bool,
+    /// Whether the jumps of traced calls are recorded to reconstruct internal function calls
+    pub decode_internal: bool,
     /// Whether the debugger is enabled
     pub debugger: bool,
     /// The fuzzer inspector and its state, if it exists.
@@ -67,6 +88,19 @@ pub struct InspectorStackConfig {
     /// If the inspector is enabled, Some(final_pc)
     /// If not, None
     pub chisel_state: Option<usize>,
+    /// The time budget of a test, after which its executions are aborted
+    ///
+    /// Executions that run before the budget is started with
+    /// [InspectorStackConfig::start_timeout()] get a budget of their own.
+    pub timeout: Option<Duration>,
+    /// The started budget that is shared by all executions of the current test
+    pub started_timeout: Option<Timeout>,
+    /// The custom gas schedule, if any opcode or intrinsic cost is overridden
+    pub gas_overrides: Option<GasOverrides>,
+    /// The inspectors provided by downstream crates, a new instance of each is added to every stack
+    pub plugins: Vec<Arc<dyn InspectorPluginFactory>>,
+    /// Whether the calls of the test contract are executed as separate transactions
+    pub isolate: bool,
 }
 
 impl InspectorStackConfig {
@@ -84,7 +118,9 @@ impl InspectorStackConfig {
         }
 
         if self.tracing {
-            stack.tracer = Some(Tracer::default());
+            let tracer = Tracer::default();
+            stack.tracer =
+                Some(if self.decode_internal { tracer.with_jumps_recording() } else { tracer });
         }
         if self.debugger {
             let gas_inspector = Rc::new(RefCell::new(GasInspector::default()));
@@ -104,9 +140,22 @@ impl InspectorStackConfig {
         if let Some(final_pc) = self.chisel_state {
             stack.chisel_state = Some(ChiselState::new(final_pc));
         }
+
+        if let Some(timeout) = self.started_timeout.or_else(|| self.timeout.map(Timeout::start)) {
+            stack.timeout = Some(TimeoutGuard::new(timeout));
+        }
+
+        stack.gas_overrides = self.gas_overrides.clone();
+        stack.plugins = self.plugins.iter().map(|plugin| plugin.create()).collect();
+        stack.isolate = self.isolate;
         stack
     }
 
+    /// Starts the time budget of a test now, so it's shared by all following executions
+    pub fn start_timeout(&mut self) {
+        self.started_timeout = self.timeout.map(Timeout::start);
+    }
+
     /// Configures the cheatcode inspector with a new and empty context
     ///
     /// Returns `None` if no cheatcodes inspector is set
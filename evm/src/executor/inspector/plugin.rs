@@ -0,0 +1,215 @@
+//! Support for inspectors provided by downstream crates
+//!
+//! An [InspectorPlugin] is called after all of the built-in inspectors of the [InspectorStack].
+//! Since the stack is recreated for every execution, plugins are registered as
+//! [InspectorPluginFactory]s, either directly via [ExecutorBuilder::with_inspector_plugin] or by
+//! name via [register_inspector_plugin] and the `inspector_plugins` config value.
+//!
+//! [InspectorStack]: super::InspectorStack
+//! [ExecutorBuilder::with_inspector_plugin]: crate::executor::ExecutorBuilder::with_inspector_plugin
+
+use crate::executor::backend::DatabaseExt;
+use bytes::Bytes;
+use ethers::types::{Address, H256};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use revm::{CallInputs, CreateInputs, EVMData, Env, Gas, Interpreter, JournaledState, Return};
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+/// The registered plugins, by name
+static INSPECTOR_PLUGINS: Lazy<RwLock<BTreeMap<String, Arc<dyn InspectorPluginFactory>>>> =
+    Lazy::new(Default::default);
+
+/// Registers the plugin under its [name](InspectorPluginFactory::name), so it can be enabled via
+/// the `inspector_plugins` config value
+///
+/// Replaces and returns a plugin previously registered under the same name, if any.
+pub fn register_inspector_plugin(
+    factory: Arc<dyn InspectorPluginFactory>,
+) -> Option<Arc<dyn InspectorPluginFactory>> {
+    INSPECTOR_PLUGINS.write().insert(factory.name().to_string(), factory)
+}
+
+/// Returns the plugin registered under the given name, if any
+pub fn inspector_plugin(name: &str) -> Option<Arc<dyn InspectorPluginFactory>> {
+    INSPECTOR_PLUGINS.read().get(name).cloned()
+}
+
+/// Returns the plugins registered under the given names
+///
+/// Returns an error if one of them isn't registered.
+pub fn inspector_plugins(
+    names: impl IntoIterator<Item = impl AsRef<str>>,
+) -> eyre::Result<Vec<Arc<dyn InspectorPluginFactory>>> {
+    names
+        .into_iter()
+        .map(|name| {
+            let name = name.as_ref();
+            inspector_plugin(name).ok_or_else(|| {
+                eyre::eyre!(
+                    "unknown inspector plugin `{name}`, registered plugins are: {:?}",
+                    INSPECTOR_PLUGINS.read().keys().collect::<Vec<_>>()
+                )
+            })
+        })
+        .collect()
+}
+
+/// Creates a new instance of an [InspectorPlugin] for every execution
+///
+/// State that should outlive a single execution, like collected metrics, can be shared between
+/// the factory and its plugins.
+pub trait InspectorPluginFactory: fmt::Debug + Send + Sync {
+    /// The name the plugin is enabled with in the `inspector_plugins` config value
+    fn name(&self) -> &str;
+
+    /// Returns a new instance of the plugin
+    fn create(&self) -> Box<dyn InspectorPlugin>;
+}
+
+/// The parts of the EVM state that are accessible to an [InspectorPlugin]
+pub struct PluginContext<'a> {
+    /// The execution environment
+    pub env: &'a Env,
+    /// The state of all accounts touched during the execution
+    pub journaled_state: &'a mut JournaledState,
+}
+
+impl<'a> PluginContext<'a> {
+    /// Returns the context of the given EVM data
+    pub fn new<DB: DatabaseExt>(data: &'a mut EVMData<'_, DB>) -> Self {
+        Self { env: &*data.env, journaled_state: &mut data.journaled_state }
+    }
+
+    /// Returns the current call depth
+    pub fn depth(&self) -> u64 {
+        self.journaled_state.depth()
+    }
+}
+
+/// An inspector provided by a downstream crate
+///
+/// This mirrors [revm::Inspector], but doesn't depend on the database of the executor, so it can
+/// be used as a trait object. All hooks default to a no-op.
+///
+/// Like the built-in inspectors, a plugin can abort the execution by returning a [Return] other
+/// than [Return::Continue] from a hook.
+#[allow(unused_variables)]
+pub trait InspectorPlugin: fmt::Debug {
+    /// Called before the interpreter of a new call frame is run
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        ctx: PluginContext<'_>,
+        is_static: bool,
+    ) -> Return {
+        Return::Continue
+    }
+
+    /// Called before every opcode
+    fn step(
+        &mut self,
+        interp: &mut Interpreter,
+        ctx: PluginContext<'_>,
+        is_static: bool,
+    ) -> Return {
+        Return::Continue
+    }
+
+    /// Called after every opcode
+    fn step_end(
+        &mut self,
+        interp: &mut Interpreter,
+        ctx: PluginContext<'_>,
+        is_static: bool,
+        status: Return,
+    ) -> Return {
+        Return::Continue
+    }
+
+    /// Called when a log is emitted
+    fn log(&mut self, ctx: PluginContext<'_>, address: &Address, topics: &[H256], data: &Bytes) {}
+
+    /// Called before a call is executed
+    fn call(
+        &mut self,
+        ctx: PluginContext<'_>,
+        call: &mut CallInputs,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    /// Called after a call is executed
+    fn call_end(
+        &mut self,
+        ctx: PluginContext<'_>,
+        call: &CallInputs,
+        remaining_gas: Gas,
+        status: Return,
+        retdata: Bytes,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        (status, remaining_gas, retdata)
+    }
+
+    /// Called before a contract is created
+    fn create(
+        &mut self,
+        ctx: PluginContext<'_>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    /// Called after a contract is created
+    fn create_end(
+        &mut self,
+        ctx: PluginContext<'_>,
+        call: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        (status, address, remaining_gas, retdata)
+    }
+
+    /// Called when a contract self destructs
+    fn selfdestruct(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct NoopPlugin;
+
+    impl InspectorPlugin for NoopPlugin {}
+
+    #[derive(Debug)]
+    struct NoopFactory;
+
+    impl InspectorPluginFactory for NoopFactory {
+        fn name(&self) -> &str {
+            "noop"
+        }
+
+        fn create(&self) -> Box<dyn InspectorPlugin> {
+            Box::new(NoopPlugin)
+        }
+    }
+
+    #[test]
+    fn can_register_plugins() {
+        assert!(register_inspector_plugin(Arc::new(NoopFactory)).is_none());
+        assert!(register_inspector_plugin(Arc::new(NoopFactory)).is_some());
+
+        let plugins = inspector_plugins(["noop"]).unwrap();
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0].name(), "noop");
+
+        assert!(inspector_plugins(["noop", "missing"]).is_err());
+    }
+}
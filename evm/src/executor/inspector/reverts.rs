@@ -0,0 +1,94 @@
+use bytes::Bytes;
+use ethers::types::Address;
+use revm::{return_ok, Return};
+
+/// Records the reverted calls that caused the revert of a transaction, so the reason of a call
+/// whose revert data was not bubbled up can be reported without tracing
+///
+/// A reverted call is only attributed to its caller if it was the last call the caller made before
+/// it reverted itself, e.g. a low level call whose success is required.
+#[derive(Debug, Clone, Default)]
+pub struct RevertCollector {
+    /// The result of the last ended call at every depth: the reverted calls that caused its
+    /// revert, innermost first, or `None` if it succeeded
+    last_ended: Vec<Option<Vec<(Address, Bytes)>>>,
+}
+
+impl RevertCollector {
+    /// Records the end of a call or create at `depth`, the depth of its caller
+    pub fn on_frame_end(
+        &mut self,
+        depth: usize,
+        address: Address,
+        status: Return,
+        retdata: &Bytes,
+    ) {
+        // the calls at deeper depths were made by the ended call
+        let last_call = self.last_ended.get_mut(depth + 1).and_then(Option::take);
+        self.last_ended.truncate(depth + 1);
+        if self.last_ended.len() <= depth {
+            self.last_ended.resize(depth + 1, None);
+        }
+
+        self.last_ended[depth] = if matches!(status, return_ok!()) {
+            None
+        } else {
+            let mut reverted = last_call.unwrap_or_default();
+            reverted.push((address, retdata.clone()));
+            Some(reverted)
+        };
+    }
+
+    /// Returns the reverted calls that caused the revert of the transaction, innermost first
+    pub fn reverted_calls(&self) -> Vec<(Address, Bytes)> {
+        self.last_ended.first().cloned().flatten().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(n: u64) -> Address {
+        Address::from_low_u64_be(n)
+    }
+
+    #[test]
+    fn follows_propagated_reverts() {
+        let mut reverts = RevertCollector::default();
+        reverts.on_frame_end(2, address(3), Return::Revert, &Bytes::from_static(b"inner"));
+        reverts.on_frame_end(1, address(2), Return::Revert, &Bytes::new());
+        reverts.on_frame_end(0, address(1), Return::Revert, &Bytes::new());
+
+        assert_eq!(
+            reverts.reverted_calls(),
+            vec![
+                (address(3), Bytes::from_static(b"inner")),
+                (address(2), Bytes::new()),
+                (address(1), Bytes::new())
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_caught_reverts() {
+        let mut reverts = RevertCollector::default();
+        // a reverted call followed by a successful one
+        reverts.on_frame_end(1, address(2), Return::Revert, &Bytes::from_static(b"caught"));
+        reverts.on_frame_end(1, address(3), Return::Return, &Bytes::new());
+        reverts.on_frame_end(0, address(1), Return::Revert, &Bytes::new());
+        assert_eq!(reverts.reverted_calls(), vec![(address(1), Bytes::new())]);
+
+        // the reverted call of a successful call
+        let mut reverts = RevertCollector::default();
+        reverts.on_frame_end(2, address(3), Return::Revert, &Bytes::from_static(b"caught"));
+        reverts.on_frame_end(1, address(2), Return::Return, &Bytes::new());
+        reverts.on_frame_end(0, address(1), Return::Revert, &Bytes::new());
+        assert_eq!(reverts.reverted_calls(), vec![(address(1), Bytes::new())]);
+
+        let mut reverts = RevertCollector::default();
+        reverts.on_frame_end(1, address(2), Return::Revert, &Bytes::from_static(b"caught"));
+        reverts.on_frame_end(0, address(1), Return::Return, &Bytes::new());
+        assert!(reverts.reverted_calls().is_empty());
+    }
+}
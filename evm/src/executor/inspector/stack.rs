@@ -1,8 +1,12 @@
-use super::{Cheatcodes, ChiselState, Debugger, Fuzzer, LogCollector, TracePrinter, Tracer};
+use super::{
+    cheatcodes::transact_deployment, isolation, Cheatcodes, ChiselState, Debugger, Fuzzer,
+    GasOverrides, InspectorPlugin, LogCollector, PluginContext, RevertCollector, TimeoutGuard,
+    TracePrinter, Tracer, TransientStorage,
+};
 use crate::{
     coverage::HitMaps,
     debug::DebugArena,
-    executor::{backend::DatabaseExt, inspector::CoverageCollector},
+    executor::{backend::DatabaseExt, delegation, inspector::CoverageCollector},
     trace::CallTraceArena,
 };
 use bytes::Bytes;
@@ -38,6 +42,8 @@ pub struct InspectorData {
     pub cheatcodes: Option<Cheatcodes>,
     pub script_wallets: Vec<LocalWallet>,
     pub chisel_state: Option<(revm::Stack, revm::Memory, revm::Return)>,
+    /// The reverted calls that caused the revert of the transaction, innermost first
+    pub reverted_calls: Vec<(Address, Bytes)>,
 }
 
 /// An inspector that calls multiple inspectors in sequence.
@@ -55,9 +61,62 @@ pub struct InspectorStack {
     pub coverage: Option<CoverageCollector>,
     pub printer: Option<TracePrinter>,
     pub chisel_state: Option<ChiselState>,
+    pub timeout: Option<TimeoutGuard>,
+    pub gas_overrides: Option<GasOverrides>,
+    pub reverts: RevertCollector,
+    /// Inspectors provided by downstream crates, called after all built-in inspectors
+    pub plugins: Vec<Box<dyn InspectorPlugin>>,
+    /// Whether the calls of the test contract are executed as separate transactions
+    pub isolate: bool,
+    /// Whether an isolated transaction is currently executed
+    pub(crate) in_isolation: bool,
+    /// Whether the next `call_end` belongs to a call that was executed as an isolated
+    /// transaction, and was already seen by all inspectors
+    pub(crate) skip_call_end: bool,
 }
 
 impl InspectorStack {
+    /// Calls `step` of all inspectors
+    fn step_inspectors<DB: DatabaseExt>(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        call_inspectors!(
+            inspector,
+            [
+                &mut self.gas_overrides,
+                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
+                &mut self.fuzzer,
+                &mut self.debugger,
+                &mut self.tracer,
+                &mut self.coverage,
+                &mut self.logs,
+                &mut self.cheatcodes,
+                &mut self.printer,
+                &mut self.timeout
+            ],
+            {
+                let status = inspector.step(interpreter, data, is_static);
+
+                // Allow inspectors to exit early
+                if status != Return::Continue {
+                    return status
+                }
+            }
+        );
+
+        for plugin in &mut self.plugins {
+            let status = plugin.step(interpreter, PluginContext::new(data), is_static);
+            if status != Return::Continue {
+                return status
+            }
+        }
+
+        Return::Continue
+    }
+
     pub fn collect_inspector_states(self) -> InspectorData {
         InspectorData {
             logs: self.logs.map(|logs| logs.logs).unwrap_or_default(),
@@ -76,6 +135,7 @@ impl InspectorStack {
                 .unwrap_or_default(),
             cheatcodes: self.cheatcodes,
             chisel_state: self.chisel_state.unwrap_or_default().state,
+            reverted_calls: self.reverts.reverted_calls(),
         }
     }
 
@@ -91,6 +151,7 @@ impl InspectorStack {
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.fuzzer,
                 &mut self.debugger,
@@ -98,7 +159,8 @@ impl InspectorStack {
                 &mut self.coverage,
                 &mut self.logs,
                 &mut self.cheatcodes,
-                &mut self.printer
+                &mut self.printer,
+                &mut self.timeout
             ],
             {
                 let (new_status, new_gas, new_retdata) = inspector.call_end(
@@ -119,45 +181,84 @@ impl InspectorStack {
             }
         );
 
+        for plugin in &mut self.plugins {
+            let (new_status, new_gas, new_retdata) = plugin.call_end(
+                PluginContext::new(data),
+                call,
+                remaining_gas,
+                status,
+                retdata.clone(),
+                is_static,
+            );
+            if new_status != status || (new_status == Return::Revert && new_retdata != retdata) {
+                return (new_status, new_gas, new_retdata)
+            }
+        }
+
         (status, remaining_gas, retdata)
     }
-}
 
-impl<DB> Inspector<DB> for InspectorStack
-where
-    DB: DatabaseExt,
-{
-    fn initialize_interp(
+    fn do_create_end<DB: DatabaseExt>(
         &mut self,
-        interpreter: &mut Interpreter,
         data: &mut EVMData<'_, DB>,
-        is_static: bool,
-    ) -> Return {
+        call: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.debugger,
-                &mut self.coverage,
                 &mut self.tracer,
+                &mut self.coverage,
                 &mut self.logs,
                 &mut self.cheatcodes,
-                &mut self.printer
+                &mut self.printer,
+                &mut self.timeout
             ],
             {
-                let status = inspector.initialize_interp(interpreter, data, is_static);
+                let (new_status, new_address, new_gas, new_retdata) = inspector.create_end(
+                    data,
+                    call,
+                    status,
+                    address,
+                    remaining_gas,
+                    retdata.clone(),
+                );
 
-                // Allow inspectors to exit early
-                if status != Return::Continue {
-                    return status
+                if new_status != status {
+                    return (new_status, new_address, new_gas, new_retdata)
                 }
             }
         );
 
-        Return::Continue
+        for plugin in &mut self.plugins {
+            let (new_status, new_address, new_gas, new_retdata) = plugin.create_end(
+                PluginContext::new(data),
+                call,
+                status,
+                address,
+                remaining_gas,
+                retdata.clone(),
+            );
+            if new_status != status {
+                return (new_status, new_address, new_gas, new_retdata)
+            }
+        }
+
+        (status, address, remaining_gas, retdata)
     }
+}
 
-    fn step(
+impl<DB> Inspector<DB> for InspectorStack
+where
+    DB: DatabaseExt,
+{
+    fn initialize_interp(
         &mut self,
         interpreter: &mut Interpreter,
         data: &mut EVMData<'_, DB>,
@@ -166,17 +267,17 @@ where
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
-                &mut self.fuzzer,
                 &mut self.debugger,
-                &mut self.tracer,
                 &mut self.coverage,
+                &mut self.tracer,
                 &mut self.logs,
                 &mut self.cheatcodes,
                 &mut self.printer
             ],
             {
-                let status = inspector.step(interpreter, data, is_static);
+                let status = inspector.initialize_interp(interpreter, data, is_static);
 
                 // Allow inspectors to exit early
                 if status != Return::Continue {
@@ -185,9 +286,48 @@ where
             }
         );
 
+        for plugin in &mut self.plugins {
+            let status = plugin.initialize_interp(interpreter, PluginContext::new(data), is_static);
+            if status != Return::Continue {
+                return status
+            }
+        }
+
         Return::Continue
     }
 
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        loop {
+            let status = self.step_inspectors(interpreter, data, is_static);
+            if status != Return::Continue {
+                return status
+            }
+
+            // the EVM doesn't know `TLOAD` and `TSTORE`, so they are executed here and every
+            // inspector sees them like any other opcode
+            let transient_storage = match self.cheatcodes.as_mut() {
+                Some(cheatcodes) if TransientStorage::is_transient_opcode(interpreter) => {
+                    &mut cheatcodes.transient_storage
+                }
+                _ => return Return::Continue,
+            };
+            let status = transient_storage.execute(interpreter, is_static);
+            let end_status = self.step_end(interpreter, data, is_static, status);
+            if status != Return::Continue {
+                return status
+            }
+            if end_status != Return::Continue {
+                return end_status
+            }
+            // the next opcode was skipped by the interpreter, so it's stepped here as well
+        }
+    }
+
     fn log(
         &mut self,
         evm_data: &mut EVMData<'_, DB>,
@@ -202,6 +342,10 @@ where
                 inspector.log(evm_data, address, topics, data);
             }
         );
+
+        for plugin in &mut self.plugins {
+            plugin.log(PluginContext::new(evm_data), address, topics, data);
+        }
     }
 
     fn step_end(
@@ -214,6 +358,7 @@ where
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.debugger,
                 &mut self.tracer,
@@ -232,6 +377,13 @@ where
             }
         );
 
+        for plugin in &mut self.plugins {
+            let status = plugin.step_end(interpreter, PluginContext::new(data), is_static, status);
+            if status != Return::Continue {
+                return status
+            }
+        }
+
         Return::Continue
     }
 
@@ -241,9 +393,18 @@ where
         call: &mut CallInputs,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        if isolation::is_isolated(self, data, call) {
+            let (status, gas, retdata) = isolation::transact_isolated(self, data, call);
+            // revm still calls `call_end` for the call, which the inspectors already saw as the
+            // top level call of the isolated transaction
+            self.skip_call_end = true;
+            return (status, gas, retdata)
+        }
+
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.fuzzer,
                 &mut self.debugger,
@@ -251,7 +412,8 @@ where
                 &mut self.coverage,
                 &mut self.logs,
                 &mut self.cheatcodes,
-                &mut self.printer
+                &mut self.printer,
+                &mut self.timeout
             ],
             {
                 let (status, gas, retdata) = inspector.call(data, call, is_static);
@@ -263,6 +425,24 @@ where
             }
         );
 
+        for plugin in &mut self.plugins {
+            let (status, gas, retdata) = plugin.call(PluginContext::new(data), call, is_static);
+            if status != Return::Continue {
+                return (status, gas, retdata)
+            }
+        }
+
+        // a deployment requested by a cheatcode is executed with all inspectors, so they see the
+        // constructor
+        if let Some(deployment) =
+            self.cheatcodes.as_mut().and_then(|cheatcodes| cheatcodes.pending_deployment.take())
+        {
+            return transact_deployment(self, data, call, deployment)
+        }
+
+        // the inspectors see the callee, the evm runs the code it delegates to
+        delegation::resolve_delegation(data, call);
+
         (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -275,7 +455,13 @@ where
         retdata: Bytes,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        if std::mem::take(&mut self.skip_call_end) {
+            return (status, remaining_gas, retdata)
+        }
+
         let res = self.do_call_end(data, call, remaining_gas, status, retdata, is_static);
+        let depth = data.journaled_state.depth() as usize;
+        self.reverts.on_frame_end(depth, call.contract, res.0, &res.2);
 
         if matches!(res.0, return_revert!()) {
             // Encountered a revert, since cheatcodes may have altered the evm state in such a way
@@ -297,13 +483,15 @@ where
         call_inspectors!(
             inspector,
             [
+                &mut self.gas_overrides,
                 &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
                 &mut self.debugger,
                 &mut self.tracer,
                 &mut self.coverage,
                 &mut self.logs,
                 &mut self.cheatcodes,
-                &mut self.printer
+                &mut self.printer,
+                &mut self.timeout
             ],
             {
                 let (status, addr, gas, retdata) = inspector.create(data, call);
@@ -315,6 +503,13 @@ where
             }
         );
 
+        for plugin in &mut self.plugins {
+            let (status, addr, gas, retdata) = plugin.create(PluginContext::new(data), call);
+            if status != Return::Continue {
+                return (status, addr, gas, retdata)
+            }
+        }
+
         (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -327,34 +522,10 @@ where
         remaining_gas: Gas,
         retdata: Bytes,
     ) -> (Return, Option<Address>, Gas, Bytes) {
-        call_inspectors!(
-            inspector,
-            [
-                &mut self.gas.as_deref().map(|gas| gas.borrow_mut()),
-                &mut self.debugger,
-                &mut self.tracer,
-                &mut self.coverage,
-                &mut self.logs,
-                &mut self.cheatcodes,
-                &mut self.printer
-            ],
-            {
-                let (new_status, new_address, new_gas, new_retdata) = inspector.create_end(
-                    data,
-                    call,
-                    status,
-                    address,
-                    remaining_gas,
-                    retdata.clone(),
-                );
-
-                if new_status != status {
-                    return (new_status, new_address, new_gas, new_retdata)
-                }
-            }
-        );
-
-        (status, address, remaining_gas, retdata)
+        let res = self.do_create_end(data, call, status, address, remaining_gas, retdata);
+        let depth = data.journaled_state.depth() as usize;
+        self.reverts.on_frame_end(depth, res.1.unwrap_or_default(), res.0, &res.3);
+        res
     }
 
     fn selfdestruct(&mut self) {
@@ -372,5 +543,9 @@ where
                 Inspector::<DB>::selfdestruct(inspector);
             }
         );
+
+        for plugin in &mut self.plugins {
+            plugin.selfdestruct();
+        }
     }
 }
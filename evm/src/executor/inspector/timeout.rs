@@ -0,0 +1,199 @@
+use crate::{error::REVERT_PREFIX, executor::backend::DatabaseExt};
+use bytes::Bytes;
+use ethers::{abi::AbiEncode, types::Address};
+use revm::{opcode, CallInputs, CreateInputs, EVMData, Gas, Inspector, Interpreter, Return};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Prefix of the revert reason of executions that were aborted by the [TimeoutGuard]
+pub const TIMEOUT_REASON_PREFIX: &str = "execution timed out";
+
+/// The number of steps between two checks of the clock
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Returns true if the given revert reason was produced by a [TimeoutGuard]
+pub fn is_timeout_reason(reason: &str) -> bool {
+    reason.starts_with(TIMEOUT_REASON_PREFIX)
+}
+
+/// The location of the last executed instruction before the timeout was hit
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutLocation {
+    /// The address of the executing contract
+    pub address: Address,
+    /// The call depth
+    pub depth: u64,
+    /// The program counter
+    pub pc: usize,
+    /// The opcode at `pc`
+    pub opcode: u8,
+}
+
+impl fmt::Display for TimeoutLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = opcode::OPCODE_JUMPMAP[self.opcode as usize].unwrap_or("UNKNOWN");
+        write!(f, "{name} at pc {} in {:?} (depth {})", self.pc, self.address, self.depth)
+    }
+}
+
+/// A time budget that is shared by all executions it is applied to, e.g. all executions of a test
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    /// The length of the budget
+    pub duration: Duration,
+    /// The instant the budget is exceeded
+    pub deadline: Instant,
+}
+
+impl Timeout {
+    /// Starts a budget of `duration` from now
+    pub fn start(duration: Duration) -> Self {
+        Self { duration, deadline: Instant::now() + duration }
+    }
+}
+
+/// An inspector that cooperatively aborts an execution once its [Timeout] is exceeded.
+///
+/// The clock is checked on the first step of the execution and then every [CHECK_INTERVAL] steps,
+/// so an execution that starts after the deadline is aborted right away. After the deadline
+/// passed, every step and every new call is halted, so all call frames unwind, each of them
+/// reverting with a reason that points to the last executed instruction.
+#[derive(Debug, Clone)]
+pub struct TimeoutGuard {
+    timeout: Timeout,
+    steps: u64,
+    /// Set once the deadline passed
+    pub location: Option<TimeoutLocation>,
+}
+
+impl TimeoutGuard {
+    /// Creates a new guard for an execution that is part of the given budget
+    pub fn new(timeout: Timeout) -> Self {
+        Self { timeout, steps: 0, location: None }
+    }
+
+    /// The revert data returned by every aborted call frame
+    fn revert_data(&self) -> Bytes {
+        let reason = match self.location {
+            Some(location) => format!(
+                "{TIMEOUT_REASON_PREFIX} after {:?}; last executed {location}",
+                self.timeout.duration
+            ),
+            None => format!("{TIMEOUT_REASON_PREFIX} after {:?}", self.timeout.duration),
+        };
+        [REVERT_PREFIX.as_slice(), reason.encode().as_slice()].concat().into()
+    }
+}
+
+impl<DB> Inspector<DB> for TimeoutGuard
+where
+    DB: DatabaseExt,
+{
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _is_static: bool,
+    ) -> Return {
+        if self.location.is_some() {
+            return Return::Revert
+        }
+
+        let check = self.steps % CHECK_INTERVAL == 0;
+        self.steps += 1;
+        if check && Instant::now() >= self.timeout.deadline {
+            self.location = Some(TimeoutLocation {
+                address: interpreter.contract.address,
+                depth: data.journaled_state.depth(),
+                pc: interpreter.program_counter(),
+                opcode: interpreter.current_opcode(),
+            });
+            return Return::Revert
+        }
+
+        Return::Continue
+    }
+
+    fn call(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        if self.location.is_some() {
+            return (Return::Revert, Gas::new(call.gas_limit), self.revert_data())
+        }
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        _: &CallInputs,
+        remaining_gas: Gas,
+        status: Return,
+        retdata: Bytes,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        if self.location.is_some() {
+            return (Return::Revert, remaining_gas, self.revert_data())
+        }
+        (status, remaining_gas, retdata)
+    }
+
+    fn create(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        if self.location.is_some() {
+            return (Return::Revert, None, Gas::new(call.gas_limit), self.revert_data())
+        }
+        (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        _: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        if self.location.is_some() {
+            return (Return::Revert, None, remaining_gas, self.revert_data())
+        }
+        (status, address, remaining_gas, retdata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::inspector::InspectorStackConfig;
+
+    fn deadline(config: &InspectorStackConfig) -> Instant {
+        config.stack().timeout.expect("timeout is set").timeout.deadline
+    }
+
+    #[test]
+    fn starts_budget_per_test() {
+        let duration = Duration::from_secs(60);
+        let mut config = InspectorStackConfig { timeout: Some(duration), ..Default::default() };
+
+        // before the budget is started, every execution gets a budget of its own
+        let now = Instant::now();
+        assert!(deadline(&config) >= now + duration);
+
+        // an exceeded budget is shared by all executions until a new one is started
+        config.started_timeout = Some(Timeout { duration, deadline: now });
+        assert_eq!(deadline(&config), now);
+        assert_eq!(deadline(&config), now);
+
+        config.start_timeout();
+        assert!(deadline(&config) >= now + duration);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     debug::Instruction::OpCode,
-    executor::inspector::utils::{gas_used, get_create_address},
+    executor::inspector::utils::{gas_used, get_create_address, refund_cap},
     trace::{
+        internal::{JumpStep, MAX_JUMP_STACK},
         CallTrace, CallTraceArena, CallTraceStep, LogCallOrder, RawOrDecodedCall, RawOrDecodedLog,
         RawOrDecodedReturnData,
     },
@@ -14,7 +15,7 @@ use ethers::{
 };
 use revm::{
     opcode, return_ok, CallInputs, CallScheme, CreateInputs, Database, EVMData, Gas, GasInspector,
-    Inspector, Interpreter, JournalEntry, Return,
+    Inspector, Interpreter, JournalEntry, Return, SpecId,
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -22,6 +23,9 @@ use std::{cell::RefCell, rc::Rc};
 #[derive(Default, Debug, Clone)]
 pub struct Tracer {
     record_steps: bool,
+    record_jumps: bool,
+    /// Whether the last step of the current call was a `JUMP`
+    jumping: bool,
 
     pub traces: CallTraceArena,
     trace_stack: Vec<usize>,
@@ -41,6 +45,12 @@ impl Tracer {
         self
     }
 
+    /// Enables recording the jumps of calls, to reconstruct internal function calls from them
+    pub fn with_jumps_recording(mut self) -> Self {
+        self.record_jumps = true;
+        self
+    }
+
     fn start_trace(
         &mut self,
         depth: usize,
@@ -65,14 +75,24 @@ impl Tracer {
         ));
     }
 
-    fn fill_trace(&mut self, status: Return, cost: u64, output: Vec<u8>, address: Option<Address>) {
+    fn fill_trace(
+        &mut self,
+        spec: SpecId,
+        status: Return,
+        gas: &Gas,
+        output: Vec<u8>,
+        address: Option<Address>,
+    ) {
         let success = matches!(status, return_ok!());
         let trace = &mut self.traces.arena
             [self.trace_stack.pop().expect("more traces were filled than started")]
         .trace;
         trace.status = status;
         trace.success = success;
-        trace.gas_cost = cost;
+        trace.gas_cost = gas_used(spec, gas.spend(), gas.refunded() as u64);
+        trace.gas_spent = gas.spend();
+        trace.gas_refunded = gas.refunded();
+        trace.gas_refund_cap = refund_cap(spec, gas.spend());
         trace.output = RawOrDecodedReturnData::Raw(output.into());
 
         if let Some(address) = address {
@@ -80,6 +100,25 @@ impl Tracer {
         }
     }
 
+    /// Records the destination of the jump of the previous step, if it was a `JUMP`
+    fn record_jump(&mut self, interp: &Interpreter) {
+        let trace_idx = match self.trace_stack.last() {
+            Some(idx) => *idx,
+            None => return,
+        };
+        let pc = interp.program_counter();
+
+        if self.jumping {
+            let node = &mut self.traces.arena[trace_idx];
+            node.trace.jumps.push(JumpStep {
+                pc,
+                position: node.ordering.len(),
+                stack: interp.stack.data().iter().rev().take(MAX_JUMP_STACK).copied().collect(),
+            });
+        }
+        self.jumping = interp.contract.bytecode.bytecode()[pc] == opcode::JUMP;
+    }
+
     fn start_step<DB: Database>(&mut self, interp: &mut Interpreter, data: &mut EVMData<'_, DB>) {
         let trace_idx =
             *self.trace_stack.last().expect("can't start step without starting a trace first");
@@ -156,6 +195,10 @@ where
         data: &mut EVMData<'_, DB>,
         _is_static: bool,
     ) -> Return {
+        if self.record_jumps {
+            self.record_jump(interp);
+        }
+
         if !self.record_steps {
             return Return::Continue
         }
@@ -222,12 +265,8 @@ where
         retdata: Bytes,
         _is_static: bool,
     ) -> (Return, Gas, Bytes) {
-        self.fill_trace(
-            status,
-            gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
-            retdata.to_vec(),
-            None,
-        );
+        self.jumping = false;
+        self.fill_trace(data.env.cfg.spec_id, status, &gas, retdata.to_vec(), None);
 
         (status, gas, retdata)
     }
@@ -271,12 +310,8 @@ where
                 .map_or(vec![], |code| code.bytes()[..code.len()].to_vec()),
             None => vec![],
         };
-        self.fill_trace(
-            status,
-            gas_used(data.env.cfg.spec_id, gas.spend(), gas.refunded() as u64),
-            code,
-            address,
-        );
+        self.jumping = false;
+        self.fill_trace(data.env.cfg.spec_id, status, &gas, code, address);
 
         (status, address, gas, retdata)
     }
@@ -31,6 +31,12 @@ pub fn get_create_address(call: &CreateInputs, nonce: u64) -> Address {
 
 /// Get the gas used, accounting for refunds
 pub fn gas_used(spec: SpecId, spent: u64, refunded: u64) -> u64 {
+    spent - refunded.min(refund_cap(spec, spent))
+}
+
+/// Get the maximum refund for the spent gas, which is a fifth of it since
+/// [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529) and half of it before
+pub fn refund_cap(spec: SpecId, spent: u64) -> u64 {
     let refund_quotient = if SpecId::enabled(spec, SpecId::LONDON) { 5 } else { 2 };
-    spent - (refunded).min(spent / refund_quotient)
+    spent / refund_quotient
 }
@@ -1,5 +1,6 @@
 use self::inspector::{
-    cheatcodes::util::BroadcastableTransactions, Cheatcodes, InspectorData, InspectorStackConfig,
+    cheatcodes::util::BroadcastableTransactions, CheatcodeFailure, Cheatcodes, InspectorData,
+    InspectorStackConfig, IntrinsicGas,
 };
 use crate::{debug::DebugArena, decode, trace::CallTraceArena, CALLER};
 pub use abi::{
@@ -22,7 +23,7 @@ use revm::{
 };
 /// Reexport commonly used revm types
 pub use revm::{db::DatabaseRef, Env, SpecId};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, time::Duration};
 use tracing::trace;
 
 /// ABIs used internally in the executor
@@ -32,6 +33,8 @@ pub mod backend;
 pub use backend::Backend;
 /// Executor builder
 pub mod builder;
+/// EIP-7702 delegations
+pub mod delegation;
 /// Forking provider
 pub mod fork;
 /// Executor inspectors
@@ -52,6 +55,9 @@ use crate::{
 };
 pub use builder::ExecutorBuilder;
 
+/// Runtime code of the canonical CREATE2 deployer, see [DEFAULT_CREATE2_DEPLOYER]
+const CREATE2_DEPLOYER_RUNTIME_CODE: &str = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3";
+
 /// A mapping of addresses to their changed state.
 pub type StateChangeset = HashMap<Address, Account>;
 
@@ -130,28 +136,50 @@ impl Executor {
         &mut self.inspector_config
     }
 
-    /// Creates the default CREATE2 Contract Deployer for local tests and scripts.
+    /// Creates the CREATE2 Contract Deployer for local tests and scripts.
+    ///
+    /// This is the default deployer, unless a different one is configured for the chain of the
+    /// executor in the cheatcodes config, in which case the code of the default deployer is placed
+    /// at that address.
     pub fn deploy_create2_deployer(&mut self) -> eyre::Result<()> {
-        trace!("deploying local create2 deployer");
-        let create2_deployer_account = self
-            .backend_mut()
-            .basic(DEFAULT_CREATE2_DEPLOYER)?
-            .ok_or(DatabaseError::MissingAccount(DEFAULT_CREATE2_DEPLOYER))?;
+        let deployer = self
+            .inspector_config
+            .cheatcodes
+            .as_ref()
+            .map(|cheats| cheats.config.create2_deployer(self.env.cfg.chain_id.as_u64()))
+            .unwrap_or(DEFAULT_CREATE2_DEPLOYER);
+        trace!(?deployer, "deploying local create2 deployer");
+        let create2_deployer_account =
+            self.backend_mut().basic(deployer)?.ok_or(DatabaseError::MissingAccount(deployer))?;
 
         if create2_deployer_account.code.is_none() ||
             create2_deployer_account.code.as_ref().unwrap().is_empty()
         {
+            if deployer != DEFAULT_CREATE2_DEPLOYER {
+                let code = hex::decode(CREATE2_DEPLOYER_RUNTIME_CODE).expect("valid hex");
+                self.backend_mut().insert_account_info(
+                    deployer,
+                    revm::AccountInfo {
+                        code: Some(Bytecode::new_raw(code.into()).to_checked()),
+                        ..create2_deployer_account
+                    },
+                );
+                trace!(create2=?deployer, "placed local create2 deployer");
+                return Ok(())
+            }
+
             let creator = "0x3fAB184622Dc19b6109349B94811493BF2a45362".parse().unwrap();
 
             // Probably 0, but just in case.
             let initial_balance = self.get_balance(creator)?;
 
             self.set_balance(creator, U256::MAX)?;
+            let init_code = format!("604580600e600039806000f350fe{CREATE2_DEPLOYER_RUNTIME_CODE}");
             let res = self.deploy(
                 creator,
-                hex::decode("604580600e600039806000f350fe7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3").expect("valid hex").into(),
+                hex::decode(init_code).expect("valid hex").into(),
                 U256::zero(),
-                None
+                None,
             )?;
             trace!(create2=?res.address, "deployed local create2 deployer");
 
@@ -199,6 +227,30 @@ impl Executor {
         self
     }
 
+    /// Sets the time budget of a test, see [Executor::start_timeout()]
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.inspector_config.timeout = timeout;
+        self.inspector_config.started_timeout = None;
+        self
+    }
+
+    /// Starts the time budget of a test now, so it covers all following executions together.
+    ///
+    /// Until then every execution is aborted once it exceeds the budget on its own.
+    pub fn start_timeout(&mut self) -> &mut Self {
+        self.inspector_config.start_timeout();
+        self
+    }
+
+    /// Advances the block timestamp by `secs` and the block number by one before every top-level
+    /// call of a test, if cheatcodes are enabled
+    pub fn set_auto_advance_time(&mut self, secs: Option<u64>) -> &mut Self {
+        if let Some(cheatcodes) = self.inspector_config.cheatcodes.as_mut() {
+            cheatcodes.auto_advance_time = secs;
+        }
+        self
+    }
+
     pub fn set_gas_limit(&mut self, gas_limit: U256) -> &mut Self {
         self.gas_limit = gas_limit;
         self
@@ -247,6 +299,8 @@ impl Executor {
                         state_changeset: None,
                         transactions: None,
                         script_wallets: res.script_wallets,
+                        cheatcode_failure: None,
+                        reverted_calls: res.reverted_calls,
                     })))
                 }
             }
@@ -409,6 +463,7 @@ impl Executor {
             debug,
             script_wallets,
             env,
+            reverted_calls,
             ..
         } = result;
 
@@ -434,7 +489,9 @@ impl Executor {
                         labels,
                         state_changeset: None,
                         transactions: None,
-                        script_wallets
+                        script_wallets,
+                        cheatcode_failure: None,
+                        reverted_calls,
                     })));
                 }
             }
@@ -454,6 +511,8 @@ impl Executor {
                     state_changeset: None,
                     transactions: None,
                     script_wallets,
+                    cheatcode_failure: None,
+                    reverted_calls,
                 })))
             }
         };
@@ -602,6 +661,10 @@ pub struct ExecutionErr {
     pub transactions: Option<BroadcastableTransactions>,
     pub state_changeset: Option<StateChangeset>,
     pub script_wallets: Vec<LocalWallet>,
+    /// The failed cheatcode that caused the revert, if any
+    pub cheatcode_failure: Option<CheatcodeFailure>,
+    /// The reverted calls that caused the revert, innermost first, with their revert data
+    pub reverted_calls: Vec<(Address, Bytes)>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -711,6 +774,10 @@ pub struct RawCallResult {
     pub env: Env,
     /// The cheatcode states after execution
     pub cheatcodes: Option<Cheatcodes>,
+    /// The last cheatcode that failed during the call
+    pub cheatcode_failure: Option<CheatcodeFailure>,
+    /// The reverted calls that caused the revert of the call, innermost first
+    pub reverted_calls: Vec<(Address, Bytes)>,
     /// The raw output of the execution
     pub out: TransactOut,
     /// The chisel state
@@ -736,18 +803,14 @@ impl Default for RawCallResult {
             script_wallets: Vec::new(),
             env: Default::default(),
             cheatcodes: Default::default(),
+            cheatcode_failure: None,
+            reverted_calls: Vec::new(),
             out: TransactOut::None,
             chisel_state: None,
         }
     }
 }
 
-/// Calculates the initial gas stipend for a transaction
-fn calc_stipend(calldata: &[u8], spec: SpecId) -> u64 {
-    let non_zero_data_cost = if SpecId::enabled(spec, SpecId::ISTANBUL) { 16 } else { 68 };
-    calldata.iter().fold(21000, |sum, byte| sum + if *byte == 0 { 4 } else { non_zero_data_cost })
-}
-
 /// Converts the data aggregated in the `inspector` and `call` to a `RawCallResult`
 fn convert_executed_result(
     env: Env,
@@ -757,7 +820,10 @@ fn convert_executed_result(
     let (exec_result, state_changeset) = result;
     let ExecutionResult { exit_reason, gas_refunded, gas_used, out, .. } = exec_result;
 
-    let stipend = calc_stipend(&env.tx.data, env.cfg.spec_id);
+    let mut stipend = IntrinsicGas::default().stipend(&env.tx.data, env.cfg.spec_id);
+    if let Some(ref gas_overrides) = inspector.gas_overrides {
+        stipend = gas_overrides.intrinsic.stipend(&env.tx.data, env.cfg.spec_id);
+    }
 
     let result = match out {
         TransactOut::Call(ref data) => data.to_owned(),
@@ -770,11 +836,15 @@ fn convert_executed_result(
         traces,
         coverage,
         debug,
-        cheatcodes,
+        mut cheatcodes,
         script_wallets,
         chisel_state,
+        reverted_calls,
     } = inspector.collect_inspector_states();
 
+    // the cheatcodes are kept for the next call, unlike the failure
+    let cheatcode_failure = cheatcodes.as_mut().and_then(|cheats| cheats.cheatcode_failure.take());
+
     let transactions = match cheatcodes.as_ref() {
         Some(cheats) if !cheats.broadcastable_transactions.is_empty() => {
             Some(cheats.broadcastable_transactions.clone())
@@ -799,6 +869,8 @@ fn convert_executed_result(
         script_wallets,
         env,
         cheatcodes,
+        cheatcode_failure,
+        reverted_calls,
         out,
         chisel_state,
     })
@@ -825,6 +897,8 @@ fn convert_call_result<D: Detokenize>(
         state_changeset,
         script_wallets,
         env,
+        cheatcode_failure,
+        reverted_calls,
         ..
     } = call_result;
 
@@ -851,6 +925,8 @@ fn convert_call_result<D: Detokenize>(
         _ => {
             let reason = decode::decode_revert(result.as_ref(), abi, Some(status))
                 .unwrap_or_else(|_| format!("{status:?}"));
+            // only a failure whose revert data was bubbled up caused the revert of the call
+            let cheatcode_failure = cheatcode_failure.filter(|failure| failure.retdata == result);
             Err(EvmError::Execution(Box::new(ExecutionErr {
                 reverted,
                 reason,
@@ -864,6 +940,8 @@ fn convert_call_result<D: Detokenize>(
                 transactions,
                 state_changeset,
                 script_wallets,
+                cheatcode_failure,
+                reverted_calls,
             })))
         }
     }
@@ -6,11 +6,15 @@ use ethers::{
 };
 use eyre::WrapErr;
 use foundry_common::{self, ProviderBuilder, RpcUrl, ALCHEMY_FREE_TIER_CUPS};
-use foundry_config::Config;
+use foundry_config::{Config, GasOverride};
 use revm::{BlockEnv, CfgEnv, SpecId, TxEnv};
 use serde::{Deserialize, Deserializer, Serialize};
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 
-use super::fork::environment;
+use super::{
+    fork::{environment, JsonBlockCacheDB},
+    inspector::{plugin, GasOverrides, InspectorPluginFactory},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct EvmOpts {
@@ -36,6 +40,19 @@ pub struct EvmOpts {
     /// Disables storage caching entirely.
     pub no_storage_caching: bool,
 
+    /// Forks only serve data from the RPC cache and never send requests to the endpoint.
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Forks fetch the info of an account along with the first storage slot requested for it.
+    #[serde(default)]
+    pub fork_prefetch_accounts: bool,
+
+    /// The URL of the RPC cache that is shared between machines, see
+    /// [RemoteBlockCache](crate::executor::fork::RemoteBlockCache)
+    #[serde(default)]
+    pub rpc_cache_url: Option<String>,
+
     /// the initial balance of each deployed test contract
     pub initial_balance: U256,
 
@@ -45,14 +62,43 @@ pub struct EvmOpts {
     /// enables the FFI cheatcode
     pub ffi: bool,
 
+    /// executes every call a test makes as a separate transaction
+    #[serde(default)]
+    pub isolate: bool,
+
+    /// records the jumps of traced calls to reconstruct internal function calls
+    #[serde(default)]
+    pub decode_internal: bool,
+
     /// Verbosity mode of EVM output as number of occurrences
     pub verbosity: u8,
 
     /// The memory limit of the EVM in bytes.
     pub memory_limit: u64,
+
+    /// Custom gas costs of opcodes and intrinsic costs, see [GasOverrides]
+    #[serde(default)]
+    pub gas_overrides: BTreeMap<String, GasOverride>,
+
+    /// The names of the registered inspector plugins to use, see [plugin]
+    #[serde(default)]
+    pub inspector_plugins: Vec<String>,
 }
 
 impl EvmOpts {
+    /// Resolves the fork url if it is an alias of the `[rpc_endpoints]` of the config, and uses
+    /// the block the endpoint is pinned to if no fork block was set explicitly
+    pub fn resolve_fork(&mut self, config: &Config) -> eyre::Result<()> {
+        if let Some(fork_url) = config.get_rpc_url() {
+            tracing::trace!(target: "forge::config", ?fork_url, "Update EvmOpts fork url");
+            self.fork_url = Some(fork_url?.into_owned());
+        }
+        if self.fork_block_number.is_none() {
+            self.fork_block_number = config.get_fork_block_number();
+        }
+        Ok(())
+    }
+
     /// Configures a new `revm::Env`
     ///
     /// If a `fork_url` is set, it gets configured with settings fetched from the endpoint (chain
@@ -79,8 +125,14 @@ impl EvmOpts {
     }
 
     /// Returns the `revm::Env` configured with settings retrieved from the endpoints
+    ///
+    /// In `offline` mode, the settings are read from the RPC cache instead, see
+    /// [Self::cached_fork_evm_env]
     pub async fn fork_evm_env(&self, fork_url: impl AsRef<str>) -> eyre::Result<revm::Env> {
         let fork_url = fork_url.as_ref();
+        if self.offline {
+            return self.cached_fork_evm_env(fork_url)
+        }
         let provider = ProviderBuilder::new(fork_url)
             .compute_units_per_second(self.get_compute_units_per_second())
             .build()?;
@@ -98,6 +150,48 @@ impl EvmOpts {
         })
     }
 
+    /// Returns the `revm::Env` of the fork as it is stored in the RPC cache, without sending any
+    /// request to the endpoint.
+    ///
+    /// This requires a pinned `fork_block_number`. If no `chain_id` is configured, the RPC cache
+    /// directories of all chains are searched for the block.
+    pub fn cached_fork_evm_env(&self, fork_url: &str) -> eyre::Result<revm::Env> {
+        let block = self.fork_block_number.ok_or_else(|| {
+            eyre::eyre!(
+                "Forking {fork_url} in offline mode requires a pinned block number, set `--fork-block-number`"
+            )
+        })?;
+        let path = match self.env.chain_id {
+            Some(chain_id) => Config::foundry_block_cache_dir(chain_id, block),
+            None => find_cached_block(block)?,
+        }
+        .ok_or_else(|| eyre::eyre!("Failed to find the RPC cache directory"))?;
+        let cache = JsonBlockCacheDB::load(&path).wrap_err_with(|| {
+            format!(
+                "Block {block} of {fork_url} is not in the RPC cache at {}, it needs to be forked online once",
+                path.display()
+            )
+        })?;
+
+        let meta = cache.meta().read();
+        let chain_id = self.env.chain_id.unwrap_or_else(|| meta.cfg_env.chain_id.as_u64());
+        Ok(revm::Env {
+            cfg: CfgEnv {
+                chain_id: chain_id.into(),
+                memory_limit: self.memory_limit,
+                ..meta.cfg_env.clone()
+            },
+            block: meta.block_env.clone(),
+            tx: TxEnv {
+                caller: self.sender,
+                gas_price: self.env.gas_price.map(U256::from).unwrap_or(meta.block_env.basefee),
+                chain_id: Some(chain_id),
+                gas_limit: meta.block_env.gas_limit.as_u64(),
+                ..Default::default()
+            },
+        })
+    }
+
     /// Returns the `revm::Env` configured with only local settings
     pub fn local_evm_env(&self) -> revm::Env {
         revm::Env {
@@ -154,6 +248,20 @@ impl EvmOpts {
         self.env.block_gas_limit.unwrap_or(self.env.gas_limit).into()
     }
 
+    /// Returns the custom gas schedule configured in `gas_overrides`
+    ///
+    /// Returns an error if it contains unknown opcodes
+    pub fn gas_overrides(&self) -> eyre::Result<GasOverrides> {
+        GasOverrides::new(&self.gas_overrides).wrap_err("invalid `gas_overrides`")
+    }
+
+    /// Returns the inspector plugins configured in `inspector_plugins`
+    ///
+    /// Returns an error if one of them is not registered
+    pub fn inspector_plugins(&self) -> eyre::Result<Vec<Arc<dyn InspectorPluginFactory>>> {
+        plugin::inspector_plugins(&self.inspector_plugins).wrap_err("invalid `inspector_plugins`")
+    }
+
     /// Returns the configured chain id, which will be
     ///   - the value of `chain_id` if set
     ///   - mainnet if `fork_url` contains "mainnet"
@@ -282,3 +390,30 @@ where
         _ => Ok(None),
     }
 }
+
+/// Returns the path of the cached `block`, if exactly one chain has it cached
+fn find_cached_block(block: u64) -> eyre::Result<Option<PathBuf>> {
+    let rpc_cache = match Config::foundry_rpc_cache_dir() {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+    let mut cached = std::fs::read_dir(&rpc_cache)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|chain| chain.path().join(block.to_string()))
+                .filter(|path| path.is_file())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    match cached.len() {
+        0 => eyre::bail!(
+            "Block {block} is not in the RPC cache at {}, it needs to be forked online once",
+            rpc_cache.display()
+        ),
+        1 => Ok(cached.pop()),
+        _ => eyre::bail!(
+            "Block {block} is cached for multiple chains, set `--chain-id` to select one"
+        ),
+    }
+}
@@ -21,6 +21,8 @@ pub enum FuzzError {
     AssumeReject,
     #[error("The `vm.assume` cheatcode rejected too many inputs ({0} allowed)")]
     TooManyRejects(u32),
+    #[error("The fuzz campaign was cancelled")]
+    Cancelled,
 }
 
 impl From<FuzzError> for Reason {
@@ -0,0 +1,255 @@
+//! Persisted progress of invariant campaigns, see `forge test --resume-invariants`
+
+use super::BasicTxDetails;
+use ethers::{types::H256, utils::keccak256};
+use eyre::WrapErr;
+use proptest::test_runner::{RngAlgorithm, TestRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How often the checkpoint of a running campaign is written
+pub const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The progress of an invariant campaign of a test contract
+///
+/// The RNG of a campaign can't be persisted, instead the RNG is seeded from the `seed` and the
+/// number of completed `runs` whenever a campaign is started or resumed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvariantCheckpoint {
+    /// The seed of the campaign
+    pub seed: H256,
+    /// The initial seeds of all campaigns whose progress is included, identifies the campaigns
+    /// that were merged
+    #[serde(default)]
+    pub campaigns: BTreeSet<H256>,
+    /// The hash of the code of the test contract and all targeted contracts, a campaign of
+    /// different code is not resumed
+    #[serde(default)]
+    pub bytecode_hash: H256,
+    /// The number of completed runs
+    pub runs: u32,
+    /// The number of calls of all completed runs
+    pub calls: u64,
+    /// The number of reverted calls
+    pub reverts: usize,
+    /// The values of the fuzz dictionary, which contains everything the campaign learned about
+    /// the state of the contracts
+    pub dictionary: BTreeSet<H256>,
+    /// The call sequences that broke invariants, by invariant function name
+    pub broken: BTreeMap<String, Vec<BasicTxDetails>>,
+}
+
+impl InvariantCheckpoint {
+    pub fn new(seed: H256, bytecode_hash: H256) -> Self {
+        Self { seed, campaigns: BTreeSet::from([seed]), bytecode_hash, ..Default::default() }
+    }
+
+    /// Reads the checkpoint from the file, if it exists
+    pub fn read(path: &Path) -> eyre::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None)
+        }
+        let content = std::fs::read_to_string(path)?;
+        let checkpoint = serde_json::from_str(&content)
+            .wrap_err_with(|| format!("Failed to read invariant checkpoint {}", path.display()))?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Writes the checkpoint to the file
+    ///
+    /// The checkpoint is written to a temporary file first, so an interrupted write does not
+    /// corrupt an existing checkpoint.
+    pub fn write(&self, path: &Path) -> eyre::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&tmp, path)
+            .wrap_err_with(|| format!("Failed to write invariant checkpoint {}", path.display()))
+    }
+
+    /// Returns the RNG for the remaining runs of the campaign
+    pub fn rng(&self) -> TestRng {
+        let mut seed = self.seed.as_bytes().to_vec();
+        seed.extend_from_slice(&self.runs.to_be_bytes());
+        TestRng::from_seed(RngAlgorithm::ChaCha, &keccak256(seed))
+    }
+
+    /// Merges the progress of a campaign of the same test contract that ran elsewhere and returns
+    /// whether anything changed
+    ///
+    /// The seed changes, so the merged campaign does not repeat the runs of either campaign.
+    /// Campaigns of different code are not merged, and neither are campaigns that were already
+    /// merged, unless the same campaign progressed further since.
+    pub fn merge(&mut self, other: InvariantCheckpoint) -> bool {
+        if other.bytecode_hash != self.bytecode_hash {
+            return false
+        }
+        if other.campaigns.is_subset(&self.campaigns) {
+            if other.campaigns == self.campaigns && other.runs > self.runs {
+                *self = other;
+                return true
+            }
+            return false
+        }
+
+        let mut seed = self.seed.as_bytes().to_vec();
+        seed.extend_from_slice(other.seed.as_bytes());
+        self.seed = keccak256(seed).into();
+        self.runs = self.runs.saturating_add(other.runs);
+        self.calls = self.calls.saturating_add(other.calls);
+        self.reverts = self.reverts.saturating_add(other.reverts);
+        self.dictionary.extend(other.dictionary);
+        for (invariant, sequence) in other.broken {
+            self.broken.entry(invariant).or_insert(sequence);
+        }
+        self.campaigns.extend(other.campaigns);
+        true
+    }
+}
+
+/// The directory the checkpoints of all invariant campaigns are persisted in
+#[derive(Debug, Clone)]
+pub struct InvariantCheckpoints {
+    pub dir: PathBuf,
+    /// Whether campaigns are resumed from existing checkpoints
+    pub resume: bool,
+}
+
+impl InvariantCheckpoints {
+    /// Returns the checkpoint file of the campaign of the test contract
+    pub fn file(&self, contract_id: &str) -> CheckpointFile {
+        let name = contract_id.replace(['/', '\\', ':'], "-");
+        CheckpointFile { path: self.dir.join(format!("{name}.json")), resume: self.resume }
+    }
+
+    /// Merges all checkpoints of another directory into the checkpoints of this directory and
+    /// returns the number of merged checkpoints
+    ///
+    /// Merging the same directory again does not change anything, see
+    /// [InvariantCheckpoint::merge].
+    pub fn merge_from(&self, dir: &Path) -> eyre::Result<usize> {
+        let mut merged = 0;
+        let entries = std::fs::read_dir(dir).wrap_err_with(|| {
+            format!("Failed to read invariant checkpoints in {}", dir.display())
+        })?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue
+            }
+            let other = match InvariantCheckpoint::read(&path)? {
+                Some(other) => other,
+                None => continue,
+            };
+            let target = self.dir.join(path.file_name().expect("is file"));
+            let checkpoint = match InvariantCheckpoint::read(&target)? {
+                Some(mut checkpoint) => {
+                    if !checkpoint.merge(other) {
+                        continue
+                    }
+                    checkpoint
+                }
+                None => other,
+            };
+            checkpoint.write(&target)?;
+            merged += 1;
+        }
+        Ok(merged)
+    }
+}
+
+/// The file the checkpoint of a single campaign is persisted in
+#[derive(Debug, Clone)]
+pub struct CheckpointFile {
+    pub path: PathBuf,
+    /// Whether the campaign is resumed from the checkpoint in the file
+    pub resume: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, Bytes};
+
+    #[test]
+    fn can_merge_checkpoints() {
+        let sequence: Vec<BasicTxDetails> =
+            vec![(Address::random(), (Address::random(), Bytes::from(vec![1, 2, 3])))];
+        let bytecode_hash = H256::from_low_u64_be(42);
+        let mut a = InvariantCheckpoint {
+            runs: 10,
+            calls: 100,
+            reverts: 1,
+            dictionary: BTreeSet::from([H256::from_low_u64_be(1)]),
+            ..InvariantCheckpoint::new(H256::from_low_u64_be(1), bytecode_hash)
+        };
+        let b = InvariantCheckpoint {
+            runs: 5,
+            calls: 50,
+            reverts: 2,
+            dictionary: BTreeSet::from([H256::from_low_u64_be(2)]),
+            broken: BTreeMap::from([("invariant_a".to_string(), sequence.clone())]),
+            ..InvariantCheckpoint::new(H256::from_low_u64_be(2), bytecode_hash)
+        };
+        assert!(a.merge(b.clone()));
+
+        assert_eq!(a.runs, 15);
+        assert_eq!(a.calls, 150);
+        assert_eq!(a.reverts, 3);
+        assert_eq!(a.dictionary.len(), 2);
+        assert_eq!(a.broken["invariant_a"], sequence);
+        assert_ne!(a.seed, H256::from_low_u64_be(1));
+        assert_eq!(a.campaigns.len(), 2);
+
+        // merging the same campaign again changes nothing
+        let merged = a.clone();
+        assert!(!a.merge(b));
+        assert!(!a.merge(merged.clone()));
+        assert_eq!(a, merged);
+
+        // campaigns of different code are not merged
+        let other = InvariantCheckpoint {
+            runs: 5,
+            ..InvariantCheckpoint::new(H256::from_low_u64_be(3), H256::zero())
+        };
+        assert!(!a.merge(other));
+        assert_eq!(a, merged);
+    }
+
+    #[test]
+    fn can_persist_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = InvariantCheckpoints { dir: dir.path().join("a"), resume: true };
+        let file = checkpoints.file("test/Counter.t.sol:CounterTest");
+        assert!(file.path.ends_with("test-Counter.t.sol-CounterTest.json"));
+        assert!(InvariantCheckpoint::read(&file.path).unwrap().is_none());
+
+        let checkpoint = InvariantCheckpoint {
+            runs: 3,
+            ..InvariantCheckpoint::new(H256::zero(), H256::from_low_u64_be(42))
+        };
+        checkpoint.write(&file.path).unwrap();
+        assert_eq!(InvariantCheckpoint::read(&file.path).unwrap(), Some(checkpoint.clone()));
+
+        let other = InvariantCheckpoints { dir: dir.path().join("b"), resume: true };
+        assert_eq!(other.merge_from(&checkpoints.dir).unwrap(), 1);
+        assert_eq!(other.merge_from(&checkpoints.dir).unwrap(), 0);
+        let merged =
+            InvariantCheckpoint::read(&other.file("test/Counter.t.sol:CounterTest").path).unwrap();
+        assert_eq!(merged.unwrap().runs, 3);
+
+        // the campaign progressed since it was merged
+        InvariantCheckpoint { runs: 5, ..checkpoint }.write(&file.path).unwrap();
+        assert_eq!(other.merge_from(&checkpoints.dir).unwrap(), 1);
+        let merged =
+            InvariantCheckpoint::read(&other.file("test/Counter.t.sol:CounterTest").path).unwrap();
+        assert_eq!(merged.unwrap().runs, 5);
+    }
+}
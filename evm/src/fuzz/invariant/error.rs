@@ -1,4 +1,4 @@
-use super::{BasicTxDetails, InvariantContract};
+use super::{encode_invariant_call, BasicTxDetails, InvariantContract};
 use crate::{
     decode::decode_revert,
     executor::{Executor, RawCallResult},
@@ -22,8 +22,8 @@ pub struct InvariantFuzzError {
     pub revert_reason: String,
     /// Address of the invariant asserter.
     pub addr: Address,
-    /// Function data for invariant check.
-    pub func: Option<ethers::prelude::Bytes>,
+    /// The broken invariant function.
+    pub func: Option<Function>,
     /// Inner fuzzing Sequence coming from overriding calls.
     pub inner_sequence: Vec<Option<BasicTxDetails>>,
 }
@@ -40,7 +40,7 @@ impl InvariantFuzzError {
         let origin: String;
 
         if let Some(f) = error_func {
-            func = Some(f.short_signature().into());
+            func = Some(f.clone());
             origin = f.name.clone();
         } else {
             origin = "Revert".to_string();
@@ -99,12 +99,18 @@ impl InvariantFuzzError {
 
         set_up_inner_replay(&mut executor, &self.inner_sequence);
 
+        // Logs emitted by the sequence, asserted by event invariants.
+        let mut emitted = vec![];
+
         // Replay each call from the sequence until we break the invariant.
         for (sender, (addr, bytes)) in calls.iter() {
             let call_result = executor
                 .call_raw_committing(*sender, *addr, bytes.0.clone(), 0.into())
                 .expect("bad call to evm");
 
+            if !call_result.reverted {
+                emitted.extend(call_result.logs.iter().cloned());
+            }
             logs.extend(call_result.logs);
             traces.push((TraceKind::Execution, call_result.traces.clone().unwrap()));
 
@@ -127,8 +133,9 @@ impl InvariantFuzzError {
 
             // Checks the invariant.
             if let Some(func) = &self.func {
+                let calldata = encode_invariant_call(func, &emitted);
                 let error_call_result = executor
-                    .call_raw(CALLER, self.addr, func.0.clone(), 0.into())
+                    .call_raw(CALLER, self.addr, calldata.0, 0.into())
                     .expect("bad call to evm");
 
                 if error_call_result.reverted {
@@ -159,19 +166,24 @@ impl InvariantFuzzError {
         });
 
         let mut new_sequence = vec![];
+        let mut logs = vec![];
         for details in calls {
             new_sequence.push(details);
 
             let (sender, (addr, bytes)) = details;
 
-            executor
+            let call_result = executor
                 .call_raw_committing(*sender, *addr, bytes.0.clone(), 0.into())
                 .expect("bad call to evm");
+            if !call_result.reverted {
+                logs.extend(call_result.logs);
+            }
 
             // Checks the invariant. If we exit before the last call, all the better.
             if let Some(func) = &self.func {
+                let calldata = encode_invariant_call(func, &logs);
                 let error_call_result = executor
-                    .call_raw(CALLER, self.addr, func.0.clone(), 0.into())
+                    .call_raw(CALLER, self.addr, calldata.0, 0.into())
                     .expect("bad call to evm");
 
                 if error_call_result.reverted {
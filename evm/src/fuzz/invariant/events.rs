@@ -0,0 +1,107 @@
+//! Support for invariants over the events emitted during a run
+//!
+//! An invariant function that takes a single `(address,bytes32[],bytes)[]` argument is an event
+//! invariant. Before it is asserted, it receives every log that was emitted by the calls of the
+//! current run so far, in order, as `(emitter, topics, data)`:
+//!
+//! ```solidity
+//! struct EmittedLog {
+//!     address emitter;
+//!     bytes32[] topics;
+//!     bytes data;
+//! }
+//!
+//! function invariant_transfersConserveSupply(EmittedLog[] calldata logs) public { ... }
+//! ```
+
+use ethers::{
+    abi::{Function, ParamType, Token},
+    types::{Bytes, Log},
+};
+
+/// Returns the ABI type of the logs an event invariant receives: `(address,bytes32[],bytes)[]`
+fn logs_param_type() -> ParamType {
+    ParamType::Array(Box::new(ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+        ParamType::Bytes,
+    ])))
+}
+
+/// Returns whether the invariant function is asserted against the emitted logs
+pub fn is_event_invariant(func: &Function) -> bool {
+    matches!(func.inputs.as_slice(), [param] if param.kind == logs_param_type())
+}
+
+/// Returns whether the function can be asserted as an invariant, i.e. it takes no inputs or is
+/// an event invariant
+pub fn is_valid_invariant(func: &Function) -> bool {
+    func.inputs.is_empty() || is_event_invariant(func)
+}
+
+/// Encodes the call of the invariant function, event invariants receive the `logs`
+pub fn encode_invariant_call(func: &Function, logs: &[Log]) -> Bytes {
+    let args = if is_event_invariant(func) {
+        let logs = logs
+            .iter()
+            .map(|log| {
+                Token::Tuple(vec![
+                    Token::Address(log.address),
+                    Token::Array(
+                        log.topics
+                            .iter()
+                            .map(|topic| Token::FixedBytes(topic.as_bytes().to_vec()))
+                            .collect(),
+                    ),
+                    Token::Bytes(log.data.to_vec()),
+                ])
+            })
+            .collect();
+        vec![Token::Array(logs)]
+    } else {
+        vec![]
+    };
+    func.encode_input(&args).expect("invariant inputs are validated").into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::{decode, HumanReadableParser},
+        types::{Address, H256},
+    };
+
+    #[test]
+    fn can_encode_event_invariants() {
+        let plain = HumanReadableParser::parse_function("function invariant_a()").unwrap();
+        let event = HumanReadableParser::parse_function(
+            "function invariant_b((address,bytes32[],bytes)[] logs)",
+        )
+        .unwrap();
+        let invalid = HumanReadableParser::parse_function("function invariant_c(uint256)").unwrap();
+
+        assert!(!is_event_invariant(&plain) && is_valid_invariant(&plain));
+        assert!(is_event_invariant(&event) && is_valid_invariant(&event));
+        assert!(!is_valid_invariant(&invalid));
+
+        let log = Log {
+            address: Address::repeat_byte(1),
+            topics: vec![H256::repeat_byte(2)],
+            data: vec![3u8].into(),
+            ..Default::default()
+        };
+        assert_eq!(encode_invariant_call(&plain, &[log.clone()]).as_ref(), plain.short_signature());
+
+        let calldata = encode_invariant_call(&event, &[log]);
+        let tokens = decode(&[logs_param_type()], &calldata[4..]).unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::Array(vec![Token::Tuple(vec![
+                Token::Address(Address::repeat_byte(1)),
+                Token::Array(vec![Token::FixedBytes(vec![2; 32])]),
+                Token::Bytes(vec![3]),
+            ])])]
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use super::{
     assert_invariants,
+    checkpoint::{CheckpointFile, InvariantCheckpoint, CHECKPOINT_INTERVAL},
     filters::{ArtifactFilters, SenderFilters},
+    is_valid_invariant,
+    stats::{handler_id, initial_handler_stats},
     BasicTxDetails, FuzzRunIdentifiedContracts, InvariantContract, InvariantFuzzError,
-    InvariantFuzzTestResult, RandomCallGenerator, TargetedContracts,
+    InvariantFuzzTestResult, InvariantHandlerStats, RandomCallGenerator, TargetedContracts,
 };
 use crate::{
     executor::{
@@ -20,7 +23,8 @@ use crate::{
 };
 use ethers::{
     abi::{Abi, Address, Detokenize, FixedBytes, Function, Tokenizable, TokenizableItem},
-    prelude::U256,
+    prelude::{Bytes, Log, H256, U256},
+    utils::keccak256,
 };
 use eyre::ContextCompat;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
@@ -28,12 +32,21 @@ use foundry_config::InvariantConfig;
 use hashbrown::HashMap;
 use parking_lot::{Mutex, RwLock};
 use proptest::{
+    prelude::RngCore,
     strategy::{BoxedStrategy, Strategy, ValueTree},
-    test_runner::{TestCaseError, TestRunner},
+    test_runner::{TestCaseError, TestError, TestRunner},
 };
-use revm::DatabaseCommit;
-use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
-use tracing::warn;
+use revm::{db::DatabaseRef, DatabaseCommit};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tracing::{trace, warn};
 
 /// Alias for (Dictionary for fuzzing, initial contracts to fuzz and an InvariantStrategy).
 type InvariantPreparation =
@@ -57,6 +70,10 @@ pub struct InvariantExecutor<'a> {
     project_contracts: &'a ContractsByArtifact,
     /// Filters contracts to be fuzzed through their artifact identifiers.
     artifact_filters: ArtifactFilters,
+    /// The file the progress of the campaign is persisted in
+    checkpoint: Option<CheckpointFile>,
+    /// Set to cancel the campaign before its next call
+    cancelled: Option<Arc<AtomicBool>>,
 }
 
 impl<'a> InvariantExecutor<'a> {
@@ -75,24 +92,54 @@ impl<'a> InvariantExecutor<'a> {
             setup_contracts,
             project_contracts,
             artifact_filters: ArtifactFilters::default(),
+            checkpoint: None,
+            cancelled: None,
         }
     }
 
+    /// Persists the progress of the campaign in the checkpoint file, and resumes the campaign
+    /// from an existing checkpoint if [CheckpointFile::resume] is set
+    #[must_use]
+    pub fn with_checkpoint(mut self, checkpoint: Option<CheckpointFile>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Stops the campaign once `cancelled` is set
+    #[must_use]
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
     /// Fuzzes any deployed contract and checks any broken invariant at `invariant_address`
     /// Returns a list of all the consumed gas and calldata of every invariant fuzz case
+    ///
+    /// Returns `None` if the campaign was cancelled before any invariant was broken.
     pub fn invariant_fuzz(
         &mut self,
         invariant_contract: InvariantContract,
     ) -> eyre::Result<Option<InvariantFuzzTestResult>> {
         let (fuzz_state, targeted_contracts, strat) = self.prepare_fuzzing(&invariant_contract)?;
+        let bytecode_hash =
+            self.bytecode_hash(invariant_contract.address, &targeted_contracts.lock());
+        let checkpoint = self.start_checkpoint(&fuzz_state, bytecode_hash)?.map(RefCell::new);
+        let last_checkpoint = Cell::new(Instant::now());
 
         // Stores the consumed gas and calldata of every successful fuzz call.
         let fuzz_cases: RefCell<Vec<FuzzedCases>> = RefCell::new(Default::default());
 
+        // Stores the calls, reverts and gas of every handler function.
+        let handlers = RefCell::new(initial_handler_stats(&targeted_contracts.lock()));
+
         // Stores data related to reverts or failed assertions of the test.
         let failures =
             RefCell::new(InvariantFailures::new(&invariant_contract.invariant_functions));
 
+        let cancelled = self.cancelled.clone();
+        let is_cancelled =
+            || cancelled.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or_default();
+
         let blank_executor = RefCell::new(&mut *self.executor);
 
         let last_call_results = RefCell::new(
@@ -100,6 +147,7 @@ impl<'a> InvariantExecutor<'a> {
                 &invariant_contract,
                 &blank_executor.borrow(),
                 &[],
+                &[],
                 &mut failures.borrow_mut(),
             )
             .ok(),
@@ -109,6 +157,23 @@ impl<'a> InvariantExecutor<'a> {
             fuzz_cases.borrow_mut().push(FuzzedCases::new(vec![]));
         }
 
+        // Invariants broken before the campaign was resumed are broken again by replaying their
+        // call sequences.
+        let previous_reverts = checkpoint.as_ref().map(|c| c.borrow().reverts).unwrap_or_default();
+        if let Some(ref checkpoint) = checkpoint {
+            let mut failures = failures.borrow_mut();
+            for sequence in checkpoint.borrow().broken.values() {
+                replay_sequence(
+                    &invariant_contract,
+                    &blank_executor.borrow(),
+                    sequence,
+                    &mut failures,
+                    self.config.fail_on_revert,
+                );
+            }
+            failures.reverts = 0;
+        }
+
         if failures.borrow().broken_invariants_count < invariant_contract.invariant_functions.len()
         {
             // The strategy only comes with the first `input`. We fill the rest of the `inputs`
@@ -119,6 +184,10 @@ impl<'a> InvariantExecutor<'a> {
             let _ = self.runner.run(&strat, |mut inputs| {
                 // Scenarios where we want to fail as soon as possible.
                 {
+                    if is_cancelled() {
+                        return Err(TestCaseError::fail("Campaign cancelled."))
+                    }
+
                     if self.config.fail_on_revert && failures.borrow().reverts == 1 {
                         return Err(TestCaseError::fail("Revert occurred."))
                     }
@@ -139,7 +208,14 @@ impl<'a> InvariantExecutor<'a> {
                 // Created contracts during a run.
                 let mut created_contracts = vec![];
 
+                // Logs emitted during a run, event invariants are asserted against them.
+                let mut logs: Vec<Log> = vec![];
+
                 'fuzz_run: for _ in 0..self.config.depth {
+                    if is_cancelled() {
+                        break 'fuzz_run
+                    }
+
                     let (sender, (address, calldata)) =
                         inputs.last().expect("to have the next randomly generated input.");
 
@@ -159,6 +235,7 @@ impl<'a> InvariantExecutor<'a> {
                         fuzz_state.clone(),
                         self.config.include_storage,
                         self.config.include_push_bytes,
+                        self.config.include_balances,
                     );
 
                     if let Err(error) = collect_created_contracts(
@@ -175,17 +252,30 @@ impl<'a> InvariantExecutor<'a> {
                     // Commit changes to the database.
                     executor.backend_mut().commit(state_changeset);
 
+                    record_handler_call(
+                        &mut handlers.borrow_mut(),
+                        &targeted_contracts.lock(),
+                        *address,
+                        calldata,
+                        &call_result,
+                    );
+
                     fuzz_runs.push(FuzzCase {
                         calldata: calldata.clone(),
                         gas: call_result.gas_used,
                         stipend: call_result.stipend,
                     });
 
+                    if !call_result.reverted {
+                        logs.extend(call_result.logs.iter().cloned());
+                    }
+
                     let (can_continue, call_results) = can_continue(
                         &invariant_contract,
                         call_result,
                         &executor,
                         &inputs,
+                        &logs,
                         &mut failures.borrow_mut(),
                         self.config.fail_on_revert,
                     );
@@ -214,12 +304,47 @@ impl<'a> InvariantExecutor<'a> {
                     }
                 }
 
+                // a run cut short by the cancellation is not checkpointed
+                if let (Some(checkpoint), Some(file), false) =
+                    (&checkpoint, &self.checkpoint, is_cancelled())
+                {
+                    let mut checkpoint = checkpoint.borrow_mut();
+                    checkpoint.runs += 1;
+                    checkpoint.calls += fuzz_runs.len() as u64;
+                    if last_checkpoint.get().elapsed() >= CHECKPOINT_INTERVAL {
+                        let reverts = previous_reverts + failures.borrow().reverts;
+                        write_checkpoint(
+                            file,
+                            &mut checkpoint,
+                            &fuzz_state,
+                            &failures.borrow(),
+                            reverts,
+                        );
+                        last_checkpoint.set(Instant::now());
+                    }
+                }
+
                 fuzz_cases.borrow_mut().push(FuzzedCases::new(fuzz_runs));
 
                 Ok(())
             });
         }
 
+        if let (Some(checkpoint), Some(file)) = (&checkpoint, &self.checkpoint) {
+            let reverts = previous_reverts + failures.borrow().reverts;
+            write_checkpoint(
+                file,
+                &mut checkpoint.borrow_mut(),
+                &fuzz_state,
+                &failures.borrow(),
+                reverts,
+            );
+        }
+
+        if is_cancelled() && failures.borrow().broken_invariants_count == 0 {
+            return Ok(None)
+        }
+
         tracing::trace!(target: "forge::test::invariant::dictionary", "{:?}", fuzz_state.read().iter().map(hex::encode).collect::<Vec<_>>());
 
         let (reverts, invariants) = failures.into_inner().into_inner();
@@ -228,10 +353,69 @@ impl<'a> InvariantExecutor<'a> {
             invariants,
             cases: fuzz_cases.into_inner(),
             reverts,
+            handlers: handlers.into_inner(),
             last_call_results: last_call_results.take(),
         }))
     }
 
+    /// Returns the checkpoint of the campaign, if the campaign is checkpointed
+    ///
+    /// If the campaign is resumed, its dictionary is restored and the runner is reseeded to only
+    /// execute the remaining runs. A checkpoint of a campaign of different code is discarded.
+    fn start_checkpoint(
+        &mut self,
+        fuzz_state: &EvmFuzzState,
+        bytecode_hash: H256,
+    ) -> eyre::Result<Option<InvariantCheckpoint>> {
+        let file = match self.checkpoint {
+            Some(ref file) => file.clone(),
+            None => return Ok(None),
+        };
+
+        let checkpoint = if file.resume { InvariantCheckpoint::read(&file.path)? } else { None };
+        let checkpoint = checkpoint.filter(|checkpoint| {
+            let is_same_code = checkpoint.bytecode_hash == bytecode_hash;
+            if !is_same_code {
+                warn!(target: "forge::test::invariant", "code changed, not resuming campaign");
+            }
+            is_same_code
+        });
+        let checkpoint = match checkpoint {
+            Some(checkpoint) => {
+                trace!(target: "forge::test::invariant", runs = checkpoint.runs, "resuming campaign");
+                fuzz_state.write().extend(checkpoint.dictionary.iter().map(|value| value.0));
+                checkpoint
+            }
+            None => {
+                let mut seed = H256::zero();
+                self.runner.rng().fill_bytes(seed.as_bytes_mut());
+                InvariantCheckpoint::new(seed, bytecode_hash)
+            }
+        };
+
+        let mut config = self.runner.config().clone();
+        config.cases = config.cases.saturating_sub(checkpoint.runs);
+        self.runner = TestRunner::new_with_rng(config, checkpoint.rng());
+        Ok(Some(checkpoint))
+    }
+
+    /// Returns the hash of the code of the test contract and all targeted contracts
+    fn bytecode_hash(&self, test_contract: Address, targets: &TargetedContracts) -> H256 {
+        let mut hashes = Vec::with_capacity((targets.len() + 1) * 32);
+        for address in std::iter::once(&test_contract).chain(targets.keys()) {
+            let code_hash = self
+                .executor
+                .backend()
+                .basic(*address)
+                .ok()
+                .flatten()
+                .map(|info| info.code_hash)
+                .unwrap_or_default();
+            hashes.extend_from_slice(code_hash.as_bytes());
+        }
+        keccak256(hashes).into()
+    }
+
     /// Prepares certain structures to execute the invariant tests:
     /// * Fuzz dictionary
     /// * Targeted contracts
@@ -240,6 +424,16 @@ impl<'a> InvariantExecutor<'a> {
         &mut self,
         invariant_contract: &InvariantContract,
     ) -> eyre::Result<InvariantPreparation> {
+        if let Some(func) =
+            invariant_contract.invariant_functions.iter().find(|func| !is_valid_invariant(func))
+        {
+            eyre::bail!(
+                "Invariant `{}` must take no arguments or the emitted logs as \
+                 `(address,bytes32[],bytes)[]`.",
+                func.name
+            );
+        }
+
         // Finds out the chosen deployed contracts and/or senders.
         self.select_contract_artifacts(invariant_contract.address, invariant_contract.abi)?;
         let (targeted_senders, targeted_contracts) =
@@ -250,11 +444,24 @@ impl<'a> InvariantExecutor<'a> {
         }
 
         // Stores fuzz state for use with [fuzz_calldata_from_state].
-        let fuzz_state: EvmFuzzState = build_initial_state(
-            self.executor.backend().mem_db(),
-            self.config.include_storage,
-            self.config.include_push_bytes,
-        );
+        let fuzz_state: EvmFuzzState =
+            if let Some(fork_db) = self.executor.backend().active_fork_db() {
+                build_initial_state(
+                    fork_db,
+                    self.config.include_storage,
+                    self.config.include_push_bytes,
+                    self.config.include_balances,
+                    self.config.dictionary_weights,
+                )
+            } else {
+                build_initial_state(
+                    self.executor.backend().mem_db(),
+                    self.config.include_storage,
+                    self.config.include_push_bytes,
+                    self.config.include_balances,
+                    self.config.dictionary_weights,
+                )
+            };
 
         // During execution, any newly created contract is added here and used through the rest of
         // the fuzz run.
@@ -531,6 +738,7 @@ fn collect_data(
     fuzz_state: EvmFuzzState,
     include_storage: bool,
     include_push_bytes: bool,
+    include_balances: bool,
 ) {
     // Verify it has no code.
     let mut has_code = false;
@@ -551,6 +759,7 @@ fn collect_data(
         fuzz_state,
         include_storage,
         include_push_bytes,
+        include_balances,
     );
 
     // Re-add changes
@@ -559,6 +768,83 @@ fn collect_data(
     }
 }
 
+/// Replays a call sequence of a previous campaign and records the invariants it breaks
+fn replay_sequence(
+    invariant_contract: &InvariantContract,
+    executor: &Executor,
+    sequence: &[BasicTxDetails],
+    failures: &mut InvariantFailures,
+    fail_on_revert: bool,
+) {
+    let mut executor = executor.clone();
+    let mut logs = vec![];
+    for (idx, (sender, (address, calldata))) in sequence.iter().enumerate() {
+        let call_result =
+            match executor.call_raw(*sender, *address, calldata.0.clone(), U256::zero()) {
+                Ok(call_result) => call_result,
+                Err(_) => return,
+            };
+        if let Some(ref state_changeset) = call_result.state_changeset {
+            executor.backend_mut().commit(state_changeset.clone());
+        }
+        if !call_result.reverted {
+            logs.extend(call_result.logs.iter().cloned());
+        }
+        let (can_continue, _) = can_continue(
+            invariant_contract,
+            call_result,
+            &executor,
+            &sequence[..=idx],
+            &logs,
+            failures,
+            fail_on_revert,
+        );
+        if !can_continue {
+            return
+        }
+    }
+}
+
+/// Records the current progress of the campaign in the checkpoint and writes it to the file
+fn write_checkpoint(
+    file: &CheckpointFile,
+    checkpoint: &mut InvariantCheckpoint,
+    fuzz_state: &EvmFuzzState,
+    failures: &InvariantFailures,
+    reverts: usize,
+) {
+    checkpoint.reverts = reverts;
+    checkpoint.dictionary.extend(fuzz_state.read().iter().map(|value| H256(*value)));
+    for (invariant, error) in &failures.failed_invariants {
+        if let Some(InvariantFuzzError { test_error: TestError::Fail(_, sequence), .. }) = error {
+            checkpoint.broken.entry(invariant.clone()).or_insert_with(|| sequence.clone());
+        }
+    }
+    if let Err(err) = checkpoint.write(&file.path) {
+        warn!(target: "forge::test::invariant", ?err, "failed to write checkpoint");
+    }
+}
+
+/// Records the call in the stats of the called handler function
+fn record_handler_call(
+    handlers: &mut InvariantHandlerStats,
+    contracts: &TargetedContracts,
+    address: Address,
+    calldata: &Bytes,
+    call_result: &RawCallResult,
+) {
+    let func = contracts.get(&address).and_then(|(name, abi, _)| {
+        let func = abi.functions().find(|func| calldata.0.starts_with(&func.short_signature()))?;
+        Some(handler_id(name, func))
+    });
+    // calls of functions that are not in the abi of the target are recorded by their selector
+    let id = func.unwrap_or_else(|| {
+        format!("{address:?}.0x{}", hex::encode(calldata.0.get(..4).unwrap_or_default()))
+    });
+    let gas = call_result.gas_used.saturating_sub(call_result.stipend);
+    handlers.entry(id).or_default().record(gas, call_result.reverted);
+}
+
 /// Verifies that the invariant run execution can continue.
 /// Returns the mapping of (Invariant Function Name -> Call Result) if invariants were asserted.
 fn can_continue(
@@ -566,12 +852,14 @@ fn can_continue(
     call_result: RawCallResult,
     executor: &Executor,
     calldata: &[BasicTxDetails],
+    logs: &[Log],
     failures: &mut InvariantFailures,
     fail_on_revert: bool,
 ) -> (bool, Option<BTreeMap<String, RawCallResult>>) {
     let mut call_results = None;
     if !call_result.reverted {
-        call_results = assert_invariants(invariant_contract, executor, calldata, failures).ok();
+        call_results =
+            assert_invariants(invariant_contract, executor, calldata, logs, failures).ok();
         if call_results.is_none() {
             return (false, None)
         }
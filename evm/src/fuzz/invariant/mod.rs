@@ -1,20 +1,25 @@
 //! Fuzzing support abstracted over the [`Evm`](crate::Evm) used
 use crate::{fuzz::*, CALLER};
+pub mod checkpoint;
 mod error;
 pub use error::InvariantFuzzError;
 mod filters;
 pub use filters::{ArtifactFilters, SenderFilters};
 mod call_override;
 pub use call_override::{set_up_inner_replay, RandomCallGenerator};
+mod events;
+pub use events::{encode_invariant_call, is_event_invariant, is_valid_invariant};
 mod executor;
+mod stats;
 use crate::executor::Executor;
 use ethers::{
     abi::{Abi, Function},
-    types::{Address, Bytes, U256},
+    types::{Address, Bytes, Log, U256},
 };
 pub use executor::{InvariantExecutor, InvariantFailures};
 use parking_lot::Mutex;
 pub use proptest::test_runner::Config as FuzzConfig;
+pub use stats::{unsuccessful_handlers, HandlerStats, InvariantHandlerStats};
 use std::{collections::BTreeMap, sync::Arc};
 
 pub type TargetedContracts = BTreeMap<Address, (String, Abi, Vec<Function>)>;
@@ -36,11 +41,13 @@ pub struct InvariantContract<'a> {
 
 /// Given the executor state, asserts that no invariant has been broken. Otherwise, it fills the
 /// external `invariant_failures.failed_invariant` map and returns a generic error.
+/// Event invariants are asserted against the `logs` emitted by the calls of the run so far.
 /// Returns the mapping of (Invariant Function Name -> Call Result).
 pub fn assert_invariants(
     invariant_contract: &InvariantContract,
     executor: &Executor,
     calldata: &[BasicTxDetails],
+    logs: &[Log],
     invariant_failures: &mut InvariantFailures,
 ) -> eyre::Result<BTreeMap<String, RawCallResult>> {
     let mut found_case = false;
@@ -58,7 +65,7 @@ pub fn assert_invariants(
             .call_raw(
                 CALLER,
                 invariant_contract.address,
-                func.encode_input(&[]).expect("invariant should have no inputs").into(),
+                encode_invariant_call(func, logs).0,
                 U256::zero(),
             )
             .expect("EVM error");
@@ -132,6 +139,8 @@ pub struct InvariantFuzzTestResult {
     pub cases: Vec<FuzzedCases>,
     /// Number of reverted fuzz calls
     pub reverts: usize,
+    /// The calls of every function the campaign could call, see [InvariantHandlerStats]
+    pub handlers: InvariantHandlerStats,
 
     pub last_call_results: Option<BTreeMap<String, RawCallResult>>,
 }
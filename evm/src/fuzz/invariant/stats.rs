@@ -0,0 +1,112 @@
+//! Statistics of the calls an invariant campaign made to the handler functions
+
+use super::TargetedContracts;
+use ethers::abi::{Function, StateMutability};
+use foundry_common::contracts::get_contract_name;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The statistics of all handlers by `<contract name>.<function signature>`
+pub type InvariantHandlerStats = BTreeMap<String, HandlerStats>;
+
+/// How often a handler function was called during an invariant campaign
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandlerStats {
+    /// The number of calls
+    pub calls: u64,
+    /// The number of reverted calls
+    pub reverts: u64,
+    /// The gas used by all calls
+    pub gas: u64,
+}
+
+impl HandlerStats {
+    /// Records a call of the handler
+    pub fn record(&mut self, gas: u64, reverted: bool) {
+        self.calls += 1;
+        self.gas += gas;
+        if reverted {
+            self.reverts += 1;
+        }
+    }
+
+    /// The number of calls that did not revert
+    pub fn successes(&self) -> u64 {
+        self.calls - self.reverts
+    }
+
+    /// The average gas used by a call, `0` if the handler was never called
+    pub fn avg_gas(&self) -> u64 {
+        self.gas.checked_div(self.calls).unwrap_or_default()
+    }
+}
+
+/// Returns the id of the function in the [InvariantHandlerStats]
+pub fn handler_id(contract: &str, func: &Function) -> String {
+    format!("{}.{}", get_contract_name(contract), func.signature())
+}
+
+/// Returns the stats of all functions the campaign can call, none of which were called yet
+pub fn initial_handler_stats(contracts: &TargetedContracts) -> InvariantHandlerStats {
+    let mut stats = InvariantHandlerStats::new();
+    for (name, abi, functions) in contracts.values() {
+        let handlers = if functions.is_empty() {
+            abi.functions()
+                .filter(|func| {
+                    !matches!(func.state_mutability, StateMutability::Pure | StateMutability::View)
+                })
+                .collect::<Vec<_>>()
+        } else {
+            functions.iter().collect()
+        };
+        for func in handlers {
+            stats.entry(handler_id(name, func)).or_default();
+        }
+    }
+    stats
+}
+
+/// Returns the handlers that were never executed successfully
+pub fn unsuccessful_handlers(stats: &InvariantHandlerStats) -> impl Iterator<Item = &str> {
+    stats.iter().filter(|(_, stats)| stats.successes() == 0).map(|(id, _)| id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::Abi;
+
+    #[test]
+    fn can_collect_handler_stats() {
+        let abi: Abi = serde_json::from_str(
+            r#"[
+                {"type":"function","name":"deposit","inputs":[{"name":"amount","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable"},
+                {"type":"function","name":"withdraw","inputs":[],"outputs":[],"stateMutability":"nonpayable"},
+                {"type":"function","name":"balance","inputs":[],"outputs":[{"name":"","type":"uint256"}],"stateMutability":"view"}
+            ]"#,
+        )
+        .unwrap();
+        let contracts = TargetedContracts::from([(
+            Default::default(),
+            ("src/Handler.sol:Handler".to_string(), abi, vec![]),
+        )]);
+
+        let mut stats = initial_handler_stats(&contracts);
+        assert_eq!(
+            stats.keys().collect::<Vec<_>>(),
+            ["Handler.deposit(uint256)", "Handler.withdraw()"]
+        );
+
+        let deposit = stats.get_mut("Handler.deposit(uint256)").unwrap();
+        deposit.record(30_000, false);
+        deposit.record(10_000, true);
+        stats.get_mut("Handler.withdraw()").unwrap().record(5_000, true);
+
+        assert_eq!(
+            stats["Handler.deposit(uint256)"],
+            HandlerStats { calls: 2, reverts: 1, gas: 40_000 }
+        );
+        assert_eq!(stats["Handler.deposit(uint256)"].avg_gas(), 20_000);
+        assert_eq!(unsuccessful_handlers(&stats).collect::<Vec<_>>(), ["Handler.withdraw()"]);
+    }
+}
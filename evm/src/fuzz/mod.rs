@@ -13,18 +13,32 @@ use eyre::Result;
 use foundry_common::{calc, contracts::ContractsByAddress};
 use foundry_config::FuzzConfig;
 pub use proptest::test_runner::Reason;
-use proptest::test_runner::{TestCaseError, TestError, TestRunner};
+use proptest::{
+    strategy::Strategy,
+    test_runner::{TestCaseError, TestError, TestRunner},
+};
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::BTreeMap, fmt};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 use strategies::{
-    build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
-    EvmFuzzState,
+    build_initial_state, calldata_from_fixtures, collect_state_from_call, fuzz_calldata,
+    fuzz_calldata_from_fixtures, fuzz_calldata_from_state, EvmFuzzState,
 };
 
 pub mod error;
 pub mod invariant;
 pub mod strategies;
 
+/// Values provided by the test contract for specific fuzz parameters, keyed by parameter name
+pub type FuzzFixtures = BTreeMap<String, Vec<Token>>;
+
 /// Wrapper around an [`Executor`] which provides fuzzing support using [`proptest`](https://docs.rs/proptest/1.0.0/proptest/).
 ///
 /// After instantiation, calling `fuzz` will proceed to hammer the deployed smart contract with
@@ -39,6 +53,10 @@ pub struct FuzzedExecutor<'a> {
     sender: Address,
     /// The fuzz configuration
     config: FuzzConfig,
+    /// The fixtures of the fuzzed function's parameters
+    fixtures: FuzzFixtures,
+    /// Set to cancel the campaign before its next case
+    cancelled: Option<Arc<AtomicBool>>,
 }
 
 impl<'a> FuzzedExecutor<'a> {
@@ -49,7 +67,26 @@ impl<'a> FuzzedExecutor<'a> {
         sender: Address,
         config: FuzzConfig,
     ) -> Self {
-        Self { executor, runner, sender, config }
+        Self { executor, runner, sender, config, fixtures: Default::default(), cancelled: None }
+    }
+
+    /// Sets the fixtures that seed the fuzzed parameters with the given values
+    #[must_use]
+    pub fn with_fixtures(mut self, fixtures: FuzzFixtures) -> Self {
+        self.fixtures = fixtures;
+        self
+    }
+
+    /// Cancels the campaign once the flag is set, e.g. because other tests failed already
+    #[must_use]
+    pub fn with_cancellation(mut self, cancelled: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Returns true if the campaign was cancelled
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().map(|c| c.load(Ordering::Relaxed)).unwrap_or_default()
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -82,21 +119,40 @@ impl<'a> FuzzedExecutor<'a> {
                 fork_db,
                 self.config.include_storage,
                 self.config.include_push_bytes,
+                self.config.include_balances,
+                self.config.dictionary_weights,
             )
         } else {
             build_initial_state(
                 self.executor.backend().mem_db(),
                 self.config.include_storage,
                 self.config.include_push_bytes,
+                self.config.include_balances,
+                self.config.dictionary_weights,
             )
         };
 
-        let strat = proptest::strategy::Union::new_weighted(vec![
+        let mut strat = proptest::strategy::Union::new_weighted(vec![
             (100 - self.config.dictionary_weight, fuzz_calldata(func.clone())),
             (self.config.dictionary_weight, fuzz_calldata_from_state(func.clone(), state.clone())),
-        ]);
+        ])
+        .boxed();
+        if func.inputs.iter().any(|input| self.fixtures.contains_key(&input.name)) {
+            strat = proptest::strategy::Union::new_weighted(vec![
+                (100 - self.config.fixture_weight, strat),
+                (
+                    self.config.fixture_weight,
+                    fuzz_calldata_from_fixtures(func.clone(), &self.fixtures),
+                ),
+            ])
+            .boxed();
+        }
         tracing::debug!(func = ?func.name, should_fail, "fuzzing");
-        let run_result = self.runner.clone().run(&strat, |calldata| {
+        let run_case = |calldata: Bytes| {
+            if self.is_cancelled() {
+                return Err(TestCaseError::fail(FuzzError::Cancelled))
+            }
+
             let call = self
                 .executor
                 .call_raw(self.sender, address, calldata.0.clone(), 0.into())
@@ -113,6 +169,7 @@ impl<'a> FuzzedExecutor<'a> {
                 state.clone(),
                 self.config.include_storage,
                 self.config.include_push_bytes,
+                self.config.include_balances,
             );
 
             // When assume cheat code is triggered return a special string "FOUNDRY::ASSUME"
@@ -163,7 +220,21 @@ impl<'a> FuzzedExecutor<'a> {
                     },
                 ))
             }
-        });
+        };
+
+        // every fixture is exercised once before the fuzzer draws its inputs, so it is used
+        // regardless of the fixture weight and the number of runs
+        let mut runner = self.runner.clone();
+        let mut run_result = Ok(());
+        for calldata in calldata_from_fixtures(func, &self.fixtures, &mut runner) {
+            if let Err(TestCaseError::Fail(reason)) = run_case(calldata.clone()) {
+                run_result = Err(TestError::Fail(reason, calldata));
+                break
+            }
+        }
+        if run_result.is_ok() {
+            run_result = runner.run(&strat, &run_case);
+        }
 
         tracing::trace!(target: "forge::test::fuzz::dictionary", "{:?}", state.read().iter().map(hex::encode).collect::<Vec<_>>());
 
@@ -178,6 +249,7 @@ impl<'a> FuzzedExecutor<'a> {
             labeled_addresses: call.labels,
             traces: if run_result.is_ok() { traces.into_inner() } else { call.traces.clone() },
             coverage: coverage.into_inner(),
+            cancelled: false,
         };
 
         match run_result {
@@ -192,6 +264,11 @@ impl<'a> FuzzedExecutor<'a> {
             Err(TestError::Abort(reason)) => {
                 result.reason = Some(reason.to_string());
             }
+            // cancelled before any case failed, otherwise the failure is reported
+            Err(TestError::Fail(_, _)) if self.is_cancelled() && calldata.is_empty() => {
+                result.cancelled = true;
+                result.reason = Some(FuzzError::Cancelled.to_string());
+            }
             Err(TestError::Fail(reason, _)) => {
                 let reason = reason.to_string();
                 result.reason = if reason.is_empty() { None } else { Some(reason) };
@@ -337,6 +414,9 @@ pub struct FuzzTestResult {
 
     /// Raw coverage info
     pub coverage: Option<HitMaps>,
+
+    /// Whether the campaign was cancelled before it completed or found a counterexample
+    pub cancelled: bool,
 }
 
 /// Container type for all successful test cases
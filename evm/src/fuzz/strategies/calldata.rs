@@ -1,6 +1,11 @@
 use super::fuzz_param;
+use crate::fuzz::FuzzFixtures;
 use ethers::{abi::Function, types::Bytes};
-use proptest::prelude::{BoxedStrategy, Strategy};
+use proptest::{
+    prelude::{BoxedStrategy, Strategy},
+    strategy::ValueTree,
+    test_runner::TestRunner,
+};
 
 /// Given a function, it returns a strategy which generates valid calldata
 /// for that function's input types.
@@ -16,3 +21,61 @@ pub fn fuzz_calldata(func: Function) -> BoxedStrategy<Bytes> {
         })
         .boxed()
 }
+
+/// Given a function and the fixtures of its parameters, it returns a strategy which generates
+/// calldata where every parameter with fixtures is selected from them, and all other parameters
+/// are generated randomly.
+pub fn fuzz_calldata_from_fixtures(
+    func: Function,
+    fixtures: &FuzzFixtures,
+) -> BoxedStrategy<Bytes> {
+    let strats = func
+        .inputs
+        .iter()
+        .map(|input| match fixtures.get(&input.name) {
+            Some(values) if !values.is_empty() => proptest::sample::select(values.clone()).boxed(),
+            _ => fuzz_param(&input.kind).boxed(),
+        })
+        .collect::<Vec<_>>();
+
+    strats
+        .prop_map(move |tokens| {
+            tracing::trace!(input = ?tokens, "fixture");
+            func.encode_input(&tokens).unwrap().into()
+        })
+        .boxed()
+}
+
+/// Given a function and the fixtures of its parameters, it returns the calldata of one call per
+/// fixture value, so that every fixture is exercised.
+///
+/// The `n`-th call uses the `n`-th fixture of every parameter, wrapping around for parameters with
+/// fewer fixtures, and all other parameters are generated randomly by the `runner`.
+pub fn calldata_from_fixtures(
+    func: &Function,
+    fixtures: &FuzzFixtures,
+    runner: &mut TestRunner,
+) -> Vec<Bytes> {
+    let len = func
+        .inputs
+        .iter()
+        .filter_map(|input| fixtures.get(&input.name))
+        .map(Vec::len)
+        .max()
+        .unwrap_or_default();
+
+    (0..len)
+        .filter_map(|n| {
+            let tokens = func
+                .inputs
+                .iter()
+                .map(|input| match fixtures.get(&input.name) {
+                    Some(values) if !values.is_empty() => Some(values[n % values.len()].clone()),
+                    _ => fuzz_param(&input.kind).new_tree(runner).ok().map(|tree| tree.current()),
+                })
+                .collect::<Option<Vec<_>>>()?;
+            tracing::trace!(input = ?tokens, "fixture");
+            func.encode_input(&tokens).ok().map(Into::into)
+        })
+        .collect()
+}
@@ -0,0 +1,235 @@
+use ethers::core::rand::Rng;
+use proptest::{
+    num::u8::BinarySearch,
+    strategy::{Just, NewTree, Strategy, ValueTree},
+    test_runner::TestRunner,
+};
+use std::ops::Range;
+
+/// Value tree for dynamic sequences: bytes, strings and arrays.
+///
+/// Shrinks the length first, by binary search over the length of the prefix that is kept. Then
+/// every element is shrunk, from first to last, e.g. a failing `bytes` shrinks to `0x00` if any
+/// input of length one fails.
+pub struct SeqValueTree<T> {
+    /// All elements, only the first `len` are part of the value
+    elements: Vec<T>,
+    /// Lower base of the length
+    lo: usize,
+    /// Current length
+    len: usize,
+    /// Higher base of the length
+    hi: usize,
+    /// The element that is currently shrunk
+    idx: usize,
+    /// What the last simplification changed
+    prev: Option<Shrink>,
+}
+
+#[derive(Clone, Copy)]
+enum Shrink {
+    Len,
+    Element(usize),
+}
+
+impl<T: ValueTree> SeqValueTree<T> {
+    /// Create a new tree
+    /// # Arguments
+    /// * `elements` - The trees of the elements of the starting value
+    /// * `min_len` - The length the sequence can't be shrunk below
+    pub fn new(elements: Vec<T>, min_len: usize) -> Self {
+        let len = elements.len();
+        Self { elements, lo: min_len.min(len), len, hi: len, idx: 0, prev: None }
+    }
+
+    fn reposition(&mut self) -> bool {
+        let new_len = self.lo + (self.hi - self.lo) / 2;
+
+        if new_len == self.len {
+            false
+        } else {
+            self.len = new_len;
+            true
+        }
+    }
+}
+
+impl<T: ValueTree> ValueTree for SeqValueTree<T> {
+    type Value = Vec<T::Value>;
+
+    fn current(&self) -> Self::Value {
+        self.elements[..self.len].iter().map(ValueTree::current).collect()
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.hi > self.lo {
+            self.hi = self.len;
+            if self.reposition() {
+                self.prev = Some(Shrink::Len);
+                return true
+            }
+        }
+
+        while self.idx < self.len {
+            if self.elements[self.idx].simplify() {
+                self.prev = Some(Shrink::Element(self.idx));
+                return true
+            }
+            self.idx += 1;
+        }
+        self.prev = None;
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.prev {
+            Some(Shrink::Len) => {
+                // the length can't grow past the last failing length
+                if self.lo >= self.hi {
+                    return false
+                }
+                self.lo = (self.len + 1).min(self.hi);
+                self.reposition()
+            }
+            Some(Shrink::Element(idx)) => self.elements[idx].complicate(),
+            None => false,
+        }
+    }
+}
+
+/// Strategy for sequences of elements of the given strategy with a random length in `len`, that
+/// shrink like [SeqValueTree]
+#[derive(Debug, Clone)]
+pub struct SeqStrategy<S> {
+    /// The strategy of the elements
+    element: S,
+    /// The range of the length
+    len: Range<usize>,
+}
+
+impl<S: Strategy> SeqStrategy<S> {
+    /// Create a new strategy.
+    /// #Arguments
+    /// * `element` - The strategy of the elements
+    /// * `len` - The range of the length of generated sequences
+    pub fn new(element: S, len: Range<usize>) -> Self {
+        Self { element, len }
+    }
+}
+
+impl<S: Strategy> Strategy for SeqStrategy<S> {
+    type Tree = SeqValueTree<S::Tree>;
+    type Value = Vec<S::Value>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let len = if self.len.is_empty() {
+            self.len.start
+        } else {
+            runner.rng().gen_range(self.len.clone())
+        };
+        let elements =
+            (0..len).map(|_| self.element.new_tree(runner)).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SeqValueTree::new(elements, self.len.start))
+    }
+}
+
+/// Strategy that starts at the given bytes, used for bytes of the fuzz state, which would
+/// otherwise not shrink at all
+#[derive(Debug, Clone)]
+pub struct ShrinkBytes(pub Vec<u8>);
+
+impl Strategy for ShrinkBytes {
+    type Tree = SeqValueTree<BinarySearch>;
+    type Value = Vec<u8>;
+
+    fn new_tree(&self, _: &mut TestRunner) -> NewTree<Self> {
+        Ok(SeqValueTree::new(self.0.iter().map(|byte| BinarySearch::new(*byte)).collect(), 0))
+    }
+}
+
+/// Strategy that starts at the given string, used for strings of the fuzz state
+///
+/// Only the length of the string is shrunk, so it stays readable.
+#[derive(Debug, Clone)]
+pub struct ShrinkString(pub String);
+
+impl Strategy for ShrinkString {
+    type Tree = SeqValueTree<Just<char>>;
+    type Value = Vec<char>;
+
+    fn new_tree(&self, _: &mut TestRunner) -> NewTree<Self> {
+        Ok(SeqValueTree::new(self.0.chars().map(Just).collect(), 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::any;
+
+    /// Shrinks the tree like the test runner does and returns the minimal failing value
+    fn shrink<T: ValueTree>(mut tree: T, fails: impl Fn(&T::Value) -> bool) -> T::Value {
+        let mut last_failure = tree.current();
+        assert!(fails(&last_failure));
+        if tree.simplify() {
+            loop {
+                let current = tree.current();
+                if fails(&current) {
+                    last_failure = current;
+                    if !tree.simplify() {
+                        break
+                    }
+                } else if !tree.complicate() {
+                    break
+                }
+            }
+        }
+        last_failure
+    }
+
+    #[test]
+    fn can_shrink_bytes() {
+        let bytes = ShrinkBytes((1..=32).collect()).new_tree(&mut TestRunner::default()).unwrap();
+        assert_eq!(shrink(bytes, |bytes| bytes.len() >= 3), vec![0, 0, 0]);
+
+        let bytes = ShrinkBytes(vec![0xff; 32]).new_tree(&mut TestRunner::default()).unwrap();
+        assert_eq!(shrink(bytes, |bytes| bytes.iter().any(|b| *b >= 0x10)), vec![0x10]);
+    }
+
+    #[test]
+    fn can_shrink_nested_arrays() {
+        let mut runner = TestRunner::deterministic();
+        let strat = SeqStrategy::new(SeqStrategy::new(any::<u8>(), 1..10), 5..10);
+        let arrays = loop {
+            let tree = strat.new_tree(&mut runner).unwrap();
+            if tree.current()[0].len() > 1 {
+                break tree
+            }
+        };
+
+        let shrunk = shrink(arrays, |arrays| arrays.iter().any(|array| array.len() > 1));
+        assert_eq!(shrunk, vec![vec![0, 0], vec![0], vec![0], vec![0], vec![0]]);
+    }
+
+    #[test]
+    fn stops_complicating_at_the_failing_length() {
+        let mut bytes =
+            ShrinkBytes((1..=8).collect()).new_tree(&mut TestRunner::default()).unwrap();
+        assert!(bytes.simplify());
+        // a flaky test that passes for every length, including the one that failed
+        let mut steps = 0;
+        while bytes.complicate() {
+            steps += 1;
+            assert!(steps <= 8, "complicate doesn't terminate");
+        }
+        assert_eq!(bytes.current().len(), 8);
+    }
+
+    #[test]
+    fn can_shrink_strings() {
+        let string = ShrinkString("hello world".to_string());
+        let string = string.new_tree(&mut TestRunner::default()).unwrap();
+        assert_eq!(shrink(string, |string| string.len() > 4), "hello".chars().collect::<Vec<_>>());
+    }
+}
@@ -3,16 +3,19 @@ mod uint;
 pub use int::IntStrategy;
 pub use uint::UintStrategy;
 
+mod dynamic;
+pub use dynamic::{SeqStrategy, SeqValueTree, ShrinkBytes, ShrinkString};
+
 mod param;
-pub use param::{fuzz_param, fuzz_param_from_state};
+pub use param::{fuzz_param, fuzz_param_from_state, MAX_ARRAY_LEN};
 
 mod calldata;
-pub use calldata::fuzz_calldata;
+pub use calldata::{calldata_from_fixtures, fuzz_calldata, fuzz_calldata_from_fixtures};
 
 mod state;
 pub use state::{
     build_initial_state, collect_created_contracts, collect_state_from_call,
-    fuzz_calldata_from_state, EvmFuzzState,
+    fuzz_calldata_from_state, DictionarySource, EvmFuzzState, FuzzDictionary,
 };
 
 mod invariants;
@@ -1,4 +1,4 @@
-use super::state::EvmFuzzState;
+use super::{state::EvmFuzzState, SeqStrategy, ShrinkBytes, ShrinkString};
 use ethers::{
     abi::{ParamType, Token, Tokenizable},
     types::{Address, Bytes, I256, U256},
@@ -8,6 +8,9 @@ use proptest::prelude::*;
 /// The max length of arrays we fuzz for is 256.
 pub const MAX_ARRAY_LEN: usize = 256;
 
+/// The max length of bytes and strings we fuzz for is 100.
+const MAX_BYTES_LEN: usize = 100;
+
 /// Given a parameter type, returns a strategy for generating values for that type.
 ///
 /// Works with ABI Encoder v2 tuples.
@@ -18,7 +21,9 @@ pub fn fuzz_param(param: &ParamType) -> impl Strategy<Value = Token> {
             // https://altsysrq.github.io/proptest-book/proptest/tutorial/transforming-strategies.html
             any::<[u8; 20]>().prop_map(|x| Address::from_slice(&x).into_token()).boxed()
         }
-        ParamType::Bytes => any::<Vec<u8>>().prop_map(|x| Bytes::from(x).into_token()).boxed(),
+        ParamType::Bytes => SeqStrategy::new(any::<u8>(), 0..MAX_BYTES_LEN)
+            .prop_map(|x| Bytes::from(x).into_token())
+            .boxed(),
         ParamType::Int(n) => {
             super::IntStrategy::new(*n, vec![]).prop_map(|x| x.into_token()).boxed()
         }
@@ -26,10 +31,10 @@ pub fn fuzz_param(param: &ParamType) -> impl Strategy<Value = Token> {
             super::UintStrategy::new(*n, vec![]).prop_map(|x| x.into_token()).boxed()
         }
         ParamType::Bool => any::<bool>().prop_map(|x| x.into_token()).boxed(),
-        ParamType::String => any::<Vec<u8>>()
-            .prop_map(|x| Token::String(unsafe { std::str::from_utf8_unchecked(&x).to_string() }))
+        ParamType::String => SeqStrategy::new(any::<char>(), 0..MAX_BYTES_LEN)
+            .prop_map(|x| Token::String(x.into_iter().collect()))
             .boxed(),
-        ParamType::Array(param) => proptest::collection::vec(fuzz_param(param), 0..MAX_ARRAY_LEN)
+        ParamType::Array(param) => SeqStrategy::new(fuzz_param(param), 0..MAX_ARRAY_LEN)
             .prop_map(Token::Array)
             .boxed(),
         ParamType::FixedBytes(size) => (0..*size as u64)
@@ -55,21 +60,20 @@ pub fn fuzz_param(param: &ParamType) -> impl Strategy<Value = Token> {
 ///
 /// Works with ABI Encoder v2 tuples.
 pub fn fuzz_param_from_state(param: &ParamType, arc_state: EvmFuzzState) -> BoxedStrategy<Token> {
-    // These are to comply with lifetime requirements
-    let state_len = arc_state.read().len();
-
-    // Select a value from the state
+    // Select a value from the state, or a random value if no source of the state can be drawn from
     let st = arc_state.clone();
-    let value = any::<prop::sample::Index>()
-        .prop_map(move |index| index.index(state_len))
-        .prop_map(move |index| *st.read().iter().nth(index).unwrap());
+    let value = any::<(prop::sample::Index, prop::sample::Index, [u8; 32])>()
+        .prop_map(move |(source, index, random)| st.read().sample(source, index).unwrap_or(random));
 
     // Convert the value based on the parameter type
     match param {
         ParamType::Address => {
             value.prop_map(move |value| Address::from_slice(&value[12..]).into_token()).boxed()
         }
-        ParamType::Bytes => value.prop_map(move |value| Bytes::from(value).into_token()).boxed(),
+        ParamType::Bytes => value
+            .prop_flat_map(move |value| ShrinkBytes(value.to_vec()))
+            .prop_map(move |value| Bytes::from(value).into_token())
+            .boxed(),
         ParamType::Int(n) => match n / 8 {
             32 => {
                 value.prop_map(move |value| I256::from_raw(U256::from(value)).into_token()).boxed()
@@ -97,14 +101,15 @@ pub fn fuzz_param_from_state(param: &ParamType, arc_state: EvmFuzzState) -> Boxe
         },
         ParamType::Bool => value.prop_map(move |value| Token::Bool(value[31] == 1)).boxed(),
         ParamType::String => value
-            .prop_map(move |value| {
-                Token::String(
+            .prop_flat_map(move |value| {
+                ShrinkString(
                     String::from_utf8_lossy(&value[..]).trim().trim_end_matches('\0').to_string(),
                 )
             })
+            .prop_map(move |value| Token::String(value.into_iter().collect()))
             .boxed(),
         ParamType::Array(param) => {
-            proptest::collection::vec(fuzz_param_from_state(param, arc_state), 0..MAX_ARRAY_LEN)
+            SeqStrategy::new(fuzz_param_from_state(param, arc_state), 0..MAX_ARRAY_LEN)
                 .prop_map(Token::Array)
                 .boxed()
         }
@@ -139,7 +144,7 @@ mod tests {
         let func = HumanReadableParser::parse_function(f).unwrap();
 
         let db = CacheDB::new(EmptyDB());
-        let state = build_initial_state(&db, true, true);
+        let state = build_initial_state(&db, true, true, true, None);
 
         let strat = proptest::strategy::Union::new_weighted(vec![
             (60, fuzz_calldata(func.clone())),
@@ -10,15 +10,19 @@ use ethers::{
     types::{Address, Log, H256, U256},
 };
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
+use foundry_config::DictionaryWeights;
 use hashbrown::HashSet;
 use parking_lot::RwLock;
-use proptest::prelude::{BoxedStrategy, Strategy};
+use proptest::{
+    prelude::{BoxedStrategy, Strategy},
+    sample::Index,
+};
 use revm::{
     db::{CacheDB, DatabaseRef},
     opcode, spec_opcode_gas, SpecId,
 };
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     io::Write,
     ops::{Deref, DerefMut},
     sync::Arc,
@@ -34,6 +38,81 @@ pub struct FuzzDictionary {
     inner: BTreeSet<[u8; 32]>,
     /// Addresses that already had their PUSH bytes collected.
     cache: HashSet<Address>,
+    /// The relative weights of the sources values are drawn from, if configured.
+    weights: Option<DictionaryWeights>,
+    /// The values collected from every source, only tracked if `weights` are configured.
+    sources: BTreeMap<DictionarySource, BTreeSet<[u8; 32]>>,
+}
+
+/// The source a value of the [FuzzDictionary] was collected from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DictionarySource {
+    Address,
+    PushBytes,
+    Storage,
+    Balance,
+    Log,
+    Stack,
+}
+
+impl DictionarySource {
+    fn weight(self, weights: &DictionaryWeights) -> u32 {
+        match self {
+            DictionarySource::Address => weights.addresses,
+            DictionarySource::PushBytes => weights.push_bytes,
+            DictionarySource::Storage => weights.storage,
+            DictionarySource::Balance => weights.balances,
+            DictionarySource::Log => weights.logs,
+            DictionarySource::Stack => weights.stack,
+        }
+    }
+}
+
+impl FuzzDictionary {
+    pub fn new(weights: Option<DictionaryWeights>) -> Self {
+        FuzzDictionary { weights, ..Default::default() }
+    }
+
+    /// Inserts a value that was collected from `source`
+    pub fn insert_from(&mut self, source: DictionarySource, value: [u8; 32]) {
+        if self.weights.is_some() {
+            self.sources.entry(source).or_default().insert(value);
+        }
+        self.inner.insert(value);
+    }
+
+    /// Draws a value from the dictionary.
+    ///
+    /// If weights are configured, the source of the value is chosen by weight among the sources
+    /// that collected values, otherwise the value is drawn uniformly from the whole dictionary.
+    /// Values inserted without a source, e.g. restored from a checkpoint, are only drawn if no
+    /// weights are configured.
+    ///
+    /// Returns `None` if weights are configured but none of the sources with a weight collected
+    /// any values, the caller should draw a random value instead.
+    pub fn sample(&self, source: Index, value: Index) -> Option<[u8; 32]> {
+        if let Some(weights) = &self.weights {
+            let sources = self
+                .sources
+                .iter()
+                .map(|(source, values)| (source.weight(weights) as usize, values))
+                .filter(|(weight, values)| *weight > 0 && !values.is_empty())
+                .collect::<Vec<_>>();
+            let total = sources.iter().map(|(weight, _)| weight).sum::<usize>();
+            if total == 0 {
+                return None
+            }
+            let mut pick = source.index(total);
+            for (weight, values) in sources {
+                if pick < weight {
+                    return values.iter().nth(value.index(values.len())).copied()
+                }
+                pick -= weight;
+            }
+            return None
+        }
+        self.inner.iter().nth(value.index(self.inner.len())).copied()
+    }
 }
 
 impl Deref for FuzzDictionary {
@@ -80,23 +159,35 @@ This is a bug, please open an issue: https://github.com/foundry-rs/foundry/issue
 }
 
 /// Builds the initial [EvmFuzzState] from a database.
+///
+/// If the database is backed by a fork, this includes all accounts and storage slots that were
+/// fetched from the remote endpoint so far, e.g. during `setUp`.
 pub fn build_initial_state<DB: DatabaseRef>(
     db: &CacheDB<DB>,
     include_storage: bool,
     include_push_bytes: bool,
+    include_balances: bool,
+    weights: Option<DictionaryWeights>,
 ) -> EvmFuzzState {
-    let mut state = FuzzDictionary::default();
+    let mut state = FuzzDictionary::new(weights);
 
     for (address, account) in db.accounts.iter() {
         // Insert basic account information
-        state.insert(H256::from(*address).into());
+        state.insert_from(DictionarySource::Address, H256::from(*address).into());
+
+        if include_balances {
+            state.insert_from(
+                DictionarySource::Balance,
+                utils::u256_to_h256_be(account.info.balance).into(),
+            );
+        }
 
         // Insert push bytes
         if include_push_bytes {
             if let Some(code) = &account.info.code {
                 if state.cache.insert(*address) {
                     for push_byte in collect_push_bytes(code.bytes().clone()) {
-                        state.insert(push_byte);
+                        state.insert_from(DictionarySource::PushBytes, push_byte);
                     }
                 }
             }
@@ -105,8 +196,8 @@ pub fn build_initial_state<DB: DatabaseRef>(
         if include_storage {
             // Insert storage
             for (slot, value) in &account.storage {
-                state.insert(utils::u256_to_h256_be(*slot).into());
-                state.insert(utils::u256_to_h256_be(*value).into());
+                state.insert_from(DictionarySource::Storage, utils::u256_to_h256_be(*slot).into());
+                state.insert_from(DictionarySource::Storage, utils::u256_to_h256_be(*value).into());
             }
         }
     }
@@ -115,7 +206,7 @@ pub fn build_initial_state<DB: DatabaseRef>(
     // fuzzing
     if state.is_empty() {
         // prefill with a random addresses
-        state.insert(H256::from(Address::random()).into());
+        state.insert_from(DictionarySource::Address, H256::from(Address::random()).into());
     }
 
     Arc::new(RwLock::new(state))
@@ -128,18 +219,29 @@ pub fn collect_state_from_call(
     state: EvmFuzzState,
     include_storage: bool,
     include_push_bytes: bool,
+    include_balances: bool,
 ) {
     let mut state = state.write();
 
     for (address, account) in state_changeset {
         // Insert basic account information
-        state.insert(H256::from(*address).into());
+        state.insert_from(DictionarySource::Address, H256::from(*address).into());
+
+        if include_balances {
+            state.insert_from(
+                DictionarySource::Balance,
+                utils::u256_to_h256_be(account.info.balance).into(),
+            );
+        }
 
         if include_storage {
             // Insert storage
             for (slot, value) in &account.storage {
-                state.insert(utils::u256_to_h256_be(*slot).into());
-                state.insert(utils::u256_to_h256_be(value.present_value()).into());
+                state.insert_from(DictionarySource::Storage, utils::u256_to_h256_be(*slot).into());
+                state.insert_from(
+                    DictionarySource::Storage,
+                    utils::u256_to_h256_be(value.present_value()).into(),
+                );
             }
         }
 
@@ -150,7 +252,7 @@ pub fn collect_state_from_call(
                     state.cache.insert(*address);
 
                     for push_byte in collect_push_bytes(code.bytes().clone()) {
-                        state.insert(push_byte);
+                        state.insert_from(DictionarySource::PushBytes, push_byte);
                     }
                 }
             }
@@ -159,14 +261,14 @@ pub fn collect_state_from_call(
         // Insert log topics and data
         for log in logs {
             log.topics.iter().for_each(|topic| {
-                state.insert(topic.0);
+                state.insert_from(DictionarySource::Log, topic.0);
             });
             log.data.0.chunks(32).for_each(|chunk| {
                 let mut buffer: [u8; 32] = [0; 32];
                 let _ = (&mut buffer[..])
                     .write(chunk)
                     .expect("log data chunk was larger than 32 bytes");
-                state.insert(buffer);
+                state.insert_from(DictionarySource::Log, buffer);
             });
         }
     }
@@ -243,3 +345,55 @@ pub fn collect_created_contracts(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{
+        prelude::any,
+        strategy::ValueTree,
+        test_runner::{Config, TestRunner},
+    };
+
+    fn sample(dictionary: &FuzzDictionary, runner: &mut TestRunner) -> Option<[u8; 32]> {
+        let (source, value) = any::<(Index, Index)>().new_tree(runner).unwrap().current();
+        dictionary.sample(source, value)
+    }
+
+    #[test]
+    fn samples_sources_by_weight() {
+        let config = Config { failure_persistence: None, ..Default::default() };
+        let mut runner = TestRunner::new(config);
+        let weights = DictionaryWeights { addresses: 0, balances: 1, ..Default::default() };
+        let mut dictionary = FuzzDictionary::new(Some(weights));
+        dictionary.insert_from(DictionarySource::Address, [1; 32]);
+        dictionary.insert_from(DictionarySource::Balance, [2; 32]);
+        dictionary.insert_from(DictionarySource::Balance, [3; 32]);
+
+        for _ in 0..100 {
+            assert_ne!(sample(&dictionary, &mut runner), Some([1; 32]));
+        }
+
+        // without weights every value can be drawn
+        let mut dictionary = FuzzDictionary::new(None);
+        dictionary.insert_from(DictionarySource::Address, [1; 32]);
+        dictionary.insert_from(DictionarySource::Balance, [2; 32]);
+        assert!((0..100).any(|_| sample(&dictionary, &mut runner) == Some([1; 32])));
+    }
+
+    #[test]
+    fn samples_nothing_if_all_weights_are_zero() {
+        let config = Config { failure_persistence: None, ..Default::default() };
+        let mut runner = TestRunner::new(config);
+        let weights = DictionaryWeights { addresses: 0, balances: 0, ..Default::default() };
+        let mut dictionary = FuzzDictionary::new(Some(weights));
+        dictionary.insert_from(DictionarySource::Address, [1; 32]);
+        dictionary.insert_from(DictionarySource::Balance, [2; 32]);
+        // inserted without a source
+        dictionary.insert([3; 32]);
+
+        for _ in 0..100 {
+            assert_eq!(sample(&dictionary, &mut runner), None);
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use super::{
     identifier::{SingleSignaturesIdentifier, TraceIdentifier},
+    internal::{self, InternalFunction},
     CallTraceArena, RawOrDecodedCall, RawOrDecodedLog, RawOrDecodedReturnData,
 };
 use crate::{
-    abi::{CHEATCODE_ADDRESS, CONSOLE_ABI, HARDHAT_CONSOLE_ABI, HARDHAT_CONSOLE_ADDRESS, HEVM_ABI},
+    abi::{
+        CHEATCODE_ADDRESS, CONSOLE_ABI, HARDHAT_CONSOLE_ABI, HARDHAT_CONSOLE_ADDRESS,
+        HARDHAT_CONSOLE_SELECTOR_PATCHES, HEVM_ABI,
+    },
     decode,
     executor::inspector::DEFAULT_CREATE2_DEPLOYER,
     trace::{node::CallTraceNode, utils},
@@ -14,7 +18,10 @@ use ethers::{
 };
 use foundry_common::{abi::get_indexed_event, SELECTOR_LEN};
 use hashbrown::HashSet;
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 /// Build a new [CallTraceDecoder].
 #[derive(Default)]
@@ -51,6 +58,18 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Set the internal functions of contracts, by `"<artifact>:<contract>"`, to reconstruct the
+    /// internal calls of traces that recorded their jumps.
+    ///
+    /// The functions are shared by the decoders of all test results.
+    pub fn with_internal_functions(
+        mut self,
+        functions: Arc<HashMap<String, Vec<InternalFunction>>>,
+    ) -> Self {
+        self.decoder.internal_functions = functions;
+        self
+    }
+
     /// Build the decoder.
     pub fn build(self) -> CallTraceDecoder {
         self.decoder
@@ -86,6 +105,8 @@ pub struct CallTraceDecoder {
     pub signature_identifier: Option<SingleSignaturesIdentifier>,
     /// Verbosity level
     pub verbosity: u8,
+    /// The internal functions of contracts by `"<artifact>:<contract>"`
+    pub internal_functions: Arc<HashMap<String, Vec<InternalFunction>>>,
 }
 
 impl CallTraceDecoder {
@@ -94,11 +115,18 @@ impl CallTraceDecoder {
     /// The call trace decoder always knows how to decode calls to the cheatcode address, as well
     /// as DSTest-style logs.
     pub fn new() -> Self {
-        let functions = HARDHAT_CONSOLE_ABI
+        let mut functions = HARDHAT_CONSOLE_ABI
             .functions()
             .map(|func| (func.short_signature(), vec![func.clone()]))
             .chain(HEVM_ABI.functions().map(|func| (func.short_signature(), vec![func.clone()])))
             .collect::<BTreeMap<[u8; 4], Vec<Function>>>();
+        // `hardhat/console.log` calls the overloads with `uint` and `int` params by the selectors
+        // of `uint` and `int` instead of `uint256` and `int256`
+        for (hardhat_selector, abigen_selector) in HARDHAT_CONSOLE_SELECTOR_PATCHES.iter() {
+            if let Some(funcs) = functions.get(abigen_selector).cloned() {
+                functions.insert(*hardhat_selector, funcs);
+            }
+        }
 
         Self {
             // TODO: These are the Ethereum precompiles. We should add a way to support precompiles
@@ -189,6 +217,7 @@ impl CallTraceDecoder {
             signature_identifier: None,
             receive_contracts: Default::default(),
             verbosity: u8::default(),
+            internal_functions: Default::default(),
         }
     }
 
@@ -259,6 +288,16 @@ impl CallTraceDecoder {
                 node.trace.label = Some(label);
             }
 
+            // Reconstruct internal calls, the jumps of constructors belong to the creation code
+            if !node.trace.jumps.is_empty() && !node.trace.created() {
+                if let Some(functions) =
+                    node.trace.contract.as_ref().and_then(|c| self.internal_functions.get(c))
+                {
+                    node.internal_calls =
+                        internal::reconstruct(&node.trace.jumps, functions, node.ordering.len());
+                }
+            }
+
             // Decode call
             if let Some(precompile_fn) = self.precompiles.get(&node.trace.address) {
                 node.decode_precompile(precompile_fn, &self.labels);
@@ -30,8 +30,8 @@ use tracing::{trace, warn};
 /// A trace identifier that tries to identify addresses using Etherscan.
 #[derive(Default)]
 pub struct EtherscanIdentifier {
-    /// The Etherscan client
-    client: Option<Arc<etherscan::Client>>,
+    /// The Etherscan clients, one for each configured API key
+    clients: Vec<Arc<etherscan::Client>>,
     /// Tracks whether the API key provides was marked as invalid
     ///
     /// After the first [EtherscanError::InvalidApiKey] this will get set to true, so we can
@@ -47,7 +47,7 @@ impl EtherscanIdentifier {
         if let Some(config) = config.get_etherscan_config_with_chain(chain)? {
             trace!(target: "etherscanidentifier", chain=?config.chain, url=?config.api_url, "using etherscan identifier");
             Ok(Self {
-                client: Some(Arc::new(config.into_client()?)),
+                clients: config.into_clients()?.into_iter().map(Arc::new).collect(),
                 invalid_api_key: Arc::new(Default::default()),
                 contracts: BTreeMap::new(),
                 sources: BTreeMap::new(),
@@ -112,38 +112,40 @@ impl TraceIdentifier for EtherscanIdentifier {
             return Vec::new()
         }
 
-        self.client.as_ref().map_or(Default::default(), |client| {
-            let mut fetcher = EtherscanFetcher::new(
-                Arc::clone(client),
-                Duration::from_secs(1),
-                5,
-                Arc::clone(&self.invalid_api_key),
-            );
+        if self.clients.is_empty() {
+            return Vec::new()
+        }
 
-            for (addr, _) in addresses {
-                if !self.contracts.contains_key(addr) {
-                    fetcher.push(*addr);
-                }
+        let mut fetcher = EtherscanFetcher::new(
+            self.clients.clone(),
+            Duration::from_secs(1),
+            5,
+            Arc::clone(&self.invalid_api_key),
+        );
+
+        for (addr, _) in addresses {
+            if !self.contracts.contains_key(addr) {
+                fetcher.push(*addr);
             }
+        }
 
-            let fut = fetcher
-                .map(|(address, metadata)| {
-                    let label = metadata.contract_name.clone();
-                    let abi = metadata.abi().ok().map(Cow::Owned);
-                    self.contracts.insert(address, metadata);
+        let fut = fetcher
+            .map(|(address, metadata)| {
+                let label = metadata.contract_name.clone();
+                let abi = metadata.abi().ok().map(Cow::Owned);
+                self.contracts.insert(address, metadata);
 
-                    AddressIdentity {
-                        address,
-                        label: Some(label.clone()),
-                        contract: Some(label),
-                        abi,
-                        artifact_id: None,
-                    }
-                })
-                .collect();
+                AddressIdentity {
+                    address,
+                    label: Some(label.clone()),
+                    contract: Some(label),
+                    abi,
+                    artifact_id: None,
+                }
+            })
+            .collect();
 
-            RuntimeOrHandle::new().block_on(fut)
-        })
+        RuntimeOrHandle::new().block_on(fut)
     }
 }
 
@@ -153,9 +155,14 @@ type EtherscanFuture =
 /// A rate limit aware Etherscan client.
 ///
 /// Fetches information about multiple addresses concurrently, while respecting rate limits.
+///
+/// If multiple clients are provided, the fetcher rotates to the next client when the rate limit of
+/// the current one is exceeded and only backs off after all of them were exhausted.
 pub struct EtherscanFetcher {
-    /// The Etherscan client
-    client: Arc<etherscan::Client>,
+    /// The Etherscan clients, one for each API key
+    clients: Vec<Arc<etherscan::Client>>,
+    /// The index of the client currently used for requests
+    current: usize,
     /// The time we wait if we hit the rate limit
     timeout: Duration,
     /// The interval we are currently waiting for before making a new request
@@ -172,13 +179,15 @@ pub struct EtherscanFetcher {
 
 impl EtherscanFetcher {
     pub fn new(
-        client: Arc<etherscan::Client>,
+        clients: Vec<Arc<etherscan::Client>>,
         timeout: Duration,
         concurrency: usize,
         invalid_api_key: Arc<AtomicBool>,
     ) -> Self {
+        assert!(!clients.is_empty(), "at least one etherscan client is required");
         Self {
-            client,
+            clients,
+            current: 0,
             timeout,
             backoff: None,
             concurrency,
@@ -195,7 +204,7 @@ impl EtherscanFetcher {
     fn queue_next_reqs(&mut self) {
         while self.in_progress.len() < self.concurrency {
             if let Some(addr) = self.queue.pop() {
-                let client = Arc::clone(&self.client);
+                let client = Arc::clone(&self.clients[self.current]);
                 trace!(target: "etherscanidentifier", "fetching info for {:?}", addr);
                 self.in_progress.push(Box::pin(async move {
                     let res = client.contract_source_code(addr).await;
@@ -238,7 +247,11 @@ impl Stream for EtherscanFetcher {
                         }
                         Err(EtherscanError::RateLimitExceeded) => {
                             warn!(target: "etherscanidentifier", "rate limit exceeded on attempt");
-                            pin.backoff = Some(tokio::time::interval(pin.timeout));
+                            pin.current = (pin.current + 1) % pin.clients.len();
+                            if pin.current == 0 {
+                                // all keys are exhausted
+                                pin.backoff = Some(tokio::time::interval(pin.timeout));
+                            }
                             pin.queue.push(addr);
                         }
                         Err(EtherscanError::InvalidApiKey) => {
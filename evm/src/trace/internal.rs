@@ -0,0 +1,180 @@
+//! Reconstruction of internal (jump based) function calls from the jumps of a call trace
+
+use ethers::{solc::artifacts::FunctionDebugData, types::U256};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
+
+/// The maximum number of stack words that are recorded for a jump, deeper arguments can't be
+/// accessed by solc either
+pub const MAX_JUMP_STACK: usize = 16;
+
+/// A jump that was executed in a call, recorded at the jump destination
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct JumpStep {
+    /// The program counter of the jump destination
+    pub pc: usize,
+    /// The number of logs and subcalls of the call before the jump, see
+    /// [CallTraceNode::ordering](crate::trace::node::CallTraceNode::ordering)
+    pub position: usize,
+    /// The top of the stack at the jump destination, the topmost word first
+    pub stack: Vec<U256>,
+}
+
+/// An internal function of a contract, see `evm.deployedBytecode.functionDebugData`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InternalFunction {
+    pub name: String,
+    /// The program counter of the first instruction of the function
+    pub entry: usize,
+    pub parameter_slots: usize,
+    pub return_slots: usize,
+}
+
+impl InternalFunction {
+    /// Creates the function from the debug data of solc, e.g. `@transfer_123`
+    ///
+    /// Returns `None` for functions generated by the compiler and for functions without an entry
+    /// point, e.g. if they were always inlined.
+    pub fn from_debug_data(name: &str, data: &FunctionDebugData) -> Option<Self> {
+        let id = data.id?;
+        let name = name.strip_prefix('@').unwrap_or(name);
+        let name = name.strip_suffix(&format!("_{id}")).unwrap_or(name);
+        Some(Self {
+            name: name.to_string(),
+            entry: data.entry_point? as usize,
+            parameter_slots: data.parameter_slots.unwrap_or_default() as usize,
+            return_slots: data.return_slots.unwrap_or_default() as usize,
+        })
+    }
+}
+
+/// An internal function call is entered or exited
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum InternalCall {
+    /// The function was called with the stack words of its parameters, the first one first
+    Enter { position: usize, name: String, args: Vec<U256> },
+    /// The function returned the stack words of its return values, or `None` if it never jumped
+    /// back to its caller, e.g. because it reverted
+    Exit { position: usize, returns: Option<Vec<U256>> },
+}
+
+impl InternalCall {
+    /// The number of logs and subcalls of the call before this event
+    pub fn position(&self) -> usize {
+        match self {
+            InternalCall::Enter { position, .. } | InternalCall::Exit { position, .. } => *position,
+        }
+    }
+}
+
+impl fmt::Display for InternalCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let words = |words: &[U256]| words.iter().map(format_word).collect::<Vec<_>>().join(", ");
+        match self {
+            InternalCall::Enter { name, args, .. } => {
+                write!(f, "[internal] {name}({})", words(args))
+            }
+            InternalCall::Exit { returns: Some(returns), .. } if returns.is_empty() => {
+                write!(f, "()")
+            }
+            InternalCall::Exit { returns: Some(returns), .. } => write!(f, "({})", words(returns)),
+            InternalCall::Exit { returns: None, .. } => write!(f, "[no return]"),
+        }
+    }
+}
+
+/// Formats a stack word as a number if it is small, otherwise as hex
+///
+/// The types of the words are not known, references to memory or calldata are shown as their
+/// offset.
+fn format_word(word: &U256) -> String {
+    if word.bits() <= 64 {
+        word.to_string()
+    } else {
+        format!("{word:#x}")
+    }
+}
+
+/// Reconstructs the internal function calls of a call from its jumps
+///
+/// A jump to the entry point of a function enters it, the return address is the stack word below
+/// its parameters. A jump to the return address of an entered function exits it and all functions
+/// it entered. Functions that are still entered after the last jump are exited at `end`.
+pub fn reconstruct(
+    jumps: &[JumpStep],
+    functions: &[InternalFunction],
+    end: usize,
+) -> Vec<InternalCall> {
+    let entries = functions.iter().map(|func| (func.entry, func)).collect::<HashMap<_, _>>();
+    // (return address, return slots) of the entered functions
+    let mut frames: Vec<(usize, usize)> = vec![];
+    let mut calls = vec![];
+
+    for jump in jumps {
+        if let Some(idx) = frames.iter().rposition(|(return_pc, _)| *return_pc == jump.pc) {
+            for _ in idx + 1..frames.len() {
+                calls.push(InternalCall::Exit { position: jump.position, returns: None });
+            }
+            let (_, return_slots) = frames[idx];
+            let returns = (jump.stack.len() >= return_slots)
+                .then(|| jump.stack[..return_slots].iter().rev().copied().collect());
+            calls.push(InternalCall::Exit { position: jump.position, returns });
+            frames.truncate(idx);
+        } else if let Some(func) = entries.get(&jump.pc) {
+            let return_pc = match jump.stack.get(func.parameter_slots) {
+                Some(word) if word.bits() <= 32 => word.as_usize(),
+                _ => continue,
+            };
+            calls.push(InternalCall::Enter {
+                position: jump.position,
+                name: func.name.clone(),
+                args: jump.stack[..func.parameter_slots].iter().rev().copied().collect(),
+            });
+            frames.push((return_pc, func.return_slots));
+        }
+    }
+
+    for _ in frames {
+        calls.push(InternalCall::Exit { position: end, returns: None });
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_reconstruct_internal_calls() {
+        let func = |name: &str, entry: usize, parameter_slots: usize, return_slots: usize| {
+            InternalFunction { name: name.to_string(), entry, parameter_slots, return_slots }
+        };
+        let functions = vec![func("add", 10, 2, 1), func("check", 20, 1, 0)];
+        let jump = |pc: usize, position: usize, stack: &[u64]| JumpStep {
+            pc,
+            position,
+            stack: stack.iter().map(|word| U256::from(*word)).collect(),
+        };
+        let jumps = vec![
+            // an unrelated jump
+            jump(5, 0, &[1]),
+            // add(1, 2), returns to 100
+            jump(10, 0, &[2, 1, 100]),
+            // check(3), returns to 30
+            jump(20, 1, &[3, 30, 2, 1, 100]),
+            jump(30, 1, &[2, 1, 100]),
+            jump(100, 2, &[3, 7]),
+            // check(0) never returns
+            jump(20, 2, &[0, 200]),
+        ];
+
+        let calls = reconstruct(&jumps, &functions, 3);
+        assert_eq!(calls.len(), 6);
+        assert_eq!(calls[0].to_string(), "[internal] add(1, 2)");
+        assert_eq!(calls[1].to_string(), "[internal] check(3)");
+        assert_eq!(calls[2], InternalCall::Exit { position: 1, returns: Some(vec![]) });
+        assert_eq!(calls[3].to_string(), "(3)");
+        assert_eq!(calls[4].position(), 2);
+        assert_eq!(calls[5], InternalCall::Exit { position: 3, returns: None });
+    }
+}
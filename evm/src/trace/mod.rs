@@ -9,6 +9,7 @@ use ethers::{
 };
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
 use hashbrown::HashMap;
+use internal::{InternalCall, JumpStep};
 use node::CallTraceNode;
 use revm::{opcode, CallContext, Memory, Return, Stack};
 use serde::{Deserialize, Serialize};
@@ -24,6 +25,7 @@ use yansi::{Color, Paint};
 pub mod identifier;
 
 mod decoder;
+pub mod internal;
 pub mod node;
 mod utils;
 
@@ -203,12 +205,36 @@ impl fmt::Display for CallTraceArena {
                 writeln!(writer, "{left}{:#}", node.trace)?;
             }
 
-            // Display logs and subcalls
-            let left_prefix = format!("{child}{BRANCH}");
-            let right_prefix = format!("{child}{PIPE}");
-            for child in &node.ordering {
-                match child {
-                    LogCallOrder::Log(index) => {
+            // Display logs, subcalls and internal calls, the prefixes of the entered internal
+            // calls are stacked on top of the prefix of the call
+            let mut prefixes = vec![child.to_string()];
+            let mut internal_calls = node.internal_calls.iter().peekable();
+            for (position, order) in node.ordering.iter().map(Some).chain([None]).enumerate() {
+                while let Some(call) =
+                    internal_calls.next_if(|call| order.is_none() || call.position() <= position)
+                {
+                    match call {
+                        InternalCall::Enter { .. } => {
+                            let prefix = prefixes.last().expect("no prefix");
+                            writeln!(writer, "{prefix}{BRANCH}{}", Paint::yellow(call))?;
+                            let nested = format!("{prefix}{PIPE}");
+                            prefixes.push(nested);
+                        }
+                        InternalCall::Exit { .. } => {
+                            if prefixes.len() > 1 {
+                                let prefix = prefixes.pop().expect("no prefix");
+                                writeln!(writer, "{prefix}{EDGE}{}{call}", Paint::yellow(RETURN))?;
+                            }
+                        }
+                    }
+                }
+
+                let prefix = prefixes.last().expect("no prefix");
+                let left_prefix = format!("{prefix}{BRANCH}");
+                let right_prefix = format!("{prefix}{PIPE}");
+                match order {
+                    None => {}
+                    Some(LogCallOrder::Log(index)) => {
                         let mut log = String::new();
                         write!(log, "{}", node.logs[*index])?;
 
@@ -222,7 +248,7 @@ impl fmt::Display for CallTraceArena {
                             )
                         })?;
                     }
-                    LogCallOrder::Call(index) => {
+                    Some(LogCallOrder::Call(index)) => {
                         inner(
                             arena,
                             writer,
@@ -464,14 +490,24 @@ pub struct CallTrace {
     /// The return data of the call if this was not a contract creation, otherwise it is the
     /// runtime bytecode of the created contract
     pub output: RawOrDecodedReturnData,
-    /// The gas cost of the call
+    /// The gas cost of the call, after refunds
     pub gas_cost: u64,
+    /// The gas spent by the call, before refunds
+    pub gas_spent: u64,
+    /// The refund counter of the call, including the refunds of all subcalls
+    pub gas_refunded: i64,
+    /// The maximum refund for the gas spent by the call, see
+    /// [EIP-3529](https://eips.ethereum.org/EIPS/eip-3529)
+    pub gas_refund_cap: u64,
     /// The status of the trace's call
     pub status: Return,
     /// call context of the runtime
     pub call_context: Option<CallContext>,
     /// Opcode-level execution steps
     pub steps: Vec<CallTraceStep>,
+    /// The jumps of the call, recorded to reconstruct internal function calls
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jumps: Vec<JumpStep>,
 }
 
 // === impl CallTrace ===
@@ -481,6 +517,12 @@ impl CallTrace {
     pub fn created(&self) -> bool {
         matches!(self.kind, CallKind::Create | CallKind::Create2)
     }
+
+    /// The refund that is applied to the gas spent by the call, the refund counter capped at the
+    /// maximum refund
+    pub fn gas_refund(&self) -> u64 {
+        (self.gas_refunded.max(0) as u64).min(self.gas_refund_cap)
+    }
 }
 
 impl Default for CallTrace {
@@ -497,9 +539,13 @@ impl Default for CallTrace {
             data: Default::default(),
             output: Default::default(),
             gas_cost: Default::default(),
+            gas_spent: Default::default(),
+            gas_refunded: Default::default(),
+            gas_refund_cap: Default::default(),
             status: Return::Continue,
             call_context: Default::default(),
             steps: Default::default(),
+            jumps: Default::default(),
         }
     }
 }
@@ -554,6 +600,15 @@ impl fmt::Display for CallTrace {
             )?;
         }
 
+        // the alternate format includes the gas refund accounting of the call
+        if f.alternate() {
+            let mut details = format!("spent: {}, refund: {}", self.gas_spent, self.gas_refunded);
+            if self.gas_refund() < self.gas_refunded.max(0) as u64 {
+                details.push_str(&format!(", capped at {}", self.gas_refund_cap));
+            }
+            write!(f, " {}", Paint::new(format!("{{{details}}}")).dimmed())?;
+        }
+
         Ok(())
     }
 }
@@ -635,4 +690,22 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn can_display_gas_refunds() {
+        let trace = CallTrace {
+            data: RawOrDecodedCall::Decoded("clear".to_string(), String::new(), vec![]),
+            gas_cost: 8000,
+            gas_spent: 10000,
+            gas_refunded: 4800,
+            gas_refund_cap: 2000,
+            ..Default::default()
+        };
+        assert_eq!(trace.gas_refund(), 2000);
+        assert!(trace.to_string().starts_with("[8000] "));
+        assert!(format!("{trace:#}").contains("{spent: 10000, refund: 4800, capped at 2000}"));
+
+        let trace = CallTrace { gas_refunded: -100, ..trace };
+        assert_eq!(trace.gas_refund(), 0);
+    }
 }
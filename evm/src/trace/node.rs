@@ -2,8 +2,8 @@ use crate::{
     decode,
     executor::CHEATCODE_ADDRESS,
     trace::{
-        utils, utils::decode_cheatcode_outputs, CallTrace, LogCallOrder, RawOrDecodedCall,
-        RawOrDecodedLog, RawOrDecodedReturnData,
+        internal::InternalCall, utils, utils::decode_cheatcode_outputs, CallTrace, LogCallOrder,
+        RawOrDecodedCall, RawOrDecodedLog, RawOrDecodedReturnData,
     },
     CallKind,
 };
@@ -32,6 +32,9 @@ pub struct CallTraceNode {
     pub logs: Vec<RawOrDecodedLog>,
     /// Ordering of child calls and logs
     pub ordering: Vec<LogCallOrder>,
+    /// The internal function calls, reconstructed from the jumps of the call when decoding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub internal_calls: Vec<InternalCall>,
 }
 
 impl CallTraceNode {
@@ -1,12 +1,45 @@
 use ethers::{
     abi::{Abi, FixedBytes, Function},
     prelude::{H256, U256},
+    solc::EvmVersion,
     types::{BigEndianHash, Block, Chain},
 };
 use eyre::ContextCompat;
 use revm::{opcode, spec_opcode_gas, SpecId};
 use std::collections::BTreeMap;
 
+/// Returns the [SpecId] of the EVM version
+///
+/// # Panics
+///
+/// If the EVM version is not supported, see [try_evm_spec]
+pub fn evm_spec(evm: &EvmVersion) -> SpecId {
+    try_evm_spec(evm).expect("Unsupported EVM version")
+}
+
+/// Returns the [SpecId] of the EVM version, or `None` if the EVM version is not supported
+///
+/// The EVM version is matched by name since not every version of `ethers-solc` knows about the
+/// latest hardforks.
+pub fn try_evm_spec(evm: &EvmVersion) -> Option<SpecId> {
+    let spec = match evm.to_string().as_str() {
+        "homestead" => SpecId::HOMESTEAD,
+        "tangerineWhistle" => SpecId::TANGERINE,
+        "spuriousDragon" => SpecId::SPURIOUS_DRAGON,
+        "byzantium" => SpecId::BYZANTIUM,
+        "constantinople" => SpecId::CONSTANTINOPLE,
+        "petersburg" => SpecId::PETERSBURG,
+        "istanbul" => SpecId::ISTANBUL,
+        "berlin" => SpecId::BERLIN,
+        "london" => SpecId::LONDON,
+        "paris" => SpecId::MERGE,
+        // revm has no dedicated spec for shanghai yet, the latest one is the closest
+        "shanghai" => SpecId::LATEST,
+        _ => return None,
+    };
+    Some(spec)
+}
+
 /// Small helper function to convert [U256] into [H256].
 pub fn u256_to_h256_le(u: U256) -> H256 {
     let mut h = H256::default();
@@ -122,3 +155,30 @@ pub fn get_function(
         .cloned()
         .wrap_err(format!("{contract_name} does not have the selector {selector:?}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_map_all_evm_versions() {
+        for (version, spec) in [
+            ("homestead", SpecId::HOMESTEAD),
+            ("tangerineWhistle", SpecId::TANGERINE),
+            ("spuriousDragon", SpecId::SPURIOUS_DRAGON),
+            ("byzantium", SpecId::BYZANTIUM),
+            ("constantinople", SpecId::CONSTANTINOPLE),
+            ("petersburg", SpecId::PETERSBURG),
+            ("istanbul", SpecId::ISTANBUL),
+            ("berlin", SpecId::BERLIN),
+            ("london", SpecId::LONDON),
+            ("paris", SpecId::MERGE),
+            ("shanghai", SpecId::LATEST),
+        ] {
+            // versions unknown to this version of `ethers-solc` can't be configured anyway
+            if let Ok(version) = version.parse::<EvmVersion>() {
+                assert_eq!(try_evm_spec(&version), Some(spec), "{version}");
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::result::TestResult;
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Counts the failed tests of a run and stops it once `max_failures` tests failed
+///
+/// All clones share the same counter. Once stopped, no new tests are started and running fuzz
+/// campaigns are cancelled, tests that already completed are still reported.
+#[derive(Debug, Clone, Default)]
+pub struct FailFast {
+    /// Unlimited if `None`
+    max_failures: Option<usize>,
+    failures: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FailFast {
+    pub fn new(max_failures: Option<usize>) -> Self {
+        Self { max_failures, ..Default::default() }
+    }
+
+    /// Counts the result if it failed
+    pub fn record(&self, result: &TestResult) {
+        if result.success {
+            return
+        }
+        let failures = self.failures.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.max_failures.map(|max| failures >= max).unwrap_or_default() {
+            self.stopped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns true if enough tests failed to stop the run
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    /// The number of failed tests so far
+    pub fn failures(&self) -> usize {
+        self.failures.load(Ordering::SeqCst)
+    }
+
+    pub fn max_failures(&self) -> Option<usize> {
+        self.max_failures
+    }
+
+    /// The flag that cancels fuzz campaigns once the run is stopped
+    pub fn cancellation(&self) -> Arc<AtomicBool> {
+        self.stopped.clone()
+    }
+}
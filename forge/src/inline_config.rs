@@ -0,0 +1,284 @@
+//! Per test configuration, set in comments right above the test function:
+//!
+//! ```solidity
+//! /// forge-config: default.test_timeout = 30
+//! function testSlow() public {}
+//! ```
+//!
+//! Supported keys are `test_timeout` and `auto_advance_time`.
+
+use foundry_config::AutoAdvanceTime;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// The marker of inline config comments
+pub const INLINE_CONFIG_PREFIX: &str = "forge-config:";
+
+/// The profile that applies regardless of the selected profile
+const DEFAULT_PROFILE: &str = "default";
+
+/// Returns the inline `test_timeout` (in seconds) of the functions of `contract` in `source`,
+/// mapped by function name.
+///
+/// A value set for `profile` takes precedence over one set for the `default` profile.
+pub fn parse_test_timeouts(source: &str, contract: &str, profile: &str) -> BTreeMap<String, u64> {
+    parse_inline_values(source, contract, profile, "test_timeout")
+}
+
+/// Returns the inline `auto_advance_time` of the functions of `contract` in `source`, mapped by
+/// function name.
+///
+/// A value set for `profile` takes precedence over one set for the `default` profile.
+pub fn parse_auto_advance_times(
+    source: &str,
+    contract: &str,
+    profile: &str,
+) -> BTreeMap<String, AutoAdvanceTime> {
+    parse_inline_values(source, contract, profile, "auto_advance_time")
+}
+
+/// Returns the inline values of `key` of the functions of `contract` in `source`, values that
+/// can't be parsed are ignored
+///
+/// Config comments only apply to the declaration right below them, any other code in between
+/// discards them.
+fn parse_inline_values<T: FromStr>(
+    source: &str,
+    contract: &str,
+    profile: &str,
+    key: &str,
+) -> BTreeMap<String, T> {
+    let mut values = BTreeMap::new();
+    // whether the innermost contract, library or interface declaration is `contract`
+    let mut in_contract = false;
+    let mut depth = 0usize;
+    // (default profile value, selected profile value)
+    let mut pending: (Option<T>, Option<T>) = (None, None);
+    // the keyword of the declaration whose name is next, with the config comments above it
+    let mut declaration: Option<(&str, (Option<T>, Option<T>))> = None;
+
+    for segment in segments(source) {
+        let code = match segment {
+            Segment::Comment(comment) => {
+                let configs =
+                    comment.lines().filter_map(|line| line.split_once(INLINE_CONFIG_PREFIX));
+                for (_, config) in configs {
+                    if let Some((k, value)) = config.split_once('=') {
+                        let value = value
+                            .trim()
+                            .trim_end_matches("*/")
+                            .trim()
+                            .trim_matches(|c| c == '"' || c == '\'')
+                            .parse::<T>()
+                            .ok();
+                        match k.trim().split_once('.') {
+                            Some((p, k)) if p == profile && k == key => pending.1 = value,
+                            Some((DEFAULT_PROFILE, k)) if k == key => pending.0 = value,
+                            _ => {}
+                        }
+                    }
+                }
+                continue
+            }
+            Segment::Code(code) => code,
+        };
+
+        for token in tokens(code) {
+            // config comments are discarded by any code that isn't a declaration keyword
+            let above = std::mem::take(&mut pending);
+            match (declaration.take(), token) {
+                (_, "{") => depth += 1,
+                (_, "}") => depth = depth.saturating_sub(1),
+                (Some(("function", config)), name) if is_identifier(name) => {
+                    // functions of `contract` are declared right inside of its body
+                    if depth == 1 && in_contract {
+                        if let Some(value) = config.1.or(config.0) {
+                            values.insert(name.to_string(), value);
+                        }
+                    }
+                }
+                (Some(_), name) if is_identifier(name) => {
+                    if depth == 0 {
+                        in_contract = name == contract;
+                    }
+                }
+                (_, keyword @ ("contract" | "library" | "interface" | "function")) => {
+                    declaration = Some((keyword, above))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    values
+}
+
+/// A part of a source file
+enum Segment<'a> {
+    /// A line or block comment, including its delimiters
+    Comment(&'a str),
+    /// Code between comments and string literals
+    Code(&'a str),
+}
+
+/// Splits `source` into comments and code, string literals are skipped
+fn segments(source: &str) -> Vec<Segment<'_>> {
+    let bytes = source.as_bytes();
+    let mut segments = Vec::new();
+    let (mut start, mut i) = (0, 0);
+    while i < bytes.len() {
+        let (end, comment) = match (bytes[i], bytes.get(i + 1)) {
+            (b'/', Some(b'/')) => (source[i..].find('\n').map_or(bytes.len(), |n| i + n), true),
+            (b'/', Some(b'*')) => {
+                (source[i + 2..].find("*/").map_or(bytes.len(), |n| i + n + 4), true)
+            }
+            (quote @ (b'"' | b'\''), _) => {
+                let mut end = i + 1;
+                while end < bytes.len() && bytes[end] != quote {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+                ((end + 1).min(bytes.len()), false)
+            }
+            _ => {
+                i += 1;
+                continue
+            }
+        };
+        if start < i {
+            segments.push(Segment::Code(&source[start..i]));
+        }
+        if comment {
+            segments.push(Segment::Comment(&source[i..end]));
+        }
+        start = end;
+        i = end;
+    }
+    if start < bytes.len() {
+        segments.push(Segment::Code(&source[start..]));
+    }
+    segments
+}
+
+/// Splits `code` into identifiers and single punctuation characters
+fn tokens(code: &str) -> impl Iterator<Item = &str> {
+    let mut rest = code;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start();
+        let first = rest.chars().next()?;
+        let len = if is_identifier_char(first) {
+            rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len())
+        } else {
+            first.len_utf8()
+        };
+        let (token, tail) = rest.split_at(len);
+        rest = tail;
+        Some(token)
+    })
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn is_identifier(token: &str) -> bool {
+    token.starts_with(is_identifier_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_test_timeouts() {
+        let source = r#"
+contract Other {
+    /// forge-config: default.test_timeout = 1
+    function testA() public {}
+}
+
+abstract contract Target is Other {
+    /// forge-config: default.test_timeout = 5
+    function testA() public {}
+
+    /// @dev slow
+    /// forge-config: default.test_timeout = 5
+    /// forge-config: ci.test_timeout = 50
+    function testB() public {}
+
+    function testC() public {}
+}
+"#;
+        let timeouts = parse_test_timeouts(source, "Target", "ci");
+        assert_eq!(
+            timeouts,
+            BTreeMap::from([("testA".to_string(), 5), ("testB".to_string(), 50)])
+        );
+
+        let timeouts = parse_test_timeouts(source, "Other", "default");
+        assert_eq!(timeouts, BTreeMap::from([("testA".to_string(), 1)]));
+    }
+
+    #[test]
+    fn parses_auto_advance_times() {
+        let source = r#"
+contract Target {
+    /// forge-config: default.auto_advance_time = "12s"
+    function testA() public {}
+
+    /// forge-config: default.test_timeout = 5
+    /// forge-config: ci.auto_advance_time = "2m"
+    function testB() public {}
+
+    /// forge-config: default.auto_advance_time = "soon"
+    function testC() public {}
+}
+"#;
+        let times = parse_auto_advance_times(source, "Target", "ci");
+        assert_eq!(
+            times,
+            BTreeMap::from([
+                ("testA".to_string(), AutoAdvanceTime::from_secs(12)),
+                ("testB".to_string(), AutoAdvanceTime::from_secs(120))
+            ])
+        );
+        assert!(parse_test_timeouts(source, "Target", "ci").contains_key("testB"));
+    }
+
+    #[test]
+    fn applies_config_to_the_declaration_below() {
+        let source = r#"
+contract Target {
+    /// forge-config: default.test_timeout = 1
+    modifier slow() { _; }
+
+    /// forge-config: default.test_timeout = 2
+    uint256 internal x = 1;
+
+    /// forge-config: default.test_timeout = 3
+    event Slow();
+
+    function testA() public slow {}
+
+    string internal s = "} /* forge-config: default.test_timeout = 4 */ {";
+
+    function testB() public {}
+
+    /** forge-config: default.test_timeout = 5 */ function testC() public {}
+} library Lib { /// forge-config: default.test_timeout = 6
+    function testA() public {}
+}
+
+interface Target2 {
+    /// forge-config: default.test_timeout = 7
+    function testB() external;
+}
+
+/// forge-config: default.test_timeout = 8
+function testD() {}
+"#;
+        let timeouts = parse_test_timeouts(source, "Target", "default");
+        assert_eq!(timeouts, BTreeMap::from([("testC".to_string(), 5)]));
+
+        let timeouts = parse_test_timeouts(source, "Lib", "default");
+        assert_eq!(timeouts, BTreeMap::from([("testA".to_string(), 6)]));
+    }
+}
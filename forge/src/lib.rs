@@ -11,6 +11,16 @@ pub mod coverage;
 mod runner;
 pub use runner::ContractRunner;
 
+/// Per test configuration in source comments
+pub mod inline_config;
+
+/// Stops test runs after a number of failures
+mod fail_fast;
+pub use fail_fast::FailFast;
+
+/// Source locations of failed cheatcodes
+pub mod source_locator;
+
 /// Forge test runners for multiple contracts
 mod multi_runner;
 pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder};
@@ -30,6 +40,12 @@ pub struct TestOptions {
     pub fuzz: foundry_config::FuzzConfig,
     /// The invariant test configuration
     pub invariant: foundry_config::InvariantConfig,
+    /// The number of seconds after which a test execution is aborted
+    pub test_timeout: Option<u64>,
+    /// The number of seconds the block timestamp advances by with every top-level call of a test
+    pub auto_advance_time: Option<u64>,
+    /// Whether to fail tests that mutate the backend beyond their own call
+    pub strict_isolation: bool,
 }
 
 impl TestOptions {
@@ -1,22 +1,41 @@
-use crate::{result::SuiteResult, ContractRunner, TestFilter, TestOptions};
+use crate::{
+    inline_config::{parse_auto_advance_times, parse_test_timeouts},
+    result::SuiteResult,
+    source_locator::SourceLocator,
+    ContractRunner, FailFast, TestFilter, TestOptions,
+};
 use ethers::{
     abi::Abi,
-    prelude::{artifacts::CompactContractBytecode, ArtifactId, ArtifactOutput},
+    prelude::{
+        artifacts::{CompactContractBytecode, Libraries},
+        ArtifactId, ArtifactOutput,
+    },
     solc::{contracts::ArtifactContracts, Artifact, ProjectCompileOutput},
     types::{Address, Bytes, U256},
 };
 use eyre::Result;
 use foundry_common::{ContractsByArtifact, TestFunctionExt};
+use foundry_config::Config;
 use foundry_evm::{
     executor::{
-        backend::Backend, fork::CreateFork, inspector::CheatsConfig, opts::EvmOpts, Executor,
-        ExecutorBuilder, SpecId,
+        backend::Backend,
+        fork::CreateFork,
+        inspector::{CheatsConfig, GasOverrides, InspectorPluginFactory},
+        opts::EvmOpts,
+        Executor, ExecutorBuilder, SpecId,
     },
-    revm,
+    fuzz::invariant::checkpoint::InvariantCheckpoints,
+    revm::{self, AccountInfo, Bytecode},
+    trace::identifier::SingleSignaturesIdentifier,
 };
 use foundry_utils::PostLinkInput;
 use rayon::prelude::*;
-use std::{collections::BTreeMap, path::Path, sync::mpsc::Sender};
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{mpsc::Sender, Arc},
+    time::Duration,
+};
 
 pub type DeployableContracts = BTreeMap<ArtifactId, (Abi, Bytes, Vec<Bytes>)>;
 
@@ -48,6 +67,20 @@ pub struct MultiContractRunner {
     pub coverage: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: TestOptions,
+    /// Where the progress of invariant campaigns is persisted
+    pub invariant_checkpoints: Option<InvariantCheckpoints>,
+    /// The custom gas schedule
+    pub gas_overrides: GasOverrides,
+    /// The inspectors provided by downstream crates
+    pub inspector_plugins: Vec<Arc<dyn InspectorPluginFactory>>,
+    /// Stops the run once enough tests failed
+    pub fail_fast: FailFast,
+    /// Resolves failed cheatcodes to the source location of their call
+    pub source_locator: Option<SourceLocator>,
+    /// Identifies custom errors of reverted calls that are not part of the project
+    pub signature_identifier: Option<SingleSignaturesIdentifier>,
+    /// The runtime code of the configured libraries by their address, set if there's no fork
+    pub library_code: BTreeMap<Address, Bytes>,
 }
 
 impl MultiContractRunner {
@@ -59,8 +92,12 @@ impl MultiContractRunner {
                 filter.matches_path(id.source.to_string_lossy()) &&
                     filter.matches_contract(&id.name)
             })
-            .flat_map(|(_, (abi, _, _))| {
-                abi.functions().filter(|func| filter.matches_test(func.signature()))
+            .flat_map(|(id, (abi, _, _))| {
+                let identifier = id.identifier();
+                abi.functions().filter(move |func| {
+                    filter.matches_test(func.signature()) &&
+                        filter.matches_contract_test(&identifier, &func.signature())
+                })
             })
             .count()
     }
@@ -89,14 +126,16 @@ impl MultiContractRunner {
                 filter.matches_path(id.source.to_string_lossy()) &&
                     filter.matches_contract(&id.name)
             })
-            .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
+            .filter(|(id, (abi, _, _))| has_matching_test(filter, id, abi))
             .map(|(id, (abi, _, _))| {
                 let source = id.source.as_path().display().to_string();
                 let name = id.name.clone();
+                let identifier = id.identifier();
                 let tests = abi
                     .functions()
                     .filter(|func| func.name.is_test())
                     .filter(|func| filter.matches_test(func.signature()))
+                    .filter(|func| filter.matches_contract_test(&identifier, &func.signature()))
                     .map(|func| func.name.clone())
                     .collect::<Vec<_>>();
 
@@ -122,7 +161,11 @@ impl MultiContractRunner {
     ) -> Result<BTreeMap<String, SuiteResult>> {
         tracing::trace!("start all tests");
 
-        let db = Backend::spawn(self.fork.take());
+        let mut db = Backend::spawn(self.fork.take());
+        for (address, code) in &self.library_code {
+            let code = Bytecode::new_raw(code.0.clone()).to_checked();
+            db.insert_account_info(*address, AccountInfo::new(U256::zero(), 1, code));
+        }
 
         let results =
             // the db backend that serves all the data, each contract gets its own instance
@@ -134,9 +177,9 @@ impl MultiContractRunner {
                     filter.matches_path(id.source.to_string_lossy()) &&
                         filter.matches_contract(&id.name)
                 })
-                .filter(|(_, (abi, _, _))| {
-                    abi.functions().any(|func| filter.matches_test(&func.name))
-                })
+                .filter(|(id, (abi, _, _))| has_matching_test(filter, id, abi))
+                // no new test contracts are started once enough tests failed
+                .filter(|_| !self.fail_fast.is_stopped())
                 .map(|(id, (abi, deploy_code, libs))| {
                     let executor = ExecutorBuilder::default()
                         .with_cheatcodes(self.cheats_config.clone())
@@ -144,7 +187,12 @@ impl MultiContractRunner {
                         .with_spec(self.evm_spec)
                         .with_gas_limit(self.evm_opts.gas_limit())
                         .set_tracing(self.evm_opts.verbosity >= 3)
+                        .set_decode_internal(self.evm_opts.decode_internal)
                         .set_coverage(self.coverage)
+                        .with_timeout(test_options.test_timeout.map(Duration::from_secs))
+                        .with_gas_overrides(self.gas_overrides.clone())
+                        .with_inspector_plugins(self.inspector_plugins.iter().cloned())
+                        .set_isolate(self.evm_opts.isolate)
                         .build(db.clone());
                     let identifier = id.identifier();
                     tracing::trace!(contract= ?identifier, "start executing all tests in contract");
@@ -175,23 +223,22 @@ impl MultiContractRunner {
         Ok(results)
     }
 
-    // The _name field is unused because we only want it for tracing
     #[tracing::instrument(
         name = "contract",
         skip_all,
         err,
-        fields(name = %_name)
+        fields(name = %name)
     )]
     fn run_tests(
         &self,
-        _name: &str,
+        name: &str,
         contract: &Abi,
         executor: Executor,
         deploy_code: Bytes,
         libs: &[Bytes],
         (filter, test_options): (&impl TestFilter, TestOptions),
     ) -> Result<SuiteResult> {
-        let runner = ContractRunner::new(
+        let mut runner = ContractRunner::new(
             executor,
             contract,
             deploy_code,
@@ -200,7 +247,61 @@ impl MultiContractRunner {
             self.errors.as_ref(),
             libs,
         );
-        runner.run_tests(filter, test_options, Some(&self.known_contracts))
+        if let Some(source) =
+            self.source_paths.get(name).and_then(|path| std::fs::read_to_string(path).ok())
+        {
+            let contract_name = name.rsplit(':').next().unwrap_or(name);
+            let profile = Config::selected_profile();
+            runner.test_timeouts =
+                parse_test_timeouts(&source, contract_name, profile.as_str().as_str());
+            runner.auto_advance_times = parse_auto_advance_times(
+                &source,
+                contract_name,
+                profile.as_str().as_str(),
+            )
+            .into_iter()
+            .map(|(name, time)| (name, time.secs()))
+            .collect();
+        }
+        runner.invariant_checkpoint =
+            self.invariant_checkpoints.as_ref().map(|checkpoints| checkpoints.file(name));
+        runner.fail_fast = self.fail_fast.clone();
+        runner.source_locator = self.source_locator.as_ref();
+        runner.signature_identifier = self.signature_identifier.as_ref();
+        let filter = ContractTestFilter { filter, contract_id: name };
+        runner.run_tests(&filter, test_options, Some(&self.known_contracts))
+    }
+}
+
+/// Returns whether the contract has any test that matches the filter
+fn has_matching_test(filter: &impl TestFilter, id: &ArtifactId, abi: &Abi) -> bool {
+    let identifier = id.identifier();
+    abi.functions().any(|func| {
+        filter.matches_test(&func.name) &&
+            filter.matches_contract_test(&identifier, &func.signature())
+    })
+}
+
+/// A [TestFilter] for the tests of a single contract that also applies
+/// [TestFilter::matches_contract_test()] when matching tests
+struct ContractTestFilter<'a, F> {
+    filter: &'a F,
+    contract_id: &'a str,
+}
+
+impl<F: TestFilter> TestFilter for ContractTestFilter<'_, F> {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        let test_name = test_name.as_ref();
+        self.filter.matches_test(test_name) &&
+            self.filter.matches_contract_test(self.contract_id, test_name)
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        self.filter.matches_contract(contract_name)
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        self.filter.matches_path(path)
     }
 }
 
@@ -222,6 +323,23 @@ pub struct MultiContractRunnerBuilder {
     pub coverage: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: Option<TestOptions>,
+    /// Where the progress of invariant campaigns is persisted
+    pub invariant_checkpoints: Option<InvariantCheckpoints>,
+    /// Libraries that are already deployed on the fork, all other libraries are deployed by the
+    /// sender
+    ///
+    /// Without a fork, their code is placed at their addresses.
+    pub libraries: Libraries,
+    /// Inspectors provided by downstream crates, in addition to the configured ones
+    pub inspector_plugins: Vec<Arc<dyn InspectorPluginFactory>>,
+    /// Contracts that are not part of the solc output, like Vyper contracts
+    pub extra_contracts: Vec<(ArtifactId, CompactContractBytecode)>,
+    /// The number of failed tests after which the run is stopped, unlimited if `None`
+    pub max_failures: Option<usize>,
+    /// Resolves failed cheatcodes to the source location of their call
+    pub source_locator: Option<SourceLocator>,
+    /// Identifies custom errors of reverted calls that are not part of the project
+    pub signature_identifier: Option<SingleSignaturesIdentifier>,
 }
 
 impl MultiContractRunnerBuilder {
@@ -243,6 +361,7 @@ impl MultiContractRunnerBuilder {
             .with_stripped_file_prefixes(&root)
             .into_artifacts()
             .map(|(i, c)| (i, c.into_contract_bytecode()))
+            .chain(self.extra_contracts)
             .collect::<Vec<(ArtifactId, CompactContractBytecode)>>();
 
         let mut known_contracts = ContractsByArtifact::default();
@@ -254,10 +373,17 @@ impl MultiContractRunnerBuilder {
         // create a mapping of name => (abi, deployment code, Vec<library deployment code>)
         let mut deployable_contracts = DeployableContracts::default();
 
+        // the configured libraries only exist on the fork
+        let (deployed_libraries, configured_libraries) = if self.fork.is_some() {
+            (self.libraries, Libraries::default())
+        } else {
+            (Libraries::default(), self.libraries)
+        };
+
         foundry_utils::link_with_nonce_or_address(
             ArtifactContracts::from_iter(contracts),
             &mut known_contracts,
-            Default::default(),
+            deployed_libraries,
             evm_opts.sender,
             U256::one(),
             &mut deployable_contracts,
@@ -279,7 +405,8 @@ impl MultiContractRunnerBuilder {
                         return Ok(())
                     };
 
-                let abi = contract.abi.expect("We should have an abi by now");
+                // Yul objects don't have an abi
+                let abi = contract.abi.unwrap_or_default();
                 // if it's a test, add it to deployable contracts
                 if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
                     abi.functions()
@@ -307,6 +434,10 @@ impl MultiContractRunnerBuilder {
             },
         )?;
 
+        let library_code = library_code(&configured_libraries, &known_contracts)?;
+        let gas_overrides = evm_opts.gas_overrides()?;
+        let mut inspector_plugins = evm_opts.inspector_plugins()?;
+        inspector_plugins.extend(self.inspector_plugins);
         let execution_info = known_contracts.flatten();
         Ok(MultiContractRunner {
             contracts: deployable_contracts,
@@ -321,6 +452,13 @@ impl MultiContractRunnerBuilder {
             cheats_config: self.cheats_config.unwrap_or_default(),
             coverage: self.coverage,
             test_options: self.test_options.unwrap_or_default(),
+            invariant_checkpoints: self.invariant_checkpoints,
+            gas_overrides,
+            inspector_plugins,
+            fail_fast: FailFast::new(self.max_failures),
+            source_locator: self.source_locator,
+            signature_identifier: self.signature_identifier,
+            library_code,
         })
     }
 
@@ -360,9 +498,92 @@ impl MultiContractRunnerBuilder {
         self
     }
 
+    #[must_use]
+    pub fn with_invariant_checkpoints(mut self, checkpoints: Option<InvariantCheckpoints>) -> Self {
+        self.invariant_checkpoints = checkpoints;
+        self
+    }
+
+    #[must_use]
+    pub fn with_libraries(mut self, libraries: Libraries) -> Self {
+        self.libraries = libraries;
+        self
+    }
+
+    /// Adds contracts that were compiled outside of the solc pipeline
+    #[must_use]
+    pub fn with_extra_contracts(
+        mut self,
+        contracts: impl IntoIterator<Item = (ArtifactId, CompactContractBytecode)>,
+    ) -> Self {
+        self.extra_contracts.extend(contracts);
+        self
+    }
+
+    /// Stops the run once the given number of tests failed
+    #[must_use]
+    pub fn with_max_failures(mut self, max_failures: Option<usize>) -> Self {
+        self.max_failures = max_failures;
+        self
+    }
+
+    /// Adds the source location of the call to the reason of tests that failed in a cheatcode
+    #[must_use]
+    pub fn with_source_locator(mut self, source_locator: SourceLocator) -> Self {
+        self.source_locator = Some(source_locator);
+        self
+    }
+
+    /// Identifies the custom errors of reverted calls that are not part of the project by their
+    /// selector
+    #[must_use]
+    pub fn with_signature_identifier(mut self, identifier: SingleSignaturesIdentifier) -> Self {
+        self.signature_identifier = Some(identifier);
+        self
+    }
+
     #[must_use]
     pub fn set_coverage(mut self, enable: bool) -> Self {
         self.coverage = enable;
         self
     }
+
+    #[must_use]
+    pub fn with_inspector_plugin(mut self, plugin: Arc<dyn InspectorPluginFactory>) -> Self {
+        self.inspector_plugins.push(plugin);
+        self
+    }
+}
+
+/// Returns the runtime code of the configured libraries that were compiled, by their address
+///
+/// Libraries are linked against their configured address by solc, so without a fork their code
+/// has to be placed there.
+fn library_code(
+    libraries: &Libraries,
+    known_contracts: &ContractsByArtifact,
+) -> Result<BTreeMap<Address, Bytes>> {
+    let mut library_code = BTreeMap::new();
+    for (file, libs) in &libraries.libs {
+        for (name, address) in libs {
+            let address: Address = address
+                .parse()
+                .map_err(|_| eyre::eyre!("Invalid address of library {}:{name}", file.display()))?;
+            let code = known_contracts
+                .iter()
+                .find(|(id, _)| id.source == *file && id.name == *name)
+                .map(|(_, (_, code))| code);
+            let mut code = match code {
+                Some(code) => code.clone(),
+                None => continue,
+            };
+            // the call protection of libraries compares the address with the first pushed value,
+            // which is set to the address of the library when it's deployed
+            if code.len() > 20 && code[0] == 0x73 {
+                code[1..21].copy_from_slice(address.as_bytes());
+            }
+            library_code.insert(address, code.into());
+        }
+    }
+    Ok(library_code)
 }
@@ -4,7 +4,7 @@ use crate::Address;
 use ethers::prelude::Log;
 use foundry_evm::{
     coverage::HitMaps,
-    fuzz::{CounterExample, FuzzedCases},
+    fuzz::{invariant::InvariantHandlerStats, CounterExample, FuzzedCases},
     trace::Traces,
 };
 use serde::{Deserialize, Serialize};
@@ -15,6 +15,8 @@ use std::{collections::BTreeMap, fmt, time::Duration};
 pub struct SuiteResult {
     /// Total duration of the test run for this block of tests
     pub duration: Duration,
+    /// Duration of the `setUp` function shared by all tests of the contract
+    pub setup_duration: Duration,
     /// Individual test results. `test method name -> TestResult`
     pub test_results: BTreeMap<String, TestResult>,
     /// Warnings
@@ -24,10 +26,11 @@ pub struct SuiteResult {
 impl SuiteResult {
     pub fn new(
         duration: Duration,
+        setup_duration: Duration,
         test_results: BTreeMap<String, TestResult>,
         warnings: Vec<String>,
     ) -> Self {
-        Self { duration, test_results, warnings }
+        Self { duration, setup_duration, test_results, warnings }
     }
 
     /// Iterator over all succeeding tests and their names
@@ -90,6 +93,10 @@ pub struct TestResult {
 
     /// Labeled addresses
     pub labeled_addresses: BTreeMap<Address, String>,
+
+    /// Wall-clock time of the test execution, excluding `setUp`
+    #[serde(default)]
+    pub duration: Duration,
 }
 
 impl TestResult {
@@ -145,8 +152,9 @@ pub enum TestKind {
     Standard(u64),
     /// A solidity fuzz test, that stores all test cases
     Fuzz(FuzzedCases),
-    /// A solidity invariant test, that stores all test cases
-    Invariant(Vec<FuzzedCases>, usize),
+    /// A solidity invariant test, that stores all test cases, the number of reverts and the
+    /// calls of every handler function
+    Invariant(Vec<FuzzedCases>, usize, InvariantHandlerStats),
 }
 
 impl TestKind {
@@ -159,7 +167,7 @@ impl TestKind {
                 median_gas: fuzzed.median_gas(false),
                 mean_gas: fuzzed.mean_gas(false),
             },
-            TestKind::Invariant(fuzzed, reverts) => TestKindReport::Invariant {
+            TestKind::Invariant(fuzzed, reverts, _) => TestKindReport::Invariant {
                 runs: fuzzed.len(),
                 calls: fuzzed.iter().map(|sequence| sequence.cases().len()).sum(),
                 reverts: *reverts,
@@ -1,31 +1,46 @@
 use crate::{
     result::{SuiteResult, TestKind, TestResult, TestSetup},
-    TestFilter, TestOptions,
+    source_locator::SourceLocator,
+    FailFast, TestFilter, TestOptions,
 };
 use ethers::{
-    abi::{Abi, Function},
+    abi::{Abi, Function, ParamType, Token},
+    solc::utils::RuntimeOrHandle,
     types::{Address, Bytes, U256},
 };
 use eyre::{Result, WrapErr};
 use foundry_common::{
     contracts::{ContractsByAddress, ContractsByArtifact},
-    TestFunctionExt,
+    TestFunctionExt, SELECTOR_LEN,
 };
+use foundry_config::FuzzConfig;
 use foundry_evm::{
-    decode::decode_console_logs,
-    executor::{CallResult, DeployResult, EvmError, ExecutionErr, Executor},
+    decode::{
+        decode_console_logs, decode_error, decode_revert, is_generic_revert_reason,
+        is_unknown_custom_error,
+    },
+    executor::{
+        backend::IsolationSnapshot,
+        inspector::{is_timeout_reason, CheatcodeFailure},
+        CallResult, DeployResult, EvmError, ExecutionErr, Executor,
+    },
     fuzz::{
         invariant::{
-            InvariantContract, InvariantExecutor, InvariantFuzzError, InvariantFuzzTestResult,
+            checkpoint::CheckpointFile, InvariantContract, InvariantExecutor, InvariantFuzzError,
+            InvariantFuzzTestResult,
         },
-        FuzzedExecutor,
+        strategies::MAX_ARRAY_LEN,
+        FuzzFixtures, FuzzedExecutor,
     },
-    trace::{load_contracts, TraceKind},
+    trace::{identifier::SingleSignaturesIdentifier, load_contracts, TraceKind},
     CALLER,
 };
 use proptest::test_runner::{TestError, TestRunner};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::{collections::BTreeMap, time::Instant};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
 use tracing::{error, trace};
 
 /// A type that executes all tests of a contract
@@ -47,6 +62,20 @@ pub struct ContractRunner<'a> {
     pub initial_balance: U256,
     /// The address which will be used as the `from` field in all EVM calls
     pub sender: Address,
+    /// Inline `test_timeout`s in seconds, by test function name
+    pub test_timeouts: BTreeMap<String, u64>,
+    /// Inline `auto_advance_time`s in seconds, by test function name
+    pub auto_advance_times: BTreeMap<String, u64>,
+    /// The file the progress of the invariant campaign is persisted in
+    pub invariant_checkpoint: Option<CheckpointFile>,
+    /// The state of the backend after `setUp`, tests must not mutate it in strict isolation mode
+    pub isolation_snapshot: Option<IsolationSnapshot>,
+    /// Stops starting tests once enough tests of the run failed
+    pub fail_fast: FailFast,
+    /// Resolves failed cheatcodes to the source location of their call
+    pub source_locator: Option<&'a SourceLocator>,
+    /// Identifies custom errors of reverted calls that are not part of the project
+    pub signature_identifier: Option<&'a SingleSignaturesIdentifier>,
 }
 
 impl<'a> ContractRunner<'a> {
@@ -68,13 +97,77 @@ impl<'a> ContractRunner<'a> {
             sender: sender.unwrap_or_default(),
             errors,
             predeploy_libs,
+            test_timeouts: Default::default(),
+            auto_advance_times: Default::default(),
+            invariant_checkpoint: None,
+            isolation_snapshot: None,
+            fail_fast: Default::default(),
+            source_locator: None,
+            signature_identifier: None,
+        }
+    }
+
+    /// Appends the source location of the cheatcode call to the revert reason, if the revert was
+    /// caused by a failed cheatcode
+    fn with_cheatcode_location(
+        &self,
+        reason: String,
+        failure: Option<&CheatcodeFailure>,
+    ) -> String {
+        let location = self
+            .source_locator
+            .zip(failure)
+            .and_then(|(locator, failure)| locator.locate_cheatcode(failure));
+        match location {
+            Some(location) => format!("{reason} (at {location})"),
+            None => reason,
+        }
+    }
+
+    /// Replaces a generic revert reason of a test with the reason of the deepest reverted call
+    /// that can be decoded, e.g. the revert string of an external contract whose revert data was
+    /// not bubbled up
+    fn with_deepest_revert_reason(
+        &self,
+        reason: String,
+        reverted_calls: &[(Address, impl AsRef<[u8]>)],
+    ) -> String {
+        if !is_generic_revert_reason(&reason) {
+            return reason
+        }
+        reverted_calls
+            .iter()
+            .find_map(|(address, data)| {
+                let deepest = self.decode_revert_data(data.as_ref())?;
+                Some(format!("{deepest} (reverted in {address:?})"))
+            })
+            .unwrap_or(reason)
+    }
+
+    /// Decodes the revert data of a call, custom errors that are not part of the project are
+    /// identified by their selector
+    fn decode_revert_data(&self, data: &[u8]) -> Option<String> {
+        let decoded = decode_revert(data, self.errors, None)
+            .ok()
+            .filter(|reason| !is_generic_revert_reason(reason));
+        if !is_unknown_custom_error(data, self.errors) {
+            return decoded
         }
+
+        let identified = self.signature_identifier.and_then(|identifier| {
+            let selector = &data[..SELECTOR_LEN];
+            let error = RuntimeOrHandle::new()
+                .block_on(async { identifier.write().await.identify_function(selector).await })?;
+            decode_error(data, &error)
+        });
+        identified.or(decoded)
     }
 }
 
 impl<'a> ContractRunner<'a> {
     /// Deploys the test contract inside the runner from the sending account, and optionally runs
     /// the `setUp` function on the test contract.
+    #[tracing::instrument(name = "setup", skip_all)]
     pub fn setup(&mut self, setup: bool) -> Result<TestSetup> {
         trace!(?setup, "Setting test contract");
 
@@ -157,7 +250,10 @@ impl<'a> ContractRunner<'a> {
                         (false, logs, traces, labels, None)
                     }
                     Err(EvmError::Execution(err)) => {
-                        let ExecutionErr { traces, labels, logs, reason, .. } = *err;
+                        let ExecutionErr { traces, labels, logs, reason, cheatcode_failure, .. } =
+                            *err;
+                        let reason =
+                            self.with_cheatcode_location(reason, cheatcode_failure.as_ref());
                         error!(reason=?reason, contract= ?address, "setUp failed");
                         (true, logs, traces, labels, Some(format!("Setup failed: {reason}")))
                     }
@@ -211,23 +307,23 @@ impl<'a> ContractRunner<'a> {
 
         // There are multiple setUp function, so we return a single test result for `setUp`
         if setup_fns.len() > 1 {
+            let result = TestResult {
+                success: false,
+                reason: Some("Multiple setUp functions".to_string()),
+                counterexample: None,
+                logs: vec![],
+                decoded_logs: vec![],
+                kind: TestKind::Standard(0),
+                traces: vec![],
+                coverage: None,
+                labeled_addresses: BTreeMap::new(),
+                duration: Duration::ZERO,
+            };
+            self.fail_fast.record(&result);
             return Ok(SuiteResult::new(
                 start.elapsed(),
-                [(
-                    "setUp()".to_string(),
-                    TestResult {
-                        success: false,
-                        reason: Some("Multiple setUp functions".to_string()),
-                        counterexample: None,
-                        logs: vec![],
-                        decoded_logs: vec![],
-                        kind: TestKind::Standard(0),
-                        traces: vec![],
-                        coverage: None,
-                        labeled_addresses: BTreeMap::new(),
-                    },
-                )]
-                .into(),
+                Duration::ZERO,
+                [("setUp()".to_string(), result)].into(),
                 warnings,
             ))
         }
@@ -240,32 +336,38 @@ impl<'a> ContractRunner<'a> {
             self.executor.set_tracing(true);
         }
 
+        let setup_start = Instant::now();
         let setup = self.setup(needs_setup)?;
+        let setup_duration = setup_start.elapsed();
         self.executor.set_tracing(original_tracing);
 
         if setup.setup_failed {
             // The setup failed, so we return a single test result for `setUp`
+            let result = TestResult {
+                success: false,
+                reason: setup.reason,
+                counterexample: None,
+                decoded_logs: decode_console_logs(&setup.logs),
+                logs: setup.logs,
+                kind: TestKind::Standard(0),
+                traces: setup.traces,
+                coverage: None,
+                labeled_addresses: setup.labeled_addresses,
+                duration: Duration::ZERO,
+            };
+            self.fail_fast.record(&result);
             return Ok(SuiteResult::new(
                 start.elapsed(),
-                [(
-                    "setUp()".to_string(),
-                    TestResult {
-                        success: false,
-                        reason: setup.reason,
-                        counterexample: None,
-                        decoded_logs: decode_console_logs(&setup.logs),
-                        logs: setup.logs,
-                        kind: TestKind::Standard(0),
-                        traces: setup.traces,
-                        coverage: None,
-                        labeled_addresses: setup.labeled_addresses,
-                    },
-                )]
-                .into(),
+                setup_duration,
+                [("setUp()".to_string(), result)].into(),
                 warnings,
             ))
         }
 
+        if test_options.strict_isolation {
+            self.isolation_snapshot = Some(IsolationSnapshot::capture(self.executor.backend()));
+        }
+
         // Collect valid test functions
         let tests: Vec<_> = self
             .contract
@@ -280,23 +382,50 @@ impl<'a> ContractRunner<'a> {
                 tests
                     .par_iter()
                     .flat_map(|(func, should_fail)| {
-                        if func.is_fuzz_test() {
-                            self.run_fuzz_test(
+                        // no new tests are started once enough tests failed
+                        if self.fail_fast.is_stopped() {
+                            return None
+                        }
+
+                        let mut runner = self.clone();
+                        let timeout = self
+                            .test_timeouts
+                            .get(&func.name)
+                            .copied()
+                            .or(test_options.test_timeout)
+                            .map(Duration::from_secs);
+                        runner.executor.set_timeout(timeout);
+                        runner.executor.set_auto_advance_time(
+                            self.auto_advance_times
+                                .get(&func.name)
+                                .copied()
+                                .or(test_options.auto_advance_time),
+                        );
+                        // the budget starts with this test, not when the executor was built
+                        runner.executor.start_timeout();
+
+                        let result = if func.is_fuzz_test() {
+                            runner.run_fuzz_test(
                                 func,
                                 *should_fail,
                                 test_options.fuzzer(),
+                                test_options.fuzz,
                                 setup.clone(),
                             )
                         } else {
-                            self.clone().run_test(func, *should_fail, setup.clone())
-                        }
-                        .map(|result| Ok((func.signature(), result)))
+                            runner.run_test(func, *should_fail, setup.clone()).map(Some)
+                        };
+
+                        // cancelled fuzz campaigns are not reported
+                        let result = result.ok().flatten()?;
+                        self.fail_fast.record(&result);
+                        Some(Ok((func.signature(), result)))
                     })
                     .collect::<Result<BTreeMap<_, _>>>()?,
             );
         }
 
-        if has_invariants {
+        if has_invariants && !self.fail_fast.is_stopped() {
             let identified_contracts = load_contracts(setup.traces.clone(), known_contracts);
             let functions: Vec<&Function> = self
                 .contract
@@ -316,8 +445,9 @@ impl<'a> ContractRunner<'a> {
             )?;
 
             results.into_iter().zip(functions.iter()).for_each(|(result, function)| {
+                self.fail_fast.record(&result);
                 match result.kind {
-                    TestKind::Invariant(ref _cases, _) => {
+                    TestKind::Invariant(..) => {
                         test_results.insert(function.signature(), result);
                     }
                     _ => unreachable!(),
@@ -336,7 +466,7 @@ impl<'a> ContractRunner<'a> {
             );
         }
 
-        Ok(SuiteResult::new(duration, test_results, warnings))
+        Ok(SuiteResult::new(duration, setup_duration, test_results, warnings))
     }
 
     /// Runs a single test
@@ -356,7 +486,7 @@ impl<'a> ContractRunner<'a> {
 
         // Run unit test
         let start = Instant::now();
-        let (reverted, reason, gas, stipend, execution_traces, coverage, state_changeset) =
+        let (reverted, mut reason, gas, stipend, execution_traces, coverage, state_changeset) =
             match self.executor.execute_test::<(), _, _>(
                 self.sender,
                 address,
@@ -390,10 +520,18 @@ impl<'a> ContractRunner<'a> {
                         traces: execution_trace,
                         labels: new_labels,
                         state_changeset,
+                        cheatcode_failure,
+                        reverted_calls,
                         ..
                     } = *err;
                     labeled_addresses.extend(new_labels);
                     logs.extend(execution_logs);
+                    let reason = if reverted && !should_fail {
+                        self.with_deepest_revert_reason(reason, &reverted_calls)
+                    } else {
+                        reason
+                    };
+                    let reason = self.with_cheatcode_location(reason, cheatcode_failure.as_ref());
                     (reverted, Some(reason), gas, stipend, execution_trace, None, state_changeset)
                 }
                 Err(err) => {
@@ -403,16 +541,38 @@ impl<'a> ContractRunner<'a> {
             };
         traces.extend(execution_traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
 
-        let success = self.executor.is_success(
-            setup.address,
-            reverted,
-            state_changeset.expect("we should have a state changeset"),
-            should_fail,
-        );
+        // a timed out test always fails, even if it was expected to revert
+        let timed_out = reason.as_deref().map(is_timeout_reason).unwrap_or_default();
+        let mut success = !timed_out &&
+            self.executor.is_success(
+                setup.address,
+                reverted,
+                state_changeset.expect("we should have a state changeset"),
+                should_fail,
+            );
+
+        // in strict isolation mode any mutation of the backend that outlives the call fails the
+        // test, regardless of its outcome
+        if let Some(before) = self.isolation_snapshot.as_ref() {
+            let violations = before.diff(&IsolationSnapshot::capture(self.executor.backend()));
+            if !violations.is_empty() {
+                let diff = violations
+                    .iter()
+                    .map(|violation| format!("\n    {violation}"))
+                    .collect::<String>();
+                let isolation_reason = format!("State leaked out of the test:{diff}");
+                reason = Some(match reason {
+                    Some(reason) if !success => format!("{reason}; {isolation_reason}"),
+                    _ => isolation_reason,
+                });
+                success = false;
+            }
+        }
 
         // Record test execution time
+        let duration = start.elapsed();
         tracing::debug!(
-            duration = ?start.elapsed(),
+            ?duration,
             %success,
             %gas
         );
@@ -427,6 +587,7 @@ impl<'a> ContractRunner<'a> {
             traces,
             coverage,
             labeled_addresses,
+            duration,
         })
     }
 
@@ -445,19 +606,28 @@ impl<'a> ContractRunner<'a> {
         let project_contracts = known_contracts.unwrap_or(&empty);
         let TestSetup { address, logs, traces, labeled_addresses, .. } = setup;
 
+        // all invariants are checked in the same campaign, so they share its duration
+        let start = Instant::now();
         let mut evm = InvariantExecutor::new(
             &mut self.executor,
             runner,
             test_options.invariant,
             &identified_contracts,
             project_contracts,
-        );
+        )
+        .with_checkpoint(self.invariant_checkpoint.clone())
+        .with_cancellation(self.fail_fast.cancellation());
 
         let invariant_contract =
             InvariantContract { address, invariant_functions: functions, abi: self.contract };
 
-        if let Some(InvariantFuzzTestResult { invariants, cases, reverts, mut last_call_results }) =
-            evm.invariant_fuzz(invariant_contract)?
+        if let Some(InvariantFuzzTestResult {
+            invariants,
+            cases,
+            reverts,
+            handlers,
+            mut last_call_results,
+        }) = evm.invariant_fuzz(invariant_contract)?
         {
             let results = invariants
                 .iter()
@@ -502,10 +672,11 @@ impl<'a> ContractRunner<'a> {
                         counterexample,
                         decoded_logs: decode_console_logs(&logs),
                         logs,
-                        kind: TestKind::Invariant(cases.clone(), reverts),
+                        kind: TestKind::Invariant(cases.clone(), reverts, handlers.clone()),
                         coverage: None, // todo?
                         traces,
                         labeled_addresses: labeled_addresses.clone(),
+                        duration: start.elapsed(),
                     })
                 })
                 .collect::<Result<Vec<TestResult>>>()
@@ -517,22 +688,32 @@ impl<'a> ContractRunner<'a> {
         }
     }
 
+    /// Runs a fuzz test
+    ///
+    /// Returns `None` if the campaign was cancelled because enough other tests failed, see
+    /// [FailFast].
     #[tracing::instrument(name = "fuzz-test", skip_all, fields(name = %func.signature(), %should_fail))]
     pub fn run_fuzz_test(
         &self,
         func: &Function,
         should_fail: bool,
         runner: TestRunner,
+        fuzz_config: FuzzConfig,
         setup: TestSetup,
-    ) -> Result<TestResult> {
+    ) -> Result<Option<TestResult>> {
         let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
 
         // Run fuzz test
         let start = Instant::now();
-        let mut result =
-            FuzzedExecutor::new(&self.executor, runner, self.sender, Default::default())
-                .fuzz(func, address, should_fail, self.errors)
-                .wrap_err("Failed to run fuzz test")?;
+        let fixtures = self.fuzz_fixtures(func, address);
+        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender, fuzz_config)
+            .with_fixtures(fixtures)
+            .with_cancellation(self.fail_fast.cancellation())
+            .fuzz(func, address, should_fail, self.errors)
+            .wrap_err("Failed to run fuzz test")?;
+        if result.cancelled {
+            return Ok(None)
+        }
 
         // Record logs, labels and traces
         logs.append(&mut result.logs);
@@ -540,13 +721,16 @@ impl<'a> ContractRunner<'a> {
         traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)));
 
         // Record test execution time
+        let duration = start.elapsed();
         tracing::debug!(
-            duration = ?start.elapsed(),
+            ?duration,
             success = %result.success
         );
 
-        Ok(TestResult {
-            success: result.success,
+        let timed_out = result.reason.as_deref().map(is_timeout_reason).unwrap_or_default();
+
+        Ok(Some(TestResult {
+            success: result.success && !timed_out,
             reason: result.reason,
             counterexample: result.counterexample,
             decoded_logs: decode_console_logs(&logs),
@@ -555,6 +739,60 @@ impl<'a> ContractRunner<'a> {
             traces,
             coverage: result.coverage,
             labeled_addresses,
-        })
+            duration,
+        }))
+    }
+
+    /// Collects the fixtures of the fuzz test's parameters from the deployed test contract.
+    ///
+    /// The fixtures of a parameter `amount` are provided by either a `fixtureAmount()` (or
+    /// `fixture_amount()`) function returning an array, or a public `fixtureAmount` array.
+    fn fuzz_fixtures(&self, func: &Function, address: Address) -> FuzzFixtures {
+        let mut fixtures = FuzzFixtures::new();
+        for input in func.inputs.iter().filter(|input| !input.name.is_empty()) {
+            let mut chars = input.name.chars();
+            let capitalized = match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => continue,
+            };
+            let names = [format!("fixture{capitalized}"), format!("fixture_{}", input.name)];
+            let values = names
+                .iter()
+                .filter_map(|name| self.contract.functions_by_name(name).ok())
+                .flatten()
+                .flat_map(|fixture| self.call_fixture(fixture, address))
+                .filter(|value| value.type_check(&input.kind))
+                .collect::<Vec<_>>();
+            if !values.is_empty() {
+                trace!(param = %input.name, count = values.len(), "collected fuzz fixtures");
+                fixtures.insert(input.name.clone(), values);
+            }
+        }
+        fixtures
+    }
+
+    /// Returns all values of the given fixture, which is either a getter of an array or a
+    /// function returning one
+    fn call_fixture(&self, fixture: &Function, address: Address) -> Vec<Token> {
+        let call = |args: &[Token]| {
+            let calldata = fixture.encode_input(args).ok()?;
+            let call =
+                self.executor.call_raw(self.sender, address, calldata.into(), 0.into()).ok()?;
+            if call.reverted {
+                return None
+            }
+            fixture.decode_output(call.result.as_ref()).ok()?.into_iter().next()
+        };
+        match fixture.inputs.iter().map(|input| &input.kind).collect::<Vec<_>>().as_slice() {
+            [] => match call(&[]) {
+                Some(Token::Array(values)) | Some(Token::FixedArray(values)) => values,
+                _ => vec![],
+            },
+            // public array getters take the index of the element
+            [ParamType::Uint(256)] => {
+                (0..MAX_ARRAY_LEN).map_while(|index| call(&[Token::Uint(index.into())])).collect()
+            }
+            _ => vec![],
+        }
     }
 }
@@ -0,0 +1,152 @@
+use ethers::solc::{sourcemap, ProjectCompileOutput};
+use foundry_common::contracts::diff_score;
+use foundry_evm::executor::inspector::CheatcodeFailure;
+use semver::Version;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A position in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The path of the file, relative to the project root if it is inside the project
+    pub path: PathBuf,
+    /// The 1-based line
+    pub line: usize,
+    /// The 1-based column
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// The deployed bytecode and source map of a compiled contract
+#[derive(Debug, Clone)]
+struct LocatableContract {
+    version: Version,
+    /// Whether the contract is a Yul object
+    yul: bool,
+    code: Vec<u8>,
+    source_map: String,
+}
+
+/// Identifies a source file of the compiler output
+///
+/// Yul sources are compiled separately from Solidity sources, so their source ids overlap.
+type SourceKey = (Version, bool, u32);
+
+/// Resolves program counters in the deployed bytecode of the compiled contracts to the source
+/// location of the instruction, using the deployed source maps
+#[derive(Debug, Clone, Default)]
+pub struct SourceLocator {
+    root: PathBuf,
+    contracts: Vec<LocatableContract>,
+    /// The source files, by compiler version, whether they are Yul sources and source id
+    files: HashMap<SourceKey, PathBuf>,
+}
+
+impl SourceLocator {
+    /// Collects the source maps and source ids of all artifacts of the `output`
+    pub fn new(output: &ProjectCompileOutput, root: impl Into<PathBuf>) -> Self {
+        let mut locator = Self { root: root.into(), ..Default::default() };
+        for (id, artifact) in output.artifact_ids() {
+            let yul = is_yul(&id.source);
+            if let Some(source_id) = artifact.id {
+                locator.files.insert((id.version.clone(), yul, source_id), id.source.clone());
+            }
+            let code = match artifact.deployed_bytecode.as_ref().and_then(|d| d.bytecode.as_ref())
+            {
+                Some(bytecode) => bytecode,
+                None => continue,
+            };
+            if let (Some(bytes), Some(source_map)) =
+                (code.object.as_bytes(), code.source_map.as_ref())
+            {
+                locator.contracts.push(LocatableContract {
+                    version: id.version.clone(),
+                    yul,
+                    code: bytes.to_vec(),
+                    source_map: source_map.clone(),
+                });
+            }
+        }
+        locator
+    }
+
+    /// Returns the source location of the instruction at `pc` of the deployed `code`
+    pub fn locate(&self, code: &[u8], pc: usize) -> Option<SourceLocation> {
+        let contract =
+            self.contracts.iter().find(|contract| diff_score(&contract.code, code) < 0.1)?;
+        let ic = instruction_counter(code, pc)?;
+        let source_map = sourcemap::parse(&contract.source_map).ok()?;
+        let element = source_map.get(ic)?;
+        let path = self.files.get(&(contract.version.clone(), contract.yul, element.index?))?;
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        let source = std::fs::read_to_string(self.root.join(path)).ok()?;
+        let (line, column) = line_and_column(&source, element.offset)?;
+        Some(SourceLocation { path: path.to_path_buf(), line, column })
+    }
+
+    /// Returns the location of the cheatcode call that failed
+    pub fn locate_cheatcode(&self, failure: &CheatcodeFailure) -> Option<SourceLocation> {
+        if failure.code.is_empty() {
+            return None
+        }
+        self.locate(&failure.code, failure.pc)
+    }
+}
+
+/// Returns true if the file is a Yul source
+fn is_yul(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "yul").unwrap_or_default()
+}
+
+/// Returns the index of the instruction at `pc`, if `pc` is not in the data of a push
+fn instruction_counter(code: &[u8], pc: usize) -> Option<usize> {
+    let mut current = 0;
+    let mut ic = 0;
+    while current < pc {
+        let op = *code.get(current)?;
+        // PUSH1..PUSH32
+        current += if (0x60..=0x7f).contains(&op) { 1 + (op - 0x5f) as usize } else { 1 };
+        ic += 1;
+    }
+    (current == pc).then_some(ic)
+}
+
+/// Returns the 1-based line and column of the byte offset
+fn line_and_column(source: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = source.as_bytes().get(..offset)?;
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let line_start = before.iter().rposition(|b| *b == b'\n').map(|pos| pos + 1).unwrap_or(0);
+    Some((line, offset - line_start + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_count_instructions() {
+        // PUSH1 0x80 PUSH1 0x40 MSTORE CALLVALUE
+        let code = [0x60, 0x80, 0x60, 0x40, 0x52, 0x34];
+        assert_eq!(instruction_counter(&code, 0), Some(0));
+        assert_eq!(instruction_counter(&code, 2), Some(1));
+        assert_eq!(instruction_counter(&code, 5), Some(3));
+        // inside the data of a push
+        assert_eq!(instruction_counter(&code, 1), None);
+    }
+
+    #[test]
+    fn can_resolve_lines_and_columns() {
+        let source = "contract A {\n    function f() {}\n}\n";
+        assert_eq!(line_and_column(source, 0), Some((1, 1)));
+        assert_eq!(line_and_column(source, 17), Some((2, 5)));
+        assert_eq!(line_and_column(source, 100), None);
+    }
+}
@@ -2,14 +2,16 @@
 
 use crate::{
     config::*,
-    test_helpers::{filter::Filter, RE_PATH_SEPARATOR},
+    test_helpers::{filter::Filter, PROJECT, RE_PATH_SEPARATOR},
 };
+use foundry_config::{fs_permissions::PathPermission, Config, FsPermissions};
 
 /// Executes all cheat code tests but not fork cheat codes
 #[test]
 fn test_cheats_local() {
     let filter =
-        Filter::new(".*", ".*", &format!(".*cheats{RE_PATH_SEPARATOR}*")).exclude_paths("Fork");
+        Filter::new(".*", ".*", &format!(".*cheats{RE_PATH_SEPARATOR}*"))
+            .exclude_paths("(Fork|ReadMemory)");
 
     // on windows exclude ffi tests since no echo and file test that expect a certain file path
     #[cfg(windows)]
@@ -17,3 +19,58 @@ fn test_cheats_local() {
 
     TestConfig::filter(filter).run();
 }
+
+/// Failed cheatcodes include the location of the call in the test contract
+#[test]
+fn test_cheats_provenance() {
+    let mut runner = runner();
+    let results = runner
+        .test(&Filter::new(".*", "CheatcodeProvenanceTest", ".*"), None, TEST_OPTS)
+        .unwrap();
+    let result = &results["cheats/CheatcodeProvenance.t.sol:CheatcodeProvenanceTest"].test_results
+        ["testFailCheatcodeProvenance()"];
+    assert!(result.success);
+    let reason = result.reason.as_deref().unwrap();
+    assert!(
+        reason.ends_with("(at cheats/CheatcodeProvenance.t.sol:11:9)"),
+        "unexpected reason: {reason}"
+    );
+}
+
+/// `deployCode` and `deployYul` look up artifacts in the configured `artifact_paths`
+#[test]
+fn test_cheats_artifact_paths() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.fs_permissions = FsPermissions::new(vec![PathPermission::read(manifest_root())]);
+    config.artifact_paths = vec![PROJECT.root().join("fixtures/ArtifactPaths")];
+    let filter = Filter::new(".*", ".*", ".*artifactpaths");
+    TestConfig::with_filter(runner_with_config(config), filter).run();
+}
+
+/// `readMemory` and `readReturndata` can read the calling frame if `memory_access` is enabled
+#[test]
+fn test_cheats_read_memory() {
+    let mut config = Config::with_root(PROJECT.root());
+    config.memory_access = true;
+    let filter = Filter::new(".*", ".*", &format!(".*cheats{RE_PATH_SEPARATOR}ReadMemory"));
+    TestConfig::with_filter(runner_with_config(config), filter).run();
+}
+
+/// `readMemory` and `readReturndata` revert if `memory_access` is disabled
+#[test]
+fn test_cheats_read_memory_disabled() {
+    let mut runner = runner();
+    let filter = Filter::new(
+        "testRead",
+        "ReadMemoryTest",
+        &format!(".*cheats{RE_PATH_SEPARATOR}ReadMemory"),
+    );
+    let results = runner.test(&filter, None, TEST_OPTS).unwrap();
+    let results = &results["cheats/ReadMemory.t.sol:ReadMemoryTest"].test_results;
+    assert_eq!(results.len(), 3);
+    for (name, result) in results {
+        assert!(!result.success, "{name} succeeded");
+        let reason = result.reason.as_deref().unwrap_or_default();
+        assert!(reason.contains("Memory access disabled"), "unexpected reason: {reason}");
+    }
+}
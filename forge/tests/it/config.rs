@@ -3,7 +3,10 @@
 use crate::test_helpers::{
     filter::Filter, COMPILED, COMPILED_WITH_LIBS, EVM_OPTS, LIBS_PROJECT, PROJECT,
 };
-use forge::{result::SuiteResult, MultiContractRunner, MultiContractRunnerBuilder, TestOptions};
+use forge::{
+    result::SuiteResult, source_locator::SourceLocator, MultiContractRunner,
+    MultiContractRunnerBuilder, TestOptions,
+};
 use foundry_config::{
     fs_permissions::PathPermission, Config, FsPermissions, FuzzConfig, InvariantConfig,
     RpcEndpoint, RpcEndpoints,
@@ -100,17 +103,25 @@ pub static TEST_OPTS: TestOptions = TestOptions {
         seed: None,
         include_storage: true,
         include_push_bytes: true,
+        include_balances: true,
         dictionary_weight: 40,
+        dictionary_weights: None,
+        fixture_weight: 20,
     },
     invariant: InvariantConfig {
         runs: 256,
         depth: 15,
         dictionary_weight: 80,
+        dictionary_weights: None,
         fail_on_revert: false,
         call_override: false,
         include_storage: true,
         include_push_bytes: true,
+        include_balances: true,
     },
+    test_timeout: None,
+    auto_advance_time: None,
+    strict_isolation: false,
 };
 
 pub fn manifest_root() -> PathBuf {
@@ -143,6 +154,7 @@ pub fn runner_with_config(mut config: Config) -> MultiContractRunner {
     base_runner()
         .with_cheats_config(CheatsConfig::new(&config, &EVM_OPTS))
         .sender(config.sender)
+        .with_source_locator(SourceLocator::new(&COMPILED, PROJECT.root()))
         .build(
             &PROJECT.paths.root,
             (*COMPILED).clone(),
@@ -1,10 +1,15 @@
 //! forge tests for core functionality
 
-use crate::{config::*, test_helpers::filter::Filter};
+use crate::{
+    config::*,
+    test_helpers::{
+        filter::Filter, libraries, COMPILED, COMPILED_WITH_LIBS, EVM_OPTS, LIBS_PROJECT, PROJECT,
+    },
+};
 
-use forge::result::SuiteResult;
+use forge::{result::SuiteResult, FailFast};
 
-use foundry_evm::trace::TraceKind;
+use foundry_evm::{executor::inspector::is_timeout_reason, trace::TraceKind};
 use std::{collections::BTreeMap, env};
 
 #[test]
@@ -61,6 +66,28 @@ fn test_core() {
                 "core/PaymentFailure.t.sol:PaymentFailureTest",
                 vec![("testCantPay()", false, Some("EvmError: Revert".to_string()), None, None)],
             ),
+            (
+                "core/RevertReason.t.sol:RevertReasonTest",
+                vec![
+                    (
+                        "testSwallowedRevertReason()",
+                        false,
+                        Some(
+                            "slippage (reverted in 0x000000000000000000000000000000000000beef)"
+                                .to_string(),
+                        ),
+                        None,
+                        None,
+                    ),
+                    (
+                        "testCaughtRevertReasonIsNotReported()",
+                        false,
+                        Some("EvmError: Revert".to_string()),
+                        None,
+                        None,
+                    ),
+                ],
+            ),
             (
                 "core/LibraryLinking.t.sol:LibraryLinkingTest",
                 vec![
@@ -637,6 +664,13 @@ fn test_logs() {
                         Some(vec!["constructor".into(), "formatted log test 1".into()]),
                         None,
                     ),
+                    (
+                        "testUnknownLogDoesNotRevert()",
+                        true,
+                        None,
+                        Some(vec!["constructor".into(), "after".into()]),
+                        None,
+                    ),
                 ],
             ),
         ]),
@@ -699,3 +733,107 @@ fn test_trace() {
         }
     }
 }
+
+#[test]
+fn test_isolate() {
+    let mut opts = EVM_OPTS.clone();
+    opts.isolate = true;
+    let env = opts.evm_env_blocking().unwrap();
+    let mut runner =
+        base_runner().build(&PROJECT.paths.root, (*COMPILED).clone(), env, opts).unwrap();
+    let results = runner.test(&Filter::new(".*", ".*", ".*isolate"), None, TEST_OPTS).unwrap();
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "isolate/Isolate.t.sol:IsolateTest",
+            vec![
+                ("testEachCallIsATransaction()", true, None, None, None),
+                ("testRevertedCallChangesNothing()", true, None, None, None),
+                ("testPrankedCallIsSentByThePrankedCaller()", true, None, None, None),
+                ("testTransientStorageIsClearedBetweenCalls()", true, None, None, None),
+                ("testTransientStorageOfTheTestIsKept()", true, None, None, None),
+            ],
+        )]),
+    );
+}
+
+#[test]
+fn test_places_configured_libraries_without_fork() {
+    let env = EVM_OPTS.evm_env_blocking().unwrap();
+    let mut runner = base_runner()
+        .with_libraries(libraries())
+        .build(&LIBS_PROJECT.paths.root, (*COMPILED_WITH_LIBS).clone(), env, EVM_OPTS.clone())
+        .unwrap();
+    assert_eq!(runner.library_code.len(), 1);
+
+    let results = runner
+        .test(&Filter::new(".*", "LibraryLinkingTest", ".*core/LibraryLinking"), None, TEST_OPTS)
+        .unwrap();
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "core/LibraryLinking.t.sol:LibraryLinkingTest",
+            vec![
+                ("testDirect()", true, None, None, None),
+                ("testNested()", true, None, None, None),
+            ],
+        )]),
+    );
+}
+
+#[test]
+fn test_max_failures() {
+    let mut runner = runner();
+    runner.fail_fast = FailFast::new(Some(1));
+    let results = runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS).unwrap();
+
+    // tests that were already running when the first test failed are still reported
+    let failed = results
+        .values()
+        .flat_map(|suite| suite.test_results.values())
+        .filter(|result| !result.success)
+        .count();
+    assert!(runner.fail_fast.is_stopped());
+    assert!(failed >= 1);
+    assert_eq!(failed, runner.fail_fast.failures());
+
+    // on a single thread no test is started after the first failure
+    let mut runner = runner();
+    let total = runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS).unwrap();
+    runner.fail_fast = FailFast::new(Some(1));
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let results =
+        pool.install(|| runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS).unwrap());
+
+    let count = |results: &BTreeMap<String, SuiteResult>| {
+        results.values().map(|suite| suite.test_results.len()).sum::<usize>()
+    };
+    assert_eq!(runner.fail_fast.failures(), 1);
+    assert!(count(&results) < count(&total));
+}
+
+#[test]
+fn test_timeout_aborts_tests() {
+    let mut runner = runner();
+    let mut opts = TEST_OPTS;
+    opts.test_timeout = Some(1);
+    runner.test_options = opts;
+
+    // on a single thread the earlier tests must not use up the budget of later ones
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+    let results =
+        pool.install(|| runner.test(&Filter::new(".*", ".*", ".*timeout"), None, opts).unwrap());
+
+    let suite = &results["timeout/Timeout.t.sol:TimeoutTest"];
+    for test in ["testLoopsForever()", "testFuzzLoopsForever(uint256)"] {
+        let result = &suite.test_results[test];
+        assert!(!result.success, "{test}");
+        assert!(
+            is_timeout_reason(result.reason.as_deref().unwrap_or_default()),
+            "{test}: {:?}",
+            result.reason
+        );
+    }
+    assert!(suite.test_results["testReturnsQuickly()"].success);
+}
@@ -50,6 +50,21 @@ fn test_launch_fork() {
     TestConfig::with_filter(runner, filter).run();
 }
 
+/// Tests that reading the state of the fork doesn't count as state that leaked out of the test
+#[test]
+fn test_launch_fork_strict_isolation() {
+    let rpc_url = foundry_utils::rpc::next_http_archive_rpc_endpoint();
+    let runner = forked_runner(&rpc_url);
+    let filter = Filter::new(
+        "testReadState|testActiveFork",
+        ".*",
+        &format!(".*fork{RE_PATH_SEPARATOR}Launch"),
+    );
+    let mut config = TestConfig::with_filter(runner, filter);
+    config.opts.strict_isolation = true;
+    config.run();
+}
+
 /// Tests that we can transact transactions in forking mode
 #[test]
 fn test_transact_fork() {
@@ -81,3 +81,74 @@ fn test_fuzz_collection() {
         )]),
     );
 }
+
+/// Test that fixtures provided by the test contract are used as fuzz inputs
+#[test]
+fn test_fuzz_fixtures() {
+    let mut runner = runner();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/FuzzFixtures.t.sol"), None, TEST_OPTS)
+        .unwrap();
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/FuzzFixtures.t.sol:FuzzFixturesTest",
+            vec![
+                (
+                    "testFixtureFromFunction(uint256)",
+                    false,
+                    Some("amount fixture found.".into()),
+                    None,
+                    None,
+                ),
+                (
+                    "testFixtureFromArray(address,uint256)",
+                    false,
+                    Some("owner fixture found.".into()),
+                    None,
+                    None,
+                ),
+            ],
+        )]),
+    );
+}
+
+/// Test that every fixture is used even if the fuzzer never draws from them
+#[test]
+fn test_fuzz_fixtures_without_fixture_runs() {
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS;
+    opts.fuzz.runs = 1;
+    opts.fuzz.fixture_weight = 0;
+    opts.fuzz.dictionary_weight = 0;
+    runner.test_options = opts;
+
+    let results =
+        runner.test(&Filter::new(".*", ".*", ".*fuzz/FuzzFixtures.t.sol"), None, opts).unwrap();
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/FuzzFixtures.t.sol:FuzzFixturesTest",
+            vec![
+                (
+                    "testFixtureFromFunction(uint256)",
+                    false,
+                    Some("amount fixture found.".into()),
+                    None,
+                    None,
+                ),
+                (
+                    "testFixtureFromArray(address,uint256)",
+                    false,
+                    Some("owner fixture found.".into()),
+                    None,
+                    None,
+                ),
+            ],
+        )]),
+    );
+}
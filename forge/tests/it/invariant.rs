@@ -24,6 +24,25 @@ fn test_invariant() {
                 "fuzz/invariant/common/InvariantInnerContract.t.sol:InvariantInnerContract",
                 vec![("invariantHideJesus()", false, Some("jesus betrayed.".into()), None, None)],
             ),
+            (
+                "fuzz/invariant/common/InvariantEvents.t.sol:InvariantEvents",
+                vec![
+                    (
+                        "invariantSupplyFromEvents((address,bytes32[],bytes)[])",
+                        false,
+                        Some("supply not conserved.".into()),
+                        None,
+                        None,
+                    ),
+                    (
+                        "invariantNoEmptyTransfers((address,bytes32[],bytes)[])",
+                        true,
+                        None,
+                        None,
+                        None,
+                    ),
+                ],
+            ),
             (
                 "fuzz/invariant/common/InvariantReentrancy.t.sol:InvariantReentrancy",
                 vec![("invariantNotStolen()", true, None, None, None)],
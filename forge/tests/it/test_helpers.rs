@@ -24,13 +24,20 @@ pub static PROJECT: Lazy<Project> = Lazy::new(|| {
     Project::builder().paths(paths).ephemeral().no_artifacts().build().unwrap()
 });
 
+/// Returns the libraries [LIBS_PROJECT] is linked against
+pub fn libraries() -> Libraries {
+    let libs = [
+        "fork/Fork.t.sol:DssExecLib:0xfD88CeE74f7D78697775aBDAE53f9Da1559728E4".to_string(),
+        "core/LibraryLinking.t.sol:Lib:0x00000000000000000000000000000000000c0de1".to_string(),
+    ];
+    Libraries::parse(&libs).unwrap()
+}
+
 pub static LIBS_PROJECT: Lazy<Project> = Lazy::new(|| {
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../testdata");
     let paths = ProjectPathsConfig::builder().root(root.clone()).sources(root).build().unwrap();
-    let libs =
-        ["fork/Fork.t.sol:DssExecLib:0xfD88CeE74f7D78697775aBDAE53f9Da1559728E4".to_string()];
 
-    let settings = Settings { libraries: Libraries::parse(&libs).unwrap(), ..Default::default() };
+    let settings = Settings { libraries: libraries(), ..Default::default() };
 
     let solc_config = SolcConfig::builder().settings(settings).build();
     Project::builder()
@@ -0,0 +1,39 @@
+//! Progress of a test run
+
+use forge::result::{SuiteResult, TestResult};
+use std::time::Duration;
+
+/// An event of a test run, in the order they are emitted
+#[derive(Debug, Clone)]
+pub enum TestEvent {
+    /// The project was compiled and the matching tests are about to be executed
+    Compiled {
+        /// The number of tests that match the filter
+        tests: usize,
+    },
+    /// A test finished
+    TestFinished {
+        /// The identifier `<path>:<name>` of the test contract
+        contract: String,
+        /// The signature of the test function
+        signature: String,
+        /// The result of the test
+        result: TestResult,
+    },
+    /// All tests of a test contract finished, after the [`TestEvent::TestFinished`] of all of them
+    SuiteFinished {
+        /// The identifier `<path>:<name>` of the test contract
+        contract: String,
+        /// The results of all tests of the contract
+        result: SuiteResult,
+    },
+    /// All tests finished
+    Finished {
+        /// The number of tests that passed
+        passed: usize,
+        /// The number of tests that failed
+        failed: usize,
+        /// The time it took to compile and run all tests
+        duration: Duration,
+    },
+}
@@ -0,0 +1,73 @@
+//! Selects the tests to run
+
+use foundry_common::TestFilter;
+use regex::Regex;
+
+/// Selects tests by regular expressions on their name, contract name and source path
+///
+/// All tests match the default filter.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    test: Option<Regex>,
+    contract: Option<Regex>,
+    path: Option<Regex>,
+}
+
+impl Filter {
+    /// Only runs tests whose function name matches the pattern
+    pub fn match_test(mut self, pattern: Regex) -> Self {
+        self.test = Some(pattern);
+        self
+    }
+
+    /// Only runs tests of contracts whose name matches the pattern
+    pub fn match_contract(mut self, pattern: Regex) -> Self {
+        self.contract = Some(pattern);
+        self
+    }
+
+    /// Only runs tests of contracts whose source path matches the pattern
+    pub fn match_path(mut self, pattern: Regex) -> Self {
+        self.path = Some(pattern);
+        self
+    }
+}
+
+fn is_match(pattern: &Option<Regex>, value: &str) -> bool {
+    pattern.as_ref().map(|pattern| pattern.is_match(value)).unwrap_or(true)
+}
+
+impl TestFilter for Filter {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        is_match(&self.test, test_name.as_ref())
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        is_match(&self.contract, contract_name.as_ref())
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        is_match(&self.path, path.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_filter_tests() {
+        let filter = Filter::default();
+        assert!(filter.matches_test("testDeposit"));
+        assert!(filter.matches_contract("VaultTest"));
+
+        let filter = filter
+            .match_test(Regex::new("^testDeposit").unwrap())
+            .match_path(Regex::new("test/Vault").unwrap());
+        assert!(filter.matches_test("testDepositFor"));
+        assert!(!filter.matches_test("testWithdraw"));
+        assert!(filter.matches_contract("TokenTest"));
+        assert!(filter.matches_path("test/Vault.t.sol"));
+        assert!(!filter.matches_path("test/Token.t.sol"));
+    }
+}
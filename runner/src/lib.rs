@@ -0,0 +1,214 @@
+//! Run the tests of a foundry project from Rust
+//!
+//! This crate compiles a project and executes its tests with the same runner as `forge test`,
+//! without shelling out to the `forge` binary. Results are streamed as [`TestEvent`]s while the
+//! tests are executed.
+//!
+//! ```no_run
+//! use foundry_runner::{Filter, TestEvent, TestRunner};
+//! use regex::Regex;
+//!
+//! let run = TestRunner::load("./my-project")?
+//!     .with_filter(Filter::default().match_contract(Regex::new("Vault")?))
+//!     .spawn();
+//! for event in run.events() {
+//!     if let TestEvent::TestFinished { contract, signature, result } = event {
+//!         println!("{contract}:{signature} passed: {}", result.success);
+//!     }
+//! }
+//! let results = run.wait()?;
+//! # Ok::<_, eyre::Report>(())
+//! ```
+#![deny(missing_docs)]
+
+use ethers_solc::ProjectCompileOutput;
+use forge::{
+    executor::inspector::CheatsConfig, utils::try_evm_spec, MultiContractRunner,
+    MultiContractRunnerBuilder, TestOptions,
+};
+use foundry_common::compile;
+use foundry_config::figment::Figment;
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+use tracing::trace;
+
+mod event;
+pub use event::TestEvent;
+
+mod filter;
+pub use filter::Filter;
+
+pub use forge::{
+    executor::opts::EvmOpts,
+    result::{SuiteResult, TestKind, TestResult},
+};
+pub use foundry_config::Config;
+
+/// Compiles a project and runs its tests
+#[derive(Debug, Clone)]
+pub struct TestRunner {
+    config: Config,
+    evm_opts: EvmOpts,
+    filter: Filter,
+}
+
+impl TestRunner {
+    /// Creates a runner for the project of the config
+    pub fn new(config: Config, evm_opts: EvmOpts) -> Self {
+        Self { config, evm_opts, filter: Filter::default() }
+    }
+
+    /// Creates a runner for the project at `root`, configured by its `foundry.toml` and the
+    /// `FOUNDRY_` environment variables like `forge test`
+    pub fn load(root: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let figment: Figment = Config::figment_with_root(root);
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::try_from(figment)?.sanitized();
+        evm_opts.resolve_fork(&config)?;
+        Ok(Self::new(config, evm_opts))
+    }
+
+    /// Only runs the tests that match the filter
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Returns the config of the project
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Compiles the project without printing anything
+    pub fn compile(&self) -> eyre::Result<ProjectCompileOutput> {
+        let project = self.config.project()?;
+        compile::suppress_compile(&project, &self.config)
+    }
+
+    /// Compiles the project and runs all matching tests on a background thread
+    ///
+    /// The progress is reported via [`TestRun::events`].
+    pub fn spawn(self) -> TestRun {
+        let (tx, events) = channel();
+        let handle = thread::spawn(move || self.run_with(None, tx));
+        TestRun { events, handle }
+    }
+
+    /// Compiles the project and runs all matching tests
+    ///
+    /// Returns the results of all test contracts by their identifier `<path>:<name>`.
+    pub fn run(self) -> eyre::Result<BTreeMap<String, SuiteResult>> {
+        self.spawn().wait()
+    }
+
+    /// Runs all matching tests of an output of [`TestRunner::compile`]
+    ///
+    /// This allows to compile the project while holding a lock, since compiling the same project
+    /// concurrently is not supported.
+    pub fn run_compiled(
+        self,
+        output: ProjectCompileOutput,
+    ) -> eyre::Result<BTreeMap<String, SuiteResult>> {
+        let (tx, _) = channel();
+        self.run_with(Some(output), tx)
+    }
+
+    fn run_with(
+        self,
+        output: Option<ProjectCompileOutput>,
+        events: Sender<TestEvent>,
+    ) -> eyre::Result<BTreeMap<String, SuiteResult>> {
+        let start = Instant::now();
+        let output = match output {
+            Some(output) => output,
+            None => self.compile()?,
+        };
+        let mut runner = self.build_runner(output)?;
+
+        let tests = runner.count_filtered_tests(&self.filter);
+        let _ = events.send(TestEvent::Compiled { tests });
+        trace!(target: "forge::runner", tests, "compiled project");
+
+        // forward the results of every test contract as soon as it finished
+        let (suite_tx, suite_rx) = channel::<(String, SuiteResult)>();
+        let forward = {
+            let events = events.clone();
+            thread::spawn(move || {
+                for (contract, suite) in suite_rx {
+                    for (signature, result) in &suite.test_results {
+                        let _ = events.send(TestEvent::TestFinished {
+                            contract: contract.clone(),
+                            signature: signature.clone(),
+                            result: result.clone(),
+                        });
+                    }
+                    let _ = events.send(TestEvent::SuiteFinished { contract, result: suite });
+                }
+            })
+        };
+
+        let test_options = self.test_options();
+        let results = runner.test(&self.filter, Some(suite_tx), test_options)?;
+        let _ = forward.join();
+
+        let failed = results.values().map(|suite| suite.failures().count()).sum::<usize>();
+        let total = results.values().map(|suite| suite.len()).sum::<usize>();
+        let _ = events.send(TestEvent::Finished {
+            passed: total - failed,
+            failed,
+            duration: start.elapsed(),
+        });
+        Ok(results)
+    }
+
+    fn test_options(&self) -> TestOptions {
+        TestOptions {
+            fuzz: self.config.fuzz,
+            invariant: self.config.invariant,
+            test_timeout: self.config.test_timeout,
+            auto_advance_time: self.config.auto_advance_time.map(|time| time.secs()),
+            strict_isolation: self.config.strict_isolation,
+        }
+    }
+
+    fn build_runner(&self, output: ProjectCompileOutput) -> eyre::Result<MultiContractRunner> {
+        let evm_spec = try_evm_spec(&self.config.evm_version).ok_or_else(|| {
+            eyre::eyre!("EVM version `{}` is not supported", self.config.evm_version)
+        })?;
+        let env = self.evm_opts.evm_env_blocking()?;
+
+        MultiContractRunnerBuilder::default()
+            .initial_balance(self.evm_opts.initial_balance)
+            .evm_spec(evm_spec)
+            .sender(self.evm_opts.sender)
+            .with_fork(self.evm_opts.get_fork(&self.config, env.clone()))
+            .with_cheats_config(CheatsConfig::new(&self.config, &self.evm_opts))
+            .with_test_options(self.test_options())
+            .with_libraries(self.config.parsed_libraries()?)
+            .build(&self.config.__root.0, output, env, self.evm_opts.clone())
+    }
+}
+
+/// A test run started with [`TestRunner::spawn`]
+#[derive(Debug)]
+pub struct TestRun {
+    events: Receiver<TestEvent>,
+    handle: JoinHandle<eyre::Result<BTreeMap<String, SuiteResult>>>,
+}
+
+impl TestRun {
+    /// Returns an iterator over the events of the run that ends when the run finished
+    pub fn events(&self) -> impl Iterator<Item = TestEvent> + '_ {
+        self.events.iter()
+    }
+
+    /// Waits for the run to finish and returns the results of all test contracts
+    pub fn wait(self) -> eyre::Result<BTreeMap<String, SuiteResult>> {
+        self.handle.join().map_err(|_| eyre::eyre!("the test runner panicked"))?
+    }
+}
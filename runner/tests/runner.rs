@@ -0,0 +1,77 @@
+use foundry_runner::{Filter, SuiteResult, TestEvent, TestRunner};
+use regex::Regex;
+use std::{collections::BTreeMap, fs};
+
+const COUNTER_TEST: &str = r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.8.10;
+
+contract CounterTest {
+    uint256 count;
+
+    function setUp() public {
+        count = 1;
+    }
+
+    function testIncrement() public {
+        count++;
+        require(count == 2, "not incremented");
+    }
+
+    function testDecrement() public {
+        count -= 2;
+    }
+}
+"#;
+
+/// Returns the results of the `CounterTest` suite, whose identifier starts with the source path
+fn counter_suite(results: &BTreeMap<String, SuiteResult>) -> &SuiteResult {
+    results
+        .iter()
+        .find(|(id, _)| id.ends_with("Counter.t.sol:CounterTest"))
+        .map(|(_, suite)| suite)
+        .expect("no results of CounterTest")
+}
+
+#[test]
+fn can_run_suite() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("test")).unwrap();
+    fs::write(root.path().join("test/Counter.t.sol"), COUNTER_TEST).unwrap();
+
+    let run = TestRunner::load(root.path()).unwrap().spawn();
+    let events = run.events().collect::<Vec<_>>();
+    let results = run.wait().unwrap();
+
+    assert!(matches!(events.first(), Some(TestEvent::Compiled { tests: 2 })));
+    assert!(matches!(events.last(), Some(TestEvent::Finished { passed: 1, failed: 1, .. })));
+    let finished = events
+        .iter()
+        .filter(|event| matches!(event, TestEvent::TestFinished { .. }))
+        .count();
+    assert_eq!(finished, 2);
+
+    let suite = counter_suite(&results);
+    assert!(suite.test_results["testIncrement()"].success);
+    assert!(!suite.test_results["testDecrement()"].success);
+
+    // only the matching tests of the suite are run
+    let results = TestRunner::load(root.path())
+        .unwrap()
+        .with_filter(Filter::default().match_test(Regex::new("Increment").unwrap()))
+        .run()
+        .unwrap();
+    let suite = counter_suite(&results);
+    assert_eq!(suite.test_results.len(), 1);
+    assert!(suite.test_results["testIncrement()"].success);
+
+    // the output of a separate compilation can be run
+    let runner = TestRunner::load(root.path())
+        .unwrap()
+        .with_filter(Filter::default().match_test(Regex::new("Decrement").unwrap()));
+    let output = runner.compile().unwrap();
+    let results = runner.run_compiled(output).unwrap();
+    let suite = counter_suite(&results);
+    assert_eq!(suite.test_results.len(), 1);
+    assert!(!suite.test_results["testDecrement()"].success);
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+// Executed with `artifact_paths = ["fixtures/ArtifactPaths"]`
+contract ArtifactPathsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testDeployCodeFromArtifactPaths() public {
+        address deployed =
+            cheats.deployCode("PayableStorage.yul:PayableStorage", abi.encode(uint256(7)), 1 ether);
+
+        (, bytes memory data) = deployed.call("");
+        assertEq(abi.decode(data, (uint256)), 7);
+        assertEq(deployed.balance, 1 ether);
+    }
+
+    function testDeployYulFromArtifactPaths() public {
+        address deployed = cheats.deployYul("PayableStorage", abi.encode(uint256(7)));
+
+        (, bytes memory data) = deployed.call("");
+        assertEq(abi.decode(data, (uint256)), 7);
+    }
+
+    function testFailDeployCodeMissingFromArtifactPaths() public {
+        cheats.deployCode("Missing.sol:Missing");
+    }
+
+    // the artifact was compiled with multiple versions and is suffixed with its version
+    function testGetDeployedCodeOfVersionWithImmutables() public {
+        string[] memory immutables = new string[](1);
+        immutables[0] = "owner";
+        bytes32[] memory values = new bytes32[](1);
+        values[0] = bytes32(uint256(uint160(address(0xbeef))));
+
+        address deployed = address(0x1234);
+        cheats.etch(deployed, cheats.getDeployedCode("ImmutableOwner.sol:ImmutableOwner:0.8.17", immutables, values));
+        (, bytes memory data) = deployed.call("");
+        assertEq(abi.decode(data, (address)), address(0xbeef));
+    }
+
+    function testFailGetDeployedCodeOfMissingVersion() public {
+        cheats.getDeployedCode("ImmutableOwner.sol:ImmutableOwner:0.8.18");
+    }
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+// `assertOnChain*` can only be used in the `preflight()` function of a script
+contract AssertOnChainTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testAssertOnChainRevertsOutsideOfPreflight() public {
+        (bool success, bytes memory reason) = address(cheats).call(
+            abi.encodeWithSelector(Cheats.assertOnChain.selector, true, "always holds")
+        );
+        assertTrue(!success);
+        assertEq(
+            reason, abi.encode("`assertOnChain*` can only be used in the `preflight()` function of a script")
+        );
+    }
+
+    function testFailAssertOnChain() public {
+        cheats.assertOnChain(true, "always holds");
+    }
+
+    function testFailAssertOnChainEq() public {
+        cheats.assertOnChainEq(uint256(1), uint256(1), "always holds");
+    }
+
+    function testFailAssertOnChainCode() public {
+        cheats.assertOnChainCode(address(this), "always holds");
+    }
+
+    function testFailAssertOnChainStorage() public {
+        cheats.assertOnChainStorage(address(this), bytes32(0), bytes32(0), "always holds");
+    }
+}
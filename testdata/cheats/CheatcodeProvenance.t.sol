@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract CheatcodeProvenanceTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testFailCheatcodeProvenance() public {
+        cheats.envUint("FOUNDRY_PROVENANCE_UNSET_VAR");
+    }
+}
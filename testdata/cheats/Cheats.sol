@@ -26,9 +26,82 @@ interface Cheats {
         uint256 created;
     }
 
+    // An EIP-7702 authorization, used in signDelegation and attachDelegation
+    struct SignedDelegation {
+        uint8 v;
+        bytes32 r;
+        bytes32 s;
+        uint64 nonce;
+        address implementation;
+    }
+
+    // Used in stopAndReturnStateDiff
+    enum AccountAccessKind {
+        Call,
+        DelegateCall,
+        CallCode,
+        StaticCall,
+        Create,
+        SelfDestruct,
+        Resume,
+        Balance,
+        Extcodesize,
+        Extcodehash,
+        Extcodecopy
+    }
+
+    // Used in stopAndReturnStateDiff
+    struct StorageAccess {
+        address account;
+        bytes32 slot;
+        bool isWrite;
+        bytes32 previousValue;
+        bytes32 newValue;
+        bool reverted;
+    }
+
+    // Used in stopAndReturnStateDiff
+    struct AccountAccess {
+        AccountAccessKind kind;
+        address account;
+        address accessor;
+        bool initialized;
+        uint256 oldBalance;
+        uint256 newBalance;
+        uint256 value;
+        bytes data;
+        bool reverted;
+        StorageAccess[] storageAccesses;
+        uint64 depth;
+    }
+
+    // Used in getStateDiff
+    struct StorageDiff {
+        bytes32 slot;
+        bytes32 previousValue;
+        bytes32 newValue;
+    }
+
+    // Used in getStateDiff
+    struct AccountStateDiff {
+        address account;
+        bool created;
+        uint256 previousBalance;
+        uint256 newBalance;
+        uint64 previousNonce;
+        uint64 newNonce;
+        StorageDiff[] storageDiffs;
+    }
+
     // Set block.timestamp (newTimestamp)
     function warp(uint256) external;
 
+    // Advance block.timestamp by the given number of seconds
+    function skip(uint256) external;
+
+    // Move block.timestamp back by the given number of seconds
+    function rewind(uint256) external;
+
     // Set block.difficulty (newDifficulty)
     function difficulty(uint256) external;
 
@@ -47,6 +120,12 @@ interface Cheats {
     // Stores a value to an address' storage slot, (who, slot, value)
     function store(address, bytes32, bytes32) external;
 
+    // Loads a transient storage slot (EIP-1153) from an address (who, slot)
+    function loadTransient(address, bytes32) external returns (bytes32);
+
+    // Stores a value to an address' transient storage slot (EIP-1153), (who, slot, value)
+    function storeTransient(address, bytes32, bytes32) external;
+
     // Signs data, (privateKey, digest) => (v, r, s)
     function sign(uint256, bytes32) external returns (uint8, bytes32, bytes32);
 
@@ -62,6 +141,16 @@ interface Cheats {
     // Adds a private key to the local forge wallet and returns the address
     function rememberKey(uint256) external returns (address);
 
+    // Signs an EIP-7702 authorization to delegate the account of the key to the implementation,
+    // for the current nonce of the account, (implementation, privateKey) => (signedDelegation)
+    function signDelegation(address, uint256) external returns (SignedDelegation memory);
+
+    // Applies a signed EIP-7702 authorization, calls to the account then run the code of the implementation
+    function attachDelegation(SignedDelegation calldata) external;
+
+    // Signs and applies an EIP-7702 authorization, (implementation, privateKey) => (signedDelegation)
+    function signAndAttachDelegation(address, uint256) external returns (SignedDelegation memory);
+
     // Performs a foreign function call via terminal, (stringInputs) => (result)
     function ffi(string[] calldata) external returns (bytes memory);
 
@@ -149,6 +238,13 @@ interface Cheats {
     // Sets an address' code, (who, newCode)
     function etch(address, bytes calldata) external;
 
+    // Copies all storage slots of an account to another account, reverts in forking mode if the
+    // storage of the account can't be enumerated, (from, to)
+    function copyStorage(address, address) external;
+
+    // Turns an account into a copy of another account: storage, code, balance and nonce, (from, to)
+    function cloneAccount(address, address) external;
+
     // Expects an error on next call
     function expectRevert() external;
 
@@ -156,6 +252,12 @@ interface Cheats {
 
     function expectRevert(bytes4) external;
 
+    // Expects an error on next call that starts with the given custom error selector, ignoring its parameters
+    function expectPartialRevert(bytes4) external;
+
+    // Expects an error on next call whose revert string contains the given message
+    function expectRevertContains(string calldata) external;
+
     // Record all storage reads and writes
     function record() external;
 
@@ -168,6 +270,36 @@ interface Cheats {
     // Gets all the recorded logs
     function getRecordedLogs() external returns (Log[] memory);
 
+    // Record all account and storage accesses, including the values before and after each access
+    function startStateDiffRecording() external;
+
+    // Stops recording and returns all account and storage accesses since startStateDiffRecording
+    function stopAndReturnStateDiff() external returns (AccountAccess[] memory);
+
+    // Returns the net changes of all accounts accessed since startStateDiffRecording, without stopping the recording
+    function getStateDiff() external returns (AccountStateDiff[] memory);
+
+    // Reverts if any storage slot of the account changed since startStateDiffRecording
+    function assertStorageUnchanged(address) external;
+
+    // Records a failed precondition with the reason if the condition is false, without reverting.
+    // forge script checks the preconditions of the preflight() function before broadcasting
+    function assertOnChain(bool, string calldata) external;
+
+    // Records a failed precondition if the values differ, (actual, expected, reason)
+    function assertOnChainEq(uint256, uint256, string calldata) external;
+
+    function assertOnChainEq(address, address, string calldata) external;
+
+    function assertOnChainEq(bytes32, bytes32, string calldata) external;
+
+    // Records a failed precondition if the account has no code, (account, reason)
+    function assertOnChainCode(address, string calldata) external;
+
+    // Records a failed precondition if the storage slot of the account doesn't hold the value,
+    // (account, slot, expected, reason)
+    function assertOnChainStorage(address, bytes32, bytes32, string calldata) external;
+
     // Prepare an expected log with all four checks enabled.
     // Call this function, then emit an event, then call a function. Internally after the call, we check if
     // logs were emitted in the expected order with the expected topics and data.
@@ -211,6 +343,15 @@ interface Cheats {
     // Expect a call to an address with the specified msg.value and calldata, and a *minimum* amount of gas.
     function expectCallMinGas(address, uint256, uint64, bytes calldata) external;
 
+    // Expect the calls to the targets with the calldata to be made in order during the next call.
+    // Calldata can either be strict or a partial match, other calls may be made in between.
+    function expectCallSequence(address[] calldata, bytes[] calldata) external;
+
+    // Compares all logs emitted until the end of the test against the snapshot file `snapshots/<name>.json`.
+    // Logs of reverted calls are ignored. The snapshot file is only written if the test is run with `--update-snapshots`,
+    // a missing snapshot file fails the test.
+    function expectSnapshot(string calldata) external;
+
     // Only allows memory writes to offsets [0x00, 0x60) ∪ [min, max) in the current subcontext. If any other
     // memory is written to, the test will fail.
     function expectSafeMemory(uint64, uint64) external;
@@ -219,12 +360,39 @@ interface Cheats {
     // If any other memory is written to, the test will fail.
     function expectSafeMemoryCall(uint64, uint64) external;
 
+    // Reads `length` bytes of the calling frame's memory starting at `offset`. Requires `memory_access = true`.
+    function readMemory(uint256 offset, uint256 length) external returns (bytes memory);
+
+    // Returns the calling frame's return data buffer, i.e. the return data of its last call. Requires `memory_access = true`.
+    function readReturndata() external returns (bytes memory);
+
     // Gets the bytecode from an artifact file. Takes in the relative path to the json file
     function getCode(string calldata) external returns (bytes memory);
 
     // Gets the _deployed_ bytecode from an artifact file. Takes in the relative path to the json file
     function getDeployedCode(string calldata) external returns (bytes memory);
 
+    // Gets the _deployed_ bytecode from an artifact file with the given values of its immutables, by name or AST id
+    function getDeployedCode(string calldata, string[] calldata, bytes32[] calldata) external returns (bytes memory);
+
+    // Deploys the creation code of an artifact file with the given constructor arguments and value and returns the
+    // address of the new contract. Takes in the relative path to the json file or `<File>.sol:<Contract>`, which is also
+    // looked up in the configured `artifact_paths`
+    function deployCode(string calldata) external returns (address);
+
+    function deployCode(string calldata, bytes calldata) external returns (address);
+
+    function deployCode(string calldata, bytes calldata, uint256) external returns (address);
+
+    // Same as `deployCode`, but also returns the hash of the runtime code of the new contract
+    function deployCodeWithHash(string calldata, bytes calldata, uint256) external returns (address, bytes32);
+
+    // Deploys a Yul object with the given constructor arguments, which are appended to its creation code. Takes in the
+    // name of the object, short for `<Name>.yul:<Name>`, or any path accepted by `deployCode`
+    function deployYul(string calldata) external returns (address);
+
+    function deployYul(string calldata, bytes calldata) external returns (address);
+
     // Labels an address in call traces
     function label(address, string calldata) external;
 
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Counter {
+    uint256 public a;
+    uint256 public b;
+
+    function set(uint256 _a, uint256 _b) public {
+        a = _a;
+        b = _b;
+    }
+}
+
+contract CopyStorageTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    Counter source;
+    Counter target;
+
+    function setUp() public {
+        source = new Counter();
+        target = new Counter();
+        source.set(1, 2);
+    }
+
+    function testCopyStorage() public {
+        cheats.copyStorage(address(source), address(target));
+        assertEq(target.a(), 1);
+        assertEq(target.b(), 2);
+
+        // the storage is copied, not shared
+        source.set(3, 4);
+        assertEq(target.a(), 1);
+        assertEq(target.b(), 2);
+    }
+
+    function testCloneAccount() public {
+        address clone = address(0x1234);
+        cheats.deal(address(source), 1 ether);
+        cheats.setNonce(address(source), 7);
+
+        cheats.cloneAccount(address(source), clone);
+        assertEq(string(clone.code), string(address(source).code));
+        assertEq(clone.balance, 1 ether);
+        assertEq(cheats.getNonce(clone), 7);
+        assertEq(Counter(clone).a(), 1);
+        assertEq(Counter(clone).b(), 2);
+    }
+
+    function testCloneAccountClearsStaleStorage() public {
+        target.set(0, 5);
+        source.set(1, 0);
+
+        cheats.cloneAccount(address(source), address(target));
+        assertEq(target.a(), 1);
+        assertEq(target.b(), 0);
+    }
+}
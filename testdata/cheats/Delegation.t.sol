@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public returns (address) {
+        count++;
+        return address(this);
+    }
+}
+
+contract DelegationTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    uint256 constant ALICE_KEY = 0xa11ce;
+
+    Counter implementation;
+    address alice;
+
+    function setUp() public {
+        implementation = new Counter();
+        alice = cheats.addr(ALICE_KEY);
+    }
+
+    function testSignAndAttachDelegation() public {
+        Cheats.SignedDelegation memory delegation =
+            cheats.signAndAttachDelegation(address(implementation), ALICE_KEY);
+        assertEq(delegation.implementation, address(implementation));
+        assertEq(delegation.nonce, 0);
+        assertEq(cheats.getNonce(alice), 1);
+        assertEq(alice.code.length, 23);
+
+        // the code of the implementation runs with the storage of alice
+        assertEq(Counter(alice).increment(), alice);
+        assertEq(Counter(alice).count(), 1);
+        assertEq(implementation.count(), 0);
+    }
+
+    function testAttachSignedDelegation() public {
+        Cheats.SignedDelegation memory delegation =
+            cheats.signDelegation(address(implementation), ALICE_KEY);
+        assertEq(cheats.getNonce(alice), 0);
+
+        cheats.attachDelegation(delegation);
+        Counter(alice).increment();
+        assertEq(Counter(alice).count(), 1);
+
+        // clears the delegation, the storage of alice is kept
+        cheats.signAndAttachDelegation(address(0), ALICE_KEY);
+        assertEq(alice.code.length, 0);
+        assertEq(cheats.getNonce(alice), 2);
+    }
+
+    function testFailAttachDelegationTwice() public {
+        Cheats.SignedDelegation memory delegation =
+            cheats.signDelegation(address(implementation), ALICE_KEY);
+        cheats.attachDelegation(delegation);
+        // the nonce of alice was used
+        cheats.attachDelegation(delegation);
+    }
+}
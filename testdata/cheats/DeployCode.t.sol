@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+interface WorkingContract {
+    function secret() external view returns (uint256);
+}
+
+contract DeployCodeTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    string constant path = "../testdata/fixtures/GetCode/WorkingContract.json";
+
+    // stores its constructor argument and emits it in an anonymous log, the runtime code returns it
+    string constant payablePath = "../testdata/fixtures/ArtifactPaths/PayableStorage.yul/PayableStorage.json";
+
+    function testDeployCode() public {
+        uint64 nonce = cheats.getNonce(address(this));
+        address deployed = cheats.deployCode(path);
+
+        assertEq(WorkingContract(deployed).secret(), 42);
+        assertEq(string(deployed.code), string(cheats.getDeployedCode(path)));
+        assertEq(cheats.getNonce(address(this)), nonce + 1);
+
+        // the next deployment gets a new address
+        assertTrue(cheats.deployCode(path, "") != deployed);
+    }
+
+    function testDeployCodeWithHash() public {
+        (address deployed, bytes32 codeHash) = cheats.deployCodeWithHash(path, "", 0);
+
+        assertEq(WorkingContract(deployed).secret(), 42);
+        assertEq(codeHash, keccak256(deployed.code));
+        assertEq(codeHash, deployed.codehash);
+    }
+
+    function testDeployCodeWithArgsAndValue() public {
+        address deployed = cheats.deployCode(payablePath, abi.encode(uint256(7)), 1 ether);
+
+        (bool success, bytes memory data) = deployed.call("");
+        assertTrue(success);
+        assertEq(abi.decode(data, (uint256)), 7);
+        assertEq(deployed.balance, 1 ether);
+    }
+
+    function testDeployCodeKeepsConstructorLogs() public {
+        cheats.recordLogs();
+        address deployed = cheats.deployCode(payablePath, abi.encode(uint256(7)));
+
+        Cheats.Log[] memory logs = cheats.getRecordedLogs();
+        assertEq(logs.length, 1);
+        assertEq(logs[0].emitter, deployed);
+        assertEq(logs[0].topics.length, 0);
+        assertEq(abi.decode(logs[0].data, (uint256)), 7);
+    }
+
+    function testDeployCodeChargesGas() public {
+        uint256 gasBefore = gasleft();
+        cheats.deployCode(payablePath, abi.encode(uint256(7)));
+
+        // at least the cost of the creation and of the storage write in the constructor
+        assertGt(gasBefore - gasleft(), 32000 + 20000);
+    }
+
+    function testFailDeployCodeWithValueToNonPayableConstructor() public {
+        cheats.deployCode(path, "", 1 ether);
+    }
+
+    function testDeployYul() public {
+        string memory yulPath = "../testdata/fixtures/GetCode/YulStorage.json";
+
+        // the object returns the value its constructor stored
+        address deployed = cheats.deployYul(yulPath, abi.encode(uint256(42)));
+        (bool success, bytes memory data) = deployed.call("");
+        assertTrue(success);
+        assertEq(abi.decode(data, (uint256)), 42);
+        assertEq(string(deployed.code), string(cheats.getDeployedCode(yulPath)));
+
+        // without arguments the constructor stores the end of its own code
+        assertEq(string(cheats.deployYul(yulPath).code), string(cheats.getDeployedCode(yulPath)));
+    }
+
+    function testFailDeployCodeMissingArtifact() public {
+        cheats.deployCode("../testdata/fixtures/GetCode/Missing.json");
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Token {
+    mapping(address => uint256) public balanceOf;
+
+    function transfer(address to, uint256 amount) public returns (bool) {
+        balanceOf[msg.sender] -= amount;
+        balanceOf[to] += amount;
+        return true;
+    }
+
+    function mint(address to, uint256 amount) public {
+        balanceOf[to] += amount;
+    }
+}
+
+contract Router {
+    Token public tokenA;
+    Token public tokenB;
+
+    constructor(Token _tokenA, Token _tokenB) {
+        tokenA = _tokenA;
+        tokenB = _tokenB;
+    }
+
+    function swap(uint256 amount) public {
+        tokenA.balanceOf(address(this));
+        tokenA.mint(address(this), amount);
+        tokenB.mint(msg.sender, amount);
+    }
+
+    function swapReversed(uint256 amount) public {
+        tokenB.mint(msg.sender, amount);
+        tokenA.mint(address(this), amount);
+    }
+}
+
+contract ExpectCallSequenceTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    Token tokenA;
+    Token tokenB;
+    Router router;
+
+    function setUp() public {
+        tokenA = new Token();
+        tokenB = new Token();
+        router = new Router(tokenA, tokenB);
+    }
+
+    function expectSwap() internal {
+        address[] memory targets = new address[](2);
+        targets[0] = address(tokenA);
+        targets[1] = address(tokenB);
+        bytes[] memory data = new bytes[](2);
+        data[0] = abi.encodeWithSelector(Token.mint.selector, address(router), 1);
+        data[1] = abi.encodeWithSelector(Token.mint.selector);
+        cheats.expectCallSequence(targets, data);
+    }
+
+    function testExpectCallSequence() public {
+        expectSwap();
+        router.swap(1);
+    }
+
+    function testExpectCallSequenceIncludesNextCall() public {
+        address[] memory targets = new address[](2);
+        targets[0] = address(router);
+        targets[1] = address(tokenB);
+        bytes[] memory data = new bytes[](2);
+        data[0] = abi.encodeWithSelector(Router.swap.selector, 1);
+        data[1] = "";
+        cheats.expectCallSequence(targets, data);
+        router.swap(1);
+    }
+
+    function testExpectCallSequenceOnlyChecksNextCall() public {
+        expectSwap();
+        router.swap(1);
+
+        // the sequence was consumed by the previous call
+        router.swapReversed(1);
+    }
+
+    function testFailExpectCallSequenceWrongOrder() public {
+        expectSwap();
+        router.swapReversed(1);
+    }
+
+    function testFailExpectCallSequenceWrongData() public {
+        expectSwap();
+        router.swap(2);
+    }
+
+    function testFailExpectCallSequenceWithoutCall() public {
+        expectSwap();
+    }
+
+    function testExpectRevertOfCallSequence() public {
+        cheats.expectRevert();
+        expectSwap();
+        router.swapReversed(1);
+    }
+}
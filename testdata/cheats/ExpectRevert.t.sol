@@ -6,6 +6,7 @@ import "./Cheats.sol";
 
 contract Reverter {
     error CustomError();
+    error CustomErrorWithArgs(uint256 amount, address who);
 
     function revertWithMessage(string memory message) public pure {
         require(false, message);
@@ -21,6 +22,10 @@ contract Reverter {
         revert CustomError();
     }
 
+    function revertWithCustomErrorArgs(uint256 amount) public view {
+        revert CustomErrorWithArgs(amount, msg.sender);
+    }
+
     function nestedRevert(Reverter inner, string memory message) public pure {
         inner.revertWithMessage(message);
     }
@@ -93,6 +98,33 @@ contract ExpectRevertTest is DSTest {
         reverter.revertWithCustomError();
     }
 
+    function testExpectPartialRevertCustomError() public {
+        Reverter reverter = new Reverter();
+        cheats.expectPartialRevert(Reverter.CustomErrorWithArgs.selector);
+        reverter.revertWithCustomErrorArgs(block.timestamp);
+
+        cheats.expectPartialRevert(Reverter.CustomError.selector);
+        reverter.revertWithCustomError();
+    }
+
+    function testFailExpectPartialRevertWrongSelector() public {
+        Reverter reverter = new Reverter();
+        cheats.expectPartialRevert(Reverter.CustomError.selector);
+        reverter.revertWithCustomErrorArgs(1);
+    }
+
+    function testExpectRevertContains() public {
+        Reverter reverter = new Reverter();
+        cheats.expectRevertContains("balance");
+        reverter.revertWithMessage("insufficient balance: 42 < 100");
+    }
+
+    function testFailExpectRevertContainsDoesNotMatch() public {
+        Reverter reverter = new Reverter();
+        cheats.expectRevertContains("allowance");
+        reverter.revertWithMessage("insufficient balance: 42 < 100");
+    }
+
     function testExpectRevertNested() public {
         Reverter reverter = new Reverter();
         Reverter inner = new Reverter();
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract SnapshotEmitter {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+
+    function transfer(address from, address to, uint256 value) public {
+        emit Transfer(from, to, value);
+    }
+
+    function transferAndRevert(address from, address to, uint256 value) public {
+        emit Transfer(from, to, value);
+        revert("reverted");
+    }
+}
+
+contract ExpectSnapshotTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    // etched to a fixed address, so the snapshot doesn't depend on the deployment
+    SnapshotEmitter emitter = SnapshotEmitter(address(0x1234));
+
+    function setUp() public {
+        cheats.etch(address(emitter), address(new SnapshotEmitter()).code);
+    }
+
+    function testExpectSnapshot() public {
+        cheats.expectSnapshot("cheats/transfer");
+        emitter.transfer(address(1), address(2), 3);
+    }
+
+    function testExpectSnapshotIgnoresRevertedCalls() public {
+        cheats.expectSnapshot("cheats/transfer");
+        try emitter.transferAndRevert(address(1), address(2), 4) {} catch {}
+        emitter.transfer(address(1), address(2), 3);
+    }
+
+    // every failing test has its own snapshot, so `--update-snapshots` can't overwrite the
+    // snapshot of a passing test
+
+    function testFailExpectSnapshotMismatch() public {
+        cheats.expectSnapshot("cheats/transfer-mismatch");
+        emitter.transfer(address(1), address(2), 4);
+    }
+
+    function testFailExpectSnapshotMissingLog() public {
+        cheats.expectSnapshot("cheats/transfer-missing-log");
+    }
+
+    function testFailExpectSnapshotMissingFile() public {
+        cheats.expectSnapshot("cheats/missing");
+        emitter.transfer(address(1), address(2), 3);
+    }
+
+    function testFailExpectSnapshotInvalidName() public {
+        cheats.expectSnapshot("../transfer");
+    }
+
+    function testFailExpectSnapshotTwice() public {
+        cheats.expectSnapshot("cheats/twice");
+        cheats.expectSnapshot("cheats/twice");
+    }
+}
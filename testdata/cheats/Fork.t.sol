@@ -112,4 +112,10 @@ contract ForkTest is DSTest {
         uint256 expected = block.chainid;
         assertEq(newChainId, expected);
     }
+
+    // the storage of a remote account can't be enumerated, so it can't be copied
+    function testFailCopyStorageOfRemoteAccount() public {
+        cheats.selectFork(forkA);
+        cheats.copyStorage(WETH_TOKEN_ADDR, address(0x1234));
+    }
 }
@@ -36,6 +36,36 @@ contract GetDeployedCodeTest is DSTest {
         over.emitPayload(address(0), "hello");
         emit Payload(address(this), address(0), "hello");
     }
+
+    // the runtime code of the fixture is hand written and returns its only immutable, `owner`
+    function testGetDeployedCodeWithImmutables() public {
+        string memory path = "../testdata/fixtures/GetCode/ImmutableOwner.json";
+        address owner = address(0xbeef);
+        string[] memory immutables = new string[](1);
+        immutables[0] = "owner";
+        bytes32[] memory values = new bytes32[](1);
+        values[0] = bytes32(uint256(uint160(owner)));
+
+        vm.etch(overrideAddress, vm.getDeployedCode(path, immutables, values));
+        assertEq(ImmutableOwner(overrideAddress).owner(), owner);
+
+        // immutables can also be referenced by the id of their declaration
+        immutables[0] = "5";
+        values[0] = bytes32(uint256(uint160(address(this))));
+        vm.etch(overrideAddress, vm.getDeployedCode(path, immutables, values));
+        assertEq(ImmutableOwner(overrideAddress).owner(), address(this));
+    }
+
+    function testFailGetDeployedCodeWithUnknownImmutable() public {
+        string[] memory immutables = new string[](1);
+        immutables[0] = "admin";
+        bytes32[] memory values = new bytes32[](1);
+        vm.getDeployedCode("../testdata/fixtures/GetCode/ImmutableOwner.json", immutables, values);
+    }
+}
+
+interface ImmutableOwner {
+    function owner() external view returns (address);
 }
 
 interface Override {
@@ -670,6 +670,20 @@ contract MemSafetyTest is DSTest {
         }
     }
 
+    /// @dev Tests that a disallowed memory write reverts with the offset, size and opcode of
+    ///      the write.
+    function testExpectSafeMemoryCall_revertReason() public {
+        // Create a new SubContext contract
+        SubContext sc = new SubContext();
+
+        // Allow memory writes in the range of [0x80, 0xA0) within the next created subcontext
+        vm.expectSafeMemoryCall(0x80, 0xA0);
+
+        // The memory write in this subcontext is outside of the allowed range.
+        vm.expectRevertContains("Memory write at offset 0xA0 of size 0x20 not allowed by MSTORE");
+        sc.doMstore(0xA0, 0xc0ffee);
+    }
+
     ////////////////////////////////////////////////////////////////
     //                          HELPERS                           //
     ////////////////////////////////////////////////////////////////
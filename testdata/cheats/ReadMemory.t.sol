@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Returner {
+    function value() public pure returns (uint256) {
+        return 42;
+    }
+}
+
+contract ReadMemoryTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testReadMemory() public {
+        bytes memory data = hex"deadbeefcafe";
+        uint256 ptr;
+        assembly {
+            ptr := add(data, 0x20)
+        }
+        assertEq0(cheats.readMemory(ptr, 6), hex"deadbeefcafe");
+        assertEq0(cheats.readMemory(ptr + 2, 2), hex"beef");
+    }
+
+    function testReadMemoryLayout() public {
+        uint256 freePtr;
+        assembly {
+            mstore(0x00, 0x1234)
+            freePtr := mload(0x40)
+        }
+        (uint256 scratch,, uint256 readFreePtr) = abi.decode(cheats.readMemory(0x00, 0x60), (uint256, uint256, uint256));
+        assertEq(scratch, 0x1234);
+        assertEq(readFreePtr, freePtr);
+    }
+
+    function testFailReadMemoryOutOfBounds() public {
+        cheats.readMemory(type(uint256).max, 1);
+    }
+
+    function testReadReturndata() public {
+        Returner returner = new Returner();
+        returner.value();
+        assertEq(abi.decode(cheats.readReturndata(), (uint256)), 42);
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract SkipTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testSkip() public {
+        cheats.warp(100);
+        cheats.skip(25);
+        assertEq(block.timestamp, 125, "skip failed");
+    }
+
+    function testRewind() public {
+        cheats.warp(100);
+        cheats.rewind(25);
+        assertEq(block.timestamp, 75, "rewind failed");
+    }
+
+    function testFailRewindBeforeZero() public {
+        cheats.warp(10);
+        cheats.rewind(11);
+    }
+}
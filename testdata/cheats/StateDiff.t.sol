@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract StorageAccessor {
+    function write(uint256 value) public {
+        assembly {
+            sstore(1, value)
+        }
+    }
+
+    function read() public view returns (uint256 value) {
+        assembly {
+            value := sload(1)
+        }
+    }
+
+    function writeAndRevert(uint256 value) public {
+        assembly {
+            sstore(1, value)
+        }
+        revert("reverted");
+    }
+}
+
+contract StateDiffTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    StorageAccessor accessor;
+
+    function setUp() public {
+        accessor = new StorageAccessor();
+        accessor.write(5);
+    }
+
+    function testRecordStorageAccesses() public {
+        cheats.startStateDiffRecording();
+        accessor.write(10);
+        accessor.read();
+        Cheats.AccountAccess[] memory accesses = cheats.stopAndReturnStateDiff();
+
+        assertEq(accesses.length, 2, "number of accesses is incorrect");
+
+        Cheats.AccountAccess memory write = accesses[0];
+        assertEq(uint256(write.kind), uint256(Cheats.AccountAccessKind.Call), "kind is incorrect");
+        assertEq(write.account, address(accessor), "account is incorrect");
+        assertEq(write.accessor, address(this), "accessor is incorrect");
+        assertTrue(write.initialized, "account should be initialized");
+        assertEq(write.data, abi.encodeCall(StorageAccessor.write, (10)));
+        assertTrue(!write.reverted, "write should not be reverted");
+        assertEq(write.depth, 1, "depth is incorrect");
+        assertEq(write.storageAccesses.length, 1, "number of storage accesses is incorrect");
+        assertTrue(write.storageAccesses[0].isWrite, "access should be a write");
+        assertEq(write.storageAccesses[0].slot, bytes32(uint256(1)));
+        assertEq(write.storageAccesses[0].previousValue, bytes32(uint256(5)));
+        assertEq(write.storageAccesses[0].newValue, bytes32(uint256(10)));
+
+        Cheats.AccountAccess memory read = accesses[1];
+        assertEq(uint256(read.kind), uint256(Cheats.AccountAccessKind.StaticCall), "kind is incorrect");
+        assertEq(read.storageAccesses.length, 1, "number of storage accesses is incorrect");
+        assertTrue(!read.storageAccesses[0].isWrite, "access should be a read");
+        assertEq(read.storageAccesses[0].previousValue, bytes32(uint256(10)));
+        assertEq(read.storageAccesses[0].newValue, bytes32(uint256(10)));
+    }
+
+    function testRecordRevertedAccesses() public {
+        cheats.startStateDiffRecording();
+        try accessor.writeAndRevert(20) {} catch {}
+        Cheats.AccountAccess[] memory accesses = cheats.stopAndReturnStateDiff();
+
+        assertEq(accesses.length, 1, "number of accesses is incorrect");
+        assertTrue(accesses[0].reverted, "call should be reverted");
+        assertEq(accesses[0].storageAccesses.length, 1, "number of storage accesses is incorrect");
+        assertTrue(accesses[0].storageAccesses[0].reverted, "storage access should be reverted");
+        assertEq(accesses[0].storageAccesses[0].newValue, bytes32(uint256(20)));
+        assertEq(accessor.read(), 5, "storage should be reverted");
+    }
+
+    function testRecordAccountQueries() public {
+        cheats.startStateDiffRecording();
+        address(accessor).balance;
+        Cheats.AccountAccess[] memory accesses = cheats.stopAndReturnStateDiff();
+
+        assertEq(accesses.length, 1, "number of accesses is incorrect");
+        assertEq(uint256(accesses[0].kind), uint256(Cheats.AccountAccessKind.Balance));
+        assertEq(accesses[0].account, address(accessor), "account is incorrect");
+        assertEq(accesses[0].accessor, address(this), "accessor is incorrect");
+    }
+
+    function testStopWithoutRecording() public {
+        assertEq(cheats.stopAndReturnStateDiff().length, 0);
+    }
+
+    function testGetStateDiff() public {
+        cheats.startStateDiffRecording();
+        accessor.write(10);
+        accessor.read();
+        Cheats.AccountStateDiff[] memory diffs = cheats.getStateDiff();
+
+        assertEq(diffs.length, 1, "number of diffs is incorrect");
+        assertEq(diffs[0].account, address(accessor), "account is incorrect");
+        assertTrue(!diffs[0].created, "account should not be created");
+        assertEq(diffs[0].storageDiffs.length, 1, "number of storage diffs is incorrect");
+        assertEq(diffs[0].storageDiffs[0].slot, bytes32(uint256(1)));
+        assertEq(diffs[0].storageDiffs[0].previousValue, bytes32(uint256(5)));
+        assertEq(diffs[0].storageDiffs[0].newValue, bytes32(uint256(10)));
+
+        // restoring the value removes the diff
+        accessor.write(5);
+        assertEq(cheats.getStateDiff().length, 0, "state should be unchanged");
+    }
+
+    function testGetStateDiffIgnoresReverted() public {
+        cheats.startStateDiffRecording();
+        try accessor.writeAndRevert(20) {} catch {}
+        assertEq(cheats.getStateDiff().length, 0, "state should be unchanged");
+    }
+
+    function testGetStateDiffTracksCreatesAndBalances() public {
+        cheats.startStateDiffRecording();
+        StorageAccessor created = new StorageAccessor();
+        payable(address(0xBEEF)).transfer(1 ether);
+        Cheats.AccountStateDiff[] memory diffs = cheats.getStateDiff();
+
+        bool foundCreated;
+        bool foundTransfer;
+        for (uint256 i = 0; i < diffs.length; i++) {
+            if (diffs[i].account == address(created)) {
+                foundCreated = diffs[i].created;
+            }
+            if (diffs[i].account == address(0xBEEF)) {
+                foundTransfer = diffs[i].newBalance == diffs[i].previousBalance + 1 ether;
+            }
+        }
+        assertTrue(foundCreated, "created account is missing");
+        assertTrue(foundTransfer, "balance change is missing");
+    }
+
+    function testGetStateDiffTracksTestContract() public {
+        cheats.deal(address(this), 2 ether);
+        cheats.startStateDiffRecording();
+        new StorageAccessor();
+        payable(address(0xBEEF)).transfer(1 ether);
+        Cheats.AccountStateDiff[] memory diffs = cheats.getStateDiff();
+
+        bool found;
+        for (uint256 i = 0; i < diffs.length; i++) {
+            if (diffs[i].account == address(this)) {
+                found = true;
+                assertEq(diffs[i].previousBalance, 2 ether, "previous balance is incorrect");
+                assertEq(diffs[i].newBalance, 1 ether, "new balance is incorrect");
+                assertEq(diffs[i].newNonce, diffs[i].previousNonce + 1, "nonce change is missing");
+            }
+        }
+        assertTrue(found, "test contract is missing");
+    }
+
+    function testGetStateDiffTracksStorageOnlyAccountBalance() public {
+        cheats.startStateDiffRecording();
+        accessor.write(10);
+        cheats.deal(address(accessor), 1 ether);
+        Cheats.AccountStateDiff[] memory diffs = cheats.getStateDiff();
+
+        assertEq(diffs.length, 1, "number of diffs is incorrect");
+        assertEq(diffs[0].previousBalance, 0, "previous balance is incorrect");
+        assertEq(diffs[0].newBalance, 1 ether, "new balance is incorrect");
+    }
+
+    function testAssertStorageUnchanged() public {
+        cheats.startStateDiffRecording();
+        accessor.read();
+        cheats.assertStorageUnchanged(address(accessor));
+    }
+
+    function testFailAssertStorageUnchanged() public {
+        cheats.startStateDiffRecording();
+        accessor.write(10);
+        cheats.assertStorageUnchanged(address(accessor));
+    }
+}
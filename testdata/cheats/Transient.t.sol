@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract TransientTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    address constant target = address(0x1000);
+
+    // tstore(calldataload(0), calldataload(32))
+    bytes constant STORE = hex"6020356000355d00";
+    // mstore(0, tload(calldataload(0))) return(0, 32)
+    bytes constant LOAD = hex"6000355c60005260206000f3";
+    // tstore(calldataload(0), calldataload(32)) revert(0, 0)
+    bytes constant STORE_AND_REVERT = hex"6020356000355d60006000fd";
+    // tstore(2, 7) tstore(1, 2) mstore(0, tload(tload(1))) return(0, 32), without any opcodes
+    // between the transient opcodes
+    bytes constant BACK_TO_BACK = hex"60026001600760025d5d60015c5c60005260206000f3";
+
+    function testStoreTransient() public {
+        cheats.etch(target, LOAD);
+        cheats.storeTransient(target, bytes32(uint256(1)), bytes32(uint256(42)));
+
+        (bool success, bytes memory ret) = target.call(abi.encode(uint256(1)));
+        assertTrue(success);
+        assertEq(abi.decode(ret, (uint256)), 42);
+    }
+
+    function testLoadTransient() public {
+        cheats.etch(target, STORE);
+
+        (bool success,) = target.call(abi.encode(uint256(1), uint256(42)));
+        assertTrue(success);
+        assertEq(uint256(cheats.loadTransient(target, bytes32(uint256(1)))), 42);
+        assertEq(cheats.loadTransient(target, bytes32(uint256(2))), bytes32(0));
+    }
+
+    function testTransientRevertedWithCallFrame() public {
+        cheats.etch(target, STORE_AND_REVERT);
+
+        (bool success,) = target.call(abi.encode(uint256(1), uint256(42)));
+        assertTrue(!success);
+        assertEq(cheats.loadTransient(target, bytes32(uint256(1))), bytes32(0));
+    }
+
+    function testBackToBackTransientOpcodes() public {
+        cheats.etch(target, BACK_TO_BACK);
+
+        (bool success, bytes memory ret) = target.call("");
+        assertTrue(success);
+        assertEq(abi.decode(ret, (uint256)), 7);
+        assertEq(uint256(cheats.loadTransient(target, bytes32(uint256(1)))), 2);
+        assertEq(uint256(cheats.loadTransient(target, bytes32(uint256(2)))), 7);
+    }
+
+    function testTransientStorageIsPerAccount() public {
+        cheats.storeTransient(target, bytes32(uint256(1)), bytes32(uint256(42)));
+        assertEq(cheats.loadTransient(address(this), bytes32(uint256(1))), bytes32(0));
+    }
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+contract Pool {
+    function swap() public pure {
+        revert("slippage");
+    }
+
+    function quote() public pure returns (uint256) {
+        return 1;
+    }
+}
+
+contract RevertReasonTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    address constant POOL = address(0xbeef);
+
+    function setUp() public {
+        cheats.etch(POOL, address(new Pool()).code);
+    }
+
+    function testSwallowedRevertReason() public {
+        (bool success,) = POOL.call(abi.encodeWithSelector(Pool.swap.selector));
+        require(success);
+    }
+
+    function testCaughtRevertReasonIsNotReported() public {
+        (bool success,) = POOL.call(abi.encodeWithSelector(Pool.swap.selector));
+        (bool quoted,) = POOL.call(abi.encodeWithSelector(Pool.quote.selector));
+        require(success && quoted);
+    }
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract FuzzFixturesTest is DSTest {
+    address[] public fixtureOwner;
+
+    function setUp() public {
+        fixtureOwner.push(address(0xdeadbeef));
+        fixtureOwner.push(address(0xc0ffee));
+    }
+
+    function fixtureAmount() public pure returns (uint256[] memory amounts) {
+        amounts = new uint256[](2);
+        amounts[0] = 0x4e65656400000000000000000000000000000000000000000000000000000000;
+        amounts[1] = type(uint256).max;
+    }
+
+    function testFixtureFromFunction(uint256 amount) public {
+        require(amount != 0x4e65656400000000000000000000000000000000000000000000000000000000, "amount fixture found.");
+    }
+
+    function testFixtureFromArray(address owner, uint256) public {
+        require(owner != address(0xc0ffee), "owner fixture found.");
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+struct EmittedLog {
+    address emitter;
+    bytes32[] topics;
+    bytes data;
+}
+
+contract EventToken {
+    event Transfer(address indexed from, address indexed to, uint256 amount);
+
+    uint256 public totalSupply;
+    mapping(address => uint256) public balanceOf;
+
+    function mint(uint256 amount) public {
+        amount = amount % 1e24;
+        totalSupply += amount;
+        balanceOf[msg.sender] += amount;
+        emit Transfer(address(0), msg.sender, amount);
+    }
+
+    function transfer(address to, uint256 amount) public {
+        require(amount > 0 && balanceOf[msg.sender] >= amount);
+        balanceOf[msg.sender] -= amount;
+        balanceOf[to] += amount;
+        emit Transfer(msg.sender, to, amount);
+    }
+
+    // Burns without emitting a `Transfer` event
+    function burnSilently(uint256 amount) public {
+        require(amount > 0 && balanceOf[msg.sender] >= amount);
+        balanceOf[msg.sender] -= amount;
+        totalSupply -= amount;
+    }
+}
+
+contract InvariantEvents is DSTest {
+    bytes32 constant TRANSFER = keccak256("Transfer(address,address,uint256)");
+
+    EventToken token;
+
+    function setUp() public {
+        token = new EventToken();
+    }
+
+    function invariantSupplyFromEvents(EmittedLog[] calldata logs) public {
+        uint256 supply;
+        for (uint256 i = 0; i < logs.length; i++) {
+            if (logs[i].emitter != address(token) || logs[i].topics[0] != TRANSFER) {
+                continue;
+            }
+            uint256 amount = abi.decode(logs[i].data, (uint256));
+            if (logs[i].topics[1] == bytes32(0)) {
+                supply += amount;
+            }
+            if (logs[i].topics[2] == bytes32(0)) {
+                supply -= amount;
+            }
+        }
+        require(supply == token.totalSupply(), "supply not conserved.");
+    }
+
+    function invariantNoEmptyTransfers(EmittedLog[] calldata logs) public {
+        for (uint256 i = 0; i < logs.length; i++) {
+            if (logs[i].emitter != address(token) || logs[i].topics[1] == bytes32(0)) {
+                continue;
+            }
+            require(abi.decode(logs[i].data, (uint256)) > 0, "empty transfer.");
+        }
+    }
+}
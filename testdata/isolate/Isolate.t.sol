@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+contract Counter {
+    uint256 public number;
+    address public lastOrigin;
+
+    function increment() public {
+        number++;
+        lastOrigin = tx.origin;
+    }
+
+    function incrementAndRevert() public {
+        number++;
+        revert("reverted");
+    }
+}
+
+// These tests are executed with `isolate = true`
+contract IsolateTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    Counter counter;
+
+    address constant target = address(0x1000);
+
+    // tstore(calldataload(0), calldataload(32))
+    bytes constant STORE = hex"6020356000355d00";
+    // mstore(0, tload(calldataload(0))) return(0, 32)
+    bytes constant LOAD = hex"6000355c60005260206000f3";
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function testEachCallIsATransaction() public {
+        counter.increment();
+        assertEq(counter.lastOrigin(), address(this));
+
+        uint256 before = gasleft();
+        counter.increment();
+        uint256 used = before - gasleft();
+
+        // the intrinsic gas and a cold storage slot are charged again
+        assertGt(used, 21000 + 2100);
+        assertEq(counter.number(), 2);
+    }
+
+    function testRevertedCallChangesNothing() public {
+        try counter.incrementAndRevert() {} catch {}
+        assertEq(counter.number(), 0);
+    }
+
+    function testPrankedCallIsSentByThePrankedCaller() public {
+        cheats.prank(address(0xbeef));
+        counter.increment();
+        assertEq(counter.lastOrigin(), address(0xbeef));
+
+        counter.increment();
+        assertEq(counter.lastOrigin(), address(this));
+    }
+
+    function testTransientStorageIsClearedBetweenCalls() public {
+        cheats.etch(target, STORE);
+        (bool success,) = target.call(abi.encode(uint256(1), uint256(42)));
+        assertTrue(success);
+
+        cheats.etch(target, LOAD);
+        bytes memory ret;
+        (success, ret) = target.call(abi.encode(uint256(1)));
+        assertTrue(success);
+        assertEq(abi.decode(ret, (uint256)), 0);
+    }
+
+    function testTransientStorageOfTheTestIsKept() public {
+        cheats.storeTransient(target, bytes32(uint256(1)), bytes32(uint256(42)));
+
+        cheats.etch(target, LOAD);
+        (bool success, bytes memory ret) = target.call(abi.encode(uint256(1)));
+        assertTrue(success);
+        assertEq(abi.decode(ret, (uint256)), 0);
+
+        assertEq(uint256(cheats.loadTransient(target, bytes32(uint256(1)))), 42);
+    }
+}
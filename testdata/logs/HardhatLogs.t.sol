@@ -235,4 +235,12 @@ contract HardhatLogsTest {
     function testConsoleLogFormatSpill() public view {
         console.log("formatted log %s", testStr, testUint);
     }
+
+    function testUnknownLogDoesNotRevert() public view {
+        (bool success,) = address(0x000000000000000000636F6e736F6c652e6c6f67).staticcall(
+            abi.encodeWithSignature("unknown(uint256)", 1)
+        );
+        require(success, "console call reverted");
+        console.log("after");
+    }
 }
@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract TimeoutTest is DSTest {
+    // only the timeout can stop these loops, the gas limit is never reached. The inline timeouts
+    // keep them from hanging when all of testdata is run without one.
+
+    /// forge-config: default.test_timeout = 1
+    function testLoopsForever() public pure {
+        uint256 x;
+        while (true) {
+            x++;
+        }
+    }
+
+    /// forge-config: default.test_timeout = 1
+    function testFuzzLoopsForever(uint256 x) public pure {
+        while (true) {
+            x ^= 1;
+        }
+    }
+
+    // runs after the looping tests on a single thread, with a budget of its own
+    function testReturnsQuickly() public {
+        assertTrue(true);
+    }
+}
@@ -0,0 +1,706 @@
+//! Interactive explorer of the call traces of failed tests
+
+use crate::{TUIExitReason, Ui};
+use crossterm::{
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ethers::types::Address;
+use eyre::Result;
+use forge::{
+    trace::{
+        node::CallTraceNode, CallTrace, CallTraceArena, RawOrDecodedCall, RawOrDecodedLog,
+        RawOrDecodedReturnData,
+    },
+    CallKind,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::PathBuf,
+};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    terminal::Frame,
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Terminal,
+};
+
+/// The call trace of a test, see [TraceExplorer]
+#[derive(Debug, Clone)]
+pub struct ExplorerTrace {
+    /// The name of the test, e.g. `CounterTest:testIncrement`
+    pub title: String,
+    /// The decoded trace
+    pub decoded: CallTraceArena,
+    /// The same trace before it was decoded, shown in the raw view
+    pub raw: CallTraceArena,
+}
+
+/// A TUI to explore the call traces of tests
+///
+/// Call frames can be expanded and collapsed, searched by address, selector or function name, and
+/// shown with their raw or decoded calldata. The source of the contract of the selected frame can
+/// be shown next to the trace. Initially only the frames of failed calls are expanded.
+pub struct TraceExplorer {
+    view: TraceView,
+    /// Source files by `"<artifact>:<contract>"`
+    sources: HashMap<String, PathBuf>,
+    /// Lines of the source files that were already read
+    source_lines: HashMap<PathBuf, Vec<String>>,
+    terminal: Terminal<CrosstermBackend<io::Stdout>>,
+    show_source: bool,
+    /// The search query that is being typed, if in search mode
+    search_input: Option<String>,
+}
+
+/// The traces and the frames of the current trace that are expanded, selected and searched
+struct TraceView {
+    traces: Vec<ExplorerTrace>,
+    /// Index of the shown trace
+    current: usize,
+    /// Collapsed nodes of every trace
+    collapsed: Vec<HashSet<usize>>,
+    /// Selected row of the visible frames
+    list_state: ListState,
+    raw: bool,
+    /// The last submitted search query
+    search: String,
+}
+
+impl TraceView {
+    fn new(traces: Vec<ExplorerTrace>) -> Self {
+        let collapsed =
+            traces.iter().map(|trace| collapsed_successful_calls(&trace.decoded)).collect();
+        let mut list_state = ListState::default();
+        list_state.select(Some(0));
+        Self { traces, current: 0, collapsed, list_state, raw: false, search: String::new() }
+    }
+
+    fn arena(&self) -> &CallTraceArena {
+        let trace = &self.traces[self.current];
+        if self.raw {
+            &trace.raw
+        } else {
+            &trace.decoded
+        }
+    }
+
+    /// The visible rows of the current trace, `(node, depth)`
+    fn rows(&self) -> Vec<(usize, usize)> {
+        let arena = &self.traces[self.current].decoded.arena;
+        let collapsed = &self.collapsed[self.current];
+        let mut rows = Vec::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((idx, depth)) = stack.pop() {
+            rows.push((idx, depth));
+            if !collapsed.contains(&idx) {
+                stack.extend(arena[idx].children.iter().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+        rows
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.list_state.selected().and_then(|row| self.rows().get(row).map(|(idx, _)| *idx))
+    }
+
+    fn select_node(&mut self, node: usize) {
+        if let Some(row) = self.rows().iter().position(|(idx, _)| *idx == node) {
+            self.list_state.select(Some(row));
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.rows().len();
+        let row = self.list_state.selected().unwrap_or_default() as isize + delta;
+        self.list_state.select(Some(row.clamp(0, len as isize - 1) as usize));
+    }
+
+    fn show_trace(&mut self, index: usize) {
+        self.current = index;
+        self.list_state.select(Some(0));
+    }
+
+    /// Expands the node, or collapses it if `collapse` is set
+    ///
+    /// Collapsing a node without children or that is already collapsed selects its parent.
+    fn toggle(&mut self, node: usize, collapse: bool) {
+        let arena = &self.traces[self.current].decoded.arena;
+        let collapsed = &mut self.collapsed[self.current];
+        if !collapse {
+            collapsed.remove(&node);
+        } else if arena[node].children.is_empty() || !collapsed.insert(node) {
+            if let Some(parent) = arena[node].parent {
+                collapsed.insert(parent);
+                self.select_node(parent);
+            }
+        }
+    }
+
+    /// Returns the nodes of the current trace that match the search query
+    fn matches(&self) -> Vec<usize> {
+        let query = self.search.to_lowercase();
+        if query.is_empty() {
+            return vec![]
+        }
+        let query = query.strip_prefix("0x").unwrap_or(&query);
+        let trace = &self.traces[self.current];
+        trace
+            .decoded
+            .arena
+            .iter()
+            .zip(trace.raw.arena.iter())
+            .filter(|(decoded, raw)| {
+                let address = hex::encode(decoded.trace.address);
+                let selector = match &raw.trace.data {
+                    RawOrDecodedCall::Raw(data) if !raw.trace.created() && data.len() >= 4 => {
+                        hex::encode(&data[..4])
+                    }
+                    _ => String::new(),
+                };
+                address.contains(query) ||
+                    selector == query ||
+                    call_label(&decoded.trace).to_lowercase().contains(query)
+            })
+            .map(|(node, _)| node.idx)
+            .collect()
+    }
+
+    /// Expands all frames of the matches and selects the next match after the selected frame
+    fn find(&mut self, backwards: bool) {
+        let matches = self.matches();
+        if matches.is_empty() {
+            return
+        }
+        let arena = &self.traces[self.current].decoded.arena;
+        let collapsed = &mut self.collapsed[self.current];
+        for node in &matches {
+            let mut parent = arena[*node].parent;
+            while let Some(idx) = parent {
+                collapsed.remove(&idx);
+                parent = arena[idx].parent;
+            }
+        }
+
+        let rows = self.rows();
+        let selected = self.list_state.selected().unwrap_or_default();
+        let mut match_rows = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, (idx, _))| matches.contains(idx))
+            .map(|(row, _)| row);
+        let row = if backwards {
+            let match_rows = match_rows.collect::<Vec<_>>();
+            match_rows.iter().rev().find(|row| **row < selected).or(match_rows.last()).copied()
+        } else {
+            let first = match_rows.clone().next();
+            match_rows.find(|row| *row > selected).or(first)
+        };
+        self.list_state.select(row);
+    }
+}
+
+impl TraceExplorer {
+    /// Creates the explorer, this enters the alternate screen of the terminal
+    pub fn new(traces: Vec<ExplorerTrace>, sources: HashMap<String, PathBuf>) -> Result<Self> {
+        eyre::ensure!(!traces.is_empty(), "no traces to explore");
+
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+        terminal.hide_cursor()?;
+
+        Ok(Self {
+            view: TraceView::new(traces),
+            sources,
+            source_lines: HashMap::new(),
+            terminal,
+            show_source: false,
+            search_input: None,
+        })
+    }
+
+    /// Returns the lines of the source file of the contract of the node
+    fn source_lines(&mut self, node: usize) -> Option<&Vec<String>> {
+        let trace = &self.view.traces[self.view.current].decoded.arena[node].trace;
+        let path = self.sources.get(trace.contract.as_ref()?)?.clone();
+        if !self.source_lines.contains_key(&path) {
+            let source = std::fs::read_to_string(&path).ok()?;
+            self.source_lines.insert(path.clone(), source.lines().map(str::to_string).collect());
+        }
+        self.source_lines.get(&path)
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        let view = &self.view;
+        let rows = view.rows();
+        let matches = view.matches();
+        let selected = view.selected_node().unwrap_or_default();
+
+        let arena = &view.arena().arena;
+        let items = rows
+            .iter()
+            .map(|(idx, depth)| {
+                let collapsed = view.collapsed[view.current].contains(idx);
+                frame_item(&arena[*idx], *depth, collapsed, matches.contains(idx))
+            })
+            .collect::<Vec<_>>();
+        let details = frame_details(&arena[selected]);
+
+        let source = if self.show_source {
+            let trace = &view.traces[view.current].decoded.arena[selected].trace;
+            let function = match &trace.data {
+                RawOrDecodedCall::Decoded(name, _, _) => Some(name.clone()),
+                _ => None,
+            };
+            let contract = trace.contract.as_ref().map(|c| c.rsplit(':').next().unwrap_or(c));
+            let contract = contract.map(str::to_string);
+            self.source_lines(selected).map(|lines| {
+                let line = definition_line(lines, function.as_deref(), contract.as_deref());
+                (lines.clone(), line)
+            })
+        } else {
+            None
+        };
+
+        let view = &mut self.view;
+        let title = format!(
+            " {} ({}/{}) {}",
+            view.traces[view.current].title,
+            view.current + 1,
+            view.traces.len(),
+            if view.raw { "[raw] " } else { "" }
+        );
+        let search = match &self.search_input {
+            Some(input) => format!("/{input}"),
+            None if !view.search.is_empty() => {
+                format!("search: {} ({} matches)", view.search, matches.len())
+            }
+            None => String::new(),
+        };
+        let show_source = self.show_source;
+        let list_state = &mut view.list_state;
+        self.terminal.draw(|f| {
+            draw_layout(f, title, items, list_state, details, show_source, source, search)
+        })?;
+        Ok(())
+    }
+
+    /// Handles a key press, returns true if the explorer should be closed
+    fn on_key(&mut self, key: KeyEvent) -> bool {
+        let view = &mut self.view;
+        if let Some(input) = self.search_input.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    view.search = self.search_input.take().unwrap_or_default();
+                    view.find(false);
+                }
+                KeyCode::Esc => self.search_input = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return false
+        }
+
+        let selected = view.selected_node().unwrap_or_default();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Char('j') | KeyCode::Down => view.move_selection(1),
+            KeyCode::Char('k') | KeyCode::Up => view.move_selection(-1),
+            KeyCode::PageDown => view.move_selection(20),
+            KeyCode::PageUp => view.move_selection(-20),
+            KeyCode::Char('g') | KeyCode::Home => view.list_state.select(Some(0)),
+            KeyCode::Char('G') | KeyCode::End => view.move_selection(isize::MAX / 2),
+            KeyCode::Char('l') | KeyCode::Right => view.toggle(selected, false),
+            KeyCode::Char('h') | KeyCode::Left => view.toggle(selected, true),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let collapse = !view.collapsed[view.current].contains(&selected);
+                view.toggle(selected, collapse);
+            }
+            KeyCode::Char('e') => view.collapsed[view.current].clear(),
+            KeyCode::Char('c') => {
+                view.collapsed[view.current] =
+                    collapsed_successful_calls(&view.traces[view.current].decoded);
+                view.list_state.select(Some(0));
+            }
+            KeyCode::Char('/') => self.search_input = Some(String::new()),
+            KeyCode::Char('n') => view.find(false),
+            KeyCode::Char('N') => view.find(true),
+            KeyCode::Char('r') => view.raw = !view.raw,
+            KeyCode::Char('s') => self.show_source = !self.show_source,
+            KeyCode::Char(']') | KeyCode::Tab if view.current + 1 < view.traces.len() => {
+                view.show_trace(view.current + 1)
+            }
+            KeyCode::Char('[') | KeyCode::BackTab if view.current > 0 => {
+                view.show_trace(view.current - 1)
+            }
+            _ => {}
+        }
+        false
+    }
+}
+
+impl Ui for TraceExplorer {
+    fn start(mut self) -> Result<TUIExitReason> {
+        // If something panics inside here, we should do everything we can to
+        // not corrupt the user's terminal.
+        std::panic::set_hook(Box::new(|e| {
+            disable_raw_mode().expect("Unable to disable raw mode");
+            execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)
+                .expect("unable to execute disable mouse capture");
+            println!("{e}");
+        }));
+
+        self.terminal.clear()?;
+        loop {
+            self.draw()?;
+            if let Event::Key(key) = event::read()? {
+                if self.on_key(key) {
+                    break
+                }
+            }
+        }
+
+        disable_raw_mode()?;
+        execute!(self.terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+        Ok(TUIExitReason::CharExit)
+    }
+}
+
+/// Returns the nodes of successful calls with subcalls, so that only failed calls are expanded
+fn collapsed_successful_calls(arena: &CallTraceArena) -> HashSet<usize> {
+    arena
+        .arena
+        .iter()
+        .filter(|node| node.trace.success && !node.children.is_empty())
+        .map(|node| node.idx)
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_layout<B: Backend>(
+    f: &mut Frame<B>,
+    title: String,
+    items: Vec<ListItem>,
+    list_state: &mut ListState,
+    details: Vec<Spans>,
+    show_source: bool,
+    source: Option<(Vec<String>, usize)>,
+    search: String,
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(10), Constraint::Length(2)].as_ref())
+        .split(f.size());
+    let top = if show_source {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+            .split(rows[0])
+    } else {
+        vec![rows[0]]
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(list, top[0], list_state);
+
+    if show_source {
+        draw_source(f, top[1], source);
+    }
+
+    let details = Paragraph::new(details)
+        .block(Block::default().borders(Borders::ALL).title(" Frame "))
+        .wrap(Wrap { trim: false });
+    f.render_widget(details, rows[1]);
+
+    draw_footer(f, rows[2], search);
+}
+
+fn draw_source<B: Backend>(f: &mut Frame<B>, area: Rect, source: Option<(Vec<String>, usize)>) {
+    let block = Block::default().borders(Borders::ALL).title(" Source ");
+    let (lines, line) = match source {
+        Some(source) => source,
+        None => {
+            let paragraph = Paragraph::new(Text::from("No source available for this contract"))
+                .block(block)
+                .alignment(Alignment::Center);
+            f.render_widget(paragraph, area);
+            return
+        }
+    };
+
+    let width = lines.len().to_string().len();
+    let text = lines
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let style = if i == line {
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:>width$} ", i + 1),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+                Span::styled(text.clone(), style),
+            ])
+        })
+        .collect::<Vec<_>>();
+    // keep the definition in the upper third of the pane
+    let offset = line.saturating_sub(area.height as usize / 3);
+    let paragraph = Paragraph::new(text).block(block).scroll((offset as u16, 0));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_footer<B: Backend>(f: &mut Frame<B>, area: Rect, search: String) {
+    let controls = Span::styled(
+        "[q]: quit | [k/j]: prev/next frame | [h/l]: collapse/expand | [enter]: toggle | [e/c]: expand all/collapse successful | [/]: search address, selector or function | [n/N]: next/prev match | [r]: toggle raw | [s]: toggle source | [[/]]: prev/next test",
+        Style::default().add_modifier(Modifier::DIM),
+    );
+    let text = Text::from(vec![Spans::from(Span::raw(search)), Spans::from(controls)]);
+    let paragraph = Paragraph::new(text).alignment(Alignment::Left).wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+/// The row of a call frame in the trace
+fn frame_item(
+    node: &CallTraceNode,
+    depth: usize,
+    collapsed: bool,
+    matched: bool,
+) -> ListItem<'static> {
+    let marker = if node.children.is_empty() {
+        "  "
+    } else if collapsed {
+        "▶ "
+    } else {
+        "▼ "
+    };
+    let color = if node.trace.success { Color::Green } else { Color::Red };
+    let mut label = Style::default();
+    if matched {
+        label = label.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    }
+    let mut spans = vec![
+        Span::raw(format!("{}{marker}", "  ".repeat(depth))),
+        Span::styled(format!("[{}] ", node.trace.gas_cost), Style::default().fg(color)),
+        Span::styled(call_label(&node.trace), label),
+    ];
+    if collapsed {
+        spans.push(Span::styled(
+            format!(" ({} calls)", node.children.len()),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+    }
+    ListItem::new(Spans::from(spans))
+}
+
+/// The name of the contract of the call, its label or address
+fn target(trace: &CallTrace) -> String {
+    trace
+        .label
+        .clone()
+        .or_else(|| trace.contract.as_ref().map(|c| c.rsplit(':').next().unwrap_or(c).to_string()))
+        .unwrap_or_else(|| format!("{:?}", trace.address))
+}
+
+/// The call of the frame, e.g. `Counter::increment(1)`
+fn call_label(trace: &CallTrace) -> String {
+    if trace.created() {
+        return format!("new {}@{:?}", target(trace), trace.address)
+    }
+    let call = match &trace.data {
+        RawOrDecodedCall::Decoded(name, _, args) => format!("{name}({})", args.join(", ")),
+        RawOrDecodedCall::Raw(data) if data.len() >= 4 => {
+            format!("0x{}(0x{})", hex::encode(&data[..4]), hex::encode(&data[4..]))
+        }
+        RawOrDecodedCall::Raw(data) if data.is_empty() => "fallback()".to_string(),
+        RawOrDecodedCall::Raw(data) => format!("0x{}", hex::encode(data)),
+    };
+    let kind = match trace.kind {
+        CallKind::StaticCall => " [staticcall]",
+        CallKind::DelegateCall => " [delegatecall]",
+        CallKind::CallCode => " [callcode]",
+        _ => "",
+    };
+    format!("{}::{call}{kind}", target(trace))
+}
+
+/// The details of the selected call frame
+fn frame_details(node: &CallTraceNode) -> Vec<Spans<'static>> {
+    let trace = &node.trace;
+    let field = |name: &str, value: String| {
+        Spans::from(vec![
+            Span::styled(format!("{name:>9}: "), Style::default().fg(Color::Blue)),
+            Span::raw(value),
+        ])
+    };
+    let output = match &trace.output {
+        RawOrDecodedReturnData::Raw(_) if trace.created() => {
+            format!("{} bytes of code", trace.output.to_raw().len())
+        }
+        output => output.to_string(),
+    };
+    let mut details = vec![
+        field("address", address_label(trace.address, trace)),
+        field("caller", format!("{:?}", trace.caller)),
+        field("kind", format!("{:?}", trace.kind)),
+        field("value", trace.value.to_string()),
+        field("gas", format!("{} ({:?})", trace.gas_cost, trace.status)),
+        field("output", output),
+    ];
+    if let RawOrDecodedCall::Raw(data) = &trace.data {
+        details.insert(4, field("calldata", format!("0x{}", hex::encode(data))));
+    }
+    for log in &node.logs {
+        let log = match log {
+            RawOrDecodedLog::Decoded(name, params) => {
+                let params = params
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}"))
+                    .collect::<Vec<_>>();
+                format!("{name}({})", params.join(", "))
+            }
+            RawOrDecodedLog::Raw(log) => {
+                let topics =
+                    log.topics.iter().map(|topic| format!("{topic:?}")).collect::<Vec<_>>();
+                format!("topics: [{}] data: 0x{}", topics.join(", "), hex::encode(&log.data))
+            }
+        };
+        details.push(field("emit", log));
+    }
+    details
+}
+
+fn address_label(address: Address, trace: &CallTrace) -> String {
+    match trace.label.as_ref().or(trace.contract.as_ref()) {
+        Some(name) => format!("{address:?} ({name})"),
+        None => format!("{address:?}"),
+    }
+}
+
+/// Returns the line of the definition of the function, or of the contract if the function is not
+/// found
+fn definition_line(lines: &[String], function: Option<&str>, contract: Option<&str>) -> usize {
+    let find =
+        |prefix: String| lines.iter().position(|line| line.trim_start().starts_with(&prefix));
+    function
+        .and_then(|function| find(format!("function {function}(")))
+        .or_else(|| contract.and_then(|contract| find(format!("contract {contract} "))))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(depth: usize, success: bool, address: u64, data: RawOrDecodedCall) -> CallTrace {
+        CallTrace {
+            depth,
+            success,
+            address: Address::from_low_u64_be(address),
+            data,
+            ..Default::default()
+        }
+    }
+
+    fn decoded(name: &str) -> RawOrDecodedCall {
+        RawOrDecodedCall::Decoded(name.to_string(), format!("{name}()"), vec![])
+    }
+
+    /// A failed test that made a successful call with a subcall, and a failed call with a subcall
+    fn view() -> TraceView {
+        let mut raw = CallTraceArena::default();
+        let mut decoded_arena = CallTraceArena::default();
+        let calls = [
+            (0, false, 1, "test", vec![0x11, 0x22, 0x33, 0x44]),
+            (1, true, 2, "deposit", vec![0xd0, 0xe3, 0x0d, 0xb0]),
+            (2, true, 3, "transfer", vec![0xa9, 0x05, 0x9c, 0xbb]),
+            (1, false, 4, "withdraw", vec![0x2e, 0x1a, 0x7d, 0x4d]),
+            (2, false, 3, "transfer", vec![0xa9, 0x05, 0x9c, 0xbb]),
+        ];
+        for (depth, success, address, name, data) in calls {
+            raw.push_trace(0, call(depth, success, address, RawOrDecodedCall::Raw(data.into())));
+            decoded_arena.push_trace(0, call(depth, success, address, decoded(name)));
+        }
+        TraceView::new(vec![ExplorerTrace {
+            title: "CounterTest:testFail".to_string(),
+            decoded: decoded_arena,
+            raw,
+        }])
+    }
+
+    #[test]
+    fn collapses_successful_calls() {
+        let view = view();
+        assert_eq!(view.collapsed[0], HashSet::from([1]));
+        assert_eq!(view.rows(), vec![(0, 0), (1, 1), (3, 1), (4, 2)]);
+    }
+
+    #[test]
+    fn can_toggle_frames() {
+        let mut view = view();
+        view.toggle(1, false);
+        assert_eq!(view.rows(), vec![(0, 0), (1, 1), (2, 2), (3, 1), (4, 2)]);
+
+        view.toggle(3, true);
+        assert_eq!(view.rows(), vec![(0, 0), (1, 1), (2, 2), (3, 1)]);
+
+        // collapsing a frame without children collapses and selects its parent
+        view.toggle(2, true);
+        assert_eq!(view.rows(), vec![(0, 0), (1, 1), (3, 1)]);
+        assert_eq!(view.selected_node(), Some(1));
+
+        view.move_selection(isize::MAX / 2);
+        assert_eq!(view.selected_node(), Some(3));
+        view.move_selection(-10);
+        assert_eq!(view.selected_node(), Some(0));
+    }
+
+    #[test]
+    fn can_find_frames() {
+        let mut view = view();
+        view.search = "transfer".to_string();
+        assert_eq!(view.matches(), vec![2, 4]);
+
+        // the collapsed match is expanded
+        view.find(false);
+        assert_eq!(view.selected_node(), Some(2));
+        view.find(false);
+        assert_eq!(view.selected_node(), Some(4));
+        view.find(false);
+        assert_eq!(view.selected_node(), Some(2));
+        view.find(true);
+        assert_eq!(view.selected_node(), Some(4));
+
+        view.search = "0x2e1a7d4d".to_string();
+        assert_eq!(view.matches(), vec![3]);
+        view.search = format!("{:?}", Address::from_low_u64_be(3));
+        assert_eq!(view.matches(), vec![2, 4]);
+        view.search = String::new();
+        assert!(view.matches().is_empty());
+    }
+
+    #[test]
+    fn can_find_definition_lines() {
+        let lines = ["contract Counter {", "    uint256 x;", "    function increment() public {"]
+            .map(str::to_string);
+        assert_eq!(definition_line(&lines, Some("increment"), Some("Counter")), 2);
+        assert_eq!(definition_line(&lines, Some("decrement"), Some("Counter")), 0);
+        assert_eq!(definition_line(&lines, None, Some("Other")), 0);
+    }
+}
@@ -194,6 +194,10 @@ pub fn link_with_nonce_or_address<T, U>(
 /// Recursively links bytecode given a target contract artifact name, the bytecode(s) to be linked,
 /// a mapping of contract artifact name to bytecode, a dependency mapping, a mutable list that
 /// will be filled with the predeploy libraries, initial nonce, and the sender.
+///
+/// Libraries are linked against their pre-deployed address if one is configured. Otherwise they
+/// are added to the predeploy libraries after all of their own dependencies, and only once, so
+/// libraries that are shared by multiple dependencies are deployed a single time.
 #[allow(clippy::too_many_arguments)]
 fn recurse_link<'a>(
     // target name
@@ -220,6 +224,26 @@ fn recurse_link<'a>(
         // for each dependency, try to link
         dependencies.dependencies.iter().for_each(|dep| {
             let ArtifactDependency { file_name: next_target, file, key } = dep;
+
+            // check if the library is pre-deployed or already deployed for another dependency
+            let library_prefix = format!("{file}:{key}:");
+            let known_address = deployed_library_addresses
+                .libs
+                .get(&PathBuf::from_str(file).expect("Invalid library path."))
+                .and_then(|library_file| library_file.get(key))
+                .map(|address| Address::from_str(address).expect("Invalid library address passed."))
+                .or_else(|| {
+                    deployment.iter().find_map(|(library, _)| {
+                        library.strip_prefix(&library_prefix)?.parse().ok()
+                    })
+                });
+            if let Some(address) = known_address {
+                trace!(target : "forge::link", dependency = next_target, ?address, "link library");
+                target_bytecode.0.link(file.clone(), key.clone(), address);
+                target_bytecode.1.link(file.clone(), key.clone(), address);
+                return
+            }
+
             // get the dependency
             trace!(target : "forge::link", dependency = next_target, file, key, version=?dependencies.artifact_id.version,  "get dependency");
             let (next_identifier, artifact) = artifacts
@@ -252,35 +276,20 @@ fn recurse_link<'a>(
                 }
             }
 
-            let mut deployed_address = None;
-
-            if let Some(library_file) = deployed_library_addresses
-                .libs
-                .get(&PathBuf::from_str(file).expect("Invalid library path."))
-            {
-                if let Some(address) = library_file.get(key) {
-                    deployed_address =
-                        Some(Address::from_str(address).expect("Invalid library address passed."));
-                }
-            }
-
-            let address = deployed_address.unwrap_or_else(|| {
-                ethers_core::utils::get_contract_address(sender, init_nonce + deployment.len())
-            });
+            let address =
+                ethers_core::utils::get_contract_address(sender, init_nonce + deployment.len());
 
             // link the dependency to the target
             target_bytecode.0.link(file.clone(), key.clone(), address);
             target_bytecode.1.link(file.clone(), key.clone(), address);
 
-            if deployed_address.is_none() {
-                let library = format!("{file}:{key}:0x{}", hex::encode(address));
+            let library = format!("{library_prefix}0x{}", hex::encode(address));
 
-                // push the dependency into the library deployment vector
-                deployment.push((
-                    library,
-                    next_target_bytecode.object.into_bytes().unwrap_or_else(|| panic!( "Bytecode should be linked for {next_target}")),
-                ));
-            }
+            // push the dependency into the library deployment vector
+            deployment.push((
+                library,
+                next_target_bytecode.object.into_bytes().unwrap_or_else(|| panic!( "Bytecode should be linked for {next_target}")),
+            ));
         });
     }
 }
@@ -497,11 +506,11 @@ mod tests {
                         assert_eq!(post_link_input.dependencies.len(), 0);
                     }
                     "LibraryLinkingTest.json:LibraryLinkingTest" => {
-                        assert_eq!(post_link_input.dependencies.len(), 3);
+                        // `Lib` is deployed once and shared with `NestedLib`
+                        assert_eq!(post_link_input.dependencies.len(), 2);
                         assert_eq!(hex::encode(&post_link_input.dependencies[0].1), lib_linked);
-                        assert_eq!(hex::encode(&post_link_input.dependencies[1].1), lib_linked);
                         assert_ne!(
-                            hex::encode(&post_link_input.dependencies[2].1),
+                            hex::encode(&post_link_input.dependencies[1].1),
                             *nested_lib_unlinked
                         );
                     }
@@ -513,11 +522,11 @@ mod tests {
                         assert_eq!(hex::encode(&post_link_input.dependencies[0].1), lib_linked);
                     }
                     "LibraryConsumer.json:LibraryConsumer" => {
-                        assert_eq!(post_link_input.dependencies.len(), 3);
+                        // `Lib` is deployed once and shared with `NestedLib`
+                        assert_eq!(post_link_input.dependencies.len(), 2);
                         assert_eq!(hex::encode(&post_link_input.dependencies[0].1), lib_linked);
-                        assert_eq!(hex::encode(&post_link_input.dependencies[1].1), lib_linked);
                         assert_ne!(
-                            hex::encode(&post_link_input.dependencies[2].1),
+                            hex::encode(&post_link_input.dependencies[1].1),
                             *nested_lib_unlinked
                         );
                     }
@@ -529,6 +538,51 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_linking_predeployed_library() {
+        let paths = ProjectPathsConfig::builder()
+            .root("../testdata")
+            .sources("../testdata/core")
+            .build()
+            .unwrap();
+        let project = Project::builder().paths(paths).ephemeral().no_artifacts().build().unwrap();
+        let output = project.compile().unwrap();
+        let contracts = output
+            .into_artifacts()
+            .filter(|(i, _)| i.slug().starts_with("Lib") || i.slug().starts_with("NestedLib"))
+            .map(|(id, c)| (id, c.into_contract_bytecode()))
+            .collect::<ArtifactContracts>();
+        let lib_file = contracts
+            .keys()
+            .find(|i| i.slug() == "Lib.json:Lib")
+            .unwrap()
+            .source
+            .to_string_lossy()
+            .to_string();
+        let library = format!("{lib_file}:Lib:0x000000000000000000000000000000000000dEaD");
+        let libraries = Libraries::parse(&[library]).unwrap();
+
+        let mut known_contracts = ContractsByArtifact::default();
+        link_with_nonce_or_address(
+            contracts,
+            &mut known_contracts,
+            libraries,
+            Address::default(),
+            U256::one(),
+            &mut (),
+            |file, key| (format!("{key}.json:{key}"), file, key),
+            |post_link_input| {
+                if post_link_input.id.slug() == "LibraryConsumer.json:LibraryConsumer" {
+                    // only `NestedLib` is deployed, it's linked against the pre-deployed `Lib`
+                    assert_eq!(post_link_input.dependencies.len(), 1);
+                    assert!(post_link_input.dependencies[0].0.contains(":NestedLib:"));
+                }
+                Ok(())
+            },
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_resolve_addr() {
         use std::str::FromStr;